@@ -0,0 +1,124 @@
+/*
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef SPLINTER_NODE_H
+#define SPLINTER_NODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SPLINTER_OK 0
+/* Also returned if the node panics while handling the call. */
+#define SPLINTER_ERROR -1
+#define SPLINTER_INVALID_ARGUMENT -2
+
+typedef struct SplinterNodeConfig SplinterNodeConfig;
+typedef struct SplinterNode SplinterNode;
+
+/* Called when a service of a host-implemented type is started or stopped. */
+typedef void (*SplinterServiceLifecycleCallback)(
+    void *user_data,
+    const char *circuit_id,
+    const char *service_id);
+
+/*
+ * Called with each message received by a service of a host-implemented
+ * type. The strings and payload are only valid for the duration of the call.
+ */
+typedef void (*SplinterServiceMessageCallback)(
+    void *user_data,
+    const char *circuit_id,
+    const char *service_id,
+    const char *sender,
+    const uint8_t *payload,
+    size_t payload_len);
+
+/*
+ * The callbacks that implement a service type. Any callback may be NULL.
+ * Callbacks are invoked from the node's threads, possibly concurrently for
+ * different services.
+ */
+typedef struct SplinterServiceCallbacks {
+    void *user_data;
+    SplinterServiceLifecycleCallback on_start;
+    SplinterServiceLifecycleCallback on_stop;
+    SplinterServiceMessageCallback on_message;
+} SplinterServiceCallbacks;
+
+/*
+ * Returns the message for the most recent failure on the calling thread, or
+ * NULL. The string is valid until the next failure on the same thread.
+ */
+const char *splinter_last_error(void);
+
+SplinterNodeConfig *splinter_node_config_new(const char *node_id,
+                                             const char *state_dir);
+void splinter_node_config_free(SplinterNodeConfig *config);
+
+int splinter_node_config_add_network_endpoint(SplinterNodeConfig *config,
+                                              const char *endpoint);
+int splinter_node_config_add_advertised_endpoint(SplinterNodeConfig *config,
+                                                 const char *endpoint);
+int splinter_node_config_add_peer(SplinterNodeConfig *config,
+                                  const char *endpoint);
+int splinter_node_config_add_registry(SplinterNodeConfig *config,
+                                      const char *registry);
+int splinter_node_config_set_display_name(SplinterNodeConfig *config,
+                                          const char *display_name);
+int splinter_node_config_set_rest_api_endpoint(SplinterNodeConfig *config,
+                                               const char *endpoint);
+/* ca_file may be NULL to skip verifying the certificates of other nodes. */
+int splinter_node_config_set_tls(SplinterNodeConfig *config,
+                                 const char *ca_file,
+                                 const char *client_cert,
+                                 const char *client_key,
+                                 const char *server_cert,
+                                 const char *server_key);
+/*
+ * Registers a service type implemented by the host. The callbacks and
+ * user_data must remain valid until the node is stopped.
+ */
+int splinter_node_config_add_service_type(
+    SplinterNodeConfig *config,
+    const char *service_type,
+    const SplinterServiceCallbacks *callbacks);
+
+/*
+ * Starts a node, returning NULL on failure. The configuration is not
+ * consumed and must still be freed.
+ */
+SplinterNode *splinter_node_start(const SplinterNodeConfig *config);
+
+/* Sends a message from a host-implemented service to another service. */
+int splinter_node_send(const SplinterNode *node,
+                       const char *circuit_id,
+                       const char *service_id,
+                       const char *recipient,
+                       const uint8_t *payload,
+                       size_t payload_len);
+
+/* Stops and frees a node. */
+int splinter_node_stop(SplinterNode *node);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SPLINTER_NODE_H */
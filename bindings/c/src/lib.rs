@@ -0,0 +1,679 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A C API for running a Splinter node inside a host application.
+//!
+//! The declarations for these functions are in `include/splinter_node.h`. Functions that can fail
+//! return `SPLINTER_OK` or a negative status code (or `NULL`, for functions that return a
+//! pointer); the message for the most recent failure on the calling thread is returned by
+//! `splinter_last_error`.
+//!
+//! Panics are caught at every entry point and in the callbacks of host-implemented services, so
+//! that they never unwind into the host application; a panic in an entry point is reported as
+//! `SPLINTER_ERROR` (or `NULL`).
+
+mod service;
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::time::Duration;
+
+use splinter::transport::multi::MultiTransport;
+use splinter::transport::socket::{TcpTransport, TlsTransport};
+use splinter::transport::Transport;
+use splinter_daemon::daemon::{RunningDaemon, SplinterDaemonBuilder};
+
+use service::{CallbackServiceFactory, ServiceSenders};
+pub use service::{
+    SplinterServiceCallbacks, SplinterServiceLifecycleCallback, SplinterServiceMessageCallback,
+};
+
+pub const SPLINTER_OK: c_int = 0;
+pub const SPLINTER_ERROR: c_int = -1;
+pub const SPLINTER_INVALID_ARGUMENT: c_int = -2;
+
+const DEFAULT_REST_API_ENDPOINT: &str = "127.0.0.1:8080";
+const DEFAULT_HEARTBEAT: u64 = 30;
+const DEFAULT_ADMIN_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_REGISTRY_AUTO_REFRESH: u64 = 600;
+const DEFAULT_REGISTRY_FORCED_REFRESH: u64 = 10;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Returns the message for the most recent failure on the calling thread, or `NULL` if nothing
+/// has failed. The string is valid until the next failure on the same thread.
+#[no_mangle]
+pub extern "C" fn splinter_last_error() -> *const c_char {
+    panic::catch_unwind(|| {
+        LAST_ERROR.with(|last_error| {
+            last_error
+                .borrow()
+                .as_ref()
+                .map(|message| message.as_ptr())
+                .unwrap_or_else(ptr::null)
+        })
+    })
+    .unwrap_or_else(|_| ptr::null())
+}
+
+/// Returns the message of a caught panic.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".into()
+    }
+}
+
+/// Runs the body of a function, returning `on_panic` and recording the panic as the last error if
+/// the body panics.
+fn catch_panic<T, F: FnOnce() -> T>(on_panic: T, body: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        set_last_error(format!("panicked: {}", panic_message(payload)));
+        on_panic
+    })
+}
+
+/// Converts a required C string argument, recording an error if it is `NULL` or not UTF-8.
+unsafe fn required_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, c_int> {
+    if value.is_null() {
+        set_last_error(format!("{} must not be NULL", name));
+        return Err(SPLINTER_INVALID_ARGUMENT);
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| {
+        set_last_error(format!("{} is not a valid UTF-8 string", name));
+        SPLINTER_INVALID_ARGUMENT
+    })
+}
+
+unsafe fn optional_str<'a>(value: *const c_char, name: &str) -> Result<Option<&'a str>, c_int> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        required_str(value, name).map(Some)
+    }
+}
+
+unsafe fn config_mut<'a>(
+    config: *mut SplinterNodeConfig,
+) -> Result<&'a mut SplinterNodeConfig, c_int> {
+    config.as_mut().ok_or_else(|| {
+        set_last_error("config must not be NULL".into());
+        SPLINTER_INVALID_ARGUMENT
+    })
+}
+
+/// Runs the body of a function that returns a status code.
+fn status<F: FnOnce() -> Result<(), c_int>>(body: F) -> c_int {
+    catch_panic(SPLINTER_ERROR, || body().err().unwrap_or(SPLINTER_OK))
+}
+
+struct TlsFiles {
+    ca_file: Option<String>,
+    client_cert: String,
+    client_key: String,
+    server_cert: String,
+    server_key: String,
+}
+
+/// The settings of a node to be started with `splinter_node_start`.
+pub struct SplinterNodeConfig {
+    node_id: String,
+    state_dir: String,
+    display_name: Option<String>,
+    network_endpoints: Vec<String>,
+    advertised_endpoints: Vec<String>,
+    initial_peers: Vec<String>,
+    rest_api_endpoint: String,
+    registries: Vec<String>,
+    tls: Option<TlsFiles>,
+    service_types: HashMap<String, SplinterServiceCallbacks>,
+}
+
+/// Creates the configuration for a node with the given ID, which stores its state in
+/// `state_dir`. Returns `NULL` if either argument is invalid.
+///
+/// # Safety
+///
+/// The arguments must be `NULL` or nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_new(
+    node_id: *const c_char,
+    state_dir: *const c_char,
+) -> *mut SplinterNodeConfig {
+    catch_panic(ptr::null_mut(), || {
+        let (node_id, state_dir) = match (
+            required_str(node_id, "node_id"),
+            required_str(state_dir, "state_dir"),
+        ) {
+            (Ok(node_id), Ok(state_dir)) => (node_id, state_dir),
+            _ => return ptr::null_mut(),
+        };
+
+        Box::into_raw(Box::new(SplinterNodeConfig {
+            node_id: node_id.to_string(),
+            state_dir: state_dir.to_string(),
+            display_name: None,
+            network_endpoints: vec![],
+            advertised_endpoints: vec![],
+            initial_peers: vec![],
+            rest_api_endpoint: DEFAULT_REST_API_ENDPOINT.to_string(),
+            registries: vec![],
+            tls: None,
+            service_types: HashMap::new(),
+        }))
+    })
+}
+
+/// Frees a node configuration.
+///
+/// # Safety
+///
+/// `config` must be `NULL` or a pointer returned by `splinter_node_config_new` that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_free(config: *mut SplinterNodeConfig) {
+    catch_panic((), || {
+        if !config.is_null() {
+            drop(Box::from_raw(config));
+        }
+    })
+}
+
+/// Adds an endpoint the node listens on for connections from other nodes, such as
+/// `tcps://0.0.0.0:8044`.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `endpoint` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_add_network_endpoint(
+    config: *mut SplinterNodeConfig,
+    endpoint: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config
+            .network_endpoints
+            .push(required_str(endpoint, "endpoint")?.to_string());
+        Ok(())
+    })
+}
+
+/// Adds an endpoint other nodes use to connect to this node. The network endpoints are
+/// advertised if none are added.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `endpoint` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_add_advertised_endpoint(
+    config: *mut SplinterNodeConfig,
+    endpoint: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config
+            .advertised_endpoints
+            .push(required_str(endpoint, "endpoint")?.to_string());
+        Ok(())
+    })
+}
+
+/// Adds the endpoint of a node to connect to on startup.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `endpoint` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_add_peer(
+    config: *mut SplinterNodeConfig,
+    endpoint: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config
+            .initial_peers
+            .push(required_str(endpoint, "endpoint")?.to_string());
+        Ok(())
+    })
+}
+
+/// Adds a read-only registry file or URL.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `registry` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_add_registry(
+    config: *mut SplinterNodeConfig,
+    registry: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config
+            .registries
+            .push(required_str(registry, "registry")?.to_string());
+        Ok(())
+    })
+}
+
+/// Sets the node's human-readable name; defaults to the node ID.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `display_name` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_set_display_name(
+    config: *mut SplinterNodeConfig,
+    display_name: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config.display_name = Some(required_str(display_name, "display_name")?.to_string());
+        Ok(())
+    })
+}
+
+/// Sets the endpoint the node's REST API is served on; defaults to `127.0.0.1:8080`.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration and `endpoint` a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_set_rest_api_endpoint(
+    config: *mut SplinterNodeConfig,
+    endpoint: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config.rest_api_endpoint = required_str(endpoint, "endpoint")?.to_string();
+        Ok(())
+    })
+}
+
+/// Enables the TLS transport with the given certificate and key files. If `ca_file` is `NULL`,
+/// the certificates of other nodes are not verified.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration, `ca_file` `NULL` or a nul-terminated string, and the
+/// other arguments nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_set_tls(
+    config: *mut SplinterNodeConfig,
+    ca_file: *const c_char,
+    client_cert: *const c_char,
+    client_key: *const c_char,
+    server_cert: *const c_char,
+    server_key: *const c_char,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        config.tls = Some(TlsFiles {
+            ca_file: optional_str(ca_file, "ca_file")?.map(String::from),
+            client_cert: required_str(client_cert, "client_cert")?.to_string(),
+            client_key: required_str(client_key, "client_key")?.to_string(),
+            server_cert: required_str(server_cert, "server_cert")?.to_string(),
+            server_key: required_str(server_key, "server_key")?.to_string(),
+        });
+        Ok(())
+    })
+}
+
+/// Registers a service type implemented by the host application. Services of this type on the
+/// node's circuits call the given callbacks.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration, `service_type` a nul-terminated string, and
+/// `callbacks` a valid pointer. The callbacks and `user_data` must remain valid until the node
+/// is stopped.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_config_add_service_type(
+    config: *mut SplinterNodeConfig,
+    service_type: *const c_char,
+    callbacks: *const SplinterServiceCallbacks,
+) -> c_int {
+    status(|| {
+        let config = config_mut(config)?;
+        let service_type = required_str(service_type, "service_type")?;
+        let callbacks = callbacks.as_ref().ok_or_else(|| {
+            set_last_error("callbacks must not be NULL".into());
+            SPLINTER_INVALID_ARGUMENT
+        })?;
+        if service_type == "scabbard" {
+            set_last_error("the scabbard service type is provided by the node".into());
+            return Err(SPLINTER_INVALID_ARGUMENT);
+        }
+        config
+            .service_types
+            .insert(service_type.to_string(), *callbacks);
+        Ok(())
+    })
+}
+
+/// A running Splinter node.
+pub struct SplinterNode {
+    running_daemon: RunningDaemon,
+    senders: ServiceSenders,
+}
+
+fn build_transport(config: &SplinterNodeConfig) -> Result<MultiTransport, String> {
+    let mut transports: Vec<Box<dyn Transport + Send>> = vec![Box::new(TcpTransport::default())];
+    if let Some(tls) = &config.tls {
+        transports.push(Box::new(
+            TlsTransport::new(
+                tls.ca_file.clone(),
+                tls.client_key.clone(),
+                tls.client_cert.clone(),
+                tls.server_key.clone(),
+                tls.server_cert.clone(),
+            )
+            .map_err(|err| format!("unable to create TLS transport: {}", err))?,
+        ));
+    }
+    Ok(MultiTransport::new(transports))
+}
+
+fn start_node(config: &SplinterNodeConfig) -> Result<SplinterNode, String> {
+    if config.network_endpoints.is_empty() {
+        return Err("at least one network endpoint is required".into());
+    }
+    let advertised_endpoints = if config.advertised_endpoints.is_empty() {
+        config.network_endpoints.clone()
+    } else {
+        config.advertised_endpoints.clone()
+    };
+
+    let senders = ServiceSenders::default();
+    let mut daemon_builder = SplinterDaemonBuilder::new()
+        .with_state_dir(config.state_dir.clone())
+        .with_network_endpoints(config.network_endpoints.clone())
+        .with_advertised_endpoints(advertised_endpoints)
+        .with_initial_peers(config.initial_peers.clone())
+        .with_node_id(config.node_id.clone())
+        .with_display_name(
+            config
+                .display_name
+                .clone()
+                .unwrap_or_else(|| config.node_id.clone()),
+        )
+        .with_rest_api_endpoint(config.rest_api_endpoint.clone())
+        .with_registries(config.registries.clone())
+        .with_registry_auto_refresh(DEFAULT_REGISTRY_AUTO_REFRESH)
+        .with_registry_forced_refresh(DEFAULT_REGISTRY_FORCED_REFRESH)
+        .with_heartbeat(DEFAULT_HEARTBEAT)
+        .with_admin_timeout(DEFAULT_ADMIN_TIMEOUT)
+        // Biome and the other database-backed stores are kept in memory; an embedded node has no
+        // migrated database to use
+        .with_db_url(Some("memory".into()))
+        .with_strict_ref_counts(false);
+    if !config.service_types.is_empty() {
+        daemon_builder = daemon_builder.with_service_factory(Box::new(
+            CallbackServiceFactory::new(config.service_types.clone(), senders.clone()),
+        ));
+    }
+
+    let mut daemon = daemon_builder
+        .build()
+        .map_err(|err| format!("unable to create node: {}", err))?;
+    let running_daemon = daemon
+        .run(build_transport(config)?)
+        .map_err(|err| format!("unable to start node: {}", err))?;
+
+    Ok(SplinterNode {
+        running_daemon,
+        senders,
+    })
+}
+
+/// Starts a node with the given configuration, returning `NULL` if it could not be started. The
+/// configuration is not consumed and must still be freed by the caller.
+///
+/// # Safety
+///
+/// `config` must be a valid configuration.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_start(
+    config: *const SplinterNodeConfig,
+) -> *mut SplinterNode {
+    catch_panic(ptr::null_mut(), || {
+        let config = match config.as_ref() {
+            Some(config) => config,
+            None => {
+                set_last_error("config must not be NULL".into());
+                return ptr::null_mut();
+            }
+        };
+
+        match start_node(config) {
+            Ok(node) => Box::into_raw(Box::new(node)),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Sends a message from a host-implemented service to another service on its circuit.
+///
+/// # Safety
+///
+/// `node` must be a running node, the string arguments nul-terminated strings, and `payload`
+/// a pointer to `payload_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_send(
+    node: *const SplinterNode,
+    circuit_id: *const c_char,
+    service_id: *const c_char,
+    recipient: *const c_char,
+    payload: *const u8,
+    payload_len: usize,
+) -> c_int {
+    status(|| {
+        let node = node.as_ref().ok_or_else(|| {
+            set_last_error("node must not be NULL".into());
+            SPLINTER_INVALID_ARGUMENT
+        })?;
+        if payload.is_null() && payload_len > 0 {
+            set_last_error("payload must not be NULL".into());
+            return Err(SPLINTER_INVALID_ARGUMENT);
+        }
+        let payload: &[u8] = if payload_len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(payload, payload_len)
+        };
+
+        node.senders
+            .send(
+                required_str(circuit_id, "circuit_id")?,
+                required_str(service_id, "service_id")?,
+                required_str(recipient, "recipient")?,
+                payload,
+            )
+            .map_err(|err| {
+                set_last_error(err);
+                SPLINTER_ERROR
+            })
+    })
+}
+
+/// Stops and frees a node. Returns `SPLINTER_ERROR` if any of the node's subsystems did not
+/// shut down in time; the node is freed either way.
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a pointer returned by `splinter_node_start` that has not already
+/// been stopped.
+#[no_mangle]
+pub unsafe extern "C" fn splinter_node_stop(node: *mut SplinterNode) -> c_int {
+    status(|| {
+        if node.is_null() {
+            return Ok(());
+        }
+
+        let node = Box::from_raw(node);
+        let timed_out = node.running_daemon.shutdown();
+        if timed_out.is_empty() {
+            Ok(())
+        } else {
+            set_last_error(format!(
+                "subsystems did not shut down in time: {}",
+                timed_out.join(", ")
+            ));
+            Err(SPLINTER_ERROR)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::raw::c_void;
+
+    use tempdir::TempDir;
+
+    unsafe fn last_error() -> String {
+        let message = splinter_last_error();
+        if message.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Verify that invalid arguments are rejected and the reason is available from
+    /// `splinter_last_error`.
+    #[test]
+    fn invalid_arguments() {
+        unsafe {
+            assert!(splinter_node_config_new(ptr::null(), ptr::null()).is_null());
+            let message = CStr::from_ptr(splinter_last_error()).to_str().unwrap();
+            assert_eq!(message, "node_id must not be NULL");
+
+            let node_id = CString::new("node-000").unwrap();
+            let state_dir = CString::new("/var/lib/splinter").unwrap();
+            let config = splinter_node_config_new(node_id.as_ptr(), state_dir.as_ptr());
+            assert!(!config.is_null());
+
+            let service_type = CString::new("scabbard").unwrap();
+            let callbacks = SplinterServiceCallbacks {
+                user_data: ptr::null_mut(),
+                on_start: None,
+                on_stop: None,
+                on_message: None,
+            };
+            assert_eq!(
+                splinter_node_config_add_service_type(config, service_type.as_ptr(), &callbacks),
+                SPLINTER_INVALID_ARGUMENT
+            );
+
+            // A node cannot start without a network endpoint
+            assert!(splinter_node_start(config).is_null());
+            let message = CStr::from_ptr(splinter_last_error()).to_str().unwrap();
+            assert_eq!(message, "at least one network endpoint is required");
+
+            splinter_node_config_free(config);
+        }
+    }
+
+    /// Verify that a panic in an entry point is reported as `SPLINTER_ERROR` with the panic
+    /// message, instead of unwinding into the host application.
+    #[test]
+    fn panic_is_reported() {
+        assert_eq!(status(|| panic!("unexpected state")), SPLINTER_ERROR);
+        assert_eq!(
+            unsafe { last_error() },
+            "panicked: unexpected state".to_string()
+        );
+    }
+
+    extern "C" fn on_lifecycle(
+        _user_data: *mut c_void,
+        _circuit_id: *const c_char,
+        _service_id: *const c_char,
+    ) {
+    }
+
+    /// Verify that a node with a host-implemented service type starts with a new state directory
+    /// and stops cleanly.
+    #[test]
+    fn start_and_stop_node() {
+        let state_dir = TempDir::new("splinter-c").expect("Unable to create state directory");
+
+        unsafe {
+            let node_id = CString::new("node-000").unwrap();
+            let state_dir = CString::new(state_dir.path().to_str().unwrap()).unwrap();
+            let config = splinter_node_config_new(node_id.as_ptr(), state_dir.as_ptr());
+            assert!(
+                !config.is_null(),
+                "Unable to create config: {}",
+                last_error()
+            );
+
+            let network_endpoint = CString::new("tcp://127.0.0.1:0").unwrap();
+            assert_eq!(
+                splinter_node_config_add_network_endpoint(config, network_endpoint.as_ptr()),
+                SPLINTER_OK
+            );
+            let rest_api_endpoint = CString::new("127.0.0.1:0").unwrap();
+            assert_eq!(
+                splinter_node_config_set_rest_api_endpoint(config, rest_api_endpoint.as_ptr()),
+                SPLINTER_OK
+            );
+
+            let service_type = CString::new("echo").unwrap();
+            let callbacks = SplinterServiceCallbacks {
+                user_data: ptr::null_mut(),
+                on_start: Some(on_lifecycle),
+                on_stop: Some(on_lifecycle),
+                on_message: None,
+            };
+            assert_eq!(
+                splinter_node_config_add_service_type(config, service_type.as_ptr(), &callbacks),
+                SPLINTER_OK
+            );
+
+            let node = splinter_node_start(config);
+            assert!(!node.is_null(), "Unable to start node: {}", last_error());
+            assert_eq!(
+                splinter_node_stop(node),
+                SPLINTER_OK,
+                "Unable to stop node: {}",
+                last_error()
+            );
+
+            splinter_node_config_free(config);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Services whose message handling is implemented by the host application.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use splinter::error::InternalError;
+use splinter::service::{
+    FactoryCreateError, Service, ServiceDestroyError, ServiceError, ServiceFactory,
+    ServiceMessageContext, ServiceNetworkRegistry, ServiceNetworkSender, ServiceStartError,
+    ServiceStopError,
+};
+
+/// Called when a service of a host-implemented type is started or stopped.
+pub type SplinterServiceLifecycleCallback =
+    extern "C" fn(user_data: *mut c_void, circuit_id: *const c_char, service_id: *const c_char);
+
+/// Called with each message received by a service of a host-implemented type.
+pub type SplinterServiceMessageCallback = extern "C" fn(
+    user_data: *mut c_void,
+    circuit_id: *const c_char,
+    service_id: *const c_char,
+    sender: *const c_char,
+    payload: *const u8,
+    payload_len: usize,
+);
+
+/// The callbacks that implement a service type in the host application.
+///
+/// The callbacks are invoked from the node's threads, and may be invoked concurrently for
+/// different services; `user_data` must be safe to use from any thread.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SplinterServiceCallbacks {
+    pub user_data: *mut c_void,
+    pub on_start: Option<SplinterServiceLifecycleCallback>,
+    pub on_stop: Option<SplinterServiceLifecycleCallback>,
+    pub on_message: Option<SplinterServiceMessageCallback>,
+}
+
+// The host application is required to make `user_data` thread-safe.
+unsafe impl Send for SplinterServiceCallbacks {}
+unsafe impl Sync for SplinterServiceCallbacks {}
+
+type ServiceKey = (String, String);
+
+/// The network senders of the running host-implemented services, by circuit and service ID.
+#[derive(Clone, Default)]
+pub struct ServiceSenders {
+    senders: Arc<Mutex<HashMap<ServiceKey, Box<dyn ServiceNetworkSender>>>>,
+}
+
+impl ServiceSenders {
+    /// Sends a message from the given service to another service on its circuit.
+    pub fn send(
+        &self,
+        circuit_id: &str,
+        service_id: &str,
+        recipient: &str,
+        message: &[u8],
+    ) -> Result<(), String> {
+        let sender = self
+            .senders
+            .lock()
+            .map_err(|_| "service sender lock poisoned".to_string())?
+            .get(&(circuit_id.to_string(), service_id.to_string()))
+            .cloned()
+            .ok_or_else(|| format!("service {}::{} is not running", circuit_id, service_id))?;
+
+        sender
+            .send(recipient, message)
+            .map_err(|err| err.to_string())
+    }
+
+    fn add(&self, key: ServiceKey, sender: Box<dyn ServiceNetworkSender>) -> Result<(), String> {
+        self.senders
+            .lock()
+            .map_err(|_| "service sender lock poisoned".to_string())?
+            .insert(key, sender);
+        Ok(())
+    }
+
+    fn remove(&self, key: &ServiceKey) -> Result<(), String> {
+        self.senders
+            .lock()
+            .map_err(|_| "service sender lock poisoned".to_string())?
+            .remove(key);
+        Ok(())
+    }
+}
+
+/// Creates `CallbackService`s for the service types registered by the host application.
+pub struct CallbackServiceFactory {
+    service_types: Vec<String>,
+    callbacks: HashMap<String, SplinterServiceCallbacks>,
+    senders: ServiceSenders,
+}
+
+impl CallbackServiceFactory {
+    pub fn new(
+        callbacks: HashMap<String, SplinterServiceCallbacks>,
+        senders: ServiceSenders,
+    ) -> Self {
+        Self {
+            service_types: callbacks.keys().cloned().collect(),
+            callbacks,
+            senders,
+        }
+    }
+}
+
+impl ServiceFactory for CallbackServiceFactory {
+    fn available_service_types(&self) -> &[String] {
+        &self.service_types
+    }
+
+    fn create(
+        &self,
+        service_id: String,
+        service_type: &str,
+        circuit_id: &str,
+        _args: HashMap<String, String>,
+    ) -> Result<Box<dyn Service>, FactoryCreateError> {
+        let callbacks = self.callbacks.get(service_type).copied().ok_or_else(|| {
+            FactoryCreateError::InvalidArguments(format!(
+                "service type {} is not registered",
+                service_type
+            ))
+        })?;
+
+        Ok(Box::new(CallbackService {
+            circuit_id: c_string(circuit_id)?,
+            service_id_c: c_string(&service_id)?,
+            circuit: circuit_id.to_string(),
+            service_id,
+            service_type: service_type.to_string(),
+            callbacks,
+            senders: self.senders.clone(),
+        }))
+    }
+
+    // Host-implemented services do not provide REST API endpoints
+    fn get_rest_endpoints(&self) -> Vec<splinter::service::rest_api::ServiceEndpoint> {
+        vec![]
+    }
+}
+
+fn c_string(value: &str) -> Result<CString, FactoryCreateError> {
+    CString::new(value).map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))
+}
+
+/// Calls one of the host application's callbacks, catching any panic so that it does not unwind
+/// into the node's threads.
+fn call_host<F: FnOnce()>(callback: &str, call: F) -> Result<(), InternalError> {
+    panic::catch_unwind(AssertUnwindSafe(call)).map_err(|payload| {
+        InternalError::with_message(format!(
+            "{} callback panicked: {}",
+            callback,
+            crate::panic_message(payload)
+        ))
+    })
+}
+
+/// A service that passes its lifecycle events and messages to the host application.
+struct CallbackService {
+    circuit: String,
+    service_id: String,
+    service_type: String,
+    circuit_id: CString,
+    service_id_c: CString,
+    callbacks: SplinterServiceCallbacks,
+    senders: ServiceSenders,
+}
+
+impl CallbackService {
+    fn key(&self) -> ServiceKey {
+        (self.circuit.clone(), self.service_id.clone())
+    }
+}
+
+impl Service for CallbackService {
+    fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    fn service_type(&self) -> &str {
+        &self.service_type
+    }
+
+    fn start(
+        &mut self,
+        service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStartError> {
+        let sender = service_registry
+            .connect(&self.service_id)
+            .map_err(ServiceStartError::UnableToConnect)?;
+        self.senders
+            .add(self.key(), sender)
+            .map_err(ServiceStartError::PoisonedLock)?;
+
+        if let Some(on_start) = self.callbacks.on_start {
+            call_host("on_start", || {
+                on_start(
+                    self.callbacks.user_data,
+                    self.circuit_id.as_ptr(),
+                    self.service_id_c.as_ptr(),
+                )
+            })
+            .map_err(|err| ServiceStartError::Internal(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn stop(
+        &mut self,
+        service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStopError> {
+        // The service is disconnected even if the callback panics
+        let result = match self.callbacks.on_stop {
+            Some(on_stop) => call_host("on_stop", || {
+                on_stop(
+                    self.callbacks.user_data,
+                    self.circuit_id.as_ptr(),
+                    self.service_id_c.as_ptr(),
+                )
+            }),
+            None => Ok(()),
+        };
+
+        self.senders
+            .remove(&self.key())
+            .map_err(ServiceStopError::PoisonedLock)?;
+        service_registry
+            .disconnect(&self.service_id)
+            .map_err(ServiceStopError::UnableToDisconnect)?;
+        result.map_err(|err| ServiceStopError::Internal(Box::new(err)))
+    }
+
+    fn destroy(self: Box<Self>) -> Result<(), ServiceDestroyError> {
+        Ok(())
+    }
+
+    fn handle_message(
+        &self,
+        message_bytes: &[u8],
+        message_context: &ServiceMessageContext,
+    ) -> Result<(), ServiceError> {
+        if let Some(on_message) = self.callbacks.on_message {
+            let sender = CString::new(message_context.sender.as_str())
+                .map_err(|err| ServiceError::InvalidMessageFormat(Box::new(err)))?;
+            call_host("on_message", || {
+                on_message(
+                    self.callbacks.user_data,
+                    self.circuit_id.as_ptr(),
+                    self.service_id_c.as_ptr(),
+                    sender.as_ptr(),
+                    message_bytes.as_ptr(),
+                    message_bytes.len(),
+                )
+            })
+            .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+        }
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Converts between the JSON values of the REST API and Python objects.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyList, PyTuple};
+use serde_json::{Map, Number, Value};
+
+/// Converts a JSON value to the equivalent Python object: objects become dictionaries and arrays
+/// become lists.
+pub fn to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(value) => value.to_object(py),
+        Value::Number(number) => {
+            if let Some(number) = number.as_i64() {
+                number.to_object(py)
+            } else if let Some(number) = number.as_u64() {
+                number.to_object(py)
+            } else {
+                number.as_f64().unwrap_or(f64::NAN).to_object(py)
+            }
+        }
+        Value::String(value) => value.to_object(py),
+        Value::Array(values) => {
+            let list = PyList::empty(py);
+            for value in values {
+                list.append(to_py(py, value)?)?;
+            }
+            list.to_object(py)
+        }
+        Value::Object(entries) => {
+            let dict = PyDict::new(py);
+            for (key, value) in entries {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.to_object(py)
+        }
+    })
+}
+
+/// Converts a Python object made of dictionaries with string keys, lists, tuples, strings,
+/// numbers, booleans and `None` to the equivalent JSON value.
+pub fn from_py(object: &PyAny) -> PyResult<Value> {
+    if object.is_none() {
+        return Ok(Value::Null);
+    }
+    // bool is a subclass of int, so it must be checked first
+    if let Ok(value) = object.extract::<&PyBool>() {
+        return Ok(Value::Bool(value.is_true()));
+    }
+    if let Ok(value) = object.extract::<i64>() {
+        return Ok(Value::from(value));
+    }
+    if let Ok(value) = object.extract::<u64>() {
+        return Ok(Value::from(value));
+    }
+    if let Ok(value) = object.extract::<f64>() {
+        return Number::from_f64(value)
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err("NaN and infinite numbers are not valid JSON"));
+    }
+    if let Ok(value) = object.extract::<String>() {
+        return Ok(Value::String(value));
+    }
+    if let Ok(dict) = object.extract::<&PyDict>() {
+        let mut entries = Map::new();
+        for (key, value) in dict.iter() {
+            let key = key
+                .extract::<String>()
+                .map_err(|_| PyValueError::new_err("dictionary keys must be strings"))?;
+            entries.insert(key, from_py(value)?);
+        }
+        return Ok(Value::Object(entries));
+    }
+    if let Ok(list) = object.extract::<&PyList>() {
+        return list
+            .iter()
+            .map(from_py)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+    if let Ok(tuple) = object.extract::<&PyTuple>() {
+        return tuple
+            .iter()
+            .map(from_py)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+
+    Err(PyValueError::new_err(format!(
+        "{} cannot be converted to JSON",
+        object.get_type().name()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pyo3::types::PyBytes;
+    use serde_json::json;
+
+    /// Verify that JSON values are converted to Python objects and back without change, and that
+    /// Python objects that have no JSON equivalent are rejected.
+    #[test]
+    fn round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let value = json!({
+            "identity": "alpha-node",
+            "endpoints": ["tcps://localhost:8044"],
+            "keys": [],
+            "metadata": {"organization": "Alpha", "admin": true, "score": 1.5},
+            "paging": {"total": 3, "limit": u64::MAX},
+            "comments": null,
+        });
+
+        let object = to_py(py, &value).expect("Failed to convert to Python");
+        let dict = object
+            .as_ref(py)
+            .extract::<&PyDict>()
+            .expect("Not converted to a dictionary");
+        assert_eq!(
+            "alpha-node",
+            dict.get_item("identity")
+                .unwrap()
+                .extract::<String>()
+                .unwrap()
+        );
+        assert_eq!(
+            value,
+            from_py(object.as_ref(py)).expect("Failed to convert")
+        );
+
+        let tuple = PyTuple::new(py, &[1, 2]);
+        assert_eq!(json!([1, 2]), from_py(tuple).unwrap());
+
+        let dict = PyDict::new(py);
+        dict.set_item(1, "one").unwrap();
+        assert!(from_py(dict).is_err());
+        assert!(from_py(PyBytes::new(py, b"bytes")).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Python bindings for the Splinter client libraries.
+
+mod json;
+mod rest;
+
+use std::time::Duration;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use scabbard::client::{self, ScabbardClientBuilder, ServiceId};
+use transact::protocol::batch::Batch;
+use transact::protos::FromBytes;
+
+create_exception!(splinter_python, ScabbardClientError, PyException);
+create_exception!(splinter_python, SplinterClientError, PyException);
+
+fn to_py_err(err: client::ScabbardClientError) -> PyErr {
+    ScabbardClientError::new_err(err.to_string())
+}
+
+fn rest_to_py_err(err: rest::RestClientError) -> PyErr {
+    SplinterClientError::new_err(err.to_string())
+}
+
+/// A client for the status, circuit management and registry endpoints of a Splinter node.
+///
+/// Resources are returned as dictionaries with the fields of the node's REST API responses.
+#[pyclass]
+#[text_signature = "(url, auth=None)"]
+struct SplinterClient {
+    client: rest::SplinterRestClient,
+}
+
+#[pymethods]
+impl SplinterClient {
+    #[new]
+    #[args(auth = "None")]
+    fn new(url: &str, auth: Option<&str>) -> Self {
+        SplinterClient {
+            client: rest::SplinterRestClient::new(url, auth),
+        }
+    }
+
+    /// Returns the node's status
+    #[text_signature = "($self)"]
+    fn get_status(&self, py: Python) -> PyResult<PyObject> {
+        json::to_py(py, &self.client.get_status().map_err(rest_to_py_err)?)
+    }
+
+    /// Returns the node's circuits, optionally only those that include the given member
+    #[text_signature = "($self, filter=None)"]
+    #[args(filter = "None")]
+    fn list_circuits(&self, py: Python, filter: Option<&str>) -> PyResult<Vec<PyObject>> {
+        self.client
+            .list_circuits(filter)
+            .map_err(rest_to_py_err)?
+            .iter()
+            .map(|circuit| json::to_py(py, circuit))
+            .collect()
+    }
+
+    /// Returns the circuit with the given ID, or `None` if it does not exist
+    #[text_signature = "($self, circuit_id)"]
+    fn get_circuit(&self, py: Python, circuit_id: &str) -> PyResult<Option<PyObject>> {
+        self.client
+            .get_circuit(circuit_id)
+            .map_err(rest_to_py_err)?
+            .map(|circuit| json::to_py(py, &circuit))
+            .transpose()
+    }
+
+    /// Returns the node's circuit proposals, optionally filtered by circuit management type and
+    /// member
+    #[text_signature = "($self, management_type=None, member=None)"]
+    #[args(management_type = "None", member = "None")]
+    fn list_proposals(
+        &self,
+        py: Python,
+        management_type: Option<&str>,
+        member: Option<&str>,
+    ) -> PyResult<Vec<PyObject>> {
+        self.client
+            .list_proposals(management_type, member)
+            .map_err(rest_to_py_err)?
+            .iter()
+            .map(|proposal| json::to_py(py, proposal))
+            .collect()
+    }
+
+    /// Returns the proposal for the circuit with the given ID, or `None` if it does not exist
+    #[text_signature = "($self, circuit_id)"]
+    fn get_proposal(&self, py: Python, circuit_id: &str) -> PyResult<Option<PyObject>> {
+        self.client
+            .get_proposal(circuit_id)
+            .map_err(rest_to_py_err)?
+            .map(|proposal| json::to_py(py, &proposal))
+            .transpose()
+    }
+
+    /// Submits a serialized, signed `CircuitManagementPayload` to the node's admin service, to
+    /// propose, vote on or disband a circuit
+    #[text_signature = "($self, payload)"]
+    fn submit_admin_payload(&self, payload: &[u8]) -> PyResult<()> {
+        self.client
+            .submit_admin_payload(payload.to_vec())
+            .map_err(rest_to_py_err)
+    }
+
+    /// Returns the nodes in the node's registry
+    #[text_signature = "($self)"]
+    fn list_registry_nodes(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        self.client
+            .list_registry_nodes()
+            .map_err(rest_to_py_err)?
+            .iter()
+            .map(|node| json::to_py(py, node))
+            .collect()
+    }
+
+    /// Returns the registry node with the given identity, or `None` if it does not exist
+    #[text_signature = "($self, identity)"]
+    fn get_registry_node(&self, py: Python, identity: &str) -> PyResult<Option<PyObject>> {
+        self.client
+            .get_registry_node(identity)
+            .map_err(rest_to_py_err)?
+            .map(|node| json::to_py(py, &node))
+            .transpose()
+    }
+
+    /// Adds the given node, a dictionary with the fields of a registry node, to the registry
+    #[text_signature = "($self, node)"]
+    fn add_registry_node(&self, node: &PyAny) -> PyResult<()> {
+        self.client
+            .add_registry_node(&json::from_py(node)?)
+            .map_err(rest_to_py_err)
+    }
+
+    /// Replaces the registry node with the identity of the given node
+    #[text_signature = "($self, node)"]
+    fn update_registry_node(&self, node: &PyAny) -> PyResult<()> {
+        self.client
+            .update_registry_node(&json::from_py(node)?)
+            .map_err(rest_to_py_err)
+    }
+
+    /// Removes the node with the given identity from the registry
+    #[text_signature = "($self, identity)"]
+    fn delete_registry_node(&self, identity: &str) -> PyResult<()> {
+        self.client
+            .delete_registry_node(identity)
+            .map_err(rest_to_py_err)
+    }
+}
+
+/// A client for the scabbard services of a Splinter node
+#[pyclass]
+#[text_signature = "(url, auth=None)"]
+struct ScabbardClient {
+    client: client::ScabbardClient,
+}
+
+#[pymethods]
+impl ScabbardClient {
+    #[new]
+    #[args(auth = "None")]
+    fn new(url: &str, auth: Option<&str>) -> PyResult<Self> {
+        #[allow(unused_mut)]
+        let mut builder = ScabbardClientBuilder::new().with_url(url);
+
+        #[cfg(feature = "client-auth")]
+        {
+            if let Some(auth) = auth {
+                builder = builder.with_auth(auth);
+            }
+        }
+        #[cfg(not(feature = "client-auth"))]
+        {
+            if auth.is_some() {
+                return Err(PyValueError::new_err(
+                    "auth is not supported; build with the client-auth feature",
+                ));
+            }
+        }
+
+        Ok(ScabbardClient {
+            client: builder.build().map_err(to_py_err)?,
+        })
+    }
+
+    /// Submits a serialized transact `BatchList` to the given service. If `wait_secs` is given,
+    /// waits up to that many seconds for the batches to commit.
+    #[text_signature = "($self, circuit_id, service_id, batch_list, wait_secs=None)"]
+    #[args(wait_secs = "None")]
+    fn submit(
+        &self,
+        circuit_id: &str,
+        service_id: &str,
+        batch_list: &[u8],
+        wait_secs: Option<u64>,
+    ) -> PyResult<()> {
+        let batches = Vec::<Batch>::from_bytes(batch_list).map_err(|err| {
+            PyValueError::new_err(format!("batch_list is not a valid batch list: {}", err))
+        })?;
+
+        self.client
+            .submit(
+                &ServiceId::new(circuit_id, service_id),
+                batches,
+                wait_secs.map(Duration::from_secs),
+            )
+            .map_err(to_py_err)
+    }
+
+    /// Returns the value at the given address in the service's state, or `None` if the address
+    /// is not set
+    #[text_signature = "($self, circuit_id, service_id, address)"]
+    fn get_state_at_address(
+        &self,
+        py: Python,
+        circuit_id: &str,
+        service_id: &str,
+        address: &str,
+    ) -> PyResult<Option<Py<PyBytes>>> {
+        Ok(self
+            .client
+            .get_state_at_address(&ServiceId::new(circuit_id, service_id), address)
+            .map_err(to_py_err)?
+            .map(|value| PyBytes::new(py, &value).into()))
+    }
+
+    /// Returns the `(address, value)` pairs in the service's state under the given address
+    /// prefix, or all of its state if no prefix is given
+    #[text_signature = "($self, circuit_id, service_id, prefix=None)"]
+    #[args(prefix = "None")]
+    fn get_state_with_prefix(
+        &self,
+        py: Python,
+        circuit_id: &str,
+        service_id: &str,
+        prefix: Option<&str>,
+    ) -> PyResult<Vec<(String, Py<PyBytes>)>> {
+        Ok(self
+            .client
+            .get_state_with_prefix(&ServiceId::new(circuit_id, service_id), prefix)
+            .map_err(to_py_err)?
+            .iter()
+            .map(|entry| {
+                (
+                    entry.address().to_string(),
+                    PyBytes::new(py, entry.value()).into(),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the current state root hash of the service
+    #[text_signature = "($self, circuit_id, service_id)"]
+    fn get_current_state_root(&self, circuit_id: &str, service_id: &str) -> PyResult<String> {
+        self.client
+            .get_current_state_root(&ServiceId::new(circuit_id, service_id))
+            .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn splinter_python(py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<ScabbardClient>()?;
+    module.add("ScabbardClientError", py.get_type::<ScabbardClientError>())?;
+    module.add_class::<SplinterClient>()?;
+    module.add("SplinterClientError", py.get_type::<SplinterClientError>())?;
+    Ok(())
+}
@@ -0,0 +1,528 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A client for the status, admin and registry endpoints of a Splinter node's REST API.
+//!
+//! Resources are returned as the JSON values of the REST API, so that the bindings can hand them
+//! to Python as dictionaries without defining a class for each of them.
+
+use std::error::Error;
+use std::fmt;
+
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    header, StatusCode,
+};
+use serde_json::Value;
+
+// The admin and registry protocol versions supported by the client
+const ADMIN_PROTOCOL_VERSION: &str = "2";
+const REGISTRY_PROTOCOL_VERSION: &str = "1";
+const PAGING_LIMIT: usize = 1000;
+
+/// A client for a Splinter node's REST API
+pub struct SplinterRestClient {
+    url: String,
+    auth: Option<String>,
+}
+
+impl SplinterRestClient {
+    /// Creates a client for the node at the given URL. If `auth` is given, it is sent as the
+    /// value of the `Authorization` header of each request.
+    pub fn new(url: &str, auth: Option<&str>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            auth: auth.map(String::from),
+        }
+    }
+
+    /// Gets the node's status
+    pub fn get_status(&self) -> Result<Value, RestClientError> {
+        let request = Client::new().get(&format!("{}/status", self.url));
+        json(self.send(request, None, "get node status")?)
+    }
+
+    /// Lists the node's circuits, optionally only those that include the given member
+    pub fn list_circuits(&self, filter: Option<&str>) -> Result<Vec<Value>, RestClientError> {
+        let mut query = vec![];
+        if let Some(filter) = filter {
+            query.push(("filter", filter.to_string()));
+        }
+        self.list(
+            "admin/circuits",
+            query,
+            ADMIN_PROTOCOL_VERSION,
+            "list circuits",
+        )
+    }
+
+    /// Gets the circuit with the given ID, or `None` if it does not exist
+    pub fn get_circuit(&self, circuit_id: &str) -> Result<Option<Value>, RestClientError> {
+        self.fetch(
+            &format!("admin/circuits/{}", circuit_id),
+            ADMIN_PROTOCOL_VERSION,
+            "get circuit",
+        )
+    }
+
+    /// Lists the node's circuit proposals, optionally filtered by circuit management type and
+    /// member
+    pub fn list_proposals(
+        &self,
+        management_type: Option<&str>,
+        member: Option<&str>,
+    ) -> Result<Vec<Value>, RestClientError> {
+        let mut query = vec![];
+        if let Some(management_type) = management_type {
+            query.push(("management_type", management_type.to_string()));
+        }
+        if let Some(member) = member {
+            query.push(("member", member.to_string()));
+        }
+        self.list(
+            "admin/proposals",
+            query,
+            ADMIN_PROTOCOL_VERSION,
+            "list proposals",
+        )
+    }
+
+    /// Gets the proposal for the circuit with the given ID, or `None` if it does not exist
+    pub fn get_proposal(&self, circuit_id: &str) -> Result<Option<Value>, RestClientError> {
+        self.fetch(
+            &format!("admin/proposals/{}", circuit_id),
+            ADMIN_PROTOCOL_VERSION,
+            "get proposal",
+        )
+    }
+
+    /// Submits a serialized, signed `CircuitManagementPayload` to the node's admin service
+    pub fn submit_admin_payload(&self, payload: Vec<u8>) -> Result<(), RestClientError> {
+        let request = Client::new()
+            .post(&format!("{}/admin/submit", self.url))
+            .header(header::CONTENT_TYPE, "octet-stream")
+            .body(payload);
+        self.send(
+            request,
+            Some(ADMIN_PROTOCOL_VERSION),
+            "submit admin payload",
+        )
+        .map(|_| ())
+    }
+
+    /// Lists the nodes in the node's registry
+    pub fn list_registry_nodes(&self) -> Result<Vec<Value>, RestClientError> {
+        self.list(
+            "registry/nodes",
+            vec![],
+            REGISTRY_PROTOCOL_VERSION,
+            "list registry nodes",
+        )
+    }
+
+    /// Gets the registry node with the given identity, or `None` if it does not exist
+    pub fn get_registry_node(&self, identity: &str) -> Result<Option<Value>, RestClientError> {
+        self.fetch(
+            &format!("registry/nodes/{}", identity),
+            REGISTRY_PROTOCOL_VERSION,
+            "get registry node",
+        )
+    }
+
+    /// Adds the given node to the node's registry
+    pub fn add_registry_node(&self, node: &Value) -> Result<(), RestClientError> {
+        let request = Client::new()
+            .post(&format!("{}/registry/nodes", self.url))
+            .json(node);
+        self.send(
+            request,
+            Some(REGISTRY_PROTOCOL_VERSION),
+            "add registry node",
+        )
+        .map(|_| ())
+    }
+
+    /// Replaces the registry node with the identity of the given node
+    pub fn update_registry_node(&self, node: &Value) -> Result<(), RestClientError> {
+        let identity = node
+            .get("identity")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RestClientError::new("node does not have an identity"))?;
+        let request = Client::new()
+            .put(&format!("{}/registry/nodes/{}", self.url, identity))
+            .json(node);
+        self.send(
+            request,
+            Some(REGISTRY_PROTOCOL_VERSION),
+            "update registry node",
+        )
+        .map(|_| ())
+    }
+
+    /// Removes the node with the given identity from the node's registry
+    pub fn delete_registry_node(&self, identity: &str) -> Result<(), RestClientError> {
+        let request = Client::new().delete(&format!("{}/registry/nodes/{}", self.url, identity));
+        self.send(
+            request,
+            Some(REGISTRY_PROTOCOL_VERSION),
+            "delete registry node",
+        )
+        .map(|_| ())
+    }
+
+    /// Reads every page of a paged list resource
+    fn list(
+        &self,
+        path: &str,
+        query: Vec<(&str, String)>,
+        protocol_version: &str,
+        action: &str,
+    ) -> Result<Vec<Value>, RestClientError> {
+        let mut items = vec![];
+        loop {
+            let request = Client::new()
+                .get(&format!("{}/{}", self.url, path))
+                .query(&query)
+                .query(&[("limit", PAGING_LIMIT), ("offset", items.len())]);
+            let mut page: Value = json(self.send(request, Some(protocol_version), action)?)?;
+
+            let data = match page.get_mut("data").map(Value::take) {
+                Some(Value::Array(data)) => data,
+                _ => {
+                    return Err(RestClientError::new(&format!(
+                        "failed to {}: response does not contain a data list",
+                        action
+                    )))
+                }
+            };
+            let total = page
+                .get("paging")
+                .and_then(|paging| paging.get("total"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as usize;
+
+            let page_len = data.len();
+            items.extend(data);
+            if page_len == 0 || items.len() >= total {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Gets a single resource, which is `None` if the node responds with `404 Not Found`
+    fn fetch(
+        &self,
+        path: &str,
+        protocol_version: &str,
+        action: &str,
+    ) -> Result<Option<Value>, RestClientError> {
+        let request = Client::new().get(&format!("{}/{}", self.url, path));
+        match self.send(request, Some(protocol_version), action) {
+            Ok(response) => json(response).map(Some),
+            Err(err) if err.status == Some(StatusCode::NOT_FOUND) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sends the request, returning an error that contains the node's error message if the
+    /// response does not have a success status
+    fn send(
+        &self,
+        mut request: RequestBuilder,
+        protocol_version: Option<&str>,
+        action: &str,
+    ) -> Result<Response, RestClientError> {
+        if let Some(protocol_version) = protocol_version {
+            request = request.header("SplinterProtocolVersion", protocol_version);
+        }
+        if let Some(auth) = &self.auth {
+            request = request.header(header::AUTHORIZATION, auth);
+        }
+
+        let response = request.send().map_err(|err| {
+            RestClientError::new_with_source(&format!("failed to {}", action), err.into())
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = response
+            .json::<Value>()
+            .ok()
+            .and_then(|body| {
+                body.get("message")
+                    .and_then(Value::as_str)
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| "error response was not valid".into());
+        Err(RestClientError {
+            context: format!("failed to {}: {}: {}", action, status, message),
+            source: None,
+            status: Some(status),
+        })
+    }
+}
+
+fn json(response: Response) -> Result<Value, RestClientError> {
+    response.json().map_err(|err| {
+        RestClientError::new_with_source("failed to deserialize response body", err.into())
+    })
+}
+
+/// The error returned by the REST API client
+#[derive(Debug)]
+pub struct RestClientError {
+    context: String,
+    source: Option<Box<dyn Error>>,
+    status: Option<StatusCode>,
+}
+
+impl RestClientError {
+    pub fn new(context: &str) -> Self {
+        Self {
+            context: context.into(),
+            source: None,
+            status: None,
+        }
+    }
+
+    pub fn new_with_source(context: &str, err: Box<dyn Error>) -> Self {
+        Self {
+            context: context.into(),
+            source: Some(err),
+            status: None,
+        }
+    }
+}
+
+impl Error for RestClientError {}
+
+impl fmt::Display for RestClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref err) = self.source {
+            write!(f, "{}: {}", self.context, err)
+        } else {
+            f.write_str(&self.context)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use serde_json::json;
+
+    /// Verify that the node status and a single circuit are returned as JSON values, that a
+    /// missing circuit is `None`, and that the protocol version and authorization are sent.
+    #[test]
+    fn get_status_and_circuit() {
+        let server = MockServer::start(|method, target| match (method, target) {
+            ("GET", "/status") => (200, json!({"node_id": "alpha-node"})),
+            ("GET", "/admin/circuits/01234-abcde") => (200, json!({"id": "01234-abcde"})),
+            _ => (404, json!({"message": "not found"})),
+        });
+        let client = SplinterRestClient::new(&server.url, Some("Bearer token"));
+
+        assert_eq!(
+            json!({"node_id": "alpha-node"}),
+            client.get_status().expect("Failed to get status")
+        );
+        assert_eq!(
+            Some(json!({"id": "01234-abcde"})),
+            client
+                .get_circuit("01234-abcde")
+                .expect("Failed to get circuit")
+        );
+        assert_eq!(
+            None,
+            client
+                .get_circuit("56789-fghij")
+                .expect("Failed to get circuit")
+        );
+
+        let requests = server.requests();
+        assert_eq!(None, requests[0].protocol_version);
+        assert_eq!(Some("2".to_string()), requests[1].protocol_version);
+        assert!(requests
+            .iter()
+            .all(|request| request.auth == Some("Bearer token".to_string())));
+    }
+
+    /// Verify that every page of a list is read, and that the list filters are sent.
+    #[test]
+    fn list_proposals_pages() {
+        let server = MockServer::start(|method, target| match (method, target) {
+            ("GET", "/admin/proposals?member=beta-node&limit=1000&offset=0") => (
+                200,
+                json!({"data": [{"circuit_id": "a"}, {"circuit_id": "b"}], "paging": {"total": 3}}),
+            ),
+            ("GET", "/admin/proposals?member=beta-node&limit=1000&offset=2") => (
+                200,
+                json!({"data": [{"circuit_id": "c"}], "paging": {"total": 3}}),
+            ),
+            _ => (404, json!({"message": "not found"})),
+        });
+        let client = SplinterRestClient::new(&server.url, None);
+
+        assert_eq!(
+            vec![
+                json!({"circuit_id": "a"}),
+                json!({"circuit_id": "b"}),
+                json!({"circuit_id": "c"})
+            ],
+            client
+                .list_proposals(None, Some("beta-node"))
+                .expect("Failed to list proposals")
+        );
+    }
+
+    /// Verify that admin payloads and registry nodes are sent as the request bodies, and that an
+    /// error response is returned as an error with the node's message.
+    #[test]
+    fn submit_and_manage_registry() {
+        let server = MockServer::start(|method, target| match (method, target) {
+            ("POST", "/admin/submit") => (202, json!({})),
+            ("POST", "/registry/nodes") => (204, Value::Null),
+            ("PUT", "/registry/nodes/alpha-node") => (204, Value::Null),
+            ("DELETE", "/registry/nodes/alpha-node") => (400, json!({"message": "node is in use"})),
+            _ => (404, json!({"message": "not found"})),
+        });
+        let client = SplinterRestClient::new(&server.url, None);
+
+        client
+            .submit_admin_payload(b"payload".to_vec())
+            .expect("Failed to submit payload");
+
+        let node = json!({"identity": "alpha-node", "endpoints": ["tcps://localhost:8044"]});
+        client.add_registry_node(&node).expect("Failed to add node");
+        client
+            .update_registry_node(&node)
+            .expect("Failed to update node");
+        assert!(client
+            .update_registry_node(&json!({"endpoints": []}))
+            .is_err());
+
+        let err = client
+            .delete_registry_node("alpha-node")
+            .expect_err("Deleting the node should fail");
+        assert!(err.to_string().contains("node is in use"));
+
+        let requests = server.requests();
+        assert_eq!(b"payload".to_vec(), requests[0].body);
+        assert_eq!(
+            node,
+            serde_json::from_slice::<Value>(&requests[1].body).unwrap()
+        );
+        assert_eq!(Some("1".to_string()), requests[2].protocol_version);
+    }
+
+    struct MockRequest {
+        protocol_version: Option<String>,
+        auth: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// An HTTP server that responds to each request with the status and JSON body returned by
+    /// its handler for the request's method and target, and records the requests it receives
+    struct MockServer {
+        url: String,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+    }
+
+    impl MockServer {
+        fn start<F>(handler: F) -> Self
+        where
+            F: Fn(&str, &str) -> (u16, Value) + Send + 'static,
+        {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(vec![]));
+
+            let thread_requests = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => break,
+                    };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let target = parts.next().unwrap_or_default().to_string();
+
+                    let mut request = MockRequest {
+                        protocol_version: None,
+                        auth: None,
+                        body: vec![],
+                    };
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        let mut header = line.splitn(2, ':');
+                        let name = header.next().unwrap_or_default().to_lowercase();
+                        let value = header.next().unwrap_or_default().trim().to_string();
+                        match name.as_str() {
+                            "content-length" => content_length = value.parse().unwrap(),
+                            "splinterprotocolversion" => request.protocol_version = Some(value),
+                            "authorization" => request.auth = Some(value),
+                            _ => (),
+                        }
+                    }
+                    request.body = vec![0; content_length];
+                    reader.read_exact(&mut request.body).unwrap();
+
+                    let (status, body) = handler(&method, &target);
+                    thread_requests.lock().unwrap().push(request);
+
+                    let body = if body.is_null() {
+                        String::new()
+                    } else {
+                        body.to_string()
+                    };
+                    write!(
+                        stream,
+                        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                }
+            });
+
+            Self { url, requests }
+        }
+
+        fn requests(&self) -> Vec<MockRequest> {
+            std::mem::replace(&mut *self.requests.lock().unwrap(), vec![])
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use splinter::mesh::{Envelope, Mesh, SendError};
+use splinter::protos::circuit::CircuitMessageType;
+use splinter::protos::envelope::{circuit_envelope, EnvelopeRef};
+use splinter::transport::{inproc::InprocTransport, Transport};
+
+use crate::error::CliError;
+
+use super::{print_table, Action};
+
+const DEFAULT_MESSAGES: u64 = 100_000;
+const DEFAULT_MESSAGE_SIZE: usize = 1024;
+// The minimum number of messages per second the mesh is expected to sustain for the default
+// message size
+const DEFAULT_TARGET_THROUGHPUT: u64 = 50_000;
+
+/// The result of a single benchmark
+struct BenchResult {
+    name: &'static str,
+    messages: u64,
+    bytes: u64,
+    elapsed: Duration,
+}
+
+impl BenchResult {
+    fn messages_per_sec(&self) -> f64 {
+        self.messages as f64 / self.elapsed.as_secs_f64()
+    }
+
+    fn mib_per_sec(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64() / (1024.0 * 1024.0)
+    }
+}
+
+pub struct BenchAction;
+
+impl Action for BenchAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let messages = parse_arg(arg_matches, "messages", DEFAULT_MESSAGES)?;
+        let size = parse_arg(arg_matches, "size", DEFAULT_MESSAGE_SIZE)?;
+        let target = parse_arg(arg_matches, "target", DEFAULT_TARGET_THROUGHPUT)?;
+
+        if messages == 0 {
+            return Err(CliError::ActionError(
+                "'messages' must be greater than 0".into(),
+            ));
+        }
+
+        let results = vec![
+            bench_mesh(messages, size)?,
+            bench_envelope_encoding(messages, size)?,
+        ];
+
+        let mut table = vec![vec![
+            "BENCHMARK".to_string(),
+            "MESSAGES".to_string(),
+            "ELAPSED".to_string(),
+            "MSG/S".to_string(),
+            "MIB/S".to_string(),
+        ]];
+        for result in &results {
+            table.push(vec![
+                result.name.to_string(),
+                result.messages.to_string(),
+                format!("{:.3}s", result.elapsed.as_secs_f64()),
+                format!("{:.0}", result.messages_per_sec()),
+                format!("{:.2}", result.mib_per_sec()),
+            ]);
+        }
+        print_table(table);
+
+        let mesh_throughput = results[0].messages_per_sec();
+        if target > 0 && mesh_throughput < target as f64 {
+            return Err(CliError::ActionError(format!(
+                "mesh throughput of {:.0} messages per second is below the target of {}",
+                mesh_throughput, target
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(
+    arg_matches: Option<&ArgMatches>,
+    name: &str,
+    default: T,
+) -> Result<T, CliError> {
+    match arg_matches.and_then(|args| args.value_of(name)) {
+        Some(value) => value.parse().map_err(|_| {
+            CliError::ActionError(format!("'{}' must be a non-negative integer", name))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Sends messages from one mesh to another over an in-process connection, measuring the time
+/// until the last message is received.
+fn bench_mesh(messages: u64, size: usize) -> Result<BenchResult, CliError> {
+    let mut transport = InprocTransport::default();
+    let mut listener = transport
+        .listen("inproc://splinter-bench")
+        .map_err(|err| CliError::ActionError(format!("Unable to listen: {}", err)))?;
+
+    let sender_mesh = Mesh::new(512, 128);
+    let receiver_mesh = Mesh::new(512, 128);
+
+    let connection = transport
+        .connect(&listener.endpoint())
+        .map_err(|err| CliError::ActionError(format!("Unable to connect: {}", err)))?;
+    sender_mesh
+        .add(connection, "receiver".to_string())
+        .map_err(|err| CliError::ActionError(format!("Unable to add connection: {}", err)))?;
+    let connection = listener
+        .accept()
+        .map_err(|err| CliError::ActionError(format!("Unable to accept connection: {}", err)))?;
+    receiver_mesh
+        .add(connection, "sender".to_string())
+        .map_err(|err| CliError::ActionError(format!("Unable to add connection: {}", err)))?;
+
+    let start = Instant::now();
+
+    let payload = vec![0u8; size];
+    let send_mesh = sender_mesh.clone();
+    let send_thread = thread::spawn(move || {
+        for _ in 0..messages {
+            let mut envelope = Envelope::new("receiver".to_string(), payload.clone());
+            // Retry when the outgoing queue is full, so the benchmark measures the sustained rate
+            loop {
+                match send_mesh.send(envelope) {
+                    Ok(()) => break,
+                    Err(SendError::Full(returned)) => {
+                        envelope = returned;
+                        thread::yield_now();
+                    }
+                    Err(err) => return Err(err.to_string()),
+                }
+            }
+        }
+        Ok(())
+    });
+
+    for _ in 0..messages {
+        receiver_mesh
+            .recv()
+            .map_err(|err| CliError::ActionError(format!("Unable to receive message: {}", err)))?;
+    }
+    let elapsed = start.elapsed();
+
+    send_thread
+        .join()
+        .map_err(|_| CliError::ActionError("Sending thread panicked".into()))?
+        .map_err(|err| CliError::ActionError(format!("Unable to send message: {}", err)))?;
+
+    sender_mesh.shutdown_signaler().shutdown();
+    receiver_mesh.shutdown_signaler().shutdown();
+
+    Ok(BenchResult {
+        name: "mesh send/recv",
+        messages,
+        bytes: messages * size as u64,
+        elapsed,
+    })
+}
+
+/// Encodes payloads into circuit message envelopes and decodes them again.
+fn bench_envelope_encoding(messages: u64, size: usize) -> Result<BenchResult, CliError> {
+    let payload = vec![0u8; size];
+
+    let start = Instant::now();
+    for _ in 0..messages {
+        let bytes = circuit_envelope(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE, &payload);
+        let network_msg = EnvelopeRef::parse(&bytes).map_err(|err| {
+            CliError::ActionError(format!("Unable to parse network message: {}", err))
+        })?;
+        let circuit_msg = EnvelopeRef::parse(network_msg.payload()).map_err(|err| {
+            CliError::ActionError(format!("Unable to parse circuit message: {}", err))
+        })?;
+        if circuit_msg.payload().len() != size {
+            return Err(CliError::ActionError(
+                "Decoded payload does not match the encoded payload".into(),
+            ));
+        }
+    }
+    let elapsed = start.elapsed();
+
+    Ok(BenchResult {
+        name: "envelope encode/decode",
+        messages,
+        bytes: messages * size as u64,
+        elapsed,
+    })
+}
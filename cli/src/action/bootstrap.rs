@@ -0,0 +1,373 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bootstraps a new Splinter node on a remote host over SSH.
+//!
+//! The remote host must already have splinterd and its systemd unit installed, and the invoking
+//! user must be able to log in without a password prompt and run `sudo` non-interactively. The
+//! node's REST API must be reachable from the machine running the command.
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use splinter::registry::Node;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+use super::node::challenge_node;
+use super::registry::parse_metadata;
+use super::{msg_from_io_error, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+
+const REMOTE_CONFIG_DIR: &str = "/etc/splinter";
+const REMOTE_CONFIG_FILE: &str = "/etc/splinter/splinterd.toml";
+const SERVICE_USER: &str = "splinterd";
+const SERVICE_NAME: &str = "splinterd";
+
+const DEFAULT_REST_API_PORT: u16 = 8080;
+const DEFAULT_TIMEOUT: u64 = 120;
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct NodeBootstrapAction;
+
+impl Action for NodeBootstrapAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let host = args
+            .value_of("host")
+            .ok_or_else(|| CliError::ActionError("'host' argument is required".into()))?;
+        if host.starts_with('-') {
+            return Err(CliError::ActionError(format!("Invalid host: {}", host)));
+        }
+        let ssh = Ssh {
+            host,
+            options: args
+                .values_of("ssh_option")
+                .map(|options| options.collect())
+                .unwrap_or_default(),
+        };
+
+        let node_url = args
+            .value_of("node_url")
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| default_node_url(host));
+
+        let registry_url = args
+            .value_of("url")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+        let timeout = args
+            .value_of("timeout")
+            .map(|timeout| {
+                timeout.parse::<u64>().map_err(|_| {
+                    CliError::ActionError(format!("'timeout' must be a number: {}", timeout))
+                })
+            })
+            .transpose()?
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| Duration::from_secs(DEFAULT_TIMEOUT));
+
+        let metadata = parse_metadata(args)?;
+        let expected_peers: Vec<&str> = args
+            .values_of("expect_peer")
+            .map(|peers| peers.collect())
+            .unwrap_or_default();
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        let auth = create_cylinder_jwt_auth(args.value_of("private_key_file"))?;
+
+        // Install the config file, then let splinterd generate its keys and certificates; files
+        // that already exist on the host are left untouched by the provisioning step
+        if let Some(config_file) = args.value_of("config") {
+            let config = fs::read(config_file).map_err(|err| {
+                CliError::EnvironmentError(format!(
+                    "Unable to read '{}': {}",
+                    config_file,
+                    msg_from_io_error(err)
+                ))
+            })?;
+            ssh.run(
+                &format!(
+                    "sudo install -d -m 755 {} && sudo tee {} > /dev/null",
+                    REMOTE_CONFIG_DIR, REMOTE_CONFIG_FILE
+                ),
+                Some(&config),
+            )?;
+            println!("# installed {} as {}", config_file, REMOTE_CONFIG_FILE);
+        }
+
+        let report = ssh.run(
+            &format!(
+                "sudo -u {} splinterd --provision --config {}",
+                SERVICE_USER, REMOTE_CONFIG_FILE
+            ),
+            None,
+        )?;
+        print!("{}", report);
+
+        ssh.run(
+            &format!(
+                "sudo systemctl enable {0} && sudo systemctl restart {0}",
+                SERVICE_NAME
+            ),
+            None,
+        )?;
+        println!("# started {} on {}", SERVICE_NAME, host);
+
+        let node_client = build_client(
+            node_url.clone(),
+            #[cfg(feature = "splinter-cli-jwt")]
+            auth.clone(),
+        )?;
+        let deadline = Instant::now() + timeout;
+
+        let node_status = wait_until(deadline, || Ok(node_client.get_node_status().ok()))?
+            .ok_or_else(|| {
+                CliError::ActionError(format!(
+                    "Node at {} did not come up within {} seconds",
+                    node_url,
+                    timeout.as_secs()
+                ))
+            })?;
+        println!("# node {} is up at {}", node_status.node_id, node_url);
+
+        // The node proves it holds the key that is about to be registered
+        let challenge = challenge_node(
+            &node_url,
+            #[cfg(feature = "splinter-cli-jwt")]
+            &auth,
+        )?;
+        if challenge.node_id != node_status.node_id {
+            return Err(CliError::ActionError(format!(
+                "Node at {} reported ID {} in its status, but {} in its challenge",
+                node_url, node_status.node_id, challenge.node_id
+            )));
+        }
+
+        let node = Node {
+            identity: node_status.node_id,
+            endpoints: node_status.advertised_endpoints,
+            display_name: node_status.display_name,
+            keys: vec![challenge.public_key],
+            metadata,
+        };
+        register_node(
+            &build_client(
+                registry_url.clone(),
+                #[cfg(feature = "splinter-cli-jwt")]
+                auth,
+            )?,
+            &node,
+            args.is_present("force"),
+        )?;
+        println!(
+            "# registered {} in the registry at {}",
+            node.identity, registry_url
+        );
+
+        if !expected_peers.is_empty() {
+            let mut missing = expected_peers.clone();
+            let peered = wait_until(deadline, || {
+                let peers = node_client.list_peers()?;
+                let connected: Vec<&str> = peers
+                    .data
+                    .iter()
+                    .filter(|peer| peer.connection_id.is_some())
+                    .map(|peer| peer.peer_id.as_str())
+                    .collect();
+                missing = missing_peers(&expected_peers, &connected);
+                Ok(if missing.is_empty() { Some(()) } else { None })
+            })?;
+            if peered.is_none() {
+                return Err(CliError::ActionError(format!(
+                    "Node {} did not connect to {} within {} seconds",
+                    node.identity,
+                    missing.join(", "),
+                    timeout.as_secs()
+                )));
+            }
+            println!(
+                "# node {} is peered with {}",
+                node.identity,
+                expected_peers.join(", ")
+            );
+        }
+
+        println!("# node {} is bootstrapped", node.identity);
+
+        Ok(())
+    }
+}
+
+/// Runs commands on a remote host with the system's `ssh` client.
+struct Ssh<'a> {
+    host: &'a str,
+    options: Vec<&'a str>,
+}
+
+impl<'a> Ssh<'a> {
+    /// Runs the command on the remote host, writing `input` to its standard input, and returns
+    /// its standard output.
+    fn run(&self, command: &str, input: Option<&[u8]>) -> Result<String, CliError> {
+        debug!("Running on {}: {}", self.host, command);
+
+        let mut ssh = Command::new("ssh");
+        // Fail instead of prompting for passwords, which would hang the command
+        ssh.args(&["-o", "BatchMode=yes"]);
+        for option in &self.options {
+            ssh.args(&["-o", option]);
+        }
+        let mut child = ssh
+            .arg(self.host)
+            .arg(command)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                CliError::EnvironmentError(format!("Unable to run ssh: {}", msg_from_io_error(err)))
+            })?;
+
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Unable to send input to {}: {}",
+                    self.host,
+                    msg_from_io_error(err)
+                ))
+            })?;
+        }
+
+        let output = child.wait_with_output().map_err(|err| {
+            CliError::ActionError(format!("Unable to run ssh: {}", msg_from_io_error(err)))
+        })?;
+
+        if !output.status.success() {
+            return Err(CliError::ActionError(format!(
+                "Command '{}' failed on {}: {}",
+                command,
+                self.host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn build_client(
+    url: String,
+    #[cfg(feature = "splinter-cli-jwt")] auth: String,
+) -> Result<SplinterRestClient, CliError> {
+    #[allow(unused_mut)]
+    let mut builder = SplinterRestClientBuilder::new().with_url(url);
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        builder = builder.with_auth(auth);
+    }
+
+    builder.build()
+}
+
+/// Adds the node to the registry; an existing entry with other keys or endpoints is only replaced
+/// if `force` is set.
+fn register_node(client: &SplinterRestClient, node: &Node, force: bool) -> Result<(), CliError> {
+    match client.fetch_registry_node(&node.identity)? {
+        None => client.create_registry_node(node),
+        Some(existing)
+            if node.keys.iter().all(|key| existing.has_key(key))
+                && existing.endpoints == node.endpoints =>
+        {
+            info!("Node {} is already registered", node.identity);
+            Ok(())
+        }
+        Some(_) if force => client.update_registry_node(node),
+        Some(_) => Err(CliError::EnvironmentError(format!(
+            "Node '{}' is already registered with other keys or endpoints; must use '--force' to \
+             overwrite the existing node",
+            node.identity
+        ))),
+    }
+}
+
+/// Calls `check` until it returns a value or the deadline passes, in which case `None` is
+/// returned.
+fn wait_until<T, F>(deadline: Instant, mut check: F) -> Result<Option<T>, CliError>
+where
+    F: FnMut() -> Result<Option<T>, CliError>,
+{
+    loop {
+        if let Some(value) = check()? {
+            return Ok(Some(value));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Returns the REST API URL of the node on the given SSH host, which may include a user name.
+fn default_node_url(host: &str) -> String {
+    let hostname = host.rsplit('@').next().unwrap_or(host);
+    format!("http://{}:{}", hostname, DEFAULT_REST_API_PORT)
+}
+
+/// Returns the expected peers that are not connected.
+fn missing_peers<'a>(expected: &[&'a str], connected: &[&str]) -> Vec<&'a str> {
+    expected
+        .iter()
+        .filter(|peer| !connected.contains(peer))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifies that the default node URL uses the host name of the SSH destination.
+    #[test]
+    fn test_default_node_url() {
+        assert_eq!(default_node_url("node-1"), "http://node-1:8080");
+        assert_eq!(
+            default_node_url("admin@node-1.example.com"),
+            "http://node-1.example.com:8080"
+        );
+    }
+
+    /// Verifies that only the expected peers without a connection are reported as missing.
+    #[test]
+    fn test_missing_peers() {
+        assert_eq!(
+            missing_peers(&["alpha", "beta", "gamma"], &["beta", "delta"]),
+            vec!["alpha", "gamma"]
+        );
+        assert!(missing_peers(&["alpha"], &["alpha"]).is_empty());
+    }
+}
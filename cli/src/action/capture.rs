@@ -0,0 +1,211 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use clap::ArgMatches;
+use serde::Deserialize;
+
+use crate::error::CliError;
+
+use super::{print_table, Action};
+
+const PEER_EVENT_DIRECTION: &str = "peer";
+
+/// A line of a capture file written by a node's message capture
+#[derive(Deserialize)]
+struct CaptureRecord {
+    timestamp: u64,
+    direction: String,
+    peer_id: String,
+    connection_id: Option<String>,
+    message_type: String,
+    circuit_message_type: Option<String>,
+    size: usize,
+    payload_hash: Option<String>,
+}
+
+#[derive(Default)]
+struct MessageTotals {
+    count: u64,
+    bytes: u64,
+}
+
+pub struct CaptureInspectAction;
+
+impl Action for CaptureInspectAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let path = args
+            .value_of("file")
+            .ok_or_else(|| CliError::ActionError("'file' argument is required".into()))?;
+        let peer = args.value_of("peer");
+
+        let records = read_capture_file(path)?
+            .into_iter()
+            .filter(|record| peer.map(|peer| record.peer_id == peer).unwrap_or(true))
+            .collect::<Vec<_>>();
+
+        if args.is_present("records") {
+            print_records(&records);
+        } else {
+            print_summary(&records);
+        }
+
+        Ok(())
+    }
+}
+
+fn read_capture_file(path: &str) -> Result<Vec<CaptureRecord>, CliError> {
+    let file = File::open(path).map_err(|err| {
+        CliError::EnvironmentError(format!("Unable to open capture file {}: {}", path, err))
+    })?;
+
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map(|line| !line.is_empty()).unwrap_or(true))
+        .map(|(index, line)| {
+            let line = line.map_err(|err| {
+                CliError::EnvironmentError(format!("Unable to read capture file {}: {}", path, err))
+            })?;
+            serde_json::from_str::<CaptureRecord>(&line).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Line {} of {} is not a valid capture record: {}",
+                    index + 1,
+                    path,
+                    err
+                ))
+            })
+        })
+        .collect()
+}
+
+fn print_summary(records: &[CaptureRecord]) {
+    let (first, last) = match (records.first(), records.last()) {
+        (Some(first), Some(last)) => (first.timestamp, last.timestamp),
+        _ => {
+            println!("The capture contains no records");
+            return;
+        }
+    };
+
+    let mut totals: BTreeMap<(&str, &str, String), MessageTotals> = BTreeMap::new();
+    let mut events = vec![vec![
+        "TIME".to_string(),
+        "PEER ID".to_string(),
+        "EVENT".to_string(),
+    ]];
+    for record in records {
+        if record.direction == PEER_EVENT_DIRECTION {
+            events.push(vec![
+                format_timestamp(record.timestamp),
+                record.peer_id.clone(),
+                record.message_type.clone(),
+            ]);
+            continue;
+        }
+
+        let totals = totals
+            .entry((&record.peer_id, &record.direction, message_type(record)))
+            .or_default();
+        totals.count += 1;
+        totals.bytes += record.size as u64;
+    }
+
+    println!(
+        "Records: {} over {}.{:03}s ({} to {})",
+        records.len(),
+        (last - first) / 1000,
+        (last - first) % 1000,
+        format_timestamp(first),
+        format_timestamp(last)
+    );
+
+    if !totals.is_empty() {
+        println!();
+        let mut table = vec![vec![
+            "PEER ID".to_string(),
+            "DIRECTION".to_string(),
+            "MESSAGE TYPE".to_string(),
+            "COUNT".to_string(),
+            "BYTES".to_string(),
+        ]];
+        table.extend(
+            totals
+                .into_iter()
+                .map(|((peer_id, direction, message_type), totals)| {
+                    vec![
+                        peer_id.to_string(),
+                        direction.to_string(),
+                        message_type,
+                        totals.count.to_string(),
+                        totals.bytes.to_string(),
+                    ]
+                }),
+        );
+        print_table(table);
+    }
+
+    if events.len() > 1 {
+        println!();
+        print_table(events);
+    }
+}
+
+fn print_records(records: &[CaptureRecord]) {
+    let mut table = vec![vec![
+        "TIME".to_string(),
+        "DIRECTION".to_string(),
+        "PEER ID".to_string(),
+        "CONNECTION ID".to_string(),
+        "MESSAGE TYPE".to_string(),
+        "BYTES".to_string(),
+        "PAYLOAD HASH".to_string(),
+    ]];
+    table.extend(records.iter().map(|record| {
+        vec![
+            format_timestamp(record.timestamp),
+            record.direction.clone(),
+            record.peer_id.clone(),
+            record
+                .connection_id
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+            message_type(record),
+            record.size.to_string(),
+            record
+                .payload_hash
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ]
+    }));
+    print_table(table);
+}
+
+/// Returns the network message type, followed by the circuit message type for circuit messages
+fn message_type(record: &CaptureRecord) -> String {
+    match &record.circuit_message_type {
+        Some(circuit_message_type) => format!("{}/{}", record.message_type, circuit_message_type),
+        None => record.message_type.clone(),
+    }
+}
+
+/// Formats milliseconds since the unix epoch as seconds with a fractional part
+fn format_timestamp(timestamp: u64) -> String {
+    format!("{}.{:03}", timestamp / 1000, timestamp % 1000)
+}
@@ -0,0 +1,762 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Actions for operating a small internal certificate authority: requesting, signing, renewing,
+//! and verifying the certificates used by splinterd's TLS configuration.
+
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+use clap::ArgMatches;
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, PKeyRef, Private, Public};
+use openssl::rsa::Rsa;
+use openssl::stack::Stack;
+use openssl::x509::extension::{
+    BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+};
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{
+    X509NameBuilder, X509NameRef, X509Ref, X509Req, X509ReqBuilder, X509StoreContext, X509,
+};
+
+use crate::action::{print_table, Action};
+use crate::error::CliError;
+
+#[cfg(feature = "https-certs")]
+use super::REST_API_CERT;
+use super::{
+    absolute_path, get_ca_cert, get_ca_key, get_cert_dir, write_file, CA_CERT, CA_KEY, CLIENT_CERT,
+    SERVER_CERT,
+};
+
+/// The CA file name splinterd uses by default.
+const SPLINTERD_CA_FILE: &str = "ca.pem";
+
+const DEFAULT_VALIDITY_DAYS: u32 = 365;
+const DEFAULT_WARN_DAYS: i32 = 30;
+
+pub struct CertRequestAction;
+
+impl Action for CertRequestAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let name = args.value_of("name").ok_or(CliError::RequiresArgs)?;
+        let common_name = args.value_of("common_name").unwrap_or("localhost");
+        let subject_alt_names = args
+            .values_of("subject_alt_names")
+            .map(|values| values.map(String::from).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let cert_dir = get_cert_dir(args)?;
+        let private_cert_path = cert_dir.join("private");
+        if !private_cert_path.is_dir() {
+            fs::create_dir_all(&private_cert_path).map_err(|err| {
+                CliError::ActionError(format!("Unable to create private directory: {}", err))
+            })?
+        }
+
+        // Reuse an existing key unless a new one is requested, so a CSR can be generated for
+        // a key that is already deployed
+        let key_file = format!("{}.key", name);
+        let key_path = private_cert_path.join(&key_file);
+        let key = if key_path.exists() && !args.is_present("new_key") {
+            info!("Using existing key: {}", absolute_path(&key_path)?);
+            get_ca_key(&key_path)?
+        } else {
+            let key = PKey::from_rsa(Rsa::generate(2048)?)?;
+            info!("Writing file: {}", key_path.display());
+            write_file(
+                &private_cert_path,
+                &key_file,
+                &key.private_key_to_pem_pkcs8()?,
+            )?;
+            key
+        };
+
+        let csr = make_csr(&key, common_name, &subject_alt_names)?;
+
+        let csr_file = format!("{}.csr", name);
+        info!("Writing file: {}", cert_dir.join(&csr_file).display());
+        write_file(&cert_dir, &csr_file, &csr.to_pem()?)?;
+
+        Ok(())
+    }
+}
+
+pub struct CertSignAction;
+
+impl Action for CertSignAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let csr_path = Path::new(args.value_of("csr").ok_or(CliError::RequiresArgs)?);
+        let days = parse_days(args.value_of("days"))?;
+        let (ca_cert, ca_key) = load_ca(args)?;
+
+        let csr = X509Req::from_pem(&fs::read(csr_path)?)?;
+        let csr_public_key = csr.public_key()?;
+        if !csr.verify(&csr_public_key)? {
+            return Err(CliError::ActionError(format!(
+                "Certificate signing request has an invalid signature: {}",
+                csr_path.display()
+            )));
+        }
+
+        let cert = sign_csr(&csr, &ca_cert, &ca_key, days)?;
+
+        let output_path = args
+            .value_of("output")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| csr_path.with_extension("crt"));
+        write_cert(&output_path, &cert)
+    }
+}
+
+pub struct CertRenewAction;
+
+impl Action for CertRenewAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let days = parse_days(args.value_of("days"))?;
+        let within = args
+            .value_of("within")
+            .map(|within| {
+                within.parse::<i32>().map_err(|_| {
+                    CliError::ActionError(format!("--within must be a number of days: {}", within))
+                })
+            })
+            .transpose()?;
+
+        let cert_dir = get_cert_dir(args)?;
+        let (ca_cert, ca_key) = load_ca(args)?;
+        let ca_public_key = ca_cert.public_key()?;
+
+        for name in cert_names(args) {
+            let cert_path = cert_dir.join(format!("{}.crt", name));
+            let cert = get_ca_cert(&cert_path).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Unable to read certificate {}: {}",
+                    cert_path.display(),
+                    err
+                ))
+            })?;
+
+            if !cert.verify(&ca_public_key)? {
+                return Err(CliError::ActionError(format!(
+                    "Certificate was not issued by the given CA: {}",
+                    cert_path.display()
+                )));
+            }
+
+            if let Some(within) = within {
+                let remaining = days_remaining(&cert)?;
+                if remaining > within {
+                    info!(
+                        "Skipping {}, which expires in {} days",
+                        cert_path.display(),
+                        remaining
+                    );
+                    continue;
+                }
+            }
+
+            // The renewed certificate keeps the subject, key, and subject alternative names of
+            // the existing certificate, so the private key file does not change
+            let renewed = make_signed_cert(
+                cert.subject_name(),
+                &cert.public_key()?,
+                &ca_cert,
+                &ca_key,
+                days,
+                &subject_alt_names(&cert),
+            )?;
+
+            write_cert(&cert_path, &renewed)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CertVerifyAction;
+
+impl Action for CertVerifyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let warn_days = args
+            .value_of("warn_days")
+            .map(|days| {
+                days.parse::<i32>().map_err(|_| {
+                    CliError::ActionError(format!("--warn-days must be a number of days: {}", days))
+                })
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_WARN_DAYS);
+
+        let cert_dir = get_cert_dir(args)?;
+        let ca_path = args
+            .value_of("ca_file")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| default_ca_file(&cert_dir));
+        let ca_cert = get_ca_cert(&ca_path).map_err(|err| {
+            CliError::ActionError(format!(
+                "Unable to read CA certificate {}: {}",
+                ca_path.display(),
+                err
+            ))
+        })?;
+
+        let names = cert_names(args);
+        let mut failures = 0;
+        let mut table = vec![vec![
+            "NAME".to_string(),
+            "STATUS".to_string(),
+            "EXPIRES".to_string(),
+            "DETAILS".to_string(),
+        ]];
+
+        for name in &names {
+            let cert_path = cert_dir.join(format!("{}.crt", name));
+            let key_path = cert_dir.join("private").join(format!("{}.key", name));
+
+            let (status, expires, details) =
+                match verify_cert_and_key(&cert_path, &key_path, &ca_cert, warn_days) {
+                    Ok(CertStatus::Valid { expires }) => ("OK", expires, String::new()),
+                    Ok(CertStatus::Expiring { expires, days }) => {
+                        ("WARN", expires, format!("expires in {} days", days))
+                    }
+                    Ok(CertStatus::Invalid { expires, reason }) => {
+                        failures += 1;
+                        ("FAIL", expires, reason)
+                    }
+                    Err(err) => {
+                        failures += 1;
+                        ("FAIL", "-".to_string(), err.to_string())
+                    }
+                };
+
+            table.push(vec![name.to_string(), status.into(), expires, details]);
+        }
+
+        print_table(table);
+
+        if failures > 0 {
+            return Err(CliError::ActionError(format!(
+                "{} certificate(s) failed verification",
+                failures
+            )));
+        }
+
+        if let Some(config_path) = args.value_of("write_config") {
+            write_tls_config(Path::new(config_path), &cert_dir, &ca_path, &names)?;
+        }
+
+        Ok(())
+    }
+}
+
+enum CertStatus {
+    Valid { expires: String },
+    Expiring { expires: String, days: i32 },
+    Invalid { expires: String, reason: String },
+}
+
+/// Checks that the certificate matches its private key, chains to the CA certificate, and is
+/// not expired or about to expire.
+fn verify_cert_and_key(
+    cert_path: &Path,
+    key_path: &Path,
+    ca_cert: &X509Ref,
+    warn_days: i32,
+) -> Result<CertStatus, CliError> {
+    let cert = get_ca_cert(cert_path)
+        .map_err(|err| CliError::ActionError(format!("unable to read certificate: {}", err)))?;
+    let key = get_ca_key(key_path)
+        .map_err(|err| CliError::ActionError(format!("unable to read key: {}", err)))?;
+
+    let expires = cert.not_after().to_string();
+
+    if !cert.public_key()?.public_eq(&key) {
+        return Ok(CertStatus::Invalid {
+            expires,
+            reason: "certificate does not match private key".into(),
+        });
+    }
+
+    let mut store_builder = X509StoreBuilder::new()?;
+    store_builder.add_cert(ca_cert.to_owned())?;
+    let store = store_builder.build();
+    let chain = Stack::new()?;
+    let chain_error = X509StoreContext::new()?.init(&store, &cert, &chain, |context| {
+        if context.verify_cert()? {
+            Ok(None)
+        } else {
+            Ok(Some(context.error().to_string()))
+        }
+    })?;
+    if let Some(reason) = chain_error {
+        return Ok(CertStatus::Invalid { expires, reason });
+    }
+
+    let days = days_remaining(&cert)?;
+    if days < warn_days {
+        Ok(CertStatus::Expiring { expires, days })
+    } else {
+        Ok(CertStatus::Valid { expires })
+    }
+}
+
+/// Writes the splinterd TLS settings for the verified certificates as a TOML fragment.
+fn write_tls_config(
+    config_path: &Path,
+    cert_dir: &Path,
+    ca_path: &Path,
+    names: &[String],
+) -> Result<(), CliError> {
+    let cert_dir = fs::canonicalize(cert_dir)?;
+    let ca_path = fs::canonicalize(ca_path)?;
+
+    let mut config = String::from("# TLS settings verified by `splinter cert verify`\n");
+    config.push_str(&format!(
+        "tls_cert_dir = {:?}\n",
+        cert_dir.display().to_string()
+    ));
+    config.push_str(&format!(
+        "tls_ca_file = {:?}\n",
+        relative_to(&ca_path, &cert_dir)
+    ));
+
+    for name in names {
+        let setting = match name.as_str() {
+            "client" => "client",
+            "server" => "server",
+            "rest_api" => "rest_api",
+            _ => continue,
+        };
+        config.push_str(&format!("tls_{}_cert = \"{}.crt\"\n", setting, name));
+        config.push_str(&format!("tls_{}_key = \"private/{}.key\"\n", setting, name));
+    }
+
+    info!("Writing file: {}", config_path.display());
+    fs::write(config_path, config)?;
+
+    Ok(())
+}
+
+/// Returns the path relative to the directory if it is inside the directory; otherwise, returns
+/// the full path.
+fn relative_to(path: &Path, dir: &Path) -> String {
+    path.strip_prefix(dir).unwrap_or(path).display().to_string()
+}
+
+/// Loads the CA certificate and key from the `ca_cert` and `ca_key` arguments, defaulting to the
+/// CA files created by `splinter cert generate` in the cert directory.
+fn load_ca(args: &ArgMatches) -> Result<(X509, PKey<Private>), CliError> {
+    let (ca_cert_path, ca_key_path) = match (args.value_of("ca_cert"), args.value_of("ca_key")) {
+        (Some(cert), Some(key)) => (PathBuf::from(cert), PathBuf::from(key)),
+        (cert, key) => {
+            let cert_dir = get_cert_dir(args)?;
+            (
+                cert.map(PathBuf::from)
+                    .unwrap_or_else(|| cert_dir.join(CA_CERT)),
+                key.map(PathBuf::from)
+                    .unwrap_or_else(|| cert_dir.join("private").join(CA_KEY)),
+            )
+        }
+    };
+
+    let ca_cert = get_ca_cert(&ca_cert_path).map_err(|err| {
+        CliError::ActionError(format!(
+            "Unable to read CA certificate {}: {}",
+            ca_cert_path.display(),
+            err
+        ))
+    })?;
+    let ca_key = get_ca_key(&ca_key_path).map_err(|err| {
+        CliError::ActionError(format!(
+            "Unable to read CA key {}: {}",
+            ca_key_path.display(),
+            err
+        ))
+    })?;
+
+    if !ca_cert.public_key()?.public_eq(&ca_key) {
+        return Err(CliError::ActionError(
+            "CA certificate does not match CA key".into(),
+        ));
+    }
+
+    Ok((ca_cert, ca_key))
+}
+
+/// Returns the CA file splinterd uses if it exists in the cert directory; otherwise, returns the
+/// CA certificate created by `splinter cert generate`.
+fn default_ca_file(cert_dir: &Path) -> PathBuf {
+    let splinterd_ca = cert_dir.join(SPLINTERD_CA_FILE);
+    if splinterd_ca.exists() {
+        splinterd_ca
+    } else {
+        cert_dir.join(CA_CERT)
+    }
+}
+
+/// Returns the certificate names given as arguments, or the certificates used by splinterd.
+fn cert_names(args: &ArgMatches) -> Vec<String> {
+    if let Some(names) = args.values_of("names") {
+        return names.map(String::from).collect();
+    }
+
+    #[allow(unused_mut)]
+    let mut names = vec![CLIENT_CERT, SERVER_CERT];
+    #[cfg(feature = "https-certs")]
+    names.push(REST_API_CERT);
+
+    names
+        .into_iter()
+        .map(|cert| cert.trim_end_matches(".crt").to_string())
+        .collect()
+}
+
+fn parse_days(days: Option<&str>) -> Result<u32, CliError> {
+    days.map(|days| {
+        days.parse::<u32>().map_err(|_| {
+            CliError::ActionError(format!("--days must be a number of days: {}", days))
+        })
+    })
+    .transpose()
+    .map(|days| days.unwrap_or(DEFAULT_VALIDITY_DAYS))
+}
+
+/// Returns the number of days until the certificate expires; negative if it has expired.
+fn days_remaining(cert: &X509Ref) -> Result<i32, CliError> {
+    Ok(Asn1Time::days_from_now(0)?.diff(cert.not_after())?.days)
+}
+
+/// Returns the DNS names and IP addresses in the certificate's subject alternative names.
+fn subject_alt_names(cert: &X509Ref) -> Vec<String> {
+    cert.subject_alt_names()
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|name| {
+                    if let Some(dns) = name.dnsname() {
+                        Some(dns.to_string())
+                    } else {
+                        name.ipaddress().and_then(ip_address_to_string)
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn ip_address_to_string(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => {
+            let mut octets = [0; 4];
+            octets.copy_from_slice(bytes);
+            Some(Ipv4Addr::from(octets).to_string())
+        }
+        16 => {
+            let mut octets = [0; 16];
+            octets.copy_from_slice(bytes);
+            Some(Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+fn write_cert(path: &Path, cert: &X509Ref) -> Result<(), CliError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| CliError::ActionError(format!("Invalid file name: {}", path.display())))?;
+
+    info!("Writing file: {}", path.display());
+    write_file(&dir, file_name, &cert.to_pem()?)
+}
+
+// Make a certificate signing request for the given key
+fn make_csr(
+    key: &PKeyRef<Private>,
+    common_name: &str,
+    subject_alt_names: &[String],
+) -> Result<X509Req, CliError> {
+    let mut x509_name = X509NameBuilder::new()?;
+    x509_name.append_entry_by_text("CN", common_name)?;
+    let x509_name = x509_name.build();
+
+    let mut req_builder = X509ReqBuilder::new()?;
+    req_builder.set_version(0)?;
+    req_builder.set_subject_name(&x509_name)?;
+    req_builder.set_pubkey(key)?;
+
+    if !subject_alt_names.is_empty() {
+        let extension =
+            build_subject_alt_names(subject_alt_names).build(&req_builder.x509v3_context(None))?;
+        let mut extensions = Stack::new()?;
+        extensions.push(extension)?;
+        req_builder.add_extensions(&extensions)?;
+    }
+
+    req_builder.sign(key, MessageDigest::sha256())?;
+
+    Ok(req_builder.build())
+}
+
+// Sign the CSR with the CA. Only the subject and the subject alternative names are taken from the
+// request; any other extensions it asks for, such as basic constraints or key usages, are ignored
+// so that a requester cannot obtain a CA certificate.
+fn sign_csr(
+    csr: &X509Req,
+    ca_cert: &X509Ref,
+    ca_key: &PKeyRef<Private>,
+    days: u32,
+) -> Result<X509, CliError> {
+    make_signed_cert(
+        csr.subject_name(),
+        &csr.public_key()?,
+        ca_cert,
+        ca_key,
+        days,
+        &csr_subject_alt_names(csr)?,
+    )
+}
+
+// Returns the DNS names and IP addresses requested in the CSR's subject alternative names
+fn csr_subject_alt_names(csr: &X509Req) -> Result<Vec<String>, CliError> {
+    let extensions = match csr.extensions() {
+        Ok(extensions) => extensions,
+        // The request has no extensions
+        Err(_) => return Ok(vec![]),
+    };
+
+    // The requested extensions are only read through a scratch certificate, which is never
+    // signed or written
+    let mut scratch = X509::builder()?;
+    for extension in extensions {
+        scratch.append_extension(extension)?;
+    }
+    Ok(subject_alt_names(&scratch.build()))
+}
+
+// Make a certificate for the given subject and public key, signed by the CA. Like the certs
+// created by `splinter cert generate`, the certificate can act as both server and client; it can
+// never act as a CA.
+fn make_signed_cert(
+    subject: &X509NameRef,
+    public_key: &PKeyRef<Public>,
+    ca_cert: &X509Ref,
+    ca_key: &PKeyRef<Private>,
+    days: u32,
+    subject_alt_names: &[String],
+) -> Result<X509, CliError> {
+    let mut cert_builder = X509::builder()?;
+    cert_builder.set_version(2)?;
+    let serial_number = {
+        let mut serial = BigNum::new()?;
+        serial.rand(159, MsbOption::MAYBE_ZERO, false)?;
+        serial.to_asn1_integer()?
+    };
+    cert_builder.set_serial_number(&serial_number)?;
+    cert_builder.set_subject_name(subject)?;
+    cert_builder.set_issuer_name(ca_cert.subject_name())?;
+    cert_builder.set_pubkey(public_key)?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    cert_builder.set_not_before(&not_before)?;
+    let not_after = Asn1Time::days_from_now(days)?;
+    cert_builder.set_not_after(&not_after)?;
+
+    cert_builder.append_extension(BasicConstraints::new().critical().build()?)?;
+    cert_builder.append_extension(
+        KeyUsage::new()
+            .critical()
+            .digital_signature()
+            .key_encipherment()
+            .build()?,
+    )?;
+    cert_builder.append_extension(
+        ExtendedKeyUsage::new()
+            .server_auth()
+            .client_auth()
+            .build()?,
+    )?;
+
+    if !subject_alt_names.is_empty() {
+        let extension = build_subject_alt_names(subject_alt_names)
+            .build(&cert_builder.x509v3_context(Some(ca_cert), None))?;
+        cert_builder.append_extension(extension)?;
+    }
+
+    cert_builder.sign(ca_key, MessageDigest::sha256())?;
+
+    Ok(cert_builder.build())
+}
+
+fn build_subject_alt_names(names: &[String]) -> SubjectAlternativeName {
+    let mut san = SubjectAlternativeName::new();
+    for name in names {
+        if name.parse::<IpAddr>().is_ok() {
+            san.ip(name);
+        } else {
+            san.dns(name);
+        }
+    }
+    san
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::make_ca_cert;
+
+    /// Verify that a certificate signed from a CSR keeps the CSR's subject and subject
+    /// alternative names, matches the requesting key, and chains to the CA.
+    #[test]
+    fn sign_csr() {
+        let (ca_key, ca_cert) = make_ca_cert().expect("Failed to create CA");
+        let key = PKey::from_rsa(Rsa::generate(2048).expect("Failed to generate key"))
+            .expect("Failed to create key");
+
+        let csr = make_csr(
+            &key,
+            "node.example.com",
+            &["node.example.com".to_string(), "10.0.0.1".to_string()],
+        )
+        .expect("Failed to create CSR");
+        let csr_public_key = csr.public_key().expect("Failed to get CSR key");
+        assert!(csr.verify(&csr_public_key).expect("Failed to verify CSR"));
+
+        let cert = sign_csr(&csr, &ca_cert, &ca_key, 30).expect("Failed to sign certificate");
+
+        assert!(cert
+            .verify(&ca_cert.public_key().expect("Failed to get CA key"))
+            .expect("Failed to verify certificate"));
+        assert!(cert
+            .public_key()
+            .expect("Failed to get certificate key")
+            .public_eq(&key));
+        assert_eq!(
+            subject_alt_names(&cert),
+            vec!["node.example.com".to_string(), "10.0.0.1".to_string()]
+        );
+
+        let remaining = days_remaining(&cert).expect("Failed to get days remaining");
+        assert!((29..=30).contains(&remaining));
+    }
+
+    /// Verify that a CSR asking to be a CA is signed as an ordinary certificate: the requested
+    /// subject alternative names are kept, but a certificate signed by the issued certificate
+    /// does not chain to the CA.
+    #[test]
+    fn sign_csr_requesting_ca() {
+        let (ca_key, ca_cert) = make_ca_cert().expect("Failed to create CA");
+        let key = PKey::from_rsa(Rsa::generate(2048).expect("Failed to generate key"))
+            .expect("Failed to create key");
+
+        let mut x509_name = X509NameBuilder::new().expect("Failed to create name");
+        x509_name
+            .append_entry_by_text("CN", "sub-ca")
+            .expect("Failed to add CN");
+        let x509_name = x509_name.build();
+        let mut req_builder = X509ReqBuilder::new().expect("Failed to create CSR builder");
+        req_builder
+            .set_subject_name(&x509_name)
+            .expect("Failed to set subject");
+        req_builder.set_pubkey(&key).expect("Failed to set key");
+        let mut extensions = Stack::new().expect("Failed to create stack");
+        extensions
+            .push(
+                BasicConstraints::new()
+                    .critical()
+                    .ca()
+                    .build()
+                    .expect("Failed to build basic constraints"),
+            )
+            .expect("Failed to add extension");
+        extensions
+            .push(
+                KeyUsage::new()
+                    .key_cert_sign()
+                    .build()
+                    .expect("Failed to build key usage"),
+            )
+            .expect("Failed to add extension");
+        extensions
+            .push(
+                build_subject_alt_names(&["sub-ca.example.com".to_string()])
+                    .build(&req_builder.x509v3_context(None))
+                    .expect("Failed to build subject alternative names"),
+            )
+            .expect("Failed to add extension");
+        req_builder
+            .add_extensions(&extensions)
+            .expect("Failed to add extensions");
+        req_builder
+            .sign(&key, MessageDigest::sha256())
+            .expect("Failed to sign CSR");
+        let csr = req_builder.build();
+
+        let cert = sign_csr(&csr, &ca_cert, &ca_key, 30).expect("Failed to sign certificate");
+        assert_eq!(
+            subject_alt_names(&cert),
+            vec!["sub-ca.example.com".to_string()]
+        );
+
+        let leaf_key = PKey::from_rsa(Rsa::generate(2048).expect("Failed to generate key"))
+            .expect("Failed to create key");
+        let leaf_public_key = PKey::public_key_from_pem(
+            &leaf_key
+                .public_key_to_pem()
+                .expect("Failed to encode leaf key"),
+        )
+        .expect("Failed to read leaf key");
+        let leaf = make_signed_cert(
+            &x509_name,
+            &leaf_public_key,
+            &cert,
+            &key,
+            30,
+            &["leaf.example.com".to_string()],
+        )
+        .expect("Failed to sign leaf certificate");
+
+        let mut store_builder = X509StoreBuilder::new().expect("Failed to create store");
+        store_builder
+            .add_cert(ca_cert)
+            .expect("Failed to add CA to store");
+        let store = store_builder.build();
+        let mut chain = Stack::new().expect("Failed to create stack");
+        chain
+            .push(cert)
+            .expect("Failed to add certificate to chain");
+        let verified = X509StoreContext::new()
+            .expect("Failed to create store context")
+            .init(&store, &leaf, &chain, |context| context.verify_cert())
+            .expect("Failed to verify leaf certificate");
+        assert!(!verified);
+    }
+}
@@ -38,6 +38,10 @@ use crate::error::CliError;
 
 use super::{chown, Action};
 
+pub use ca::{CertRenewAction, CertRequestAction, CertSignAction, CertVerifyAction};
+
+mod ca;
+
 pub struct CertGenAction;
 
 const DEFAULT_CERT_DIR: &str = "/etc/splinter/certs/";
@@ -68,39 +72,8 @@ impl Action for CertGenAction {
         #[cfg(feature = "https-certs")]
         let rest_api_common_name = args.value_of("rest_api_common_name").unwrap_or("localhost");
 
-        let cert_dir_string = args
-            .value_of("cert_dir")
-            .map(ToOwned::to_owned)
-            .or_else(|| env::var(CERT_DIR_ENV).ok())
-            .or_else(|| {
-                if let Ok(splinter_home) = env::var(SPLINTER_HOME_ENV) {
-                    let cert_path = Path::new(&splinter_home).join("certs");
-                    if !cert_path.is_dir() {
-                        fs::create_dir_all(&cert_path)
-                            .map_err(|err| {
-                                CliError::ActionError(format!(
-                                    "Unable to create cert directory: {}",
-                                    err
-                                ))
-                            })
-                            .ok()?
-                    }
-                    cert_path.to_str().map(ToOwned::to_owned)
-                } else {
-                    Some(DEFAULT_CERT_DIR.to_string())
-                }
-            })
-            .unwrap();
-
-        let cert_dir = Path::new(&cert_dir_string);
-
-        // Check if the provided cert directory exists
-        if !cert_dir.is_dir() {
-            return Err(CliError::ActionError(format!(
-                "Cert directory does not exist: {}",
-                cert_dir.display()
-            )));
-        }
+        let cert_dir_buf = get_cert_dir(args)?;
+        let cert_dir = cert_dir_buf.as_path();
 
         let private_cert_path = cert_dir.join("private/");
         let cert_path = cert_dir.to_path_buf();
@@ -270,6 +243,48 @@ impl Action for CertGenAction {
     }
 }
 
+/// Determines the cert directory from the `cert_dir` argument, the `SPLINTER_CERT_DIR` or
+/// `SPLINTER_HOME` environment variables, or the default location, in that order.
+///
+/// Returns an error if the directory does not exist.
+fn get_cert_dir(args: &ArgMatches) -> Result<PathBuf, CliError> {
+    let cert_dir_string = args
+        .value_of("cert_dir")
+        .map(ToOwned::to_owned)
+        .or_else(|| env::var(CERT_DIR_ENV).ok())
+        .or_else(|| {
+            if let Ok(splinter_home) = env::var(SPLINTER_HOME_ENV) {
+                let cert_path = Path::new(&splinter_home).join("certs");
+                if !cert_path.is_dir() {
+                    fs::create_dir_all(&cert_path)
+                        .map_err(|err| {
+                            CliError::ActionError(format!(
+                                "Unable to create cert directory: {}",
+                                err
+                            ))
+                        })
+                        .ok()?
+                }
+                cert_path.to_str().map(ToOwned::to_owned)
+            } else {
+                Some(DEFAULT_CERT_DIR.to_string())
+            }
+        })
+        .unwrap();
+
+    let cert_dir = PathBuf::from(cert_dir_string);
+
+    // Check if the provided cert directory exists
+    if !cert_dir.is_dir() {
+        return Err(CliError::ActionError(format!(
+            "Cert directory does not exist: {}",
+            cert_dir.display()
+        )));
+    }
+
+    Ok(cert_dir)
+}
+
 // if skip, check each pair of certificate/key to see if it exists. If not generate the
 // the missing files. If only one of the two files exists, this is an error.
 fn handle_skip(
@@ -14,6 +14,8 @@
 
 use std::collections::BTreeMap;
 use std::fmt;
+#[cfg(feature = "circuit-ping")]
+use std::time::Duration;
 
 use reqwest::{blocking::Client, header, StatusCode};
 use serde::{Deserialize, Serialize};
@@ -25,6 +27,8 @@ use crate::error::CliError;
 const PAGING_LIMIT: &str = "1000";
 // The admin protocol version supported by the current CLI
 const CLI_ADMIN_PROTOCOL_VERSION: &str = "2";
+#[cfg(feature = "circuit-ping")]
+const PING_REQUEST_TIMEOUT_MARGIN_SECS: u64 = 10;
 
 impl SplinterRestClient {
     /// Submits an admin payload to this client's Splinter node.
@@ -271,6 +275,63 @@ impl SplinterRestClient {
                 }
             })
     }
+
+    #[cfg(feature = "circuit-ping")]
+    pub fn ping_service(
+        &self,
+        circuit_id: &str,
+        service_id: &str,
+        timeout: u64,
+    ) -> Result<PingResultSlice, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .post(&format!("{}/admin/circuits/{}/ping", self.url, circuit_id))
+            // allow the node the full ping timeout before giving up on the request
+            .timeout(Duration::from_secs(
+                timeout + PING_REQUEST_TIMEOUT_MARGIN_SECS,
+            ))
+            .json(&PingRequest {
+                service_id: service_id.to_string(),
+                timeout,
+            });
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to ping service: {}", err)))
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    res.json::<PingResultSlice>().map_err(|_| {
+                        CliError::ActionError(
+                            "Request was successful, but received an invalid response".into(),
+                        )
+                    })
+                } else {
+                    let message = res
+                        .json::<ServerError>()
+                        .map_err(|_| {
+                            CliError::ActionError(format!(
+                                "Ping request failed with status code '{}', but error response \
+                                 was not valid",
+                                status
+                            ))
+                        })?
+                        .message;
+
+                    Err(CliError::ActionError(format!(
+                        "Failed to ping service: {}",
+                        message
+                    )))
+                }
+            })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -280,6 +341,21 @@ pub struct CircuitSlice {
     pub roster: Vec<CircuitServiceSlice>,
     pub management_type: String,
     pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proposer_public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proposer_node_id: Option<String>,
+    /// The registry entries of the circuit's members, keyed by node ID; `None` if a member is not
+    /// in the registry. Only set by the CLI.
+    #[cfg(feature = "circuit-show-registry")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registry_members: BTreeMap<String, Option<RegistryMemberSlice>>,
 }
 
 impl fmt::Display for CircuitSlice {
@@ -292,10 +368,37 @@ impl fmt::Display for CircuitSlice {
             display_string += "Display Name: -\n    ";
         }
 
+        if let Some(circuit_status) = &self.circuit_status {
+            display_string += &format!("Status: {}\n    ", circuit_status);
+        }
+
+        if let Some(created_at) = self.created_at {
+            display_string += &format!("Created: {}\n    ", created_at);
+        }
+
+        if let Some(updated_at) = self.updated_at {
+            display_string += &format!("Last Modified: {}\n    ", updated_at);
+        }
+
+        match (&self.proposer_public_key, &self.proposer_node_id) {
+            (Some(public_key), Some(node_id)) => {
+                display_string += &format!("Proposed By: {} ({})\n    ", public_key, node_id)
+            }
+            (Some(public_key), None) => {
+                display_string += &format!("Proposed By: {}\n    ", public_key)
+            }
+            (None, Some(node_id)) => display_string += &format!("Proposed By: {}\n    ", node_id),
+            (None, None) => (),
+        }
+
         display_string += &format!("Management Type: {}\n", self.management_type);
 
         for member in self.members.iter() {
             display_string += &format!("\n    {}\n", member);
+            #[cfg(feature = "circuit-show-registry")]
+            {
+                display_string += &registry_member_string(&self.registry_members, member);
+            }
             for service in self.roster.iter() {
                 if member == &service.node_id {
                     display_string += &format!(
@@ -328,6 +431,36 @@ impl fmt::Display for CircuitSlice {
     }
 }
 
+/// The registry entry of a circuit member, with its keys reduced to fingerprints
+#[cfg(feature = "circuit-show-registry")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegistryMemberSlice {
+    pub display_name: String,
+    pub endpoints: Vec<String>,
+    pub key_fingerprints: Vec<String>,
+}
+
+/// Formats the registry entry of the given member for display beneath the member's node ID. If
+/// the member was looked up but is not in the registry, this is noted instead.
+#[cfg(feature = "circuit-show-registry")]
+fn registry_member_string(
+    registry_members: &BTreeMap<String, Option<RegistryMemberSlice>>,
+    node_id: &str,
+) -> String {
+    match registry_members.get(node_id) {
+        Some(Some(member)) => {
+            let mut member_string = format!("        Display Name: {}\n", member.display_name);
+            member_string += &format!("        Endpoints: {}\n", member.endpoints.join(", "));
+            for fingerprint in &member.key_fingerprints {
+                member_string += &format!("        Key: {}\n", fingerprint);
+            }
+            member_string
+        }
+        Some(None) => "        Registry: not found\n".to_string(),
+        None => String::new(),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CircuitServiceSlice {
     pub service_id: String,
@@ -351,6 +484,15 @@ pub struct ProposalSlice {
     pub votes: Vec<VoteRecord>,
     pub requester: String,
     pub requester_node_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    /// The registry entries of the proposed circuit's members, keyed by node ID; `None` if a
+    /// member is not in the registry. Only set by the CLI.
+    #[cfg(feature = "circuit-show-registry")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registry_members: BTreeMap<String, Option<RegistryMemberSlice>>,
 }
 
 impl fmt::Display for ProposalSlice {
@@ -363,10 +505,22 @@ impl fmt::Display for ProposalSlice {
             display_string += "Display Name: -\n    ";
         }
 
+        if let Some(created_at) = self.created_at {
+            display_string += &format!("Submitted: {}\n    ", created_at);
+        }
+
+        if let Some(updated_at) = self.updated_at {
+            display_string += &format!("Last Modified: {}\n    ", updated_at);
+        }
+
         display_string += &format!("Management Type: {}\n", self.circuit.management_type);
 
         for member in self.circuit.members.iter() {
             display_string += &format!("\n    {} ({:?})\n", member.node_id, member.endpoints);
+            #[cfg(feature = "circuit-show-registry")]
+            {
+                display_string += &registry_member_string(&self.registry_members, &member.node_id);
+            }
             if member.node_id == self.requester_node_id {
                 display_string += &"        Vote: ACCEPT (implied as requester):\n".to_string();
                 display_string += &format!("            {}\n", self.requester);
@@ -461,3 +615,94 @@ pub struct Paging {
     pub next: String,
     pub last: String,
 }
+
+#[cfg(feature = "circuit-ping")]
+#[derive(Debug, Serialize)]
+struct PingRequest {
+    service_id: String,
+    timeout: u64,
+}
+
+#[cfg(feature = "circuit-ping")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PingResultSlice {
+    pub circuit_id: String,
+    pub service_id: String,
+    pub status: String,
+    pub message: Option<String>,
+    pub path: Vec<String>,
+    pub peer_id: Option<String>,
+    pub round_trip_ms: Option<f64>,
+}
+
+#[cfg(feature = "circuit-ping")]
+impl fmt::Display for PingResultSlice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut display_string = format!(
+            "Ping {} on circuit {}: {}\n",
+            self.service_id, self.circuit_id, self.status
+        );
+
+        if let Some(message) = &self.message {
+            display_string += &format!("    Message: {}\n", message);
+        }
+
+        if let Some(round_trip_ms) = self.round_trip_ms {
+            display_string += &format!("    Round trip: {:.3} ms\n", round_trip_ms);
+        }
+
+        if !self.path.is_empty() {
+            let mut path = self.path.join(" -> ");
+            if let Some(peer_id) = &self.peer_id {
+                path += &format!(" -> {} (service connection)", peer_id);
+            }
+            display_string += &format!("    Path: {}\n", path);
+        }
+
+        write!(f, "{}", display_string)
+    }
+}
+
+#[cfg(all(test, feature = "circuit-show-registry"))]
+mod tests {
+    use super::*;
+
+    /// Verify that a member's registry entry is displayed beneath its node ID, that a member
+    /// missing from the registry is noted, and that nothing is added for members that were not
+    /// looked up.
+    #[test]
+    fn circuit_display_registry_members() {
+        let mut registry_members = BTreeMap::new();
+        registry_members.insert(
+            "node-000".to_string(),
+            Some(RegistryMemberSlice {
+                display_name: "Node 0".into(),
+                endpoints: vec!["tcps://node-000:8044".into()],
+                key_fingerprints: vec!["01:02".into()],
+            }),
+        );
+        registry_members.insert("node-001".to_string(), None);
+
+        let circuit = CircuitSlice {
+            id: "abcde-01234".into(),
+            members: vec!["node-000".into(), "node-001".into(), "node-002".into()],
+            roster: vec![],
+            management_type: "test".into(),
+            display_name: None,
+            circuit_status: None,
+            created_at: None,
+            updated_at: None,
+            proposer_public_key: None,
+            proposer_node_id: None,
+            registry_members,
+        };
+
+        let display = circuit.to_string();
+        assert!(display.contains(
+            "    node-000\n        Display Name: Node 0\n        \
+             Endpoints: tcps://node-000:8044\n        Key: 01:02\n"
+        ));
+        assert!(display.contains("    node-001\n        Registry: not found\n"));
+        assert!(display.ends_with("    node-002\n"));
+    }
+}
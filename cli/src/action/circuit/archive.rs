@@ -0,0 +1,246 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signed, portable archives of circuit definitions.
+//!
+//! An archive is a JSON document containing the circuit definition and the secp256k1 public key
+//! and signature of the exporter. The signature covers the JSON serialization of the `circuit`
+//! field, so an archive must be verified before its definition is used.
+
+use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CliError;
+
+use super::api::{CircuitServiceSlice, CircuitSlice};
+
+/// The version of the archive format written by this CLI
+pub const CIRCUIT_ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CircuitArchive {
+    pub version: u32,
+    pub circuit: ArchivedCircuit,
+    /// The hex-encoded public key of the exporter
+    pub signer: String,
+    /// The hex-encoded signature of the serialized circuit
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchivedCircuit {
+    pub circuit_id: String,
+    pub display_name: Option<String>,
+    pub management_type: String,
+    pub members: Vec<ArchivedMember>,
+    pub roster: Vec<CircuitServiceSlice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchivedMember {
+    pub node_id: String,
+    pub endpoints: Vec<String>,
+}
+
+impl ArchivedCircuit {
+    /// Creates the archived form of the circuit. The endpoints of each member are looked up
+    /// with `member_endpoints`.
+    pub fn from_circuit<F>(circuit: CircuitSlice, member_endpoints: F) -> Result<Self, CliError>
+    where
+        F: Fn(&str) -> Result<Vec<String>, CliError>,
+    {
+        let members = circuit
+            .members
+            .iter()
+            .map(|node_id| {
+                Ok(ArchivedMember {
+                    node_id: node_id.clone(),
+                    endpoints: member_endpoints(node_id)?,
+                })
+            })
+            .collect::<Result<_, CliError>>()?;
+
+        Ok(Self {
+            circuit_id: circuit.id,
+            display_name: circuit.display_name,
+            management_type: circuit.management_type,
+            members,
+            roster: circuit.roster,
+        })
+    }
+
+    fn signed_bytes(&self) -> Result<Vec<u8>, CliError> {
+        serde_json::to_vec(self).map_err(|err| {
+            CliError::ActionError(format!("Failed to serialize circuit archive: {}", err))
+        })
+    }
+}
+
+impl CircuitArchive {
+    /// Signs the circuit with the given hex-encoded secp256k1 private key.
+    pub fn sign(circuit: ArchivedCircuit, private_key: &str) -> Result<Self, CliError> {
+        let private_key = PrivateKey::new_from_hex(private_key).map_err(|err| {
+            CliError::ActionError(format!("Invalid secp256k1 private key provided: {}", err))
+        })?;
+        let signer = Secp256k1Context::new().new_signer(private_key);
+
+        let public_key = signer.public_key().map_err(|err| {
+            CliError::ActionError(format!(
+                "Failed to get public key from secp256k1 private key: {}",
+                err
+            ))
+        })?;
+        let signature = signer.sign(&circuit.signed_bytes()?).map_err(|err| {
+            CliError::ActionError(format!("Failed to sign circuit archive: {}", err))
+        })?;
+
+        Ok(Self {
+            version: CIRCUIT_ARCHIVE_VERSION,
+            circuit,
+            signer: to_hex(&public_key.into_bytes()),
+            signature: to_hex(&signature.take_bytes()),
+        })
+    }
+
+    /// Verifies the archive's signature, and that it was signed by `expected_signer` if one is
+    /// given. Returns the archived circuit if the archive is valid.
+    pub fn verify(self, expected_signer: Option<&str>) -> Result<ArchivedCircuit, CliError> {
+        if self.version != CIRCUIT_ARCHIVE_VERSION {
+            return Err(CliError::ActionError(format!(
+                "Unsupported circuit archive version: {}",
+                self.version
+            )));
+        }
+
+        if let Some(expected_signer) = expected_signer {
+            if !self.signer.eq_ignore_ascii_case(expected_signer.trim()) {
+                return Err(CliError::ActionError(format!(
+                    "Circuit archive was signed by {}, not by the expected signer",
+                    self.signer
+                )));
+            }
+        }
+
+        let public_key = PublicKey::new(parse_hex(&self.signer)?);
+        let signature = Signature::new(parse_hex(&self.signature)?);
+        let valid = Secp256k1Context::new()
+            .new_verifier()
+            .verify(&self.circuit.signed_bytes()?, &signature, &public_key)
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to verify circuit archive: {}", err))
+            })?;
+
+        if valid {
+            Ok(self.circuit)
+        } else {
+            Err(CliError::ActionError(
+                "Circuit archive signature is not valid".into(),
+            ))
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, CliError> {
+    if hex.len() % 2 != 0 {
+        return Err(CliError::ActionError(format!(
+            "Circuit archive contains an invalid hex string: {}",
+            hex
+        )));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| {
+                    CliError::ActionError(format!(
+                        "Circuit archive contains an invalid hex string: {}",
+                        hex
+                    ))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    const PRIVATE_KEY: &str = "2f1e7b7a130d7ba9da0068b3bb0ba1d79e7e77110302c9f746c3c2a63fe40088";
+    const OTHER_PRIVATE_KEY: &str =
+        "f8d4ed6e3ac4e5ac23a8efcb7b1b4d2eb2c4c3eb6d9d1c4e8f5f0b3e4b5a6c7d";
+
+    fn circuit() -> ArchivedCircuit {
+        let circuit = CircuitSlice {
+            id: "abcde-01234".into(),
+            members: vec!["node-000".into(), "node-001".into()],
+            roster: vec![CircuitServiceSlice {
+                service_id: "a000".into(),
+                service_type: "scabbard".into(),
+                node_id: "node-000".into(),
+                arguments: BTreeMap::new(),
+            }],
+            management_type: "test".into(),
+            display_name: None,
+            circuit_status: None,
+            created_at: None,
+            updated_at: None,
+            proposer_public_key: None,
+            proposer_node_id: None,
+            #[cfg(feature = "circuit-show-registry")]
+            registry_members: BTreeMap::new(),
+        };
+
+        ArchivedCircuit::from_circuit(circuit, |node_id| {
+            Ok(vec![format!("tcps://{}:8044", node_id)])
+        })
+        .expect("Failed to create archived circuit")
+    }
+
+    /// Verify that a signed archive verifies, including against the signer's public key, and
+    /// that archives that were modified or signed by another key do not.
+    #[test]
+    fn sign_and_verify() {
+        let archive = CircuitArchive::sign(circuit(), PRIVATE_KEY).expect("Failed to sign");
+        assert_eq!(
+            archive.circuit.members[1].endpoints,
+            vec!["tcps://node-001:8044".to_string()]
+        );
+
+        let signer = archive.signer.clone();
+        assert_eq!(
+            archive
+                .clone()
+                .verify(Some(&signer))
+                .expect("Failed to verify"),
+            circuit()
+        );
+
+        let other_signer = CircuitArchive::sign(circuit(), OTHER_PRIVATE_KEY)
+            .expect("Failed to sign")
+            .signer;
+        assert!(archive.clone().verify(Some(&other_signer)).is_err());
+
+        let mut modified = archive;
+        modified.circuit.members[1].endpoints = vec!["tcps://attacker:8044".into()];
+        assert!(modified.verify(None).is_err());
+    }
+}
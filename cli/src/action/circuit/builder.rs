@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "circuit-id-policy")]
+use splinter::admin::circuit_id::CircuitIdFormat;
 #[cfg(feature = "circuit-auth-type")]
 use splinter::admin::messages::AuthorizationType;
 use splinter::admin::messages::{
@@ -21,6 +23,8 @@ use splinter::admin::messages::{
 
 use crate::error::CliError;
 
+#[cfg(feature = "circuit-observers")]
+const OBSERVERS_ARG: &str = "observers";
 const PEER_SERVICES_ARG: &str = "peer_services";
 const MANAGEMENT_TYPE_ENV: &str = "SPLINTER_CIRCUIT_MANAGEMENT_TYPE";
 const SERVICE_TYPE_ENV: &str = "SPLINTER_CIRCUIT_SERVICE_TYPE";
@@ -206,6 +210,99 @@ impl CreateCircuitMessageBuilder {
         Ok(())
     }
 
+    #[cfg(feature = "circuit-relay")]
+    pub fn set_relay(&mut self, node_id: &str) -> Result<(), CliError> {
+        match self.nodes.iter_mut().find(|node| node.node_id == node_id) {
+            Some(node) => {
+                node.relay = true;
+                Ok(())
+            }
+            None => Err(CliError::ActionError(format!(
+                "Relay {} is not a member of the circuit",
+                node_id
+            ))),
+        }
+    }
+
+    #[cfg(feature = "circuit-observers")]
+    pub fn set_observer(&mut self, node_id: &str) -> Result<(), CliError> {
+        match self.nodes.iter_mut().find(|node| node.node_id == node_id) {
+            Some(node) => {
+                node.observer = true;
+                Ok(())
+            }
+            None => Err(CliError::ActionError(format!(
+                "Observer {} is not a member of the circuit",
+                node_id
+            ))),
+        }
+    }
+
+    /// Add the list of services that run on observer nodes to each service that has peer
+    /// services, unless the list was already set with a service argument
+    #[cfg(feature = "circuit-observers")]
+    fn apply_observer_services(&mut self) {
+        let observer_nodes = self
+            .nodes
+            .iter()
+            .filter(|node| node.observer)
+            .map(|node| node.node_id.clone())
+            .collect::<Vec<_>>();
+        if observer_nodes.is_empty() {
+            return;
+        }
+
+        let observer_services = self
+            .services
+            .iter()
+            .filter(|service_builder| {
+                service_builder
+                    .allowed_nodes()
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|node_id| observer_nodes.contains(node_id))
+            })
+            .filter_map(|service_builder| service_builder.service_id())
+            .collect::<Vec<_>>();
+        if observer_services.is_empty() {
+            return;
+        }
+
+        self.services = self
+            .services
+            .clone()
+            .into_iter()
+            .map(|service_builder| {
+                let mut service_args = service_builder.arguments().unwrap_or_default();
+                if !service_args.iter().any(|arg| arg.0 == PEER_SERVICES_ARG)
+                    || service_args.iter().any(|arg| arg.0 == OBSERVERS_ARG)
+                {
+                    return service_builder;
+                }
+
+                service_args.push((
+                    OBSERVERS_ARG.into(),
+                    format!("[\"{}\"]", observer_services.join("\", \"")),
+                ));
+                service_builder.with_arguments(&service_args)
+            })
+            .collect();
+    }
+
+    #[cfg(any(feature = "circuit-archive", feature = "circuit-id-policy"))]
+    pub fn set_circuit_id(&mut self, circuit_id: &str) {
+        self.create_circuit_builder = self.create_circuit_builder().with_circuit_id(circuit_id);
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn set_circuit_id_format(&mut self, format: &str) -> Result<(), CliError> {
+        let format = format
+            .parse::<CircuitIdFormat>()
+            .map_err(|err| CliError::ActionError(err.message()))?;
+        self.create_circuit_builder = self.create_circuit_builder().with_circuit_id_format(format);
+        Ok(())
+    }
+
     pub fn set_management_type(&mut self, management_type: &str) {
         self.management_type = Some(management_type.into());
     }
@@ -241,6 +338,9 @@ impl CreateCircuitMessageBuilder {
     pub fn build(mut self) -> Result<CreateCircuit, CliError> {
         let circuit_builder = self.create_circuit_builder();
 
+        #[cfg(feature = "circuit-observers")]
+        self.apply_observer_services();
+
         // if management type is not set, check for environment variable
         let management_type = self
             .management_type
@@ -375,6 +475,7 @@ fn msg_from_builder_error(err: BuilderError) -> String {
     match err {
         BuilderError::InvalidField(msg) => msg,
         BuilderError::MissingField(field) => format!("Missing node parameter: {}", field),
+        BuilderError::SigningFailed(msg) => msg,
     }
 }
 
@@ -406,4 +507,46 @@ mod tests {
         assert!(!is_match("0*0", "abcd"));
         assert!(!is_match("*0*", "abcd"));
     }
+
+    /// Verify that services with peer services are told which services run on observer nodes,
+    /// and that an observer must be a member of the circuit.
+    #[cfg(feature = "circuit-observers")]
+    #[test]
+    fn observer_services() {
+        let mut builder = CreateCircuitMessageBuilder::new();
+        builder
+            .add_node("node-a", &["tcp://a:8044".into()])
+            .expect("failed to add node-a");
+        builder
+            .add_node("node-b", &["tcp://b:8044".into()])
+            .expect("failed to add node-b");
+        builder
+            .add_service("sa00", &["node-a".into()])
+            .expect("failed to add sa00");
+        builder
+            .add_service("sb00", &["node-b".into()])
+            .expect("failed to add sb00");
+        builder.apply_service_type("*", "scabbard");
+        builder
+            .apply_peer_services(&["sa00", "sb00"])
+            .expect("failed to apply peer services");
+        builder.set_management_type("test");
+
+        assert!(builder.set_observer("node-c").is_err());
+        builder
+            .set_observer("node-b")
+            .expect("failed to set observer");
+
+        let create_circuit = builder.build().expect("failed to build circuit");
+
+        assert!(create_circuit
+            .members
+            .iter()
+            .any(|node| node.node_id == "node-b" && node.observer));
+        for service in create_circuit.roster {
+            assert!(service
+                .arguments
+                .contains(&(OBSERVERS_ARG.to_string(), "[\"sb00\"]".to_string())));
+        }
+    }
 }
@@ -13,34 +13,53 @@
 // limitations under the License.
 
 mod api;
+#[cfg(feature = "circuit-archive")]
+mod archive;
 mod builder;
 mod payload;
 #[cfg(feature = "circuit-template")]
 pub mod template;
 
+#[cfg(feature = "circuit-show-registry")]
+use std::collections::BTreeMap;
 #[cfg(feature = "circuit-template")]
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs::File;
+#[cfg(feature = "circuit-archive")]
+use std::io::Write;
 
 use clap::ArgMatches;
+use cylinder::Signer;
 use serde::Deserialize;
-use splinter::admin::messages::{CreateCircuit, SplinterService};
+#[cfg(feature = "circuit-id-policy")]
+use splinter::admin::circuit_id::CircuitIdFormat;
+use splinter::admin::messages::{CircuitProposalVoteBuilder, CreateCircuit, SplinterService, Vote};
 
 use crate::error::CliError;
 #[cfg(feature = "circuit-template")]
 use crate::template::CircuitTemplate;
 
+#[cfg(feature = "circuit-show-registry")]
+use super::api::SplinterRestClient;
 use super::api::SplinterRestClientBuilder;
+#[cfg(feature = "circuit-show-registry")]
+use super::key::fingerprint;
+#[cfg(feature = "circuit-archive")]
+use super::read_private_key;
 use super::{
-    msg_from_io_error, read_private_key, Action, DEFAULT_SPLINTER_REST_API_URL,
+    load_payload_signer, msg_from_io_error, print_table, Action, DEFAULT_SPLINTER_REST_API_URL,
     SPLINTER_REST_API_URL_ENV,
 };
 
 #[cfg(feature = "splinter-cli-jwt")]
-use super::create_cylinder_jwt_auth;
+use super::{create_cylinder_jwt_auth, create_payload_jwt_auth};
 
+#[cfg(feature = "circuit-show-registry")]
+use api::RegistryMemberSlice;
 use api::{CircuitServiceSlice, CircuitSlice};
+#[cfg(feature = "circuit-archive")]
+use archive::{ArchivedCircuit, CircuitArchive};
 pub(crate) use builder::CreateCircuitMessageBuilder;
 use payload::make_signed_payload;
 
@@ -111,6 +130,41 @@ impl Action for CircuitProposeAction {
             None => (),
         }
 
+        #[cfg(feature = "circuit-relay")]
+        {
+            if let Some(relays) = args.values_of("relay") {
+                for relay in relays {
+                    builder.set_relay(relay)?;
+                }
+            }
+        }
+
+        #[cfg(feature = "circuit-observers")]
+        {
+            if let Some(observers) = args.values_of("observer") {
+                for observer in observers {
+                    builder.set_observer(observer)?;
+                }
+            }
+        }
+
+        #[cfg(feature = "circuit-id-policy")]
+        {
+            if let Some(circuit_id) = args.value_of("circuit_id") {
+                if CircuitIdFormat::of(circuit_id).is_none() {
+                    return Err(CliError::ActionError(format!(
+                        "Circuit ID {} is invalid: must be two 5 character base62 strings joined \
+                         with a '-' (example: abcDE-F0123) or a lowercase, hyphenated UUIDv7",
+                        circuit_id
+                    )));
+                }
+                builder.set_circuit_id(circuit_id);
+            }
+            if let Some(format) = args.value_of("circuit_id_format") {
+                builder.set_circuit_id_format(format)?;
+            }
+        }
+
         if let Some(management_type) = args.value_of("management_type") {
             builder.set_management_type(management_type);
         }
@@ -181,22 +235,21 @@ impl Action for CircuitProposeAction {
                 .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
 
             let key = args.value_of("key");
+            let signer = load_payload_signer(args, key.unwrap_or("./splinter.priv"))?;
 
             let mut builder = SplinterRestClientBuilder::new();
             builder = builder.with_url(url);
 
             #[cfg(feature = "splinter-cli-jwt")]
             {
-                builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+                builder = builder.with_auth(create_payload_jwt_auth(args, key, &*signer)?);
             }
 
             let client = builder.build()?;
 
             let requester_node = client.get_node_status()?.node_id;
-            let private_key_hex = read_private_key(&key.unwrap_or("./splinter.priv"))?;
 
-            let signed_payload =
-                make_signed_payload(&requester_node, &private_key_hex, create_circuit)?;
+            let signed_payload = make_signed_payload(&requester_node, &*signer, create_circuit)?;
             client.submit_admin_payload(signed_payload)?;
 
             info!("The circuit proposal was submited successfully");
@@ -526,6 +579,11 @@ impl TryFrom<&CreateCircuit> for CircuitSlice {
                 .collect::<Result<Vec<CircuitServiceSlice>, CliError>>()?,
             management_type: circuit.circuit_management_type.clone(),
             display_name: circuit.display_name.clone(),
+            circuit_status: None,
+            created_at: None,
+            updated_at: None,
+            proposer_public_key: None,
+            proposer_node_id: None,
         })
     }
 }
@@ -552,17 +610,6 @@ impl TryFrom<&SplinterService> for CircuitServiceSlice {
     }
 }
 
-enum Vote {
-    Accept,
-    Reject,
-}
-
-struct CircuitVote {
-    circuit_id: String,
-    circuit_hash: String,
-    vote: Vote,
-}
-
 pub struct CircuitVoteAction;
 
 impl Action for CircuitVoteAction {
@@ -588,38 +635,71 @@ impl Action for CircuitVoteAction {
             }
         };
 
-        vote_on_circuit_proposal(&url, key, circuit_id, vote)
+        let signer = load_payload_signer(args, key.unwrap_or("splinter"))?;
+
+        let mut builder = SplinterRestClientBuilder::new();
+        builder = builder.with_url(url);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder = builder.with_auth(create_payload_jwt_auth(args, key, &*signer)?);
+        }
+
+        vote_on_circuit_proposal(
+            builder,
+            &*signer,
+            circuit_id,
+            vote,
+            #[cfg(feature = "admin-vote-cosigning")]
+            args.is_present("cosign"),
+            #[cfg(feature = "admin-vote-delegation")]
+            args.is_present("delegated"),
+        )
     }
 }
 
 fn vote_on_circuit_proposal(
-    url: &str,
-    key: Option<&str>,
+    builder: SplinterRestClientBuilder,
+    signer: &dyn Signer,
     circuit_id: &str,
     vote: Vote,
+    #[cfg(feature = "admin-vote-cosigning")] cosign: bool,
+    #[cfg(feature = "admin-vote-delegation")] delegated: bool,
 ) -> Result<(), CliError> {
-    let mut builder = SplinterRestClientBuilder::new();
-    builder = builder.with_url(url.to_string());
-
-    #[cfg(feature = "splinter-cli-jwt")]
-    {
-        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
-    }
-
     let client = builder.build()?;
 
-    let private_key_hex = read_private_key(key.unwrap_or("splinter"))?;
-
     let requester_node = client.get_node_status()?.node_id;
     let proposal = client.fetch_proposal(circuit_id)?;
 
     if let Some(proposal) = proposal {
-        let circuit_vote = CircuitVote {
-            circuit_id: circuit_id.into(),
-            circuit_hash: proposal.circuit_hash,
-            vote,
+        let circuit_vote_builder = CircuitProposalVoteBuilder::new()
+            .with_circuit_id(circuit_id)
+            .with_circuit_hash(&proposal.circuit_hash)
+            .with_vote(vote);
+        #[cfg(feature = "admin-vote-delegation")]
+        let circuit_vote_builder = if delegated {
+            circuit_vote_builder.with_delegation(super::delegation::fetch_delegation(
+                &client,
+                &requester_node,
+                signer,
+            )?)
+        } else {
+            circuit_vote_builder
         };
-        let signed_payload = make_signed_payload(&requester_node, &private_key_hex, circuit_vote)?;
+        let circuit_vote = circuit_vote_builder
+            .build()
+            .map_err(|err| CliError::ActionError(format!("Failed to build vote: {}", err)))?;
+        let signed_payload = make_signed_payload(&requester_node, signer, circuit_vote)?;
+
+        #[cfg(feature = "admin-vote-cosigning")]
+        {
+            if cosign {
+                let pending_vote = client.submit_cosigned_vote(signed_payload)?;
+                super::cosign::report_submission(circuit_id, pending_vote);
+                return Ok(());
+            }
+        }
+
         client.submit_admin_payload(signed_payload)
     } else {
         Err(CliError::ActionError(format!(
@@ -686,6 +766,7 @@ fn list_circuits(
         "ID".to_string(),
         "NAME".to_string(),
         "MANAGEMENT".to_string(),
+        "STATUS".to_string(),
         "MEMBERS".to_string(),
     ]);
     circuits.data.iter().for_each(|circuit| {
@@ -700,10 +781,18 @@ fn list_circuits(
                     .unwrap_or_else(|| "-".to_string())
             }
         };
+        let circuit_status = circuit.circuit_status.clone().unwrap_or_else(|| {
+            if format == "csv" {
+                String::new()
+            } else {
+                "-".to_string()
+            }
+        });
         data.push(vec![
             circuit.id.to_string(),
             display_name,
             circuit.management_type.to_string(),
+            circuit_status,
             members,
         ]);
     });
@@ -748,6 +837,8 @@ impl Action for CircuitShowAction {
             format,
             #[cfg(feature = "splinter-cli-jwt")]
             key,
+            #[cfg(feature = "circuit-show-registry")]
+            !args.is_present("offline"),
         )
     }
 }
@@ -757,6 +848,7 @@ fn show_circuit(
     circuit_id: &str,
     format: &str,
     #[cfg(feature = "splinter-cli-jwt")] key: Option<&str>,
+    #[cfg(feature = "circuit-show-registry")] resolve_members: bool,
 ) -> Result<(), CliError> {
     let mut builder = SplinterRestClientBuilder::new();
     builder = builder.with_url(url.to_string());
@@ -769,6 +861,13 @@ fn show_circuit(
     let client = builder.build()?;
 
     let circuit = client.fetch_circuit(circuit_id)?;
+    #[cfg(feature = "circuit-show-registry")]
+    let circuit = circuit.map(|mut circuit| {
+        if resolve_members {
+            circuit.registry_members = resolve_registry_members(&client, &circuit.members);
+        }
+        circuit
+    });
     let mut print_circuit = false;
     let mut print_proposal = false;
     if let Some(circuit) = circuit {
@@ -793,6 +892,19 @@ fn show_circuit(
     }
 
     let proposal = client.fetch_proposal(circuit_id)?;
+    #[cfg(feature = "circuit-show-registry")]
+    let proposal = proposal.map(|mut proposal| {
+        if resolve_members {
+            let members = proposal
+                .circuit
+                .members
+                .iter()
+                .map(|member| member.node_id.clone())
+                .collect::<Vec<_>>();
+            proposal.registry_members = resolve_registry_members(&client, &members);
+        }
+        proposal
+    });
 
     if let Some(proposal) = proposal {
         print_proposal = true;
@@ -825,6 +937,268 @@ fn show_circuit(
     Ok(())
 }
 
+/// Looks up each of the given members in the node's registry. A member that cannot be looked up
+/// is logged and left out, so the circuit is still shown if the registry is unavailable.
+#[cfg(feature = "circuit-show-registry")]
+fn resolve_registry_members(
+    client: &SplinterRestClient,
+    members: &[String],
+) -> BTreeMap<String, Option<RegistryMemberSlice>> {
+    members
+        .iter()
+        .filter_map(|node_id| match client.fetch_registry_node(node_id) {
+            Ok(node) => Some((
+                node_id.clone(),
+                node.map(|node| RegistryMemberSlice {
+                    display_name: node.display_name,
+                    endpoints: node.endpoints,
+                    key_fingerprints: node
+                        .keys
+                        .iter()
+                        .map(|key| fingerprint(key).unwrap_or_else(|_| key.clone()))
+                        .collect(),
+                }),
+            )),
+            Err(err) => {
+                warn!("Unable to look up {} in the registry: {}", node_id, err);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "circuit-ping")]
+pub struct CircuitPingAction;
+
+#[cfg(feature = "circuit-ping")]
+impl Action for CircuitPingAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let url = args
+            .value_of("url")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+        let circuit_id = args
+            .value_of("circuit")
+            .ok_or_else(|| CliError::ActionError("'circuit' argument is required".to_string()))?;
+        let service_id = args
+            .value_of("service")
+            .ok_or_else(|| CliError::ActionError("'service' argument is required".to_string()))?;
+        let timeout = args
+            .value_of("timeout")
+            .unwrap_or("5")
+            .parse::<u64>()
+            .map_err(|_| CliError::ActionError("'timeout' must be a number of seconds".into()))?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let mut builder = SplinterRestClientBuilder::new();
+        builder = builder.with_url(url);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder =
+                builder.with_auth(create_cylinder_jwt_auth(args.value_of("private_key_file"))?);
+        }
+
+        let result = builder
+            .build()?
+            .ping_service(circuit_id, service_id, timeout)?;
+
+        match format {
+            "json" => println!(
+                "{}",
+                serde_json::to_string(&result).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format ping result into json: {}",
+                    err
+                )))?
+            ),
+            "yaml" => println!(
+                "{}",
+                serde_yaml::to_string(&result).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format ping result into yaml: {}",
+                    err
+                )))?
+            ),
+            _ => print!("{}", result),
+        }
+
+        if result.status != "OK" {
+            return Err(CliError::ActionError(format!(
+                "Service '{}' on circuit '{}' is not reachable: {}",
+                service_id, circuit_id, result.status
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "circuit-archive")]
+pub struct CircuitExportAction;
+
+#[cfg(feature = "circuit-archive")]
+impl Action for CircuitExportAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let url = args
+            .value_of("url")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+        let circuit_id = args
+            .value_of("circuit")
+            .ok_or_else(|| CliError::ActionError("'circuit' argument is required".to_string()))?;
+        let key = args.value_of("private_key_file");
+
+        let mut builder = SplinterRestClientBuilder::new();
+        builder = builder.with_url(url);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+        }
+
+        let client = builder.build()?;
+
+        let circuit = client.fetch_circuit(circuit_id)?.ok_or_else(|| {
+            CliError::ActionError(format!("Circuit '{}' does not exist", circuit_id))
+        })?;
+        let archived_circuit = ArchivedCircuit::from_circuit(circuit, |node_id| {
+            client
+                .fetch_registry_node(node_id)?
+                .map(|node| node.endpoints)
+                .ok_or_else(|| {
+                    CliError::ActionError(format!(
+                        "Member '{}' is not in the node's registry, so its endpoints cannot be \
+                         exported",
+                        node_id
+                    ))
+                })
+        })?;
+
+        let private_key_hex = read_private_key(&key.unwrap_or("./splinter.priv"))?;
+        let archive = CircuitArchive::sign(archived_circuit, &private_key_hex)?;
+        let archive_json = serde_json::to_string_pretty(&archive).map_err(|err| {
+            CliError::ActionError(format!("Cannot format circuit archive into json: {}", err))
+        })?;
+
+        let output = args
+            .value_of("output")
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("{}.circuit.json", circuit_id));
+        if output == "-" {
+            println!("{}", archive_json);
+        } else {
+            File::create(&output)
+                .and_then(|mut file| file.write_all(archive_json.as_bytes()))
+                .map_err(|err| {
+                    CliError::EnvironmentError(format!(
+                        "Unable to write circuit archive '{}': {}",
+                        output,
+                        msg_from_io_error(err)
+                    ))
+                })?;
+            info!("Exported circuit {} to {}", circuit_id, output);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "circuit-archive")]
+pub struct CircuitImportAction;
+
+#[cfg(feature = "circuit-archive")]
+impl Action for CircuitImportAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let archive_file = args
+            .value_of("archive")
+            .ok_or_else(|| CliError::ActionError("'archive' argument is required".to_string()))?;
+        let archive: CircuitArchive = File::open(archive_file)
+            .map_err(|err| {
+                CliError::EnvironmentError(format!(
+                    "Unable to open circuit archive '{}': {}",
+                    archive_file,
+                    msg_from_io_error(err)
+                ))
+            })
+            .and_then(|file| {
+                serde_json::from_reader(file).map_err(|err| {
+                    CliError::ActionError(format!(
+                        "Unable to parse circuit archive '{}': {}",
+                        archive_file, err
+                    ))
+                })
+            })?;
+
+        let archived_circuit = archive.verify(args.value_of("signer"))?;
+        let create_circuit = create_circuit_from_archive(archived_circuit)?;
+        let circuit_slice = CircuitSlice::try_from(&create_circuit)?;
+
+        if !args.is_present("dry_run") {
+            let url = args
+                .value_of("url")
+                .map(ToOwned::to_owned)
+                .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+                .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+            let key = args.value_of("private_key_file");
+            let signer = load_payload_signer(args, key.unwrap_or("./splinter.priv"))?;
+
+            let mut builder = SplinterRestClientBuilder::new();
+            builder = builder.with_url(url);
+
+            #[cfg(feature = "splinter-cli-jwt")]
+            {
+                builder = builder.with_auth(create_payload_jwt_auth(args, key, &*signer)?);
+            }
+
+            let client = builder.build()?;
+
+            let requester_node = client.get_node_status()?.node_id;
+
+            let signed_payload = make_signed_payload(&requester_node, &*signer, create_circuit)?;
+            client.submit_admin_payload(signed_payload)?;
+
+            info!("The circuit proposal was submited successfully");
+        }
+
+        info!("{}", circuit_slice);
+
+        Ok(())
+    }
+}
+
+/// Builds the proposal that recreates an archived circuit.
+#[cfg(feature = "circuit-archive")]
+fn create_circuit_from_archive(circuit: ArchivedCircuit) -> Result<CreateCircuit, CliError> {
+    let mut builder = CreateCircuitMessageBuilder::new();
+    builder.set_circuit_id(&circuit.circuit_id);
+    builder.set_management_type(&circuit.management_type);
+    if let Some(display_name) = &circuit.display_name {
+        builder.set_display_name(display_name);
+    }
+
+    for member in &circuit.members {
+        builder.add_node(&member.node_id, &member.endpoints)?;
+    }
+
+    for service in &circuit.roster {
+        builder.add_service(&service.service_id, &[service.node_id.clone()])?;
+        builder.apply_service_type(&service.service_id, &service.service_type);
+        for (key, value) in &service.arguments {
+            builder.apply_service_arguments(&service.service_id, &(key.clone(), value.clone()))?;
+        }
+    }
+
+    builder.build()
+}
+
 pub struct CircuitProposalsAction;
 
 impl Action for CircuitProposalsAction {
@@ -940,36 +1314,3 @@ fn list_proposals(
 
     Ok(())
 }
-
-// Takes a vec of vecs of strings. The first vec should include the title of the columns.
-// The max length of each column is calculated and is used as the column with when printing the
-// table.
-fn print_table(table: Vec<Vec<String>>) {
-    let mut max_lengths = Vec::new();
-
-    // find the max lengths of the columns
-    for row in table.iter() {
-        for (i, col) in row.iter().enumerate() {
-            if let Some(length) = max_lengths.get_mut(i) {
-                if col.len() > *length {
-                    *length = col.len()
-                }
-            } else {
-                max_lengths.push(col.len())
-            }
-        }
-    }
-
-    // print each row with correct column size
-    for row in table.iter() {
-        let mut col_string = String::from("");
-        for (i, len) in max_lengths.iter().enumerate() {
-            if let Some(value) = row.get(i) {
-                col_string += &format!("{}{} ", value, " ".repeat(*len - value.len()),);
-            } else {
-                col_string += &" ".repeat(*len);
-            }
-        }
-        println!("{}", col_string);
-    }
-}
@@ -12,130 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey};
-use openssl::hash::{hash, MessageDigest};
+use cylinder::Signer;
 use protobuf::Message;
-use splinter::admin::messages::CreateCircuit;
-use splinter::protos::admin::{
-    CircuitCreateRequest, CircuitManagementPayload, CircuitManagementPayload_Action as Action,
-    CircuitManagementPayload_Header as Header, CircuitProposalVote, CircuitProposalVote_Vote,
-};
+use splinter::admin::messages::{CircuitManagementAction, CircuitManagementPayloadBuilder};
 
 use crate::error::CliError;
 
-use super::{CircuitVote, Vote};
-
-/// A circuit action that has a type and can be converted into a protobuf-serializable struct.
-pub trait CircuitAction<M: Message> {
-    fn into_proto(self) -> Result<M, CliError>;
-
-    fn action_type(&self) -> Action;
-}
-
-/// Applies a circuit payload action to the given CircuitManagementPayload.
-pub trait ApplyToEnvelope {
-    fn apply(self, circuit_management_payload: &mut CircuitManagementPayload);
-}
-
 /// Makes a signed, circuit management payload to be submitted to the Splinter REST API.
-pub fn make_signed_payload<M, A>(
+pub fn make_signed_payload<A: Into<CircuitManagementAction>>(
     requester_node: &str,
-    private_key: &str,
+    signer: &dyn Signer,
     action: A,
-) -> Result<Vec<u8>, CliError>
-where
-    M: Message + ApplyToEnvelope,
-    A: CircuitAction<M>,
-{
-    let action_type = action.action_type();
-    let action_proto = action.into_proto()?;
-    let serialized_action = action_proto
-        .write_to_bytes()
-        .map_err(|err| CliError::ActionError(format!("Failed to serialize action: {}", err)))?;
-
-    let hashed_bytes = hash(MessageDigest::sha512(), &serialized_action)?;
-
-    let private_key = PrivateKey::new_from_hex(private_key).map_err(|err| {
-        CliError::ActionError(format!("Invalid secp256k1 private key provided: {}", err))
-    })?;
-    let signer = Secp256k1Context::new().new_signer(private_key);
-
-    let public_key = signer
-        .public_key()
-        .map_err(|err| {
-            CliError::ActionError(format!(
-                "Failed to get public key from secp256k1 private key: {}",
-                err
-            ))
-        })?
-        .into_bytes();
-
-    let mut header = Header::new();
-    header.set_action(action_type);
-    header.set_payload_sha512(hashed_bytes.to_vec());
-    header.set_requester(public_key);
-    header.set_requester_node_id(requester_node.into());
-    let header_bytes = header.write_to_bytes().map_err(|err| {
-        CliError::ActionError(format!("Failed to serialize payload header: {}", err))
-    })?;
-
-    let header_signature = signer
-        .sign(&header_bytes)
-        .map_err(|err| CliError::ActionError(format!("Failed to sign payload header: {}", err)))?;
-
-    let mut circuit_management_payload = CircuitManagementPayload::new();
-    circuit_management_payload.set_header(header_bytes);
-    circuit_management_payload.set_signature(header_signature.take_bytes());
-    action_proto.apply(&mut circuit_management_payload);
-    let payload_bytes = circuit_management_payload
+) -> Result<Vec<u8>, CliError> {
+    CircuitManagementPayloadBuilder::new()
+        .with_action(action)
+        .with_requester_node_id(requester_node)
+        .build(signer)
+        .map_err(|err| CliError::ActionError(format!("Failed to build payload: {}", err)))?
         .write_to_bytes()
-        .map_err(|err| CliError::ActionError(format!("Failed to serialize payload: {}", err)))?;
-    Ok(payload_bytes)
-}
-
-// Conversions for explicit actions and their associated types.
-
-impl CircuitAction<CircuitCreateRequest> for CreateCircuit {
-    fn action_type(&self) -> Action {
-        Action::CIRCUIT_CREATE_REQUEST
-    }
-
-    fn into_proto(self) -> Result<CircuitCreateRequest, CliError> {
-        CreateCircuit::into_proto(self).map_err(|err| {
-            CliError::ActionError(format!(
-                "Failed to convert circuit create request to protobuf: {}",
-                err
-            ))
-        })
-    }
-}
-
-impl ApplyToEnvelope for CircuitCreateRequest {
-    fn apply(self, circuit_management_payload: &mut CircuitManagementPayload) {
-        circuit_management_payload.set_circuit_create_request(self);
-    }
-}
-
-impl CircuitAction<CircuitProposalVote> for CircuitVote {
-    fn action_type(&self) -> Action {
-        Action::CIRCUIT_PROPOSAL_VOTE
-    }
-
-    fn into_proto(self) -> Result<CircuitProposalVote, CliError> {
-        let mut vote = CircuitProposalVote::new();
-        vote.set_vote(match self.vote {
-            Vote::Accept => CircuitProposalVote_Vote::ACCEPT,
-            Vote::Reject => CircuitProposalVote_Vote::REJECT,
-        });
-        vote.set_circuit_id(self.circuit_id);
-        vote.set_circuit_hash(self.circuit_hash);
-
-        Ok(vote)
-    }
-}
-
-impl ApplyToEnvelope for CircuitProposalVote {
-    fn apply(self, circuit_management_payload: &mut CircuitManagementPayload) {
-        circuit_management_payload.set_circuit_proposal_vote(self);
-    }
+        .map_err(|err| CliError::ActionError(format!("Failed to serialize payload: {}", err)))
 }
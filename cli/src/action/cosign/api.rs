@@ -0,0 +1,234 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    header,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+const CLI_ADMIN_PROTOCOL_VERSION: &str = "2";
+
+impl SplinterRestClient {
+    /// Lists the node's vote thresholds.
+    pub fn list_vote_thresholds(&self) -> Result<Vec<VoteThreshold>, CliError> {
+        self.cosign_request(Client::new().get(&format!("{}/admin/vote-thresholds", self.url)))
+            .send()
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to list vote thresholds: {}", err))
+            })
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<ListResponse<VoteThreshold>>()
+                        .map(|response| response.data)
+                        .map_err(|_| invalid_response())
+                } else {
+                    Err(request_error(res, "list vote thresholds"))
+                }
+            })
+    }
+
+    /// Sets the number of signatures required on the node's votes for proposals of the
+    /// threshold's circuit management type.
+    pub fn set_vote_threshold(&self, threshold: &VoteThreshold) -> Result<(), CliError> {
+        self.cosign_request(
+            Client::new()
+                .post(&format!("{}/admin/vote-thresholds", self.url))
+                .json(threshold),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to set vote threshold: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "set vote threshold"))
+            }
+        })
+    }
+
+    /// Removes the vote threshold of the given circuit management type.
+    pub fn remove_vote_threshold(&self, management_type: &str) -> Result<(), CliError> {
+        self.cosign_request(Client::new().delete(&format!(
+            "{}/admin/vote-thresholds/{}",
+            self.url, management_type
+        )))
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to remove vote threshold: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "remove vote threshold"))
+            }
+        })
+    }
+
+    /// Lists the votes waiting for co-signatures.
+    pub fn list_pending_votes(&self) -> Result<Vec<PendingVote>, CliError> {
+        self.cosign_request(Client::new().get(&format!("{}/admin/votes", self.url)))
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to list pending votes: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<ListResponse<PendingVote>>()
+                        .map(|response| response.data)
+                        .map_err(|_| invalid_response())
+                } else {
+                    Err(request_error(res, "list pending votes"))
+                }
+            })
+    }
+
+    /// Fetches the pending vote for the given circuit, if there is one.
+    pub fn fetch_pending_vote(&self, circuit_id: &str) -> Result<Option<PendingVote>, CliError> {
+        self.cosign_request(Client::new().get(&format!("{}/admin/votes/{}", self.url, circuit_id)))
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to fetch pending vote: {}", err)))
+            .and_then(|res| match res.status() {
+                status if status.is_success() => res
+                    .json::<PendingVote>()
+                    .map(Some)
+                    .map_err(|_| invalid_response()),
+                reqwest::StatusCode::NOT_FOUND => Ok(None),
+                _ => Err(request_error(res, "fetch pending vote")),
+            })
+    }
+
+    /// Discards the pending vote for the given circuit.
+    pub fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), CliError> {
+        self.cosign_request(
+            Client::new().delete(&format!("{}/admin/votes/{}", self.url, circuit_id)),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to discard pending vote: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "discard pending vote"))
+            }
+        })
+    }
+
+    /// Submits a signed vote payload. Returns the pending vote if the vote must still be
+    /// co-signed before it is submitted to the admin service.
+    pub fn submit_cosigned_vote(&self, payload: Vec<u8>) -> Result<Option<PendingVote>, CliError> {
+        self.cosign_request(
+            Client::new()
+                .post(&format!("{}/admin/votes", self.url))
+                .header(header::CONTENT_TYPE, "octet-stream")
+                .body(payload),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to submit vote: {}", err)))
+        .and_then(|res| submission_result(res, "submit vote"))
+    }
+
+    /// Adds a co-signature to the pending vote for the given circuit. Returns the pending vote if
+    /// it still requires more signatures.
+    pub fn add_vote_signature(
+        &self,
+        circuit_id: &str,
+        signature: &VoteSignature,
+    ) -> Result<Option<PendingVote>, CliError> {
+        self.cosign_request(
+            Client::new()
+                .post(&format!(
+                    "{}/admin/votes/{}/signatures",
+                    self.url, circuit_id
+                ))
+                .json(signature),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to co-sign vote: {}", err)))
+        .and_then(|res| submission_result(res, "co-sign vote"))
+    }
+
+    fn cosign_request(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = request.header("SplinterProtocolVersion", CLI_ADMIN_PROTOCOL_VERSION);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request.header("Authorization", &self.auth)
+        }
+        #[cfg(not(feature = "splinter-cli-jwt"))]
+        {
+            request
+        }
+    }
+}
+
+fn submission_result(res: Response, action: &str) -> Result<Option<PendingVote>, CliError> {
+    if res.status().is_success() {
+        res.json::<SubmissionResponse>()
+            .map(|response| response.data)
+            .map_err(|_| invalid_response())
+    } else {
+        Err(request_error(res, action))
+    }
+}
+
+fn invalid_response() -> CliError {
+    CliError::ActionError("Request was successful, but received an invalid response".into())
+}
+
+/// Builds the error for an unsuccessful request from the server's error response.
+fn request_error(res: Response, action: &str) -> CliError {
+    let status = res.status();
+    match res.json::<ServerError>() {
+        Ok(ServerError { message }) => {
+            CliError::ActionError(format!("Failed to {}: {}", action, message))
+        }
+        Err(_) => CliError::ActionError(format!(
+            "Request to {} failed with status code '{}', but error response was not valid",
+            action, status
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct ListResponse<T> {
+    data: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct SubmissionResponse {
+    data: Option<PendingVote>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoteThreshold {
+    pub management_type: String,
+    pub required_signatures: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingVote {
+    pub circuit_id: String,
+    pub vote: String,
+    pub requester: String,
+    pub requester_node_id: String,
+    pub header: String,
+    pub cosigners: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VoteSignature {
+    pub public_key: String,
+    pub signature: String,
+}
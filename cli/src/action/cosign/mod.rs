@@ -0,0 +1,320 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Co-signing of the votes this node submits for circuit proposals, and management of the number
+//! of signatures those votes require.
+
+mod api;
+
+use clap::ArgMatches;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::{create_cylinder_jwt_auth, create_payload_jwt_auth};
+use super::{
+    load_payload_signer, print_table, Action, DEFAULT_SPLINTER_REST_API_URL,
+    SPLINTER_REST_API_URL_ENV,
+};
+
+use self::api::{PendingVote, VoteSignature, VoteThreshold};
+
+pub struct VoteThresholdListAction;
+
+impl Action for VoteThresholdListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let thresholds = build_client(args)?.list_vote_thresholds()?;
+
+        let mut data = Vec::new();
+        data.push(vec![
+            "MANAGEMENT TYPE".to_string(),
+            "REQUIRED SIGNATURES".to_string(),
+        ]);
+        thresholds.into_iter().for_each(|threshold| {
+            data.push(vec![
+                threshold.management_type,
+                threshold.required_signatures.to_string(),
+            ]);
+        });
+
+        print_data(data, format);
+
+        Ok(())
+    }
+}
+
+pub struct VoteThresholdSetAction;
+
+impl Action for VoteThresholdSetAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let management_type = management_type_arg(args)?;
+        let required_signatures = args
+            .value_of("required_signatures")
+            .ok_or_else(|| CliError::ActionError("'signatures' argument is required".into()))?
+            .parse::<u32>()
+            .map_err(|_| {
+                CliError::ActionError("'signatures' argument must be a positive integer".into())
+            })?;
+
+        build_client(args)?.set_vote_threshold(&VoteThreshold {
+            management_type: management_type.to_string(),
+            required_signatures,
+        })?;
+        info!(
+            "Votes for {} proposals now require {} signature(s)",
+            management_type, required_signatures
+        );
+
+        Ok(())
+    }
+}
+
+pub struct VoteThresholdRemoveAction;
+
+impl Action for VoteThresholdRemoveAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let management_type = management_type_arg(args)?;
+
+        build_client(args)?.remove_vote_threshold(management_type)?;
+        info!("Removed vote threshold for {}", management_type);
+
+        Ok(())
+    }
+}
+
+pub struct PendingVoteListAction;
+
+impl Action for PendingVoteListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let votes = build_client(args)?.list_pending_votes()?;
+
+        let mut data = Vec::new();
+        data.push(vec![
+            "CIRCUIT ID".to_string(),
+            "VOTE".to_string(),
+            "REQUESTER".to_string(),
+            "CO-SIGNERS".to_string(),
+        ]);
+        votes.into_iter().for_each(|vote| {
+            data.push(vec![
+                vote.circuit_id,
+                vote.vote,
+                vote.requester,
+                vote.cosigners.len().to_string(),
+            ]);
+        });
+
+        print_data(data, format);
+
+        Ok(())
+    }
+}
+
+pub struct PendingVoteShowAction;
+
+impl Action for PendingVoteShowAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let circuit_id = circuit_id_arg(args)?;
+
+        let vote = build_client(args)?
+            .fetch_pending_vote(circuit_id)?
+            .ok_or_else(|| no_pending_vote(circuit_id))?;
+        print_pending_vote(&vote);
+
+        Ok(())
+    }
+}
+
+pub struct PendingVoteSignAction;
+
+impl Action for PendingVoteSignAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let circuit_id = circuit_id_arg(args)?;
+        let key = args.value_of("private_key_file");
+
+        let signer = load_payload_signer(args, key.unwrap_or("splinter"))?;
+
+        let mut builder = SplinterRestClientBuilder::new();
+        builder = builder.with_url(rest_api_url(args));
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder = builder.with_auth(create_payload_jwt_auth(args, key, &*signer)?);
+        }
+
+        let client = builder.build()?;
+        let vote = client
+            .fetch_pending_vote(circuit_id)?
+            .ok_or_else(|| no_pending_vote(circuit_id))?;
+
+        let public_key = signer.public_key().map_err(|err| {
+            CliError::ActionError(format!("Failed to get public key of signer: {}", err))
+        })?;
+        let signature = signer
+            .sign(&parse_hex(&vote.header)?)
+            .map_err(|err| CliError::ActionError(format!("Failed to co-sign vote: {}", err)))?;
+
+        let pending_vote = client.add_vote_signature(
+            circuit_id,
+            &VoteSignature {
+                public_key: to_hex(&public_key.into_bytes()),
+                signature: to_hex(&signature.take_bytes()),
+            },
+        )?;
+        report_submission(circuit_id, pending_vote);
+
+        Ok(())
+    }
+}
+
+pub struct PendingVoteDiscardAction;
+
+impl Action for PendingVoteDiscardAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let circuit_id = circuit_id_arg(args)?;
+
+        build_client(args)?.remove_pending_vote(circuit_id)?;
+        info!("Discarded pending vote for circuit {}", circuit_id);
+
+        Ok(())
+    }
+}
+
+/// Logs the result of submitting a vote that may require co-signatures.
+pub(super) fn report_submission(circuit_id: &str, pending_vote: Option<PendingVote>) {
+    match pending_vote {
+        Some(vote) => {
+            info!(
+                "Vote for circuit {} is waiting for co-signatures ({} collected)",
+                circuit_id,
+                vote.cosigners.len()
+            );
+        }
+        None => info!("Vote for circuit {} submitted", circuit_id),
+    }
+}
+
+fn print_pending_vote(vote: &PendingVote) {
+    println!("Circuit: {}", vote.circuit_id);
+    println!("    Vote: {}", vote.vote);
+    println!("    Requester: {}", vote.requester);
+    println!("    Requester Node: {}", vote.requester_node_id);
+    println!("    Header: {}", vote.header);
+    println!("    Co-signers:");
+    for cosigner in &vote.cosigners {
+        println!("        {}", cosigner);
+    }
+}
+
+fn print_data(data: Vec<Vec<String>>, format: &str) {
+    if format == "csv" {
+        for row in data {
+            println!("{}", row.join(","))
+        }
+    } else {
+        print_table(data);
+    }
+}
+
+fn management_type_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("management_type")
+        .ok_or_else(|| CliError::ActionError("'management-type' argument is required".into()))
+}
+
+fn circuit_id_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("circuit_id")
+        .ok_or_else(|| CliError::ActionError("'circuit-id' argument is required".into()))
+}
+
+fn no_pending_vote(circuit_id: &str) -> CliError {
+    CliError::ActionError(format!(
+        "There is no pending vote for circuit '{}'",
+        circuit_id
+    ))
+}
+
+fn rest_api_url(args: &ArgMatches) -> String {
+    args.value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string())
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(rest_api_url(args));
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, CliError> {
+    if hex.len() % 2 != 0 {
+        return Err(CliError::ActionError(format!(
+            "Pending vote contains an invalid hex string: {}",
+            hex
+        )));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| {
+                    CliError::ActionError(format!(
+                        "Pending vote contains an invalid hex string: {}",
+                        hex
+                    ))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the header of a pending vote survives the hex round trip, and that invalid hex
+    /// is rejected.
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = vec![0x00, 0x0a, 0xff, 0x10];
+        assert_eq!(to_hex(&bytes), "000aff10");
+        assert_eq!(parse_hex("000aff10").expect("failed to parse hex"), bytes);
+        assert!(parse_hex("000").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+const CLI_ADMIN_PROTOCOL_VERSION: &str = "2";
+
+impl SplinterRestClient {
+    /// Lists the vote delegations honored by the node.
+    pub fn list_vote_delegations(&self) -> Result<Vec<VoteDelegation>, CliError> {
+        self.delegation_request(Client::new().get(&format!("{}/admin/vote-delegations", self.url)))
+            .send()
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to list vote delegations: {}", err))
+            })
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<ListResponse<VoteDelegation>>()
+                        .map(|response| response.data)
+                        .map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                } else {
+                    Err(request_error(res, "list vote delegations"))
+                }
+            })
+    }
+
+    /// Adds a signed vote delegation to the node, replacing any existing delegation of the same
+    /// node to the same delegate.
+    pub fn add_vote_delegation(&self, delegation: &VoteDelegation) -> Result<(), CliError> {
+        self.delegation_request(
+            Client::new()
+                .post(&format!("{}/admin/vote-delegations", self.url))
+                .json(delegation),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to add vote delegation: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "add vote delegation"))
+            }
+        })
+    }
+
+    /// Revokes the delegation of the given node's votes to the given delegate.
+    pub fn remove_vote_delegation(&self, node_id: &str, delegate: &str) -> Result<(), CliError> {
+        self.delegation_request(Client::new().delete(&format!(
+            "{}/admin/vote-delegations/{}/{}",
+            self.url, node_id, delegate
+        )))
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to revoke vote delegation: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "revoke vote delegation"))
+            }
+        })
+    }
+
+    fn delegation_request(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = request.header("SplinterProtocolVersion", CLI_ADMIN_PROTOCOL_VERSION);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request.header("Authorization", &self.auth)
+        }
+        #[cfg(not(feature = "splinter-cli-jwt"))]
+        {
+            request
+        }
+    }
+}
+
+/// Builds the error for an unsuccessful request from the server's error response.
+fn request_error(res: Response, action: &str) -> CliError {
+    let status = res.status();
+    match res.json::<ServerError>() {
+        Ok(ServerError { message }) => {
+            CliError::ActionError(format!("Failed to {}: {}", action, message))
+        }
+        Err(_) => CliError::ActionError(format!(
+            "Request to {} failed with status code '{}', but error response was not valid",
+            action, status
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct ListResponse<T> {
+    data: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoteDelegation {
+    pub node_id: String,
+    pub delegator_public_key: String,
+    pub delegate_public_key: String,
+    pub management_types: Vec<String>,
+    pub expires_at: u64,
+    pub signature: String,
+}
@@ -0,0 +1,264 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delegation of a node's voting authority on circuit proposals to another key, such as the key
+//! of an automated policy engine.
+
+mod api;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::ArgMatches;
+use cylinder::Signer;
+use splinter::admin::delegation::VoteDelegation as SignedVoteDelegation;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::{create_cylinder_jwt_auth, create_payload_jwt_auth};
+use super::{
+    load_payload_signer, print_table, Action, DEFAULT_SPLINTER_REST_API_URL,
+    SPLINTER_REST_API_URL_ENV,
+};
+
+use self::api::VoteDelegation;
+
+pub struct DelegationCreateAction;
+
+impl Action for DelegationCreateAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let node_id = node_id_arg(args)?;
+        let delegate = delegate_arg(args)?;
+        let management_types = args
+            .values_of("management_type")
+            .ok_or_else(|| CliError::ActionError("'management-type' argument is required".into()))?
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let expires_in = args
+            .value_of("expires_in")
+            .ok_or_else(|| CliError::ActionError("'expires-in' argument is required".into()))?
+            .parse::<u64>()
+            .map_err(|_| {
+                CliError::ActionError("'expires-in' argument must be a number of seconds".into())
+            })?;
+        let expires_at = expiration_time(SystemTime::now(), expires_in)?;
+
+        let key = args.value_of("private_key_file");
+        let signer = load_payload_signer(args, key.unwrap_or("splinter"))?;
+
+        let delegation =
+            SignedVoteDelegation::sign(node_id, delegate, management_types, expires_at, &*signer)
+                .map_err(|err| {
+                CliError::ActionError(format!("Failed to sign vote delegation: {}", err))
+            })?;
+
+        let mut builder = SplinterRestClientBuilder::new();
+        builder = builder.with_url(rest_api_url(args));
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder = builder.with_auth(create_payload_jwt_auth(args, key, &*signer)?);
+        }
+
+        builder
+            .build()?
+            .add_vote_delegation(&VoteDelegation::from(&delegation))?;
+        info!(
+            "Delegated the votes of node {} on {} proposals to {} until {}",
+            node_id,
+            delegation.management_types().join(", "),
+            delegation.delegate_public_key(),
+            expires_at
+        );
+
+        Ok(())
+    }
+}
+
+pub struct DelegationListAction;
+
+impl Action for DelegationListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let delegations = build_client(args)?.list_vote_delegations()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0);
+
+        let mut data = Vec::new();
+        data.push(vec![
+            "NODE ID".to_string(),
+            "DELEGATE".to_string(),
+            "MANAGEMENT TYPES".to_string(),
+            "EXPIRES AT".to_string(),
+            "STATUS".to_string(),
+        ]);
+        delegations.into_iter().for_each(|delegation| {
+            let status = if now >= delegation.expires_at {
+                "expired"
+            } else {
+                "active"
+            };
+            data.push(vec![
+                delegation.node_id,
+                delegation.delegate_public_key,
+                delegation.management_types.join(" "),
+                delegation.expires_at.to_string(),
+                status.to_string(),
+            ]);
+        });
+
+        if format == "csv" {
+            for row in data {
+                println!("{}", row.join(","))
+            }
+        } else {
+            print_table(data);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct DelegationRevokeAction;
+
+impl Action for DelegationRevokeAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let node_id = node_id_arg(args)?;
+        let delegate = delegate_arg(args)?;
+
+        build_client(args)?.remove_vote_delegation(node_id, delegate)?;
+        info!(
+            "Revoked the delegation of node {}'s votes to {}",
+            node_id, delegate
+        );
+
+        Ok(())
+    }
+}
+
+/// Fetches the delegation of the given node's voting authority to the signer, which must be
+/// included in the votes the signer casts for the node.
+pub fn fetch_delegation(
+    client: &SplinterRestClient,
+    node_id: &str,
+    signer: &dyn Signer,
+) -> Result<SignedVoteDelegation, CliError> {
+    let public_key = signer
+        .public_key()
+        .map_err(|err| {
+            CliError::ActionError(format!("Failed to get public key of signer: {}", err))
+        })?
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+
+    client
+        .list_vote_delegations()?
+        .into_iter()
+        .find(|delegation| {
+            delegation.node_id == node_id && delegation.delegate_public_key == public_key
+        })
+        .map(|delegation| {
+            SignedVoteDelegation::new(
+                &delegation.node_id,
+                &delegation.delegator_public_key,
+                &delegation.delegate_public_key,
+                delegation.management_types,
+                delegation.expires_at,
+                &delegation.signature,
+            )
+        })
+        .ok_or_else(|| {
+            CliError::ActionError(format!(
+                "Node {} has not delegated its voting authority to {}",
+                node_id, public_key
+            ))
+        })
+}
+
+impl From<&SignedVoteDelegation> for VoteDelegation {
+    fn from(delegation: &SignedVoteDelegation) -> Self {
+        Self {
+            node_id: delegation.node_id().to_string(),
+            delegator_public_key: delegation.delegator_public_key().to_string(),
+            delegate_public_key: delegation.delegate_public_key().to_string(),
+            management_types: delegation.management_types().to_vec(),
+            expires_at: delegation.expires_at(),
+            signature: delegation.signature().to_string(),
+        }
+    }
+}
+
+/// Returns the time, in seconds since the Unix epoch, that is `expires_in` seconds after `now`.
+fn expiration_time(now: SystemTime, expires_in: u64) -> Result<u64, CliError> {
+    now.checked_add(Duration::from_secs(expires_in))
+        .and_then(|expires_at| expires_at.duration_since(UNIX_EPOCH).ok())
+        .map(|expires_at| expires_at.as_secs())
+        .ok_or_else(|| CliError::ActionError("'expires-in' argument is too large".into()))
+}
+
+fn node_id_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("node_id")
+        .ok_or_else(|| CliError::ActionError("'node-id' argument is required".into()))
+}
+
+fn delegate_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("delegate")
+        .ok_or_else(|| CliError::ActionError("'delegate' argument is required".into()))
+}
+
+fn rest_api_url(args: &ArgMatches) -> String {
+    args.value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string())
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(rest_api_url(args));
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the expiration time is the given number of seconds after now, and that an
+    /// expiration time that overflows is rejected.
+    #[test]
+    fn test_expiration_time() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        assert_eq!(
+            expiration_time(now, 3600).expect("failed to compute expiration"),
+            4_600
+        );
+        assert!(expiration_time(now, u64::MAX).is_err());
+    }
+}
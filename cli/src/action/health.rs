@@ -14,17 +14,37 @@
 
 use clap::ArgMatches;
 use reqwest::{blocking::Client, StatusCode};
-use serde_json::Value;
+use serde::Deserialize;
 
 #[cfg(feature = "splinter-cli-jwt")]
 use super::create_cylinder_jwt_auth;
-use super::{Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+use super::{print_table, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
 
 use crate::error::CliError;
 
 pub struct StatusAction;
 
-const SPLINTERD_MISSING_HEALTH_STATUS: &str = "The health status endpoint was not found. \
+#[derive(Deserialize)]
+struct NodesResponse {
+    nodes: Vec<NodeHealth>,
+}
+
+#[derive(Deserialize)]
+struct NodeHealth {
+    node_id: String,
+    reachable: bool,
+    average_round_trip_ms: Option<f64>,
+    failures: usize,
+    history: Vec<Probe>,
+}
+
+#[derive(Deserialize)]
+struct Probe {
+    round_trip_ms: Option<f64>,
+    error: Option<String>,
+}
+
+const SPLINTERD_MISSING_HEALTH_STATUS: &str = "The health nodes endpoint was not found. \
                                                The specified splinter daemon has not enabled this \
                                                feature.";
 
@@ -39,7 +59,7 @@ impl Action for StatusAction {
         // Allowing unused_mut because request must be mutable if experimental feature
         // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
         #[allow(unused_mut)]
-        let mut request = Client::new().get(&format!("{}/health/status", url));
+        let mut request = Client::new().get(&format!("{}/health/nodes", url));
 
         #[cfg(feature = "splinter-cli-jwt")]
         {
@@ -75,14 +95,46 @@ impl Action for StatusAction {
                     status_code.as_u16()
                 ))),
             })
-            .and_then(|status: Value| {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&status).map_err(|_| CliError::ActionError(
-                        "Failed to serialize response".into()
-                    ))?
-                );
-                Ok(())
-            })
+            .map(|response: NodesResponse| print_node_health(response.nodes))
+    }
+}
+
+fn print_node_health(nodes: Vec<NodeHealth>) {
+    if nodes.is_empty() {
+        println!("No circuit members have been probed");
+        return;
+    }
+
+    let mut table = vec![vec![
+        "NODE".to_string(),
+        "STATUS".to_string(),
+        "LAST RTT (ms)".to_string(),
+        "AVG RTT (ms)".to_string(),
+        "FAILURES".to_string(),
+        "LAST ERROR".to_string(),
+    ]];
+    for node in nodes {
+        let last = node.history.last();
+        table.push(vec![
+            node.node_id,
+            if node.reachable {
+                "reachable".to_string()
+            } else {
+                "unreachable".to_string()
+            },
+            last.and_then(|probe| probe.round_trip_ms)
+                .map(|rtt| format!("{:.2}", rtt))
+                .unwrap_or_else(|| "-".to_string()),
+            node.average_round_trip_ms
+                .map(|rtt| format!("{:.2}", rtt))
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{}/{}", node.failures, node.history.len()),
+            node.history
+                .iter()
+                .rev()
+                .find_map(|probe| probe.error.clone())
+                .unwrap_or_else(|| "-".to_string()),
+        ]);
     }
+    print_table(table);
 }
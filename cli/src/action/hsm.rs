@@ -0,0 +1,346 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signing with secp256k1 keys held in a hardware security module (HSM).
+//!
+//! The HSM is accessed through its PKCS#11 module. The private key never leaves the HSM: the
+//! module is asked to sign the SHA-256 digest of each message with `CKM_ECDSA`, and the resulting
+//! signature is normalized to the low-S form that secp256k1 verifiers require.
+
+use clap::ArgMatches;
+use cylinder::{PublicKey, Signature, Signer, SigningError};
+use openssl::bn::BigNum;
+use openssl::hash::{hash, MessageDigest};
+use pkcs11::types::{
+    CKA_CLASS, CKA_EC_PARAMS, CKA_EC_POINT, CKA_ID, CKA_KEY_TYPE, CKA_LABEL, CKF_SERIAL_SESSION,
+    CKK_EC, CKM_ECDSA, CKO_PRIVATE_KEY, CKO_PUBLIC_KEY, CKU_USER, CK_ATTRIBUTE, CK_ATTRIBUTE_TYPE,
+    CK_MECHANISM, CK_OBJECT_HANDLE, CK_SESSION_HANDLE, CK_SLOT_ID,
+};
+use pkcs11::Ctx;
+
+use crate::error::CliError;
+
+const ALGORITHM_NAME: &str = "secp256k1";
+
+/// The environment variable holding the path of the PKCS#11 module, if `--hsm-module` is not
+/// given
+const HSM_MODULE_ENV: &str = "SPLINTER_HSM_MODULE";
+/// The environment variable holding the user PIN, if `--hsm-pin-env` is not given
+const DEFAULT_HSM_PIN_ENV: &str = "SPLINTER_HSM_PIN";
+
+/// The DER encoding of the secp256k1 curve's object identifier (1.3.132.0.10)
+const SECP256K1_OID: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+/// The order of the secp256k1 curve
+const SECP256K1_ORDER: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
+
+/// A cylinder `Signer` for a secp256k1 key held in an HSM.
+///
+/// A new PKCS#11 session is opened for each signature, which keeps the signer `Send` and
+/// `Clone`; the CLI only signs a few messages per command.
+#[derive(Clone)]
+pub struct HsmSigner {
+    module: String,
+    slot: CK_SLOT_ID,
+    pin: String,
+    key_label: Option<String>,
+    public_key: PublicKey,
+}
+
+impl HsmSigner {
+    /// Creates a signer from the `hsm_slot`, `hsm_pin_env`, `hsm_module` and `hsm_key_label`
+    /// arguments, and loads the key's public key from the HSM.
+    pub fn from_args(args: &ArgMatches) -> Result<Self, CliError> {
+        let slot = args
+            .value_of("hsm_slot")
+            .ok_or_else(|| CliError::ActionError("'hsm-slot' argument is required".into()))?
+            .parse::<u64>()
+            .map_err(|_| CliError::ActionError("'hsm-slot' must be a slot ID number".into()))?;
+
+        let module = args
+            .value_of("hsm_module")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(HSM_MODULE_ENV).ok())
+            .ok_or_else(|| {
+                CliError::EnvironmentError(format!(
+                    "The PKCS#11 module must be given with --hsm-module or {}",
+                    HSM_MODULE_ENV
+                ))
+            })?;
+
+        let pin_env = args.value_of("hsm_pin_env").unwrap_or(DEFAULT_HSM_PIN_ENV);
+        let pin = std::env::var(pin_env).map_err(|_| {
+            CliError::EnvironmentError(format!("The HSM PIN must be set in {}", pin_env))
+        })?;
+
+        let mut signer = Self {
+            module,
+            slot: slot as CK_SLOT_ID,
+            pin,
+            key_label: args.value_of("hsm_key_label").map(ToOwned::to_owned),
+            public_key: PublicKey::new(vec![]),
+        };
+        signer.public_key = signer
+            .with_session(|ctx, session| signer.load_public_key(ctx, session))
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to load key from the HSM: {}", err))
+            })?;
+
+        Ok(signer)
+    }
+
+    /// Opens a logged-in session on the HSM slot, and runs `f` with it.
+    fn with_session<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Ctx, CK_SESSION_HANDLE) -> Result<T, String>,
+    {
+        let ctx = Ctx::new_and_initialize(&self.module)
+            .map_err(|err| format!("unable to load PKCS#11 module '{}': {}", self.module, err))?;
+        let session = ctx
+            .open_session(self.slot, CKF_SERIAL_SESSION, None, None)
+            .map_err(|err| format!("unable to open session on slot {}: {}", self.slot, err))?;
+        ctx.login(session, CKU_USER, Some(&self.pin))
+            .map_err(|err| format!("unable to log in to slot {}: {}", self.slot, err))?;
+
+        let result = f(&ctx, session);
+
+        if let Err(err) = ctx.logout(session) {
+            debug!("Unable to log out of HSM session: {}", err);
+        }
+        if let Err(err) = ctx.close_session(session) {
+            debug!("Unable to close HSM session: {}", err);
+        }
+
+        result
+    }
+
+    /// Finds the secp256k1 private key in the slot, selected by label if one was given.
+    fn find_private_key(
+        &self,
+        ctx: &Ctx,
+        session: CK_SESSION_HANDLE,
+    ) -> Result<CK_OBJECT_HANDLE, String> {
+        let mut template = vec![
+            CK_ATTRIBUTE::new(CKA_CLASS).with_ck_ulong(&CKO_PRIVATE_KEY),
+            CK_ATTRIBUTE::new(CKA_KEY_TYPE).with_ck_ulong(&CKK_EC),
+        ];
+        if let Some(label) = &self.key_label {
+            template.push(CK_ATTRIBUTE::new(CKA_LABEL).with_string(label));
+        }
+
+        let keys = find_objects(ctx, session, &template)?;
+        match keys.as_slice() {
+            [key] => Ok(*key),
+            [] => Err("no EC private key found in the slot".into()),
+            _ => Err("more than one EC private key found in the slot; use --hsm-key-label".into()),
+        }
+    }
+
+    /// Loads the compressed public key that corresponds to the private key.
+    fn load_public_key(&self, ctx: &Ctx, session: CK_SESSION_HANDLE) -> Result<PublicKey, String> {
+        let private_key = self.find_private_key(ctx, session)?;
+        let key_id = get_attribute(ctx, session, private_key, CKA_ID)?;
+
+        let template = vec![
+            CK_ATTRIBUTE::new(CKA_CLASS).with_ck_ulong(&CKO_PUBLIC_KEY),
+            CK_ATTRIBUTE::new(CKA_ID).with_bytes(&key_id),
+        ];
+        let public_key = find_objects(ctx, session, &template)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "no public key found for the private key".to_string())?;
+
+        if get_attribute(ctx, session, public_key, CKA_EC_PARAMS)? != SECP256K1_OID {
+            return Err("the key is not a secp256k1 key".into());
+        }
+
+        compress_ec_point(&get_attribute(ctx, session, public_key, CKA_EC_POINT)?)
+            .map(PublicKey::new)
+    }
+}
+
+impl Signer for HsmSigner {
+    fn algorithm_name(&self) -> &str {
+        ALGORITHM_NAME
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        let digest = hash(MessageDigest::sha256(), message)
+            .map_err(|err| SigningError::Internal(err.to_string()))?;
+
+        self.with_session(|ctx, session| {
+            let private_key = self.find_private_key(ctx, session)?;
+            let mechanism = CK_MECHANISM {
+                mechanism: CKM_ECDSA,
+                pParameter: std::ptr::null_mut(),
+                ulParameterLen: 0,
+            };
+            ctx.sign_init(session, &mechanism, private_key)
+                .map_err(|err| format!("unable to start signing: {}", err))?;
+            ctx.sign(session, &digest)
+                .map_err(|err| format!("unable to sign: {}", err))
+        })
+        .and_then(|signature| normalize_signature(&signature))
+        .map(Signature::new)
+        .map_err(SigningError::Internal)
+    }
+
+    fn public_key(&self) -> Result<PublicKey, SigningError> {
+        Ok(self.public_key.clone())
+    }
+
+    fn clone_box(&self) -> Box<dyn Signer> {
+        Box::new(self.clone())
+    }
+}
+
+fn find_objects(
+    ctx: &Ctx,
+    session: CK_SESSION_HANDLE,
+    template: &[CK_ATTRIBUTE],
+) -> Result<Vec<CK_OBJECT_HANDLE>, String> {
+    ctx.find_objects_init(session, template)
+        .map_err(|err| format!("unable to search for keys: {}", err))?;
+    let objects = ctx
+        .find_objects(session, 2)
+        .map_err(|err| format!("unable to search for keys: {}", err));
+    ctx.find_objects_final(session)
+        .map_err(|err| format!("unable to finish searching for keys: {}", err))?;
+    objects
+}
+
+fn get_attribute(
+    ctx: &Ctx,
+    session: CK_SESSION_HANDLE,
+    object: CK_OBJECT_HANDLE,
+    attribute: CK_ATTRIBUTE_TYPE,
+) -> Result<Vec<u8>, String> {
+    let read_error = |err| format!("unable to read key attribute {:#x}: {}", attribute, err);
+
+    // The first call returns the length of the value, the second the value itself
+    let mut template = vec![CK_ATTRIBUTE::new(attribute)];
+    ctx.get_attribute_value(session, object, &mut template)
+        .map_err(read_error)?;
+    let value = vec![0; template[0].ulValueLen as usize];
+    let mut template = vec![CK_ATTRIBUTE::new(attribute).with_bytes(&value)];
+    ctx.get_attribute_value(session, object, &mut template)
+        .map_err(read_error)?;
+
+    Ok(template[0].get_bytes())
+}
+
+/// Converts a PKCS#11 `CKA_EC_POINT`, an uncompressed point that may be wrapped in a DER octet
+/// string, to the 33-byte compressed form used for Splinter public keys.
+fn compress_ec_point(ec_point: &[u8]) -> Result<Vec<u8>, String> {
+    let point = match ec_point {
+        [0x04, 0x41, point @ ..] if point.len() == 65 => point,
+        point => point,
+    };
+
+    match point {
+        [0x04, x_and_y @ ..] if x_and_y.len() == 64 => {
+            let (x, y) = x_and_y.split_at(32);
+            let mut compressed = vec![0x02 | (y[31] & 1)];
+            compressed.extend_from_slice(x);
+            Ok(compressed)
+        }
+        _ => Err("the HSM returned an invalid public key".into()),
+    }
+}
+
+/// Converts an ECDSA signature to the low-S form: if `s` is more than half the curve order, it is
+/// replaced with `order - s`.
+fn normalize_signature(signature: &[u8]) -> Result<Vec<u8>, String> {
+    if signature.len() != 64 {
+        return Err(format!(
+            "the HSM returned a signature of {} bytes; expected 64",
+            signature.len()
+        ));
+    }
+
+    let to_string = |err: openssl::error::ErrorStack| err.to_string();
+    let order = BigNum::from_hex_str(SECP256K1_ORDER).map_err(to_string)?;
+    let mut half_order = BigNum::new().map_err(to_string)?;
+    half_order.rshift1(&order).map_err(to_string)?;
+
+    let (r, s) = signature.split_at(32);
+    let s = BigNum::from_slice(s).map_err(to_string)?;
+    if s <= half_order {
+        return Ok(signature.to_vec());
+    }
+
+    let mut low_s = BigNum::new().map_err(to_string)?;
+    low_s.checked_sub(&order, &s).map_err(to_string)?;
+    let low_s = low_s.to_vec();
+
+    let mut normalized = r.to_vec();
+    normalized.resize(64 - low_s.len(), 0);
+    normalized.extend_from_slice(&low_s);
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that an uncompressed point, either bare or wrapped in a DER octet string, is
+    /// compressed with the prefix matching the parity of y, and that invalid points are rejected.
+    #[test]
+    fn compress_point() {
+        let mut point = vec![0x04];
+        point.extend_from_slice(&[0xaa; 32]);
+        point.extend_from_slice(&[0xbb; 32]);
+
+        let mut expected = vec![0x03];
+        expected.extend_from_slice(&[0xaa; 32]);
+        assert_eq!(compress_ec_point(&point), Ok(expected.clone()));
+
+        let mut wrapped = vec![0x04, 0x41];
+        wrapped.extend_from_slice(&point);
+        assert_eq!(compress_ec_point(&wrapped), Ok(expected));
+
+        point[64] = 0xbc;
+        assert_eq!(compress_ec_point(&point).map(|key| key[0]), Ok(0x02));
+
+        assert!(compress_ec_point(&point[..64]).is_err());
+    }
+
+    /// Verify that signatures with a high S value are converted to the low-S form, and that
+    /// signatures with a low S value are unchanged.
+    #[test]
+    fn normalize_high_s() {
+        let mut signature = vec![0x11; 32];
+        signature.extend_from_slice(&[0x22; 32]);
+        assert_eq!(normalize_signature(&signature), Ok(signature.clone()));
+
+        // order - 1 is a high S value, and normalizes to 1
+        let mut high = vec![0x11; 32];
+        high.extend_from_slice(
+            &BigNum::from_hex_str(SECP256K1_ORDER)
+                .and_then(|order| {
+                    let mut one = BigNum::new()?;
+                    one.set_word(1)?;
+                    let mut s = BigNum::new()?;
+                    s.checked_sub(&order, &one)?;
+                    Ok(s.to_vec())
+                })
+                .expect("Failed to compute high S"),
+        );
+        let mut expected = vec![0x11; 32];
+        expected.extend_from_slice(&[0; 31]);
+        expected.push(1);
+        assert_eq!(normalize_signature(&high), Ok(expected));
+
+        assert!(normalize_signature(&signature[..63]).is_err());
+    }
+}
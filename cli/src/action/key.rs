@@ -0,0 +1,591 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the `splinter key` subcommands for managing the keys in a key directory.
+
+use std::fs::{create_dir_all, read_dir, rename, OpenOptions};
+use std::io::prelude::*;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ArgMatches;
+use cylinder::{secp256k1::Secp256k1Context, Context};
+use openssl::sha::sha256;
+#[cfg(feature = "admin-key-rotation")]
+use splinter::admin::key_rotation::{remove_retired_key, rotate_node_key};
+#[cfg(feature = "signing-ed25519")]
+use splinter::signing::SigningAlgorithm;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+use super::keygen::{create_key_pair, get_key_dir};
+use super::{
+    msg_from_io_error, print_table, read_private_key, Action, DEFAULT_SPLINTER_REST_API_URL,
+    SPLINTER_REST_API_URL_ENV,
+};
+
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+
+/// The name of the sub-directory of the key directory that holds archived keys.
+const ARCHIVE_DIR: &str = "archive";
+
+pub struct KeyListAction;
+
+impl Action for KeyListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let key_dir = get_key_dir(args)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let list_dir = if args.is_present("archived") {
+            key_dir.join(ARCHIVE_DIR)
+        } else {
+            key_dir
+        };
+
+        let mut data = vec![vec![
+            "NAME".to_string(),
+            "FINGERPRINT".to_string(),
+            "PRIVATE KEY".to_string(),
+        ]];
+        for (name, public_key) in list_public_keys(&list_dir)? {
+            let has_private_key = list_dir.join(&name).with_extension("priv").exists();
+            data.push(vec![
+                name,
+                fingerprint(&public_key)?,
+                if has_private_key { "yes" } else { "no" }.to_string(),
+            ]);
+        }
+
+        if format == "csv" {
+            for row in data {
+                println!("{}", row.join(","))
+            }
+        } else {
+            print_table(data);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct KeyRotateAction;
+
+impl Action for KeyRotateAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let key_name = args
+            .value_of("key-name")
+            .map(String::from)
+            .unwrap_or_else(whoami::username);
+        let key_dir = get_key_dir(args)?;
+
+        // The node is fetched before the key pair is rotated, so the rotation is not started if
+        // the key cannot be published
+        #[cfg(feature = "admin-key-rotation")]
+        let publish = if args.is_present("publish") {
+            let old_public_key = read_public_key(&key_dir, &key_name)?;
+            let client = build_rest_client(args)?;
+            let node_id = match args.value_of("node_id") {
+                Some(node_id) => node_id.to_string(),
+                None => client.get_node_status()?.node_id,
+            };
+            let node = client.fetch_registry_node(&node_id)?.ok_or_else(|| {
+                CliError::EnvironmentError(format!("Node '{}' not found in the registry", node_id))
+            })?;
+            if !node.has_key(&old_public_key) {
+                return Err(CliError::EnvironmentError(format!(
+                    "Key {} is not registered for node '{}'",
+                    fingerprint(&old_public_key)?,
+                    node_id
+                )));
+            }
+            Some((client, node, old_public_key))
+        } else {
+            None
+        };
+
+        let (archived_name, public_key) = rotate_key_pair(&key_dir, &key_name)?;
+
+        info!(
+            "Rotated key '{}'; the previous key was archived as '{}'",
+            key_name, archived_name
+        );
+        info!("New public key fingerprint: {}", fingerprint(&public_key)?);
+
+        #[cfg(feature = "admin-key-rotation")]
+        {
+            if let Some((client, mut node, old_public_key)) = publish {
+                rotate_node_key(&mut node, &old_public_key, &public_key, SystemTime::now())
+                    .map_err(|err| CliError::ActionError(err.to_string()))
+                    .and_then(|_| client.update_registry_node(&node))
+                    .map_err(|err| {
+                        CliError::ActionError(format!(
+                            "Rotated key '{}' locally, but failed to publish it for node '{}': {}",
+                            key_name, node.identity, err
+                        ))
+                    })?;
+                info!(
+                    "Published key {} for node '{}'; key {} is retired",
+                    fingerprint(&public_key)?,
+                    node.identity,
+                    fingerprint(&old_public_key)?
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct KeyExportPublicAction;
+
+impl Action for KeyExportPublicAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let key_name = args
+            .value_of("key-name")
+            .map(String::from)
+            .unwrap_or_else(whoami::username);
+        let key_dir = get_key_dir(args)?;
+
+        let public_key = read_public_key(&key_dir, &key_name)?;
+
+        match args.value_of("output") {
+            Some(output) => {
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .mode(0o644)
+                    .open(output)
+                    .map_err(|err| {
+                        CliError::EnvironmentError(format!(
+                            "Failed to open output file '{}': {}",
+                            output,
+                            msg_from_io_error(err)
+                        ))
+                    })?;
+                writeln!(file, "{}", public_key).map_err(|err| {
+                    CliError::ActionError(format!(
+                        "Failed to write to output file '{}': {}",
+                        output,
+                        msg_from_io_error(err)
+                    ))
+                })?;
+                info!("Exported public key '{}' to {}", key_name, output);
+            }
+            None => println!("{}", public_key),
+        }
+
+        Ok(())
+    }
+}
+
+pub struct KeyRevokeAction;
+
+impl Action for KeyRevokeAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let key_dir = get_key_dir(args)?;
+
+        let (key_name, public_key) = match args.value_of("public_key") {
+            Some(public_key) => (None, public_key.to_string()),
+            None => {
+                let key_name = args
+                    .value_of("key-name")
+                    .map(String::from)
+                    .unwrap_or_else(whoami::username);
+                let public_key = read_public_key(&key_dir, &key_name)?;
+                (Some(key_name), public_key)
+            }
+        };
+
+        let client = build_rest_client(args)?;
+
+        let node_id = match args.value_of("node_id") {
+            Some(node_id) => node_id.to_string(),
+            None => client.get_node_status()?.node_id,
+        };
+
+        let mut node = client.fetch_registry_node(&node_id)?.ok_or_else(|| {
+            CliError::EnvironmentError(format!("Node '{}' not found in the registry", node_id))
+        })?;
+
+        // A retired key is revoked by ending its overlap period immediately
+        #[cfg(feature = "admin-key-rotation")]
+        {
+            if !node.has_key(&public_key)
+                && remove_retired_key(&mut node, &public_key)
+                    .map_err(|err| CliError::ActionError(err.to_string()))?
+            {
+                client.update_registry_node(&node)?;
+                info!(
+                    "Revoked retired key {} for node '{}'",
+                    fingerprint(&public_key)?,
+                    node_id
+                );
+                return Ok(());
+            }
+        }
+
+        if !node.keys.contains(&public_key) {
+            return Err(CliError::EnvironmentError(format!(
+                "Key {} is not registered for node '{}'",
+                fingerprint(&public_key)?,
+                node_id
+            )));
+        }
+        if node.keys.len() == 1 {
+            return Err(CliError::EnvironmentError(format!(
+                "Key {} is the only key registered for node '{}'; add a new key to the node \
+                 before revoking this one",
+                fingerprint(&public_key)?,
+                node_id
+            )));
+        }
+
+        node.keys.retain(|key| key != &public_key);
+        client.update_registry_node(&node)?;
+
+        info!(
+            "Revoked key {} for node '{}'",
+            fingerprint(&public_key)?,
+            node_id
+        );
+
+        if let Some(key_name) = key_name {
+            if !args.is_present("keep_local") {
+                let archived_name = archive_key_pair(&key_dir, &key_name, "revoked")?;
+                info!("Archived revoked key '{}' as '{}'", key_name, archived_name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a REST client for the node at the `url` argument, authorized with the
+/// `private_key_file` argument.
+fn build_rest_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let url = args
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(url);
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
+
+/// Returns the name and public key of every public key file in the given directory, sorted by
+/// name.
+fn list_public_keys(dir: &Path) -> Result<Vec<(String, String)>, CliError> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries = read_dir(dir).map_err(|err| {
+        CliError::EnvironmentError(format!(
+            "Failed to read key directory '{}': {}",
+            dir.display(),
+            msg_from_io_error(err)
+        ))
+    })?;
+
+    let mut keys = vec![];
+    for entry in entries {
+        let path = entry
+            .map_err(|err| {
+                CliError::EnvironmentError(format!(
+                    "Failed to read key directory '{}': {}",
+                    dir.display(),
+                    msg_from_io_error(err)
+                ))
+            })?
+            .path();
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some("pub") {
+            continue;
+        }
+
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        keys.push((name, read_key_file(&path)?));
+    }
+
+    keys.sort_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b));
+
+    Ok(keys)
+}
+
+/// Generates a new key pair with the given name and archives the existing pair.
+///
+/// The new key pair is written to temporary files first, so the existing key pair is only
+/// archived once the replacement has been successfully created. Returns the name of the archived
+/// key pair and the new public key.
+fn rotate_key_pair(key_dir: &Path, key_name: &str) -> Result<(String, String), CliError> {
+    let private_key_path = key_dir.join(key_name).with_extension("priv");
+    let public_key_path = key_dir.join(key_name).with_extension("pub");
+
+    if !private_key_path.exists() || !public_key_path.exists() {
+        return Err(CliError::EnvironmentError(format!(
+            "Key pair '{}' not found in {}",
+            key_name,
+            key_dir.display()
+        )));
+    }
+
+    let new_private_key_path = key_dir.join(format!(".{}.priv.new", key_name));
+    let new_public_key_path = key_dir.join(format!(".{}.pub.new", key_name));
+
+    // Keep the algorithm of the key pair being replaced
+    let context = context_for_public_key(&read_public_key(key_dir, key_name)?)?;
+
+    let public_key = create_key_pair(
+        &*context,
+        key_dir,
+        new_private_key_path.clone(),
+        new_public_key_path.clone(),
+        true,
+        true,
+    )?;
+
+    let archived_name = archive_key_pair(key_dir, key_name, "rotated")?;
+
+    if let Err(err) = rename(&new_private_key_path, &private_key_path)
+        .and_then(|_| rename(&new_public_key_path, &public_key_path))
+    {
+        // Attempt to restore the archived key pair so the key directory is left unchanged
+        let archive_dir = key_dir.join(ARCHIVE_DIR);
+        let _ = rename(
+            archive_dir.join(&archived_name).with_extension("priv"),
+            &private_key_path,
+        );
+        let _ = rename(
+            archive_dir.join(&archived_name).with_extension("pub"),
+            &public_key_path,
+        );
+        return Err(CliError::EnvironmentError(format!(
+            "Failed to move new key pair '{}' into place: {}",
+            key_name,
+            msg_from_io_error(err)
+        )));
+    }
+
+    Ok((
+        archived_name,
+        public_key.iter().map(|b| format!("{:02x}", b)).collect(),
+    ))
+}
+
+/// Moves the key pair with the given name into the archive directory.
+///
+/// The archived key pair is named `<key_name>-<reason>-<unix timestamp>`; this name is returned.
+fn archive_key_pair(key_dir: &Path, key_name: &str, reason: &str) -> Result<String, CliError> {
+    let archive_dir = key_dir.join(ARCHIVE_DIR);
+    create_dir_all(&archive_dir).map_err(|err| {
+        CliError::EnvironmentError(format!(
+            "Failed to create archive directory '{}': {}",
+            archive_dir.display(),
+            msg_from_io_error(err)
+        ))
+    })?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| CliError::EnvironmentError(format!("Invalid system time: {}", err)))?
+        .as_secs();
+    let archived_name = format!("{}-{}-{}", key_name, reason, timestamp);
+
+    let moves: Vec<(PathBuf, PathBuf)> = ["priv", "pub"]
+        .iter()
+        .map(|ext| {
+            (
+                key_dir.join(key_name).with_extension(ext),
+                archive_dir.join(&archived_name).with_extension(ext),
+            )
+        })
+        .filter(|(from, _)| from.exists())
+        .collect();
+
+    for (from, to) in moves {
+        rename(&from, &to).map_err(|err| {
+            CliError::EnvironmentError(format!(
+                "Failed to archive key file '{}': {}",
+                from.display(),
+                msg_from_io_error(err)
+            ))
+        })?;
+    }
+
+    Ok(archived_name)
+}
+
+/// Reads the public key with the given name from the key directory.
+fn read_public_key(key_dir: &Path, key_name: &str) -> Result<String, CliError> {
+    read_key_file(&key_dir.join(key_name).with_extension("pub"))
+}
+
+fn read_key_file(path: &Path) -> Result<String, CliError> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| CliError::EnvironmentError(format!("Invalid path: {:?}", path)))?;
+    read_private_key(path_str)
+}
+
+/// Returns the signing context for the algorithm of the given hex-encoded public key.
+///
+/// Ed25519 public keys are 32 bytes, while compressed secp256k1 public keys are 33 bytes.
+fn context_for_public_key(public_key: &str) -> Result<Box<dyn Context>, CliError> {
+    let _len = parse_hex(public_key)?.len();
+
+    #[cfg(feature = "signing-ed25519")]
+    {
+        if _len == 32 {
+            return Ok(SigningAlgorithm::Ed25519.new_context());
+        }
+    }
+
+    Ok(Box::new(Secp256k1Context::new()))
+}
+
+/// Computes the fingerprint of a hex-encoded public key.
+///
+/// The fingerprint is the first 16 bytes of the SHA-256 digest of the key, formatted as
+/// colon-separated hex pairs.
+pub(crate) fn fingerprint(public_key: &str) -> Result<String, CliError> {
+    let bytes = parse_hex(public_key)?;
+    Ok(sha256(&bytes)[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, CliError> {
+    if hex.len() % 2 != 0 {
+        return Err(CliError::ActionError(format!(
+            "Invalid key '{}': odd number of hex digits",
+            hex
+        )));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| {
+                CliError::ActionError(format!("Invalid key '{}': not a valid hex string", hex))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::remove_dir_all;
+
+    /// Creates an empty, uniquely named key directory for a test.
+    fn test_key_dir(test_name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("splinter-key-{}-{}", test_name, std::process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).expect("Failed to create key dir");
+        dir
+    }
+
+    /// Verify that fingerprints are stable, formatted as colon-separated hex pairs, and that
+    /// invalid keys are rejected.
+    #[test]
+    fn fingerprint_format() {
+        let fingerprint_a = fingerprint("0102").expect("Failed to fingerprint key");
+        let fingerprint_b = fingerprint("0102").expect("Failed to fingerprint key");
+
+        assert_eq!(fingerprint_a, fingerprint_b);
+        assert_eq!(fingerprint_a.split(':').count(), 16);
+        assert!(fingerprint("012").is_err());
+        assert!(fingerprint("zz").is_err());
+    }
+
+    /// Verify that rotating a key pair replaces the active pair and archives the previous one.
+    ///
+    /// 1. Create a key pair and rotate it
+    /// 2. Verify that the active public key changed
+    /// 3. Verify that the previous public key is listed in the archive directory
+    #[test]
+    fn rotate_archives_previous_key() {
+        let key_dir = test_key_dir("rotate");
+
+        create_key_pair(
+            &Secp256k1Context::new(),
+            &key_dir,
+            key_dir.join("test.priv"),
+            key_dir.join("test.pub"),
+            false,
+            false,
+        )
+        .expect("Failed to create key pair");
+        let old_public_key = read_public_key(&key_dir, "test").expect("Failed to read key");
+
+        let (archived_name, new_public_key) =
+            rotate_key_pair(&key_dir, "test").expect("Failed to rotate key pair");
+
+        assert_ne!(old_public_key, new_public_key);
+        assert_eq!(
+            read_public_key(&key_dir, "test").expect("Failed to read key"),
+            new_public_key
+        );
+
+        let archived = list_public_keys(&key_dir.join(ARCHIVE_DIR)).expect("Failed to list");
+        assert_eq!(archived, vec![(archived_name, old_public_key)]);
+
+        let active = list_public_keys(&key_dir).expect("Failed to list");
+        assert_eq!(active, vec![("test".to_string(), new_public_key)]);
+
+        remove_dir_all(&key_dir).expect("Failed to remove key dir");
+    }
+
+    /// Verify that rotating a key pair that does not exist fails without creating any files.
+    #[test]
+    fn rotate_missing_key() {
+        let key_dir = test_key_dir("rotate-missing");
+
+        assert!(rotate_key_pair(&key_dir, "missing").is_err());
+        assert!(list_public_keys(&key_dir)
+            .expect("Failed to list")
+            .is_empty());
+
+        remove_dir_all(&key_dir).expect("Failed to remove key dir");
+    }
+}
@@ -24,6 +24,8 @@ use std::os::unix::fs::MetadataExt;
 
 use clap::ArgMatches;
 use cylinder::{secp256k1::Secp256k1Context, Context};
+#[cfg(feature = "signing-ed25519")]
+use splinter::signing::SigningAlgorithm;
 
 use crate::error::CliError;
 
@@ -42,18 +44,7 @@ impl Action for KeyGenAction {
             .map(String::from)
             .unwrap_or_else(whoami::username);
 
-        let key_dir = if let Some(dir) = args.value_of("key_dir") {
-            PathBuf::from(dir)
-        } else if args.is_present("system") {
-            PathBuf::from(SYSTEM_KEY_PATH)
-        } else {
-            dirs::home_dir()
-                .map(|mut p| {
-                    p.push(".splinter/keys");
-                    p
-                })
-                .ok_or_else(|| CliError::EnvironmentError("Home directory not found".into()))?
-        };
+        let key_dir = get_key_dir(args)?;
 
         create_dir_all(key_dir.as_path()).map_err(|err| {
             CliError::EnvironmentError(format!("Failed to create keys directory: {}", err))
@@ -62,7 +53,10 @@ impl Action for KeyGenAction {
         let private_key_path = key_dir.join(&key_name).with_extension("priv");
         let public_key_path = key_dir.join(&key_name).with_extension("pub");
 
+        let context = get_signing_context(args)?;
+
         create_key_pair(
+            &*context,
             &key_dir,
             private_key_path,
             public_key_path,
@@ -74,10 +68,46 @@ impl Action for KeyGenAction {
     }
 }
 
-/// Creates a public/private key pair.
+/// Determines the key directory from the `key_dir` and `system` arguments.
+///
+/// Defaults to `$HOME/.splinter/keys` if neither argument is provided.
+pub fn get_key_dir(args: &ArgMatches) -> Result<PathBuf, CliError> {
+    if let Some(dir) = args.value_of("key_dir") {
+        Ok(PathBuf::from(dir))
+    } else if args.is_present("system") {
+        Ok(PathBuf::from(SYSTEM_KEY_PATH))
+    } else {
+        dirs::home_dir()
+            .map(|mut p| {
+                p.push(".splinter/keys");
+                p
+            })
+            .ok_or_else(|| CliError::EnvironmentError("Home directory not found".into()))
+    }
+}
+
+/// Determines the signing context from the `algorithm` argument.
+///
+/// Defaults to secp256k1 if the argument is not provided.
+pub fn get_signing_context(_args: &ArgMatches) -> Result<Box<dyn Context>, CliError> {
+    #[cfg(feature = "signing-ed25519")]
+    {
+        if let Some(algorithm) = _args.value_of("algorithm") {
+            return algorithm
+                .parse::<SigningAlgorithm>()
+                .map(|algorithm| algorithm.new_context())
+                .map_err(|err| CliError::ActionError(err.to_string()));
+        }
+    }
+
+    Ok(Box::new(Secp256k1Context::new()))
+}
+
+/// Creates a public/private key pair using the given signing context.
 ///
 /// Returns the public key in hex, if successful.
 pub fn create_key_pair(
+    context: &dyn Context,
     key_dir: &Path,
     private_key_path: PathBuf,
     public_key_path: PathBuf,
@@ -99,8 +129,6 @@ pub fn create_key_pair(
         }
     }
 
-    let context = Secp256k1Context::new();
-
     let private_key = context.new_random_private_key();
     let public_key = context
         .get_public_key(&private_key)
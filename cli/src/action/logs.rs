@@ -0,0 +1,192 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::thread;
+use std::time::Duration;
+
+use clap::ArgMatches;
+use reqwest::{blocking::Client, StatusCode};
+use serde::Deserialize;
+
+use crate::error::CliError;
+
+use super::api::{ServerError, SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+use super::{Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+
+const DEFAULT_LINES: usize = 100;
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+const SPLINTERD_MISSING_LOGS: &str = "The logs endpoint was not found. The node has not enabled \
+                                      log retrieval.";
+
+pub struct NodeLogsAction;
+
+impl Action for NodeLogsAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let lines = args
+            .value_of("lines")
+            .map(|lines| {
+                lines.parse::<usize>().map_err(|_| {
+                    CliError::ActionError(format!("'lines' must be a number: {}", lines))
+                })
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_LINES);
+
+        let mut query = LogQuery {
+            since: None,
+            level: args.value_of("level"),
+            module: args.value_of("module"),
+            limit: Some(lines),
+        };
+
+        let client = build_client(args)?;
+
+        let logs = client.list_logs(&query)?;
+        logs.data.iter().for_each(print_entry);
+
+        if args.is_present("follow") {
+            // After the initial entries, every new entry is displayed
+            query.limit = None;
+            query.since = Some(logs.last_seq);
+            loop {
+                thread::sleep(FOLLOW_INTERVAL);
+                let mut logs = client.list_logs(&query)?;
+                if Some(logs.last_seq) < query.since {
+                    // The node was restarted, so its sequence numbers started over
+                    query.since = None;
+                    logs = client.list_logs(&query)?;
+                }
+                logs.data.iter().for_each(print_entry);
+                query.since = Some(logs.last_seq);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn print_entry(entry: &LogEntry) {
+    println!(
+        "[{}.{:03}] {} [{}] {}",
+        entry.timestamp / 1000,
+        entry.timestamp % 1000,
+        entry.level,
+        entry.module,
+        entry.message
+    );
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let url = args
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(url);
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
+
+struct LogQuery<'a> {
+    since: Option<u64>,
+    level: Option<&'a str>,
+    module: Option<&'a str>,
+    limit: Option<usize>,
+}
+
+impl<'a> LogQuery<'a> {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![];
+        if let Some(since) = self.since {
+            params.push(("since", since.to_string()));
+        }
+        if let Some(level) = self.level {
+            params.push(("level", level.to_string()));
+        }
+        if let Some(module) = self.module {
+            params.push(("module", module.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        params
+    }
+}
+
+#[derive(Deserialize)]
+struct LogListSlice {
+    data: Vec<LogEntry>,
+    last_seq: u64,
+}
+
+#[derive(Deserialize)]
+struct LogEntry {
+    timestamp: u64,
+    level: String,
+    module: String,
+    message: String,
+}
+
+impl SplinterRestClient {
+    /// Lists the node's most recent log entries that match the query.
+    fn list_logs(&self, query: &LogQuery) -> Result<LogListSlice, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .get(&format!("{}/admin/logs", self.url))
+            .query(&query.params());
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to fetch logs: {}", err)))
+            .and_then(|res| match res.status() {
+                StatusCode::OK => res.json::<LogListSlice>().map_err(|_| {
+                    CliError::ActionError(
+                        "Request was successful, but received an invalid response".into(),
+                    )
+                }),
+                StatusCode::NOT_FOUND => Err(CliError::ActionError(SPLINTERD_MISSING_LOGS.into())),
+                status => match res.json::<ServerError>() {
+                    Ok(ServerError { message }) => Err(CliError::ActionError(format!(
+                        "Failed to fetch logs: {}",
+                        message
+                    ))),
+                    Err(_) => Err(CliError::ActionError(format!(
+                        "Request to fetch logs failed with status code '{}', but error response \
+                         was not valid",
+                        status
+                    ))),
+                },
+            })
+    }
+}
@@ -15,14 +15,39 @@
 
 pub mod admin;
 mod api;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "node-bootstrap")]
+pub mod bootstrap;
+#[cfg(feature = "message-capture")]
+pub mod capture;
 pub mod certs;
 pub mod circuit;
+#[cfg(feature = "admin-vote-cosigning")]
+pub mod cosign;
 #[cfg(feature = "database")]
 pub mod database;
+#[cfg(feature = "admin-vote-delegation")]
+pub mod delegation;
 #[cfg(feature = "health")]
 pub mod health;
+#[cfg(feature = "hsm-signing")]
+mod hsm;
+pub mod key;
 pub mod keygen;
+#[cfg(feature = "log-retrieval")]
+pub mod logs;
+#[cfg(feature = "node-challenge")]
+pub mod node;
+#[cfg(feature = "peer-management")]
+pub mod peer;
 pub mod registry;
+#[cfg(feature = "scabbard-verify")]
+pub mod scabbard;
+#[cfg(feature = "admin-allowed-signers")]
+pub mod signer;
+#[cfg(feature = "biome-user-admin")]
+pub mod user;
 
 use std::collections::HashMap;
 use std::ffi::CString;
@@ -32,7 +57,8 @@ use std::path::Path;
 
 use clap::ArgMatches;
 #[cfg(feature = "splinter-cli-jwt")]
-use cylinder::{jwt::JsonWebTokenBuilder, load_user_key, secp256k1::Secp256k1Context, Context};
+use cylinder::{jwt::JsonWebTokenBuilder, load_user_key};
+use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey, Signer};
 
 use super::error::CliError;
 
@@ -122,6 +148,29 @@ fn read_private_key(file_name: &str) -> Result<String, CliError> {
     Ok(key)
 }
 
+/// Returns the signer for admin payloads: the key held in an HSM if the `hsm_slot` argument is
+/// present, otherwise the secp256k1 private key read from `key_file`.
+fn load_payload_signer(args: &ArgMatches, key_file: &str) -> Result<Box<dyn Signer>, CliError> {
+    if args.is_present("hsm_slot") {
+        #[cfg(feature = "hsm-signing")]
+        {
+            return Ok(Box::new(hsm::HsmSigner::from_args(args)?));
+        }
+        #[cfg(not(feature = "hsm-signing"))]
+        {
+            return Err(CliError::ActionError(
+                "HSM signing is not enabled in this build".into(),
+            ));
+        }
+    }
+
+    let private_key = PrivateKey::new_from_hex(&read_private_key(key_file)?).map_err(|err| {
+        CliError::ActionError(format!("Invalid secp256k1 private key provided: {}", err))
+    })?;
+
+    Ok(Secp256k1Context::new().new_signer(private_key))
+}
+
 fn msg_from_io_error(err: IoError) -> String {
     match err.kind() {
         ErrorKind::NotFound => "File not found".into(),
@@ -131,6 +180,39 @@ fn msg_from_io_error(err: IoError) -> String {
     }
 }
 
+// Takes a vec of vecs of strings. The first vec should include the title of the columns.
+// The max length of each column is calculated and is used as the column with when printing the
+// table.
+fn print_table(table: Vec<Vec<String>>) {
+    let mut max_lengths = Vec::new();
+
+    // find the max lengths of the columns
+    for row in table.iter() {
+        for (i, col) in row.iter().enumerate() {
+            if let Some(length) = max_lengths.get_mut(i) {
+                if col.len() > *length {
+                    *length = col.len()
+                }
+            } else {
+                max_lengths.push(col.len())
+            }
+        }
+    }
+
+    // print each row with correct column size
+    for row in table.iter() {
+        let mut col_string = String::from("");
+        for (i, len) in max_lengths.iter().enumerate() {
+            if let Some(value) = row.get(i) {
+                col_string += &format!("{}{} ", value, " ".repeat(*len - value.len()),);
+            } else {
+                col_string += &" ".repeat(*len);
+            }
+        }
+        println!("{}", col_string);
+    }
+}
+
 #[cfg(feature = "splinter-cli-jwt")]
 // build a signed json web token using the private key
 fn create_cylinder_jwt_auth(key_name: Option<&str>) -> Result<String, CliError> {
@@ -157,9 +239,29 @@ fn create_cylinder_jwt_auth(key_name: Option<&str>) -> Result<String, CliError>
     let context = Secp256k1Context::new();
     let signer = context.new_signer(private_key);
 
+    create_cylinder_jwt_auth_from_signer(&*signer)
+}
+
+#[cfg(feature = "splinter-cli-jwt")]
+fn create_cylinder_jwt_auth_from_signer(signer: &dyn Signer) -> Result<String, CliError> {
     let encoded_token = JsonWebTokenBuilder::new()
-        .build(&*signer)
+        .build(signer)
         .map_err(|err| CliError::ActionError(format!("failed to build json web token: {}", err)))?;
 
     Ok(format!("Bearer Cylinder:{}", encoded_token))
 }
+
+/// Creates the authorization for submitting admin payloads. When the payloads are signed by a key
+/// held in an HSM, the token is signed by the same key, so no key file is required.
+#[cfg(feature = "splinter-cli-jwt")]
+fn create_payload_jwt_auth(
+    args: &ArgMatches,
+    key_name: Option<&str>,
+    signer: &dyn Signer,
+) -> Result<String, CliError> {
+    if args.is_present("hsm_slot") {
+        create_cylinder_jwt_auth_from_signer(signer)
+    } else {
+        create_cylinder_jwt_auth(key_name)
+    }
+}
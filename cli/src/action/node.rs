@@ -0,0 +1,240 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::ArgMatches;
+use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
+use reqwest::{blocking::Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CliError;
+
+use super::api::SplinterRestClientBuilder;
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+use super::{Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+
+const NONCE_LENGTH: usize = 32;
+
+const SPLINTERD_MISSING_NODE_CHALLENGE: &str = "The node challenge endpoint was not found. \
+                                                The node has not enabled this feature or is not \
+                                                configured with a node key.";
+
+#[derive(Serialize)]
+struct ChallengeRequest<'a> {
+    nonce: &'a str,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ChallengeResponse {
+    pub node_id: String,
+    pub public_key: String,
+    signature: String,
+}
+
+pub struct NodeVerifyAction;
+
+impl Action for NodeVerifyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let target = args
+            .value_of("target")
+            .ok_or_else(|| CliError::ActionError("'target' argument is required".into()))?;
+
+        let url = args
+            .value_of("url")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        let auth = create_cylinder_jwt_auth(args.value_of("private_key_file"))?;
+
+        let response = challenge_node(
+            target,
+            #[cfg(feature = "splinter-cli-jwt")]
+            &auth,
+        )?;
+
+        if let Some(expected_node_id) = args.value_of("node_id") {
+            if response.node_id != expected_node_id {
+                return Err(CliError::ActionError(format!(
+                    "Node at {} identified itself as {}, not {}",
+                    target, response.node_id, expected_node_id
+                )));
+            }
+        }
+
+        #[allow(unused_mut)]
+        let mut builder = SplinterRestClientBuilder::new().with_url(url);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            builder = builder.with_auth(auth);
+        }
+
+        let node = builder
+            .build()?
+            .fetch_registry_node(&response.node_id)?
+            .ok_or_else(|| {
+                CliError::ActionError(format!(
+                    "Node {} is not in the local registry",
+                    response.node_id
+                ))
+            })?;
+
+        if !node
+            .keys
+            .iter()
+            .any(|key| key.eq_ignore_ascii_case(&response.public_key))
+        {
+            return Err(CliError::ActionError(format!(
+                "Node {} signed the challenge with key {}, which is not listed in its registry \
+                 entry",
+                response.node_id, response.public_key
+            )));
+        }
+
+        println!(
+            "Verified node {} at {} holds registry key {}",
+            response.node_id, target, response.public_key
+        );
+
+        Ok(())
+    }
+}
+
+/// Challenges the node at the given REST API URL to sign a random nonce, returning the node's
+/// response once its signature has been verified.
+pub(crate) fn challenge_node(
+    target: &str,
+    #[cfg(feature = "splinter-cli-jwt")] auth: &str,
+) -> Result<ChallengeResponse, CliError> {
+    let nonce = new_nonce()?;
+
+    // Allowing unused_mut because request must be mutable if experimental feature
+    // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+    #[allow(unused_mut)]
+    let mut request = Client::new()
+        .post(&format!("{}/node/challenge", target))
+        .json(&ChallengeRequest { nonce: &nonce });
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        request = request.header("Authorization", auth);
+    }
+
+    let response = request
+        .send()
+        .map_err(|_| CliError::ActionError(format!("Unable to contact the node at {}", target)))
+        .and_then(|res| match res.status() {
+            StatusCode::OK => res.json::<ChallengeResponse>().map_err(|_| {
+                CliError::ActionError("The node failed to send a valid response".into())
+            }),
+            StatusCode::NOT_FOUND => Err(CliError::ActionError(
+                SPLINTERD_MISSING_NODE_CHALLENGE.into(),
+            )),
+            status_code => Err(CliError::ActionError(format!(
+                "The node failed to respond({}).",
+                status_code.as_u16()
+            ))),
+        })?;
+
+    if !verify_challenge(&response, &nonce)? {
+        return Err(CliError::ActionError(format!(
+            "Node {} returned an invalid signature",
+            response.node_id
+        )));
+    }
+
+    Ok(response)
+}
+
+fn new_nonce() -> Result<String, CliError> {
+    let mut bytes = [0u8; NONCE_LENGTH];
+    openssl::rand::rand_bytes(&mut bytes)
+        .map_err(|err| CliError::EnvironmentError(format!("Unable to generate nonce: {}", err)))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Returns the bytes a node signs in response to a challenge; this must match the message
+/// signed by splinterd.
+fn challenge_message(node_id: &str, nonce: &str) -> Vec<u8> {
+    format!("splinter-node-challenge:{}:{}", node_id, nonce).into_bytes()
+}
+
+fn verify_challenge(response: &ChallengeResponse, nonce: &str) -> Result<bool, CliError> {
+    let public_key = PublicKey::new(parse_hex(&response.public_key)?);
+    let signature = Signature::new(parse_hex(&response.signature)?);
+
+    Secp256k1Context::new()
+        .new_verifier()
+        .verify(
+            &challenge_message(&response.node_id, nonce),
+            &signature,
+            &public_key,
+        )
+        .map_err(|err| CliError::ActionError(format!("Failed to verify signature: {}", err)))
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, CliError> {
+    let invalid = || CliError::ActionError(format!("Node sent an invalid hex string: {}", hex));
+
+    if hex.len() % 2 != 0 {
+        return Err(invalid());
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::PrivateKey;
+
+    const PRIVATE_KEY: &str = "2f1e7b7a130d7ba9da0068b3bb0ba1d79e7e77110302c9f746c3c2a63fe40088";
+
+    /// Verify that a signature over the challenge message verifies, and that it does not verify
+    /// for a different nonce or node ID.
+    #[test]
+    fn verify_signed_challenge() {
+        let signer = Secp256k1Context::new()
+            .new_signer(PrivateKey::new_from_hex(PRIVATE_KEY).expect("Invalid key"));
+        let signature = signer
+            .sign(&challenge_message("acme-node-000", "abcd"))
+            .expect("Failed to sign");
+        let response = ChallengeResponse {
+            node_id: "acme-node-000".into(),
+            public_key: signer.public_key().expect("No public key").as_hex(),
+            signature: signature.as_hex(),
+        };
+
+        assert!(verify_challenge(&response, "abcd").expect("Failed to verify"));
+        assert!(!verify_challenge(&response, "abce").expect("Failed to verify"));
+
+        let response = ChallengeResponse {
+            node_id: "acme-node-001".into(),
+            ..response
+        };
+        assert!(!verify_challenge(&response, "abcd").expect("Failed to verify"));
+    }
+}
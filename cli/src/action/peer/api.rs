@@ -0,0 +1,175 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::{
+    blocking::{Client, Response},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+impl SplinterRestClient {
+    /// Lists the node's connected peers.
+    pub fn list_peers(&self) -> Result<PeerListSlice, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new().get(&format!("{}/peers", self.url));
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to list peers: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<PeerListSlice>().map_err(|_| {
+                        CliError::ActionError(
+                            "Request was successful, but received an invalid response".into(),
+                        )
+                    })
+                } else {
+                    Err(request_error(res, "list peers"))
+                }
+            })
+    }
+
+    /// Fetches the connected peer with the given ID, or `None` if the node is not connected to
+    /// the peer.
+    pub fn fetch_peer(&self, peer_id: &str) -> Result<Option<Peer>, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new().get(&format!("{}/peers/{}", self.url, peer_id));
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to fetch peer: {}", err)))
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    res.json::<PeerResponse>()
+                        .map(|response| Some(response.data))
+                        .map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                } else if status == StatusCode::NOT_FOUND {
+                    Ok(None)
+                } else {
+                    Err(request_error(res, "fetch peer"))
+                }
+            })
+    }
+
+    /// Connects the node to the peer at the given endpoint.
+    ///
+    /// Returns `false` if the endpoint was already added.
+    pub fn add_peer(&self, endpoint: &str) -> Result<bool, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .post(&format!("{}/peers", self.url))
+            .json(&AddPeerRequest { endpoint });
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to add peer: {}", err)))
+            .and_then(|res| match res.status() {
+                StatusCode::ACCEPTED => Ok(true),
+                status if status.is_success() => Ok(false),
+                _ => Err(request_error(res, "add peer")),
+            })
+    }
+
+    /// Removes the peer that was added with the given endpoint.
+    pub fn remove_peer(&self, endpoint: &str) -> Result<(), CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .delete(&format!("{}/peers", self.url))
+            .query(&[("endpoint", endpoint)]);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to remove peer: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(request_error(res, "remove peer"))
+                }
+            })
+    }
+}
+
+/// Builds the error for an unsuccessful request from the server's error response.
+fn request_error(res: Response, action: &str) -> CliError {
+    let status = res.status();
+    match res.json::<ServerError>() {
+        Ok(ServerError { message }) => {
+            CliError::ActionError(format!("Failed to {}: {}", action, message))
+        }
+        Err(_) => CliError::ActionError(format!(
+            "Request to {} failed with status code '{}', but error response was not valid",
+            action, status
+        )),
+    }
+}
+
+#[derive(Serialize)]
+struct AddPeerRequest<'a> {
+    endpoint: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PeerResponse {
+    data: Peer,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Peer {
+    pub peer_id: String,
+    pub connection_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PeerListSlice {
+    pub data: Vec<Peer>,
+    /// The endpoints of the peers that were added by endpoint, which may be removed
+    pub endpoints: Vec<String>,
+}
@@ -0,0 +1,162 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod api;
+
+use clap::ArgMatches;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+use super::{print_table, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+
+pub struct PeerListAction;
+
+impl Action for PeerListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let peers = build_client(args)?.list_peers()?;
+
+        let mut data = Vec::new();
+        data.push(vec!["PEER ID".to_string(), "CONNECTION ID".to_string()]);
+        peers.data.into_iter().for_each(|peer| {
+            let connection_id = if format == "csv" {
+                peer.connection_id.unwrap_or_default()
+            } else {
+                peer.connection_id.unwrap_or_else(|| "-".to_string())
+            };
+            data.push(vec![peer.peer_id, connection_id]);
+        });
+
+        if format == "csv" {
+            for row in data {
+                println!("{}", row.join(","))
+            }
+        } else {
+            print_table(data);
+
+            if !peers.endpoints.is_empty() {
+                println!();
+                let mut endpoints = vec![vec!["ADDED ENDPOINT".to_string()]];
+                endpoints.extend(peers.endpoints.into_iter().map(|endpoint| vec![endpoint]));
+                print_table(endpoints);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct PeerShowAction;
+
+impl Action for PeerShowAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let peer_id = args
+            .value_of("peer_id")
+            .ok_or_else(|| CliError::ActionError("'peer_id' argument is required".to_string()))?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let peer = build_client(args)?
+            .fetch_peer(peer_id)?
+            .ok_or_else(|| CliError::ActionError(format!("Not connected to peer '{}'", peer_id)))?;
+
+        match format {
+            "json" => println!(
+                "{}",
+                serde_json::to_string(&peer).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format peer into json: {}",
+                    err
+                )))?
+            ),
+            "yaml" => println!(
+                "{}",
+                serde_yaml::to_string(&peer).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format peer into yaml: {}",
+                    err
+                )))?
+            ),
+            _ => {
+                println!("Peer ID: {}", peer.peer_id);
+                println!(
+                    "Connection ID: {}",
+                    peer.connection_id.as_deref().unwrap_or("-")
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct PeerAddAction;
+
+impl Action for PeerAddAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let endpoint = args
+            .value_of("endpoint")
+            .ok_or_else(|| CliError::ActionError("'endpoint' argument is required".to_string()))?;
+
+        if build_client(args)?.add_peer(endpoint)? {
+            info!(
+                "Added peer {}; the connection is established in the background",
+                endpoint
+            );
+        } else {
+            info!("Peer {} was already added", endpoint);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct PeerRemoveAction;
+
+impl Action for PeerRemoveAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let endpoint = args
+            .value_of("endpoint")
+            .ok_or_else(|| CliError::ActionError("'endpoint' argument is required".to_string()))?;
+
+        build_client(args)?.remove_peer(endpoint)?;
+        info!("Removed peer {}", endpoint);
+
+        Ok(())
+    }
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let url = args
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(url);
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
@@ -0,0 +1,158 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::{blocking::Client, StatusCode};
+use splinter::registry::Node;
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+// The registry protocol version supported by the current CLI
+const CLI_REGISTRY_PROTOCOL_VERSION: &str = "1";
+
+impl SplinterRestClient {
+    /// Fetches the node with the given identity from the node's registry.
+    pub fn fetch_registry_node(&self, identity: &str) -> Result<Option<Node>, CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .get(&format!("{}/registry/nodes/{}", self.url, identity))
+            .header("SplinterProtocolVersion", CLI_REGISTRY_PROTOCOL_VERSION);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to fetch registry node: {}", err)))
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    res.json::<Node>().map(Some).map_err(|_| {
+                        CliError::ActionError(
+                            "Request was successful, but received an invalid response".into(),
+                        )
+                    })
+                } else if status == StatusCode::NOT_FOUND {
+                    Ok(None)
+                } else {
+                    let message = res
+                        .json::<ServerError>()
+                        .map_err(|_| {
+                            CliError::ActionError(format!(
+                                "Registry node fetch request failed with status code '{}', but \
+                                 error response was not valid",
+                                status
+                            ))
+                        })?
+                        .message;
+
+                    Err(CliError::ActionError(format!(
+                        "Failed to fetch registry node: {}",
+                        message
+                    )))
+                }
+            })
+    }
+
+    /// Adds the given node to the node's registry.
+    #[cfg(feature = "node-bootstrap")]
+    pub fn create_registry_node(&self, node: &Node) -> Result<(), CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .post(&format!("{}/registry/nodes", self.url))
+            .header("SplinterProtocolVersion", CLI_REGISTRY_PROTOCOL_VERSION)
+            .json(node);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to add registry node: {}", err)))
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    Ok(())
+                } else {
+                    let message = res
+                        .json::<ServerError>()
+                        .map_err(|_| {
+                            CliError::ActionError(format!(
+                                "Registry node add request failed with status code '{}', but \
+                                 error response was not valid",
+                                status
+                            ))
+                        })?
+                        .message;
+
+                    Err(CliError::ActionError(format!(
+                        "Failed to add registry node: {}",
+                        message
+                    )))
+                }
+            })
+    }
+
+    /// Replaces the given node in the node's registry.
+    pub fn update_registry_node(&self, node: &Node) -> Result<(), CliError> {
+        // Allowing unused_mut because request must be mutable if experimental feature
+        // splinter-cli-jwt is enabled, if feature is removed unused_mut notation can be removed
+        #[allow(unused_mut)]
+        let mut request = Client::new()
+            .put(&format!("{}/registry/nodes/{}", self.url, node.identity))
+            .header("SplinterProtocolVersion", CLI_REGISTRY_PROTOCOL_VERSION)
+            .json(node);
+
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request = request.header("Authorization", &self.auth);
+        }
+
+        request
+            .send()
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to update registry node: {}", err))
+            })
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    Ok(())
+                } else {
+                    let message = res
+                        .json::<ServerError>()
+                        .map_err(|_| {
+                            CliError::ActionError(format!(
+                                "Registry node update request failed with status code '{}', but \
+                                 error response was not valid",
+                                status
+                            ))
+                        })?
+                        .message;
+
+                    Err(CliError::ActionError(format!(
+                        "Failed to update registry node: {}",
+                        message
+                    )))
+                }
+            })
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reads the splinterd nodes of a deployment from a docker-compose file or an Ansible-style INI
+//! inventory, so a registry can be built for all of them at once.
+//!
+//! In a docker-compose file, every service whose image contains `splinterd`, or that has a
+//! `splinter.rest-api-url` label, is a node. Its REST API is reached at the URL in that label, or
+//! else at the service's container name (or service name) on the port of its
+//! `--rest-api-endpoint` argument, which defaults to 8080. The `splinter.key-files` label lists
+//! extra public key files for the node, separated by commas, and `splinter.metadata.<key>` labels
+//! set the node's metadata.
+//!
+//! In an inventory, every host is a node, unless a group is given. Its REST API is reached at the
+//! URL in its `splinter_rest_api_url` variable, or else at its `ansible_host` (or host name) on
+//! port 8080. The `splinter_key_files` and `splinter_metadata_<key>` variables are read like the
+//! compose labels. Only host lines are read; `:vars` and `:children` sections are skipped.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde_yaml::Value;
+
+use crate::error::CliError;
+
+use super::super::msg_from_io_error;
+
+const DEFAULT_REST_API_PORT: &str = "8080";
+
+/// A splinterd node found in a deployment file
+#[derive(Debug, Default, PartialEq)]
+pub struct DeployedNode {
+    /// The service or host the node was found as
+    pub name: String,
+    /// The URL of the node's REST API
+    pub url: String,
+    /// Paths of public key files whose keys are added to the node's keys
+    pub key_files: Vec<String>,
+    pub metadata: HashMap<String, String>,
+}
+
+/// The format of a deployment file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeploymentFormat {
+    Compose,
+    Inventory,
+}
+
+impl FromStr for DeploymentFormat {
+    type Err = CliError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "compose" => Ok(DeploymentFormat::Compose),
+            "inventory" => Ok(DeploymentFormat::Inventory),
+            _ => Err(CliError::ActionError(format!(
+                "Invalid deployment format '{}': must be 'compose' or 'inventory'",
+                format
+            ))),
+        }
+    }
+}
+
+impl DeploymentFormat {
+    /// Guesses the format from the file's extension: YAML files are docker-compose files, and
+    /// anything else is an inventory.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => DeploymentFormat::Compose,
+            _ => DeploymentFormat::Inventory,
+        }
+    }
+}
+
+/// Reads the nodes from the given deployment file.
+///
+/// # Arguments
+///
+/// * `path` - The docker-compose file or inventory
+/// * `format` - The format of the file; guessed from its extension if not given
+/// * `group` - For inventories, only read the hosts in this group
+pub fn read_deployment(
+    path: &str,
+    format: Option<DeploymentFormat>,
+    group: Option<&str>,
+) -> Result<Vec<DeployedNode>, CliError> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        CliError::EnvironmentError(format!(
+            "Unable to read deployment file '{}': {}",
+            path,
+            msg_from_io_error(err)
+        ))
+    })?;
+
+    let nodes = match format.unwrap_or_else(|| DeploymentFormat::from_path(Path::new(path))) {
+        DeploymentFormat::Compose => parse_compose(&contents)?,
+        DeploymentFormat::Inventory => parse_inventory(&contents, group),
+    };
+
+    if nodes.is_empty() {
+        return Err(CliError::ActionError(format!(
+            "No splinterd nodes found in '{}'",
+            path
+        )));
+    }
+
+    Ok(nodes)
+}
+
+fn parse_compose(contents: &str) -> Result<Vec<DeployedNode>, CliError> {
+    let compose: Value = serde_yaml::from_str(contents)
+        .map_err(|err| CliError::ActionError(format!("Invalid docker-compose file: {}", err)))?;
+    let services = match compose.get("services").and_then(Value::as_mapping) {
+        Some(services) => services,
+        None => return Ok(vec![]),
+    };
+
+    let mut nodes = vec![];
+    for (name, service) in services {
+        let name = match name.as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        let labels = compose_labels(service);
+        let is_splinterd = service
+            .get("image")
+            .and_then(Value::as_str)
+            .map(|image| image.contains("splinterd"))
+            .unwrap_or(false);
+        if !is_splinterd && !labels.contains_key("splinter.rest-api-url") {
+            continue;
+        }
+
+        let url = match labels.get("splinter.rest-api-url") {
+            Some(url) => url.to_string(),
+            None => {
+                let host = service
+                    .get("container_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or(name);
+                let port = ["entrypoint", "command"]
+                    .iter()
+                    .filter_map(|key| service.get(*key))
+                    .find_map(rest_api_port)
+                    .unwrap_or_else(|| DEFAULT_REST_API_PORT.to_string());
+                format!("http://{}:{}", host, port)
+            }
+        };
+
+        nodes.push(DeployedNode {
+            name: name.to_string(),
+            url,
+            key_files: labels
+                .get("splinter.key-files")
+                .map(|key_files| split_list(key_files))
+                .unwrap_or_default(),
+            metadata: labels
+                .iter()
+                .filter_map(|(key, value)| {
+                    key.strip_prefix("splinter.metadata.")
+                        .map(|key| (key.to_string(), value.to_string()))
+                })
+                .collect(),
+        });
+    }
+
+    Ok(nodes)
+}
+
+/// Returns a service's labels, which may be given as a map or as a list of `key=value` strings
+fn compose_labels(service: &Value) -> HashMap<String, String> {
+    match service.get("labels") {
+        Some(Value::Mapping(labels)) => labels
+            .iter()
+            .filter_map(|(key, value)| Some((key.as_str()?.to_string(), scalar(value)?)))
+            .collect(),
+        Some(Value::Sequence(labels)) => labels
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(|label| {
+                let mut parts = label.splitn(2, '=');
+                Some((parts.next()?.to_string(), parts.next()?.to_string()))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the port of the `--rest-api-endpoint` argument in a service's command or entrypoint,
+/// which may be given as a string or as a list of arguments
+fn rest_api_port(command: &Value) -> Option<String> {
+    let args: Vec<String> = match command {
+        Value::String(command) => command.split_whitespace().map(String::from).collect(),
+        Value::Sequence(args) => args.iter().filter_map(scalar).collect(),
+        _ => return None,
+    };
+
+    let endpoint = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--rest-api-endpoint" {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix("--rest-api-endpoint=").map(String::from)
+        }
+    })?;
+
+    endpoint
+        .trim_matches(|c| c == '"' || c == '\'')
+        .rsplit(':')
+        .next()
+        .filter(|port| !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+fn parse_inventory(contents: &str, group: Option<&str>) -> Vec<DeployedNode> {
+    let mut nodes: Vec<DeployedNode> = vec![];
+    // Hosts listed before the first section are ungrouped
+    let mut section: Option<String> = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = Some(line[1..line.len() - 1].trim().to_string());
+            continue;
+        }
+
+        let in_group = match (&section, group) {
+            (Some(section), _) if section.contains(':') => false,
+            (_, None) => true,
+            (Some(section), Some(group)) => section == group,
+            (None, Some(_)) => false,
+        };
+        if !in_group {
+            continue;
+        }
+
+        let mut words = split_words(line).into_iter();
+        let host = match words.next() {
+            Some(host) => host,
+            None => continue,
+        };
+        if nodes.iter().any(|node| node.name == host) {
+            continue;
+        }
+        let vars: HashMap<String, String> = words
+            .filter_map(|word| {
+                let mut parts = word.splitn(2, '=');
+                Some((parts.next()?.to_string(), parts.next()?.to_string()))
+            })
+            .collect();
+
+        let url = vars
+            .get("splinter_rest_api_url")
+            .cloned()
+            .unwrap_or_else(|| {
+                format!(
+                    "http://{}:{}",
+                    vars.get("ansible_host").unwrap_or(&host),
+                    DEFAULT_REST_API_PORT
+                )
+            });
+
+        nodes.push(DeployedNode {
+            key_files: vars
+                .get("splinter_key_files")
+                .map(|key_files| split_list(key_files))
+                .unwrap_or_default(),
+            metadata: vars
+                .iter()
+                .filter_map(|(key, value)| {
+                    key.strip_prefix("splinter_metadata_")
+                        .map(|key| (key.to_string(), value.to_string()))
+                })
+                .collect(),
+            name: host,
+            url,
+        });
+    }
+
+    nodes
+}
+
+/// Splits an inventory line on whitespace, keeping quoted values together and removing their
+/// quotes
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quote = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            None => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that splinterd services are read from a docker-compose file, with their REST API
+    /// URLs, key files and metadata, and that other services are skipped.
+    #[test]
+    fn test_parse_compose() {
+        let compose = r#"
+version: "3.6"
+services:
+  splinterd-node-acme:
+    image: splinterd-node
+    container_name: splinterd-node-acme
+    labels:
+      splinter.key-files: /registry/alice.pub
+      splinter.metadata.organization: ACME Corporation
+    entrypoint: |
+      bash -c "
+        splinterd -c ./configs/splinterd-node-acme.toml -vv \
+            --rest-api-endpoint 0.0.0.0:8085 \
+            --tls-insecure
+      "
+  splinterd-node-bubba:
+    image: example/node
+    labels:
+      - splinter.rest-api-url=https://bubba.example.com:8443
+  db-acme:
+    image: postgres
+"#;
+
+        let nodes = parse_compose(compose).expect("Failed to parse compose file");
+        assert_eq!(nodes.len(), 2);
+
+        let acme = &nodes[0];
+        assert_eq!(acme.name, "splinterd-node-acme");
+        assert_eq!(acme.url, "http://splinterd-node-acme:8085");
+        assert_eq!(acme.key_files, vec!["/registry/alice.pub".to_string()]);
+        assert_eq!(
+            acme.metadata.get("organization").map(String::as_str),
+            Some("ACME Corporation")
+        );
+
+        assert_eq!(nodes[1].url, "https://bubba.example.com:8443");
+    }
+
+    /// Verify that hosts are read from an inventory, optionally limited to a group, that a host
+    /// listed twice is read once, and that `:vars` sections are skipped.
+    #[test]
+    fn test_parse_inventory() {
+        let inventory = r#"
+# Splinter nodes
+[splinter]
+alpha.example.com splinter_metadata_organization="ACME Corporation"
+beta ansible_host=10.0.0.2 splinter_key_files=/keys/bob.pub,/keys/carol.pub
+
+[splinter:vars]
+ansible_user=admin
+
+[databases]
+db.example.com
+beta
+"#;
+
+        let nodes = parse_inventory(inventory, Some("splinter"));
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].url, "http://alpha.example.com:8080");
+        assert_eq!(
+            nodes[0].metadata.get("organization").map(String::as_str),
+            Some("ACME Corporation")
+        );
+        assert_eq!(nodes[1].url, "http://10.0.0.2:8080");
+        assert_eq!(nodes[1].key_files.len(), 2);
+
+        let names = parse_inventory(inventory, None)
+            .into_iter()
+            .map(|node| node.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["alpha.example.com", "beta", "db.example.com"]);
+    }
+}
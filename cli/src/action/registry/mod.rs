@@ -12,6 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod api;
+#[cfg(feature = "registry-build-inventory")]
+mod deployment;
+
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -29,6 +34,11 @@ use super::{
 
 #[cfg(feature = "splinter-cli-jwt")]
 use super::create_cylinder_jwt_auth;
+#[cfg(feature = "registry-build-inventory")]
+use super::node::challenge_node;
+
+#[cfg(feature = "registry-build-inventory")]
+use deployment::{read_deployment, DeploymentFormat};
 
 const DEFAULT_OUTPUT_FILE: &str = "./nodes.yaml";
 
@@ -40,23 +50,86 @@ impl Action for RegistryGenerateAction {
 
         let output_file = args.value_of("file").unwrap_or(DEFAULT_OUTPUT_FILE);
 
-        let mut nodes: Vec<Node> = if Path::new(output_file).exists() {
-            let file = File::open(output_file).map_err(|err| {
-                CliError::ActionError(format!(
-                    "Failed to open '{}': {}",
-                    output_file,
-                    msg_from_io_error(err)
-                ))
-            })?;
-            serde_yaml::from_reader(file).map_err(|_| {
-                CliError::ActionError(format!(
-                    "Failed to read registry file '{}': Not a valid YAML sequence of nodes",
+        let mut nodes = read_registry_file(output_file)?;
+
+        let metadata = parse_metadata(args)?;
+
+        #[cfg(feature = "registry-build-inventory")]
+        {
+            if let Some(deployment_file) = args.value_of("from") {
+                let format = args
+                    .value_of("format")
+                    .map(|format| format.parse::<DeploymentFormat>())
+                    .transpose()?;
+                let deployed_nodes =
+                    read_deployment(deployment_file, format, args.value_of("group"))?;
+
+                #[cfg(feature = "splinter-cli-jwt")]
+                let auth = create_cylinder_jwt_auth(args.value_of("private_key_file"))?;
+
+                let mut added = vec![];
+                for deployed_node in deployed_nodes {
+                    #[allow(unused_mut)]
+                    let mut builder =
+                        SplinterRestClientBuilder::new().with_url(deployed_node.url.clone());
+                    #[cfg(feature = "splinter-cli-jwt")]
+                    {
+                        builder = builder.with_auth(auth.clone());
+                    }
+                    let node_status = builder.build()?.get_node_status().map_err(|err| {
+                        CliError::ActionError(format!(
+                            "Failed to get the status of '{}' at {}: {}",
+                            deployed_node.name, deployed_node.url, err
+                        ))
+                    })?;
+
+                    // The node proves it holds its key by signing a challenge
+                    let challenge = challenge_node(
+                        &deployed_node.url,
+                        #[cfg(feature = "splinter-cli-jwt")]
+                        &auth,
+                    )?;
+                    if challenge.node_id != node_status.node_id {
+                        return Err(CliError::ActionError(format!(
+                            "Node at {} reported ID {} in its status, but {} in its challenge",
+                            deployed_node.url, node_status.node_id, challenge.node_id
+                        )));
+                    }
+
+                    let mut keys = vec![challenge.public_key];
+                    for key_file in &deployed_node.key_files {
+                        let key = read_private_key(key_file)?;
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+
+                    let mut node_metadata = metadata.clone();
+                    node_metadata.extend(deployed_node.metadata);
+
+                    let node = Node {
+                        identity: node_status.node_id,
+                        endpoints: node_status.advertised_endpoints,
+                        display_name: node_status.display_name,
+                        keys,
+                        metadata: node_metadata,
+                    };
+                    added.push(node.identity.clone());
+                    add_node(&mut nodes, node, args.is_present("force"))?;
+                }
+
+                write_registry_file(output_file, &nodes)?;
+
+                info!(
+                    "Added nodes {} from '{}' to '{}'",
+                    added.join(", "),
+                    deployment_file,
                     output_file
-                ))
-            })?
-        } else {
-            vec![]
-        };
+                );
+
+                return Ok(());
+            }
+        }
 
         let url = args
             .value_of("status_url")
@@ -83,44 +156,6 @@ impl Action for RegistryGenerateAction {
             .map(|key_file| read_private_key(key_file))
             .collect::<Result<_, _>>()?;
 
-        let metadata = if let Some(metadata) = args.values_of("metadata") {
-            metadata
-                .map(|kv| {
-                    let mut kv_iter = kv.splitn(2, '=');
-
-                    let key = kv_iter
-                        .next()
-                        .expect("str::split cannot return an empty iterator")
-                        .to_string();
-                    if key.is_empty() {
-                        return Err(CliError::ActionError(
-                            "Empty '--metadata' argument detected".into(),
-                        ));
-                    }
-
-                    let value = kv_iter
-                        .next()
-                        .ok_or_else(|| {
-                            CliError::ActionError(format!(
-                                "Missing value for metadata key '{}'",
-                                key
-                            ))
-                        })?
-                        .to_string();
-                    if value.is_empty() {
-                        return Err(CliError::ActionError(format!(
-                            "Empty value detected for metadata key '{}'",
-                            key
-                        )));
-                    }
-
-                    Ok((key, value))
-                })
-                .collect::<Result<_, _>>()?
-        } else {
-            Default::default()
-        };
-
         let node = Node {
             identity: node_status.node_id.clone(),
             endpoints: node_status.advertised_endpoints,
@@ -129,51 +164,123 @@ impl Action for RegistryGenerateAction {
             metadata,
         };
 
-        if let Some(idx) = nodes
-            .iter()
-            .position(|existing_node| existing_node.identity == node.identity)
-        {
-            if args.is_present("force") {
-                nodes.remove(idx);
-            } else {
-                return Err(CliError::EnvironmentError(format!(
-                    "Node '{}' already exists; must use '--force' to overwrite an existing node",
-                    node.identity
-                )));
-            }
-        }
+        add_node(&mut nodes, node, args.is_present("force"))?;
 
-        nodes.push(node);
-
-        let yaml = serde_yaml::to_vec(&nodes).map_err(|err| {
-            CliError::ActionError(format!("Cannot format node list into yaml: {}", err))
-        })?;
-
-        let mut file = File::create(output_file).map_err(|err| {
-            CliError::ActionError(format!(
-                "Failed to create or overwrite '{}': {}",
-                output_file,
-                msg_from_io_error(err)
-            ))
-        })?;
-        file.write_all(&yaml).map_err(|err| {
-            CliError::ActionError(format!(
-                "Failed to write to file '{}': {}",
-                output_file,
-                msg_from_io_error(err)
-            ))
-        })?;
-        // Append newline to file
-        writeln!(file).map_err(|err| {
-            CliError::ActionError(format!(
-                "Failed to write to file '{}': {}",
-                output_file,
-                msg_from_io_error(err)
-            ))
-        })?;
+        write_registry_file(output_file, &nodes)?;
 
         info!("Added node '{}' to '{}'", node_status.node_id, output_file);
 
         Ok(())
     }
 }
+
+/// Reads the nodes of the given registry file, or returns an empty list if it does not exist.
+fn read_registry_file(output_file: &str) -> Result<Vec<Node>, CliError> {
+    if !Path::new(output_file).exists() {
+        return Ok(vec![]);
+    }
+
+    let file = File::open(output_file).map_err(|err| {
+        CliError::ActionError(format!(
+            "Failed to open '{}': {}",
+            output_file,
+            msg_from_io_error(err)
+        ))
+    })?;
+    serde_yaml::from_reader(file).map_err(|_| {
+        CliError::ActionError(format!(
+            "Failed to read registry file '{}': Not a valid YAML sequence of nodes",
+            output_file
+        ))
+    })
+}
+
+/// Parses the `--metadata` arguments, given as `<key>=<value>`.
+pub(crate) fn parse_metadata(args: &ArgMatches) -> Result<HashMap<String, String>, CliError> {
+    let metadata = match args.values_of("metadata") {
+        Some(metadata) => metadata,
+        None => return Ok(Default::default()),
+    };
+
+    metadata
+        .map(|kv| {
+            let mut kv_iter = kv.splitn(2, '=');
+
+            let key = kv_iter
+                .next()
+                .expect("str::split cannot return an empty iterator")
+                .to_string();
+            if key.is_empty() {
+                return Err(CliError::ActionError(
+                    "Empty '--metadata' argument detected".into(),
+                ));
+            }
+
+            let value = kv_iter
+                .next()
+                .ok_or_else(|| {
+                    CliError::ActionError(format!("Missing value for metadata key '{}'", key))
+                })?
+                .to_string();
+            if value.is_empty() {
+                return Err(CliError::ActionError(format!(
+                    "Empty value detected for metadata key '{}'",
+                    key
+                )));
+            }
+
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Adds the node to the list, replacing an existing node with the same identity only if `force`
+/// is set.
+fn add_node(nodes: &mut Vec<Node>, node: Node, force: bool) -> Result<(), CliError> {
+    if let Some(idx) = nodes
+        .iter()
+        .position(|existing_node| existing_node.identity == node.identity)
+    {
+        if force {
+            nodes.remove(idx);
+        } else {
+            return Err(CliError::EnvironmentError(format!(
+                "Node '{}' already exists; must use '--force' to overwrite an existing node",
+                node.identity
+            )));
+        }
+    }
+
+    nodes.push(node);
+
+    Ok(())
+}
+
+fn write_registry_file(output_file: &str, nodes: &[Node]) -> Result<(), CliError> {
+    let yaml = serde_yaml::to_vec(nodes).map_err(|err| {
+        CliError::ActionError(format!("Cannot format node list into yaml: {}", err))
+    })?;
+
+    let mut file = File::create(output_file).map_err(|err| {
+        CliError::ActionError(format!(
+            "Failed to create or overwrite '{}': {}",
+            output_file,
+            msg_from_io_error(err)
+        ))
+    })?;
+    file.write_all(&yaml).map_err(|err| {
+        CliError::ActionError(format!(
+            "Failed to write to file '{}': {}",
+            output_file,
+            msg_from_io_error(err)
+        ))
+    })?;
+    // Append newline to file
+    writeln!(file).map_err(|err| {
+        CliError::ActionError(format!(
+            "Failed to write to file '{}': {}",
+            output_file,
+            msg_from_io_error(err)
+        ))
+    })
+}
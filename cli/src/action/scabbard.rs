@@ -0,0 +1,75 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use clap::ArgMatches;
+use scabbard::service::verify_state;
+
+use crate::error::CliError;
+
+use super::Action;
+
+const DEFAULT_DB_DIR: &str = "/var/lib/splinter";
+const DEFAULT_DB_SIZE_MB: usize = 1024;
+
+pub struct ScabbardVerifyAction;
+
+impl Action for ScabbardVerifyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let circuit_id = args
+            .value_of("circuit")
+            .ok_or_else(|| CliError::ActionError("'circuit' argument is required".into()))?;
+        let service_id = args
+            .value_of("service")
+            .ok_or_else(|| CliError::ActionError("'service' argument is required".into()))?;
+        let db_dir = Path::new(args.value_of("db_dir").unwrap_or(DEFAULT_DB_DIR));
+        let db_size = args
+            .value_of("db_size")
+            .map(|size| {
+                size.parse::<usize>().map_err(|_| {
+                    CliError::ActionError(format!("'db-size' must be a number: {}", size))
+                })
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_DB_SIZE_MB)
+            * 1024
+            * 1024;
+
+        // The state and receipt databases of scabbard services are stored in the same directory
+        let result = verify_state(circuit_id, service_id, db_dir, db_size, db_dir, db_size);
+        let verification = result.map_err(|err| {
+            CliError::ActionError(format!(
+                "Unable to verify state of service {}::{}: {}",
+                circuit_id, service_id, err
+            ))
+        })?;
+
+        println!("Current state root:  {}", verification.current_state_root);
+        println!("Replayed state root: {}", verification.replayed_state_root);
+        println!("Receipts replayed:   {}", verification.receipt_count);
+
+        if verification.is_verified() {
+            info!("State of service {}::{} verified", circuit_id, service_id);
+            Ok(())
+        } else {
+            Err(CliError::ActionError(format!(
+                "State of service {}::{} does not match its transaction receipts",
+                circuit_id, service_id
+            )))
+        }
+    }
+}
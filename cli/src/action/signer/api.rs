@@ -0,0 +1,119 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+impl SplinterRestClient {
+    /// Lists the node's allowed signers, optionally only those of the given node.
+    pub fn list_allowed_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, CliError> {
+        let mut request = Client::new().get(&format!("{}/admin/signers", self.url));
+        if let Some(node_id) = node_id {
+            request = request.query(&[("node_id", node_id)]);
+        }
+
+        self.authorize(request)
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to list signers: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<AllowedSignerListResponse>()
+                        .map(|response| response.data)
+                        .map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                } else {
+                    Err(request_error(res, "list signers"))
+                }
+            })
+    }
+
+    /// Allows the signer to act on behalf of its node, replacing any existing entry for the
+    /// signer's public key.
+    pub fn add_allowed_signer(&self, signer: &AllowedSigner) -> Result<(), CliError> {
+        self.authorize(
+            Client::new()
+                .post(&format!("{}/admin/signers", self.url))
+                .json(signer),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to add signer: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "add signer"))
+            }
+        })
+    }
+
+    /// Removes the allowed signer with the given public key.
+    pub fn remove_allowed_signer(&self, public_key: &str) -> Result<(), CliError> {
+        self.authorize(Client::new().delete(&format!("{}/admin/signers/{}", self.url, public_key)))
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to remove signer: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(request_error(res, "remove signer"))
+                }
+            })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        #[cfg(feature = "splinter-cli-jwt")]
+        {
+            request.header("Authorization", &self.auth)
+        }
+        #[cfg(not(feature = "splinter-cli-jwt"))]
+        {
+            request
+        }
+    }
+}
+
+/// Builds the error for an unsuccessful request from the server's error response.
+fn request_error(res: Response, action: &str) -> CliError {
+    let status = res.status();
+    match res.json::<ServerError>() {
+        Ok(ServerError { message }) => {
+            CliError::ActionError(format!("Failed to {}: {}", action, message))
+        }
+        Err(_) => CliError::ActionError(format!(
+            "Request to {} failed with status code '{}', but error response was not valid",
+            action, status
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct AllowedSignerListResponse {
+    data: Vec<AllowedSigner>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllowedSigner {
+    pub public_key: String,
+    pub node_id: String,
+    pub roles: Vec<String>,
+}
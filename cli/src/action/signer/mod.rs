@@ -0,0 +1,129 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Management of the signers that a node allows to propose and vote on circuit changes on behalf
+//! of circuit member nodes.
+
+mod api;
+
+use clap::ArgMatches;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+#[cfg(feature = "splinter-cli-jwt")]
+use super::create_cylinder_jwt_auth;
+use super::{print_table, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
+
+use self::api::AllowedSigner;
+
+pub struct SignerListAction;
+
+impl Action for SignerListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let signers = build_client(args)?.list_allowed_signers(args.value_of("node_id"))?;
+
+        let mut data = Vec::new();
+        data.push(vec![
+            "PUBLIC KEY".to_string(),
+            "NODE ID".to_string(),
+            "ROLES".to_string(),
+        ]);
+        signers.into_iter().for_each(|signer| {
+            let roles = if format == "csv" {
+                signer.roles.join(";")
+            } else {
+                signer.roles.join(", ")
+            };
+            data.push(vec![signer.public_key, signer.node_id, roles]);
+        });
+
+        if format == "csv" {
+            for row in data {
+                println!("{}", row.join(","))
+            }
+        } else {
+            print_table(data);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SignerAddAction;
+
+impl Action for SignerAddAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let public_key = public_key_arg(args)?;
+        let node_id = args
+            .value_of("node_id")
+            .ok_or_else(|| CliError::ActionError("'node_id' argument is required".into()))?;
+        let roles = args
+            .values_of("roles")
+            .ok_or_else(|| CliError::ActionError("'role' argument is required".into()))?
+            .map(String::from)
+            .collect();
+
+        build_client(args)?.add_allowed_signer(&AllowedSigner {
+            public_key: public_key.to_string(),
+            node_id: node_id.to_string(),
+            roles,
+        })?;
+        info!("Allowed {} to sign for node {}", public_key, node_id);
+
+        Ok(())
+    }
+}
+
+pub struct SignerRemoveAction;
+
+impl Action for SignerRemoveAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let public_key = public_key_arg(args)?;
+
+        build_client(args)?.remove_allowed_signer(public_key)?;
+        info!("Removed allowed signer {}", public_key);
+
+        Ok(())
+    }
+}
+
+fn public_key_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("public_key")
+        .ok_or_else(|| CliError::ActionError("'public_key' argument is required".into()))
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let url = args
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+    let mut builder = SplinterRestClientBuilder::new();
+    builder = builder.with_url(url);
+
+    #[cfg(feature = "splinter-cli-jwt")]
+    {
+        let key = args.value_of("private_key_file");
+        builder = builder.with_auth(create_cylinder_jwt_auth(key)?);
+    }
+
+    builder.build()
+}
@@ -0,0 +1,112 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::{ServerError, SplinterRestClient};
+use crate::error::CliError;
+
+impl SplinterRestClient {
+    /// Lists the Biome user accounts on the node.
+    pub fn list_users(&self) -> Result<Vec<UserAccount>, CliError> {
+        self.authorize(Client::new().get(&format!("{}/biome/admin/users", self.url)))
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to list users: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    res.json::<UserListResponse>()
+                        .map(|response| response.data)
+                        .map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                } else {
+                    Err(request_error(res, "list users"))
+                }
+            })
+    }
+
+    /// Updates whether the given user is disabled, or the roles assigned to the user.
+    pub fn update_user(&self, user_id: &str, update: &UserAccountUpdate) -> Result<(), CliError> {
+        self.authorize(
+            Client::new()
+                .patch(&format!("{}/biome/admin/users/{}", self.url, user_id))
+                .json(update),
+        )
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to update user: {}", err)))
+        .and_then(|res| {
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(request_error(res, "update user"))
+            }
+        })
+    }
+
+    /// Removes the given user, along with the user's keys and sessions.
+    pub fn remove_user(&self, user_id: &str) -> Result<(), CliError> {
+        self.authorize(Client::new().delete(&format!("{}/biome/admin/users/{}", self.url, user_id)))
+            .send()
+            .map_err(|err| CliError::ActionError(format!("Failed to remove user: {}", err)))
+            .and_then(|res| {
+                if res.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(request_error(res, "remove user"))
+                }
+            })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header("Authorization", &self.auth)
+    }
+}
+
+/// Builds the error for an unsuccessful request from the server's error response.
+fn request_error(res: Response, action: &str) -> CliError {
+    let status = res.status();
+    match res.json::<ServerError>() {
+        Ok(ServerError { message }) => {
+            CliError::ActionError(format!("Failed to {}: {}", action, message))
+        }
+        Err(_) => CliError::ActionError(format!(
+            "Request to {} failed with status code '{}', but error response was not valid",
+            action, status
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct UserListResponse {
+    data: Vec<UserAccount>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserAccount {
+    pub user_id: String,
+    pub username: String,
+    pub disabled: bool,
+    pub roles: Vec<String>,
+}
+
+#[derive(Default, Serialize)]
+pub struct UserAccountUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<String>>,
+}
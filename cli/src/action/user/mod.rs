@@ -0,0 +1,164 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Administration of the Biome users of a node. These commands must be signed with one of the
+//! node's configured Biome admin keys.
+
+mod api;
+
+use clap::ArgMatches;
+
+use crate::error::CliError;
+
+use super::api::{SplinterRestClient, SplinterRestClientBuilder};
+use super::{
+    create_cylinder_jwt_auth, print_table, Action, DEFAULT_SPLINTER_REST_API_URL,
+    SPLINTER_REST_API_URL_ENV,
+};
+
+use self::api::UserAccountUpdate;
+
+pub struct UserListAction;
+
+impl Action for UserListAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let format = args.value_of("format").unwrap_or("human");
+
+        let users = build_client(args)?.list_users()?;
+
+        let mut data = Vec::new();
+        data.push(vec![
+            "USER ID".to_string(),
+            "USERNAME".to_string(),
+            "STATUS".to_string(),
+            "ROLES".to_string(),
+        ]);
+        users.into_iter().for_each(|user| {
+            let status = if user.disabled { "disabled" } else { "enabled" };
+            let roles = if format == "csv" {
+                user.roles.join(";")
+            } else if user.roles.is_empty() {
+                "-".to_string()
+            } else {
+                user.roles.join(", ")
+            };
+            data.push(vec![user.user_id, user.username, status.to_string(), roles]);
+        });
+
+        if format == "csv" {
+            for row in data {
+                println!("{}", row.join(","))
+            }
+        } else {
+            print_table(data);
+        }
+
+        Ok(())
+    }
+}
+
+/// Disables or enables a user; a disabled user cannot log in.
+pub struct UserDisableAction {
+    pub disabled: bool,
+}
+
+impl Action for UserDisableAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let user_id = user_id_arg(args)?;
+
+        build_client(args)?.update_user(
+            user_id,
+            &UserAccountUpdate {
+                disabled: Some(self.disabled),
+                ..Default::default()
+            },
+        )?;
+
+        if self.disabled {
+            info!("Disabled user {}", user_id);
+        } else {
+            info!("Enabled user {}", user_id);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct UserRemoveAction;
+
+impl Action for UserRemoveAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let user_id = user_id_arg(args)?;
+
+        build_client(args)?.remove_user(user_id)?;
+        info!("Removed user {}", user_id);
+
+        Ok(())
+    }
+}
+
+pub struct UserSetRoleAction;
+
+impl Action for UserSetRoleAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+        let user_id = user_id_arg(args)?;
+
+        let roles = if args.is_present("clear") {
+            vec![]
+        } else {
+            args.values_of("roles")
+                .ok_or_else(|| CliError::ActionError("'roles' argument is required".to_string()))?
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        build_client(args)?.update_user(
+            user_id,
+            &UserAccountUpdate {
+                roles: Some(roles.clone()),
+                ..Default::default()
+            },
+        )?;
+
+        if roles.is_empty() {
+            info!("Removed all roles from user {}", user_id);
+        } else {
+            info!("Set roles of user {} to {}", user_id, roles.join(", "));
+        }
+
+        Ok(())
+    }
+}
+
+fn user_id_arg<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("user_id")
+        .ok_or_else(|| CliError::ActionError("'user_id' argument is required".to_string()))
+}
+
+fn build_client(args: &ArgMatches) -> Result<SplinterRestClient, CliError> {
+    let url = args
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+    SplinterRestClientBuilder::new()
+        .with_url(url)
+        .with_auth(create_cylinder_jwt_auth(args.value_of("private_key_file"))?)
+        .build()
+}
@@ -27,13 +27,15 @@ mod template;
 
 use std::ffi::OsString;
 
+#[cfg(feature = "hsm-signing")]
+use clap::App;
 use clap::{clap_app, AppSettings, Arg, SubCommand};
 #[cfg(test)]
 use flexi_logger::FlexiLoggerError;
 use flexi_logger::{DeferredNow, LogSpecBuilder, Logger};
 use log::Record;
 
-use action::{admin, certs, circuit, keygen, registry, Action, SubcommandActions};
+use action::{admin, certs, circuit, key, keygen, registry, Action, SubcommandActions};
 use error::CliError;
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
@@ -95,9 +97,20 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
         )
     );
 
+    // Only secp256k1 is accepted unless the signing-ed25519 feature is enabled
+    #[cfg(not(feature = "signing-ed25519"))]
+    let algorithms = &["secp256k1"];
+    #[cfg(feature = "signing-ed25519")]
+    let algorithms = &["secp256k1", "ed25519"];
+    let algorithm_arg = Arg::with_name("algorithm")
+        .long("algorithm")
+        .takes_value(true)
+        .possible_values(algorithms)
+        .help("Signature algorithm of the generated keys; defaults to secp256k1");
+
     app = app.subcommand(
         SubCommand::with_name("keygen")
-            .about("Generates secp256k1 keys")
+            .about("Generates signing keys")
             .arg(
                 Arg::with_name("key-name")
                     .takes_value(true)
@@ -123,6 +136,166 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                 Arg::with_name("system")
                     .long("system")
                     .help("Generate system keys in /etc/splinter/keys"),
+            )
+            .arg(algorithm_arg.clone()),
+    );
+
+    let key_dir_arg = Arg::with_name("key_dir")
+        .long("key-dir")
+        .takes_value(true)
+        .conflicts_with("system")
+        .help("Name of the directory containing the keys; defaults to $HOME/.splinter/keys");
+    let system_arg = Arg::with_name("system")
+        .long("system")
+        .help("Use the system keys in /etc/splinter/keys");
+
+    let rotate_key = SubCommand::with_name("rotate")
+        .about("Generates a new key pair and archives the existing one")
+        .arg(
+            Arg::with_name("key-name")
+                .takes_value(true)
+                .help("Name of the keys to rotate; defaults to user name"),
+        )
+        .arg(key_dir_arg.clone())
+        .arg(system_arg.clone());
+
+    #[cfg(feature = "admin-key-rotation")]
+    let rotate_key =
+        rotate_key
+            .arg(Arg::with_name("publish").long("publish").help(
+                "Publish the new public key for the node in the registry and retire the old one",
+            ))
+            .arg(
+                Arg::with_name("url")
+                    .short("U")
+                    .long("url")
+                    .takes_value(true)
+                    .requires("publish")
+                    .help("URL of Splinter Daemon"),
+            )
+            .arg(
+                Arg::with_name("node_id")
+                    .long("node-id")
+                    .takes_value(true)
+                    .requires("publish")
+                    .help("ID of the node to publish the key for; defaults to the node at --url"),
+            )
+            .arg(
+                Arg::with_name("private_key_file")
+                    .value_name("private-key-file")
+                    .short("k")
+                    .long("key")
+                    .takes_value(true)
+                    .requires("publish")
+                    .help("Name or path of private key"),
+            );
+
+    app = app.subcommand(
+        SubCommand::with_name("key")
+            .about("Manages the keys in a key directory")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                SubCommand::with_name("generate")
+                    .about("Generates signing keys")
+                    .arg(
+                        Arg::with_name("key-name")
+                            .takes_value(true)
+                            .help("Name of keys generated; defaults to user name"),
+                    )
+                    .arg(key_dir_arg.clone())
+                    .arg(
+                        Arg::with_name("force")
+                            .short("f")
+                            .long("force")
+                            .help("Overwrite files if they exist"),
+                    )
+                    .arg(system_arg.clone())
+                    .arg(algorithm_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("list")
+                    .about("Lists the keys in the key directory with their fingerprints")
+                    .arg(key_dir_arg.clone())
+                    .arg(system_arg.clone())
+                    .arg(
+                        Arg::with_name("archived")
+                            .long("archived")
+                            .help("List the archived keys instead of the active keys"),
+                    )
+                    .arg(
+                        Arg::with_name("format")
+                            .short("F")
+                            .long("format")
+                            .help("Output format")
+                            .possible_values(&["human", "csv"])
+                            .default_value("human")
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(rotate_key)
+            .subcommand(
+                SubCommand::with_name("export-public")
+                    .about("Exports a public key")
+                    .arg(
+                        Arg::with_name("key-name")
+                            .takes_value(true)
+                            .help("Name of the key to export; defaults to user name"),
+                    )
+                    .arg(key_dir_arg.clone())
+                    .arg(system_arg.clone())
+                    .arg(
+                        Arg::with_name("output")
+                            .short("o")
+                            .long("output")
+                            .takes_value(true)
+                            .help("File to write the public key to; defaults to stdout"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("revoke")
+                    .about("Removes a public key from the node's registry entry")
+                    .arg(
+                        Arg::with_name("key-name")
+                            .takes_value(true)
+                            .conflicts_with("public_key")
+                            .help("Name of the key to revoke; defaults to user name"),
+                    )
+                    .arg(
+                        Arg::with_name("public_key")
+                            .long("public-key")
+                            .takes_value(true)
+                            .help("Hex-encoded public key to revoke"),
+                    )
+                    .arg(key_dir_arg)
+                    .arg(system_arg)
+                    .arg(
+                        Arg::with_name("url")
+                            .short("U")
+                            .long("url")
+                            .takes_value(true)
+                            .help("URL of Splinter Daemon"),
+                    )
+                    .arg(
+                        Arg::with_name("node_id")
+                            .long("node-id")
+                            .takes_value(true)
+                            .help(
+                            "ID of the node to revoke the key for; defaults to the node at --url",
+                        ),
+                    )
+                    .arg(
+                        Arg::with_name("keep_local")
+                            .long("keep-local")
+                            .help("Do not archive the local key files of the revoked key"),
+                    )
+                    .arg(
+                        Arg::with_name("private_key_file")
+                            .value_name("private-key-file")
+                            .short("k")
+                            .long("key")
+                            .takes_value(true)
+                            .help("Name or path of private key"),
+                    ),
             ),
     );
 
@@ -262,6 +435,41 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
             .help("Authorization type for the circuit"),
     );
 
+    #[cfg(feature = "circuit-relay")]
+    let propose_circuit = propose_circuit.arg(
+        Arg::with_name("relay")
+            .long("relay")
+            .takes_value(true)
+            .multiple(true)
+            .help("Node ID of a member that may relay messages between other members"),
+    );
+
+    #[cfg(feature = "circuit-observers")]
+    let propose_circuit = propose_circuit.arg(
+        Arg::with_name("observer")
+            .long("observer")
+            .takes_value(true)
+            .multiple(true)
+            .help("Node ID of a member that only observes the circuit's committed state"),
+    );
+
+    #[cfg(feature = "circuit-id-policy")]
+    let propose_circuit = propose_circuit
+        .arg(
+            Arg::with_name("circuit_id")
+                .long("circuit-id")
+                .takes_value(true)
+                .help("ID of the circuit; generated if not given"),
+        )
+        .arg(
+            Arg::with_name("circuit_id_format")
+                .long("circuit-id-format")
+                .takes_value(true)
+                .possible_values(&["random", "uuidv7"])
+                .conflicts_with("circuit_id")
+                .help("Format of the generated circuit ID (default: random)"),
+        );
+
     #[cfg(feature = "circuit-template")]
     let propose_circuit = propose_circuit
         .arg(
@@ -283,50 +491,118 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                 ),
         );
 
+    #[cfg(feature = "hsm-signing")]
+    let propose_circuit = with_hsm_args(propose_circuit, "key");
+
+    let vote_circuit = SubCommand::with_name("vote")
+        .about("Vote on a new circuit proposal")
+        .arg(
+            Arg::with_name("url")
+                .short("U")
+                .long("url")
+                .takes_value(true)
+                .help("URL of Splinter Daemon"),
+        )
+        .arg(
+            Arg::with_name("private_key_file")
+                .value_name("private-key-file")
+                .short("k")
+                .long("key")
+                .takes_value(true)
+                .help("Path to private key file"),
+        )
+        .arg(
+            Arg::with_name("circuit_id")
+                .value_name("circuit-id")
+                .takes_value(true)
+                .required(true)
+                .help("ID of the proposed circuit"),
+        )
+        .arg(
+            Arg::with_name("accept")
+                .required(true)
+                .long("accept")
+                .conflicts_with("reject")
+                .help("Accept the proposal"),
+        )
+        .arg(
+            Arg::with_name("reject")
+                .required(true)
+                .long("reject")
+                .conflicts_with("accept")
+                .help("Reject the proposal"),
+        );
+
+    #[cfg(feature = "hsm-signing")]
+    let vote_circuit = with_hsm_args(vote_circuit, "private_key_file");
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    let vote_circuit = vote_circuit.arg(
+        Arg::with_name("cosign")
+            .long("cosign")
+            .help("Hold the vote on the node until it has collected enough co-signatures"),
+    );
+
+    #[cfg(feature = "admin-vote-delegation")]
+    let vote_circuit = vote_circuit.arg(
+        Arg::with_name("delegated")
+            .long("delegated")
+            .help("Include the node's delegation of its voting authority to the signing key"),
+    );
+
+    let show_circuit = SubCommand::with_name("show")
+        .about("Show a specific circuit or proposal")
+        .arg(
+            Arg::with_name("url")
+                .short("U")
+                .long("url")
+                .help("URL of the Splinter daemon REST API")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("circuit")
+                .help("ID of the circuit to be shown")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("F")
+                .long("format")
+                .help("Output format")
+                .possible_values(&["human", "yaml", "json"])
+                .default_value("human")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("hidden_format")
+                .short("f")
+                .hidden(true)
+                .help("Output format")
+                .possible_values(&["human", "yaml", "json"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("private_key_file")
+                .value_name("private-key-file")
+                .short("k")
+                .long("key")
+                .takes_value(true)
+                .help("Name or path of private key"),
+        );
+
+    #[cfg(feature = "circuit-show-registry")]
+    let show_circuit = show_circuit.arg(
+        Arg::with_name("offline")
+            .long("offline")
+            .help("Do not look up the circuit members in the registry"),
+    );
+
     let circuit_command = SubCommand::with_name("circuit")
         .about("Provides circuit management functionality")
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .subcommand(propose_circuit)
-        .subcommand(
-            SubCommand::with_name("vote")
-                .about("Vote on a new circuit proposal")
-                .arg(
-                    Arg::with_name("url")
-                        .short("U")
-                        .long("url")
-                        .takes_value(true)
-                        .help("URL of Splinter Daemon"),
-                )
-                .arg(
-                    Arg::with_name("private_key_file")
-                        .value_name("private-key-file")
-                        .short("k")
-                        .long("key")
-                        .takes_value(true)
-                        .help("Path to private key file"),
-                )
-                .arg(
-                    Arg::with_name("circuit_id")
-                        .value_name("circuit-id")
-                        .takes_value(true)
-                        .required(true)
-                        .help("ID of the proposed circuit"),
-                )
-                .arg(
-                    Arg::with_name("accept")
-                        .required(true)
-                        .long("accept")
-                        .conflicts_with("reject")
-                        .help("Accept the proposal"),
-                )
-                .arg(
-                    Arg::with_name("reject")
-                        .required(true)
-                        .long("reject")
-                        .conflicts_with("accept")
-                        .help("Reject the proposal"),
-                ),
-        )
+        .subcommand(vote_circuit)
         .subcommand(
             SubCommand::with_name("list")
                 .about("List the circuits")
@@ -370,9 +646,10 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .help("Name or path of private key"),
                 ),
         )
+        .subcommand(show_circuit)
         .subcommand(
-            SubCommand::with_name("show")
-                .about("Show a specific circuit or proposal")
+            SubCommand::with_name("proposals")
+                .about("List the circuit proposals")
                 .arg(
                     Arg::with_name("url")
                         .short("U")
@@ -381,9 +658,21 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .takes_value(true),
                 )
                 .arg(
-                    Arg::with_name("circuit")
-                        .help("ID of the circuit to be shown")
-                        .required(true)
+                    Arg::with_name("management_type")
+                        .long("management-type")
+                        .help(
+                            "Filter circuit proposals by circuit \
+                             management type",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("member")
+                        .long("member")
+                        .help(
+                            "Show proposals with the given node ID in \
+                            its member list",
+                        )
                         .takes_value(true),
                 )
                 .arg(
@@ -391,7 +680,7 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .short("F")
                         .long("format")
                         .help("Output format")
-                        .possible_values(&["human", "yaml", "json"])
+                        .possible_values(&["human", "csv"])
                         .default_value("human")
                         .takes_value(true),
                 )
@@ -400,7 +689,7 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .short("f")
                         .hidden(true)
                         .help("Output format")
-                        .possible_values(&["human", "yaml", "json"])
+                        .possible_values(&["human", "csv"])
                         .takes_value(true),
                 )
                 .arg(
@@ -411,10 +700,63 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .takes_value(true)
                         .help("Name or path of private key"),
                 ),
-        )
+        );
+
+    #[cfg(feature = "circuit-ping")]
+    let circuit_command = circuit_command.subcommand(
+        SubCommand::with_name("ping")
+            .about("Send a diagnostic message to a service on a circuit")
+            .arg(
+                Arg::with_name("url")
+                    .short("U")
+                    .long("url")
+                    .help("URL of the Splinter daemon REST API")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("circuit")
+                    .help("ID of the circuit the service is on")
+                    .required(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("service")
+                    .help("ID of the service to ping")
+                    .required(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("timeout")
+                    .short("t")
+                    .long("timeout")
+                    .help("Number of seconds to wait for a response (1-60)")
+                    .default_value("5")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .short("F")
+                    .long("format")
+                    .help("Output format")
+                    .possible_values(&["human", "yaml", "json"])
+                    .default_value("human")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("private_key_file")
+                    .value_name("private-key-file")
+                    .short("k")
+                    .long("key")
+                    .takes_value(true)
+                    .help("Name or path of private key"),
+            ),
+    );
+
+    #[cfg(feature = "circuit-archive")]
+    let circuit_command = circuit_command
         .subcommand(
-            SubCommand::with_name("proposals")
-                .about("List the circuit proposals")
+            SubCommand::with_name("export")
+                .about("Export a circuit definition to a signed archive")
                 .arg(
                     Arg::with_name("url")
                         .short("U")
@@ -423,38 +765,50 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .takes_value(true),
                 )
                 .arg(
-                    Arg::with_name("management_type")
-                        .long("management-type")
-                        .help(
-                            "Filter circuit proposals by circuit \
-                             management type",
-                        )
+                    Arg::with_name("circuit")
+                        .help("ID of the circuit to export")
+                        .required(true)
                         .takes_value(true),
                 )
                 .arg(
-                    Arg::with_name("member")
-                        .long("member")
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
                         .help(
-                            "Show proposals with the given node ID in \
-                            its member list",
+                            "File to write the archive to, or '-' for stdout \
+                             (default: <circuit-id>.circuit.json)",
                         )
                         .takes_value(true),
                 )
                 .arg(
-                    Arg::with_name("format")
-                        .short("F")
-                        .long("format")
-                        .help("Output format")
-                        .possible_values(&["human", "csv"])
-                        .default_value("human")
+                    Arg::with_name("private_key_file")
+                        .value_name("private-key-file")
+                        .short("k")
+                        .long("key")
+                        .takes_value(true)
+                        .help("Path to the private key file used to sign the archive"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Propose a circuit from a signed archive")
+                .arg(
+                    Arg::with_name("url")
+                        .short("U")
+                        .long("url")
+                        .help("URL of the Splinter daemon REST API")
                         .takes_value(true),
                 )
                 .arg(
-                    Arg::with_name("hidden_format")
-                        .short("f")
-                        .hidden(true)
-                        .help("Output format")
-                        .possible_values(&["human", "csv"])
+                    Arg::with_name("archive")
+                        .help("Path to the circuit archive")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("signer")
+                        .long("signer")
+                        .help("Public key the archive must be signed by")
                         .takes_value(true),
                 )
                 .arg(
@@ -463,7 +817,13 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                         .short("k")
                         .long("key")
                         .takes_value(true)
-                        .help("Name or path of private key"),
+                        .help("Path to the private key file used to sign the proposal"),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .short("n")
+                        .help("Verify the archive and print the circuit without proposing it"),
                 ),
         );
 
@@ -610,14 +970,156 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                                 ",
         );
 
+    let cert_dir_arg = Arg::with_name("cert_dir")
+        .long("cert-dir")
+        .short("d")
+        .takes_value(true)
+        .long_help(
+            "Path to the certificate directory. Defaults to /etc/splinter/certs/. This \
+             location can also be changed with the SPLINTER_CERT_DIR environment variable.",
+        );
+    let ca_cert_arg = Arg::with_name("ca_cert")
+        .long("ca-cert")
+        .takes_value(true)
+        .help("Path to the CA certificate; defaults to generated_ca.pem in the cert directory");
+    let ca_key_arg = Arg::with_name("ca_key")
+        .long("ca-key")
+        .takes_value(true)
+        .help(
+            "Path to the CA private key; defaults to private/generated_ca.key in the cert \
+             directory",
+        );
+    let days_arg = Arg::with_name("days")
+        .long("days")
+        .takes_value(true)
+        .help("Number of days the certificate is valid; defaults to 365");
+    let cert_names_arg = Arg::with_name("names")
+        .takes_value(true)
+        .multiple(true)
+        .help(
+            "Base names of the certificates in the cert directory (for example, \"server\" \
+             for server.crt and private/server.key); defaults to the certificates used by \
+             splinterd",
+        );
+
     app = app.subcommand(
         SubCommand::with_name("cert")
-            .about("Generates certificates that can be used for development")
+            .about("Generates, signs, renews, and verifies certificates")
             .setting(AppSettings::SubcommandRequiredElseHelp)
-            .subcommand(cert_generate_subcommand),
-    );
-
-    #[cfg(feature = "circuit-template")]
+            .subcommand(cert_generate_subcommand)
+            .subcommand(
+                SubCommand::with_name("request")
+                    .about("Generates a private key and certificate signing request")
+                    .arg(
+                        Arg::with_name("name")
+                            .takes_value(true)
+                            .required(true)
+                            .help(
+                                "Base name of the key and request files; creates \
+                                 private/NAME.key and NAME.csr in the cert directory",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("common_name")
+                            .long("common-name")
+                            .takes_value(true)
+                            .help("Common name of the certificate subject; defaults to localhost"),
+                    )
+                    .arg(
+                        Arg::with_name("subject_alt_names")
+                            .long("subject-alt-name")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .help(
+                                "DNS name or IP address to include as a subject alternative name",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("new_key")
+                            .long("new-key")
+                            .help("Generate a new private key even if the key file already exists"),
+                    )
+                    .arg(cert_dir_arg.clone()),
+            )
+            .subcommand(
+                SubCommand::with_name("sign")
+                    .about("Signs a certificate signing request with the CA")
+                    .arg(
+                        Arg::with_name("csr")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Path to the certificate signing request"),
+                    )
+                    .arg(
+                        Arg::with_name("output")
+                            .short("o")
+                            .long("output")
+                            .takes_value(true)
+                            .help(
+                                "Path of the signed certificate; defaults to the request path \
+                                 with a .crt extension",
+                            ),
+                    )
+                    .arg(ca_cert_arg.clone())
+                    .arg(ca_key_arg.clone())
+                    .arg(days_arg.clone())
+                    .arg(cert_dir_arg.clone()),
+            )
+            .subcommand(
+                SubCommand::with_name("renew")
+                    .about("Reissues existing certificates with the same key and subject")
+                    .arg(cert_names_arg.clone())
+                    .arg(
+                        Arg::with_name("within")
+                            .long("within")
+                            .takes_value(true)
+                            .help("Only renew certificates that expire within this many days"),
+                    )
+                    .arg(ca_cert_arg)
+                    .arg(ca_key_arg)
+                    .arg(days_arg)
+                    .arg(cert_dir_arg.clone()),
+            )
+            .subcommand(
+                SubCommand::with_name("verify")
+                    .about(
+                        "Verifies certificates against their private keys, the CA, and their \
+                         expiration",
+                    )
+                    .arg(cert_names_arg)
+                    .arg(
+                        Arg::with_name("ca_file")
+                            .long("ca-file")
+                            .takes_value(true)
+                            .help(
+                                "Path to the CA certificate; defaults to ca.pem or \
+                                 generated_ca.pem in the cert directory",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("warn_days")
+                            .long("warn-days")
+                            .takes_value(true)
+                            .help(
+                                "Warn about certificates that expire within this many days; \
+                                 defaults to 30",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("write_config")
+                            .long("write-config")
+                            .takes_value(true)
+                            .help(
+                                "Write the splinterd TLS settings for the verified \
+                                 certificates to this file",
+                            ),
+                    )
+                    .arg(cert_dir_arg),
+            ),
+    );
+
+    #[cfg(feature = "circuit-template")]
     let circuit_command = circuit_command.subcommand(
         SubCommand::with_name("template")
             .about("Manage circuit templates")
@@ -659,41 +1161,301 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
 
     app = app.subcommand(circuit_command);
 
+    let key_files_arg = Arg::with_name("key_files")
+        .long("key-file")
+        .takes_value(true)
+        .multiple(true)
+        .help("Path of public key file to include with node");
+    #[cfg(not(feature = "registry-build-inventory"))]
+    let key_files_arg = key_files_arg.required(true);
+    #[cfg(feature = "registry-build-inventory")]
+    let key_files_arg = key_files_arg.required_unless("from");
+
+    #[allow(unused_mut)]
+    let mut registry_build_command = SubCommand::with_name("build")
+        .about("Add a node to a YAML file")
+        .arg(Arg::with_name("file").long("file").takes_value(true).help(
+            "Path of registry file to add node to; defaults to \
+                    './nodes.yaml'",
+        ))
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Overwrite node if it already exists"),
+        )
+        .arg(
+            Arg::with_name("status_url")
+                .takes_value(true)
+                .help("URL of splinter REST API to query for node data"),
+        )
+        .arg(key_files_arg)
+        .arg(
+            Arg::with_name("metadata")
+                .long("metadata")
+                .takes_value(true)
+                .multiple(true)
+                .help("Metadata to include with node (<key>=<value>)"),
+        )
+        .arg(
+            Arg::with_name("private_key_file")
+                .value_name("private-key-file")
+                .short("k")
+                .long("key")
+                .takes_value(true)
+                .help("Name or path of private key"),
+        );
+
+    #[cfg(feature = "registry-build-inventory")]
+    {
+        registry_build_command = registry_build_command
+            .arg(
+                Arg::with_name("from")
+                    .long("from")
+                    .takes_value(true)
+                    .conflicts_with("status_url")
+                    .help(
+                        "Add every splinterd node of a docker-compose file or Ansible-style \
+                         inventory, querying each node for its ID, endpoints and key",
+                    ),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(&["compose", "inventory"])
+                    .requires("from")
+                    .help(
+                        "Format of the --from file; defaults to compose for YAML files and \
+                         inventory otherwise",
+                    ),
+            )
+            .arg(
+                Arg::with_name("group")
+                    .long("group")
+                    .takes_value(true)
+                    .requires("from")
+                    .help("Only add the hosts in this inventory group"),
+            );
+    }
+
     app = app.subcommand(
         SubCommand::with_name("registry")
             .about("Splinter registry commands")
             .setting(AppSettings::SubcommandRequiredElseHelp)
-            .subcommand(
-                SubCommand::with_name("build")
-                    .about("Add a node to a YAML file")
-                    .arg(Arg::with_name("file").long("file").takes_value(true).help(
-                        "Path of registry file to add node to; defaults to \
-                                './nodes.yaml'",
-                    ))
+            .subcommand(registry_build_command),
+    );
+
+    #[cfg(feature = "bench")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("bench")
+                .about("Measures the throughput of the message path on this machine")
+                .arg(
+                    Arg::with_name("messages")
+                        .short("n")
+                        .long("messages")
+                        .takes_value(true)
+                        .help("Number of messages to send in each benchmark (default: 100000)"),
+                )
+                .arg(
+                    Arg::with_name("size")
+                        .short("s")
+                        .long("size")
+                        .takes_value(true)
+                        .help("Size in bytes of each message payload (default: 1024)"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .long("target")
+                        .takes_value(true)
+                        .help(
+                            "Minimum mesh throughput in messages per second; fails if the \
+                             measured throughput is lower (default: 50000, 0 to disable)",
+                        ),
+                ),
+        );
+    }
+
+    #[cfg(feature = "message-capture")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("capture")
+                .about("Analyzes captures of the messages a node exchanged with its peers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("inspect")
+                        .about("Summarizes the messages and peer events in a capture file")
+                        .arg(
+                            Arg::with_name("file")
+                                .required(true)
+                                .takes_value(true)
+                                .help("Path of the capture file downloaded from the node"),
+                        )
+                        .arg(
+                            Arg::with_name("peer")
+                                .long("peer")
+                                .takes_value(true)
+                                .help("Only include records for the given peer ID"),
+                        )
+                        .arg(
+                            Arg::with_name("records")
+                                .long("records")
+                                .help("Display every record instead of a summary"),
+                        ),
+                ),
+        );
+    }
+
+    #[cfg(feature = "health")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("health")
+                .about("Displays information about network health")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("status")
+                        .about(
+                            "Displays the reachability and response times of the other\n\
+                             members of a node's circuits",
+                        )
+                        .arg(
+                            Arg::with_name("url")
+                                .short("U")
+                                .long("url")
+                                .help("URL of the Splinter daemon REST API")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private_key_file")
+                                .value_name("private-key-file")
+                                .short("k")
+                                .long("key")
+                                .takes_value(true)
+                                .help("Name or path of private key"),
+                        ),
+                ),
+        );
+    }
+
+    #[cfg(any(feature = "node-challenge", feature = "log-retrieval"))]
+    {
+        #[allow(unused_mut)]
+        let mut node_command = SubCommand::with_name("node")
+            .about("Provides commands for checking Splinter nodes")
+            .setting(AppSettings::SubcommandRequiredElseHelp);
+
+        #[cfg(feature = "node-challenge")]
+        {
+            node_command = node_command.subcommand(
+                SubCommand::with_name("verify")
+                    .about(
+                        "Challenges a node to sign a random nonce, and checks the signature\n\
+                         against the node's keys in the local registry",
+                    )
                     .arg(
-                        Arg::with_name("force")
-                            .long("force")
-                            .help("Overwrite node if it already exists"),
+                        Arg::with_name("target")
+                            .required(true)
+                            .takes_value(true)
+                            .help("URL of the REST API of the node to verify"),
                     )
                     .arg(
-                        Arg::with_name("status_url")
+                        Arg::with_name("node_id")
+                            .long("node-id")
                             .takes_value(true)
-                            .help("URL of splinter REST API to query for node data"),
+                            .help("ID the node is expected to identify itself as"),
                     )
                     .arg(
-                        Arg::with_name("key_files")
-                            .long("key-file")
+                        Arg::with_name("url")
+                            .short("U")
+                            .long("url")
+                            .help("URL of the Splinter daemon REST API whose registry is used")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("private_key_file")
+                            .value_name("private-key-file")
+                            .short("k")
+                            .long("key")
                             .takes_value(true)
-                            .multiple(true)
+                            .help("Name or path of private key"),
+                    ),
+            );
+        }
+
+        #[cfg(feature = "node-bootstrap")]
+        {
+            node_command = node_command.subcommand(
+                SubCommand::with_name("bootstrap")
+                    .about(
+                        "Provisions and starts a node on a remote host over SSH, adds it to a \
+                         registry, and waits for it to peer",
+                    )
+                    .arg(
+                        Arg::with_name("host")
                             .required(true)
-                            .help("Path of public key file to include with node"),
+                            .takes_value(true)
+                            .help("SSH destination of the host to bootstrap ([user@]hostname)"),
+                    )
+                    .arg(
+                        Arg::with_name("config")
+                            .long("config")
+                            .takes_value(true)
+                            .help("Local splinterd config file to install on the host"),
+                    )
+                    .arg(
+                        Arg::with_name("node_url")
+                            .long("node-url")
+                            .takes_value(true)
+                            .help(
+                                "URL of the REST API of the new node \
+                                 (default: http://<hostname>:8080)",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("url")
+                            .short("U")
+                            .long("url")
+                            .takes_value(true)
+                            .help("URL of the Splinter daemon REST API whose registry is updated"),
                     )
                     .arg(
                         Arg::with_name("metadata")
                             .long("metadata")
                             .takes_value(true)
                             .multiple(true)
-                            .help("Metadata to include with node (<key>=<value>)"),
+                            .number_of_values(1)
+                            .help("Metadata for the node's registry entry (<key>=<value>)"),
+                    )
+                    .arg(
+                        Arg::with_name("expect_peer")
+                            .long("expect-peer")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .help("ID of a node the new node must connect to"),
+                    )
+                    .arg(
+                        Arg::with_name("timeout")
+                            .long("timeout")
+                            .takes_value(true)
+                            .help(
+                                "Seconds to wait for the node to come up and peer (default: 120)",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("ssh_option")
+                            .short("o")
+                            .long("ssh-option")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .help("Option passed to ssh, such as Port=2222"),
+                    )
+                    .arg(
+                        Arg::with_name("force")
+                            .short("f")
+                            .long("force")
+                            .help("Replace an existing, differing registry entry"),
                     )
                     .arg(
                         Arg::with_name("private_key_file")
@@ -703,35 +1465,554 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                             .takes_value(true)
                             .help("Name or path of private key"),
                     ),
-            ),
-    );
+            );
+        }
 
-    #[cfg(feature = "health")]
+        #[cfg(feature = "log-retrieval")]
+        {
+            node_command = node_command.subcommand(
+                SubCommand::with_name("logs")
+                    .about("Displays the most recent log entries of a running node")
+                    .arg(
+                        Arg::with_name("follow")
+                            .short("f")
+                            .long("follow")
+                            .help("Keep displaying new log entries as they are logged"),
+                    )
+                    .arg(
+                        Arg::with_name("level")
+                            .long("level")
+                            .takes_value(true)
+                            .possible_values(&["error", "warn", "info", "debug", "trace"])
+                            .help("Only display entries at the given level or more severe"),
+                    )
+                    .arg(
+                        Arg::with_name("module")
+                            .long("module")
+                            .takes_value(true)
+                            .help("Only display entries from modules starting with the given path"),
+                    )
+                    .arg(
+                        Arg::with_name("lines")
+                            .short("n")
+                            .long("lines")
+                            .takes_value(true)
+                            .help("Number of recent entries to display initially (default: 100)"),
+                    )
+                    .arg(
+                        Arg::with_name("url")
+                            .short("U")
+                            .long("url")
+                            .takes_value(true)
+                            .help("URL of the Splinter daemon REST API"),
+                    )
+                    .arg(
+                        Arg::with_name("private_key_file")
+                            .value_name("private-key-file")
+                            .short("k")
+                            .long("key")
+                            .takes_value(true)
+                            .help("Name or path of private key"),
+                    ),
+            );
+        }
+
+        app = app.subcommand(node_command);
+    }
+
+    #[cfg(feature = "peer-management")]
     {
+        let url_arg = Arg::with_name("url")
+            .short("U")
+            .long("url")
+            .takes_value(true)
+            .help("URL of the Splinter daemon REST API");
+        let private_key_file_arg = Arg::with_name("private_key_file")
+            .value_name("private-key-file")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("Name or path of private key");
+
         app = app.subcommand(
-            SubCommand::with_name("health")
-                .about("Displays information about network health")
+            SubCommand::with_name("peer")
+                .about("Manages the peers of a running node")
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(
-                    SubCommand::with_name("status")
-                        .about(
-                            "Displays a node's version, endpoint, node id, and a list\n\
-                             of endpoints of its connected peers",
+                    SubCommand::with_name("list")
+                        .about("Lists the node's connected peers and the endpoints added to it")
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "csv"])
+                                .default_value("human")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Shows a connected peer")
+                        .arg(
+                            Arg::with_name("peer_id")
+                                .value_name("peer-id")
+                                .takes_value(true)
+                                .required(true)
+                                .help("ID of the peer to show"),
                         )
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
                         .arg(
-                            Arg::with_name("url")
-                                .short("U")
-                                .long("url")
-                                .help("URL of the Splinter daemon REST API")
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "yaml", "json"])
+                                .default_value("human")
                                 .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Connects the node to a peer")
+                        .arg(
+                            Arg::with_name("endpoint")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Network endpoint of the peer"),
                         )
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes a peer that was added by endpoint")
                         .arg(
-                            Arg::with_name("private_key_file")
-                                .value_name("private-key-file")
-                                .short("k")
-                                .long("key")
+                            Arg::with_name("endpoint")
                                 .takes_value(true)
-                                .help("Name or path of private key"),
+                                .required(true)
+                                .help("Network endpoint the peer was added with"),
+                        )
+                        .arg(url_arg)
+                        .arg(private_key_file_arg),
+                ),
+        );
+    }
+
+    #[cfg(feature = "admin-allowed-signers")]
+    {
+        let url_arg = Arg::with_name("url")
+            .short("U")
+            .long("url")
+            .takes_value(true)
+            .help("URL of the Splinter daemon REST API");
+        let private_key_file_arg = Arg::with_name("private_key_file")
+            .value_name("private-key-file")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("Name or path of private key");
+        let public_key_arg = Arg::with_name("public_key")
+            .value_name("public-key")
+            .takes_value(true)
+            .required(true)
+            .help("Hex-encoded public key of the signer");
+
+        app = app.subcommand(
+            SubCommand::with_name("signer")
+                .about(
+                    "Manages the signers a running node allows to propose and vote on circuit \
+                     changes on behalf of member nodes",
+                )
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the node's allowed signers")
+                        .arg(
+                            Arg::with_name("node_id")
+                                .long("node-id")
+                                .takes_value(true)
+                                .help("Only list the signers of the given node"),
+                        )
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "csv"])
+                                .default_value("human")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about(
+                            "Allows a key to sign for a node in the given roles, replacing any \
+                             existing entry for the key",
+                        )
+                        .long_about(
+                            "Allows a key to sign for a node in the given roles, replacing any \
+                             existing entry for the key. Once a node has an allowed signer, \
+                             proposals and votes for that node are only accepted from its \
+                             allowed signers.",
+                        )
+                        .arg(public_key_arg.clone())
+                        .arg(
+                            Arg::with_name("node_id")
+                                .long("node-id")
+                                .takes_value(true)
+                                .required(true)
+                                .help("ID of the node the key signs for"),
+                        )
+                        .arg(
+                            Arg::with_name("roles")
+                                .long("role")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .required(true)
+                                .possible_values(&["proposer", "voter"])
+                                .help("Role the key may act in; may be repeated"),
+                        )
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes an allowed signer")
+                        .arg(public_key_arg)
+                        .arg(url_arg)
+                        .arg(private_key_file_arg),
+                ),
+        );
+    }
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    {
+        let url_arg = Arg::with_name("url")
+            .short("U")
+            .long("url")
+            .takes_value(true)
+            .help("URL of the Splinter daemon REST API");
+        let private_key_file_arg = Arg::with_name("private_key_file")
+            .value_name("private-key-file")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("Name or path of private key");
+        let format_arg = Arg::with_name("format")
+            .short("F")
+            .long("format")
+            .help("Output format")
+            .possible_values(&["human", "csv"])
+            .default_value("human")
+            .takes_value(true);
+        let circuit_id_arg = Arg::with_name("circuit_id")
+            .value_name("circuit-id")
+            .takes_value(true)
+            .required(true)
+            .help("ID of the proposed circuit");
+        let management_type_arg = Arg::with_name("management_type")
+            .value_name("management-type")
+            .takes_value(true)
+            .required(true)
+            .help("Circuit management type of the proposals");
+
+        let sign_vote = SubCommand::with_name("sign")
+            .about("Co-signs a vote waiting for co-signatures")
+            .long_about(
+                "Co-signs a vote waiting for co-signatures. The vote is submitted once it has \
+                 collected the number of signatures required for the proposal's circuit \
+                 management type.",
+            )
+            .arg(circuit_id_arg.clone())
+            .arg(url_arg.clone())
+            .arg(private_key_file_arg.clone());
+
+        #[cfg(feature = "hsm-signing")]
+        let sign_vote = with_hsm_args(sign_vote, "private_key_file");
+
+        app = app.subcommand(
+            SubCommand::with_name("cosign")
+                .about("Manages the co-signing of a running node's votes on circuit proposals")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the votes waiting for co-signatures")
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
+                        .arg(format_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Shows a vote waiting for co-signatures")
+                        .arg(circuit_id_arg.clone())
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(sign_vote)
+                .subcommand(
+                    SubCommand::with_name("discard")
+                        .about("Discards a vote waiting for co-signatures")
+                        .arg(circuit_id_arg)
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("threshold")
+                        .about("Manages the number of signatures required on votes")
+                        .setting(AppSettings::SubcommandRequiredElseHelp)
+                        .subcommand(
+                            SubCommand::with_name("list")
+                                .about("Lists the vote thresholds")
+                                .arg(url_arg.clone())
+                                .arg(private_key_file_arg.clone())
+                                .arg(format_arg),
+                        )
+                        .subcommand(
+                            SubCommand::with_name("set")
+                                .about(
+                                    "Sets the number of signatures required on votes for \
+                                     proposals of a circuit management type",
+                                )
+                                .arg(management_type_arg.clone())
+                                .arg(
+                                    Arg::with_name("required_signatures")
+                                        .value_name("signatures")
+                                        .takes_value(true)
+                                        .required(true)
+                                        .help(
+                                            "Number of signatures required, including the \
+                                             signer of the vote",
+                                        ),
+                                )
+                                .arg(url_arg.clone())
+                                .arg(private_key_file_arg.clone()),
+                        )
+                        .subcommand(
+                            SubCommand::with_name("remove")
+                                .about("Removes a vote threshold")
+                                .arg(management_type_arg)
+                                .arg(url_arg)
+                                .arg(private_key_file_arg),
+                        ),
+                ),
+        );
+    }
+
+    #[cfg(feature = "admin-vote-delegation")]
+    {
+        let url_arg = Arg::with_name("url")
+            .short("U")
+            .long("url")
+            .takes_value(true)
+            .help("URL of the Splinter daemon REST API");
+        let private_key_file_arg = Arg::with_name("private_key_file")
+            .value_name("private-key-file")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("Name or path of private key");
+        let node_id_arg = Arg::with_name("node_id")
+            .long("node-id")
+            .takes_value(true)
+            .required(true)
+            .help("ID of the node whose votes are delegated");
+        let delegate_arg = Arg::with_name("delegate")
+            .value_name("public-key")
+            .long("delegate")
+            .takes_value(true)
+            .required(true)
+            .help("Hex-encoded public key the node's votes are delegated to");
+
+        let create_delegation = SubCommand::with_name("create")
+            .about("Delegates a node's votes on circuit proposals to another key")
+            .long_about(
+                "Delegates a node's votes on proposals of the given circuit management types to \
+                 another key until the delegation expires. The delegation is signed with the \
+                 private key, which must be permitted to vote for the node, and is added to the \
+                 node at the given URL. The delegation must be added to each member node of a \
+                 circuit for the delegate's votes to be accepted by all of them.",
+            )
+            .arg(node_id_arg.clone())
+            .arg(delegate_arg.clone())
+            .arg(
+                Arg::with_name("management_type")
+                    .long("management-type")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .required(true)
+                    .help("Circuit management type of the proposals the delegate may vote on"),
+            )
+            .arg(
+                Arg::with_name("expires_in")
+                    .value_name("seconds")
+                    .long("expires-in")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Number of seconds until the delegation expires"),
+            )
+            .arg(url_arg.clone())
+            .arg(private_key_file_arg.clone());
+
+        #[cfg(feature = "hsm-signing")]
+        let create_delegation = with_hsm_args(create_delegation, "private_key_file");
+
+        app = app.subcommand(
+            SubCommand::with_name("delegation")
+                .about("Manages the delegations of votes on circuit proposals on a running node")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(create_delegation)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the vote delegations honored by the node")
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "csv"])
+                                .default_value("human")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("revoke")
+                        .about("Revokes a vote delegation")
+                        .arg(node_id_arg)
+                        .arg(delegate_arg)
+                        .arg(url_arg)
+                        .arg(private_key_file_arg),
+                ),
+        );
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    {
+        let url_arg = Arg::with_name("url")
+            .short("U")
+            .long("url")
+            .takes_value(true)
+            .help("URL of the Splinter daemon REST API");
+        let private_key_file_arg = Arg::with_name("private_key_file")
+            .value_name("private-key-file")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("Name or path of a Biome admin private key");
+        let user_id_arg = Arg::with_name("user_id")
+            .value_name("user-id")
+            .takes_value(true)
+            .required(true)
+            .help("ID of the Biome user");
+
+        app = app.subcommand(
+            SubCommand::with_name("user")
+                .about("Administers the Biome users of a running node")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the node's Biome users")
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone())
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "csv"])
+                                .default_value("human")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("disable")
+                        .about("Prevents a user from logging in and ends the user's sessions")
+                        .arg(user_id_arg.clone())
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("enable")
+                        .about("Allows a disabled user to log in again")
+                        .arg(user_id_arg.clone())
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes a user along with the user's keys and sessions")
+                        .arg(user_id_arg.clone())
+                        .arg(url_arg.clone())
+                        .arg(private_key_file_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-role")
+                        .about("Replaces the roles assigned to a user")
+                        .arg(user_id_arg)
+                        .arg(
+                            Arg::with_name("roles")
+                                .value_name("role")
+                                .takes_value(true)
+                                .multiple(true)
+                                .required_unless("clear")
+                                .help("Roles to assign to the user"),
+                        )
+                        .arg(
+                            Arg::with_name("clear")
+                                .long("clear")
+                                .conflicts_with("roles")
+                                .help("Remove all roles from the user"),
+                        )
+                        .arg(url_arg)
+                        .arg(private_key_file_arg),
+                ),
+        );
+    }
+
+    #[cfg(feature = "scabbard-verify")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("scabbard")
+                .about("Scabbard service commands")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about(
+                            "Replays a scabbard service's transaction receipts and checks that\n\
+                             they result in the service's current state root",
+                        )
+                        .arg(
+                            Arg::with_name("circuit")
+                                .help("ID of the circuit the service is on")
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("service")
+                                .help("ID of the service to verify")
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("db_dir")
+                                .long("db-dir")
+                                .help("Directory containing the service's databases")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("db_size")
+                                .long("db-size")
+                                .help("Size of the service's databases, in megabytes")
+                                .takes_value(true),
                         ),
                 ),
         );
@@ -796,7 +2077,21 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
         )
         .with_command(
             "cert",
-            SubcommandActions::new().with_command("generate", certs::CertGenAction),
+            SubcommandActions::new()
+                .with_command("generate", certs::CertGenAction)
+                .with_command("request", certs::CertRequestAction)
+                .with_command("sign", certs::CertSignAction)
+                .with_command("renew", certs::CertRenewAction)
+                .with_command("verify", certs::CertVerifyAction),
+        )
+        .with_command(
+            "key",
+            SubcommandActions::new()
+                .with_command("generate", keygen::KeyGenAction)
+                .with_command("list", key::KeyListAction)
+                .with_command("rotate", key::KeyRotateAction)
+                .with_command("export-public", key::KeyExportPublicAction)
+                .with_command("revoke", key::KeyRevokeAction),
         )
         .with_command("keygen", keygen::KeyGenAction);
 
@@ -816,6 +2111,14 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
             .with_command("arguments", circuit::template::ListCircuitTemplateArguments),
     );
 
+    #[cfg(feature = "circuit-ping")]
+    let circuit_command = circuit_command.with_command("ping", circuit::CircuitPingAction);
+
+    #[cfg(feature = "circuit-archive")]
+    let circuit_command = circuit_command
+        .with_command("export", circuit::CircuitExportAction)
+        .with_command("import", circuit::CircuitImportAction);
+
     subcommands = subcommands.with_command("circuit", circuit_command);
 
     subcommands = subcommands.with_command(
@@ -823,6 +2126,21 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
         SubcommandActions::new().with_command("build", registry::RegistryGenerateAction),
     );
 
+    #[cfg(feature = "bench")]
+    {
+        use action::bench;
+        subcommands = subcommands.with_command("bench", bench::BenchAction);
+    }
+
+    #[cfg(feature = "message-capture")]
+    {
+        use action::capture;
+        subcommands = subcommands.with_command(
+            "capture",
+            SubcommandActions::new().with_command("inspect", capture::CaptureInspectAction),
+        );
+    }
+
     #[cfg(feature = "health")]
     {
         use action::health;
@@ -832,6 +2150,108 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
         );
     }
 
+    #[cfg(any(feature = "node-challenge", feature = "log-retrieval"))]
+    {
+        #[allow(unused_mut)]
+        let mut node_command = SubcommandActions::new();
+        #[cfg(feature = "node-challenge")]
+        {
+            use action::node;
+            node_command = node_command.with_command("verify", node::NodeVerifyAction);
+        }
+        #[cfg(feature = "node-bootstrap")]
+        {
+            use action::bootstrap;
+            node_command = node_command.with_command("bootstrap", bootstrap::NodeBootstrapAction);
+        }
+        #[cfg(feature = "log-retrieval")]
+        {
+            use action::logs;
+            node_command = node_command.with_command("logs", logs::NodeLogsAction);
+        }
+        subcommands = subcommands.with_command("node", node_command);
+    }
+
+    #[cfg(feature = "peer-management")]
+    {
+        use action::peer;
+        subcommands = subcommands.with_command(
+            "peer",
+            SubcommandActions::new()
+                .with_command("list", peer::PeerListAction)
+                .with_command("show", peer::PeerShowAction)
+                .with_command("add", peer::PeerAddAction)
+                .with_command("remove", peer::PeerRemoveAction),
+        );
+    }
+
+    #[cfg(feature = "scabbard-verify")]
+    {
+        use action::scabbard;
+        subcommands = subcommands.with_command(
+            "scabbard",
+            SubcommandActions::new().with_command("verify", scabbard::ScabbardVerifyAction),
+        );
+    }
+
+    #[cfg(feature = "admin-allowed-signers")]
+    {
+        use action::signer;
+        subcommands = subcommands.with_command(
+            "signer",
+            SubcommandActions::new()
+                .with_command("list", signer::SignerListAction)
+                .with_command("add", signer::SignerAddAction)
+                .with_command("remove", signer::SignerRemoveAction),
+        );
+    }
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    {
+        use action::cosign;
+        subcommands = subcommands.with_command(
+            "cosign",
+            SubcommandActions::new()
+                .with_command("list", cosign::PendingVoteListAction)
+                .with_command("show", cosign::PendingVoteShowAction)
+                .with_command("sign", cosign::PendingVoteSignAction)
+                .with_command("discard", cosign::PendingVoteDiscardAction)
+                .with_command(
+                    "threshold",
+                    SubcommandActions::new()
+                        .with_command("list", cosign::VoteThresholdListAction)
+                        .with_command("set", cosign::VoteThresholdSetAction)
+                        .with_command("remove", cosign::VoteThresholdRemoveAction),
+                ),
+        );
+    }
+
+    #[cfg(feature = "admin-vote-delegation")]
+    {
+        use action::delegation;
+        subcommands = subcommands.with_command(
+            "delegation",
+            SubcommandActions::new()
+                .with_command("create", delegation::DelegationCreateAction)
+                .with_command("list", delegation::DelegationListAction)
+                .with_command("revoke", delegation::DelegationRevokeAction),
+        );
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    {
+        use action::user;
+        subcommands = subcommands.with_command(
+            "user",
+            SubcommandActions::new()
+                .with_command("list", user::UserListAction)
+                .with_command("disable", user::UserDisableAction { disabled: true })
+                .with_command("enable", user::UserDisableAction { disabled: false })
+                .with_command("remove", user::UserRemoveAction)
+                .with_command("set-role", user::UserSetRoleAction),
+        );
+    }
+
     #[cfg(feature = "database")]
     {
         use action::database;
@@ -844,6 +2264,46 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
     subcommands.run(Some(&matches))
 }
 
+/// Adds the arguments for signing admin payloads with a key held in an HSM.
+#[cfg(feature = "hsm-signing")]
+fn with_hsm_args<'a, 'b>(subcommand: App<'a, 'b>, key_arg: &'a str) -> App<'a, 'b> {
+    subcommand
+        .arg(
+            Arg::with_name("hsm_slot")
+                .long("hsm-slot")
+                .takes_value(true)
+                .conflicts_with(key_arg)
+                .help("ID of the PKCS#11 slot holding the signing key"),
+        )
+        .arg(
+            Arg::with_name("hsm_pin_env")
+                .long("hsm-pin-env")
+                .takes_value(true)
+                .requires("hsm_slot")
+                .help(
+                    "Name of the environment variable holding the HSM user PIN \
+                     (default: SPLINTER_HSM_PIN)",
+                ),
+        )
+        .arg(
+            Arg::with_name("hsm_module")
+                .long("hsm-module")
+                .takes_value(true)
+                .requires("hsm_slot")
+                .help(
+                    "Path to the HSM's PKCS#11 module (default: the value of \
+                     SPLINTER_HSM_MODULE)",
+                ),
+        )
+        .arg(
+            Arg::with_name("hsm_key_label")
+                .long("hsm-key-label")
+                .takes_value(true)
+                .requires("hsm_slot")
+                .help("Label of the signing key, if the slot holds more than one key"),
+        )
+}
+
 fn main() {
     match run(std::env::args_os()) {
         Ok(_) => {}
@@ -1284,6 +1284,8 @@ mod test {
             members: vec![SplinterNode {
                 node_id: "Node-123".to_string(),
                 endpoints: vec!["127.0.0.1:8282".to_string()],
+                relay: false,
+                observer: false,
             }],
             authorization_type: AuthorizationType::Trust,
             persistence: PersistenceType::Any,
@@ -1293,6 +1295,7 @@ mod test {
             application_metadata,
             comments: Some("test circuit".to_string()),
             display_name: None,
+            qos: Default::default(),
         }
     }
 
@@ -1306,6 +1309,8 @@ mod test {
             votes: vec![],
             requester: public_key(),
             requester_node_id: "acme_corp".to_string(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
@@ -1325,6 +1330,8 @@ mod test {
             votes: vec![vote],
             requester: public_key(),
             requester_node_id: "acme_corp".to_string(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
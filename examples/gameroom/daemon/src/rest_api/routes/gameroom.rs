@@ -129,12 +129,16 @@ pub async fn propose_gameroom(
         .map(|node| SplinterNode {
             node_id: node.identity.to_string(),
             endpoints: node.endpoints.to_vec(),
+            relay: false,
+            observer: false,
         })
         .collect::<Vec<SplinterNode>>();
 
     members.push(SplinterNode {
         node_id: node_info.identity.to_string(),
         endpoints: node_info.endpoints.to_vec(),
+        relay: false,
+        observer: false,
     });
 
     let node_ids = nodes
@@ -0,0 +1,164 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for routing messages through a `Dispatcher` to their handlers.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use protobuf::Message;
+
+use splinter::network::dispatch::{
+    DispatchError, Dispatcher, Handler, MessageContext, MessageSender, PeerId, RawBytes,
+};
+use splinter::protos::network::{NetworkEcho, NetworkMessageType};
+
+// A sender that discards all messages.
+struct NoopSender;
+
+impl MessageSender<PeerId> for NoopSender {
+    fn send(&self, _: PeerId, _: Vec<u8>) -> Result<(), (PeerId, Vec<u8>)> {
+        Ok(())
+    }
+}
+
+// A handler that accepts the raw bytes of a message of the given type.
+struct RawHandler(NetworkMessageType);
+
+impl Handler for RawHandler {
+    type Source = PeerId;
+    type MessageType = NetworkMessageType;
+    type Message = RawBytes;
+
+    fn match_type(&self) -> Self::MessageType {
+        self.0
+    }
+
+    fn handle(
+        &self,
+        message: RawBytes,
+        _: &MessageContext<Self::Source, Self::MessageType>,
+        _: &dyn MessageSender<Self::Source>,
+    ) -> Result<(), DispatchError> {
+        black_box(message);
+        Ok(())
+    }
+}
+
+// A handler that parses a `NetworkEcho` and replies with its payload.
+struct EchoHandler;
+
+impl Handler for EchoHandler {
+    type Source = PeerId;
+    type MessageType = NetworkMessageType;
+    type Message = NetworkEcho;
+
+    fn match_type(&self) -> Self::MessageType {
+        NetworkMessageType::NETWORK_ECHO
+    }
+
+    fn handle(
+        &self,
+        message: NetworkEcho,
+        context: &MessageContext<Self::Source, Self::MessageType>,
+        sender: &dyn MessageSender<Self::Source>,
+    ) -> Result<(), DispatchError> {
+        sender
+            .send(
+                context.source_peer_id().into(),
+                message.get_payload().to_vec(),
+            )
+            .map_err(|(recipient, payload)| {
+                DispatchError::NetworkSendError((recipient.into(), payload))
+            })
+    }
+}
+
+fn echo_bytes(size: usize) -> Vec<u8> {
+    let mut echo = NetworkEcho::new();
+    echo.set_payload(vec![0u8; size]);
+    echo.write_to_bytes().expect("Unable to write echo")
+}
+
+// Benchmark routing raw bytes to one of several registered handlers.
+fn bench_dispatch_raw_bytes(c: &mut Criterion) {
+    let mut dispatcher = Dispatcher::new(Box::new(NoopSender));
+    dispatcher.set_handler(Box::new(RawHandler(NetworkMessageType::NETWORK_HEARTBEAT)));
+    dispatcher.set_handler(Box::new(RawHandler(NetworkMessageType::CIRCUIT)));
+    dispatcher.set_handler(Box::new(RawHandler(NetworkMessageType::AUTHORIZATION)));
+
+    let bytes = vec![0u8; 1024];
+
+    let mut group = c.benchmark_group("dispatch");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("raw_bytes", |b| {
+        b.iter(|| {
+            dispatcher
+                .dispatch(
+                    "bench-peer".into(),
+                    &NetworkMessageType::CIRCUIT,
+                    bytes.clone(),
+                )
+                .expect("Unable to dispatch message")
+        })
+    });
+    group.finish();
+}
+
+// Benchmark routing a 1 KiB `NetworkEcho`, including parsing the message and sending the reply.
+fn bench_dispatch_echo(c: &mut Criterion) {
+    let mut dispatcher = Dispatcher::new(Box::new(NoopSender));
+    dispatcher.set_handler(Box::new(EchoHandler));
+
+    let bytes = echo_bytes(1024);
+
+    let mut group = c.benchmark_group("dispatch");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("echo_1kib", |b| {
+        b.iter(|| {
+            dispatcher
+                .dispatch(
+                    "bench-peer".into(),
+                    &NetworkMessageType::NETWORK_ECHO,
+                    bytes.clone(),
+                )
+                .expect("Unable to dispatch message")
+        })
+    });
+    group.finish();
+}
+
+// Benchmark the error path for a message type with no handler.
+fn bench_dispatch_unknown_type(c: &mut Criterion) {
+    let mut dispatcher = Dispatcher::new(Box::new(NoopSender));
+    dispatcher.set_handler(Box::new(EchoHandler));
+
+    c.bench_function("dispatch/unknown_type", |b| {
+        b.iter(|| {
+            dispatcher
+                .dispatch(
+                    "bench-peer".into(),
+                    &NetworkMessageType::NETWORK_HEARTBEAT,
+                    vec![],
+                )
+                .expect_err("Dispatch should fail without a handler")
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_dispatch_raw_bytes,
+    bench_dispatch_echo,
+    bench_dispatch_unknown_type
+);
+criterion_main!(benches);
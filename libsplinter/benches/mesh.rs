@@ -0,0 +1,109 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for sending and receiving envelopes between two meshes connected over an in-process
+//! transport.
+//!
+//! The message path target is 50,000 envelopes per second for 1 KiB payloads; see
+//! `splinter bench` for a standalone check against this target.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use splinter::mesh::{Envelope, Mesh};
+use splinter::transport::inproc::InprocTransport;
+use splinter::transport::Transport;
+
+// Connects two meshes, returning the mesh for each side of the connection. Envelopes sent from
+// the first mesh to "node-b" are received by the second mesh from "node-a".
+fn connected_meshes(name: &str) -> (Mesh, Mesh) {
+    let mut transport = InprocTransport::default();
+    let mut listener = transport
+        .listen(&format!("inproc://{}", name))
+        .expect("Unable to listen");
+
+    let mesh_a = Mesh::new(512, 128);
+    let mesh_b = Mesh::new(512, 128);
+
+    mesh_a
+        .add(
+            transport
+                .connect(&listener.endpoint())
+                .expect("Unable to connect"),
+            "node-b".to_string(),
+        )
+        .expect("Unable to add connection to mesh a");
+    mesh_b
+        .add(
+            listener.accept().expect("Unable to accept connection"),
+            "node-a".to_string(),
+        )
+        .expect("Unable to add connection to mesh b");
+
+    (mesh_a, mesh_b)
+}
+
+// Benchmark sending a payload from one mesh and receiving it on the other, for payloads of 64
+// bytes, 1 KiB and 64 KiB.
+fn bench_send_recv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mesh_send_recv");
+    for size in &[64, 1024, 64 * 1024] {
+        let (mesh_a, mesh_b) = connected_meshes(&format!("bench_mesh_send_recv_{}", size));
+        let payload = vec![0u8; *size];
+
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &payload, |b, payload| {
+            b.iter(|| {
+                mesh_a
+                    .send(Envelope::new("node-b".to_string(), payload.clone()))
+                    .expect("Unable to send envelope");
+                mesh_b.recv().expect("Unable to receive envelope")
+            })
+        });
+
+        mesh_a.shutdown_signaler().shutdown();
+        mesh_b.shutdown_signaler().shutdown();
+    }
+    group.finish();
+}
+
+// Benchmark the round trip of a 1 KiB payload, sent from one mesh and echoed back by the other.
+fn bench_round_trip(c: &mut Criterion) {
+    let (mesh_a, mesh_b) = connected_meshes("bench_mesh_round_trip_1kib");
+    let payload = vec![0u8; 1024];
+
+    let mut group = c.benchmark_group("mesh_round_trip");
+    group.throughput(Throughput::Bytes(2 * 1024));
+    group.bench_function("1024", |b| {
+        b.iter(|| {
+            mesh_a
+                .send(Envelope::new("node-b".to_string(), payload.clone()))
+                .expect("Unable to send envelope");
+            let envelope = mesh_b.recv().expect("Unable to receive envelope");
+            mesh_b
+                .send(Envelope::new(
+                    envelope.id().to_string(),
+                    envelope.take_payload(),
+                ))
+                .expect("Unable to echo envelope");
+            mesh_a.recv().expect("Unable to receive echo")
+        })
+    });
+    group.finish();
+
+    mesh_a.shutdown_signaler().shutdown();
+    mesh_b.shutdown_signaler().shutdown();
+}
+
+criterion_group!(benches, bench_send_recv, bench_round_trip);
+criterion_main!(benches);
@@ -0,0 +1,108 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for encoding and decoding the messages on the circuit message path: a
+//! `CircuitDirectMessage` wrapped in a `CircuitMessage`, wrapped in a `NetworkMessage`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use protobuf::Message;
+
+use splinter::protos::circuit::{CircuitDirectMessage, CircuitMessage, CircuitMessageType};
+use splinter::protos::envelope::{circuit_envelope, EnvelopeRef};
+use splinter::protos::network::{NetworkMessage, NetworkMessageType};
+
+const PAYLOAD_SIZE: usize = 1024;
+
+fn direct_message_bytes() -> Vec<u8> {
+    let mut direct_message = CircuitDirectMessage::new();
+    direct_message.set_circuit("alpha-bravo".to_string());
+    direct_message.set_sender("abcd::sender".to_string());
+    direct_message.set_recipient("abcd::recipient".to_string());
+    direct_message.set_payload(vec![0u8; PAYLOAD_SIZE]);
+    direct_message.set_correlation_id("1234".to_string());
+    direct_message
+        .write_to_bytes()
+        .expect("Unable to write direct message")
+}
+
+// Encodes the direct message into its envelopes using protobuf messages.
+fn encode_with_protobuf(direct_message_bytes: &[u8]) -> Vec<u8> {
+    let mut circuit_msg = CircuitMessage::new();
+    circuit_msg.set_message_type(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE);
+    circuit_msg.set_payload(direct_message_bytes.to_vec());
+    let circuit_bytes = circuit_msg
+        .write_to_bytes()
+        .expect("Unable to write circuit message");
+
+    let mut network_msg = NetworkMessage::new();
+    network_msg.set_message_type(NetworkMessageType::CIRCUIT);
+    network_msg.set_payload(circuit_bytes);
+    network_msg
+        .write_to_bytes()
+        .expect("Unable to write network message")
+}
+
+// Benchmark encoding a direct message into its envelopes, with protobuf messages and with
+// `circuit_envelope`.
+fn bench_encode_circuit_message(c: &mut Criterion) {
+    let bytes = direct_message_bytes();
+
+    let mut group = c.benchmark_group("encode_circuit_message");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("protobuf", |b| b.iter(|| encode_with_protobuf(&bytes)));
+    group.bench_function("envelope", |b| {
+        b.iter(|| circuit_envelope(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE, &bytes))
+    });
+    group.finish();
+}
+
+// Benchmark decoding a direct message from its envelopes, with protobuf messages and with
+// `EnvelopeRef`.
+fn bench_decode_circuit_message(c: &mut Criterion) {
+    let bytes = encode_with_protobuf(&direct_message_bytes());
+
+    let mut group = c.benchmark_group("decode_circuit_message");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("protobuf", |b| {
+        b.iter(|| {
+            let network_msg: NetworkMessage =
+                protobuf::parse_from_bytes(&bytes).expect("Unable to parse network message");
+            let circuit_msg: CircuitMessage = protobuf::parse_from_bytes(network_msg.get_payload())
+                .expect("Unable to parse circuit message");
+            let direct_message: CircuitDirectMessage =
+                protobuf::parse_from_bytes(circuit_msg.get_payload())
+                    .expect("Unable to parse direct message");
+            direct_message
+        })
+    });
+    group.bench_function("envelope", |b| {
+        b.iter(|| {
+            let network_msg = EnvelopeRef::parse(&bytes).expect("Unable to parse network message");
+            let circuit_msg =
+                EnvelopeRef::parse(network_msg.payload()).expect("Unable to parse circuit message");
+            let direct_message: CircuitDirectMessage =
+                protobuf::parse_from_bytes(circuit_msg.payload())
+                    .expect("Unable to parse direct message");
+            direct_message
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_encode_circuit_message,
+    bench_decode_circuit_message
+);
+criterion_main!(benches);
@@ -65,9 +65,66 @@ fn main() {
                 .collect::<Vec<&str>>(),
         )
         .includes(&["src", "protos"])
-        .customize(Customize::default())
+        .customize(Customize {
+            // Serde implementations are only compiled with the `protos-serde` feature
+            serde_derive: Some(true),
+            serde_derive_cfg: Some("feature = \"protos-serde\"".into()),
+            ..Default::default()
+        })
         .run()
         .expect("unable to run protoc");
+
+    // Serialize bytes fields as hex strings, rather than arrays of numbers
+    for proto_file in &proto_src_files {
+        let stem = Path::new(proto_file)
+            .file_stem()
+            .expect("Unable to extract stem")
+            .to_str()
+            .expect("Unable to extract filename");
+        add_hex_bytes_attributes(&dest_path.join(format!("{}.rs", stem)));
+    }
+}
+
+/// Adds the `protos::hex_bytes` serde functions to the bytes fields of the generated messages.
+fn add_hex_bytes_attributes(path: &Path) {
+    let content = fs::read_to_string(path).expect("Unable to read generated proto file");
+
+    let mut output = String::with_capacity(content.len());
+    for line in content.lines() {
+        let with = if is_field(line, "::std::vec::Vec<u8>") {
+            Some("crate::protos::hex_bytes")
+        } else if is_field(line, "::protobuf::RepeatedField<::std::vec::Vec<u8>>") {
+            Some("crate::protos::hex_bytes::repeated")
+        } else {
+            None
+        };
+        if let Some(with) = with {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            output.push_str(&format!(
+                "{}#[cfg_attr(feature = \"protos-serde\", serde(with = \"{}\"))]\n",
+                indent, with
+            ));
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    fs::write(path, output).expect("Unable to write generated proto file");
+}
+
+/// Returns whether the line declares a struct field of the given type.
+fn is_field(line: &str, field_type: &str) -> bool {
+    let line = line.trim();
+    let line = line.trim_start_matches("pub ");
+    let suffix = format!(": {},", field_type);
+    if !line.ends_with(&suffix) {
+        return false;
+    }
+    let name = &line[..line.len() - suffix.len()];
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '#')
 }
 
 fn glob_simple(pattern: &str) -> Vec<String> {
@@ -0,0 +1,187 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(in crate::admin) mod models;
+mod operations;
+pub(in crate::admin) mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use super::{AllowedSigner, AllowedSignerStore, AllowedSignerStoreError};
+
+use operations::{
+    add_signer::AllowedSignerStoreAddSigner as _, get_signer::AllowedSignerStoreGetSigner as _,
+    list_signers::AllowedSignerStoreListSigners as _,
+    remove_signer::AllowedSignerStoreRemoveSigner as _, AllowedSignerStoreOperations,
+};
+
+pub struct DieselAllowedSignerStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselAllowedSignerStore<C> {
+    /// Creates a new `DieselAllowedSignerStore`
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl AllowedSignerStore for DieselAllowedSignerStore<diesel::sqlite::SqliteConnection> {
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).add_signer(signer)
+    }
+
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).get_signer(public_key)
+    }
+
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).list_signers(node_id)
+    }
+
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).remove_signer(public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn AllowedSignerStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl AllowedSignerStore for DieselAllowedSignerStore<diesel::pg::PgConnection> {
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).add_signer(signer)
+    }
+
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).get_signer(public_key)
+    }
+
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).list_signers(node_id)
+    }
+
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError> {
+        AllowedSignerStoreOperations::new(&*self.connection_pool.get()?).remove_signer(public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn AllowedSignerStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::sqlite::SqliteConnection;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    /// Verify that a SQLite-backed `DieselAllowedSignerStore` correctly supports adding,
+    /// replacing, listing and removing allowed signers.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Add two signers for different nodes and verify that they are returned and listed,
+    ///    both in full and by node.
+    /// 3. Replace the roles of the first signer and verify that only the new roles are returned.
+    /// 4. Remove the first signer and verify that it is no longer returned, and that removing it
+    ///    again returns a `NotFound` error.
+    #[test]
+    fn sqlite_allowed_signers() {
+        let store = DieselAllowedSignerStore::new(create_connection_pool_and_migrate());
+
+        let signer_a = AllowedSigner::new(KEY_A, "node-a", vec!["proposer".into(), "voter".into()]);
+        let signer_b = AllowedSigner::new(KEY_B, "node-b", vec!["voter".into()]);
+
+        store
+            .add_signer(signer_a.clone())
+            .expect("Failed to add signer");
+        store
+            .add_signer(signer_b.clone())
+            .expect("Failed to add signer");
+
+        assert_eq!(
+            store.get_signer(KEY_A).expect("Failed to get signer"),
+            Some(signer_a.clone())
+        );
+        assert_eq!(
+            store.list_signers(None).expect("Failed to list signers"),
+            vec![signer_a, signer_b.clone()]
+        );
+        assert_eq!(
+            store
+                .list_signers(Some("node-b"))
+                .expect("Failed to list signers"),
+            vec![signer_b]
+        );
+
+        let signer_a = AllowedSigner::new(KEY_A, "node-a", vec!["voter".into()]);
+        store
+            .add_signer(signer_a.clone())
+            .expect("Failed to replace signer");
+        assert_eq!(
+            store.get_signer(KEY_A).expect("Failed to get signer"),
+            Some(signer_a)
+        );
+
+        store.remove_signer(KEY_A).expect("Failed to remove signer");
+        assert_eq!(store.get_signer(KEY_A).expect("Failed to get signer"), None);
+        assert!(matches!(
+            store.remove_signer(KEY_A),
+            Err(AllowedSignerStoreError::NotFound(_))
+        ));
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
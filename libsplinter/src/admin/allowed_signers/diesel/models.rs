@@ -0,0 +1,30 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::{admin_allowed_signer_roles, admin_allowed_signers};
+
+#[derive(Debug, PartialEq, Identifiable, Insertable, Queryable)]
+#[table_name = "admin_allowed_signers"]
+#[primary_key(public_key)]
+pub struct AllowedSignerModel {
+    pub public_key: String,
+    pub node_id: String,
+}
+
+#[derive(Debug, PartialEq, Insertable, Queryable)]
+#[table_name = "admin_allowed_signer_roles"]
+pub struct AllowedSignerRoleModel {
+    pub public_key: String,
+    pub role: String,
+}
@@ -0,0 +1,115 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::admin::allowed_signers::{
+    check_signer,
+    diesel::{
+        models::{AllowedSignerModel, AllowedSignerRoleModel},
+        schema::{admin_allowed_signer_roles, admin_allowed_signers},
+    },
+    AllowedSigner, AllowedSignerStoreError,
+};
+
+use super::AllowedSignerStoreOperations;
+
+pub trait AllowedSignerStoreAddSigner {
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> AllowedSignerStoreAddSigner
+    for AllowedSignerStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        check_signer(&signer)?;
+        let (signer_model, role_models) = into_models(signer);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing entry for the public key
+            delete(
+                admin_allowed_signer_roles::table
+                    .filter(admin_allowed_signer_roles::public_key.eq(&signer_model.public_key)),
+            )
+            .execute(self.conn)?;
+            delete(admin_allowed_signers::table.find(&signer_model.public_key))
+                .execute(self.conn)?;
+
+            insert_into(admin_allowed_signers::table)
+                .values(&signer_model)
+                .execute(self.conn)?;
+            insert_into(admin_allowed_signer_roles::table)
+                .values(&role_models)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> AllowedSignerStoreAddSigner
+    for AllowedSignerStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        check_signer(&signer)?;
+        let (signer_model, role_models) = into_models(signer);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing entry for the public key
+            delete(
+                admin_allowed_signer_roles::table
+                    .filter(admin_allowed_signer_roles::public_key.eq(&signer_model.public_key)),
+            )
+            .execute(self.conn)?;
+            delete(admin_allowed_signers::table.find(&signer_model.public_key))
+                .execute(self.conn)?;
+
+            insert_into(admin_allowed_signers::table)
+                .values(&signer_model)
+                .execute(self.conn)?;
+            insert_into(admin_allowed_signer_roles::table)
+                .values(&role_models)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+fn into_models(signer: AllowedSigner) -> (AllowedSignerModel, Vec<AllowedSignerRoleModel>) {
+    let AllowedSigner {
+        public_key,
+        node_id,
+        roles,
+    } = signer;
+
+    let role_models = roles
+        .into_iter()
+        .map(|role| AllowedSignerRoleModel {
+            public_key: public_key.clone(),
+            role,
+        })
+        .collect();
+
+    (
+        AllowedSignerModel {
+            public_key,
+            node_id,
+        },
+        role_models,
+    )
+}
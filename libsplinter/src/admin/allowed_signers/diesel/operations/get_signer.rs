@@ -0,0 +1,64 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::allowed_signers::{
+    diesel::{
+        models::AllowedSignerModel,
+        schema::{admin_allowed_signer_roles, admin_allowed_signers},
+    },
+    AllowedSigner, AllowedSignerStoreError,
+};
+
+use super::AllowedSignerStoreOperations;
+
+pub trait AllowedSignerStoreGetSigner {
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError>;
+}
+
+impl<'a, C> AllowedSignerStoreGetSigner for AllowedSignerStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError> {
+        let public_key = public_key.to_lowercase();
+        let signer = match admin_allowed_signers::table
+            .find(&public_key)
+            .first::<AllowedSignerModel>(self.conn)
+            .optional()?
+        {
+            Some(signer) => signer,
+            None => return Ok(None),
+        };
+
+        let roles = admin_allowed_signer_roles::table
+            .filter(admin_allowed_signer_roles::public_key.eq(&public_key))
+            .select(admin_allowed_signer_roles::role)
+            .load::<String>(self.conn)?;
+
+        Ok(Some(AllowedSigner::new(
+            &signer.public_key,
+            &signer.node_id,
+            roles,
+        )))
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use crate::admin::allowed_signers::{
+    diesel::{
+        models::{AllowedSignerModel, AllowedSignerRoleModel},
+        schema::{admin_allowed_signer_roles, admin_allowed_signers},
+    },
+    AllowedSigner, AllowedSignerStoreError,
+};
+
+use super::AllowedSignerStoreOperations;
+
+pub trait AllowedSignerStoreListSigners {
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError>;
+}
+
+impl<'a, C> AllowedSignerStoreListSigners for AllowedSignerStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError> {
+        let signers = match node_id {
+            Some(node_id) => admin_allowed_signers::table
+                .filter(admin_allowed_signers::node_id.eq(node_id))
+                .order(admin_allowed_signers::public_key)
+                .load::<AllowedSignerModel>(self.conn)?,
+            None => admin_allowed_signers::table
+                .order(admin_allowed_signers::public_key)
+                .load::<AllowedSignerModel>(self.conn)?,
+        };
+
+        let mut roles = HashMap::<String, Vec<String>>::new();
+        for role in admin_allowed_signer_roles::table.load::<AllowedSignerRoleModel>(self.conn)? {
+            roles.entry(role.public_key).or_default().push(role.role);
+        }
+
+        Ok(signers
+            .into_iter()
+            .map(|signer| {
+                let signer_roles = roles.remove(&signer.public_key).unwrap_or_default();
+                AllowedSigner::new(&signer.public_key, &signer.node_id, signer_roles)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::admin::allowed_signers::{
+    diesel::schema::{admin_allowed_signer_roles, admin_allowed_signers},
+    AllowedSignerStoreError,
+};
+
+use super::AllowedSignerStoreOperations;
+
+pub trait AllowedSignerStoreRemoveSigner {
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError>;
+}
+
+impl<'a, C> AllowedSignerStoreRemoveSigner for AllowedSignerStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError> {
+        let normalized_key = public_key.to_lowercase();
+        self.conn.transaction::<_, _, _>(|| {
+            delete(
+                admin_allowed_signer_roles::table
+                    .filter(admin_allowed_signer_roles::public_key.eq(&normalized_key)),
+            )
+            .execute(self.conn)?;
+
+            let deleted =
+                delete(admin_allowed_signers::table.find(&normalized_key)).execute(self.conn)?;
+            if deleted == 0 {
+                return Err(AllowedSignerStoreError::NotFound(public_key.to_string()));
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+
+use super::{check_signer, AllowedSigner, AllowedSignerStore, AllowedSignerStoreError};
+
+#[derive(Default, Clone)]
+pub struct MemoryAllowedSignerStore {
+    /// Map of public key -> allowed signer
+    inner: Arc<Mutex<BTreeMap<String, AllowedSigner>>>,
+}
+
+impl MemoryAllowedSignerStore {
+    /// Creates a new memory-backed allowed signer store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<BTreeMap<String, AllowedSigner>>, AllowedSignerStoreError> {
+        self.inner.lock().map_err(|_| {
+            AllowedSignerStoreError::Internal(InternalError::with_message(
+                "Cannot access allowed signer store: mutex lock poisoned".to_string(),
+            ))
+        })
+    }
+}
+
+impl AllowedSignerStore for MemoryAllowedSignerStore {
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        check_signer(&signer)?;
+        self.lock()?.insert(signer.public_key.clone(), signer);
+        Ok(())
+    }
+
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError> {
+        Ok(self.lock()?.get(&public_key.to_lowercase()).cloned())
+    }
+
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError> {
+        Ok(self
+            .lock()?
+            .values()
+            .filter(|signer| node_id.map(|id| signer.node_id == id).unwrap_or(true))
+            .cloned()
+            .collect())
+    }
+
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError> {
+        self.lock()?
+            .remove(&public_key.to_lowercase())
+            .map(|_| ())
+            .ok_or_else(|| AllowedSignerStoreError::NotFound(public_key.to_string()))
+    }
+
+    fn clone_box(&self) -> Box<dyn AllowedSignerStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    /// Verify that the memory store adds, replaces, lists and removes signers, and rejects
+    /// invalid signers.
+    #[test]
+    fn add_list_and_remove_signers() {
+        let store = MemoryAllowedSignerStore::new();
+
+        store
+            .add_signer(AllowedSigner::new(
+                &KEY_A.to_uppercase(),
+                "node-a",
+                vec!["voter".into(), "proposer".into()],
+            ))
+            .expect("Failed to add signer");
+        store
+            .add_signer(AllowedSigner::new(KEY_B, "node-b", vec!["voter".into()]))
+            .expect("Failed to add signer");
+
+        let signer_a = store
+            .get_signer(KEY_A)
+            .expect("Failed to get signer")
+            .expect("Signer not found");
+        assert_eq!(signer_a.node_id(), "node-a");
+        assert_eq!(
+            signer_a.roles(),
+            &["proposer".to_string(), "voter".to_string()]
+        );
+
+        assert_eq!(
+            store
+                .list_signers(Some("node-b"))
+                .expect("Failed to list signers"),
+            vec![AllowedSigner::new(KEY_B, "node-b", vec!["voter".into()])]
+        );
+
+        store
+            .add_signer(AllowedSigner::new(KEY_B, "node-a", vec!["proposer".into()]))
+            .expect("Failed to replace signer");
+        assert!(store
+            .list_signers(Some("node-b"))
+            .expect("Failed to list signers")
+            .is_empty());
+        assert_eq!(
+            store
+                .list_signers(None)
+                .expect("Failed to list signers")
+                .len(),
+            2
+        );
+
+        assert!(matches!(
+            store.add_signer(AllowedSigner::new(KEY_A, "node-a", vec!["admin".into()])),
+            Err(AllowedSignerStoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            store.add_signer(AllowedSigner::new("xyz", "node-a", vec!["voter".into()])),
+            Err(AllowedSignerStoreError::InvalidArgument(_))
+        ));
+
+        store.remove_signer(KEY_A).expect("Failed to remove signer");
+        assert!(store
+            .get_signer(KEY_A)
+            .expect("Failed to get signer")
+            .is_none());
+        assert!(matches!(
+            store.remove_signer(KEY_A),
+            Err(AllowedSignerStoreError::NotFound(_))
+        ));
+    }
+}
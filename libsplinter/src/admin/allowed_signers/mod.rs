@@ -0,0 +1,332 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A node-level store of the signers that are allowed to submit circuit management payloads on
+//! behalf of circuit member nodes.
+//!
+//! Each allowed signer is a public key assigned to a single node, along with the roles
+//! (`proposer` and/or `voter`) the key may act in for that node. The store is consulted by the
+//! admin service through the [`AllowedSignerKeyVerifier`]: a node without any allowed signers is
+//! only checked by the wrapped verifier, but once a node has allowed signers, proposals and votes
+//! for that node are only accepted from those signers in their assigned roles.
+
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod memory;
+
+use crate::admin::service::{AdminKeyVerifier, AdminKeyVerifierError, PROPOSER_ROLE, VOTER_ROLE};
+use crate::error::InvalidArgumentError;
+use crate::hex::to_hex;
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselAllowedSignerStore;
+pub use error::AllowedSignerStoreError;
+pub use memory::MemoryAllowedSignerStore;
+
+/// A public key that is allowed to sign circuit management payloads on behalf of a node
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowedSigner {
+    public_key: String,
+    node_id: String,
+    roles: Vec<String>,
+}
+
+impl AllowedSigner {
+    /// Creates a new allowed signer
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The hex-encoded public key of the signer
+    /// * `node_id`: The ID of the node the signer acts on behalf of
+    /// * `roles`: The roles the signer may act in, `proposer` and/or `voter`
+    pub fn new(public_key: &str, node_id: &str, mut roles: Vec<String>) -> Self {
+        roles.sort();
+        roles.dedup();
+        Self {
+            public_key: public_key.to_lowercase(),
+            node_id: node_id.to_string(),
+            roles,
+        }
+    }
+
+    /// Returns the hex-encoded public key of the signer
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Returns the ID of the node the signer acts on behalf of
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Returns the roles the signer may act in, in sorted order
+    pub fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    /// Returns whether or not the signer may act in the given role
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|assigned| assigned == role)
+    }
+}
+
+/// Defines methods for managing the signers that are allowed to act on behalf of nodes
+pub trait AllowedSignerStore: Send + Sync {
+    /// Adds the signer, replacing any existing entry for the signer's public key
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if the public key is not valid hex, the node ID is
+    /// empty, or the roles are empty or contain an unknown role.
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError>;
+
+    /// Returns the signer with the given hex-encoded public key, if it exists
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError>;
+
+    /// Lists the allowed signers, sorted by public key. If `node_id` is provided, only the
+    /// signers of that node are returned.
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError>;
+
+    /// Removes the signer with the given hex-encoded public key
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if the signer does not exist.
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn AllowedSignerStore>;
+}
+
+impl Clone for Box<dyn AllowedSignerStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<AS> AllowedSignerStore for Box<AS>
+where
+    AS: AllowedSignerStore + ?Sized,
+{
+    fn add_signer(&self, signer: AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+        (**self).add_signer(signer)
+    }
+
+    fn get_signer(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<AllowedSigner>, AllowedSignerStoreError> {
+        (**self).get_signer(public_key)
+    }
+
+    fn list_signers(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<AllowedSigner>, AllowedSignerStoreError> {
+        (**self).list_signers(node_id)
+    }
+
+    fn remove_signer(&self, public_key: &str) -> Result<(), AllowedSignerStoreError> {
+        (**self).remove_signer(public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn AllowedSignerStore> {
+        (**self).clone_box()
+    }
+}
+
+/// An `AdminKeyVerifier` that restricts the keys permitted by another verifier to the allowed
+/// signers of each node.
+///
+/// A key is permitted for a node if the wrapped verifier permits it and either the key is an
+/// allowed signer of the node in the requested role, or the node has no allowed signers at all.
+pub struct AllowedSignerKeyVerifier {
+    inner: Box<dyn AdminKeyVerifier>,
+    store: Box<dyn AllowedSignerStore>,
+}
+
+impl AllowedSignerKeyVerifier {
+    /// Creates a new verifier
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The verifier that must also permit a key, such as the registry
+    /// * `store`: The store of allowed signers
+    pub fn new(inner: Box<dyn AdminKeyVerifier>, store: Box<dyn AllowedSignerStore>) -> Self {
+        Self { inner, store }
+    }
+
+    fn is_allowed(
+        &self,
+        node_id: &str,
+        key: &[u8],
+        role: Option<&str>,
+    ) -> Result<bool, AdminKeyVerifierError> {
+        if !self.inner.is_permitted(node_id, key)? {
+            return Ok(false);
+        }
+
+        let public_key = to_hex(key);
+        let signer = self.store.get_signer(&public_key).map_err(|err| {
+            AdminKeyVerifierError::new_with_source(
+                &format!("Failed to lookup allowed signer {}", public_key),
+                Box::new(err),
+            )
+        })?;
+
+        match signer {
+            Some(signer) if signer.node_id() == node_id => {
+                Ok(role.map(|role| signer.has_role(role)).unwrap_or(true))
+            }
+            // A key that is assigned to another node is only permitted if this node does not
+            // restrict its signers
+            _ => self
+                .store
+                .list_signers(Some(node_id))
+                .map(|signers| signers.is_empty())
+                .map_err(|err| {
+                    AdminKeyVerifierError::new_with_source(
+                        &format!("Failed to list allowed signers of node '{}'", node_id),
+                        Box::new(err),
+                    )
+                }),
+        }
+    }
+}
+
+impl AdminKeyVerifier for AllowedSignerKeyVerifier {
+    fn is_permitted(&self, node_id: &str, key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+        self.is_allowed(node_id, key, None)
+    }
+
+    fn is_permitted_for_role(
+        &self,
+        node_id: &str,
+        key: &[u8],
+        role: &str,
+    ) -> Result<bool, AdminKeyVerifierError> {
+        self.is_allowed(node_id, key, Some(role))
+    }
+}
+
+/// Checks that the signer has a hex public key, a node ID and only known roles
+fn check_signer(signer: &AllowedSigner) -> Result<(), AllowedSignerStoreError> {
+    let invalid = |argument: &str, message: &str| {
+        Err(AllowedSignerStoreError::InvalidArgument(
+            InvalidArgumentError::new(argument.to_string(), message.to_string()),
+        ))
+    };
+
+    if signer.public_key.is_empty()
+        || signer.public_key.len() % 2 != 0
+        || !signer.public_key.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return invalid("public_key", "public key must be a hex string");
+    }
+
+    if signer.node_id.trim().is_empty() {
+        return invalid("node_id", "node ID cannot be empty");
+    }
+
+    if signer.roles.is_empty() {
+        return invalid("roles", "at least one role is required");
+    }
+
+    if let Some(role) = signer
+        .roles
+        .iter()
+        .find(|role| role.as_str() != PROPOSER_ROLE && role.as_str() != VOTER_ROLE)
+    {
+        return invalid(
+            "roles",
+            &format!(
+                "unknown role '{}'; must be '{}' or '{}'",
+                role, PROPOSER_ROLE, VOTER_ROLE
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE_KEY: &[u8] = &[2; 33];
+    const SIGNER_KEY: &[u8] = &[3; 33];
+
+    /// Permits any key for the nodes `node-a` and `node-b`
+    struct MockAdminKeyVerifier;
+
+    impl AdminKeyVerifier for MockAdminKeyVerifier {
+        fn is_permitted(&self, node_id: &str, _key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+            Ok(node_id == "node-a" || node_id == "node-b")
+        }
+    }
+
+    /// Verify that the verifier permits any key accepted by the wrapped verifier for nodes without
+    /// allowed signers, and only allowed signers in their assigned roles otherwise.
+    ///
+    /// 1. Verify that keys are permitted for a node without allowed signers, but not if the
+    ///    wrapped verifier rejects them.
+    /// 2. Allow a signer as a voter for node-a, and verify that it may vote but not propose for
+    ///    node-a, and that other keys are no longer permitted for node-a.
+    /// 3. Verify that the signer is still permitted for node-b, which has no allowed signers.
+    #[test]
+    fn allowed_signer_key_verifier() {
+        let store = MemoryAllowedSignerStore::new();
+        let verifier =
+            AllowedSignerKeyVerifier::new(Box::new(MockAdminKeyVerifier), Box::new(store.clone()));
+
+        assert!(verifier
+            .is_permitted_for_role("node-a", NODE_KEY, PROPOSER_ROLE)
+            .expect("Failed to check key"));
+        assert!(!verifier
+            .is_permitted_for_role("node-c", NODE_KEY, PROPOSER_ROLE)
+            .expect("Failed to check key"));
+
+        store
+            .add_signer(AllowedSigner::new(
+                &to_hex(SIGNER_KEY),
+                "node-a",
+                vec![VOTER_ROLE.into()],
+            ))
+            .expect("Failed to add signer");
+
+        assert!(verifier
+            .is_permitted_for_role("node-a", SIGNER_KEY, VOTER_ROLE)
+            .expect("Failed to check key"));
+        assert!(verifier
+            .is_permitted("node-a", SIGNER_KEY)
+            .expect("Failed to check key"));
+        assert!(!verifier
+            .is_permitted_for_role("node-a", SIGNER_KEY, PROPOSER_ROLE)
+            .expect("Failed to check key"));
+        assert!(!verifier
+            .is_permitted_for_role("node-a", NODE_KEY, VOTER_ROLE)
+            .expect("Failed to check key"));
+
+        assert!(verifier
+            .is_permitted_for_role("node-b", SIGNER_KEY, PROPOSER_ROLE)
+            .expect("Failed to check key"));
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Automatic voting on incoming circuit proposals.
+//!
+//! A [`ProposalVotePolicy`] decides whether this node accepts or rejects a circuit proposal, or
+//! leaves the decision to a person or an external application. When an [`AutoVoter`] is set on
+//! the admin service, the policy is evaluated for each new proposal this node is a member of but
+//! did not request, and the resulting vote is signed with the auto-voter's key and submitted like
+//! any other vote. The key must be permitted to vote for the node.
+//!
+//! [`RuleVotePolicy`] is a policy built from rules on the proposed circuit's members, service
+//! types and application metadata.
+
+use std::collections::BTreeSet;
+
+use cylinder::Signer;
+
+use crate::admin::messages::{
+    BuilderError, CircuitManagementPayloadBuilder, CircuitProposal, CircuitProposalVoteBuilder,
+    Vote,
+};
+use crate::protos::admin::CircuitManagementPayload;
+
+/// The decision of a [`ProposalVotePolicy`] on a circuit proposal
+#[derive(Clone, Debug, PartialEq)]
+pub enum VoteDecision {
+    /// Vote to accept the proposal
+    Accept,
+    /// Vote to reject the proposal, for the given reason
+    Reject(String),
+    /// Do not vote on the proposal, for the given reason
+    Abstain(String),
+}
+
+/// Decides how this node votes on incoming circuit proposals
+pub trait ProposalVotePolicy: Send {
+    /// Returns the decision on the given proposal
+    fn evaluate(&self, proposal: &CircuitProposal) -> VoteDecision;
+}
+
+/// A policy that accepts a proposal if the proposed circuit satisfies every configured rule.
+///
+/// A proposal that breaks a rule is rejected if the policy rejects mismatches; otherwise, the
+/// policy abstains so the proposal can be voted on by other means. A policy without rules accepts
+/// every proposal.
+#[derive(Clone, Debug, Default)]
+pub struct RuleVotePolicy {
+    allowed_members: Option<BTreeSet<String>>,
+    allowed_service_types: Option<BTreeSet<String>>,
+    metadata_pattern: Option<String>,
+    reject_mismatches: bool,
+}
+
+impl RuleVotePolicy {
+    /// Creates a policy without rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires every member of the proposed circuit to be one of the given nodes
+    pub fn with_allowed_members(mut self, node_ids: Vec<String>) -> Self {
+        self.allowed_members = Some(node_ids.into_iter().collect());
+        self
+    }
+
+    /// Requires every service of the proposed circuit to have one of the given service types
+    pub fn with_allowed_service_types(mut self, service_types: Vec<String>) -> Self {
+        self.allowed_service_types = Some(service_types.into_iter().collect());
+        self
+    }
+
+    /// Requires the proposed circuit's application metadata to be UTF-8 text that matches the
+    /// given pattern, in which `*` matches any sequence of characters
+    pub fn with_metadata_pattern(mut self, pattern: &str) -> Self {
+        self.metadata_pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Sets whether proposals that break a rule are rejected, rather than left for other voters
+    pub fn with_reject_mismatches(mut self, reject_mismatches: bool) -> Self {
+        self.reject_mismatches = reject_mismatches;
+        self
+    }
+
+    /// Returns the first rule the proposal breaks, if any
+    fn broken_rule(&self, proposal: &CircuitProposal) -> Option<String> {
+        let circuit = &proposal.circuit;
+
+        if let Some(allowed_members) = &self.allowed_members {
+            if let Some(member) = circuit
+                .members
+                .iter()
+                .find(|member| !allowed_members.contains(&member.node_id))
+            {
+                return Some(format!("member {} is not allowed", member.node_id));
+            }
+        }
+
+        if let Some(allowed_service_types) = &self.allowed_service_types {
+            if let Some(service) = circuit
+                .roster
+                .iter()
+                .find(|service| !allowed_service_types.contains(&service.service_type))
+            {
+                return Some(format!(
+                    "service type {} of service {} is not allowed",
+                    service.service_type, service.service_id
+                ));
+            }
+        }
+
+        if let Some(pattern) = &self.metadata_pattern {
+            let matches = std::str::from_utf8(&circuit.application_metadata)
+                .map(|metadata| matches_pattern(pattern, metadata))
+                .unwrap_or(false);
+            if !matches {
+                return Some(format!(
+                    "application metadata does not match pattern {}",
+                    pattern
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+impl ProposalVotePolicy for RuleVotePolicy {
+    fn evaluate(&self, proposal: &CircuitProposal) -> VoteDecision {
+        match self.broken_rule(proposal) {
+            None => VoteDecision::Accept,
+            Some(reason) if self.reject_mismatches => VoteDecision::Reject(reason),
+            Some(reason) => VoteDecision::Abstain(reason),
+        }
+    }
+}
+
+/// Votes on behalf of this node on the proposals its policy decides on
+pub struct AutoVoter {
+    policy: Box<dyn ProposalVotePolicy>,
+    signer: Box<dyn Signer>,
+}
+
+impl AutoVoter {
+    /// Creates a new auto-voter
+    ///
+    /// # Arguments
+    ///
+    /// * `policy`: Decides how the node votes on each proposal
+    /// * `signer`: Signs the votes; its key must be permitted to vote for the node
+    pub fn new(policy: Box<dyn ProposalVotePolicy>, signer: Box<dyn Signer>) -> Self {
+        Self { policy, signer }
+    }
+
+    /// Returns the signed vote of the given node on the proposal, or `None` if the node should
+    /// not vote on it automatically.
+    ///
+    /// The node does not vote on proposals it requested or is not a member of, nor on proposals
+    /// the policy abstains from.
+    pub fn vote(
+        &self,
+        node_id: &str,
+        proposal: &CircuitProposal,
+    ) -> Result<Option<CircuitManagementPayload>, BuilderError> {
+        if proposal.requester_node_id == node_id
+            || !proposal
+                .circuit
+                .members
+                .iter()
+                .any(|member| member.node_id == node_id)
+        {
+            return Ok(None);
+        }
+
+        let vote = match self.policy.evaluate(proposal) {
+            VoteDecision::Accept => {
+                info!(
+                    "Automatically accepting proposal for circuit {}",
+                    proposal.circuit_id
+                );
+                Vote::Accept
+            }
+            VoteDecision::Reject(reason) => {
+                info!(
+                    "Automatically rejecting proposal for circuit {}: {}",
+                    proposal.circuit_id, reason
+                );
+                Vote::Reject
+            }
+            VoteDecision::Abstain(reason) => {
+                debug!(
+                    "Not voting automatically on proposal for circuit {}: {}",
+                    proposal.circuit_id, reason
+                );
+                return Ok(None);
+            }
+        };
+
+        let vote = CircuitProposalVoteBuilder::new()
+            .with_circuit_id(&proposal.circuit_id)
+            .with_circuit_hash(&proposal.circuit_hash)
+            .with_vote(vote)
+            .build()?;
+
+        CircuitManagementPayloadBuilder::new()
+            .with_action(vote)
+            .with_requester_node_id(node_id)
+            .build(&*self.signer)
+            .map(Some)
+    }
+}
+
+/// Returns whether the value matches the pattern, in which `*` matches any sequence of characters
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one part
+    let first = parts.next().unwrap_or("");
+    let mut rest = match value.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        // the pattern has no wildcard
+        None => return rest.is_empty(),
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context};
+
+    use crate::admin::messages::{
+        AuthorizationType, CreateCircuit, DurabilityType, PersistenceType, ProposalType, RouteType,
+        SplinterNode, SplinterService,
+    };
+
+    fn proposal(requester_node_id: &str, service_type: &str, metadata: &str) -> CircuitProposal {
+        let member = |node_id: &str| SplinterNode {
+            node_id: node_id.into(),
+            endpoints: vec![format!("tcps://{}:8044", node_id)],
+            relay: false,
+            observer: false,
+        };
+
+        CircuitProposal {
+            proposal_type: ProposalType::Create,
+            circuit_id: "abcde-01234".into(),
+            circuit_hash: "0123456789abcdef".into(),
+            circuit: CreateCircuit {
+                circuit_id: "abcde-01234".into(),
+                roster: vec![SplinterService {
+                    service_id: "a000".into(),
+                    service_type: service_type.into(),
+                    allowed_nodes: vec!["node-a".into()],
+                    arguments: vec![],
+                }],
+                members: vec![member("node-a"), member("node-b")],
+                authorization_type: AuthorizationType::Trust,
+                persistence: PersistenceType::Any,
+                durability: DurabilityType::NoDurability,
+                routes: RouteType::Any,
+                circuit_management_type: "gameroom".into(),
+                application_metadata: metadata.as_bytes().to_vec(),
+                comments: None,
+                display_name: None,
+                qos: Default::default(),
+            },
+            votes: vec![],
+            requester: vec![],
+            requester_node_id: requester_node_id.into(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Verify that the rule policy accepts proposals that satisfy every rule, and rejects or
+    /// abstains from proposals that break a rule.
+    #[test]
+    fn rule_policy() {
+        let policy = RuleVotePolicy::new()
+            .with_allowed_members(vec!["node-a".into(), "node-b".into()])
+            .with_allowed_service_types(vec!["scabbard".into()])
+            .with_metadata_pattern("{\"game\":*}");
+
+        assert_eq!(
+            policy.evaluate(&proposal("node-b", "scabbard", "{\"game\":\"chess\"}")),
+            VoteDecision::Accept
+        );
+        assert!(matches!(
+            policy.evaluate(&proposal("node-b", "other", "{\"game\":\"chess\"}")),
+            VoteDecision::Abstain(_)
+        ));
+        assert!(matches!(
+            policy.evaluate(&proposal("node-b", "scabbard", "{}")),
+            VoteDecision::Abstain(_)
+        ));
+
+        let policy = policy
+            .with_allowed_members(vec!["node-a".into()])
+            .with_reject_mismatches(true);
+        assert!(matches!(
+            policy.evaluate(&proposal("node-b", "scabbard", "{\"game\":\"chess\"}")),
+            VoteDecision::Reject(_)
+        ));
+    }
+
+    /// Verify that the auto-voter signs a vote for the node on proposals it is a member of, but
+    /// does not vote on proposals the node requested.
+    #[test]
+    fn auto_voter() {
+        let context = Secp256k1Context::new();
+        let auto_voter = AutoVoter::new(
+            Box::new(RuleVotePolicy::new()),
+            context.new_signer(context.new_random_private_key()),
+        );
+
+        let payload = auto_voter
+            .vote("node-a", &proposal("node-b", "scabbard", ""))
+            .expect("Failed to vote")
+            .expect("Should have voted");
+        assert!(payload.has_circuit_proposal_vote());
+        assert_eq!(
+            payload.get_circuit_proposal_vote().get_circuit_id(),
+            "abcde-01234"
+        );
+
+        assert_eq!(
+            auto_voter
+                .vote("node-b", &proposal("node-b", "scabbard", ""))
+                .expect("Failed to vote"),
+            None
+        );
+        assert_eq!(
+            auto_voter
+                .vote("node-c", &proposal("node-b", "scabbard", ""))
+                .expect("Failed to vote"),
+            None
+        );
+    }
+
+    /// Verify that patterns match literally, with `*` matching any sequence of characters.
+    #[test]
+    fn patterns() {
+        assert!(matches_pattern("abc", "abc"));
+        assert!(!matches_pattern("abc", "abcd"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("a*c", "abbbc"));
+        assert!(matches_pattern("*b*", "abc"));
+        assert!(!matches_pattern("a*c*d", "abcd-c"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Circuit ID formats and the policy that determines which formats are accepted.
+//!
+//! Circuit IDs are either in the `random` format, two 5 character base62 strings joined with a
+//! '-' (example: abcDE-F0123), or in the `uuidv7` format, a lowercase, hyphenated UUID version 7
+//! (example: 01890a5d-ac96-774b-bcce-b302099a8057). UUIDv7 IDs begin with a millisecond Unix
+//! timestamp, so they sort in the order the circuits were proposed.
+//!
+//! A [`CircuitIdPolicy`] lists the formats that are accepted for new circuit proposals; by
+//! default, only the `random` format is accepted.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+
+use crate::admin::service::messages::is_random_circuit_id;
+use crate::base62::generate_random_base62_string;
+use crate::error::InvalidArgumentError;
+
+/// The formats a circuit ID may take
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitIdFormat {
+    /// Two 5 character base62 strings joined with a '-' (example: abcDE-F0123)
+    Random,
+    /// A lowercase, hyphenated UUID version 7 (example: 01890a5d-ac96-774b-bcce-b302099a8057)
+    UuidV7,
+}
+
+impl CircuitIdFormat {
+    /// Generates a new circuit ID in this format
+    pub fn generate(&self) -> String {
+        match self {
+            CircuitIdFormat::Random => {
+                generate_random_base62_string(5) + "-" + &generate_random_base62_string(5)
+            }
+            CircuitIdFormat::UuidV7 => generate_uuid_v7(SystemTime::now()),
+        }
+    }
+
+    /// Returns whether or not the given circuit ID is in this format
+    pub fn matches(&self, circuit_id: &str) -> bool {
+        match self {
+            CircuitIdFormat::Random => is_random_circuit_id(circuit_id),
+            CircuitIdFormat::UuidV7 => is_uuid_v7_format(circuit_id),
+        }
+    }
+
+    /// Returns the format of the given circuit ID, or `None` if it is not in any known format
+    pub fn of(circuit_id: &str) -> Option<CircuitIdFormat> {
+        [CircuitIdFormat::Random, CircuitIdFormat::UuidV7]
+            .iter()
+            .find(|format| format.matches(circuit_id))
+            .copied()
+    }
+}
+
+impl fmt::Display for CircuitIdFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitIdFormat::Random => f.write_str("random"),
+            CircuitIdFormat::UuidV7 => f.write_str("uuidv7"),
+        }
+    }
+}
+
+impl FromStr for CircuitIdFormat {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(CircuitIdFormat::Random),
+            "uuidv7" => Ok(CircuitIdFormat::UuidV7),
+            _ => Err(InvalidArgumentError::new(
+                "circuit_id_format".into(),
+                format!("unknown circuit ID format {}; must be random or uuidv7", s),
+            )),
+        }
+    }
+}
+
+/// The circuit ID formats that are accepted for new circuit proposals
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitIdPolicy {
+    accepted_formats: Vec<CircuitIdFormat>,
+}
+
+impl CircuitIdPolicy {
+    /// Creates a policy that accepts the given formats
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgumentError` if no formats are given.
+    pub fn new(accepted_formats: Vec<CircuitIdFormat>) -> Result<Self, InvalidArgumentError> {
+        if accepted_formats.is_empty() {
+            return Err(InvalidArgumentError::new(
+                "accepted_formats".into(),
+                "at least one circuit ID format must be accepted".into(),
+            ));
+        }
+
+        Ok(Self { accepted_formats })
+    }
+
+    /// Returns the accepted formats
+    pub fn accepted_formats(&self) -> &[CircuitIdFormat] {
+        &self.accepted_formats
+    }
+
+    /// Checks that the given circuit ID is in one of the accepted formats
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgumentError` describing the accepted formats if the circuit ID is not
+    /// in any of them.
+    pub fn check(&self, circuit_id: &str) -> Result<(), InvalidArgumentError> {
+        if self
+            .accepted_formats
+            .iter()
+            .any(|format| format.matches(circuit_id))
+        {
+            return Ok(());
+        }
+
+        let accepted = self
+            .accepted_formats
+            .iter()
+            .map(|format| format.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(InvalidArgumentError::new(
+            "circuit_id".into(),
+            format!(
+                "circuit ID {} is not in an accepted format ({})",
+                circuit_id, accepted
+            ),
+        ))
+    }
+}
+
+impl Default for CircuitIdPolicy {
+    fn default() -> Self {
+        Self {
+            accepted_formats: vec![CircuitIdFormat::Random],
+        }
+    }
+}
+
+/// Returns whether or not the given string is a lowercase, hyphenated UUID with version 7 and the
+/// RFC 4122 variant.
+pub(crate) fn is_uuid_v7_format(circuit_id: &str) -> bool {
+    let groups = circuit_id.split('-').collect::<Vec<_>>();
+    let are_groups_valid = groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12].iter())
+            .all(|(group, len)| {
+                group.len() == *len
+                    && group
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+            });
+    if !are_groups_valid {
+        return false;
+    }
+
+    let is_version_7 = groups[2].starts_with('7');
+    let is_rfc_variant = groups[3].starts_with(|c| c == '8' || c == '9' || c == 'a' || c == 'b');
+    is_version_7 && is_rfc_variant
+}
+
+/// Generates a UUIDv7: a 48-bit millisecond Unix timestamp, followed by the version, 74 random
+/// bits and the RFC 4122 variant.
+fn generate_uuid_v7(now: SystemTime) -> String {
+    let millis = now
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis() as u64)
+        .unwrap_or(0);
+
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill(&mut bytes[6..]);
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x70;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    /// Verify that generated IDs match their own format and only their own format.
+    #[test]
+    fn generated_ids_match_format() {
+        let random = CircuitIdFormat::Random.generate();
+        assert_eq!(CircuitIdFormat::of(&random), Some(CircuitIdFormat::Random));
+
+        let uuid = CircuitIdFormat::UuidV7.generate();
+        assert_eq!(CircuitIdFormat::of(&uuid), Some(CircuitIdFormat::UuidV7));
+        assert_eq!(uuid.len(), 36);
+
+        assert_eq!(CircuitIdFormat::of("not-a-circuit-id"), None);
+        // A version 4 UUID is not accepted as a UUIDv7
+        assert_eq!(
+            CircuitIdFormat::of("3b241101-e2bb-4255-8caf-4136c566a962"),
+            None
+        );
+        // Uppercase hex digits are not accepted
+        assert_eq!(
+            CircuitIdFormat::of("01890A5D-AC96-774B-BCCE-B302099A8057"),
+            None
+        );
+    }
+
+    /// Verify that UUIDv7 IDs are ordered by the time they were generated.
+    #[test]
+    fn uuid_v7_ids_are_time_ordered() {
+        let earlier = generate_uuid_v7(UNIX_EPOCH + Duration::from_millis(1_600_000_000_000));
+        let later = generate_uuid_v7(UNIX_EPOCH + Duration::from_millis(1_600_000_000_001));
+        assert!(earlier < later);
+        assert!(earlier.starts_with("0174876e-8000-7"));
+    }
+
+    /// Verify that the policy only accepts IDs in its accepted formats and that it cannot be
+    /// created without any formats.
+    #[test]
+    fn policy_checks_accepted_formats() {
+        let random = CircuitIdFormat::Random.generate();
+        let uuid = CircuitIdFormat::UuidV7.generate();
+
+        let default_policy = CircuitIdPolicy::default();
+        assert!(default_policy.check(&random).is_ok());
+        assert!(default_policy.check(&uuid).is_err());
+
+        let policy = CircuitIdPolicy::new(vec![CircuitIdFormat::UuidV7]).expect("invalid policy");
+        assert!(policy.check(&random).is_err());
+        assert!(policy.check(&uuid).is_ok());
+
+        assert!(CircuitIdPolicy::new(vec![]).is_err());
+        assert_eq!(
+            "uuidv7".parse::<CircuitIdFormat>().ok(),
+            Some(CircuitIdFormat::UuidV7)
+        );
+        assert!("uuid".parse::<CircuitIdFormat>().is_err());
+    }
+}
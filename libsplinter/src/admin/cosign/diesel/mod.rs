@@ -0,0 +1,287 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(in crate::admin) mod models;
+mod operations;
+pub(in crate::admin) mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use super::{PendingVote, VoteCosignStore, VoteCosignStoreError, VoteSignature, VoteThreshold};
+
+use operations::{
+    add_pending_vote::VoteCosignStoreAddPendingVote as _,
+    add_signature::VoteCosignStoreAddSignature as _,
+    get_pending_vote::VoteCosignStoreGetPendingVote as _,
+    get_threshold::VoteCosignStoreGetThreshold as _,
+    list_pending_votes::VoteCosignStoreListPendingVotes as _,
+    list_thresholds::VoteCosignStoreListThresholds as _,
+    remove_pending_vote::VoteCosignStoreRemovePendingVote as _,
+    remove_threshold::VoteCosignStoreRemoveThreshold as _,
+    set_threshold::VoteCosignStoreSetThreshold as _, VoteCosignStoreOperations,
+};
+
+pub struct DieselVoteCosignStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselVoteCosignStore<C> {
+    /// Creates a new `DieselVoteCosignStore`
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl VoteCosignStore for DieselVoteCosignStore<diesel::sqlite::SqliteConnection> {
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).set_threshold(threshold)
+    }
+
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).get_threshold(management_type)
+    }
+
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).list_thresholds()
+    }
+
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_threshold(management_type)
+    }
+
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).add_pending_vote(vote)
+    }
+
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).get_pending_vote(circuit_id)
+    }
+
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).list_pending_votes()
+    }
+
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .add_signature(circuit_id, signature)
+    }
+
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_pending_vote(circuit_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteCosignStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl VoteCosignStore for DieselVoteCosignStore<diesel::pg::PgConnection> {
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).set_threshold(threshold)
+    }
+
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).get_threshold(management_type)
+    }
+
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).list_thresholds()
+    }
+
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_threshold(management_type)
+    }
+
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).add_pending_vote(vote)
+    }
+
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).get_pending_vote(circuit_id)
+    }
+
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?).list_pending_votes()
+    }
+
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .add_signature(circuit_id, signature)
+    }
+
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError> {
+        VoteCosignStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_pending_vote(circuit_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteCosignStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::sqlite::SqliteConnection;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    /// Verify that a SQLite-backed `DieselVoteCosignStore` correctly supports managing
+    /// thresholds and collecting signatures on pending votes.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Set two thresholds, replace one, and verify that they are returned and listed.
+    /// 3. Add a pending vote with one signature, add a second signature and replace the first,
+    ///    and verify the signatures of the returned and listed vote.
+    /// 4. Remove the pending vote and verify that it is no longer returned, and that adding a
+    ///    signature to it returns a `NotFound` error.
+    #[test]
+    fn sqlite_vote_cosigning() {
+        let store = DieselVoteCosignStore::new(create_connection_pool_and_migrate());
+
+        store
+            .set_threshold(VoteThreshold::new("gameroom", 2))
+            .expect("Failed to set threshold");
+        store
+            .set_threshold(VoteThreshold::new("acme", 3))
+            .expect("Failed to set threshold");
+        store
+            .set_threshold(VoteThreshold::new("gameroom", 4))
+            .expect("Failed to replace threshold");
+        assert_eq!(
+            store
+                .get_threshold("gameroom")
+                .expect("Failed to get threshold"),
+            Some(VoteThreshold::new("gameroom", 4))
+        );
+        assert_eq!(
+            store.list_thresholds().expect("Failed to list thresholds"),
+            vec![
+                VoteThreshold::new("acme", 3),
+                VoteThreshold::new("gameroom", 4)
+            ]
+        );
+        store
+            .remove_threshold("acme")
+            .expect("Failed to remove threshold");
+        assert_eq!(
+            store
+                .get_threshold("acme")
+                .expect("Failed to get threshold"),
+            None
+        );
+
+        store
+            .add_pending_vote(PendingVote::new(
+                "abcde-01234",
+                vec![1, 2, 3],
+                vec![VoteSignature::new(KEY_A, "0a0a")],
+            ))
+            .expect("Failed to add pending vote");
+        store
+            .add_signature("abcde-01234", VoteSignature::new(KEY_B, "0b0b"))
+            .expect("Failed to add signature");
+        store
+            .add_signature("abcde-01234", VoteSignature::new(KEY_A, "0c0c"))
+            .expect("Failed to replace signature");
+
+        let expected = PendingVote::new(
+            "abcde-01234",
+            vec![1, 2, 3],
+            vec![
+                VoteSignature::new(KEY_A, "0c0c"),
+                VoteSignature::new(KEY_B, "0b0b"),
+            ],
+        );
+        assert_eq!(
+            store
+                .get_pending_vote("abcde-01234")
+                .expect("Failed to get pending vote"),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            store
+                .list_pending_votes()
+                .expect("Failed to list pending votes"),
+            vec![expected]
+        );
+
+        store
+            .remove_pending_vote("abcde-01234")
+            .expect("Failed to remove pending vote");
+        assert_eq!(
+            store
+                .get_pending_vote("abcde-01234")
+                .expect("Failed to get pending vote"),
+            None
+        );
+        assert!(matches!(
+            store.add_signature("abcde-01234", VoteSignature::new(KEY_B, "0b0b")),
+            Err(VoteCosignStoreError::NotFound(_))
+        ));
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
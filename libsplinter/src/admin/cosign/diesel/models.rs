@@ -0,0 +1,39 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::{admin_pending_vote_signatures, admin_pending_votes, admin_vote_thresholds};
+
+#[derive(Debug, PartialEq, Identifiable, Insertable, Queryable)]
+#[table_name = "admin_vote_thresholds"]
+#[primary_key(management_type)]
+pub struct VoteThresholdModel {
+    pub management_type: String,
+    pub required_signatures: i32,
+}
+
+#[derive(Debug, PartialEq, Identifiable, Insertable, Queryable)]
+#[table_name = "admin_pending_votes"]
+#[primary_key(circuit_id)]
+pub struct PendingVoteModel {
+    pub circuit_id: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Insertable, Queryable)]
+#[table_name = "admin_pending_vote_signatures"]
+pub struct VoteSignatureModel {
+    pub circuit_id: String,
+    pub public_key: String,
+    pub signature: String,
+}
@@ -0,0 +1,112 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::admin::cosign::{
+    check_pending_vote,
+    diesel::{
+        models::{PendingVoteModel, VoteSignatureModel},
+        schema::{admin_pending_vote_signatures, admin_pending_votes},
+    },
+    PendingVote, VoteCosignStoreError,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreAddPendingVote {
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> VoteCosignStoreAddPendingVote
+    for VoteCosignStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        check_pending_vote(&vote)?;
+        let (vote_model, signature_models) = into_models(vote);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing pending vote for the circuit
+            delete(
+                admin_pending_vote_signatures::table
+                    .filter(admin_pending_vote_signatures::circuit_id.eq(&vote_model.circuit_id)),
+            )
+            .execute(self.conn)?;
+            delete(admin_pending_votes::table.find(&vote_model.circuit_id)).execute(self.conn)?;
+
+            insert_into(admin_pending_votes::table)
+                .values(&vote_model)
+                .execute(self.conn)?;
+            insert_into(admin_pending_vote_signatures::table)
+                .values(&signature_models)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> VoteCosignStoreAddPendingVote for VoteCosignStoreOperations<'a, diesel::pg::PgConnection> {
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        check_pending_vote(&vote)?;
+        let (vote_model, signature_models) = into_models(vote);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing pending vote for the circuit
+            delete(
+                admin_pending_vote_signatures::table
+                    .filter(admin_pending_vote_signatures::circuit_id.eq(&vote_model.circuit_id)),
+            )
+            .execute(self.conn)?;
+            delete(admin_pending_votes::table.find(&vote_model.circuit_id)).execute(self.conn)?;
+
+            insert_into(admin_pending_votes::table)
+                .values(&vote_model)
+                .execute(self.conn)?;
+            insert_into(admin_pending_vote_signatures::table)
+                .values(&signature_models)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+fn into_models(vote: PendingVote) -> (PendingVoteModel, Vec<VoteSignatureModel>) {
+    let PendingVote {
+        circuit_id,
+        payload,
+        signatures,
+    } = vote;
+
+    let signature_models = signatures
+        .into_iter()
+        .map(|signature| VoteSignatureModel {
+            circuit_id: circuit_id.clone(),
+            public_key: signature.public_key,
+            signature: signature.signature,
+        })
+        .collect();
+
+    (
+        PendingVoteModel {
+            circuit_id,
+            payload,
+        },
+        signature_models,
+    )
+}
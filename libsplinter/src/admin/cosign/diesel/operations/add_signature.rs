@@ -0,0 +1,115 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::admin::cosign::{
+    check_signature,
+    diesel::{
+        models::VoteSignatureModel,
+        schema::{admin_pending_vote_signatures, admin_pending_votes},
+    },
+    VoteCosignStoreError, VoteSignature,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreAddSignature {
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> VoteCosignStoreAddSignature
+    for VoteCosignStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        check_signature(&signature)?;
+        let model = into_model(circuit_id, signature);
+
+        self.conn.transaction::<_, _, _>(|| {
+            check_vote_exists(self.conn, circuit_id)?;
+
+            // Replace any existing signature from the same key
+            delete(admin_pending_vote_signatures::table.find((circuit_id, &model.public_key)))
+                .execute(self.conn)?;
+            insert_into(admin_pending_vote_signatures::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> VoteCosignStoreAddSignature for VoteCosignStoreOperations<'a, diesel::pg::PgConnection> {
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        check_signature(&signature)?;
+        let model = into_model(circuit_id, signature);
+
+        self.conn.transaction::<_, _, _>(|| {
+            check_vote_exists(self.conn, circuit_id)?;
+
+            // Replace any existing signature from the same key
+            delete(admin_pending_vote_signatures::table.find((circuit_id, &model.public_key)))
+                .execute(self.conn)?;
+            insert_into(admin_pending_vote_signatures::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+fn check_vote_exists<C>(conn: &C, circuit_id: &str) -> Result<(), VoteCosignStoreError>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    let count = admin_pending_votes::table
+        .filter(admin_pending_votes::circuit_id.eq(circuit_id))
+        .count()
+        .get_result::<i64>(conn)?;
+
+    if count == 0 {
+        Err(VoteCosignStoreError::NotFound(format!(
+            "pending vote for circuit {}",
+            circuit_id
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn into_model(circuit_id: &str, signature: VoteSignature) -> VoteSignatureModel {
+    VoteSignatureModel {
+        circuit_id: circuit_id.to_string(),
+        public_key: signature.public_key,
+        signature: signature.signature,
+    }
+}
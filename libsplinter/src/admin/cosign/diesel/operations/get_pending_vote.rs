@@ -0,0 +1,66 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::cosign::{
+    diesel::{
+        models::{PendingVoteModel, VoteSignatureModel},
+        schema::{admin_pending_vote_signatures, admin_pending_votes},
+    },
+    PendingVote, VoteCosignStoreError, VoteSignature,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreGetPendingVote {
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreGetPendingVote for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
+{
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError> {
+        let vote = match admin_pending_votes::table
+            .find(circuit_id)
+            .first::<PendingVoteModel>(self.conn)
+            .optional()?
+        {
+            Some(vote) => vote,
+            None => return Ok(None),
+        };
+
+        let signatures = admin_pending_vote_signatures::table
+            .filter(admin_pending_vote_signatures::circuit_id.eq(circuit_id))
+            .load::<VoteSignatureModel>(self.conn)?
+            .into_iter()
+            .map(|signature| VoteSignature::new(&signature.public_key, &signature.signature))
+            .collect();
+
+        Ok(Some(PendingVote::new(
+            &vote.circuit_id,
+            vote.payload,
+            signatures,
+        )))
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::cosign::{
+    diesel::{models::VoteThresholdModel, schema::admin_vote_thresholds},
+    VoteCosignStoreError, VoteThreshold,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreGetThreshold {
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreGetThreshold for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i32: diesel::deserialize::FromSql<diesel::sql_types::Integer, C::Backend>,
+{
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError> {
+        Ok(admin_vote_thresholds::table
+            .find(management_type)
+            .first::<VoteThresholdModel>(self.conn)
+            .optional()?
+            .map(VoteThreshold::from))
+    }
+}
+
+impl From<VoteThresholdModel> for VoteThreshold {
+    fn from(model: VoteThresholdModel) -> Self {
+        // Thresholds are checked to be positive before they are stored
+        VoteThreshold::new(
+            &model.management_type,
+            model.required_signatures.max(0) as u32,
+        )
+    }
+}
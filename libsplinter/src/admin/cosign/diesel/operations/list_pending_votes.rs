@@ -0,0 +1,65 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use crate::admin::cosign::{
+    diesel::{
+        models::{PendingVoteModel, VoteSignatureModel},
+        schema::{admin_pending_vote_signatures, admin_pending_votes},
+    },
+    PendingVote, VoteCosignStoreError, VoteSignature,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreListPendingVotes {
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreListPendingVotes for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
+{
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError> {
+        let votes = admin_pending_votes::table
+            .order(admin_pending_votes::circuit_id)
+            .load::<PendingVoteModel>(self.conn)?;
+
+        let mut signatures = HashMap::<String, Vec<VoteSignature>>::new();
+        for signature in
+            admin_pending_vote_signatures::table.load::<VoteSignatureModel>(self.conn)?
+        {
+            signatures
+                .entry(signature.circuit_id)
+                .or_default()
+                .push(VoteSignature::new(
+                    &signature.public_key,
+                    &signature.signature,
+                ));
+        }
+
+        Ok(votes
+            .into_iter()
+            .map(|vote| {
+                let vote_signatures = signatures.remove(&vote.circuit_id).unwrap_or_default();
+                PendingVote::new(&vote.circuit_id, vote.payload, vote_signatures)
+            })
+            .collect())
+    }
+}
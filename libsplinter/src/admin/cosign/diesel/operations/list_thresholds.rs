@@ -0,0 +1,42 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::cosign::{
+    diesel::{models::VoteThresholdModel, schema::admin_vote_thresholds},
+    VoteCosignStoreError, VoteThreshold,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreListThresholds {
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreListThresholds for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i32: diesel::deserialize::FromSql<diesel::sql_types::Integer, C::Backend>,
+{
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError> {
+        Ok(admin_vote_thresholds::table
+            .order(admin_vote_thresholds::management_type)
+            .load::<VoteThresholdModel>(self.conn)?
+            .into_iter()
+            .map(VoteThreshold::from)
+            .collect())
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::admin::cosign::{
+    diesel::schema::{admin_pending_vote_signatures, admin_pending_votes},
+    VoteCosignStoreError,
+};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreRemovePendingVote {
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreRemovePendingVote for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            delete(
+                admin_pending_vote_signatures::table
+                    .filter(admin_pending_vote_signatures::circuit_id.eq(circuit_id)),
+            )
+            .execute(self.conn)?;
+
+            let deleted = delete(admin_pending_votes::table.find(circuit_id)).execute(self.conn)?;
+            if deleted == 0 {
+                return Err(VoteCosignStoreError::NotFound(format!(
+                    "pending vote for circuit {}",
+                    circuit_id
+                )));
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::admin::cosign::{diesel::schema::admin_vote_thresholds, VoteCosignStoreError};
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreRemoveThreshold {
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError>;
+}
+
+impl<'a, C> VoteCosignStoreRemoveThreshold for VoteCosignStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError> {
+        let deleted =
+            delete(admin_vote_thresholds::table.find(management_type)).execute(self.conn)?;
+        if deleted == 0 {
+            return Err(VoteCosignStoreError::NotFound(format!(
+                "threshold for management type {}",
+                management_type
+            )));
+        }
+
+        Ok(())
+    }
+}
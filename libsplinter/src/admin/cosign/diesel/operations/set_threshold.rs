@@ -0,0 +1,83 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::admin::cosign::{
+    check_threshold,
+    diesel::{models::VoteThresholdModel, schema::admin_vote_thresholds},
+    VoteCosignStoreError, VoteThreshold,
+};
+use crate::error::InvalidArgumentError;
+
+use super::VoteCosignStoreOperations;
+
+pub trait VoteCosignStoreSetThreshold {
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> VoteCosignStoreSetThreshold
+    for VoteCosignStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        let model = into_model(threshold)?;
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing threshold for the management type
+            delete(admin_vote_thresholds::table.find(&model.management_type)).execute(self.conn)?;
+            insert_into(admin_vote_thresholds::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> VoteCosignStoreSetThreshold for VoteCosignStoreOperations<'a, diesel::pg::PgConnection> {
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        let model = into_model(threshold)?;
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing threshold for the management type
+            delete(admin_vote_thresholds::table.find(&model.management_type)).execute(self.conn)?;
+            insert_into(admin_vote_thresholds::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+fn into_model(threshold: VoteThreshold) -> Result<VoteThresholdModel, VoteCosignStoreError> {
+    check_threshold(&threshold)?;
+
+    let required_signatures = i32::try_from(threshold.required_signatures).map_err(|_| {
+        VoteCosignStoreError::InvalidArgument(InvalidArgumentError::new(
+            "required_signatures".to_string(),
+            "too many signatures required".to_string(),
+        ))
+    })?;
+
+    Ok(VoteThresholdModel {
+        management_type: threshold.management_type,
+        required_signatures,
+    })
+}
@@ -0,0 +1,35 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+table! {
+    admin_vote_thresholds (management_type) {
+        management_type -> Text,
+        required_signatures -> Integer,
+    }
+}
+
+table! {
+    admin_pending_votes (circuit_id) {
+        circuit_id -> Text,
+        payload -> Binary,
+    }
+}
+
+table! {
+    admin_pending_vote_signatures (circuit_id, public_key) {
+        circuit_id -> Text,
+        public_key -> Text,
+        signature -> Text,
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{InternalError, InvalidArgumentError};
+
+/// Represents errors raised while managing vote thresholds and pending votes
+#[derive(Debug)]
+pub enum VoteCosignStoreError {
+    Internal(InternalError),
+    InvalidArgument(InvalidArgumentError),
+    NotFound(String),
+}
+
+impl Error for VoteCosignStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VoteCosignStoreError::Internal(err) => err.source(),
+            VoteCosignStoreError::InvalidArgument(err) => err.source(),
+            VoteCosignStoreError::NotFound(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for VoteCosignStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VoteCosignStoreError::Internal(err) => f.write_str(&err.to_string()),
+            VoteCosignStoreError::InvalidArgument(err) => f.write_str(&err.to_string()),
+            VoteCosignStoreError::NotFound(msg) => write!(f, "Not found: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl From<diesel::r2d2::PoolError> for VoteCosignStoreError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        VoteCosignStoreError::Internal(InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl From<diesel::result::Error> for VoteCosignStoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        VoteCosignStoreError::Internal(InternalError::from_source(Box::new(err)))
+    }
+}
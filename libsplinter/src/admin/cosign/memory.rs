@@ -0,0 +1,239 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+
+use super::{
+    check_pending_vote, check_signature, check_threshold, PendingVote, VoteCosignStore,
+    VoteCosignStoreError, VoteSignature, VoteThreshold,
+};
+
+#[derive(Default)]
+struct Inner {
+    /// Map of circuit management type -> threshold
+    thresholds: BTreeMap<String, VoteThreshold>,
+    /// Map of circuit ID -> pending vote
+    pending_votes: BTreeMap<String, PendingVote>,
+}
+
+#[derive(Default, Clone)]
+pub struct MemoryVoteCosignStore {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MemoryVoteCosignStore {
+    /// Creates a new memory-backed vote co-signing store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<Inner>, VoteCosignStoreError> {
+        self.inner.lock().map_err(|_| {
+            VoteCosignStoreError::Internal(InternalError::with_message(
+                "Cannot access vote co-signing store: mutex lock poisoned".to_string(),
+            ))
+        })
+    }
+}
+
+impl VoteCosignStore for MemoryVoteCosignStore {
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        check_threshold(&threshold)?;
+        self.lock()?
+            .thresholds
+            .insert(threshold.management_type.clone(), threshold);
+        Ok(())
+    }
+
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError> {
+        Ok(self.lock()?.thresholds.get(management_type).cloned())
+    }
+
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError> {
+        Ok(self.lock()?.thresholds.values().cloned().collect())
+    }
+
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError> {
+        self.lock()?
+            .thresholds
+            .remove(management_type)
+            .map(|_| ())
+            .ok_or_else(|| {
+                VoteCosignStoreError::NotFound(format!(
+                    "threshold for management type {}",
+                    management_type
+                ))
+            })
+    }
+
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        check_pending_vote(&vote)?;
+        self.lock()?
+            .pending_votes
+            .insert(vote.circuit_id.clone(), vote);
+        Ok(())
+    }
+
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError> {
+        Ok(self.lock()?.pending_votes.get(circuit_id).cloned())
+    }
+
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError> {
+        Ok(self.lock()?.pending_votes.values().cloned().collect())
+    }
+
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        check_signature(&signature)?;
+        let mut inner = self.lock()?;
+        let vote = inner.pending_votes.get_mut(circuit_id).ok_or_else(|| {
+            VoteCosignStoreError::NotFound(format!("pending vote for circuit {}", circuit_id))
+        })?;
+
+        let mut signatures = std::mem::take(&mut vote.signatures);
+        signatures.retain(|existing| existing.public_key != signature.public_key);
+        signatures.push(signature);
+        *vote = PendingVote::new(circuit_id, std::mem::take(&mut vote.payload), signatures);
+
+        Ok(())
+    }
+
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError> {
+        self.lock()?
+            .pending_votes
+            .remove(circuit_id)
+            .map(|_| ())
+            .ok_or_else(|| {
+                VoteCosignStoreError::NotFound(format!("pending vote for circuit {}", circuit_id))
+            })
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteCosignStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    /// Verify that the memory store sets, lists and removes thresholds, and rejects invalid
+    /// thresholds.
+    #[test]
+    fn set_list_and_remove_thresholds() {
+        let store = MemoryVoteCosignStore::new();
+
+        store
+            .set_threshold(VoteThreshold::new("gameroom", 2))
+            .expect("Failed to set threshold");
+        store
+            .set_threshold(VoteThreshold::new("acme", 3))
+            .expect("Failed to set threshold");
+        store
+            .set_threshold(VoteThreshold::new("gameroom", 4))
+            .expect("Failed to replace threshold");
+
+        assert_eq!(
+            store.get_threshold("gameroom").expect("Failed to get"),
+            Some(VoteThreshold::new("gameroom", 4))
+        );
+        assert_eq!(
+            store.list_thresholds().expect("Failed to list"),
+            vec![
+                VoteThreshold::new("acme", 3),
+                VoteThreshold::new("gameroom", 4)
+            ]
+        );
+
+        assert!(matches!(
+            store.set_threshold(VoteThreshold::new("acme", 0)),
+            Err(VoteCosignStoreError::InvalidArgument(_))
+        ));
+
+        store
+            .remove_threshold("acme")
+            .expect("Failed to remove threshold");
+        assert_eq!(store.get_threshold("acme").expect("Failed to get"), None);
+        assert!(matches!(
+            store.remove_threshold("acme"),
+            Err(VoteCosignStoreError::NotFound(_))
+        ));
+    }
+
+    /// Verify that the memory store collects signatures on a pending vote, replacing a repeated
+    /// signature from the same key, and that signatures cannot be added once the vote is removed.
+    #[test]
+    fn collect_signatures() {
+        let store = MemoryVoteCosignStore::new();
+
+        store
+            .add_pending_vote(PendingVote::new("abcde-01234", vec![1, 2, 3], vec![]))
+            .expect("Failed to add pending vote");
+        store
+            .add_signature("abcde-01234", VoteSignature::new(KEY_B, "0b0b"))
+            .expect("Failed to add signature");
+        store
+            .add_signature("abcde-01234", VoteSignature::new(KEY_A, "0a0a"))
+            .expect("Failed to add signature");
+        store
+            .add_signature("abcde-01234", VoteSignature::new(KEY_B, "0c0c"))
+            .expect("Failed to replace signature");
+
+        assert_eq!(
+            store
+                .get_pending_vote("abcde-01234")
+                .expect("Failed to get pending vote"),
+            Some(PendingVote::new(
+                "abcde-01234",
+                vec![1, 2, 3],
+                vec![
+                    VoteSignature::new(KEY_A, "0a0a"),
+                    VoteSignature::new(KEY_B, "0c0c")
+                ]
+            ))
+        );
+
+        assert!(matches!(
+            store.add_signature("abcde-01234", VoteSignature::new(KEY_A, "xyz")),
+            Err(VoteCosignStoreError::InvalidArgument(_))
+        ));
+
+        store
+            .remove_pending_vote("abcde-01234")
+            .expect("Failed to remove pending vote");
+        assert!(store
+            .list_pending_votes()
+            .expect("Failed to list pending votes")
+            .is_empty());
+        assert!(matches!(
+            store.add_signature("abcde-01234", VoteSignature::new(KEY_A, "0a0a")),
+            Err(VoteCosignStoreError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A node-level store for collecting co-signatures on circuit proposal votes.
+//!
+//! A circuit management type may be given a vote threshold: the number of distinct signatures,
+//! from keys permitted to vote for the node, that the node's vote on a proposal of that type must
+//! carry. A vote that requires co-signatures is held as a pending vote while the node's other
+//! voters sign the vote's payload header; once the threshold has been met, the vote is accepted by
+//! the node's admin service. The threshold only applies to the votes submitted to this node, which
+//! are the votes of this node's organization.
+
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod memory;
+
+use crate::error::InvalidArgumentError;
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselVoteCosignStore;
+pub use error::VoteCosignStoreError;
+pub use memory::MemoryVoteCosignStore;
+
+/// The number of signatures required on a node's vote for proposals of a circuit management type
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoteThreshold {
+    management_type: String,
+    required_signatures: u32,
+}
+
+impl VoteThreshold {
+    /// Creates a new vote threshold
+    ///
+    /// # Arguments
+    ///
+    /// * `management_type`: The circuit management type the threshold applies to
+    /// * `required_signatures`: The number of distinct signatures a vote must carry
+    pub fn new(management_type: &str, required_signatures: u32) -> Self {
+        Self {
+            management_type: management_type.to_string(),
+            required_signatures,
+        }
+    }
+
+    /// Returns the circuit management type the threshold applies to
+    pub fn management_type(&self) -> &str {
+        &self.management_type
+    }
+
+    /// Returns the number of distinct signatures a vote must carry
+    pub fn required_signatures(&self) -> u32 {
+        self.required_signatures
+    }
+}
+
+/// A co-signature of a pending vote's payload header
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoteSignature {
+    public_key: String,
+    signature: String,
+}
+
+impl VoteSignature {
+    /// Creates a new vote signature
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The hex-encoded public key of the co-signer
+    /// * `signature`: The hex-encoded signature of the vote's payload header
+    pub fn new(public_key: &str, signature: &str) -> Self {
+        Self {
+            public_key: public_key.to_lowercase(),
+            signature: signature.to_lowercase(),
+        }
+    }
+
+    /// Returns the hex-encoded public key of the co-signer
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Returns the hex-encoded signature of the vote's payload header
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+}
+
+/// A signed vote that is waiting for co-signatures before it is submitted
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingVote {
+    circuit_id: String,
+    payload: Vec<u8>,
+    signatures: Vec<VoteSignature>,
+}
+
+impl PendingVote {
+    /// Creates a new pending vote
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_id`: The ID of the circuit proposal being voted on
+    /// * `payload`: The serialized, signed `CircuitManagementPayload` containing the vote
+    /// * `signatures`: The co-signatures collected for the vote
+    pub fn new(circuit_id: &str, payload: Vec<u8>, mut signatures: Vec<VoteSignature>) -> Self {
+        signatures.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        signatures.dedup_by(|a, b| a.public_key == b.public_key);
+        Self {
+            circuit_id: circuit_id.to_string(),
+            payload,
+            signatures,
+        }
+    }
+
+    /// Returns the ID of the circuit proposal being voted on
+    pub fn circuit_id(&self) -> &str {
+        &self.circuit_id
+    }
+
+    /// Returns the serialized, signed `CircuitManagementPayload` containing the vote
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the co-signatures collected for the vote, sorted by public key
+    pub fn signatures(&self) -> &[VoteSignature] {
+        &self.signatures
+    }
+}
+
+/// Defines methods for managing vote thresholds and collecting co-signatures on pending votes
+pub trait VoteCosignStore: Send + Sync {
+    /// Sets the threshold for a circuit management type, replacing any existing threshold
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if the management type is empty or the number of
+    /// required signatures is zero.
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError>;
+
+    /// Returns the threshold for the given circuit management type, if one is set
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError>;
+
+    /// Lists the thresholds, sorted by circuit management type
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError>;
+
+    /// Removes the threshold for the given circuit management type
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if no threshold is set for the management type.
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError>;
+
+    /// Adds a pending vote, replacing any existing pending vote for the same circuit
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if the circuit ID or payload is empty, or if any
+    /// signature is not valid hex.
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError>;
+
+    /// Returns the pending vote for the given circuit, if it exists
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError>;
+
+    /// Lists the pending votes, sorted by circuit ID
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError>;
+
+    /// Adds a co-signature to the pending vote for the given circuit, replacing any existing
+    /// signature from the same public key
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if there is no pending vote for the circuit, or an
+    /// `InvalidArgument` error if the public key or signature is not valid hex.
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError>;
+
+    /// Removes the pending vote for the given circuit, along with its co-signatures
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if there is no pending vote for the circuit.
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn VoteCosignStore>;
+}
+
+impl Clone for Box<dyn VoteCosignStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<VS> VoteCosignStore for Box<VS>
+where
+    VS: VoteCosignStore + ?Sized,
+{
+    fn set_threshold(&self, threshold: VoteThreshold) -> Result<(), VoteCosignStoreError> {
+        (**self).set_threshold(threshold)
+    }
+
+    fn get_threshold(
+        &self,
+        management_type: &str,
+    ) -> Result<Option<VoteThreshold>, VoteCosignStoreError> {
+        (**self).get_threshold(management_type)
+    }
+
+    fn list_thresholds(&self) -> Result<Vec<VoteThreshold>, VoteCosignStoreError> {
+        (**self).list_thresholds()
+    }
+
+    fn remove_threshold(&self, management_type: &str) -> Result<(), VoteCosignStoreError> {
+        (**self).remove_threshold(management_type)
+    }
+
+    fn add_pending_vote(&self, vote: PendingVote) -> Result<(), VoteCosignStoreError> {
+        (**self).add_pending_vote(vote)
+    }
+
+    fn get_pending_vote(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Option<PendingVote>, VoteCosignStoreError> {
+        (**self).get_pending_vote(circuit_id)
+    }
+
+    fn list_pending_votes(&self) -> Result<Vec<PendingVote>, VoteCosignStoreError> {
+        (**self).list_pending_votes()
+    }
+
+    fn add_signature(
+        &self,
+        circuit_id: &str,
+        signature: VoteSignature,
+    ) -> Result<(), VoteCosignStoreError> {
+        (**self).add_signature(circuit_id, signature)
+    }
+
+    fn remove_pending_vote(&self, circuit_id: &str) -> Result<(), VoteCosignStoreError> {
+        (**self).remove_pending_vote(circuit_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteCosignStore> {
+        (**self).clone_box()
+    }
+}
+
+fn invalid_argument<T>(argument: &str, message: &str) -> Result<T, VoteCosignStoreError> {
+    Err(VoteCosignStoreError::InvalidArgument(
+        InvalidArgumentError::new(argument.to_string(), message.to_string()),
+    ))
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.len() % 2 == 0 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Checks that the threshold has a management type and requires at least one signature
+fn check_threshold(threshold: &VoteThreshold) -> Result<(), VoteCosignStoreError> {
+    if threshold.management_type.trim().is_empty() {
+        return invalid_argument("management_type", "management type cannot be empty");
+    }
+
+    if threshold.required_signatures == 0 {
+        return invalid_argument(
+            "required_signatures",
+            "at least one signature must be required",
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that the signature has a hex public key and signature
+fn check_signature(signature: &VoteSignature) -> Result<(), VoteCosignStoreError> {
+    if !is_hex(&signature.public_key) {
+        return invalid_argument("public_key", "public key must be a hex string");
+    }
+
+    if !is_hex(&signature.signature) {
+        return invalid_argument("signature", "signature must be a hex string");
+    }
+
+    Ok(())
+}
+
+/// Checks that the pending vote has a circuit ID, a payload and valid signatures
+fn check_pending_vote(vote: &PendingVote) -> Result<(), VoteCosignStoreError> {
+    if vote.circuit_id.trim().is_empty() {
+        return invalid_argument("circuit_id", "circuit ID cannot be empty");
+    }
+
+    if vote.payload.is_empty() {
+        return invalid_argument("payload", "payload cannot be empty");
+    }
+
+    vote.signatures.iter().try_for_each(check_signature)
+}
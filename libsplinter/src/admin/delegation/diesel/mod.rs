@@ -0,0 +1,211 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(in crate::admin) mod models;
+mod operations;
+pub(in crate::admin) mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use super::{VoteDelegation, VoteDelegationStore, VoteDelegationStoreError};
+
+use operations::{
+    add_delegation::VoteDelegationStoreAddDelegation as _,
+    get_delegation::VoteDelegationStoreGetDelegation as _,
+    list_delegations::VoteDelegationStoreListDelegations as _,
+    remove_delegation::VoteDelegationStoreRemoveDelegation as _, VoteDelegationStoreOperations,
+};
+
+pub struct DieselVoteDelegationStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselVoteDelegationStore<C> {
+    /// Creates a new `DieselVoteDelegationStore`
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl VoteDelegationStore for DieselVoteDelegationStore<diesel::sqlite::SqliteConnection> {
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?).add_delegation(delegation)
+    }
+
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?)
+            .get_delegation(node_id, delegate_public_key)
+    }
+
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?).list_delegations()
+    }
+
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_delegation(node_id, delegate_public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteDelegationStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl VoteDelegationStore for DieselVoteDelegationStore<diesel::pg::PgConnection> {
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?).add_delegation(delegation)
+    }
+
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?)
+            .get_delegation(node_id, delegate_public_key)
+    }
+
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?).list_delegations()
+    }
+
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError> {
+        VoteDelegationStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_delegation(node_id, delegate_public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteDelegationStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::sqlite::SqliteConnection;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const KEY_C: &str = "03cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+    /// Verify that a SQLite-backed `DieselVoteDelegationStore` correctly supports managing
+    /// delegations.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Add two delegations and replace one, and verify that they are returned and listed with
+    ///    their management types intact.
+    /// 3. Remove a delegation and verify that it is no longer returned, and that removing it
+    ///    again returns a `NotFound` error.
+    #[test]
+    fn sqlite_vote_delegation() {
+        let store = DieselVoteDelegationStore::new(create_connection_pool_and_migrate());
+
+        let first = VoteDelegation::new(
+            "node-b",
+            KEY_A,
+            KEY_B,
+            vec!["gameroom".into()],
+            1_000,
+            "0a0a",
+        );
+        let second = VoteDelegation::new(
+            "node-a",
+            KEY_A,
+            KEY_C,
+            vec!["gameroom".into(), "acme".into()],
+            2_000,
+            "0b0b",
+        );
+        let replacement =
+            VoteDelegation::new("node-b", KEY_A, KEY_B, vec!["acme".into()], 3_000, "0c0c");
+
+        store
+            .add_delegation(first)
+            .expect("Failed to add delegation");
+        store
+            .add_delegation(second.clone())
+            .expect("Failed to add delegation");
+        store
+            .add_delegation(replacement.clone())
+            .expect("Failed to replace delegation");
+
+        assert_eq!(
+            store
+                .get_delegation("node-b", KEY_B)
+                .expect("Failed to get delegation"),
+            Some(replacement.clone())
+        );
+        assert_eq!(
+            store
+                .list_delegations()
+                .expect("Failed to list delegations"),
+            vec![second, replacement]
+        );
+
+        store
+            .remove_delegation("node-a", KEY_C)
+            .expect("Failed to remove delegation");
+        assert_eq!(
+            store
+                .get_delegation("node-a", KEY_C)
+                .expect("Failed to get delegation"),
+            None
+        );
+        assert!(matches!(
+            store.remove_delegation("node-a", KEY_C),
+            Err(VoteDelegationStoreError::NotFound(_))
+        ));
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
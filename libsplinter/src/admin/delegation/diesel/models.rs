@@ -0,0 +1,28 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::admin_vote_delegations;
+
+#[derive(Debug, PartialEq, Identifiable, Insertable, Queryable)]
+#[table_name = "admin_vote_delegations"]
+#[primary_key(node_id, delegate_public_key)]
+pub struct VoteDelegationModel {
+    pub node_id: String,
+    pub delegate_public_key: String,
+    pub delegator_public_key: String,
+    /// The delegated management types, separated by commas
+    pub management_types: String,
+    pub expires_at: i64,
+    pub signature: String,
+}
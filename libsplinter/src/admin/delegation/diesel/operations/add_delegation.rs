@@ -0,0 +1,95 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::admin::delegation::{
+    check_delegation,
+    diesel::{models::VoteDelegationModel, schema::admin_vote_delegations},
+    VoteDelegation, VoteDelegationStoreError,
+};
+use crate::error::InvalidArgumentError;
+
+use super::VoteDelegationStoreOperations;
+
+pub trait VoteDelegationStoreAddDelegation {
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> VoteDelegationStoreAddDelegation
+    for VoteDelegationStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        let model = into_model(delegation)?;
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing delegation of the node to the delegate
+            delete(
+                admin_vote_delegations::table.find((&model.node_id, &model.delegate_public_key)),
+            )
+            .execute(self.conn)?;
+            insert_into(admin_vote_delegations::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> VoteDelegationStoreAddDelegation
+    for VoteDelegationStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        let model = into_model(delegation)?;
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing delegation of the node to the delegate
+            delete(
+                admin_vote_delegations::table.find((&model.node_id, &model.delegate_public_key)),
+            )
+            .execute(self.conn)?;
+            insert_into(admin_vote_delegations::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+fn into_model(delegation: VoteDelegation) -> Result<VoteDelegationModel, VoteDelegationStoreError> {
+    check_delegation(&delegation)?;
+
+    let expires_at = i64::try_from(delegation.expires_at).map_err(|_| {
+        VoteDelegationStoreError::InvalidArgument(InvalidArgumentError::new(
+            "expires_at".to_string(),
+            "expiration time is too far in the future".to_string(),
+        ))
+    })?;
+
+    Ok(VoteDelegationModel {
+        node_id: delegation.node_id,
+        delegate_public_key: delegation.delegate_public_key,
+        delegator_public_key: delegation.delegator_public_key,
+        management_types: delegation.management_types.join(","),
+        expires_at,
+        signature: delegation.signature,
+    })
+}
@@ -0,0 +1,67 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::delegation::{
+    diesel::{models::VoteDelegationModel, schema::admin_vote_delegations},
+    VoteDelegation, VoteDelegationStoreError,
+};
+
+use super::VoteDelegationStoreOperations;
+
+pub trait VoteDelegationStoreGetDelegation {
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError>;
+}
+
+impl<'a, C> VoteDelegationStoreGetDelegation for VoteDelegationStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError> {
+        Ok(admin_vote_delegations::table
+            .find((node_id, delegate_public_key.to_lowercase()))
+            .first::<VoteDelegationModel>(self.conn)
+            .optional()?
+            .map(VoteDelegation::from))
+    }
+}
+
+impl From<VoteDelegationModel> for VoteDelegation {
+    fn from(model: VoteDelegationModel) -> Self {
+        // Expiration times are checked to fit in an i64 before they are stored
+        VoteDelegation::new(
+            &model.node_id,
+            &model.delegator_public_key,
+            &model.delegate_public_key,
+            model
+                .management_types
+                .split(',')
+                .map(String::from)
+                .collect(),
+            model.expires_at.max(0) as u64,
+            &model.signature,
+        )
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::admin::delegation::{
+    diesel::{models::VoteDelegationModel, schema::admin_vote_delegations},
+    VoteDelegation, VoteDelegationStoreError,
+};
+
+use super::VoteDelegationStoreOperations;
+
+pub trait VoteDelegationStoreListDelegations {
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError>;
+}
+
+impl<'a, C> VoteDelegationStoreListDelegations for VoteDelegationStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError> {
+        Ok(admin_vote_delegations::table
+            .order((
+                admin_vote_delegations::node_id,
+                admin_vote_delegations::delegate_public_key,
+            ))
+            .load::<VoteDelegationModel>(self.conn)?
+            .into_iter()
+            .map(VoteDelegation::from)
+            .collect())
+    }
+}
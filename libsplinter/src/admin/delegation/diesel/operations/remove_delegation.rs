@@ -0,0 +1,51 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::admin::delegation::{diesel::schema::admin_vote_delegations, VoteDelegationStoreError};
+
+use super::VoteDelegationStoreOperations;
+
+pub trait VoteDelegationStoreRemoveDelegation {
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError>;
+}
+
+impl<'a, C> VoteDelegationStoreRemoveDelegation for VoteDelegationStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError> {
+        let deleted = delete(
+            admin_vote_delegations::table.find((node_id, delegate_public_key.to_lowercase())),
+        )
+        .execute(self.conn)?;
+        if deleted == 0 {
+            return Err(VoteDelegationStoreError::NotFound(format!(
+                "delegation of node {} to {}",
+                node_id, delegate_public_key
+            )));
+        }
+
+        Ok(())
+    }
+}
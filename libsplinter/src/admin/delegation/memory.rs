@@ -0,0 +1,167 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+
+use super::{check_delegation, VoteDelegation, VoteDelegationStore, VoteDelegationStoreError};
+
+#[derive(Default, Clone)]
+pub struct MemoryVoteDelegationStore {
+    /// Map of (node ID, delegate public key) -> delegation
+    delegations: Arc<Mutex<BTreeMap<(String, String), VoteDelegation>>>,
+}
+
+impl MemoryVoteDelegationStore {
+    /// Creates a new memory-backed vote delegation store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<MutexGuard<BTreeMap<(String, String), VoteDelegation>>, VoteDelegationStoreError>
+    {
+        self.delegations.lock().map_err(|_| {
+            VoteDelegationStoreError::Internal(InternalError::with_message(
+                "Cannot access vote delegation store: mutex lock poisoned".to_string(),
+            ))
+        })
+    }
+}
+
+impl VoteDelegationStore for MemoryVoteDelegationStore {
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        check_delegation(&delegation)?;
+        self.lock()?.insert(
+            (
+                delegation.node_id.clone(),
+                delegation.delegate_public_key.clone(),
+            ),
+            delegation,
+        );
+        Ok(())
+    }
+
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError> {
+        Ok(self
+            .lock()?
+            .get(&(node_id.to_string(), delegate_public_key.to_lowercase()))
+            .cloned())
+    }
+
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError> {
+        Ok(self.lock()?.values().cloned().collect())
+    }
+
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError> {
+        self.lock()?
+            .remove(&(node_id.to_string(), delegate_public_key.to_lowercase()))
+            .map(|_| ())
+            .ok_or_else(|| {
+                VoteDelegationStoreError::NotFound(format!(
+                    "delegation of node {} to {}",
+                    node_id, delegate_public_key
+                ))
+            })
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteDelegationStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const KEY_C: &str = "03cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+    fn delegation(node_id: &str, delegate: &str, management_type: &str) -> VoteDelegation {
+        VoteDelegation::new(
+            node_id,
+            KEY_A,
+            delegate,
+            vec![management_type.into()],
+            1_000,
+            "0a0a",
+        )
+    }
+
+    /// Verify that the memory store adds, replaces, lists and removes delegations, and rejects
+    /// invalid delegations.
+    #[test]
+    fn add_list_and_remove_delegations() {
+        let store = MemoryVoteDelegationStore::new();
+
+        store
+            .add_delegation(delegation("node-b", KEY_B, "gameroom"))
+            .expect("Failed to add delegation");
+        store
+            .add_delegation(delegation("node-a", KEY_C, "gameroom"))
+            .expect("Failed to add delegation");
+        store
+            .add_delegation(delegation("node-b", KEY_B, "acme"))
+            .expect("Failed to replace delegation");
+
+        assert_eq!(
+            store
+                .get_delegation("node-b", KEY_B)
+                .expect("Failed to get delegation"),
+            Some(delegation("node-b", KEY_B, "acme"))
+        );
+        assert_eq!(
+            store.list_delegations().expect("Failed to list"),
+            vec![
+                delegation("node-a", KEY_C, "gameroom"),
+                delegation("node-b", KEY_B, "acme")
+            ]
+        );
+
+        assert!(matches!(
+            store.add_delegation(delegation("node-a", KEY_B, "a,b")),
+            Err(VoteDelegationStoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            store.add_delegation(delegation("node-a", "xyz", "gameroom")),
+            Err(VoteDelegationStoreError::InvalidArgument(_))
+        ));
+
+        store
+            .remove_delegation("node-b", KEY_B)
+            .expect("Failed to remove delegation");
+        assert_eq!(
+            store
+                .get_delegation("node-b", KEY_B)
+                .expect("Failed to get delegation"),
+            None
+        );
+        assert!(matches!(
+            store.remove_delegation("node-b", KEY_B),
+            Err(VoteDelegationStoreError::NotFound(_))
+        ));
+    }
+}
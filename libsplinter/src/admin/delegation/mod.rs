@@ -0,0 +1,452 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delegation of a node's voting authority on circuit proposals to another key.
+//!
+//! A key that is permitted to vote for a node may delegate that authority to another key, such as
+//! the key of an automated policy engine, for proposals of specific circuit management types. The
+//! delegation is a record signed by the delegating key that expires at a fixed time, and is added
+//! to the delegating node's [`VoteDelegationStore`].
+//!
+//! The delegate includes the signed record in each vote it casts, so that every member of the
+//! circuit can verify the vote on its own: a member accepts the delegate's vote on behalf of the
+//! delegating node if the record's signature is valid, it has not expired, it covers the
+//! proposal's management type and the delegating key is still permitted to vote for the node.
+//! The delegating node additionally requires the record to still be in its store when the vote is
+//! submitted to it, so that removing the record revokes the delegation.
+
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod memory;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cylinder::{PublicKey, Signature, Signer, Verifier};
+
+use crate::error::{InternalError, InvalidArgumentError};
+use crate::hex::{parse_hex, to_hex};
+use crate::protos::admin;
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselVoteDelegationStore;
+pub use error::VoteDelegationStoreError;
+pub use memory::MemoryVoteDelegationStore;
+
+/// The prefix of the message that is signed to create a delegation, which keeps a delegation
+/// signature from being valid for any other kind of message
+const DELEGATION_MESSAGE_PREFIX: &str = "splinter-vote-delegation";
+
+/// A signed record delegating a node's voting authority to another key
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteDelegation {
+    node_id: String,
+    delegator_public_key: String,
+    delegate_public_key: String,
+    management_types: Vec<String>,
+    expires_at: u64,
+    signature: String,
+}
+
+impl VoteDelegation {
+    /// Creates a delegation from a record that was signed elsewhere
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id`: The ID of the node whose votes the delegate may cast
+    /// * `delegator_public_key`: The hex-encoded public key that signed the delegation
+    /// * `delegate_public_key`: The hex-encoded public key the voting authority is delegated to
+    /// * `management_types`: The circuit management types of the proposals the delegate may vote
+    ///   on
+    /// * `expires_at`: The time the delegation expires, in seconds since the Unix epoch
+    /// * `signature`: The delegator's hex-encoded signature of the delegation
+    pub fn new(
+        node_id: &str,
+        delegator_public_key: &str,
+        delegate_public_key: &str,
+        mut management_types: Vec<String>,
+        expires_at: u64,
+        signature: &str,
+    ) -> Self {
+        management_types.sort();
+        management_types.dedup();
+        Self {
+            node_id: node_id.to_string(),
+            delegator_public_key: delegator_public_key.to_lowercase(),
+            delegate_public_key: delegate_public_key.to_lowercase(),
+            management_types,
+            expires_at,
+            signature: signature.to_lowercase(),
+        }
+    }
+
+    /// Creates a delegation signed by the given signer
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id`: The ID of the node whose votes the delegate may cast
+    /// * `delegate_public_key`: The hex-encoded public key the voting authority is delegated to
+    /// * `management_types`: The circuit management types of the proposals the delegate may vote
+    ///   on
+    /// * `expires_at`: The time the delegation expires, in seconds since the Unix epoch
+    /// * `signer`: The signer of a key that is permitted to vote for the node
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InternalError` if the signer's public key cannot be read or signing fails.
+    pub fn sign(
+        node_id: &str,
+        delegate_public_key: &str,
+        management_types: Vec<String>,
+        expires_at: u64,
+        signer: &dyn Signer,
+    ) -> Result<Self, InternalError> {
+        let delegator_public_key = signer
+            .public_key()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        let mut delegation = Self::new(
+            node_id,
+            &to_hex(delegator_public_key.as_slice()),
+            delegate_public_key,
+            management_types,
+            expires_at,
+            "",
+        );
+        let signature = signer
+            .sign(&delegation.message())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        delegation.signature = to_hex(signature.as_slice());
+        Ok(delegation)
+    }
+
+    /// Creates a delegation from the record carried in a vote
+    pub fn from_proto(mut proto: admin::VoteDelegation) -> Self {
+        Self::new(
+            proto.get_node_id(),
+            proto.get_delegator_public_key(),
+            proto.get_delegate_public_key(),
+            proto.take_management_types().into_vec(),
+            proto.get_expires_at(),
+            proto.get_signature(),
+        )
+    }
+
+    /// Converts the delegation into the record carried in a vote
+    pub fn into_proto(self) -> admin::VoteDelegation {
+        let mut proto = admin::VoteDelegation::new();
+        proto.set_node_id(self.node_id);
+        proto.set_delegator_public_key(self.delegator_public_key);
+        proto.set_delegate_public_key(self.delegate_public_key);
+        proto.set_management_types(self.management_types.into());
+        proto.set_expires_at(self.expires_at);
+        proto.set_signature(self.signature);
+        proto
+    }
+
+    /// Returns the ID of the node whose votes the delegate may cast
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Returns the hex-encoded public key that signed the delegation
+    pub fn delegator_public_key(&self) -> &str {
+        &self.delegator_public_key
+    }
+
+    /// Returns the hex-encoded public key the voting authority is delegated to
+    pub fn delegate_public_key(&self) -> &str {
+        &self.delegate_public_key
+    }
+
+    /// Returns the circuit management types of the proposals the delegate may vote on, sorted
+    pub fn management_types(&self) -> &[String] {
+        &self.management_types
+    }
+
+    /// Returns the time the delegation expires, in seconds since the Unix epoch
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    /// Returns the delegator's hex-encoded signature of the delegation
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// Returns whether or not the delegation covers proposals of the given management type
+    pub fn covers(&self, management_type: &str) -> bool {
+        self.management_types
+            .iter()
+            .any(|covered| covered == management_type)
+    }
+
+    /// Returns whether or not the delegation has expired at time `now`
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0);
+        now >= self.expires_at
+    }
+
+    /// Checks that the delegation was signed by its delegator
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgumentError` if the delegator's public key or the signature is not
+    /// valid hex, or if the signature does not verify.
+    pub fn verify(&self, verifier: &dyn Verifier) -> Result<(), InvalidArgumentError> {
+        let public_key = parse_hex(&self.delegator_public_key).map_err(|_| {
+            InvalidArgumentError::new(
+                "delegator_public_key".into(),
+                "delegator public key must be a hex string".into(),
+            )
+        })?;
+        let signature = parse_hex(&self.signature).map_err(|_| {
+            InvalidArgumentError::new("signature".into(), "signature must be a hex string".into())
+        })?;
+
+        let verified = verifier
+            .verify(
+                &self.message(),
+                &Signature::new(signature),
+                &PublicKey::new(public_key),
+            )
+            .unwrap_or(false);
+        if !verified {
+            return Err(InvalidArgumentError::new(
+                "signature".into(),
+                "signature does not match the delegation and delegator public key".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the message that is signed by the delegator: the prefix, node ID, delegator and
+    /// delegate public keys, comma-separated management types and expiration time, each on its
+    /// own line
+    fn message(&self) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            DELEGATION_MESSAGE_PREFIX,
+            self.node_id,
+            self.delegator_public_key,
+            self.delegate_public_key,
+            self.management_types.join(","),
+            self.expires_at
+        )
+        .into_bytes()
+    }
+}
+
+/// Defines methods for managing the vote delegations honored by a node
+pub trait VoteDelegationStore: Send + Sync {
+    /// Adds a delegation, replacing any existing delegation of the same node to the same delegate
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if the node ID is empty, a public key or the signature
+    /// is not valid hex, or the management types are empty or contain a comma.
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError>;
+
+    /// Returns the delegation of the given node to the given delegate, if it exists
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError>;
+
+    /// Lists the delegations, sorted by node ID and delegate public key
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError>;
+
+    /// Removes the delegation of the given node to the given delegate
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if the delegation does not exist.
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn VoteDelegationStore>;
+}
+
+impl Clone for Box<dyn VoteDelegationStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<DS> VoteDelegationStore for Box<DS>
+where
+    DS: VoteDelegationStore + ?Sized,
+{
+    fn add_delegation(&self, delegation: VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+        (**self).add_delegation(delegation)
+    }
+
+    fn get_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<Option<VoteDelegation>, VoteDelegationStoreError> {
+        (**self).get_delegation(node_id, delegate_public_key)
+    }
+
+    fn list_delegations(&self) -> Result<Vec<VoteDelegation>, VoteDelegationStoreError> {
+        (**self).list_delegations()
+    }
+
+    fn remove_delegation(
+        &self,
+        node_id: &str,
+        delegate_public_key: &str,
+    ) -> Result<(), VoteDelegationStoreError> {
+        (**self).remove_delegation(node_id, delegate_public_key)
+    }
+
+    fn clone_box(&self) -> Box<dyn VoteDelegationStore> {
+        (**self).clone_box()
+    }
+}
+
+fn invalid_argument<T>(argument: &str, message: &str) -> Result<T, VoteDelegationStoreError> {
+    Err(VoteDelegationStoreError::InvalidArgument(
+        InvalidArgumentError::new(argument.to_string(), message.to_string()),
+    ))
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.len() % 2 == 0 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Checks that the delegation has a node ID, hex keys and signature, and management types that
+/// can be stored as a comma-separated list
+fn check_delegation(delegation: &VoteDelegation) -> Result<(), VoteDelegationStoreError> {
+    if delegation.node_id.trim().is_empty() {
+        return invalid_argument("node_id", "node ID cannot be empty");
+    }
+
+    if !is_hex(&delegation.delegator_public_key) {
+        return invalid_argument(
+            "delegator_public_key",
+            "delegator public key must be a hex string",
+        );
+    }
+
+    if !is_hex(&delegation.delegate_public_key) {
+        return invalid_argument(
+            "delegate_public_key",
+            "delegate public key must be a hex string",
+        );
+    }
+
+    if delegation.management_types.is_empty() {
+        return invalid_argument(
+            "management_types",
+            "at least one management type must be delegated",
+        );
+    }
+
+    if delegation
+        .management_types
+        .iter()
+        .any(|management_type| management_type.trim().is_empty() || management_type.contains(','))
+    {
+        return invalid_argument(
+            "management_types",
+            "management types cannot be empty or contain a comma",
+        );
+    }
+
+    if !is_hex(&delegation.signature) {
+        return invalid_argument("signature", "signature must be a hex string");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context};
+
+    /// Verify that a signed delegation verifies, and that it no longer verifies once any of its
+    /// fields is changed.
+    #[test]
+    fn sign_and_verify() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let verifier = context.new_verifier();
+
+        let delegation = VoteDelegation::sign(
+            "node-000",
+            "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            vec!["gameroom".into(), "acme".into()],
+            1_700_000_000,
+            &*signer,
+        )
+        .expect("Failed to sign delegation");
+
+        assert_eq!(delegation.management_types(), &["acme", "gameroom"]);
+        assert!(delegation.verify(&*verifier).is_ok());
+        assert!(check_delegation(&delegation).is_ok());
+
+        let tampered = VoteDelegation::new(
+            delegation.node_id(),
+            delegation.delegator_public_key(),
+            delegation.delegate_public_key(),
+            vec!["acme".into(), "gameroom".into(), "other".into()],
+            delegation.expires_at(),
+            delegation.signature(),
+        );
+        assert!(tampered.verify(&*verifier).is_err());
+
+        let extended = VoteDelegation::new(
+            delegation.node_id(),
+            delegation.delegator_public_key(),
+            delegation.delegate_public_key(),
+            delegation.management_types().to_vec(),
+            delegation.expires_at() + 1,
+            delegation.signature(),
+        );
+        assert!(extended.verify(&*verifier).is_err());
+    }
+
+    /// Verify that a delegation only covers its management types and expires at its expiration
+    /// time.
+    #[test]
+    fn covers_and_expires() {
+        let delegation = VoteDelegation::new(
+            "node-000",
+            "02aa",
+            "03bb",
+            vec!["gameroom".into()],
+            1_000,
+            "0a0a",
+        );
+
+        assert!(delegation.covers("gameroom"));
+        assert!(!delegation.covers("acme"));
+        assert!(!delegation.is_expired(UNIX_EPOCH + Duration::from_secs(999)));
+        assert!(delegation.is_expired(UNIX_EPOCH + Duration::from_secs(1_000)));
+    }
+}
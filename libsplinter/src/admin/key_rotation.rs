@@ -0,0 +1,401 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rotation of a node's admin keys with an overlap period.
+//!
+//! A node's key is rotated by publishing the new key in the node's registry entry and moving the
+//! old key from the node's `keys` to its `retired_keys` metadata entry, along with the time the
+//! key was retired. The [`KeyRotationVerifier`] continues to permit a retired key for a
+//! configurable overlap period, so proposals and votes that were signed with the old key before
+//! the rotation reached every member are not rejected. Once the overlap period has passed, the
+//! retired key is no longer permitted and can be pruned from the node's metadata.
+//!
+//! The `retired_keys` metadata entry is a comma-separated list of `<public key>:<retired at>`
+//! pairs, where the public key is hex-encoded and the retirement time is in seconds since the
+//! Unix epoch.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::admin::service::{AdminKeyVerifier, AdminKeyVerifierError};
+use crate::error::InvalidArgumentError;
+use crate::hex::to_hex;
+use crate::registry::{Node, RegistryReader};
+
+/// The registry node metadata key that holds a node's retired keys
+pub const RETIRED_KEYS_METADATA_KEY: &str = "retired_keys";
+
+/// The default period a retired key remains permitted after it was retired (24 hours)
+pub const DEFAULT_KEY_ROTATION_OVERLAP: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A key that was removed from a node's keys by a rotation
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetiredKey {
+    public_key: String,
+    retired_at: u64,
+}
+
+impl RetiredKey {
+    /// Returns the hex-encoded public key
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Returns the time the key was retired, in seconds since the Unix epoch
+    pub fn retired_at(&self) -> u64 {
+        self.retired_at
+    }
+
+    /// Returns whether or not the key is still within the given overlap period at time `now`
+    pub fn is_within_overlap(&self, overlap: Duration, now: SystemTime) -> bool {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0);
+        now < self.retired_at.saturating_add(overlap.as_secs())
+    }
+}
+
+/// Returns the retired keys of the node, as recorded in its `retired_keys` metadata entry
+///
+/// # Errors
+///
+/// Returns an `InvalidArgumentError` if the metadata entry is malformed.
+pub fn retired_keys(node: &Node) -> Result<Vec<RetiredKey>, InvalidArgumentError> {
+    let entry = match node.metadata.get(RETIRED_KEYS_METADATA_KEY) {
+        Some(entry) => entry,
+        None => return Ok(vec![]),
+    };
+
+    entry
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, ':');
+            let public_key = parts.next().unwrap_or("");
+            let retired_at = parts
+                .next()
+                .and_then(|retired_at| retired_at.parse::<u64>().ok());
+            match retired_at {
+                Some(retired_at) if !public_key.is_empty() => Ok(RetiredKey {
+                    public_key: public_key.to_string(),
+                    retired_at,
+                }),
+                _ => Err(InvalidArgumentError::new(
+                    RETIRED_KEYS_METADATA_KEY.to_string(),
+                    format!(
+                        "invalid retired key '{}' for node '{}'; expected <key>:<retired at>",
+                        pair, node.identity
+                    ),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Publishes `new_key` as a key of the node and retires `old_key`
+///
+/// The new key is added to the node's keys, the old key is removed from them and recorded in the
+/// node's `retired_keys` metadata entry with the time `retired_at`. The updated node must then be
+/// written back to the registry.
+///
+/// # Errors
+///
+/// Returns an `InvalidArgumentError` if the keys are the same, if `old_key` is not a key of the
+/// node, or if the node's retired keys are malformed.
+pub fn rotate_node_key(
+    node: &mut Node,
+    old_key: &str,
+    new_key: &str,
+    retired_at: SystemTime,
+) -> Result<(), InvalidArgumentError> {
+    if old_key == new_key {
+        return Err(InvalidArgumentError::new(
+            "new_key".to_string(),
+            "new key must differ from the key being retired".to_string(),
+        ));
+    }
+
+    if !node.has_key(old_key) {
+        return Err(InvalidArgumentError::new(
+            "old_key".to_string(),
+            format!("key {} is not a key of node '{}'", old_key, node.identity),
+        ));
+    }
+
+    let retired_at = retired_at
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| {
+            InvalidArgumentError::new("retired_at".to_string(), format!("invalid time: {}", err))
+        })?
+        .as_secs();
+
+    let mut retired = retired_keys(node)?;
+    retired.retain(|key| key.public_key != old_key && key.public_key != new_key);
+    retired.push(RetiredKey {
+        public_key: old_key.to_string(),
+        retired_at,
+    });
+
+    if !node.has_key(new_key) {
+        node.keys.push(new_key.to_string());
+    }
+    node.keys.retain(|key| key != old_key);
+    set_retired_keys(node, &retired);
+
+    Ok(())
+}
+
+/// Removes the retired keys of the node whose overlap period has passed at time `now`, returning
+/// the removed keys
+///
+/// # Errors
+///
+/// Returns an `InvalidArgumentError` if the node's retired keys are malformed.
+pub fn prune_retired_keys(
+    node: &mut Node,
+    overlap: Duration,
+    now: SystemTime,
+) -> Result<Vec<RetiredKey>, InvalidArgumentError> {
+    let (retained, pruned): (Vec<_>, Vec<_>) = retired_keys(node)?
+        .into_iter()
+        .partition(|key| key.is_within_overlap(overlap, now));
+
+    if !pruned.is_empty() {
+        set_retired_keys(node, &retained);
+    }
+
+    Ok(pruned)
+}
+
+/// Removes the given key from the node's retired keys, ending its overlap period immediately.
+/// Returns whether or not the key was a retired key of the node.
+///
+/// # Errors
+///
+/// Returns an `InvalidArgumentError` if the node's retired keys are malformed.
+pub fn remove_retired_key(node: &mut Node, public_key: &str) -> Result<bool, InvalidArgumentError> {
+    let mut retired = retired_keys(node)?;
+    let count = retired.len();
+    retired.retain(|key| key.public_key != public_key);
+
+    if retired.len() == count {
+        return Ok(false);
+    }
+
+    set_retired_keys(node, &retired);
+    Ok(true)
+}
+
+fn set_retired_keys(node: &mut Node, retired: &[RetiredKey]) {
+    if retired.is_empty() {
+        node.metadata.remove(RETIRED_KEYS_METADATA_KEY);
+    } else {
+        node.metadata.insert(
+            RETIRED_KEYS_METADATA_KEY.to_string(),
+            retired
+                .iter()
+                .map(|key| format!("{}:{}", key.public_key, key.retired_at))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+}
+
+/// An `AdminKeyVerifier` that permits the keys of a node in the registry, as well as the node's
+/// retired keys during the overlap period after they were retired.
+pub struct KeyRotationVerifier {
+    registry: Box<dyn RegistryReader>,
+    overlap: Duration,
+}
+
+impl KeyRotationVerifier {
+    /// Creates a new verifier
+    ///
+    /// # Arguments
+    ///
+    /// * `registry`: The registry to lookup nodes in
+    /// * `overlap`: How long a retired key remains permitted after it was retired
+    pub fn new(registry: Box<dyn RegistryReader>, overlap: Duration) -> Self {
+        Self { registry, overlap }
+    }
+}
+
+impl AdminKeyVerifier for KeyRotationVerifier {
+    fn is_permitted(&self, node_id: &str, key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+        let node = match self.registry.fetch_node(node_id).map_err(|err| {
+            AdminKeyVerifierError::new_with_source(
+                &format!("Failed to lookup node '{}' in registry", node_id),
+                Box::new(err),
+            )
+        })? {
+            Some(node) => node,
+            None => return Ok(false),
+        };
+
+        let public_key = to_hex(key);
+        if node.has_key(&public_key) {
+            return Ok(true);
+        }
+
+        let retired = match retired_keys(&node) {
+            Ok(retired) => retired,
+            Err(err) => {
+                warn!("Ignoring retired keys of node '{}': {}", node_id, err);
+                return Ok(false);
+            }
+        };
+
+        let now = SystemTime::now();
+        Ok(retired.iter().any(|retired_key| {
+            retired_key.public_key == public_key && retired_key.is_within_overlap(self.overlap, now)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::registry::{MetadataPredicate, NodeIter, RegistryError};
+
+    struct MemRegistry {
+        nodes: HashMap<String, Node>,
+    }
+
+    impl RegistryReader for MemRegistry {
+        fn list_nodes<'a, 'b: 'a>(
+            &'b self,
+            predicates: &'a [MetadataPredicate],
+        ) -> Result<NodeIter<'a>, RegistryError> {
+            Ok(Box::new(
+                self.nodes
+                    .values()
+                    .filter(|node| predicates.iter().all(|predicate| predicate.apply(node)))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ))
+        }
+
+        fn count_nodes(&self, predicates: &[MetadataPredicate]) -> Result<u32, RegistryError> {
+            self.list_nodes(predicates).map(|iter| iter.count() as u32)
+        }
+
+        fn fetch_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
+            Ok(self.nodes.get(identity).cloned())
+        }
+    }
+
+    fn node() -> Node {
+        Node::builder("node-a")
+            .with_endpoint("tcps://127.0.0.1:8044")
+            .with_display_name("Node A")
+            .with_key("0123")
+            .build()
+            .expect("Failed to build node")
+    }
+
+    /// Verify that rotating a key publishes the new key, retires the old key, and that the
+    /// retired keys round-trip through the node's metadata.
+    ///
+    /// 1. Rotate the node's key and verify its keys and retired keys
+    /// 2. Rotate the node's key again and verify both previous keys are retired
+    /// 3. Verify that rotating to the same key, or from an unknown key, fails
+    #[test]
+    fn test_rotate_node_key() {
+        let mut node = node();
+        let first = UNIX_EPOCH + Duration::from_secs(100);
+        let second = UNIX_EPOCH + Duration::from_secs(200);
+
+        rotate_node_key(&mut node, "0123", "4567", first).expect("Failed to rotate key");
+        assert_eq!(node.keys, vec!["4567".to_string()]);
+        assert_eq!(
+            node.metadata.get(RETIRED_KEYS_METADATA_KEY),
+            Some(&"0123:100".to_string())
+        );
+
+        rotate_node_key(&mut node, "4567", "89ab", second).expect("Failed to rotate key");
+        assert_eq!(node.keys, vec!["89ab".to_string()]);
+        let retired = retired_keys(&node).expect("Failed to parse retired keys");
+        assert_eq!(retired.len(), 2);
+        assert_eq!(retired[0].public_key(), "0123");
+        assert_eq!(retired[0].retired_at(), 100);
+        assert_eq!(retired[1].public_key(), "4567");
+        assert_eq!(retired[1].retired_at(), 200);
+
+        assert!(rotate_node_key(&mut node, "89ab", "89ab", second).is_err());
+        assert!(rotate_node_key(&mut node, "0123", "cdef", second).is_err());
+    }
+
+    /// Verify that pruning removes only the retired keys whose overlap period has passed, and
+    /// that malformed retired keys are rejected.
+    #[test]
+    fn test_prune_retired_keys() {
+        let mut node = node();
+        node.metadata.insert(
+            RETIRED_KEYS_METADATA_KEY.to_string(),
+            "4567:100,89ab:200".to_string(),
+        );
+
+        let pruned = prune_retired_keys(
+            &mut node,
+            Duration::from_secs(60),
+            UNIX_EPOCH + Duration::from_secs(200),
+        )
+        .expect("Failed to prune retired keys");
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].public_key(), "4567");
+        assert_eq!(
+            node.metadata.get(RETIRED_KEYS_METADATA_KEY),
+            Some(&"89ab:200".to_string())
+        );
+
+        assert!(remove_retired_key(&mut node, "89ab").expect("Failed to remove key"));
+        assert!(node.metadata.get(RETIRED_KEYS_METADATA_KEY).is_none());
+
+        node.metadata
+            .insert(RETIRED_KEYS_METADATA_KEY.to_string(), "4567".to_string());
+        assert!(retired_keys(&node).is_err());
+    }
+
+    /// Verify that the verifier permits the node's keys, and its retired keys only during the
+    /// overlap period.
+    #[test]
+    fn test_key_rotation_verifier() {
+        let mut node = node();
+        let now = SystemTime::now();
+        rotate_node_key(&mut node, "0123", "4567", now).expect("Failed to rotate key");
+        // a key that was retired long before the overlap period
+        node.metadata.insert(
+            RETIRED_KEYS_METADATA_KEY.to_string(),
+            format!(
+                "{},89ab:100",
+                node.metadata.get(RETIRED_KEYS_METADATA_KEY).unwrap()
+            ),
+        );
+        let mut nodes = HashMap::new();
+        nodes.insert(node.identity.clone(), node);
+
+        let verifier =
+            KeyRotationVerifier::new(Box::new(MemRegistry { nodes }), Duration::from_secs(3600));
+
+        assert!(verifier.is_permitted("node-a", &[0x45, 0x67]).unwrap());
+        assert!(verifier.is_permitted("node-a", &[0x01, 0x23]).unwrap());
+        assert!(!verifier.is_permitted("node-a", &[0x89, 0xab]).unwrap());
+        assert!(!verifier.is_permitted("node-b", &[0x45, 0x67]).unwrap());
+    }
+}
@@ -19,5 +19,10 @@ pub use super::service::messages::{
 };
 
 pub use super::service::messages::builders::{
-    BuilderError, CreateCircuitBuilder, SplinterNodeBuilder, SplinterServiceBuilder,
+    BuilderError, CircuitProposalVoteBuilder, CreateCircuitBuilder, SplinterNodeBuilder,
+    SplinterServiceBuilder,
+};
+
+pub use super::service::messages::payload::{
+    CircuitManagementAction, CircuitManagementPayloadBuilder,
 };
@@ -12,9 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "admin-allowed-signers")]
+pub mod allowed_signers;
+#[cfg(feature = "admin-auto-vote")]
+pub mod auto_vote;
+#[cfg(feature = "circuit-id-policy")]
+pub mod circuit_id;
+#[cfg(feature = "admin-vote-cosigning")]
+pub mod cosign;
+#[cfg(feature = "admin-vote-delegation")]
+pub mod delegation;
 pub mod error;
+#[cfg(feature = "admin-key-rotation")]
+pub mod key_rotation;
 pub mod messages;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
+pub mod routing;
+#[cfg(feature = "admin-routing-snapshot")]
+pub mod routing_snapshot;
 pub mod service;
+#[cfg(feature = "admin-signature-cache")]
+pub mod signature_cache;
+#[cfg(feature = "admin-signed-submit")]
+pub mod signed_payload;
+#[cfg(feature = "admin-parallel-startup")]
+pub(crate) mod startup;
 pub mod store;
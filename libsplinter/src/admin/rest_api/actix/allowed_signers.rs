@@ -0,0 +1,168 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for managing the signers that are allowed to
+//! submit circuit management payloads on behalf of circuit member nodes:
+//!
+//! * `GET /admin/signers` - List the allowed signers, optionally filtered by `node_id`
+//! * `POST /admin/signers` - Add an allowed signer, replacing any existing entry for its key
+//! * `GET /admin/signers/{public_key}` - Fetch an allowed signer
+//! * `DELETE /admin/signers/{public_key}` - Remove an allowed signer
+
+use std::collections::HashMap;
+
+use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use futures::{future::IntoFuture, Future};
+
+use crate::admin::allowed_signers::{AllowedSigner, AllowedSignerStore, AllowedSignerStoreError};
+use crate::error::InvalidArgumentError;
+use crate::protocol;
+use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+
+use super::super::resources::allowed_signers::{AllowedSignerResponse, NewAllowedSigner};
+
+pub fn make_allowed_signers_resource(store: Box<dyn AllowedSignerStore>) -> Resource {
+    let list_store = store.clone();
+    Resource::build("/admin/signers")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_ALLOWED_SIGNERS_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |r, _| {
+            list_signers(r, web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, move |_, p| {
+            add_signer(p, web::Data::new(store.clone()))
+        })
+}
+
+pub fn make_allowed_signer_resource(store: Box<dyn AllowedSignerStore>) -> Resource {
+    let fetch_store = store.clone();
+    Resource::build("/admin/signers/{public_key}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_ALLOWED_SIGNERS_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |r, _| {
+            fetch_signer(r, web::Data::new(fetch_store.clone()))
+        })
+        .add_method(Method::Delete, move |r, _| {
+            remove_signer(r, web::Data::new(store.clone()))
+        })
+}
+
+fn list_signers(
+    req: HttpRequest,
+    store: web::Data<Box<dyn AllowedSignerStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let node_id = match web::Query::<HashMap<String, String>>::from_query(req.query_string()) {
+        Ok(query) => query.get("node_id").cloned(),
+        Err(_) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request("Invalid query"))
+                    .into_future(),
+            )
+        }
+    };
+
+    Box::new(
+        web::block(move || store.list_signers(node_id.as_deref())).then(|res| match res {
+            Ok(signers) => Ok(HttpResponse::Ok().json(json!({
+                "data": signers
+                    .iter()
+                    .map(AllowedSignerResponse::from)
+                    .collect::<Vec<_>>()
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn add_signer(
+    payload: web::Payload,
+    store: web::Data<Box<dyn AllowedSignerStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let signer: AllowedSigner = serde_json::from_slice::<NewAllowedSigner>(&bytes)
+                .map_err(|err| {
+                    AllowedSignerStoreError::InvalidArgument(InvalidArgumentError::new(
+                        "payload".to_string(),
+                        format!("failed to parse payload: {}", err),
+                    ))
+                })?
+                .into();
+            store.add_signer(signer.clone())?;
+            Ok(signer)
+        })
+        .then(|res| match res {
+            Ok(signer) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Allowed signer added successfully",
+                "data": AllowedSignerResponse::from(&signer),
+            }))),
+            Err(err) => Ok(error_response(err)),
+        })
+    }))
+}
+
+fn fetch_signer(
+    req: HttpRequest,
+    store: web::Data<Box<dyn AllowedSignerStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let public_key = req.match_info().get("public_key").unwrap_or("").to_string();
+
+    Box::new(
+        web::block(move || {
+            store
+                .get_signer(&public_key)?
+                .ok_or_else(|| AllowedSignerStoreError::NotFound(public_key.to_string()))
+        })
+        .then(|res| match res {
+            Ok(signer) => Ok(HttpResponse::Ok().json(AllowedSignerResponse::from(&signer))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn remove_signer(
+    req: HttpRequest,
+    store: web::Data<Box<dyn AllowedSignerStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let public_key = req.match_info().get("public_key").unwrap_or("").to_string();
+
+    Box::new(
+        web::block(move || store.remove_signer(&public_key)).then(|res| match res {
+            Ok(()) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Allowed signer removed successfully"
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn error_response(err: BlockingError<AllowedSignerStoreError>) -> HttpResponse {
+    match err {
+        BlockingError::Error(AllowedSignerStoreError::InvalidArgument(err)) => {
+            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()))
+        }
+        BlockingError::Error(err @ AllowedSignerStoreError::NotFound(_)) => {
+            HttpResponse::NotFound().json(ErrorResponse::not_found(&err.to_string()))
+        }
+        err => {
+            error!("{}", err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
@@ -21,6 +21,8 @@ use std::collections::HashMap;
 
 use crate::admin::store::{AdminServiceStore, CircuitPredicate};
 use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::{
     paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET},
     ErrorResponse, Method, ProtocolVersionRangeGuard, Resource,
@@ -115,6 +117,9 @@ fn list_circuits(
         None => format!("{}", protocol::ADMIN_PROTOCOL_VERSION),
     };
 
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    let scope = req.extensions().get::<CircuitScope>().cloned();
+
     Box::new(query_list_circuits(
         store,
         link,
@@ -122,6 +127,8 @@ fn list_circuits(
         Some(offset),
         Some(limit),
         protocol_version,
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        scope,
     ))
 }
 
@@ -132,6 +139,7 @@ fn query_list_circuits(
     offset: Option<usize>,
     limit: Option<usize>,
     protocol_version: String,
+    #[cfg(feature = "rest-api-circuit-scopes")] scope: Option<CircuitScope>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     web::block(move || {
         let filters = {
@@ -146,6 +154,19 @@ fn query_list_circuits(
             .list_circuits(&filters)
             .map_err(|err| CircuitListError::CircuitStoreError(err.to_string()))?;
 
+        // Only list the circuits in the client's scope, so that the paging reflects the circuits
+        // the client may see
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        let circuits = circuits
+            .filter(|circuit| {
+                scope
+                    .as_ref()
+                    .map(|scope| scope.is_permitted(circuit.circuit_id()))
+                    .unwrap_or(true)
+            })
+            .collect::<Vec<_>>()
+            .into_iter();
+
         let offset_value = offset.unwrap_or(0);
         let total = circuits.len();
         let limit_value = limit.unwrap_or_else(|| total as usize);
@@ -17,9 +17,13 @@
 
 use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
 use futures::Future;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use futures::IntoFuture;
 
 use crate::admin::store::AdminServiceStore;
 use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
 
 use super::super::error::CircuitFetchError;
@@ -46,6 +50,22 @@ fn fetch_circuit(
         .unwrap_or("")
         .to_string();
 
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    {
+        if let Some(scope) = request.extensions().get::<CircuitScope>() {
+            if !scope.is_permitted(&circuit_id) {
+                return Box::new(
+                    HttpResponse::Forbidden()
+                        .json(ErrorResponse::forbidden(&format!(
+                            "Not permitted to access circuit {}",
+                            circuit_id
+                        )))
+                        .into_future(),
+                );
+            }
+        }
+    }
+
     let protocol_version = match request.headers().get("SplinterProtocolVersion") {
         Some(header_value) => match header_value.to_str() {
             Ok(protocol_version) => Ok(protocol_version.to_string()),
@@ -0,0 +1,71 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the `GET /admin/debug` endpoint for reporting the admin service's internal state: the
+//! payloads waiting for members to be peered or to agree on a protocol version, the peering
+//! state of each circuit proposal, including peers whose acquisition is being retried, and the
+//! state of the consensus rounds.
+//!
+//! The state spans every circuit, so it may only be read by clients whose access to circuits is
+//! not restricted, such as the node operator; other clients receive a `403 Forbidden` response.
+
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use futures::future::IntoFuture;
+use futures::Future;
+
+use crate::admin::service::debug::AdminServiceDebug;
+use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
+use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+
+use super::super::resources::debug::DebugStateResponse;
+
+pub fn make_debug_resource(debug: AdminServiceDebug) -> Resource {
+    Resource::build("/admin/debug")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_DEBUG_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |r, _| {
+            get_debug_state(r, web::Data::new(debug.clone()))
+        })
+}
+
+fn get_debug_state(
+    #[allow(unused_variables)] req: HttpRequest,
+    debug: web::Data<AdminServiceDebug>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    {
+        if let Some(CircuitScope::Circuits(_)) = req.extensions().get::<CircuitScope>() {
+            return Box::new(
+                HttpResponse::Forbidden()
+                    .json(ErrorResponse::forbidden(
+                        "Not permitted to read the admin service state",
+                    ))
+                    .into_future(),
+            );
+        }
+    }
+
+    Box::new(web::block(move || debug.state()).then(|res| match res {
+        Ok(state) => Ok(HttpResponse::Ok().json(DebugStateResponse::from(&state))),
+        Err(err) => {
+            error!("{}", err);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+        }
+    }))
+}
@@ -12,9 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "admin-allowed-signers")]
+pub(super) mod allowed_signers;
 pub(super) mod circuits;
 pub(super) mod circuits_circuit_id;
+#[cfg(feature = "admin-service-debug")]
+pub(super) mod debug;
 pub(super) mod proposals;
 pub(super) mod proposals_circuit_id;
 pub(super) mod submit;
+#[cfg(feature = "admin-signed-submit")]
+pub(super) mod submit_signed;
+#[cfg(feature = "tenancy")]
+pub(super) mod tenants;
+#[cfg(feature = "admin-vote-cosigning")]
+pub(super) mod vote_cosigning;
+#[cfg(feature = "admin-vote-delegation")]
+pub(super) mod vote_delegation;
 pub(super) mod ws_register_type;
@@ -22,6 +22,8 @@ use futures::{future::IntoFuture, Future};
 use crate::admin::service::proposal_store::ProposalStore;
 use crate::admin::store::CircuitPredicate;
 use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET};
 use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
 
@@ -119,6 +121,9 @@ fn list_proposals<PS: ProposalStore + 'static>(
         None => format!("{}", protocol::ADMIN_PROTOCOL_VERSION),
     };
 
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    let scope = req.extensions().get::<CircuitScope>().cloned();
+
     Box::new(query_list_proposals(
         proposal_store,
         link,
@@ -127,9 +132,12 @@ fn list_proposals<PS: ProposalStore + 'static>(
         Some(offset),
         Some(limit),
         protocol_version,
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        scope,
     ))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn query_list_proposals<PS: ProposalStore + 'static>(
     proposal_store: web::Data<PS>,
     link: String,
@@ -138,6 +146,7 @@ fn query_list_proposals<PS: ProposalStore + 'static>(
     offset: Option<usize>,
     limit: Option<usize>,
     protocol_version: String,
+    #[cfg(feature = "rest-api-circuit-scopes")] scope: Option<CircuitScope>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     web::block(move || {
         let mut filters = vec![];
@@ -151,19 +160,49 @@ fn query_list_proposals<PS: ProposalStore + 'static>(
         let proposals = proposal_store
             .proposals(filters)
             .map_err(|err| ProposalListError::InternalError(err.to_string()))?;
-        let offset_value = offset.unwrap_or(0);
+
+        // Only list the proposals for circuits in the client's scope, so that the paging reflects
+        // the proposals the client may see
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        let proposals = proposals
+            .filter(|proposal| {
+                scope
+                    .as_ref()
+                    .map(|scope| scope.is_permitted(&proposal.circuit_id))
+                    .unwrap_or(true)
+            })
+            .collect::<Vec<_>>();
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        let total = proposals.len();
+        #[cfg(not(feature = "rest-api-circuit-scopes"))]
         let total = proposals.total() as usize;
+
+        let offset_value = offset.unwrap_or(0);
         let limit_value = limit.unwrap_or(total);
 
         let proposals = proposals
+            .into_iter()
             .skip(offset_value)
             .take(limit_value)
             .collect::<Vec<_>>();
+        let peering = proposals
+            .iter()
+            .map(|proposal| proposal_store.peering_status(&proposal.circuit_id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ProposalListError::InternalError(err.to_string()))?;
 
-        Ok((proposals, link, limit, offset, total, protocol_version))
+        Ok((
+            proposals,
+            peering,
+            link,
+            limit,
+            offset,
+            total,
+            protocol_version,
+        ))
     })
     .then(|res| match res {
-        Ok((proposals, link, limit, offset, total_count, protocol_version)) => {
+        Ok((proposals, peering, link, limit, offset, total_count, protocol_version)) => {
             match protocol_version.as_str() {
                 "1" => Ok(HttpResponse::Ok().json(
                     resources::v1::proposals::ListProposalsResponse {
@@ -178,7 +217,11 @@ fn query_list_proposals<PS: ProposalStore + 'static>(
                 _ => {
                     let proposal_responses = match proposals
                         .iter()
-                        .map(resources::v2::proposals::ProposalResponse::try_from)
+                        .zip(peering.iter())
+                        .map(|(proposal, peering)| {
+                            resources::v2::proposals::ProposalResponse::try_from(proposal)
+                                .map(|response| response.with_peering(peering))
+                        })
                         .collect::<Result<
                             Vec<resources::v2::proposals::ProposalResponse>, &'static str>>()
                     {
@@ -739,6 +782,8 @@ mod tests {
                 members: vec![SplinterNode {
                     node_id: "node_id".into(),
                     endpoints: vec!["".into()],
+                    relay: false,
+                    observer: false,
                 }],
                 authorization_type: AuthorizationType::Trust,
                 persistence: PersistenceType::Any,
@@ -748,10 +793,13 @@ mod tests {
                 application_metadata: vec![],
                 comments: Some("mock circuit 1".into()),
                 display_name: Some("circuit_1".into()),
+                qos: Default::default(),
             },
             votes: vec![],
             requester: vec![],
             requester_node_id: "node_id".into(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
@@ -772,10 +820,13 @@ mod tests {
                 application_metadata: vec![],
                 comments: Some("mock circuit 2".into()),
                 display_name: Some("circuit_2".into()),
+                qos: Default::default(),
             },
             votes: vec![],
             requester: vec![],
             requester_node_id: "node_id".into(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
@@ -790,6 +841,8 @@ mod tests {
                 members: vec![SplinterNode {
                     node_id: "node_id".into(),
                     endpoints: vec!["".into()],
+                    relay: false,
+                    observer: false,
                 }],
                 authorization_type: AuthorizationType::Trust,
                 persistence: PersistenceType::Any,
@@ -799,10 +852,13 @@ mod tests {
                 application_metadata: vec![],
                 comments: Some("mock circuit 3".into()),
                 display_name: None,
+                qos: Default::default(),
             },
             votes: vec![],
             requester: vec![],
             requester_node_id: "node_id".into(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
@@ -19,10 +19,14 @@ use std::convert::TryFrom;
 
 use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
 use futures::Future;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use futures::IntoFuture;
 
 use crate::admin::rest_api::error::ProposalFetchError;
 use crate::admin::service::proposal_store::ProposalStore;
 use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
 
 use super::super::resources;
@@ -48,6 +52,22 @@ fn fetch_proposal<PS: ProposalStore + 'static>(
         .unwrap_or("")
         .to_string();
 
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    {
+        if let Some(scope) = request.extensions().get::<CircuitScope>() {
+            if !scope.is_permitted(&circuit_id) {
+                return Box::new(
+                    HttpResponse::Forbidden()
+                        .json(ErrorResponse::forbidden(&format!(
+                            "Not permitted to access circuit {}",
+                            circuit_id
+                        )))
+                        .into_future(),
+                );
+            }
+        }
+    }
+
     let protocol_version = match request.headers().get("SplinterProtocolVersion") {
         Some(header_value) => match header_value.to_str() {
             Ok(protocol_version) => Ok(protocol_version.to_string()),
@@ -66,11 +86,14 @@ fn fetch_proposal<PS: ProposalStore + 'static>(
                 .ok_or_else(|| {
                     ProposalFetchError::NotFound(format!("Unable to find proposal: {}", circuit_id))
                 })?;
+            let peering = proposal_store
+                .peering_status(&circuit_id)
+                .map_err(|err| ProposalFetchError::InternalError(err.to_string()))?;
 
-            Ok((proposal, protocol_version?))
+            Ok((proposal, peering, protocol_version?))
         })
         .then(|res| match res {
-            Ok((proposal, protocol_version)) => match protocol_version.as_str() {
+            Ok((proposal, peering, protocol_version)) => match protocol_version.as_str() {
                 "1" => Ok(HttpResponse::Ok().json(
                     resources::v1::proposals_circuit_id::ProposalResponse::from(&proposal),
                 )),
@@ -78,7 +101,9 @@ fn fetch_proposal<PS: ProposalStore + 'static>(
                 _ => {
                     match resources::v2::proposals_circuit_id::ProposalResponse::try_from(&proposal)
                     {
-                        Ok(proposal_response) => Ok(HttpResponse::Ok().json(proposal_response)),
+                        Ok(proposal_response) => {
+                            Ok(HttpResponse::Ok().json(proposal_response.with_peering(&peering)))
+                        }
                         Err(err) => {
                             error!("{}", err);
                             Ok(HttpResponse::InternalServerError()
@@ -262,10 +287,13 @@ mod tests {
                 application_metadata: vec![],
                 comments: Some("mock circuit".into()),
                 display_name: Some("test_circuit".into()),
+                qos: Default::default(),
             },
             votes: vec![],
             requester: vec![],
             requester_node_id: "node_id".into(),
+            created_at: None,
+            updated_at: None,
         }
     }
 
@@ -0,0 +1,118 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoint for submitting externally-signed payloads:
+//!
+//! * `POST /admin/submit/signed` - Validate a hex-encoded, externally-signed circuit management
+//!   payload and submit it to the admin service, unless `validate_only` is set
+
+use actix_web::{error::BlockingError, web, Error, HttpResponse};
+use cylinder::VerifierFactory;
+use futures::Future;
+
+use crate::admin::service::{AdminCommands, AdminServiceError};
+use crate::admin::signed_payload::verify_signed_payload;
+use crate::hex::parse_hex;
+use crate::protocol;
+use crate::protos::admin::CircuitManagementPayload;
+use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+use crate::service::ServiceError;
+use crate::signing::MultiVerifierFactory;
+
+use super::super::resources::signed_payload::{SignedPayloadResponse, SignedPayloadSubmission};
+
+#[derive(Debug)]
+enum SubmitSignedError {
+    InvalidArgument(String),
+    Internal(String),
+}
+
+pub fn make_submit_signed_route(
+    admin_commands: Box<dyn AdminCommands>,
+    verifier_factory: MultiVerifierFactory,
+) -> Resource {
+    Resource::build("/admin/submit/signed")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_SIGNED_SUBMIT_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Post, move |_, p| {
+            submit_signed_payload(
+                p,
+                web::Data::new(admin_commands.clone()),
+                web::Data::new(verifier_factory.clone()),
+            )
+        })
+}
+
+fn submit_signed_payload(
+    payload: web::Payload,
+    admin_commands: web::Data<Box<dyn AdminCommands>>,
+    verifier_factory: web::Data<MultiVerifierFactory>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let submission = serde_json::from_slice::<SignedPayloadSubmission>(&bytes)
+                .map_err(|err| invalid(format!("failed to parse request: {}", err)))?;
+            let payload_bytes = parse_hex(&submission.payload)
+                .map_err(|_| invalid("payload must be a hex string".into()))?;
+            let payload = protobuf::parse_from_bytes::<CircuitManagementPayload>(&payload_bytes)
+                .map_err(|err| invalid(format!("failed to parse payload: {}", err)))?;
+
+            let info = verify_signed_payload(&payload, &*verifier_factory.new_verifier())
+                .map_err(|err| invalid(err.to_string()))?;
+
+            if !submission.validate_only {
+                match admin_commands.submit_circuit_change(payload) {
+                    Ok(()) => (),
+                    Err(AdminServiceError::ServiceError(ServiceError::UnableToHandleMessage(
+                        err,
+                    ))) => return Err(invalid(format!("Unable to handle message: {}", err))),
+                    Err(AdminServiceError::ServiceError(ServiceError::InvalidMessageFormat(
+                        err,
+                    ))) => return Err(invalid(format!("Failed to parse payload: {}", err))),
+                    Err(err) => return Err(SubmitSignedError::Internal(err.to_string())),
+                }
+            }
+
+            Ok((info, submission.validate_only))
+        })
+        .then(|res| match res {
+            Ok((info, true)) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Payload is valid",
+                "data": SignedPayloadResponse::from(&info),
+            }))),
+            Ok((info, false)) => Ok(HttpResponse::Accepted().json(json!({
+                "message": "Payload submitted successfully",
+                "data": SignedPayloadResponse::from(&info),
+            }))),
+            Err(BlockingError::Error(SubmitSignedError::InvalidArgument(message))) => {
+                debug!("Rejected signed payload: {}", message);
+                Ok(HttpResponse::BadRequest().json(ErrorResponse::bad_request(&message)))
+            }
+            Err(BlockingError::Error(SubmitSignedError::Internal(message))) => {
+                error!("Failed to submit signed payload: {}", message);
+                Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+            }
+            Err(err) => {
+                error!("{}", err);
+                Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+            }
+        })
+    }))
+}
+
+fn invalid(message: String) -> SubmitSignedError {
+    SubmitSignedError::InvalidArgument(message)
+}
@@ -0,0 +1,200 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for managing the assignment of circuits, nodes,
+//! Biome users and keys to the tenants of this node:
+//!
+//! * `GET /admin/tenants/assignments` - List the tenant assignments, optionally filtered by
+//!   `tenant_id`
+//! * `POST /admin/tenants/assignments` - Assign a resource to a tenant, replacing any existing
+//!   assignment of the resource
+//! * `DELETE /admin/tenants/assignments/{resource_type}/{resource_id}` - Remove the assignment
+//!   of a resource
+//!
+//! Tenant assignments may only be managed by the node operators configured for tenancy; other
+//! clients, including clients that are not assigned to a tenant, receive a `403 Forbidden`
+//! response.
+
+use std::collections::HashMap;
+
+use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use futures::{future::IntoFuture, Future};
+
+use crate::error::InvalidArgumentError;
+use crate::protocol;
+use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+use crate::tenant::{
+    TenantAssignment, TenantResourceType, TenantScope, TenantStore, TenantStoreError,
+};
+
+use super::super::resources::tenants::{NewTenantAssignment, TenantAssignmentResponse};
+
+pub fn make_tenant_assignments_resource(store: Box<dyn TenantStore>) -> Resource {
+    let list_store = store.clone();
+    Resource::build("/admin/tenants/assignments")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_TENANTS_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |r, _| {
+            list_assignments(r, web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, move |r, p| {
+            assign(r, p, web::Data::new(store.clone()))
+        })
+}
+
+pub fn make_tenant_assignment_resource(store: Box<dyn TenantStore>) -> Resource {
+    Resource::build("/admin/tenants/assignments/{resource_type}/{resource_id}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_TENANTS_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Delete, move |r, _| {
+            unassign(r, web::Data::new(store.clone()))
+        })
+}
+
+fn list_assignments(
+    req: HttpRequest,
+    store: web::Data<Box<dyn TenantStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    if let Some(response) = forbidden_unless_operator(&req) {
+        return Box::new(response.into_future());
+    }
+
+    let tenant_id = match web::Query::<HashMap<String, String>>::from_query(req.query_string()) {
+        Ok(query) => query.get("tenant_id").cloned(),
+        Err(_) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request("Invalid query"))
+                    .into_future(),
+            )
+        }
+    };
+
+    Box::new(
+        web::block(move || store.list_assignments(tenant_id.as_deref())).then(|res| match res {
+            Ok(assignments) => Ok(HttpResponse::Ok().json(json!({
+                "data": assignments
+                    .iter()
+                    .map(TenantAssignmentResponse::from)
+                    .collect::<Vec<_>>()
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn assign(
+    req: HttpRequest,
+    payload: web::Payload,
+    store: web::Data<Box<dyn TenantStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    if let Some(response) = forbidden_unless_operator(&req) {
+        return Box::new(response.into_future());
+    }
+
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let new_assignment =
+                serde_json::from_slice::<NewTenantAssignment>(&bytes).map_err(|err| {
+                    TenantStoreError::InvalidArgument(InvalidArgumentError::new(
+                        "payload".to_string(),
+                        format!("failed to parse payload: {}", err),
+                    ))
+                })?;
+            let resource_type = new_assignment
+                .resource_type
+                .parse::<TenantResourceType>()
+                .map_err(TenantStoreError::InvalidArgument)?;
+            let assignment = TenantAssignment::new(
+                &new_assignment.tenant_id,
+                resource_type,
+                &new_assignment.resource_id,
+            );
+            store.assign(assignment.clone())?;
+            Ok(assignment)
+        })
+        .then(|res| match res {
+            Ok(assignment) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Resource assigned to tenant successfully",
+                "data": TenantAssignmentResponse::from(&assignment),
+            }))),
+            Err(err) => Ok(error_response(err)),
+        })
+    }))
+}
+
+fn unassign(
+    req: HttpRequest,
+    store: web::Data<Box<dyn TenantStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    if let Some(response) = forbidden_unless_operator(&req) {
+        return Box::new(response.into_future());
+    }
+
+    let resource_type = req
+        .match_info()
+        .get("resource_type")
+        .unwrap_or("")
+        .to_string();
+    let resource_id = req
+        .match_info()
+        .get("resource_id")
+        .unwrap_or("")
+        .to_string();
+
+    Box::new(
+        web::block(move || {
+            let resource_type = resource_type
+                .parse::<TenantResourceType>()
+                .map_err(TenantStoreError::InvalidArgument)?;
+            store.unassign(resource_type, &resource_id)
+        })
+        .then(|res| match res {
+            Ok(()) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Tenant assignment removed successfully"
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+/// Returns a `403 Forbidden` response if the client may only access a limited set of circuits,
+/// such as the users and keys of a tenant
+fn forbidden_unless_operator(req: &HttpRequest) -> Option<HttpResponse> {
+    match req.extensions().get::<TenantScope>() {
+        Some(scope) if scope.is_operator() => None,
+        _ => Some(HttpResponse::Forbidden().json(ErrorResponse::forbidden(
+            "Not permitted to manage tenant assignments",
+        ))),
+    }
+}
+
+fn error_response(err: BlockingError<TenantStoreError>) -> HttpResponse {
+    match err {
+        BlockingError::Error(TenantStoreError::InvalidArgument(err)) => {
+            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()))
+        }
+        BlockingError::Error(err @ TenantStoreError::NotFound(_)) => {
+            HttpResponse::NotFound().json(ErrorResponse::not_found(&err.to_string()))
+        }
+        err => {
+            error!("{}", err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for requiring co-signatures on this node's votes:
+//!
+//! * `GET /admin/vote-thresholds` - List the vote thresholds
+//! * `POST /admin/vote-thresholds` - Set the vote threshold of a circuit management type
+//! * `DELETE /admin/vote-thresholds/{management_type}` - Remove a vote threshold
+//! * `GET /admin/votes` - List the votes waiting for co-signatures
+//! * `POST /admin/votes` - Submit a vote, holding it for co-signatures if required
+//! * `GET /admin/votes/{circuit_id}` - Fetch a pending vote, including the header to co-sign
+//! * `DELETE /admin/votes/{circuit_id}` - Discard a pending vote
+//! * `POST /admin/votes/{circuit_id}/signatures` - Co-sign a pending vote, submitting it once
+//!   enough signatures have been collected
+
+use std::convert::TryFrom;
+
+use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use futures::{future::IntoFuture, Future};
+use protobuf::Message;
+
+use crate::admin::cosign::{
+    PendingVote, VoteCosignStore, VoteCosignStoreError, VoteSignature, VoteThreshold,
+};
+use crate::admin::service::error::AdminSharedError;
+use crate::admin::service::{AdminCommands, AdminServiceError};
+use crate::error::{InternalError, InvalidArgumentError};
+use crate::protocol;
+use crate::protos::admin::CircuitManagementPayload;
+use crate::rest_api::{
+    into_bytes, into_protobuf, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource,
+};
+use crate::service::ServiceError;
+
+use super::super::resources::vote_cosigning::{
+    NewVoteSignature, NewVoteThreshold, PendingVoteResponse, VoteThresholdResponse,
+};
+
+pub fn make_vote_thresholds_resource(store: Box<dyn VoteCosignStore>) -> Resource {
+    let list_store = store.clone();
+    Resource::build("/admin/vote-thresholds")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_COSIGNING_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            list_thresholds(web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, move |_, p| {
+            set_threshold(p, web::Data::new(store.clone()))
+        })
+}
+
+pub fn make_vote_threshold_resource(store: Box<dyn VoteCosignStore>) -> Resource {
+    Resource::build("/admin/vote-thresholds/{management_type}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_COSIGNING_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Delete, move |r, _| {
+            remove_threshold(r, web::Data::new(store.clone()))
+        })
+}
+
+pub fn make_pending_votes_resource(
+    store: Box<dyn VoteCosignStore>,
+    admin_commands: Box<dyn AdminCommands>,
+) -> Resource {
+    let list_store = store.clone();
+    Resource::build("/admin/votes")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_COSIGNING_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            list_pending_votes(web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, move |_, p| {
+            submit_vote(
+                p,
+                web::Data::new(store.clone()),
+                web::Data::new(admin_commands.clone()),
+            )
+        })
+}
+
+pub fn make_pending_vote_resource(store: Box<dyn VoteCosignStore>) -> Resource {
+    let fetch_store = store.clone();
+    Resource::build("/admin/votes/{circuit_id}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_COSIGNING_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |r, _| {
+            fetch_pending_vote(r, web::Data::new(fetch_store.clone()))
+        })
+        .add_method(Method::Delete, move |r, _| {
+            remove_pending_vote(r, web::Data::new(store.clone()))
+        })
+}
+
+pub fn make_vote_signatures_resource(
+    store: Box<dyn VoteCosignStore>,
+    admin_commands: Box<dyn AdminCommands>,
+) -> Resource {
+    Resource::build("/admin/votes/{circuit_id}/signatures")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_COSIGNING_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Post, move |r, p| {
+            add_signature(
+                r,
+                p,
+                web::Data::new(store.clone()),
+                web::Data::new(admin_commands.clone()),
+            )
+        })
+}
+
+fn list_thresholds(
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || store.list_thresholds()).then(|res| match res {
+            Ok(thresholds) => Ok(HttpResponse::Ok().json(json!({
+                "data": thresholds
+                    .iter()
+                    .map(VoteThresholdResponse::from)
+                    .collect::<Vec<_>>()
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn set_threshold(
+    payload: web::Payload,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let threshold: VoteThreshold = serde_json::from_slice::<NewVoteThreshold>(&bytes)
+                .map_err(|err| invalid_payload(format!("failed to parse payload: {}", err)))?
+                .into();
+            store.set_threshold(threshold.clone())?;
+            Ok(threshold)
+        })
+        .then(|res| match res {
+            Ok(threshold) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Vote threshold set successfully",
+                "data": VoteThresholdResponse::from(&threshold),
+            }))),
+            Err(err) => Ok(error_response(err)),
+        })
+    }))
+}
+
+fn remove_threshold(
+    req: HttpRequest,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let management_type = req
+        .match_info()
+        .get("management_type")
+        .unwrap_or("")
+        .to_string();
+
+    Box::new(
+        web::block(move || store.remove_threshold(&management_type)).then(|res| match res {
+            Ok(()) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Vote threshold removed successfully"
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn list_pending_votes(
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            store
+                .list_pending_votes()?
+                .iter()
+                .map(to_response)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .then(|res| match res {
+            Ok(votes) => Ok(HttpResponse::Ok().json(json!({ "data": votes }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn submit_vote(
+    payload: web::Payload,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+    admin_commands: web::Data<Box<dyn AdminCommands>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        into_protobuf::<CircuitManagementPayload>(payload).and_then(move |payload| {
+            web::block(move || {
+                if !payload.has_circuit_proposal_vote() {
+                    return Err(invalid_payload(
+                        "payload must contain a circuit proposal vote".into(),
+                    ));
+                }
+
+                let circuit_id = payload
+                    .get_circuit_proposal_vote()
+                    .get_circuit_id()
+                    .to_string();
+                let payload_bytes = payload.write_to_bytes().map_err(|err| {
+                    VoteCosignStoreError::Internal(InternalError::from_source(Box::new(err)))
+                })?;
+
+                if try_submit(&**admin_commands, payload)? {
+                    return Ok(None);
+                }
+
+                let pending_vote = PendingVote::new(&circuit_id, payload_bytes, vec![]);
+                store.add_pending_vote(pending_vote.clone())?;
+                to_response(&pending_vote).map(Some)
+            })
+            .then(|res| match res {
+                Ok(pending_vote) => Ok(submission_response(pending_vote)),
+                Err(err) => Ok(error_response(err)),
+            })
+        }),
+    )
+}
+
+fn fetch_pending_vote(
+    req: HttpRequest,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let circuit_id = req.match_info().get("circuit_id").unwrap_or("").to_string();
+
+    Box::new(
+        web::block(move || {
+            store
+                .get_pending_vote(&circuit_id)?
+                .ok_or_else(|| not_found(&circuit_id))
+                .and_then(|pending_vote| to_response(&pending_vote))
+        })
+        .then(|res| match res {
+            Ok(pending_vote) => Ok(HttpResponse::Ok().json(pending_vote)),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn remove_pending_vote(
+    req: HttpRequest,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let circuit_id = req.match_info().get("circuit_id").unwrap_or("").to_string();
+
+    Box::new(
+        web::block(move || store.remove_pending_vote(&circuit_id)).then(|res| match res {
+            Ok(()) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Pending vote removed successfully"
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn add_signature(
+    req: HttpRequest,
+    payload: web::Payload,
+    store: web::Data<Box<dyn VoteCosignStore>>,
+    admin_commands: web::Data<Box<dyn AdminCommands>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let circuit_id = req.match_info().get("circuit_id").unwrap_or("").to_string();
+
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let signature: VoteSignature = serde_json::from_slice::<NewVoteSignature>(&bytes)
+                .map_err(|err| invalid_payload(format!("failed to parse payload: {}", err)))?
+                .into();
+            store.add_signature(&circuit_id, signature)?;
+
+            let pending_vote = store
+                .get_pending_vote(&circuit_id)?
+                .ok_or_else(|| not_found(&circuit_id))?;
+            let vote_payload =
+                protobuf::parse_from_bytes::<CircuitManagementPayload>(pending_vote.payload())
+                    .map_err(|err| {
+                        VoteCosignStoreError::Internal(InternalError::from_source(Box::new(err)))
+                    })?;
+
+            // The admin service removes the pending vote once it accepts the vote
+            if try_submit(&**admin_commands, vote_payload)? {
+                Ok(None)
+            } else {
+                to_response(&pending_vote).map(Some)
+            }
+        })
+        .then(|res| match res {
+            Ok(pending_vote) => Ok(submission_response(pending_vote)),
+            Err(err) => Ok(error_response(err)),
+        })
+    }))
+}
+
+/// Submits the vote to the admin service. Returns `false` if the vote does not yet carry enough
+/// signatures to be accepted.
+fn try_submit(
+    admin_commands: &dyn AdminCommands,
+    payload: CircuitManagementPayload,
+) -> Result<bool, VoteCosignStoreError> {
+    if payload
+        .get_circuit_proposal_vote()
+        .get_circuit_id()
+        .is_empty()
+    {
+        return Err(invalid_payload("vote must have a circuit ID".into()));
+    }
+
+    match admin_commands.submit_circuit_change(payload) {
+        Ok(()) => Ok(true),
+        Err(AdminServiceError::ServiceError(ServiceError::UnableToHandleMessage(err))) => {
+            match err.downcast_ref::<AdminSharedError>() {
+                Some(AdminSharedError::InsufficientVoteSignatures { .. }) => Ok(false),
+                _ => Err(invalid_payload(format!("Unable to handle vote: {}", err))),
+            }
+        }
+        Err(AdminServiceError::ServiceError(ServiceError::InvalidMessageFormat(err))) => {
+            Err(invalid_payload(format!("Failed to parse vote: {}", err)))
+        }
+        Err(err) => Err(VoteCosignStoreError::Internal(InternalError::with_message(
+            err.to_string(),
+        ))),
+    }
+}
+
+fn to_response(pending_vote: &PendingVote) -> Result<PendingVoteResponse, VoteCosignStoreError> {
+    PendingVoteResponse::try_from(pending_vote)
+        .map_err(|err| VoteCosignStoreError::Internal(InternalError::from_source(Box::new(err))))
+}
+
+fn submission_response(pending_vote: Option<PendingVoteResponse>) -> HttpResponse {
+    match pending_vote {
+        Some(pending_vote) => HttpResponse::Ok().json(json!({
+            "message": "Vote is waiting for co-signatures",
+            "status": "pending",
+            "data": pending_vote,
+        })),
+        None => HttpResponse::Accepted().json(json!({
+            "message": "Vote submitted successfully",
+            "status": "submitted",
+        })),
+    }
+}
+
+fn invalid_payload(message: String) -> VoteCosignStoreError {
+    VoteCosignStoreError::InvalidArgument(InvalidArgumentError::new("payload".into(), message))
+}
+
+fn not_found(circuit_id: &str) -> VoteCosignStoreError {
+    VoteCosignStoreError::NotFound(format!("pending vote for circuit {}", circuit_id))
+}
+
+fn error_response(err: BlockingError<VoteCosignStoreError>) -> HttpResponse {
+    match err {
+        BlockingError::Error(VoteCosignStoreError::InvalidArgument(err)) => {
+            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()))
+        }
+        BlockingError::Error(err @ VoteCosignStoreError::NotFound(_)) => {
+            HttpResponse::NotFound().json(ErrorResponse::not_found(&err.to_string()))
+        }
+        err => {
+            error!("{}", err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
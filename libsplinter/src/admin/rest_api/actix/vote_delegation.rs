@@ -0,0 +1,151 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for managing the delegations of voting authority
+//! honored by this node:
+//!
+//! * `GET /admin/vote-delegations` - List the vote delegations
+//! * `POST /admin/vote-delegations` - Add a signed vote delegation, replacing any existing
+//!   delegation of the same node to the same delegate
+//! * `DELETE /admin/vote-delegations/{node_id}/{delegate_public_key}` - Revoke a vote delegation
+
+use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use cylinder::VerifierFactory;
+use futures::Future;
+
+use crate::admin::delegation::{VoteDelegation, VoteDelegationStore, VoteDelegationStoreError};
+use crate::error::InvalidArgumentError;
+use crate::protocol;
+use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+use crate::signing::MultiVerifierFactory;
+
+use super::super::resources::vote_delegation::{NewVoteDelegation, VoteDelegationResponse};
+
+pub fn make_vote_delegations_resource(
+    store: Box<dyn VoteDelegationStore>,
+    verifier_factory: MultiVerifierFactory,
+) -> Resource {
+    let list_store = store.clone();
+    Resource::build("/admin/vote-delegations")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_DELEGATION_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            list_delegations(web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, move |_, p| {
+            add_delegation(
+                p,
+                web::Data::new(store.clone()),
+                web::Data::new(verifier_factory.clone()),
+            )
+        })
+}
+
+pub fn make_vote_delegation_resource(store: Box<dyn VoteDelegationStore>) -> Resource {
+    Resource::build("/admin/vote-delegations/{node_id}/{delegate_public_key}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::ADMIN_VOTE_DELEGATION_MIN,
+            protocol::ADMIN_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Delete, move |r, _| {
+            remove_delegation(r, web::Data::new(store.clone()))
+        })
+}
+
+fn list_delegations(
+    store: web::Data<Box<dyn VoteDelegationStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || store.list_delegations()).then(|res| match res {
+            Ok(delegations) => Ok(HttpResponse::Ok().json(json!({
+                "data": delegations
+                    .iter()
+                    .map(VoteDelegationResponse::from)
+                    .collect::<Vec<_>>()
+            }))),
+            Err(err) => Ok(error_response(err)),
+        }),
+    )
+}
+
+fn add_delegation(
+    payload: web::Payload,
+    store: web::Data<Box<dyn VoteDelegationStore>>,
+    verifier_factory: web::Data<MultiVerifierFactory>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(into_bytes(payload).and_then(move |bytes| {
+        web::block(move || {
+            let delegation: VoteDelegation = serde_json::from_slice::<NewVoteDelegation>(&bytes)
+                .map_err(|err| {
+                    VoteDelegationStoreError::InvalidArgument(InvalidArgumentError::new(
+                        "payload".into(),
+                        format!("failed to parse payload: {}", err),
+                    ))
+                })?
+                .into();
+            delegation
+                .verify(&*verifier_factory.new_verifier())
+                .map_err(VoteDelegationStoreError::InvalidArgument)?;
+            store.add_delegation(delegation.clone())?;
+            Ok(delegation)
+        })
+        .then(|res| match res {
+            Ok(delegation) => Ok(HttpResponse::Ok().json(json!({
+                "message": "Vote delegation added successfully",
+                "data": VoteDelegationResponse::from(&delegation),
+            }))),
+            Err(err) => Ok(error_response(err)),
+        })
+    }))
+}
+
+fn remove_delegation(
+    req: HttpRequest,
+    store: web::Data<Box<dyn VoteDelegationStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let node_id = req.match_info().get("node_id").unwrap_or("").to_string();
+    let delegate_public_key = req
+        .match_info()
+        .get("delegate_public_key")
+        .unwrap_or("")
+        .to_string();
+
+    Box::new(
+        web::block(move || store.remove_delegation(&node_id, &delegate_public_key)).then(|res| {
+            match res {
+                Ok(()) => Ok(HttpResponse::Ok().json(json!({
+                    "message": "Vote delegation revoked successfully"
+                }))),
+                Err(err) => Ok(error_response(err)),
+            }
+        }),
+    )
+}
+
+fn error_response(err: BlockingError<VoteDelegationStoreError>) -> HttpResponse {
+    match err {
+        BlockingError::Error(VoteDelegationStoreError::InvalidArgument(err)) => {
+            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()))
+        }
+        BlockingError::Error(err @ VoteDelegationStoreError::NotFound(_)) => {
+            HttpResponse::NotFound().json(ErrorResponse::not_found(&err.to_string()))
+        }
+        err => {
+            error!("{}", err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
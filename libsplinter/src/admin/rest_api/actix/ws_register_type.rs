@@ -24,6 +24,8 @@ use crate::admin::service::{
     AdminCommands, AdminServiceEventSubscriber, AdminServiceStatus, AdminSubscriberError,
 };
 use crate::protocol;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::{
     new_websocket_event_sender, EventSender, Method, ProtocolVersionRangeGuard, Request, Resource,
 };
@@ -123,12 +125,25 @@ pub fn make_application_handler_registration_route<A: AdminCommands + Clone + 's
                 }
             };
 
+            // Only send the events of circuits in the client's scope
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            let scope = request.extensions().get::<CircuitScope>().cloned();
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            let initial_events = {
+                let scope = scope.clone();
+                initial_events.filter(move |json_event| is_in_scope(&scope, &json_event.event))
+            };
+
             let request = Request::from((request, payload));
             match new_websocket_event_sender(request, Box::new(initial_events)) {
                 Ok((sender, res)) => {
                     if let Err(err) = admin_commands.add_event_subscriber(
                         &circuit_management_type,
-                        Box::new(WsAdminServiceEventSubscriber { sender }),
+                        Box::new(WsAdminServiceEventSubscriber {
+                            sender,
+                            #[cfg(feature = "rest-api-circuit-scopes")]
+                            scope,
+                        }),
                     ) {
                         error!("Unable to add admin event subscriber: {}", err);
                         return Box::new(
@@ -148,6 +163,18 @@ pub fn make_application_handler_registration_route<A: AdminCommands + Clone + 's
 
 struct WsAdminServiceEventSubscriber {
     sender: EventSender<JsonAdminEvent>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    scope: Option<CircuitScope>,
+}
+
+/// Returns whether or not the event's circuit is in the given scope; every circuit is in scope if
+/// the client's scope is not restricted.
+#[cfg(feature = "rest-api-circuit-scopes")]
+fn is_in_scope(scope: &Option<CircuitScope>, event: &AdminServiceEvent) -> bool {
+    scope
+        .as_ref()
+        .map(|scope| scope.is_permitted(&event.proposal().circuit_id))
+        .unwrap_or(true)
 }
 
 impl AdminServiceEventSubscriber for WsAdminServiceEventSubscriber {
@@ -157,6 +184,13 @@ impl AdminServiceEventSubscriber for WsAdminServiceEventSubscriber {
         event: &AdminServiceEvent,
         timestamp: &time::SystemTime,
     ) -> Result<(), AdminSubscriberError> {
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        {
+            if !is_in_scope(&self.scope, event) {
+                return Ok(());
+            }
+        }
+
         let json_event = JsonAdminEvent {
             timestamp: *timestamp,
             event: event.clone(),
@@ -174,6 +208,13 @@ impl AdminServiceEventSubscriber for WsAdminServiceEventSubscriber {
         event: &AdminServiceEvent,
         event_id: &i64,
     ) -> Result<(), AdminSubscriberError> {
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        {
+            if !is_in_scope(&self.scope, event) {
+                return Ok(());
+            }
+        }
+
         let json_event = JsonAdminEvent {
             timestamp: time::SystemTime::now(),
             event: event.clone(),
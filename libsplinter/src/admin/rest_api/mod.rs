@@ -20,9 +20,21 @@ mod actix;
 mod error;
 mod resources;
 
+#[cfg(feature = "admin-allowed-signers")]
+use crate::admin::allowed_signers::AllowedSignerStore;
+#[cfg(feature = "admin-vote-cosigning")]
+use crate::admin::cosign::VoteCosignStore;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::VoteDelegationStore;
+#[cfg(any(feature = "admin-signed-submit", feature = "admin-vote-cosigning"))]
+use crate::admin::service::AdminCommands;
 use crate::admin::service::AdminService;
 use crate::admin::store::AdminServiceStore;
 use crate::rest_api::{Resource, RestResourceProvider};
+#[cfg(any(feature = "admin-signed-submit", feature = "admin-vote-delegation"))]
+use crate::signing::MultiVerifierFactory;
+#[cfg(feature = "tenancy")]
+use crate::tenant::TenantStore;
 
 /// The admin service provides the following endpoints as REST API resources:
 ///
@@ -32,6 +44,9 @@ use crate::rest_api::{Resource, RestResourceProvider};
 /// * `GET /admin/proposals` - List circuit proposals in Splinter's state
 /// * `GET /admin/proposals/{circuit_id}` - Fetch a specific circuit proposal in Splinter's state
 ///   by circuit ID
+/// * `GET /admin/debug` - Report the payloads held by the admin service, the peering state of
+///   each circuit proposal and the state of the consensus rounds (requires the
+///   `admin-service-debug` feature)
 ///
 /// These endpoints are only available if the following REST API backend feature is enabled:
 ///
@@ -53,6 +68,9 @@ impl RestResourceProvider for AdminService {
                 actix::proposals_circuit_id::make_fetch_proposal_resource(self.proposals()),
                 actix::proposals::make_list_proposals_resource(self.proposals()),
             ]);
+
+            #[cfg(feature = "admin-service-debug")]
+            resources.push(actix::debug::make_debug_resource(self.debug()));
         }
 
         resources
@@ -110,3 +128,286 @@ impl RestResourceProvider for CircuitResourceProvider {
         resources
     }
 }
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definitions for managing the
+/// signers that are allowed to submit circuit management payloads on behalf of member nodes.
+///
+/// The following endpoints are provided:
+///
+/// * `GET /admin/signers` - List the allowed signers, optionally filtered by `node_id`
+/// * `POST /admin/signers` - Add an allowed signer
+/// * `GET /admin/signers/{public_key}` - Fetch an allowed signer
+/// * `DELETE /admin/signers/{public_key}` - Remove an allowed signer
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "admin-allowed-signers")]
+#[derive(Clone)]
+pub struct AllowedSignerResourceProvider {
+    store: Box<dyn AllowedSignerStore>,
+}
+
+#[cfg(feature = "admin-allowed-signers")]
+impl AllowedSignerResourceProvider {
+    pub fn new(store: Box<dyn AllowedSignerStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[cfg(feature = "admin-allowed-signers")]
+impl RestResourceProvider for AllowedSignerResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix::allowed_signers::make_allowed_signer_resource(self.store.clone()),
+                actix::allowed_signers::make_allowed_signers_resource(self.store.clone()),
+            ]);
+        }
+
+        resources
+    }
+}
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definitions for requiring
+/// co-signatures on the votes this node submits for circuit proposals.
+///
+/// The following endpoints are provided:
+///
+/// * `GET /admin/vote-thresholds` - List the vote thresholds
+/// * `POST /admin/vote-thresholds` - Set the number of signatures required to vote on proposals
+///   of a circuit management type
+/// * `DELETE /admin/vote-thresholds/{management_type}` - Remove a vote threshold
+/// * `GET /admin/votes` - List the votes waiting for co-signatures
+/// * `POST /admin/votes` - Submit a vote, holding it for co-signatures if required
+/// * `GET /admin/votes/{circuit_id}` - Fetch a pending vote
+/// * `DELETE /admin/votes/{circuit_id}` - Discard a pending vote
+/// * `POST /admin/votes/{circuit_id}/signatures` - Co-sign a pending vote
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "admin-vote-cosigning")]
+#[derive(Clone)]
+pub struct VoteCosignResourceProvider {
+    store: Box<dyn VoteCosignStore>,
+    admin_commands: Box<dyn AdminCommands>,
+}
+
+#[cfg(feature = "admin-vote-cosigning")]
+impl VoteCosignResourceProvider {
+    pub fn new(store: Box<dyn VoteCosignStore>, admin_commands: Box<dyn AdminCommands>) -> Self {
+        Self {
+            store,
+            admin_commands,
+        }
+    }
+}
+
+#[cfg(feature = "admin-vote-cosigning")]
+impl RestResourceProvider for VoteCosignResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix::vote_cosigning::make_vote_thresholds_resource(self.store.clone()),
+                actix::vote_cosigning::make_vote_threshold_resource(self.store.clone()),
+                actix::vote_cosigning::make_pending_votes_resource(
+                    self.store.clone(),
+                    self.admin_commands.clone(),
+                ),
+                actix::vote_cosigning::make_pending_vote_resource(self.store.clone()),
+                actix::vote_cosigning::make_vote_signatures_resource(
+                    self.store.clone(),
+                    self.admin_commands.clone(),
+                ),
+            ]);
+        }
+
+        resources
+    }
+}
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definitions for managing the
+/// delegations of voting authority that this node honors when verifying votes on circuit
+/// proposals.
+///
+/// The following endpoints are provided:
+///
+/// * `GET /admin/vote-delegations` - List the vote delegations
+/// * `POST /admin/vote-delegations` - Add a signed vote delegation, which is rejected if its
+///   signature does not verify
+/// * `DELETE /admin/vote-delegations/{node_id}/{delegate_public_key}` - Revoke a vote delegation
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "admin-vote-delegation")]
+#[derive(Clone)]
+pub struct VoteDelegationResourceProvider {
+    store: Box<dyn VoteDelegationStore>,
+    verifier_factory: MultiVerifierFactory,
+}
+
+#[cfg(feature = "admin-vote-delegation")]
+impl VoteDelegationResourceProvider {
+    /// Creates a new provider
+    ///
+    /// # Arguments
+    ///
+    /// * `store`: The store of the delegations honored by this node
+    /// * `verifier_factory`: Creates the verifiers for delegation signatures, accepting the
+    ///   node's signing algorithms
+    pub fn new(
+        store: Box<dyn VoteDelegationStore>,
+        verifier_factory: MultiVerifierFactory,
+    ) -> Self {
+        Self {
+            store,
+            verifier_factory,
+        }
+    }
+}
+
+#[cfg(feature = "admin-vote-delegation")]
+impl RestResourceProvider for VoteDelegationResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix::vote_delegation::make_vote_delegations_resource(
+                    self.store.clone(),
+                    self.verifier_factory.clone(),
+                ),
+                actix::vote_delegation::make_vote_delegation_resource(self.store.clone()),
+            ]);
+        }
+
+        resources
+    }
+}
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definition for submitting
+/// circuit management payloads that were constructed and signed outside of the node, such as by
+/// a custodial signing service or a browser wallet.
+///
+/// The following endpoint is provided:
+///
+/// * `POST /admin/submit/signed` - Validate a hex-encoded, signed circuit management payload and
+///   submit it to the admin service
+///
+/// The payload's header, action digest and signature are checked before the payload is
+/// submitted; setting `validate_only` in the request only performs these checks.
+///
+/// This endpoint is only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "admin-signed-submit")]
+#[derive(Clone)]
+pub struct SignedPayloadResourceProvider {
+    admin_commands: Box<dyn AdminCommands>,
+    verifier_factory: MultiVerifierFactory,
+}
+
+#[cfg(feature = "admin-signed-submit")]
+impl SignedPayloadResourceProvider {
+    /// Creates a new provider
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_commands`: The admin service to submit payloads to
+    /// * `verifier_factory`: Creates the verifiers for payload signatures, accepting the node's
+    ///   signing algorithms
+    pub fn new(
+        admin_commands: Box<dyn AdminCommands>,
+        verifier_factory: MultiVerifierFactory,
+    ) -> Self {
+        Self {
+            admin_commands,
+            verifier_factory,
+        }
+    }
+}
+
+#[cfg(feature = "admin-signed-submit")]
+impl RestResourceProvider for SignedPayloadResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.push(actix::submit_signed::make_submit_signed_route(
+                self.admin_commands.clone(),
+                self.verifier_factory.clone(),
+            ));
+        }
+
+        resources
+    }
+}
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definitions for managing the
+/// assignment of circuits, nodes, Biome users and keys to the tenants of this node.
+///
+/// The following endpoints are provided:
+///
+/// * `GET /admin/tenants/assignments` - List the tenant assignments, optionally filtered by
+///   `tenant_id`
+/// * `POST /admin/tenants/assignments` - Assign a resource to a tenant
+/// * `DELETE /admin/tenants/assignments/{resource_type}/{resource_id}` - Remove the assignment of
+///   a resource
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "tenancy")]
+#[derive(Clone)]
+pub struct TenantResourceProvider {
+    store: Box<dyn TenantStore>,
+}
+
+#[cfg(feature = "tenancy")]
+impl TenantResourceProvider {
+    pub fn new(store: Box<dyn TenantStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[cfg(feature = "tenancy")]
+impl RestResourceProvider for TenantResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix::tenants::make_tenant_assignments_resource(self.store.clone()),
+                actix::tenants::make_tenant_assignment_resource(self.store.clone()),
+            ]);
+        }
+
+        resources
+    }
+}
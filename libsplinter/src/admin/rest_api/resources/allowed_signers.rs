@@ -0,0 +1,45 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::admin::allowed_signers::AllowedSigner;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct AllowedSignerResponse<'a> {
+    pub public_key: &'a str,
+    pub node_id: &'a str,
+    pub roles: &'a [String],
+}
+
+impl<'a> From<&'a AllowedSigner> for AllowedSignerResponse<'a> {
+    fn from(signer: &'a AllowedSigner) -> Self {
+        Self {
+            public_key: signer.public_key(),
+            node_id: signer.node_id(),
+            roles: signer.roles(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewAllowedSigner {
+    pub public_key: String,
+    pub node_id: String,
+    pub roles: Vec<String>,
+}
+
+impl From<NewAllowedSigner> for AllowedSigner {
+    fn from(signer: NewAllowedSigner) -> Self {
+        AllowedSigner::new(&signer.public_key, &signer.node_id, signer.roles)
+    }
+}
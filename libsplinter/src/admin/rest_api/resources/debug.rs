@@ -0,0 +1,153 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::admin::service::debug::{
+    AdminServiceDebugState, ConsensusProposalState, PayloadSummary, PendingPayloadState,
+    ProposalPeeringState,
+};
+
+use super::v2::proposals_circuit_id::PeeringResponse;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct DebugStateResponse<'a> {
+    pub unpeered_payloads: Vec<PendingPayloadResponse<'a>>,
+    pub pending_protocol_payloads: Vec<PendingPayloadResponse<'a>>,
+    pub proposal_peering: Vec<ProposalPeeringResponse<'a>>,
+    pub consensus: ConsensusResponse<'a>,
+}
+
+impl<'a> From<&'a AdminServiceDebugState> for DebugStateResponse<'a> {
+    fn from(state: &'a AdminServiceDebugState) -> Self {
+        Self {
+            unpeered_payloads: state
+                .unpeered_payloads
+                .iter()
+                .map(PendingPayloadResponse::from)
+                .collect(),
+            pending_protocol_payloads: state
+                .pending_protocol_payloads
+                .iter()
+                .map(PendingPayloadResponse::from)
+                .collect(),
+            proposal_peering: state
+                .proposal_peering
+                .iter()
+                .map(ProposalPeeringResponse::from)
+                .collect(),
+            consensus: ConsensusResponse {
+                queued_payloads: state
+                    .consensus
+                    .queued_payloads
+                    .iter()
+                    .map(PayloadResponse::from)
+                    .collect(),
+                pending_proposals: state
+                    .consensus
+                    .pending_proposals
+                    .iter()
+                    .map(ConsensusProposalResponse::from)
+                    .collect(),
+                current_change: state
+                    .consensus
+                    .current_change
+                    .as_ref()
+                    .map(PayloadResponse::from),
+                current_verifiers: &state.consensus.current_verifiers,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct PayloadResponse<'a> {
+    pub circuit_id: &'a str,
+    pub action: &'a str,
+}
+
+impl<'a> From<&'a PayloadSummary> for PayloadResponse<'a> {
+    fn from(payload: &'a PayloadSummary) -> Self {
+        Self {
+            circuit_id: &payload.circuit_id,
+            action: &payload.action,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct PendingPayloadResponse<'a> {
+    pub circuit_id: &'a str,
+    pub action: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposal_id: Option<&'a str>,
+    pub message_sender: &'a str,
+    pub members: &'a [String],
+    pub unpeered_ids: &'a [String],
+    pub missing_protocol_ids: &'a [String],
+}
+
+impl<'a> From<&'a PendingPayloadState> for PendingPayloadResponse<'a> {
+    fn from(pending: &'a PendingPayloadState) -> Self {
+        Self {
+            circuit_id: &pending.payload.circuit_id,
+            action: &pending.payload.action,
+            proposal_id: pending.proposal_id.as_deref(),
+            message_sender: &pending.message_sender,
+            members: &pending.members,
+            unpeered_ids: &pending.unpeered_ids,
+            missing_protocol_ids: &pending.missing_protocol_ids,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct ProposalPeeringResponse<'a> {
+    pub circuit_id: &'a str,
+    pub members: Vec<PeeringResponse<'a>>,
+}
+
+impl<'a> From<&'a ProposalPeeringState> for ProposalPeeringResponse<'a> {
+    fn from(peering: &'a ProposalPeeringState) -> Self {
+        Self {
+            circuit_id: &peering.circuit_id,
+            members: peering.members.iter().map(PeeringResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct ConsensusResponse<'a> {
+    pub queued_payloads: Vec<PayloadResponse<'a>>,
+    pub pending_proposals: Vec<ConsensusProposalResponse<'a>>,
+    pub current_change: Option<PayloadResponse<'a>>,
+    pub current_verifiers: &'a [String],
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct ConsensusProposalResponse<'a> {
+    pub proposal_id: &'a str,
+    pub proposal_height: u64,
+    pub circuit_id: &'a str,
+    pub action: &'a str,
+}
+
+impl<'a> From<&'a ConsensusProposalState> for ConsensusProposalResponse<'a> {
+    fn from(proposal: &'a ConsensusProposalState) -> Self {
+        Self {
+            proposal_id: &proposal.proposal_id,
+            proposal_height: proposal.proposal_height,
+            circuit_id: &proposal.payload.circuit_id,
+            action: &proposal.payload.action,
+        }
+    }
+}
@@ -12,5 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "admin-allowed-signers")]
+pub mod allowed_signers;
+#[cfg(feature = "admin-service-debug")]
+pub mod debug;
+#[cfg(feature = "admin-signed-submit")]
+pub mod signed_payload;
+#[cfg(feature = "tenancy")]
+pub mod tenants;
 pub mod v1;
 pub mod v2;
+#[cfg(feature = "admin-vote-cosigning")]
+pub mod vote_cosigning;
+#[cfg(feature = "admin-vote-delegation")]
+pub mod vote_delegation;
@@ -0,0 +1,45 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::admin::signed_payload::SignedPayloadInfo;
+
+/// A request to submit an externally-signed circuit management payload
+#[derive(Debug, Deserialize)]
+pub(crate) struct SignedPayloadSubmission {
+    /// The hex-encoded bytes of the serialized `CircuitManagementPayload`
+    pub payload: String,
+    /// If `true`, the payload is only validated and is not submitted to the admin service
+    #[serde(default)]
+    pub validate_only: bool,
+}
+
+/// The header details of a validated payload
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct SignedPayloadResponse<'a> {
+    pub action: &'a str,
+    pub requester: &'a str,
+    pub requester_node_id: &'a str,
+    pub payload_sha512: &'a str,
+}
+
+impl<'a> From<&'a SignedPayloadInfo> for SignedPayloadResponse<'a> {
+    fn from(info: &'a SignedPayloadInfo) -> Self {
+        Self {
+            action: info.action(),
+            requester: info.requester(),
+            requester_node_id: info.requester_node_id(),
+            payload_sha512: info.payload_sha512(),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tenant::TenantAssignment;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct TenantAssignmentResponse<'a> {
+    pub tenant_id: &'a str,
+    pub resource_type: &'static str,
+    pub resource_id: &'a str,
+}
+
+impl<'a> From<&'a TenantAssignment> for TenantAssignmentResponse<'a> {
+    fn from(assignment: &'a TenantAssignment) -> Self {
+        Self {
+            tenant_id: assignment.tenant_id(),
+            resource_type: assignment.resource_type().as_str(),
+            resource_id: assignment.resource_id(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewTenantAssignment {
+    pub tenant_id: String,
+    pub resource_type: String,
+    pub resource_id: String,
+}
@@ -15,6 +15,7 @@
 use std::collections::BTreeMap;
 
 use crate::admin::store::{Circuit, Service};
+use crate::hex::to_hex;
 use crate::rest_api::paging::Paging;
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
@@ -30,6 +31,15 @@ pub(crate) struct CircuitResponse<'a> {
     pub roster: Vec<ServiceResponse<'a>>,
     pub management_type: &'a str,
     pub display_name: &'a Option<String>,
+    pub circuit_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposer_public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposer_node_id: Option<&'a str>,
 }
 
 impl<'a> From<&'a Circuit> for CircuitResponse<'a> {
@@ -40,6 +50,11 @@ impl<'a> From<&'a Circuit> for CircuitResponse<'a> {
             roster: circuit.roster().iter().map(ServiceResponse::from).collect(),
             management_type: circuit.circuit_management_type(),
             display_name: circuit.display_name(),
+            circuit_status: circuit.circuit_status().to_string(),
+            created_at: circuit.created_at(),
+            updated_at: circuit.updated_at(),
+            proposer_public_key: circuit.proposer_public_key().map(to_hex),
+            proposer_node_id: circuit.proposer_node_id(),
         }
     }
 }
@@ -17,6 +17,7 @@ use std::convert::TryFrom;
 use crate::admin::messages::{
     CircuitProposal, CreateCircuit, ProposalType, SplinterNode, SplinterService, Vote, VoteRecord,
 };
+use crate::admin::service::{MemberPeeringStatus, PeeringStatus};
 use crate::hex::as_hex;
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
@@ -29,6 +30,12 @@ pub(crate) struct ProposalResponse<'a> {
     #[serde(serialize_with = "as_hex")]
     pub requester: &'a [u8],
     pub requester_node_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub peering: Vec<PeeringResponse<'a>>,
 }
 
 impl<'a> TryFrom<&'a CircuitProposal> for ProposalResponse<'a> {
@@ -51,10 +58,52 @@ impl<'a> TryFrom<&'a CircuitProposal> for ProposalResponse<'a> {
             votes: proposal.votes.iter().map(VoteResponse::from).collect(),
             requester: &proposal.requester,
             requester_node_id: &proposal.requester_node_id,
+            created_at: proposal.created_at,
+            updated_at: proposal.updated_at,
+            peering: vec![],
         })
     }
 }
 
+impl<'a> ProposalResponse<'a> {
+    /// Adds the peering status of the proposed circuit's members to the response.
+    pub fn with_peering(mut self, peering: &'a [MemberPeeringStatus]) -> Self {
+        self.peering = peering.iter().map(PeeringResponse::from).collect();
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct PeeringResponse<'a> {
+    pub node_id: &'a str,
+    pub status: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<&'a str>,
+}
+
+impl<'a> From<&'a MemberPeeringStatus> for PeeringResponse<'a> {
+    fn from(member: &'a MemberPeeringStatus) -> Self {
+        let (status, attempts, last_error) = match &member.status {
+            PeeringStatus::Pending => ("Pending", None, None),
+            PeeringStatus::Connecting => ("Connecting", None, None),
+            PeeringStatus::Peered => ("Peered", None, None),
+            PeeringStatus::Retrying {
+                attempts,
+                last_error,
+            } => ("Retrying", Some(*attempts), Some(last_error.as_str())),
+        };
+
+        Self {
+            node_id: &member.node_id,
+            status,
+            attempts,
+            last_error,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone, PartialEq)]
 pub(crate) struct VoteResponse<'a> {
     #[serde(serialize_with = "as_hex")]
@@ -0,0 +1,104 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use protobuf::ProtobufError;
+
+use crate::admin::cosign::{PendingVote, VoteSignature, VoteThreshold};
+use crate::hex::to_hex;
+use crate::protos::admin::{
+    CircuitManagementPayload, CircuitManagementPayload_Header, CircuitProposalVote_Vote,
+};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct VoteThresholdResponse<'a> {
+    pub management_type: &'a str,
+    pub required_signatures: u32,
+}
+
+impl<'a> From<&'a VoteThreshold> for VoteThresholdResponse<'a> {
+    fn from(threshold: &'a VoteThreshold) -> Self {
+        Self {
+            management_type: threshold.management_type(),
+            required_signatures: threshold.required_signatures(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewVoteThreshold {
+    pub management_type: String,
+    pub required_signatures: u32,
+}
+
+impl From<NewVoteThreshold> for VoteThreshold {
+    fn from(threshold: NewVoteThreshold) -> Self {
+        VoteThreshold::new(&threshold.management_type, threshold.required_signatures)
+    }
+}
+
+/// A pending vote, including the hex-encoded payload header that co-signers must sign
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct PendingVoteResponse {
+    pub circuit_id: String,
+    pub circuit_hash: String,
+    pub vote: &'static str,
+    pub requester: String,
+    pub requester_node_id: String,
+    pub header: String,
+    pub cosigners: Vec<String>,
+}
+
+impl TryFrom<&PendingVote> for PendingVoteResponse {
+    type Error = ProtobufError;
+
+    fn try_from(pending_vote: &PendingVote) -> Result<Self, Self::Error> {
+        let payload =
+            protobuf::parse_from_bytes::<CircuitManagementPayload>(pending_vote.payload())?;
+        let header =
+            protobuf::parse_from_bytes::<CircuitManagementPayload_Header>(payload.get_header())?;
+        let vote = payload.get_circuit_proposal_vote();
+
+        Ok(Self {
+            circuit_id: pending_vote.circuit_id().to_string(),
+            circuit_hash: vote.get_circuit_hash().to_string(),
+            vote: match vote.get_vote() {
+                CircuitProposalVote_Vote::ACCEPT => "Accept",
+                CircuitProposalVote_Vote::REJECT => "Reject",
+                CircuitProposalVote_Vote::UNSET_VOTE => "Unset",
+            },
+            requester: to_hex(header.get_requester()),
+            requester_node_id: header.get_requester_node_id().to_string(),
+            header: to_hex(payload.get_header()),
+            cosigners: pending_vote
+                .signatures()
+                .iter()
+                .map(|signature| signature.public_key().to_string())
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewVoteSignature {
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl From<NewVoteSignature> for VoteSignature {
+    fn from(signature: NewVoteSignature) -> Self {
+        VoteSignature::new(&signature.public_key, &signature.signature)
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::admin::delegation::VoteDelegation;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct VoteDelegationResponse<'a> {
+    pub node_id: &'a str,
+    pub delegator_public_key: &'a str,
+    pub delegate_public_key: &'a str,
+    pub management_types: &'a [String],
+    pub expires_at: u64,
+    pub signature: &'a str,
+}
+
+impl<'a> From<&'a VoteDelegation> for VoteDelegationResponse<'a> {
+    fn from(delegation: &'a VoteDelegation) -> Self {
+        Self {
+            node_id: delegation.node_id(),
+            delegator_public_key: delegation.delegator_public_key(),
+            delegate_public_key: delegation.delegate_public_key(),
+            management_types: delegation.management_types(),
+            expires_at: delegation.expires_at(),
+            signature: delegation.signature(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewVoteDelegation {
+    pub node_id: String,
+    pub delegator_public_key: String,
+    pub delegate_public_key: String,
+    pub management_types: Vec<String>,
+    pub expires_at: u64,
+    pub signature: String,
+}
+
+impl From<NewVoteDelegation> for VoteDelegation {
+    fn from(delegation: NewVoteDelegation) -> Self {
+        VoteDelegation::new(
+            &delegation.node_id,
+            &delegation.delegator_public_key,
+            &delegation.delegate_public_key,
+            delegation.management_types,
+            delegation.expires_at,
+            &delegation.signature,
+        )
+    }
+}
@@ -0,0 +1,463 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rebuilding the circuit routing table from the admin service store.
+//!
+//! The routing table is populated by the `AdminService` when circuits are created or when the
+//! node restarts. If the two ever diverge, messages for a circuit that exists in the store will be
+//! dropped because the routing table does not know where to send them.
+//! [`rebuild_routing_table`] brings the routing table back in line with the store and reports
+//! where the two differed.
+//!
+//! [`rebuild_routing_table`]: fn.rebuild_routing_table.html
+
+use std::collections::{HashMap, HashSet};
+
+use crate::circuit::routing::{self, RoutingTableReader, RoutingTableWriter, ServiceId};
+use crate::error::InternalError;
+
+use super::store::{AdminServiceStore, CircuitNode};
+
+/// A summary of the changes made by `rebuild_routing_table`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoutingTableRebuild {
+    /// The circuits that were in the store but missing from the routing table
+    pub added_circuits: Vec<String>,
+    /// The circuits that were in the routing table but not in the store
+    pub removed_circuits: Vec<String>,
+    /// The circuits whose routing table entry did not match the store, such as a different set
+    /// of members or services
+    pub changed_circuits: Vec<String>,
+    /// The circuit members that could not be routed to because the store has no node information
+    /// for them
+    pub missing_nodes: Vec<String>,
+    /// The number of circuits in the routing table after the rebuild
+    pub circuit_count: usize,
+}
+
+impl RoutingTableRebuild {
+    /// Returns whether the routing table differed from the store, or could not be fully rebuilt
+    /// from it.
+    pub fn has_discrepancies(&self) -> bool {
+        !(self.added_circuits.is_empty()
+            && self.removed_circuits.is_empty()
+            && self.changed_circuits.is_empty()
+            && self.missing_nodes.is_empty())
+    }
+}
+
+/// Rebuilds the routing table from the circuits and nodes in the admin service store.
+///
+/// Circuits that are not in the store are removed from the routing table, and every circuit in
+/// the store is re-added along with its services and member nodes. Any peer IDs that were already
+/// assigned to local services are kept, so services that are connected stay routable. The
+/// returned summary lists the circuits that were missing, stale or different in the routing
+/// table.
+///
+/// # Arguments
+///
+/// * `node_id` - The ID of the local node, which is never added to the routing table as a node
+/// * `store` - The admin service store that holds the source of truth for circuit state
+/// * `reader` - A reader for the current routing table
+/// * `writer` - A writer for the routing table that will be rebuilt
+pub fn rebuild_routing_table(
+    node_id: &str,
+    store: &dyn AdminServiceStore,
+    reader: &dyn RoutingTableReader,
+    writer: &mut dyn RoutingTableWriter,
+) -> Result<RoutingTableRebuild, InternalError> {
+    let circuits = store
+        .list_circuits(&[])
+        .map_err(|err| InternalError::from_source(Box::new(err)))?
+        .collect::<Vec<_>>();
+
+    let nodes: HashMap<String, CircuitNode> = store
+        .list_nodes()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?
+        .map(|node| (node.node_id().to_string(), node))
+        .collect();
+
+    let existing_circuits: HashSet<String> = reader
+        .list_circuits()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?
+        .map(|(circuit_id, _)| circuit_id)
+        .collect();
+
+    let store_circuits: HashSet<&str> = circuits
+        .iter()
+        .map(|circuit| circuit.circuit_id())
+        .collect();
+
+    let mut summary = RoutingTableRebuild::default();
+
+    for circuit_id in existing_circuits.iter() {
+        if !store_circuits.contains(circuit_id.as_str()) {
+            writer.remove_circuit(circuit_id)?;
+            summary.removed_circuits.push(circuit_id.to_string());
+        }
+    }
+
+    for circuit in circuits.iter() {
+        let mut routing_services = vec![];
+        for service in circuit.roster() {
+            let mut routing_service = routing::Service::new(
+                service.service_id().to_string(),
+                service.service_type().to_string(),
+                service.node_id().to_string(),
+                service.arguments().to_vec(),
+            );
+
+            let service_id = ServiceId::new(
+                circuit.circuit_id().to_string(),
+                service.service_id().to_string(),
+            );
+            let existing_peer_id = reader
+                .get_service(&service_id)
+                .map_err(|err| InternalError::from_source(Box::new(err)))?
+                .and_then(|existing| existing.peer_id().clone());
+            if let Some(peer_id) = existing_peer_id {
+                routing_service.set_peer_id(peer_id);
+            }
+
+            routing_services.push(routing_service);
+        }
+
+        let mut routing_members = vec![];
+        for member in circuit.members() {
+            if member == node_id {
+                continue;
+            }
+
+            match nodes.get(member) {
+                Some(node) => {
+                    let routing_node =
+                        routing::CircuitNode::new(member.to_string(), node.endpoints().to_vec());
+                    // add_circuit does not replace existing nodes, so update them directly
+                    writer.add_node(member.to_string(), routing_node.clone())?;
+                    routing_members.push(routing_node);
+                }
+                None => {
+                    error!("Missing node information for {}", member);
+                    if !summary.missing_nodes.contains(member) {
+                        summary.missing_nodes.push(member.to_string());
+                    }
+                }
+            }
+        }
+
+        let routing_circuit = routing::Circuit::new(
+            circuit.circuit_id().to_string(),
+            routing_services,
+            circuit.members().to_vec(),
+        )
+        .with_relays(circuit.relay_members().to_vec())
+        .with_observers(circuit.observer_members().to_vec())
+        .with_qos(*circuit.qos());
+
+        if !existing_circuits.contains(circuit.circuit_id()) {
+            summary
+                .added_circuits
+                .push(circuit.circuit_id().to_string());
+        } else if let Some(existing) = reader
+            .get_circuit(circuit.circuit_id())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+        {
+            if !same_routes(&existing, &routing_circuit) {
+                summary
+                    .changed_circuits
+                    .push(circuit.circuit_id().to_string());
+            }
+        }
+
+        writer.add_circuit(
+            circuit.circuit_id().to_string(),
+            routing_circuit,
+            routing_members,
+        )?;
+    }
+
+    summary.added_circuits.sort();
+    summary.removed_circuits.sort();
+    summary.changed_circuits.sort();
+    summary.missing_nodes.sort();
+    summary.circuit_count = circuits.len();
+
+    Ok(summary)
+}
+
+/// Returns whether two routing table entries for a circuit route messages the same way. Peer IDs
+/// are ignored, because they are assigned as services connect rather than taken from the store.
+fn same_routes(existing: &routing::Circuit, expected: &routing::Circuit) -> bool {
+    let without_peer_ids = |circuit: &routing::Circuit| {
+        circuit
+            .roster()
+            .iter()
+            .cloned()
+            .map(|mut service| {
+                service.remove_peer_id();
+                service
+            })
+            .collect::<Vec<_>>()
+    };
+
+    existing.members() == expected.members()
+        && existing.relays() == expected.relays()
+        && existing.observers() == expected.observers()
+        && existing.qos() == expected.qos()
+        && without_peer_ids(existing) == without_peer_ids(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crate::admin::store::yaml::YamlAdminServiceStore;
+    use crate::admin::store::{CircuitBuilder, CircuitNodeBuilder, ServiceBuilder};
+    use crate::circuit::routing::memory::RoutingTable;
+
+    /// Verify that rebuilding the routing table adds circuits that are missing from the table,
+    /// removes circuits that are not in the store, and keeps the peer IDs of existing services.
+    ///
+    /// 1. Add a circuit and its remote node to a YAML admin service store
+    /// 2. Add a stale circuit to the routing table, along with a copy of the stored circuit's
+    ///    local service that has a peer ID
+    /// 3. Rebuild the routing table and verify the summary
+    /// 4. Verify the stale circuit was removed, the stored circuit and remote node were added,
+    ///    and the peer ID of the local service was kept
+    #[test]
+    fn test_rebuild_routing_table() {
+        let temp_dir =
+            TempDir::new("test_rebuild_routing_table").expect("Failed to create temp dir");
+        let circuit_path = temp_dir
+            .path()
+            .join("circuits.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+        let proposals_path = temp_dir
+            .path()
+            .join("circuit_proposals.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        let store = YamlAdminServiceStore::new(circuit_path, proposals_path)
+            .expect("Unable to create yaml admin store");
+
+        let circuit = CircuitBuilder::default()
+            .with_circuit_id("WBKLF-AAAAA")
+            .with_roster(&vec![
+                ServiceBuilder::default()
+                    .with_service_id("a000")
+                    .with_service_type("scabbard")
+                    .with_node_id("acme-node-000")
+                    .build()
+                    .expect("Unable to build service"),
+                ServiceBuilder::default()
+                    .with_service_id("a001")
+                    .with_service_type("scabbard")
+                    .with_node_id("bubba-node-000")
+                    .build()
+                    .expect("Unable to build service"),
+            ])
+            .with_members(&vec!["acme-node-000".into(), "bubba-node-000".into()])
+            .with_circuit_management_type("test")
+            .build()
+            .expect("Unable to build circuit");
+        let node = CircuitNodeBuilder::default()
+            .with_node_id("bubba-node-000")
+            .with_endpoints(&vec!["tcps://bubba:8044".into()])
+            .build()
+            .expect("Unable to build node");
+
+        store
+            .add_circuit(circuit, vec![node])
+            .expect("Unable to add circuit to store");
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table);
+
+        writer
+            .add_circuit(
+                "WBKLF-BBBBB".into(),
+                routing::Circuit::new("WBKLF-BBBBB".into(), vec![], vec![]),
+                vec![],
+            )
+            .expect("Unable to add stale circuit");
+
+        let mut local_service = routing::Service::new(
+            "a000".into(),
+            "scabbard".into(),
+            "acme-node-000".into(),
+            vec![],
+        );
+        local_service.set_peer_id("service-peer".into());
+        writer
+            .add_service(
+                ServiceId::new("WBKLF-AAAAA".into(), "a000".into()),
+                local_service,
+            )
+            .expect("Unable to add local service");
+
+        let summary = rebuild_routing_table("acme-node-000", &store, &*reader, &mut *writer)
+            .expect("Unable to rebuild routing table");
+
+        assert_eq!(
+            summary,
+            RoutingTableRebuild {
+                added_circuits: vec!["WBKLF-AAAAA".into()],
+                removed_circuits: vec!["WBKLF-BBBBB".into()],
+                changed_circuits: vec![],
+                missing_nodes: vec![],
+                circuit_count: 1,
+            }
+        );
+        assert!(summary.has_discrepancies());
+
+        assert!(reader
+            .get_circuit("WBKLF-BBBBB")
+            .expect("Unable to get circuit")
+            .is_none());
+        assert!(reader
+            .get_circuit("WBKLF-AAAAA")
+            .expect("Unable to get circuit")
+            .is_some());
+
+        let node = reader
+            .get_node("bubba-node-000")
+            .expect("Unable to get node")
+            .expect("Node was not added");
+        assert_eq!(node.endpoints(), &["tcps://bubba:8044".to_string()]);
+        assert!(reader
+            .get_node("acme-node-000")
+            .expect("Unable to get node")
+            .is_none());
+
+        let service = reader
+            .get_service(&ServiceId::new("WBKLF-AAAAA".into(), "a000".into()))
+            .expect("Unable to get service")
+            .expect("Service was not added");
+        assert_eq!(service.peer_id(), &Some("service-peer".to_string()));
+    }
+
+    /// Verify that rebuilding the routing table reports circuits whose routing entry differs from
+    /// the store, and members without node information.
+    ///
+    /// 1. Add a circuit with three members to a YAML admin service store, with node information
+    ///    for only one of the remote members
+    /// 2. Add the circuit to the routing table with only two members and one service
+    /// 3. Rebuild the routing table and verify the circuit is reported as changed and the member
+    ///    without node information is reported as missing
+    /// 4. Rebuild the routing table again and verify the circuit is no longer reported as changed
+    #[test]
+    fn test_rebuild_routing_table_discrepancies() {
+        let temp_dir = TempDir::new("test_rebuild_routing_table_discrepancies")
+            .expect("Failed to create temp dir");
+        let circuit_path = temp_dir
+            .path()
+            .join("circuits.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+        let proposals_path = temp_dir
+            .path()
+            .join("circuit_proposals.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        let store = YamlAdminServiceStore::new(circuit_path, proposals_path)
+            .expect("Unable to create yaml admin store");
+
+        let local_service = ServiceBuilder::default()
+            .with_service_id("a000")
+            .with_service_type("scabbard")
+            .with_node_id("acme-node-000")
+            .build()
+            .expect("Unable to build service");
+        let circuit = CircuitBuilder::default()
+            .with_circuit_id("WBKLF-AAAAA")
+            .with_roster(&vec![
+                local_service,
+                ServiceBuilder::default()
+                    .with_service_id("a001")
+                    .with_service_type("scabbard")
+                    .with_node_id("bubba-node-000")
+                    .build()
+                    .expect("Unable to build service"),
+            ])
+            .with_members(&vec![
+                "acme-node-000".into(),
+                "bubba-node-000".into(),
+                "carol-node-000".into(),
+            ])
+            .with_circuit_management_type("test")
+            .build()
+            .expect("Unable to build circuit");
+        let node = CircuitNodeBuilder::default()
+            .with_node_id("bubba-node-000")
+            .with_endpoints(&vec!["tcps://bubba:8044".into()])
+            .build()
+            .expect("Unable to build node");
+
+        store
+            .add_circuit(circuit, vec![node])
+            .expect("Unable to add circuit to store");
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table);
+
+        writer
+            .add_circuit(
+                "WBKLF-AAAAA".into(),
+                routing::Circuit::new(
+                    "WBKLF-AAAAA".into(),
+                    vec![routing::Service::new(
+                        "a000".into(),
+                        "scabbard".into(),
+                        "acme-node-000".into(),
+                        vec![],
+                    )],
+                    vec!["acme-node-000".into(), "bubba-node-000".into()],
+                ),
+                vec![],
+            )
+            .expect("Unable to add outdated circuit");
+
+        let summary = rebuild_routing_table("acme-node-000", &store, &*reader, &mut *writer)
+            .expect("Unable to rebuild routing table");
+
+        assert_eq!(
+            summary,
+            RoutingTableRebuild {
+                added_circuits: vec![],
+                removed_circuits: vec![],
+                changed_circuits: vec!["WBKLF-AAAAA".into()],
+                missing_nodes: vec!["carol-node-000".into()],
+                circuit_count: 1,
+            }
+        );
+
+        let summary = rebuild_routing_table("acme-node-000", &store, &*reader, &mut *writer)
+            .expect("Unable to rebuild routing table");
+
+        assert!(summary.changed_circuits.is_empty());
+        assert_eq!(summary.missing_nodes, vec!["carol-node-000".to_string()]);
+        assert!(summary.has_discrepancies());
+    }
+}
@@ -0,0 +1,406 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persisted snapshots of the routing table entries built from the admin service store.
+//!
+//! When a node starts, the `AdminService` adds every circuit in the store to the routing table.
+//! A [`RoutingTableSnapshot`] saved when the node last stopped lets the service restore the
+//! entries of circuits that have not changed since, so only the circuits that were added or
+//! modified in the meantime are rebuilt from the store.
+//!
+//! A snapshot entry is only restored if the circuit's `updated_at` time and the endpoints of its
+//! member nodes in the store still match the snapshot; circuits that have been removed from the
+//! store are never restored. Circuits without an `updated_at` time are always rebuilt.
+//!
+//! [`RoutingTableSnapshot`]: struct.RoutingTableSnapshot.html
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use crate::circuit::qos::QosLimits;
+use crate::circuit::routing::{self, RoutingTableWriter};
+use crate::error::InternalError;
+
+use super::store::{Circuit, CircuitNode};
+
+/// The version of the snapshot format; snapshots of other versions are ignored
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// The routing table entries of the circuits in the admin service store
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingTableSnapshot {
+    version: u32,
+    node_id: String,
+    circuits: BTreeMap<String, SnapshotCircuit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SnapshotCircuit {
+    updated_at: u64,
+    members: Vec<String>,
+    relays: Vec<String>,
+    observers: Vec<String>,
+    services: Vec<SnapshotService>,
+    nodes: BTreeMap<String, Vec<String>>,
+    qos: QosLimits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SnapshotService {
+    service_id: String,
+    service_type: String,
+    node_id: String,
+    arguments: Vec<(String, String)>,
+}
+
+impl RoutingTableSnapshot {
+    /// Captures the routing table entries of the given circuits.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the local node, which is never added to the routing table as a node
+    /// * `circuits` - The circuits in the admin service store
+    /// * `nodes` - The nodes in the admin service store, by node ID
+    pub fn capture(
+        node_id: &str,
+        circuits: &[Circuit],
+        nodes: &BTreeMap<String, CircuitNode>,
+    ) -> Self {
+        let circuits = circuits
+            .iter()
+            .filter_map(|circuit| {
+                let updated_at = circuit.updated_at()?;
+                Some((
+                    circuit.circuit_id().to_string(),
+                    SnapshotCircuit {
+                        updated_at,
+                        members: circuit.members().to_vec(),
+                        relays: circuit.relay_members().to_vec(),
+                        observers: circuit.observer_members().to_vec(),
+                        services: circuit
+                            .roster()
+                            .iter()
+                            .map(|service| SnapshotService {
+                                service_id: service.service_id().to_string(),
+                                service_type: service.service_type().to_string(),
+                                node_id: service.node_id().to_string(),
+                                arguments: service.arguments().to_vec(),
+                            })
+                            .collect(),
+                        nodes: member_endpoints(node_id, circuit, nodes),
+                        qos: *circuit.qos(),
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            node_id: node_id.to_string(),
+            circuits,
+        }
+    }
+
+    /// Loads the snapshot at the given path. An empty snapshot is returned if the file does not
+    /// exist or was written in a different format version.
+    pub fn load(path: &Path) -> Result<Self, InternalError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let file = File::open(path).map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!("Unable to open {}", path.display()),
+            )
+        })?;
+        let snapshot: Self = serde_json::from_reader(file).map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!("Unable to read {}", path.display()),
+            )
+        })?;
+
+        if snapshot.version == SNAPSHOT_VERSION {
+            Ok(snapshot)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Writes the snapshot to the given path. The snapshot is written to a temporary file that is
+    /// renamed, so the file at the path is never partially written.
+    pub fn save(&self, path: &Path) -> Result<(), InternalError> {
+        let contents =
+            serde_json::to_vec(self).map_err(|err| InternalError::from_source(Box::new(err)))?;
+        let temp_path = path.with_extension("json.tmp");
+        File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(&contents)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|err| {
+                InternalError::from_source_with_prefix(
+                    Box::new(err),
+                    format!("Unable to write {}", path.display()),
+                )
+            })
+    }
+
+    /// Returns the number of circuits in the snapshot.
+    pub fn len(&self) -> usize {
+        self.circuits.len()
+    }
+
+    /// Returns whether the snapshot contains no circuits.
+    pub fn is_empty(&self) -> bool {
+        self.circuits.is_empty()
+    }
+
+    /// Adds the snapshot's entries for the circuits that have not changed in the store to the
+    /// routing table, returning the IDs of the restored circuits. The remaining circuits must be
+    /// added from the store by the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the local node; a snapshot taken by another node is not restored
+    /// * `circuits` - The circuits in the admin service store
+    /// * `nodes` - The nodes in the admin service store, by node ID
+    /// * `writer` - A writer for the routing table that the entries are restored to
+    pub fn restore(
+        &self,
+        node_id: &str,
+        circuits: &[Circuit],
+        nodes: &BTreeMap<String, CircuitNode>,
+        writer: &mut dyn RoutingTableWriter,
+    ) -> Result<HashSet<String>, InternalError> {
+        let mut restored = HashSet::new();
+        if self.node_id != node_id {
+            return Ok(restored);
+        }
+
+        for circuit in circuits {
+            let entry = match self.circuits.get(circuit.circuit_id()) {
+                Some(entry) if Some(entry.updated_at) == circuit.updated_at() => entry,
+                _ => continue,
+            };
+            if entry.nodes != member_endpoints(node_id, circuit, nodes) {
+                continue;
+            }
+
+            let routing_services = entry
+                .services
+                .iter()
+                .map(|service| {
+                    routing::Service::new(
+                        service.service_id.clone(),
+                        service.service_type.clone(),
+                        service.node_id.clone(),
+                        service.arguments.clone(),
+                    )
+                })
+                .collect();
+            let routing_members = entry
+                .nodes
+                .iter()
+                .map(|(member, endpoints)| {
+                    routing::CircuitNode::new(member.clone(), endpoints.clone())
+                })
+                .collect();
+
+            writer.add_circuit(
+                circuit.circuit_id().to_string(),
+                routing::Circuit::new(
+                    circuit.circuit_id().to_string(),
+                    routing_services,
+                    entry.members.clone(),
+                )
+                .with_relays(entry.relays.clone())
+                .with_observers(entry.observers.clone())
+                .with_qos(entry.qos),
+                routing_members,
+            )?;
+            restored.insert(circuit.circuit_id().to_string());
+        }
+
+        Ok(restored)
+    }
+}
+
+/// Returns the endpoints of the circuit's members, other than the local node, that have node
+/// information in the store.
+fn member_endpoints(
+    node_id: &str,
+    circuit: &Circuit,
+    nodes: &BTreeMap<String, CircuitNode>,
+) -> BTreeMap<String, Vec<String>> {
+    circuit
+        .members()
+        .iter()
+        .filter(|member| member.as_str() != node_id)
+        .filter_map(|member| {
+            nodes
+                .get(member)
+                .map(|node| (member.to_string(), node.endpoints().to_vec()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crate::admin::store::{CircuitBuilder, CircuitNodeBuilder, ServiceBuilder};
+    use crate::circuit::routing::memory::RoutingTable;
+    use crate::circuit::routing::RoutingTableReader;
+
+    fn circuit(circuit_id: &str, updated_at: u64) -> Circuit {
+        CircuitBuilder::default()
+            .with_circuit_id(circuit_id)
+            .with_roster(&vec![
+                ServiceBuilder::default()
+                    .with_service_id("a000")
+                    .with_service_type("scabbard")
+                    .with_node_id("acme-node-000")
+                    .build()
+                    .expect("Unable to build service"),
+                ServiceBuilder::default()
+                    .with_service_id("a001")
+                    .with_service_type("scabbard")
+                    .with_node_id("bubba-node-000")
+                    .build()
+                    .expect("Unable to build service"),
+            ])
+            .with_members(&vec!["acme-node-000".into(), "bubba-node-000".into()])
+            .with_circuit_management_type("test")
+            .with_updated_at(updated_at)
+            .build()
+            .expect("Unable to build circuit")
+    }
+
+    fn nodes(endpoint: &str) -> BTreeMap<String, CircuitNode> {
+        let node = CircuitNodeBuilder::default()
+            .with_node_id("bubba-node-000")
+            .with_endpoints(&vec![endpoint.into()])
+            .build()
+            .expect("Unable to build node");
+        vec![("bubba-node-000".to_string(), node)]
+            .into_iter()
+            .collect()
+    }
+
+    /// Verify that a saved snapshot only restores the circuits that have not changed.
+    ///
+    /// 1. Capture a snapshot of three circuits and save it to a file
+    /// 2. Load the snapshot and restore it against a store in which one circuit has been
+    ///    updated, one has been removed and one has been added
+    /// 3. Verify that only the unchanged circuit was restored to the routing table
+    /// 4. Verify that a member endpoint change prevents the unchanged circuit from being restored
+    /// 5. Verify that a snapshot taken by another node is not restored
+    #[test]
+    fn test_snapshot_restore() {
+        let temp_dir = TempDir::new("test_snapshot_restore").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("routing_table.json");
+
+        let snapshot = RoutingTableSnapshot::capture(
+            "acme-node-000",
+            &[
+                circuit("WBKLF-AAAAA", 10),
+                circuit("WBKLF-BBBBB", 10),
+                circuit("WBKLF-CCCCC", 10),
+            ],
+            &nodes("tcps://bubba:8044"),
+        );
+        snapshot.save(&path).expect("Unable to save snapshot");
+
+        let loaded = RoutingTableSnapshot::load(&path).expect("Unable to load snapshot");
+        assert_eq!(snapshot, loaded);
+        assert_eq!(3, loaded.len());
+
+        let store_circuits = vec![
+            circuit("WBKLF-AAAAA", 10),
+            circuit("WBKLF-BBBBB", 20),
+            circuit("WBKLF-DDDDD", 20),
+        ];
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table);
+
+        let restored = loaded
+            .restore(
+                "acme-node-000",
+                &store_circuits,
+                &nodes("tcps://bubba:8044"),
+                &mut *writer,
+            )
+            .expect("Unable to restore snapshot");
+        assert_eq!(
+            vec!["WBKLF-AAAAA".to_string()]
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            restored
+        );
+
+        let restored_circuit = reader
+            .get_circuit("WBKLF-AAAAA")
+            .expect("Unable to get circuit")
+            .expect("Circuit was not restored");
+        assert_eq!(restored_circuit.roster().len(), 2);
+        let node = reader
+            .get_node("bubba-node-000")
+            .expect("Unable to get node")
+            .expect("Node was not restored");
+        assert_eq!(node.endpoints(), &["tcps://bubba:8044".to_string()]);
+        assert!(reader
+            .get_circuit("WBKLF-BBBBB")
+            .expect("Unable to get circuit")
+            .is_none());
+
+        let restored = loaded
+            .restore(
+                "acme-node-000",
+                &store_circuits,
+                &nodes("tcps://bubba:8045"),
+                &mut *writer,
+            )
+            .expect("Unable to restore snapshot");
+        assert!(restored.is_empty());
+
+        let restored = loaded
+            .restore(
+                "bubba-node-000",
+                &store_circuits,
+                &nodes("tcps://bubba:8044"),
+                &mut *writer,
+            )
+            .expect("Unable to restore snapshot");
+        assert!(restored.is_empty());
+    }
+
+    /// Verify that loading a snapshot that does not exist returns an empty snapshot.
+    #[test]
+    fn test_snapshot_load_missing() {
+        let temp_dir =
+            TempDir::new("test_snapshot_load_missing").expect("Failed to create temp dir");
+        let snapshot = RoutingTableSnapshot::load(&temp_dir.path().join("routing_table.json"))
+            .expect("Unable to load snapshot");
+        assert!(snapshot.is_empty());
+    }
+}
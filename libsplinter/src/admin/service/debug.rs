@@ -0,0 +1,164 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshots of the admin service's internal state, used to diagnose circuit proposals that are
+//! not making progress.
+
+use std::sync::{Arc, Mutex};
+
+use crate::error::InternalError;
+use crate::protos::admin::{CircuitManagementPayload, CircuitManagementPayload_Header};
+
+use super::peering::MemberPeeringStatus;
+use super::shared::{AdminServiceShared, PayloadType, PendingPayload};
+
+/// A snapshot of the admin service's internal state
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminServiceDebugState {
+    /// Payloads waiting for connections to the members of their circuits
+    pub unpeered_payloads: Vec<PendingPayloadState>,
+    /// Payloads waiting for the members' admin services to agree on a protocol version
+    pub pending_protocol_payloads: Vec<PendingPayloadState>,
+    /// The peering state of the members of each circuit proposal, including failed attempts to
+    /// acquire a peer that will be retried
+    pub proposal_peering: Vec<ProposalPeeringState>,
+    /// The state of the consensus rounds for circuit management payloads
+    pub consensus: ConsensusState,
+}
+
+/// The circuit and action of a circuit management payload
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayloadSummary {
+    /// The ID of the circuit the payload applies to, or an empty string if the payload does not
+    /// have one
+    pub circuit_id: String,
+    /// The payload's action, such as `CIRCUIT_CREATE_REQUEST`
+    pub action: String,
+}
+
+impl PayloadSummary {
+    pub(super) fn new(circuit_id: &str, action: String) -> Self {
+        Self {
+            circuit_id: circuit_id.to_string(),
+            action,
+        }
+    }
+
+    pub(super) fn from_payload(payload: &CircuitManagementPayload) -> Self {
+        let action =
+            protobuf::parse_from_bytes::<CircuitManagementPayload_Header>(payload.get_header())
+                .map(|header| format!("{:?}", header.get_action()))
+                .unwrap_or_else(|_| "UNKNOWN".into());
+        let circuit_id = if payload.has_circuit_create_request() {
+            payload
+                .get_circuit_create_request()
+                .get_circuit()
+                .get_circuit_id()
+        } else if payload.has_circuit_proposal_vote() {
+            payload.get_circuit_proposal_vote().get_circuit_id()
+        } else {
+            ""
+        };
+
+        Self::new(circuit_id, action)
+    }
+}
+
+/// A payload that is held until the members of its circuit are ready for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingPayloadState {
+    pub payload: PayloadSummary,
+    /// The ID of the consensus proposal, if the payload is held for consensus rather than
+    /// submission
+    pub proposal_id: Option<String>,
+    /// The node or service that sent the payload
+    pub message_sender: String,
+    pub members: Vec<String>,
+    /// The members that are not yet peered
+    pub unpeered_ids: Vec<String>,
+    /// The members whose admin services have not agreed on a protocol version
+    pub missing_protocol_ids: Vec<String>,
+}
+
+impl From<&PendingPayload> for PendingPayloadState {
+    fn from(pending: &PendingPayload) -> Self {
+        let (payload, proposal_id) = match &pending.payload_type {
+            PayloadType::Circuit(payload) => (PayloadSummary::from_payload(payload), None),
+            PayloadType::Consensus(proposal_id, (_, payload)) => (
+                PayloadSummary::from_payload(payload),
+                Some(proposal_id.to_string()),
+            ),
+        };
+
+        Self {
+            payload,
+            proposal_id,
+            message_sender: pending.message_sender.clone(),
+            members: pending.members.clone(),
+            unpeered_ids: pending.unpeered_ids.clone(),
+            missing_protocol_ids: pending.missing_protocol_ids.clone(),
+        }
+    }
+}
+
+/// The peering state of the members of a circuit proposal
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProposalPeeringState {
+    pub circuit_id: String,
+    pub members: Vec<MemberPeeringStatus>,
+}
+
+/// The state of the consensus rounds for circuit management payloads
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsensusState {
+    /// Payloads waiting for their consensus round
+    pub queued_payloads: Vec<PayloadSummary>,
+    /// Consensus proposals that have not been accepted or rejected
+    pub pending_proposals: Vec<ConsensusProposalState>,
+    /// The change that is being agreed on in the current round
+    pub current_change: Option<PayloadSummary>,
+    /// The verifiers of the current round
+    pub current_verifiers: Vec<String>,
+}
+
+/// A consensus proposal that has not been accepted or rejected
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsensusProposalState {
+    pub proposal_id: String,
+    pub proposal_height: u64,
+    pub payload: PayloadSummary,
+}
+
+/// Provides snapshots of a running admin service's internal state.
+#[derive(Clone)]
+pub struct AdminServiceDebug {
+    shared: Arc<Mutex<AdminServiceShared>>,
+}
+
+impl AdminServiceDebug {
+    pub(super) fn new(shared: &Arc<Mutex<AdminServiceShared>>) -> Self {
+        Self {
+            shared: Arc::clone(shared),
+        }
+    }
+
+    /// Returns a snapshot of the admin service's internal state.
+    pub fn state(&self) -> Result<AdminServiceDebugState, InternalError> {
+        Ok(self
+            .shared
+            .lock()
+            .map_err(|_| InternalError::with_message("Admin shared lock was poisoned".into()))?
+            .debug_state())
+    }
+}
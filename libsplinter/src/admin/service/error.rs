@@ -170,6 +170,19 @@ pub enum AdminSharedError {
 
     // An error occured while trying to negotiated protocol versions
     ServiceProtocolError(String),
+
+    /// A vote does not yet carry the number of signatures required by the vote threshold of the
+    /// proposal's circuit management type.
+    #[cfg(feature = "admin-vote-cosigning")]
+    InsufficientVoteSignatures {
+        circuit_id: String,
+        required: usize,
+        collected: usize,
+    },
+
+    /// The node is in read-only mode, so circuit changes may not be submitted.
+    #[cfg(feature = "rest-api-read-only")]
+    ReadOnly,
 }
 
 impl Error for AdminSharedError {
@@ -191,6 +204,10 @@ impl Error for AdminSharedError {
             AdminSharedError::ValidationFailed(_) => None,
             AdminSharedError::UnableToAddSubscriber(_) => None,
             AdminSharedError::ServiceProtocolError(_) => None,
+            #[cfg(feature = "admin-vote-cosigning")]
+            AdminSharedError::InsufficientVoteSignatures { .. } => None,
+            #[cfg(feature = "rest-api-read-only")]
+            AdminSharedError::ReadOnly => None,
         }
     }
 }
@@ -228,6 +245,20 @@ impl fmt::Display for AdminSharedError {
                 "error occured while trying to agree on protocol: {}",
                 msg
             ),
+            #[cfg(feature = "admin-vote-cosigning")]
+            AdminSharedError::InsufficientVoteSignatures {
+                circuit_id,
+                required,
+                collected,
+            } => write!(
+                f,
+                "vote for circuit {} requires {} signatures; {} collected",
+                circuit_id, required, collected
+            ),
+            #[cfg(feature = "rest-api-read-only")]
+            AdminSharedError::ReadOnly => {
+                f.write_str("node is in read-only mode; circuit changes are refused")
+            }
         }
     }
 }
@@ -301,6 +332,32 @@ impl From<AdminConsensusManagerError> for AdminError {
     }
 }
 
+#[cfg(feature = "admin-permission-prefetch")]
+#[derive(Debug)]
+pub enum ProposalPipelineError {
+    /// The worker for the proposal's circuit already has a full queue
+    QueueFull(String),
+    /// The pipeline has been shutdown
+    Shutdown,
+}
+
+#[cfg(feature = "admin-permission-prefetch")]
+impl Error for ProposalPipelineError {}
+
+#[cfg(feature = "admin-permission-prefetch")]
+impl std::fmt::Display for ProposalPipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProposalPipelineError::QueueFull(circuit_id) => write!(
+                f,
+                "proposal pipeline is full; unable to queue proposal for circuit {}",
+                circuit_id
+            ),
+            ProposalPipelineError::Shutdown => write!(f, "proposal pipeline has been shutdown"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Sha256Error(pub Box<dyn Error + Send>);
 
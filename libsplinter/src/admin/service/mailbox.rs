@@ -333,10 +333,13 @@ mod tests {
                 application_metadata: vec![],
                 comments: Some("mock circuit".into()),
                 display_name: Some("test_circuit".into()),
+                qos: Default::default(),
             },
             votes: vec![],
             requester: vec![],
             requester_node_id: "another-node".into(),
+            created_at: None,
+            updated_at: None,
         })
     }
 }
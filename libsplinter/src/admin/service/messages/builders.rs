@@ -14,11 +14,16 @@
 
 use std::error::Error as StdError;
 
+#[cfg(feature = "circuit-id-policy")]
+use crate::admin::circuit_id::CircuitIdFormat;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::VoteDelegation;
 use crate::base62::generate_random_base62_string;
+use crate::circuit::qos::QosLimits;
 
 use super::{
-    is_valid_circuit_id, is_valid_service_id, AuthorizationType, CreateCircuit, DurabilityType,
-    PersistenceType, RouteType, SplinterNode, SplinterService,
+    is_valid_circuit_id, is_valid_service_id, AuthorizationType, CircuitProposalVote,
+    CreateCircuit, DurabilityType, PersistenceType, RouteType, SplinterNode, SplinterService, Vote,
 };
 
 #[derive(Default, Clone)]
@@ -34,6 +39,9 @@ pub struct CreateCircuitBuilder {
     application_metadata: Option<Vec<u8>>,
     comments: Option<String>,
     display_name: Option<String>,
+    qos: QosLimits,
+    #[cfg(feature = "circuit-id-policy")]
+    circuit_id_format: Option<CircuitIdFormat>,
 }
 
 impl CreateCircuitBuilder {
@@ -85,6 +93,10 @@ impl CreateCircuitBuilder {
         self.display_name.clone()
     }
 
+    pub fn qos(&self) -> QosLimits {
+        self.qos
+    }
+
     pub fn with_circuit_id(mut self, circuit_id: &str) -> CreateCircuitBuilder {
         self.circuit_id = Some(circuit_id.into());
         self
@@ -149,6 +161,18 @@ impl CreateCircuitBuilder {
         self
     }
 
+    pub fn with_qos(mut self, qos: QosLimits) -> CreateCircuitBuilder {
+        self.qos = qos;
+        self
+    }
+
+    /// Sets the format of the circuit ID that is generated if no circuit ID is set
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn with_circuit_id_format(mut self, format: CircuitIdFormat) -> CreateCircuitBuilder {
+        self.circuit_id_format = Some(format);
+        self
+    }
+
     pub fn build(self) -> Result<CreateCircuit, BuilderError> {
         let circuit_id = match self.circuit_id {
             Some(circuit_id) if is_valid_circuit_id(&circuit_id) => circuit_id,
@@ -159,6 +183,12 @@ impl CreateCircuitBuilder {
                     circuit_id,
                 )))
             }
+            #[cfg(feature = "circuit-id-policy")]
+            None => self
+                .circuit_id_format
+                .unwrap_or(CircuitIdFormat::Random)
+                .generate(),
+            #[cfg(not(feature = "circuit-id-policy"))]
             None => generate_random_base62_string(5) + "-" + &generate_random_base62_string(5),
         };
 
@@ -224,6 +254,7 @@ impl CreateCircuitBuilder {
             application_metadata,
             comments,
             display_name,
+            qos: self.qos,
         };
 
         Ok(create_circuit_message)
@@ -316,6 +347,8 @@ impl SplinterServiceBuilder {
 pub struct SplinterNodeBuilder {
     node_id: Option<String>,
     endpoints: Option<Vec<String>>,
+    relay: bool,
+    observer: bool,
 }
 
 impl SplinterNodeBuilder {
@@ -341,6 +374,16 @@ impl SplinterNodeBuilder {
         self
     }
 
+    pub fn with_relay(mut self, relay: bool) -> SplinterNodeBuilder {
+        self.relay = relay;
+        self
+    }
+
+    pub fn with_observer(mut self, observer: bool) -> SplinterNodeBuilder {
+        self.observer = observer;
+        self
+    }
+
     pub fn build(self) -> Result<SplinterNode, BuilderError> {
         let node_id = self
             .node_id
@@ -350,16 +393,105 @@ impl SplinterNodeBuilder {
             .endpoints
             .ok_or_else(|| BuilderError::MissingField("endpoints".to_string()))?;
 
-        let node = SplinterNode { node_id, endpoints };
+        let node = SplinterNode {
+            node_id,
+            endpoints,
+            relay: self.relay,
+            observer: self.observer,
+        };
 
         Ok(node)
     }
 }
 
+/// Builds a vote on a circuit proposal
+#[derive(Default, Clone)]
+pub struct CircuitProposalVoteBuilder {
+    circuit_id: Option<String>,
+    circuit_hash: Option<String>,
+    vote: Option<Vote>,
+    #[cfg(feature = "admin-vote-delegation")]
+    delegation: Option<VoteDelegation>,
+}
+
+impl CircuitProposalVoteBuilder {
+    pub fn new() -> Self {
+        CircuitProposalVoteBuilder::default()
+    }
+
+    pub fn circuit_id(&self) -> Option<String> {
+        self.circuit_id.clone()
+    }
+
+    pub fn circuit_hash(&self) -> Option<String> {
+        self.circuit_hash.clone()
+    }
+
+    pub fn vote(&self) -> Option<Vote> {
+        self.vote.clone()
+    }
+
+    /// Sets the ID of the circuit whose proposal is voted on
+    pub fn with_circuit_id(mut self, circuit_id: &str) -> CircuitProposalVoteBuilder {
+        self.circuit_id = Some(circuit_id.into());
+        self
+    }
+
+    /// Sets the hash of the proposed circuit, as listed in the proposal
+    pub fn with_circuit_hash(mut self, circuit_hash: &str) -> CircuitProposalVoteBuilder {
+        self.circuit_hash = Some(circuit_hash.into());
+        self
+    }
+
+    pub fn with_vote(mut self, vote: Vote) -> CircuitProposalVoteBuilder {
+        self.vote = Some(vote);
+        self
+    }
+
+    /// Sets the delegation of the voting node's authority to the key that signs the vote, which
+    /// is required for votes cast by a delegate
+    #[cfg(feature = "admin-vote-delegation")]
+    pub fn with_delegation(mut self, delegation: VoteDelegation) -> CircuitProposalVoteBuilder {
+        self.delegation = Some(delegation);
+        self
+    }
+
+    pub fn build(self) -> Result<CircuitProposalVote, BuilderError> {
+        let circuit_id = match self.circuit_id {
+            Some(circuit_id) if is_valid_circuit_id(&circuit_id) => circuit_id,
+            Some(circuit_id) => {
+                return Err(BuilderError::InvalidField(format!(
+                    "circuit_id is invalid ({})",
+                    circuit_id
+                )))
+            }
+            None => return Err(BuilderError::MissingField("circuit_id".to_string())),
+        };
+
+        let circuit_hash = self
+            .circuit_hash
+            .ok_or_else(|| BuilderError::MissingField("circuit_hash".to_string()))?;
+
+        let vote = self
+            .vote
+            .ok_or_else(|| BuilderError::MissingField("vote".to_string()))?;
+
+        Ok(CircuitProposalVote {
+            circuit_id,
+            circuit_hash,
+            vote,
+            #[cfg(feature = "admin-vote-delegation")]
+            delegation: self.delegation,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub enum BuilderError {
     InvalidField(String),
     MissingField(String),
+    /// The payload could not be signed
+    SigningFailed(String),
 }
 
 impl StdError for BuilderError {}
@@ -369,6 +501,7 @@ impl std::fmt::Display for BuilderError {
         match *self {
             BuilderError::InvalidField(ref s) => write!(f, "invalid field: {}", s),
             BuilderError::MissingField(ref s) => write!(f, "missing field: {}", s),
+            BuilderError::SigningFailed(ref s) => write!(f, "failed to sign payload: {}", s),
         }
     }
 }
@@ -747,4 +880,45 @@ mod tests {
             Err(err) => panic!("Got unexpected error: {}", err),
         }
     }
+
+    /// Verify that the `CircuitProposalVoteBuilder` builds a vote when all fields are set.
+    #[test]
+    fn vote_builder_success() {
+        let vote = CircuitProposalVoteBuilder::new()
+            .with_circuit_id("0123a-bcDEF")
+            .with_circuit_hash("abcdef")
+            .with_vote(Vote::Accept)
+            .build()
+            .expect("failed to build vote");
+
+        assert_eq!(&vote.circuit_id, "0123a-bcDEF");
+        assert_eq!(&vote.circuit_hash, "abcdef");
+        assert_eq!(vote.vote, Vote::Accept);
+    }
+
+    /// Verify that the `CircuitProposalVoteBuilder` fails to build when a field is not set or the
+    /// circuit ID is invalid.
+    #[test]
+    fn vote_builder_invalid() {
+        match CircuitProposalVoteBuilder::new()
+            .with_circuit_id("0123a-bcDEF")
+            .with_vote(Vote::Reject)
+            .build()
+        {
+            Ok(vote) => panic!("Build did not fail; got vote: {:?}", vote),
+            Err(BuilderError::MissingField(_)) => {}
+            Err(err) => panic!("Got unexpected error: {}", err),
+        }
+
+        match CircuitProposalVoteBuilder::new()
+            .with_circuit_id("invalid")
+            .with_circuit_hash("abcdef")
+            .with_vote(Vote::Reject)
+            .build()
+        {
+            Ok(vote) => panic!("Build did not fail; got vote: {:?}", vote),
+            Err(BuilderError::InvalidField(_)) => {}
+            Err(err) => panic!("Got unexpected error: {}", err),
+        }
+    }
 }
@@ -13,10 +13,16 @@
 // limitations under the License.
 
 pub mod builders;
+pub mod payload;
 
 use protobuf::{self, RepeatedField};
 
+#[cfg(feature = "circuit-id-policy")]
+use crate::admin::circuit_id::is_uuid_v7_format;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::VoteDelegation;
 use crate::admin::store;
+use crate::circuit::qos::QosLimits;
 use crate::hex::{as_hex, deserialize_hex};
 use crate::protos::admin::{self, CircuitCreateRequest};
 
@@ -41,6 +47,8 @@ pub struct CreateCircuit {
     #[serde(default)]
     pub comments: Option<String>,
     pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "QosLimits::is_unlimited")]
+    pub qos: QosLimits,
 }
 
 impl CreateCircuit {
@@ -111,6 +119,7 @@ impl CreateCircuit {
             application_metadata: proto.take_application_metadata(),
             comments,
             display_name,
+            qos: qos_from_proto(&proto),
         })
     }
 
@@ -142,6 +151,8 @@ impl CreateCircuit {
             circuit.set_display_name(display_name);
         }
 
+        set_qos(&mut circuit, &self.qos);
+
         match self.authorization_type {
             AuthorizationType::Trust => {
                 circuit
@@ -171,9 +182,48 @@ impl CreateCircuit {
     }
 }
 
+/// Reads the quality-of-service limits of a circuit; a limit of 0 is not set.
+pub(crate) fn qos_from_proto(proto: &admin::Circuit) -> QosLimits {
+    let mut qos = QosLimits::new();
+    if proto.get_max_message_size() > 0 {
+        qos = qos.with_max_message_size(proto.get_max_message_size());
+    }
+    if proto.get_max_messages_per_second() > 0 {
+        qos = qos.with_max_messages_per_second(proto.get_max_messages_per_second());
+    }
+    if proto.get_max_queued_bytes() > 0 {
+        qos = qos.with_max_queued_bytes(proto.get_max_queued_bytes());
+    }
+    qos
+}
+
+/// Sets the quality-of-service limits of a circuit; a limit that is not set is left as 0.
+pub(crate) fn set_qos(proto: &mut admin::Circuit, qos: &QosLimits) {
+    proto.set_max_message_size(qos.max_message_size().unwrap_or(0));
+    proto.set_max_messages_per_second(qos.max_messages_per_second().unwrap_or(0));
+    proto.set_max_queued_bytes(qos.max_queued_bytes().unwrap_or(0));
+}
+
 /// Determines if a circuit ID is valid. A valid circuit ID is an 11 character string composed of
-/// two, 5 character base62 strings joined with a '-' (example: abcDE-F0123).
+/// two, 5 character base62 strings joined with a '-' (example: abcDE-F0123), or, with the
+/// `circuit-id-policy` feature, a lowercase, hyphenated UUIDv7.
+///
+/// Whether a valid circuit ID is accepted for a new proposal is decided by the admin service's
+/// circuit ID policy.
 pub fn is_valid_circuit_id(circuit_id: &str) -> bool {
+    #[cfg(feature = "circuit-id-policy")]
+    {
+        is_random_circuit_id(circuit_id) || is_uuid_v7_format(circuit_id)
+    }
+    #[cfg(not(feature = "circuit-id-policy"))]
+    {
+        is_random_circuit_id(circuit_id)
+    }
+}
+
+/// Determines if a circuit ID is in the random format: two, 5 character base62 strings joined
+/// with a '-'.
+pub(crate) fn is_random_circuit_id(circuit_id: &str) -> bool {
     let mut split = circuit_id.splitn(2, '-');
     let is_two_parts = split.clone().count() == 2;
     let are_parts_valid = split.all(|part| {
@@ -220,6 +270,10 @@ impl Default for RouteType {
 pub struct SplinterNode {
     pub node_id: String,
     pub endpoints: Vec<String>,
+    #[serde(default)]
+    pub relay: bool,
+    #[serde(default)]
+    pub observer: bool,
 }
 
 impl SplinterNode {
@@ -228,6 +282,8 @@ impl SplinterNode {
 
         proto.set_node_id(self.node_id);
         proto.set_endpoints(self.endpoints.into());
+        proto.set_relay(self.relay);
+        proto.set_observer(self.observer);
 
         proto
     }
@@ -236,6 +292,8 @@ impl SplinterNode {
         Ok(Self {
             node_id: proto.take_node_id(),
             endpoints: proto.take_endpoints().into(),
+            relay: proto.get_relay(),
+            observer: proto.get_observer(),
         })
     }
 }
@@ -306,6 +364,10 @@ pub struct CircuitProposal {
     #[serde(deserialize_with = "deserialize_hex")]
     pub requester: Vec<u8>,
     pub requester_node_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
 }
 
 impl CircuitProposal {
@@ -337,6 +399,8 @@ impl CircuitProposal {
             votes,
             requester: proto.take_requester(),
             requester_node_id: proto.take_requester_node_id(),
+            created_at: None,
+            updated_at: None,
         })
     }
 
@@ -404,6 +468,8 @@ impl From<store::CircuitProposal> for CircuitProposal {
                 .map(|node| SplinterNode {
                     node_id: node.node_id().to_string(),
                     endpoints: node.endpoints().to_vec(),
+                    relay: node.is_relay(),
+                    observer: node.is_observer(),
                 })
                 .collect::<Vec<SplinterNode>>(),
             authorization_type: AuthorizationType::Trust,
@@ -417,6 +483,7 @@ impl From<store::CircuitProposal> for CircuitProposal {
                 .unwrap_or_default(),
             comments: store_circuit.comments().clone(),
             display_name: store_circuit.display_name().clone(),
+            qos: *store_circuit.qos(),
         };
 
         Self {
@@ -442,6 +509,8 @@ impl From<store::CircuitProposal> for CircuitProposal {
                 .collect(),
             requester: store_proposal.requester().into(),
             requester_node_id: store_proposal.requester_node_id().into(),
+            created_at: store_proposal.created_at(),
+            updated_at: store_proposal.updated_at(),
         }
     }
 }
@@ -460,6 +529,11 @@ pub struct CircuitProposalVote {
     pub circuit_id: String,
     pub circuit_hash: String,
     pub vote: Vote,
+    /// The delegation of the voting node's authority to the signer of the vote, if the vote is
+    /// cast by a delegate
+    #[cfg(feature = "admin-vote-delegation")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<VoteDelegation>,
 }
 
 impl CircuitProposalVote {
@@ -476,6 +550,12 @@ impl CircuitProposalVote {
             circuit_id: proto.take_circuit_id(),
             circuit_hash: proto.take_circuit_hash(),
             vote,
+            #[cfg(feature = "admin-vote-delegation")]
+            delegation: if proto.has_delegation() {
+                Some(VoteDelegation::from_proto(proto.take_delegation()))
+            } else {
+                None
+            },
         })
     }
 
@@ -487,6 +567,12 @@ impl CircuitProposalVote {
             Vote::Accept => vote.set_vote(admin::CircuitProposalVote_Vote::ACCEPT),
             Vote::Reject => vote.set_vote(admin::CircuitProposalVote_Vote::REJECT),
         }
+        #[cfg(feature = "admin-vote-delegation")]
+        {
+            if let Some(delegation) = self.delegation {
+                vote.set_delegation(delegation.into_proto());
+            }
+        }
         vote
     }
 }
@@ -558,4 +644,41 @@ impl AdminServiceEvent {
             AdminServiceEvent::CircuitReady(proposal) => proposal,
         }
     }
+
+    pub fn into_proto(self) -> Result<admin::AdminServiceEvent, MarshallingError> {
+        let (event_type, proposal, requester) = match self {
+            AdminServiceEvent::ProposalSubmitted(proposal) => (
+                admin::AdminServiceEvent_Type::PROPOSAL_SUBMITTED,
+                proposal,
+                vec![],
+            ),
+            AdminServiceEvent::ProposalVote((proposal, requester)) => (
+                admin::AdminServiceEvent_Type::PROPOSAL_VOTE,
+                proposal,
+                requester,
+            ),
+            AdminServiceEvent::ProposalAccepted((proposal, requester)) => (
+                admin::AdminServiceEvent_Type::PROPOSAL_ACCEPTED,
+                proposal,
+                requester,
+            ),
+            AdminServiceEvent::ProposalRejected((proposal, requester)) => (
+                admin::AdminServiceEvent_Type::PROPOSAL_REJECTED,
+                proposal,
+                requester,
+            ),
+            AdminServiceEvent::CircuitReady(proposal) => (
+                admin::AdminServiceEvent_Type::CIRCUIT_READY,
+                proposal,
+                vec![],
+            ),
+        };
+
+        let mut event = admin::AdminServiceEvent::new();
+        event.set_event_type(event_type);
+        event.set_proposal(proposal.into_proto()?);
+        event.set_requester(requester);
+
+        Ok(event)
+    }
 }
@@ -0,0 +1,226 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Construction of signed circuit management payloads.
+//!
+//! A circuit management payload carries a single action, such as a circuit create request or a
+//! vote on a proposal, and a header that names the action, the SHA-512 digest of the serialized
+//! action, the requester's public key and the node the requester acts on behalf of. The header is
+//! signed by the requester. [`CircuitManagementPayloadBuilder`] assembles and signs the payload,
+//! so applications do not need to construct the protobuf messages themselves.
+
+use cylinder::Signer;
+use openssl::hash::{hash, MessageDigest};
+use protobuf::Message;
+
+use crate::protos::admin::{
+    CircuitManagementPayload, CircuitManagementPayload_Action as Action,
+    CircuitManagementPayload_Header as Header,
+};
+
+use super::builders::BuilderError;
+use super::{CircuitProposalVote, CreateCircuit};
+
+/// An action that can be submitted to the admin service in a circuit management payload
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitManagementAction {
+    /// Propose a new circuit
+    CreateCircuit(CreateCircuit),
+    /// Vote on a circuit proposal
+    Vote(CircuitProposalVote),
+}
+
+impl From<CreateCircuit> for CircuitManagementAction {
+    fn from(create_circuit: CreateCircuit) -> Self {
+        CircuitManagementAction::CreateCircuit(create_circuit)
+    }
+}
+
+impl From<CircuitProposalVote> for CircuitManagementAction {
+    fn from(vote: CircuitProposalVote) -> Self {
+        CircuitManagementAction::Vote(vote)
+    }
+}
+
+/// Builds a signed circuit management payload
+#[derive(Default, Clone)]
+pub struct CircuitManagementPayloadBuilder {
+    action: Option<CircuitManagementAction>,
+    requester_node_id: Option<String>,
+}
+
+impl CircuitManagementPayloadBuilder {
+    pub fn new() -> Self {
+        CircuitManagementPayloadBuilder::default()
+    }
+
+    pub fn action(&self) -> Option<CircuitManagementAction> {
+        self.action.clone()
+    }
+
+    pub fn requester_node_id(&self) -> Option<String> {
+        self.requester_node_id.clone()
+    }
+
+    /// Sets the action carried by the payload
+    pub fn with_action<A: Into<CircuitManagementAction>>(
+        mut self,
+        action: A,
+    ) -> CircuitManagementPayloadBuilder {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Sets the ID of the node the requester acts on behalf of; this is usually the node the
+    /// payload is submitted to
+    pub fn with_requester_node_id(
+        mut self,
+        requester_node_id: &str,
+    ) -> CircuitManagementPayloadBuilder {
+        self.requester_node_id = Some(requester_node_id.into());
+        self
+    }
+
+    /// Builds the payload, signing its header with the given signer
+    pub fn build(self, signer: &dyn Signer) -> Result<CircuitManagementPayload, BuilderError> {
+        let action = self
+            .action
+            .ok_or_else(|| BuilderError::MissingField("action".to_string()))?;
+        let requester_node_id = self
+            .requester_node_id
+            .ok_or_else(|| BuilderError::MissingField("requester_node_id".to_string()))?;
+
+        let mut payload = CircuitManagementPayload::new();
+        let (action_type, action_bytes) = match action {
+            CircuitManagementAction::CreateCircuit(create_circuit) => {
+                let request = create_circuit.into_proto().map_err(|err| {
+                    BuilderError::InvalidField(format!("invalid circuit: {}", err))
+                })?;
+                let bytes = serialize(&request)?;
+                payload.set_circuit_create_request(request);
+                (Action::CIRCUIT_CREATE_REQUEST, bytes)
+            }
+            CircuitManagementAction::Vote(vote) => {
+                let vote = vote.into_proto();
+                let bytes = serialize(&vote)?;
+                payload.set_circuit_proposal_vote(vote);
+                (Action::CIRCUIT_PROPOSAL_VOTE, bytes)
+            }
+        };
+
+        let payload_sha512 = hash(MessageDigest::sha512(), &action_bytes).map_err(|err| {
+            BuilderError::SigningFailed(format!("failed to hash action: {}", err))
+        })?;
+        let public_key = signer
+            .public_key()
+            .map_err(|err| BuilderError::SigningFailed(err.to_string()))?;
+
+        let mut header = Header::new();
+        header.set_action(action_type);
+        header.set_payload_sha512(payload_sha512.to_vec());
+        header.set_requester(public_key.into_bytes());
+        header.set_requester_node_id(requester_node_id);
+        let header_bytes = serialize(&header)?;
+
+        let signature = signer
+            .sign(&header_bytes)
+            .map_err(|err| BuilderError::SigningFailed(err.to_string()))?;
+
+        payload.set_header(header_bytes);
+        payload.set_signature(signature.take_bytes());
+
+        Ok(payload)
+    }
+}
+
+fn serialize<M: Message>(message: &M) -> Result<Vec<u8>, BuilderError> {
+    message
+        .write_to_bytes()
+        .map_err(|err| BuilderError::InvalidField(format!("failed to serialize: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
+
+    use crate::admin::service::messages::builders::CircuitProposalVoteBuilder;
+    use crate::admin::service::messages::Vote;
+
+    /// Verify that a built vote payload carries the vote, a header that names the vote action and
+    /// its digest, and a signature over the header by the signer.
+    #[test]
+    fn test_build_vote_payload() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+
+        let vote = CircuitProposalVoteBuilder::new()
+            .with_circuit_id("0123a-bcDEF")
+            .with_circuit_hash("abcdef")
+            .with_vote(Vote::Accept)
+            .build()
+            .expect("failed to build vote");
+
+        let payload = CircuitManagementPayloadBuilder::new()
+            .with_action(vote.clone())
+            .with_requester_node_id("node-a")
+            .build(&*signer)
+            .expect("failed to build payload");
+
+        let header = protobuf::parse_from_bytes::<Header>(payload.get_header())
+            .expect("failed to parse header");
+        assert_eq!(header.get_action(), Action::CIRCUIT_PROPOSAL_VOTE);
+        assert_eq!(header.get_requester_node_id(), "node-a");
+        assert_eq!(
+            header.get_payload_sha512(),
+            &hash(
+                MessageDigest::sha512(),
+                &vote
+                    .into_proto()
+                    .write_to_bytes()
+                    .expect("failed to serialize")
+            )
+            .expect("failed to hash")[..]
+        );
+        assert_eq!(
+            payload.get_circuit_proposal_vote().get_circuit_id(),
+            "0123a-bcDEF"
+        );
+
+        assert!(context
+            .new_verifier()
+            .verify(
+                payload.get_header(),
+                &Signature::new(payload.get_signature().to_vec()),
+                &PublicKey::new(header.get_requester().to_vec()),
+            )
+            .expect("failed to verify signature"));
+    }
+
+    /// Verify that the builder fails when the action or requester node ID is not set.
+    #[test]
+    fn test_build_payload_missing_fields() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+
+        match CircuitManagementPayloadBuilder::new()
+            .with_requester_node_id("node-a")
+            .build(&*signer)
+        {
+            Err(BuilderError::MissingField(_)) => {}
+            res => panic!("Got unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+}
@@ -13,29 +13,56 @@
 // limitations under the License.
 
 mod consensus;
+#[cfg(feature = "admin-service-debug")]
+pub mod debug;
 pub(crate) mod error;
 #[cfg(feature = "admin-service-event-store")]
 pub mod event;
 #[cfg(not(feature = "admin-service-event-store"))]
 mod mailbox;
 pub(crate) mod messages;
+mod peering;
+#[cfg(feature = "admin-permission-prefetch")]
+mod pipeline;
 pub(super) mod proposal_store;
 mod shared;
 
 use std::any::Any;
-#[cfg(feature = "service-arg-validation")]
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::sync::{mpsc::channel, Arc, Mutex};
+#[cfg(feature = "admin-routing-snapshot")]
+use std::collections::HashSet;
+#[cfg(feature = "admin-routing-snapshot")]
+use std::path::PathBuf;
+use std::sync::{mpsc::channel, Arc, Mutex, Weak};
 use std::thread;
-use std::time::Duration;
 #[cfg(not(feature = "admin-service-event-store"))]
 use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
 use cylinder::Verifier as SignatureVerifier;
 use openssl::hash::{hash, MessageDigest};
 use protobuf::{self, Message};
 
-use crate::admin::store::AdminServiceStore;
+#[cfg(feature = "admin-auto-vote")]
+use crate::admin::auto_vote::AutoVoter;
+#[cfg(feature = "circuit-id-policy")]
+use crate::admin::circuit_id::CircuitIdPolicy;
+#[cfg(feature = "admin-vote-cosigning")]
+use crate::admin::cosign::VoteCosignStore;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::VoteDelegationStore;
+#[cfg(feature = "admin-signature-cache")]
+use crate::admin::signature_cache::CachingVerifier;
+#[cfg(feature = "admin-parallel-startup")]
+use crate::admin::startup::{parallel_map, STARTUP_WORKERS};
+use crate::admin::store::{
+    AdminServiceStore, Circuit as StoreCircuit, CircuitNode as StoreCircuitNode,
+};
+#[cfg(feature = "circuit-relay")]
+use crate::circuit::handlers::AdminRelayRoutes;
+#[cfg(feature = "admin-routing-reconciliation")]
+use crate::circuit::routing::RoutingTableReader;
 use crate::circuit::routing::{self, RoutingTableWriter};
 use crate::consensus::Proposal;
 use crate::hex::to_hex;
@@ -48,25 +75,51 @@ use crate::protos::admin::{
 };
 #[cfg(feature = "registry")]
 use crate::registry::RegistryReader;
+#[cfg(feature = "rest-api-read-only")]
+use crate::rest_api::read_only::ReadOnly;
+#[cfg(feature = "runtime-diagnostics")]
+use crate::runtime::RuntimeMonitor;
 #[cfg(feature = "service-arg-validation")]
 use crate::service::validation::ServiceArgValidator;
 use crate::service::{
     error::{ServiceDestroyError, ServiceError, ServiceStartError, ServiceStopError},
     Service, ServiceMessageContext, ServiceNetworkRegistry,
 };
+#[cfg(feature = "tenancy")]
+use crate::tenant::TenantStore;
 
 use self::consensus::AdminConsensusManager;
+#[cfg(feature = "admin-service-debug")]
+use self::debug::AdminServiceDebug;
 use self::error::{AdminError, Sha256Error};
+use self::peering::PeeringResult;
+#[cfg(feature = "admin-permission-prefetch")]
+use self::pipeline::{
+    payload_circuit_id, PermissionPrefetch, ProposalPipeline, DEFAULT_PIPELINE_QUEUE_SIZE,
+    DEFAULT_PIPELINE_WORKERS,
+};
 use self::proposal_store::{AdminServiceProposals, ProposalStore};
 use self::shared::AdminServiceShared;
 
 pub use self::error::AdminKeyVerifierError;
 pub use self::error::AdminServiceError;
 pub use self::error::AdminSubscriberError;
+pub use self::peering::{MemberPeeringStatus, PeeringStatus};
 pub use self::shared::AdminServiceStatus;
 
 const DEFAULT_COORDINATOR_TIMEOUT: u64 = 30; // 30 seconds
 
+/// The number of circuits between progress messages while circuits are initialized at startup
+const CIRCUIT_PROGRESS_INTERVAL: usize = 1000;
+/// The startup phase that circuit initialization is reported as in the runtime diagnostics
+#[cfg(feature = "runtime-diagnostics")]
+const CIRCUIT_INIT_PHASE: &str = "admin-circuit-init";
+
+/// The role of a key that is permitted to propose circuit changes on behalf of a node
+pub const PROPOSER_ROLE: &str = "proposer";
+/// The role of a key that is permitted to vote on circuit proposals on behalf of a node
+pub const VOTER_ROLE: &str = "voter";
+
 pub trait AdminServiceEventSubscriber: Send {
     #[cfg(not(feature = "admin-service-event-store"))]
     fn handle_event(
@@ -124,6 +177,34 @@ impl Clone for Box<dyn AdminCommands> {
 pub trait AdminKeyVerifier: Send + Sync {
     /// Check if the given `key` is permitted as an admin for the given node.
     fn is_permitted(&self, node_id: &str, key: &[u8]) -> Result<bool, AdminKeyVerifierError>;
+
+    /// Check if the given `key` is permitted to act in the given `role` as an admin for the given
+    /// node.
+    ///
+    /// By default, roles are not distinguished and this is the same as `is_permitted`.
+    fn is_permitted_for_role(
+        &self,
+        node_id: &str,
+        key: &[u8],
+        _role: &str,
+    ) -> Result<bool, AdminKeyVerifierError> {
+        self.is_permitted(node_id, key)
+    }
+}
+
+impl AdminKeyVerifier for Box<dyn AdminKeyVerifier> {
+    fn is_permitted(&self, node_id: &str, key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+        (**self).is_permitted(node_id, key)
+    }
+
+    fn is_permitted_for_role(
+        &self,
+        node_id: &str,
+        key: &[u8],
+        role: &str,
+    ) -> Result<bool, AdminKeyVerifierError> {
+        (**self).is_permitted_for_role(node_id, key, role)
+    }
 }
 
 #[cfg(feature = "registry")]
@@ -188,7 +269,11 @@ pub struct AdminService {
     /// The coordinator timeout for the two-phase commit consensus engine
     coordinator_timeout: Duration,
     consensus: Option<AdminConsensusManager>,
-    peer_connector: PeerManagerConnector,
+    /// Prefetched key permissions, shared with the admin service's key verifier
+    #[cfg(feature = "admin-permission-prefetch")]
+    permission_prefetch: PermissionPrefetch,
+    #[cfg(feature = "admin-permission-prefetch")]
+    proposal_pipeline: Option<ProposalPipeline>,
 }
 
 impl AdminService {
@@ -218,6 +303,13 @@ impl AdminService {
             .subscribe_sender(sender)
             .map_err(|err| ServiceError::UnableToCreate(Box::new(err)))?;
 
+        // Permissions are checked through the prefetch so that the proposal pipeline can look
+        // them up before the shared lock is taken
+        #[cfg(feature = "admin-permission-prefetch")]
+        let permission_prefetch = PermissionPrefetch::new(key_verifier);
+        #[cfg(feature = "admin-permission-prefetch")]
+        let key_verifier: Box<dyn AdminKeyVerifier> = Box::new(permission_prefetch.clone());
+
         let new_service = Self {
             service_id: admin_service_id(node_id),
             node_id: node_id.to_string(),
@@ -226,7 +318,7 @@ impl AdminService {
                 orchestrator.clone(),
                 #[cfg(feature = "service-arg-validation")]
                 service_arg_validators,
-                peer_connector.clone(),
+                peer_connector,
                 admin_store,
                 signature_verifier,
                 key_verifier,
@@ -236,9 +328,23 @@ impl AdminService {
             orchestrator,
             coordinator_timeout,
             consensus: None,
-            peer_connector,
+            #[cfg(feature = "admin-permission-prefetch")]
+            permission_prefetch,
+            #[cfg(feature = "admin-permission-prefetch")]
+            proposal_pipeline: None,
         };
 
+        // Peer references are acquired on a separate thread; it holds a weak reference to the
+        // shared state so that it stops once the service is dropped
+        let peering_admin_shared = Arc::downgrade(&new_service.admin_service_shared);
+        new_service
+            .admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .start_peering_worker(move |result| {
+                handle_peering_result(result, &peering_admin_shared)
+            })?;
+
         let peer_admin_shared = new_service.admin_service_shared.clone();
 
         debug!("Starting admin service's peer manager notification receiver");
@@ -271,6 +377,8 @@ impl AdminService {
     pub fn commands(&self) -> impl AdminCommands + Clone {
         AdminServiceCommands {
             shared: Arc::clone(&self.admin_service_shared),
+            #[cfg(feature = "admin-permission-prefetch")]
+            permission_prefetch: self.permission_prefetch.clone(),
         }
     }
 
@@ -278,37 +386,319 @@ impl AdminService {
         AdminServiceProposals::new(&self.admin_service_shared)
     }
 
-    /// On restart of a splinter node, all services that this node should run on the existing
-    /// circuits should be initialized using the service orchestrator. This may not include all
-    /// services if they are not supported locally. It is expected that some services will be
-    /// started externally.
-    ///
-    /// Also adds peer references for members of the circuits and proposals.
-    fn re_initialize_circuits(&self) -> Result<(), ServiceStartError> {
-        let circuits = self
+    /// Returns a provider of snapshots of this service's internal state.
+    #[cfg(feature = "admin-service-debug")]
+    pub fn debug(&self) -> AdminServiceDebug {
+        AdminServiceDebug::new(&self.admin_service_shared)
+    }
+
+    /// Votes automatically on the proposals this node receives, as decided by the auto-voter's
+    /// policy.
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn set_auto_voter(&self, auto_voter: AutoVoter) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_auto_voter(Some(auto_voter));
+        Ok(())
+    }
+
+    /// Reconciles the routing table with the circuits in the admin store when the service
+    /// starts, after the circuits have been re-initialized. Circuits that are missing from or
+    /// stale in the routing table are repaired and reported in the log.
+    #[cfg(feature = "admin-routing-reconciliation")]
+    pub fn set_routing_table_reader(
+        &self,
+        routing_table_reader: Box<dyn RoutingTableReader>,
+    ) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_routing_table_reader(Some(routing_table_reader));
+        Ok(())
+    }
+
+    /// Records the time taken to initialize the circuits in the admin store at startup in the given
+    /// runtime monitor, which reports it in the runtime diagnostics.
+    #[cfg(feature = "runtime-diagnostics")]
+    pub fn set_runtime_monitor(&self, runtime_monitor: RuntimeMonitor) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_runtime_monitor(Some(runtime_monitor));
+        Ok(())
+    }
+
+    /// Saves the routing table entries of the circuits in the admin store to the given file when
+    /// the service stops. When the service next starts, the entries of circuits that have not
+    /// changed in the meantime are restored from the file rather than rebuilt.
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn set_routing_snapshot_path(&self, path: PathBuf) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_routing_snapshot_path(Some(path));
+        Ok(())
+    }
+
+    /// Requires the votes submitted to this node to carry the number of signatures set by the
+    /// vote thresholds in the given store, counting the co-signatures collected in the store's
+    /// pending votes.
+    #[cfg(feature = "admin-vote-cosigning")]
+    pub fn set_vote_cosign_store(
+        &self,
+        vote_cosign_store: Box<dyn VoteCosignStore>,
+    ) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_vote_cosign_store(Some(vote_cosign_store));
+        Ok(())
+    }
+
+    /// Accepts the votes of the keys that a node's voting authority is delegated to by the
+    /// delegations in the given store.
+    #[cfg(feature = "admin-vote-delegation")]
+    pub fn set_vote_delegation_store(
+        &self,
+        vote_delegation_store: Box<dyn VoteDelegationStore>,
+    ) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_vote_delegation_store(Some(vote_delegation_store));
+        Ok(())
+    }
+
+    /// Verifies signatures with the given caching verifier in place of the admin service's
+    /// signature verifier, and verifies the co-signatures of votes as a batch.
+    #[cfg(feature = "admin-signature-cache")]
+    pub fn set_signature_cache(
+        &self,
+        signature_cache: CachingVerifier,
+    ) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_signature_cache(Some(signature_cache));
+        Ok(())
+    }
+
+    /// Uses the given routes to reach the admin services of proposal members through the
+    /// proposal's relay members, so that members that are reachable through a connected relay
+    /// do not need to be peered with this node.
+    /// Refuses circuit changes submitted to the admin service while the given read-only mode is
+    /// enabled.
+    #[cfg(feature = "rest-api-read-only")]
+    pub fn set_read_only(&self, read_only: ReadOnly) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_read_only(Some(read_only));
+        Ok(())
+    }
+
+    #[cfg(feature = "circuit-relay")]
+    pub fn set_relay_routes(&self, relay_routes: AdminRelayRoutes) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_relay_routes(Some(relay_routes));
+        Ok(())
+    }
+
+    /// Sets the circuit ID formats that are accepted for new circuit proposals; by default, only
+    /// the random format is accepted.
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn set_circuit_id_policy(
+        &self,
+        circuit_id_policy: CircuitIdPolicy,
+    ) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_circuit_id_policy(circuit_id_policy);
+        Ok(())
+    }
+
+    /// Isolates the tenants of this node using the assignments in the given store: a tenant's keys
+    /// may only propose and vote on circuits of their own tenant, and the circuits they propose or
+    /// vote on are assigned to their tenant.
+    #[cfg(feature = "tenancy")]
+    pub fn set_tenant_store(&self, tenant_store: Box<dyn TenantStore>) -> Result<(), ServiceError> {
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?
+            .set_tenant_store(Some(tenant_store));
+        Ok(())
+    }
+
+    /// Rebuilds the routing table from the admin store, logging any discrepancies. A failure to
+    /// reconcile is logged rather than returned, since the circuits have already been routed by
+    /// `re_initialize_circuits`.
+    #[cfg(feature = "admin-routing-reconciliation")]
+    fn reconcile_routing_table(&self) -> Result<(), ServiceStartError> {
+        let result = self
             .admin_service_shared
             .lock()
             .map_err(|_| {
                 ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
             })?
-            .get_circuits()
-            .map_err(|err| {
-                ServiceStartError::Internal(format!("Unable to get circuits: {}", err))
-            })?;
+            .reconcile_routing_table();
+
+        match result {
+            Ok(Some(rebuild)) if rebuild.has_discrepancies() => warn!(
+                "Reconciled routing table with admin store: added {:?}, removed {:?}, changed \
+                 {:?}, missing node information for {:?}",
+                rebuild.added_circuits,
+                rebuild.removed_circuits,
+                rebuild.changed_circuits,
+                rebuild.missing_nodes
+            ),
+            Ok(Some(rebuild)) => debug!(
+                "Routing table matches admin store ({} circuits)",
+                rebuild.circuit_count
+            ),
+            Ok(None) => (),
+            Err(err) => error!("Unable to reconcile routing table: {}", err),
+        }
 
-        let nodes = self
+        Ok(())
+    }
+
+    /// Restores the routing table entries of the unchanged circuits from the routing snapshot,
+    /// returning the IDs of the restored circuits. A failure to restore is logged rather than
+    /// returned, since the circuits can still be routed from the admin store.
+    #[cfg(feature = "admin-routing-snapshot")]
+    fn restore_routing_snapshot(
+        &self,
+        circuits: &[StoreCircuit],
+        nodes: &BTreeMap<String, StoreCircuitNode>,
+    ) -> Result<HashSet<String>, ServiceStartError> {
+        let result = self
             .admin_service_shared
             .lock()
             .map_err(|_| {
                 ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
             })?
-            .get_nodes()
-            .map_err(|err| ServiceStartError::Internal(format!("Unable to get nodes: {}", err)))?;
+            .restore_routing_snapshot(circuits, nodes);
+
+        match result {
+            Ok(restored) => {
+                debug!(
+                    "Restored {} of {} circuits from routing snapshot",
+                    restored.len(),
+                    circuits.len()
+                );
+                Ok(restored)
+            }
+            Err(err) => {
+                error!("Unable to restore routing snapshot: {}", err);
+                Ok(HashSet::new())
+            }
+        }
+    }
+
+    /// Saves the routing table entries of the circuits in the admin store to the routing
+    /// snapshot. A failure to save is logged rather than returned, since the snapshot is only
+    /// used to speed up the next start.
+    #[cfg(feature = "admin-routing-snapshot")]
+    fn save_routing_snapshot(&self) -> Result<(), ServiceStopError> {
+        let result = self
+            .admin_service_shared
+            .lock()
+            .map_err(|_| {
+                ServiceStopError::PoisonedLock("the admin shared lock was poisoned".into())
+            })?
+            .save_routing_snapshot();
+
+        match result {
+            Ok(Some(count)) => debug!("Saved {} circuits to routing snapshot", count),
+            Ok(None) => (),
+            Err(err) => error!("Unable to save routing snapshot: {}", err),
+        }
+
+        Ok(())
+    }
+
+    /// Pairs each of the given circuits with its routing table entry, building the entries on
+    /// several threads.
+    #[cfg(feature = "admin-parallel-startup")]
+    fn build_routing_entries(
+        &self,
+        circuits: Vec<StoreCircuit>,
+        nodes: &Arc<BTreeMap<String, StoreCircuitNode>>,
+    ) -> Result<Vec<(StoreCircuit, RoutingEntry)>, ServiceStartError> {
+        let node_id = self.node_id.clone();
+        let nodes = Arc::clone(nodes);
+        parallel_map(circuits, STARTUP_WORKERS, move |circuit| {
+            let entry = routing_entry(&node_id, &circuit, &nodes);
+            (circuit, entry)
+        })
+        .map_err(|err| ServiceStartError::Internal(err.to_string()))
+    }
+
+    /// Pairs each of the given circuits with its routing table entry.
+    #[cfg(not(feature = "admin-parallel-startup"))]
+    fn build_routing_entries(
+        &self,
+        circuits: Vec<StoreCircuit>,
+        nodes: &BTreeMap<String, StoreCircuitNode>,
+    ) -> Result<Vec<(StoreCircuit, RoutingEntry)>, ServiceStartError> {
+        Ok(circuits
+            .into_iter()
+            .map(|circuit| {
+                let entry = routing_entry(&self.node_id, &circuit, nodes);
+                (circuit, entry)
+            })
+            .collect())
+    }
+
+    /// On restart of a splinter node, all services that this node should run on the existing
+    /// circuits should be initialized using the service orchestrator. This may not include all
+    /// services if they are not supported locally. It is expected that some services will be
+    /// started externally.
+    ///
+    /// Also adds peer references for members of the circuits and proposals.
+    fn re_initialize_circuits(&self) -> Result<(), ServiceStartError> {
+        let start = Instant::now();
+
+        let circuits = {
+            let admin_service_shared = self.admin_service_shared.lock().map_err(|_| {
+                ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
+            })?;
+            #[cfg(feature = "admin-parallel-startup")]
+            let circuits = admin_service_shared.load_circuits(STARTUP_WORKERS);
+            #[cfg(not(feature = "admin-parallel-startup"))]
+            let circuits = admin_service_shared
+                .get_circuits()
+                .map(|circuits| circuits.collect::<Vec<_>>());
+            circuits.map_err(|err| {
+                ServiceStartError::Internal(format!("Unable to get circuits: {}", err))
+            })?
+        };
+
+        let nodes = Arc::new(
+            self.admin_service_shared
+                .lock()
+                .map_err(|_| {
+                    ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
+                })?
+                .get_nodes()
+                .map_err(|err| {
+                    ServiceStartError::Internal(format!("Unable to get nodes: {}", err))
+                })?,
+        );
+
+        #[cfg(feature = "admin-routing-snapshot")]
+        let restored = self.restore_routing_snapshot(&circuits, &nodes)?;
+
+        let circuit_count = circuits.len();
+        let circuits = self.build_routing_entries(circuits, &nodes)?;
 
         let orchestrator = self.orchestrator.lock().map_err(|_| {
             ServiceStartError::PoisonedLock("the admin orchestrator lock was poisoned".into())
         })?;
-        let mut peer_refs = vec![];
         // start all services of the supported types
         let mut writer = self
             .admin_service_shared
@@ -318,97 +708,121 @@ impl AdminService {
             })?
             .routing_table_writer();
 
-        for circuit in circuits {
-            let mut routing_members = vec![];
-            // restart all peer in the circuit
+        let mut services = vec![];
+        for (index, (circuit, (routing_circuit, routing_members))) in
+            circuits.into_iter().enumerate()
+        {
+            // restart all peer in the circuit; the references are acquired asynchronously so
+            // unreachable members don't delay startup
             for member in circuit.members().iter() {
                 if member != &self.node_id {
                     if let Some(node) = nodes.get(member) {
-                        let peer_ref = self
-                            .peer_connector
-                            .add_peer_ref(member.to_string(), node.endpoints().to_vec());
-
-                        if let Ok(peer_ref) = peer_ref {
-                            peer_refs.push(peer_ref);
-                        } else {
-                            info!("Unable to peer with {} at this time", member);
-                        }
-
-                        routing_members.push(routing::CircuitNode::new(
-                            member.to_string(),
-                            node.endpoints().to_vec(),
-                        ))
-                    } else {
-                        error!("Missing node information for {}", member);
+                        self.admin_service_shared
+                            .lock()
+                            .map_err(|_| {
+                                ServiceStartError::PoisonedLock(
+                                    "the admin shared lock was poisoned".into(),
+                                )
+                            })?
+                            .request_peering(
+                                circuit.circuit_id(),
+                                member,
+                                node.endpoints().to_vec(),
+                            );
                     }
                 }
             }
 
             // Get all services this node is allowed to run and the orchestrator has a factory for
-
-            let routing_services = circuit
-                .roster()
-                .iter()
-                .map(|service| {
-                    routing::Service::new(
-                        service.service_id().to_string(),
-                        service.service_type().to_string(),
-                        service.node_id().to_string(),
-                        service.arguments().to_vec(),
-                    )
-                })
-                .collect();
-
-            let services = circuit
-                .roster()
-                .iter()
-                .filter(|service| {
-                    service.node_id() == self.node_id
-                        && orchestrator
-                            .supported_service_types()
-                            .contains(&service.service_type().to_string())
-                })
-                .collect::<Vec<_>>();
-
-            writer
-                .add_circuit(
-                    circuit.circuit_id().to_string(),
-                    routing::Circuit::new(
+            services.extend(
+                circuit
+                    .roster()
+                    .iter()
+                    .filter(|service| {
+                        service.node_id() == self.node_id
+                            && orchestrator
+                                .supported_service_types()
+                                .contains(&service.service_type().to_string())
+                    })
+                    .map(|service| {
+                        let service_definition = ServiceDefinition {
+                            circuit: circuit.circuit_id().into(),
+                            service_id: service.service_id().into(),
+                            service_type: service.service_type().into(),
+                        };
+
+                        let service_arguments = service
+                            .arguments()
+                            .iter()
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect::<HashMap<_, _>>();
+
+                        (service_definition, service_arguments)
+                    }),
+            );
+
+            // circuits restored from the routing snapshot are already in the routing table
+            #[cfg(feature = "admin-routing-snapshot")]
+            let routed = restored.contains(circuit.circuit_id());
+            #[cfg(not(feature = "admin-routing-snapshot"))]
+            let routed = false;
+
+            if !routed {
+                writer
+                    .add_circuit(
                         circuit.circuit_id().to_string(),
-                        routing_services,
-                        circuit.members().to_vec(),
-                    ),
-                    routing_members,
-                )
-                .map_err(|err| ServiceStartError::Internal(err.reduce_to_string()))?;
-
-            // Start all services
-            for service in services {
-                let service_definition = ServiceDefinition {
-                    circuit: circuit.circuit_id().into(),
-                    service_id: service.service_id().into(),
-                    service_type: service.service_type().into(),
-                };
+                        routing_circuit,
+                        routing_members,
+                    )
+                    .map_err(|err| ServiceStartError::Internal(err.reduce_to_string()))?;
+            }
 
-                let service_arguments = service
-                    .arguments()
-                    .iter()
-                    .map(|(key, value)| (key.clone(), value.clone()))
-                    .collect();
+            if (index + 1) % CIRCUIT_PROGRESS_INTERVAL == 0 {
+                info!("Routed {} of {} circuits", index + 1, circuit_count);
+            }
+        }
 
-                if let Err(err) =
-                    orchestrator.initialize_service(service_definition.clone(), service_arguments)
-                {
-                    error!(
-                        "Unable to start service {} on circuit {}: {}",
-                        service.service_id(),
-                        circuit.circuit_id(),
-                        err
-                    );
-                }
+        // Start all services
+        let service_count = services.len();
+        #[cfg(feature = "admin-parallel-startup")]
+        let results = orchestrator
+            .initialize_services(services, STARTUP_WORKERS)
+            .map_err(|err| ServiceStartError::Internal(err.to_string()))?;
+        #[cfg(not(feature = "admin-parallel-startup"))]
+        let results = services
+            .into_iter()
+            .map(|(service_definition, service_arguments)| {
+                let result =
+                    orchestrator.initialize_service(service_definition.clone(), service_arguments);
+                (service_definition, result)
+            })
+            .collect::<Vec<_>>();
+
+        for (service_definition, result) in results {
+            if let Err(err) = result {
+                error!(
+                    "Unable to start service {} on circuit {}: {}",
+                    service_definition.service_id, service_definition.circuit, err
+                );
             }
         }
 
+        let elapsed = start.elapsed();
+        info!(
+            "Initialized {} circuits and {} services in {} ms",
+            circuit_count,
+            service_count,
+            elapsed.as_millis()
+        );
+
+        #[cfg(feature = "runtime-diagnostics")]
+        self.admin_service_shared
+            .lock()
+            .map_err(|_| {
+                ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
+            })?
+            .add_startup_phase(CIRCUIT_INIT_PHASE, elapsed);
+
         let proposals = self
             .admin_service_shared
             .lock()
@@ -420,31 +834,80 @@ impl AdminService {
                 ServiceStartError::Internal(format!("Unable to get circuit proposals: {}", err))
             })?;
 
+        let mut shared = self.admin_service_shared.lock().map_err(|_| {
+            ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
+        })?;
         for proposal in proposals {
             // connect to all peers in the circuit proposal
-            for member in proposal.circuit().members().iter() {
-                if member.node_id() != self.node_id {
-                    let peer_ref = self
-                        .peer_connector
-                        .add_peer_ref(member.node_id().to_string(), member.endpoints().to_vec());
-
-                    if let Ok(peer_ref) = peer_ref {
-                        peer_refs.push(peer_ref);
-                    } else {
-                        info!("Unable to peer with {} at this time", member.node_id());
-                    }
-                }
-            }
+            shared.request_proposal_peering(
+                proposal.circuit_id(),
+                proposal
+                    .circuit()
+                    .members()
+                    .iter()
+                    .map(|member| (member.node_id().to_string(), member.endpoints().to_vec()))
+                    .collect(),
+            );
         }
 
-        self.admin_service_shared
-            .lock()
-            .map_err(|_| {
-                ServiceStartError::PoisonedLock("the admin shared lock was poisoned".into())
-            })?
-            .add_peer_refs(peer_refs);
         Ok(())
     }
+
+    /// Queues a lookup of the requester's permission on the proposal pipeline, so that it is
+    /// ready when consensus validates the proposal, and hands the proposal to the shared state.
+    ///
+    /// Errors from handing over the proposal are returned, as they are without the prefetch.
+    #[cfg(feature = "admin-permission-prefetch")]
+    fn handle_proposed_circuit(
+        &self,
+        proposal: Proposal,
+        circuit_payload: &CircuitManagementPayload,
+        message_sender: String,
+    ) -> Result<(), ServiceError> {
+        let prefetch_payload = circuit_payload.clone();
+        let permission_prefetch = self.permission_prefetch.clone();
+        if let Err(err) = self
+            .proposal_pipeline
+            .as_ref()
+            .ok_or(ServiceError::NotStarted)?
+            .submit(payload_circuit_id(circuit_payload), move || {
+                permission_prefetch.prefetch_payload(&prefetch_payload)
+            })
+        {
+            // the permission is then looked up when the proposal is validated
+            debug!("Unable to prefetch proposal permission: {}", err);
+        }
+
+        let mut admin_service_shared = self
+            .admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?;
+
+        admin_service_shared.handle_proposed_circuit(
+            proposal,
+            circuit_payload.clone(),
+            message_sender,
+        )
+    }
+
+    #[cfg(not(feature = "admin-permission-prefetch"))]
+    fn handle_proposed_circuit(
+        &self,
+        proposal: Proposal,
+        circuit_payload: &CircuitManagementPayload,
+        message_sender: String,
+    ) -> Result<(), ServiceError> {
+        let mut admin_service_shared = self
+            .admin_service_shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("the admin shared lock was poisoned".into()))?;
+
+        admin_service_shared.handle_proposed_circuit(
+            proposal,
+            circuit_payload.clone(),
+            message_sender,
+        )
+    }
 }
 
 impl Service for AdminService {
@@ -488,6 +951,19 @@ impl Service for AdminService {
 
         self.consensus = Some(consensus);
 
+        #[cfg(feature = "admin-permission-prefetch")]
+        {
+            self.proposal_pipeline = Some(
+                ProposalPipeline::new(DEFAULT_PIPELINE_WORKERS, DEFAULT_PIPELINE_QUEUE_SIZE)
+                    .map_err(|err| {
+                        ServiceStartError::Internal(format!(
+                            "Unable to start proposal pipeline: {}",
+                            err
+                        ))
+                    })?,
+            );
+        }
+
         self.admin_service_shared
             .lock()
             .map_err(|_| {
@@ -497,6 +973,9 @@ impl Service for AdminService {
 
         self.re_initialize_circuits()?;
 
+        #[cfg(feature = "admin-routing-reconciliation")]
+        self.reconcile_routing_table()?;
+
         self.admin_service_shared
             .lock()
             .map_err(|_| {
@@ -513,6 +992,17 @@ impl Service for AdminService {
     ) -> Result<(), ServiceStopError> {
         service_registry.disconnect(&self.service_id)?;
 
+        #[cfg(feature = "admin-routing-snapshot")]
+        self.save_routing_snapshot()?;
+
+        // Stop the permission lookups before consensus
+        #[cfg(feature = "admin-permission-prefetch")]
+        {
+            if let Some(proposal_pipeline) = self.proposal_pipeline.take() {
+                proposal_pipeline.shutdown();
+            }
+        }
+
         // Shutdown consensus
         self.consensus
             .take()
@@ -599,13 +1089,10 @@ impl Service for AdminService {
                     .into();
                 proposal.summary = expected_hash;
                 proposal.consensus_data = required_verifiers.to_vec();
-                let mut admin_service_shared = self.admin_service_shared.lock().map_err(|_| {
-                    ServiceError::PoisonedLock("the admin shared lock was poisoned".into())
-                })?;
 
-                admin_service_shared.handle_proposed_circuit(
+                self.handle_proposed_circuit(
                     proposal,
-                    circuit_payload.clone(),
+                    circuit_payload,
                     message_context.sender.to_string(),
                 )
             }
@@ -689,6 +1176,88 @@ impl Service for AdminService {
     }
 }
 
+/// Passes a result from the peering worker to the admin service's shared state. Returns `false`
+/// if the admin service has been dropped.
+/// The routing table entry of a circuit and the members that are added with it
+type RoutingEntry = (routing::Circuit, Vec<routing::CircuitNode>);
+
+/// Builds the routing table entry of a circuit from the admin store.
+fn routing_entry(
+    node_id: &str,
+    circuit: &StoreCircuit,
+    nodes: &BTreeMap<String, StoreCircuitNode>,
+) -> RoutingEntry {
+    let mut routing_members = vec![];
+    for member in circuit.members().iter() {
+        if member != node_id {
+            if let Some(node) = nodes.get(member) {
+                routing_members.push(routing::CircuitNode::new(
+                    member.to_string(),
+                    node.endpoints().to_vec(),
+                ))
+            } else {
+                error!("Missing node information for {}", member);
+            }
+        }
+    }
+
+    let routing_services = circuit
+        .roster()
+        .iter()
+        .map(|service| {
+            routing::Service::new(
+                service.service_id().to_string(),
+                service.service_type().to_string(),
+                service.node_id().to_string(),
+                service.arguments().to_vec(),
+            )
+        })
+        .collect();
+
+    let routing_circuit = routing::Circuit::new(
+        circuit.circuit_id().to_string(),
+        routing_services,
+        circuit.members().to_vec(),
+    )
+    .with_relays(circuit.relay_members().to_vec())
+    .with_observers(circuit.observer_members().to_vec())
+    .with_qos(*circuit.qos());
+
+    (routing_circuit, routing_members)
+}
+
+fn handle_peering_result(
+    result: PeeringResult,
+    admin_shared: &Weak<Mutex<AdminServiceShared>>,
+) -> bool {
+    let admin_shared = match admin_shared.upgrade() {
+        Some(admin_shared) => admin_shared,
+        None => return false,
+    };
+    let mut admin_shared = match admin_shared.lock() {
+        Ok(admin_shared) => admin_shared,
+        Err(_) => {
+            error!("the admin shared lock was poisoned");
+            return false;
+        }
+    };
+
+    match result {
+        PeeringResult::Acquired {
+            circuit_id,
+            peer_ref,
+        } => admin_shared.on_peer_ref_acquired(&circuit_id, peer_ref),
+        PeeringResult::Failed {
+            circuit_id,
+            peer_id,
+            attempts,
+            error,
+        } => admin_shared.on_peering_failed(&circuit_id, &peer_id, attempts, error),
+    }
+
+    true
+}
+
 fn handle_peer_manager_notification(
     notification: PeerManagerNotification,
     admin_shared: &mut AdminServiceShared,
@@ -704,12 +1273,20 @@ fn handle_peer_manager_notification(
             debug!("Peer {} has disconnected", peer);
             admin_shared.on_peer_disconnected(peer);
         }
+        PeerManagerNotification::EndpointsUpdated { peer, endpoints } => {
+            debug!("Peer {} endpoints updated to {:?}", peer, endpoints);
+        }
+        PeerManagerNotification::Removed { peer } => {
+            debug!("Peer {} has been removed", peer);
+        }
     }
 }
 
 #[derive(Clone)]
 struct AdminServiceCommands {
     shared: Arc<Mutex<AdminServiceShared>>,
+    #[cfg(feature = "admin-permission-prefetch")]
+    permission_prefetch: PermissionPrefetch,
 }
 
 impl AdminCommands for AdminServiceCommands {
@@ -717,6 +1294,10 @@ impl AdminCommands for AdminServiceCommands {
         &self,
         circuit_change: CircuitManagementPayload,
     ) -> Result<(), AdminServiceError> {
+        // Look up the requester's permission before the shared lock is taken
+        #[cfg(feature = "admin-permission-prefetch")]
+        self.permission_prefetch.prefetch_payload(&circuit_change);
+
         self.shared
             .lock()
             .map_err(|_| AdminServiceError::general_error("Admin shared lock was lock poisoned"))?
@@ -0,0 +1,199 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Acquires references to the peers needed by circuits and circuit proposals.
+//!
+//! Connecting to a peer may block for as long as the transport takes to time out, so references
+//! are acquired on a separate thread instead of while the admin service's shared state is locked.
+//! This allows proposals whose members are reachable to proceed while other members are offline.
+//! Requests that fail are retried with an exponential backoff until they succeed or are cancelled.
+
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::peer::{PeerManagerConnector, PeerRef};
+
+/// The delay before the first retry of a failed request
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The maximum delay between retries of a failed request
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The status of the connection to a member of a proposed circuit
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeeringStatus {
+    /// A reference to the peer has been requested, but not yet acquired
+    Pending,
+    /// The peer is referenced and a connection is being established
+    Connecting,
+    /// The peer is connected
+    Peered,
+    /// Acquiring a reference to the peer failed and will be retried
+    Retrying { attempts: u32, last_error: String },
+}
+
+/// The status of the connection to a single member of a proposed circuit
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberPeeringStatus {
+    pub node_id: String,
+    pub status: PeeringStatus,
+}
+
+/// Messages handled by the peering worker
+pub(super) enum PeeringMessage {
+    /// Acquire a reference to a peer on behalf of the given circuit or circuit proposal
+    Acquire {
+        circuit_id: String,
+        peer_id: String,
+        endpoints: Vec<String>,
+    },
+    /// Stop acquiring references on behalf of the given circuit or circuit proposal
+    Cancel { circuit_id: String },
+}
+
+/// The outcome of an attempt to acquire a reference to a peer
+pub(super) enum PeeringResult {
+    Acquired {
+        circuit_id: String,
+        peer_ref: PeerRef,
+    },
+    Failed {
+        circuit_id: String,
+        peer_id: String,
+        attempts: u32,
+        error: String,
+    },
+}
+
+/// A request that has not yet succeeded
+struct PeeringAttempt {
+    circuit_id: String,
+    peer_id: String,
+    endpoints: Vec<String>,
+    attempts: u32,
+    due: Instant,
+}
+
+/// Starts the thread that handles peering requests received on `receiver`.
+///
+/// Each result is passed to `on_result`, which returns `false` if the worker should stop. The
+/// worker also stops once all senders for `receiver` have been dropped.
+pub(super) fn start_peering_worker<F>(
+    receiver: Receiver<PeeringMessage>,
+    peer_connector: PeerManagerConnector,
+    on_result: F,
+) -> std::io::Result<thread::JoinHandle<()>>
+where
+    F: Fn(PeeringResult) -> bool + Send + 'static,
+{
+    thread::Builder::new()
+        .name("AdminService Peering".into())
+        .spawn(move || run_peering_worker(receiver, peer_connector, on_result))
+}
+
+fn run_peering_worker<F>(
+    receiver: Receiver<PeeringMessage>,
+    peer_connector: PeerManagerConnector,
+    on_result: F,
+) where
+    F: Fn(PeeringResult) -> bool,
+{
+    let mut attempts: Vec<PeeringAttempt> = vec![];
+
+    loop {
+        // Wait for a new message, or until the next retry is due
+        let message = match attempts.iter().map(|attempt| attempt.due).min() {
+            Some(due) => receiver.recv_timeout(due.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match message {
+            Ok(PeeringMessage::Acquire {
+                circuit_id,
+                peer_id,
+                endpoints,
+            }) => attempts.push(PeeringAttempt {
+                circuit_id,
+                peer_id,
+                endpoints,
+                attempts: 0,
+                due: Instant::now(),
+            }),
+            Ok(PeeringMessage::Cancel { circuit_id }) => {
+                attempts.retain(|attempt| attempt.circuit_id != circuit_id)
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => {
+                debug!("Admin service peering worker shutting down");
+                break;
+            }
+        }
+
+        let now = Instant::now();
+        let (due, waiting): (Vec<_>, Vec<_>) =
+            attempts.into_iter().partition(|attempt| attempt.due <= now);
+        attempts = waiting;
+
+        for mut attempt in due {
+            let result = match peer_connector
+                .add_peer_ref(attempt.peer_id.clone(), attempt.endpoints.clone())
+            {
+                Ok(peer_ref) => PeeringResult::Acquired {
+                    circuit_id: attempt.circuit_id,
+                    peer_ref,
+                },
+                Err(err) => {
+                    attempt.attempts += 1;
+                    attempt.due = Instant::now() + retry_delay(attempt.attempts);
+                    let result = PeeringResult::Failed {
+                        circuit_id: attempt.circuit_id.clone(),
+                        peer_id: attempt.peer_id.clone(),
+                        attempts: attempt.attempts,
+                        error: err.to_string(),
+                    };
+                    attempts.push(attempt);
+                    result
+                }
+            };
+
+            if !on_result(result) {
+                debug!("Admin service peering worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Returns the delay before retrying a request that has failed the given number of times.
+fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .checked_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .map(|delay| std::cmp::min(delay, MAX_RETRY_DELAY))
+        .unwrap_or(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the retry delay doubles with each failed attempt, up to the maximum delay.
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(4), Duration::from_secs(8));
+        assert_eq!(retry_delay(7), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prefetching of the key permissions that circuit proposals need, on a bounded pool of worker
+//! threads.
+//!
+//! Consensus validates proposals one at a time while holding the admin service's shared state
+//! lock, so one slow registry lookup delays every other proposal. When a proposal is received, the
+//! pipeline looks up the permission its requester needs on a worker thread, without the lock, and
+//! records the result in a [`PermissionPrefetch`]. The permission check made when the proposal is
+//! validated is then answered from the prefetched result.
+//!
+//! Only the permission lookups run concurrently. Proposals are still handed to consensus, validated
+//! and voted on one at a time, and errors from handing them over are returned to the caller.
+//!
+//! A prefetched result is used for at most [`PREFETCH_TTL`] after it was looked up; a permission
+//! that is revoked within that time may still be honoured once, just as it would be by a lookup
+//! that was in progress when it was revoked.
+//!
+//! Lookups are assigned to workers by circuit ID, so the lookups for a circuit are made in the
+//! order the proposals were received.
+//!
+//! [`PREFETCH_TTL`]: constant.PREFETCH_TTL.html
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::protos::admin::{
+    CircuitManagementPayload, CircuitManagementPayload_Action, CircuitManagementPayload_Header,
+};
+
+use super::error::{AdminKeyVerifierError, ProposalPipelineError};
+use super::{AdminKeyVerifier, PROPOSER_ROLE, VOTER_ROLE};
+
+/// The default number of worker threads in the proposal pipeline
+pub const DEFAULT_PIPELINE_WORKERS: usize = 4;
+/// The default number of proposals that may be queued for each worker
+pub const DEFAULT_PIPELINE_QUEUE_SIZE: usize = 256;
+
+/// How long a prefetched permission is used before it is looked up again
+pub const PREFETCH_TTL: Duration = Duration::from_secs(1);
+
+type PermissionKey = (String, Vec<u8>, String);
+type PipelineJob = Box<dyn FnOnce() + Send>;
+
+/// An `AdminKeyVerifier` that answers permission checks from results that were looked up ahead of
+/// time.
+///
+/// A prefetched result is used for a single check; checks without a prefetched result are passed
+/// on to the wrapped verifier.
+#[derive(Clone)]
+pub struct PermissionPrefetch {
+    key_verifier: Arc<dyn AdminKeyVerifier>,
+    results: Arc<Mutex<HashMap<PermissionKey, (bool, Instant)>>>,
+}
+
+impl PermissionPrefetch {
+    pub fn new(key_verifier: Box<dyn AdminKeyVerifier>) -> Self {
+        Self {
+            key_verifier: Arc::from(key_verifier),
+            results: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Looks up whether the `key` is permitted to act in the given `role` for the node and stores
+    /// the result for the next check.
+    ///
+    /// Failed lookups are not stored; the check will look the permission up again and report the
+    /// error.
+    pub fn prefetch(&self, node_id: &str, key: &[u8], role: &str) {
+        let permitted = match self.key_verifier.is_permitted_for_role(node_id, key, role) {
+            Ok(permitted) => permitted,
+            Err(err) => {
+                debug!(
+                    "Unable to prefetch {} permission for node {}: {}",
+                    role, node_id, err
+                );
+                return;
+            }
+        };
+
+        match self.results.lock() {
+            Ok(mut results) => {
+                results.retain(|_, (_, fetched_at)| fetched_at.elapsed() < PREFETCH_TTL);
+                results.insert(
+                    (node_id.to_string(), key.to_vec(), role.to_string()),
+                    (permitted, Instant::now()),
+                );
+            }
+            Err(_) => error!("Permission prefetch lock was poisoned"),
+        }
+    }
+
+    /// Prefetches the permission that the requester of the given payload needs for its action.
+    pub fn prefetch_payload(&self, payload: &CircuitManagementPayload) {
+        let header = match protobuf::parse_from_bytes::<CircuitManagementPayload_Header>(
+            payload.get_header(),
+        ) {
+            Ok(header) => header,
+            Err(_) => return,
+        };
+
+        let role = match header.get_action() {
+            CircuitManagementPayload_Action::CIRCUIT_CREATE_REQUEST => PROPOSER_ROLE,
+            CircuitManagementPayload_Action::CIRCUIT_PROPOSAL_VOTE => VOTER_ROLE,
+            CircuitManagementPayload_Action::ACTION_UNSET => return,
+        };
+
+        self.prefetch(header.get_requester_node_id(), header.get_requester(), role)
+    }
+
+    fn take(&self, node_id: &str, key: &[u8], role: &str) -> Option<bool> {
+        let mut results = self.results.lock().ok()?;
+        let (permitted, fetched_at) =
+            results.remove(&(node_id.to_string(), key.to_vec(), role.to_string()))?;
+        if fetched_at.elapsed() < PREFETCH_TTL {
+            Some(permitted)
+        } else {
+            None
+        }
+    }
+}
+
+impl AdminKeyVerifier for PermissionPrefetch {
+    fn is_permitted(&self, node_id: &str, key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+        self.key_verifier.is_permitted(node_id, key)
+    }
+
+    fn is_permitted_for_role(
+        &self,
+        node_id: &str,
+        key: &[u8],
+        role: &str,
+    ) -> Result<bool, AdminKeyVerifierError> {
+        match self.take(node_id, key, role) {
+            Some(permitted) => Ok(permitted),
+            None => self.key_verifier.is_permitted_for_role(node_id, key, role),
+        }
+    }
+}
+
+/// A bounded pool of worker threads that runs proposal jobs, keeping the jobs for each circuit in
+/// order.
+pub struct ProposalPipeline {
+    senders: Vec<SyncSender<(Instant, PipelineJob)>>,
+    join_handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl ProposalPipeline {
+    /// Starts a pipeline with the given number of workers, each of which queues up to
+    /// `queue_size` jobs.
+    pub fn new(workers: usize, queue_size: usize) -> Result<Self, io::Error> {
+        let mut senders = Vec::with_capacity(workers);
+        let mut join_handles = Vec::with_capacity(workers);
+
+        for index in 0..workers.max(1) {
+            let (sender, receiver) = sync_channel::<(Instant, PipelineJob)>(queue_size);
+            let join_handle = thread::Builder::new()
+                .name(format!("AdminProposalPipeline-{}", index))
+                .spawn(move || {
+                    while let Ok((queued_at, job)) = receiver.recv() {
+                        let queue_time = queued_at.elapsed();
+                        let started_at = Instant::now();
+                        job();
+                        let processing_time = started_at.elapsed();
+
+                        trace!(
+                            "Proposal queued for {} ms and processed in {} ms",
+                            queue_time.as_millis(),
+                            processing_time.as_millis()
+                        );
+                        #[cfg(feature = "metrics")]
+                        metrics::histogram!(
+                            "splinter.admin.pipeline_queue_time",
+                            queue_time.as_millis() as u64
+                        );
+                        #[cfg(feature = "metrics")]
+                        metrics::histogram!(
+                            "splinter.admin.pipeline_processing_time",
+                            processing_time.as_millis() as u64
+                        );
+                    }
+                })?;
+
+            senders.push(sender);
+            join_handles.push(join_handle);
+        }
+
+        Ok(Self {
+            senders,
+            join_handles,
+        })
+    }
+
+    /// Queues a job for the given circuit.
+    ///
+    /// Returns an error if the worker for the circuit has a full queue.
+    pub fn submit<F>(&self, circuit_id: &str, job: F) -> Result<(), ProposalPipelineError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let sender = &self.senders[worker_index(circuit_id, self.senders.len())];
+        sender
+            .try_send((Instant::now(), Box::new(job)))
+            .map_err(|err| match err {
+                TrySendError::Full(_) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("splinter.admin.pipeline_rejected", 1);
+                    ProposalPipelineError::QueueFull(circuit_id.to_string())
+                }
+                TrySendError::Disconnected(_) => ProposalPipelineError::Shutdown,
+            })
+    }
+
+    /// Stops the pipeline once the queued jobs have been run.
+    pub fn shutdown(self) {
+        drop(self.senders);
+        for join_handle in self.join_handles {
+            if join_handle.join().is_err() {
+                error!("Admin proposal pipeline worker panicked");
+            }
+        }
+    }
+}
+
+/// Returns the ID of the circuit that the given payload applies to.
+pub fn payload_circuit_id(payload: &CircuitManagementPayload) -> &str {
+    if payload.has_circuit_create_request() {
+        payload
+            .get_circuit_create_request()
+            .get_circuit()
+            .get_circuit_id()
+    } else {
+        payload.get_circuit_proposal_vote().get_circuit_id()
+    }
+}
+
+fn worker_index(circuit_id: &str, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    circuit_id.hash(&mut hasher);
+    (hasher.finish() % workers as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Verify that the jobs for a circuit are run in the order they were submitted, while jobs
+    /// for many circuits are spread across the workers.
+    #[test]
+    fn test_pipeline_circuit_order() {
+        let pipeline = ProposalPipeline::new(4, 100).expect("Unable to start pipeline");
+        let runs = Arc::new(Mutex::new(HashMap::<String, Vec<usize>>::new()));
+
+        for circuit in 0..10 {
+            let circuit_id = format!("circuit-{}", circuit);
+            for sequence in 0..10 {
+                let runs = Arc::clone(&runs);
+                let job_circuit_id = circuit_id.clone();
+                pipeline
+                    .submit(&circuit_id, move || {
+                        runs.lock()
+                            .expect("runs lock poisoned")
+                            .entry(job_circuit_id)
+                            .or_insert_with(Vec::new)
+                            .push(sequence);
+                    })
+                    .expect("Unable to submit job");
+            }
+        }
+
+        pipeline.shutdown();
+
+        let runs = runs.lock().expect("runs lock poisoned");
+        assert_eq!(runs.len(), 10);
+        for sequence in runs.values() {
+            assert_eq!(sequence, &(0..10).collect::<Vec<_>>());
+        }
+    }
+
+    /// Verify that a prefetched permission answers the next check without asking the wrapped
+    /// verifier, and that later checks are passed on to it.
+    #[test]
+    fn test_permission_prefetch() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let prefetch = PermissionPrefetch::new(Box::new(CountingKeyVerifier {
+            lookups: Arc::clone(&lookups),
+        }));
+
+        prefetch.prefetch("node-a", b"key", PROPOSER_ROLE);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        assert!(prefetch
+            .is_permitted_for_role("node-a", b"key", PROPOSER_ROLE)
+            .expect("Unable to check permission"));
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        assert!(prefetch
+            .is_permitted_for_role("node-a", b"key", PROPOSER_ROLE)
+            .expect("Unable to check permission"));
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+
+    /// Verify that a prefetched permission is not used once it is older than the TTL, so a
+    /// revoked permission is looked up again.
+    #[test]
+    fn test_permission_prefetch_expires() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let prefetch = PermissionPrefetch::new(Box::new(CountingKeyVerifier {
+            lookups: Arc::clone(&lookups),
+        }));
+
+        prefetch.prefetch("node-a", b"key", VOTER_ROLE);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        thread::sleep(PREFETCH_TTL + Duration::from_millis(100));
+
+        assert!(prefetch
+            .is_permitted_for_role("node-a", b"key", VOTER_ROLE)
+            .expect("Unable to check permission"));
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+
+    struct CountingKeyVerifier {
+        lookups: Arc<AtomicUsize>,
+    }
+
+    impl AdminKeyVerifier for CountingKeyVerifier {
+        fn is_permitted(&self, _node_id: &str, _key: &[u8]) -> Result<bool, AdminKeyVerifierError> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        }
+    }
+}
@@ -17,6 +17,7 @@ use std::sync::{Arc, Mutex};
 use crate::admin::store::{CircuitPredicate, CircuitProposal as StoreProposal};
 
 use super::messages::CircuitProposal;
+use super::peering::MemberPeeringStatus;
 use super::shared::AdminServiceShared;
 
 pub trait ProposalStore: Send + Sync + Clone {
@@ -26,6 +27,15 @@ pub trait ProposalStore: Send + Sync + Clone {
         -> Result<ProposalIter, ProposalStoreError>;
 
     fn proposal(&self, circuit_id: &str) -> Result<Option<CircuitProposal>, ProposalStoreError>;
+
+    /// Return the status of the connection to each member of the proposed circuit, other than
+    /// the local node. Stores that don't track peering return an empty list.
+    fn peering_status(
+        &self,
+        _circuit_id: &str,
+    ) -> Result<Vec<MemberPeeringStatus>, ProposalStoreError> {
+        Ok(vec![])
+    }
 }
 
 #[derive(Debug)]
@@ -109,15 +119,34 @@ impl ProposalStore for AdminServiceProposals {
                 ProposalStoreError::from_source("Unable to get proposal", Box::new(err))
             })?
             .map(|proposal| {
-                CircuitProposal::from_proto(proposal.into_proto()).map_err(|err| {
-                    ProposalStoreError::from_source(
-                        "Unable to convert proposal protobuf to native",
-                        Box::new(err),
-                    )
-                })
+                let created_at = proposal.created_at();
+                let updated_at = proposal.updated_at();
+                CircuitProposal::from_proto(proposal.into_proto())
+                    .map(|circuit_proposal| CircuitProposal {
+                        created_at,
+                        updated_at,
+                        ..circuit_proposal
+                    })
+                    .map_err(|err| {
+                        ProposalStoreError::from_source(
+                            "Unable to convert proposal protobuf to native",
+                            Box::new(err),
+                        )
+                    })
             })
             .transpose()
     }
+
+    fn peering_status(
+        &self,
+        circuit_id: &str,
+    ) -> Result<Vec<MemberPeeringStatus>, ProposalStoreError> {
+        Ok(self
+            .shared
+            .lock()
+            .map_err(|_| ProposalStoreError::new("Admin shared lock was lock poisoned"))?
+            .proposal_peering_status(circuit_id))
+    }
 }
 
 /// An iterator over CircuitProposals, with a well-known count of values.
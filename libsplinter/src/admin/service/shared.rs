@@ -16,20 +16,58 @@ use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::iter::ExactSizeIterator;
 use std::iter::FromIterator;
-use std::sync::mpsc::Sender;
+#[cfg(feature = "admin-routing-snapshot")]
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-#[cfg(not(feature = "admin-service-event-store"))]
+use std::thread;
+#[cfg(feature = "runtime-diagnostics")]
+use std::time::Duration;
+#[cfg(any(
+    not(feature = "admin-service-event-store"),
+    feature = "admin-vote-delegation"
+))]
 use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+#[cfg(feature = "admin-vote-cosigning")]
+use cylinder::VerificationError;
 use cylinder::{PublicKey, Signature, Verifier as SignatureVerifier};
 use protobuf::Message;
 
+#[cfg(feature = "admin-auto-vote")]
+use crate::admin::auto_vote::AutoVoter;
+#[cfg(feature = "circuit-id-policy")]
+use crate::admin::circuit_id::CircuitIdPolicy;
+#[cfg(feature = "admin-vote-cosigning")]
+use crate::admin::cosign::VoteCosignStore;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::{VoteDelegation, VoteDelegationStore};
+#[cfg(feature = "admin-routing-reconciliation")]
+use crate::admin::routing::{rebuild_routing_table, RoutingTableRebuild};
+#[cfg(feature = "admin-routing-snapshot")]
+use crate::admin::routing_snapshot::RoutingTableSnapshot;
+#[cfg(feature = "admin-signature-cache")]
+use crate::admin::signature_cache::CachingVerifier;
 use crate::admin::store::{
     AdminServiceStore, Circuit as StoreCircuit, CircuitNode, CircuitPredicate,
     CircuitProposal as StoreProposal, ProposalType, ProposedNode, Vote, VoteRecordBuilder,
 };
+#[cfg(feature = "circuit-relay")]
+use crate::circuit::handlers::AdminRelayRoutes;
+#[cfg(feature = "admin-routing-reconciliation")]
+use crate::circuit::routing::RoutingTableReader;
 use crate::circuit::routing::{self, RoutingTableWriter};
+#[cfg(feature = "circuit-payload-schema")]
+use crate::circuit::schema::{PayloadSchema, PAYLOAD_SCHEMA_ARG};
 use crate::consensus::{Proposal, ProposalId, ProposalUpdate};
+#[cfg(any(
+    feature = "admin-routing-reconciliation",
+    feature = "admin-routing-snapshot"
+))]
+use crate::error::InternalError;
+#[cfg(any(feature = "admin-vote-cosigning", feature = "admin-vote-delegation"))]
+use crate::hex::parse_hex;
 use crate::hex::to_hex;
 use crate::keys::KeyPermissionManager;
 use crate::orchestrator::{ServiceDefinition, ServiceOrchestrator};
@@ -44,6 +82,10 @@ use crate::protos::admin::{
     Circuit_AuthorizationType, Circuit_DurabilityType, Circuit_PersistenceType, Circuit_RouteType,
     MemberReady, ServiceProtocolVersionRequest, SplinterNode,
 };
+#[cfg(feature = "rest-api-read-only")]
+use crate::rest_api::read_only::ReadOnly;
+#[cfg(feature = "runtime-diagnostics")]
+use crate::runtime::RuntimeMonitor;
 use crate::service::error::ServiceError;
 #[cfg(feature = "service-arg-validation")]
 use crate::service::validation::ServiceArgValidator;
@@ -51,23 +93,30 @@ use crate::service::validation::ServiceArgValidator;
 use crate::service::ServiceNetworkSender;
 #[cfg(not(feature = "admin-service-event-store"))]
 use crate::sets::mem::DurableBTreeSet;
+#[cfg(feature = "tenancy")]
+use crate::tenant::{TenantAssignment, TenantResourceType, TenantStore, TenantStoreError};
 
+#[cfg(feature = "admin-service-debug")]
+use super::debug::{
+    AdminServiceDebugState, ConsensusProposalState, ConsensusState, PayloadSummary,
+    PendingPayloadState, ProposalPeeringState,
+};
 use super::error::{AdminSharedError, MarshallingError};
 #[cfg(not(feature = "admin-service-event-store"))]
 use super::mailbox::Mailbox;
 use super::messages;
+use super::peering::{
+    start_peering_worker, MemberPeeringStatus, PeeringMessage, PeeringResult, PeeringStatus,
+};
 use super::{
     admin_service_id, sha256, AdminKeyVerifier, AdminServiceEventSubscriber, AdminSubscriberError,
-    Events,
+    Events, PROPOSER_ROLE, VOTER_ROLE,
 };
 #[cfg(feature = "admin-service-event-store")]
 use crate::admin::service::event::store::{
     memory::MemoryAdminServiceEventStore, AdminServiceEventStore,
 };
 
-static VOTER_ROLE: &str = "voter";
-static PROPOSER_ROLE: &str = "proposer";
-
 const DEFAULT_IN_MEMORY_EVENT_LIMIT: usize = 100;
 
 pub enum PayloadType {
@@ -108,6 +157,13 @@ struct UninitializedCircuit {
     pub ready_members: HashSet<String>,
 }
 
+/// The peering state of a single member of a proposed circuit
+struct MemberPeering {
+    status: PeeringStatus,
+    // whether or not a reference to the peer has been acquired for the proposal
+    referenced: bool,
+}
+
 struct SubscriberMap {
     subscribers_by_type: RefCell<HashMap<String, Vec<Box<dyn AdminServiceEventSubscriber>>>>,
 }
@@ -195,6 +251,12 @@ pub struct AdminServiceShared {
     // PeerRef Map, peer_id to PeerRef, these PeerRef should be dropped when the peer is no longer
     // needed
     peer_refs: HashMap<String, Vec<PeerRef>>,
+    // sends requests for peer references to the peering worker
+    peering_sender: Sender<PeeringMessage>,
+    // the receiver for the peering worker, until the worker is started
+    peering_receiver: Option<Receiver<PeeringMessage>>,
+    // the peering state of the members of each circuit proposal, by circuit ID
+    proposal_peering: HashMap<String, BTreeMap<String, MemberPeering>>,
     // network sender is used to comunicated with other services on the splinter network
     network_sender: Option<Box<dyn ServiceNetworkSender>>,
     // the CircuitManagementPayloads that are waiting for members to be peered
@@ -231,6 +293,50 @@ pub struct AdminServiceShared {
 
     #[cfg(feature = "admin-service-event-store")]
     admin_event_store: Box<dyn AdminServiceEventStore>,
+
+    // the vote thresholds and pending votes used to require co-signatures on local votes
+    #[cfg(feature = "admin-vote-cosigning")]
+    vote_cosign_store: Option<Box<dyn VoteCosignStore>>,
+
+    // the delegations of voting authority honored when verifying votes
+    #[cfg(feature = "admin-vote-delegation")]
+    vote_delegation_store: Option<Box<dyn VoteDelegationStore>>,
+
+    // caches the results of signature verification; used in place of the signature verifier
+    #[cfg(feature = "admin-signature-cache")]
+    signature_cache: Option<CachingVerifier>,
+
+    // the relays through which the admin services of unpeered members are reached
+    #[cfg(feature = "circuit-relay")]
+    relay_routes: Option<AdminRelayRoutes>,
+
+    // the node's read-only mode; circuit changes are not submitted while it is enabled
+    #[cfg(feature = "rest-api-read-only")]
+    read_only: Option<ReadOnly>,
+
+    // the tenant assignments used to isolate the circuits of the tenants of this node
+    #[cfg(feature = "tenancy")]
+    tenant_store: Option<Box<dyn TenantStore>>,
+
+    // the circuit ID formats that are accepted for new circuit proposals
+    #[cfg(feature = "circuit-id-policy")]
+    circuit_id_policy: CircuitIdPolicy,
+
+    // votes automatically on the proposals this node receives
+    #[cfg(feature = "admin-auto-vote")]
+    auto_voter: Option<AutoVoter>,
+
+    // reads the routing table when reconciling it with the admin store at startup
+    #[cfg(feature = "admin-routing-reconciliation")]
+    routing_table_reader: Option<Box<dyn RoutingTableReader>>,
+
+    // the file the routing table entries of the admin store's circuits are saved to on shutdown
+    #[cfg(feature = "admin-routing-snapshot")]
+    routing_snapshot_path: Option<PathBuf>,
+
+    // records the time taken by the service's startup phases in the runtime diagnostics
+    #[cfg(feature = "runtime-diagnostics")]
+    runtime_monitor: Option<RuntimeMonitor>,
 }
 
 impl AdminServiceShared {
@@ -258,6 +364,8 @@ impl AdminServiceShared {
             std::num::NonZeroUsize::new(DEFAULT_IN_MEMORY_EVENT_LIMIT).unwrap(),
         );
 
+        let (peering_sender, peering_receiver) = channel();
+
         Ok(AdminServiceShared {
             node_id,
             network_sender: None,
@@ -267,6 +375,9 @@ impl AdminServiceShared {
             service_arg_validators,
             peer_connector,
             peer_refs: HashMap::new(),
+            peering_sender,
+            peering_receiver: Some(peering_receiver),
+            proposal_peering: HashMap::new(),
             unpeered_payloads: Vec::new(),
             pending_protocol_payloads: Vec::new(),
             service_protocols: HashMap::new(),
@@ -286,6 +397,28 @@ impl AdminServiceShared {
             routing_table_writer,
             #[cfg(feature = "admin-service-event-store")]
             admin_event_store,
+            #[cfg(feature = "admin-vote-cosigning")]
+            vote_cosign_store: None,
+            #[cfg(feature = "admin-vote-delegation")]
+            vote_delegation_store: None,
+            #[cfg(feature = "admin-signature-cache")]
+            signature_cache: None,
+            #[cfg(feature = "circuit-relay")]
+            relay_routes: None,
+            #[cfg(feature = "rest-api-read-only")]
+            read_only: None,
+            #[cfg(feature = "tenancy")]
+            tenant_store: None,
+            #[cfg(feature = "circuit-id-policy")]
+            circuit_id_policy: CircuitIdPolicy::default(),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_voter: None,
+            #[cfg(feature = "admin-routing-reconciliation")]
+            routing_table_reader: None,
+            #[cfg(feature = "admin-routing-snapshot")]
+            routing_snapshot_path: None,
+            #[cfg(feature = "runtime-diagnostics")]
+            runtime_monitor: None,
         })
     }
 
@@ -301,6 +434,203 @@ impl AdminServiceShared {
         self.network_sender = network_sender;
     }
 
+    #[cfg(feature = "admin-vote-cosigning")]
+    pub fn set_vote_cosign_store(&mut self, vote_cosign_store: Option<Box<dyn VoteCosignStore>>) {
+        self.vote_cosign_store = vote_cosign_store;
+    }
+
+    #[cfg(feature = "admin-vote-delegation")]
+    pub fn set_vote_delegation_store(
+        &mut self,
+        vote_delegation_store: Option<Box<dyn VoteDelegationStore>>,
+    ) {
+        self.vote_delegation_store = vote_delegation_store;
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    pub fn set_signature_cache(&mut self, signature_cache: Option<CachingVerifier>) {
+        self.signature_cache = signature_cache;
+    }
+
+    #[cfg(feature = "circuit-relay")]
+    pub fn set_relay_routes(&mut self, relay_routes: Option<AdminRelayRoutes>) {
+        self.relay_routes = relay_routes;
+    }
+
+    #[cfg(feature = "rest-api-read-only")]
+    pub fn set_read_only(&mut self, read_only: Option<ReadOnly>) {
+        self.read_only = read_only;
+    }
+
+    /// Adds the relay members of a circuit proposal to the admin relay routes of the other
+    /// members and returns whether one of the relays is connected, in which case the members
+    /// that are not peered can be reached through it.
+    #[cfg(feature = "circuit-relay")]
+    fn add_proposal_relay_routes(&self, members: &[SplinterNode]) -> bool {
+        let relay_routes = match &self.relay_routes {
+            Some(relay_routes) => relay_routes,
+            None => return false,
+        };
+
+        let relays = members
+            .iter()
+            .filter(|node| node.get_relay() && node.get_node_id() != self.node_id())
+            .map(|node| node.get_node_id().to_string())
+            .collect::<Vec<_>>();
+        if relays.is_empty() {
+            return false;
+        }
+
+        for node in members {
+            if node.get_node_id() != self.node_id() {
+                relay_routes.add_relays(node.get_node_id(), &relays);
+            }
+        }
+
+        relays.iter().any(|relay| {
+            self.service_protocols
+                .contains_key(&admin_service_id(relay))
+        })
+    }
+
+    /// Returns the verifier used to check signatures, which is the signature cache if one is set
+    fn verifier(&self) -> &dyn SignatureVerifier {
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            if let Some(signature_cache) = &self.signature_cache {
+                return signature_cache;
+            }
+        }
+
+        &*self.signature_verifier
+    }
+
+    /// Verifies a batch of signatures, returning the result of each in the order of the batch
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn verify_batch(
+        &self,
+        batch: &[(&[u8], &Signature, &PublicKey)],
+    ) -> Vec<Result<bool, VerificationError>> {
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            if let Some(signature_cache) = &self.signature_cache {
+                return signature_cache.verify_batch(batch);
+            }
+        }
+
+        batch
+            .iter()
+            .map(|(message, signature, public_key)| {
+                self.signature_verifier
+                    .verify(message, signature, public_key)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "tenancy")]
+    pub fn set_tenant_store(&mut self, tenant_store: Option<Box<dyn TenantStore>>) {
+        self.tenant_store = tenant_store;
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn set_circuit_id_policy(&mut self, circuit_id_policy: CircuitIdPolicy) {
+        self.circuit_id_policy = circuit_id_policy;
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn set_auto_voter(&mut self, auto_voter: Option<AutoVoter>) {
+        self.auto_voter = auto_voter;
+    }
+
+    #[cfg(feature = "admin-routing-reconciliation")]
+    pub fn set_routing_table_reader(
+        &mut self,
+        routing_table_reader: Option<Box<dyn RoutingTableReader>>,
+    ) {
+        self.routing_table_reader = routing_table_reader;
+    }
+
+    /// Rebuilds the routing table from the circuits in the admin store, returning the
+    /// discrepancies that were found, or `None` if no routing table reader has been set.
+    #[cfg(feature = "admin-routing-reconciliation")]
+    pub fn reconcile_routing_table(
+        &mut self,
+    ) -> Result<Option<RoutingTableRebuild>, InternalError> {
+        let reader = match &self.routing_table_reader {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+
+        rebuild_routing_table(
+            &self.node_id,
+            &*self.admin_store,
+            &**reader,
+            &mut *self.routing_table_writer,
+        )
+        .map(Some)
+    }
+
+    #[cfg(feature = "runtime-diagnostics")]
+    pub fn set_runtime_monitor(&mut self, runtime_monitor: Option<RuntimeMonitor>) {
+        self.runtime_monitor = runtime_monitor;
+    }
+
+    /// Records the time taken by a startup phase, if a runtime monitor has been set.
+    #[cfg(feature = "runtime-diagnostics")]
+    pub fn add_startup_phase(&self, name: &str, duration: Duration) {
+        if let Some(runtime_monitor) = &self.runtime_monitor {
+            runtime_monitor.add_startup_phase(name, duration);
+        }
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn set_routing_snapshot_path(&mut self, routing_snapshot_path: Option<PathBuf>) {
+        self.routing_snapshot_path = routing_snapshot_path;
+    }
+
+    /// Restores the routing table entries of the given circuits that have not changed since the
+    /// routing snapshot was saved, returning the IDs of the restored circuits.
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn restore_routing_snapshot(
+        &mut self,
+        circuits: &[StoreCircuit],
+        nodes: &BTreeMap<String, CircuitNode>,
+    ) -> Result<HashSet<String>, InternalError> {
+        let path = match &self.routing_snapshot_path {
+            Some(path) => path,
+            None => return Ok(HashSet::new()),
+        };
+
+        RoutingTableSnapshot::load(path)?.restore(
+            &self.node_id,
+            circuits,
+            nodes,
+            &mut *self.routing_table_writer,
+        )
+    }
+
+    /// Saves the routing table entries of the circuits in the admin store to the routing snapshot,
+    /// returning the number of circuits saved, or `None` if no snapshot path has been set.
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn save_routing_snapshot(&self) -> Result<Option<usize>, InternalError> {
+        let path = match &self.routing_snapshot_path {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        let circuits = self
+            .get_circuits()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+            .collect::<Vec<_>>();
+        let nodes = self
+            .get_nodes()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let snapshot = RoutingTableSnapshot::capture(&self.node_id, &circuits, &nodes);
+        snapshot.save(path)?;
+        Ok(Some(snapshot.len()))
+    }
+
     pub fn set_proposal_sender(&mut self, proposal_sender: Option<Sender<ProposalUpdate>>) {
         self.proposal_sender = proposal_sender;
     }
@@ -348,12 +678,6 @@ impl AdminServiceShared {
         }
     }
 
-    pub fn add_peer_refs(&mut self, peer_refs: Vec<PeerRef>) {
-        for peer_ref in peer_refs {
-            self.add_peer_ref(peer_ref);
-        }
-    }
-
     pub fn remove_peer_ref(&mut self, peer_id: &str) {
         if let Some(mut peer_ref_vec) = self.peer_refs.remove(peer_id) {
             peer_ref_vec.pop();
@@ -363,6 +687,242 @@ impl AdminServiceShared {
         }
     }
 
+    /// Starts the thread that acquires peer references requested by the admin service. Each
+    /// result is passed to `on_result`, which returns `false` if the thread should stop.
+    pub fn start_peering_worker<F>(
+        &mut self,
+        on_result: F,
+    ) -> Result<thread::JoinHandle<()>, ServiceError>
+    where
+        F: Fn(PeeringResult) -> bool + Send + 'static,
+    {
+        let receiver = self.peering_receiver.take().ok_or_else(|| {
+            ServiceError::UnableToCreate(Box::new(AdminSharedError::SplinterStateError(
+                "Peering worker has already been started".into(),
+            )))
+        })?;
+
+        start_peering_worker(receiver, self.peer_connector.clone(), on_result)
+            .map_err(|err| ServiceError::UnableToCreate(Box::new(err)))
+    }
+
+    /// Requests a reference to the given peer on behalf of a circuit or circuit proposal. The
+    /// reference is acquired asynchronously and passed to `on_peer_ref_acquired`.
+    pub fn request_peering(&self, circuit_id: &str, peer_id: &str, endpoints: Vec<String>) {
+        debug!("Requesting peering with {} for {}", peer_id, circuit_id);
+        if self
+            .peering_sender
+            .send(PeeringMessage::Acquire {
+                circuit_id: circuit_id.into(),
+                peer_id: peer_id.into(),
+                endpoints,
+            })
+            .is_err()
+        {
+            error!(
+                "Unable to request peering with {}; peering worker has shut down",
+                peer_id
+            );
+        }
+    }
+
+    /// Requests references to the members of a proposed circuit, tracking the peering status
+    /// of each member until the proposal is resolved.
+    pub fn request_proposal_peering(
+        &mut self,
+        circuit_id: &str,
+        members: Vec<(String, Vec<String>)>,
+    ) {
+        for (node_id, endpoints) in members {
+            if node_id == self.node_id {
+                continue;
+            }
+
+            let status = if self
+                .service_protocols
+                .contains_key(&admin_service_id(&node_id))
+            {
+                PeeringStatus::Peered
+            } else {
+                PeeringStatus::Pending
+            };
+            self.proposal_peering
+                .entry(circuit_id.to_string())
+                .or_default()
+                .insert(
+                    node_id.clone(),
+                    MemberPeering {
+                        status,
+                        referenced: false,
+                    },
+                );
+
+            self.request_peering(circuit_id, &node_id, endpoints);
+        }
+    }
+
+    /// Returns the peering status of each member of the given proposed circuit, other than this
+    /// node.
+    pub fn proposal_peering_status(&self, circuit_id: &str) -> Vec<MemberPeeringStatus> {
+        self.proposal_peering
+            .get(circuit_id)
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|(node_id, member)| MemberPeeringStatus {
+                        node_id: node_id.to_string(),
+                        status: member.status.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns a snapshot of the payloads held by this service, the peering state of each
+    /// circuit proposal and the state of the consensus rounds.
+    #[cfg(feature = "admin-service-debug")]
+    pub fn debug_state(&self) -> AdminServiceDebugState {
+        AdminServiceDebugState {
+            unpeered_payloads: self
+                .unpeered_payloads
+                .iter()
+                .map(PendingPayloadState::from)
+                .collect(),
+            pending_protocol_payloads: self
+                .pending_protocol_payloads
+                .iter()
+                .map(PendingPayloadState::from)
+                .collect(),
+            proposal_peering: self
+                .proposal_peering
+                .keys()
+                .map(|circuit_id| ProposalPeeringState {
+                    circuit_id: circuit_id.to_string(),
+                    members: self.proposal_peering_status(circuit_id),
+                })
+                .collect(),
+            consensus: ConsensusState {
+                queued_payloads: self
+                    .pending_circuit_payloads
+                    .iter()
+                    .map(PayloadSummary::from_payload)
+                    .collect(),
+                pending_proposals: self
+                    .pending_consensus_proposals
+                    .values()
+                    .map(|(proposal, payload)| ConsensusProposalState {
+                        proposal_id: proposal.id.to_string(),
+                        proposal_height: proposal.proposal_height,
+                        payload: PayloadSummary::from_payload(payload),
+                    })
+                    .collect(),
+                current_change: self.pending_changes.as_ref().map(|context| {
+                    PayloadSummary::new(
+                        context.circuit_proposal.get_circuit_id(),
+                        format!("{:?}", context.action),
+                    )
+                }),
+                current_verifiers: self.current_consensus_verifiers.clone(),
+            },
+        }
+    }
+
+    /// Handles a peer reference acquired by the peering worker. The reference is dropped if the
+    /// circuit proposal it was requested for has since been rejected.
+    pub fn on_peer_ref_acquired(&mut self, circuit_id: &str, peer_ref: PeerRef) {
+        match self.proposal_peering.get_mut(circuit_id) {
+            Some(members) => {
+                if let Some(member) = members.get_mut(peer_ref.peer_id()) {
+                    member.referenced = true;
+                    if member.status != PeeringStatus::Peered {
+                        member.status = PeeringStatus::Connecting;
+                    }
+                }
+            }
+            None => match self.admin_store.get_circuit(circuit_id) {
+                Ok(Some(_)) => (),
+                Ok(None) => {
+                    debug!(
+                        "Dropping reference to {}; {} is no longer proposed",
+                        peer_ref.peer_id(),
+                        circuit_id
+                    );
+                    return;
+                }
+                Err(err) => error!(
+                    "Unable to check if circuit {} exists, keeping reference to {}: {}",
+                    circuit_id,
+                    peer_ref.peer_id(),
+                    err
+                ),
+            },
+        }
+
+        self.add_peer_ref(peer_ref);
+    }
+
+    /// Records a failed attempt by the peering worker to acquire a peer reference; the attempt
+    /// will be retried.
+    pub fn on_peering_failed(
+        &mut self,
+        circuit_id: &str,
+        peer_id: &str,
+        attempts: u32,
+        error: String,
+    ) {
+        if attempts == 1 {
+            warn!(
+                "Unable to peer with {} for {}, will retry: {}",
+                peer_id, circuit_id, error
+            );
+        } else {
+            debug!(
+                "Attempt {} to peer with {} for {} failed: {}",
+                attempts, peer_id, circuit_id, error
+            );
+        }
+
+        if let Some(member) = self
+            .proposal_peering
+            .get_mut(circuit_id)
+            .and_then(|members| members.get_mut(peer_id))
+        {
+            member.status = PeeringStatus::Retrying {
+                attempts,
+                last_error: error,
+            };
+        }
+    }
+
+    /// Stops tracking the peering status of a proposed circuit. If `release` is true, the
+    /// proposal will not become a circuit, so any outstanding requests are cancelled and the
+    /// references acquired for it are removed.
+    fn clear_proposal_peering(&mut self, circuit_id: &str, release: bool) {
+        let members = self.proposal_peering.remove(circuit_id);
+        if !release {
+            return;
+        }
+
+        if self
+            .peering_sender
+            .send(PeeringMessage::Cancel {
+                circuit_id: circuit_id.into(),
+            })
+            .is_err()
+        {
+            debug!(
+                "Unable to cancel peering for {}; peering worker has shut down",
+                circuit_id
+            );
+        }
+
+        for (node_id, member) in members.into_iter().flatten() {
+            if member.referenced {
+                self.remove_peer_ref(&node_id);
+            }
+        }
+    }
+
     pub fn change_status(&mut self) {
         match self.admin_service_status {
             AdminServiceStatus::NotRunning => {
@@ -397,6 +957,9 @@ impl AdminServiceShared {
                     Ok(CircuitProposalStatus::Accepted) => {
                         // commit new circuit
                         self.admin_store.upgrade_proposal_to_circuit(circuit_id)?;
+                        // the circuit keeps the proposal's peer refs, including any that are
+                        // still being acquired
+                        self.clear_proposal_peering(circuit_id, false);
                         let circuit =
                             self.admin_store.get_circuit(circuit_id)?.ok_or_else(|| {
                                 AdminSharedError::SplinterStateError(format!(
@@ -420,7 +983,10 @@ impl AdminServiceShared {
                                 })
                                 .collect(),
                             circuit.members().to_vec(),
-                        );
+                        )
+                        .with_relays(circuit.relay_members().to_vec())
+                        .with_observers(circuit.observer_members().to_vec())
+                        .with_qos(*circuit.qos());
 
                         let routing_members = circuit_proposal
                             .get_circuit_proposal()
@@ -493,6 +1059,10 @@ impl AdminServiceShared {
                                 self.send_event(&mgmt_type, event);
 
                                 info!("committed changes for new circuit proposal {}", circuit_id);
+
+                                #[cfg(feature = "admin-auto-vote")]
+                                self.auto_vote(&circuit_proposal);
+
                                 Ok(())
                             }
 
@@ -519,12 +1089,8 @@ impl AdminServiceShared {
                     }
                     Ok(CircuitProposalStatus::Rejected) => {
                         // remove circuit
-                        let proposal = self.remove_proposal(&circuit_id)?;
-                        if let Some(proposal) = proposal {
-                            for member in proposal.circuit().members().iter() {
-                                self.remove_peer_ref(member.node_id());
-                            }
-                        }
+                        self.remove_proposal(&circuit_id)?;
+                        self.clear_proposal_peering(&circuit_id, true);
                         let circuit_proposal_proto =
                             messages::CircuitProposal::from_proto(circuit_proposal.clone())
                                 .map_err(AdminSharedError::InvalidMessageFormat)?;
@@ -598,9 +1164,7 @@ impl AdminServiceShared {
                 )
                 .map_err(|err| {
                     // remove peer_ref because we will not accept this proposal
-                    for member in proposed_circuit.get_members() {
-                        self.remove_peer_ref(member.get_node_id())
-                    }
+                    self.clear_proposal_peering(proposed_circuit.get_circuit_id(), true);
                     err
                 })?;
                 debug!("proposing {}", proposed_circuit.get_circuit_id());
@@ -658,9 +1222,7 @@ impl AdminServiceShared {
                 .map_err(|err| {
                     if circuit_proposal.proposal_type() == &ProposalType::Create {
                         // remove peer_ref because we will not accept this proposal
-                        for member in circuit_proposal.circuit().members() {
-                            self.remove_peer_ref(member.node_id())
-                        }
+                        self.clear_proposal_peering(circuit_proposal.circuit_id(), true);
                     }
                     err
                 })?;
@@ -865,35 +1427,41 @@ impl AdminServiceShared {
         let mut missing_protocol_ids = vec![];
         let mut pending_peers = vec![];
         let mut pending_members = vec![];
-        let mut added_peers: Vec<String> = vec![];
-        for node in members {
-            if self.node_id() != node.get_node_id() {
-                debug!("Referencing node {:?}", node);
-                let peer_ref = self
-                    .peer_connector
-                    .add_peer_ref(
+        // peer references are acquired asynchronously, so that unreachable members don't block
+        // the admin service
+        self.request_proposal_peering(
+            payload
+                .get_circuit_create_request()
+                .get_circuit()
+                .get_circuit_id(),
+            members
+                .iter()
+                .map(|node| {
+                    (
                         node.get_node_id().to_string(),
                         node.get_endpoints().to_vec(),
                     )
-                    .map_err(|err| {
-                        // remove all peer refs added for this proposal
-                        for node_id in added_peers.iter() {
-                            self.remove_peer_ref(node_id);
-                        }
-
-                        ServiceError::UnableToHandleMessage(Box::new(err))
-                    })?;
-
-                self.add_peer_ref(peer_ref);
-                added_peers.push(node.get_node_id().to_string());
-
+                })
+                .collect(),
+        );
+        // members that are reachable through a connected relay do not need to be peered
+        #[cfg(feature = "circuit-relay")]
+        let relay_connected = self.add_proposal_relay_routes(members);
+        #[cfg(not(feature = "circuit-relay"))]
+        let relay_connected = false;
+        for node in members {
+            if self.node_id() != node.get_node_id() {
                 // if we have a protocol the connection exists for the peer already
                 if self
                     .service_protocols
                     .get(&admin_service_id(node.get_node_id()))
                     .is_none()
                 {
-                    pending_peers.push(node.get_node_id().to_string());
+                    if relay_connected {
+                        self.send_protocol_request(node.get_node_id())?;
+                    } else {
+                        pending_peers.push(node.get_node_id().to_string());
+                    }
                     missing_protocol_ids.push(admin_service_id(node.get_node_id()))
                 }
             }
@@ -908,13 +1476,18 @@ impl AdminServiceShared {
                 proceeding",
                 &missing_protocol_ids
             );
-            self.unpeered_payloads.push(PendingPayload {
+            let pending_payload = PendingPayload {
                 unpeered_ids: pending_peers,
                 missing_protocol_ids,
                 payload_type: PayloadType::Circuit(payload),
                 members: pending_members,
                 message_sender,
-            });
+            };
+            if pending_payload.unpeered_ids.is_empty() {
+                self.pending_protocol_payloads.push(pending_payload);
+            } else {
+                self.unpeered_payloads.push(pending_payload);
+            }
         }
 
         Ok(())
@@ -923,6 +1496,20 @@ impl AdminServiceShared {
     pub fn submit(&mut self, payload: CircuitManagementPayload) -> Result<(), ServiceError> {
         debug!("Payload submitted: {:?}", payload);
 
+        #[cfg(feature = "rest-api-read-only")]
+        {
+            if self
+                .read_only
+                .as_ref()
+                .map(|read_only| read_only.is_enabled())
+                .unwrap_or(false)
+            {
+                return Err(ServiceError::UnableToHandleMessage(Box::new(
+                    AdminSharedError::ReadOnly,
+                )));
+            }
+        }
+
         let header =
             protobuf::parse_from_bytes::<CircuitManagementPayload_Header>(payload.get_header())?;
         self.validate_circuit_management_payload(&payload, &header)
@@ -941,7 +1528,25 @@ impl AdminServiceShared {
                 )
                 .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
 
-                self.propose_circuit(payload, "local".to_string())
+                #[cfg(feature = "tenancy")]
+                let (tenant_id, circuit_id) = {
+                    let circuit = payload.get_circuit_create_request().get_circuit();
+                    let tenant_id = self
+                        .check_tenant(
+                            signer_public_key,
+                            circuit.get_circuit_id(),
+                            circuit.get_members().iter().map(|node| node.get_node_id()),
+                        )
+                        .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+                    (tenant_id, circuit.get_circuit_id().to_string())
+                };
+
+                self.propose_circuit(payload, "local".to_string())?;
+
+                #[cfg(feature = "tenancy")]
+                self.assign_circuit_to_tenant(tenant_id, &circuit_id);
+
+                Ok(())
             }
             CircuitManagementPayload_Action::CIRCUIT_PROPOSAL_VOTE => {
                 let proposal_vote = payload.get_circuit_proposal_vote();
@@ -976,7 +1581,37 @@ impl AdminServiceShared {
                 )
                 .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
 
-                self.propose_vote(payload, "local".to_string())
+                #[cfg(feature = "admin-vote-delegation")]
+                self.check_delegation_not_revoked(proposal_vote)
+                    .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+                #[cfg(feature = "admin-vote-cosigning")]
+                self.check_vote_signatures(&payload, &header, &circuit_proposal)
+                    .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+                #[cfg(feature = "tenancy")]
+                let tenant_id = self
+                    .check_tenant(
+                        signer_public_key,
+                        circuit_proposal.circuit_id(),
+                        circuit_proposal
+                            .circuit()
+                            .members()
+                            .iter()
+                            .map(|node| node.node_id()),
+                    )
+                    .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+                #[cfg(feature = "tenancy")]
+                let tenant_circuit_id = circuit_proposal.circuit_id().to_string();
+                #[cfg(feature = "admin-vote-cosigning")]
+                let circuit_id = proposal_vote.get_circuit_id().to_string();
+
+                self.propose_vote(payload, "local".to_string())?;
+
+                #[cfg(feature = "admin-vote-cosigning")]
+                self.remove_pending_vote(&circuit_id);
+                #[cfg(feature = "tenancy")]
+                self.assign_circuit_to_tenant(tenant_id, &tenant_circuit_id);
+
+                Ok(())
             }
             CircuitManagementPayload_Action::ACTION_UNSET => {
                 Err(ServiceError::UnableToHandleMessage(Box::new(
@@ -1004,40 +1639,45 @@ impl AdminServiceShared {
     ) -> Result<(), ServiceError> {
         let mut missing_protocol_ids = vec![];
         let mut pending_peers = vec![];
-        let mut added_peers: Vec<String> = vec![];
         let mut pending_members = vec![];
+        let circuit = payload.get_circuit_create_request().get_circuit();
+        // peer references are acquired asynchronously, so that unreachable members don't block
+        // the admin service
+        self.request_proposal_peering(
+            circuit.get_circuit_id(),
+            circuit
+                .get_members()
+                .iter()
+                .map(|node| {
+                    (
+                        node.get_node_id().to_string(),
+                        node.get_endpoints().to_vec(),
+                    )
+                })
+                .collect(),
+        );
+        // members that are reachable through a connected relay do not need to be peered
+        #[cfg(feature = "circuit-relay")]
+        let relay_connected = self.add_proposal_relay_routes(circuit.get_members());
+        #[cfg(not(feature = "circuit-relay"))]
+        let relay_connected = false;
         for node in payload
             .get_circuit_create_request()
             .get_circuit()
             .get_members()
         {
             if self.node_id() != node.get_node_id() {
-                debug!("Referencing node {:?}", node);
-                let peer_ref = self
-                    .peer_connector
-                    .add_peer_ref(
-                        node.get_node_id().to_string(),
-                        node.get_endpoints().to_vec(),
-                    )
-                    .map_err(|err| {
-                        // remove all peer refs added for this proposal
-                        for node_id in added_peers.iter() {
-                            self.remove_peer_ref(node_id);
-                        }
-
-                        ServiceError::UnableToHandleMessage(Box::new(err))
-                    })?;
-
-                self.add_peer_ref(peer_ref);
-                added_peers.push(node.get_node_id().to_string());
-
                 // if we have a protocol the connection exists for the peer already
                 if self
                     .service_protocols
                     .get(&admin_service_id(node.get_node_id()))
                     .is_none()
                 {
-                    pending_peers.push(node.get_node_id().to_string());
+                    if relay_connected {
+                        self.send_protocol_request(node.get_node_id())?;
+                    } else {
+                        pending_peers.push(node.get_node_id().to_string());
+                    }
                     missing_protocol_ids.push(admin_service_id(node.get_node_id()))
                 }
             }
@@ -1189,9 +1829,27 @@ impl AdminServiceShared {
         // add payloads that are not waiting on peer connection
         unpeered_payloads.extend(peering);
         self.unpeered_payloads = unpeered_payloads;
+
+        // the peer manager will reconnect to peered members of proposals
+        for member in self
+            .proposal_peering
+            .values_mut()
+            .filter_map(|members| members.get_mut(&peer_id))
+            .filter(|member| member.status == PeeringStatus::Peered)
+        {
+            member.status = PeeringStatus::Connecting;
+        }
     }
 
     pub fn on_peer_connected(&mut self, peer_id: &str) -> Result<(), AdminSharedError> {
+        for member in self
+            .proposal_peering
+            .values_mut()
+            .filter_map(|members| members.get_mut(peer_id))
+        {
+            member.status = PeeringStatus::Peered;
+        }
+
         let mut unpeered_payloads = std::mem::replace(&mut self.unpeered_payloads, vec![]);
         for unpeered_payload in unpeered_payloads.iter_mut() {
             unpeered_payload
@@ -1199,6 +1857,24 @@ impl AdminServiceShared {
                 .retain(|unpeered_id| unpeered_id != peer_id);
         }
 
+        // once a relay member of a proposal is connected, the members that are not peered can be
+        // reached through it
+        #[cfg(feature = "circuit-relay")]
+        let mut relayed_members = vec![];
+        #[cfg(feature = "circuit-relay")]
+        {
+            if self.relay_routes.is_some() {
+                for unpeered_payload in unpeered_payloads.iter_mut() {
+                    if proposal_relays(&unpeered_payload.payload_type)
+                        .iter()
+                        .any(|relay| relay == peer_id)
+                    {
+                        relayed_members.append(&mut unpeered_payload.unpeered_ids);
+                    }
+                }
+            }
+        }
+
         let (fully_peered, still_unpeered): (Vec<PendingPayload>, Vec<PendingPayload>) =
             unpeered_payloads
                 .into_iter()
@@ -1209,6 +1885,16 @@ impl AdminServiceShared {
             self.pending_protocol_payloads.push(peered_payload);
         }
 
+        #[cfg(feature = "circuit-relay")]
+        for node_id in relayed_members {
+            self.send_protocol_request(&node_id).map_err(|err| {
+                AdminSharedError::ServiceProtocolError(format!(
+                    "Unable to send service protocol request: {}",
+                    err
+                ))
+            })?;
+        }
+
         // Ignore own admin service
         if peer_id == admin_service_id(self.node_id()) {
             return Ok(());
@@ -1306,8 +1992,19 @@ impl AdminServiceShared {
         if protocol == 0 {
             // if no agreed protocol, remove all peer refs for proposals
             for pending_payload in ready {
-                for peer in pending_payload.members {
-                    self.remove_peer_ref(&peer);
+                let payload = match &pending_payload.payload_type {
+                    PayloadType::Circuit(payload) => payload,
+                    PayloadType::Consensus(_, (_, payload)) => payload,
+                };
+                // only circuit create requests acquire peer refs
+                if payload.has_circuit_create_request() {
+                    self.clear_proposal_peering(
+                        payload
+                            .get_circuit_create_request()
+                            .get_circuit()
+                            .get_circuit_id(),
+                        true,
+                    );
                 }
             }
             return Ok(());
@@ -1369,11 +2066,44 @@ impl AdminServiceShared {
         &mut self,
         circuit_proposal: CircuitProposal,
     ) -> Result<(), AdminSharedError> {
-        Ok(self
-            .admin_store
-            .add_proposal(StoreProposal::from_proto(circuit_proposal).map_err(|err| {
-                AdminSharedError::SplinterStateError(format!("Unable to add proposal: {}", err))
-            })?)?)
+        let proposal = StoreProposal::from_proto(circuit_proposal).map_err(|err| {
+            AdminSharedError::SplinterStateError(format!("Unable to add proposal: {}", err))
+        })?;
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        // A proposal is added again each time a vote is recorded; keep its original creation time
+        match self.admin_store.get_proposal(proposal.circuit_id())? {
+            Some(existing) => {
+                let mut builder = proposal.builder().with_updated_at(now);
+                if let Some(created_at) = existing.created_at() {
+                    builder = builder.with_created_at(created_at);
+                }
+                let proposal = builder.build().map_err(|err| {
+                    AdminSharedError::SplinterStateError(format!(
+                        "Unable to update proposal: {}",
+                        err
+                    ))
+                })?;
+                Ok(self.admin_store.update_proposal(proposal)?)
+            }
+            None => {
+                let proposal = proposal
+                    .builder()
+                    .with_created_at(now)
+                    .with_updated_at(now)
+                    .build()
+                    .map_err(|err| {
+                        AdminSharedError::SplinterStateError(format!(
+                            "Unable to add proposal: {}",
+                            err
+                        ))
+                    })?;
+                Ok(self.admin_store.add_proposal(proposal)?)
+            }
+        }
     }
 
     /// Add a circuit definition as an uninitialized circuit. If all members are ready, initialize
@@ -1518,10 +2248,11 @@ impl AdminServiceShared {
 
         self.validate_key(signer_public_key)?;
 
-        if !self
-            .key_verifier
-            .is_permitted(requester_node_id, signer_public_key)?
-        {
+        if !self.key_verifier.is_permitted_for_role(
+            requester_node_id,
+            signer_public_key,
+            PROPOSER_ROLE,
+        )? {
             return Err(AdminSharedError::ValidationFailed(format!(
                 "{} is not registered for the requester node {}",
                 to_hex(signer_public_key),
@@ -1609,6 +2340,10 @@ impl AdminServiceShared {
                 circuit.get_circuit_id(),
             )));
         }
+        #[cfg(feature = "circuit-id-policy")]
+        self.circuit_id_policy
+            .check(circuit.get_circuit_id())
+            .map_err(|err| AdminSharedError::ValidationFailed(err.message()))?;
 
         if circuit.get_circuit_management_type().is_empty() {
             return Err(AdminSharedError::ValidationFailed(
@@ -1659,6 +2394,16 @@ impl AdminServiceShared {
             ));
         }
 
+        if circuit
+            .get_members()
+            .iter()
+            .all(|member| member.get_observer())
+        {
+            return Err(AdminSharedError::ValidationFailed(
+                "At least one member of the circuit must not be an observer".to_string(),
+            ));
+        }
+
         // check this node is in members
         if !members.contains(&self.node_id) {
             return Err(AdminSharedError::ValidationFailed(format!(
@@ -1719,6 +2464,23 @@ impl AdminServiceShared {
             {
                 self.validate_service_args(&service)?;
             }
+
+            #[cfg(feature = "circuit-payload-schema")]
+            {
+                if let Some(arg) = service
+                    .get_arguments()
+                    .iter()
+                    .find(|arg| arg.get_key() == PAYLOAD_SCHEMA_ARG)
+                {
+                    PayloadSchema::parse(arg.get_value()).map_err(|err| {
+                        AdminSharedError::ValidationFailed(format!(
+                            "Invalid payload schema for service {}: {}",
+                            service.get_service_id(),
+                            err
+                        ))
+                    })?;
+                }
+            }
         }
 
         if circuit.get_circuit_management_type().is_empty() {
@@ -1758,13 +2520,33 @@ impl AdminServiceShared {
 
         self.validate_key(signer_public_key)?;
 
-        if !self.key_verifier.is_permitted(node_id, signer_public_key)? {
-            return Err(AdminSharedError::ValidationFailed(format!(
-                "{} is not registered for voting node {}",
-                to_hex(signer_public_key),
-                node_id,
-            )));
-        }
+        // The key whose voting permissions are checked; this is the delegating key when the vote
+        // is signed by a delegate
+        let voter_public_key =
+            if self
+                .key_verifier
+                .is_permitted_for_role(node_id, signer_public_key, VOTER_ROLE)?
+            {
+                signer_public_key.to_vec()
+            } else {
+                #[cfg(feature = "admin-vote-delegation")]
+                let delegator = self.delegated_voter(
+                    proposal_vote,
+                    node_id,
+                    signer_public_key,
+                    circuit_proposal.circuit().circuit_management_type(),
+                )?;
+                #[cfg(not(feature = "admin-vote-delegation"))]
+                let delegator = None;
+
+                delegator.ok_or_else(|| {
+                    AdminSharedError::ValidationFailed(format!(
+                        "{} is not registered for voting node {}",
+                        to_hex(signer_public_key),
+                        node_id,
+                    ))
+                })?
+            };
 
         if circuit_proposal.requester_node_id() == node_id {
             return Err(AdminSharedError::ValidationFailed(format!(
@@ -1787,11 +2569,11 @@ impl AdminServiceShared {
         }
 
         self.key_permission_manager
-            .is_permitted(signer_public_key, VOTER_ROLE)
+            .is_permitted(&voter_public_key, VOTER_ROLE)
             .map_err(|_| {
                 AdminSharedError::ValidationFailed(format!(
                     "{} is not permitted to vote for node {}",
-                    to_hex(signer_public_key),
+                    to_hex(&voter_public_key),
                     node_id
                 ))
             })?;
@@ -1807,6 +2589,357 @@ impl AdminServiceShared {
         Ok(())
     }
 
+    /// Submits this node's vote on a newly committed proposal if the auto-voter decides on it.
+    ///
+    /// Failing to vote automatically does not fail the commit; the proposal is left for other
+    /// voters.
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote(&mut self, circuit_proposal: &CircuitProposal) {
+        let auto_voter = match &self.auto_voter {
+            Some(auto_voter) => auto_voter,
+            None => return,
+        };
+
+        let circuit_id = circuit_proposal.get_circuit_id().to_string();
+        let payload = match messages::CircuitProposal::from_proto(circuit_proposal.clone()) {
+            Ok(proposal) => auto_voter.vote(&self.node_id, &proposal),
+            Err(err) => {
+                warn!(
+                    "Unable to vote automatically on proposal for circuit {}: {}",
+                    circuit_id, err
+                );
+                return;
+            }
+        };
+
+        match payload {
+            Ok(Some(payload)) => {
+                if let Err(err) = self.submit(payload) {
+                    warn!(
+                        "Unable to submit automatic vote on proposal for circuit {}: {}",
+                        circuit_id, err
+                    );
+                }
+            }
+            Ok(None) => (),
+            Err(err) => warn!(
+                "Unable to vote automatically on proposal for circuit {}: {}",
+                circuit_id, err
+            ),
+        }
+    }
+
+    /// Returns the public key that delegated its authority to vote for the given node on proposals
+    /// of the given management type to the signer, if the vote carries such a delegation.
+    ///
+    /// A delegation is only honored if it was made for the node and the signer, it has not
+    /// expired, its signature is valid and the delegating key is still permitted to vote for the
+    /// node. Since the delegation is carried in the vote, every member of the circuit can check it
+    /// without having a copy of the delegating node's records.
+    #[cfg(feature = "admin-vote-delegation")]
+    fn delegated_voter(
+        &self,
+        proposal_vote: &CircuitProposalVote,
+        node_id: &str,
+        signer_public_key: &[u8],
+        management_type: &str,
+    ) -> Result<Option<Vec<u8>>, AdminSharedError> {
+        if !proposal_vote.has_delegation() {
+            return Ok(None);
+        }
+
+        let delegation = VoteDelegation::from_proto(proposal_vote.get_delegation().clone());
+
+        if delegation.node_id() != node_id
+            || delegation.delegate_public_key() != to_hex(signer_public_key)
+            || !delegation.covers(management_type)
+            || delegation.is_expired(SystemTime::now())
+        {
+            return Ok(None);
+        }
+
+        if let Err(err) = delegation.verify(self.verifier()) {
+            warn!(
+                "Ignoring vote delegation of node {} to {}: {}",
+                node_id,
+                delegation.delegate_public_key(),
+                err
+            );
+            return Ok(None);
+        }
+
+        let delegator = parse_hex(delegation.delegator_public_key()).map_err(|err| {
+            AdminSharedError::SplinterStateError(format!(
+                "unable to parse delegator public key: {}",
+                err
+            ))
+        })?;
+
+        if self
+            .key_verifier
+            .is_permitted_for_role(node_id, &delegator, VOTER_ROLE)?
+        {
+            Ok(Some(delegator))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks that the delegation carried by a vote submitted to this node has not been revoked,
+    /// that is, it is still in this node's vote delegation store.
+    ///
+    /// Revocation can only be enforced by the delegating node, so it is checked before the vote is
+    /// sent to the other members; they rely on the delegation's expiration instead.
+    #[cfg(feature = "admin-vote-delegation")]
+    fn check_delegation_not_revoked(
+        &self,
+        proposal_vote: &CircuitProposalVote,
+    ) -> Result<(), AdminSharedError> {
+        if !proposal_vote.has_delegation() {
+            return Ok(());
+        }
+
+        let delegation = VoteDelegation::from_proto(proposal_vote.get_delegation().clone());
+        let stored = match &self.vote_delegation_store {
+            Some(store) => store
+                .get_delegation(delegation.node_id(), delegation.delegate_public_key())
+                .map_err(|err| {
+                    AdminSharedError::SplinterStateError(format!(
+                        "unable to check vote delegations: {}",
+                        err
+                    ))
+                })?,
+            None => None,
+        };
+
+        if stored.as_ref() == Some(&delegation) {
+            Ok(())
+        } else {
+            Err(AdminSharedError::ValidationFailed(format!(
+                "Vote delegation of node {} to {} has been revoked",
+                delegation.node_id(),
+                delegation.delegate_public_key()
+            )))
+        }
+    }
+
+    /// Checks that a vote submitted to this node carries the number of signatures required by the
+    /// vote threshold of the proposal's circuit management type, if one is set.
+    ///
+    /// The requester's signature of the payload header counts as the first signature; the others
+    /// are the co-signatures collected for the node's pending vote on the circuit. Each signer
+    /// must be a distinct key that is permitted to vote for the requester node.
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn check_vote_signatures(
+        &self,
+        payload: &CircuitManagementPayload,
+        header: &CircuitManagementPayload_Header,
+        circuit_proposal: &StoreProposal,
+    ) -> Result<(), AdminSharedError> {
+        let store = match &self.vote_cosign_store {
+            Some(store) => store,
+            None => return Ok(()),
+        };
+
+        let store_error = |err| {
+            AdminSharedError::SplinterStateError(format!(
+                "unable to check vote co-signatures: {}",
+                err
+            ))
+        };
+
+        let required = match store
+            .get_threshold(circuit_proposal.circuit().circuit_management_type())
+            .map_err(store_error)?
+        {
+            Some(threshold) => threshold.required_signatures() as usize,
+            None => return Ok(()),
+        };
+
+        let circuit_id = circuit_proposal.circuit_id();
+        let node_id = header.get_requester_node_id();
+
+        // Only the co-signatures of the same payload header count towards the threshold
+        let signatures = match store.get_pending_vote(circuit_id).map_err(store_error)? {
+            Some(pending_vote) => {
+                match protobuf::parse_from_bytes::<CircuitManagementPayload>(pending_vote.payload())
+                {
+                    Ok(pending_payload) if pending_payload.get_header() == payload.get_header() => {
+                        pending_vote.signatures().to_vec()
+                    }
+                    _ => vec![],
+                }
+            }
+            None => vec![],
+        };
+
+        // Parse the co-signatures first, so that they can be verified as a batch
+        let requester = to_hex(header.get_requester());
+        let mut cosignatures = vec![];
+        for signature in &signatures {
+            if signature.public_key() == requester {
+                continue;
+            }
+
+            if let (Ok(public_key), Ok(signature_bytes)) = (
+                parse_hex(signature.public_key()),
+                parse_hex(signature.signature()),
+            ) {
+                cosignatures.push((
+                    signature,
+                    PublicKey::new(public_key),
+                    Signature::new(signature_bytes),
+                ));
+            }
+        }
+        let results = self.verify_batch(
+            &cosignatures
+                .iter()
+                .map(|(_, public_key, signature_bytes)| {
+                    (payload.get_header(), signature_bytes, public_key)
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut signers = vec![requester];
+        for ((signature, public_key, _), result) in cosignatures.iter().zip(results) {
+            if signers
+                .iter()
+                .any(|signer| signer == signature.public_key())
+            {
+                continue;
+            }
+
+            if result.unwrap_or(false)
+                && self.key_verifier.is_permitted_for_role(
+                    node_id,
+                    public_key.as_slice(),
+                    VOTER_ROLE,
+                )?
+            {
+                signers.push(signature.public_key().to_string());
+            } else {
+                debug!(
+                    "Ignoring invalid co-signature from {} on vote for circuit {}",
+                    signature.public_key(),
+                    circuit_id
+                );
+            }
+        }
+
+        if signers.len() < required {
+            return Err(AdminSharedError::InsufficientVoteSignatures {
+                circuit_id: circuit_id.to_string(),
+                required,
+                collected: signers.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Removes the pending vote for the circuit once the vote has been submitted, if there is one
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn remove_pending_vote(&self, circuit_id: &str) {
+        if let Some(store) = &self.vote_cosign_store {
+            match store.get_pending_vote(circuit_id) {
+                Ok(Some(_)) => {
+                    if let Err(err) = store.remove_pending_vote(circuit_id) {
+                        warn!("Unable to remove pending vote for {}: {}", circuit_id, err);
+                    }
+                }
+                Ok(None) => (),
+                Err(err) => warn!("Unable to check pending vote for {}: {}", circuit_id, err),
+            }
+        }
+    }
+
+    /// Checks that a key that belongs to a tenant only submits payloads for circuits that are not
+    /// assigned to another tenant, and whose members are not assigned to another tenant.
+    ///
+    /// Returns the ID of the key's tenant, if it has one.
+    #[cfg(feature = "tenancy")]
+    fn check_tenant<'a>(
+        &self,
+        signer_public_key: &[u8],
+        circuit_id: &str,
+        member_node_ids: impl Iterator<Item = &'a str>,
+    ) -> Result<Option<String>, AdminSharedError> {
+        let store = match &self.tenant_store {
+            Some(store) => store,
+            None => return Ok(None),
+        };
+
+        let store_error = |err: TenantStoreError| {
+            AdminSharedError::SplinterStateError(format!(
+                "unable to check tenant assignments: {}",
+                err
+            ))
+        };
+
+        let public_key = to_hex(signer_public_key);
+        let tenant_id = match store
+            .get_tenant(TenantResourceType::Key, &public_key)
+            .map_err(store_error)?
+        {
+            Some(tenant_id) => tenant_id,
+            None => return Ok(None),
+        };
+
+        let belongs_to_other_tenant = |resource_type: TenantResourceType, resource_id: &str| {
+            store
+                .get_tenant(resource_type, resource_id)
+                .map(|assigned| {
+                    assigned
+                        .map(|assigned| assigned != tenant_id)
+                        .unwrap_or(false)
+                })
+                .map_err(store_error)
+        };
+
+        if belongs_to_other_tenant(TenantResourceType::Circuit, circuit_id)? {
+            return Err(AdminSharedError::ValidationFailed(format!(
+                "Circuit {} belongs to another tenant than requester {}",
+                circuit_id, public_key
+            )));
+        }
+
+        for node_id in member_node_ids {
+            if belongs_to_other_tenant(TenantResourceType::Node, node_id)? {
+                return Err(AdminSharedError::ValidationFailed(format!(
+                    "Member node {} of circuit {} belongs to another tenant than requester {}",
+                    node_id, circuit_id, public_key
+                )));
+            }
+        }
+
+        Ok(Some(tenant_id))
+    }
+
+    /// Assigns a circuit that a tenant's key has proposed or voted on to the tenant, unless the
+    /// circuit is already assigned
+    #[cfg(feature = "tenancy")]
+    fn assign_circuit_to_tenant(&self, tenant_id: Option<String>, circuit_id: &str) {
+        if let (Some(store), Some(tenant_id)) = (&self.tenant_store, tenant_id) {
+            let result = store
+                .get_tenant(TenantResourceType::Circuit, circuit_id)
+                .and_then(|assigned| match assigned {
+                    Some(_) => Ok(()),
+                    None => store.assign(TenantAssignment::new(
+                        &tenant_id,
+                        TenantResourceType::Circuit,
+                        circuit_id,
+                    )),
+                });
+            if let Err(err) = result {
+                warn!(
+                    "Unable to assign circuit {} to tenant {}: {}",
+                    circuit_id, tenant_id, err
+                );
+            }
+        }
+    }
+
     fn validate_circuit_management_payload(
         &self,
         payload: &CircuitManagementPayload,
@@ -1933,6 +3066,14 @@ impl AdminServiceShared {
             .map_err(AdminSharedError::from)
     }
 
+    /// Lists all circuits, loading them on up to `workers` threads if the store supports it.
+    #[cfg(feature = "admin-parallel-startup")]
+    pub fn load_circuits(&self, workers: usize) -> Result<Vec<StoreCircuit>, AdminSharedError> {
+        self.admin_store
+            .list_all_circuits(workers)
+            .map_err(AdminSharedError::from)
+    }
+
     pub fn get_nodes(&self) -> Result<BTreeMap<String, CircuitNode>, AdminSharedError> {
         Ok(self
             .admin_store
@@ -1950,7 +3091,7 @@ impl AdminServiceShared {
         let signature = payload.get_signature().to_vec();
         let public_key = header.get_requester().to_vec();
 
-        self.signature_verifier
+        self.verifier()
             .verify(
                 &payload.get_header(),
                 &Signature::new(signature),
@@ -1960,6 +3101,23 @@ impl AdminServiceShared {
     }
 }
 
+/// Returns the relay members of the circuit proposed by a pending payload
+#[cfg(feature = "circuit-relay")]
+fn proposal_relays(payload_type: &PayloadType) -> Vec<String> {
+    let payload = match payload_type {
+        PayloadType::Circuit(payload) => payload,
+        PayloadType::Consensus(_, (_, payload)) => payload,
+    };
+    payload
+        .get_circuit_create_request()
+        .get_circuit()
+        .get_members()
+        .iter()
+        .filter(|node| node.get_relay())
+        .map(|node| node.get_node_id().to_string())
+        .collect()
+}
+
 #[cfg(all(test, feature = "sqlite"))]
 mod tests {
     use super::*;
@@ -1974,6 +3132,8 @@ mod tests {
         sqlite::SqliteConnection,
     };
 
+    #[cfg(feature = "circuit-id-policy")]
+    use crate::admin::circuit_id::CircuitIdFormat;
     use crate::admin::service::AdminKeyVerifierError;
     use crate::admin::store::diesel::DieselAdminServiceStore;
     use crate::circuit::routing::memory::RoutingTable;
@@ -2102,15 +3262,56 @@ mod tests {
         // None of the proposed members are peered
         assert_eq!(1, shared.unpeered_payloads.len());
         assert_eq!(0, shared.pending_circuit_payloads.len());
+        assert_eq!(
+            vec![
+                member_peering_status("other-node", PeeringStatus::Pending),
+                member_peering_status("test-node", PeeringStatus::Pending),
+            ],
+            shared.proposal_peering_status("01234-ABCDE")
+        );
 
         // Set other-node to peered
         shared
             .on_peer_connected("other-node")
             .expect("Unable to set peer to peered");
+        // Failing to reference test-node is reported until the peering worker retries
+        shared.on_peering_failed("01234-ABCDE", "test-node", 1, "unreachable".into());
 
         // Still waitin on 1 peer
         assert_eq!(1, shared.unpeered_payloads.len());
         assert_eq!(0, shared.pending_circuit_payloads.len());
+        assert_eq!(
+            vec![
+                member_peering_status("other-node", PeeringStatus::Peered),
+                member_peering_status(
+                    "test-node",
+                    PeeringStatus::Retrying {
+                        attempts: 1,
+                        last_error: "unreachable".into(),
+                    }
+                ),
+            ],
+            shared.proposal_peering_status("01234-ABCDE")
+        );
+
+        // The debug state reports the payload held for test-node and its retried peering
+        #[cfg(feature = "admin-service-debug")]
+        {
+            let state = shared.debug_state();
+            assert_eq!(1, state.unpeered_payloads.len());
+            let pending = &state.unpeered_payloads[0];
+            assert_eq!("01234-ABCDE", pending.payload.circuit_id);
+            assert_eq!("CIRCUIT_CREATE_REQUEST", pending.payload.action);
+            assert_eq!(None, pending.proposal_id);
+            assert_eq!(vec!["test-node".to_string()], pending.unpeered_ids);
+            assert_eq!(1, state.proposal_peering.len());
+            assert_eq!(
+                shared.proposal_peering_status("01234-ABCDE"),
+                state.proposal_peering[0].members
+            );
+            assert!(state.consensus.queued_payloads.is_empty());
+            assert_eq!(None, state.consensus.current_change);
+        }
 
         // Set other-node to peered
         shared
@@ -2229,22 +3430,144 @@ mod tests {
         assert_eq!(1, shared.pending_protocol_payloads.len());
         assert_eq!(0, shared.pending_circuit_payloads.len());
         shared
-            .on_protocol_agreement("admin::other-node", 0)
+            .on_protocol_agreement("admin::other-node", 0)
+            .expect("received unexpected error");
+
+        // The message should be dropped
+        assert_eq!(0, shared.pending_circuit_payloads.len());
+        assert_eq!(0, shared.pending_protocol_payloads.len());
+
+        // sent the service protocol request but not the payload
+        assert_eq!(
+            1,
+            service_sender
+                .sent
+                .lock()
+                .expect("Network sender lock poisoned")
+                .len()
+        );
+        shutdown(mesh, cm, pm);
+    }
+
+    /// Test that a member that is never peered with this node is reached through the relay
+    /// member of the proposal: once the relay is connected, the protocol request is sent to the
+    /// unpeered member and the payload proceeds when both agree on a protocol.
+    #[cfg(feature = "circuit-relay")]
+    #[test]
+    fn test_protocol_agreement_through_relay() {
+        let mut transport = InprocTransport::default();
+        let mut orchestrator_transport = transport.clone();
+
+        let _relay_listener = transport
+            .listen("inproc://relayplace:8000")
+            .expect("Unable to get listener");
+        let _admin_listener = transport
+            .listen("inproc://admin-service")
+            .expect("Unable to get listener");
+
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(Some(transport));
+        let orchestrator_connection = orchestrator_transport
+            .connect("inproc://admin-service")
+            .expect("failed to create connection");
+        let (orchestrator, _) = ServiceOrchestrator::new(vec![], orchestrator_connection, 1, 1, 1)
+            .expect("failed to create orchestrator");
+        let store = setup_admin_service_store();
+
+        let signature_verifier = Secp256k1Context::new().new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let mut shared = AdminServiceShared::new(
+            "test-node".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+
+        let service_sender = MockServiceNetworkSender::new();
+        shared.set_network_sender(Some(Box::new(service_sender.clone())));
+        let relay_routes = AdminRelayRoutes::new();
+        shared.set_relay_routes(Some(relay_routes.clone()));
+
+        let mut circuit = admin::Circuit::new();
+        circuit.set_circuit_id("01234-ABCDE".into());
+        circuit.set_authorization_type(admin::Circuit_AuthorizationType::TRUST_AUTHORIZATION);
+        circuit.set_persistence(admin::Circuit_PersistenceType::ANY_PERSISTENCE);
+        circuit.set_routes(admin::Circuit_RouteType::ANY_ROUTE);
+        circuit.set_circuit_management_type("test app auth handler".into());
+        circuit.set_comments("test circuit".into());
+        circuit.set_display_name("test_display".into());
+
+        let mut relay_node = splinter_node("relay-node", &["inproc://relayplace:8000".to_string()]);
+        relay_node.set_relay(true);
+        circuit.set_members(protobuf::RepeatedField::from_vec(vec![
+            splinter_node("test-node", &["inproc://someplace:8000".to_string()]),
+            relay_node,
+            splinter_node("other-node", &["inproc://otherplace:8000".to_string()]),
+        ]));
+        circuit.set_roster(protobuf::RepeatedField::from_vec(vec![
+            splinter_service("0123", "sabre"),
+            splinter_service("ABCD", "sabre"),
+        ]));
+
+        let mut request = admin::CircuitCreateRequest::new();
+        request.set_circuit(circuit);
+
+        let mut header = admin::CircuitManagementPayload_Header::new();
+        header.set_action(admin::CircuitManagementPayload_Action::CIRCUIT_CREATE_REQUEST);
+
+        let mut payload = admin::CircuitManagementPayload::new();
+
+        payload.set_signature(Vec::new());
+        payload.set_header(protobuf::Message::write_to_bytes(&header).unwrap());
+        payload.set_circuit_create_request(request);
+
+        shared
+            .propose_circuit(payload, "local".to_string())
+            .expect("Proposal not accepted");
+
+        // None of the proposed members are peered, and other-node is reachable through the relay
+        assert_eq!(1, shared.unpeered_payloads.len());
+        assert_eq!(vec!["relay-node".to_string()], relay_routes.relays("other-node"));
+
+        // Once the relay is connected, other-node no longer needs to be peered
+        shared
+            .on_peer_connected("relay-node")
+            .expect("Unable to set peer to peered");
+
+        assert_eq!(0, shared.unpeered_payloads.len());
+        assert_eq!(1, shared.pending_protocol_payloads.len());
+        let mut recipients = service_sender
+            .sent
+            .lock()
+            .expect("Network sender lock poisoned")
+            .iter()
+            .map(|(recipient, _)| recipient.to_string())
+            .collect::<Vec<_>>();
+        recipients.sort();
+        assert_eq!(
+            vec!["admin::other-node".to_string(), "admin::relay-node".to_string()],
+            recipients
+        );
+
+        shared
+            .on_protocol_agreement("admin::relay-node", 1)
+            .expect("received unexpected error");
+        shared
+            .on_protocol_agreement("admin::other-node", 1)
             .expect("received unexpected error");
 
-        // The message should be dropped
-        assert_eq!(0, shared.pending_circuit_payloads.len());
+        // other-node was never peered, but the payload is now available
         assert_eq!(0, shared.pending_protocol_payloads.len());
-
-        // sent the service protocol request but not the payload
-        assert_eq!(
-            1,
-            service_sender
-                .sent
-                .lock()
-                .expect("Network sender lock poisoned")
-                .len()
-        );
+        assert_eq!(1, shared.pending_circuit_payloads.len());
         shutdown(mesh, cm, pm);
     }
 
@@ -2725,6 +4048,59 @@ mod tests {
         shutdown(mesh, cm, pm);
     }
 
+    #[test]
+    // test that if every member of a circuit is an observer an error is returned
+    fn test_validate_circuit_all_observers() {
+        let store = setup_admin_service_store();
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+        let orchestrator = setup_orchestrator();
+
+        let signature_verifier = Secp256k1Context::new().new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let admin_shared = AdminServiceShared::new(
+            "node_a".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+        let mut circuit = setup_test_circuit();
+
+        for member in circuit.mut_members().iter_mut() {
+            member.set_observer(true);
+        }
+
+        if let Ok(_) = admin_shared.validate_create_circuit(
+            &circuit,
+            PUB_KEY,
+            "node_a",
+            ADMIN_SERVICE_PROTOCOL_VERSION,
+        ) {
+            panic!("Should have been invalid because all members are observers");
+        }
+
+        circuit.mut_members()[0].set_observer(false);
+
+        if let Err(err) = admin_shared.validate_create_circuit(
+            &circuit,
+            PUB_KEY,
+            "node_a",
+            ADMIN_SERVICE_PROTOCOL_VERSION,
+        ) {
+            panic!("Should have been valid with an observer: {}", err);
+        }
+        shutdown(mesh, cm, pm);
+    }
+
     #[test]
     // test that if a circuit does not have the local node in the member list an error is
     // returned
@@ -2955,6 +4331,69 @@ mod tests {
         shutdown(mesh, cm, pm);
     }
 
+    #[cfg(feature = "circuit-id-policy")]
+    #[test]
+    // test that a circuit ID is only accepted if it is in a format accepted by the circuit ID
+    // policy
+    fn test_validate_circuit_circuit_id_policy() {
+        let store = setup_admin_service_store();
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+        let orchestrator = setup_orchestrator();
+
+        let signature_verifier = Secp256k1Context::new().new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let mut admin_shared = AdminServiceShared::new(
+            "node_a".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+        let random_circuit = setup_test_circuit();
+        let mut uuid_circuit = setup_test_circuit();
+        uuid_circuit.set_circuit_id(CircuitIdFormat::UuidV7.generate());
+
+        // By default, only the random format is accepted
+        assert!(admin_shared
+            .validate_create_circuit(
+                &uuid_circuit,
+                PUB_KEY,
+                "node_a",
+                ADMIN_SERVICE_PROTOCOL_VERSION,
+            )
+            .is_err());
+
+        admin_shared.set_circuit_id_policy(
+            CircuitIdPolicy::new(vec![CircuitIdFormat::UuidV7]).expect("invalid policy"),
+        );
+        assert!(admin_shared
+            .validate_create_circuit(
+                &uuid_circuit,
+                PUB_KEY,
+                "node_a",
+                ADMIN_SERVICE_PROTOCOL_VERSION,
+            )
+            .is_ok());
+        assert!(admin_shared
+            .validate_create_circuit(
+                &random_circuit,
+                PUB_KEY,
+                "node_a",
+                ADMIN_SERVICE_PROTOCOL_VERSION,
+            )
+            .is_err());
+        shutdown(mesh, cm, pm);
+    }
+
     #[test]
     // test that if a circuit has a member with no endpoints an error is returned
     fn test_validate_circuit_no_endpoints() {
@@ -3510,6 +4949,169 @@ mod tests {
         shutdown(mesh, cm, pm);
     }
 
+    #[cfg(feature = "admin-vote-delegation")]
+    #[test]
+    // test that a vote signed by a key that is not permitted to vote for the node is accepted by
+    // another member, which has no record of the delegation, only if the vote carries a valid,
+    // unexpired delegation for the proposal's management type; and that the delegating node
+    // refuses to submit a vote whose delegation has been removed from its store
+    fn test_validate_circuit_vote_delegation() {
+        use std::time::Duration;
+
+        use crate::admin::delegation::MemoryVoteDelegationStore;
+
+        // permits only the given key to vote for any node
+        struct SingleKeyVerifier(Vec<u8>);
+
+        impl AdminKeyVerifier for SingleKeyVerifier {
+            fn is_permitted(
+                &self,
+                _node_id: &str,
+                key: &[u8],
+            ) -> Result<bool, AdminKeyVerifierError> {
+                Ok(key == self.0.as_slice())
+            }
+        }
+
+        let context = Secp256k1Context::new();
+        let delegator = context.new_signer(context.new_random_private_key());
+        let delegate = context.new_signer(context.new_random_private_key());
+        let delegator_key = delegator.public_key().unwrap().into_bytes();
+        let delegate_key = delegate.public_key().unwrap().into_bytes();
+
+        let new_admin_shared = |node_id: &str| {
+            let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+            let table = RoutingTable::default();
+            let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+            let admin_shared = AdminServiceShared::new(
+                node_id.into(),
+                Arc::new(Mutex::new(setup_orchestrator())),
+                #[cfg(feature = "service-arg-validation")]
+                HashMap::new(),
+                peer_connector,
+                setup_admin_service_store(),
+                context.new_verifier(),
+                Box::new(SingleKeyVerifier(delegator_key.clone())),
+                Box::new(AllowAllKeyPermissionManager),
+                writer,
+            )
+            .unwrap();
+            (admin_shared, (mesh, cm, pm))
+        };
+
+        // node_a is the delegating node; node_b is another member of the circuit that receives
+        // node_a's votes and has no vote delegation store
+        let (mut node_a, node_a_network) = new_admin_shared("node_a");
+        let delegation_store = MemoryVoteDelegationStore::new();
+        node_a.set_vote_delegation_store(Some(Box::new(delegation_store.clone())));
+        let (node_b, node_b_network) = new_admin_shared("node_b");
+
+        let circuit = setup_test_circuit();
+        let proposal = StoreProposal::from_proto(setup_test_proposal(&circuit))
+            .expect("Unable to get proposal");
+        let vote_with_delegation = |delegation: VoteDelegation| {
+            let mut vote = setup_test_vote(&circuit);
+            vote.set_delegation(delegation.into_proto());
+            vote
+        };
+
+        let expires_at = (SystemTime::now() + Duration::from_secs(3600))
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let delegation = VoteDelegation::sign(
+            "node_a",
+            &to_hex(&delegate_key),
+            vec!["test_circuit".into()],
+            expires_at,
+            &*delegator,
+        )
+        .unwrap();
+        delegation_store.add_delegation(delegation.clone()).unwrap();
+
+        // a vote without a delegation is not accepted, even by the delegating node
+        let vote = setup_test_vote(&circuit);
+        assert!(node_a
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a")
+            .is_err());
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a")
+            .is_err());
+
+        // a vote carrying the delegation is accepted by the other member
+        let vote = vote_with_delegation(delegation.clone());
+        if let Err(err) = node_b.validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a") {
+            panic!("Delegated vote should have been valid: {}", err);
+        }
+        node_a
+            .check_delegation_not_revoked(&vote)
+            .expect("Delegation should not have been revoked");
+
+        // a delegation is not honored for another node or another signer
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_c")
+            .is_err());
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegator_key, &proposal, "node_a")
+            .is_err());
+
+        // a delegation for another management type is not honored
+        let vote = vote_with_delegation(
+            VoteDelegation::sign(
+                "node_a",
+                &to_hex(&delegate_key),
+                vec!["other".into()],
+                expires_at,
+                &*delegator,
+            )
+            .unwrap(),
+        );
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a")
+            .is_err());
+
+        // an expired delegation is not honored
+        let vote = vote_with_delegation(
+            VoteDelegation::sign(
+                "node_a",
+                &to_hex(&delegate_key),
+                vec!["test_circuit".into()],
+                1,
+                &*delegator,
+            )
+            .unwrap(),
+        );
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a")
+            .is_err());
+
+        // a delegation that was not signed by the delegating key is not honored
+        let vote = vote_with_delegation(VoteDelegation::new(
+            "node_a",
+            &to_hex(&delegator_key),
+            &to_hex(&delegate_key),
+            vec!["test_circuit".into()],
+            expires_at,
+            delegation.signature().replace('0', "1").as_str(),
+        ));
+        assert!(node_b
+            .validate_circuit_vote(&vote, &delegate_key, &proposal, "node_a")
+            .is_err());
+
+        // once the delegating node removes the delegation, it refuses to submit the vote
+        delegation_store
+            .remove_delegation("node_a", &to_hex(&delegate_key))
+            .unwrap();
+        assert!(node_a
+            .check_delegation_not_revoked(&vote_with_delegation(delegation))
+            .is_err());
+
+        let (mesh, cm, pm) = node_a_network;
+        shutdown(mesh, cm, pm);
+        let (mesh, cm, pm) = node_b_network;
+        shutdown(mesh, cm, pm);
+    }
+
     #[test]
     // test that the validate_circuit_management_payload method returns an error in case the
     // signature is empty.
@@ -3567,6 +5169,252 @@ mod tests {
         shutdown(mesh, cm, pm);
     }
 
+    #[cfg(feature = "rest-api-read-only")]
+    #[test]
+    // test that a valid circuit management payload is refused by submit while the node is in
+    // read-only mode
+    fn test_submit_read_only() {
+        let store = setup_admin_service_store();
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+        let orchestrator = setup_orchestrator();
+
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let signer = context.new_signer(private_key);
+        let signature_verifier = context.new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let mut shared = AdminServiceShared::new(
+            "node_a".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+        shared.set_read_only(Some(ReadOnly::new(true)));
+
+        let mut request = admin::CircuitCreateRequest::new();
+        request.set_circuit(setup_test_circuit());
+
+        let mut header = admin::CircuitManagementPayload_Header::new();
+        header.set_action(admin::CircuitManagementPayload_Action::CIRCUIT_CREATE_REQUEST);
+        header.set_requester(PUB_KEY.into());
+        header.set_requester_node_id("node_b".to_string());
+        let mut payload = admin::CircuitManagementPayload::new();
+        payload.set_header(protobuf::Message::write_to_bytes(&header).unwrap());
+        payload.set_signature(signer.sign(&payload.header).unwrap().take_bytes());
+        payload.set_circuit_create_request(request);
+
+        match shared.submit(payload) {
+            Err(ServiceError::UnableToHandleMessage(err)) => {
+                assert_eq!(err.to_string(), AdminSharedError::ReadOnly.to_string())
+            }
+            res => panic!("Expected the payload to be refused, got {:?}", res),
+        }
+
+        shutdown(mesh, cm, pm);
+    }
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    #[test]
+    // test that a vote is only accepted once it carries the number of distinct signatures required
+    // by the threshold of the proposal's circuit management type, counting the requester's
+    // signature and the co-signatures of the node's pending vote
+    fn test_check_vote_signatures() {
+        use crate::admin::cosign::{
+            MemoryVoteCosignStore, PendingVote, VoteCosignStore, VoteSignature, VoteThreshold,
+        };
+
+        let store = setup_admin_service_store();
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+        let orchestrator = setup_orchestrator();
+
+        let context = Secp256k1Context::new();
+        let requester = context.new_signer(context.new_random_private_key());
+        let cosigner = context.new_signer(context.new_random_private_key());
+        let signature_verifier = context.new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let mut shared = AdminServiceShared::new(
+            "node_a".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+        let cosign_store = MemoryVoteCosignStore::new();
+        shared.set_vote_cosign_store(Some(Box::new(cosign_store.clone())));
+
+        let circuit = setup_test_circuit();
+        let proposal = StoreProposal::from_proto(setup_test_proposal(&circuit))
+            .expect("Unable to get proposal");
+
+        let mut header = admin::CircuitManagementPayload_Header::new();
+        header.set_action(admin::CircuitManagementPayload_Action::CIRCUIT_PROPOSAL_VOTE);
+        header.set_requester(requester.public_key().unwrap().into_bytes());
+        header.set_requester_node_id("node_a".to_string());
+        let mut payload = admin::CircuitManagementPayload::new();
+        payload.set_header(protobuf::Message::write_to_bytes(&header).unwrap());
+        payload.set_signature(requester.sign(&payload.header).unwrap().take_bytes());
+        payload.set_circuit_proposal_vote(setup_test_vote(&circuit));
+
+        // Without a threshold, the requester's signature is sufficient
+        if let Err(err) = shared.check_vote_signatures(&payload, &header, &proposal) {
+            panic!("Should have been valid: {}", err);
+        }
+
+        cosign_store
+            .set_threshold(VoteThreshold::new(circuit.get_circuit_management_type(), 2))
+            .expect("Unable to set threshold");
+        match shared.check_vote_signatures(&payload, &header, &proposal) {
+            Err(AdminSharedError::InsufficientVoteSignatures {
+                required: 2,
+                collected: 1,
+                ..
+            }) => (),
+            res => panic!("Should have been insufficient, got {:?}", res),
+        }
+
+        // A repeated signature from the requester does not count
+        cosign_store
+            .add_pending_vote(PendingVote::new(
+                circuit.get_circuit_id(),
+                protobuf::Message::write_to_bytes(&payload).unwrap(),
+                vec![VoteSignature::new(
+                    &to_hex(&requester.public_key().unwrap().into_bytes()),
+                    &to_hex(&requester.sign(&payload.header).unwrap().take_bytes()),
+                )],
+            ))
+            .expect("Unable to add pending vote");
+        if let Ok(()) = shared.check_vote_signatures(&payload, &header, &proposal) {
+            panic!("Should have been invalid due to a repeated signer");
+        }
+
+        cosign_store
+            .add_signature(
+                circuit.get_circuit_id(),
+                VoteSignature::new(
+                    &to_hex(&cosigner.public_key().unwrap().into_bytes()),
+                    &to_hex(&cosigner.sign(&payload.header).unwrap().take_bytes()),
+                ),
+            )
+            .expect("Unable to add signature");
+        if let Err(err) = shared.check_vote_signatures(&payload, &header, &proposal) {
+            panic!("Should have been valid: {}", err);
+        }
+
+        shutdown(mesh, cm, pm);
+    }
+
+    #[cfg(feature = "tenancy")]
+    #[test]
+    // test that a tenant's key may only submit payloads for circuits and member nodes that are
+    // not assigned to another tenant, that keys without a tenant are not restricted, and that
+    // circuits are assigned to the tenant of the key that proposed them
+    fn test_check_tenant() {
+        use crate::tenant::MemoryTenantStore;
+
+        let store = setup_admin_service_store();
+        let (mesh, cm, pm, peer_connector) = setup_peer_connector(None);
+        let orchestrator = setup_orchestrator();
+
+        let context = Secp256k1Context::new();
+        let signature_verifier = context.new_verifier();
+
+        let table = RoutingTable::default();
+        let writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let mut shared = AdminServiceShared::new(
+            "node_a".into(),
+            Arc::new(Mutex::new(orchestrator)),
+            #[cfg(feature = "service-arg-validation")]
+            HashMap::new(),
+            peer_connector,
+            store,
+            signature_verifier,
+            Box::new(MockAdminKeyVerifier::default()),
+            Box::new(AllowAllKeyPermissionManager),
+            writer,
+        )
+        .unwrap();
+        let tenant_store = MemoryTenantStore::new();
+        shared.set_tenant_store(Some(Box::new(tenant_store.clone())));
+
+        let tenant_key: &[u8] = &[2; 33];
+        let other_key: &[u8] = &[3; 33];
+        let members = || vec!["node_a", "node_b"].into_iter();
+
+        tenant_store
+            .assign(TenantAssignment::new(
+                "tenant-a",
+                TenantResourceType::Key,
+                &to_hex(tenant_key),
+            ))
+            .expect("Unable to assign key");
+
+        // Keys without a tenant are not restricted
+        assert_eq!(
+            shared
+                .check_tenant(other_key, "01234-ABCDE", members())
+                .expect("Should have been valid"),
+            None
+        );
+        assert_eq!(
+            shared
+                .check_tenant(tenant_key, "01234-ABCDE", members())
+                .expect("Should have been valid"),
+            Some("tenant-a".to_string())
+        );
+
+        shared.assign_circuit_to_tenant(Some("tenant-a".to_string()), "01234-ABCDE");
+        assert_eq!(
+            tenant_store
+                .get_tenant(TenantResourceType::Circuit, "01234-ABCDE")
+                .expect("Unable to get tenant"),
+            Some("tenant-a".to_string())
+        );
+
+        tenant_store
+            .assign(TenantAssignment::new(
+                "tenant-b",
+                TenantResourceType::Circuit,
+                "56789-ABCDE",
+            ))
+            .expect("Unable to assign circuit");
+        if let Ok(_) = shared.check_tenant(tenant_key, "56789-ABCDE", members()) {
+            panic!("Should have been invalid due to a circuit of another tenant");
+        }
+
+        tenant_store
+            .assign(TenantAssignment::new(
+                "tenant-b",
+                TenantResourceType::Node,
+                "node_b",
+            ))
+            .expect("Unable to assign node");
+        if let Ok(_) = shared.check_tenant(tenant_key, "01234-ABCDE", members()) {
+            panic!("Should have been invalid due to a member node of another tenant");
+        }
+
+        shutdown(mesh, cm, pm);
+    }
+
     #[test]
     // test that the validate_circuit_management_payload method returns an error in case the
     // header is empty.
@@ -3893,6 +5741,13 @@ mod tests {
         node
     }
 
+    fn member_peering_status(node_id: &str, status: PeeringStatus) -> MemberPeeringStatus {
+        MemberPeeringStatus {
+            node_id: node_id.into(),
+            status,
+        }
+    }
+
     fn splinter_service(service_id: &str, service_type: &str) -> admin::SplinterService {
         let mut service = admin::SplinterService::new();
         service.set_service_id(service_id.into());
@@ -4103,6 +5958,7 @@ mod tests {
             connection_id,
             AuthorizationMessage::ConnectResponse(ConnectResponse {
                 accepted_authorization_types: vec![AuthorizationType::Trust],
+                capabilities: None,
             }),
         );
         mesh.send(env).expect("Unable to send connect response");
@@ -0,0 +1,423 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A cache of signature verification results for the admin service.
+//!
+//! Circuit proposals and votes often arrive in bursts, and the same signed payload may be
+//! verified several times as it moves through consensus. A [`CachingVerifier`] wraps the admin
+//! service's verifier and remembers the result of the most recently used verifications, keyed
+//! by the hash of the payload, the signature and the signer's public key, so repeated
+//! verifications do not repeat the signature check.
+//!
+//! The co-signatures of a vote are verified together with
+//! [`CachingVerifier::verify_batch`](struct.CachingVerifier.html#method.verify_batch), which
+//! consults the cache once for the whole batch. Cylinder does not provide batched verification,
+//! so each signature that is not in the cache is still verified individually.
+//!
+//! [`CachingVerifier`]: struct.CachingVerifier.html
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use cylinder::{PublicKey, Signature, VerificationError, Verifier};
+use openssl::hash::{hash, MessageDigest};
+
+/// The number of verification results cached by default
+pub const DEFAULT_SIGNATURE_CACHE_SIZE: usize = 10_000;
+
+// The SHA-256 hash of the message, the signature and the public key. The signature and the public
+// key are kept whole rather than hashed together with the message, so that no two distinct
+// verifications share a key.
+type CacheKey = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+/// A message, its signature and the public key of its signer
+pub type SignedMessage<'a> = (&'a [u8], &'a Signature, &'a PublicKey);
+
+/// The hits and misses of a `CachingVerifier`'s cache.
+#[derive(Clone, Debug, Default)]
+pub struct SignatureCacheStats {
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl SignatureCacheStats {
+    /// Returns the number of verifications answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of verifications that were not in the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::SeqCst)
+    }
+
+    /// Returns the fraction of verifications answered from the cache, or `0.0` if there have
+    /// been none.
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.hits();
+        let total = hits + self.misses();
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
+    }
+}
+
+// Entries are ordered by the tick at which they were last used, so the least recently used entry
+// is the first in `order`
+#[derive(Default)]
+struct LruCache {
+    entries: HashMap<CacheKey, (bool, u64)>,
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl LruCache {
+    fn get(&mut self, key: &CacheKey) -> Option<bool> {
+        let tick = self.next_tick();
+        let (valid, last_used) = self.entries.get_mut(key)?;
+        if let Some(key) = self.order.remove(last_used) {
+            self.order.insert(tick, key);
+        }
+        *last_used = tick;
+        Some(*valid)
+    }
+
+    fn insert(&mut self, key: CacheKey, valid: bool, capacity: usize) {
+        let tick = self.next_tick();
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (valid, tick)) {
+            self.order.remove(&last_used);
+        }
+        self.order.insert(tick, key);
+
+        while self.entries.len() > capacity {
+            let oldest = match self.order.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(key) = self.order.remove(&oldest) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// A verifier that caches the results of another verifier.
+///
+/// Both valid and invalid results are cached; errors returned by the wrapped verifier are not.
+pub struct CachingVerifier {
+    verifier: Box<dyn Verifier>,
+    capacity: usize,
+    cache: Mutex<LruCache>,
+    stats: SignatureCacheStats,
+}
+
+impl CachingVerifier {
+    /// Creates a verifier that caches up to `capacity` results of the given verifier.
+    pub fn new(verifier: Box<dyn Verifier>, capacity: usize) -> Self {
+        Self {
+            verifier,
+            capacity,
+            cache: Mutex::new(LruCache::default()),
+            stats: SignatureCacheStats::default(),
+        }
+    }
+
+    /// Returns the cache's hit and miss counts, which remain readable after the verifier has been
+    /// given to the admin service.
+    pub fn stats(&self) -> SignatureCacheStats {
+        self.stats.clone()
+    }
+
+    fn record_hit(&self) {
+        self.stats.hits.fetch_add(1, Ordering::SeqCst);
+        #[cfg(feature = "metrics")]
+        metrics::counter!("splinter.admin.signature_cache_hits", 1);
+    }
+
+    fn record_miss(&self) {
+        self.stats.misses.fetch_add(1, Ordering::SeqCst);
+        #[cfg(feature = "metrics")]
+        metrics::counter!("splinter.admin.signature_cache_misses", 1);
+    }
+
+    /// Verifies a batch of signatures, returning the result of each in the order of the batch.
+    ///
+    /// The cache is read and updated once for the whole batch, and a signature that appears more
+    /// than once in the batch is only verified once.
+    pub fn verify_batch(&self, batch: &[SignedMessage]) -> Vec<Result<bool, VerificationError>> {
+        let keys = batch
+            .iter()
+            .map(|(message, signature, public_key)| cache_key(message, signature, public_key))
+            .collect::<Vec<_>>();
+
+        // The lock is not held while verifying, so a poisoned lock only disables the cache
+        let cached: Vec<Option<bool>> = match self.cache.lock() {
+            Ok(mut cache) => keys
+                .iter()
+                .map(|key| key.as_ref().and_then(|key| cache.get(key)))
+                .collect(),
+            Err(_) => vec![None; batch.len()],
+        };
+
+        let mut verified = HashMap::new();
+        let mut results = Vec::with_capacity(batch.len());
+        for (((message, signature, public_key), key), cached) in
+            batch.iter().zip(keys.iter()).zip(cached)
+        {
+            if let Some(valid) =
+                cached.or_else(|| key.as_ref().and_then(|key| verified.get(key).copied()))
+            {
+                self.record_hit();
+                results.push(Ok(valid));
+                continue;
+            }
+
+            self.record_miss();
+            let result = self.verifier.verify(message, signature, public_key);
+            if let (Some(key), Ok(valid)) = (key, &result) {
+                verified.insert(key, *valid);
+            }
+            results.push(result);
+        }
+
+        if let Ok(mut cache) = self.cache.lock() {
+            for (key, valid) in verified {
+                cache.insert(key.clone(), valid, self.capacity);
+            }
+        }
+
+        results
+    }
+}
+
+/// Returns the cache key of a verification, or `None` if the message cannot be hashed, in which
+/// case the verification is not cached.
+fn cache_key(message: &[u8], signature: &Signature, public_key: &PublicKey) -> Option<CacheKey> {
+    match hash(MessageDigest::sha256(), message) {
+        Ok(message_hash) => Some((
+            message_hash.to_vec(),
+            signature.as_slice().to_vec(),
+            public_key.as_slice().to_vec(),
+        )),
+        Err(err) => {
+            trace!("Unable to hash payload for signature cache: {}", err);
+            None
+        }
+    }
+}
+
+impl Verifier for CachingVerifier {
+    fn algorithm_name(&self) -> &str {
+        self.verifier.algorithm_name()
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<bool, VerificationError> {
+        let key = match cache_key(message, signature, public_key) {
+            Some(key) => key,
+            None => return self.verifier.verify(message, signature, public_key),
+        };
+
+        // The lock is not held while verifying, so a poisoned lock only disables the cache
+        let cached = match self.cache.lock() {
+            Ok(mut cache) => cache.get(&key),
+            Err(_) => None,
+        };
+        if let Some(valid) = cached {
+            self.record_hit();
+            return Ok(valid);
+        }
+
+        self.record_miss();
+        let valid = self.verifier.verify(message, signature, public_key)?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(key, valid, self.capacity);
+        }
+
+        Ok(valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context};
+
+    /// Counts the verifications that reach the wrapped verifier.
+    struct CountingVerifier {
+        verifier: Box<dyn Verifier>,
+        count: Arc<AtomicU64>,
+    }
+
+    impl Verifier for CountingVerifier {
+        fn algorithm_name(&self) -> &str {
+            self.verifier.algorithm_name()
+        }
+
+        fn verify(
+            &self,
+            message: &[u8],
+            signature: &Signature,
+            public_key: &PublicKey,
+        ) -> Result<bool, VerificationError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.verifier.verify(message, signature, public_key)
+        }
+    }
+
+    /// Verify that repeated verifications are answered from the cache, that a different signature
+    /// over the same message is not, and that the least recently used entry is evicted.
+    ///
+    /// 1. Verify a valid signature twice; the second verification is a cache hit
+    /// 2. Verify an invalid signature over the same message; it is a miss and is rejected
+    /// 3. Verify a third signature with a cache of two entries, evicting the valid signature, and
+    ///    verify that the valid signature is a miss again
+    #[test]
+    fn test_caching_verifier() {
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Unable to get public key");
+        let signer = context.new_signer(private_key);
+
+        let signature = signer.sign(b"payload").expect("Unable to sign");
+        let other_signature = signer.sign(b"other payload").expect("Unable to sign");
+
+        let count = Arc::new(AtomicU64::new(0));
+        let verifier = CachingVerifier::new(
+            Box::new(CountingVerifier {
+                verifier: context.new_verifier(),
+                count: count.clone(),
+            }),
+            2,
+        );
+        let stats = verifier.stats();
+
+        assert!(verifier
+            .verify(b"payload", &signature, &public_key)
+            .expect("Unable to verify"));
+        assert!(verifier
+            .verify(b"payload", &signature, &public_key)
+            .expect("Unable to verify"));
+        assert_eq!(1, count.load(Ordering::SeqCst));
+        assert_eq!(1, stats.hits());
+        assert_eq!(1, stats.misses());
+
+        assert!(!verifier
+            .verify(b"payload", &other_signature, &public_key)
+            .expect("Unable to verify"));
+        assert_eq!(2, count.load(Ordering::SeqCst));
+
+        assert!(verifier
+            .verify(b"other payload", &other_signature, &public_key)
+            .expect("Unable to verify"));
+        assert!(verifier
+            .verify(b"payload", &signature, &public_key)
+            .expect("Unable to verify"));
+        assert_eq!(4, count.load(Ordering::SeqCst));
+        assert_eq!(1, stats.hits());
+        assert_eq!(4, stats.misses());
+        assert!((stats.hit_rate() - 0.2).abs() < f64::EPSILON);
+    }
+
+    /// Verify that the cache key keeps the message and the signature apart, so that moving bytes
+    /// from the end of the message to the start of the signature does not produce the same key.
+    #[test]
+    fn test_cache_key_field_boundary() {
+        let public_key = PublicKey::new(vec![1]);
+        assert_ne!(
+            cache_key(
+                b"payload",
+                &Signature::new(b"signature".to_vec()),
+                &public_key
+            ),
+            cache_key(
+                b"payloadsig",
+                &Signature::new(b"nature".to_vec()),
+                &public_key
+            ),
+        );
+    }
+
+    /// Verify that a batch is answered from the cache where possible, that a signature repeated in
+    /// the batch is verified once, and that the batch's results are cached.
+    ///
+    /// 1. Verify a valid signature individually, which caches it
+    /// 2. Verify a batch of the cached signature, a new valid signature twice and an invalid
+    ///    signature; only the new and the invalid signatures reach the wrapped verifier
+    /// 3. Verify the new signature individually; it is a cache hit
+    #[test]
+    fn test_verify_batch() {
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Unable to get public key");
+        let signer = context.new_signer(private_key);
+
+        let signature = signer.sign(b"payload").expect("Unable to sign");
+        let other_signature = signer.sign(b"other payload").expect("Unable to sign");
+
+        let count = Arc::new(AtomicU64::new(0));
+        let verifier = CachingVerifier::new(
+            Box::new(CountingVerifier {
+                verifier: context.new_verifier(),
+                count: count.clone(),
+            }),
+            10,
+        );
+        let stats = verifier.stats();
+
+        assert!(verifier
+            .verify(b"payload", &signature, &public_key)
+            .expect("Unable to verify"));
+
+        let payload: &[u8] = b"payload";
+        let other_payload: &[u8] = b"other payload";
+        let results = verifier
+            .verify_batch(&[
+                (payload, &signature, &public_key),
+                (other_payload, &other_signature, &public_key),
+                (other_payload, &other_signature, &public_key),
+                (payload, &other_signature, &public_key),
+            ])
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Unable to verify batch");
+        assert_eq!(vec![true, true, true, false], results);
+        assert_eq!(3, count.load(Ordering::SeqCst));
+        assert_eq!(2, stats.hits());
+        assert_eq!(3, stats.misses());
+
+        assert!(verifier
+            .verify(b"other payload", &other_signature, &public_key)
+            .expect("Unable to verify"));
+        assert_eq!(3, count.load(Ordering::SeqCst));
+        assert_eq!(3, stats.hits());
+    }
+}
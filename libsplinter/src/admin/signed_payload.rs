@@ -0,0 +1,283 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of externally-signed circuit management payloads.
+//!
+//! Custodial signing services and browser wallets can construct and sign a
+//! `CircuitManagementPayload` themselves and submit the serialized bytes to a node, which only
+//! validates and relays it. Before the payload is handed to the admin service,
+//! [`verify_signed_payload`] checks that the header is well formed, that the header's action is
+//! the one carried by the payload, that the header's `payload_sha512` is the digest of that
+//! action, and that the signature over the header was made with the requester's key.
+
+use cylinder::{PublicKey, Signature, Verifier};
+use openssl::hash::{hash, MessageDigest};
+use protobuf::Message;
+
+use crate::error::InvalidArgumentError;
+use crate::hex::to_hex;
+use crate::protos::admin::{
+    CircuitManagementPayload, CircuitManagementPayload_Action as Action,
+    CircuitManagementPayload_Header as Header,
+};
+
+/// The header details of a signed circuit management payload that passed validation
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedPayloadInfo {
+    action: String,
+    requester: String,
+    requester_node_id: String,
+    payload_sha512: String,
+}
+
+impl SignedPayloadInfo {
+    /// Returns the name of the payload's action, such as `CIRCUIT_CREATE_REQUEST`
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// Returns the hex-encoded public key that signed the payload
+    pub fn requester(&self) -> &str {
+        &self.requester
+    }
+
+    /// Returns the ID of the node the payload was signed on behalf of
+    pub fn requester_node_id(&self) -> &str {
+        &self.requester_node_id
+    }
+
+    /// Returns the hex-encoded SHA-512 digest of the payload's action
+    pub fn payload_sha512(&self) -> &str {
+        &self.payload_sha512
+    }
+}
+
+/// Validates an externally-signed circuit management payload, returning its header details
+///
+/// # Errors
+///
+/// Returns an `InvalidArgumentError` if the header or signature is missing or malformed, if the
+/// payload does not carry the action named in its header, if the header's `payload_sha512` does
+/// not match the action, or if the signature does not verify with the requester's key.
+pub fn verify_signed_payload(
+    payload: &CircuitManagementPayload,
+    verifier: &dyn Verifier,
+) -> Result<SignedPayloadInfo, InvalidArgumentError> {
+    if payload.get_header().is_empty() {
+        return Err(invalid("header", "payload header must be set".into()));
+    }
+    if payload.get_signature().is_empty() {
+        return Err(invalid("signature", "payload signature must be set".into()));
+    }
+
+    let header = protobuf::parse_from_bytes::<Header>(payload.get_header())
+        .map_err(|err| invalid("header", format!("failed to parse payload header: {}", err)))?;
+
+    if header.get_requester().is_empty() {
+        return Err(invalid("header", "header must have a requester".into()));
+    }
+    if header.get_requester_node_id().is_empty() {
+        return Err(invalid(
+            "header",
+            "header must have a requester node ID".into(),
+        ));
+    }
+
+    let action_bytes = action_bytes(payload, header.get_action())?;
+    let digest = hash(MessageDigest::sha512(), &action_bytes)
+        .map_err(|err| invalid("payload", format!("failed to hash payload action: {}", err)))?;
+    if digest.as_ref() != header.get_payload_sha512() {
+        return Err(invalid(
+            "header",
+            "header payload_sha512 does not match the payload action".into(),
+        ));
+    }
+
+    let verified = verifier
+        .verify(
+            payload.get_header(),
+            &Signature::new(payload.get_signature().to_vec()),
+            &PublicKey::new(header.get_requester().to_vec()),
+        )
+        .unwrap_or(false);
+    if !verified {
+        return Err(invalid(
+            "signature",
+            "signature does not match the header and requester".into(),
+        ));
+    }
+
+    Ok(SignedPayloadInfo {
+        action: format!("{:?}", header.get_action()),
+        requester: to_hex(header.get_requester()),
+        requester_node_id: header.get_requester_node_id().to_string(),
+        payload_sha512: to_hex(header.get_payload_sha512()),
+    })
+}
+
+/// Returns the serialized action of the payload that matches the header's action
+fn action_bytes(
+    payload: &CircuitManagementPayload,
+    action: Action,
+) -> Result<Vec<u8>, InvalidArgumentError> {
+    let bytes = match action {
+        Action::CIRCUIT_PROPOSAL_VOTE if payload.has_circuit_proposal_vote() => {
+            payload.get_circuit_proposal_vote().write_to_bytes()
+        }
+        Action::CIRCUIT_CREATE_REQUEST if payload.has_circuit_create_request() => {
+            payload.get_circuit_create_request().write_to_bytes()
+        }
+        Action::CIRCUIT_UPDATE_ROSTER_REQUEST if payload.has_circuit_update_roster_request() => {
+            payload.get_circuit_update_roster_request().write_to_bytes()
+        }
+        Action::CIRCUIT_UPDATE_ADD_NODE if payload.has_circuit_update_add_node() => {
+            payload.get_circuit_update_add_node().write_to_bytes()
+        }
+        Action::CIRCUIT_UPDATE_REMOVE_NODE if payload.has_circuit_update_remove_node() => {
+            payload.get_circuit_update_remove_node().write_to_bytes()
+        }
+        Action::CIRCUIT_UPDATE_APPLICATION_METADATA_REQUEST
+            if payload.has_circuit_update_application_metadata_request() =>
+        {
+            payload
+                .get_circuit_update_application_metadata_request()
+                .write_to_bytes()
+        }
+        Action::CIRCUIT_JOIN_REQUEST if payload.has_circuit_join_request() => {
+            payload.get_circuit_join_request().write_to_bytes()
+        }
+        Action::CIRCUIT_DESTROY_REQUEST if payload.has_circuit_destroy_request() => {
+            payload.get_circuit_destroy_request().write_to_bytes()
+        }
+        Action::CIRCUIT_ABANDON if payload.has_circuit_abandon() => {
+            payload.get_circuit_abandon().write_to_bytes()
+        }
+        Action::ACTION_UNSET => {
+            return Err(invalid("header", "header action must be set".into()));
+        }
+        action => {
+            return Err(invalid(
+                "payload",
+                format!("payload does not contain the {:?} action", action),
+            ));
+        }
+    };
+
+    bytes.map_err(|err| invalid("payload", format!("failed to serialize action: {}", err)))
+}
+
+fn invalid(argument: &str, message: String) -> InvalidArgumentError {
+    InvalidArgumentError::new(argument.to_string(), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::{secp256k1::Secp256k1Context, Context, Signer};
+
+    use crate::protos::admin::{CircuitProposalVote, CircuitProposalVote_Vote};
+
+    fn vote() -> CircuitProposalVote {
+        let mut vote = CircuitProposalVote::new();
+        vote.set_circuit_id("01234-ABCDE".into());
+        vote.set_circuit_hash("abcdef".into());
+        vote.set_vote(CircuitProposalVote_Vote::ACCEPT);
+        vote
+    }
+
+    /// Builds a vote payload signed by the given signer, the way an external signer would
+    fn signed_payload(signer: &dyn Signer, vote: CircuitProposalVote) -> CircuitManagementPayload {
+        let vote_bytes = vote.write_to_bytes().expect("Failed to serialize vote");
+
+        let mut header = Header::new();
+        header.set_action(Action::CIRCUIT_PROPOSAL_VOTE);
+        header.set_requester(
+            signer
+                .public_key()
+                .expect("Failed to get public key")
+                .into_bytes(),
+        );
+        header.set_payload_sha512(
+            hash(MessageDigest::sha512(), &vote_bytes)
+                .expect("Failed to hash vote")
+                .to_vec(),
+        );
+        header.set_requester_node_id("node-a".into());
+        let header_bytes = header.write_to_bytes().expect("Failed to serialize header");
+
+        let mut payload = CircuitManagementPayload::new();
+        payload.set_signature(
+            signer
+                .sign(&header_bytes)
+                .expect("Failed to sign header")
+                .take_bytes(),
+        );
+        payload.set_header(header_bytes);
+        payload.set_circuit_proposal_vote(vote);
+        payload
+    }
+
+    /// Verify that a correctly signed payload is accepted and its header details are returned.
+    #[test]
+    fn test_verify_signed_payload() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let verifier = context.new_verifier();
+
+        let info = verify_signed_payload(&signed_payload(&*signer, vote()), &*verifier)
+            .expect("Payload should be valid");
+
+        assert_eq!(info.action(), "CIRCUIT_PROPOSAL_VOTE");
+        assert_eq!(info.requester_node_id(), "node-a");
+        assert_eq!(
+            info.requester(),
+            to_hex(signer.public_key().expect("Failed to get key").as_slice())
+        );
+    }
+
+    /// Verify that a payload whose action was changed after signing is rejected, as is a payload
+    /// that does not carry the action named in its header.
+    #[test]
+    fn test_verify_signed_payload_action_mismatch() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let verifier = context.new_verifier();
+
+        let mut payload = signed_payload(&*signer, vote());
+        let mut tampered = vote();
+        tampered.set_vote(CircuitProposalVote_Vote::REJECT);
+        payload.set_circuit_proposal_vote(tampered);
+        assert!(verify_signed_payload(&payload, &*verifier).is_err());
+
+        let mut payload = signed_payload(&*signer, vote());
+        payload.clear_circuit_proposal_vote();
+        assert!(verify_signed_payload(&payload, &*verifier).is_err());
+    }
+
+    /// Verify that a payload signed by a key other than the requester's is rejected.
+    #[test]
+    fn test_verify_signed_payload_bad_signature() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let other_signer = context.new_signer(context.new_random_private_key());
+        let verifier = context.new_verifier();
+
+        let mut payload = signed_payload(&*signer, vote());
+        let other_payload = signed_payload(&*other_signer, vote());
+        payload.set_signature(other_payload.get_signature().to_vec());
+
+        assert!(verify_signed_payload(&payload, &*verifier).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for loading the admin service's circuits and starting their services on several
+//! threads at startup.
+
+use std::sync::Arc;
+use std::thread;
+
+use crate::error::InternalError;
+
+/// The number of threads that circuits are loaded on at startup
+pub(crate) const STARTUP_WORKERS: usize = 4;
+
+/// Applies `f` to each of the items on up to `workers` threads, returning the results in the
+/// order of the items.
+///
+/// The items are split into contiguous chunks, one per thread; small inputs are handled on the
+/// calling thread.
+pub(crate) fn parallel_map<T, U, F>(
+    items: Vec<T>,
+    workers: usize,
+    f: F,
+) -> Result<Vec<U>, InternalError>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    F: Fn(T) -> U + Send + Sync + 'static,
+{
+    if workers <= 1 || items.len() < workers * 2 {
+        return Ok(items.into_iter().map(f).collect());
+    }
+
+    let total = items.len();
+    let chunk_size = total / workers + 1;
+    let f = Arc::new(f);
+
+    let mut items = items.into_iter();
+    let mut join_handles = Vec::with_capacity(workers);
+    loop {
+        let chunk = items.by_ref().take(chunk_size).collect::<Vec<_>>();
+        if chunk.is_empty() {
+            break;
+        }
+        let f = Arc::clone(&f);
+        let join_handle = thread::Builder::new()
+            .name(format!("AdminStartupWorker-{}", join_handles.len()))
+            .spawn(move || chunk.into_iter().map(&*f).collect::<Vec<_>>())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        join_handles.push(join_handle);
+    }
+
+    let mut results = Vec::with_capacity(total);
+    for join_handle in join_handles {
+        results.extend(join_handle.join().map_err(|_| {
+            InternalError::with_message("Admin startup worker thread panicked".into())
+        })?);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the results of `parallel_map` are returned in the order of the items, both
+    /// when the items are split across threads and when they are handled on the calling thread.
+    #[test]
+    fn test_parallel_map_order() {
+        let items = (0..1001).collect::<Vec<u64>>();
+        let results = parallel_map(items.clone(), STARTUP_WORKERS, |item| item * 2)
+            .expect("Unable to map items");
+        assert_eq!(
+            items.iter().map(|item| item * 2).collect::<Vec<_>>(),
+            results
+        );
+
+        let results = parallel_map(vec![1u64, 2, 3], STARTUP_WORKERS, |item| item + 1)
+            .expect("Unable to map items");
+        assert_eq!(vec![2, 3, 4], results);
+    }
+}
@@ -14,7 +14,10 @@
 
 //! Structs for building circuits
 
+use std::fmt;
+
 use crate::admin::messages::is_valid_circuit_id;
+use crate::circuit::qos::QosLimits;
 use crate::error::InvalidStateError;
 
 use super::{ProposedCircuit, Service};
@@ -25,12 +28,20 @@ pub struct Circuit {
     id: String,
     roster: Vec<Service>,
     members: Vec<String>,
+    relay_members: Vec<String>,
+    observer_members: Vec<String>,
     authorization_type: AuthorizationType,
     persistence: PersistenceType,
     durability: DurabilityType,
     routes: RouteType,
     circuit_management_type: String,
     display_name: Option<String>,
+    qos: QosLimits,
+    circuit_status: CircuitStatus,
+    created_at: Option<u64>,
+    updated_at: Option<u64>,
+    proposer_public_key: Option<Vec<u8>>,
+    proposer_node_id: Option<String>,
 }
 
 impl Circuit {
@@ -49,6 +60,16 @@ impl Circuit {
         &self.members
     }
 
+    /// Returns the list of node IDs of the members that may relay messages for other members
+    pub fn relay_members(&self) -> &[String] {
+        &self.relay_members
+    }
+
+    /// Returns the list of node IDs of the members that only observe the circuit's committed state
+    pub fn observer_members(&self) -> &[String] {
+        &self.observer_members
+    }
+
     /// Returns the authorization type of the circuit
     pub fn authorization_type(&self) -> &AuthorizationType {
         &self.authorization_type
@@ -78,6 +99,123 @@ impl Circuit {
     pub fn display_name(&self) -> &Option<String> {
         &self.display_name
     }
+
+    /// Returns the quality-of-service limits for the circuit
+    pub fn qos(&self) -> &QosLimits {
+        &self.qos
+    }
+
+    /// Returns the lifecycle status of the circuit
+    pub fn circuit_status(&self) -> &CircuitStatus {
+        &self.circuit_status
+    }
+
+    /// Returns the time the circuit was created, in seconds since the Unix epoch, if known
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    /// Returns the time the circuit was last modified, in seconds since the Unix epoch, if known
+    pub fn updated_at(&self) -> Option<u64> {
+        self.updated_at
+    }
+
+    /// Returns the public key of the requester of the circuit's proposal, if known
+    pub fn proposer_public_key(&self) -> Option<&[u8]> {
+        self.proposer_public_key.as_deref()
+    }
+
+    /// Returns the ID of the node the circuit's proposal was submitted to, if known
+    pub fn proposer_node_id(&self) -> Option<&str> {
+        self.proposer_node_id.as_deref()
+    }
+
+    pub fn builder(&self) -> CircuitBuilder {
+        CircuitBuilder {
+            circuit_id: Some(self.id.clone()),
+            roster: Some(self.roster.clone()),
+            members: Some(self.members.clone()),
+            relay_members: Some(self.relay_members.clone()),
+            observer_members: Some(self.observer_members.clone()),
+            authorization_type: Some(self.authorization_type.clone()),
+            persistence: Some(self.persistence.clone()),
+            durability: Some(self.durability.clone()),
+            routes: Some(self.routes.clone()),
+            circuit_management_type: Some(self.circuit_management_type.clone()),
+            display_name: self.display_name.clone(),
+            qos: Some(self.qos),
+            circuit_status: Some(self.circuit_status),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            proposer_public_key: self.proposer_public_key.clone(),
+            proposer_node_id: self.proposer_node_id.clone(),
+        }
+    }
+}
+
+/// The lifecycle status of a circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitStatus {
+    /// The circuit has been proposed but has not yet been accepted by all members
+    Proposed,
+    /// The circuit has been accepted and is ready for use
+    Active,
+    /// A member has requested that the circuit be disbanded
+    Disbanding,
+    /// The circuit has been disbanded and its services have been stopped
+    Disbanded,
+    /// The circuit has been abandoned by this node and its services have been stopped
+    Abandoned,
+    /// The circuit's service state is being removed from this node
+    Purging,
+}
+
+impl CircuitStatus {
+    /// Returns true if a circuit in this status may be moved to the `next` status
+    ///
+    /// A circuit may always remain in its current status. Otherwise, a proposed circuit may
+    /// become active or be abandoned, an active circuit may begin disbanding or be abandoned, a
+    /// disbanding circuit may return to active, complete disbanding or be abandoned, and a
+    /// disbanded or abandoned circuit may be purged.
+    pub fn can_transition_to(&self, next: &CircuitStatus) -> bool {
+        use CircuitStatus::*;
+
+        if self == next {
+            return true;
+        }
+
+        matches!(
+            (self, next),
+            (Proposed, Active)
+                | (Proposed, Abandoned)
+                | (Active, Disbanding)
+                | (Active, Abandoned)
+                | (Disbanding, Active)
+                | (Disbanding, Disbanded)
+                | (Disbanding, Abandoned)
+                | (Disbanded, Purging)
+                | (Abandoned, Purging)
+        )
+    }
+}
+
+impl Default for CircuitStatus {
+    fn default() -> Self {
+        CircuitStatus::Active
+    }
+}
+
+impl fmt::Display for CircuitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CircuitStatus::Proposed => "Proposed",
+            CircuitStatus::Active => "Active",
+            CircuitStatus::Disbanding => "Disbanding",
+            CircuitStatus::Disbanded => "Disbanded",
+            CircuitStatus::Abandoned => "Abandoned",
+            CircuitStatus::Purging => "Purging",
+        })
+    }
 }
 
 /// What type of authorization the circuit requires
@@ -122,12 +260,20 @@ pub struct CircuitBuilder {
     circuit_id: Option<String>,
     roster: Option<Vec<Service>>,
     members: Option<Vec<String>>,
+    relay_members: Option<Vec<String>>,
+    observer_members: Option<Vec<String>>,
     authorization_type: Option<AuthorizationType>,
     persistence: Option<PersistenceType>,
     durability: Option<DurabilityType>,
     routes: Option<RouteType>,
     circuit_management_type: Option<String>,
     display_name: Option<String>,
+    qos: Option<QosLimits>,
+    circuit_status: Option<CircuitStatus>,
+    created_at: Option<u64>,
+    updated_at: Option<u64>,
+    proposer_public_key: Option<Vec<u8>>,
+    proposer_node_id: Option<String>,
 }
 
 impl CircuitBuilder {
@@ -151,6 +297,16 @@ impl CircuitBuilder {
         self.members.clone()
     }
 
+    /// Returns the list of relay member node IDs in the builder
+    pub fn relay_members(&self) -> Option<Vec<String>> {
+        self.relay_members.clone()
+    }
+
+    /// Returns the list of observer member node IDs in the builder
+    pub fn observer_members(&self) -> Option<Vec<String>> {
+        self.observer_members.clone()
+    }
+
     /// Returns the authorization type in the builder
     pub fn authorization_type(&self) -> Option<AuthorizationType> {
         self.authorization_type.clone()
@@ -181,6 +337,36 @@ impl CircuitBuilder {
         self.display_name.clone()
     }
 
+    /// Returns the quality-of-service limits in the builder
+    pub fn qos(&self) -> Option<QosLimits> {
+        self.qos
+    }
+
+    /// Returns the lifecycle status in the builder
+    pub fn circuit_status(&self) -> Option<CircuitStatus> {
+        self.circuit_status
+    }
+
+    /// Returns the creation time in the builder
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    /// Returns the last-modified time in the builder
+    pub fn updated_at(&self) -> Option<u64> {
+        self.updated_at
+    }
+
+    /// Returns the proposer's public key in the builder
+    pub fn proposer_public_key(&self) -> Option<Vec<u8>> {
+        self.proposer_public_key.clone()
+    }
+
+    /// Returns the proposer's node ID in the builder
+    pub fn proposer_node_id(&self) -> Option<String> {
+        self.proposer_node_id.clone()
+    }
+
     /// Sets the circuit ID
     ///
     /// # Arguments
@@ -211,6 +397,27 @@ impl CircuitBuilder {
         self
     }
 
+    /// Sets the list of node IDs for the members that may relay messages for other members
+    ///
+    /// # Arguments
+    ///
+    ///  * `relay_members` - List of node IDs, each of which must also be a member
+    pub fn with_relay_members(mut self, relay_members: &[String]) -> CircuitBuilder {
+        self.relay_members = Some(relay_members.into());
+        self
+    }
+
+    /// Sets the list of node IDs for the members that receive the circuit's committed state but
+    /// do not take part in consensus or submit changes
+    ///
+    /// # Arguments
+    ///
+    ///  * `observer_members` - List of node IDs, each of which must also be a member
+    pub fn with_observer_members(mut self, observer_members: &[String]) -> CircuitBuilder {
+        self.observer_members = Some(observer_members.into());
+        self
+    }
+
     /// Sets the authorization type
     ///
     /// # Arguments
@@ -274,6 +481,66 @@ impl CircuitBuilder {
         self
     }
 
+    /// Sets the quality-of-service limits for the circuit
+    ///
+    /// # Arguments
+    ///
+    ///  * `qos` - The limits the circuit's messages are held to
+    pub fn with_qos(mut self, qos: &QosLimits) -> CircuitBuilder {
+        self.qos = Some(*qos);
+        self
+    }
+
+    /// Sets the lifecycle status of the circuit
+    ///
+    /// # Arguments
+    ///
+    ///  * `circuit_status` - The status of the circuit; defaults to `Active`
+    pub fn with_circuit_status(mut self, circuit_status: &CircuitStatus) -> CircuitBuilder {
+        self.circuit_status = Some(*circuit_status);
+        self
+    }
+
+    /// Sets the time the circuit was created
+    ///
+    /// # Arguments
+    ///
+    ///  * `created_at` - The creation time, in seconds since the Unix epoch
+    pub fn with_created_at(mut self, created_at: u64) -> CircuitBuilder {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Sets the time the circuit was last modified
+    ///
+    /// # Arguments
+    ///
+    ///  * `updated_at` - The last-modified time, in seconds since the Unix epoch
+    pub fn with_updated_at(mut self, updated_at: u64) -> CircuitBuilder {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// Sets the public key of the requester of the circuit's proposal
+    ///
+    /// # Arguments
+    ///
+    ///  * `proposer_public_key` - The public key that signed the circuit's create request
+    pub fn with_proposer_public_key(mut self, proposer_public_key: &[u8]) -> CircuitBuilder {
+        self.proposer_public_key = Some(proposer_public_key.into());
+        self
+    }
+
+    /// Sets the ID of the node the circuit's proposal was submitted to
+    ///
+    /// # Arguments
+    ///
+    ///  * `proposer_node_id` - The node ID of the requester of the circuit's proposal
+    pub fn with_proposer_node_id(mut self, proposer_node_id: &str) -> CircuitBuilder {
+        self.proposer_node_id = Some(proposer_node_id.into());
+        self
+    }
+
     /// Builds a `Circuit`
     ///
     /// Returns an error if the circuit ID, roster, members or circuit management
@@ -307,6 +574,39 @@ impl CircuitBuilder {
 
         members.sort();
 
+        let mut relay_members = self.relay_members.unwrap_or_default();
+        if let Some(relay) = relay_members.iter().find(|relay| !members.contains(relay)) {
+            return Err(InvalidStateError::with_message(format!(
+                "relay member {} is not a member of the circuit",
+                relay
+            )));
+        }
+
+        relay_members.sort();
+
+        let mut observer_members = self.observer_members.unwrap_or_default();
+        if let Some(observer) = observer_members
+            .iter()
+            .find(|observer| !members.contains(observer))
+        {
+            return Err(InvalidStateError::with_message(format!(
+                "observer member {} is not a member of the circuit",
+                observer
+            )));
+        }
+
+        if !members.is_empty()
+            && members
+                .iter()
+                .all(|member| observer_members.contains(member))
+        {
+            return Err(InvalidStateError::with_message(
+                "at least one member of the circuit must not be an observer".to_string(),
+            ));
+        }
+
+        observer_members.sort();
+
         let authorization_type = self.authorization_type.unwrap_or(AuthorizationType::Trust);
 
         let persistence = self.persistence.unwrap_or_else(PersistenceType::default);
@@ -323,16 +623,28 @@ impl CircuitBuilder {
 
         let display_name = self.display_name;
 
+        let qos = self.qos.unwrap_or_default();
+
+        let circuit_status = self.circuit_status.unwrap_or_default();
+
         let circuit = Circuit {
             id: circuit_id,
             roster,
             members,
+            relay_members,
+            observer_members,
             authorization_type,
             persistence,
             durability,
             routes,
             circuit_management_type,
             display_name,
+            qos,
+            circuit_status,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            proposer_public_key: self.proposer_public_key,
+            proposer_node_id: self.proposer_node_id,
         };
 
         Ok(circuit)
@@ -349,12 +661,55 @@ impl From<ProposedCircuit> for Circuit {
                 .iter()
                 .map(|node| node.node_id().to_string())
                 .collect(),
+            relay_members: circuit
+                .members()
+                .iter()
+                .filter(|node| node.is_relay())
+                .map(|node| node.node_id().to_string())
+                .collect(),
+            observer_members: circuit
+                .members()
+                .iter()
+                .filter(|node| node.is_observer())
+                .map(|node| node.node_id().to_string())
+                .collect(),
             authorization_type: circuit.authorization_type().clone(),
             persistence: circuit.persistence().clone(),
             durability: circuit.durability().clone(),
             routes: circuit.routes().clone(),
             circuit_management_type: circuit.circuit_management_type().into(),
             display_name: circuit.display_name().clone(),
+            qos: *circuit.qos(),
+            circuit_status: CircuitStatus::Active,
+            created_at: None,
+            updated_at: None,
+            proposer_public_key: None,
+            proposer_node_id: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that only the legal lifecycle transitions are allowed.
+    #[test]
+    fn circuit_status_transitions() {
+        use CircuitStatus::*;
+
+        assert!(Proposed.can_transition_to(&Active));
+        assert!(Active.can_transition_to(&Active));
+        assert!(Active.can_transition_to(&Disbanding));
+        assert!(Disbanding.can_transition_to(&Disbanded));
+        assert!(Disbanding.can_transition_to(&Active));
+        assert!(Disbanded.can_transition_to(&Purging));
+        assert!(Abandoned.can_transition_to(&Purging));
+
+        assert!(!Active.can_transition_to(&Proposed));
+        assert!(!Active.can_transition_to(&Disbanded));
+        assert!(!Disbanded.can_transition_to(&Active));
+        assert!(!Purging.can_transition_to(&Active));
+        assert!(!Proposed.can_transition_to(&Purging));
+    }
+}
@@ -30,6 +30,8 @@ pub struct CircuitProposal {
     votes: Vec<VoteRecord>,
     requester: Vec<u8>,
     requester_node_id: String,
+    created_at: Option<u64>,
+    updated_at: Option<u64>,
 }
 
 impl CircuitProposal {
@@ -68,15 +70,28 @@ impl CircuitProposal {
         &self.requester_node_id
     }
 
+    /// Returns the time the proposal was created, in seconds since the Unix epoch, if known
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    /// Returns the time the proposal was last modified, in seconds since the Unix epoch, if known
+    pub fn updated_at(&self) -> Option<u64> {
+        self.updated_at
+    }
+
     pub fn builder(&self) -> CircuitProposalBuilder {
-        CircuitProposalBuilder::new()
+        let mut builder = CircuitProposalBuilder::new()
             .with_proposal_type(self.proposal_type())
             .with_circuit_id(self.circuit_id())
             .with_circuit_hash(self.circuit_hash())
             .with_circuit(self.circuit())
             .with_votes(self.votes())
             .with_requester(self.requester())
-            .with_requester_node_id(self.requester_node_id())
+            .with_requester_node_id(self.requester_node_id());
+        builder.created_at = self.created_at;
+        builder.updated_at = self.updated_at;
+        builder
     }
 
     pub fn from_proto(mut proto: admin::CircuitProposal) -> Result<Self, InvalidStateError> {
@@ -107,6 +122,8 @@ impl CircuitProposal {
             votes,
             requester: proto.take_requester(),
             requester_node_id: proto.take_requester_node_id(),
+            created_at: None,
+            updated_at: None,
         })
     }
 
@@ -150,6 +167,8 @@ pub struct CircuitProposalBuilder {
     votes: Option<Vec<VoteRecord>>,
     requester: Option<Vec<u8>>,
     requester_node_id: Option<String>,
+    created_at: Option<u64>,
+    updated_at: Option<u64>,
 }
 
 impl CircuitProposalBuilder {
@@ -193,6 +212,16 @@ impl CircuitProposalBuilder {
         self.requester_node_id.clone()
     }
 
+    /// Returns the creation time of the proposal
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    /// Returns the last-modified time of the proposal
+    pub fn updated_at(&self) -> Option<u64> {
+        self.updated_at
+    }
+
     /// Set the proposal type of the circuit proposal
     ///
     /// # Arguments
@@ -265,6 +294,26 @@ impl CircuitProposalBuilder {
         self
     }
 
+    /// Sets the time the proposal was created
+    ///
+    /// # Arguments
+    ///
+    ///  * `created_at` - The creation time, in seconds since the Unix epoch
+    pub fn with_created_at(mut self, created_at: u64) -> CircuitProposalBuilder {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Sets the time the proposal was last modified
+    ///
+    /// # Arguments
+    ///
+    ///  * `updated_at` - The last-modified time, in seconds since the Unix epoch
+    pub fn with_updated_at(mut self, updated_at: u64) -> CircuitProposalBuilder {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
     /// Builds a `CircuitProposal`
     ///
     /// Returns an error if the circuit ID, circuit, circuit hash, requester, or requester node id
@@ -326,6 +375,8 @@ impl CircuitProposalBuilder {
             votes,
             requester,
             requester_node_id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
         })
     }
 }
@@ -27,9 +27,11 @@ mod schema;
 
 use diesel::r2d2::{ConnectionManager, Pool};
 
+#[cfg(feature = "admin-parallel-startup")]
+use crate::admin::startup::parallel_map;
 use crate::admin::store::{
     error::AdminServiceStoreError, AdminServiceStore, Circuit, CircuitNode, CircuitPredicate,
-    CircuitProposal, Service, ServiceId,
+    CircuitProposal, CircuitSort, CircuitSortField, CircuitStatus, Service, ServiceId,
 };
 use operations::add_circuit::AdminServiceStoreAddCircuitOperation as _;
 use operations::add_proposal::AdminServiceStoreAddProposalOperation as _;
@@ -44,6 +46,7 @@ use operations::list_services::AdminServiceStoreListServicesOperation as _;
 use operations::remove_circuit::AdminServiceStoreRemoveCircuitOperation as _;
 use operations::remove_proposal::AdminServiceStoreRemoveProposalOperation as _;
 use operations::update_circuit::AdminServiceStoreUpdateCircuitOperation as _;
+use operations::update_circuit_status::AdminServiceStoreUpdateCircuitStatusOperation as _;
 use operations::update_proposal::AdminServiceStoreUpdateProposalOperation as _;
 use operations::upgrade::AdminServiceStoreUpgradeProposalToCircuitOperation as _;
 use operations::AdminServiceStoreOperations;
@@ -64,6 +67,50 @@ impl<C: diesel::Connection> DieselAdminServiceStore<C> {
     }
 }
 
+#[cfg(feature = "admin-parallel-startup")]
+impl<C> DieselAdminServiceStore<C>
+where
+    C: diesel::Connection + Send,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
+{
+    /// Lists all circuits on up to `workers` threads. The circuit IDs are split into ranges, and
+    /// each range is loaded with its own connection from the pool.
+    fn list_circuits_in_parallel(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        let circuit_ids =
+            AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_circuit_ids()?;
+
+        // at least two ranges per worker, so that large stores are split across the workers
+        let range_size = (circuit_ids.len() / (workers.max(1) * 2)).max(1);
+        let mut circuit_ids = circuit_ids.into_iter();
+        let mut ranges = vec![];
+        loop {
+            let range = circuit_ids.by_ref().take(range_size).collect::<Vec<_>>();
+            if range.is_empty() {
+                break;
+            }
+            ranges.push(range);
+        }
+
+        let connection_pool = self.connection_pool.clone();
+        let mut circuits = vec![];
+        for range in parallel_map(ranges, workers, move |circuit_ids| {
+            AdminServiceStoreOperations::new(&*connection_pool.get()?)
+                .list_circuits_by_id(&circuit_ids)
+        })
+        .map_err(AdminServiceStoreError::InternalError)?
+        {
+            circuits.extend(range?);
+        }
+
+        Ok(circuits)
+    }
+}
+
 #[cfg(feature = "sqlite")]
 impl Clone for DieselAdminServiceStore<diesel::sqlite::SqliteConnection> {
     fn clone(&self) -> Self {
@@ -107,7 +154,19 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::pg::PgConnection> {
         &self,
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
-        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_proposals(predicates)
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_proposals(
+            predicates,
+            &CircuitSort::descending(CircuitSortField::CircuitId),
+        )
+    }
+
+    fn list_proposals_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .list_proposals(predicates, sort)
     }
 
     fn add_circuit(
@@ -122,6 +181,15 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::pg::PgConnection> {
         AdminServiceStoreOperations::new(&*self.connection_pool.get()?).update_circuit(circuit)
     }
 
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .update_circuit_status(circuit_id, status)
+    }
+
     fn remove_circuit(&self, circuit_id: &str) -> Result<(), AdminServiceStoreError> {
         AdminServiceStoreOperations::new(&*self.connection_pool.get()?).remove_circuit(circuit_id)
     }
@@ -134,7 +202,24 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::pg::PgConnection> {
         &self,
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
-        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_circuits(predicates)
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_circuits(
+            predicates,
+            &CircuitSort::descending(CircuitSortField::CircuitId),
+        )
+    }
+
+    fn list_circuits_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .list_circuits(predicates, sort)
+    }
+
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_all_circuits(&self, workers: usize) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        self.list_circuits_in_parallel(workers)
     }
 
     fn upgrade_proposal_to_circuit(&self, circuit_id: &str) -> Result<(), AdminServiceStoreError> {
@@ -196,7 +281,19 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::sqlite::SqliteConnect
         &self,
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
-        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_proposals(predicates)
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_proposals(
+            predicates,
+            &CircuitSort::descending(CircuitSortField::CircuitId),
+        )
+    }
+
+    fn list_proposals_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .list_proposals(predicates, sort)
     }
 
     fn add_circuit(
@@ -211,6 +308,15 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::sqlite::SqliteConnect
         AdminServiceStoreOperations::new(&*self.connection_pool.get()?).update_circuit(circuit)
     }
 
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .update_circuit_status(circuit_id, status)
+    }
+
     fn remove_circuit(&self, circuit_id: &str) -> Result<(), AdminServiceStoreError> {
         AdminServiceStoreOperations::new(&*self.connection_pool.get()?).remove_circuit(circuit_id)
     }
@@ -223,7 +329,24 @@ impl AdminServiceStore for DieselAdminServiceStore<diesel::sqlite::SqliteConnect
         &self,
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
-        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_circuits(predicates)
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?).list_circuits(
+            predicates,
+            &CircuitSort::descending(CircuitSortField::CircuitId),
+        )
+    }
+
+    fn list_circuits_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
+        AdminServiceStoreOperations::new(&*self.connection_pool.get()?)
+            .list_circuits(predicates, sort)
+    }
+
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_all_circuits(&self, workers: usize) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        self.list_circuits_in_parallel(workers)
     }
 
     fn upgrade_proposal_to_circuit(&self, circuit_id: &str) -> Result<(), AdminServiceStoreError> {
@@ -493,7 +616,8 @@ pub mod tests {
     /// 5. Fetch Proposal from store
     /// 6. Validate fetched proposal is the same as the proposal added
     /// 7. Call upgrade_proposal_to_circuit for the proposal
-    /// 8. Fetch the new circuit and validate it is as expected
+    /// 8. Fetch the new circuit and validate it is as expected, with its creation time and
+    ///    proposer recorded
     #[test]
     fn test_upgrade_proposals() {
         let pool = create_connection_pool_and_migrate();
@@ -527,7 +651,96 @@ pub mod tests {
             .expect("Unable to get circuit")
             .expect("Got None when expecting circuit");
 
-        assert_eq!(create_circuit(), fetched_circuit);
+        // the creation time is recorded when the proposal is upgraded
+        let created_at = fetched_circuit
+            .created_at()
+            .expect("Upgraded circuit has no creation time");
+        let expected_circuit = create_circuit()
+            .builder()
+            .with_created_at(created_at)
+            .with_updated_at(created_at)
+            .with_proposer_public_key(proposal.requester())
+            .with_proposer_node_id("acme-node-000")
+            .build()
+            .expect("Unable to build circuit");
+
+        assert_eq!(expected_circuit, fetched_circuit);
+    }
+
+    /// Verify that the creation and modification times and the proposer of circuits and
+    /// proposals are stored and fetched as given
+    ///
+    /// 1. Run sqlite migrations
+    /// 2. Create DieselAdminServiceStore
+    /// 3. Add a proposal with creation and modification times, and validate the fetched proposal
+    /// 4. Update the proposal's modification time, and validate the fetched proposal
+    /// 5. Add a circuit with creation and modification times and a proposer, and validate the
+    ///    fetched and listed circuits
+    #[test]
+    fn test_circuit_and_proposal_metadata() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselAdminServiceStore::new(pool);
+
+        let proposal = create_proposal()
+            .builder()
+            .with_created_at(1_600_000_000)
+            .with_updated_at(1_600_000_000)
+            .build()
+            .expect("Unable to build proposal");
+        store
+            .add_proposal(proposal.clone())
+            .expect("Unable to add circuit proposal");
+        assert_eq!(
+            Some(proposal.clone()),
+            store
+                .get_proposal("WBKLF-BBBBB")
+                .expect("Unable to get proposal")
+        );
+
+        let updated_proposal = proposal
+            .builder()
+            .with_updated_at(1_600_000_100)
+            .build()
+            .expect("Unable to build proposal");
+        store
+            .update_proposal(updated_proposal.clone())
+            .expect("Unable to update proposal");
+        let fetched_proposal = store
+            .get_proposal("WBKLF-BBBBB")
+            .expect("Unable to get proposal")
+            .expect("Got None when expecting proposal");
+        assert_eq!(fetched_proposal.created_at(), Some(1_600_000_000));
+        assert_eq!(fetched_proposal.updated_at(), Some(1_600_000_100));
+
+        let circuit = CircuitBuilder::default()
+            .with_circuit_id("WBKLF-CCCCC")
+            .with_roster(create_circuit().roster())
+            .with_members(create_circuit().members())
+            .with_circuit_management_type("gameroom")
+            .with_created_at(1_600_000_000)
+            .with_updated_at(1_600_000_200)
+            .with_proposer_public_key(proposal.requester())
+            .with_proposer_node_id("acme-node-000")
+            .build()
+            .expect("Unable to build circuit");
+        store
+            .add_circuit(circuit.clone(), create_nodes())
+            .expect("Unable to add circuit");
+
+        assert_eq!(
+            Some(circuit.clone()),
+            store
+                .get_circuit("WBKLF-CCCCC")
+                .expect("Unable to get circuit")
+        );
+        assert_eq!(
+            vec![circuit],
+            store
+                .list_circuits(&[])
+                .expect("Unable to list circuits")
+                .collect::<Vec<_>>()
+        );
     }
 
     /// Verify that a circuit can be added to the store correctly and then fetched from the store
@@ -653,6 +866,111 @@ pub mod tests {
         assert_eq!(circuits.len(), 2);
     }
 
+    /// Verify that listing all circuits on several threads returns the same circuits, in the same
+    /// order, as listing them on a single connection
+    ///
+    /// 1. Run sqlite migrations
+    /// 2. Create DieselAdminServiceStore
+    /// 3. Add ten circuits
+    /// 4. List all circuits on two threads, validate the circuits match `list_circuits`
+    #[cfg(feature = "admin-parallel-startup")]
+    #[test]
+    fn test_list_all_circuits() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselAdminServiceStore::new(pool);
+
+        let template = create_circuit();
+        for index in 0..10 {
+            let circuit = CircuitBuilder::default()
+                .with_circuit_id(&format!("WBKLF-{:05}", index))
+                .with_roster(template.roster())
+                .with_members(template.members())
+                .with_circuit_management_type(template.circuit_management_type())
+                .build()
+                .expect("Unable to build circuit");
+            store
+                .add_circuit(circuit, create_nodes())
+                .expect("Unable to add circuit");
+        }
+
+        let expected = store
+            .list_circuits(&[])
+            .expect("Unable to list circuits")
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), 10);
+
+        let circuits = store
+            .list_all_circuits(2)
+            .expect("Unable to list all circuits");
+        assert_eq!(expected, circuits);
+    }
+
+    /// Verify that circuits can be filtered by status and by multiple members, and sorted
+    ///
+    /// 1. Run sqlite migrations
+    /// 2. Create DieselAdminServiceStore
+    /// 3. Add two circuits that share one member
+    /// 4. List circuits with a predicate for both members of the second circuit, validate only
+    ///    the second circuit is returned
+    /// 5. List circuits sorted by ID and by management type, validate the order
+    /// 6. Move the first circuit to disbanding and list the active circuits, validate only the
+    ///    second circuit is returned
+    #[test]
+    fn test_list_circuits_filtered_and_sorted() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselAdminServiceStore::new(pool);
+
+        let circuit = create_circuit();
+        let extra_circuit = create_extra_circuit();
+
+        store
+            .add_circuit(circuit.clone(), create_nodes())
+            .expect("Unable to add circuit");
+        store
+            .add_circuit(extra_circuit.clone(), create_extra_nodes())
+            .expect("Unable to add circuit");
+
+        let circuits = store
+            .list_circuits(&vec![CircuitPredicate::MembersInclude(vec![
+                "acme-node-000".to_string(),
+                "gumbo-node-000".to_string(),
+            ])])
+            .expect("Unable to list circuits with members include predicate")
+            .collect::<Vec<_>>();
+        assert_eq!(circuits, vec![extra_circuit.clone()]);
+
+        let circuits = store
+            .list_circuits_sorted(&[], &CircuitSort::ascending(CircuitSortField::CircuitId))
+            .expect("Unable to list sorted circuits")
+            .collect::<Vec<_>>();
+        assert_eq!(circuits, vec![circuit.clone(), extra_circuit.clone()]);
+
+        // "gameroom" sorts before "other"
+        let circuits = store
+            .list_circuits_sorted(
+                &[],
+                &CircuitSort::descending(CircuitSortField::ManagementType),
+            )
+            .expect("Unable to list sorted circuits")
+            .collect::<Vec<_>>();
+        assert_eq!(circuits, vec![extra_circuit.clone(), circuit]);
+
+        store
+            .update_circuit_status("WBKLF-BBBBB", CircuitStatus::Disbanding)
+            .expect("Unable to update circuit status");
+
+        let circuits = store
+            .list_circuits_sorted(
+                &[CircuitPredicate::CircuitStatusEq(CircuitStatus::Active)],
+                &CircuitSort::descending(CircuitSortField::CreatedAt),
+            )
+            .expect("Unable to list circuits with status predicate")
+            .collect::<Vec<_>>();
+        assert_eq!(circuits, vec![extra_circuit]);
+    }
+
     /// Verify that a circuit can be removed from the store
     ///
     /// 1. Run sqlite migrations
@@ -694,6 +1012,55 @@ pub mod tests {
         assert_eq!(None, fetched_circuit);
     }
 
+    /// Verify that a circuit's status can be updated through its legal lifecycle transitions
+    ///
+    /// 1. Run sqlite migrations
+    /// 2. Create DieselAdminServiceStore
+    /// 3. Add a circuit, which is active by default
+    /// 4. Move the circuit to disbanding, then disbanded, and validate the fetched status
+    /// 5. Validate that the disbanded circuit cannot become active again
+    /// 6. Validate that the status of an unknown circuit cannot be updated
+    #[test]
+    fn test_update_circuit_status() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselAdminServiceStore::new(pool);
+
+        store
+            .add_circuit(create_circuit(), create_nodes())
+            .expect("Unable to add circuit");
+
+        let fetched_circuit = store
+            .get_circuit("WBKLF-BBBBB")
+            .expect("Unable to get circuit")
+            .expect("Got None when expecting circuit");
+        assert_eq!(fetched_circuit.circuit_status(), &CircuitStatus::Active);
+        assert_eq!(fetched_circuit.updated_at(), None);
+
+        store
+            .update_circuit_status("WBKLF-BBBBB", CircuitStatus::Disbanding)
+            .expect("Unable to update circuit status");
+        store
+            .update_circuit_status("WBKLF-BBBBB", CircuitStatus::Disbanded)
+            .expect("Unable to update circuit status");
+
+        let fetched_circuit = store
+            .get_circuit("WBKLF-BBBBB")
+            .expect("Unable to get circuit")
+            .expect("Got None when expecting circuit");
+        assert_eq!(fetched_circuit.circuit_status(), &CircuitStatus::Disbanded);
+        assert!(fetched_circuit.updated_at().is_some());
+
+        assert!(matches!(
+            store.update_circuit_status("WBKLF-BBBBB", CircuitStatus::Active),
+            Err(AdminServiceStoreError::InvalidStateError(_))
+        ));
+        assert!(matches!(
+            store.update_circuit_status("WBKLF-CCCCC", CircuitStatus::Active),
+            Err(AdminServiceStoreError::InvalidStateError(_))
+        ));
+    }
+
     /// Verify that a service can be fetched from the store
     ///
     /// 1. Run sqlite migrations
@@ -25,10 +25,11 @@ use crate::admin::store::diesel::schema::{
 };
 use crate::admin::store::error::AdminServiceStoreError;
 use crate::admin::store::{
-    AuthorizationType, DurabilityType, PersistenceType, ProposalType, RouteType, Vote, VoteRecord,
-    VoteRecordBuilder,
+    AuthorizationType, CircuitStatus, DurabilityType, PersistenceType, ProposalType, RouteType,
+    Vote, VoteRecord, VoteRecordBuilder,
 };
 use crate::admin::store::{Circuit, CircuitProposal, ProposedCircuit};
+use crate::circuit::qos::QosLimits;
 use crate::error::InvalidStateError;
 
 /// Database model representation of a `CircuitProposal`
@@ -41,6 +42,8 @@ pub struct CircuitProposalModel {
     pub circuit_hash: String,
     pub requester: Vec<u8>,
     pub requester_node_id: String,
+    pub created_at: i64,
+    pub updated_at: i64,
 }
 
 impl From<&CircuitProposal> for CircuitProposalModel {
@@ -51,6 +54,8 @@ impl From<&CircuitProposal> for CircuitProposalModel {
             circuit_hash: proposal.circuit_hash().into(),
             requester: proposal.requester().to_vec(),
             requester_node_id: proposal.requester_node_id().into(),
+            created_at: timestamp_to_column(proposal.created_at()),
+            updated_at: timestamp_to_column(proposal.updated_at()),
         }
     }
 }
@@ -70,10 +75,25 @@ pub struct ProposedCircuitModel {
     pub application_metadata: Option<Vec<u8>>,
     pub comments: Option<String>,
     pub display_name: Option<String>,
+    pub max_message_size: Option<i64>,
+    pub max_messages_per_second: Option<i64>,
+    pub max_queued_bytes: Option<i64>,
+}
+
+impl ProposedCircuitModel {
+    /// Returns the quality-of-service limits stored for the proposed circuit
+    pub fn qos(&self) -> QosLimits {
+        qos_from_columns(
+            self.max_message_size,
+            self.max_messages_per_second,
+            self.max_queued_bytes,
+        )
+    }
 }
 
 impl From<&ProposedCircuit> for ProposedCircuitModel {
     fn from(proposed_circuit: &ProposedCircuit) -> Self {
+        let qos = proposed_circuit.qos();
         ProposedCircuitModel {
             circuit_id: proposed_circuit.circuit_id().into(),
             authorization_type: String::from(proposed_circuit.authorization_type()),
@@ -84,6 +104,9 @@ impl From<&ProposedCircuit> for ProposedCircuitModel {
             application_metadata: proposed_circuit.application_metadata().clone(),
             comments: proposed_circuit.comments().clone(),
             display_name: proposed_circuit.display_name().clone(),
+            max_message_size: qos.max_message_size().map(|max| max as i64),
+            max_messages_per_second: qos.max_messages_per_second().map(|max| max as i64),
+            max_queued_bytes: qos.max_queued_bytes().map(|max| max as i64),
         }
     }
 }
@@ -135,6 +158,8 @@ impl TryFrom<&VoteRecordModel> for VoteRecord {
 pub struct ProposedNodeModel {
     pub circuit_id: String,
     pub node_id: String,
+    pub relay: bool,
+    pub observer: bool,
 }
 
 impl From<&ProposedCircuit> for Vec<ProposedNodeModel> {
@@ -145,6 +170,8 @@ impl From<&ProposedCircuit> for Vec<ProposedNodeModel> {
             .map(|node| ProposedNodeModel {
                 circuit_id: proposed_circuit.circuit_id().into(),
                 node_id: node.node_id().into(),
+                relay: node.is_relay(),
+                observer: node.is_observer(),
             })
             .collect()
     }
@@ -312,10 +339,30 @@ pub struct CircuitModel {
     pub routes: String,
     pub circuit_management_type: String,
     pub display_name: Option<String>,
+    pub max_message_size: Option<i64>,
+    pub max_messages_per_second: Option<i64>,
+    pub max_queued_bytes: Option<i64>,
+    pub circuit_status: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub proposer_public_key: Option<Vec<u8>>,
+    pub proposer_node_id: Option<String>,
+}
+
+impl CircuitModel {
+    /// Returns the quality-of-service limits stored for the circuit
+    pub fn qos(&self) -> QosLimits {
+        qos_from_columns(
+            self.max_message_size,
+            self.max_messages_per_second,
+            self.max_queued_bytes,
+        )
+    }
 }
 
 impl From<&Circuit> for CircuitModel {
     fn from(circuit: &Circuit) -> Self {
+        let qos = circuit.qos();
         CircuitModel {
             circuit_id: circuit.circuit_id().into(),
             authorization_type: String::from(circuit.authorization_type()),
@@ -324,10 +371,51 @@ impl From<&Circuit> for CircuitModel {
             routes: String::from(circuit.routes()),
             circuit_management_type: circuit.circuit_management_type().into(),
             display_name: circuit.display_name().clone(),
+            max_message_size: qos.max_message_size().map(|max| max as i64),
+            max_messages_per_second: qos.max_messages_per_second().map(|max| max as i64),
+            max_queued_bytes: qos.max_queued_bytes().map(|max| max as i64),
+            circuit_status: String::from(circuit.circuit_status()),
+            created_at: timestamp_to_column(circuit.created_at()),
+            updated_at: timestamp_to_column(circuit.updated_at()),
+            proposer_public_key: circuit.proposer_public_key().map(|key| key.to_vec()),
+            proposer_node_id: circuit.proposer_node_id().map(String::from),
         }
     }
 }
 
+/// Converts an optional timestamp to its column value. Timestamp columns store 0 when the time
+/// is not known, which is also the value given to rows that existed before the column was added.
+pub fn timestamp_to_column(timestamp: Option<u64>) -> i64 {
+    timestamp.map(|secs| secs as i64).unwrap_or(0)
+}
+
+/// Converts a timestamp column value to an optional timestamp
+pub fn timestamp_from_column(column: i64) -> Option<u64> {
+    if column > 0 {
+        Some(column as u64)
+    } else {
+        None
+    }
+}
+
+fn qos_from_columns(
+    max_message_size: Option<i64>,
+    max_messages_per_second: Option<i64>,
+    max_queued_bytes: Option<i64>,
+) -> QosLimits {
+    let mut qos = QosLimits::new();
+    if let Some(max) = max_message_size {
+        qos = qos.with_max_message_size(max as u64);
+    }
+    if let Some(max) = max_messages_per_second {
+        qos = qos.with_max_messages_per_second(max as u64);
+    }
+    if let Some(max) = max_queued_bytes {
+        qos = qos.with_max_queued_bytes(max as u64);
+    }
+    qos
+}
+
 /// Database model representation of the `members` of a `Circuit`
 #[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable, QueryableByName)]
 #[table_name = "circuit_member"]
@@ -336,6 +424,8 @@ impl From<&Circuit> for CircuitModel {
 pub struct CircuitMemberModel {
     pub circuit_id: String,
     pub node_id: String,
+    pub relay: bool,
+    pub observer: bool,
 }
 
 impl From<&Circuit> for Vec<CircuitMemberModel> {
@@ -346,6 +436,8 @@ impl From<&Circuit> for Vec<CircuitMemberModel> {
             .map(|node_id| CircuitMemberModel {
                 circuit_id: circuit.circuit_id().into(),
                 node_id: node_id.clone(),
+                relay: circuit.relay_members().contains(node_id),
+                observer: circuit.observer_members().contains(node_id),
             })
             .collect()
     }
@@ -413,6 +505,36 @@ impl From<&ProposalType> for String {
     }
 }
 
+impl TryFrom<String> for CircuitStatus {
+    type Error = AdminServiceStoreError;
+    fn try_from(variant: String) -> Result<Self, Self::Error> {
+        match variant.as_ref() {
+            "Proposed" => Ok(CircuitStatus::Proposed),
+            "Active" => Ok(CircuitStatus::Active),
+            "Disbanding" => Ok(CircuitStatus::Disbanding),
+            "Disbanded" => Ok(CircuitStatus::Disbanded),
+            "Abandoned" => Ok(CircuitStatus::Abandoned),
+            "Purging" => Ok(CircuitStatus::Purging),
+            _ => Err(AdminServiceStoreError::InvalidStateError(
+                InvalidStateError::with_message("Unable to convert string to CircuitStatus".into()),
+            )),
+        }
+    }
+}
+
+impl From<&CircuitStatus> for String {
+    fn from(variant: &CircuitStatus) -> Self {
+        match variant {
+            CircuitStatus::Proposed => String::from("Proposed"),
+            CircuitStatus::Active => String::from("Active"),
+            CircuitStatus::Disbanding => String::from("Disbanding"),
+            CircuitStatus::Disbanded => String::from("Disbanded"),
+            CircuitStatus::Abandoned => String::from("Abandoned"),
+            CircuitStatus::Purging => String::from("Purging"),
+        }
+    }
+}
+
 impl TryFrom<String> for AuthorizationType {
     type Error = AdminServiceStoreError;
     fn try_from(variant: String) -> Result<Self, Self::Error> {
@@ -74,6 +74,14 @@ impl<'a> AdminServiceStoreAddCircuitOperation
                 .map(|node| CircuitMemberModel {
                     circuit_id: circuit.circuit_id().into(),
                     node_id: node.node_id().into(),
+                    relay: circuit
+                        .relay_members()
+                        .iter()
+                        .any(|relay| relay == node.node_id()),
+                    observer: circuit
+                        .observer_members()
+                        .iter()
+                        .any(|observer| observer == node.node_id()),
                 })
                 .collect();
             insert_into(circuit_member::table)
@@ -161,6 +169,14 @@ impl<'a> AdminServiceStoreAddCircuitOperation
                 .map(|node| CircuitMemberModel {
                     circuit_id: circuit.circuit_id().into(),
                     node_id: node.node_id().into(),
+                    relay: circuit
+                        .relay_members()
+                        .iter()
+                        .any(|relay| relay == node.node_id()),
+                    observer: circuit
+                        .observer_members()
+                        .iter()
+                        .any(|observer| observer == node.node_id()),
                 })
                 .collect();
             insert_into(circuit_member::table)
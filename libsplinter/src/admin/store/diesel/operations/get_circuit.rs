@@ -20,12 +20,12 @@ use std::convert::TryFrom;
 use super::{list_services::AdminServiceStoreListServicesOperation, AdminServiceStoreOperations};
 use crate::admin::store::{
     diesel::{
-        models::{CircuitMemberModel, CircuitModel},
+        models::{timestamp_from_column, CircuitMemberModel, CircuitModel},
         schema::{circuit, circuit_member},
     },
     error::AdminServiceStoreError,
-    AuthorizationType, Circuit, CircuitBuilder, DurabilityType, PersistenceType, RouteType,
-    Service,
+    AuthorizationType, Circuit, CircuitBuilder, CircuitStatus, DurabilityType, PersistenceType,
+    RouteType, Service,
 };
 
 pub(in crate::admin::store::diesel) trait AdminServiceStoreFetchCircuitOperation {
@@ -37,6 +37,7 @@ where
     C: diesel::Connection,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
     i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
 {
     fn get_circuit(&self, circuit_id: &str) -> Result<Option<Circuit>, AdminServiceStoreError> {
         self.conn.transaction::<Option<Circuit>, _, _>(|| {
@@ -64,21 +65,47 @@ where
                 .iter()
                 .map(|member| member.node_id.to_string())
                 .collect();
+            let relay_members: Vec<String> = members
+                .iter()
+                .filter(|member| member.relay)
+                .map(|member| member.node_id.to_string())
+                .collect();
+            let observer_members: Vec<String> = members
+                .iter()
+                .filter(|member| member.observer)
+                .map(|member| member.node_id.to_string())
+                .collect();
 
             let mut builder = CircuitBuilder::new()
                 .with_circuit_id(&circuit.circuit_id)
+                .with_qos(&circuit.qos())
                 .with_roster(&services)
                 .with_members(&circuit_member)
+                .with_relay_members(&relay_members)
+                .with_observer_members(&observer_members)
                 .with_authorization_type(&AuthorizationType::try_from(circuit.authorization_type)?)
                 .with_persistence(&PersistenceType::try_from(circuit.persistence)?)
                 .with_durability(&DurabilityType::try_from(circuit.durability)?)
                 .with_routes(&RouteType::try_from(circuit.routes)?)
-                .with_circuit_management_type(&circuit.circuit_management_type);
+                .with_circuit_management_type(&circuit.circuit_management_type)
+                .with_circuit_status(&CircuitStatus::try_from(circuit.circuit_status)?);
 
             // if display name is set, add to builder
             if let Some(display_name) = circuit.display_name {
                 builder = builder.with_display_name(&display_name);
             }
+            if let Some(created_at) = timestamp_from_column(circuit.created_at) {
+                builder = builder.with_created_at(created_at);
+            }
+            if let Some(updated_at) = timestamp_from_column(circuit.updated_at) {
+                builder = builder.with_updated_at(updated_at);
+            }
+            if let Some(proposer_public_key) = &circuit.proposer_public_key {
+                builder = builder.with_proposer_public_key(proposer_public_key);
+            }
+            if let Some(proposer_node_id) = &circuit.proposer_node_id {
+                builder = builder.with_proposer_node_id(proposer_node_id);
+            }
 
             Ok(Some(
                 builder
@@ -16,7 +16,7 @@
 
 use diesel::{
     prelude::*,
-    sql_types::{Binary, Nullable, Text},
+    sql_types::{BigInt, Binary, Nullable, Text},
 };
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -25,7 +25,7 @@ use super::AdminServiceStoreOperations;
 use crate::admin::store::{
     diesel::{
         models::{
-            CircuitProposalModel, ProposedCircuitModel, ProposedNodeModel,
+            timestamp_from_column, CircuitProposalModel, ProposedCircuitModel, ProposedNodeModel,
             ProposedServiceArgumentModel, ProposedServiceModel, VoteRecordModel,
         },
         schema::{
@@ -51,7 +51,8 @@ where
     C: diesel::Connection,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
     i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
-    CircuitProposalModel: diesel::Queryable<(Text, Text, Text, Binary, Text), C::Backend>,
+    CircuitProposalModel:
+        diesel::Queryable<(Text, Text, Text, Binary, Text, BigInt, BigInt), C::Backend>,
     ProposedCircuitModel: diesel::Queryable<
         (
             Text,
@@ -63,6 +64,9 @@ where
             Nullable<Binary>,
             Nullable<Text>,
             Nullable<Text>,
+            Nullable<BigInt>,
+            Nullable<BigInt>,
+            Nullable<BigInt>,
         ),
         C::Backend,
     >,
@@ -118,7 +122,10 @@ where
                 if !nodes.contains_key(&node.node_id) {
                     nodes.insert(
                         node.node_id.to_string(),
-                        ProposedNodeBuilder::new().with_node_id(&node.node_id),
+                        ProposedNodeBuilder::new()
+                            .with_node_id(&node.node_id)
+                            .with_relay(node.relay)
+                            .with_observer(node.observer),
                     );
                 }
             }
@@ -205,6 +212,7 @@ where
                 .collect();
             let mut builder = ProposedCircuitBuilder::new()
                 .with_circuit_id(&proposal.circuit_id)
+                .with_qos(&proposed_circuit.qos())
                 .with_roster(&built_proposed_services)
                 .with_members(built_proposed_nodes.as_slice())
                 .with_authorization_type(&AuthorizationType::try_from(
@@ -230,15 +238,22 @@ where
             let native_proposed_circuit = builder
                 .build()
                 .map_err(AdminServiceStoreError::InvalidStateError)?;
+            let mut proposal_builder = CircuitProposalBuilder::new()
+                .with_proposal_type(&ProposalType::try_from(proposal.proposal_type)?)
+                .with_circuit_id(&proposal.circuit_id)
+                .with_circuit_hash(&proposal.circuit_hash)
+                .with_circuit(&native_proposed_circuit)
+                .with_votes(&vote_record)
+                .with_requester(&proposal.requester)
+                .with_requester_node_id(&proposal.requester_node_id);
+            if let Some(created_at) = timestamp_from_column(proposal.created_at) {
+                proposal_builder = proposal_builder.with_created_at(created_at);
+            }
+            if let Some(updated_at) = timestamp_from_column(proposal.updated_at) {
+                proposal_builder = proposal_builder.with_updated_at(updated_at);
+            }
             Ok(Some(
-                CircuitProposalBuilder::new()
-                    .with_proposal_type(&ProposalType::try_from(proposal.proposal_type)?)
-                    .with_circuit_id(&proposal.circuit_id)
-                    .with_circuit_hash(&proposal.circuit_hash)
-                    .with_circuit(&native_proposed_circuit)
-                    .with_votes(&vote_record)
-                    .with_requester(&proposal.requester)
-                    .with_requester_node_id(&proposal.requester_node_id)
+                proposal_builder
                     .build()
                     .map_err(AdminServiceStoreError::InvalidStateError)?,
             ))
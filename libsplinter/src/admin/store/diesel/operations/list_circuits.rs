@@ -21,12 +21,15 @@ use diesel::{dsl::exists, prelude::*};
 
 use crate::admin::store::{
     diesel::{
-        models::{CircuitMemberModel, CircuitModel, ServiceArgumentModel, ServiceModel},
+        models::{
+            timestamp_from_column, CircuitMemberModel, CircuitModel, ServiceArgumentModel,
+            ServiceModel,
+        },
         schema::{circuit, circuit_member, service, service_argument},
     },
     error::AdminServiceStoreError,
-    AuthorizationType, Circuit, CircuitBuilder, CircuitPredicate, DurabilityType, PersistenceType,
-    RouteType, Service, ServiceBuilder,
+    AuthorizationType, Circuit, CircuitBuilder, CircuitPredicate, CircuitSort, CircuitSortField,
+    CircuitStatus, DurabilityType, PersistenceType, RouteType, Service, ServiceBuilder,
 };
 
 use super::AdminServiceStoreOperations;
@@ -35,7 +38,19 @@ pub(in crate::admin::store::diesel) trait AdminServiceStoreListCircuitsOperation
     fn list_circuits(
         &self,
         predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
     ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError>;
+
+    /// Lists the IDs of all circuits, in descending order
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_circuit_ids(&self) -> Result<Vec<String>, AdminServiceStoreError>;
+
+    /// Lists the circuits with the given IDs, in descending order of circuit ID
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_circuits_by_id(
+        &self,
+        circuit_ids: &[String],
+    ) -> Result<Vec<Circuit>, AdminServiceStoreError>;
 }
 
 impl<'a, C> AdminServiceStoreListCircuitsOperation for AdminServiceStoreOperations<'a, C>
@@ -43,10 +58,12 @@ where
     C: diesel::Connection,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
     i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
 {
     fn list_circuits(
         &self,
         predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
     ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
         // Collect the management types included in the list of `CircuitPredicates`
         let management_types: Vec<String> = predicates
@@ -65,6 +82,14 @@ where
             })
             .flatten()
             .collect();
+        // Collects the statuses included in the list of `CircuitPredicates`
+        let statuses: Vec<String> = predicates
+            .iter()
+            .filter_map(|pred| match pred {
+                CircuitPredicate::CircuitStatusEq(status) => Some(String::from(status)),
+                _ => None,
+            })
+            .collect();
 
         self.conn
             .transaction::<Box<dyn ExactSizeIterator<Item = Circuit>>, _, _>(|| {
@@ -75,154 +100,248 @@ where
                     query = query.filter(circuit::circuit_management_type.eq_any(management_types));
                 }
 
-                if !members.is_empty() {
+                // Every member in the circuit predicates must be a member of the circuit
+                for member in members {
                     query = query.filter(exists(
-                        // Selects all `circuit_member` entries where the `node_id` is equal
-                        // to any of the members in the circuit predicates
+                        // Selects the `circuit_member` entry of the circuit where the `node_id`
+                        // is equal to the member
                         circuit_member::table.filter(
                             circuit_member::circuit_id
                                 .eq(circuit::circuit_id)
-                                .and(circuit_member::node_id.eq_any(members)),
+                                .and(circuit_member::node_id.eq(member)),
                         ),
                     ));
                 }
 
-                let circuits: Vec<CircuitModel> = query
+                if !statuses.is_empty() {
+                    query = query.filter(circuit::circuit_status.eq_any(statuses));
+                }
+
+                // Ties are broken by the circuit ID, in the same direction
+                query = match (sort.field(), sort.is_descending()) {
+                    (CircuitSortField::CircuitId, false) => query.order(circuit::circuit_id.asc()),
+                    (CircuitSortField::CircuitId, true) => query.order(circuit::circuit_id.desc()),
+                    (CircuitSortField::ManagementType, false) => query.order((
+                        circuit::circuit_management_type.asc(),
+                        circuit::circuit_id.asc(),
+                    )),
+                    (CircuitSortField::ManagementType, true) => query.order((
+                        circuit::circuit_management_type.desc(),
+                        circuit::circuit_id.desc(),
+                    )),
+                    (CircuitSortField::CreatedAt, false) => {
+                        query.order((circuit::created_at.asc(), circuit::circuit_id.asc()))
+                    }
+                    (CircuitSortField::CreatedAt, true) => {
+                        query.order((circuit::created_at.desc(), circuit::circuit_id.desc()))
+                    }
+                };
+
+                let circuits: Vec<CircuitModel> = query.load::<CircuitModel>(self.conn)?;
+
+                Ok(Box::new(self.build_circuits(circuits)?.into_iter()))
+            })
+    }
+
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_circuit_ids(&self) -> Result<Vec<String>, AdminServiceStoreError> {
+        circuit::table
+            .select(circuit::circuit_id)
+            .order(circuit::circuit_id.desc())
+            .load::<String>(self.conn)
+            .map_err(AdminServiceStoreError::from)
+    }
+
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_circuits_by_id(
+        &self,
+        circuit_ids: &[String],
+    ) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        self.conn
+            .transaction::<Vec<Circuit>, AdminServiceStoreError, _>(|| {
+                let circuits = circuit::table
+                    .filter(circuit::circuit_id.eq_any(circuit_ids))
                     .order(circuit::circuit_id.desc())
                     .load::<CircuitModel>(self.conn)?;
 
-                // Store circuit IDs separately to make it easier to filter following queries
-                let circuit_ids: Vec<&str> = circuits
-                    .iter()
-                    .map(|circuit| circuit.circuit_id.as_str())
-                    .collect();
-
-                // Collect the `Circuit` members and put them in a HashMap to associate the list
-                // of `node_ids` to the `circuit_id`
-                let mut circuit_members: HashMap<String, Vec<String>> = HashMap::new();
-                for member in circuit_member::table
-                    .filter(circuit_member::circuit_id.eq_any(&circuit_ids))
-                    .load::<CircuitMemberModel>(self.conn)?
-                {
-                    if let Some(members) = circuit_members.get_mut(&member.circuit_id) {
-                        members.push(member.node_id.to_string());
-                    } else {
-                        circuit_members.insert(
-                            member.circuit_id.to_string(),
-                            vec![member.node_id.to_string()],
-                        );
-                    }
-                }
+                self.build_circuits(circuits)
+            })
+    }
+}
 
-                // Create HashMap of (`circuit_id`, ` service_id`) to a `ServiceBuilder`
-                let mut services: HashMap<(String, String), ServiceBuilder> = HashMap::new();
-                // Create HashMap of (`circuit_id`, `service_id`) to the associated argument values
-                let mut arguments_map: HashMap<(String, String), Vec<(String, String)>> =
-                    HashMap::new();
-                // Collects all `service` and `service_argument` entries using an inner_join on the
-                // `service_id`, since the relationship between `service` and `service_argument` is
-                // one-to-many. Adding the models retrieved from the database backend to HashMaps
-                // removed the duplicate `service` entries collected, and also makes it simpler
-                // to build each `Service` later on.
-                for (service, opt_arg) in service::table
-                    // Filters the services based on the circuit_ids collected based on the circuits
-                    // which matched the predicates.
-                    .filter(service::circuit_id.eq_any(&circuit_ids))
-                    // Joins a `service_argument` entry to a `service` entry, based on `service_id`.
-                    .left_join(
-                        service_argument::table.on(service::service_id
-                            .eq(service_argument::service_id)
-                            .and(service_argument::circuit_id.eq(service::circuit_id))),
-                    )
-                    // Collects all data from the `service` entry, and the pertinent data from the
-                    // `service_argument` entry.
-                    // Making `service_argument` nullable is required to return all matching
-                    // records since the relationship with services is one-to-many for each.
-                    .select((
-                        service::all_columns,
-                        service_argument::all_columns.nullable(),
-                    ))
-                    .load::<(ServiceModel, Option<ServiceArgumentModel>)>(self.conn)?
-                {
-                    if let Some(arg_model) = opt_arg {
-                        if let Some(args) = arguments_map.get_mut(&(
-                            service.circuit_id.to_string(),
-                            service.service_id.to_string(),
-                        )) {
-                            args.push((arg_model.key.to_string(), arg_model.value.to_string()));
-                        } else {
-                            arguments_map.insert(
-                                (
-                                    service.circuit_id.to_string(),
-                                    service.service_id.to_string(),
-                                ),
-                                vec![(arg_model.key.to_string(), arg_model.value.to_string())],
-                            );
-                        }
-                    }
-                    // Insert new `ServiceBuilder` if it does not already exist
-                    services
-                        .entry((
+impl<'a, C> AdminServiceStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    Vec<u8>: diesel::deserialize::FromSql<diesel::sql_types::Binary, C::Backend>,
+{
+    /// Builds the given circuits, loading their members and services
+    fn build_circuits(
+        &self,
+        circuits: Vec<CircuitModel>,
+    ) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        // Store circuit IDs separately to make it easier to filter following queries
+        let circuit_ids: Vec<&str> = circuits
+            .iter()
+            .map(|circuit| circuit.circuit_id.as_str())
+            .collect();
+
+        // Collect the `Circuit` members and put them in a HashMap to associate the list
+        // of `node_ids` to the `circuit_id`
+        let mut circuit_members: HashMap<String, Vec<String>> = HashMap::new();
+        let mut relay_members: HashMap<String, Vec<String>> = HashMap::new();
+        let mut observer_members: HashMap<String, Vec<String>> = HashMap::new();
+        for member in circuit_member::table
+            .filter(circuit_member::circuit_id.eq_any(&circuit_ids))
+            .load::<CircuitMemberModel>(self.conn)?
+        {
+            if member.relay {
+                relay_members
+                    .entry(member.circuit_id.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(member.node_id.to_string());
+            }
+            if member.observer {
+                observer_members
+                    .entry(member.circuit_id.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(member.node_id.to_string());
+            }
+            if let Some(members) = circuit_members.get_mut(&member.circuit_id) {
+                members.push(member.node_id.to_string());
+            } else {
+                circuit_members.insert(
+                    member.circuit_id.to_string(),
+                    vec![member.node_id.to_string()],
+                );
+            }
+        }
+
+        // Create HashMap of (`circuit_id`, ` service_id`) to a `ServiceBuilder`
+        let mut services: HashMap<(String, String), ServiceBuilder> = HashMap::new();
+        // Create HashMap of (`circuit_id`, `service_id`) to the associated argument values
+        let mut arguments_map: HashMap<(String, String), Vec<(String, String)>> = HashMap::new();
+        // Collects all `service` and `service_argument` entries using an inner_join on the
+        // `service_id`, since the relationship between `service` and `service_argument` is
+        // one-to-many. Adding the models retrieved from the database backend to HashMaps
+        // removed the duplicate `service` entries collected, and also makes it simpler
+        // to build each `Service` later on.
+        for (service, opt_arg) in service::table
+            // Filters the services based on the circuit_ids collected based on the circuits
+            // which matched the predicates.
+            .filter(service::circuit_id.eq_any(&circuit_ids))
+            // Joins a `service_argument` entry to a `service` entry, based on `service_id`.
+            .left_join(
+                service_argument::table.on(service::service_id
+                    .eq(service_argument::service_id)
+                    .and(service_argument::circuit_id.eq(service::circuit_id))),
+            )
+            // Collects all data from the `service` entry, and the pertinent data from the
+            // `service_argument` entry.
+            // Making `service_argument` nullable is required to return all matching
+            // records since the relationship with services is one-to-many for each.
+            .select((
+                service::all_columns,
+                service_argument::all_columns.nullable(),
+            ))
+            .load::<(ServiceModel, Option<ServiceArgumentModel>)>(self.conn)?
+        {
+            if let Some(arg_model) = opt_arg {
+                if let Some(args) = arguments_map.get_mut(&(
+                    service.circuit_id.to_string(),
+                    service.service_id.to_string(),
+                )) {
+                    args.push((arg_model.key.to_string(), arg_model.value.to_string()));
+                } else {
+                    arguments_map.insert(
+                        (
                             service.circuit_id.to_string(),
                             service.service_id.to_string(),
-                        ))
-                        .or_insert_with(|| {
-                            ServiceBuilder::new()
-                                .with_service_id(&service.service_id)
-                                .with_service_type(&service.service_type)
-                                .with_node_id(&service.node_id)
-                        });
-                }
-                // Collect the `Services` mapped to `circuit_ids` after adding any
-                // `service_arguments` to the `ServiceBuilder`.
-                let mut built_services: HashMap<String, Vec<Service>> = HashMap::new();
-                for ((circuit_id, service_id), mut builder) in services.into_iter() {
-                    if let Some(args) =
-                        arguments_map.get(&(circuit_id.to_string(), service_id.to_string()))
-                    {
-                        builder = builder.with_arguments(&args);
-                    }
-                    let service = builder
-                        .build()
-                        .map_err(AdminServiceStoreError::InvalidStateError)?;
-
-                    if let Some(service_list) = built_services.get_mut(&circuit_id) {
-                        service_list.push(service);
-                    } else {
-                        built_services.insert(circuit_id.to_string(), vec![service]);
-                    }
+                        ),
+                        vec![(arg_model.key.to_string(), arg_model.value.to_string())],
+                    );
                 }
+            }
+            // Insert new `ServiceBuilder` if it does not already exist
+            services
+                .entry((
+                    service.circuit_id.to_string(),
+                    service.service_id.to_string(),
+                ))
+                .or_insert_with(|| {
+                    ServiceBuilder::new()
+                        .with_service_id(&service.service_id)
+                        .with_service_type(&service.service_type)
+                        .with_node_id(&service.node_id)
+                });
+        }
+        // Collect the `Services` mapped to `circuit_ids` after adding any
+        // `service_arguments` to the `ServiceBuilder`.
+        let mut built_services: HashMap<String, Vec<Service>> = HashMap::new();
+        for ((circuit_id, service_id), mut builder) in services.into_iter() {
+            if let Some(args) = arguments_map.get(&(circuit_id.to_string(), service_id.to_string()))
+            {
+                builder = builder.with_arguments(&args);
+            }
+            let service = builder
+                .build()
+                .map_err(AdminServiceStoreError::InvalidStateError)?;
 
-                let mut ret_circuits: Vec<Circuit> = Vec::new();
-                for model in circuits {
-                    let mut circuit_builder = CircuitBuilder::new()
-                        .with_circuit_id(&model.circuit_id)
-                        .with_authorization_type(&AuthorizationType::try_from(
-                            model.authorization_type,
-                        )?)
-                        .with_persistence(&PersistenceType::try_from(model.persistence)?)
-                        .with_durability(&DurabilityType::try_from(model.durability)?)
-                        .with_routes(&RouteType::try_from(model.routes)?)
-                        .with_circuit_management_type(&model.circuit_management_type);
-
-                    if let Some(display_name) = &model.display_name {
-                        circuit_builder = circuit_builder.with_display_name(&display_name);
-                    }
-                    if let Some(members) = circuit_members.get(&model.circuit_id) {
-                        circuit_builder = circuit_builder.with_members(&members);
-                    }
-                    if let Some(services) = built_services.get(&model.circuit_id) {
-                        circuit_builder = circuit_builder.with_roster(&services);
-                    }
+            if let Some(service_list) = built_services.get_mut(&circuit_id) {
+                service_list.push(service);
+            } else {
+                built_services.insert(circuit_id.to_string(), vec![service]);
+            }
+        }
 
-                    ret_circuits.push(
-                        circuit_builder
-                            .build()
-                            .map_err(AdminServiceStoreError::InvalidStateError)?,
-                    );
-                }
+        let mut ret_circuits: Vec<Circuit> = Vec::new();
+        for model in circuits {
+            let mut circuit_builder = CircuitBuilder::new()
+                .with_circuit_id(&model.circuit_id)
+                .with_qos(&model.qos())
+                .with_authorization_type(&AuthorizationType::try_from(model.authorization_type)?)
+                .with_persistence(&PersistenceType::try_from(model.persistence)?)
+                .with_durability(&DurabilityType::try_from(model.durability)?)
+                .with_routes(&RouteType::try_from(model.routes)?)
+                .with_circuit_management_type(&model.circuit_management_type)
+                .with_circuit_status(&CircuitStatus::try_from(model.circuit_status)?);
 
-                Ok(Box::new(ret_circuits.into_iter()))
-            })
+            if let Some(display_name) = &model.display_name {
+                circuit_builder = circuit_builder.with_display_name(&display_name);
+            }
+            if let Some(created_at) = timestamp_from_column(model.created_at) {
+                circuit_builder = circuit_builder.with_created_at(created_at);
+            }
+            if let Some(updated_at) = timestamp_from_column(model.updated_at) {
+                circuit_builder = circuit_builder.with_updated_at(updated_at);
+            }
+            if let Some(proposer_public_key) = &model.proposer_public_key {
+                circuit_builder = circuit_builder.with_proposer_public_key(proposer_public_key);
+            }
+            if let Some(proposer_node_id) = &model.proposer_node_id {
+                circuit_builder = circuit_builder.with_proposer_node_id(proposer_node_id);
+            }
+            if let Some(members) = circuit_members.get(&model.circuit_id) {
+                circuit_builder = circuit_builder.with_members(&members);
+            }
+            if let Some(relays) = relay_members.get(&model.circuit_id) {
+                circuit_builder = circuit_builder.with_relay_members(&relays);
+            }
+            if let Some(observers) = observer_members.get(&model.circuit_id) {
+                circuit_builder = circuit_builder.with_observer_members(&observers);
+            }
+            if let Some(services) = built_services.get(&model.circuit_id) {
+                circuit_builder = circuit_builder.with_roster(&services);
+            }
+
+            ret_circuits.push(
+                circuit_builder
+                    .build()
+                    .map_err(AdminServiceStoreError::InvalidStateError)?,
+            );
+        }
+
+        Ok(ret_circuits)
     }
 }
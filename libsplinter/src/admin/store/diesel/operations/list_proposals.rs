@@ -20,13 +20,13 @@ use std::convert::TryFrom;
 use diesel::{
     dsl::exists,
     prelude::*,
-    sql_types::{Binary, Nullable, Text},
+    sql_types::{BigInt, Binary, Nullable, Text},
 };
 
 use crate::admin::store::{
     diesel::{
         models::{
-            CircuitProposalModel, ProposedCircuitModel, ProposedNodeModel,
+            timestamp_from_column, CircuitProposalModel, ProposedCircuitModel, ProposedNodeModel,
             ProposedServiceArgumentModel, ProposedServiceModel, VoteRecordModel,
         },
         schema::{
@@ -35,9 +35,10 @@ use crate::admin::store::{
         },
     },
     error::AdminServiceStoreError,
-    AuthorizationType, CircuitPredicate, CircuitProposal, CircuitProposalBuilder, DurabilityType,
-    PersistenceType, ProposalType, ProposedCircuitBuilder, ProposedNode, ProposedNodeBuilder,
-    ProposedService, ProposedServiceBuilder, RouteType, VoteRecord,
+    AuthorizationType, CircuitPredicate, CircuitProposal, CircuitProposalBuilder, CircuitSort,
+    CircuitSortField, CircuitStatus, DurabilityType, PersistenceType, ProposalType,
+    ProposedCircuitBuilder, ProposedNode, ProposedNodeBuilder, ProposedService,
+    ProposedServiceBuilder, RouteType, VoteRecord,
 };
 use crate::error::InvalidStateError;
 
@@ -47,6 +48,7 @@ pub(in crate::admin::store::diesel) trait AdminServiceStoreListProposalsOperatio
     fn list_proposals(
         &self,
         predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
     ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError>;
 }
 
@@ -55,7 +57,8 @@ where
     C: diesel::Connection,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
     i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
-    CircuitProposalModel: diesel::Queryable<(Text, Text, Text, Binary, Text), C::Backend>,
+    CircuitProposalModel:
+        diesel::Queryable<(Text, Text, Text, Binary, Text, BigInt, BigInt), C::Backend>,
     ProposedCircuitModel: diesel::Queryable<
         (
             Text,
@@ -67,6 +70,9 @@ where
             Nullable<Binary>,
             Nullable<Text>,
             Nullable<Text>,
+            Nullable<BigInt>,
+            Nullable<BigInt>,
+            Nullable<BigInt>,
         ),
         C::Backend,
     >,
@@ -75,7 +81,16 @@ where
     fn list_proposals(
         &self,
         predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
     ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
+        // Proposals are always in the `Proposed` status, so any other status matches nothing
+        if predicates.iter().any(|pred| match pred {
+            CircuitPredicate::CircuitStatusEq(status) => *status != CircuitStatus::Proposed,
+            _ => false,
+        }) {
+            return Ok(Box::new(Vec::new().into_iter()));
+        }
+
         // Collect the management types included in the list of `CircuitPredicates`
         let management_types: Vec<String> = predicates
             .iter()
@@ -100,14 +115,15 @@ where
                     .into_boxed()
                     .select(proposed_circuit::all_columns);
 
-                if !members.is_empty() {
+                // Every member in the circuit predicates must be a member of the proposed circuit
+                for member in members {
                     query = query.filter(exists(
-                        // Selects all `proposed_node` entries where the `node_id` is not equal
-                        // to any of the members in the circuit predicates
+                        // Selects the `proposed_node` entry of the proposed circuit where the
+                        // `node_id` is equal to the member
                         proposed_node::table.filter(
                             proposed_node::circuit_id
                                 .eq(proposed_circuit::circuit_id)
-                                .and(proposed_node::node_id.eq_any(members)),
+                                .and(proposed_node::node_id.eq(member)),
                         ),
                     ))
                 }
@@ -118,10 +134,25 @@ where
                         .filter(proposed_circuit::circuit_management_type.eq_any(management_types));
                 }
 
+                // Ties are broken by the circuit ID, in the same direction. Proposals sorted by
+                // creation time are ordered below, once their `circuit_proposal` entries have
+                // been loaded.
+                query = match (sort.field(), sort.is_descending()) {
+                    (CircuitSortField::ManagementType, false) => query.order((
+                        proposed_circuit::circuit_management_type.asc(),
+                        proposed_circuit::circuit_id.asc(),
+                    )),
+                    (CircuitSortField::ManagementType, true) => query.order((
+                        proposed_circuit::circuit_management_type.desc(),
+                        proposed_circuit::circuit_id.desc(),
+                    )),
+                    (_, false) => query.order(proposed_circuit::circuit_id.asc()),
+                    (_, true) => query.order(proposed_circuit::circuit_id.desc()),
+                };
+
                 // Collects proposed circuits which match the circuit predicates
-                let proposed_circuits: Vec<ProposedCircuitModel> = query
-                    .order(proposed_circuit::circuit_id.desc())
-                    .load::<ProposedCircuitModel>(self.conn)?;
+                let proposed_circuits: Vec<ProposedCircuitModel> =
+                    query.load::<ProposedCircuitModel>(self.conn)?;
 
                 // Store circuit IDs separately to make it easier to filter following queries
                 let circuit_ids: Vec<&str> = proposed_circuits
@@ -156,7 +187,7 @@ where
                                 )
                             })?;
 
-                        let proposal_builder = CircuitProposalBuilder::new()
+                        let mut proposal_builder = CircuitProposalBuilder::new()
                             .with_proposal_type(&ProposalType::try_from(
                                 proposal.proposal_type.to_string(),
                             )?)
@@ -164,8 +195,15 @@ where
                             .with_circuit_hash(&proposal.circuit_hash)
                             .with_requester(&proposal.requester)
                             .with_requester_node_id(&proposal.requester_node_id);
+                        if let Some(created_at) = timestamp_from_column(proposal.created_at) {
+                            proposal_builder = proposal_builder.with_created_at(created_at);
+                        }
+                        if let Some(updated_at) = timestamp_from_column(proposal.updated_at) {
+                            proposal_builder = proposal_builder.with_updated_at(updated_at);
+                        }
                         let mut proposed_circuit_builder = ProposedCircuitBuilder::new()
                             .with_circuit_id(&proposed_circuit.circuit_id)
+                            .with_qos(&proposed_circuit.qos())
                             .with_authorization_type(&AuthorizationType::try_from(
                                 proposed_circuit.authorization_type,
                             )?)
@@ -301,6 +339,8 @@ where
                     } else {
                         let proposed_node = ProposedNodeBuilder::new()
                             .with_node_id(&node.node_id)
+                            .with_relay(node.relay)
+                            .with_observer(node.observer)
                             .with_endpoints(&[endpoint]);
                         proposed_nodes.insert((node.circuit_id, node.node_id), proposed_node);
                     }
@@ -364,6 +404,10 @@ where
                     )
                 }
 
+                if sort.field() == CircuitSortField::CreatedAt {
+                    sort.sort_proposals(&mut proposals);
+                }
+
                 Ok(Box::new(proposals.into_iter()))
             })
     }
@@ -27,6 +27,7 @@ pub(super) mod list_services;
 pub(super) mod remove_circuit;
 pub(super) mod remove_proposal;
 pub(super) mod update_circuit;
+pub(super) mod update_circuit_status;
 pub(super) mod update_proposal;
 pub(super) mod upgrade;
 
@@ -14,6 +14,8 @@
 
 //! Provides the "update circuit" operation for the `DieselAdminServiceStore`.
 
+use std::convert::TryFrom;
+
 use diesel::{
     dsl::{delete, insert_into, update},
     prelude::*,
@@ -21,12 +23,13 @@ use diesel::{
 
 use super::AdminServiceStoreOperations;
 use crate::admin::store::{
+    check_circuit_status_transition,
     diesel::{
         models::{CircuitMemberModel, CircuitModel, ServiceArgumentModel, ServiceModel},
         schema::{circuit, circuit_member, service, service_argument},
     },
     error::AdminServiceStoreError,
-    Circuit,
+    Circuit, CircuitStatus,
 };
 use crate::error::InvalidStateError;
 
@@ -41,7 +44,7 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
     fn update_circuit(&self, circuit: Circuit) -> Result<(), AdminServiceStoreError> {
         self.conn.transaction::<(), _, _>(|| {
             // Verify the `circuit` entry to be updated exists
-            circuit::table
+            let existing = circuit::table
                 .filter(circuit::circuit_id.eq(circuit.circuit_id()))
                 .first::<CircuitModel>(self.conn)
                 .optional()?
@@ -50,6 +53,10 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
                         String::from("Circuit does not exist in AdminServiceStore"),
                     ))
                 })?;
+            check_circuit_status_transition(
+                &CircuitStatus::try_from(existing.circuit_status)?,
+                circuit.circuit_status(),
+            )?;
 
             // Update existing `Circuit`
             let circuit_model = CircuitModel::from(&circuit);
@@ -60,6 +67,11 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
                     circuit::durability.eq(circuit_model.durability),
                     circuit::routes.eq(circuit_model.routes),
                     circuit::circuit_management_type.eq(circuit_model.circuit_management_type),
+                    circuit::circuit_status.eq(circuit_model.circuit_status),
+                    circuit::created_at.eq(circuit_model.created_at),
+                    circuit::updated_at.eq(circuit_model.updated_at),
+                    circuit::proposer_public_key.eq(circuit_model.proposer_public_key),
+                    circuit::proposer_node_id.eq(circuit_model.proposer_node_id),
                 ))
                 .execute(self.conn)?;
             // Delete existing data associated with the `Circuit`
@@ -95,7 +107,7 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
     fn update_circuit(&self, circuit: Circuit) -> Result<(), AdminServiceStoreError> {
         self.conn.transaction::<(), _, _>(|| {
             // Verify the `circuit` entry to be updated exists
-            circuit::table
+            let existing = circuit::table
                 .filter(circuit::circuit_id.eq(circuit.circuit_id()))
                 .first::<CircuitModel>(self.conn)
                 .optional()?
@@ -104,6 +116,10 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
                         String::from("Circuit does not exist in AdminServiceStore"),
                     ))
                 })?;
+            check_circuit_status_transition(
+                &CircuitStatus::try_from(existing.circuit_status)?,
+                circuit.circuit_status(),
+            )?;
 
             // Update existing `Circuit`
             let circuit_model = CircuitModel::from(&circuit);
@@ -114,6 +130,11 @@ impl<'a> AdminServiceStoreUpdateCircuitOperation
                     circuit::durability.eq(circuit_model.durability),
                     circuit::routes.eq(circuit_model.routes),
                     circuit::circuit_management_type.eq(circuit_model.circuit_management_type),
+                    circuit::circuit_status.eq(circuit_model.circuit_status),
+                    circuit::created_at.eq(circuit_model.created_at),
+                    circuit::updated_at.eq(circuit_model.updated_at),
+                    circuit::proposer_public_key.eq(circuit_model.proposer_public_key),
+                    circuit::proposer_node_id.eq(circuit_model.proposer_node_id),
                 ))
                 .execute(self.conn)?;
             // Delete existing data associated with the `Circuit`
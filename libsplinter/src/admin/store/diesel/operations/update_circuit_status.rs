@@ -0,0 +1,110 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the "update circuit status" operation for the `DieselAdminServiceStore`.
+
+use std::convert::TryFrom;
+
+use diesel::{dsl::update, prelude::*};
+
+use super::AdminServiceStoreOperations;
+use crate::admin::store::{
+    check_circuit_status_transition, current_time_secs,
+    diesel::{models::CircuitModel, schema::circuit},
+    error::AdminServiceStoreError,
+    CircuitStatus,
+};
+use crate::error::InvalidStateError;
+
+pub(in crate::admin::store::diesel) trait AdminServiceStoreUpdateCircuitStatusOperation {
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> AdminServiceStoreUpdateCircuitStatusOperation
+    for AdminServiceStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError> {
+        self.conn.transaction::<(), _, _>(|| {
+            // Verify the `circuit` entry to be updated exists and may move to the new status
+            let existing = circuit::table
+                .filter(circuit::circuit_id.eq(circuit_id))
+                .first::<CircuitModel>(self.conn)
+                .optional()?
+                .ok_or_else(|| {
+                    AdminServiceStoreError::InvalidStateError(InvalidStateError::with_message(
+                        String::from("Circuit does not exist in AdminServiceStore"),
+                    ))
+                })?;
+            check_circuit_status_transition(
+                &CircuitStatus::try_from(existing.circuit_status)?,
+                &status,
+            )?;
+
+            update(circuit::table.find(circuit_id))
+                .set((
+                    circuit::circuit_status.eq(String::from(&status)),
+                    circuit::updated_at.eq(current_time_secs() as i64),
+                ))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> AdminServiceStoreUpdateCircuitStatusOperation
+    for AdminServiceStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError> {
+        self.conn.transaction::<(), _, _>(|| {
+            // Verify the `circuit` entry to be updated exists and may move to the new status
+            let existing = circuit::table
+                .filter(circuit::circuit_id.eq(circuit_id))
+                .first::<CircuitModel>(self.conn)
+                .optional()?
+                .ok_or_else(|| {
+                    AdminServiceStoreError::InvalidStateError(InvalidStateError::with_message(
+                        String::from("Circuit does not exist in AdminServiceStore"),
+                    ))
+                })?;
+            check_circuit_status_transition(
+                &CircuitStatus::try_from(existing.circuit_status)?,
+                &status,
+            )?;
+
+            update(circuit::table.find(circuit_id))
+                .set((
+                    circuit::circuit_status.eq(String::from(&status)),
+                    circuit::updated_at.eq(current_time_secs() as i64),
+                ))
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
@@ -65,6 +65,8 @@ impl<'a> AdminServiceStoreUpdateProposalOperation
                     circuit_proposal::circuit_hash.eq(proposal_model.circuit_hash),
                     circuit_proposal::requester.eq(proposal_model.requester),
                     circuit_proposal::requester_node_id.eq(proposal_model.requester_node_id),
+                    circuit_proposal::created_at.eq(proposal_model.created_at),
+                    circuit_proposal::updated_at.eq(proposal_model.updated_at),
                 ))
                 .execute(self.conn)?;
             // Update existing `ProposedCircuit`
@@ -171,6 +173,8 @@ impl<'a> AdminServiceStoreUpdateProposalOperation
                     circuit_proposal::circuit_hash.eq(proposal_model.circuit_hash),
                     circuit_proposal::requester.eq(proposal_model.requester),
                     circuit_proposal::requester_node_id.eq(proposal_model.requester_node_id),
+                    circuit_proposal::created_at.eq(proposal_model.created_at),
+                    circuit_proposal::updated_at.eq(proposal_model.updated_at),
                 ))
                 .execute(self.conn)?;
             // Update existing `ProposedCircuit`
@@ -16,7 +16,9 @@
 
 use diesel::prelude::*;
 
-use crate::admin::store::{error::AdminServiceStoreError, CircuitBuilder, CircuitNode, Service};
+use crate::admin::store::{
+    current_time_secs, error::AdminServiceStoreError, CircuitBuilder, CircuitNode, Service,
+};
 use crate::error::InvalidStateError;
 
 use super::{
@@ -44,7 +46,9 @@ impl<'a> AdminServiceStoreUpgradeProposalToCircuitOperation
                     )),
                 )),
             }?;
-            // Need to construct the `Circuit` from the `ProposedCircuit`
+            // Need to construct the `Circuit` from the `ProposedCircuit`, recording when it was
+            // created and who proposed it
+            let now = current_time_secs();
             let proposed_circuit = proposal.circuit();
             let mut builder = CircuitBuilder::new()
                 .with_circuit_id(proposed_circuit.circuit_id())
@@ -62,11 +66,32 @@ impl<'a> AdminServiceStoreUpgradeProposalToCircuitOperation
                         .map(|node| node.node_id().to_string())
                         .collect::<Vec<String>>(),
                 )
+                .with_relay_members(
+                    &proposed_circuit
+                        .members()
+                        .iter()
+                        .filter(|node| node.is_relay())
+                        .map(|node| node.node_id().to_string())
+                        .collect::<Vec<String>>(),
+                )
+                .with_observer_members(
+                    &proposed_circuit
+                        .members()
+                        .iter()
+                        .filter(|node| node.is_observer())
+                        .map(|node| node.node_id().to_string())
+                        .collect::<Vec<String>>(),
+                )
                 .with_authorization_type(proposed_circuit.authorization_type())
                 .with_persistence(proposed_circuit.persistence())
                 .with_durability(proposed_circuit.durability())
                 .with_routes(proposed_circuit.routes())
-                .with_circuit_management_type(proposed_circuit.circuit_management_type());
+                .with_circuit_management_type(proposed_circuit.circuit_management_type())
+                .with_qos(proposed_circuit.qos())
+                .with_created_at(now)
+                .with_updated_at(now)
+                .with_proposer_public_key(proposal.requester())
+                .with_proposer_node_id(proposal.requester_node_id());
 
             if let Some(display_name) = proposed_circuit.display_name() {
                 builder = builder.with_display_name(display_name);
@@ -104,7 +129,9 @@ impl<'a> AdminServiceStoreUpgradeProposalToCircuitOperation
                     )),
                 )),
             }?;
-            // Need to construct the `Circuit` from the `ProposedCircuit`
+            // Need to construct the `Circuit` from the `ProposedCircuit`, recording when it was
+            // created and who proposed it
+            let now = current_time_secs();
             let proposed_circuit = proposal.circuit();
             let mut builder = CircuitBuilder::new()
                 .with_circuit_id(proposed_circuit.circuit_id())
@@ -122,11 +149,32 @@ impl<'a> AdminServiceStoreUpgradeProposalToCircuitOperation
                         .map(|node| node.node_id().to_string())
                         .collect::<Vec<String>>(),
                 )
+                .with_relay_members(
+                    &proposed_circuit
+                        .members()
+                        .iter()
+                        .filter(|node| node.is_relay())
+                        .map(|node| node.node_id().to_string())
+                        .collect::<Vec<String>>(),
+                )
+                .with_observer_members(
+                    &proposed_circuit
+                        .members()
+                        .iter()
+                        .filter(|node| node.is_observer())
+                        .map(|node| node.node_id().to_string())
+                        .collect::<Vec<String>>(),
+                )
                 .with_authorization_type(proposed_circuit.authorization_type())
                 .with_persistence(proposed_circuit.persistence())
                 .with_durability(proposed_circuit.durability())
                 .with_routes(proposed_circuit.routes())
-                .with_circuit_management_type(proposed_circuit.circuit_management_type());
+                .with_circuit_management_type(proposed_circuit.circuit_management_type())
+                .with_qos(proposed_circuit.qos())
+                .with_created_at(now)
+                .with_updated_at(now)
+                .with_proposer_public_key(proposal.requester())
+                .with_proposer_node_id(proposal.requester_node_id());
 
             if let Some(display_name) = proposed_circuit.display_name() {
                 builder = builder.with_display_name(display_name);
@@ -19,6 +19,8 @@ table! {
         circuit_hash -> Text,
         requester -> Binary,
         requester_node_id -> Text,
+        created_at -> BigInt,
+        updated_at -> BigInt,
     }
 }
 
@@ -33,6 +35,9 @@ table! {
         application_metadata -> Nullable<Binary>,
         comments -> Nullable<Text>,
         display_name -> Nullable<Text>,
+        max_message_size -> Nullable<BigInt>,
+        max_messages_per_second -> Nullable<BigInt>,
+        max_queued_bytes -> Nullable<BigInt>,
     }
 }
 
@@ -49,6 +54,8 @@ table! {
     proposed_node (circuit_id, node_id) {
         circuit_id -> Text,
         node_id -> Text,
+        relay -> Bool,
+        observer -> Bool,
     }
 }
 
@@ -105,6 +112,14 @@ table! {
         routes -> Text,
         circuit_management_type -> Text,
         display_name -> Nullable<Text>,
+        max_message_size -> Nullable<BigInt>,
+        max_messages_per_second -> Nullable<BigInt>,
+        max_queued_bytes -> Nullable<BigInt>,
+        circuit_status -> Text,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+        proposer_public_key -> Nullable<Binary>,
+        proposer_node_id -> Nullable<Text>,
     }
 }
 
@@ -112,6 +127,8 @@ table! {
     circuit_member (circuit_id, node_id) {
         circuit_id -> Text,
         node_id -> Text,
+        relay -> Bool,
+        observer -> Bool,
     }
 }
 
@@ -41,9 +41,13 @@ pub mod yaml;
 
 use std::cmp::Ordering;
 use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::InvalidStateError;
 
 pub use self::circuit::{
-    AuthorizationType, Circuit, CircuitBuilder, DurabilityType, PersistenceType, RouteType,
+    AuthorizationType, Circuit, CircuitBuilder, CircuitStatus, DurabilityType, PersistenceType,
+    RouteType,
 };
 pub use self::circuit_node::{CircuitNode, CircuitNodeBuilder};
 pub use self::circuit_proposal::{
@@ -122,6 +126,8 @@ impl PartialOrd for ServiceId {
 pub enum CircuitPredicate {
     ManagementTypeEq(String),
     MembersInclude(Vec<String>),
+    /// Matches circuits with the given status; circuit proposals only match `Proposed`
+    CircuitStatusEq(CircuitStatus),
 }
 
 impl CircuitPredicate {
@@ -139,6 +145,7 @@ impl CircuitPredicate {
                 }
                 true
             }
+            CircuitPredicate::CircuitStatusEq(status) => circuit.circuit_status() == status,
         }
     }
 
@@ -162,10 +169,103 @@ impl CircuitPredicate {
                 }
                 true
             }
+            CircuitPredicate::CircuitStatusEq(status) => *status == CircuitStatus::Proposed,
         }
     }
 }
 
+/// The fields by which lists of circuits and circuit proposals may be sorted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitSortField {
+    CircuitId,
+    ManagementType,
+    /// The time the circuit or proposal was created. Entries without a recorded creation time
+    /// sort before those with one.
+    CreatedAt,
+}
+
+/// The order in which lists of circuits and circuit proposals are returned
+///
+/// Entries that are equal by the sort field are ordered by circuit ID, in the same direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitSort {
+    field: CircuitSortField,
+    descending: bool,
+}
+
+impl CircuitSort {
+    /// Sorts by the given field, in ascending order
+    pub fn ascending(field: CircuitSortField) -> Self {
+        Self {
+            field,
+            descending: false,
+        }
+    }
+
+    /// Sorts by the given field, in descending order
+    pub fn descending(field: CircuitSortField) -> Self {
+        Self {
+            field,
+            descending: true,
+        }
+    }
+
+    /// Returns the field to sort by
+    pub fn field(&self) -> CircuitSortField {
+        self.field
+    }
+
+    /// Returns true if the entries are sorted in descending order
+    pub fn is_descending(&self) -> bool {
+        self.descending
+    }
+
+    /// Sorts the circuits in memory, for stores that cannot sort in their backend
+    fn sort_circuits(&self, circuits: &mut Vec<Circuit>) {
+        circuits.sort_by(|a, b| {
+            let ordering = match self.field {
+                CircuitSortField::ManagementType => a
+                    .circuit_management_type()
+                    .cmp(b.circuit_management_type())
+                    .then_with(|| a.circuit_id().cmp(b.circuit_id())),
+                CircuitSortField::CreatedAt => a
+                    .created_at()
+                    .cmp(&b.created_at())
+                    .then_with(|| a.circuit_id().cmp(b.circuit_id())),
+                CircuitSortField::CircuitId => a.circuit_id().cmp(b.circuit_id()),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// Sorts the circuit proposals in memory, for stores that cannot sort in their backend
+    fn sort_proposals(&self, proposals: &mut Vec<CircuitProposal>) {
+        proposals.sort_by(|a, b| {
+            let ordering = match self.field {
+                CircuitSortField::ManagementType => a
+                    .circuit()
+                    .circuit_management_type()
+                    .cmp(b.circuit().circuit_management_type())
+                    .then_with(|| a.circuit_id().cmp(b.circuit_id())),
+                CircuitSortField::CreatedAt => a
+                    .created_at()
+                    .cmp(&b.created_at())
+                    .then_with(|| a.circuit_id().cmp(b.circuit_id())),
+                CircuitSortField::CircuitId => a.circuit_id().cmp(b.circuit_id()),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
 /// Interface for performing CRUD operations on circuits, proposals, nodes, and services
 pub trait AdminServiceStore: Send + Sync {
     /// Adds a circuit proposal to the store
@@ -214,6 +314,16 @@ pub trait AdminServiceStore: Send + Sync {
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError>;
 
+    /// List circuit proposals from the store, in the given order
+    ///
+    /// The proposals returned can be filtered by provided `CircuitPredicate`. This enables
+    /// filtering by management type, members and status.
+    fn list_proposals_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError>;
+
     /// Adds a circuit to the store along with the associated services and nodes
     ///
     /// # Arguments
@@ -237,6 +347,21 @@ pub trait AdminServiceStore: Send + Sync {
     ///  Returns an error if a `CircuitProposal` with the same ID does not exist
     fn update_circuit(&self, circuit: Circuit) -> Result<(), AdminServiceStoreError>;
 
+    /// Updates the lifecycle status of a circuit in the store
+    ///
+    /// # Arguments
+    ///
+    ///  * `circuit_id` - The unique ID of the circuit to be updated
+    ///  * `status` - The new status of the circuit
+    ///
+    ///  Returns an error if a `Circuit` with the specified ID does not exist, or if the circuit
+    ///  may not move from its current status to the new status
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError>;
+
     /// Removes a circuit from the store
     ///
     /// # Arguments
@@ -261,6 +386,24 @@ pub trait AdminServiceStore: Send + Sync {
         predicates: &[CircuitPredicate],
     ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError>;
 
+    /// List all circuits from the store, in the given order
+    ///
+    /// `CircuitPredicate`s may be provided for filtering which circuits are returned.
+    fn list_circuits_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError>;
+
+    /// List all circuits from the store, loading them on up to `workers` threads if the store
+    /// supports it. This is used to load the circuits when the admin service starts.
+    ///
+    /// Unlike `list_circuits`, the circuits may not be read from a single snapshot of the store.
+    #[cfg(feature = "admin-parallel-startup")]
+    fn list_all_circuits(&self, _workers: usize) -> Result<Vec<Circuit>, AdminServiceStoreError> {
+        Ok(self.list_circuits(&[])?.collect())
+    }
+
     /// Adds a circuit, along with the associated services and nodes, to the store based on the
     /// proposal that is already in state. The associated circuit proposal for the circuit ID is
     /// also removed.
@@ -310,3 +453,29 @@ impl Clone for Box<dyn AdminServiceStore> {
         self.clone_boxed()
     }
 }
+
+/// Checks that a circuit may move from its `current` status to the `next` status
+fn check_circuit_status_transition(
+    current: &CircuitStatus,
+    next: &CircuitStatus,
+) -> Result<(), AdminServiceStoreError> {
+    if current.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(AdminServiceStoreError::InvalidStateError(
+            InvalidStateError::with_message(format!(
+                "Circuit cannot move from status {} to status {}",
+                current, next
+            )),
+        ))
+    }
+}
+
+/// Returns the current time as seconds since the Unix epoch, used to record when circuits are
+/// created and modified by the store
+fn current_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
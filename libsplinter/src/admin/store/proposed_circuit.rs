@@ -15,6 +15,8 @@
 //! Structs for building proposed circuits
 
 use crate::admin::messages::is_valid_circuit_id;
+use crate::admin::service::messages::{qos_from_proto, set_qos};
+use crate::circuit::qos::QosLimits;
 use crate::error::InvalidStateError;
 use crate::protos::admin;
 
@@ -36,6 +38,7 @@ pub struct ProposedCircuit {
     application_metadata: Option<Vec<u8>>,
     comments: Option<String>,
     display_name: Option<String>,
+    qos: QosLimits,
 }
 
 impl ProposedCircuit {
@@ -93,6 +96,11 @@ impl ProposedCircuit {
         &self.display_name
     }
 
+    /// Returns the quality-of-service limits for the circuit
+    pub fn qos(&self) -> &QosLimits {
+        &self.qos
+    }
+
     pub fn from_proto(mut proto: admin::Circuit) -> Result<Self, InvalidStateError> {
         let authorization_type = match proto.get_authorization_type() {
             admin::Circuit_AuthorizationType::TRUST_AUTHORIZATION => AuthorizationType::Trust,
@@ -148,6 +156,8 @@ impl ProposedCircuit {
             Some(proto.take_application_metadata())
         };
 
+        let qos = qos_from_proto(&proto);
+
         Ok(Self {
             circuit_id: proto.take_circuit_id(),
             roster: proto
@@ -168,6 +178,7 @@ impl ProposedCircuit {
             application_metadata,
             comments,
             display_name,
+            qos,
         })
     }
 
@@ -202,6 +213,8 @@ impl ProposedCircuit {
             circuit.set_display_name(display_name);
         }
 
+        set_qos(&mut circuit, &self.qos);
+
         match self.authorization_type {
             AuthorizationType::Trust => {
                 circuit
@@ -242,6 +255,7 @@ pub struct ProposedCircuitBuilder {
     application_metadata: Option<Vec<u8>>,
     comments: Option<String>,
     display_name: Option<String>,
+    qos: Option<QosLimits>,
 }
 
 impl ProposedCircuitBuilder {
@@ -305,6 +319,11 @@ impl ProposedCircuitBuilder {
         self.display_name.clone()
     }
 
+    /// Returns the quality-of-service limits for the circuit proposal in the builder
+    pub fn qos(&self) -> Option<QosLimits> {
+        self.qos
+    }
+
     /// Sets the circuit ID
     ///
     /// # Arguments
@@ -421,6 +440,16 @@ impl ProposedCircuitBuilder {
         self
     }
 
+    /// Sets the quality-of-service limits
+    ///
+    /// # Arguments
+    ///
+    ///  * `qos` - The limits the proposed circuit's messages are held to
+    pub fn with_qos(mut self, qos: &QosLimits) -> ProposedCircuitBuilder {
+        self.qos = Some(*qos);
+        self
+    }
+
     /// Builds a `ProposedCircuit`
     ///
     /// Returns an error if the circuit ID, roster, members or circuit management
@@ -474,6 +503,8 @@ impl ProposedCircuitBuilder {
 
         let display_name = self.display_name;
 
+        let qos = self.qos.unwrap_or_default();
+
         let create_circuit_message = ProposedCircuit {
             circuit_id,
             roster,
@@ -486,6 +517,7 @@ impl ProposedCircuitBuilder {
             application_metadata,
             comments,
             display_name,
+            qos,
         };
 
         Ok(create_circuit_message)
@@ -22,6 +22,8 @@ use crate::protos::admin;
 pub struct ProposedNode {
     node_id: String,
     endpoints: Vec<String>,
+    relay: bool,
+    observer: bool,
 }
 
 impl ProposedNode {
@@ -35,11 +37,24 @@ impl ProposedNode {
         &self.endpoints
     }
 
+    /// Returns whether the node may relay messages for other members of the circuit
+    pub fn is_relay(&self) -> bool {
+        self.relay
+    }
+
+    /// Returns whether the node is an observer, which receives the circuit's committed state but
+    /// does not take part in consensus
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
     pub fn into_proto(self) -> admin::SplinterNode {
         let mut proto = admin::SplinterNode::new();
 
         proto.set_node_id(self.node_id);
         proto.set_endpoints(self.endpoints.into());
+        proto.set_relay(self.relay);
+        proto.set_observer(self.observer);
 
         proto
     }
@@ -48,6 +63,8 @@ impl ProposedNode {
         Self {
             node_id: proto.take_node_id(),
             endpoints: proto.take_endpoints().into(),
+            relay: proto.get_relay(),
+            observer: proto.get_observer(),
         }
     }
 }
@@ -57,6 +74,8 @@ impl ProposedNode {
 pub struct ProposedNodeBuilder {
     node_id: Option<String>,
     endpoints: Option<Vec<String>>,
+    relay: bool,
+    observer: bool,
 }
 
 impl ProposedNodeBuilder {
@@ -75,6 +94,16 @@ impl ProposedNodeBuilder {
         self.endpoints.clone()
     }
 
+    /// Returns whether the node may relay messages
+    pub fn relay(&self) -> bool {
+        self.relay
+    }
+
+    /// Returns whether the node is an observer
+    pub fn observer(&self) -> bool {
+        self.observer
+    }
+
     /// Sets the node ID
     ///
     /// # Arguments
@@ -95,6 +124,27 @@ impl ProposedNodeBuilder {
         self
     }
 
+    /// Sets whether the node may relay messages for other members of the circuit; defaults to
+    /// `false`
+    ///
+    /// # Arguments
+    ///
+    ///  * `relay` - Whether the node is a relay
+    pub fn with_relay(mut self, relay: bool) -> ProposedNodeBuilder {
+        self.relay = relay;
+        self
+    }
+
+    /// Sets whether the node is an observer of the circuit; defaults to `false`
+    ///
+    /// # Arguments
+    ///
+    ///  * `observer` - Whether the node is an observer
+    pub fn with_observer(mut self, observer: bool) -> ProposedNodeBuilder {
+        self.observer = observer;
+        self
+    }
+
     /// Builds the `ProposedNode`
     ///
     /// Returns an error if the node ID or endpoints are not set
@@ -111,7 +161,12 @@ impl ProposedNodeBuilder {
 
         endpoints.sort();
 
-        let node = ProposedNode { node_id, endpoints };
+        let node = ProposedNode {
+            node_id,
+            endpoints,
+            relay: self.relay,
+            observer: self.observer,
+        };
 
         Ok(node)
     }
@@ -21,23 +21,33 @@
 
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
-use std::fs::{rename, File};
+#[cfg(not(feature = "yaml-state-journal"))]
+use std::fs::rename;
+use std::fs::File;
+#[cfg(not(feature = "yaml-state-journal"))]
 use std::io::Write;
+#[cfg(not(feature = "yaml-state-journal"))]
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use super::{
-    AdminServiceStore, AdminServiceStoreError, AuthorizationType, Circuit, CircuitBuilder,
-    CircuitNode, CircuitNodeBuilder, CircuitPredicate, CircuitProposal, CircuitProposalBuilder,
-    DurabilityType, PersistenceType, ProposalType, ProposedCircuit, ProposedCircuitBuilder,
-    ProposedNode, ProposedNodeBuilder, ProposedService, ProposedServiceBuilder, RouteType, Service,
+    check_circuit_status_transition, current_time_secs, AdminServiceStore, AdminServiceStoreError,
+    AuthorizationType, Circuit, CircuitBuilder, CircuitNode, CircuitNodeBuilder, CircuitPredicate,
+    CircuitProposal, CircuitProposalBuilder, CircuitSort, CircuitStatus, DurabilityType,
+    PersistenceType, ProposalType, ProposedCircuit, ProposedCircuitBuilder, ProposedNode,
+    ProposedNodeBuilder, ProposedService, ProposedServiceBuilder, RouteType, Service,
     ServiceBuilder, ServiceId, Vote, VoteRecord, VoteRecordBuilder,
 };
 
+#[cfg(feature = "admin-parallel-startup")]
+use crate::admin::startup::{parallel_map, STARTUP_WORKERS};
+use crate::circuit::qos::QosLimits;
 use crate::error::{
     ConstraintViolationError, ConstraintViolationType, InternalError, InvalidStateError,
 };
 use crate::hex::{parse_hex, to_hex};
+#[cfg(feature = "yaml-state-journal")]
+use crate::journal::{self, STATE_BACKUPS};
 
 /// A YAML backed implementation of the `AdminServiceStore`
 #[derive(Clone)]
@@ -68,18 +78,18 @@ impl YamlAdminServiceStore {
             state: Arc::new(Mutex::new(YamlState::default())),
         };
 
-        let circuit_file_path_buf = PathBuf::from(circuit_file_path);
-        let proposal_file_path_buf = PathBuf::from(proposal_file_path);
+        let circuit_file_exists = state_file_exists(&circuit_file_path);
+        let proposal_file_exists = state_file_exists(&proposal_file_path);
 
         // If file already exists, read it; otherwise initialize it.
-        if circuit_file_path_buf.is_file() && proposal_file_path_buf.is_file() {
+        if circuit_file_exists && proposal_file_exists {
             store.read_state()?;
-        } else if circuit_file_path_buf.is_file() {
+        } else if circuit_file_exists {
             // read circuit
             store.read_circuit_state()?;
             // write proposals
             store.write_proposal_state()?;
-        } else if proposal_file_path_buf.is_file() {
+        } else if proposal_file_exists {
             // write circuit
             store.write_circuit_state()?;
             // read proposals
@@ -94,23 +104,7 @@ impl YamlAdminServiceStore {
 
     /// Read circuit state from the circuit file path and cache the contents in the store
     fn read_circuit_state(&mut self) -> Result<(), AdminServiceStoreError> {
-        let circuit_file = File::open(&self.circuit_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                "Failed to open YAML circuit state file".to_string(),
-            ))
-        })?;
-
-        let yaml_state_circuits: YamlCircuitState = serde_yaml::from_reader(&circuit_file)
-            .map_err(|err| {
-                AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                    Box::new(err),
-                    "Failed to read YAML circuit state file".to_string(),
-                ))
-            })?;
-
-        let yaml_state = CircuitState::try_from(yaml_state_circuits)
-            .map_err(AdminServiceStoreError::InvalidStateError)?;
+        let yaml_state = read_state_file(&self.circuit_file_path, load_circuit_state)?;
 
         let mut state = self.state.lock().map_err(|_| {
             AdminServiceStoreError::InternalError(InternalError::with_message(
@@ -134,23 +128,7 @@ impl YamlAdminServiceStore {
     /// Read circuit proposal state from the proposal file path and cache the contents in the
     /// store
     fn read_proposal_state(&mut self) -> Result<(), AdminServiceStoreError> {
-        let proposal_file = File::open(&self.proposal_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                "Failed to open YAML proposal state file".to_string(),
-            ))
-        })?;
-
-        let yaml_proposals_state: YamlProposalState = serde_yaml::from_reader(&proposal_file)
-            .map_err(|err| {
-                AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                    Box::new(err),
-                    "Failed to read YAML proposal state file".to_string(),
-                ))
-            })?;
-
-        let proposals_state = ProposalState::try_from(yaml_proposals_state)
-            .map_err(AdminServiceStoreError::InvalidStateError)?;
+        let proposals_state = read_state_file(&self.proposal_file_path, load_proposal_state)?;
 
         let mut state = self.state.lock().map_err(|_| {
             AdminServiceStoreError::InternalError(InternalError::with_message(
@@ -166,41 +144,8 @@ impl YamlAdminServiceStore {
     /// read circuit proposal state from the proposal file path and cache the contents in the
     /// store
     fn read_state(&mut self) -> Result<(), AdminServiceStoreError> {
-        let circuit_file = File::open(&self.circuit_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                "Failed to open YAML circuit state file".to_string(),
-            ))
-        })?;
-
-        let yaml_state_circuits: YamlCircuitState = serde_yaml::from_reader(&circuit_file)
-            .map_err(|err| {
-                AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                    Box::new(err),
-                    "Failed to read YAML circuit state file".to_string(),
-                ))
-            })?;
-
-        let yaml_state = CircuitState::try_from(yaml_state_circuits)
-            .map_err(AdminServiceStoreError::InvalidStateError)?;
-
-        let proposal_file = File::open(&self.proposal_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                "Failed to open YAML proposal state file".to_string(),
-            ))
-        })?;
-
-        let yaml_proposals_state: YamlProposalState = serde_yaml::from_reader(&proposal_file)
-            .map_err(|err| {
-                AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                    Box::new(err),
-                    "Failed to read YAML proposal state file".to_string(),
-                ))
-            })?;
-
-        let proposals_state = ProposalState::try_from(yaml_proposals_state)
-            .map_err(AdminServiceStoreError::InvalidStateError)?;
+        let yaml_state = read_state_file(&self.circuit_file_path, load_circuit_state)?;
+        let proposals_state = read_state_file(&self.proposal_file_path, load_proposal_state)?;
 
         let mut state = self.state.lock().map_err(|_| {
             AdminServiceStoreError::InternalError(InternalError::with_message(
@@ -241,52 +186,7 @@ impl YamlAdminServiceStore {
             ))
         })?;
 
-        // write state to a temporary file to avoid state corruption if an IO error occurs during
-        // write
-        let temp_circuit_file = format!("{}.temp", self.circuit_file_path);
-        let mut circuit_file = File::create(&temp_circuit_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to open YAML circuit state file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        circuit_file.write_all(&circuit_output).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML circuit state file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        // Append newline to file
-        writeln!(circuit_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML circuit file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        // rename temp file to circuit state filename
-        rename(&temp_circuit_file, &self.circuit_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to rename temp circuit state file to final location'{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        Ok(())
+        write_state_file(&self.circuit_file_path, circuit_output, "circuit")
     }
 
     /// Write the current circuit proposal state to file at the proposal file path
@@ -307,50 +207,7 @@ impl YamlAdminServiceStore {
             ))
         })?;
 
-        let temp_proposal_file = format!("{}.temp", self.proposal_file_path);
-        let mut proposal_file = File::create(&temp_proposal_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to open YAML proposal state file '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
-
-        proposal_file.write_all(&proposal_output).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write YAML proposal state file '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
-
-        // Append newline to file
-        writeln!(proposal_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML proposal file '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
-
-        // rename temp file to proposal state filename
-        rename(&temp_proposal_file, &self.proposal_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to rename temp proposal state file to final location '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
-
-        Ok(())
+        write_state_file(&self.proposal_file_path, proposal_output, "proposal")
     }
 
     /// Write the current circuit state to file at the circuit file path and then write the current
@@ -372,50 +229,7 @@ impl YamlAdminServiceStore {
             ))
         })?;
 
-        // write state to a temporary file to avoid state corruption if an IO error occurs during
-        // write
-        let temp_circuit_file = format!("{}.temp", self.circuit_file_path);
-        let mut circuit_file = File::create(&temp_circuit_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to open YAML circuit state file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        circuit_file.write_all(&circuit_output).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML circuit state file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        // Append newline to file
-        writeln!(circuit_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML circuit file '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
-
-        // rename temp file to circuit state filename
-        rename(&temp_circuit_file, &self.circuit_file_path).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to rename temp circuit state file to final location '{}'",
-                    temp_circuit_file
-                ),
-            ))
-        })?;
+        write_state_file(&self.circuit_file_path, circuit_output, "circuit")?;
 
         let proposal_output = serde_yaml::to_vec(&YamlProposalState::from(
             state.proposal_state.clone(),
@@ -427,53 +241,186 @@ impl YamlAdminServiceStore {
             ))
         })?;
 
-        // write state to a temporary file to avoid state corruption if an IO error occurs during
-        // write
-        let temp_proposal_file = format!("{}.temp", self.proposal_file_path);
-        let mut proposal_file = File::create(&temp_proposal_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to open YAML proposal state file '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
+        write_state_file(&self.proposal_file_path, proposal_output, "proposal")
+    }
+}
 
-        proposal_file.write_all(&proposal_output).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML proposal state file '{}'",
-                    temp_proposal_file
-                ),
-            ))
-        })?;
+/// Load the circuit state from the YAML circuit state file at the given path
+fn load_circuit_state(path: &str) -> Result<CircuitState, AdminServiceStoreError> {
+    let circuit_file = File::open(path).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            "Failed to open YAML circuit state file".to_string(),
+        ))
+    })?;
 
-        // Append newline to file
-        writeln!(proposal_file).map_err(|err| {
-            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
-                Box::new(err),
-                format!(
-                    "Failed to write to YAML proposal file '{}'",
-                    self.proposal_file_path
-                ),
-            ))
-        })?;
+    let yaml_state_circuits = read_yaml_circuit_state(&circuit_file)?;
+
+    CircuitState::try_from(yaml_state_circuits).map_err(AdminServiceStoreError::InvalidStateError)
+}
+
+/// Deserialize the YAML circuit state from the given file
+#[cfg(not(feature = "admin-parallel-startup"))]
+fn read_yaml_circuit_state(
+    circuit_file: &File,
+) -> Result<YamlCircuitState, AdminServiceStoreError> {
+    serde_yaml::from_reader(circuit_file).map_err(circuit_state_read_error)
+}
+
+/// Deserialize the YAML circuit state from the given file, deserializing the circuits on several
+/// threads.
+///
+/// The file is parsed into YAML values on the calling thread, since the YAML parser cannot be
+/// split; each circuit's value is then deserialized on a worker thread.
+#[cfg(feature = "admin-parallel-startup")]
+fn read_yaml_circuit_state(
+    circuit_file: &File,
+) -> Result<YamlCircuitState, AdminServiceStoreError> {
+    let values: YamlCircuitStateValues =
+        serde_yaml::from_reader(circuit_file).map_err(circuit_state_read_error)?;
+
+    let circuits = parallel_map(
+        values.circuits.into_iter().collect(),
+        STARTUP_WORKERS,
+        |(id, value)| serde_yaml::from_value::<YamlCircuit>(value).map(|circuit| (id, circuit)),
+    )
+    .map_err(AdminServiceStoreError::InternalError)?
+    .into_iter()
+    .collect::<Result<BTreeMap<String, YamlCircuit>, serde_yaml::Error>>()
+    .map_err(circuit_state_read_error)?;
+
+    Ok(YamlCircuitState {
+        nodes: values.nodes,
+        circuits,
+    })
+}
+
+fn circuit_state_read_error(err: serde_yaml::Error) -> AdminServiceStoreError {
+    AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+        Box::new(err),
+        "Failed to read YAML circuit state file".to_string(),
+    ))
+}
 
-        // rename temp file to proposal state filename
-        rename(&temp_proposal_file, &self.proposal_file_path).map_err(|err| {
+/// Load the circuit proposal state from the YAML proposal state file at the given path
+fn load_proposal_state(path: &str) -> Result<ProposalState, AdminServiceStoreError> {
+    let proposal_file = File::open(path).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            "Failed to open YAML proposal state file".to_string(),
+        ))
+    })?;
+
+    let yaml_proposals_state: YamlProposalState =
+        serde_yaml::from_reader(&proposal_file).map_err(|err| {
             AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
                 Box::new(err),
-                format!(
-                    "Failed to rename temp proposal state file to final location '{}'",
-                    temp_proposal_file
-                ),
+                "Failed to read YAML proposal state file".to_string(),
             ))
         })?;
 
-        Ok(())
-    }
+    ProposalState::try_from(yaml_proposals_state).map_err(AdminServiceStoreError::InvalidStateError)
+}
+
+#[cfg(not(feature = "yaml-state-journal"))]
+fn state_file_exists(path: &str) -> bool {
+    PathBuf::from(path).is_file()
+}
+
+/// A state file exists if it or any of its backups exist, so that a missing file is recovered
+/// rather than replaced with empty state
+#[cfg(feature = "yaml-state-journal")]
+fn state_file_exists(path: &str) -> bool {
+    journal::exists(path, STATE_BACKUPS)
+}
+
+#[cfg(not(feature = "yaml-state-journal"))]
+fn read_state_file<T>(
+    path: &str,
+    load: fn(&str) -> Result<T, AdminServiceStoreError>,
+) -> Result<T, AdminServiceStoreError> {
+    load(path)
+}
+
+/// Load a state file, recovering its newest valid backup if the file is missing or invalid
+#[cfg(feature = "yaml-state-journal")]
+fn read_state_file<T>(
+    path: &str,
+    load: fn(&str) -> Result<T, AdminServiceStoreError>,
+) -> Result<T, AdminServiceStoreError> {
+    journal::read_verified(path, STATE_BACKUPS, load)
+}
+
+/// Write the YAML output to the state file at the given path, followed by a newline
+#[cfg(not(feature = "yaml-state-journal"))]
+fn write_state_file(
+    path: &str,
+    output: Vec<u8>,
+    state_type: &str,
+) -> Result<(), AdminServiceStoreError> {
+    // write state to a temporary file to avoid state corruption if an IO error occurs during
+    // write
+    let temp_file = format!("{}.temp", path);
+    let mut file = File::create(&temp_file).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!(
+                "Failed to open YAML {} state file '{}'",
+                state_type, temp_file
+            ),
+        ))
+    })?;
+
+    file.write_all(&output).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!(
+                "Failed to write to YAML {} state file '{}'",
+                state_type, temp_file
+            ),
+        ))
+    })?;
+
+    // Append newline to file
+    writeln!(file).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!(
+                "Failed to write to YAML {} file '{}'",
+                state_type, temp_file
+            ),
+        ))
+    })?;
+
+    // rename temp file to state filename
+    rename(&temp_file, path).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!(
+                "Failed to rename temp {} state file to final location '{}'",
+                state_type, temp_file
+            ),
+        ))
+    })
+}
+
+/// Write the YAML output to the state file at the given path, followed by a newline, keeping
+/// backups of the previous versions of the file
+#[cfg(feature = "yaml-state-journal")]
+fn write_state_file(
+    path: &str,
+    mut output: Vec<u8>,
+    state_type: &str,
+) -> Result<(), AdminServiceStoreError> {
+    // Append newline to file
+    output.push(b'\n');
+
+    journal::write_journaled(path, &output, STATE_BACKUPS).map_err(|err| {
+        AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!("Failed to write YAML {} state file '{}'", state_type, path),
+        ))
+    })
 }
 
 /// Defines methods for CRUD operations and fetching and listing circuits, proposals, nodes and
@@ -649,6 +596,20 @@ impl AdminServiceStore for YamlAdminServiceStore {
         Ok(Box::new(proposals.into_iter()))
     }
 
+    /// List circuit proposals from the underlying storage, in the given order
+    ///
+    /// The YAML state does not record when proposals were added, so sorting by creation time
+    /// sorts by circuit ID instead.
+    fn list_proposals_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = CircuitProposal>>, AdminServiceStoreError> {
+        let mut proposals = self.list_proposals(predicates)?.collect::<Vec<_>>();
+        sort.sort_proposals(&mut proposals);
+        Ok(Box::new(proposals.into_iter()))
+    }
+
     /// Adds a circuit to the underlying storage. Also includes the associated Services and
     /// Nodes
     ///
@@ -727,11 +688,11 @@ impl AdminServiceStore for YamlAdminServiceStore {
                 ))
             })?;
 
-            if state
-                .circuit_state
-                .circuits
-                .contains_key(circuit.circuit_id())
-            {
+            if let Some(existing) = state.circuit_state.circuits.get(circuit.circuit_id()) {
+                check_circuit_status_transition(
+                    existing.circuit_status(),
+                    circuit.circuit_status(),
+                )?;
                 state
                     .circuit_state
                     .circuits
@@ -754,6 +715,59 @@ impl AdminServiceStore for YamlAdminServiceStore {
         })
     }
 
+    /// Updates the lifecycle status of a circuit in the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `circuit_id` - The unique ID of the circuit to be updated
+    ///  * `status` - The new status of the circuit
+    ///
+    ///  Returns an error if a `Circuit` with the specified ID does not exist, or if the circuit
+    ///  may not move from its current status to the new status
+    fn update_circuit_status(
+        &self,
+        circuit_id: &str,
+        status: CircuitStatus,
+    ) -> Result<(), AdminServiceStoreError> {
+        {
+            let mut state = self.state.lock().map_err(|_| {
+                AdminServiceStoreError::InternalError(InternalError::with_message(
+                    "YAML admin service store's internal lock was poisoned".to_string(),
+                ))
+            })?;
+
+            let circuit = state
+                .circuit_state
+                .circuits
+                .get(circuit_id)
+                .ok_or_else(|| {
+                    AdminServiceStoreError::InvalidStateError(InvalidStateError::with_message(
+                        format!("A circuit with ID {} does not exist", circuit_id),
+                    ))
+                })?;
+            check_circuit_status_transition(circuit.circuit_status(), &status)?;
+
+            let updated = circuit
+                .builder()
+                .with_circuit_status(&status)
+                .with_updated_at(current_time_secs())
+                .build()
+                .map_err(AdminServiceStoreError::InvalidStateError)?;
+
+            state
+                .circuit_state
+                .circuits
+                .insert(circuit_id.to_string(), updated);
+        }
+
+        self.write_circuit_state().map_err(|err| {
+            AdminServiceStoreError::InternalError(InternalError::from_source_with_prefix(
+                Box::new(err),
+                "Unable to write circuit state yaml file".to_string(),
+            ))
+        })
+    }
+
     /// Removes a circuit from the underlying storage
     ///
     /// # Arguments
@@ -848,6 +862,20 @@ impl AdminServiceStore for YamlAdminServiceStore {
         Ok(Box::new(circuits.into_iter()))
     }
 
+    /// List all circuits from the underlying storage, in the given order
+    ///
+    /// The YAML state does not record when circuits were added, so sorting by creation time
+    /// sorts by circuit ID instead.
+    fn list_circuits_sorted(
+        &self,
+        predicates: &[CircuitPredicate],
+        sort: &CircuitSort,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = Circuit>>, AdminServiceStoreError> {
+        let mut circuits = self.list_circuits(predicates)?.collect::<Vec<_>>();
+        sort.sort_circuits(&mut circuits);
+        Ok(Box::new(circuits.into_iter()))
+    }
+
     /// Adds a circuit to the underlying storage based on the proposal that is already in state..
     /// Also includes the associated Services and Nodes. The associated circuit proposal for
     /// the circuit ID is also removed
@@ -867,7 +895,15 @@ impl AdminServiceStore for YamlAdminServiceStore {
                 let nodes = proposal.circuit().members().to_vec();
                 let services = proposal.circuit().roster().to_vec();
 
-                let circuit = Circuit::from(proposal.circuit().clone());
+                let now = current_time_secs();
+                let circuit = Circuit::from(proposal.circuit().clone())
+                    .builder()
+                    .with_created_at(now)
+                    .with_updated_at(now)
+                    .with_proposer_public_key(proposal.requester())
+                    .with_proposer_node_id(proposal.requester_node_id())
+                    .build()
+                    .map_err(AdminServiceStoreError::InvalidStateError)?;
                 state
                     .circuit_state
                     .circuits
@@ -1016,12 +1052,28 @@ struct YamlCircuit {
     id: String,
     roster: Vec<YamlService>,
     members: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    relay_members: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    observer_members: Vec<String>,
     auth: YamlAuthorizationType,
     persistence: YamlPersistenceType,
     durability: YamlDurabilityType,
     routes: YamlRouteType,
     circuit_management_type: String,
     display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "QosLimits::is_unlimited")]
+    qos: QosLimits,
+    #[serde(default, skip_serializing_if = "YamlCircuitStatus::is_active")]
+    circuit_status: YamlCircuitStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proposer_public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proposer_node_id: Option<String>,
 }
 
 impl TryFrom<YamlCircuit> for Circuit {
@@ -1038,15 +1090,37 @@ impl TryFrom<YamlCircuit> for Circuit {
                     .collect::<Result<Vec<Service>, InvalidStateError>>()?,
             )
             .with_members(&circuit.members)
+            .with_relay_members(&circuit.relay_members)
+            .with_observer_members(&circuit.observer_members)
             .with_authorization_type(&AuthorizationType::from(circuit.auth))
             .with_persistence(&PersistenceType::from(circuit.persistence))
             .with_durability(&DurabilityType::from(circuit.durability))
             .with_routes(&RouteType::from(circuit.routes))
-            .with_circuit_management_type(&circuit.circuit_management_type);
+            .with_circuit_management_type(&circuit.circuit_management_type)
+            .with_qos(&circuit.qos)
+            .with_circuit_status(&CircuitStatus::from(circuit.circuit_status));
 
         if let Some(display_name) = &circuit.display_name {
             builder = builder.with_display_name(display_name);
         }
+        if let Some(created_at) = circuit.created_at {
+            builder = builder.with_created_at(created_at);
+        }
+        if let Some(updated_at) = circuit.updated_at {
+            builder = builder.with_updated_at(updated_at);
+        }
+        if let Some(proposer_public_key) = &circuit.proposer_public_key {
+            builder = builder.with_proposer_public_key(&parse_hex(proposer_public_key).map_err(
+                |_| {
+                    InvalidStateError::with_message(
+                        "Proposer public key is not valid hex".to_string(),
+                    )
+                },
+            )?);
+        }
+        if let Some(proposer_node_id) = &circuit.proposer_node_id {
+            builder = builder.with_proposer_node_id(proposer_node_id);
+        }
 
         builder.build()
     }
@@ -1062,12 +1136,20 @@ impl From<Circuit> for YamlCircuit {
                 .map(|service| YamlService::from(service.clone()))
                 .collect(),
             members: circuit.members().to_vec(),
+            relay_members: circuit.relay_members().to_vec(),
+            observer_members: circuit.observer_members().to_vec(),
             auth: circuit.authorization_type().clone().into(),
             persistence: circuit.persistence().clone().into(),
             durability: circuit.durability().clone().into(),
             routes: circuit.routes().clone().into(),
             circuit_management_type: circuit.circuit_management_type().into(),
             display_name: circuit.display_name().clone(),
+            qos: *circuit.qos(),
+            circuit_status: (*circuit.circuit_status()).into(),
+            created_at: circuit.created_at(),
+            updated_at: circuit.updated_at(),
+            proposer_public_key: circuit.proposer_public_key().map(to_hex),
+            proposer_node_id: circuit.proposer_node_id().map(String::from),
         }
     }
 }
@@ -1149,6 +1231,15 @@ impl TryFrom<YamlCircuitState> for CircuitState {
     }
 }
 
+/// The YAML circuit state with each circuit left as a YAML value, so that the circuits can be
+/// deserialized separately
+#[cfg(feature = "admin-parallel-startup")]
+#[derive(Deserialize)]
+struct YamlCircuitStateValues {
+    nodes: BTreeMap<String, YamlCircuitNode>,
+    circuits: BTreeMap<String, serde_yaml::Value>,
+}
+
 impl From<CircuitState> for YamlCircuitState {
     fn from(state: CircuitState) -> Self {
         YamlCircuitState {
@@ -1186,6 +1277,10 @@ pub struct YamlCircuitProposal {
     votes: Vec<YamlVoteRecord>,
     requester: String,
     requester_node_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<u64>,
 }
 
 impl From<ProposalState> for YamlProposalState {
@@ -1221,7 +1316,7 @@ impl TryFrom<YamlCircuitProposal> for CircuitProposal {
     type Error = InvalidStateError;
 
     fn try_from(proposal: YamlCircuitProposal) -> Result<Self, Self::Error> {
-        CircuitProposalBuilder::new()
+        let mut builder = CircuitProposalBuilder::new()
             .with_circuit_id(&proposal.circuit_id)
             .with_proposal_type(&ProposalType::from(proposal.proposal_type))
             .with_circuit_hash(&proposal.circuit_hash)
@@ -1236,8 +1331,16 @@ impl TryFrom<YamlCircuitProposal> for CircuitProposal {
             .with_requester(&parse_hex(&proposal.requester).map_err(|_| {
                 InvalidStateError::with_message("Requester public key is not valid hex".to_string())
             })?)
-            .with_requester_node_id(&proposal.requester_node_id)
-            .build()
+            .with_requester_node_id(&proposal.requester_node_id);
+
+        if let Some(created_at) = proposal.created_at {
+            builder = builder.with_created_at(created_at);
+        }
+        if let Some(updated_at) = proposal.updated_at {
+            builder = builder.with_updated_at(updated_at);
+        }
+
+        builder.build()
     }
 }
 
@@ -1255,6 +1358,8 @@ impl From<CircuitProposal> for YamlCircuitProposal {
                 .collect(),
             requester: to_hex(proposal.requester()),
             requester_node_id: proposal.requester_node_id().into(),
+            created_at: proposal.created_at(),
+            updated_at: proposal.updated_at(),
         }
     }
 }
@@ -1342,6 +1447,8 @@ struct YamlProposedCircuit {
     application_metadata: Option<String>,
     comments: Option<String>,
     display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "QosLimits::is_unlimited")]
+    qos: QosLimits,
 }
 
 impl TryFrom<YamlProposedCircuit> for ProposedCircuit {
@@ -1368,7 +1475,8 @@ impl TryFrom<YamlProposedCircuit> for ProposedCircuit {
             .with_persistence(&PersistenceType::from(circuit.persistence))
             .with_durability(&DurabilityType::from(circuit.durability))
             .with_routes(&RouteType::from(circuit.routes))
-            .with_circuit_management_type(&circuit.circuit_management_type);
+            .with_circuit_management_type(&circuit.circuit_management_type)
+            .with_qos(&circuit.qos);
 
         if let Some(application_metadata) = circuit.application_metadata {
             builder = builder.with_application_metadata(&parse_hex(&application_metadata).map_err(
@@ -1424,6 +1532,7 @@ impl From<ProposedCircuit> for YamlProposedCircuit {
             application_metadata,
             comments: circuit.comments().clone(),
             display_name: circuit.display_name().clone(),
+            qos: *circuit.qos(),
         }
     }
 }
@@ -1477,6 +1586,10 @@ impl From<ProposedService> for YamlProposedService {
 pub struct YamlProposedNode {
     node_id: String,
     endpoints: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    relay: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    observer: bool,
 }
 
 impl TryFrom<YamlProposedNode> for ProposedNode {
@@ -1486,6 +1599,8 @@ impl TryFrom<YamlProposedNode> for ProposedNode {
         ProposedNodeBuilder::new()
             .with_node_id(&node.node_id)
             .with_endpoints(&node.endpoints)
+            .with_relay(node.relay)
+            .with_observer(node.observer)
             .build()
     }
 }
@@ -1495,6 +1610,61 @@ impl From<ProposedNode> for YamlProposedNode {
         YamlProposedNode {
             node_id: node.node_id().into(),
             endpoints: node.endpoints().into(),
+            relay: node.is_relay(),
+            observer: node.is_observer(),
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// YAML file specific CircuitStatus definition for serialization.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum YamlCircuitStatus {
+    Proposed,
+    Active,
+    Disbanding,
+    Disbanded,
+    Abandoned,
+    Purging,
+}
+
+impl YamlCircuitStatus {
+    fn is_active(&self) -> bool {
+        *self == YamlCircuitStatus::Active
+    }
+}
+
+impl Default for YamlCircuitStatus {
+    fn default() -> Self {
+        YamlCircuitStatus::Active
+    }
+}
+
+impl From<CircuitStatus> for YamlCircuitStatus {
+    fn from(circuit_status: CircuitStatus) -> Self {
+        match circuit_status {
+            CircuitStatus::Proposed => YamlCircuitStatus::Proposed,
+            CircuitStatus::Active => YamlCircuitStatus::Active,
+            CircuitStatus::Disbanding => YamlCircuitStatus::Disbanding,
+            CircuitStatus::Disbanded => YamlCircuitStatus::Disbanded,
+            CircuitStatus::Abandoned => YamlCircuitStatus::Abandoned,
+            CircuitStatus::Purging => YamlCircuitStatus::Purging,
+        }
+    }
+}
+
+impl From<YamlCircuitStatus> for CircuitStatus {
+    fn from(yaml_circuit_status: YamlCircuitStatus) -> Self {
+        match yaml_circuit_status {
+            YamlCircuitStatus::Proposed => CircuitStatus::Proposed,
+            YamlCircuitStatus::Active => CircuitStatus::Active,
+            YamlCircuitStatus::Disbanding => CircuitStatus::Disbanding,
+            YamlCircuitStatus::Disbanded => CircuitStatus::Disbanded,
+            YamlCircuitStatus::Abandoned => CircuitStatus::Abandoned,
+            YamlCircuitStatus::Purging => CircuitStatus::Purging,
         }
     }
 }
@@ -1662,7 +1832,8 @@ struct YamlState {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Read;
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
 
     use tempdir::TempDir;
 
@@ -2112,6 +2283,61 @@ proposals:
         assert_eq!(yaml_state_vec, contents)
     }
 
+    // Test updating the lifecycle status of a circuit
+    //
+    // 1. Setup the temp directory with existing state
+    // 2. Move the existing circuit to disbanding, validate ok
+    // 3. Reload the store from the YAML files, validate the circuit is still disbanding
+    // 4. Attempt to move the circuit to purging, validate an error is returned
+    // 5. Attempt to update the status of a nonexisting circuit, validate an error is returned
+    #[test]
+    fn test_circuit_status() {
+        // create temp dir
+        let temp_dir = TempDir::new("test_circuit_status").expect("Failed to create temp dir");
+        let circuit_path = temp_dir
+            .path()
+            .join("circuits.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        let proposals_path = temp_dir
+            .path()
+            .join("circuit_proposals.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        // write yaml files to temp_dir
+        write_file(CIRCUIT_STATE, &circuit_path);
+        write_file(PROPOSAL_STATE, &proposals_path);
+
+        let store = YamlAdminServiceStore::new(circuit_path.clone(), proposals_path.clone())
+            .expect("Unable to create yaml admin store");
+
+        store
+            .update_circuit_status("WBKLF-AAAAA", CircuitStatus::Disbanding)
+            .expect("Unable to update circuit status");
+
+        let store = YamlAdminServiceStore::new(circuit_path, proposals_path)
+            .expect("Unable to create yaml admin store");
+        let circuit = store
+            .get_circuit("WBKLF-AAAAA")
+            .expect("unable to fetch circuit")
+            .expect("Expected circuit, got none");
+        assert_eq!(circuit.circuit_status(), &CircuitStatus::Disbanding);
+        assert!(circuit.updated_at().is_some());
+
+        assert!(matches!(
+            store.update_circuit_status("WBKLF-AAAAA", CircuitStatus::Purging),
+            Err(AdminServiceStoreError::InvalidStateError(_))
+        ));
+        assert!(matches!(
+            store.update_circuit_status("WBKLF-BADD", CircuitStatus::Active),
+            Err(AdminServiceStoreError::InvalidStateError(_))
+        ));
+    }
+
     // Test the node CRUD operations
     //
     // 1. Setup the temp directory with existing state
@@ -2270,6 +2496,7 @@ proposals:
     // 2. Upgrade proposal to circuit, validate ok
     // 3. Check that proposals are now empty
     // 4. Check that the circuit, nodes and services have been set
+    // 5. Check that the circuit's creation time and proposer are written to the yaml file
     #[test]
     fn test_upgrading_proposals_to_circuit() {
         // create temp dir
@@ -2310,6 +2537,78 @@ proposals:
         assert!(store.get_circuit("WBKLF-BBBBB").unwrap().is_some());
         assert!(store.get_node("acme-node-000").unwrap().is_some());
         assert!(store.get_service(&service_id).unwrap().is_some());
+
+        let store = YamlAdminServiceStore::new(circuit_path, proposals_path)
+            .expect("Unable to create yaml admin store");
+        let circuit = store
+            .get_circuit("WBKLF-BBBBB")
+            .expect("unable to fetch circuit")
+            .expect("Expected circuit, got none");
+        assert!(circuit.created_at().is_some());
+        assert_eq!(circuit.updated_at(), circuit.created_at());
+        assert_eq!(
+            circuit.proposer_public_key(),
+            Some(create_expected_proposal().requester())
+        );
+        assert_eq!(circuit.proposer_node_id(), Some("acme-node-000"));
+    }
+
+    // Measure how long it takes to read a circuit state file with 10,000 circuits, with and
+    // without deserializing the circuits on several threads, and validate that both produce the
+    // same state. Run with:
+    //
+    //   cargo test --features admin-parallel-startup -- --ignored --nocapture \
+    //       measure_parallel_circuit_state_read
+    #[cfg(feature = "admin-parallel-startup")]
+    #[test]
+    #[ignore]
+    fn measure_parallel_circuit_state_read() {
+        let temp_dir =
+            TempDir::new("measure_parallel_circuit_state_read").expect("Failed to create temp dir");
+        let circuit_path = temp_dir.path().join("circuits.yaml");
+
+        let template = create_expected_circuit();
+        let circuits = (0..10_000)
+            .map(|index| {
+                let circuit_id = format!("{:05}-AAAAA", index);
+                let circuit = CircuitBuilder::default()
+                    .with_circuit_id(&circuit_id)
+                    .with_roster(template.roster())
+                    .with_members(template.members())
+                    .with_circuit_management_type(template.circuit_management_type())
+                    .build()
+                    .expect("Unable to build circuit");
+                (circuit_id, circuit)
+            })
+            .collect();
+        let state = YamlCircuitState::from(CircuitState {
+            nodes: BTreeMap::new(),
+            circuits,
+        });
+        write_file(
+            &serde_yaml::to_vec(&state).expect("Unable to serialize state"),
+            circuit_path.to_str().expect("Failed to get path"),
+        );
+
+        let start = std::time::Instant::now();
+        let serial: YamlCircuitState =
+            serde_yaml::from_reader(File::open(&circuit_path).expect("Unable to open file"))
+                .expect("Unable to read state");
+        let serial_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let parallel =
+            read_yaml_circuit_state(&File::open(&circuit_path).expect("Unable to open file"))
+                .expect("Unable to read state");
+        let parallel_time = start.elapsed();
+
+        assert_eq!(serial, parallel);
+        println!(
+            "Read 10000 circuits in {} ms on one thread and {} ms on {} threads",
+            serial_time.as_millis(),
+            parallel_time.as_millis(),
+            STARTUP_WORKERS
+        );
     }
 
     fn write_file(data: &[u8], file_path: &str) {
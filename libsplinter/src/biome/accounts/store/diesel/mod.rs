@@ -0,0 +1,178 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(in crate::biome) mod models;
+mod operations;
+pub(in crate::biome) mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use super::{UserAccount, UserAccountStore, UserAccountStoreError};
+
+use operations::{
+    get_account::UserAccountStoreGetAccount as _, list_accounts::UserAccountStoreListAccounts as _,
+    remove_account::UserAccountStoreRemoveAccount as _,
+    set_disabled::UserAccountStoreSetDisabled as _, set_roles::UserAccountStoreSetRoles as _,
+    UserAccountStoreOperations,
+};
+
+pub struct DieselUserAccountStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselUserAccountStore<C> {
+    /// Creates a new `DieselUserAccountStore`
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl UserAccountStore for DieselUserAccountStore<diesel::sqlite::SqliteConnection> {
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).get_account(user_id)
+    }
+
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).list_accounts()
+    }
+
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?)
+            .set_disabled(user_id, disabled)
+    }
+
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).set_roles(user_id, roles)
+    }
+
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).remove_account(user_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn UserAccountStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl UserAccountStore for DieselUserAccountStore<diesel::pg::PgConnection> {
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).get_account(user_id)
+    }
+
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).list_accounts()
+    }
+
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?)
+            .set_disabled(user_id, disabled)
+    }
+
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).set_roles(user_id, roles)
+    }
+
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError> {
+        UserAccountStoreOperations::new(&*self.connection_pool.get()?).remove_account(user_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn UserAccountStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::sqlite::SqliteConnection;
+
+    /// Verify that a SQLite-backed `DieselUserAccountStore` correctly supports disabling users,
+    /// setting their roles, listing accounts and removing accounts.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Verify that an unknown user has an enabled account with no roles.
+    /// 3. Set the roles of one user and disable another, and verify that both accounts are
+    ///    returned correctly and listed.
+    /// 4. Replace the first user's roles and verify that only the new roles are returned.
+    /// 5. Remove the first user's account and verify that it is no longer listed.
+    #[test]
+    fn sqlite_user_accounts() {
+        let store = DieselUserAccountStore::new(create_connection_pool_and_migrate());
+
+        assert_eq!(
+            store.get_account("alice").expect("Failed to get account"),
+            UserAccount::new("alice", false, vec![])
+        );
+
+        store
+            .set_roles("alice", vec!["admin".into(), "operator".into()])
+            .expect("Failed to set roles");
+        store
+            .set_disabled("bob", true)
+            .expect("Failed to disable user");
+
+        assert_eq!(
+            store.list_accounts().expect("Failed to list accounts"),
+            vec![
+                UserAccount::new("alice", false, vec!["admin".into(), "operator".into()]),
+                UserAccount::new("bob", true, vec![]),
+            ]
+        );
+
+        store
+            .set_roles("alice", vec!["auditor".into()])
+            .expect("Failed to set roles");
+        assert_eq!(
+            store.get_account("alice").expect("Failed to get account"),
+            UserAccount::new("alice", false, vec!["auditor".into()])
+        );
+
+        store
+            .remove_account("alice")
+            .expect("Failed to remove account");
+        assert_eq!(
+            store.list_accounts().expect("Failed to list accounts"),
+            vec![UserAccount::new("bob", true, vec![])]
+        );
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::biome::accounts::store::{
+    diesel::{
+        models::UserAccountModel,
+        schema::{user_account_roles, user_accounts},
+    },
+    UserAccount, UserAccountStoreError,
+};
+
+use super::UserAccountStoreOperations;
+
+pub trait UserAccountStoreGetAccount {
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError>;
+}
+
+impl<'a, C> UserAccountStoreGetAccount for UserAccountStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    bool: diesel::deserialize::FromSql<diesel::sql_types::Bool, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError> {
+        let disabled = user_accounts::table
+            .find(user_id)
+            .first::<UserAccountModel>(self.conn)
+            .optional()?
+            .map(|account| account.disabled)
+            .unwrap_or(false);
+
+        let roles = user_account_roles::table
+            .filter(user_account_roles::user_id.eq(user_id))
+            .select(user_account_roles::role)
+            .load::<String>(self.conn)?;
+
+        Ok(UserAccount::new(user_id, disabled, roles))
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use crate::biome::accounts::store::{
+    diesel::{
+        models::{UserAccountModel, UserAccountRoleModel},
+        schema::{user_account_roles, user_accounts},
+    },
+    UserAccount, UserAccountStoreError,
+};
+
+use super::UserAccountStoreOperations;
+
+pub trait UserAccountStoreListAccounts {
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError>;
+}
+
+impl<'a, C> UserAccountStoreListAccounts for UserAccountStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    bool: diesel::deserialize::FromSql<diesel::sql_types::Bool, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError> {
+        let accounts = user_accounts::table
+            .order(user_accounts::user_id)
+            .load::<UserAccountModel>(self.conn)?;
+
+        let mut roles = HashMap::<String, Vec<String>>::new();
+        for role in user_account_roles::table.load::<UserAccountRoleModel>(self.conn)? {
+            roles.entry(role.user_id).or_default().push(role.role);
+        }
+
+        Ok(accounts
+            .into_iter()
+            .map(|account| {
+                let account_roles = roles.remove(&account.user_id).unwrap_or_default();
+                UserAccount::new(&account.user_id, account.disabled, account_roles)
+            })
+            .collect())
+    }
+}
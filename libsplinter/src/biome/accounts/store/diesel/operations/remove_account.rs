@@ -0,0 +1,40 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::biome::accounts::store::{
+    diesel::schema::{user_account_roles, user_accounts},
+    UserAccountStoreError,
+};
+
+use super::UserAccountStoreOperations;
+
+pub trait UserAccountStoreRemoveAccount {
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError>;
+}
+
+impl<'a, C> UserAccountStoreRemoveAccount for UserAccountStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            delete(user_account_roles::table.filter(user_account_roles::user_id.eq(user_id)))
+                .execute(self.conn)?;
+            delete(user_accounts::table.find(user_id)).execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
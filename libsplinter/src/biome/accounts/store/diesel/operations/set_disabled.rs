@@ -0,0 +1,83 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{insert_into, update},
+    prelude::*,
+};
+
+use crate::biome::accounts::store::{
+    diesel::{models::UserAccountModel, schema::user_accounts},
+    UserAccountStoreError,
+};
+
+use super::UserAccountStoreOperations;
+
+pub trait UserAccountStoreSetDisabled {
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> UserAccountStoreSetDisabled
+    for UserAccountStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            if user_accounts::table
+                .find(user_id)
+                .first::<UserAccountModel>(self.conn)
+                .optional()?
+                .is_some()
+            {
+                update(user_accounts::table.find(user_id))
+                    .set(user_accounts::disabled.eq(disabled))
+                    .execute(self.conn)?;
+            } else {
+                insert_into(user_accounts::table)
+                    .values(UserAccountModel {
+                        user_id: user_id.to_string(),
+                        disabled,
+                    })
+                    .execute(self.conn)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> UserAccountStoreSetDisabled for UserAccountStoreOperations<'a, diesel::pg::PgConnection> {
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            if user_accounts::table
+                .find(user_id)
+                .first::<UserAccountModel>(self.conn)
+                .optional()?
+                .is_some()
+            {
+                update(user_accounts::table.find(user_id))
+                    .set(user_accounts::disabled.eq(disabled))
+                    .execute(self.conn)?;
+            } else {
+                insert_into(user_accounts::table)
+                    .values(UserAccountModel {
+                        user_id: user_id.to_string(),
+                        disabled,
+                    })
+                    .execute(self.conn)?;
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::biome::accounts::store::{
+    check_roles,
+    diesel::{
+        models::{UserAccountModel, UserAccountRoleModel},
+        schema::{user_account_roles, user_accounts},
+    },
+    UserAccount, UserAccountStoreError,
+};
+
+use super::UserAccountStoreOperations;
+
+pub trait UserAccountStoreSetRoles {
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> UserAccountStoreSetRoles
+    for UserAccountStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        check_roles(&roles)?;
+        // Normalize the roles so duplicates are not inserted
+        let roles = UserAccount::new(user_id, false, roles).roles().to_vec();
+
+        self.conn.transaction::<_, _, _>(|| {
+            // The roles table references the user's account entry, so create one if necessary
+            if user_accounts::table
+                .find(user_id)
+                .first::<UserAccountModel>(self.conn)
+                .optional()?
+                .is_none()
+            {
+                insert_into(user_accounts::table)
+                    .values(UserAccountModel {
+                        user_id: user_id.to_string(),
+                        disabled: false,
+                    })
+                    .execute(self.conn)?;
+            }
+
+            delete(user_account_roles::table.filter(user_account_roles::user_id.eq(user_id)))
+                .execute(self.conn)?;
+            insert_into(user_account_roles::table)
+                .values(
+                    roles
+                        .into_iter()
+                        .map(|role| UserAccountRoleModel {
+                            user_id: user_id.to_string(),
+                            role,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> UserAccountStoreSetRoles for UserAccountStoreOperations<'a, diesel::pg::PgConnection> {
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        check_roles(&roles)?;
+        // Normalize the roles so duplicates are not inserted
+        let roles = UserAccount::new(user_id, false, roles).roles().to_vec();
+
+        self.conn.transaction::<_, _, _>(|| {
+            // The roles table references the user's account entry, so create one if necessary
+            if user_accounts::table
+                .find(user_id)
+                .first::<UserAccountModel>(self.conn)
+                .optional()?
+                .is_none()
+            {
+                insert_into(user_accounts::table)
+                    .values(UserAccountModel {
+                        user_id: user_id.to_string(),
+                        disabled: false,
+                    })
+                    .execute(self.conn)?;
+            }
+
+            delete(user_account_roles::table.filter(user_account_roles::user_id.eq(user_id)))
+                .execute(self.conn)?;
+            insert_into(user_account_roles::table)
+                .values(
+                    roles
+                        .into_iter()
+                        .map(|role| UserAccountRoleModel {
+                            user_id: user_id.to_string(),
+                            role,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{InternalError, InvalidArgumentError};
+
+/// Represents errors raised while administering user accounts
+#[derive(Debug)]
+pub enum UserAccountStoreError {
+    Internal(InternalError),
+    InvalidArgument(InvalidArgumentError),
+}
+
+impl Error for UserAccountStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UserAccountStoreError::Internal(err) => err.source(),
+            UserAccountStoreError::InvalidArgument(err) => err.source(),
+        }
+    }
+}
+
+impl fmt::Display for UserAccountStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UserAccountStoreError::Internal(err) => f.write_str(&err.to_string()),
+            UserAccountStoreError::InvalidArgument(err) => f.write_str(&err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl From<diesel::r2d2::PoolError> for UserAccountStoreError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        UserAccountStoreError::Internal(InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl From<diesel::result::Error> for UserAccountStoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        UserAccountStoreError::Internal(InternalError::from_source(Box::new(err)))
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+
+use super::{check_roles, UserAccount, UserAccountStore, UserAccountStoreError};
+
+#[derive(Default, Clone)]
+pub struct MemoryUserAccountStore {
+    /// Map of user ID -> account
+    inner: Arc<Mutex<HashMap<String, UserAccount>>>,
+}
+
+impl MemoryUserAccountStore {
+    /// Creates a new memory-backed user account store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<HashMap<String, UserAccount>>, UserAccountStoreError> {
+        self.inner.lock().map_err(|_| {
+            UserAccountStoreError::Internal(InternalError::with_message(
+                "Cannot access user account store: mutex lock poisoned".to_string(),
+            ))
+        })
+    }
+}
+
+impl UserAccountStore for MemoryUserAccountStore {
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError> {
+        Ok(self
+            .lock()?
+            .get(user_id)
+            .cloned()
+            .unwrap_or_else(|| UserAccount::new(user_id, false, vec![])))
+    }
+
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError> {
+        let mut accounts = self.lock()?.values().cloned().collect::<Vec<_>>();
+        accounts.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        Ok(accounts)
+    }
+
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        self.lock()?
+            .entry(user_id.to_string())
+            .or_insert_with(|| UserAccount::new(user_id, false, vec![]))
+            .disabled = disabled;
+        Ok(())
+    }
+
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        check_roles(&roles)?;
+        let mut inner = self.lock()?;
+        let disabled = inner
+            .get(user_id)
+            .map(|account| account.disabled)
+            .unwrap_or(false);
+        inner.insert(
+            user_id.to_string(),
+            UserAccount::new(user_id, disabled, roles),
+        );
+        Ok(())
+    }
+
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError> {
+        self.lock()?.remove(user_id);
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn UserAccountStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the memory store returns a default account for unknown users, and that
+    /// disabling a user and setting its roles are reflected in the account.
+    #[test]
+    fn disable_and_set_roles() {
+        let store = MemoryUserAccountStore::new();
+
+        assert_eq!(
+            store.get_account("user").expect("Failed to get account"),
+            UserAccount::new("user", false, vec![])
+        );
+        assert!(store
+            .list_accounts()
+            .expect("Failed to list accounts")
+            .is_empty());
+
+        store
+            .set_disabled("user", true)
+            .expect("Failed to disable user");
+        store
+            .set_roles("user", vec!["operator".into(), "admin".into()])
+            .expect("Failed to set roles");
+
+        let account = store.get_account("user").expect("Failed to get account");
+        assert!(account.disabled());
+        assert_eq!(
+            account.roles(),
+            &["admin".to_string(), "operator".to_string()]
+        );
+        assert!(account.has_role("admin"));
+
+        assert!(matches!(
+            store.set_roles("user", vec!["".into()]),
+            Err(UserAccountStoreError::InvalidArgument(_))
+        ));
+
+        store
+            .remove_account("user")
+            .expect("Failed to remove account");
+        assert!(!store
+            .get_account("user")
+            .expect("Failed to get account")
+            .disabled());
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "diesel")]
+pub(in crate::biome) mod diesel;
+mod error;
+pub(in crate::biome) mod memory;
+
+use crate::error::InvalidArgumentError;
+
+pub use error::UserAccountStoreError;
+
+/// The administrative state of a Biome user
+///
+/// Users that do not have an account entry in the store are enabled and have no roles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserAccount {
+    user_id: String,
+    disabled: bool,
+    roles: Vec<String>,
+}
+
+impl UserAccount {
+    /// Creates a new user account
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id`: The ID of the Biome user
+    /// * `disabled`: Whether or not the user is prevented from logging in
+    /// * `roles`: The roles assigned to the user
+    pub fn new(user_id: &str, disabled: bool, mut roles: Vec<String>) -> Self {
+        roles.sort();
+        roles.dedup();
+        Self {
+            user_id: user_id.to_string(),
+            disabled,
+            roles,
+        }
+    }
+
+    /// Returns the ID of the Biome user
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Returns whether or not the user is prevented from logging in
+    pub fn disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Returns the roles assigned to the user, in sorted order
+    pub fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    /// Returns whether or not the user has been assigned the given role
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|assigned| assigned == role)
+    }
+}
+
+/// Defines methods for administering the accounts of Biome users
+pub trait UserAccountStore: Send + Sync {
+    /// Returns the account of the given user. If the user does not have an account entry, an
+    /// enabled account with no roles is returned.
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError>;
+
+    /// Returns all account entries in the store
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError>;
+
+    /// Sets whether or not the given user is prevented from logging in
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError>;
+
+    /// Replaces the roles assigned to the given user
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if any of the roles is empty.
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError>;
+
+    /// Removes the account entry of the given user, if it exists
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn UserAccountStore>;
+}
+
+impl Clone for Box<dyn UserAccountStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<US> UserAccountStore for Box<US>
+where
+    US: UserAccountStore + ?Sized,
+{
+    fn get_account(&self, user_id: &str) -> Result<UserAccount, UserAccountStoreError> {
+        (**self).get_account(user_id)
+    }
+
+    fn list_accounts(&self) -> Result<Vec<UserAccount>, UserAccountStoreError> {
+        (**self).list_accounts()
+    }
+
+    fn set_disabled(&self, user_id: &str, disabled: bool) -> Result<(), UserAccountStoreError> {
+        (**self).set_disabled(user_id, disabled)
+    }
+
+    fn set_roles(&self, user_id: &str, roles: Vec<String>) -> Result<(), UserAccountStoreError> {
+        (**self).set_roles(user_id, roles)
+    }
+
+    fn remove_account(&self, user_id: &str) -> Result<(), UserAccountStoreError> {
+        (**self).remove_account(user_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn UserAccountStore> {
+        (**self).clone_box()
+    }
+}
+
+/// Checks that none of the given roles are empty
+fn check_roles(roles: &[String]) -> Result<(), UserAccountStoreError> {
+    if roles.iter().any(|role| role.trim().is_empty()) {
+        Err(UserAccountStoreError::InvalidArgument(
+            InvalidArgumentError::new("roles".to_string(), "roles cannot be empty".to_string()),
+        ))
+    } else {
+        Ok(())
+    }
+}
@@ -16,6 +16,11 @@
 
 pub mod store;
 
+#[cfg(feature = "biome-key-expiration")]
+use std::time::SystemTime;
+#[cfg(all(feature = "biome-key-expiration", feature = "diesel"))]
+use std::time::{Duration, UNIX_EPOCH};
+
 #[cfg(feature = "diesel")]
 use store::diesel::models::KeyModel;
 
@@ -26,6 +31,12 @@ pub struct Key {
     pub encrypted_private_key: String,
     pub user_id: String,
     pub display_name: String,
+    /// The time after which the key may no longer be used to authenticate, if any
+    #[cfg(feature = "biome-key-expiration")]
+    pub expires_at: Option<SystemTime>,
+    /// The last time the key was used to authenticate, if it has been used
+    #[cfg(feature = "biome-key-expiration")]
+    pub last_used: Option<SystemTime>,
 }
 
 impl Key {
@@ -51,6 +62,19 @@ impl Key {
             encrypted_private_key: encrypted_private_key.to_string(),
             user_id: user_id.to_string(),
             display_name: display_name.to_string(),
+            #[cfg(feature = "biome-key-expiration")]
+            expires_at: None,
+            #[cfg(feature = "biome-key-expiration")]
+            last_used: None,
+        }
+    }
+
+    /// Returns `true` if the key has an expiration time that has passed
+    #[cfg(feature = "biome-key-expiration")]
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at <= SystemTime::now(),
+            None => false,
         }
     }
 }
@@ -63,6 +87,10 @@ impl From<KeyModel> for Key {
             encrypted_private_key: key.encrypted_private_key,
             user_id: key.user_id,
             display_name: key.display_name,
+            #[cfg(feature = "biome-key-expiration")]
+            expires_at: key.expires_at.map(from_timestamp),
+            #[cfg(feature = "biome-key-expiration")]
+            last_used: key.last_used.map(from_timestamp),
         }
     }
 }
@@ -75,6 +103,29 @@ impl Into<KeyModel> for Key {
             encrypted_private_key: self.encrypted_private_key,
             user_id: self.user_id,
             display_name: self.display_name,
+            #[cfg(feature = "biome-key-expiration")]
+            expires_at: self.expires_at.map(to_timestamp),
+            #[cfg(not(feature = "biome-key-expiration"))]
+            expires_at: None,
+            #[cfg(feature = "biome-key-expiration")]
+            last_used: self.last_used.map(to_timestamp),
+            #[cfg(not(feature = "biome-key-expiration"))]
+            last_used: None,
         }
     }
 }
+
+/// Converts a timestamp in seconds since the Unix epoch to a `SystemTime`. Timestamps before the
+/// epoch are treated as the epoch, so that an invalid expiration is never extended.
+#[cfg(all(feature = "biome-key-expiration", feature = "diesel"))]
+fn from_timestamp(timestamp: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(timestamp.max(0) as u64)
+}
+
+/// Converts a `SystemTime` to a timestamp in seconds since the Unix epoch
+#[cfg(all(feature = "biome-key-expiration", feature = "diesel"))]
+pub(in crate::biome) fn to_timestamp(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -16,6 +16,9 @@ pub(in crate::biome) mod models;
 mod operations;
 mod schema;
 
+#[cfg(feature = "biome-key-expiration")]
+use std::time::SystemTime;
+
 use diesel::r2d2::{ConnectionManager, Pool};
 
 #[cfg(feature = "biome-credentials")]
@@ -31,6 +34,12 @@ use operations::{
     remove_key::KeyStoreRemoveKeyOperation as _, update_key::KeyStoreUpdateKeyOperation as _,
     KeyStoreOperations,
 };
+#[cfg(feature = "biome-key-expiration")]
+use operations::{
+    list_keys::KeyStoreListKeysWithPublicKeyOperation as _,
+    update_key_expiration::KeyStoreUpdateKeyExpirationOperation as _,
+    update_key_last_used::KeyStoreUpdateKeyLastUsedOperation as _,
+};
 
 /// Manages creating, updating and fetching keys from a database.
 pub struct DieselKeyStore<C: diesel::Connection + 'static> {
@@ -99,6 +108,32 @@ impl KeyStore for DieselKeyStore<diesel::pg::PgConnection> {
             keys,
         )
     }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).list_keys_with_public_key(public_key)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?)
+            .update_key_expiration(public_key, user_id, expires_at)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?)
+            .update_key_last_used(public_key, last_used)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -151,6 +186,32 @@ impl KeyStore for DieselKeyStore<diesel::sqlite::SqliteConnection> {
             keys,
         )
     }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).list_keys_with_public_key(public_key)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?)
+            .update_key_expiration(public_key, user_id, expires_at)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?)
+            .update_key_last_used(public_key, last_used)
+    }
 }
 
 #[cfg(all(test, feature = "sqlite"))]
@@ -411,6 +472,70 @@ pub mod tests {
             .expect("Failed to verify password"));
     }
 
+    #[cfg(feature = "biome-key-expiration")]
+    /// Verify that a SQLite-backed `DieselKeyStore` correctly supports key expiration and
+    /// last-used times.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselKeyStore`.
+    /// 3. Add a key with an expiration time that has passed, and verify that it is returned as
+    ///    expired.
+    /// 4. Clear the expiration and verify that the key is no longer expired.
+    /// 5. Add the same public key for another user, record that the public key was used, and
+    ///    verify that the last-used time is set for both keys.
+    #[test]
+    fn sqlite_key_expiration_and_last_used() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselKeyStore::new(pool);
+
+        let mut key1 = Key::new("pubkey", "privkey1", "user1", "name1");
+        key1.expires_at = Some(UNIX_EPOCH + Duration::from_secs(1000));
+        store.add_key(key1.clone()).expect("Failed to add key1");
+        let fetched = store
+            .fetch_key("pubkey", "user1")
+            .expect("Failed to fetch key1");
+        assert_eq!(fetched, key1);
+        assert!(fetched.is_expired());
+
+        store
+            .update_key_expiration("pubkey", "user1", None)
+            .expect("Failed to clear expiration");
+        assert!(!store
+            .fetch_key("pubkey", "user1")
+            .expect("Failed to fetch key1")
+            .is_expired());
+
+        match store.update_key_expiration("pubkey", "user2", None) {
+            Err(KeyStoreError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(KeyStoreError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+
+        let key2 = Key::new("pubkey", "privkey2", "user2", "name2");
+        store.add_key(key2).expect("Failed to add key2");
+
+        let last_used = UNIX_EPOCH + Duration::from_secs(2000);
+        store
+            .update_key_last_used("pubkey", last_used)
+            .expect("Failed to update last used");
+
+        let keys = store
+            .list_keys_with_public_key("pubkey")
+            .expect("Failed to list keys");
+        assert_eq!(keys.len(), 2);
+        assert!(keys.iter().all(|key| key.last_used == Some(last_used)));
+
+        assert!(store
+            .list_keys_with_public_key("otherkey")
+            .expect("Failed to list keys")
+            .is_empty());
+    }
+
     /// Creates a conneciton pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection insures that the same DB is used for all operations.
@@ -22,4 +22,6 @@ pub struct KeyModel {
     pub encrypted_private_key: String,
     pub user_id: String,
     pub display_name: String,
+    pub expires_at: Option<i64>,
+    pub last_used: Option<i64>,
 }
@@ -26,6 +26,7 @@ pub(in crate::biome::key_management) trait KeyStoreFetchKeyOperation {
 impl<'a, C> KeyStoreFetchKeyOperation for KeyStoreOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn fetch_key(&self, public_key: &str, user_id: &str) -> Result<Key, KeyStoreError> {
@@ -26,6 +26,7 @@ pub(in crate::biome::key_management) trait KeyStoreListKeysOperation {
 impl<'a, C> KeyStoreListKeysOperation for KeyStoreOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn list_keys(&self) -> Result<Vec<Key>, KeyStoreError> {
@@ -54,6 +55,7 @@ pub(in crate::biome::key_management) trait KeyStoreListKeysWithUserIDOperation {
 impl<'a, C> KeyStoreListKeysWithUserIDOperation for KeyStoreOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn list_keys_with_user_id(&self, user_id: &str) -> Result<Vec<Key>, KeyStoreError> {
@@ -77,3 +79,30 @@ where
         Ok(keys)
     }
 }
+
+#[cfg(feature = "biome-key-expiration")]
+pub(in crate::biome::key_management) trait KeyStoreListKeysWithPublicKeyOperation {
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError>;
+}
+
+#[cfg(feature = "biome-key-expiration")]
+impl<'a, C> KeyStoreListKeysWithPublicKeyOperation for KeyStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError> {
+        let keys = keys::table
+            .filter(keys::public_key.eq(public_key))
+            .load::<KeyModel>(self.conn)
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to get keys with public key".to_string(),
+                source: Box::new(err),
+            })?
+            .into_iter()
+            .map(Key::from)
+            .collect();
+        Ok(keys)
+    }
+}
@@ -17,6 +17,10 @@ pub(super) mod insert_key;
 pub(super) mod list_keys;
 pub(super) mod remove_key;
 pub(super) mod update_key;
+#[cfg(feature = "biome-key-expiration")]
+pub(super) mod update_key_expiration;
+#[cfg(feature = "biome-key-expiration")]
+pub(super) mod update_key_last_used;
 #[cfg(feature = "biome-credentials")]
 pub(super) mod update_keys_and_password;
 
@@ -26,6 +26,7 @@ pub(in crate::biome::key_management) trait KeyStoreRemoveKeyOperation {
 impl<'a, C> KeyStoreRemoveKeyOperation for KeyStoreOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn remove_key(&self, public_key: &str, user_id: &str) -> Result<Key, KeyStoreError> {
@@ -0,0 +1,81 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::SystemTime;
+
+use super::KeyStoreOperations;
+use crate::biome::key_management::store::diesel::schema::keys;
+use crate::biome::key_management::store::KeyStoreError;
+use crate::biome::key_management::to_timestamp;
+
+use diesel::prelude::*;
+
+pub(in crate::biome::key_management) trait KeyStoreUpdateKeyExpirationOperation {
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> KeyStoreUpdateKeyExpirationOperation for KeyStoreOperations<'a, diesel::pg::PgConnection> {
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        match diesel::update(keys::table.find((public_key, user_id)))
+            .set(keys::expires_at.eq(expires_at.map(to_timestamp)))
+            .execute(self.conn)
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to update key expiration".to_string(),
+                source: Box::new(err),
+            })? {
+            0 => Err(KeyStoreError::NotFoundError(format!(
+                "Key with public key {} and user ID {} not found",
+                public_key, user_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> KeyStoreUpdateKeyExpirationOperation
+    for KeyStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        match diesel::update(keys::table.find((public_key, user_id)))
+            .set(keys::expires_at.eq(expires_at.map(to_timestamp)))
+            .execute(self.conn)
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to update key expiration".to_string(),
+                source: Box::new(err),
+            })? {
+            0 => Err(KeyStoreError::NotFoundError(format!(
+                "Key with public key {} and user ID {} not found",
+                public_key, user_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::SystemTime;
+
+use super::KeyStoreOperations;
+use crate::biome::key_management::store::diesel::schema::keys;
+use crate::biome::key_management::store::KeyStoreError;
+use crate::biome::key_management::to_timestamp;
+
+use diesel::prelude::*;
+
+pub(in crate::biome::key_management) trait KeyStoreUpdateKeyLastUsedOperation {
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> KeyStoreUpdateKeyLastUsedOperation for KeyStoreOperations<'a, diesel::pg::PgConnection> {
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        diesel::update(keys::table.filter(keys::public_key.eq(public_key)))
+            .set(keys::last_used.eq(Some(to_timestamp(last_used))))
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to update key last used time".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> KeyStoreUpdateKeyLastUsedOperation
+    for KeyStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        diesel::update(keys::table.filter(keys::public_key.eq(public_key)))
+            .set(keys::last_used.eq(Some(to_timestamp(last_used))))
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to update key last used time".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
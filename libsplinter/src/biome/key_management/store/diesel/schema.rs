@@ -18,5 +18,7 @@ table! {
         encrypted_private_key -> Text,
         user_id -> Text,
         display_name -> Text,
+        expires_at -> Nullable<BigInt>,
+        last_used -> Nullable<BigInt>,
     }
 }
@@ -12,6 +12,8 @@
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "biome-key-expiration")]
+use std::time::SystemTime;
 
 #[cfg(feature = "biome-credentials")]
 use crate::biome::credentials::store::{
@@ -160,4 +162,60 @@ impl KeyStore for MemoryKeyStore {
 
         Ok(())
     }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError> {
+        let inner = self.inner.lock().map_err(|_| KeyStoreError::StorageError {
+            context: "Cannot access key store: mutex lock poisoned".to_string(),
+            source: None,
+        })?;
+
+        Ok(inner
+            .iter()
+            .filter(|((_, key), _)| key == public_key)
+            .map(|(_, v)| v.clone())
+            .collect())
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        let mut inner = self.inner.lock().map_err(|_| KeyStoreError::StorageError {
+            context: "Cannot access key store: mutex lock poisoned".to_string(),
+            source: None,
+        })?;
+
+        if let Some(key) = inner.get_mut(&(user_id.into(), public_key.into())) {
+            key.expires_at = expires_at;
+            Ok(())
+        } else {
+            Err(KeyStoreError::NotFoundError(format!(
+                "Key with user id {} not found",
+                user_id
+            )))
+        }
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        let mut inner = self.inner.lock().map_err(|_| KeyStoreError::StorageError {
+            context: "Cannot access key store: mutex lock poisoned".to_string(),
+            source: None,
+        })?;
+
+        inner
+            .iter_mut()
+            .filter(|((_, key), _)| key == public_key)
+            .for_each(|(_, key)| key.last_used = Some(last_used));
+
+        Ok(())
+    }
 }
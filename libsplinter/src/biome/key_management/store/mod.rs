@@ -17,6 +17,9 @@ pub(in crate::biome) mod diesel;
 pub mod error;
 pub(in crate::biome) mod memory;
 
+#[cfg(feature = "biome-key-expiration")]
+use std::time::SystemTime;
+
 #[cfg(feature = "biome-credentials")]
 use crate::biome::credentials::store::PasswordEncryptionCost;
 
@@ -87,6 +90,43 @@ pub trait KeyStore: Sync + Send {
         password_encryption_cost: PasswordEncryptionCost,
         keys: &[Key],
     ) -> Result<(), KeyStoreError>;
+
+    /// Lists all keys with the given public key, regardless of the user that owns them
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The public key of the key records to list.
+    #[cfg(feature = "biome-key-expiration")]
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError>;
+
+    /// Sets or clears the time after which a key may no longer be used to authenticate
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The public key of the key record to be updated.
+    /// * `user_id`: The ID owner of the key record to be updated.
+    /// * `expires_at`: The new expiration time, or `None` if the key does not expire.
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError>;
+
+    /// Records the time at which a public key was last used to authenticate. All keys with the
+    /// public key are updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The public key that was used.
+    /// * `last_used`: The time the key was used.
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError>;
 }
 
 impl<KS> KeyStore for Box<KS>
@@ -128,4 +168,28 @@ where
     ) -> Result<(), KeyStoreError> {
         (**self).update_keys_and_password(user_id, updated_password, password_encryption_cost, keys)
     }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn list_keys_with_public_key(&self, public_key: &str) -> Result<Vec<Key>, KeyStoreError> {
+        (**self).list_keys_with_public_key(public_key)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_expiration(
+        &self,
+        public_key: &str,
+        user_id: &str,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), KeyStoreError> {
+        (**self).update_key_expiration(public_key, user_id, expires_at)
+    }
+
+    #[cfg(feature = "biome-key-expiration")]
+    fn update_key_last_used(
+        &self,
+        public_key: &str,
+        last_used: SystemTime,
+    ) -> Result<(), KeyStoreError> {
+        (**self).update_key_last_used(public_key, last_used)
+    }
 }
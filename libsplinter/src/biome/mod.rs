@@ -25,6 +25,9 @@
 //!
 //! User Notifications: API to create and manage user notifications.
 
+#[cfg(feature = "biome-user-admin")]
+pub mod accounts;
+
 #[cfg(feature = "biome-credentials")]
 pub mod credentials;
 
@@ -43,6 +46,13 @@ pub mod refresh_tokens;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
 
+#[cfg(all(feature = "biome-user-admin", feature = "diesel"))]
+pub use accounts::store::diesel::DieselUserAccountStore;
+#[cfg(feature = "biome-user-admin")]
+pub use accounts::store::memory::MemoryUserAccountStore;
+#[cfg(feature = "biome-user-admin")]
+pub use accounts::store::UserAccountStore;
+
 #[cfg(all(feature = "biome-credentials", feature = "diesel"))]
 pub use credentials::store::diesel::DieselCredentialsStore;
 #[cfg(feature = "biome-credentials")]
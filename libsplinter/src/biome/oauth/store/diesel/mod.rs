@@ -31,6 +31,11 @@ use operations::{
     remove_session::OAuthUserSessionStoreRemoveSession as _,
     update_session::OAuthUserSessionStoreUpdateSession as _, OAuthUserSessionStoreOperations,
 };
+#[cfg(feature = "biome-oauth-account-linking")]
+use operations::{
+    link_user::OAuthUserSessionStoreLinkUser as _,
+    unlink_user::OAuthUserSessionStoreUnlinkUser as _,
+};
 
 /// A database-backed [OAuthUserSessionStore], powered by [diesel].
 pub struct DieselOAuthUserSessionStore<C: diesel::Connection + 'static> {
@@ -82,6 +87,18 @@ impl OAuthUserSessionStore for DieselOAuthUserSessionStore<diesel::sqlite::Sqlit
         OAuthUserSessionStoreOperations::new(&*connection).get_user(subject)
     }
 
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError> {
+        let connection = self.connection_pool.get()?;
+        OAuthUserSessionStoreOperations::new(&*connection).link_user(subject, user_id)
+    }
+
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError> {
+        let connection = self.connection_pool.get()?;
+        OAuthUserSessionStoreOperations::new(&*connection).unlink_user(subject)
+    }
+
     fn clone_box(&self) -> Box<dyn OAuthUserSessionStore> {
         Box::new(Self {
             connection_pool: self.connection_pool.clone(),
@@ -128,6 +145,18 @@ impl OAuthUserSessionStore for DieselOAuthUserSessionStore<diesel::pg::PgConnect
         OAuthUserSessionStoreOperations::new(&*connection).get_user(subject)
     }
 
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError> {
+        let connection = self.connection_pool.get()?;
+        OAuthUserSessionStoreOperations::new(&*connection).link_user(subject, user_id)
+    }
+
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError> {
+        let connection = self.connection_pool.get()?;
+        OAuthUserSessionStoreOperations::new(&*connection).unlink_user(subject)
+    }
+
     fn clone_box(&self) -> Box<dyn OAuthUserSessionStore> {
         Box::new(Self {
             connection_pool: self.connection_pool.clone(),
@@ -459,6 +488,71 @@ pub mod tests {
         assert_eq!(stored_session2.oauth_access_token(), oauth_access_token2);
     }
 
+    /// Verify that a SQLite-backed `DieselOAuthUserSessionStore` correctly supports linking OAuth
+    /// subjects to Biome users and removing the links.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create a `DieselOAuthUserSessionStore`.
+    /// 3. Add sessions for two subjects.
+    /// 4. Link the first subject to a Biome user ID and verify that both the user and the existing
+    ///    session report the linked ID.
+    /// 5. Verify that linking the second subject to the same Biome user ID results in a
+    ///    ConstraintViolation error, and linking an unknown subject results in an InvalidState
+    ///    error.
+    /// 6. Unlink the first subject and verify that it has been assigned a new user ID.
+    #[cfg(feature = "biome-oauth-account-linking")]
+    #[test]
+    fn sqlite_link_and_unlink_user() {
+        let pool = create_connection_pool_and_migrate();
+
+        let oauth_user_session_store = DieselOAuthUserSessionStore::new(pool);
+
+        for (splinter_access_token, subject) in &[("token1", "subject1"), ("token2", "subject2")] {
+            let session = InsertableOAuthUserSessionBuilder::new()
+                .with_splinter_access_token(splinter_access_token.to_string())
+                .with_subject(subject.to_string())
+                .with_oauth_access_token("oauth_access_token".into())
+                .build()
+                .expect("Unable to build session");
+            oauth_user_session_store
+                .add_session(session)
+                .expect("Unable to add session");
+        }
+
+        oauth_user_session_store
+            .link_user("subject1", "biome_user")
+            .expect("Unable to link user");
+        let user = oauth_user_session_store
+            .get_user("subject1")
+            .expect("Unable to get user")
+            .expect("User not found");
+        assert_eq!(user.user_id(), "biome_user");
+        let session = oauth_user_session_store
+            .get_session("token1")
+            .expect("Unable to get session")
+            .expect("Session not found");
+        assert_eq!(session.user().user_id(), "biome_user");
+
+        assert!(matches!(
+            oauth_user_session_store.link_user("subject2", "biome_user"),
+            Err(OAuthUserSessionStoreError::ConstraintViolation(_)),
+        ));
+        assert!(matches!(
+            oauth_user_session_store.link_user("unknown_subject", "other_user"),
+            Err(OAuthUserSessionStoreError::InvalidState(_)),
+        ));
+
+        let unlinked_user = oauth_user_session_store
+            .unlink_user("subject1")
+            .expect("Unable to unlink user");
+        assert_ne!(unlinked_user.user_id(), "biome_user");
+        let user = oauth_user_session_store
+            .get_user("subject1")
+            .expect("Unable to get user")
+            .expect("User not found");
+        assert_eq!(user.user_id(), unlinked_user.user_id());
+    }
+
     /// Creates a connection pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection insures that the same DB is used for all operations.
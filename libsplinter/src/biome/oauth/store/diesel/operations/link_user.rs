@@ -0,0 +1,70 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::update, prelude::*};
+
+use crate::biome::oauth::store::{
+    diesel::{models::OAuthUserModel, schema::oauth_users},
+    OAuthUserSessionStoreError,
+};
+use crate::error::{ConstraintViolationError, ConstraintViolationType, InvalidStateError};
+
+use super::OAuthUserSessionStoreOperations;
+
+pub trait OAuthUserSessionStoreLinkUser {
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError>;
+}
+
+impl<'a, C> OAuthUserSessionStoreLinkUser for OAuthUserSessionStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            // Check that the subject has an entry in the users table
+            if oauth_users::table
+                .find(subject)
+                .first::<OAuthUserModel>(self.conn)
+                .optional()?
+                .is_none()
+            {
+                return Err(OAuthUserSessionStoreError::InvalidState(
+                    InvalidStateError::with_message(
+                        "An OAuth user for the given subject does not exist".to_string(),
+                    ),
+                ));
+            }
+
+            // Check that no other subject is already linked to the Biome user ID
+            if oauth_users::table
+                .filter(oauth_users::user_id.eq(user_id))
+                .filter(oauth_users::subject.ne(subject))
+                .first::<OAuthUserModel>(self.conn)
+                .optional()?
+                .is_some()
+            {
+                return Err(OAuthUserSessionStoreError::ConstraintViolation(
+                    ConstraintViolationError::with_violation_type(ConstraintViolationType::Unique),
+                ));
+            }
+
+            update(oauth_users::table.find(subject))
+                .set(oauth_users::user_id.eq(user_id))
+                .execute(self.conn)
+                .map(|_| ())
+                .map_err(OAuthUserSessionStoreError::from)
+        })
+    }
+}
@@ -17,7 +17,11 @@
 pub(super) mod add_session;
 pub(super) mod get_session;
 pub(super) mod get_user;
+#[cfg(feature = "biome-oauth-account-linking")]
+pub(super) mod link_user;
 pub(super) mod remove_session;
+#[cfg(feature = "biome-oauth-account-linking")]
+pub(super) mod unlink_user;
 pub(super) mod update_session;
 
 pub(super) struct OAuthUserSessionStoreOperations<'a, C> {
@@ -0,0 +1,60 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::update, prelude::*};
+
+use crate::biome::oauth::store::{
+    diesel::{models::OAuthUserModel, schema::oauth_users},
+    OAuthUser, OAuthUserSessionStoreError,
+};
+use crate::error::InvalidStateError;
+
+use super::OAuthUserSessionStoreOperations;
+
+pub trait OAuthUserSessionStoreUnlinkUser {
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError>;
+}
+
+impl<'a, C> OAuthUserSessionStoreUnlinkUser for OAuthUserSessionStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            if oauth_users::table
+                .find(subject)
+                .first::<OAuthUserModel>(self.conn)
+                .optional()?
+                .is_none()
+            {
+                return Err(OAuthUserSessionStoreError::InvalidState(
+                    InvalidStateError::with_message(
+                        "An OAuth user for the given subject does not exist".to_string(),
+                    ),
+                ));
+            }
+
+            // Assign the subject a new Biome user ID, which is no longer shared with any other
+            // login method
+            let user = OAuthUser::new(subject.to_string());
+            update(oauth_users::table.find(subject))
+                .set(oauth_users::user_id.eq(user.user_id()))
+                .execute(self.conn)
+                .map_err(OAuthUserSessionStoreError::from)?;
+
+            Ok(user)
+        })
+    }
+}
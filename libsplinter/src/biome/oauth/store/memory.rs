@@ -186,6 +186,61 @@ impl OAuthUserSessionStore for MemoryOAuthUserSessionStore {
             .cloned())
     }
 
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError> {
+        let mut internal = self.internal.lock().map_err(|_| {
+            OAuthUserSessionStoreError::Internal(InternalError::with_message(
+                "Cannot access OAuth user session store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        if !internal.users.contains_key(subject) {
+            return Err(OAuthUserSessionStoreError::InvalidState(
+                InvalidStateError::with_message(
+                    "An OAuth user for the given subject does not exist".to_string(),
+                ),
+            ));
+        }
+
+        if internal
+            .users
+            .values()
+            .any(|user| user.user_id == user_id && user.subject != subject)
+        {
+            return Err(OAuthUserSessionStoreError::ConstraintViolation(
+                ConstraintViolationError::with_violation_type(ConstraintViolationType::Unique),
+            ));
+        }
+
+        internal.users.insert(
+            subject.to_string(),
+            OAuthUser::new_with_id(subject.to_string(), user_id.to_string()),
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError> {
+        let mut internal = self.internal.lock().map_err(|_| {
+            OAuthUserSessionStoreError::Internal(InternalError::with_message(
+                "Cannot access OAuth user session store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        match internal.users.get_mut(subject) {
+            Some(user) => {
+                *user = OAuthUser::new(subject.to_string());
+                Ok(user.clone())
+            }
+            None => Err(OAuthUserSessionStoreError::InvalidState(
+                InvalidStateError::with_message(
+                    "An OAuth user for the given subject does not exist".to_string(),
+                ),
+            )),
+        }
+    }
+
     fn clone_box(&self) -> Box<dyn OAuthUserSessionStore> {
         Box::new(self.clone())
     }
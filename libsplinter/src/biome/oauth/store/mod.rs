@@ -390,6 +390,27 @@ pub trait OAuthUserSessionStore: Send + Sync {
     /// exists
     fn get_user(&self, subject: &str) -> Result<Option<OAuthUser>, OAuthUserSessionStoreError>;
 
+    /// Links the given OAuth subject identifier to an existing Biome user ID, so the subject is
+    /// treated as that user regardless of how the user logs in. Existing sessions for the subject
+    /// will be associated with the linked user.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an `InvalidState` error if the subject does not have an OAuth user entry
+    /// * Returns a `ConstraintViolation` error if another subject is already linked to the given
+    ///   Biome user ID
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn link_user(&self, subject: &str, user_id: &str) -> Result<(), OAuthUserSessionStoreError>;
+
+    /// Removes the link between the given OAuth subject identifier and its Biome user ID by
+    /// assigning the subject a new, generated Biome user ID. The new user is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidState` error if the subject does not have an OAuth user entry
+    #[cfg(feature = "biome-oauth-account-linking")]
+    fn unlink_user(&self, subject: &str) -> Result<OAuthUser, OAuthUserSessionStoreError>;
+
     /// Clone into a boxed, dynamically dispatched store
     fn clone_box(&self) -> Box<dyn OAuthUserSessionStore>;
 }
@@ -0,0 +1,332 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the `/biome/admin/users` endpoints, which allow administrators to list, disable,
+//! enable and delete Biome users, and to assign their roles.
+//!
+//! Requests must be authorized with a Cylinder JWT signed by one of the configured administrator
+//! keys.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cylinder::{jwt::JsonWebTokenParser, secp256k1::Secp256k1Context, Context};
+
+use crate::actix_web::{Error as ActixError, HttpRequest, HttpResponse};
+use crate::biome::accounts::store::{UserAccount, UserAccountStore, UserAccountStoreError};
+use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
+use crate::biome::key_management::store::KeyStore;
+use crate::biome::refresh_tokens::store::{error::RefreshTokenError, RefreshTokenStore};
+use crate::biome::rest_api::resources::admin::{ResponseUserAccount, UpdateUserAccount};
+use crate::futures::{Future, IntoFuture};
+use crate::protocol;
+use crate::rest_api::auth::{AuthorizationHeader, BearerToken};
+use crate::rest_api::{
+    into_bytes, ErrorResponse, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource,
+};
+
+type ErrorHttpResponse = Box<dyn Future<Item = HttpResponse, Error = ActixError>>;
+
+/// The stores and configuration used by the user administration endpoints
+#[derive(Clone)]
+pub struct UserAdmin {
+    /// The public keys, as hex, that are permitted to administer users
+    pub admin_keys: Arc<Vec<String>>,
+    pub account_store: Arc<dyn UserAccountStore>,
+    pub credentials_store: Arc<dyn CredentialsStore>,
+    pub key_store: Arc<dyn KeyStore>,
+    pub refresh_token_store: Arc<dyn RefreshTokenStore>,
+}
+
+/// Defines a REST endpoint for listing all users along with their account state
+pub fn make_admin_users_route(user_admin: UserAdmin) -> Resource {
+    Resource::build("/biome/admin/users")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_ADMIN_USERS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |request, _| {
+            if let Err(response) = authorize_admin(&request, &user_admin.admin_keys) {
+                return response;
+            }
+
+            Box::new(list_users(&user_admin).into_future())
+        })
+}
+
+/// Defines a REST endpoint for updating and deleting a single user
+pub fn make_admin_user_route(user_admin: UserAdmin) -> Resource {
+    Resource::build("/biome/admin/users/{id}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_ADMIN_USERS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Patch, handle_patch(user_admin.clone()))
+        .add_method(Method::Delete, handle_delete(user_admin))
+}
+
+fn list_users(user_admin: &UserAdmin) -> HttpResponse {
+    let usernames = match user_admin.credentials_store.list_usernames() {
+        Ok(usernames) => usernames,
+        Err(err) => {
+            error!("Failed to list users: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+
+    let mut accounts = match user_admin.account_store.list_accounts() {
+        Ok(accounts) => accounts
+            .into_iter()
+            .map(|account| (account.user_id().to_string(), account))
+            .collect::<HashMap<_, _>>(),
+        Err(err) => {
+            error!("Failed to list user accounts: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+
+    let accounts = usernames
+        .iter()
+        .map(|username| {
+            let account = accounts
+                .remove(&username.user_id)
+                .unwrap_or_else(|| UserAccount::new(&username.user_id, false, vec![]));
+            (username, account)
+        })
+        .collect::<Vec<_>>();
+
+    HttpResponse::Ok().json(json!({
+        "data": accounts
+            .iter()
+            .map(|(username, account)| ResponseUserAccount::new(&username.username, account))
+            .collect::<Vec<_>>()
+    }))
+}
+
+fn handle_patch(user_admin: UserAdmin) -> HandlerFunction {
+    Box::new(move |request, payload| {
+        if let Err(response) = authorize_admin(&request, &user_admin.admin_keys) {
+            return response;
+        }
+
+        let user_id = match request.match_info().get("id") {
+            Some(user_id) => user_id.to_string(),
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Failed to parse user id"))
+                        .into_future(),
+                )
+            }
+        };
+
+        let user_admin = user_admin.clone();
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let update = match serde_json::from_slice::<UpdateUserAccount>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            update_user(&user_admin, &user_id, update).into_future()
+        }))
+    })
+}
+
+fn update_user(user_admin: &UserAdmin, user_id: &str, update: UpdateUserAccount) -> HttpResponse {
+    let username = match fetch_username(user_admin, user_id) {
+        Ok(username) => username,
+        Err(response) => return response,
+    };
+
+    if let Some(roles) = update.roles {
+        match user_admin.account_store.set_roles(user_id, roles) {
+            Ok(()) => (),
+            Err(UserAccountStoreError::InvalidArgument(err)) => {
+                return HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request(&err.to_string()))
+            }
+            Err(err) => {
+                error!("Failed to set roles of user {}: {}", user_id, err);
+                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+            }
+        }
+    }
+
+    if let Some(disabled) = update.disabled {
+        if let Err(err) = user_admin.account_store.set_disabled(user_id, disabled) {
+            error!("Failed to update user {}: {}", user_id, err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+
+        // A disabled user may not refresh their access token; the current access token is valid
+        // until it expires.
+        if disabled {
+            if let Err(response) = remove_refresh_token(user_admin, user_id) {
+                return response;
+            }
+        }
+    }
+
+    match user_admin.account_store.get_account(user_id) {
+        Ok(account) => HttpResponse::Ok().json(json!({
+            "message": "User updated successfully",
+            "data": ResponseUserAccount::new(&username, &account),
+        })),
+        Err(err) => {
+            error!("Failed to get account of user {}: {}", user_id, err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
+
+fn handle_delete(user_admin: UserAdmin) -> HandlerFunction {
+    Box::new(move |request, _| {
+        if let Err(response) = authorize_admin(&request, &user_admin.admin_keys) {
+            return response;
+        }
+
+        let user_id = match request.match_info().get("id") {
+            Some(user_id) => user_id,
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Failed to parse user id"))
+                        .into_future(),
+                )
+            }
+        };
+
+        Box::new(delete_user(&user_admin, user_id).into_future())
+    })
+}
+
+/// Deletes the user along with the user's keys, refresh token and account state
+fn delete_user(user_admin: &UserAdmin, user_id: &str) -> HttpResponse {
+    if let Err(response) = fetch_username(user_admin, user_id) {
+        return response;
+    }
+
+    let keys = match user_admin.key_store.list_keys(Some(user_id)) {
+        Ok(keys) => keys,
+        Err(err) => {
+            error!("Failed to list keys of user {}: {}", user_id, err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+    for key in keys {
+        if let Err(err) = user_admin.key_store.remove_key(&key.public_key, user_id) {
+            error!("Failed to remove key {}: {}", key.public_key, err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    if let Err(response) = remove_refresh_token(user_admin, user_id) {
+        return response;
+    }
+
+    if let Err(err) = user_admin.account_store.remove_account(user_id) {
+        error!("Failed to remove account of user {}: {}", user_id, err);
+        return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+    }
+
+    match user_admin.credentials_store.remove_credentials(user_id) {
+        Ok(()) => HttpResponse::Ok().json(json!({ "message": "User deleted successfully" })),
+        Err(err) => {
+            error!("Failed to remove credentials of user {}: {}", user_id, err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
+
+fn fetch_username(user_admin: &UserAdmin, user_id: &str) -> Result<String, HttpResponse> {
+    match user_admin.credentials_store.fetch_username_by_id(user_id) {
+        Ok(username) => Ok(username.username),
+        Err(CredentialsStoreError::NotFoundError(_)) => Err(HttpResponse::NotFound().json(
+            ErrorResponse::not_found(&format!("User ID not found: {}", user_id)),
+        )),
+        Err(err) => {
+            error!("Failed to fetch user {}: {}", user_id, err);
+            Err(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+        }
+    }
+}
+
+fn remove_refresh_token(user_admin: &UserAdmin, user_id: &str) -> Result<(), HttpResponse> {
+    match user_admin.refresh_token_store.remove_token(user_id) {
+        Ok(()) | Err(RefreshTokenError::NotFoundError(_)) => Ok(()),
+        Err(err) => {
+            error!(
+                "Failed to remove refresh token of user {}: {}",
+                user_id, err
+            );
+            Err(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+        }
+    }
+}
+
+/// Checks that the request is authorized by a Cylinder JWT signed with an administrator key
+fn authorize_admin(request: &HttpRequest, admin_keys: &[String]) -> Result<(), ErrorHttpResponse> {
+    let token = match request
+        .headers()
+        .get("Authorization")
+        .and_then(|auth| auth.to_str().ok())
+        .map(|auth| auth.parse())
+    {
+        Some(Ok(AuthorizationHeader::Bearer(BearerToken::Cylinder(token)))) => token,
+        _ => {
+            return Err(Box::new(
+                HttpResponse::Unauthorized()
+                    .json(ErrorResponse::unauthorized())
+                    .into_future(),
+            ))
+        }
+    };
+
+    let verifier = Secp256k1Context::new().new_verifier();
+    match JsonWebTokenParser::new(&*verifier).parse(&token) {
+        Ok(parsed_token) => {
+            let public_key = parsed_token.issuer().as_hex();
+            if admin_keys
+                .iter()
+                .any(|admin_key| admin_key.eq_ignore_ascii_case(&public_key))
+            {
+                Ok(())
+            } else {
+                Err(Box::new(
+                    HttpResponse::Forbidden()
+                        .json(ErrorResponse::forbidden(
+                            "Client is not permitted to administer users",
+                        ))
+                        .into_future(),
+                ))
+            }
+        }
+        Err(err) => {
+            debug!("Invalid Cylinder JWT: {}", err);
+            Err(Box::new(
+                HttpResponse::Unauthorized()
+                    .json(ErrorResponse::unauthorized())
+                    .into_future(),
+            ))
+        }
+    }
+}
@@ -13,8 +13,12 @@
 // limitations under the License.
 
 use std::sync::Arc;
+#[cfg(feature = "biome-key-expiration")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::authorize::get_authorized_user;
+#[cfg(feature = "tenancy")]
+use super::tenancy;
 use crate::actix_web::HttpResponse;
 use crate::biome::key_management::{
     store::{KeyStore, KeyStoreError},
@@ -95,12 +99,35 @@ fn handle_post(
                 user.id(),
                 &new_key.display_name,
             );
+            #[cfg(feature = "biome-key-expiration")]
+            let key = match expiration_time(new_key.expires_at) {
+                Ok(expires_at) => Key { expires_at, ..key },
+                Err(response) => return response.into_future(),
+            };
             let response_key = ResponseKey::from(&key);
 
+            // Keys added by the users of a tenant are assigned to the tenant
+            #[cfg(feature = "tenancy")]
+            let tenant_scope = match tenancy::key_tenant_scope(&request, &[&key.public_key]) {
+                Ok(scope) => scope,
+                Err(response) => return response.into_future(),
+            };
+
             match key_store.add_key(key.clone()) {
-                Ok(()) => HttpResponse::Ok()
-                    .json(json!({ "message": "Key added successfully", "data": response_key }))
-                    .into_future(),
+                Ok(()) => {
+                    #[cfg(feature = "tenancy")]
+                    {
+                        if let Err(response) =
+                            tenancy::claim_keys(tenant_scope.as_ref(), &[&key.public_key])
+                        {
+                            return response.into_future();
+                        }
+                    }
+
+                    HttpResponse::Ok()
+                        .json(json!({ "message": "Key added successfully", "data": response_key }))
+                        .into_future()
+                }
                 Err(err) => {
                     debug!("Failed to add new key to database {}", err);
                     match err {
@@ -185,14 +212,38 @@ fn handle_patch(
                 }
             };
 
+            #[cfg(feature = "biome-key-expiration")]
+            let expires_at = match updated_key.expires_at.map(expiration_time).transpose() {
+                Ok(expires_at) => expires_at,
+                Err(response) => return response.into_future(),
+            };
+
             match key_store.update_key(
                 &updated_key.public_key,
                 user.id(),
                 &updated_key.new_display_name,
             ) {
-                Ok(()) => HttpResponse::Ok()
-                    .json(json!({ "message": "Key updated successfully" }))
-                    .into_future(),
+                Ok(()) => {
+                    #[cfg(feature = "biome-key-expiration")]
+                    {
+                        if let Some(expires_at) = expires_at {
+                            if let Err(err) = key_store.update_key_expiration(
+                                &updated_key.public_key,
+                                user.id(),
+                                expires_at,
+                            ) {
+                                error!("Failed to update key expiration {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        }
+                    }
+
+                    HttpResponse::Ok()
+                        .json(json!({ "message": "Key updated successfully" }))
+                        .into_future()
+                }
                 Err(err) => {
                     debug!("Failed to update key {}", err);
                     match err {
@@ -319,15 +370,24 @@ fn handle_delete(
         };
 
         match key_store.remove_key(&public_key, user.id()) {
-            Ok(key) => Box::new(
-                HttpResponse::Ok()
-                    .json(json!(
-                    {
-                        "message": "Key successfully deleted",
-                        "data": ResponseKey::from(&key)
-                    }))
-                    .into_future(),
-            ),
+            Ok(key) => {
+                #[cfg(feature = "tenancy")]
+                {
+                    if let Err(response) = tenancy::release_key(&request, &public_key) {
+                        return Box::new(response.into_future());
+                    }
+                }
+
+                Box::new(
+                    HttpResponse::Ok()
+                        .json(json!(
+                        {
+                            "message": "Key successfully deleted",
+                            "data": ResponseKey::from(&key)
+                        }))
+                        .into_future(),
+                )
+            }
             Err(err) => match err {
                 KeyStoreError::NotFoundError(msg) => {
                     debug!("Failed to delete key: {}", msg);
@@ -349,3 +409,16 @@ fn handle_delete(
         }
     })
 }
+
+/// Converts an expiration time in seconds since the Unix epoch to a `SystemTime`, returning a bad
+/// request response if the time has already passed.
+#[cfg(feature = "biome-key-expiration")]
+fn expiration_time(expires_at: Option<u64>) -> Result<Option<SystemTime>, HttpResponse> {
+    match expires_at.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)) {
+        Some(expires_at) if expires_at <= SystemTime::now() => Err(HttpResponse::BadRequest()
+            .json(ErrorResponse::bad_request(
+                "Key expiration time must be in the future",
+            ))),
+        expires_at => Ok(expires_at),
+    }
+}
@@ -20,6 +20,8 @@ use crate::futures::{Future, IntoFuture};
 use crate::protocol;
 use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
 
+#[cfg(feature = "biome-user-admin")]
+use crate::biome::accounts::store::UserAccountStore;
 use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
 use crate::biome::rest_api::resources::credentials::UsernamePassword;
 use crate::biome::rest_api::BiomeRestConfig;
@@ -37,6 +39,7 @@ pub fn make_login_route(
     refresh_token_store: Arc<dyn RefreshTokenStore>,
     rest_config: Arc<BiomeRestConfig>,
     token_issuer: Arc<AccessTokenIssuer>,
+    #[cfg(feature = "biome-user-admin")] account_store: Option<Arc<dyn UserAccountStore>>,
 ) -> Resource {
     Resource::build("/biome/login")
         .add_request_guard(ProtocolVersionRangeGuard::new(
@@ -48,6 +51,8 @@ pub fn make_login_route(
             let rest_config = rest_config.clone();
             let token_issuer = token_issuer.clone();
             let refresh_token_store = refresh_token_store.clone();
+            #[cfg(feature = "biome-user-admin")]
+            let account_store = account_store.clone();
             Box::new(into_bytes(payload).and_then(move |bytes| {
                 let username_password = match serde_json::from_slice::<UsernamePassword>(&bytes) {
                     Ok(val) => val,
@@ -89,6 +94,24 @@ pub fn make_login_route(
                 match credentials.verify_password(&username_password.hashed_password) {
                     Ok(is_valid) => {
                         if is_valid {
+                            #[cfg(feature = "biome-user-admin")]
+                            if let Some(account_store) = &account_store {
+                                match account_store.get_account(&credentials.user_id) {
+                                    Ok(account) if account.disabled() => {
+                                        return HttpResponse::Forbidden()
+                                            .json(ErrorResponse::forbidden("User is disabled"))
+                                            .into_future();
+                                    }
+                                    Ok(_) => (),
+                                    Err(err) => {
+                                        error!("Failed to get user account {}", err);
+                                        return HttpResponse::InternalServerError()
+                                            .json(ErrorResponse::internal_error())
+                                            .into_future();
+                                    }
+                                }
+                            }
+
                             let claim_builder = ClaimsBuilder::default();
                             let claim = match claim_builder
                                 .with_user_id(&credentials.user_id)
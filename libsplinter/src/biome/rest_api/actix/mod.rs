@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-user-admin")]
+pub(super) mod admin_users;
 #[cfg(any(feature = "biome-key-management", feature = "biome-credentials"))]
 pub(crate) mod authorize;
 #[cfg(feature = "biome-key-management")]
@@ -20,8 +22,12 @@ pub(super) mod key_management;
 pub(super) mod login;
 #[cfg(feature = "biome-credentials")]
 pub(super) mod logout;
+#[cfg(feature = "biome-oauth-account-linking")]
+pub(super) mod oauth_link;
 #[cfg(feature = "biome-credentials")]
 pub(super) mod register;
+#[cfg(all(feature = "biome-key-management", feature = "tenancy"))]
+mod tenancy;
 #[cfg(feature = "biome-credentials")]
 pub(super) mod token;
 #[cfg(feature = "biome-credentials")]
@@ -0,0 +1,289 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the `/biome/oauth/link` endpoint, which links the OAuth user of the request to a
+//! Biome user with credentials so both login methods resolve to the same canonical user.
+
+use std::sync::Arc;
+
+use crate::actix_web::{HttpRequest, HttpResponse};
+use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
+use crate::biome::key_management::{
+    store::{KeyStore, KeyStoreError},
+    Key,
+};
+use crate::biome::oauth::store::{
+    OAuthUserSession, OAuthUserSessionStore, OAuthUserSessionStoreError,
+};
+use crate::biome::rest_api::resources::credentials::UsernamePassword;
+use crate::futures::{Future, IntoFuture};
+use crate::protocol;
+use crate::rest_api::auth::{AuthorizationHeader, BearerToken};
+use crate::rest_api::{into_bytes, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+
+/// Defines a REST endpoint for managing the link between the requesting OAuth user and a Biome
+/// user
+///
+/// `POST` takes the username and hashed password of a Biome user, and links the OAuth user to
+/// that Biome user. Keys owned by the OAuth user are moved to the Biome user.
+///
+/// `DELETE` removes the link; the OAuth user is given a new user ID, and keys stay with the Biome
+/// user.
+pub fn make_oauth_link_route(
+    oauth_user_session_store: Box<dyn OAuthUserSessionStore>,
+    credentials_store: Arc<dyn CredentialsStore>,
+    key_store: Arc<dyn KeyStore>,
+) -> Resource {
+    let unlink_session_store = oauth_user_session_store.clone();
+    let unlink_credentials_store = credentials_store.clone();
+    Resource::build("/biome/oauth/link")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_OAUTH_LINK_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Post, move |request, payload| {
+            let oauth_user_session_store = oauth_user_session_store.clone();
+            let credentials_store = credentials_store.clone();
+            let key_store = key_store.clone();
+
+            let session = match get_session(&request, &*oauth_user_session_store) {
+                Ok(session) => session,
+                Err(response) => return response,
+            };
+
+            Box::new(into_bytes(payload).and_then(move |bytes| {
+                let username_password = match serde_json::from_slice::<UsernamePassword>(&bytes) {
+                    Ok(val) => val,
+                    Err(err) => {
+                        debug!("Error parsing payload {}", err);
+                        return HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Failed to parse payload: {}",
+                                err
+                            )))
+                            .into_future();
+                    }
+                };
+
+                link_user(
+                    &session,
+                    &username_password,
+                    &*oauth_user_session_store,
+                    &*credentials_store,
+                    &*key_store,
+                )
+                .into_future()
+            }))
+        })
+        .add_method(Method::Delete, move |request, _| {
+            let session = match get_session(&request, &*unlink_session_store) {
+                Ok(session) => session,
+                Err(response) => return response,
+            };
+
+            Box::new(
+                unlink_user(&session, &*unlink_session_store, &*unlink_credentials_store)
+                    .into_future(),
+            )
+        })
+}
+
+fn link_user(
+    session: &OAuthUserSession,
+    username_password: &UsernamePassword,
+    oauth_user_session_store: &dyn OAuthUserSessionStore,
+    credentials_store: &dyn CredentialsStore,
+    key_store: &dyn KeyStore,
+) -> HttpResponse {
+    let credentials =
+        match credentials_store.fetch_credential_by_username(&username_password.username) {
+            Ok(credentials) => credentials,
+            Err(CredentialsStoreError::NotFoundError(_)) => {
+                return HttpResponse::BadRequest().json(ErrorResponse::bad_request(&format!(
+                    "Username not found: {}",
+                    username_password.username
+                )))
+            }
+            Err(err) => {
+                error!("Failed to fetch credentials: {}", err);
+                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+            }
+        };
+
+    match credentials.verify_password(&username_password.hashed_password) {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request("Invalid password"))
+        }
+        Err(err) => {
+            error!("Failed to verify password: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    let subject = session.user().subject();
+    let current_user_id = session.user().user_id();
+
+    if current_user_id == credentials.user_id {
+        return HttpResponse::Ok().json(json!({
+            "message": "OAuth user is already linked to this user",
+            "user_id": credentials.user_id,
+        }));
+    }
+
+    // An OAuth user that already resolves to a user with credentials must be unlinked before it
+    // can be linked to another one
+    match credentials_store.fetch_credential_by_user_id(current_user_id) {
+        Ok(_) => {
+            return HttpResponse::Conflict().json(ErrorResponse::conflict(
+                "OAuth user is already linked to another user",
+            ))
+        }
+        Err(CredentialsStoreError::NotFoundError(_)) => (),
+        Err(err) => {
+            error!("Failed to fetch credentials: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    // Check that all of the OAuth user's keys can be moved before changing anything
+    let keys = match key_store.list_keys(Some(current_user_id)) {
+        Ok(keys) => keys,
+        Err(err) => {
+            error!("Failed to list keys: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+    for key in &keys {
+        match key_store.fetch_key(&key.public_key, &credentials.user_id) {
+            Ok(_) => {
+                return HttpResponse::Conflict().json(ErrorResponse::conflict(&format!(
+                    "User already has a key with public key {}",
+                    key.public_key
+                )))
+            }
+            Err(KeyStoreError::NotFoundError(_)) => (),
+            Err(err) => {
+                error!("Failed to fetch key: {}", err);
+                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+            }
+        }
+    }
+
+    match oauth_user_session_store.link_user(subject, &credentials.user_id) {
+        Ok(()) => (),
+        Err(OAuthUserSessionStoreError::ConstraintViolation(_)) => {
+            return HttpResponse::Conflict().json(ErrorResponse::conflict(
+                "User is already linked to another OAuth user",
+            ))
+        }
+        Err(err) => {
+            error!("Failed to link OAuth user: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    for key in keys {
+        let public_key = key.public_key.clone();
+        let moved_key = Key {
+            user_id: credentials.user_id.clone(),
+            ..key
+        };
+        if let Err(err) = key_store
+            .add_key(moved_key)
+            .and_then(|_| key_store.remove_key(&public_key, current_user_id))
+        {
+            error!(
+                "Failed to move key {} to linked user {}: {}",
+                public_key, credentials.user_id, err
+            );
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    HttpResponse::Ok().json(json!({
+        "message": "OAuth user linked successfully",
+        "user_id": credentials.user_id,
+    }))
+}
+
+fn unlink_user(
+    session: &OAuthUserSession,
+    oauth_user_session_store: &dyn OAuthUserSessionStore,
+    credentials_store: &dyn CredentialsStore,
+) -> HttpResponse {
+    match credentials_store.fetch_credential_by_user_id(session.user().user_id()) {
+        Ok(_) => (),
+        Err(CredentialsStoreError::NotFoundError(_)) => {
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                "OAuth user is not linked to a user",
+            ))
+        }
+        Err(err) => {
+            error!("Failed to fetch credentials: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    match oauth_user_session_store.unlink_user(session.user().subject()) {
+        Ok(user) => HttpResponse::Ok().json(json!({
+            "message": "OAuth user unlinked successfully",
+            "user_id": user.user_id(),
+        })),
+        Err(err) => {
+            error!("Failed to unlink OAuth user: {}", err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
+
+/// Returns the OAuth session for the access token in the request's `Authorization` header, or
+/// the response to return if there is no such session.
+fn get_session(
+    request: &HttpRequest,
+    oauth_user_session_store: &dyn OAuthUserSessionStore,
+) -> Result<OAuthUserSession, Box<dyn Future<Item = HttpResponse, Error = actix_web::Error>>> {
+    let access_token = match request
+        .headers()
+        .get("Authorization")
+        .and_then(|auth| auth.to_str().ok())
+        .map(|auth| auth.parse())
+    {
+        Some(Ok(AuthorizationHeader::Bearer(BearerToken::OAuth2(access_token)))) => access_token,
+        _ => {
+            return Err(Box::new(
+                HttpResponse::Unauthorized()
+                    .json(ErrorResponse::unauthorized())
+                    .into_future(),
+            ))
+        }
+    };
+
+    match oauth_user_session_store.get_session(&access_token) {
+        Ok(Some(session)) => Ok(session),
+        Ok(None) => Err(Box::new(
+            HttpResponse::Unauthorized()
+                .json(ErrorResponse::unauthorized())
+                .into_future(),
+        )),
+        Err(err) => {
+            error!("Failed to get OAuth user session: {}", err);
+            Err(Box::new(
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future(),
+            ))
+        }
+    }
+}
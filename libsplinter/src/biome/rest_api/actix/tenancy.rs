@@ -0,0 +1,97 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Assigns the keys that the users of a tenant add to Biome to the tenant.
+
+use crate::actix_web::{HttpRequest, HttpResponse};
+use crate::rest_api::ErrorResponse;
+use crate::tenant::{TenantResourceType, TenantScope};
+
+/// Returns the tenant scope of the client if it belongs to a tenant, so that the keys it adds can
+/// be assigned to the tenant.
+///
+/// Returns a `403 Forbidden` response if any of the keys is assigned to another tenant.
+pub fn key_tenant_scope(
+    request: &HttpRequest,
+    public_keys: &[&str],
+) -> Result<Option<TenantScope>, HttpResponse> {
+    let scope = match request.extensions().get::<TenantScope>() {
+        Some(scope) if scope.tenant_id().is_some() => scope.clone(),
+        _ => return Ok(None),
+    };
+
+    for public_key in public_keys {
+        match scope.may_claim(TenantResourceType::Key, public_key) {
+            Ok(true) => (),
+            Ok(false) => {
+                return Err(
+                    HttpResponse::Forbidden().json(ErrorResponse::forbidden(&format!(
+                        "Key {} belongs to another tenant",
+                        public_key
+                    ))),
+                )
+            }
+            Err(err) => {
+                error!("Failed to check the tenant of key {}: {}", public_key, err);
+                return Err(
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                );
+            }
+        }
+    }
+
+    Ok(Some(scope))
+}
+
+/// Assigns the keys to the client's tenant, if it belongs to one
+pub fn claim_keys(scope: Option<&TenantScope>, public_keys: &[&str]) -> Result<(), HttpResponse> {
+    if let Some(scope) = scope {
+        for public_key in public_keys {
+            if let Err(err) = scope.claim(TenantResourceType::Key, public_key) {
+                error!("Failed to assign key {} to tenant: {}", public_key, err);
+                return Err(
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the tenant assignment of a key that the client has removed from Biome. The assignment
+/// is kept if the key belongs to a tenant other than the client's.
+pub fn release_key(request: &HttpRequest, public_key: &str) -> Result<(), HttpResponse> {
+    let scope = match request.extensions().get::<TenantScope>() {
+        Some(scope) => scope.clone(),
+        None => return Ok(()),
+    };
+
+    let result = scope
+        .owns(TenantResourceType::Key, public_key)
+        .and_then(|owned| {
+            if owned {
+                scope.release(TenantResourceType::Key, public_key)
+            } else {
+                Ok(())
+            }
+        });
+    result.map_err(|err| {
+        error!(
+            "Failed to remove tenant assignment of key {}: {}",
+            public_key, err
+        );
+        HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+    })
+}
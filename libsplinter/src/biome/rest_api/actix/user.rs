@@ -14,9 +14,11 @@
 
 use std::sync::Arc;
 
-use crate::actix_web::HttpResponse;
-use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
+use crate::actix_web::{HttpRequest, HttpResponse};
+use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError, UsernameId};
 use crate::biome::rest_api::BiomeRestConfig;
+#[cfg(feature = "tenancy")]
+use crate::error::InternalError;
 use crate::futures::{Future, IntoFuture};
 use crate::protocol;
 use crate::rest_api::{
@@ -29,21 +31,27 @@ use crate::biome::key_management::{
     Key,
 };
 use crate::rest_api::secrets::SecretManager;
+#[cfg(feature = "tenancy")]
+use crate::tenant::{TenantResourceType, TenantScope};
 
 use crate::biome::rest_api::actix::authorize::get_authorized_user;
+#[cfg(all(feature = "biome-key-management", feature = "tenancy"))]
+use crate::biome::rest_api::actix::tenancy;
 #[cfg(feature = "biome-key-management")]
 use crate::biome::rest_api::resources::{key_management::ResponseKey, user::ModifyUser};
 
 /// Defines a REST endpoint to list users from the db
+///
+/// When tenancy is enabled, only the users of the client's tenant are listed.
 pub fn make_list_route(credentials_store: Arc<dyn CredentialsStore>) -> Resource {
     Resource::build("/biome/users")
         .add_request_guard(ProtocolVersionRangeGuard::new(
             protocol::BIOME_LIST_USERS_PROTOCOL_MIN,
             protocol::BIOME_PROTOCOL_VERSION,
         ))
-        .add_method(Method::Get, move |_, _| {
+        .add_method(Method::Get, move |request, _| {
             let credentials_store = credentials_store.clone();
-            Box::new(match credentials_store.list_usernames() {
+            Box::new(match list_users(&request, &*credentials_store) {
                 Ok(users) => HttpResponse::Ok().json(users).into_future(),
                 Err(err) => {
                     debug!("Failed to get users from the database {}", err);
@@ -55,6 +63,40 @@ pub fn make_list_route(credentials_store: Arc<dyn CredentialsStore>) -> Resource
         })
 }
 
+/// Lists the users that the client may see
+fn list_users(
+    #[allow(unused_variables)] request: &HttpRequest,
+    credentials_store: &dyn CredentialsStore,
+) -> Result<Vec<UsernameId>, CredentialsStoreError> {
+    let users = credentials_store.list_usernames()?;
+
+    #[cfg(feature = "tenancy")]
+    {
+        if let Some(scope) = request.extensions().get::<TenantScope>() {
+            let mut permitted = vec![];
+            for user in users {
+                if scope
+                    .permits(TenantResourceType::User, &user.user_id)
+                    .map_err(tenant_error)?
+                {
+                    permitted.push(user);
+                }
+            }
+            return Ok(permitted);
+        }
+    }
+
+    Ok(users)
+}
+
+#[cfg(feature = "tenancy")]
+fn tenant_error(err: InternalError) -> CredentialsStoreError {
+    CredentialsStoreError::StorageError {
+        context: "Unable to check the tenant of a user".into(),
+        source: Some(Box::new(err)),
+    }
+}
+
 #[cfg(feature = "biome-key-management")]
 /// Defines the `/biome/users/{id}` REST resource for managing users
 pub fn make_user_routes(
@@ -103,6 +145,35 @@ fn add_fetch_user_method(credentials_store: Arc<dyn CredentialsStore>) -> Handle
                     .into_future(),
             );
         };
+
+        // The users of other tenants are reported as not found
+        #[cfg(feature = "tenancy")]
+        {
+            if let Some(scope) = request.extensions().get::<TenantScope>() {
+                match scope.permits(TenantResourceType::User, &user_id) {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        return Box::new(
+                            HttpResponse::NotFound()
+                                .json(ErrorResponse::not_found(&format!(
+                                    "User ID not found: {}",
+                                    &user_id
+                                )))
+                                .into_future(),
+                        )
+                    }
+                    Err(err) => {
+                        error!("Failed to check the tenant of user {}: {}", user_id, err);
+                        return Box::new(
+                            HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future(),
+                        );
+                    }
+                }
+            }
+        }
+
         Box::new(match credentials_store.fetch_username_by_id(&user_id) {
             Ok(user) => HttpResponse::Ok().json(user).into_future(),
             Err(err) => {
@@ -212,23 +283,73 @@ fn add_modify_user_method(
                         None => credentials.password,
                     };
 
+                    // Re-encrypted keys keep the expiration and usage of the keys they replace
+                    #[cfg(feature = "biome-key-expiration")]
+                    let new_key_pairs = match key_store.list_keys(Some(user.id())) {
+                        Ok(current_keys) => new_key_pairs
+                            .into_iter()
+                            .map(|key| {
+                                match current_keys
+                                    .iter()
+                                    .find(|current| current.public_key == key.public_key)
+                                {
+                                    Some(current) => Key {
+                                        expires_at: current.expires_at,
+                                        last_used: current.last_used,
+                                        ..key
+                                    },
+                                    None => key,
+                                }
+                            })
+                            .collect::<Vec<Key>>(),
+                        Err(err) => {
+                            error!("Failed to fetch keys {}", err);
+                            return HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future();
+                        }
+                    };
+
                     let response_keys = new_key_pairs
                         .iter()
                         .map(ResponseKey::from)
                         .collect::<Vec<ResponseKey>>();
 
+                    // Keys added by the users of a tenant are assigned to the tenant
+                    #[cfg(feature = "tenancy")]
+                    let public_keys = new_key_pairs
+                        .iter()
+                        .map(|key| key.public_key.as_str())
+                        .collect::<Vec<_>>();
+                    #[cfg(feature = "tenancy")]
+                    let tenant_scope = match tenancy::key_tenant_scope(&request, &public_keys) {
+                        Ok(scope) => scope,
+                        Err(response) => return response.into_future(),
+                    };
+
                     match key_store.update_keys_and_password(
                         user.id(),
                         &new_password,
                         encryption_cost,
                         &new_key_pairs,
                     ) {
-                        Ok(()) => HttpResponse::Ok()
-                            .json(json!({
-                                "message": "Credentials and key updated successfully",
-                                "data": response_keys,
-                            }))
-                            .into_future(),
+                        Ok(()) => {
+                            #[cfg(feature = "tenancy")]
+                            {
+                                if let Err(response) =
+                                    tenancy::claim_keys(tenant_scope.as_ref(), &public_keys)
+                                {
+                                    return response.into_future();
+                                }
+                            }
+
+                            HttpResponse::Ok()
+                                .json(json!({
+                                    "message": "Credentials and key updated successfully",
+                                    "data": response_keys,
+                                }))
+                                .into_future()
+                        }
                         Err(err) => match err {
                             KeyStoreError::DuplicateKeyError(msg) => HttpResponse::BadRequest()
                                 .json(ErrorResponse::bad_request(&msg))
@@ -270,9 +391,25 @@ fn add_delete_user_method(
         };
 
         Box::new(match credentials_store.remove_credentials(user.id()) {
-            Ok(()) => HttpResponse::Ok()
-                .json(json!({ "message": "User deleted sucessfully" }))
-                .into_future(),
+            Ok(()) => {
+                #[cfg(feature = "tenancy")]
+                {
+                    if let Some(scope) = request.extensions().get::<TenantScope>() {
+                        if let Err(err) = scope.release(TenantResourceType::User, user.id()) {
+                            error!("Failed to remove tenant assignment of user: {}", err);
+                            return Box::new(
+                                HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future(),
+                            );
+                        }
+                    }
+                }
+
+                HttpResponse::Ok()
+                    .json(json!({ "message": "User deleted sucessfully" }))
+                    .into_future()
+            }
             Err(err) => match err {
                 CredentialsStoreError::NotFoundError(msg) => {
                     debug!("User not found: {}", msg);
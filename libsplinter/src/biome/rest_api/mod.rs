@@ -50,8 +50,12 @@ use crate::rest_api::secrets::SecretManager;
 pub use config::{BiomeRestConfig, BiomeRestConfigBuilder};
 pub use error::BiomeRestResourceManagerBuilderError;
 
+#[cfg(all(feature = "biome-user-admin", feature = "rest-api-actix"))]
+use self::actix::admin_users::{make_admin_user_route, make_admin_users_route, UserAdmin};
 #[cfg(all(feature = "rest-api-actix", feature = "biome-credentials"))]
 use self::actix::logout::make_logout_route;
+#[cfg(all(feature = "biome-oauth-account-linking", feature = "rest-api-actix"))]
+use self::actix::oauth_link::make_oauth_link_route;
 #[cfg(all(feature = "biome-credentials", feature = "rest-api-actix"))]
 use self::actix::register::make_register_route;
 #[cfg(all(feature = "biome-credentials", feature = "rest-api-actix"))]
@@ -66,8 +70,12 @@ use self::actix::user::make_user_routes;
 use self::actix::{login::make_login_route, user::make_list_route, verify::make_verify_route};
 #[cfg(all(feature = "auth", feature = "biome-credentials"))]
 use self::auth::GetUserByBiomeAuthorization;
+#[cfg(feature = "biome-user-admin")]
+use super::accounts::store::UserAccountStore;
 #[cfg(feature = "biome-credentials")]
 use super::credentials::store::CredentialsStore;
+#[cfg(feature = "biome-oauth-account-linking")]
+use super::oauth::store::OAuthUserSessionStore;
 
 #[allow(unused_imports)]
 use crate::rest_api::sessions::AccessTokenIssuer;
@@ -107,6 +115,12 @@ pub struct BiomeRestResourceManager {
     refresh_token_store: Arc<dyn RefreshTokenStore>,
     #[cfg(feature = "biome-credentials")]
     credentials_store: Arc<dyn CredentialsStore>,
+    #[cfg(feature = "biome-oauth-account-linking")]
+    oauth_user_session_store: Option<Box<dyn OAuthUserSessionStore>>,
+    #[cfg(feature = "biome-user-admin")]
+    user_account_store: Option<Arc<dyn UserAccountStore>>,
+    #[cfg(feature = "biome-user-admin")]
+    admin_keys: Arc<Vec<String>>,
 }
 
 impl BiomeRestResourceManager {
@@ -165,6 +179,8 @@ impl RestResourceProvider for BiomeRestResourceManager {
                     self.token_secret_manager.clone(),
                     self.refresh_token_secret_manager.clone(),
                 )),
+                #[cfg(feature = "biome-user-admin")]
+                self.user_account_store.clone(),
             ));
             resources.push(make_token_route(
                 self.refresh_token_store.clone(),
@@ -201,6 +217,32 @@ impl RestResourceProvider for BiomeRestResourceManager {
                 self.token_secret_manager.clone(),
             ));
         }
+
+        #[cfg(all(feature = "biome-user-admin", feature = "rest-api-actix"))]
+        {
+            if let Some(user_account_store) = &self.user_account_store {
+                let user_admin = UserAdmin {
+                    admin_keys: self.admin_keys.clone(),
+                    account_store: user_account_store.clone(),
+                    credentials_store: self.credentials_store.clone(),
+                    key_store: self.key_store.clone(),
+                    refresh_token_store: self.refresh_token_store.clone(),
+                };
+                resources.push(make_admin_users_route(user_admin.clone()));
+                resources.push(make_admin_user_route(user_admin));
+            }
+        }
+
+        #[cfg(all(feature = "biome-oauth-account-linking", feature = "rest-api-actix"))]
+        {
+            if let Some(oauth_user_session_store) = &self.oauth_user_session_store {
+                resources.push(make_oauth_link_route(
+                    oauth_user_session_store.clone(),
+                    self.credentials_store.clone(),
+                    self.key_store.clone(),
+                ));
+            }
+        }
         resources
     }
 }
@@ -218,6 +260,12 @@ pub struct BiomeRestResourceManagerBuilder {
     refresh_token_store: Option<Arc<dyn RefreshTokenStore>>,
     #[cfg(feature = "biome-credentials")]
     credentials_store: Option<Arc<dyn CredentialsStore>>,
+    #[cfg(feature = "biome-oauth-account-linking")]
+    oauth_user_session_store: Option<Box<dyn OAuthUserSessionStore>>,
+    #[cfg(feature = "biome-user-admin")]
+    user_account_store: Option<Arc<dyn UserAccountStore>>,
+    #[cfg(feature = "biome-user-admin")]
+    admin_keys: Vec<String>,
 }
 
 impl BiomeRestResourceManagerBuilder {
@@ -304,6 +352,48 @@ impl BiomeRestResourceManagerBuilder {
         self
     }
 
+    /// Sets the OAuth user session store for the BiomeRestResourceManager. If set, the
+    /// `/biome/oauth/link` endpoint is provided for linking OAuth users to Biome users.
+    ///
+    /// # Arguments
+    ///
+    /// * `store`: the OAuthUserSessionStore that correlates OAuth subjects with Biome users
+    #[cfg(feature = "biome-oauth-account-linking")]
+    pub fn with_oauth_user_session_store(
+        mut self,
+        store: Box<dyn OAuthUserSessionStore>,
+    ) -> BiomeRestResourceManagerBuilder {
+        self.oauth_user_session_store = Some(store);
+        self
+    }
+
+    /// Sets the UserAccountStore for the BiomeRestResourceManager. If set, disabled users are
+    /// not permitted to log in, and the `/biome/admin/users` endpoints are provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `store`: the UserAccountStore that tracks whether users are disabled and their roles
+    #[cfg(feature = "biome-user-admin")]
+    pub fn with_user_account_store(
+        mut self,
+        store: impl UserAccountStore + 'static,
+    ) -> BiomeRestResourceManagerBuilder {
+        self.user_account_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Sets the public keys that are permitted to use the `/biome/admin/users` endpoints
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_keys`: hex-encoded public keys; requests must be signed by one of these keys
+    ///   using a Cylinder JWT
+    #[cfg(feature = "biome-user-admin")]
+    pub fn with_admin_keys(mut self, admin_keys: Vec<String>) -> BiomeRestResourceManagerBuilder {
+        self.admin_keys = admin_keys;
+        self
+    }
+
     /// Consumes the builder and returns a BiomeRestResourceManager
     pub fn build(self) -> Result<BiomeRestResourceManager, BiomeRestResourceManagerBuilderError> {
         #[cfg(feature = "biome-key-management")]
@@ -361,6 +451,12 @@ impl BiomeRestResourceManagerBuilder {
             refresh_token_store,
             #[cfg(feature = "biome-credentials")]
             credentials_store,
+            #[cfg(feature = "biome-oauth-account-linking")]
+            oauth_user_session_store: self.oauth_user_session_store,
+            #[cfg(feature = "biome-user-admin")]
+            user_account_store: self.user_account_store,
+            #[cfg(feature = "biome-user-admin")]
+            admin_keys: Arc::new(self.admin_keys),
         })
     }
 }
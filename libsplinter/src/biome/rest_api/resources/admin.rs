@@ -0,0 +1,42 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Defines structures used in user administration.
+
+use crate::biome::accounts::store::UserAccount;
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateUserAccount {
+    pub disabled: Option<bool>,
+    pub roles: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ResponseUserAccount<'a> {
+    user_id: &'a str,
+    username: &'a str,
+    disabled: bool,
+    roles: &'a [String],
+}
+
+impl<'a> ResponseUserAccount<'a> {
+    pub fn new(username: &'a str, account: &'a UserAccount) -> Self {
+        ResponseUserAccount {
+            user_id: account.user_id(),
+            username,
+            disabled: account.disabled(),
+            roles: account.roles(),
+        }
+    }
+}
@@ -14,6 +14,12 @@
 
 //! Defines structures used in key management.
 
+#[cfg(feature = "biome-key-expiration")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "biome-key-expiration")]
+use serde::{Deserialize, Deserializer};
+
 use crate::biome::key_management::Key;
 
 #[derive(Deserialize)]
@@ -21,12 +27,30 @@ pub(crate) struct NewKey {
     pub public_key: String,
     pub encrypted_private_key: String,
     pub display_name: String,
+    /// The time after which the key expires, in seconds since the Unix epoch
+    #[cfg(feature = "biome-key-expiration")]
+    #[serde(default)]
+    pub expires_at: Option<u64>,
 }
 
 #[derive(Deserialize)]
 pub(crate) struct UpdatedKey {
     pub public_key: String,
     pub new_display_name: String,
+    /// The new expiration time of the key, in seconds since the Unix epoch. The expiration is
+    /// unchanged if the field is not present, and cleared if it is `null`.
+    #[cfg(feature = "biome-key-expiration")]
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub expires_at: Option<Option<u64>>,
+}
+
+/// Deserializes a field that is present, including one that is `null`, as `Some`
+#[cfg(feature = "biome-key-expiration")]
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Option<u64>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize)]
@@ -35,6 +59,10 @@ pub(crate) struct ResponseKey<'a> {
     user_id: &'a str,
     display_name: &'a str,
     encrypted_private_key: &'a str,
+    #[cfg(feature = "biome-key-expiration")]
+    expires_at: Option<u64>,
+    #[cfg(feature = "biome-key-expiration")]
+    last_used: Option<u64>,
 }
 
 impl<'a> From<&'a Key> for ResponseKey<'a> {
@@ -44,6 +72,44 @@ impl<'a> From<&'a Key> for ResponseKey<'a> {
             user_id: &key.user_id,
             display_name: &key.display_name,
             encrypted_private_key: &key.encrypted_private_key,
+            #[cfg(feature = "biome-key-expiration")]
+            expires_at: key.expires_at.map(to_unix_seconds),
+            #[cfg(feature = "biome-key-expiration")]
+            last_used: key.last_used.map(to_unix_seconds),
         }
     }
 }
+
+#[cfg(feature = "biome-key-expiration")]
+fn to_unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(all(test, feature = "biome-key-expiration"))]
+mod tests {
+    use super::*;
+
+    /// Verify that an `expires_at` field that is not present is left unchanged, and one that is
+    /// `null` clears the expiration.
+    #[test]
+    fn updated_key_expiration() {
+        let update: UpdatedKey =
+            serde_json::from_str(r#"{"public_key": "abcd", "new_display_name": "key"}"#)
+                .expect("Failed to parse update");
+        assert_eq!(update.expires_at, None);
+
+        let update: UpdatedKey = serde_json::from_str(
+            r#"{"public_key": "abcd", "new_display_name": "key", "expires_at": null}"#,
+        )
+        .expect("Failed to parse update");
+        assert_eq!(update.expires_at, Some(None));
+
+        let update: UpdatedKey = serde_json::from_str(
+            r#"{"public_key": "abcd", "new_display_name": "key", "expires_at": 1000}"#,
+        )
+        .expect("Failed to parse update");
+        assert_eq!(update.expires_at, Some(Some(1000)));
+    }
+}
@@ -14,6 +14,8 @@
 
 //! Provides structures for the REST resources.
 
+#[cfg(feature = "biome-user-admin")]
+pub(in crate::biome::rest_api) mod admin;
 #[cfg(feature = "biome-credentials")]
 pub(in crate::biome::rest_api) mod authorize;
 #[cfg(feature = "biome-credentials")]
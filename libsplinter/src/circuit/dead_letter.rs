@@ -0,0 +1,427 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bounded store for circuit direct messages that could not be delivered.
+//!
+//! When the `CircuitDirectMessageHandler` cannot route a message, because the circuit or
+//! recipient is unknown or the recipient's connection is unavailable, the message is added to a
+//! [`DeadLetterQueue`] along with the reason it was undeliverable. The messages can then be
+//! inspected, replayed through the circuit dispatcher once the problem has been fixed, or purged.
+//!
+//! The queue holds a fixed number of messages; when it is full the oldest message is evicted.
+//!
+//! [`DeadLetterQueue`]: struct.DeadLetterQueue.html
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use protobuf::Message;
+
+use crate::error::InternalError;
+use crate::network::dispatch::{DispatchMessageSender, PeerId};
+use crate::protos::circuit::{CircuitDirectMessage, CircuitMessageType};
+
+/// The default number of messages a `DeadLetterQueue` holds.
+pub const DEFAULT_DEAD_LETTER_CAPACITY: usize = 1000;
+
+/// The reason a message could not be delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeadLetterReason {
+    /// The circuit is not in the routing table
+    CircuitDoesNotExist,
+    /// The recipient is not in the circuit's roster
+    RecipientNotInCircuitRoster,
+    /// The recipient is in the circuit's roster but not in the service directory
+    RecipientNotInDirectory,
+    /// The recipient runs on this node but is not connected
+    RecipientNotConnected,
+    /// The message could not be sent to the recipient or the node it is connected to
+    SendFailed,
+}
+
+impl DeadLetterReason {
+    /// Returns the reason as a static string, suitable for use as a metric label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterReason::CircuitDoesNotExist => "circuit_does_not_exist",
+            DeadLetterReason::RecipientNotInCircuitRoster => "recipient_not_in_circuit_roster",
+            DeadLetterReason::RecipientNotInDirectory => "recipient_not_in_directory",
+            DeadLetterReason::RecipientNotConnected => "recipient_not_connected",
+            DeadLetterReason::SendFailed => "send_failed",
+        }
+    }
+}
+
+impl fmt::Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A message that could not be delivered
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    id: u64,
+    reason: DeadLetterReason,
+    source_peer_id: String,
+    received_at: SystemTime,
+    message: CircuitDirectMessage,
+}
+
+impl DeadLetter {
+    /// Returns the ID of the dead letter, which is unique for the life of the queue
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the reason the message could not be delivered
+    pub fn reason(&self) -> DeadLetterReason {
+        self.reason
+    }
+
+    /// Returns the peer ID of the connection the message was received on
+    pub fn source_peer_id(&self) -> &str {
+        &self.source_peer_id
+    }
+
+    /// Returns the time the message was added to the queue
+    pub fn received_at(&self) -> SystemTime {
+        self.received_at
+    }
+
+    /// Returns the circuit the message was sent on
+    pub fn circuit_id(&self) -> &str {
+        self.message.get_circuit()
+    }
+
+    /// Returns the ID of the service that sent the message
+    pub fn sender(&self) -> &str {
+        self.message.get_sender()
+    }
+
+    /// Returns the ID of the service the message was for
+    pub fn recipient(&self) -> &str {
+        self.message.get_recipient()
+    }
+
+    /// Returns the correlation ID of the message
+    pub fn correlation_id(&self) -> &str {
+        self.message.get_correlation_id()
+    }
+
+    /// Returns the payload of the message
+    pub fn payload(&self) -> &[u8] {
+        self.message.get_payload()
+    }
+}
+
+/// The counts of messages that could not be delivered, which include messages that have since
+/// been evicted, replayed or purged
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeadLetterCounts {
+    /// The number of messages that could not be delivered, by reason
+    pub by_reason: BTreeMap<DeadLetterReason, u64>,
+    /// The number of messages evicted because the queue was full
+    pub evicted: u64,
+}
+
+#[derive(Default)]
+struct DeadLetterQueueState {
+    letters: VecDeque<DeadLetter>,
+    next_id: u64,
+    counts: DeadLetterCounts,
+}
+
+/// A bounded, shared store of messages that could not be delivered.
+#[derive(Clone)]
+pub struct DeadLetterQueue {
+    capacity: usize,
+    state: Arc<Mutex<DeadLetterQueueState>>,
+}
+
+impl DeadLetterQueue {
+    /// Creates a new `DeadLetterQueue`
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of messages the queue holds
+    pub fn new(capacity: usize) -> Self {
+        DeadLetterQueue {
+            capacity,
+            state: Arc::new(Mutex::new(DeadLetterQueueState::default())),
+        }
+    }
+
+    /// Returns the maximum number of messages the queue holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds a message to the queue, evicting the oldest message if the queue is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message that could not be delivered
+    /// * `source_peer_id` - The peer ID of the connection the message was received on
+    /// * `reason` - The reason the message could not be delivered
+    pub fn add(
+        &self,
+        message: CircuitDirectMessage,
+        source_peer_id: String,
+        reason: DeadLetterReason,
+    ) -> Result<(), InternalError> {
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "splinter.circuit.undeliverable_messages",
+            1,
+            "reason" => reason.as_str()
+        );
+
+        let mut state = self.lock()?;
+
+        *state.counts.by_reason.entry(reason).or_insert(0) += 1;
+
+        if self.capacity == 0 {
+            state.counts.evicted += 1;
+            return Ok(());
+        }
+
+        while state.letters.len() >= self.capacity {
+            state.letters.pop_front();
+            state.counts.evicted += 1;
+        }
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.letters.push_back(DeadLetter {
+            id,
+            reason,
+            source_peer_id,
+            received_at: SystemTime::now(),
+            message,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the messages in the queue, oldest first
+    pub fn list(&self) -> Result<Vec<DeadLetter>, InternalError> {
+        Ok(self.lock()?.letters.iter().cloned().collect())
+    }
+
+    /// Returns the message with the given ID, if it is in the queue
+    pub fn get(&self, id: u64) -> Result<Option<DeadLetter>, InternalError> {
+        Ok(self
+            .lock()?
+            .letters
+            .iter()
+            .find(|letter| letter.id == id)
+            .cloned())
+    }
+
+    /// Returns the counts of messages that could not be delivered
+    pub fn counts(&self) -> Result<DeadLetterCounts, InternalError> {
+        Ok(self.lock()?.counts.clone())
+    }
+
+    /// Removes the message with the given ID from the queue, returning it if it was in the queue
+    pub fn remove(&self, id: u64) -> Result<Option<DeadLetter>, InternalError> {
+        let mut state = self.lock()?;
+        let index = state.letters.iter().position(|letter| letter.id == id);
+        Ok(index.and_then(|index| state.letters.remove(index)))
+    }
+
+    /// Removes all messages from the queue, returning the number of messages removed
+    pub fn purge(&self) -> Result<usize, InternalError> {
+        let mut state = self.lock()?;
+        let purged = state.letters.len();
+        state.letters.clear();
+        Ok(purged)
+    }
+
+    /// Removes the message with the given ID from the queue and dispatches it again. If the
+    /// message still cannot be delivered it is added back to the queue with a new ID.
+    ///
+    /// Returns `false` if the message was not in the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the message to replay
+    /// * `dispatch_sender` - A sender for the circuit dispatcher
+    pub fn replay(
+        &self,
+        id: u64,
+        dispatch_sender: &DispatchMessageSender<CircuitMessageType>,
+    ) -> Result<bool, InternalError> {
+        match self.remove(id)? {
+            Some(letter) => {
+                replay_letter(letter, dispatch_sender)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Removes all messages from the queue and dispatches them again, oldest first. Messages that
+    /// still cannot be delivered are added back to the queue with new IDs.
+    ///
+    /// Returns the number of messages replayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispatch_sender` - A sender for the circuit dispatcher
+    pub fn replay_all(
+        &self,
+        dispatch_sender: &DispatchMessageSender<CircuitMessageType>,
+    ) -> Result<usize, InternalError> {
+        let letters = self.lock()?.letters.drain(..).collect::<Vec<_>>();
+        let replayed = letters.len();
+        for letter in letters {
+            replay_letter(letter, dispatch_sender)?;
+        }
+        Ok(replayed)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<DeadLetterQueueState>, InternalError> {
+        self.state
+            .lock()
+            .map_err(|_| InternalError::with_message("Dead letter queue lock was poisoned".into()))
+    }
+}
+
+fn replay_letter(
+    letter: DeadLetter,
+    dispatch_sender: &DispatchMessageSender<CircuitMessageType>,
+) -> Result<(), InternalError> {
+    let message_bytes = letter
+        .message
+        .write_to_bytes()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    dispatch_sender
+        .send(
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            message_bytes,
+            PeerId::from(letter.source_peer_id),
+        )
+        .map_err(|_| {
+            InternalError::with_message(
+                "Unable to send dead letter to the circuit dispatcher".into(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::network::dispatch::dispatch_channel;
+
+    /// Verify that the queue holds at most its capacity, evicting the oldest messages, and that
+    /// the counts include the evicted messages.
+    #[test]
+    fn test_dead_letter_queue_eviction() {
+        let queue = DeadLetterQueue::new(2);
+
+        queue
+            .add(
+                create_message("1"),
+                "peer".into(),
+                DeadLetterReason::CircuitDoesNotExist,
+            )
+            .expect("Unable to add message");
+        queue
+            .add(
+                create_message("2"),
+                "peer".into(),
+                DeadLetterReason::RecipientNotConnected,
+            )
+            .expect("Unable to add message");
+        queue
+            .add(
+                create_message("3"),
+                "peer".into(),
+                DeadLetterReason::RecipientNotConnected,
+            )
+            .expect("Unable to add message");
+
+        let letters = queue.list().expect("Unable to list messages");
+        assert_eq!(
+            letters
+                .iter()
+                .map(|letter| letter.correlation_id())
+                .collect::<Vec<_>>(),
+            vec!["2", "3"]
+        );
+        assert_eq!(letters[0].id(), 1);
+
+        let counts = queue.counts().expect("Unable to get counts");
+        assert_eq!(counts.evicted, 1);
+        assert_eq!(
+            counts
+                .by_reason
+                .get(&DeadLetterReason::RecipientNotConnected),
+            Some(&2)
+        );
+        assert_eq!(
+            counts.by_reason.get(&DeadLetterReason::CircuitDoesNotExist),
+            Some(&1)
+        );
+    }
+
+    /// Verify that replaying a message removes it from the queue and that purging removes the
+    /// remaining messages.
+    #[test]
+    fn test_dead_letter_queue_replay_and_purge() {
+        let queue = DeadLetterQueue::new(10);
+        queue
+            .add(
+                create_message("1"),
+                "peer".into(),
+                DeadLetterReason::SendFailed,
+            )
+            .expect("Unable to add message");
+        queue
+            .add(
+                create_message("2"),
+                "peer".into(),
+                DeadLetterReason::SendFailed,
+            )
+            .expect("Unable to add message");
+
+        let (dispatch_sender, _dispatch_receiver) = dispatch_channel();
+
+        assert!(queue
+            .replay(0, &dispatch_sender)
+            .expect("Unable to replay message"));
+        assert!(!queue
+            .replay(0, &dispatch_sender)
+            .expect("Unable to replay message"));
+        assert!(queue.get(0).expect("Unable to get message").is_none());
+
+        assert_eq!(queue.purge().expect("Unable to purge"), 1);
+        assert!(queue.list().expect("Unable to list messages").is_empty());
+    }
+
+    fn create_message(correlation_id: &str) -> CircuitDirectMessage {
+        let mut message = CircuitDirectMessage::new();
+        message.set_circuit("alpha".into());
+        message.set_sender("abc".into());
+        message.set_recipient("def".into());
+        message.set_payload(b"test".to_vec());
+        message.set_correlation_id(correlation_id.into());
+        message
+    }
+}
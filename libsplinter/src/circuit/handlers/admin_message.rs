@@ -12,9 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "circuit-relay")]
+use std::collections::HashMap;
+#[cfg(feature = "circuit-relay")]
+use std::sync::{Arc, Mutex};
+
 use crate::circuit::handlers::create_message;
 use crate::circuit::routing::RoutingTableReader;
 use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
+#[cfg(feature = "circuit-relay")]
+use crate::peer::PeerLookup;
 use crate::protos::circuit::{
     AdminDirectMessage, CircuitError, CircuitError_Error, CircuitMessageType,
 };
@@ -26,6 +33,53 @@ const ADMIN_SERVICE_ID_PREFIX: &str = "admin::";
 pub struct AdminDirectMessageHandler {
     node_id: String,
     routing_table: Box<dyn RoutingTableReader>,
+    #[cfg(feature = "circuit-relay")]
+    relay_routing: Option<(AdminRelayRoutes, Box<dyn PeerLookup>)>,
+}
+
+/// The relays through which the admin services of nodes that are not connected peers can be
+/// reached.
+///
+/// The admin service adds the relay members of each circuit proposal for the other members of the
+/// proposal. A route is also added when a relay delivers an admin message from another node, so
+/// that the response can be sent back before the proposal is known.
+#[cfg(feature = "circuit-relay")]
+#[derive(Clone, Default)]
+pub struct AdminRelayRoutes {
+    routes: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+#[cfg(feature = "circuit-relay")]
+impl AdminRelayRoutes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds relays through which the given node can be reached
+    pub fn add_relays(&self, node_id: &str, relays: &[String]) {
+        match self.routes.lock() {
+            Ok(mut routes) => {
+                let node_relays = routes.entry(node_id.to_string()).or_insert_with(Vec::new);
+                for relay in relays {
+                    if relay != node_id && !node_relays.contains(relay) {
+                        node_relays.push(relay.to_string());
+                    }
+                }
+            }
+            Err(_) => error!("Unable to add admin relay routes: lock poisoned"),
+        }
+    }
+
+    /// Returns the relays through which the given node can be reached
+    pub fn relays(&self, node_id: &str) -> Vec<String> {
+        match self.routes.lock() {
+            Ok(routes) => routes.get(node_id).cloned().unwrap_or_default(),
+            Err(_) => {
+                error!("Unable to read admin relay routes: lock poisoned");
+                vec![]
+            }
+        }
+    }
 }
 
 impl Handler for AdminDirectMessageHandler {
@@ -80,9 +134,24 @@ impl AdminDirectMessageHandler {
         Self {
             node_id,
             routing_table,
+            #[cfg(feature = "circuit-relay")]
+            relay_routing: None,
         }
     }
 
+    /// Sends the local admin service's messages for nodes that are not connected peers through a
+    /// connected relay from the given routes. The `PeerLookup` is used to check which nodes are
+    /// connected.
+    #[cfg(feature = "circuit-relay")]
+    pub fn with_relay_routing(
+        mut self,
+        relay_routes: AdminRelayRoutes,
+        peer_lookup: Box<dyn PeerLookup>,
+    ) -> Self {
+        self.relay_routing = Some((relay_routes, peer_lookup));
+        self
+    }
+
     fn create_response(
         &self,
         msg: AdminDirectMessage,
@@ -102,7 +171,7 @@ impl AdminDirectMessageHandler {
                 ),
             )?;
             return Ok((
-                create_message(err_msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?,
+                create_message(&err_msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE),
                 context.source_peer_id().into(),
             ));
         }
@@ -117,7 +186,7 @@ impl AdminDirectMessageHandler {
                 ),
             )?;
             return Ok((
-                create_message(err_msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?,
+                create_message(&err_msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE),
                 context.source_peer_id().into(),
             ));
         }
@@ -135,16 +204,21 @@ impl AdminDirectMessageHandler {
             // If the service is on this node send message to the service, otherwise
             // send the message to the node the service is connected to
             let target_node = if node_id != self.node_id {
-                node_id
+                // Nodes that are not connected peers may be reachable through a relay
+                #[cfg(feature = "circuit-relay")]
+                let node_id = self.next_hop(node_id, context.source_peer_id());
+                node_id.to_string()
             } else {
+                #[cfg(feature = "circuit-relay")]
+                self.add_relay_route(msg_sender, context.source_peer_id());
                 // The internal admin service is at the node id with an identical name
-                recipient
+                recipient.to_string()
             };
 
             let msg_bytes = context.message_bytes().to_vec();
             let network_msg_bytes =
-                create_message(msg_bytes, CircuitMessageType::ADMIN_DIRECT_MESSAGE)?;
-            (network_msg_bytes, target_node.to_string())
+                create_message(&msg_bytes, CircuitMessageType::ADMIN_DIRECT_MESSAGE);
+            (network_msg_bytes, target_node)
         } else {
             // if the circuit does not exist, send circuit error
             let msg_bytes = create_circuit_error_msg(
@@ -154,11 +228,63 @@ impl AdminDirectMessageHandler {
             )?;
 
             let network_msg_bytes =
-                create_message(msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?;
+                create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
             (network_msg_bytes, context.source_peer_id().to_string())
         };
         Ok(response)
     }
+
+    /// Returns the node the message should be sent to in order to reach `node_id`.
+    ///
+    /// This is `node_id` itself unless it is not a connected peer, in which case the first
+    /// connected relay for the node is used. Only messages from the local admin service are
+    /// relayed, so a message takes at most one intermediate hop.
+    #[cfg(feature = "circuit-relay")]
+    fn next_hop(&self, node_id: &str, source_peer_id: &str) -> String {
+        let (relay_routes, peer_lookup) = match &self.relay_routing {
+            Some(relay_routing) => relay_routing,
+            None => return node_id.to_string(),
+        };
+
+        let is_connected = |peer_id: &str| match peer_lookup.connection_id(peer_id) {
+            Ok(connection_id) => connection_id.is_some(),
+            Err(err) => {
+                error!("Unable to look up connection for {}: {}", peer_id, err);
+                false
+            }
+        };
+
+        if source_peer_id != admin_service_id(&self.node_id) || is_connected(node_id) {
+            return node_id.to_string();
+        }
+
+        match relay_routes
+            .relays(node_id)
+            .into_iter()
+            .find(|relay| relay != &self.node_id && is_connected(relay))
+        {
+            Some(relay) => {
+                debug!(
+                    "Node {} is not connected; relaying admin message through {}",
+                    node_id, relay
+                );
+                relay
+            }
+            None => node_id.to_string(),
+        }
+    }
+
+    /// Records that the node of the sending admin service can be reached through the peer that
+    /// delivered its message, if that peer is a different node.
+    #[cfg(feature = "circuit-relay")]
+    fn add_relay_route(&self, msg_sender: &str, source_peer_id: &str) {
+        if let Some((relay_routes, _)) = &self.relay_routing {
+            let sender_node_id = &msg_sender[ADMIN_SERVICE_ID_PREFIX.len()..];
+            if sender_node_id != source_peer_id && !is_admin_service_id(source_peer_id) {
+                relay_routes.add_relays(sender_node_id, &[source_peer_id.to_string()]);
+            }
+        }
+    }
 }
 
 fn create_circuit_error_msg(
@@ -180,6 +306,11 @@ fn is_admin_service_id(service_id: &str) -> bool {
     service_id.starts_with(ADMIN_SERVICE_ID_PREFIX)
 }
 
+#[cfg(feature = "circuit-relay")]
+fn admin_service_id(node_id: &str) -> String {
+    format!("{}{}", ADMIN_SERVICE_ID_PREFIX, node_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,6 +656,94 @@ mod tests {
         )
     }
 
+    /// Send an admin message between two nodes that are never peered, each of which is connected
+    /// to a relay. Expect that the message and its response are delivered through the relay, and
+    /// that the relay route back to the sender is learned from the relayed message.
+    #[cfg(feature = "circuit-relay")]
+    #[test]
+    fn test_admin_direct_message_through_relay() {
+        let new_node = |node_id: &str, connected: &[&str], relay_routes: AdminRelayRoutes| {
+            let mock_sender = MockSender::new();
+            let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+            let reader: Box<dyn RoutingTableReader> = Box::new(RoutingTable::default());
+            let peer_lookup = MockPeerLookup(connected.iter().map(|p| p.to_string()).collect());
+            let handler = AdminDirectMessageHandler::new(node_id.into(), reader)
+                .with_relay_routing(relay_routes, Box::new(peer_lookup));
+            dispatcher.set_handler(Box::new(handler));
+            (dispatcher, mock_sender)
+        };
+
+        // The admin service of node-a has added the relay of the proposal for node-x, while
+        // node-x has not received the proposal yet
+        let routes_a = AdminRelayRoutes::new();
+        routes_a.add_relays("node-x", &["relay".to_string()]);
+        let routes_x = AdminRelayRoutes::new();
+        let node_a = new_node("node-a", &["relay"], routes_a);
+        let relay = new_node("relay", &["node-a", "node-x"], AdminRelayRoutes::new());
+        let node_x = new_node("node-x", &["relay"], routes_x.clone());
+
+        let mut direct_message = AdminDirectMessage::new();
+        direct_message.set_circuit("admin".into());
+        direct_message.set_sender("admin::node-a".into());
+        direct_message.set_recipient("admin::node-x".into());
+        direct_message.set_payload(b"request".to_vec());
+
+        // Route the request from the admin service of node-a to the admin service of node-x
+        let hops = route_admin_message(
+            &[("node-a", &node_a), ("relay", &relay), ("node-x", &node_x)],
+            "admin::node-a",
+            direct_message.write_to_bytes().unwrap(),
+        );
+        assert_eq!(vec!["relay", "node-x", "admin::node-x"], hops);
+        assert_eq!(vec!["relay".to_string()], routes_x.relays("node-a"));
+
+        // Route the response from the admin service of node-x back to node-a
+        direct_message.set_sender("admin::node-x".into());
+        direct_message.set_recipient("admin::node-a".into());
+        direct_message.set_payload(b"response".to_vec());
+
+        let hops = route_admin_message(
+            &[("node-x", &node_x), ("relay", &relay), ("node-a", &node_a)],
+            "admin::node-x",
+            direct_message.write_to_bytes().unwrap(),
+        );
+        assert_eq!(vec!["relay", "node-a", "admin::node-a"], hops);
+    }
+
+    /// Dispatches an admin message on each of the given nodes in turn, each receiving the message
+    /// from the previous one, and returns the recipient the message was sent to by each node.
+    #[cfg(feature = "circuit-relay")]
+    fn route_admin_message(
+        nodes: &[(&str, &(Dispatcher<CircuitMessageType>, MockSender))],
+        source: &str,
+        mut message: Vec<u8>,
+    ) -> Vec<String> {
+        let mut source = source.to_string();
+        let mut recipients = vec![];
+        for (node_id, (dispatcher, sender)) in nodes {
+            dispatcher
+                .dispatch(
+                    source.as_str().into(),
+                    &CircuitMessageType::ADMIN_DIRECT_MESSAGE,
+                    message,
+                )
+                .expect("Unable to dispatch message");
+            let (id, outbound) = sender.next_outbound().expect("No message was sent");
+            recipients.push(String::from(id));
+
+            let network_msg: NetworkMessage = protobuf::parse_from_bytes(&outbound).unwrap();
+            let mut circuit_msg: CircuitMessage =
+                protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+            assert_eq!(
+                CircuitMessageType::ADMIN_DIRECT_MESSAGE,
+                circuit_msg.get_message_type()
+            );
+            message = circuit_msg.take_payload();
+            source = node_id.to_string();
+        }
+        recipients
+    }
+
     fn assert_network_message<M: protobuf::Message, F: Fn(M)>(
         message: Vec<u8>,
         recipient: String,
@@ -570,4 +789,28 @@ mod tests {
             Ok(())
         }
     }
+
+    #[cfg(feature = "circuit-relay")]
+    struct MockPeerLookup(Vec<String>);
+
+    #[cfg(feature = "circuit-relay")]
+    impl PeerLookup for MockPeerLookup {
+        fn connection_id(
+            &self,
+            peer_id: &str,
+        ) -> Result<Option<String>, crate::peer::PeerLookupError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|connected| connected.as_str() == peer_id)
+                .map(|connected| format!("{}-connection", connected)))
+        }
+
+        fn peer_id(
+            &self,
+            _connection_id: &str,
+        ) -> Result<Option<String>, crate::peer::PeerLookupError> {
+            Ok(None)
+        }
+    }
 }
@@ -80,9 +80,9 @@ impl Handler for CircuitErrorHandler {
         };
 
         let network_msg_bytes = create_message(
-            context.message_bytes().to_vec(),
+            context.message_bytes(),
             CircuitMessageType::CIRCUIT_ERROR_MESSAGE,
-        )?;
+        );
 
         // forward error message
         sender
@@ -11,15 +11,36 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+#[cfg(any(feature = "circuit-intake-pause", feature = "circuit-qos"))]
+use crate::circuit::handlers::create_message;
+#[cfg(feature = "circuit-intake-pause")]
+use crate::circuit::intake::IntakePause;
+#[cfg(feature = "circuit-qos")]
+use crate::circuit::qos::QosEnforcer;
 use crate::network::dispatch::{
     DispatchError, DispatchMessageSender, Handler, MessageContext, MessageSender, PeerId,
 };
+#[cfg(any(feature = "circuit-intake-pause", feature = "circuit-qos"))]
+use crate::protos::circuit::{CircuitDirectMessage, CircuitError, CircuitError_Error};
 use crate::protos::circuit::{CircuitMessage, CircuitMessageType};
+#[cfg(feature = "circuit-qos")]
+use crate::protos::envelope::read_string_field;
 use crate::protos::network::NetworkMessageType;
 
+#[cfg(any(feature = "circuit-intake-pause", feature = "circuit-qos"))]
+use protobuf::Message;
+
+// The field number of the circuit in a CircuitDirectMessage
+#[cfg(feature = "circuit-qos")]
+const CIRCUIT_DIRECT_MESSAGE_CIRCUIT_FIELD: u32 = 1;
+
 // Implements a handler that pass messages to another dispatcher loop
 pub struct CircuitMessageHandler {
     sender: DispatchMessageSender<CircuitMessageType>,
+    #[cfg(feature = "circuit-intake-pause")]
+    intake_pause: Option<IntakePause>,
+    #[cfg(feature = "circuit-qos")]
+    qos_enforcer: Option<QosEnforcer>,
 }
 
 impl Handler for CircuitMessageHandler {
@@ -33,9 +54,9 @@ impl Handler for CircuitMessageHandler {
 
     fn handle(
         &self,
-        msg: Self::Message,
+        mut msg: Self::Message,
         context: &MessageContext<Self::Source, Self::MessageType>,
-        _: &dyn MessageSender<Self::Source>,
+        #[allow(unused_variables)] sender: &dyn MessageSender<Self::Source>,
     ) -> Result<(), DispatchError> {
         debug!(
             "Handle CircuitMessage {:?} from {} [{} byte{}]",
@@ -49,14 +70,35 @@ impl Handler for CircuitMessageHandler {
             }
         );
 
+        #[cfg(any(feature = "circuit-intake-pause", feature = "circuit-qos"))]
+        {
+            if msg.get_message_type() == CircuitMessageType::CIRCUIT_DIRECT_MESSAGE {
+                // reject direct messages while their intake is paused
+                #[cfg(feature = "circuit-intake-pause")]
+                {
+                    if let Some(error_message) = self.check_intake(msg.get_payload(), context)? {
+                        return Self::send_error(error_message, context, sender);
+                    }
+                }
+
+                // reject direct messages that would exceed the QoS limits of their circuit
+                #[cfg(feature = "circuit-qos")]
+                {
+                    if let Some(error_message) = self.check_qos(msg.get_payload(), context)? {
+                        return Self::send_error(error_message, context, sender);
+                    }
+                }
+            }
+        }
+
         self.sender
             .send(
                 msg.get_message_type(),
-                msg.get_payload().to_vec(),
+                msg.take_payload(),
                 context.source_id().clone(),
             )
-            .map_err(|_| {
-                DispatchError::NetworkSendError((context.source_peer_id().to_string(), msg.payload))
+            .map_err(|(_, payload, _)| {
+                DispatchError::NetworkSendError((context.source_peer_id().to_string(), payload))
             })?;
         Ok(())
     }
@@ -64,14 +106,139 @@ impl Handler for CircuitMessageHandler {
 
 impl CircuitMessageHandler {
     pub fn new(sender: DispatchMessageSender<CircuitMessageType>) -> Self {
-        CircuitMessageHandler { sender }
+        CircuitMessageHandler {
+            sender,
+            #[cfg(feature = "circuit-intake-pause")]
+            intake_pause: None,
+            #[cfg(feature = "circuit-qos")]
+            qos_enforcer: None,
+        }
+    }
+
+    /// Rejects direct messages with a `CircuitError` instead of queuing them while the given
+    /// `IntakePause` is paused.
+    #[cfg(feature = "circuit-intake-pause")]
+    pub fn with_intake_pause(mut self, intake_pause: IntakePause) -> Self {
+        self.intake_pause = Some(intake_pause);
+        self
+    }
+
+    /// Enforces the QoS limits of each circuit on the direct messages passed to the circuit
+    /// dispatcher. Messages that would exceed their circuit's limits are rejected with a
+    /// `CircuitError` instead of being queued.
+    ///
+    /// The same `QosEnforcer` must be given to the `CircuitDirectMessageHandler`, which releases
+    /// the queued bytes of each message it handles.
+    #[cfg(feature = "circuit-qos")]
+    pub fn with_qos_enforcer(mut self, qos_enforcer: QosEnforcer) -> Self {
+        self.qos_enforcer = Some(qos_enforcer);
+        self
+    }
+
+    /// Returns the error to send back to the sender if the intake of direct messages is paused.
+    #[cfg(feature = "circuit-intake-pause")]
+    fn check_intake(
+        &self,
+        payload: &[u8],
+        context: &MessageContext<PeerId, NetworkMessageType>,
+    ) -> Result<Option<CircuitError>, DispatchError> {
+        match &self.intake_pause {
+            Some(intake_pause) if intake_pause.is_paused() => (),
+            _ => return Ok(None),
+        }
+
+        let direct_msg: CircuitDirectMessage = protobuf::parse_from_bytes(payload)?;
+        warn!(
+            "Rejecting message from {} on circuit {} from {}: intake is paused",
+            direct_msg.get_sender(),
+            direct_msg.get_circuit(),
+            context.source_peer_id(),
+        );
+        #[cfg(feature = "metrics")]
+        metrics::counter!("splinter.circuit.intake_paused_rejected", 1);
+
+        let mut error_message = CircuitError::new();
+        error_message.set_correlation_id(direct_msg.get_correlation_id().to_string());
+        error_message.set_service_id(direct_msg.get_sender().into());
+        error_message.set_circuit_name(direct_msg.get_circuit().into());
+        error_message.set_error(CircuitError_Error::ERROR_INTAKE_PAUSED);
+        error_message
+            .set_error_message("Message intake is paused; the node is low on resources".into());
+        Ok(Some(error_message))
+    }
+
+    /// Sends the error back to the peer the rejected message was received from.
+    #[cfg(any(feature = "circuit-intake-pause", feature = "circuit-qos"))]
+    fn send_error(
+        error_message: CircuitError,
+        context: &MessageContext<PeerId, NetworkMessageType>,
+        sender: &dyn MessageSender<PeerId>,
+    ) -> Result<(), DispatchError> {
+        let msg_bytes = error_message.write_to_bytes()?;
+        let network_msg_bytes =
+            create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+        sender
+            .send(
+                context.source_peer_id().to_string().into(),
+                network_msg_bytes,
+            )
+            .map_err(|(recipient, payload)| {
+                DispatchError::NetworkSendError((recipient.into(), payload))
+            })
+    }
+
+    /// Returns the error to send back to the sender if the direct message violates the QoS limits
+    /// of its circuit.
+    #[cfg(feature = "circuit-qos")]
+    fn check_qos(
+        &self,
+        payload: &[u8],
+        context: &MessageContext<PeerId, NetworkMessageType>,
+    ) -> Result<Option<CircuitError>, DispatchError> {
+        let qos_enforcer = match &self.qos_enforcer {
+            Some(qos_enforcer) => qos_enforcer,
+            None => return Ok(None),
+        };
+
+        // Only the circuit is read here; the rest of the message is parsed by the circuit
+        // dispatcher
+        let circuit_name = match read_string_field(payload, CIRCUIT_DIRECT_MESSAGE_CIRCUIT_FIELD)? {
+            Some(circuit_name) => circuit_name,
+            None => return Ok(None),
+        };
+
+        let violation = match qos_enforcer
+            .admit(circuit_name, payload.len())
+            .map_err(|err| DispatchError::HandleError(err.to_string()))?
+        {
+            Some(violation) => violation,
+            None => return Ok(None),
+        };
+
+        let direct_msg: CircuitDirectMessage = protobuf::parse_from_bytes(payload)?;
+        warn!(
+            "Rejecting message from {} on circuit {} from {}: {}",
+            direct_msg.get_sender(),
+            circuit_name,
+            context.source_peer_id(),
+            violation
+        );
+
+        let mut error_message = CircuitError::new();
+        error_message.set_correlation_id(direct_msg.get_correlation_id().to_string());
+        error_message.set_service_id(direct_msg.get_sender().into());
+        error_message.set_circuit_name(circuit_name.into());
+        error_message.set_error(CircuitError_Error::ERROR_QOS_LIMIT_EXCEEDED);
+        error_message.set_error_message(format!("QoS limit exceeded: {}", violation));
+        Ok(Some(error_message))
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::sync::{Arc, RwLock};
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex, RwLock};
     use std::{thread, time};
 
     use super::*;
@@ -134,6 +301,120 @@ mod tests {
         );
     }
 
+    /// Test that direct messages are rejected with a `CircuitError` while intake is paused, and
+    /// are passed to the circuit dispatcher again once intake is resumed
+    #[cfg(feature = "circuit-intake-pause")]
+    #[test]
+    fn test_circuit_message_handler_intake_paused() {
+        use crate::protos::network::NetworkMessage;
+
+        let network_sender = MockSender::default();
+        let mut network_dispatcher = Dispatcher::new(Box::new(network_sender.clone()));
+
+        let mut circuit_dispatcher = Dispatcher::new(Box::new(network_sender.clone()));
+        let handler = DirectMessageTestHandler::default();
+        let senders = handler.senders.clone();
+        circuit_dispatcher.set_handler(Box::new(handler));
+
+        let circuit_dispatcher_loop = DispatchLoopBuilder::new()
+            .with_dispatcher(circuit_dispatcher)
+            .build()
+            .unwrap();
+        let circuit_dispatcher_message_sender = circuit_dispatcher_loop.new_dispatcher_sender();
+
+        let intake_pause = IntakePause::new();
+        let handler = CircuitMessageHandler::new(circuit_dispatcher_message_sender)
+            .with_intake_pause(intake_pause.clone());
+        network_dispatcher.set_handler(Box::new(handler));
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("abc".into());
+        direct_message.set_recipient("def".into());
+        direct_message.set_payload(b"test".to_vec());
+        direct_message.set_correlation_id("1234".into());
+        let mut circuit_msg = CircuitMessage::new();
+        circuit_msg.set_message_type(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE);
+        circuit_msg.set_payload(direct_message.write_to_bytes().unwrap());
+        let circuit_bytes = circuit_msg.write_to_bytes().unwrap();
+
+        // While intake is paused, the message is rejected
+        intake_pause.set_paused(true);
+        network_dispatcher
+            .dispatch(
+                "PEER".into(),
+                &NetworkMessageType::CIRCUIT,
+                circuit_bytes.clone(),
+            )
+            .unwrap();
+
+        let (id, message) = network_sender.next_outbound().expect("No error was sent");
+        let id: String = id.into();
+        assert_eq!(id, "PEER");
+        let network_msg: NetworkMessage = protobuf::parse_from_bytes(&message).unwrap();
+        let circuit_msg: CircuitMessage =
+            protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+        assert_eq!(
+            circuit_msg.get_message_type(),
+            CircuitMessageType::CIRCUIT_ERROR_MESSAGE
+        );
+        let error_msg: CircuitError =
+            protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap();
+        assert_eq!(
+            error_msg.get_error(),
+            CircuitError_Error::ERROR_INTAKE_PAUSED
+        );
+        assert_eq!(error_msg.get_correlation_id(), "1234");
+        assert_eq!(error_msg.get_service_id(), "abc");
+        assert_eq!(error_msg.get_circuit_name(), "alpha");
+
+        // Once intake is resumed, the message is passed to the circuit dispatcher
+        intake_pause.set_paused(false);
+        network_dispatcher
+            .dispatch("PEER".into(), &NetworkMessageType::CIRCUIT, circuit_bytes)
+            .unwrap();
+
+        let mut count = 0;
+        let ten_millis = time::Duration::from_millis(10);
+        while senders.read().unwrap().is_empty() && count < 10 {
+            thread::sleep(ten_millis);
+            count += 1;
+        }
+
+        assert_eq!(vec!["abc".to_string()], senders.read().unwrap().clone());
+        assert!(network_sender.next_outbound().is_none());
+    }
+
+    #[cfg(feature = "circuit-intake-pause")]
+    #[derive(Default)]
+    struct DirectMessageTestHandler {
+        senders: Arc<RwLock<Vec<String>>>,
+    }
+
+    #[cfg(feature = "circuit-intake-pause")]
+    impl Handler for DirectMessageTestHandler {
+        type Source = PeerId;
+        type MessageType = CircuitMessageType;
+        type Message = CircuitDirectMessage;
+
+        fn match_type(&self) -> Self::MessageType {
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE
+        }
+
+        fn handle(
+            &self,
+            message: Self::Message,
+            _message_context: &MessageContext<Self::Source, Self::MessageType>,
+            _: &dyn MessageSender<Self::Source>,
+        ) -> Result<(), DispatchError> {
+            self.senders
+                .write()
+                .unwrap()
+                .push(message.get_sender().to_string());
+            Ok(())
+        }
+    }
+
     #[derive(Default)]
     struct ServiceConnectedTestHandler {
         echos: Arc<RwLock<Vec<String>>>,
@@ -163,10 +444,24 @@ mod tests {
     }
 
     #[derive(Clone, Default)]
-    struct MockSender {}
+    struct MockSender {
+        outbound: Arc<Mutex<VecDeque<(PeerId, Vec<u8>)>>>,
+    }
+
+    #[cfg(feature = "circuit-intake-pause")]
+    impl MockSender {
+        fn next_outbound(&self) -> Option<(PeerId, Vec<u8>)> {
+            self.outbound.lock().expect("lock was poisoned").pop_front()
+        }
+    }
 
     impl MessageSender<PeerId> for MockSender {
-        fn send(&self, _id: PeerId, _message: Vec<u8>) -> Result<(), (PeerId, Vec<u8>)> {
+        fn send(&self, id: PeerId, message: Vec<u8>) -> Result<(), (PeerId, Vec<u8>)> {
+            self.outbound
+                .lock()
+                .expect("lock was poisoned")
+                .push_back((id, message));
+
             Ok(())
         }
     }
@@ -12,9 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "circuit-dead-letter")]
+use crate::circuit::dead_letter::{DeadLetterQueue, DeadLetterReason};
 use crate::circuit::handlers::create_message;
+#[cfg(feature = "circuit-qos")]
+use crate::circuit::qos::QosEnforcer;
+#[cfg(feature = "circuit-replay-protection")]
+use crate::circuit::replay::ReplayWindow;
+#[cfg(any(feature = "circuit-observers", feature = "circuit-relay"))]
+use crate::circuit::routing::Circuit;
 use crate::circuit::routing::{RoutingTableReader, ServiceId};
+#[cfg(feature = "circuit-payload-schema")]
+use crate::circuit::schema::PayloadValidator;
 use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
+#[cfg(feature = "circuit-relay")]
+use crate::peer::PeerLookup;
 use crate::protos::circuit::{
     CircuitDirectMessage, CircuitError, CircuitError_Error, CircuitMessageType,
 };
@@ -25,6 +37,16 @@ use protobuf::Message;
 pub struct CircuitDirectMessageHandler {
     node_id: String,
     routing_table: Box<dyn RoutingTableReader>,
+    #[cfg(feature = "circuit-dead-letter")]
+    dead_letters: Option<DeadLetterQueue>,
+    #[cfg(feature = "circuit-replay-protection")]
+    replay_window: Option<ReplayWindow>,
+    #[cfg(feature = "circuit-relay")]
+    peer_lookup: Option<Box<dyn PeerLookup>>,
+    #[cfg(feature = "circuit-payload-schema")]
+    payload_validator: Option<PayloadValidator>,
+    #[cfg(feature = "circuit-qos")]
+    qos_enforcer: Option<QosEnforcer>,
 }
 
 impl Handler for CircuitDirectMessageHandler {
@@ -60,6 +82,58 @@ impl Handler for CircuitDirectMessageHandler {
             }
         );
 
+        // the message has left the circuit dispatcher's queue
+        #[cfg(feature = "circuit-qos")]
+        {
+            if let Some(qos_enforcer) = &self.qos_enforcer {
+                qos_enforcer
+                    .release(msg.get_circuit(), context.message_bytes().len())
+                    .map_err(|err| DispatchError::HandleError(err.to_string()))?;
+            }
+        }
+
+        // drop messages that have already been delivered
+        #[cfg(feature = "circuit-replay-protection")]
+        {
+            if let Some(replay_window) = &self.replay_window {
+                let replay_check = replay_window
+                    .check(
+                        context.source_peer_id(),
+                        msg.get_sender(),
+                        msg.get_sequence_number(),
+                    )
+                    .map_err(|err| DispatchError::HandleError(err.to_string()))?;
+                if replay_check.is_replay() {
+                    warn!(
+                        "Dropping {} message {} from {} on circuit {}",
+                        replay_check,
+                        msg.get_message_id(),
+                        context.source_peer_id(),
+                        msg.get_circuit()
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        // reject payloads from local services that do not match the recipient's schema
+        #[cfg(feature = "circuit-payload-schema")]
+        {
+            if let Some(error_message) = self.check_payload(&msg, context)? {
+                let msg_bytes = error_message.write_to_bytes()?;
+                let network_msg_bytes =
+                    create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                return sender
+                    .send(
+                        context.source_peer_id().to_string().into(),
+                        network_msg_bytes,
+                    )
+                    .map_err(|(recipient, payload)| {
+                        DispatchError::NetworkSendError((recipient.into(), payload))
+                    });
+            }
+        }
+
         let circuit_name = msg.get_circuit();
         let msg_sender = msg.get_sender();
         let recipient = msg.get_recipient();
@@ -67,13 +141,31 @@ impl Handler for CircuitDirectMessageHandler {
 
         // msg bytes will either be message bytes of a direct message or an error message
         // the msg_recipient is either the service/node id to send the message to or is the
-        // peer_id to send back the error message
-        let (msg_bytes, msg_recipient) = {
+        // peer_id to send back the error message; forwarded is true for a direct message
+        let (msg_bytes, msg_recipient, forwarded) = {
             if let Some(circuit) = self
                 .routing_table
                 .get_circuit(circuit_name)
                 .map_err(|err| DispatchError::HandleError(err.to_string()))?
             {
+                // services on observer members may only receive messages on the circuit
+                #[cfg(feature = "circuit-observers")]
+                {
+                    if let Some(error_message) = Self::check_observer_sender(&circuit, &msg) {
+                        let msg_bytes = error_message.write_to_bytes()?;
+                        let network_msg_bytes =
+                            create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                        return sender
+                            .send(
+                                context.source_peer_id().to_string().into(),
+                                network_msg_bytes,
+                            )
+                            .map_err(|(recipient, payload)| {
+                                DispatchError::NetworkSendError((recipient.into(), payload))
+                            });
+                    }
+                }
+
                 // Check if the message sender is allowed on the circuit
                 // if the sender is not allowed on the circuit
                 if !circuit
@@ -93,8 +185,12 @@ impl Handler for CircuitDirectMessageHandler {
 
                     let msg_bytes = error_message.write_to_bytes()?;
                     let network_msg_bytes =
-                        create_message(msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?;
-                    (network_msg_bytes, context.source_peer_id().to_string())
+                        create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                    (
+                        network_msg_bytes,
+                        context.source_peer_id().to_string(),
+                        false,
+                    )
                 } else if circuit
                     .roster()
                     .iter()
@@ -107,31 +203,39 @@ impl Handler for CircuitDirectMessageHandler {
                         .map_err(|err| DispatchError::HandleError(err.to_string()))?
                     {
                         let node_id = service.node_id().to_string();
+                        // If the node cannot be reached directly, the message may be sent through
+                        // one of the circuit's relay members instead
+                        #[cfg(feature = "circuit-relay")]
+                        let node_id = self.next_hop(&circuit, node_id, context.source_peer_id());
                         // If the service is on this node send message to the service, otherwise
                         // send the message to the node the service is connected to
                         if node_id != self.node_id {
-                            let msg_bytes = context.message_bytes().to_vec();
                             let network_msg_bytes = create_message(
-                                msg_bytes,
+                                context.message_bytes(),
                                 CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
-                            )?;
-                            (network_msg_bytes, node_id)
+                            );
+                            (network_msg_bytes, node_id, true)
                         } else {
-                            let msg_bytes = context.message_bytes().to_vec();
                             let network_msg_bytes = create_message(
-                                msg_bytes,
+                                context.message_bytes(),
                                 CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
-                            )?;
+                            );
                             let peer_id = match service.peer_id() {
                                 Some(peer_id) => peer_id.clone(),
                                 None => {
                                     // This should never happen, as a peer id will always
                                     // be set on a service that is connected to the local node.
                                     warn!("No peer id for service:{} ", service.service_id());
+                                    #[cfg(feature = "circuit-dead-letter")]
+                                    self.add_dead_letter(
+                                        msg,
+                                        context,
+                                        DeadLetterReason::RecipientNotConnected,
+                                    );
                                     return Ok(());
                                 }
                             };
-                            (network_msg_bytes, peer_id)
+                            (network_msg_bytes, peer_id, true)
                         }
                     } else {
                         // This should not happen as every service should be added on circuit
@@ -149,8 +253,18 @@ impl Handler for CircuitDirectMessageHandler {
 
                         let msg_bytes = error_message.write_to_bytes()?;
                         let network_msg_bytes =
-                            create_message(msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?;
-                        (network_msg_bytes, context.source_peer_id().to_string())
+                            create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                        #[cfg(feature = "circuit-dead-letter")]
+                        self.add_dead_letter(
+                            msg.clone(),
+                            context,
+                            DeadLetterReason::RecipientNotInDirectory,
+                        );
+                        (
+                            network_msg_bytes,
+                            context.source_peer_id().to_string(),
+                            false,
+                        )
                     }
                 } else {
                     // if the recipient is not allowed on the circuit, send circuit error
@@ -167,8 +281,18 @@ impl Handler for CircuitDirectMessageHandler {
 
                     let msg_bytes = error_message.write_to_bytes()?;
                     let network_msg_bytes =
-                        create_message(msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?;
-                    (network_msg_bytes, context.source_peer_id().to_string())
+                        create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                    #[cfg(feature = "circuit-dead-letter")]
+                    self.add_dead_letter(
+                        msg.clone(),
+                        context,
+                        DeadLetterReason::RecipientNotInCircuitRoster,
+                    );
+                    (
+                        network_msg_bytes,
+                        context.source_peer_id().to_string(),
+                        false,
+                    )
                 }
             } else {
                 // if the circuit does not exist, send circuit error
@@ -182,17 +306,39 @@ impl Handler for CircuitDirectMessageHandler {
 
                 let msg_bytes = error_message.write_to_bytes()?;
                 let network_msg_bytes =
-                    create_message(msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE)?;
-                (network_msg_bytes, context.source_peer_id().to_string())
+                    create_message(&msg_bytes, CircuitMessageType::CIRCUIT_ERROR_MESSAGE);
+                #[cfg(feature = "circuit-dead-letter")]
+                self.add_dead_letter(msg.clone(), context, DeadLetterReason::CircuitDoesNotExist);
+                (
+                    network_msg_bytes,
+                    context.source_peer_id().to_string(),
+                    false,
+                )
             }
         };
 
         // either forward the direct message or send back an error message.
-        sender
-            .send(msg_recipient.into(), msg_bytes)
-            .map_err(|(recipient, payload)| {
-                DispatchError::NetworkSendError((recipient.into(), payload))
-            })?;
+        if let Err((recipient, payload)) = sender.send(msg_recipient.into(), msg_bytes) {
+            if forwarded {
+                #[cfg(feature = "circuit-dead-letter")]
+                self.add_dead_letter(msg, context, DeadLetterReason::SendFailed);
+            }
+            return Err(DispatchError::NetworkSendError((recipient.into(), payload)));
+        }
+
+        #[cfg(feature = "circuit-replay-protection")]
+        {
+            if let (true, Some(replay_window)) = (forwarded, &self.replay_window) {
+                replay_window
+                    .record(
+                        context.source_peer_id(),
+                        msg.get_sender(),
+                        msg.get_sequence_number(),
+                    )
+                    .map_err(|err| DispatchError::HandleError(err.to_string()))?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -202,6 +348,228 @@ impl CircuitDirectMessageHandler {
         CircuitDirectMessageHandler {
             node_id,
             routing_table,
+            #[cfg(feature = "circuit-dead-letter")]
+            dead_letters: None,
+            #[cfg(feature = "circuit-replay-protection")]
+            replay_window: None,
+            #[cfg(feature = "circuit-relay")]
+            peer_lookup: None,
+            #[cfg(feature = "circuit-payload-schema")]
+            payload_validator: None,
+            #[cfg(feature = "circuit-qos")]
+            qos_enforcer: None,
+        }
+    }
+
+    /// Forwards messages for nodes that are not connected peers through one of the circuit's
+    /// relay members. The `PeerLookup` is used to check which nodes are connected.
+    #[cfg(feature = "circuit-relay")]
+    pub fn with_relay_routing(mut self, peer_lookup: Box<dyn PeerLookup>) -> Self {
+        self.peer_lookup = Some(peer_lookup);
+        self
+    }
+
+    /// Validates the payloads sent by services connected to this node against the payload schema
+    /// of the recipient service, if it has one. Messages that do not conform are rejected with a
+    /// `CircuitError` instead of being forwarded.
+    #[cfg(feature = "circuit-payload-schema")]
+    pub fn with_payload_validator(mut self, payload_validator: PayloadValidator) -> Self {
+        self.payload_validator = Some(payload_validator);
+        self
+    }
+
+    /// Releases the queued bytes of each message handled from the QoS limits of its circuit. This
+    /// must be the same `QosEnforcer` given to the `CircuitMessageHandler` that admits messages to
+    /// the circuit dispatcher.
+    #[cfg(feature = "circuit-qos")]
+    pub fn with_qos_enforcer(mut self, qos_enforcer: QosEnforcer) -> Self {
+        self.qos_enforcer = Some(qos_enforcer);
+        self
+    }
+
+    /// Returns the error to send back to the sender if the message's payload does not conform to
+    /// the recipient's schema.
+    ///
+    /// Only messages from services connected to this node are checked; messages received from
+    /// other nodes were checked by the node the sender is connected to. Messages for unknown
+    /// circuits or services are left for the normal routing checks to reject.
+    #[cfg(feature = "circuit-payload-schema")]
+    fn check_payload(
+        &self,
+        msg: &CircuitDirectMessage,
+        context: &MessageContext<PeerId, CircuitMessageType>,
+    ) -> Result<Option<CircuitError>, DispatchError> {
+        let payload_validator = match &self.payload_validator {
+            Some(payload_validator) => payload_validator,
+            None => return Ok(None),
+        };
+
+        let sender_id = ServiceId::new(msg.get_circuit().into(), msg.get_sender().into());
+        let is_local_sender = self
+            .routing_table
+            .get_service(&sender_id)
+            .map_err(|err| DispatchError::HandleError(err.to_string()))?
+            .map(|service| {
+                service.node_id() == self.node_id
+                    && service.peer_id().as_deref() == Some(context.source_peer_id())
+            })
+            .unwrap_or(false);
+        if !is_local_sender {
+            return Ok(None);
+        }
+
+        let recipient_id = ServiceId::new(msg.get_circuit().into(), msg.get_recipient().into());
+        let recipient = match self
+            .routing_table
+            .get_service(&recipient_id)
+            .map_err(|err| DispatchError::HandleError(err.to_string()))?
+        {
+            Some(recipient) => recipient,
+            None => return Ok(None),
+        };
+
+        match payload_validator
+            .validate(&recipient, msg.get_payload())
+            .map_err(|err| DispatchError::HandleError(err.to_string()))?
+        {
+            Ok(()) => Ok(None),
+            Err(err) => {
+                warn!(
+                    "Rejecting message from {} to {} on circuit {}: {}",
+                    msg.get_sender(),
+                    msg.get_recipient(),
+                    msg.get_circuit(),
+                    err.message()
+                );
+                #[cfg(feature = "metrics")]
+                metrics::counter!("splinter.circuit.payload_rejected", 1);
+
+                let mut error_message = CircuitError::new();
+                error_message.set_correlation_id(msg.get_correlation_id().into());
+                error_message.set_service_id(msg.get_sender().into());
+                error_message.set_circuit_name(msg.get_circuit().into());
+                error_message.set_error(CircuitError_Error::ERROR_INVALID_PAYLOAD);
+                error_message.set_error_message(format!("Invalid payload: {}", err.message()));
+                Ok(Some(error_message))
+            }
+        }
+    }
+
+    /// Returns the error to send back to the sender if the sender is a service on one of the
+    /// circuit's observer members.
+    ///
+    /// Observers hold a copy of the circuit's committed state but take no part in the circuit, so
+    /// their services are not allowed to send messages to other services.
+    #[cfg(feature = "circuit-observers")]
+    fn check_observer_sender(
+        circuit: &Circuit,
+        msg: &CircuitDirectMessage,
+    ) -> Option<CircuitError> {
+        let sender_node = circuit
+            .roster()
+            .iter()
+            .find(|service| service.service_id() == msg.get_sender())?
+            .node_id();
+
+        if !circuit
+            .observers()
+            .iter()
+            .any(|observer| observer == sender_node)
+        {
+            return None;
+        }
+
+        warn!(
+            "Rejecting message from {} to {} on circuit {}: sender is on observer {}",
+            msg.get_sender(),
+            msg.get_recipient(),
+            msg.get_circuit(),
+            sender_node
+        );
+
+        let mut error_message = CircuitError::new();
+        error_message.set_correlation_id(msg.get_correlation_id().into());
+        error_message.set_service_id(msg.get_sender().into());
+        error_message.set_circuit_name(msg.get_circuit().into());
+        error_message.set_error(CircuitError_Error::ERROR_SENDER_IS_OBSERVER);
+        error_message.set_error_message(format!(
+            "Sender is on an observer of the circuit: {}",
+            msg.get_sender()
+        ));
+        Some(error_message)
+    }
+
+    /// Returns the node the message should be sent to in order to reach `node_id`.
+    ///
+    /// This is `node_id` itself unless it is not a connected peer, in which case the first
+    /// connected relay member of the circuit is used. Messages that were received from another
+    /// member are not relayed again, so a message takes at most one intermediate hop.
+    #[cfg(feature = "circuit-relay")]
+    fn next_hop(&self, circuit: &Circuit, node_id: String, source_peer_id: &str) -> String {
+        let peer_lookup = match &self.peer_lookup {
+            Some(peer_lookup) => peer_lookup,
+            None => return node_id,
+        };
+
+        let is_connected = |peer_id: &str| match peer_lookup.connection_id(peer_id) {
+            Ok(connection_id) => connection_id.is_some(),
+            Err(err) => {
+                error!("Unable to look up connection for {}: {}", peer_id, err);
+                false
+            }
+        };
+
+        if is_connected(node_id.as_str()) || circuit.members().iter().any(|m| m == source_peer_id) {
+            return node_id;
+        }
+
+        match circuit.relays().iter().find(|relay| {
+            relay.as_str() != node_id
+                && relay.as_str() != self.node_id
+                && is_connected(relay.as_str())
+        }) {
+            Some(relay) => {
+                debug!(
+                    "Node {} is not connected; relaying message on {} through {}",
+                    node_id,
+                    circuit.circuit_id(),
+                    relay
+                );
+                relay.to_string()
+            }
+            None => node_id,
+        }
+    }
+
+    /// Drops messages whose sequence number has already been delivered from the same peer and
+    /// sender, according to the given replay window
+    #[cfg(feature = "circuit-replay-protection")]
+    pub fn with_replay_window(mut self, replay_window: ReplayWindow) -> Self {
+        self.replay_window = Some(replay_window);
+        self
+    }
+
+    /// Adds messages that cannot be delivered to the given dead letter queue
+    #[cfg(feature = "circuit-dead-letter")]
+    pub fn with_dead_letter_queue(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    #[cfg(feature = "circuit-dead-letter")]
+    fn add_dead_letter(
+        &self,
+        msg: CircuitDirectMessage,
+        context: &MessageContext<PeerId, CircuitMessageType>,
+        reason: DeadLetterReason,
+    ) {
+        if let Some(dead_letters) = &self.dead_letters {
+            if let Err(err) = dead_letters.add(msg, context.source_peer_id().to_string(), reason) {
+                error!(
+                    "Unable to add undeliverable message to dead letters: {}",
+                    err
+                );
+            }
         }
     }
 }
@@ -592,6 +960,426 @@ mod tests {
         )
     }
 
+    // Test that a direct message for a circuit that does not exist is added to the dead letter
+    // queue, along with the reason it could not be delivered
+    #[cfg(feature = "circuit-dead-letter")]
+    #[test]
+    fn test_circuit_direct_message_handler_dead_letter() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+
+        let dead_letters = DeadLetterQueue::new(10);
+        let handler = CircuitDirectMessageHandler::new("345".to_string(), reader)
+            .with_dead_letter_queue(dead_letters.clone());
+        dispatcher.set_handler(Box::new(handler));
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("def".into());
+        direct_message.set_recipient("abc".into());
+        direct_message.set_payload(b"test".to_vec());
+        direct_message.set_correlation_id("1234".into());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "def".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+
+        let letters = dead_letters.list().expect("Unable to list dead letters");
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].reason(), DeadLetterReason::CircuitDoesNotExist);
+        assert_eq!(letters[0].source_peer_id(), "def");
+        assert_eq!(letters[0].correlation_id(), "1234");
+        assert_eq!(letters[0].payload(), b"test");
+    }
+
+    // Test that a direct message with a sequence number that has already been delivered from the
+    // same peer and sender is dropped, while the first copy is delivered, and that a message
+    // without a sequence number is delivered
+    #[cfg(feature = "circuit-replay-protection")]
+    #[test]
+    fn test_circuit_direct_message_handler_duplicate() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![],
+        );
+        service_abc.set_peer_id("abc_network".to_string());
+
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc, service_def],
+            vec!["123".into(), "345".into()],
+        );
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345],
+            )
+            .expect("Unable to add circuits");
+
+        let replay_window = ReplayWindow::new(10);
+        let handler = CircuitDirectMessageHandler::new("123".to_string(), reader)
+            .with_replay_window(replay_window.clone());
+        dispatcher.set_handler(Box::new(handler));
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("def".into());
+        direct_message.set_recipient("abc".into());
+        direct_message.set_payload(b"test".to_vec());
+        direct_message.set_message_id("msg-1".into());
+        let unsequenced_bytes = direct_message.write_to_bytes().unwrap();
+        direct_message.set_sequence_number(1);
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        for bytes in &[direct_bytes.clone(), direct_bytes, unsequenced_bytes] {
+            dispatcher
+                .dispatch(
+                    "345".into(),
+                    &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                    bytes.clone(),
+                )
+                .unwrap();
+        }
+
+        assert!(mock_sender.next_outbound().is_some());
+        assert!(mock_sender.next_outbound().is_some());
+        assert!(mock_sender.next_outbound().is_none());
+
+        let counts = replay_window
+            .duplicate_counts()
+            .expect("Unable to get duplicate counts");
+        assert_eq!(Some(&1), counts.get("345"));
+    }
+
+    // Test that a payload sent by a local service that does not conform to the recipient's payload
+    // schema is rejected with an error, while a conforming payload is forwarded to the recipient
+    #[cfg(feature = "circuit-payload-schema")]
+    #[test]
+    fn test_circuit_direct_message_handler_invalid_payload() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let mut service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![(
+                "payload_schema".to_string(),
+                r#"{"type": "object", "required": ["value"]}"#.to_string(),
+            )],
+        );
+
+        service_abc.set_peer_id("abc_network".to_string());
+        service_def.set_peer_id("def_network".to_string());
+
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc.clone(), service_def.clone()],
+            vec!["123".into(), "345".into()],
+        );
+
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345],
+            )
+            .expect("Unable to add circuits");
+
+        let handler = CircuitDirectMessageHandler::new("123".to_string(), reader)
+            .with_payload_validator(PayloadValidator::new());
+        dispatcher.set_handler(Box::new(handler));
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("abc".into());
+        direct_message.set_recipient("def".into());
+        direct_message.set_payload(br#"{"other": 1}"#.to_vec());
+        direct_message.set_correlation_id("1234".into());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "abc_network".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "abc_network",
+            CircuitMessageType::CIRCUIT_ERROR_MESSAGE,
+            |msg: CircuitError| {
+                assert_eq!(msg.get_service_id(), "abc");
+                assert_eq!(msg.get_error(), CircuitError_Error::ERROR_INVALID_PAYLOAD);
+                assert_eq!(msg.get_correlation_id(), "1234");
+            },
+        );
+
+        direct_message.set_payload(br#"{"value": 1}"#.to_vec());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "abc_network".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "345",
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            |msg: CircuitDirectMessage| {
+                assert_eq!(msg.get_payload().to_vec(), br#"{"value": 1}"#.to_vec());
+            },
+        );
+    }
+
+    // Test that a direct message for a service on a node that is not a connected peer is sent to
+    // a connected relay member of the circuit, and that a message that was received from another
+    // member is not relayed a second time
+    #[cfg(feature = "circuit-relay")]
+    #[test]
+    fn test_circuit_direct_message_handler_relay() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+        let node_678 = CircuitNode::new("678".to_string(), vec!["123.0.0.1:2".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![],
+        );
+        service_abc.set_peer_id("abc_network".to_string());
+
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc, service_def],
+            vec!["123".into(), "345".into(), "678".into()],
+        )
+        .with_relays(vec!["678".into()]);
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345, node_678],
+            )
+            .expect("Unable to add circuits");
+
+        let handler = CircuitDirectMessageHandler::new("123".to_string(), reader)
+            .with_relay_routing(Box::new(MockPeerLookup(vec!["678".to_string()])));
+        dispatcher.set_handler(Box::new(handler));
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("abc".into());
+        direct_message.set_recipient("def".into());
+        direct_message.set_payload(b"test".to_vec());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "abc_network".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes.clone(),
+            )
+            .unwrap();
+        let (id, _) = mock_sender.next_outbound().expect("No message was sent");
+        assert_eq!("678", &String::from(id));
+
+        dispatcher
+            .dispatch(
+                "678".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+        let (id, _) = mock_sender.next_outbound().expect("No message was sent");
+        assert_eq!("345", &String::from(id));
+    }
+
+    // Test that a direct message sent by a service on an observer member is rejected with a
+    // circuit error, while messages to that service are still delivered
+    #[cfg(feature = "circuit-observers")]
+    #[test]
+    fn test_circuit_direct_message_handler_observer() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![],
+        );
+        service_abc.set_peer_id("abc_network".to_string());
+
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc, service_def],
+            vec!["123".into(), "345".into()],
+        )
+        .with_observers(vec!["123".into()]);
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345],
+            )
+            .expect("Unable to add circuits");
+
+        let handler = CircuitDirectMessageHandler::new("123".to_string(), reader);
+        dispatcher.set_handler(Box::new(handler));
+
+        // a message from the observer's service is rejected
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("abc".into());
+        direct_message.set_recipient("def".into());
+        direct_message.set_payload(b"test".to_vec());
+        direct_message.set_correlation_id("1234".into());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "abc_network".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "abc_network",
+            CircuitMessageType::CIRCUIT_ERROR_MESSAGE,
+            |msg: CircuitError| {
+                assert_eq!(msg.get_service_id(), "abc");
+                assert_eq!(
+                    msg.get_error(),
+                    CircuitError_Error::ERROR_SENDER_IS_OBSERVER
+                );
+                assert_eq!(msg.get_correlation_id(), "1234");
+            },
+        );
+
+        // a message to the observer's service is delivered
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("def".into());
+        direct_message.set_recipient("abc".into());
+        direct_message.set_payload(b"test".to_vec());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "345".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+        let (id, _) = mock_sender.next_outbound().expect("No message was sent");
+        assert_eq!("abc_network", &String::from(id));
+    }
+
+    #[cfg(feature = "circuit-relay")]
+    struct MockPeerLookup(Vec<String>);
+
+    #[cfg(feature = "circuit-relay")]
+    impl PeerLookup for MockPeerLookup {
+        fn connection_id(
+            &self,
+            peer_id: &str,
+        ) -> Result<Option<String>, crate::peer::PeerLookupError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|connected| connected.as_str() == peer_id)
+                .map(|connected| format!("{}-connection", connected)))
+        }
+
+        fn peer_id(
+            &self,
+            _connection_id: &str,
+        ) -> Result<Option<String>, crate::peer::PeerLookupError> {
+            Ok(None)
+        }
+    }
+
     fn assert_network_message<M: protobuf::Message, F: Fn(M)>(
         message: Vec<u8>,
         recipient: String,
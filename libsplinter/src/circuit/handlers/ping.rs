@@ -0,0 +1,455 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use protobuf::Message;
+
+use crate::circuit::handlers::create_message;
+use crate::circuit::ping::PendingPings;
+use crate::circuit::routing::{RoutingTableReader, ServiceId};
+use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
+use crate::protos::circuit::{
+    CircuitMessageType, CircuitPingRequest, CircuitPingResponse, CircuitPingResponse_Status,
+};
+
+// Implements a handler that handles CircuitPingRequest messages. If the service being pinged is
+// on another node the request is forwarded to that node, otherwise this node answers on behalf of
+// the service.
+pub struct CircuitPingRequestHandler {
+    node_id: String,
+    routing_table: Box<dyn RoutingTableReader>,
+    pending: PendingPings,
+}
+
+impl Handler for CircuitPingRequestHandler {
+    type Source = PeerId;
+    type MessageType = CircuitMessageType;
+    type Message = CircuitPingRequest;
+
+    fn match_type(&self) -> Self::MessageType {
+        CircuitMessageType::CIRCUIT_PING_REQUEST
+    }
+
+    fn handle(
+        &self,
+        mut msg: Self::Message,
+        _context: &MessageContext<Self::Source, Self::MessageType>,
+        sender: &dyn MessageSender<Self::Source>,
+    ) -> Result<(), DispatchError> {
+        debug!(
+            "Handle Circuit Ping Request {} on {} for {} from {}",
+            msg.get_correlation_id(),
+            msg.get_circuit(),
+            msg.get_recipient(),
+            msg.get_origin_node(),
+        );
+
+        msg.mut_path().push(self.node_id.clone());
+
+        let circuit_name = msg.get_circuit().to_string();
+        let recipient = msg.get_recipient().to_string();
+        let recipient_id = ServiceId::new(circuit_name.clone(), recipient.clone());
+
+        let (status, error_message, peer_id) = match self
+            .routing_table
+            .get_circuit(&circuit_name)
+            .map_err(|err| {
+            DispatchError::HandleError(err.to_string())
+        })? {
+            None => (
+                CircuitPingResponse_Status::ERROR_CIRCUIT_DOES_NOT_EXIST,
+                format!("Circuit does not exist: {}", circuit_name),
+                None,
+            ),
+            Some(circuit)
+                if !circuit
+                    .roster()
+                    .iter()
+                    .any(|service| service.service_id() == recipient) =>
+            {
+                (
+                    CircuitPingResponse_Status::ERROR_RECIPIENT_NOT_IN_CIRCUIT_ROSTER,
+                    format!("Recipient is not allowed in the Circuit: {}", recipient),
+                    None,
+                )
+            }
+            Some(_) => match self
+                .routing_table
+                .get_service(&recipient_id)
+                .map_err(|err| DispatchError::HandleError(err.to_string()))?
+            {
+                None => (
+                    CircuitPingResponse_Status::ERROR_RECIPIENT_NOT_CONNECTED,
+                    format!("Recipient is not in the service directory: {}", recipient),
+                    None,
+                ),
+                Some(service) if service.node_id() != self.node_id => {
+                    let node_id = service.node_id().to_string();
+                    if msg.get_path().iter().any(|visited| visited == &node_id) {
+                        (
+                            CircuitPingResponse_Status::ERROR_ROUTING_LOOP,
+                            format!("Request was routed back to node {}", node_id),
+                            None,
+                        )
+                    } else {
+                        let msg_bytes = msg.write_to_bytes()?;
+                        let network_msg_bytes =
+                            create_message(&msg_bytes, CircuitMessageType::CIRCUIT_PING_REQUEST);
+                        match sender.send(node_id.clone().into(), network_msg_bytes) {
+                            Ok(()) => return Ok(()),
+                            Err(_) => (
+                                CircuitPingResponse_Status::ERROR_NODE_UNREACHABLE,
+                                format!("Unable to send request to node {}", node_id),
+                                None,
+                            ),
+                        }
+                    }
+                }
+                Some(service) => match service.peer_id() {
+                    Some(peer_id) => (
+                        CircuitPingResponse_Status::OK,
+                        String::new(),
+                        Some(peer_id.clone()),
+                    ),
+                    None => (
+                        CircuitPingResponse_Status::ERROR_RECIPIENT_NOT_CONNECTED,
+                        format!("Recipient is not connected: {}", recipient),
+                        None,
+                    ),
+                },
+            },
+        };
+
+        let mut response = CircuitPingResponse::new();
+        response.set_circuit(msg.take_circuit());
+        response.set_recipient(msg.take_recipient());
+        response.set_correlation_id(msg.take_correlation_id());
+        response.set_origin_node(msg.take_origin_node());
+        response.set_timestamp(msg.get_timestamp());
+        response.set_path(msg.take_path());
+        response.set_status(status);
+        response.set_error_message(error_message);
+        if let Some(peer_id) = peer_id {
+            response.set_peer_id(peer_id);
+        }
+
+        if response.get_origin_node() == self.node_id {
+            if !self.pending.complete(response) {
+                warn!("Received a ping response that is no longer awaited");
+            }
+            return Ok(());
+        }
+
+        let origin_node = response.get_origin_node().to_string();
+        let msg_bytes = response.write_to_bytes()?;
+        let network_msg_bytes =
+            create_message(&msg_bytes, CircuitMessageType::CIRCUIT_PING_RESPONSE);
+        sender
+            .send(origin_node.into(), network_msg_bytes)
+            .map_err(|(recipient, payload)| {
+                DispatchError::NetworkSendError((recipient.into(), payload))
+            })?;
+        Ok(())
+    }
+}
+
+impl CircuitPingRequestHandler {
+    pub fn new(
+        node_id: String,
+        routing_table: Box<dyn RoutingTableReader>,
+        pending: PendingPings,
+    ) -> Self {
+        CircuitPingRequestHandler {
+            node_id,
+            routing_table,
+            pending,
+        }
+    }
+}
+
+// Implements a handler that passes CircuitPingResponse messages to the ping that is waiting for
+// them
+pub struct CircuitPingResponseHandler {
+    node_id: String,
+    pending: PendingPings,
+}
+
+impl Handler for CircuitPingResponseHandler {
+    type Source = PeerId;
+    type MessageType = CircuitMessageType;
+    type Message = CircuitPingResponse;
+
+    fn match_type(&self) -> Self::MessageType {
+        CircuitMessageType::CIRCUIT_PING_RESPONSE
+    }
+
+    fn handle(
+        &self,
+        msg: Self::Message,
+        context: &MessageContext<Self::Source, Self::MessageType>,
+        _sender: &dyn MessageSender<Self::Source>,
+    ) -> Result<(), DispatchError> {
+        debug!(
+            "Handle Circuit Ping Response {} from {}",
+            msg.get_correlation_id(),
+            context.source_peer_id()
+        );
+
+        if msg.get_origin_node() != self.node_id {
+            warn!(
+                "Received a ping response for another node: {}",
+                msg.get_origin_node()
+            );
+            return Ok(());
+        }
+
+        if !self.pending.complete(msg) {
+            warn!("Received a ping response that is no longer awaited");
+        }
+
+        Ok(())
+    }
+}
+
+impl CircuitPingResponseHandler {
+    pub fn new(node_id: String, pending: PendingPings) -> Self {
+        CircuitPingResponseHandler { node_id, pending }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::circuit::routing::{
+        memory::RoutingTable, Circuit, CircuitNode, RoutingTableWriter, Service,
+    };
+    use crate::network::dispatch::Dispatcher;
+    use crate::protos::circuit::CircuitMessage;
+    use crate::protos::network::NetworkMessage;
+
+    // Test that a ping for a service connected to the local node is answered by the local node,
+    // and that the response is passed to the pending ping when the request came from this node
+    #[test]
+    fn test_circuit_ping_local_service() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let reader = create_routing_table();
+        let pending = PendingPings::new();
+        let receiver = pending.register("1234").expect("Unable to register ping");
+
+        dispatcher.set_handler(Box::new(CircuitPingRequestHandler::new(
+            "123".to_string(),
+            reader,
+            pending,
+        )));
+
+        let request_bytes = create_request("alpha", "abc", "123");
+        dispatcher
+            .dispatch(
+                "123".into(),
+                &CircuitMessageType::CIRCUIT_PING_REQUEST,
+                request_bytes,
+            )
+            .expect("Unable to dispatch request");
+
+        let response = receiver
+            .recv_timeout(Duration::from_secs(1))
+            .expect("No response was received");
+        assert_eq!(response.get_status(), CircuitPingResponse_Status::OK);
+        assert_eq!(response.get_path().to_vec(), vec!["123".to_string()]);
+        assert_eq!(response.get_peer_id(), "abc_network");
+        assert_eq!(response.get_timestamp(), 1000);
+        assert!(mock_sender.next_outbound().is_none());
+    }
+
+    // Test that a ping for a service on another node is forwarded to that node with this node
+    // added to the path
+    #[test]
+    fn test_circuit_ping_forward_to_node() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        dispatcher.set_handler(Box::new(CircuitPingRequestHandler::new(
+            "345".to_string(),
+            create_routing_table(),
+            PendingPings::new(),
+        )));
+
+        let request_bytes = create_request("alpha", "abc", "345");
+        dispatcher
+            .dispatch(
+                "345".into(),
+                &CircuitMessageType::CIRCUIT_PING_REQUEST,
+                request_bytes,
+            )
+            .expect("Unable to dispatch request");
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "123",
+            CircuitMessageType::CIRCUIT_PING_REQUEST,
+            |msg: CircuitPingRequest| {
+                assert_eq!(msg.get_recipient(), "abc");
+                assert_eq!(msg.get_origin_node(), "345");
+                assert_eq!(msg.get_path().to_vec(), vec!["345".to_string()]);
+            },
+        )
+    }
+
+    // Test that a ping for a circuit that does not exist is answered with an error that is
+    // returned to the node that sent the request
+    #[test]
+    fn test_circuit_ping_circuit_does_not_exist() {
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        dispatcher.set_handler(Box::new(CircuitPingRequestHandler::new(
+            "123".to_string(),
+            create_routing_table(),
+            PendingPings::new(),
+        )));
+
+        let request_bytes = create_request("beta", "abc", "345");
+        dispatcher
+            .dispatch(
+                "345".into(),
+                &CircuitMessageType::CIRCUIT_PING_REQUEST,
+                request_bytes,
+            )
+            .expect("Unable to dispatch request");
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "345",
+            CircuitMessageType::CIRCUIT_PING_RESPONSE,
+            |msg: CircuitPingResponse| {
+                assert_eq!(
+                    msg.get_status(),
+                    CircuitPingResponse_Status::ERROR_CIRCUIT_DOES_NOT_EXIST
+                );
+                assert_eq!(msg.get_correlation_id(), "1234");
+                assert_eq!(msg.get_path().to_vec(), vec!["123".to_string()]);
+            },
+        )
+    }
+
+    // Creates a routing table with circuit "alpha", which has service "abc" connected to node
+    // "123" and service "def" on node "345"
+    fn create_routing_table() -> Box<dyn RoutingTableReader> {
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table);
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![],
+        );
+        service_abc.set_peer_id("abc_network".to_string());
+
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc.clone(), service_def.clone()],
+            vec!["123".into(), "345".into()],
+        );
+
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345],
+            )
+            .expect("Unable to add circuits");
+        writer
+            .add_service(ServiceId::new("alpha".into(), "abc".into()), service_abc)
+            .expect("Unable to add service");
+
+        reader
+    }
+
+    fn create_request(circuit: &str, recipient: &str, origin_node: &str) -> Vec<u8> {
+        let mut request = CircuitPingRequest::new();
+        request.set_circuit(circuit.into());
+        request.set_recipient(recipient.into());
+        request.set_correlation_id("1234".into());
+        request.set_origin_node(origin_node.into());
+        request.set_timestamp(1000);
+        request.write_to_bytes().expect("Unable to write request")
+    }
+
+    fn assert_network_message<M: protobuf::Message, F: Fn(M)>(
+        message: Vec<u8>,
+        recipient: String,
+        expected_recipient: &str,
+        expected_circuit_msg_type: CircuitMessageType,
+        detail_assertions: F,
+    ) {
+        assert_eq!(expected_recipient, &recipient);
+
+        let network_msg: NetworkMessage = protobuf::parse_from_bytes(&message).unwrap();
+        let circuit_msg: CircuitMessage =
+            protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+        assert_eq!(expected_circuit_msg_type, circuit_msg.get_message_type(),);
+        let circuit_msg: M = protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap();
+
+        detail_assertions(circuit_msg);
+    }
+
+    #[derive(Clone)]
+    struct MockSender {
+        outbound: Arc<Mutex<VecDeque<(PeerId, Vec<u8>)>>>,
+    }
+
+    impl MockSender {
+        fn new() -> Self {
+            Self {
+                outbound: Arc::new(Mutex::new(VecDeque::new())),
+            }
+        }
+
+        fn next_outbound(&self) -> Option<(PeerId, Vec<u8>)> {
+            self.outbound.lock().expect("lock was poisoned").pop_front()
+        }
+    }
+
+    impl MessageSender<PeerId> for MockSender {
+        fn send(&self, id: PeerId, message: Vec<u8>) -> Result<(), (PeerId, Vec<u8>)> {
+            self.outbound
+                .lock()
+                .expect("lock was poisoned")
+                .push_back((id, message));
+
+            Ok(())
+        }
+    }
+}
@@ -14,12 +14,16 @@
 
 use crate::circuit::handlers::create_message;
 use crate::circuit::routing::{RoutingTableReader, RoutingTableWriter, Service, ServiceId};
+#[cfg(feature = "service-endpoint-auth")]
+use crate::circuit::service_auth::ServiceChallenges;
 use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
 use crate::protos::circuit::{
     CircuitMessageType, ServiceConnectRequest, ServiceConnectResponse,
     ServiceConnectResponse_Status, ServiceDisconnectRequest, ServiceDisconnectResponse,
     ServiceDisconnectResponse_Status,
 };
+#[cfg(feature = "service-endpoint-auth")]
+use crate::protos::circuit::{ServiceAuthChallengeRequest, ServiceAuthChallengeResponse};
 
 use protobuf::Message;
 
@@ -31,6 +35,8 @@ pub struct ServiceConnectRequestHandler {
     node_id: String,
     routing_table_reader: Box<dyn RoutingTableReader>,
     routing_table_writer: Box<dyn RoutingTableWriter>,
+    #[cfg(feature = "service-endpoint-auth")]
+    challenges: Option<ServiceChallenges>,
 }
 
 impl Handler for ServiceConnectRequestHandler {
@@ -92,6 +98,18 @@ impl Handler for ServiceConnectRequestHandler {
                     }
                 };
 
+                #[cfg(feature = "service-endpoint-auth")]
+                let auth_error = self
+                    .authenticate(
+                        context.source_peer_id(),
+                        &unique_id,
+                        &service,
+                        msg.get_signature(),
+                    )
+                    .err();
+                #[cfg(not(feature = "service-endpoint-auth"))]
+                let auth_error: Option<String> = None;
+
                 // If the circuit exists and has the service in the roster but the service is already
                 // connected, return an error response
                 if service.peer_id().is_some() {
@@ -103,6 +121,16 @@ impl Handler for ServiceConnectRequestHandler {
                 } else if service.node_id() != self.node_id {
                     response.set_status(ServiceConnectResponse_Status::ERROR_NOT_AN_ALLOWED_NODE);
                     response.set_error_message(format!("{} is not allowed on this node", unique_id))
+                // If the service has a registered key but did not sign its challenge with it,
+                // return an error response
+                } else if let Some(message) = auth_error {
+                    warn!(
+                        "Rejected connection from {}: {}",
+                        context.source_peer_id(),
+                        message
+                    );
+                    response.set_status(ServiceConnectResponse_Status::ERROR_AUTHENTICATION_FAILED);
+                    response.set_error_message(message)
                 } else {
                     service.set_peer_id(context.source_peer_id().to_string());
                     let mut writer = self.routing_table_writer.clone();
@@ -131,8 +159,10 @@ impl Handler for ServiceConnectRequestHandler {
 
         // Return response
         let response_bytes = response.write_to_bytes()?;
-        let network_msg_bytes =
-            create_message(response_bytes, CircuitMessageType::SERVICE_CONNECT_RESPONSE)?;
+        let network_msg_bytes = create_message(
+            &response_bytes,
+            CircuitMessageType::SERVICE_CONNECT_RESPONSE,
+        );
 
         let recipient = context.source_peer_id().to_string();
 
@@ -155,10 +185,92 @@ impl ServiceConnectRequestHandler {
             node_id,
             routing_table_reader,
             routing_table_writer,
+            #[cfg(feature = "service-endpoint-auth")]
+            challenges: None,
+        }
+    }
+
+    /// Requires services with a key registered in their circuit definition to sign a challenge
+    /// issued by a `ServiceAuthChallengeRequestHandler` sharing the given challenges.
+    #[cfg(feature = "service-endpoint-auth")]
+    pub fn with_service_auth(mut self, challenges: ServiceChallenges) -> Self {
+        self.challenges = Some(challenges);
+        self
+    }
+
+    #[cfg(feature = "service-endpoint-auth")]
+    fn authenticate(
+        &self,
+        peer_id: &str,
+        service_id: &ServiceId,
+        service: &Service,
+        signature: &[u8],
+    ) -> Result<(), String> {
+        match &self.challenges {
+            Some(challenges) => challenges.authenticate(peer_id, service_id, service, signature),
+            None => Ok(()),
         }
     }
 }
 
+// Implements a handler that issues the nonces services sign to authenticate their connect
+// requests
+#[cfg(feature = "service-endpoint-auth")]
+pub struct ServiceAuthChallengeRequestHandler {
+    challenges: ServiceChallenges,
+}
+
+#[cfg(feature = "service-endpoint-auth")]
+impl Handler for ServiceAuthChallengeRequestHandler {
+    type Source = PeerId;
+    type MessageType = CircuitMessageType;
+    type Message = ServiceAuthChallengeRequest;
+
+    fn match_type(&self) -> Self::MessageType {
+        CircuitMessageType::SERVICE_AUTH_CHALLENGE_REQUEST
+    }
+
+    fn handle(
+        &self,
+        msg: Self::Message,
+        context: &MessageContext<Self::Source, Self::MessageType>,
+        sender: &dyn MessageSender<Self::Source>,
+    ) -> Result<(), DispatchError> {
+        debug!("Handle Service Auth Challenge Request {:?}", msg);
+        let unique_id = ServiceId::new(
+            msg.get_circuit().to_string(),
+            msg.get_service_id().to_string(),
+        );
+
+        let mut response = ServiceAuthChallengeResponse::new();
+        response.set_correlation_id(msg.get_correlation_id().into());
+        response.set_circuit(msg.get_circuit().into());
+        response.set_service_id(msg.get_service_id().into());
+        response.set_nonce(self.challenges.issue(context.source_peer_id(), unique_id));
+
+        let response_bytes = response.write_to_bytes()?;
+        let network_msg_bytes = create_message(
+            &response_bytes,
+            CircuitMessageType::SERVICE_AUTH_CHALLENGE_RESPONSE,
+        );
+
+        let recipient = context.source_peer_id().to_string();
+        sender
+            .send(recipient.into(), network_msg_bytes)
+            .map_err(|(recipient, payload)| {
+                DispatchError::NetworkSendError((recipient.into(), payload))
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "service-endpoint-auth")]
+impl ServiceAuthChallengeRequestHandler {
+    pub fn new(challenges: ServiceChallenges) -> Self {
+        ServiceAuthChallengeRequestHandler { challenges }
+    }
+}
+
 // Implements a handler that handles ServiceDisconnectRequest
 pub struct ServiceDisconnectRequestHandler {
     routing_table_reader: Box<dyn RoutingTableReader>,
@@ -245,9 +357,9 @@ impl Handler for ServiceDisconnectRequestHandler {
         // Return response
         let response_bytes = response.write_to_bytes()?;
         let network_msg_bytes = create_message(
-            response_bytes,
+            &response_bytes,
             CircuitMessageType::SERVICE_DISCONNECT_RESPONSE,
-        )?;
+        );
 
         let recipient = context.source_peer_id().to_string();
         sender
@@ -698,6 +810,125 @@ mod tests {
         )
     }
 
+    #[cfg(feature = "service-endpoint-auth")]
+    #[test]
+    // Test that a service with a registered key is only connected once it signs the challenge
+    // issued to its connection with that key
+    fn test_service_connect_request_handler_authentication() {
+        use cylinder::{secp256k1::Secp256k1Context, Context};
+
+        use crate::circuit::service_auth::{
+            challenge_message, ServiceChallenges, SERVICE_PUBLIC_KEY_ARGUMENT,
+        };
+        use crate::hex::to_hex;
+
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let public_key = to_hex(signer.public_key().unwrap().as_slice());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![(SERVICE_PUBLIC_KEY_ARGUMENT.to_string(), public_key)],
+        );
+        let circuit = Circuit::new("alpha".into(), vec![service_abc], vec!["123".into()]);
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+        writer
+            .add_circuit(circuit.circuit_id().to_string(), circuit, vec![node_123])
+            .expect("Unable to add circuit");
+
+        let challenges = ServiceChallenges::new();
+        dispatcher.set_handler(Box::new(
+            ServiceConnectRequestHandler::new("123".to_string(), reader.clone(), writer)
+                .with_service_auth(challenges.clone()),
+        ));
+        dispatcher.set_handler(Box::new(ServiceAuthChallengeRequestHandler::new(
+            challenges,
+        )));
+
+        // A connect request without a challenge is rejected
+        let mut connect_request = ServiceConnectRequest::new();
+        connect_request.set_circuit("alpha".into());
+        connect_request.set_service_id("abc".into());
+        dispatcher
+            .dispatch(
+                "abc".into(),
+                &CircuitMessageType::SERVICE_CONNECT_REQUEST,
+                connect_request.write_to_bytes().unwrap(),
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "abc",
+            CircuitMessageType::SERVICE_CONNECT_RESPONSE,
+            |msg: ServiceConnectResponse| {
+                assert_eq!(
+                    msg.get_status(),
+                    ServiceConnectResponse_Status::ERROR_AUTHENTICATION_FAILED
+                );
+            },
+        );
+
+        let mut challenge_request = ServiceAuthChallengeRequest::new();
+        challenge_request.set_circuit("alpha".into());
+        challenge_request.set_service_id("abc".into());
+        dispatcher
+            .dispatch(
+                "abc".into(),
+                &CircuitMessageType::SERVICE_AUTH_CHALLENGE_REQUEST,
+                challenge_request.write_to_bytes().unwrap(),
+            )
+            .unwrap();
+
+        let (_, message) = mock_sender.next_outbound().expect("No message was sent");
+        let network_msg: NetworkMessage = protobuf::parse_from_bytes(&message).unwrap();
+        let circuit_msg: CircuitMessage =
+            protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+        let challenge: ServiceAuthChallengeResponse =
+            protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap();
+
+        connect_request.set_signature(
+            signer
+                .sign(&challenge_message("alpha", "abc", challenge.get_nonce()))
+                .unwrap()
+                .take_bytes(),
+        );
+        dispatcher
+            .dispatch(
+                "abc".into(),
+                &CircuitMessageType::SERVICE_CONNECT_REQUEST,
+                connect_request.write_to_bytes().unwrap(),
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "abc",
+            CircuitMessageType::SERVICE_CONNECT_RESPONSE,
+            |msg: ServiceConnectResponse| {
+                assert_eq!(msg.get_status(), ServiceConnectResponse_Status::OK);
+            },
+        );
+        let service = reader
+            .get_service(&ServiceId::new("alpha".into(), "abc".into()))
+            .expect("Unable to get service")
+            .unwrap();
+        assert_eq!(Some("abc"), service.peer_id().as_deref());
+    }
+
     fn build_circuit() -> (Circuit, Vec<CircuitNode>) {
         let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
         let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
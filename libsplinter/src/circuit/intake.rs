@@ -0,0 +1,56 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pausing the intake of circuit messages.
+//!
+//! While an [`IntakePause`] is paused, the handler that queues circuit messages for the circuit
+//! dispatcher rejects circuit direct messages with a `CircuitError`, so that the services on the
+//! node are not handed application traffic they can't store, such as while the node's disk is
+//! running out of space. Other circuit messages, such as service connection requests, are still
+//! accepted.
+//!
+//! [`IntakePause`]: struct.IntakePause.html
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A switch that pauses the intake of circuit direct messages.
+///
+/// Clones of an `IntakePause` share the same switch.
+#[derive(Clone, Default)]
+pub struct IntakePause {
+    paused: Arc<AtomicBool>,
+}
+
+impl IntakePause {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the intake of circuit direct messages is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Pauses or resumes the intake of circuit direct messages
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) != paused {
+            if paused {
+                warn!("Circuit message intake paused; direct messages will be rejected");
+            } else {
+                info!("Circuit message intake resumed");
+            }
+        }
+    }
+}
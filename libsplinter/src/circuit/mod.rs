@@ -14,7 +14,20 @@
 
 #[cfg(feature = "service-network")]
 pub mod component;
+#[cfg(feature = "circuit-dead-letter")]
+pub mod dead_letter;
 pub mod handlers;
+#[cfg(feature = "circuit-intake-pause")]
+pub mod intake;
+#[cfg(feature = "circuit-ping")]
+pub mod ping;
+pub mod qos;
+#[cfg(feature = "circuit-replay-protection")]
+pub mod replay;
 pub mod routing;
+#[cfg(feature = "circuit-payload-schema")]
+pub mod schema;
+#[cfg(feature = "service-endpoint-auth")]
+pub mod service_auth;
 #[cfg(feature = "circuit-template")]
 pub mod template;
@@ -0,0 +1,303 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Diagnostic pings for checking that a service on a circuit can be reached.
+//!
+//! A [`CircuitPinger`] sends a timestamped `CircuitPingRequest` through the local circuit
+//! dispatcher. The request is routed like a direct message: each node it passes through adds
+//! itself to the request's path, and the node the service is connected to answers on behalf of the
+//! service. The response is returned to the node that sent the request, where it is matched to
+//! the waiting ping by its correlation ID.
+//!
+//! [`CircuitPinger`]: struct.CircuitPinger.html
+
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{error, fmt};
+
+use protobuf::Message;
+use uuid::Uuid;
+
+use crate::error::InternalError;
+use crate::network::dispatch::{DispatchMessageSender, PeerId};
+use crate::protos::circuit::{
+    CircuitMessageType, CircuitPingRequest, CircuitPingResponse, CircuitPingResponse_Status,
+};
+
+/// The pings sent by this node that are waiting for a response, keyed by correlation ID.
+///
+/// `PendingPings` is shared between the [`CircuitPinger`] and the ping handlers of the circuit
+/// dispatcher, which complete the pings when responses arrive.
+///
+/// [`CircuitPinger`]: struct.CircuitPinger.html
+#[derive(Clone, Default)]
+pub struct PendingPings {
+    senders: Arc<Mutex<HashMap<String, Sender<CircuitPingResponse>>>>,
+}
+
+impl PendingPings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn register(
+        &self,
+        correlation_id: &str,
+    ) -> Result<Receiver<CircuitPingResponse>, InternalError> {
+        let (sender, receiver) = channel();
+        self.senders
+            .lock()
+            .map_err(|_| InternalError::with_message("Pending pings lock was poisoned".into()))?
+            .insert(correlation_id.to_string(), sender);
+        Ok(receiver)
+    }
+
+    fn remove(&self, correlation_id: &str) {
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.remove(correlation_id);
+        }
+    }
+
+    /// Passes the response to the ping waiting for it. Returns `false` if no ping is waiting for
+    /// the response, such as when the ping has already timed out.
+    pub(crate) fn complete(&self, response: CircuitPingResponse) -> bool {
+        let sender = match self.senders.lock() {
+            Ok(mut senders) => senders.remove(response.get_correlation_id()),
+            Err(_) => {
+                error!("Pending pings lock was poisoned");
+                return false;
+            }
+        };
+
+        match sender {
+            Some(sender) => sender.send(response).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// The outcome of a ping that received a response
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitPingStatus {
+    /// The service is connected and messages can be delivered to it
+    Ok,
+    /// The circuit is not in the routing table of a node on the path
+    CircuitDoesNotExist,
+    /// The service is not in the circuit's roster
+    RecipientNotInCircuitRoster,
+    /// The service is not connected to the node it runs on
+    RecipientNotConnected,
+    /// The node the service runs on could not be sent the request
+    NodeUnreachable,
+    /// The request was routed back to a node it had already passed through
+    RoutingLoop,
+}
+
+impl fmt::Display for CircuitPingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CircuitPingStatus::Ok => "OK",
+            CircuitPingStatus::CircuitDoesNotExist => "CIRCUIT_DOES_NOT_EXIST",
+            CircuitPingStatus::RecipientNotInCircuitRoster => "RECIPIENT_NOT_IN_CIRCUIT_ROSTER",
+            CircuitPingStatus::RecipientNotConnected => "RECIPIENT_NOT_CONNECTED",
+            CircuitPingStatus::NodeUnreachable => "NODE_UNREACHABLE",
+            CircuitPingStatus::RoutingLoop => "ROUTING_LOOP",
+        })
+    }
+}
+
+/// The result of a ping that received a response
+#[derive(Clone, Debug)]
+pub struct CircuitPingResult {
+    /// The circuit the service is on
+    pub circuit_id: String,
+    /// The service that was pinged
+    pub service_id: String,
+    /// Whether the service was reached, or why it was not
+    pub status: CircuitPingStatus,
+    /// An explanation of why the service was not reached
+    pub error_message: Option<String>,
+    /// The IDs of the nodes the request passed through, in order
+    pub path: Vec<String>,
+    /// The peer ID of the service's connection, if the service was reached
+    pub peer_id: Option<String>,
+    /// The time between sending the request and receiving the response
+    pub round_trip: Duration,
+}
+
+/// Errors that could be raised by `CircuitPinger::ping`
+#[derive(Debug)]
+pub enum CircuitPingError {
+    /// The ping could not be sent or its response could not be read
+    InternalError(InternalError),
+    /// No response was received before the timeout
+    Timeout(Duration),
+}
+
+impl error::Error for CircuitPingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CircuitPingError::InternalError(err) => Some(err),
+            CircuitPingError::Timeout(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for CircuitPingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitPingError::InternalError(err) => f.write_str(&err.to_string()),
+            CircuitPingError::Timeout(timeout) => {
+                write!(f, "No ping response received after {:?}", timeout)
+            }
+        }
+    }
+}
+
+impl From<InternalError> for CircuitPingError {
+    fn from(err: InternalError) -> Self {
+        CircuitPingError::InternalError(err)
+    }
+}
+
+/// Sends diagnostic pings to services on circuits.
+#[derive(Clone)]
+pub struct CircuitPinger {
+    node_id: String,
+    dispatch_sender: DispatchMessageSender<CircuitMessageType>,
+    pending: PendingPings,
+}
+
+impl CircuitPinger {
+    /// Creates a new `CircuitPinger`
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the local node
+    /// * `dispatch_sender` - A sender for the local circuit dispatcher
+    /// * `pending` - The pending pings shared with the circuit dispatcher's ping handlers
+    pub fn new(
+        node_id: String,
+        dispatch_sender: DispatchMessageSender<CircuitMessageType>,
+        pending: PendingPings,
+    ) -> Self {
+        CircuitPinger {
+            node_id,
+            dispatch_sender,
+            pending,
+        }
+    }
+
+    /// Pings a service on a circuit and waits for the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_id` - The circuit the service is on
+    /// * `service_id` - The service to ping
+    /// * `timeout` - How long to wait for a response
+    pub fn ping(
+        &self,
+        circuit_id: &str,
+        service_id: &str,
+        timeout: Duration,
+    ) -> Result<CircuitPingResult, CircuitPingError> {
+        let correlation_id = Uuid::new_v4().to_string();
+        let receiver = self.pending.register(&correlation_id)?;
+
+        let mut request = CircuitPingRequest::new();
+        request.set_circuit(circuit_id.to_string());
+        request.set_recipient(service_id.to_string());
+        request.set_correlation_id(correlation_id.clone());
+        request.set_origin_node(self.node_id.clone());
+        request.set_timestamp(now_micros()?);
+
+        let request_bytes = request
+            .write_to_bytes()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        if self
+            .dispatch_sender
+            .send(
+                CircuitMessageType::CIRCUIT_PING_REQUEST,
+                request_bytes,
+                PeerId::from(self.node_id.as_str()),
+            )
+            .is_err()
+        {
+            self.pending.remove(&correlation_id);
+            return Err(CircuitPingError::InternalError(
+                InternalError::with_message(
+                    "Unable to send ping request to the circuit dispatcher".into(),
+                ),
+            ));
+        }
+
+        let mut response = match receiver.recv_timeout(timeout) {
+            Ok(response) => response,
+            Err(RecvTimeoutError::Timeout) => {
+                self.pending.remove(&correlation_id);
+                return Err(CircuitPingError::Timeout(timeout));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(CircuitPingError::InternalError(
+                    InternalError::with_message("Ping response channel was disconnected".into()),
+                ))
+            }
+        };
+
+        let round_trip =
+            Duration::from_micros(now_micros()?.saturating_sub(response.get_timestamp()));
+
+        let status = match response.get_status() {
+            CircuitPingResponse_Status::OK => CircuitPingStatus::Ok,
+            CircuitPingResponse_Status::ERROR_CIRCUIT_DOES_NOT_EXIST => {
+                CircuitPingStatus::CircuitDoesNotExist
+            }
+            CircuitPingResponse_Status::ERROR_RECIPIENT_NOT_IN_CIRCUIT_ROSTER => {
+                CircuitPingStatus::RecipientNotInCircuitRoster
+            }
+            CircuitPingResponse_Status::ERROR_RECIPIENT_NOT_CONNECTED => {
+                CircuitPingStatus::RecipientNotConnected
+            }
+            CircuitPingResponse_Status::ERROR_NODE_UNREACHABLE => {
+                CircuitPingStatus::NodeUnreachable
+            }
+            CircuitPingResponse_Status::ERROR_ROUTING_LOOP => CircuitPingStatus::RoutingLoop,
+            CircuitPingResponse_Status::UNSET_STATUS => {
+                return Err(CircuitPingError::InternalError(
+                    InternalError::with_message("Ping response status was not set".into()),
+                ))
+            }
+        };
+
+        Ok(CircuitPingResult {
+            circuit_id: response.take_circuit(),
+            service_id: response.take_recipient(),
+            status,
+            error_message: Some(response.take_error_message()).filter(|msg| !msg.is_empty()),
+            path: response.take_path().into_vec(),
+            peer_id: Some(response.take_peer_id()).filter(|peer_id| !peer_id.is_empty()),
+            round_trip,
+        })
+    }
+}
+
+fn now_micros() -> Result<u64, InternalError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_micros() as u64)
+        .map_err(|err| InternalError::from_source(Box::new(err)))
+}
@@ -0,0 +1,344 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-circuit quality-of-service limits.
+//!
+//! A circuit may be created with limits on the size of its messages, the rate at which its
+//! messages are accepted, and the number of bytes of its messages that may be waiting in the
+//! circuit dispatcher's queue, so that one application's circuit cannot degrade the other circuits
+//! sharing the node.
+//!
+//! The limits apply to circuit direct messages, which carry the circuit's application traffic.
+//! They are enforced by a [`QosEnforcer`] that is shared by the handler that queues circuit
+//! messages for the circuit dispatcher, which admits or rejects each message, and the direct
+//! message handler, which releases a message's bytes from the queue once it has been handled.
+//!
+//! [`QosEnforcer`]: struct.QosEnforcer.html
+
+#[cfg(feature = "circuit-qos")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "circuit-qos")]
+use std::fmt;
+#[cfg(feature = "circuit-qos")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "circuit-qos")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "circuit-qos")]
+use crate::circuit::routing::RoutingTableReader;
+#[cfg(feature = "circuit-qos")]
+use crate::error::InternalError;
+
+/// The quality-of-service limits of a circuit. A limit that is not set is not enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QosLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_message_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_messages_per_second: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_queued_bytes: Option<u64>,
+}
+
+impl QosLimits {
+    /// Creates a new `QosLimits` with no limits set
+    pub fn new() -> Self {
+        QosLimits::default()
+    }
+
+    /// Sets the maximum size, in bytes, of a message on the circuit
+    pub fn with_max_message_size(mut self, max_message_size: u64) -> Self {
+        self.max_message_size = Some(max_message_size);
+        self
+    }
+
+    /// Sets the maximum number of messages per second accepted on the circuit
+    pub fn with_max_messages_per_second(mut self, max_messages_per_second: u64) -> Self {
+        self.max_messages_per_second = Some(max_messages_per_second);
+        self
+    }
+
+    /// Sets the maximum number of bytes of the circuit's messages that may be queued in the
+    /// circuit dispatcher
+    pub fn with_max_queued_bytes(mut self, max_queued_bytes: u64) -> Self {
+        self.max_queued_bytes = Some(max_queued_bytes);
+        self
+    }
+
+    /// Returns the maximum size, in bytes, of a message on the circuit
+    pub fn max_message_size(&self) -> Option<u64> {
+        self.max_message_size
+    }
+
+    /// Returns the maximum number of messages per second accepted on the circuit
+    pub fn max_messages_per_second(&self) -> Option<u64> {
+        self.max_messages_per_second
+    }
+
+    /// Returns the maximum number of bytes of the circuit's messages that may be queued in the
+    /// circuit dispatcher
+    pub fn max_queued_bytes(&self) -> Option<u64> {
+        self.max_queued_bytes
+    }
+
+    /// Returns true if none of the limits are set
+    pub fn is_unlimited(&self) -> bool {
+        self == &QosLimits::default()
+    }
+}
+
+/// The limit a rejected message violated
+#[cfg(feature = "circuit-qos")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QosViolation {
+    /// The message was larger than the circuit's maximum message size
+    MessageTooLarge,
+    /// The circuit's maximum messages per second had already been reached
+    RateExceeded,
+    /// Queueing the message would have exceeded the circuit's maximum queued bytes
+    QueueFull,
+}
+
+#[cfg(feature = "circuit-qos")]
+impl QosViolation {
+    /// Returns the violation as a static string, suitable for use as a metric label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QosViolation::MessageTooLarge => "message_too_large",
+            QosViolation::RateExceeded => "rate_exceeded",
+            QosViolation::QueueFull => "queue_full",
+        }
+    }
+}
+
+#[cfg(feature = "circuit-qos")]
+impl fmt::Display for QosViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "circuit-qos")]
+#[derive(Default)]
+struct CircuitUsage {
+    window_start: Option<Instant>,
+    window_messages: u64,
+    queued_bytes: u64,
+    violations: BTreeMap<QosViolation, u64>,
+}
+
+#[cfg(feature = "circuit-qos")]
+impl CircuitUsage {
+    fn admit(&mut self, limits: &QosLimits, size: u64, now: Instant) -> Option<QosViolation> {
+        match self.window_start {
+            Some(start) if now.duration_since(start) < Duration::from_secs(1) => (),
+            _ => {
+                self.window_start = Some(now);
+                self.window_messages = 0;
+            }
+        }
+
+        let violation = if limits.max_message_size.map_or(false, |max| size > max) {
+            Some(QosViolation::MessageTooLarge)
+        } else if limits
+            .max_messages_per_second
+            .map_or(false, |max| self.window_messages >= max)
+        {
+            Some(QosViolation::RateExceeded)
+        } else if limits
+            .max_queued_bytes
+            .map_or(false, |max| self.queued_bytes + size > max)
+        {
+            Some(QosViolation::QueueFull)
+        } else {
+            None
+        };
+
+        match violation {
+            Some(violation) => *self.violations.entry(violation).or_insert(0) += 1,
+            None => {
+                self.window_messages += 1;
+                self.queued_bytes += size;
+            }
+        }
+
+        violation
+    }
+}
+
+/// Enforces the quality-of-service limits of the circuits in the routing table.
+///
+/// The enforcer is cloned to share it between the handlers that admit and release messages.
+#[cfg(feature = "circuit-qos")]
+#[derive(Clone)]
+pub struct QosEnforcer {
+    routing_table: Box<dyn RoutingTableReader>,
+    usage: Arc<Mutex<HashMap<String, CircuitUsage>>>,
+}
+
+#[cfg(feature = "circuit-qos")]
+impl QosEnforcer {
+    /// Creates a new `QosEnforcer`
+    ///
+    /// # Arguments
+    ///
+    /// * `routing_table` - The routing table the limits of each circuit are read from
+    pub fn new(routing_table: Box<dyn RoutingTableReader>) -> Self {
+        QosEnforcer {
+            routing_table,
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Checks whether a message may be queued for the circuit dispatcher. If it may, its bytes
+    /// are counted as queued until they are released with `release`.
+    ///
+    /// Returns the limit the message violates if it is rejected. Messages for circuits that are
+    /// not in the routing table are always admitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_id` - The circuit the message was sent on
+    /// * `size` - The size of the message, in bytes
+    pub fn admit(
+        &self,
+        circuit_id: &str,
+        size: usize,
+    ) -> Result<Option<QosViolation>, InternalError> {
+        let limits = match self
+            .routing_table
+            .get_circuit(circuit_id)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+        {
+            Some(circuit) => *circuit.qos(),
+            None => return Ok(None),
+        };
+
+        let violation = self
+            .lock()?
+            .entry(circuit_id.to_string())
+            .or_default()
+            .admit(&limits, size as u64, Instant::now());
+
+        if let Some(violation) = violation {
+            #[cfg(feature = "metrics")]
+            metrics::counter!(
+                "splinter.circuit.qos_violations",
+                1,
+                "circuit" => circuit_id.to_string(),
+                "limit" => violation.as_str()
+            );
+        }
+
+        Ok(violation)
+    }
+
+    /// Releases the bytes of a message that was admitted once it has left the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_id` - The circuit the message was sent on
+    /// * `size` - The size of the message, in bytes
+    pub fn release(&self, circuit_id: &str, size: usize) -> Result<(), InternalError> {
+        if let Some(usage) = self.lock()?.get_mut(circuit_id) {
+            // Messages that are dispatched without being admitted, such as replayed dead
+            // letters, are also released, so the count may not go below zero
+            usage.queued_bytes = usage.queued_bytes.saturating_sub(size as u64);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes of the circuit's messages that are queued
+    pub fn queued_bytes(&self, circuit_id: &str) -> Result<u64, InternalError> {
+        Ok(self
+            .lock()?
+            .get(circuit_id)
+            .map(|usage| usage.queued_bytes)
+            .unwrap_or(0))
+    }
+
+    /// Returns the number of messages rejected on the circuit, by the limit they violated
+    pub fn violations(
+        &self,
+        circuit_id: &str,
+    ) -> Result<BTreeMap<QosViolation, u64>, InternalError> {
+        Ok(self
+            .lock()?
+            .get(circuit_id)
+            .map(|usage| usage.violations.clone())
+            .unwrap_or_default())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<HashMap<String, CircuitUsage>>, InternalError> {
+        self.usage
+            .lock()
+            .map_err(|_| InternalError::with_message("QoS enforcer lock was poisoned".into()))
+    }
+}
+
+#[cfg(all(test, feature = "circuit-qos"))]
+mod tests {
+    use super::*;
+
+    use crate::circuit::routing::{memory::RoutingTable, Circuit, RoutingTableWriter};
+
+    // Test that each limit is enforced, that rejected messages are counted by the limit they
+    // violated, and that released bytes may be queued again
+    #[test]
+    fn test_admit_and_release() {
+        let table = RoutingTable::default();
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+        writer
+            .add_circuit(
+                "alpha".into(),
+                Circuit::new("alpha".into(), vec![], vec![]).with_qos(
+                    QosLimits::new()
+                        .with_max_message_size(10)
+                        .with_max_messages_per_second(3)
+                        .with_max_queued_bytes(15),
+                ),
+                vec![],
+            )
+            .expect("Unable to add circuit");
+
+        let enforcer = QosEnforcer::new(Box::new(table));
+
+        assert_eq!(None, enforcer.admit("alpha", 10).unwrap());
+        assert_eq!(
+            Some(QosViolation::MessageTooLarge),
+            enforcer.admit("alpha", 11).unwrap()
+        );
+        assert_eq!(
+            Some(QosViolation::QueueFull),
+            enforcer.admit("alpha", 6).unwrap()
+        );
+        assert_eq!(None, enforcer.admit("alpha", 5).unwrap());
+        assert_eq!(15, enforcer.queued_bytes("alpha").unwrap());
+
+        enforcer.release("alpha", 10).unwrap();
+        assert_eq!(None, enforcer.admit("alpha", 5).unwrap());
+        assert_eq!(
+            Some(QosViolation::RateExceeded),
+            enforcer.admit("alpha", 1).unwrap()
+        );
+
+        // Unknown circuits are not limited
+        assert_eq!(None, enforcer.admit("beta", 1000).unwrap());
+
+        let violations = enforcer.violations("alpha").unwrap();
+        assert_eq!(Some(&1), violations.get(&QosViolation::MessageTooLarge));
+        assert_eq!(Some(&1), violations.get(&QosViolation::QueueFull));
+        assert_eq!(Some(&1), violations.get(&QosViolation::RateExceeded));
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of duplicate and replayed circuit direct messages.
+//!
+//! Services number the `CircuitDirectMessage`s they send with a `sequence_number` that increases
+//! with each message. The `CircuitDirectMessageHandler` records the sequence numbers of the
+//! messages it has delivered in a [`ReplayWindow`], per peer the message was received from and
+//! per sending service, and drops any message whose sequence number has already been delivered.
+//! Since the peer is authenticated by its connection, a peer can only affect the windows of the
+//! messages it sends itself.
+//!
+//! The window holds a fixed number of sequence numbers per sender. When it is full, the lowest
+//! sequence number is forgotten, and every message with a sequence number up to the forgotten one
+//! is dropped from then on; a sender that floods the window with new messages can therefore not
+//! make it accept a message that was already delivered.
+//!
+//! Messages without a sequence number, such as those sent by external services and older nodes,
+//! can not be checked; they are delivered, and counted by the
+//! `splinter.circuit.unsequenced_messages` metric.
+//!
+//! The windows of a peer are forgotten when the peer manager removes the peer.
+//!
+//! [`ReplayWindow`]: struct.ReplayWindow.html
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::error::InternalError;
+use crate::peer::PeerManagerNotification;
+
+/// The default number of sequence numbers remembered for each sender.
+pub const DEFAULT_REPLAY_WINDOW_SIZE: usize = 10_000;
+
+/// The result of checking a message against a `ReplayWindow`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayCheck {
+    /// The message has not been delivered yet
+    New,
+    /// A message with the same sequence number has already been delivered
+    Duplicate,
+    /// The message's sequence number is older than the sequence numbers in the window
+    Expired,
+    /// The message does not have a sequence number, so it can not be checked
+    Unsequenced,
+}
+
+impl ReplayCheck {
+    /// Returns whether the message must be dropped
+    pub fn is_replay(&self) -> bool {
+        match self {
+            ReplayCheck::Duplicate | ReplayCheck::Expired => true,
+            ReplayCheck::New | ReplayCheck::Unsequenced => false,
+        }
+    }
+}
+
+impl fmt::Display for ReplayCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayCheck::New => f.write_str("new"),
+            ReplayCheck::Duplicate => f.write_str("duplicate"),
+            ReplayCheck::Expired => f.write_str("expired"),
+            ReplayCheck::Unsequenced => f.write_str("unsequenced"),
+        }
+    }
+}
+
+// The sequence numbers delivered from one sender. Every sequence number up to and including
+// `floor` is considered delivered.
+#[derive(Default)]
+struct SenderWindow {
+    seen: BTreeSet<u64>,
+    floor: u64,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    // keyed by peer ID and sender service ID
+    senders: HashMap<(String, String), SenderWindow>,
+    duplicates: HashMap<String, u64>,
+}
+
+/// A per-sender record of recently delivered message sequence numbers.
+///
+/// The window is cheap to clone; all clones share the same state.
+#[derive(Clone)]
+pub struct ReplayWindow {
+    state: Arc<Mutex<ReplayState>>,
+    window_size: usize,
+}
+
+impl ReplayWindow {
+    /// Creates a new `ReplayWindow` that remembers up to `window_size` sequence numbers per sender
+    pub fn new(window_size: usize) -> Self {
+        ReplayWindow {
+            state: Arc::new(Mutex::new(ReplayState::default())),
+            window_size,
+        }
+    }
+
+    /// Checks whether the message has already been delivered.
+    ///
+    /// If the message must be dropped, because its sequence number is in the window or older than
+    /// the window, it is counted as a duplicate for the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer ID of the connection the message was received on
+    /// * `sender` - The ID of the service that sent the message
+    /// * `sequence_number` - The sequence number set by the sending service
+    pub fn check(
+        &self,
+        peer_id: &str,
+        sender: &str,
+        sequence_number: u64,
+    ) -> Result<ReplayCheck, InternalError> {
+        if sequence_number == 0 {
+            #[cfg(feature = "metrics")]
+            metrics::counter!(
+                "splinter.circuit.unsequenced_messages",
+                1,
+                "peer" => peer_id.to_string()
+            );
+
+            return Ok(ReplayCheck::Unsequenced);
+        }
+
+        let mut state = self.lock()?;
+        let result = match state
+            .senders
+            .get(&(peer_id.to_string(), sender.to_string()))
+        {
+            Some(window) if sequence_number <= window.floor => ReplayCheck::Expired,
+            Some(window) if window.seen.contains(&sequence_number) => ReplayCheck::Duplicate,
+            _ => ReplayCheck::New,
+        };
+
+        if result.is_replay() {
+            *state.duplicates.entry(peer_id.to_string()).or_insert(0) += 1;
+
+            #[cfg(feature = "metrics")]
+            metrics::counter!(
+                "splinter.circuit.duplicate_messages",
+                1,
+                "peer" => peer_id.to_string()
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// Records that the message has been delivered, so later copies are detected.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer ID of the connection the message was received on
+    /// * `sender` - The ID of the service that sent the message
+    /// * `sequence_number` - The sequence number set by the sending service
+    pub fn record(
+        &self,
+        peer_id: &str,
+        sender: &str,
+        sequence_number: u64,
+    ) -> Result<(), InternalError> {
+        if sequence_number == 0 {
+            return Ok(());
+        }
+
+        let mut state = self.lock()?;
+        let window = state
+            .senders
+            .entry((peer_id.to_string(), sender.to_string()))
+            .or_default();
+        if sequence_number <= window.floor || !window.seen.insert(sequence_number) {
+            return Ok(());
+        }
+
+        while window.seen.len() > self.window_size {
+            let lowest = match window.seen.iter().next() {
+                Some(lowest) => *lowest,
+                None => break,
+            };
+            window.seen.remove(&lowest);
+            window.floor = lowest;
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the windows and duplicate count for a peer, for example when it is removed
+    pub fn remove_peer(&self, peer_id: &str) -> Result<(), InternalError> {
+        let mut state = self.lock()?;
+        state
+            .senders
+            .retain(|(peer, _), _| peer.as_str() != peer_id);
+        state.duplicates.remove(peer_id);
+        Ok(())
+    }
+
+    /// Forgets the windows of a peer that the peer manager has removed.
+    ///
+    /// The windows of a peer that has only disconnected are kept, so that messages that are sent
+    /// again when it reconnects are still detected.
+    pub fn handle_peer_notification(
+        &self,
+        notification: &PeerManagerNotification,
+    ) -> Result<(), InternalError> {
+        match notification {
+            PeerManagerNotification::Removed { peer } => self.remove_peer(peer),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of messages dropped as duplicates or replays, by the peer they were
+    /// received from
+    pub fn duplicate_counts(&self) -> Result<HashMap<String, u64>, InternalError> {
+        Ok(self.lock()?.duplicates.clone())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<ReplayState>, InternalError> {
+        self.state
+            .lock()
+            .map_err(|_| InternalError::with_message("Replay window lock was poisoned".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verify that recorded sequence numbers are detected as duplicates for the same peer and
+    // sender only, that messages without a sequence number are delivered, and that out-of-order
+    // messages within the window are accepted.
+    #[test]
+    fn test_replay_window() {
+        let window = ReplayWindow::new(2);
+
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-a", "abc", 1).expect("Unable to check")
+        );
+        window.record("peer-a", "abc", 1).expect("Unable to record");
+        assert_eq!(
+            ReplayCheck::Duplicate,
+            window.check("peer-a", "abc", 1).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-a", "def", 1).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-b", "abc", 1).expect("Unable to check")
+        );
+
+        window.record("peer-a", "abc", 0).expect("Unable to record");
+        let unsequenced = window.check("peer-a", "abc", 0).expect("Unable to check");
+        assert_eq!(ReplayCheck::Unsequenced, unsequenced);
+        assert!(!unsequenced.is_replay());
+
+        window.record("peer-a", "abc", 3).expect("Unable to record");
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-a", "abc", 2).expect("Unable to check")
+        );
+
+        let counts = window.duplicate_counts().expect("Unable to get counts");
+        assert_eq!(Some(&1), counts.get("peer-a"));
+        assert_eq!(None, counts.get("peer-b"));
+    }
+
+    // Verify that the windows of a peer are forgotten when the peer manager removes it, but not
+    // when it only disconnects, and that the windows of other peers are kept.
+    #[test]
+    fn test_replay_window_peer_removed() {
+        let window = ReplayWindow::new(2);
+
+        window.record("peer-a", "abc", 1).expect("Unable to record");
+        window.record("peer-b", "abc", 1).expect("Unable to record");
+
+        window
+            .handle_peer_notification(&PeerManagerNotification::Disconnected {
+                peer: "peer-a".into(),
+            })
+            .expect("Unable to handle notification");
+        assert_eq!(
+            ReplayCheck::Duplicate,
+            window.check("peer-a", "abc", 1).expect("Unable to check")
+        );
+
+        window
+            .handle_peer_notification(&PeerManagerNotification::Removed {
+                peer: "peer-a".into(),
+            })
+            .expect("Unable to handle notification");
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-a", "abc", 1).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::Duplicate,
+            window.check("peer-b", "abc", 1).expect("Unable to check")
+        );
+
+        let counts = window.duplicate_counts().expect("Unable to get counts");
+        assert_eq!(None, counts.get("peer-a"));
+        assert_eq!(Some(&1), counts.get("peer-b"));
+    }
+
+    // Verify that flooding the window with new sequence numbers does not make it accept a message
+    // that was already delivered: the forgotten sequence numbers are dropped as expired.
+    #[test]
+    fn test_replay_window_flood() {
+        let window = ReplayWindow::new(2);
+
+        window.record("peer-a", "abc", 5).expect("Unable to record");
+        for sequence_number in 100..110 {
+            window
+                .record("peer-a", "abc", sequence_number)
+                .expect("Unable to record");
+        }
+
+        assert_eq!(
+            ReplayCheck::Expired,
+            window.check("peer-a", "abc", 5).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::Expired,
+            window.check("peer-a", "abc", 107).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::Duplicate,
+            window.check("peer-a", "abc", 109).expect("Unable to check")
+        );
+        assert_eq!(
+            ReplayCheck::New,
+            window.check("peer-a", "abc", 110).expect("Unable to check")
+        );
+    }
+}
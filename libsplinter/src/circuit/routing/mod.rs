@@ -41,6 +41,7 @@ use std::fmt;
 
 use self::error::RoutingTableReaderError;
 
+use crate::circuit::qos::QosLimits;
 use crate::error::InternalError;
 
 /// Interface for updating the routing table
@@ -186,6 +187,9 @@ pub struct Circuit {
     circuit_id: String,
     roster: Vec<Service>,
     members: Vec<String>,
+    relays: Vec<String>,
+    observers: Vec<String>,
+    qos: QosLimits,
 }
 
 impl Circuit {
@@ -201,9 +205,43 @@ impl Circuit {
             circuit_id,
             roster,
             members,
+            relays: vec![],
+            observers: vec![],
+            qos: QosLimits::default(),
         }
     }
 
+    /// Sets the members that may relay messages between members that are not directly connected
+    ///
+    /// # Arguments
+    ///
+    /// * `relays` - The list of node IDs of the relay members
+    pub fn with_relays(mut self, relays: Vec<String>) -> Self {
+        self.relays = relays;
+        self
+    }
+
+    /// Sets the members that only receive messages on the circuit; services on these members may
+    /// not send messages to other services
+    ///
+    /// # Arguments
+    ///
+    /// * `observers` - The list of node IDs of the observer members
+    pub fn with_observers(mut self, observers: Vec<String>) -> Self {
+        self.observers = observers;
+        self
+    }
+
+    /// Sets the quality-of-service limits of the circuit
+    ///
+    /// # Arguments
+    ///
+    /// * `qos` - The limits the circuit's messages are held to
+    pub fn with_qos(mut self, qos: QosLimits) -> Self {
+        self.qos = qos;
+        self
+    }
+
     /// Returns the ID of the circuit
     pub fn circuit_id(&self) -> &str {
         &self.circuit_id
@@ -218,6 +256,21 @@ impl Circuit {
     pub fn members(&self) -> &[String] {
         &self.members
     }
+
+    /// Returns the list of node IDs that may relay messages for other members
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    /// Returns the list of node IDs that only observe the circuit
+    pub fn observers(&self) -> &[String] {
+        &self.observers
+    }
+
+    /// Returns the quality-of-service limits of the circuit
+    pub fn qos(&self) -> &QosLimits {
+        &self.qos
+    }
 }
 
 /// The routing table representation of a node
@@ -237,6 +290,16 @@ impl CircuitNode {
     pub fn new(node_id: String, endpoints: Vec<String>) -> Self {
         CircuitNode { node_id, endpoints }
     }
+
+    /// Returns the ID of the node
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Returns the list of endpoints the node can be reached at
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
 }
 
 impl Ord for CircuitNode {
@@ -0,0 +1,515 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payload schemas for messages sent to services on a circuit.
+//!
+//! A service may declare the payloads it accepts by setting the `payload_schema` argument when the
+//! circuit is created. The value is a JSON Schema document, and payloads sent to the service by
+//! services connected to this node must then be JSON documents that conform to it.
+//!
+//! Only a subset of JSON Schema is supported: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties` (as a boolean), `items`, `minLength`, `maxLength`, `minimum`,
+//! `maximum`, `minItems` and `maxItems`. The annotation keywords `$schema`, `$id`, `$comment`,
+//! `title`, `description`, `default` and `examples` are accepted and ignored. Any other keyword
+//! is rejected when the schema is parsed, so a schema is never silently weaker than intended.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Map, Value};
+
+use crate::circuit::routing::Service;
+use crate::error::{InternalError, InvalidArgumentError};
+
+/// The service argument that holds the payload schema of a service.
+pub const PAYLOAD_SCHEMA_ARG: &str = "payload_schema";
+
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// A parsed payload schema.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PayloadSchema {
+    types: Option<Vec<JsonType>>,
+    allowed_values: Option<Vec<Value>>,
+    properties: BTreeMap<String, PayloadSchema>,
+    required: Vec<String>,
+    additional_properties: bool,
+    items: Option<Box<PayloadSchema>>,
+    min_length: Option<u64>,
+    max_length: Option<u64>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    min_items: Option<u64>,
+    max_items: Option<u64>,
+}
+
+impl PayloadSchema {
+    /// Parses a JSON Schema document.
+    ///
+    /// Returns an `InvalidArgumentError` if the document is not valid JSON or uses a keyword that
+    /// is not supported.
+    pub fn parse(schema: &str) -> Result<Self, InvalidArgumentError> {
+        let value: Value = serde_json::from_str(schema).map_err(|err| {
+            InvalidArgumentError::new(
+                PAYLOAD_SCHEMA_ARG.into(),
+                format!("schema is not valid JSON: {}", err),
+            )
+        })?;
+
+        Self::from_value(&value, "#")
+    }
+
+    fn from_value(value: &Value, path: &str) -> Result<Self, InvalidArgumentError> {
+        let invalid = |message: String| {
+            InvalidArgumentError::new(PAYLOAD_SCHEMA_ARG.into(), format!("{}: {}", path, message))
+        };
+
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("schema must be an object".into()))?;
+
+        let mut schema = PayloadSchema {
+            additional_properties: true,
+            ..Default::default()
+        };
+
+        for (keyword, value) in object {
+            match keyword.as_str() {
+                "type" => {
+                    let names = match value {
+                        Value::String(name) => vec![name.as_str()],
+                        Value::Array(names) => names
+                            .iter()
+                            .map(|name| {
+                                name.as_str()
+                                    .ok_or_else(|| invalid("type names must be strings".into()))
+                            })
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(invalid("type must be a string or an array".into())),
+                    };
+                    schema.types = Some(
+                        names
+                            .into_iter()
+                            .map(|name| {
+                                JsonType::from_name(name)
+                                    .ok_or_else(|| invalid(format!("unknown type {}", name)))
+                            })
+                            .collect::<Result<_, _>>()?,
+                    );
+                }
+                "enum" => {
+                    schema.allowed_values = Some(
+                        value
+                            .as_array()
+                            .ok_or_else(|| invalid("enum must be an array".into()))?
+                            .clone(),
+                    )
+                }
+                "const" => schema.allowed_values = Some(vec![value.clone()]),
+                "properties" => {
+                    let properties = value
+                        .as_object()
+                        .ok_or_else(|| invalid("properties must be an object".into()))?;
+                    for (name, property) in properties {
+                        schema.properties.insert(
+                            name.to_string(),
+                            Self::from_value(property, &format!("{}/properties/{}", path, name))?,
+                        );
+                    }
+                }
+                "required" => {
+                    schema.required = value
+                        .as_array()
+                        .ok_or_else(|| invalid("required must be an array".into()))?
+                        .iter()
+                        .map(|name| {
+                            name.as_str()
+                                .map(String::from)
+                                .ok_or_else(|| invalid("required names must be strings".into()))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "additionalProperties" => {
+                    schema.additional_properties = value
+                        .as_bool()
+                        .ok_or_else(|| invalid("additionalProperties must be a boolean".into()))?
+                }
+                "items" => {
+                    schema.items = Some(Box::new(Self::from_value(
+                        value,
+                        &format!("{}/items", path),
+                    )?))
+                }
+                "minLength" => schema.min_length = Some(as_count(keyword, value, &invalid)?),
+                "maxLength" => schema.max_length = Some(as_count(keyword, value, &invalid)?),
+                "minItems" => schema.min_items = Some(as_count(keyword, value, &invalid)?),
+                "maxItems" => schema.max_items = Some(as_count(keyword, value, &invalid)?),
+                "minimum" => {
+                    schema.minimum = Some(
+                        value
+                            .as_f64()
+                            .ok_or_else(|| invalid("minimum must be a number".into()))?,
+                    )
+                }
+                "maximum" => {
+                    schema.maximum = Some(
+                        value
+                            .as_f64()
+                            .ok_or_else(|| invalid("maximum must be a number".into()))?,
+                    )
+                }
+                keyword if ANNOTATION_KEYWORDS.contains(&keyword) => (),
+                keyword => return Err(invalid(format!("unsupported keyword {}", keyword))),
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Checks that the payload is a JSON document that conforms to the schema.
+    ///
+    /// Returns an `InvalidArgumentError` describing the first violation found.
+    pub fn validate(&self, payload: &[u8]) -> Result<(), InvalidArgumentError> {
+        let value: Value = serde_json::from_slice(payload).map_err(|err| {
+            InvalidArgumentError::new(
+                "payload".into(),
+                format!("payload is not valid JSON: {}", err),
+            )
+        })?;
+
+        self.check(&value, "#")
+            .map_err(|message| InvalidArgumentError::new("payload".into(), message))
+    }
+
+    fn check(&self, value: &Value, path: &str) -> Result<(), String> {
+        if let Some(types) = &self.types {
+            if !types.iter().any(|json_type| json_type.matches(value)) {
+                return Err(format!(
+                    "{}: expected {}",
+                    path,
+                    types
+                        .iter()
+                        .map(|json_type| json_type.name())
+                        .collect::<Vec<_>>()
+                        .join(" or ")
+                ));
+            }
+        }
+
+        if let Some(allowed_values) = &self.allowed_values {
+            if !allowed_values.contains(value) {
+                return Err(format!("{}: value is not one of the allowed values", path));
+            }
+        }
+
+        match value {
+            Value::String(string) => {
+                let length = string.chars().count() as u64;
+                check_bounds(path, "length", length, self.min_length, self.max_length)?;
+            }
+            Value::Number(number) => {
+                // serde_json numbers are always representable as f64
+                let number = number.as_f64().unwrap_or_default();
+                if self.minimum.map(|min| number < min).unwrap_or(false)
+                    || self.maximum.map(|max| number > max).unwrap_or(false)
+                {
+                    return Err(format!("{}: {} is out of range", path, number));
+                }
+            }
+            Value::Array(items) => {
+                check_bounds(
+                    path,
+                    "item count",
+                    items.len() as u64,
+                    self.min_items,
+                    self.max_items,
+                )?;
+                if let Some(item_schema) = &self.items {
+                    for (i, item) in items.iter().enumerate() {
+                        item_schema.check(item, &format!("{}/{}", path, i))?;
+                    }
+                }
+            }
+            Value::Object(object) => self.check_object(object, path)?,
+            Value::Null | Value::Bool(_) => (),
+        }
+
+        Ok(())
+    }
+
+    fn check_object(&self, object: &Map<String, Value>, path: &str) -> Result<(), String> {
+        if let Some(missing) = self
+            .required
+            .iter()
+            .find(|name| !object.contains_key(*name))
+        {
+            return Err(format!("{}: missing required property {}", path, missing));
+        }
+
+        for (name, value) in object {
+            match self.properties.get(name) {
+                Some(property) => property.check(value, &format!("{}/{}", path, name))?,
+                None if !self.additional_properties => {
+                    return Err(format!("{}: unexpected property {}", path, name))
+                }
+                None => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates payloads against the schemas of the services they are sent to.
+///
+/// Parsed schemas are cached by their text, so a schema is only parsed the first time a message is
+/// sent to a service using it.
+#[derive(Default)]
+pub struct PayloadValidator {
+    schemas: Mutex<HashMap<String, Arc<PayloadSchema>>>,
+}
+
+impl PayloadValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the payload against the schema of the recipient service.
+    ///
+    /// Returns `Ok(Ok(()))` if the service does not have a schema or the payload conforms to it,
+    /// and `Ok(Err(_))` describing the violation otherwise.
+    pub fn validate(
+        &self,
+        recipient: &Service,
+        payload: &[u8],
+    ) -> Result<Result<(), InvalidArgumentError>, InternalError> {
+        let schema_text = match recipient
+            .arguments()
+            .iter()
+            .find(|(key, _)| key == PAYLOAD_SCHEMA_ARG)
+        {
+            Some((_, schema_text)) => schema_text,
+            None => return Ok(Ok(())),
+        };
+
+        let schema = {
+            let mut schemas = self.schemas.lock().map_err(|_| {
+                InternalError::with_message("Payload schema cache lock was poisoned".into())
+            })?;
+
+            match schemas.get(schema_text) {
+                Some(schema) => schema.clone(),
+                None => {
+                    let schema = match PayloadSchema::parse(schema_text) {
+                        Ok(schema) => Arc::new(schema),
+                        Err(err) => return Ok(Err(err)),
+                    };
+                    schemas.insert(schema_text.to_string(), schema.clone());
+                    schema
+                }
+            }
+        };
+
+        Ok(schema.validate(payload))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum JsonType {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    Integer,
+    String,
+}
+
+impl JsonType {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "null" => Some(JsonType::Null),
+            "boolean" => Some(JsonType::Boolean),
+            "object" => Some(JsonType::Object),
+            "array" => Some(JsonType::Array),
+            "number" => Some(JsonType::Number),
+            "integer" => Some(JsonType::Integer),
+            "string" => Some(JsonType::String),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Object => "object",
+            JsonType::Array => "array",
+            JsonType::Number => "number",
+            JsonType::Integer => "integer",
+            JsonType::String => "string",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (JsonType::Null, Value::Null)
+            | (JsonType::Boolean, Value::Bool(_))
+            | (JsonType::Object, Value::Object(_))
+            | (JsonType::Array, Value::Array(_))
+            | (JsonType::Number, Value::Number(_))
+            | (JsonType::String, Value::String(_)) => true,
+            (JsonType::Integer, Value::Number(number)) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().map(|n| n.fract() == 0.0).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn as_count<F>(keyword: &str, value: &Value, invalid: &F) -> Result<u64, InvalidArgumentError>
+where
+    F: Fn(String) -> InvalidArgumentError,
+{
+    value
+        .as_u64()
+        .ok_or_else(|| invalid(format!("{} must be a non-negative integer", keyword)))
+}
+
+fn check_bounds(
+    path: &str,
+    what: &str,
+    actual: u64,
+    min: Option<u64>,
+    max: Option<u64>,
+) -> Result<(), String> {
+    if min.map(|min| actual < min).unwrap_or(false) || max.map(|max| actual > max).unwrap_or(false)
+    {
+        Err(format!("{}: {} {} is out of range", path, what, actual))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["action", "amount"],
+        "additionalProperties": false,
+        "properties": {
+            "action": { "enum": ["deposit", "withdraw"] },
+            "amount": { "type": "integer", "minimum": 1, "maximum": 1000 },
+            "memo": { "type": "string", "maxLength": 8 },
+            "tags": { "type": "array", "maxItems": 2, "items": { "type": "string" } }
+        }
+    }"#;
+
+    // Test that payloads are checked against each of the supported keywords, and that the first
+    // violation is reported with the path to the offending value
+    #[test]
+    fn test_validate() {
+        let schema = PayloadSchema::parse(SCHEMA).expect("Unable to parse schema");
+
+        assert!(schema
+            .validate(br#"{"action": "deposit", "amount": 10, "tags": ["a"]}"#)
+            .is_ok());
+
+        let rejected = |payload: &[u8]| match schema.validate(payload) {
+            Ok(()) => panic!("Payload should have been rejected"),
+            Err(err) => err.message(),
+        };
+
+        assert!(rejected(b"not json").starts_with("payload is not valid JSON"));
+        assert_eq!(
+            "#: missing required property amount",
+            rejected(br#"{"action": "deposit"}"#)
+        );
+        assert_eq!(
+            "#/action: value is not one of the allowed values",
+            rejected(br#"{"action": "steal", "amount": 1}"#)
+        );
+        assert_eq!(
+            "#/amount: expected integer",
+            rejected(br#"{"action": "deposit", "amount": 1.5}"#)
+        );
+        assert_eq!(
+            "#/amount: 0 is out of range",
+            rejected(br#"{"action": "deposit", "amount": 0}"#)
+        );
+        assert_eq!(
+            "#/memo: length 9 is out of range",
+            rejected(br#"{"action": "deposit", "amount": 1, "memo": "123456789"}"#)
+        );
+        assert_eq!(
+            "#/tags/1: expected string",
+            rejected(br#"{"action": "deposit", "amount": 1, "tags": ["a", 2]}"#)
+        );
+        assert_eq!(
+            "#: unexpected property extra",
+            rejected(br#"{"action": "deposit", "amount": 1, "extra": true}"#)
+        );
+    }
+
+    // Test that schemas that are not valid JSON or use unsupported keywords are rejected
+    #[test]
+    fn test_parse_invalid() {
+        assert!(PayloadSchema::parse("{").is_err());
+        assert!(PayloadSchema::parse(r#"{"type": "decimal"}"#).is_err());
+
+        let err = PayloadSchema::parse(r#"{"properties": {"a": {"pattern": "^a$"}}}"#)
+            .expect_err("Schema should have been rejected");
+        assert_eq!("#/properties/a: unsupported keyword pattern", err.message());
+    }
+
+    // Test that the validator only checks payloads sent to services with a schema
+    #[test]
+    fn test_validator() {
+        let validator = PayloadValidator::new();
+
+        let unchecked = Service::new("abc".into(), "test".into(), "123".into(), vec![]);
+        assert!(validator
+            .validate(&unchecked, b"anything")
+            .expect("Unable to validate")
+            .is_ok());
+
+        let checked = Service::new(
+            "def".into(),
+            "test".into(),
+            "123".into(),
+            vec![(PAYLOAD_SCHEMA_ARG.into(), SCHEMA.into())],
+        );
+        assert!(validator
+            .validate(&checked, br#"{"action": "withdraw", "amount": 5}"#)
+            .expect("Unable to validate")
+            .is_ok());
+        assert!(validator
+            .validate(&checked, b"anything")
+            .expect("Unable to validate")
+            .is_err());
+    }
+}
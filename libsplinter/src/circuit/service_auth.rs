@@ -0,0 +1,275 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Authentication of services connecting to the node's service endpoint.
+//!
+//! A service whose definition in the circuit includes a `service_public_key` argument must prove
+//! that it holds the matching private key before it is allowed to connect. The service first
+//! sends a `ServiceAuthChallengeRequest`, which the node answers with a random nonce that is
+//! remembered for the service's connection. The service then signs the message returned by
+//! [`challenge_message`] and includes the signature in its `ServiceConnectRequest`. Each nonce can
+//! only be used for a single connect request.
+//!
+//! Services without a registered key connect without a challenge, as before.
+//!
+//! [`challenge_message`]: fn.challenge_message.html
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
+use rand::Rng;
+
+use crate::circuit::routing::{Service, ServiceId};
+use crate::hex::{parse_hex, to_hex};
+
+/// The service argument that holds the hex-encoded public key of a service.
+pub const SERVICE_PUBLIC_KEY_ARGUMENT: &str = "service_public_key";
+
+const NONCE_LENGTH: usize = 32;
+const CHALLENGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The nonces issued to connecting services, keyed by the connection's peer ID and the service.
+///
+/// `ServiceChallenges` is shared between the challenge and connect request handlers of the circuit
+/// dispatcher.
+#[derive(Clone, Default)]
+pub struct ServiceChallenges {
+    nonces: Arc<Mutex<HashMap<(String, ServiceId), (Vec<u8>, Instant)>>>,
+}
+
+impl ServiceChallenges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a nonce for the service connecting over the given connection, replacing any nonce
+    /// issued to it before.
+    pub(crate) fn issue(&self, peer_id: &str, service_id: ServiceId) -> Vec<u8> {
+        let mut nonce = vec![0u8; NONCE_LENGTH];
+        rand::thread_rng().fill(&mut nonce[..]);
+
+        match self.nonces.lock() {
+            Ok(mut nonces) => {
+                // Challenges that were never answered are dropped once they expire
+                nonces.retain(|_, (_, issued)| issued.elapsed() < CHALLENGE_TIMEOUT);
+                nonces.insert(
+                    (peer_id.to_string(), service_id),
+                    (nonce.clone(), Instant::now()),
+                );
+            }
+            Err(_) => error!("Service challenges lock was poisoned"),
+        }
+
+        nonce
+    }
+
+    /// Checks that the service connecting over the given connection signed the nonce issued to
+    /// it with the key registered for the service. Services without a registered key are always
+    /// authenticated.
+    ///
+    /// Returns a description of the failure if the service is not authenticated.
+    pub(crate) fn authenticate(
+        &self,
+        peer_id: &str,
+        service_id: &ServiceId,
+        service: &Service,
+        signature: &[u8],
+    ) -> Result<(), String> {
+        let public_key = match registered_key(service)? {
+            Some(public_key) => public_key,
+            None => return Ok(()),
+        };
+
+        let nonce = self.take(peer_id, service_id).ok_or_else(|| {
+            format!(
+                "Service {} must request a challenge before connecting",
+                service_id
+            )
+        })?;
+
+        if signature.is_empty() {
+            return Err(format!(
+                "Service {} must sign its challenge to connect",
+                service_id
+            ));
+        }
+
+        let verified = Secp256k1Context::new()
+            .new_verifier()
+            .verify(
+                &challenge_message(service_id.circuit(), service_id.service_id(), &nonce),
+                &Signature::new(signature.to_vec()),
+                &PublicKey::new(public_key),
+            )
+            .unwrap_or(false);
+        if verified {
+            Ok(())
+        } else {
+            Err(format!(
+                "Service {} did not sign its challenge with its registered key",
+                service_id
+            ))
+        }
+    }
+
+    /// Removes and returns the nonce issued to the service on the given connection, unless it has
+    /// expired.
+    fn take(&self, peer_id: &str, service_id: &ServiceId) -> Option<Vec<u8>> {
+        let mut nonces = match self.nonces.lock() {
+            Ok(nonces) => nonces,
+            Err(_) => {
+                error!("Service challenges lock was poisoned");
+                return None;
+            }
+        };
+
+        nonces
+            .remove(&(peer_id.to_string(), service_id.clone()))
+            .filter(|(_, issued)| issued.elapsed() < CHALLENGE_TIMEOUT)
+            .map(|(nonce, _)| nonce)
+    }
+}
+
+/// Returns the message a service signs to answer a challenge.
+pub fn challenge_message(circuit: &str, service_id: &str, nonce: &[u8]) -> Vec<u8> {
+    format!(
+        "splinter-service-connect:{}:{}:{}",
+        circuit,
+        service_id,
+        to_hex(nonce)
+    )
+    .into_bytes()
+}
+
+/// Returns the public key registered for the service in its circuit definition, if any.
+fn registered_key(service: &Service) -> Result<Option<Vec<u8>>, String> {
+    service
+        .arguments()
+        .iter()
+        .find(|(key, _)| key == SERVICE_PUBLIC_KEY_ARGUMENT)
+        .map(|(_, value)| {
+            parse_hex(value).map_err(|err| {
+                format!(
+                    "Service {} has an invalid {} argument: {}",
+                    service.service_id(),
+                    SERVICE_PUBLIC_KEY_ARGUMENT,
+                    err
+                )
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::Signer;
+
+    fn service(public_key: Option<&str>) -> Service {
+        let arguments = public_key
+            .map(|key| vec![(SERVICE_PUBLIC_KEY_ARGUMENT.to_string(), key.to_string())])
+            .unwrap_or_default();
+        Service::new(
+            "abcd".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            arguments,
+        )
+    }
+
+    fn service_id() -> ServiceId {
+        ServiceId::new("alpha".to_string(), "abcd".to_string())
+    }
+
+    fn sign(signer: &dyn Signer, nonce: &[u8]) -> Vec<u8> {
+        signer
+            .sign(&challenge_message("alpha", "abcd", nonce))
+            .expect("Failed to sign challenge")
+            .take_bytes()
+    }
+
+    /// Verify that a service that signs its challenge with its registered key is authenticated,
+    /// and that the challenge cannot be used again.
+    #[test]
+    fn test_authenticate_signed_challenge() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let public_key = to_hex(
+            signer
+                .public_key()
+                .expect("Failed to get public key")
+                .as_slice(),
+        );
+        let service = service(Some(&public_key));
+        let challenges = ServiceChallenges::new();
+
+        let nonce = challenges.issue("peer", service_id());
+        let signature = sign(&*signer, &nonce);
+        assert_eq!(
+            Ok(()),
+            challenges.authenticate("peer", &service_id(), &service, &signature)
+        );
+
+        assert!(challenges
+            .authenticate("peer", &service_id(), &service, &signature)
+            .is_err());
+    }
+
+    /// Verify that a service is not authenticated if it signs with another key, answers a
+    /// challenge issued to another connection, or does not sign its challenge.
+    #[test]
+    fn test_authenticate_rejected() {
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let other_signer = context.new_signer(context.new_random_private_key());
+        let public_key = to_hex(
+            signer
+                .public_key()
+                .expect("Failed to get public key")
+                .as_slice(),
+        );
+        let service = service(Some(&public_key));
+        let challenges = ServiceChallenges::new();
+
+        let nonce = challenges.issue("peer", service_id());
+        let signature = sign(&*other_signer, &nonce);
+        assert!(challenges
+            .authenticate("peer", &service_id(), &service, &signature)
+            .is_err());
+
+        let nonce = challenges.issue("peer", service_id());
+        let signature = sign(&*signer, &nonce);
+        assert!(challenges
+            .authenticate("rogue", &service_id(), &service, &signature)
+            .is_err());
+
+        challenges.issue("peer", service_id());
+        assert!(challenges
+            .authenticate("peer", &service_id(), &service, &[])
+            .is_err());
+    }
+
+    /// Verify that a service without a registered key is authenticated without a challenge.
+    #[test]
+    fn test_authenticate_no_registered_key() {
+        let challenges = ServiceChallenges::new();
+        assert_eq!(
+            Ok(()),
+            challenges.authenticate("peer", &service_id(), &service(None), &[])
+        );
+    }
+}
@@ -15,9 +15,17 @@
 use std::error::Error;
 use std::fmt::{self, Write};
 
-#[cfg(feature = "admin-service")]
+#[cfg(any(
+    feature = "admin-service",
+    feature = "outbound-spool",
+    feature = "protos-serde"
+))]
 use serde::de;
-#[cfg(feature = "admin-service")]
+#[cfg(any(
+    feature = "admin-service",
+    feature = "outbound-spool",
+    feature = "protos-serde"
+))]
 use serde::{Deserializer, Serializer};
 
 pub fn to_hex(bytes: &[u8]) -> String {
@@ -29,7 +37,12 @@ pub fn to_hex(bytes: &[u8]) -> String {
     buf
 }
 
-#[cfg(feature = "admin-service")]
+#[cfg(any(
+    feature = "admin-service",
+    feature = "outbound-spool",
+    feature = "protos-serde",
+    feature = "service-endpoint-auth"
+))]
 pub fn parse_hex(hex: &str) -> Result<Vec<u8>, HexError> {
     if hex.len() % 2 != 0 {
         return Err(HexError {
@@ -51,7 +64,11 @@ pub fn parse_hex(hex: &str) -> Result<Vec<u8>, HexError> {
     Ok(res)
 }
 
-#[cfg(feature = "admin-service")]
+#[cfg(any(
+    feature = "admin-service",
+    feature = "outbound-spool",
+    feature = "protos-serde"
+))]
 pub fn as_hex<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -59,7 +76,11 @@ where
     serializer.serialize_str(&to_hex(data))
 }
 
-#[cfg(feature = "admin-service")]
+#[cfg(any(
+    feature = "admin-service",
+    feature = "outbound-spool",
+    feature = "protos-serde"
+))]
 pub fn deserialize_hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
@@ -0,0 +1,202 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Crash-safe replacement of small state files.
+//!
+//! A state file is replaced by first writing the new contents to a journal file next to it
+//! (`<path>.tmp`) and syncing it to disk. The current contents are then copied to the newest of a
+//! small set of rotating backups (`<path>.bak.1` is the newest, `<path>.bak.<n>` the oldest), and
+//! finally the journal file is renamed over the state file. The state file always holds either the
+//! previous or the new contents, never a partially written file.
+//!
+//! When a state file is loaded, its contents are verified by the caller. If the file is missing or
+//! fails verification, the journal file and then the backups are tried in turn, and the first one
+//! that passes verification is restored as the state file.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The number of previous versions that are kept for each state file
+pub(crate) const STATE_BACKUPS: usize = 3;
+
+/// Replaces the contents of the file at `path`, keeping up to `backups` previous versions.
+pub(crate) fn write_journaled(path: &str, contents: &[u8], backups: usize) -> io::Result<()> {
+    let journal_path = journal_path(path);
+    let mut journal = File::create(&journal_path)?;
+    journal.write_all(contents)?;
+    journal.sync_all()?;
+
+    if backups > 0 && Path::new(path).is_file() {
+        for index in (1..backups).rev() {
+            let backup = backup_path(path, index);
+            if Path::new(&backup).is_file() {
+                fs::rename(&backup, backup_path(path, index + 1))?;
+            }
+        }
+        let newest_backup = backup_path(path, 1);
+        fs::copy(path, &newest_backup)?;
+        File::open(&newest_backup)?.sync_all()?;
+        // The rotated backups must be durable before the file they were copied from is replaced
+        sync_parent_dir(path)?;
+    }
+
+    fs::rename(&journal_path, path)?;
+    sync_parent_dir(path)
+}
+
+/// Loads the file at `path` with the given `load` function, falling back to the journal file and
+/// then the backups, newest first, if it cannot be loaded.
+///
+/// A version that is recovered this way is restored as the file at `path`. If no version can be
+/// loaded, the error from loading `path` is returned.
+pub(crate) fn read_verified<T, E, F>(path: &str, backups: usize, mut load: F) -> Result<T, E>
+where
+    F: FnMut(&str) -> Result<T, E>,
+    E: std::fmt::Display,
+{
+    let err = match load(path) {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+
+    let candidates = std::iter::once(journal_path(path))
+        .chain((1..=backups).map(|index| backup_path(path, index)));
+    for candidate in candidates {
+        if !Path::new(&candidate).is_file() {
+            continue;
+        }
+        match load(&candidate) {
+            Ok(value) => {
+                warn!(
+                    "Unable to load '{}' ({}); recovered the previous version from '{}'",
+                    path, err, candidate
+                );
+                if let Err(restore_err) = restore(&candidate, path) {
+                    warn!(
+                        "Unable to restore '{}' from '{}': {}",
+                        path, candidate, restore_err
+                    );
+                }
+                return Ok(value);
+            }
+            Err(candidate_err) => debug!("Unable to load '{}': {}", candidate, candidate_err),
+        }
+    }
+
+    Err(err)
+}
+
+/// Returns `true` if the file at `path`, its journal file, or any of its backups exist.
+pub(crate) fn exists(path: &str, backups: usize) -> bool {
+    Path::new(path).is_file()
+        || Path::new(&journal_path(path)).is_file()
+        || (1..=backups).any(|index| Path::new(&backup_path(path, index)).is_file())
+}
+
+/// Copies the recovered version at `source` over the file at `path` through the journal file.
+fn restore(source: &str, path: &str) -> io::Result<()> {
+    let journal_path = journal_path(path);
+    if source != journal_path {
+        fs::copy(source, &journal_path)?;
+    }
+    File::open(&journal_path)?.sync_all()?;
+    fs::rename(&journal_path, path)?;
+    sync_parent_dir(path)
+}
+
+/// Syncs the directory containing `path`, so that a rename within it is durable.
+fn sync_parent_dir(path: &str) -> io::Result<()> {
+    let parent = match Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+fn journal_path(path: &str) -> String {
+    format!("{}.tmp", path)
+}
+
+fn backup_path(path: &str, index: usize) -> String {
+    format!("{}.bak.{}", path, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that each write keeps the previous versions as backups, up to the backup count.
+    #[test]
+    fn test_write_journaled_rotates_backups() {
+        let temp_dir = TempDir::new("test_write_journaled_rotates_backups")
+            .expect("Failed to create temp dir");
+        let path = temp_dir
+            .path()
+            .join("state.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        for version in 1..=4 {
+            write_journaled(&path, format!("{}", version).as_bytes(), 2)
+                .expect("Failed to write file");
+        }
+
+        assert_eq!(fs::read_to_string(&path).expect("Failed to read file"), "4");
+        assert_eq!(
+            fs::read_to_string(backup_path(&path, 1)).expect("Failed to read backup"),
+            "3"
+        );
+        assert_eq!(
+            fs::read_to_string(backup_path(&path, 2)).expect("Failed to read backup"),
+            "2"
+        );
+        assert!(!Path::new(&backup_path(&path, 3)).exists());
+        assert!(!Path::new(&journal_path(&path)).exists());
+    }
+
+    /// Verify that a file that fails verification is recovered from the newest valid backup and
+    /// restored, and that the original error is returned when there is nothing to recover.
+    #[test]
+    fn test_read_verified_recovers_backup() {
+        let temp_dir =
+            TempDir::new("test_read_verified_recovers_backup").expect("Failed to create temp dir");
+        let path = temp_dir
+            .path()
+            .join("state.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        let load = |path: &str| -> Result<u32, String> {
+            fs::read_to_string(path)
+                .map_err(|err| err.to_string())?
+                .parse::<u32>()
+                .map_err(|err| err.to_string())
+        };
+
+        assert!(read_verified(&path, 2, load).is_err());
+
+        write_journaled(&path, b"1", 2).expect("Failed to write file");
+        write_journaled(&path, b"2", 2).expect("Failed to write file");
+        fs::write(&path, b"corrupt").expect("Failed to corrupt file");
+
+        assert!(exists(&path, 2));
+        assert_eq!(read_verified(&path, 2, load), Ok(1));
+        assert_eq!(fs::read_to_string(&path).expect("Failed to read file"), "1");
+    }
+}
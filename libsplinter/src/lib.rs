@@ -77,6 +77,11 @@ pub mod error;
 #[cfg(feature = "events")]
 pub mod events;
 mod hex;
+#[cfg(all(
+    feature = "yaml-state-journal",
+    any(feature = "admin-service", feature = "registry")
+))]
+mod journal;
 pub mod keys;
 pub mod mesh;
 pub mod migrations;
@@ -91,11 +96,18 @@ pub mod protos;
 pub mod registry;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
+#[cfg(feature = "runtime-diagnostics")]
+pub mod runtime;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
 pub mod service;
 pub mod sets;
+pub mod signing;
 #[cfg(feature = "store-factory")]
 pub mod store;
-mod threading;
+#[cfg(feature = "tenancy")]
+pub mod tenant;
+pub mod threading;
 pub mod transport;
 
 #[cfg(feature = "rest-api")]
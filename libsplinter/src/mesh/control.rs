@@ -32,10 +32,19 @@ impl Control {
         Control { tx }
     }
 
-    pub fn add(&self, connection: Box<dyn Connection>) -> Result<Outgoing, AddError> {
+    /// Add a connection to the reactor. If `outgoing_capacity` is `None`, the mesh's default
+    /// outgoing capacity is used.
+    pub fn add(
+        &self,
+        connection: Box<dyn Connection>,
+        outgoing_capacity: Option<usize>,
+        send_weight: usize,
+    ) -> Result<Outgoing, AddError> {
         let (response_tx, response_rx) = crossbeam_channel::bounded(1);
         self.tx.send(ControlRequest::Add(AddRequest {
             connection,
+            outgoing_capacity,
+            send_weight,
             response_tx,
         }))?;
         match response_rx.recv() {
@@ -71,6 +80,8 @@ pub(super) enum ControlRequest {
 
 pub(super) struct AddRequest {
     pub connection: Box<dyn Connection>,
+    pub outgoing_capacity: Option<usize>,
+    pub send_weight: usize,
     pub response_tx: crossbeam_channel::Sender<AddResponse>,
 }
 
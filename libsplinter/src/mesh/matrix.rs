@@ -18,7 +18,7 @@ use crate::transport::matrix::{
     ConnectionMatrixAddError, ConnectionMatrixEnvelope, ConnectionMatrixLifeCycle,
     ConnectionMatrixReceiver, ConnectionMatrixRecvError, ConnectionMatrixRecvTimeoutError,
     ConnectionMatrixRemoveError, ConnectionMatrixSendError, ConnectionMatrixSender,
-    ConnectionMatrixShutdown,
+    ConnectionMatrixShutdown, SendLimits,
 };
 use crate::transport::Connection;
 
@@ -50,6 +50,22 @@ impl ConnectionMatrixLifeCycle for MeshLifeCycle {
         })
     }
 
+    fn add_with_send_limits(
+        &self,
+        connection: Box<dyn Connection>,
+        id: String,
+        send_limits: SendLimits,
+    ) -> Result<usize, ConnectionMatrixAddError> {
+        self.mesh
+            .add_with_send_limits(connection, id, send_limits)
+            .map_err(|err| {
+                ConnectionMatrixAddError::new(
+                    "Unable to add connection to matrix".to_string(),
+                    Some(Box::new(err)),
+                )
+            })
+    }
+
     fn remove(&self, id: &str) -> Result<Box<dyn Connection>, ConnectionMatrixRemoveError> {
         self.mesh.remove(id).map_err(|err| {
             ConnectionMatrixRemoveError::new(
@@ -48,6 +48,9 @@
 //!    be a more efficient implementation.
 //! 3. Backpressure should be built in. This means all queues should be bounded so that a
 //!    backpressure error can be returned when the queue is full.
+//! 4. Connections should share the reactor fairly. Sends are scheduled across the outgoing queues
+//!    in weighted round-robin order, so a Connection with many queued sends cannot starve the
+//!    others.
 
 mod control;
 mod incoming;
@@ -55,6 +58,7 @@ mod matrix;
 mod outgoing;
 mod pool;
 mod reactor;
+mod scheduler;
 
 use std::collections::HashMap;
 use std::error::Error;
@@ -73,6 +77,8 @@ pub use crate::transport::matrix::ConnectionMatrixEnvelope as Envelope;
 
 use crate::collections::BiHashMap;
 use crate::mesh::reactor::Reactor;
+pub use crate::mesh::scheduler::DEFAULT_SEND_WEIGHT;
+use crate::transport::matrix::SendLimits;
 use crate::transport::Connection;
 
 /// Wrapper around payload to include connection id
@@ -139,9 +145,43 @@ impl Mesh {
         &self,
         connection: Box<dyn Connection>,
         unique_id: String,
+    ) -> Result<usize, AddError> {
+        self.add_connection(connection, unique_id, None, DEFAULT_SEND_WEIGHT)
+    }
+
+    /// Add a new connection to the mesh with its own outgoing queue capacity and send weight.
+    ///
+    /// When several connections have queued sends, each connection may send up to its send weight
+    /// in messages in turn. Sends to the connection fail with `SendError::Full` once its outgoing
+    /// capacity is queued. Limits that are not set default to the mesh's outgoing capacity and
+    /// `DEFAULT_SEND_WEIGHT`.
+    pub fn add_with_send_limits(
+        &self,
+        connection: Box<dyn Connection>,
+        unique_id: String,
+        send_limits: SendLimits,
+    ) -> Result<usize, AddError> {
+        self.add_connection(
+            connection,
+            unique_id,
+            send_limits.outgoing_capacity(),
+            // a connection with no weight would never be scheduled
+            send_limits
+                .send_weight()
+                .unwrap_or(DEFAULT_SEND_WEIGHT)
+                .max(1),
+        )
+    }
+
+    fn add_connection(
+        &self,
+        connection: Box<dyn Connection>,
+        unique_id: String,
+        outgoing_capacity: Option<usize>,
+        send_weight: usize,
     ) -> Result<usize, AddError> {
         let mut state = self.state.write().map_err(|_| AddError::PoisonedLock)?;
-        let outgoing = self.ctrl.add(connection)?;
+        let outgoing = self.ctrl.add(connection, outgoing_capacity, send_weight)?;
         let mesh_id = outgoing.id();
 
         state.outgoings.insert(mesh_id, outgoing);
@@ -177,6 +217,11 @@ impl Mesh {
             match state.outgoings.get(mesh_id) {
                 Some(ref outgoing) => match outgoing.send(Vec::from(envelope)) {
                     Ok(()) => Ok(()),
+                    Err(outgoing::SendError::Full(payload)) => {
+                        #[cfg(feature = "metrics")]
+                        metrics::counter!("splinter.mesh.send_dropped", 1);
+                        Err(SendError::Full(Envelope::new(id, payload)))
+                    }
                     Err(err) => Err(SendError::from_outgoing_send_error(err, id)),
                 },
                 None => Err(SendError::NotFound),
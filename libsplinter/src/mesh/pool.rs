@@ -17,13 +17,14 @@ use mio::{Event, Evented, Events, Poll, PollOpt, Ready, Token};
 use mio_extras::channel as mio_channel;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::io;
 use std::sync::mpsc::TryRecvError;
 
-use crate::transport::{Connection, RecvError, SendError};
+use crate::transport::{buffer::BufferPool, Connection, RecvError, SendError};
 
+use super::scheduler::SendScheduler;
 use super::InternalEnvelope;
 
 /// A structure for holding onto many connections and receivers and assigning new connections
@@ -34,6 +35,11 @@ pub(super) struct Pool {
     next_id: usize,
     poll: Poll,
     disconnected: HashMap<usize, Option<Box<dyn Connection>>>,
+    scheduler: SendScheduler,
+    // Connections with outgoing messages waiting to be scheduled
+    ready: HashSet<usize>,
+    // Payloads that have been sent are reused to receive incoming messages
+    buffers: BufferPool,
 }
 
 impl fmt::Debug for Pool {
@@ -55,15 +61,20 @@ impl Pool {
             next_id: 0,
             poll,
             disconnected: HashMap::new(),
+            scheduler: SendScheduler::new(),
+            ready: HashSet::new(),
+            buffers: BufferPool::default(),
         }
     }
 
     /// Add a new connection to the reactor, returning unique ids for the actual connection and the
-    /// outgoing queue
+    /// outgoing queue. The connection may send up to `send_weight` messages per turn while other
+    /// connections are waiting to send.
     pub fn add(
         &mut self,
         connection: Box<dyn Connection>,
         outgoing: mio_channel::Receiver<InternalEnvelope>,
+        send_weight: usize,
     ) -> Result<usize, io::Error> {
         let connection_token = self.next_token();
         let outgoing_token = self.next_token();
@@ -89,12 +100,16 @@ impl Pool {
             id,
             Entry::new(id, connection, connection_token, outgoing, outgoing_token),
         );
+        self.scheduler.add(id, send_weight);
 
         Ok(id)
     }
 
     /// Remove a connection from the reactor, returning it if it exists
     pub fn remove(&mut self, id: usize) -> Result<Option<Box<dyn Connection>>, io::Error> {
+        self.scheduler.remove(id);
+        self.ready.remove(&id);
+
         if let Some(entry) = self.entries.remove(&id) {
             let connection_token = entry.connection_token();
             let outgoing_token = entry.outgoing_token();
@@ -127,24 +142,69 @@ impl Pool {
         self.poll.poll(events, None)
     }
 
+    /// Handle an event for a connection; outgoing messages are not sent until `send_outgoing` is
+    /// called, so that sends can be scheduled fairly across connections
     pub fn handle_event(
         &mut self,
         event: &Event,
         incoming_tx: &crossbeam_channel::Sender<InternalEnvelope>,
     ) {
+        if let Some(entry) = self.entry_by_token(event.token()) {
+            if entry.outgoing_wants_read(event) {
+                let id = entry.id();
+                self.ready.insert(id);
+                return;
+            }
+        }
+
         if let Err((id, err)) = self.try_handle_event(event, incoming_tx) {
-            debug!(
-                "Removing Connection {} due to error handling event: {:?}",
-                id, err
+            self.handle_error(id, err);
+        }
+    }
+
+    /// Send queued outgoing messages from the connections that are ready, sending at most
+    /// `budget` messages in total. Connections are serviced in weighted round-robin order.
+    pub fn send_outgoing(&mut self, budget: usize) {
+        if self.ready.is_empty() {
+            return;
+        }
+
+        let (scheduled, starved) = self.scheduler.schedule(&self.ready, budget);
+        self.ready.clear();
+
+        if !starved.is_empty() {
+            trace!(
+                "Send budget exhausted, deferring sends for connections {:?}",
+                starved
             );
-            match self.remove(id) {
-                Ok(connection) => {
-                    self.disconnected.insert(id, connection);
-                }
-                Err(err) => {
-                    error!("Error removing connection: {:?}", err);
-                    self.disconnected.insert(id, None);
-                }
+            #[cfg(feature = "metrics")]
+            metrics::counter!("splinter.mesh.send_starved", starved.len() as u64);
+        }
+
+        for (id, quota) in scheduled {
+            let result = match self.entries.get(&id) {
+                Some(entry) => entry.try_send_outgoing(quota, &self.poll, &self.buffers),
+                None => Ok(()),
+            };
+
+            if let Err(err) = result {
+                self.handle_error(id, err);
+            }
+        }
+    }
+
+    fn handle_error(&mut self, id: usize, err: TryEventError) {
+        debug!(
+            "Removing Connection {} due to error handling event: {:?}",
+            id, err
+        );
+        match self.remove(id) {
+            Ok(connection) => {
+                self.disconnected.insert(id, connection);
+            }
+            Err(err) => {
+                error!("Error removing connection: {:?}", err);
+                self.disconnected.insert(id, None);
             }
         }
     }
@@ -156,7 +216,7 @@ impl Pool {
     ) -> Result<(), (usize, TryEventError)> {
         if let Some(entry) = self.entry_by_token(event.token()) {
             entry
-                .try_event(event, incoming_tx, &self.poll)
+                .try_event(event, incoming_tx, &self.poll, &self.buffers)
                 .map_err(|err| (entry.id(), err))
         } else {
             Ok(())
@@ -242,13 +302,12 @@ impl Entry {
         event: &Event,
         incoming_tx: &crossbeam_channel::Sender<InternalEnvelope>,
         poll: &Poll,
+        buffers: &BufferPool,
     ) -> Result<(), TryEventError> {
-        if self.outgoing_wants_read(event) {
-            self.try_read_outgoing(poll)
-        } else if self.connection_wants_write(event) {
-            self.try_send_connection_from_cached(poll)
+        if self.connection_wants_write(event) {
+            self.try_send_connection_from_cached(poll, buffers)
         } else if self.connection_wants_read(event) {
-            self.try_read_connection(incoming_tx)
+            self.try_read_connection(incoming_tx, buffers)
         } else {
             Ok(())
         }
@@ -262,16 +321,35 @@ impl Entry {
             && self.cached.borrow().is_none()
     }
 
-    fn try_read_outgoing(&self, poll: &Poll) -> Result<(), TryEventError> {
+    // Send up to `quota` messages from the outgoing queue, stopping early if the queue is empty or
+    // the connection is not writable
+    fn try_send_outgoing(
+        &self,
+        quota: usize,
+        poll: &Poll,
+        buffers: &BufferPool,
+    ) -> Result<(), TryEventError> {
+        for _ in 0..quota {
+            if self.cached.borrow().is_some() || !self.try_read_outgoing(poll, buffers)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Returns false if there was nothing in the outgoing queue
+    fn try_read_outgoing(&self, poll: &Poll, buffers: &BufferPool) -> Result<bool, TryEventError> {
         let envelope = match self.outgoing.try_recv() {
             Ok(envelope) => envelope,
-            Err(TryRecvError::Empty) => return Ok(()),
+            Err(TryRecvError::Empty) => return Ok(false),
             Err(TryRecvError::Disconnected) => return Err(TryEventError::OutgoingDisconnected),
         };
 
         match envelope {
             InternalEnvelope::Message { payload, .. } => {
-                self.try_send_connection_or_cache(payload, poll)
+                self.try_send_connection_or_cache(payload, poll, buffers)?;
+                Ok(true)
             }
             // won't be sent outgoing
             InternalEnvelope::Shutdown => unreachable!(),
@@ -290,9 +368,13 @@ impl Entry {
         self.connection_token == event.token() && event.readiness().is_readable()
     }
 
-    fn try_send_connection_from_cached(&self, poll: &Poll) -> Result<(), TryEventError> {
+    fn try_send_connection_from_cached(
+        &self,
+        poll: &Poll,
+        buffers: &BufferPool,
+    ) -> Result<(), TryEventError> {
         if let Some(cached) = self.cached.replace(None) {
-            self.try_send_connection_or_cache(cached, poll)
+            self.try_send_connection_or_cache(cached, poll, buffers)
         } else {
             Ok(())
         }
@@ -302,6 +384,7 @@ impl Entry {
         &self,
         payload: Vec<u8>,
         poll: &Poll,
+        buffers: &BufferPool,
     ) -> Result<(), TryEventError> {
         let mut connection = match self.connection.try_borrow_mut() {
             Ok(conn) => conn,
@@ -313,6 +396,7 @@ impl Entry {
 
         match connection.send(&payload) {
             Ok(()) => {
+                buffers.release(payload);
                 // Return to readable only.
                 if self.write_evented_guard.replace(false) {
                     poll.reregister(
@@ -350,6 +434,7 @@ impl Entry {
     fn try_read_connection(
         &self,
         incoming_tx: &crossbeam_channel::Sender<InternalEnvelope>,
+        buffers: &BufferPool,
     ) -> Result<(), TryEventError> {
         if !incoming_tx.is_full() {
             let mut connection = match self.connection.try_borrow_mut() {
@@ -359,8 +444,9 @@ impl Entry {
                     return Ok(());
                 }
             };
-            match connection.recv() {
-                Ok(payload) => {
+            let mut payload = buffers.take();
+            match connection.recv_into(&mut payload) {
+                Ok(()) => {
                     match incoming_tx.try_send(InternalEnvelope::Message {
                         id: self.id,
                         payload,
@@ -375,7 +461,10 @@ impl Entry {
                         Ok(()) => Ok(()),
                     }
                 }
-                Err(RecvError::WouldBlock) => Ok(()),
+                Err(RecvError::WouldBlock) => {
+                    buffers.release(payload);
+                    Ok(())
+                }
                 Err(RecvError::Disconnected) => Err(TryEventError::ConnectionDisconnected),
                 Err(RecvError::ProtocolError(err)) => Err(TryEventError::ProtocolError(err)),
                 Err(RecvError::IoError(err)) => Err(TryEventError::IoError(err)),
@@ -33,6 +33,9 @@ use crate::transport::Connection;
 // Maximum number of events to receive and handle per turn of the reactor
 const MAX_EVENTS_PER_TURN: usize = 1024;
 
+// Maximum number of outgoing messages to send, across all connections, per turn of the reactor
+const MAX_SENDS_PER_TURN: usize = 1024;
+
 pub struct Reactor {
     pool: Pool,
     ctrl_rx: mio_channel::Receiver<ControlRequest>,
@@ -104,6 +107,8 @@ impl Reactor {
             }
         }
 
+        self.pool.send_outgoing(MAX_SENDS_PER_TURN);
+
         Turn::Continue
     }
 
@@ -120,10 +125,16 @@ impl Reactor {
         match self.ctrl_rx.try_recv() {
             Ok(ControlRequest::Add(AddRequest {
                 connection,
+                outgoing_capacity,
+                send_weight,
                 response_tx,
-                ..
             })) => {
-                if let Err(err) = response_tx.send(self.add_connection(connection)) {
+                let response = self.add_connection(
+                    connection,
+                    outgoing_capacity.unwrap_or(self.outgoing_capacity),
+                    send_weight,
+                );
+                if let Err(err) = response_tx.send(response) {
                     error!("Failed to send back AddResponse: {:?}", err);
                 }
                 Turn::Continue
@@ -147,10 +158,15 @@ impl Reactor {
         }
     }
 
-    fn add_connection(&mut self, connection: Box<dyn Connection>) -> AddResponse {
-        let (tx, rx) = mio_channel::sync_channel(self.outgoing_capacity);
+    fn add_connection(
+        &mut self,
+        connection: Box<dyn Connection>,
+        outgoing_capacity: usize,
+        send_weight: usize,
+    ) -> AddResponse {
+        let (tx, rx) = mio_channel::sync_channel(outgoing_capacity);
 
-        match self.pool.add(connection, rx) {
+        match self.pool.add(connection, rx, send_weight) {
             Ok(id) => Ok(Outgoing::new(id, tx)),
             Err(err) => Err(AddError::Io(err)),
         }
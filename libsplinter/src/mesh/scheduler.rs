@@ -0,0 +1,142 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The number of messages a connection may send per turn of the reactor, relative to other
+/// connections, when no weight is given
+pub const DEFAULT_SEND_WEIGHT: usize = 1;
+
+/// Schedules sends from connections' outgoing queues using weighted round-robin, so that a
+/// connection with many queued messages cannot starve the other connections.
+///
+/// Each turn, every connection with queued messages may send up to its weight in messages, in
+/// round-robin order, until the turn's send budget is spent. The starting connection rotates every
+/// turn, and connections that were not serviced because the budget ran out go first on the next
+/// turn.
+#[derive(Debug, Default)]
+pub(super) struct SendScheduler {
+    order: VecDeque<usize>,
+    weights: HashMap<usize, usize>,
+}
+
+impl SendScheduler {
+    pub fn new() -> Self {
+        SendScheduler::default()
+    }
+
+    /// Add a connection to the schedule; a weight of zero is treated as one
+    pub fn add(&mut self, id: usize, weight: usize) {
+        if self.weights.insert(id, weight.max(1)).is_none() {
+            self.order.push_back(id);
+        }
+    }
+
+    /// Remove a connection from the schedule
+    pub fn remove(&mut self, id: usize) {
+        if self.weights.remove(&id).is_some() {
+            self.order.retain(|scheduled| *scheduled != id);
+        }
+    }
+
+    /// Returns the connections that may send this turn, in order, with the number of messages
+    /// each may send, followed by the ready connections that were starved by the send budget.
+    pub fn schedule(
+        &mut self,
+        ready: &HashSet<usize>,
+        budget: usize,
+    ) -> (Vec<(usize, usize)>, Vec<usize>) {
+        let mut remaining = budget;
+        let mut scheduled = vec![];
+        let mut starved = vec![];
+
+        for id in self.order.iter().filter(|id| ready.contains(id)) {
+            if remaining == 0 {
+                starved.push(*id);
+                continue;
+            }
+
+            let quota = self
+                .weights
+                .get(id)
+                .copied()
+                .unwrap_or(DEFAULT_SEND_WEIGHT)
+                .min(remaining);
+            remaining -= quota;
+            scheduled.push((*id, quota));
+        }
+
+        match starved.first() {
+            Some(first_starved) => {
+                if let Some(position) = self.order.iter().position(|id| id == first_starved) {
+                    self.order.rotate_left(position);
+                }
+            }
+            None if !self.order.is_empty() => self.order.rotate_left(1),
+            None => (),
+        }
+
+        (scheduled, starved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that ready connections are scheduled by weight, that the starting connection rotates
+    // between turns, and that connections starved by the send budget go first on the next turn
+    //  1. Add connections 0, 1, and 2, with connection 1 having a weight of 3
+    //  2. Schedule with only connections 0 and 1 ready and check that both are scheduled by weight
+    //  3. Schedule again and check that connection 1 is now first
+    //  4. Schedule with a budget of 3 and check that connection 2 is starved and goes first on the
+    //     next turn
+    #[test]
+    fn test_schedule() {
+        let mut scheduler = SendScheduler::new();
+        scheduler.add(0, DEFAULT_SEND_WEIGHT);
+        scheduler.add(1, 3);
+        scheduler.add(2, 0);
+
+        let ready = [0, 1].iter().copied().collect::<HashSet<_>>();
+        assert_eq!(
+            scheduler.schedule(&ready, 10),
+            (vec![(0, 1), (1, 3)], vec![])
+        );
+        assert_eq!(
+            scheduler.schedule(&ready, 10),
+            (vec![(1, 3), (0, 1)], vec![])
+        );
+
+        let ready = [0, 1, 2].iter().copied().collect::<HashSet<_>>();
+        assert_eq!(
+            scheduler.schedule(&ready, 3),
+            (vec![(2, 1), (0, 1), (1, 1)], vec![])
+        );
+        assert_eq!(
+            scheduler.schedule(&ready, 3),
+            (vec![(0, 1), (1, 2)], vec![2])
+        );
+        assert_eq!(
+            scheduler.schedule(&ready, 3),
+            (vec![(2, 1), (0, 1), (1, 1)], vec![])
+        );
+
+        scheduler.remove(2);
+        assert_eq!(
+            scheduler.schedule(&ready, 3),
+            (vec![(0, 1), (1, 2)], vec![])
+        );
+    }
+}
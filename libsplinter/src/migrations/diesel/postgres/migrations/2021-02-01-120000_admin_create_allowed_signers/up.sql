@@ -0,0 +1,26 @@
+--- Copyright 2018-2020 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS admin_allowed_signers (
+    public_key  TEXT  PRIMARY KEY,
+    node_id     TEXT  NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS admin_allowed_signer_roles (
+    public_key  TEXT  NOT NULL,
+    role        TEXT  NOT NULL,
+    PRIMARY KEY (public_key, role),
+    FOREIGN KEY (public_key) REFERENCES admin_allowed_signers(public_key) ON DELETE CASCADE
+);
@@ -0,0 +1,32 @@
+--- Copyright 2018-2020 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS admin_vote_thresholds (
+    management_type      TEXT     PRIMARY KEY,
+    required_signatures  INTEGER  NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS admin_pending_votes (
+    circuit_id  TEXT   PRIMARY KEY,
+    payload     BYTEA  NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS admin_pending_vote_signatures (
+    circuit_id  TEXT  NOT NULL,
+    public_key  TEXT  NOT NULL,
+    signature   TEXT  NOT NULL,
+    PRIMARY KEY (circuit_id, public_key),
+    FOREIGN KEY (circuit_id) REFERENCES admin_pending_votes(circuit_id) ON DELETE CASCADE
+);
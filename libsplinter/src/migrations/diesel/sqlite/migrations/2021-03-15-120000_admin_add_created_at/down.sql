@@ -0,0 +1,24 @@
+---- Copyright 2018-2020 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- --
+
+DROP INDEX IF EXISTS idx_circuit_proposal_created_at;
+
+DROP INDEX IF EXISTS idx_circuit_created_at;
+
+ALTER TABLE circuit_proposal
+DROP COLUMN created_at;
+
+ALTER TABLE circuit
+DROP COLUMN created_at;
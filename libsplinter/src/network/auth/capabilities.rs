@@ -0,0 +1,70 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+use crate::protocol::authorization::NodeCapabilities;
+use crate::protocol::ADMIN_SERVICE_PROTOCOL_VERSION;
+
+/// Returns the capabilities of this node, advertising the given features.
+pub(super) fn local_capabilities(features: Vec<String>) -> NodeCapabilities {
+    NodeCapabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        admin_service_protocol_version: ADMIN_SERVICE_PROTOCOL_VERSION,
+        features,
+    }
+}
+
+/// The capabilities advertised by each peer during authorization, keyed by the peer's identity.
+///
+/// A peer's capabilities are replaced each time it is authorized, so they reflect the version
+/// the peer was running when it last connected. Peers that predate the capability exchange are
+/// not listed.
+#[derive(Clone, Default)]
+pub struct PeerCapabilities {
+    capabilities: Arc<Mutex<HashMap<String, NodeCapabilities>>>,
+}
+
+impl PeerCapabilities {
+    /// Returns the capabilities advertised by the peer with the given identity.
+    pub fn get(&self, identity: &str) -> Result<Option<NodeCapabilities>, InternalError> {
+        Ok(self.lock()?.get(identity).cloned())
+    }
+
+    /// Returns the capabilities advertised by every peer, sorted by identity.
+    pub fn list(&self) -> Result<BTreeMap<String, NodeCapabilities>, InternalError> {
+        Ok(self
+            .lock()?
+            .iter()
+            .map(|(identity, capabilities)| (identity.clone(), capabilities.clone()))
+            .collect())
+    }
+
+    pub(super) fn insert(
+        &self,
+        identity: String,
+        capabilities: NodeCapabilities,
+    ) -> Result<(), InternalError> {
+        self.lock()?.insert(identity, capabilities);
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<HashMap<String, NodeCapabilities>>, InternalError> {
+        self.capabilities
+            .lock()
+            .map_err(|_| InternalError::with_message("Peer capabilities lock was poisoned".into()))
+    }
+}
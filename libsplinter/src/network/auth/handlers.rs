@@ -19,7 +19,7 @@ use crate::network::dispatch::{
 };
 use crate::protocol::authorization::{
     AuthorizationError, AuthorizationMessage, AuthorizationType, Authorized, ConnectRequest,
-    ConnectResponse, TrustRequest,
+    ConnectResponse, NodeCapabilities, TrustRequest,
 };
 use crate::protos::authorization;
 use crate::protos::network::{NetworkMessage, NetworkMessageType};
@@ -36,17 +36,25 @@ use super::{
 /// The dispatcher is provided the given network sender for response messages, and the network
 /// itself to handle updating identities (or removing connections with authorization failures).
 ///
-/// The identity provided is sent to connections for Trust authorizations.
+/// The identity provided is sent to connections for Trust authorizations, and the capabilities
+/// are advertised in each connect response.
 pub fn create_authorization_dispatcher(
     identity: String,
+    capabilities: NodeCapabilities,
     auth_manager: AuthorizationManagerStateMachine,
     auth_msg_sender: impl MessageSender<ConnectionId> + Clone + 'static,
 ) -> Dispatcher<NetworkMessageType, ConnectionId> {
     let mut auth_dispatcher = Dispatcher::new(Box::new(auth_msg_sender.clone()));
 
-    auth_dispatcher.set_handler(Box::new(ConnectRequestHandler::new(auth_manager.clone())));
+    auth_dispatcher.set_handler(Box::new(ConnectRequestHandler::new(
+        auth_manager.clone(),
+        capabilities,
+    )));
 
-    auth_dispatcher.set_handler(Box::new(ConnectResponseHandler::new(identity)));
+    auth_dispatcher.set_handler(Box::new(ConnectResponseHandler::new(
+        identity,
+        auth_manager.clone(),
+    )));
 
     auth_dispatcher.set_handler(Box::new(TrustRequestHandler::new(auth_manager.clone())));
 
@@ -157,11 +165,15 @@ impl Handler for AuthorizedHandler {
 /// Handler for the Connect Request Authorization Message Type
 struct ConnectRequestHandler {
     auth_manager: AuthorizationManagerStateMachine,
+    capabilities: NodeCapabilities,
 }
 
 impl ConnectRequestHandler {
-    fn new(auth_manager: AuthorizationManagerStateMachine) -> Self {
-        ConnectRequestHandler { auth_manager }
+    fn new(auth_manager: AuthorizationManagerStateMachine, capabilities: NodeCapabilities) -> Self {
+        ConnectRequestHandler {
+            auth_manager,
+            capabilities,
+        }
     }
 }
 
@@ -229,6 +241,7 @@ impl Handler for ConnectRequestHandler {
 
                 let response = AuthorizationMessage::ConnectResponse(ConnectResponse {
                     accepted_authorization_types: vec![AuthorizationType::Trust],
+                    capabilities: Some(self.capabilities.clone()),
                 });
 
                 let mut msg = NetworkMessage::new();
@@ -253,11 +266,15 @@ impl Handler for ConnectRequestHandler {
 /// Handler for the ConnectResponse Authorization Message Type
 struct ConnectResponseHandler {
     identity: String,
+    auth_manager: AuthorizationManagerStateMachine,
 }
 
 impl ConnectResponseHandler {
-    fn new(identity: String) -> Self {
-        ConnectResponseHandler { identity }
+    fn new(identity: String, auth_manager: AuthorizationManagerStateMachine) -> Self {
+        ConnectResponseHandler {
+            identity,
+            auth_manager,
+        }
     }
 }
 
@@ -283,6 +300,19 @@ impl Handler for ConnectResponseHandler {
             connect_response,
         );
 
+        if let Some(capabilities) = connect_response.capabilities.clone() {
+            if let Err(err) = self
+                .auth_manager
+                .set_remote_capabilities(context.source_connection_id(), capabilities)
+            {
+                warn!(
+                    "Unable to record capabilities of connection {}: {}",
+                    context.source_connection_id(),
+                    err
+                );
+            }
+        }
+
         if connect_response
             .accepted_authorization_types
             .iter()
@@ -444,6 +474,8 @@ mod tests {
     use crate::protos::authorization;
     use crate::protos::network::{NetworkMessage, NetworkMessageType};
 
+    use super::super::capabilities::local_capabilities;
+
     /// Test that an connect request is properly handled via the dispatcher.
     ///
     /// This is verified by:
@@ -451,13 +483,18 @@ mod tests {
     /// 1) no error from the dispatcher
     /// 2) the handler should send out two messages, a Unidirectional connect request and a connect
     ///    response.
+    /// 3) the connect response advertises the local node's capabilities
     #[test]
     fn connect_request_dispatch() {
         let auth_mgr = AuthorizationManagerStateMachine::default();
         let mock_sender = MockSender::new();
         let dispatch_sender = mock_sender.clone();
-        let dispatcher =
-            create_authorization_dispatcher("mock_identity".into(), auth_mgr, dispatch_sender);
+        let dispatcher = create_authorization_dispatcher(
+            "mock_identity".into(),
+            local_capabilities(vec![]),
+            auth_mgr,
+            dispatch_sender,
+        );
 
         let connection_id = "test_connection".to_string();
         let mut msg = authorization::ConnectRequest::new();
@@ -502,6 +539,10 @@ mod tests {
             vec![authorization::ConnectResponse_AuthorizationType::TRUST],
             connect_res_msg.get_accepted_authorization_types().to_vec()
         );
+        assert_eq!(
+            env!("CARGO_PKG_VERSION"),
+            connect_res_msg.get_capabilities().get_version()
+        );
     }
 
     /// Test that a connect response is properly handled via the dispatcher.
@@ -510,18 +551,28 @@ mod tests {
     ///
     /// 1) a trust request is sent to the remote connection
     /// 2) the trust request includes the local identity
+    /// 3) the capabilities advertised by the remote connection are recorded
     #[test]
     fn connect_response_dispatch() {
         let auth_mgr = AuthorizationManagerStateMachine::default();
         let mock_sender = MockSender::new();
         let dispatch_sender = mock_sender.clone();
-        let dispatcher =
-            create_authorization_dispatcher("mock_identity".into(), auth_mgr, dispatch_sender);
+        let dispatcher = create_authorization_dispatcher(
+            "mock_identity".into(),
+            local_capabilities(vec![]),
+            auth_mgr.clone(),
+            dispatch_sender,
+        );
         let connection_id = "test_connection".to_string();
+        let remote_capabilities = local_capabilities(vec!["remote-feature".into()]);
         let mut msg = authorization::ConnectResponse::new();
         msg.set_accepted_authorization_types(
             vec![authorization::ConnectResponse_AuthorizationType::TRUST].into(),
         );
+        msg.set_capabilities(
+            authorization::NodeCapabilities::from_native(remote_capabilities.clone())
+                .expect("Unable to convert capabilities"),
+        );
         let mut auth_msg = authorization::AuthorizationMessage::new();
         auth_msg.set_message_type(authorization::AuthorizationMessageType::CONNECT_RESPONSE);
         auth_msg.set_payload(msg.write_to_bytes().unwrap());
@@ -545,6 +596,15 @@ mod tests {
             &msg_bytes,
         );
         assert_eq!("mock_identity", trust_req.get_identity());
+
+        assert_eq!(
+            Some(remote_capabilities),
+            auth_mgr
+                .shared
+                .lock()
+                .expect("lock was poisoned")
+                .take_connection_capabilities(&connection_id)
+        );
     }
 
     /// Test a trust request is properly handled via the dispatcher
@@ -559,8 +619,12 @@ mod tests {
         let auth_mgr = AuthorizationManagerStateMachine::default();
         let mock_sender = MockSender::new();
         let dispatch_sender = mock_sender.clone();
-        let dispatcher =
-            create_authorization_dispatcher("mock_identity".into(), auth_mgr, dispatch_sender);
+        let dispatcher = create_authorization_dispatcher(
+            "mock_identity".into(),
+            local_capabilities(vec![]),
+            auth_mgr,
+            dispatch_sender,
+        );
         let connection_id = "test_connection".to_string();
         // Begin the connection process, otherwise, the response will fail
         let mut msg = authorization::ConnectRequest::new();
@@ -12,9 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod capabilities;
 mod connection_manager;
 mod handlers;
+#[cfg(feature = "peer-identity-pinning")]
+pub mod pinning;
 mod pool;
+#[cfg(feature = "spiffe")]
+pub mod spiffe;
 
 use std::collections::HashMap;
 use std::fmt;
@@ -22,14 +27,21 @@ use std::sync::{mpsc, Arc, Mutex};
 
 use protobuf::Message;
 
-use crate::protocol::authorization::{AuthorizationMessage, ConnectRequest};
+use crate::protocol::authorization::{AuthorizationMessage, ConnectRequest, NodeCapabilities};
 use crate::protos::authorization;
 use crate::protos::network::{NetworkMessage, NetworkMessageType};
 use crate::protos::prelude::*;
 use crate::transport::{Connection, RecvError};
 
+use self::capabilities::local_capabilities;
 use self::handlers::create_authorization_dispatcher;
+#[cfg(feature = "peer-identity-pinning")]
+use self::pinning::IdentityPinVerifier;
 use self::pool::{ThreadPool, ThreadPoolBuilder};
+#[cfg(feature = "spiffe")]
+use self::spiffe::SpiffeIdVerifier;
+
+pub use self::capabilities::PeerCapabilities;
 
 const AUTHORIZATION_THREAD_POOL_SIZE: usize = 8;
 
@@ -115,8 +127,14 @@ impl fmt::Display for AuthorizationManagerError {
 /// Manages authorization states for connections on a network.
 pub struct AuthorizationManager {
     local_identity: String,
+    local_capabilities: NodeCapabilities,
+    peer_capabilities: PeerCapabilities,
     thread_pool: ThreadPool,
     shared: Arc<Mutex<ManagedAuthorizations>>,
+    #[cfg(feature = "spiffe")]
+    spiffe_id_verifier: Option<Arc<SpiffeIdVerifier>>,
+    #[cfg(feature = "peer-identity-pinning")]
+    identity_pin_verifier: Option<IdentityPinVerifier>,
 }
 
 impl AuthorizationManager {
@@ -134,9 +152,43 @@ impl AuthorizationManager {
             thread_pool,
             shared,
             local_identity,
+            local_capabilities: local_capabilities(vec![]),
+            peer_capabilities: PeerCapabilities::default(),
+            #[cfg(feature = "spiffe")]
+            spiffe_id_verifier: None,
+            #[cfg(feature = "peer-identity-pinning")]
+            identity_pin_verifier: None,
         })
     }
 
+    /// Sets the optional features this node advertises to its peers during authorization, along
+    /// with its version and protocol versions.
+    pub fn with_advertised_features(mut self, features: Vec<String>) -> Self {
+        self.local_capabilities = local_capabilities(features);
+        self
+    }
+
+    /// Sets the verifier used to check that the identity claimed by a peer matches the SPIFFE ID
+    /// in the certificate it presented. Peers that fail the check are not authorized.
+    #[cfg(feature = "spiffe")]
+    pub fn with_spiffe_id_verifier(mut self, verifier: SpiffeIdVerifier) -> Self {
+        self.spiffe_id_verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Sets the verifier used to check that peers present the certificate their identity is
+    /// pinned to. Peers that fail the check are not authorized.
+    #[cfg(feature = "peer-identity-pinning")]
+    pub fn with_identity_pin_verifier(mut self, verifier: IdentityPinVerifier) -> Self {
+        self.identity_pin_verifier = Some(verifier);
+        self
+    }
+
+    /// Returns the capabilities advertised by the peers this node has authorized.
+    pub fn peer_capabilities(&self) -> PeerCapabilities {
+        self.peer_capabilities.clone()
+    }
+
     pub fn shutdown_signaler(&self) -> ShutdownSignaler {
         ShutdownSignaler {
             thread_pool_signaler: self.thread_pool.shutdown_signaler(),
@@ -150,8 +202,14 @@ impl AuthorizationManager {
     pub fn authorization_connector(&self) -> AuthorizationConnector {
         AuthorizationConnector {
             local_identity: self.local_identity.clone(),
+            local_capabilities: self.local_capabilities.clone(),
+            peer_capabilities: self.peer_capabilities.clone(),
             shared: Arc::clone(&self.shared),
             executor: self.thread_pool.executor(),
+            #[cfg(feature = "spiffe")]
+            spiffe_id_verifier: self.spiffe_id_verifier.clone(),
+            #[cfg(feature = "peer-identity-pinning")]
+            identity_pin_verifier: self.identity_pin_verifier.clone(),
         }
     }
 }
@@ -171,8 +229,14 @@ type Callback =
 
 pub struct AuthorizationConnector {
     local_identity: String,
+    local_capabilities: NodeCapabilities,
+    peer_capabilities: PeerCapabilities,
     shared: Arc<Mutex<ManagedAuthorizations>>,
     executor: pool::JobExecutor,
+    #[cfg(feature = "spiffe")]
+    spiffe_id_verifier: Option<Arc<SpiffeIdVerifier>>,
+    #[cfg(feature = "peer-identity-pinning")]
+    identity_pin_verifier: Option<IdentityPinVerifier>,
 }
 
 impl AuthorizationConnector {
@@ -190,8 +254,17 @@ impl AuthorizationConnector {
             shared: Arc::clone(&self.shared),
         };
         let msg_sender = AuthorizationMessageSender { sender: tx };
-        let dispatcher =
-            create_authorization_dispatcher(self.local_identity.clone(), state_machine, msg_sender);
+        let dispatcher = create_authorization_dispatcher(
+            self.local_identity.clone(),
+            self.local_capabilities.clone(),
+            state_machine,
+            msg_sender,
+        );
+        let peer_capabilities = self.peer_capabilities.clone();
+        #[cfg(feature = "spiffe")]
+        let spiffe_id_verifier = self.spiffe_id_verifier.clone();
+        #[cfg(feature = "peer-identity-pinning")]
+        let identity_pin_verifier = self.identity_pin_verifier.clone();
         self.executor.execute(move || {
             let connect_request_bytes = match connect_msg_bytes() {
                 Ok(bytes) => bytes,
@@ -271,10 +344,31 @@ impl AuthorizationConnector {
                 };
 
                 if let Some(true) = shared.is_complete(&connection_id) {
-                    break 'main shared.take_connection_identity(&connection_id);
+                    let capabilities = shared.take_connection_capabilities(&connection_id);
+                    let identity = shared.take_connection_identity(&connection_id);
+                    break 'main identity.map(|identity| (identity, capabilities));
                 }
             };
 
+            #[cfg(feature = "spiffe")]
+            let authed_identity = authed_identity.filter(|(identity, _)| {
+                spiffe_id_verified(spiffe_id_verifier.as_deref(), identity, &*connection)
+            });
+
+            #[cfg(feature = "peer-identity-pinning")]
+            let authed_identity = authed_identity.filter(|(identity, _)| {
+                identity_pin_verified(identity_pin_verifier.as_ref(), identity, &*connection)
+            });
+
+            let authed_identity = authed_identity.map(|(identity, capabilities)| {
+                if let Some(capabilities) = capabilities {
+                    if let Err(err) = peer_capabilities.insert(identity.clone(), capabilities) {
+                        error!("Unable to record capabilities of {}: {}", identity, err);
+                    }
+                }
+                identity
+            });
+
             let auth_state = if let Some(identity) = authed_identity {
                 ConnectionAuthorizationState::Authorized {
                     connection_id,
@@ -297,6 +391,61 @@ impl AuthorizationConnector {
     }
 }
 
+/// Checks the identity claimed by a peer against its certificate's SPIFFE ID, if a verifier is
+/// configured.
+#[cfg(feature = "spiffe")]
+fn spiffe_id_verified(
+    verifier: Option<&SpiffeIdVerifier>,
+    identity: &str,
+    connection: &dyn Connection,
+) -> bool {
+    match verifier.map(|verifier| verifier.verify(identity, &connection.peer_certificate_uris())) {
+        Some(Err(err)) => {
+            warn!(
+                "Refusing to authorize {} at {}: {}",
+                identity,
+                connection.remote_endpoint(),
+                err
+            );
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Checks that a peer presented the certificate its identity is pinned to, if a verifier is
+/// configured.
+#[cfg(feature = "peer-identity-pinning")]
+fn identity_pin_verified(
+    verifier: Option<&IdentityPinVerifier>,
+    identity: &str,
+    connection: &dyn Connection,
+) -> bool {
+    let verifier = match verifier {
+        Some(verifier) => verifier,
+        None => return true,
+    };
+    let fingerprint = connection.peer_certificate_fingerprint();
+    match verifier.verify(
+        identity,
+        fingerprint.as_deref(),
+        &connection.remote_endpoint(),
+    ) {
+        Ok(()) => true,
+        // mismatches are logged by the verifier
+        Err(pinning::IdentityPinError::Mismatch(_)) => false,
+        Err(err) => {
+            warn!(
+                "Refusing to authorize {} at {}: {}",
+                identity,
+                connection.remote_endpoint(),
+                err
+            );
+            false
+        }
+    }
+}
+
 fn connect_msg_bytes() -> Result<Vec<u8>, AuthorizationManagerError> {
     let mut network_msg = NetworkMessage::new();
     network_msg.set_message_type(NetworkMessageType::AUTHORIZATION);
@@ -330,6 +479,25 @@ pub struct AuthorizationManagerStateMachine {
 }
 
 impl AuthorizationManagerStateMachine {
+    /// Records the capabilities the remote node advertised on the connection, to be reported
+    /// once the connection is authorized.
+    pub(crate) fn set_remote_capabilities(
+        &self,
+        connection_id: &str,
+        capabilities: NodeCapabilities,
+    ) -> Result<(), AuthorizationActionError> {
+        self.shared
+            .lock()
+            .map_err(|_| {
+                AuthorizationActionError::InternalError(
+                    "Authorization pool lock was poisoned".into(),
+                )
+            })?
+            .capabilities
+            .insert(connection_id.to_string(), capabilities);
+        Ok(())
+    }
+
     /// Transitions from one authorization state to another
     ///
     /// Errors
@@ -415,15 +583,21 @@ impl AuthorizationManagerStateMachine {
 #[derive(Default)]
 struct ManagedAuthorizations {
     states: HashMap<String, AuthorizationState>,
+    capabilities: HashMap<String, NodeCapabilities>,
 }
 
 impl ManagedAuthorizations {
     fn new() -> Self {
         Self {
             states: HashMap::new(),
+            capabilities: HashMap::new(),
         }
     }
 
+    fn take_connection_capabilities(&mut self, connection_id: &str) -> Option<NodeCapabilities> {
+        self.capabilities.remove(connection_id)
+    }
+
     fn take_connection_identity(&mut self, connection_id: &str) -> Option<String> {
         self.states
             .remove(connection_id)
@@ -522,6 +696,7 @@ pub(in crate::network) mod tests {
             connection_id,
             AuthorizationMessage::ConnectResponse(ConnectResponse {
                 accepted_authorization_types: vec![AuthorizationType::Trust],
+                capabilities: Some(local_capabilities(vec![])),
             }),
         );
         mesh.send(env).expect("Unable to send connect response");
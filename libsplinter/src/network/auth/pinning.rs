@@ -0,0 +1,621 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Peer identity pinning.
+//!
+//! Once a node identity has been associated with the fingerprint of a TLS certificate, peers that
+//! later claim that identity while presenting a different certificate are refused, which protects
+//! against a node's endpoint being taken over. A node's fingerprint is either pinned in the
+//! registry, through the `tls_fingerprint` metadata entry, or recorded the first time the node is
+//! authorized (trust on first use).
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::error::{InternalError, InvalidArgumentError};
+use crate::registry::RegistryReader;
+
+/// The registry node metadata key holding the SHA-256 fingerprint of the node's TLS certificate.
+pub const TLS_FINGERPRINT_METADATA_KEY: &str = "tls_fingerprint";
+
+/// The number of identity mismatches kept for inspection.
+const MAX_RECORDED_MISMATCHES: usize = 100;
+
+/// The presented fingerprint recorded when a peer claims a pinned identity without a certificate.
+const NO_CERTIFICATE: &str = "none";
+
+/// How the certificate fingerprints of peers are pinned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinningMode {
+    /// Fingerprints pinned in the registry are enforced; other nodes are pinned to the first
+    /// certificate they are authorized with.
+    TrustOnFirstUse,
+    /// Only fingerprints pinned in the registry are accepted; nodes without one are refused.
+    Registry,
+}
+
+impl FromStr for PinningMode {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tofu" => Ok(PinningMode::TrustOnFirstUse),
+            "registry" => Ok(PinningMode::Registry),
+            _ => Err(InvalidArgumentError::new(
+                "mode".into(),
+                format!("{} is not a pinning mode; expected tofu or registry", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PinningMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PinningMode::TrustOnFirstUse => "tofu",
+            PinningMode::Registry => "registry",
+        })
+    }
+}
+
+/// A connection that claimed a pinned identity while presenting a different certificate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdentityMismatch {
+    pub identity: String,
+    pub remote_endpoint: String,
+    pub pinned_fingerprint: String,
+    /// The fingerprint of the certificate the peer presented, or `none` if it did not present one
+    pub presented_fingerprint: String,
+    pub timestamp: SystemTime,
+}
+
+/// Stores the certificate fingerprints that node identities were pinned to on first use.
+pub trait IdentityPinStore: Send + Sync {
+    /// Pins the identity to the given fingerprint, unless it is already pinned, and returns the
+    /// fingerprint the identity is pinned to.
+    fn pin_identity(&self, identity: &str, fingerprint: &str) -> Result<String, InternalError>;
+
+    /// Removes the pin of the given identity, returning whether it was pinned.
+    fn remove_pin(&self, identity: &str) -> Result<bool, InternalError>;
+
+    /// Lists the pinned identities and their fingerprints.
+    fn list_pins(&self) -> Result<BTreeMap<String, String>, InternalError>;
+}
+
+/// An `IdentityPinStore` that keeps the pins in memory.
+#[derive(Clone, Default)]
+pub struct MemoryIdentityPinStore {
+    pins: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl MemoryIdentityPinStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdentityPinStore for MemoryIdentityPinStore {
+    fn pin_identity(&self, identity: &str, fingerprint: &str) -> Result<String, InternalError> {
+        Ok(self
+            .pins
+            .lock()
+            .map_err(|_| InternalError::with_message("identity pin lock poisoned".into()))?
+            .entry(identity.to_string())
+            .or_insert_with(|| fingerprint.to_string())
+            .clone())
+    }
+
+    fn remove_pin(&self, identity: &str) -> Result<bool, InternalError> {
+        Ok(self
+            .pins
+            .lock()
+            .map_err(|_| InternalError::with_message("identity pin lock poisoned".into()))?
+            .remove(identity)
+            .is_some())
+    }
+
+    fn list_pins(&self) -> Result<BTreeMap<String, String>, InternalError> {
+        Ok(self
+            .pins
+            .lock()
+            .map_err(|_| InternalError::with_message("identity pin lock poisoned".into()))?
+            .clone())
+    }
+}
+
+/// An `IdentityPinStore` backed by a YAML file mapping node identities to fingerprints.
+///
+/// The file is read on every access, so pins removed from it by an operator take effect without
+/// a restart, and is rewritten atomically when an identity is pinned.
+#[derive(Clone)]
+pub struct YamlIdentityPinStore {
+    file_path: Arc<Mutex<PathBuf>>,
+}
+
+impl YamlIdentityPinStore {
+    pub fn new<P: Into<PathBuf>>(file_path: P) -> Self {
+        YamlIdentityPinStore {
+            file_path: Arc::new(Mutex::new(file_path.into())),
+        }
+    }
+
+    fn update<F>(&self, update: F) -> Result<BTreeMap<String, String>, InternalError>
+    where
+        F: FnOnce(&mut BTreeMap<String, String>) -> bool,
+    {
+        let file_path = self
+            .file_path
+            .lock()
+            .map_err(|_| InternalError::with_message("identity pin lock poisoned".into()))?;
+        let mut pins = read_pins(&file_path)?;
+        if update(&mut pins) {
+            write_pins(&file_path, &pins)?;
+        }
+        Ok(pins)
+    }
+}
+
+impl IdentityPinStore for YamlIdentityPinStore {
+    fn pin_identity(&self, identity: &str, fingerprint: &str) -> Result<String, InternalError> {
+        let pins = self.update(|pins| {
+            if pins.contains_key(identity) {
+                false
+            } else {
+                pins.insert(identity.to_string(), fingerprint.to_string());
+                true
+            }
+        })?;
+        Ok(pins
+            .get(identity)
+            .cloned()
+            .unwrap_or_else(|| fingerprint.to_string()))
+    }
+
+    fn remove_pin(&self, identity: &str) -> Result<bool, InternalError> {
+        let mut removed = false;
+        self.update(|pins| {
+            removed = pins.remove(identity).is_some();
+            removed
+        })?;
+        Ok(removed)
+    }
+
+    fn list_pins(&self) -> Result<BTreeMap<String, String>, InternalError> {
+        self.update(|_| false)
+    }
+}
+
+fn read_pins(file_path: &Path) -> Result<BTreeMap<String, String>, InternalError> {
+    if !file_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let file = File::open(file_path).map_err(|err| {
+        InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!("Unable to open {}", file_path.display()),
+        )
+    })?;
+    Ok(
+        serde_yaml::from_reader::<_, Option<BTreeMap<String, String>>>(file)
+            .map_err(|err| {
+                InternalError::from_source_with_prefix(
+                    Box::new(err),
+                    format!("Unable to read {}", file_path.display()),
+                )
+            })?
+            .unwrap_or_default(),
+    )
+}
+
+fn write_pins(file_path: &Path, pins: &BTreeMap<String, String>) -> Result<(), InternalError> {
+    let contents =
+        serde_yaml::to_vec(pins).map_err(|err| InternalError::from_source(Box::new(err)))?;
+    let temp_path = file_path.with_extension("yaml.tmp");
+    File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(&contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, file_path))
+        .map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!("Unable to write {}", file_path.display()),
+            )
+        })
+}
+
+/// Verifies that peers present the certificate their identity is pinned to.
+#[derive(Clone)]
+pub struct IdentityPinVerifier {
+    mode: PinningMode,
+    store: Arc<dyn IdentityPinStore>,
+    registry: Option<Arc<dyn RegistryReader>>,
+    mismatches: Arc<Mutex<VecDeque<IdentityMismatch>>>,
+}
+
+impl IdentityPinVerifier {
+    /// Creates a verifier that records first-use pins in the given store.
+    pub fn new(mode: PinningMode, store: Box<dyn IdentityPinStore>) -> Self {
+        IdentityPinVerifier {
+            mode,
+            store: store.into(),
+            registry: None,
+            mismatches: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Sets the registry whose `tls_fingerprint` node metadata pins take precedence over the
+    /// first-use pins.
+    pub fn with_registry(mut self, registry: Box<dyn RegistryReader>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    pub fn mode(&self) -> PinningMode {
+        self.mode
+    }
+
+    /// Lists the identities pinned on first use and their fingerprints.
+    pub fn list_pins(&self) -> Result<BTreeMap<String, String>, InternalError> {
+        self.store.list_pins()
+    }
+
+    /// Removes the first-use pin of the given identity, so that the next certificate it is
+    /// authorized with is pinned; this is used when a node's certificate is legitimately
+    /// replaced. Returns whether the identity was pinned.
+    pub fn remove_pin(&self, identity: &str) -> Result<bool, InternalError> {
+        self.store.remove_pin(identity)
+    }
+
+    /// Returns the most recent identity mismatches, oldest first.
+    pub fn mismatches(&self) -> Vec<IdentityMismatch> {
+        match self.mismatches.lock() {
+            Ok(mismatches) => mismatches.iter().cloned().collect(),
+            Err(_) => {
+                error!("Unable to list identity mismatches: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    /// Verifies that the certificate fingerprint presented by a peer matches the fingerprint its
+    /// claimed identity is pinned to, pinning it if the identity is not pinned yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - The identity claimed by the peer
+    /// * `fingerprint` - The SHA-256 fingerprint of the peer's certificate, if it presented one
+    /// * `remote_endpoint` - The endpoint of the peer's connection
+    pub fn verify(
+        &self,
+        identity: &str,
+        fingerprint: Option<&str>,
+        remote_endpoint: &str,
+    ) -> Result<(), IdentityPinError> {
+        let fingerprint = match (fingerprint, self.mode) {
+            (Some(fingerprint), _) => normalize_fingerprint(fingerprint),
+            // A peer without a certificate, such as one connected over plain TCP, cannot be
+            // pinned, but it may not claim an identity that is already pinned
+            (None, PinningMode::TrustOnFirstUse) => {
+                let pinned = match self.registry_fingerprint(identity)? {
+                    Some(pinned) => Some(pinned),
+                    None => self
+                        .store
+                        .list_pins()
+                        .map_err(IdentityPinError::Internal)?
+                        .remove(identity),
+                };
+                return match pinned {
+                    Some(pinned) => {
+                        self.check(identity, pinned, NO_CERTIFICATE.into(), remote_endpoint)
+                    }
+                    None => Ok(()),
+                };
+            }
+            (None, PinningMode::Registry) => {
+                return Err(IdentityPinError::Unpinned(
+                    "peer did not present a certificate".into(),
+                ))
+            }
+        };
+
+        if let Some(pinned) = self.registry_fingerprint(identity)? {
+            return self.check(identity, pinned, fingerprint, remote_endpoint);
+        }
+
+        match self.mode {
+            PinningMode::Registry => Err(IdentityPinError::Unpinned(format!(
+                "node {} does not have a {} in the registry",
+                identity, TLS_FINGERPRINT_METADATA_KEY
+            ))),
+            PinningMode::TrustOnFirstUse => {
+                let pinned = self
+                    .store
+                    .pin_identity(identity, &fingerprint)
+                    .map_err(IdentityPinError::Internal)?;
+                if pinned == fingerprint {
+                    debug!("Identity {} is pinned to {}", identity, fingerprint);
+                }
+                self.check(identity, pinned, fingerprint, remote_endpoint)
+            }
+        }
+    }
+
+    fn registry_fingerprint(&self, identity: &str) -> Result<Option<String>, IdentityPinError> {
+        let registry = match &self.registry {
+            Some(registry) => registry,
+            None => return Ok(None),
+        };
+        let node = registry
+            .fetch_node(identity)
+            .map_err(|err| IdentityPinError::Internal(InternalError::from_source(Box::new(err))))?;
+        Ok(node.and_then(|node| {
+            node.metadata
+                .get(TLS_FINGERPRINT_METADATA_KEY)
+                .map(|fingerprint| normalize_fingerprint(fingerprint))
+        }))
+    }
+
+    fn check(
+        &self,
+        identity: &str,
+        pinned_fingerprint: String,
+        presented_fingerprint: String,
+        remote_endpoint: &str,
+    ) -> Result<(), IdentityPinError> {
+        if pinned_fingerprint == presented_fingerprint {
+            return Ok(());
+        }
+
+        let mismatch = IdentityMismatch {
+            identity: identity.to_string(),
+            remote_endpoint: remote_endpoint.to_string(),
+            pinned_fingerprint,
+            presented_fingerprint,
+            timestamp: SystemTime::now(),
+        };
+        error!(
+            "Peer at {} claimed identity {} with certificate {}, but the identity is pinned to {}",
+            mismatch.remote_endpoint,
+            mismatch.identity,
+            mismatch.presented_fingerprint,
+            mismatch.pinned_fingerprint
+        );
+        #[cfg(feature = "metrics")]
+        metrics::counter!("splinter.network.peer_identity_mismatches", 1);
+
+        match self.mismatches.lock() {
+            Ok(mut mismatches) => {
+                if mismatches.len() == MAX_RECORDED_MISMATCHES {
+                    mismatches.pop_front();
+                }
+                mismatches.push_back(mismatch.clone());
+            }
+            Err(_) => error!("Unable to record identity mismatch: lock poisoned"),
+        }
+
+        Err(IdentityPinError::Mismatch(mismatch))
+    }
+}
+
+/// Normalizes a hex fingerprint, which may be colon-separated as printed by
+/// `openssl x509 -fingerprint`, to lowercase hex.
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| *c != ':')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[derive(Debug)]
+pub enum IdentityPinError {
+    /// The peer presented a certificate other than the one its identity is pinned to
+    Mismatch(IdentityMismatch),
+    /// The peer's identity cannot be checked because it has no pinned fingerprint
+    Unpinned(String),
+    Internal(InternalError),
+}
+
+impl std::error::Error for IdentityPinError {}
+
+impl fmt::Display for IdentityPinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdentityPinError::Mismatch(mismatch) => write!(
+                f,
+                "certificate {} does not match {}, the pinned certificate of {}",
+                mismatch.presented_fingerprint, mismatch.pinned_fingerprint, mismatch.identity
+            ),
+            IdentityPinError::Unpinned(msg) => f.write_str(msg),
+            IdentityPinError::Internal(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    use crate::registry::{MetadataPredicate, Node, NodeIter, RegistryError};
+
+    struct MemRegistry {
+        nodes: HashMap<String, Node>,
+    }
+
+    impl RegistryReader for MemRegistry {
+        fn list_nodes<'a, 'b: 'a>(
+            &'b self,
+            predicates: &'a [MetadataPredicate],
+        ) -> Result<NodeIter<'a>, RegistryError> {
+            Ok(Box::new(
+                self.nodes
+                    .values()
+                    .filter(|node| predicates.iter().all(|predicate| predicate.apply(node)))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ))
+        }
+
+        fn count_nodes(&self, predicates: &[MetadataPredicate]) -> Result<u32, RegistryError> {
+            self.list_nodes(predicates).map(|iter| iter.count() as u32)
+        }
+
+        fn fetch_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
+            Ok(self.nodes.get(identity).cloned())
+        }
+    }
+
+    fn registry() -> Box<dyn RegistryReader> {
+        let node = Node::builder("node-a")
+            .with_endpoint("tcps://127.0.0.1:8044")
+            .with_display_name("Node A")
+            .with_key("0123")
+            .with_metadata(TLS_FINGERPRINT_METADATA_KEY, "AA:BB:CC")
+            .build()
+            .expect("Failed to build node");
+        let mut nodes = HashMap::new();
+        nodes.insert(node.identity.clone(), node);
+        Box::new(MemRegistry { nodes })
+    }
+
+    /// Verify that, on first use, a node is pinned to the first certificate it presents and that
+    /// later connections with another certificate are refused and recorded until the pin is
+    /// removed.
+    #[test]
+    fn test_trust_on_first_use() {
+        let verifier = IdentityPinVerifier::new(
+            PinningMode::TrustOnFirstUse,
+            Box::new(MemoryIdentityPinStore::new()),
+        );
+
+        assert!(verifier.verify("node-b", Some("0102"), "tcps://a").is_ok());
+        assert!(verifier.verify("node-b", Some("01:02"), "tcps://a").is_ok());
+        match verifier.verify("node-b", Some("0304"), "tcps://b") {
+            Err(IdentityPinError::Mismatch(mismatch)) => {
+                assert_eq!(mismatch.pinned_fingerprint, "0102");
+                assert_eq!(mismatch.presented_fingerprint, "0304");
+                assert_eq!(mismatch.remote_endpoint, "tcps://b");
+            }
+            res => panic!("Expected a mismatch, got {:?}", res),
+        }
+        assert_eq!(verifier.mismatches().len(), 1);
+
+        // peers without certificates cannot be pinned
+        assert!(verifier.verify("node-c", None, "tcp://c").is_ok());
+        assert!(verifier.verify("node-c", Some("0506"), "tcps://c").is_ok());
+
+        assert!(verifier.remove_pin("node-b").expect("Failed to remove pin"));
+        assert!(verifier.verify("node-b", Some("0304"), "tcps://b").is_ok());
+    }
+
+    /// Verify that registry pins take precedence and that, in registry mode, nodes without a
+    /// registry pin are refused.
+    #[test]
+    fn test_registry_pins() {
+        let tofu = IdentityPinVerifier::new(
+            PinningMode::TrustOnFirstUse,
+            Box::new(MemoryIdentityPinStore::new()),
+        )
+        .with_registry(registry());
+        assert!(tofu.verify("node-a", Some("aabbcc"), "tcps://a").is_ok());
+        assert!(tofu.verify("node-a", Some("0102"), "tcps://a").is_err());
+        assert!(tofu.verify("node-b", Some("0102"), "tcps://b").is_ok());
+
+        let registry_only = IdentityPinVerifier::new(
+            PinningMode::Registry,
+            Box::new(MemoryIdentityPinStore::new()),
+        )
+        .with_registry(registry());
+        assert!(registry_only
+            .verify("node-a", Some("aabbcc"), "tcps://a")
+            .is_ok());
+        assert!(registry_only
+            .verify("node-b", Some("0102"), "tcps://b")
+            .is_err());
+        assert!(registry_only.verify("node-a", None, "tcps://a").is_err());
+    }
+
+    /// Verify that a peer that presents no certificate, such as one connected over plain TCP, is
+    /// refused and recorded as a mismatch when it claims an identity that is pinned, either on
+    /// first use or in the registry.
+    #[test]
+    fn test_pinned_identity_without_certificate() {
+        let verifier = IdentityPinVerifier::new(
+            PinningMode::TrustOnFirstUse,
+            Box::new(MemoryIdentityPinStore::new()),
+        )
+        .with_registry(registry());
+
+        assert!(verifier.verify("node-b", Some("0102"), "tcps://b").is_ok());
+        match verifier.verify("node-b", None, "tcp://attacker") {
+            Err(IdentityPinError::Mismatch(mismatch)) => {
+                assert_eq!(mismatch.identity, "node-b");
+                assert_eq!(mismatch.pinned_fingerprint, "0102");
+                assert_eq!(mismatch.presented_fingerprint, NO_CERTIFICATE);
+                assert_eq!(mismatch.remote_endpoint, "tcp://attacker");
+            }
+            res => panic!("Expected a mismatch, got {:?}", res),
+        }
+
+        // node-a is pinned in the registry
+        match verifier.verify("node-a", None, "tcp://attacker") {
+            Err(IdentityPinError::Mismatch(mismatch)) => {
+                assert_eq!(mismatch.pinned_fingerprint, "aabbcc");
+            }
+            res => panic!("Expected a mismatch, got {:?}", res),
+        }
+        assert_eq!(verifier.mismatches().len(), 2);
+
+        // identities that are not pinned may still connect without a certificate
+        assert!(verifier.verify("node-c", None, "tcp://c").is_ok());
+    }
+
+    /// Verify that the YAML store persists pins and keeps the first pin of an identity.
+    #[test]
+    fn test_yaml_identity_pin_store() {
+        let temp_dir = TempDir::new("test_yaml_identity_pin_store").expect("Failed to create dir");
+        let path = temp_dir.path().join("identity_pins.yaml");
+
+        let store = YamlIdentityPinStore::new(&path);
+        assert_eq!(
+            store.pin_identity("node-a", "0102").expect("Failed to pin"),
+            "0102"
+        );
+        assert_eq!(
+            store.pin_identity("node-a", "0304").expect("Failed to pin"),
+            "0102"
+        );
+
+        let reopened = YamlIdentityPinStore::new(&path);
+        let pins = reopened.list_pins().expect("Failed to list pins");
+        assert_eq!(pins.get("node-a").map(String::as_str), Some("0102"));
+
+        assert!(reopened.remove_pin("node-a").expect("Failed to remove pin"));
+        assert!(!reopened.remove_pin("node-a").expect("Failed to remove pin"));
+        assert!(store.list_pins().expect("Failed to list pins").is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SPIFFE identity verification for peer connections.
+//!
+//! When nodes authenticate with SPIFFE X.509 SVIDs, the certificate a peer presents carries its
+//! SPIFFE ID as a URI subject alternative name. The registry maps each node to the SPIFFE ID of
+//! its workload through the `spiffe_id` metadata entry, so a peer that claims a node identity
+//! during authorization is only accepted if its certificate holds that node's SPIFFE ID.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::registry::RegistryReader;
+
+/// The registry node metadata key holding the node's SPIFFE ID.
+pub const SPIFFE_ID_METADATA_KEY: &str = "spiffe_id";
+
+const SPIFFE_SCHEME: &str = "spiffe://";
+
+/// Verifies that the identity claimed by a peer matches the SPIFFE ID in its certificate.
+pub struct SpiffeIdVerifier {
+    trust_domain: String,
+    registry: Box<dyn RegistryReader>,
+    require_id: bool,
+}
+
+impl SpiffeIdVerifier {
+    /// Creates a verifier that accepts SPIFFE IDs from the given trust domain, mapping them to
+    /// node identities using the given registry.
+    pub fn new(trust_domain: &str, registry: Box<dyn RegistryReader>) -> Self {
+        SpiffeIdVerifier {
+            trust_domain: trust_domain.to_lowercase(),
+            registry,
+            require_id: false,
+        }
+    }
+
+    /// Sets whether peers must present a SPIFFE ID. When not required, peers whose certificates
+    /// do not contain a SPIFFE ID are accepted without being mapped to a registry node.
+    pub fn with_require_id(mut self, require_id: bool) -> Self {
+        self.require_id = require_id;
+        self
+    }
+
+    /// Verifies the identity claimed by a peer against the URI subject alternative names of the
+    /// certificate it presented.
+    pub fn verify(
+        &self,
+        identity: &str,
+        peer_certificate_uris: &[String],
+    ) -> Result<(), SpiffeIdError> {
+        let spiffe_ids = peer_certificate_uris
+            .iter()
+            .filter(|uri| uri.starts_with(SPIFFE_SCHEME))
+            .collect::<Vec<_>>();
+
+        let spiffe_id = match spiffe_ids.as_slice() {
+            [] if self.require_id => {
+                return Err(SpiffeIdError(
+                    "peer certificate does not contain a SPIFFE ID".into(),
+                ))
+            }
+            [] => return Ok(()),
+            [spiffe_id] => spiffe_id.as_str(),
+            _ => {
+                return Err(SpiffeIdError(
+                    "peer certificate contains more than one SPIFFE ID".into(),
+                ))
+            }
+        };
+
+        let trust_domain = trust_domain(spiffe_id)
+            .ok_or_else(|| SpiffeIdError(format!("invalid SPIFFE ID {}", spiffe_id)))?;
+        if trust_domain != self.trust_domain {
+            return Err(SpiffeIdError(format!(
+                "SPIFFE ID {} is not in trust domain {}",
+                spiffe_id, self.trust_domain
+            )));
+        }
+
+        let node = self
+            .registry
+            .fetch_node(identity)
+            .map_err(|err| SpiffeIdError(format!("unable to fetch node {}: {}", identity, err)))?
+            .ok_or_else(|| SpiffeIdError(format!("node {} is not in the registry", identity)))?;
+
+        match node.metadata.get(SPIFFE_ID_METADATA_KEY) {
+            Some(node_spiffe_id) if node_spiffe_id == spiffe_id => Ok(()),
+            Some(node_spiffe_id) => Err(SpiffeIdError(format!(
+                "SPIFFE ID {} does not match {}, the SPIFFE ID of node {}",
+                spiffe_id, node_spiffe_id, identity
+            ))),
+            None => Err(SpiffeIdError(format!(
+                "node {} does not have a SPIFFE ID in the registry",
+                identity
+            ))),
+        }
+    }
+}
+
+/// Returns the trust domain of the given SPIFFE ID, or `None` if it is not a valid workload
+/// SPIFFE ID.
+fn trust_domain(spiffe_id: &str) -> Option<String> {
+    let rest = spiffe_id.strip_prefix(SPIFFE_SCHEME)?;
+    let (trust_domain, path) = rest.split_at(rest.find('/')?);
+    if trust_domain.is_empty()
+        || path.len() < 2
+        || path.ends_with('/')
+        || path.contains("//")
+        || path.contains(|c| c == '?' || c == '#')
+    {
+        return None;
+    }
+    Some(trust_domain.to_lowercase())
+}
+
+#[derive(Debug)]
+pub struct SpiffeIdError(pub String);
+
+impl Error for SpiffeIdError {}
+
+impl fmt::Display for SpiffeIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::registry::{MetadataPredicate, Node, NodeIter, RegistryError};
+
+    struct MemRegistry {
+        nodes: HashMap<String, Node>,
+    }
+
+    impl RegistryReader for MemRegistry {
+        fn list_nodes<'a, 'b: 'a>(
+            &'b self,
+            predicates: &'a [MetadataPredicate],
+        ) -> Result<NodeIter<'a>, RegistryError> {
+            Ok(Box::new(
+                self.nodes
+                    .values()
+                    .filter(|node| predicates.iter().all(|predicate| predicate.apply(node)))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ))
+        }
+
+        fn count_nodes(&self, predicates: &[MetadataPredicate]) -> Result<u32, RegistryError> {
+            self.list_nodes(predicates).map(|iter| iter.count() as u32)
+        }
+
+        fn fetch_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
+            Ok(self.nodes.get(identity).cloned())
+        }
+    }
+
+    fn verifier() -> SpiffeIdVerifier {
+        let node = Node::builder("node-a")
+            .with_endpoint("tcps://127.0.0.1:8044")
+            .with_display_name("Node A")
+            .with_key("0123")
+            .with_metadata(
+                SPIFFE_ID_METADATA_KEY,
+                "spiffe://example.org/splinter/node-a",
+            )
+            .build()
+            .expect("Failed to build node");
+        let mut nodes = HashMap::new();
+        nodes.insert(node.identity.clone(), node);
+
+        SpiffeIdVerifier::new("example.org", Box::new(MemRegistry { nodes }))
+    }
+
+    /// Verify that a peer is accepted only if its SPIFFE ID is the one recorded in the registry
+    /// for the identity it claims.
+    #[test]
+    fn test_verify_spiffe_id() {
+        let verifier = verifier();
+        let uris = vec!["spiffe://example.org/splinter/node-a".to_string()];
+
+        assert!(verifier.verify("node-a", &uris).is_ok());
+        // the SPIFFE ID belongs to a different node
+        assert!(verifier.verify("node-b", &uris).is_err());
+        // the SPIFFE ID is not the one registered for the node
+        assert!(verifier
+            .verify(
+                "node-a",
+                &["spiffe://example.org/splinter/node-b".to_string()]
+            )
+            .is_err());
+        // the SPIFFE ID is from another trust domain
+        assert!(verifier
+            .verify(
+                "node-a",
+                &["spiffe://other.org/splinter/node-a".to_string()]
+            )
+            .is_err());
+        // the certificate contains more than one SPIFFE ID
+        assert!(verifier
+            .verify(
+                "node-a",
+                &[
+                    "spiffe://example.org/splinter/node-a".to_string(),
+                    "spiffe://example.org/splinter/node-b".to_string(),
+                ]
+            )
+            .is_err());
+    }
+
+    /// Verify that peers without a SPIFFE ID are rejected only when one is required.
+    #[test]
+    fn test_verify_missing_spiffe_id() {
+        let uris = vec!["https://example.org/node-a".to_string()];
+
+        assert!(verifier().verify("node-a", &uris).is_ok());
+        assert!(verifier()
+            .with_require_id(true)
+            .verify("node-a", &uris)
+            .is_err());
+    }
+
+    /// Verify that the trust domain is parsed from valid SPIFFE IDs only.
+    #[test]
+    fn test_trust_domain() {
+        assert_eq!(
+            trust_domain("spiffe://Example.org/splinter/node-a"),
+            Some("example.org".to_string())
+        );
+        assert_eq!(trust_domain("spiffe://example.org"), None);
+        assert_eq!(trust_domain("spiffe://example.org/"), None);
+        assert_eq!(trust_domain("spiffe:///splinter/node-a"), None);
+        assert_eq!(trust_domain("spiffe://example.org/node-a?x=1"), None);
+        assert_eq!(trust_domain("https://example.org/node-a"), None);
+    }
+}
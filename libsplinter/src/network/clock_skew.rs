@@ -0,0 +1,195 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimates how far each peer's clock is from the local clock.
+//!
+//! Every network heartbeat carries the time it was sent. The difference between that time and
+//! the time the heartbeat is received is the peer's clock offset, less the time the heartbeat
+//! spent in transit. Transit times are small compared to the skew that affects consensus and
+//! proposal timestamps, so the offset is estimated from heartbeats alone, smoothed over several
+//! heartbeats.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::InternalError;
+
+/// The default offset above which a peer's clock is considered skewed
+pub const DEFAULT_CLOCK_SKEW_THRESHOLD: Duration = Duration::from_secs(5);
+
+// Peers that have not sent a heartbeat in this long are no longer reported
+const STALE_AFTER: Duration = Duration::from_secs(300);
+
+// The weight of each new sample in the estimated offset
+const SMOOTHING_FACTOR: f64 = 0.25;
+
+struct PeerClock {
+    offset_millis: f64,
+    updated: Instant,
+    skewed: bool,
+}
+
+/// Tracks the clock offset of each peer from the heartbeats it sends.
+///
+/// A warning is logged when a peer's offset first exceeds the threshold, and again once it
+/// returns within the threshold.
+#[derive(Clone)]
+pub struct ClockSkewMonitor {
+    threshold: Duration,
+    peers: Arc<Mutex<HashMap<String, PeerClock>>>,
+}
+
+impl ClockSkewMonitor {
+    /// Creates a new `ClockSkewMonitor`
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The offset above which a peer's clock is considered skewed
+    pub fn new(threshold: Duration) -> Self {
+        ClockSkewMonitor {
+            threshold,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the offset above which a peer's clock is considered skewed
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Records a heartbeat received from a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer that sent the heartbeat
+    /// * `sent_millis` - The time the heartbeat was sent, according to the peer, in milliseconds
+    ///   since the Unix epoch
+    /// * `received_millis` - The time the heartbeat was received, in milliseconds since the Unix
+    ///   epoch
+    pub fn record(
+        &self,
+        peer_id: &str,
+        sent_millis: u64,
+        received_millis: u64,
+    ) -> Result<(), InternalError> {
+        let sample = sent_millis as f64 - received_millis as f64;
+        let threshold_millis = self.threshold.as_millis() as f64;
+
+        let mut peers = self.lock()?;
+        let peer = peers
+            .entry(peer_id.to_string())
+            .and_modify(|peer| {
+                if peer.updated.elapsed() < STALE_AFTER {
+                    peer.offset_millis += SMOOTHING_FACTOR * (sample - peer.offset_millis);
+                } else {
+                    peer.offset_millis = sample;
+                }
+                peer.updated = Instant::now();
+            })
+            .or_insert_with(|| PeerClock {
+                offset_millis: sample,
+                updated: Instant::now(),
+                skewed: false,
+            });
+
+        let skewed = peer.offset_millis.abs() > threshold_millis;
+        if skewed && !peer.skewed {
+            warn!(
+                "Clock of peer {} is {}ms {} the local clock, exceeding the threshold of {}ms",
+                peer_id,
+                peer.offset_millis.abs().round(),
+                if peer.offset_millis > 0.0 {
+                    "ahead of"
+                } else {
+                    "behind"
+                },
+                self.threshold.as_millis()
+            );
+        } else if !skewed && peer.skewed {
+            info!(
+                "Clock of peer {} is within the skew threshold again",
+                peer_id
+            );
+        }
+        peer.skewed = skewed;
+
+        Ok(())
+    }
+
+    /// Returns the estimated offset of the peer's clock from the local clock, in milliseconds.
+    /// The offset is positive if the peer's clock is ahead.
+    ///
+    /// Returns `None` if the peer has not sent a timestamped heartbeat recently.
+    pub fn offset_millis(&self, peer_id: &str) -> Result<Option<i64>, InternalError> {
+        Ok(self
+            .lock()?
+            .get(peer_id)
+            .filter(|peer| peer.updated.elapsed() < STALE_AFTER)
+            .map(|peer| peer.offset_millis.round() as i64))
+    }
+
+    /// Returns the peers whose clock offset exceeds the threshold, along with their offsets in
+    /// milliseconds, sorted by peer ID.
+    pub fn skewed_peers(&self) -> Result<Vec<(String, i64)>, InternalError> {
+        let mut skewed = self
+            .lock()?
+            .iter()
+            .filter(|(_, peer)| peer.skewed && peer.updated.elapsed() < STALE_AFTER)
+            .map(|(peer_id, peer)| (peer_id.to_string(), peer.offset_millis.round() as i64))
+            .collect::<Vec<_>>();
+        skewed.sort();
+        Ok(skewed)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<HashMap<String, PeerClock>>, InternalError> {
+        self.peers
+            .lock()
+            .map_err(|_| InternalError::with_message("Clock skew monitor lock was poisoned".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the offset of a peer is estimated from its heartbeats, and that the peer is
+    /// reported as skewed while the offset exceeds the threshold.
+    #[test]
+    fn test_clock_skew() {
+        let monitor = ClockSkewMonitor::new(Duration::from_secs(1));
+
+        assert_eq!(None, monitor.offset_millis("peer-a").unwrap());
+
+        monitor.record("peer-a", 10_000, 10_000).unwrap();
+        monitor.record("peer-b", 10_000, 12_000).unwrap();
+        assert_eq!(Some(0), monitor.offset_millis("peer-a").unwrap());
+        assert_eq!(Some(-2000), monitor.offset_millis("peer-b").unwrap());
+        assert_eq!(
+            vec![("peer-b".to_string(), -2000)],
+            monitor.skewed_peers().unwrap()
+        );
+
+        // A single late heartbeat only moves the estimate part of the way
+        monitor.record("peer-a", 20_000, 18_000).unwrap();
+        assert_eq!(Some(500), monitor.offset_millis("peer-a").unwrap());
+
+        // The estimate converges on the offset of the peer's clock
+        for _ in 0..10 {
+            monitor.record("peer-b", 20_000, 20_010).unwrap();
+        }
+        assert_eq!(Some(-122), monitor.offset_millis("peer-b").unwrap());
+        assert!(monitor.skewed_peers().unwrap().is_empty());
+    }
+}
@@ -12,14 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
 use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use protobuf::Message;
 
 use crate::protos::network::{NetworkHeartbeat, NetworkMessage, NetworkMessageType};
+use crate::threading::clock::{Clock, SystemClock};
 use crate::threading::pacemaker;
-use crate::transport::matrix::{ConnectionMatrixLifeCycle, ConnectionMatrixSender};
+use crate::transport::matrix::{ConnectionMatrixLifeCycle, ConnectionMatrixSender, SendLimits};
 use crate::transport::Transport;
 
 use super::error::ConnectionManagerError;
@@ -38,6 +42,9 @@ pub struct ConnectionManagerBuilder<T, U> {
     transport: Option<Box<dyn Transport + Send>>,
     heartbeat_interval: u64,
     maximum_retry_frequency: u64,
+    clock: Arc<dyn Clock>,
+    send_limits: SendLimits,
+    peer_send_limits: HashMap<String, SendLimits>,
 }
 
 impl<T, U> Default for ConnectionManagerBuilder<T, U> {
@@ -49,6 +56,9 @@ impl<T, U> Default for ConnectionManagerBuilder<T, U> {
             transport: None,
             heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
             maximum_retry_frequency: DEFAULT_MAXIMUM_RETRY_FREQUENCY,
+            clock: Arc::new(SystemClock),
+            send_limits: SendLimits::default(),
+            peer_send_limits: HashMap::new(),
         }
     }
 }
@@ -119,6 +129,32 @@ where
         self
     }
 
+    /// Set the limits on the outgoing messages of the connections managed by the resulting
+    /// connection manager.
+    ///
+    /// The limits are passed to the connection matrix life-cycle as each connection is added.
+    pub fn with_send_limits(mut self, send_limits: SendLimits) -> Self {
+        self.send_limits = send_limits;
+        self
+    }
+
+    /// Set the limits on the outgoing messages of the connections authorized as the given
+    /// identity, such as a peer's node ID, instead of the limits set by `with_send_limits`.
+    pub fn with_peer_send_limits(mut self, identity: String, send_limits: SendLimits) -> Self {
+        self.peer_send_limits.insert(identity, send_limits);
+        self
+    }
+
+    /// Set the clock used by the resulting connection manager.
+    ///
+    /// The clock drives the heartbeat interval and measures the time between reconnection
+    /// attempts. Defaults to the `SystemClock`; tests may provide a `SimulatedClock` to exercise
+    /// reconnection backoff without waiting on real time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Create a started connection manager instance.
     ///
     /// This function creates and starts a `ConnectionManager` instance, which includes a
@@ -132,6 +168,7 @@ where
         let (sender, recv) = channel();
         let heartbeat = self.heartbeat_interval;
         let retry_frequency = self.maximum_retry_frequency;
+        let clock = self.clock.clone();
 
         let authorizer = self
             .authorizer
@@ -150,6 +187,9 @@ where
             ConnectionManagerError::StartUpError("No matrix life cycle provided".into())
         })?;
 
+        let send_limits = self.send_limits;
+        let peer_send_limits = std::mem::take(&mut self.peer_send_limits);
+
         let resender = sender.clone();
         let join_handle = thread::Builder::new()
             .name("Connection Manager".into())
@@ -159,7 +199,9 @@ where
                     matrix_sender,
                     transport,
                     retry_frequency,
-                );
+                    clock,
+                )
+                .with_send_limits(send_limits, peer_send_limits);
                 let mut subscribers = SubscriberMap::new();
                 loop {
                     match recv.recv() {
@@ -198,6 +240,7 @@ where
             .with_interval(heartbeat)
             .with_sender(sender.clone())
             .with_message_factory(|| CmMessage::SendHeartbeats)
+            .with_clock(self.clock.clone())
             .start()
             .map_err(|err| ConnectionManagerError::StartUpError(err.to_string()))?;
 
@@ -311,6 +354,7 @@ fn send_heartbeats<T: ConnectionMatrixLifeCycle, U: ConnectionMatrixSender>(
     };
 
     let matrix_sender = state.matrix_sender();
+    let clock = state.clock();
     let mut reconnections = vec![];
     for (endpoint, metadata) in state.connection_metadata_mut().iter_mut() {
         match metadata.extended_metadata {
@@ -322,7 +366,7 @@ fn send_heartbeats<T: ConnectionMatrixLifeCycle, U: ConnectionMatrixSender>(
             } => {
                 // if connection is already attempting reconnection, call reconnect
                 if reconnecting {
-                    if last_connection_attempt.elapsed().as_secs() > retry_frequency {
+                    if clock.elapsed(last_connection_attempt).as_secs() > retry_frequency {
                         reconnections.push(endpoint.to_string());
                     }
                 } else {
@@ -381,9 +425,17 @@ fn send_heartbeats<T: ConnectionMatrixLifeCycle, U: ConnectionMatrixSender>(
     }
 }
 
-/// Creates NetworkHeartbeat message and serializes it into a byte array.
+/// Creates NetworkHeartbeat message, stamped with the current time, and serializes it into a byte
+/// array.
 fn create_heartbeat() -> Result<Vec<u8>, ConnectionManagerError> {
-    let heartbeat = NetworkHeartbeat::new().write_to_bytes().map_err(|_| {
+    let mut heartbeat = NetworkHeartbeat::new();
+    heartbeat.set_timestamp(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_millis() as u64)
+            .unwrap_or(0),
+    );
+    let heartbeat = heartbeat.write_to_bytes().map_err(|_| {
         ConnectionManagerError::HeartbeatError("cannot create NetworkHeartbeat message".to_string())
     })?;
     let mut heartbeat_message = NetworkMessage::new();
@@ -20,6 +20,7 @@ mod notification;
 use std::cmp::min;
 use std::collections::HashMap;
 use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
@@ -29,8 +30,9 @@ pub use builder::ConnectionManagerBuilder;
 pub use error::{AuthorizerError, ConnectionManagerError};
 pub use notification::ConnectionManagerNotification;
 
+use crate::threading::clock::Clock;
 use crate::threading::pacemaker;
-use crate::transport::matrix::{ConnectionMatrixLifeCycle, ConnectionMatrixSender};
+use crate::transport::matrix::{ConnectionMatrixLifeCycle, ConnectionMatrixSender, SendLimits};
 use crate::transport::{ConnectError, Connection, Transport};
 
 const INITIAL_RETRY_FREQUENCY: u64 = 10;
@@ -430,7 +432,10 @@ struct ConnectionMetadata {
 
 impl ConnectionMetadata {
     fn is_outbound(&self) -> bool {
-        matches!(self.extended_metadata, ConnectionMetadataExt::Outbound { .. })
+        matches!(
+            self.extended_metadata,
+            ConnectionMetadataExt::Outbound { .. }
+        )
     }
 
     fn connection_id(&self) -> &str {
@@ -474,6 +479,9 @@ where
     matrix_sender: U,
     transport: Box<dyn Transport>,
     maximum_retry_frequency: u64,
+    clock: Arc<dyn Clock>,
+    send_limits: SendLimits,
+    peer_send_limits: HashMap<String, SendLimits>,
 }
 
 impl<T, U> ConnectionManagerState<T, U>
@@ -486,6 +494,7 @@ where
         matrix_sender: U,
         transport: Box<dyn Transport + Send>,
         maximum_retry_frequency: u64,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             life_cycle,
@@ -493,9 +502,41 @@ where
             transport,
             connections: HashMap::new(),
             maximum_retry_frequency,
+            clock,
+            send_limits: SendLimits::default(),
+            peer_send_limits: HashMap::new(),
         }
     }
 
+    fn with_send_limits(
+        mut self,
+        send_limits: SendLimits,
+        peer_send_limits: HashMap<String, SendLimits>,
+    ) -> Self {
+        self.send_limits = send_limits;
+        self.peer_send_limits = peer_send_limits;
+        self
+    }
+
+    /// Adds an authorized connection to the matrix life cycle with the send limits of its
+    /// identity.
+    fn add_to_life_cycle(
+        &self,
+        connection: Box<dyn Connection>,
+        connection_id: String,
+        identity: &str,
+    ) -> Result<usize, ConnectionManagerError> {
+        let send_limits = self
+            .peer_send_limits
+            .get(identity)
+            .copied()
+            .unwrap_or(self.send_limits);
+
+        self.life_cycle
+            .add_with_send_limits(connection, connection_id, send_limits)
+            .map_err(|err| ConnectionManagerError::connection_creation_error(&err.to_string()))
+    }
+
     /// Adds a new connection as an inbound connection.
     fn add_inbound_connection(
         &mut self,
@@ -645,12 +686,8 @@ where
                 connection,
                 identity,
             } => {
-                if let Err(err) = self
-                    .life_cycle
-                    .add(connection, connection_id.clone())
-                    .map_err(|err| {
-                        ConnectionManagerError::connection_creation_error(&err.to_string())
-                    })
+                if let Err(err) =
+                    self.add_to_life_cycle(connection, connection_id.clone(), &identity)
                 {
                     subscribers.broadcast(ConnectionManagerNotification::FatalConnectionError {
                         endpoint,
@@ -669,7 +706,7 @@ where
                         extended_metadata: ConnectionMetadataExt::Outbound {
                             reconnecting: false,
                             retry_frequency: INITIAL_RETRY_FREQUENCY,
-                            last_connection_attempt: Instant::now(),
+                            last_connection_attempt: self.clock.now(),
                             reconnection_attempts: 0,
                         },
                     },
@@ -713,12 +750,8 @@ where
                 connection,
                 identity,
             } => {
-                if let Err(err) = self
-                    .life_cycle
-                    .add(connection, connection_id.clone())
-                    .map_err(|err| {
-                        ConnectionManagerError::connection_creation_error(&err.to_string())
-                    })
+                if let Err(err) =
+                    self.add_to_life_cycle(connection, connection_id.clone(), &identity)
                 {
                     subscribers.broadcast(ConnectionManagerNotification::FatalConnectionError {
                         endpoint,
@@ -852,7 +885,7 @@ where
                 } => {
                     *reconnecting = true;
                     *retry_frequency = min(*retry_frequency * 2, self.maximum_retry_frequency);
-                    *last_connection_attempt = Instant::now();
+                    *last_connection_attempt = self.clock.now();
                     *reconnection_attempts += 1;
 
                     *reconnection_attempts
@@ -884,6 +917,10 @@ where
     fn matrix_sender(&self) -> U {
         self.matrix_sender.clone()
     }
+
+    fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
 }
 
 #[cfg(test)]
@@ -891,12 +928,15 @@ mod tests {
     use super::*;
 
     use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::time::Duration;
 
-    use crate::mesh::Mesh;
+    use crate::mesh::{Mesh, MeshLifeCycle};
     use crate::network::auth::tests::negotiation_connection_auth;
     use crate::network::auth::AuthorizationManager;
     use crate::protos::network::{NetworkMessage, NetworkMessageType};
     use crate::transport::inproc::InprocTransport;
+    use crate::transport::matrix::{ConnectionMatrixAddError, ConnectionMatrixRemoveError};
     use crate::transport::socket::TcpTransport;
 
     #[test]
@@ -1468,6 +1508,103 @@ mod tests {
         auth_mgr.shutdown_and_await();
     }
 
+    /// Test that connections are added to the matrix life cycle with the send limits of the
+    /// identity they were authorized as, and with the default send limits otherwise.
+    #[test]
+    fn test_send_limits() {
+        let default_limits = SendLimits::new().with_outgoing_capacity(16);
+        let peer_limits = SendLimits::new()
+            .with_outgoing_capacity(32)
+            .with_send_weight(4);
+
+        for (identity, expected_limits) in &[
+            ("test_identity", peer_limits),
+            ("other_identity", default_limits),
+        ] {
+            let mut transport = Box::new(InprocTransport::default());
+            let mut listener = transport.listen("inproc://test").unwrap();
+
+            thread::spawn(move || {
+                listener.accept().unwrap();
+            });
+
+            let mesh = Mesh::new(512, 128);
+            let life_cycle = SendLimitsLifeCycle::new(mesh.get_life_cycle());
+            let cm = ConnectionManager::builder()
+                .with_authorizer(Box::new(NoopAuthorizer::new(identity)))
+                .with_matrix_life_cycle(life_cycle.clone())
+                .with_matrix_sender(mesh.get_sender())
+                .with_transport(transport)
+                .with_send_limits(default_limits)
+                .with_peer_send_limits("test_identity".into(), peer_limits)
+                .start()
+                .expect("Unable to start Connection Manager");
+
+            cm.connector()
+                .request_connection("inproc://test", "test_id")
+                .expect("A connection could not be created");
+
+            let mut count = 0;
+            while life_cycle.send_limits().is_empty() && count < 100 {
+                thread::sleep(Duration::from_millis(10));
+                count += 1;
+            }
+            assert_eq!(life_cycle.send_limits(), vec![*expected_limits]);
+
+            cm.shutdown_signaler().shutdown();
+            cm.await_shutdown();
+            mesh.shutdown_signaler().shutdown();
+        }
+    }
+
+    /// Records the send limits of the connections added to a mesh
+    #[derive(Clone)]
+    struct SendLimitsLifeCycle {
+        life_cycle: MeshLifeCycle,
+        send_limits: Arc<Mutex<Vec<SendLimits>>>,
+    }
+
+    impl SendLimitsLifeCycle {
+        fn new(life_cycle: MeshLifeCycle) -> Self {
+            Self {
+                life_cycle,
+                send_limits: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        fn send_limits(&self) -> Vec<SendLimits> {
+            self.send_limits.lock().expect("lock was poisoned").clone()
+        }
+    }
+
+    impl ConnectionMatrixLifeCycle for SendLimitsLifeCycle {
+        fn add(
+            &self,
+            connection: Box<dyn Connection>,
+            id: String,
+        ) -> Result<usize, ConnectionMatrixAddError> {
+            self.life_cycle.add(connection, id)
+        }
+
+        fn add_with_send_limits(
+            &self,
+            connection: Box<dyn Connection>,
+            id: String,
+            send_limits: SendLimits,
+        ) -> Result<usize, ConnectionMatrixAddError> {
+            self.send_limits
+                .lock()
+                .expect("lock was poisoned")
+                .push(send_limits);
+            self.life_cycle
+                .add_with_send_limits(connection, id, send_limits)
+        }
+
+        fn remove(&self, id: &str) -> Result<Box<dyn Connection>, ConnectionMatrixRemoveError> {
+            self.life_cycle.remove(id)
+        }
+    }
+
     struct NoopAuthorizer {
         authorized_id: String,
     }
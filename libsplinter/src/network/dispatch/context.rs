@@ -13,9 +13,49 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::ops::Range;
 
 use super::{ConnectionId, PeerId};
 
+/// The bytes of a message to be dispatched.
+///
+/// The message may be a range within a larger buffer, such as the payload of a message envelope,
+/// which allows it to be dispatched without copying it out of the buffer.
+pub(super) struct MessageBytes {
+    buffer: Vec<u8>,
+    range: Range<usize>,
+}
+
+impl MessageBytes {
+    /// Creates `MessageBytes` for the given range of the buffer. The range must be within the
+    /// buffer.
+    pub fn new(buffer: Vec<u8>, range: Range<usize>) -> Self {
+        debug_assert!(range.start <= range.end && range.end <= buffer.len());
+        MessageBytes { buffer, range }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[self.range.clone()]
+    }
+
+    /// Returns the message as a vector, which only copies the message if it is a part of a larger
+    /// buffer.
+    pub fn into_vec(self) -> Vec<u8> {
+        if self.range.start == 0 && self.range.end == self.buffer.len() {
+            self.buffer
+        } else {
+            self.buffer[self.range].to_vec()
+        }
+    }
+}
+
+impl From<Vec<u8>> for MessageBytes {
+    fn from(buffer: Vec<u8>) -> Self {
+        let range = 0..buffer.len();
+        MessageBytes { buffer, range }
+    }
+}
+
 /// The Message Context
 ///
 /// The message context provides information about an incoming message beyond its parsed bytes.  It
@@ -24,12 +64,12 @@ use super::{ConnectionId, PeerId};
 pub struct MessageContext<Source, MT> {
     source_id: Source,
     message_type: MT,
-    message_bytes: Vec<u8>,
+    message_bytes: MessageBytes,
     parent_context: Option<Box<dyn Any + Send>>,
 }
 
 impl<Source, MT> MessageContext<Source, MT> {
-    pub(super) fn new(message_type: MT, message_bytes: Vec<u8>, source_id: Source) -> Self {
+    pub(super) fn new(message_type: MT, message_bytes: MessageBytes, source_id: Source) -> Self {
         Self {
             message_type,
             message_bytes,
@@ -48,7 +88,7 @@ impl<Source, MT> MessageContext<Source, MT> {
 
     /// The raw message bytes.
     pub fn message_bytes(&self) -> &[u8] {
-        &self.message_bytes
+        self.message_bytes.as_slice()
     }
 
     pub fn source_id(&self) -> &Source {
@@ -17,9 +17,17 @@ use std::error::Error;
 use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::Range;
+#[cfg(feature = "dispatch-message-count")]
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, RecvError, Sender};
+#[cfg(feature = "dispatch-message-count")]
+use std::sync::Arc;
 
-use super::{Dispatcher, PeerId};
+#[cfg(feature = "runtime-diagnostics")]
+use crate::runtime::{QueueCounter, RuntimeMonitor, ThreadHeartbeat, ThreadMonitor};
+
+use super::{Dispatcher, MessageBytes, PeerId};
 
 /// A message to be dispatched.
 ///
@@ -31,7 +39,7 @@ where
 {
     Message {
         message_type: MT,
-        message_bytes: Vec<u8>,
+        message_bytes: MessageBytes,
         source_id: Source,
         parent_context: Option<Box<dyn Any + Send>>,
     },
@@ -129,37 +137,49 @@ where
             .thread_name
             .unwrap_or_else(|| format!("DispatchLoop({})", std::any::type_name::<MT>()));
 
+        #[cfg(feature = "runtime-diagnostics")]
+        let heartbeat = ThreadHeartbeat::new(&thread_name);
+        #[cfg(feature = "runtime-diagnostics")]
+        let thread_monitor = heartbeat.monitor();
+        #[cfg(feature = "runtime-diagnostics")]
+        let depth = rx.depth.clone();
+        #[cfg(feature = "dispatch-message-count")]
+        let message_count = DispatchMessageCount::default();
+        #[cfg(feature = "dispatch-message-count")]
+        let loop_message_count = message_count.clone();
+
         let join_handle = std::thread::Builder::new()
             .name(thread_name)
             .spawn(move || loop {
-                match rx.receiver.recv() {
+                #[cfg(feature = "runtime-diagnostics")]
+                heartbeat.idle();
+                let message = rx.receiver.recv();
+                #[cfg(feature = "runtime-diagnostics")]
+                {
+                    if let Ok(DispatchMessage::Message { .. }) = message {
+                        rx.depth.decrement();
+                        heartbeat.busy();
+                    }
+                }
+
+                match message {
                     Ok(DispatchMessage::Message {
                         message_type,
                         message_bytes,
                         source_id,
-                        parent_context: Some(context),
+                        parent_context,
                     }) => {
-                        if let Err(err) = dispatcher.dispatch_with_parent_context(
+                        #[cfg(feature = "dispatch-message-count")]
+                        loop_message_count.increment();
+                        if let Err(err) = dispatcher.dispatch_message_bytes(
                             source_id,
                             &message_type,
                             message_bytes,
-                            context,
+                            parent_context,
                         ) {
                             warn!("Unable to dispatch message: {:?}", err);
                         }
                     }
-                    Ok(DispatchMessage::Message {
-                        message_type,
-                        message_bytes,
-                        source_id,
-                        parent_context: None,
-                    }) => {
-                        if let Err(err) =
-                            dispatcher.dispatch(source_id, &message_type, message_bytes)
-                        {
-                            warn!("Unable to dispatch message: {:?}", err);
-                        }
-                    }
                     Ok(DispatchMessage::Shutdown) => {
                         debug!("Received shutdown signal");
                         break;
@@ -175,12 +195,35 @@ where
             Ok(join_handle) => Ok(DispatchLoop {
                 sender: tx.sender,
                 join_handle,
+                #[cfg(feature = "runtime-diagnostics")]
+                thread_monitor,
+                #[cfg(feature = "runtime-diagnostics")]
+                depth,
+                #[cfg(feature = "dispatch-message-count")]
+                message_count,
             }),
             Err(err) => Err(format!("Unable to start up dispatch loop thread: {}", err)),
         }
     }
 }
 
+/// Counts the messages a dispatch loop has passed to its dispatcher since it started.
+#[cfg(feature = "dispatch-message-count")]
+#[derive(Clone, Default)]
+pub struct DispatchMessageCount(Arc<AtomicU64>);
+
+#[cfg(feature = "dispatch-message-count")]
+impl DispatchMessageCount {
+    /// Returns the number of messages dispatched so far
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 /// The Dispatch Loop
 ///
 /// The dispatch loop processes messages that are pulled from a `Receiver<DispatchMessage>` and
@@ -192,6 +235,12 @@ where
 {
     sender: Sender<DispatchMessage<MT, Source>>,
     join_handle: std::thread::JoinHandle<()>,
+    #[cfg(feature = "runtime-diagnostics")]
+    thread_monitor: ThreadMonitor,
+    #[cfg(feature = "runtime-diagnostics")]
+    depth: QueueCounter,
+    #[cfg(feature = "dispatch-message-count")]
+    message_count: DispatchMessageCount,
 }
 
 impl<MT, Source> DispatchLoop<MT, Source>
@@ -207,9 +256,25 @@ where
     pub fn new_dispatcher_sender(&self) -> DispatchMessageSender<MT, Source> {
         DispatchMessageSender {
             sender: self.sender.clone(),
+            #[cfg(feature = "runtime-diagnostics")]
+            depth: self.depth.clone(),
         }
     }
 
+    /// Adds the dispatch loop's thread and its queue of messages waiting to be dispatched, both
+    /// named after the thread, to the given runtime monitor.
+    #[cfg(feature = "runtime-diagnostics")]
+    pub fn register_runtime_diagnostics(&self, monitor: &RuntimeMonitor) {
+        monitor.add_thread(self.thread_monitor.clone());
+        monitor.add_queue_counter(self.thread_monitor.name(), self.depth.clone());
+    }
+
+    /// Returns a counter of the messages the dispatch loop has passed to its dispatcher.
+    #[cfg(feature = "dispatch-message-count")]
+    pub fn message_count(&self) -> DispatchMessageCount {
+        self.message_count.clone()
+    }
+
     pub fn shutdown_signaler(&self) -> DispatchLoopShutdownSignaler<MT, Source> {
         DispatchLoopShutdownSignaler {
             sender: self.sender.clone(),
@@ -225,9 +290,19 @@ where
     MT: Any + Hash + Eq + Debug + Clone,
 {
     let (tx, rx) = channel();
+    #[cfg(feature = "runtime-diagnostics")]
+    let depth = QueueCounter::new();
     (
-        DispatchMessageSender { sender: tx },
-        DispatchMessageReceiver { receiver: rx },
+        DispatchMessageSender {
+            sender: tx,
+            #[cfg(feature = "runtime-diagnostics")]
+            depth: depth.clone(),
+        },
+        DispatchMessageReceiver {
+            receiver: rx,
+            #[cfg(feature = "runtime-diagnostics")]
+            depth,
+        },
     )
 }
 
@@ -236,6 +311,10 @@ where
     MT: Any + Hash + Eq + Debug + Clone,
 {
     receiver: Receiver<DispatchMessage<MT, Source>>,
+    // Counts the messages sent through the channel's senders, as the receiver cannot report its
+    // own length
+    #[cfg(feature = "runtime-diagnostics")]
+    depth: QueueCounter,
 }
 
 // These type defs make clippy happy.
@@ -248,6 +327,8 @@ where
     MT: Any + Hash + Eq + Debug + Clone,
 {
     sender: Sender<DispatchMessage<MT, Source>>,
+    #[cfg(feature = "runtime-diagnostics")]
+    depth: QueueCounter,
 }
 
 impl<MT, Source> DispatchMessageSender<MT, Source>
@@ -260,22 +341,59 @@ where
         message_bytes: Vec<u8>,
         source_id: Source,
     ) -> Result<(), MessageTuple<MT, Source>> {
-        self.sender
-            .send(DispatchMessage::Message {
+        self.send_message_bytes(message_type, message_bytes.into(), source_id)
+    }
+
+    /// Sends a message that is a range of the given buffer, such as the payload of a message
+    /// envelope, without copying the message out of the buffer.
+    ///
+    /// If the message cannot be sent, the message type, the message bytes and the source are
+    /// returned.
+    pub fn send_range(
+        &self,
+        message_type: MT,
+        buffer: Vec<u8>,
+        range: Range<usize>,
+        source_id: Source,
+    ) -> Result<(), MessageTuple<MT, Source>> {
+        // An invalid range is returned as a failure to send, along with the whole buffer
+        if range.start > range.end || range.end > buffer.len() {
+            return Err((message_type, buffer, source_id));
+        }
+
+        self.send_message_bytes(message_type, MessageBytes::new(buffer, range), source_id)
+    }
+
+    fn send_message_bytes(
+        &self,
+        message_type: MT,
+        message_bytes: MessageBytes,
+        source_id: Source,
+    ) -> Result<(), MessageTuple<MT, Source>> {
+        #[cfg(feature = "runtime-diagnostics")]
+        self.depth.increment();
+        let result = self.sender.send(DispatchMessage::Message {
+            message_type,
+            message_bytes,
+            source_id,
+            parent_context: None,
+        });
+        #[cfg(feature = "runtime-diagnostics")]
+        {
+            if result.is_err() {
+                self.depth.decrement();
+            }
+        }
+
+        result.map_err(|err| match err.0 {
+            DispatchMessage::Message {
                 message_type,
                 message_bytes,
                 source_id,
-                parent_context: None,
-            })
-            .map_err(|err| match err.0 {
-                DispatchMessage::Message {
-                    message_type,
-                    message_bytes,
-                    source_id,
-                    ..
-                } => (message_type, message_bytes, source_id),
-                DispatchMessage::Shutdown => unreachable!(), // we didn't send this
-            })
+                ..
+            } => (message_type, message_bytes.into_vec(), source_id),
+            DispatchMessage::Shutdown => unreachable!(), // we didn't send this
+        })
     }
 
     pub fn send_with_parent_context(
@@ -285,21 +403,29 @@ where
         source_id: Source,
         parent_context: Box<dyn Any + Send>,
     ) -> Result<(), MessageTupleWithParentContext<MT, Source>> {
-        self.sender
-            .send(DispatchMessage::Message {
+        #[cfg(feature = "runtime-diagnostics")]
+        self.depth.increment();
+        let result = self.sender.send(DispatchMessage::Message {
+            message_type,
+            message_bytes: message_bytes.into(),
+            source_id,
+            parent_context: Some(parent_context),
+        });
+        #[cfg(feature = "runtime-diagnostics")]
+        {
+            if result.is_err() {
+                self.depth.decrement();
+            }
+        }
+
+        result.map_err(|err| match err.0 {
+            DispatchMessage::Message {
                 message_type,
                 message_bytes,
                 source_id,
-                parent_context: Some(parent_context),
-            })
-            .map_err(|err| match err.0 {
-                DispatchMessage::Message {
-                    message_type,
-                    message_bytes,
-                    source_id,
-                    parent_context: Some(pc),
-                } => (message_type, message_bytes, source_id, pc),
-                _ => unreachable!(), // we didn't anything else
-            })
+                parent_context: Some(pc),
+            } => (message_type, message_bytes.into_vec(), source_id, pc),
+            _ => unreachable!(), // we didn't anything else
+        })
     }
 }
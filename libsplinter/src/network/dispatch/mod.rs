@@ -25,7 +25,10 @@ use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use context::MessageBytes;
 pub use context::MessageContext;
+#[cfg(feature = "dispatch-message-count")]
+pub use r#loop::DispatchMessageCount;
 pub use r#loop::{
     dispatch_channel, DispatchLoop, DispatchLoopBuilder, DispatchLoopError,
     DispatchLoopShutdownSignaler, DispatchMessageReceiver, DispatchMessageSender,
@@ -316,8 +319,7 @@ where
         message_type: &MT,
         message_bytes: Vec<u8>,
     ) -> Result<(), DispatchError> {
-        let message_context = MessageContext::new(message_type.clone(), message_bytes, source_id);
-        self.execute(message_context)
+        self.dispatch_message_bytes(source_id, message_type, message_bytes.into(), None)
     }
 
     /// Dispatch a message by type, including a parent context.
@@ -335,10 +337,27 @@ where
         message_type: &MT,
         message_bytes: Vec<u8>,
         parent_context: Box<dyn Any + Send>,
+    ) -> Result<(), DispatchError> {
+        self.dispatch_message_bytes(
+            source_id,
+            message_type,
+            message_bytes.into(),
+            Some(parent_context),
+        )
+    }
+
+    fn dispatch_message_bytes(
+        &self,
+        source_id: Source,
+        message_type: &MT,
+        message_bytes: MessageBytes,
+        parent_context: Option<Box<dyn Any + Send>>,
     ) -> Result<(), DispatchError> {
         let mut message_context =
             MessageContext::new(message_type.clone(), message_bytes, source_id);
-        message_context.set_parent_context(parent_context);
+        if let Some(parent_context) = parent_context {
+            message_context.set_parent_context(parent_context);
+        }
 
         self.execute(message_context)
     }
@@ -12,6 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "network-clock-skew")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "network-clock-skew")]
+use crate::network::clock_skew::ClockSkewMonitor;
 use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
 use crate::protos::network::{NetworkEcho, NetworkHeartbeat, NetworkMessage, NetworkMessageType};
 
@@ -78,7 +83,10 @@ impl NetworkEchoHandler {
 
 // Implements a handler that handles NetworkHeartbeat Messages
 #[derive(Default)]
-pub struct NetworkHeartbeatHandler {}
+pub struct NetworkHeartbeatHandler {
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_monitor: Option<ClockSkewMonitor>,
+}
 
 impl Handler for NetworkHeartbeatHandler {
     type Source = PeerId;
@@ -91,18 +99,46 @@ impl Handler for NetworkHeartbeatHandler {
 
     fn handle(
         &self,
-        _msg: Self::Message,
+        #[allow(unused_variables)] msg: Self::Message,
         context: &MessageContext<Self::Source, Self::MessageType>,
         _sender: &dyn MessageSender<Self::Source>,
     ) -> Result<(), DispatchError> {
         trace!("Received Heartbeat from {}", context.source_peer_id());
+
+        // heartbeats from nodes that do not include the time they were sent are ignored
+        #[cfg(feature = "network-clock-skew")]
+        {
+            if let Some(clock_skew_monitor) = &self.clock_skew_monitor {
+                if msg.get_timestamp() > 0 {
+                    let received_millis = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_err(|err| DispatchError::HandleError(err.to_string()))?
+                        .as_millis() as u64;
+                    clock_skew_monitor
+                        .record(
+                            context.source_peer_id(),
+                            msg.get_timestamp(),
+                            received_millis,
+                        )
+                        .map_err(|err| DispatchError::HandleError(err.to_string()))?;
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
 impl NetworkHeartbeatHandler {
     pub fn new() -> Self {
-        NetworkHeartbeatHandler {}
+        NetworkHeartbeatHandler::default()
+    }
+
+    /// Estimates the clock offset of each peer from the timestamps of the heartbeats it sends.
+    #[cfg(feature = "network-clock-skew")]
+    pub fn with_clock_skew_monitor(mut self, clock_skew_monitor: ClockSkewMonitor) -> Self {
+        self.clock_skew_monitor = Some(clock_skew_monitor);
+        self
     }
 }
 
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 pub mod auth;
+#[cfg(feature = "network-clock-skew")]
+pub mod clock_skew;
 pub mod connection_manager;
 pub mod dispatch;
 pub mod handlers;
@@ -39,6 +39,7 @@ pub use openid::OpenIdOAuthClientBuilder;
 pub struct OAuthClientBuilder {
     client_id: Option<String>,
     client_secret: Option<String>,
+    public_client: bool,
     auth_url: Option<String>,
     redirect_url: Option<String>,
     token_url: Option<String>,
@@ -66,11 +67,21 @@ impl OAuthClientBuilder {
                 "A client ID is required to successfully build an OAuthClient".into(),
             )
         })?;
-        let client_secret = self.client_secret.ok_or_else(|| {
-            InvalidStateError::with_message(
-                "A client secret is required to successfully build an OAuthClient".into(),
-            )
-        })?;
+        let client_secret = match (self.client_secret, self.public_client) {
+            (Some(_), true) => {
+                return Err(InvalidStateError::with_message(
+                    "A public OAuthClient cannot have a client secret".into(),
+                )
+                .into())
+            }
+            (None, false) => {
+                return Err(InvalidStateError::with_message(
+                    "A client secret is required to successfully build an OAuthClient".into(),
+                )
+                .into())
+            }
+            (client_secret, _) => client_secret,
+        };
         let auth_url = self.auth_url.ok_or_else(|| {
             InvalidStateError::with_message(
                 "An auth URL is required to successfully build an OAuthClient".into(),
@@ -119,6 +130,14 @@ impl OAuthClientBuilder {
         self
     }
 
+    /// Configures a public client, which has no client secret; the authorization code exchange
+    /// is protected by PKCE alone. The OAuth2 provider must allow public clients, as single-page
+    /// application registrations of OpenID Connect providers do.
+    pub fn with_public_client(mut self) -> Self {
+        self.public_client = true;
+        self
+    }
+
     /// Sets the authorize URL for the OAuth2 provider.
     pub fn with_auth_url(mut self, auth_url: String) -> Self {
         self.auth_url = Some(auth_url);
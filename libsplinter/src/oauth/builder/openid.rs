@@ -83,6 +83,15 @@ impl OpenIdOAuthClientBuilder {
         }
     }
 
+    /// Configures a public client, which has no client secret; the authorization code exchange
+    /// is protected by PKCE alone.
+    pub fn with_public_client(self) -> Self {
+        Self {
+            openid_discovery_url: self.openid_discovery_url,
+            inner: self.inner.with_public_client(),
+        }
+    }
+
     /// Sets the in-flight request store in order to store values between requests to and from the
     /// OAuth2 provider.
     pub fn with_inflight_request_store(
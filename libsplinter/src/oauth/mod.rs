@@ -48,7 +48,9 @@ pub use subject::SubjectProvider;
 /// An OAuth2 client for Splinter
 ///
 /// This client currently supports OAuth2 authorization code grants
-/// (<https://tools.ietf.org/html/rfc6749#section-4.1>).
+/// (<https://tools.ietf.org/html/rfc6749#section-4.1>) with PKCE
+/// (<https://tools.ietf.org/html/rfc7636>), so it may be registered with the provider as either a
+/// confidential client or a public client without a client secret.
 #[derive(Clone)]
 pub struct OAuthClient {
     /// The inner OAuth2 client
@@ -209,14 +211,14 @@ impl OAuthClient {
 
 fn new_basic_client(
     client_id: String,
-    client_secret: String,
+    client_secret: Option<String>,
     auth_url: String,
     redirect_url: String,
     token_url: String,
 ) -> Result<BasicClient, InvalidArgumentError> {
     Ok(BasicClient::new(
         ClientId::new(client_id),
-        Some(ClientSecret::new(client_secret)),
+        client_secret.map(ClientSecret::new),
         AuthUrl::new(auth_url)
             .map_err(|err| InvalidArgumentError::new("auth_url".into(), err.to_string()))?,
         Some(
@@ -304,7 +306,7 @@ mod tests {
         OAuthClient::new(
             new_basic_client(
                 "client_id".into(),
-                "client_secret".into(),
+                Some("client_secret".into()),
                 "https://provider.com/auth".into(),
                 "https://localhost/oauth/callback".into(),
                 "https://provider.com/token".into(),
@@ -320,7 +322,7 @@ mod tests {
         assert!(matches!(
             new_basic_client(
                 "client_id".into(),
-                "client_secret".into(),
+                Some("client_secret".into()),
                 "invalid_auth_url".into(),
                 "https://localhost/oauth/callback".into(),
                 "https://provider.com/token".into(),
@@ -331,7 +333,7 @@ mod tests {
         assert!(matches!(
             new_basic_client(
                 "client_id".into(),
-                "client_secret".into(),
+                Some("client_secret".into()),
                 "https://provider.com/auth".into(),
                 "invalid_redirect_url".into(),
                 "https://provider.com/token".into(),
@@ -342,7 +344,7 @@ mod tests {
         assert!(matches!(
             new_basic_client(
                 "client_id".into(),
-                "client_secret".into(),
+                Some("client_secret".into()),
                 "https://provider.com/auth".into(),
                 "https://localhost/oauth/callback".into(),
                 "invalid_token_url".into(),
@@ -351,6 +353,39 @@ mod tests {
         ));
     }
 
+    /// Verifies that a public client can be built without a client secret, and that a client
+    /// secret is rejected for a public client and required otherwise.
+    #[test]
+    fn public_client_construction() {
+        let builder = || {
+            OAuthClientBuilder::new()
+                .with_client_id("client_id".into())
+                .with_auth_url("https://provider.com/auth".into())
+                .with_redirect_url("https://localhost/oauth/callback".into())
+                .with_token_url("https://provider.com/token".into())
+                .with_subject_provider(Box::new(TestSubjectProvider))
+                .with_inflight_request_store(Box::new(TestInflightOAuthRequestStore))
+        };
+
+        builder()
+            .with_public_client()
+            .build()
+            .expect("Failed to create public client");
+
+        assert!(matches!(
+            builder()
+                .with_public_client()
+                .with_client_secret("client_secret".into())
+                .build(),
+            Err(OAuthClientBuildError::InvalidStateError(_))
+        ));
+
+        assert!(matches!(
+            builder().build(),
+            Err(OAuthClientBuildError::InvalidStateError(_))
+        ));
+    }
+
     #[derive(Clone)]
     pub struct TestSubjectProvider;
 
@@ -0,0 +1,421 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Services that are executed by an external process.
+//!
+//! The `ExternalServiceFactory` creates services whose type is implemented by an executable
+//! rather than by the splinter daemon. Starting such a service launches the executable, which is
+//! expected to connect the service to the node's service endpoint itself, and the process is
+//! restarted if it exits before the service is stopped.
+//!
+//! The executable is given the service's definition in its environment:
+//!
+//! * `SPLINTER_CIRCUIT_ID` - the circuit the service belongs to
+//! * `SPLINTER_SERVICE_ID` - the ID of the service
+//! * `SPLINTER_SERVICE_TYPE` - the type of the service
+//! * `SPLINTER_SERVICE_ENDPOINT` - the node's service endpoint
+//!
+//! The service's arguments are written to the process's standard input as a JSON object, after
+//! which standard input is closed.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+
+use crate::error::InternalError;
+use crate::service::{
+    FactoryCreateError, Service, ServiceDestroyError, ServiceError, ServiceFactory,
+    ServiceMessageContext, ServiceNetworkRegistry, ServiceStartError, ServiceStopError,
+};
+
+const CIRCUIT_ID_ENV: &str = "SPLINTER_CIRCUIT_ID";
+const SERVICE_ID_ENV: &str = "SPLINTER_SERVICE_ID";
+const SERVICE_TYPE_ENV: &str = "SPLINTER_SERVICE_TYPE";
+const SERVICE_ENDPOINT_ENV: &str = "SPLINTER_SERVICE_ENDPOINT";
+
+const DEFAULT_RESTART_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The interval at which a service's process is checked for having exited.
+const PROCESS_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Creates services that are executed by launching a configured executable for their type.
+pub struct ExternalServiceFactory {
+    service_endpoint: String,
+    service_types: Vec<String>,
+    executables: HashMap<String, PathBuf>,
+    restart_interval: Duration,
+}
+
+impl ExternalServiceFactory {
+    /// Creates a factory for services that connect to the node through the given service
+    /// endpoint.
+    pub fn new(service_endpoint: String) -> Self {
+        ExternalServiceFactory {
+            service_endpoint,
+            service_types: vec![],
+            executables: HashMap::new(),
+            restart_interval: DEFAULT_RESTART_INTERVAL,
+        }
+    }
+
+    /// Executes services of the given type with the given executable.
+    pub fn with_service_type(mut self, service_type: String, executable: PathBuf) -> Self {
+        if self
+            .executables
+            .insert(service_type.clone(), executable)
+            .is_none()
+        {
+            self.service_types.push(service_type);
+        }
+        self
+    }
+
+    /// Sets how long to wait before restarting a service's process after it exits. Defaults to 5
+    /// seconds.
+    pub fn with_restart_interval(mut self, restart_interval: Duration) -> Self {
+        self.restart_interval = restart_interval;
+        self
+    }
+}
+
+impl ServiceFactory for ExternalServiceFactory {
+    fn available_service_types(&self) -> &[String] {
+        &self.service_types
+    }
+
+    fn create(
+        &self,
+        service_id: String,
+        service_type: &str,
+        circuit_id: &str,
+        args: HashMap<String, String>,
+    ) -> Result<Box<dyn Service>, FactoryCreateError> {
+        let executable = self.executables.get(service_type).ok_or_else(|| {
+            FactoryCreateError::InvalidArguments(format!(
+                "no executable is configured for service type {}",
+                service_type
+            ))
+        })?;
+
+        let input = serde_json::to_vec(&args)
+            .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+
+        let process = ServiceProcess {
+            executable: executable.clone(),
+            env: vec![
+                (CIRCUIT_ID_ENV, circuit_id.to_string()),
+                (SERVICE_ID_ENV, service_id.clone()),
+                (SERVICE_TYPE_ENV, service_type.to_string()),
+                (SERVICE_ENDPOINT_ENV, self.service_endpoint.clone()),
+            ],
+            input,
+        };
+
+        Ok(Box::new(ExternalService {
+            service_id,
+            service_type: service_type.to_string(),
+            process,
+            restart_interval: self.restart_interval,
+            supervisor: None,
+        }))
+    }
+
+    #[cfg(feature = "rest-api")]
+    fn get_rest_endpoints(&self) -> Vec<crate::service::rest_api::ServiceEndpoint> {
+        vec![]
+    }
+}
+
+/// A service whose messages are handled by an external process.
+///
+/// The process connects the service to the node itself, so no messages are handled by this
+/// service; it only manages the process's lifecycle.
+struct ExternalService {
+    service_id: String,
+    service_type: String,
+    process: ServiceProcess,
+    restart_interval: Duration,
+    supervisor: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl Service for ExternalService {
+    fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    fn service_type(&self) -> &str {
+        &self.service_type
+    }
+
+    fn start(
+        &mut self,
+        _service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStartError> {
+        if self.supervisor.is_some() {
+            return Err(ServiceStartError::AlreadyStarted);
+        }
+
+        let child = self.process.spawn().map_err(|err| {
+            ServiceStartError::Internal(format!(
+                "unable to launch process for service {}: {}",
+                self.service_id, err
+            ))
+        })?;
+
+        let (shutdown_sender, shutdown_receiver) = crossbeam_channel::bounded(1);
+        let supervisor = Supervisor {
+            service_id: self.service_id.clone(),
+            process: self.process.clone(),
+            restart_interval: self.restart_interval,
+            shutdown_receiver,
+        };
+        let join_handle = thread::Builder::new()
+            .name(format!("Service process {}", self.service_id))
+            .spawn(move || supervisor.run(child))
+            .map_err(|err| {
+                ServiceStartError::Internal(format!(
+                    "unable to start process supervisor for service {}: {}",
+                    self.service_id, err
+                ))
+            })?;
+
+        self.supervisor = Some((shutdown_sender, join_handle));
+
+        Ok(())
+    }
+
+    fn stop(
+        &mut self,
+        _service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStopError> {
+        let (shutdown_sender, join_handle) =
+            self.supervisor.take().ok_or(ServiceStopError::NotStarted)?;
+
+        if shutdown_sender.send(()).is_err() {
+            // the supervisor has already exited
+        }
+        join_handle.join().map_err(|_| {
+            ServiceStopError::Internal(Box::new(InternalError::with_message(format!(
+                "process supervisor for service {} panicked",
+                self.service_id
+            ))))
+        })?;
+
+        Ok(())
+    }
+
+    fn destroy(self: Box<Self>) -> Result<(), ServiceDestroyError> {
+        if self.supervisor.is_some() {
+            Err(ServiceDestroyError::NotStopped)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn handle_message(
+        &self,
+        _message_bytes: &[u8],
+        _message_context: &ServiceMessageContext,
+    ) -> Result<(), ServiceError> {
+        Err(ServiceError::UnableToHandleMessage(Box::new(
+            InternalError::with_message(format!(
+                "messages for service {} are handled by its process",
+                self.service_id
+            )),
+        )))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// How to launch the process that executes a service.
+#[derive(Clone)]
+struct ServiceProcess {
+    executable: PathBuf,
+    env: Vec<(&'static str, String)>,
+    input: Vec<u8>,
+}
+
+impl ServiceProcess {
+    /// Launches the process, writing the service's arguments to its standard input.
+    fn spawn(&self) -> std::io::Result<Child> {
+        let mut child = Command::new(&self.executable)
+            .envs(self.env.iter().cloned())
+            .stdin(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // a process that exits without reading its arguments is restarted by the supervisor
+            if let Err(err) = stdin.write_all(&self.input) {
+                debug!("Unable to write arguments to service process: {}", err);
+            }
+        }
+        Ok(child)
+    }
+}
+
+/// Restarts a service's process whenever it exits, until the service is stopped.
+struct Supervisor {
+    service_id: String,
+    process: ServiceProcess,
+    restart_interval: Duration,
+    shutdown_receiver: Receiver<()>,
+}
+
+impl Supervisor {
+    fn run(self, child: Child) {
+        let mut child = Some(child);
+        loop {
+            let timeout = if child.is_some() {
+                PROCESS_CHECK_INTERVAL
+            } else {
+                self.restart_interval
+            };
+            match self.shutdown_receiver.recv_timeout(timeout) {
+                Err(RecvTimeoutError::Timeout) => (),
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            child = match child {
+                Some(mut running_child) => match running_child.try_wait() {
+                    Ok(Some(status)) => {
+                        warn!(
+                            "Process for service {} exited ({}); restarting in {:?}",
+                            self.service_id, status, self.restart_interval
+                        );
+                        None
+                    }
+                    Ok(None) => Some(running_child),
+                    Err(err) => {
+                        error!(
+                            "Unable to check process for service {}: {}",
+                            self.service_id, err
+                        );
+                        Some(running_child)
+                    }
+                },
+                None => match self.process.spawn() {
+                    Ok(new_child) => {
+                        info!("Restarted process for service {}", self.service_id);
+                        Some(new_child)
+                    }
+                    Err(err) => {
+                        error!(
+                            "Unable to restart process for service {}: {}",
+                            self.service_id, err
+                        );
+                        None
+                    }
+                },
+            };
+        }
+
+        if let Some(mut child) = child {
+            if let Err(err) = child.kill().and_then(|_| child.wait()) {
+                error!(
+                    "Unable to terminate process for service {}: {}",
+                    self.service_id, err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    use crate::service::{ServiceConnectionError, ServiceDisconnectionError, ServiceNetworkSender};
+
+    /// Verifies that a service's process is given the service's definition and arguments, is
+    /// restarted after it exits, and is terminated when the service is stopped.
+    #[cfg(unix)]
+    #[test]
+    fn supervise_process() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir::TempDir::new("external_service").expect("Unable to create temp dir");
+        let output = dir.path().join("output");
+        let executable = dir.path().join("service.sh");
+        std::fs::write(
+            &executable,
+            format!(
+                "#!/bin/sh\necho \"$SPLINTER_CIRCUIT_ID $SPLINTER_SERVICE_ID $(cat)\" >> {}\n",
+                output.display()
+            ),
+        )
+        .expect("Unable to write executable");
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755))
+            .expect("Unable to make executable");
+
+        let factory = ExternalServiceFactory::new("tcp://localhost:8043".into())
+            .with_service_type("test".into(), executable)
+            .with_restart_interval(Duration::from_millis(100));
+        assert_eq!(factory.available_service_types(), &["test".to_string()]);
+
+        let mut args = HashMap::new();
+        args.insert("key".to_string(), "value".to_string());
+        let mut service = factory
+            .create("svc0".into(), "test", "alpha", args)
+            .expect("Unable to create service");
+
+        service
+            .start(&MockRegistry)
+            .expect("Unable to start service");
+
+        // the process exits immediately and is restarted
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while std::fs::read_to_string(&output)
+            .map(|contents| contents.lines().count())
+            .unwrap_or(0)
+            < 2
+        {
+            assert!(Instant::now() < deadline, "process was not restarted");
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        service.stop(&MockRegistry).expect("Unable to stop service");
+        service.destroy().expect("Unable to destroy service");
+
+        let contents = std::fs::read_to_string(&output).expect("Unable to read output");
+        assert_eq!(
+            contents.lines().next(),
+            Some(r#"alpha svc0 {"key":"value"}"#)
+        );
+    }
+
+    struct MockRegistry;
+
+    impl ServiceNetworkRegistry for MockRegistry {
+        fn connect(
+            &self,
+            _service_id: &str,
+        ) -> Result<Box<dyn ServiceNetworkSender>, ServiceConnectionError> {
+            unimplemented!()
+        }
+
+        fn disconnect(&self, _service_id: &str) -> Result<(), ServiceDisconnectionError> {
+            unimplemented!()
+        }
+    }
+}
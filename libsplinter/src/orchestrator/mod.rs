@@ -13,6 +13,8 @@
 // limitations under the License.
 
 mod error;
+#[cfg(feature = "orchestrator-external-services")]
+mod external;
 #[cfg(feature = "rest-api")]
 mod rest_api;
 
@@ -25,7 +27,11 @@ use std::time::Duration;
 use crossbeam_channel::{Receiver, Sender};
 use uuid::Uuid;
 
+#[cfg(feature = "admin-parallel-startup")]
+use crate::admin::startup::parallel_map;
 use crate::channel;
+#[cfg(feature = "admin-parallel-startup")]
+use crate::error::InternalError;
 use crate::mesh::{Envelope, Mesh, RecvTimeoutError as MeshRecvTimeoutError};
 use crate::network::reply::InboundRouter;
 use crate::protos::circuit::{
@@ -33,8 +39,10 @@ use crate::protos::circuit::{
     ServiceConnectResponse, ServiceDisconnectResponse,
 };
 use crate::protos::network::{NetworkMessage, NetworkMessageType};
+#[cfg(feature = "runtime-diagnostics")]
+use crate::runtime::{RuntimeMonitor, ThreadHeartbeat, ThreadMonitor};
 use crate::service::{
-    Service, ServiceFactory, ServiceMessageContext, StandardServiceNetworkRegistry,
+    SequenceNumbers, Service, ServiceFactory, ServiceMessageContext, StandardServiceNetworkRegistry,
 };
 use crate::transport::Connection;
 
@@ -42,6 +50,8 @@ pub use self::error::{
     InitializeServiceError, ListServicesError, NewOrchestratorError, OrchestratorError,
     ShutdownServiceError,
 };
+#[cfg(feature = "orchestrator-external-services")]
+pub use self::external::ExternalServiceFactory;
 
 // Recv timeout in secs
 const TIMEOUT_SEC: u64 = 2;
@@ -70,8 +80,15 @@ pub struct ServiceOrchestrator {
     /// `network_sender` and `inbound_router` are used to create services' senders.
     network_sender: Sender<Vec<u8>>,
     inbound_router: InboundRouter<CircuitMessageType>,
+    /// The sequence numbers set on the direct messages sent by services
+    sequence_numbers: SequenceNumbers,
     /// `running` and `join_handles` are used to shutdown the orchestrator's background threads
     running: Arc<AtomicBool>,
+    /// Monitors of the background threads and a sender for reporting the inbound queue's depth
+    #[cfg(feature = "runtime-diagnostics")]
+    thread_monitors: Vec<ThreadMonitor>,
+    #[cfg(feature = "runtime-diagnostics")]
+    inbound_sender: Sender<Result<(CircuitMessageType, Vec<u8>), channel::RecvError>>,
 }
 
 impl ServiceOrchestrator {
@@ -91,11 +108,26 @@ impl ServiceOrchestrator {
             .map_err(|err| NewOrchestratorError(Box::new(err)))?;
         let (network_sender, network_receiver) = crossbeam_channel::bounded(channel_capacity);
         let (inbound_sender, inbound_receiver) = crossbeam_channel::bounded(channel_capacity);
+        #[cfg(feature = "runtime-diagnostics")]
+        let diagnostics_inbound_sender = inbound_sender.clone();
         let inbound_router = InboundRouter::new(Box::new(inbound_sender));
         let running = Arc::new(AtomicBool::new(true));
 
         debug!("Orchestrator authorized");
 
+        #[cfg(feature = "runtime-diagnostics")]
+        let incoming_heartbeat = ThreadHeartbeat::new("Orchestrator Incoming");
+        #[cfg(feature = "runtime-diagnostics")]
+        let inbound_heartbeat = ThreadHeartbeat::new("Orchestrator Inbound");
+        #[cfg(feature = "runtime-diagnostics")]
+        let outgoing_heartbeat = ThreadHeartbeat::new("Orchestrator Outgoing");
+        #[cfg(feature = "runtime-diagnostics")]
+        let thread_monitors = vec![
+            incoming_heartbeat.monitor(),
+            inbound_heartbeat.monitor(),
+            outgoing_heartbeat.monitor(),
+        ];
+
         // Start thread that handles incoming messages from a splinter node.
         let incoming_mesh = mesh.clone();
         let incoming_running = running.clone();
@@ -103,9 +135,13 @@ impl ServiceOrchestrator {
         let incoming_join_handle = thread::Builder::new()
             .name("Orchestrator Incoming".into())
             .spawn(move || {
-                if let Err(err) =
-                    run_incoming_loop(incoming_mesh, incoming_running, incoming_router)
-                {
+                if let Err(err) = run_incoming_loop(
+                    incoming_mesh,
+                    incoming_running,
+                    incoming_router,
+                    #[cfg(feature = "runtime-diagnostics")]
+                    incoming_heartbeat,
+                ) {
                     error!(
                         "Terminating orchestrator incoming thread due to error: {}",
                         err
@@ -123,9 +159,13 @@ impl ServiceOrchestrator {
         let inbound_join_handle = thread::Builder::new()
             .name("Orchestrator Inbound".into())
             .spawn(move || {
-                if let Err(err) =
-                    run_inbound_loop(inbound_services, inbound_receiver, inbound_running)
-                {
+                if let Err(err) = run_inbound_loop(
+                    inbound_services,
+                    inbound_receiver,
+                    inbound_running,
+                    #[cfg(feature = "runtime-diagnostics")]
+                    inbound_heartbeat,
+                ) {
                     error!(
                         "Terminating orchestrator inbound thread due to error: {}",
                         err
@@ -142,9 +182,14 @@ impl ServiceOrchestrator {
         let outgoing_join_handle = thread::Builder::new()
             .name("Orchestrator Outgoing".into())
             .spawn(move || {
-                if let Err(err) =
-                    run_outgoing_loop(mesh, outgoing_running, network_receiver, mesh_id)
-                {
+                if let Err(err) = run_outgoing_loop(
+                    mesh,
+                    outgoing_running,
+                    network_receiver,
+                    mesh_id,
+                    #[cfg(feature = "runtime-diagnostics")]
+                    outgoing_heartbeat,
+                ) {
                     error!(
                         "Terminating orchestrator outgoing thread due to error: {}",
                         err
@@ -173,7 +218,12 @@ impl ServiceOrchestrator {
                 supported_service_types,
                 network_sender,
                 inbound_router,
+                sequence_numbers: SequenceNumbers::new(),
                 running,
+                #[cfg(feature = "runtime-diagnostics")]
+                thread_monitors,
+                #[cfg(feature = "runtime-diagnostics")]
+                inbound_sender: diagnostics_inbound_sender,
             },
             JoinHandles::new(vec![
                 incoming_join_handle,
@@ -183,6 +233,28 @@ impl ServiceOrchestrator {
         ))
     }
 
+    /// Numbers the direct messages sent by the orchestrator's services with the given sequence
+    /// numbers, so they keep increasing across restarts when the numbers are backed by a file.
+    pub fn with_sequence_numbers(mut self, sequence_numbers: SequenceNumbers) -> Self {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
+
+    /// Adds the orchestrator's background threads, its queue of messages waiting to be sent to the
+    /// splinter node and its queue of inbound messages waiting to be handled to the given runtime
+    /// monitor.
+    #[cfg(feature = "runtime-diagnostics")]
+    pub fn register_runtime_diagnostics(&self, monitor: &RuntimeMonitor) {
+        for thread_monitor in &self.thread_monitors {
+            monitor.add_thread(thread_monitor.clone());
+        }
+
+        let network_sender = self.network_sender.clone();
+        monitor.add_queue("Orchestrator Outgoing", move || network_sender.len());
+        let inbound_sender = self.inbound_sender.clone();
+        monitor.add_queue("Orchestrator Inbound", move || inbound_sender.len());
+    }
+
     /// Initialize (create and start) a service according to the specified definition. The
     /// arguments provided must match those required to create the service.
     pub fn initialize_service(
@@ -190,6 +262,76 @@ impl ServiceOrchestrator {
         service_definition: ServiceDefinition,
         args: HashMap<String, String>,
     ) -> Result<(), InitializeServiceError> {
+        let (mut service, registry) = self.create_service(&service_definition, args)?;
+
+        // Start the service.
+        service
+            .start(&registry)
+            .map_err(|err| InitializeServiceError::InitializationFailed(Box::new(err)))?;
+
+        // Save the service.
+        self.services
+            .lock()
+            .map_err(|_| InitializeServiceError::LockPoisoned)?
+            .insert(service_definition, ManagedService { service, registry });
+
+        Ok(())
+    }
+
+    /// Initialize (create and start) the specified services, starting them on up to `workers`
+    /// threads.
+    ///
+    /// The services are created on the calling thread, since the service factories cannot be
+    /// shared between threads. Starting a service waits for the service's connection to be
+    /// acknowledged, so the services are started concurrently.
+    ///
+    /// Returns the result of initializing each service, in the order they were specified.
+    #[cfg(feature = "admin-parallel-startup")]
+    pub fn initialize_services(
+        &self,
+        services: Vec<(ServiceDefinition, HashMap<String, String>)>,
+        workers: usize,
+    ) -> Result<Vec<(ServiceDefinition, Result<(), InitializeServiceError>)>, InternalError> {
+        let created = services
+            .into_iter()
+            .map(|(service_definition, args)| {
+                let result = self.create_service(&service_definition, args);
+                (service_definition, result)
+            })
+            .collect::<Vec<_>>();
+
+        let started = parallel_map(created, workers, |(service_definition, result)| {
+            let result = result.and_then(|(mut service, registry)| {
+                service
+                    .start(&registry)
+                    .map_err(|err| InitializeServiceError::InitializationFailed(Box::new(err)))?;
+                Ok(ManagedService { service, registry })
+            });
+            (service_definition, result)
+        })?;
+
+        let mut services = self.services.lock().map_err(|_| {
+            InternalError::with_message("Orchestrator services lock poisoned".into())
+        })?;
+
+        Ok(started
+            .into_iter()
+            .map(|(service_definition, result)| {
+                let result = result.map(|managed_service| {
+                    services.insert(service_definition.clone(), managed_service);
+                });
+                (service_definition, result)
+            })
+            .collect())
+    }
+
+    /// Creates a service according to the specified definition, along with the registry it is
+    /// started with.
+    fn create_service(
+        &self,
+        service_definition: &ServiceDefinition,
+        args: HashMap<String, String>,
+    ) -> Result<(Box<dyn Service>, StandardServiceNetworkRegistry), InitializeServiceError> {
         // Get the factory that can create this service.
         let factory = self
             .service_factories
@@ -202,31 +344,22 @@ impl ServiceOrchestrator {
             .ok_or(InitializeServiceError::UnknownType)?;
 
         // Create the service.
-        let mut service = factory.create(
+        let service = factory.create(
             service_definition.service_id.clone(),
             service_definition.service_type.as_str(),
             service_definition.circuit.as_str(),
             args,
         )?;
 
-        // Start the service.
+        // Create the registry the service will be started with.
         let registry = StandardServiceNetworkRegistry::new(
             service_definition.circuit.clone(),
             self.network_sender.clone(),
             self.inbound_router.clone(),
-        );
-
-        service
-            .start(&registry)
-            .map_err(|err| InitializeServiceError::InitializationFailed(Box::new(err)))?;
-
-        // Save the service.
-        self.services
-            .lock()
-            .map_err(|_| InitializeServiceError::LockPoisoned)?
-            .insert(service_definition, ManagedService { service, registry });
+        )
+        .with_sequence_numbers(self.sequence_numbers.clone());
 
-        Ok(())
+        Ok((service, registry))
     }
 
     /// Shut down (stop and destroy) the specified service.
@@ -333,8 +466,11 @@ pub fn run_incoming_loop(
     incoming_mesh: Mesh,
     incoming_running: Arc<AtomicBool>,
     mut inbound_router: InboundRouter<CircuitMessageType>,
+    #[cfg(feature = "runtime-diagnostics")] heartbeat: ThreadHeartbeat,
 ) -> Result<(), OrchestratorError> {
     while incoming_running.load(Ordering::SeqCst) {
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.idle();
         let timeout = Duration::from_secs(TIMEOUT_SEC);
         let message_bytes = match incoming_mesh.recv_timeout(timeout) {
             Ok(envelope) => Vec::from(envelope),
@@ -352,6 +488,8 @@ pub fn run_incoming_loop(
                 break;
             }
         };
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.busy();
 
         let msg: NetworkMessage = protobuf::parse_from_bytes(&message_bytes)
             .map_err(|err| OrchestratorError::Internal(Box::new(err)))?;
@@ -443,9 +581,12 @@ fn run_inbound_loop(
     services: Arc<Mutex<HashMap<ServiceDefinition, ManagedService>>>,
     inbound_receiver: Receiver<Result<(CircuitMessageType, Vec<u8>), channel::RecvError>>,
     inbound_running: Arc<AtomicBool>,
+    #[cfg(feature = "runtime-diagnostics")] heartbeat: ThreadHeartbeat,
 ) -> Result<(), OrchestratorError> {
     let timeout = Duration::from_secs(TIMEOUT_SEC);
     while inbound_running.load(Ordering::SeqCst) {
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.idle();
         let service_message = match inbound_receiver.recv_timeout(timeout) {
             Ok(msg) => msg,
             Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
@@ -455,6 +596,8 @@ fn run_inbound_loop(
             }
         }
         .map_err(|err| OrchestratorError::Internal(Box::new(err)))?;
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.busy();
 
         match service_message {
             (CircuitMessageType::ADMIN_DIRECT_MESSAGE, msg) => {
@@ -546,8 +689,11 @@ fn run_outgoing_loop(
     outgoing_running: Arc<AtomicBool>,
     outgoing_receiver: Receiver<Vec<u8>>,
     mesh_id: String,
+    #[cfg(feature = "runtime-diagnostics")] heartbeat: ThreadHeartbeat,
 ) -> Result<(), OrchestratorError> {
     while outgoing_running.load(Ordering::SeqCst) {
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.idle();
         let timeout = Duration::from_secs(TIMEOUT_SEC);
         let message_bytes = match outgoing_receiver.recv_timeout(timeout) {
             Ok(msg) => msg,
@@ -557,6 +703,8 @@ fn run_outgoing_loop(
                 break;
             }
         };
+        #[cfg(feature = "runtime-diagnostics")]
+        heartbeat.busy();
 
         // Send message to splinter node
         outgoing_mesh
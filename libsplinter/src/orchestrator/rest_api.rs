@@ -14,6 +14,8 @@
 
 use crate::actix_web::HttpResponse;
 use crate::futures::IntoFuture;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::rest_api::auth::circuit_scope::CircuitScope;
 use crate::rest_api::{Resource, RestResourceProvider};
 
 use super::ServiceOrchestrator;
@@ -62,6 +64,28 @@ impl RestResourceProvider for ServiceOrchestrator {
                                 .unwrap_or("")
                                 .to_string();
 
+                            #[cfg(feature = "rest-api-circuit-scopes")]
+                            {
+                                let permitted = request
+                                    .extensions()
+                                    .get::<CircuitScope>()
+                                    .map(|scope| scope.is_permitted(&circuit))
+                                    .unwrap_or(true);
+                                if !permitted {
+                                    return Box::new(
+                                        HttpResponse::Forbidden()
+                                            .json(json!({
+                                                "message": format!(
+                                                    "Not permitted to access circuit {}",
+                                                    circuit
+                                                )
+                                            }))
+                                            .into_future(),
+                                    )
+                                    .into_future();
+                                }
+                            }
+
                             let services = match services.lock() {
                                 Ok(s) => s,
                                 Err(err) => {
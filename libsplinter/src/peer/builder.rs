@@ -16,7 +16,10 @@
 //!
 //! The public interface includes the structs [`PeerManagerBuilder`]
 
+use std::sync::Arc;
+
 use crate::network::connection_manager::Connector;
+use crate::threading::clock::{Clock, SystemClock};
 
 use super::error::PeerManagerError;
 use super::PeerManager;
@@ -43,6 +46,8 @@ pub struct PeerManagerBuilder {
     endpoint_retry_frequency: Option<u64>,
     identity: Option<String>,
     strict_ref_counts: Option<bool>,
+    endpoint_preference: Vec<String>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 /// Constructs new `PeerManager` instances.
@@ -128,6 +133,26 @@ impl PeerManagerBuilder {
         self
     }
 
+    /// Set the endpoint_preference to use with the resulting `PeerManager`.
+    ///
+    /// An ordered list of transport schemes (for example, `["tcps", "ws"]`). A peer's endpoints
+    /// are attempted in this order, failing over to the next endpoint when a connection cannot be
+    /// made. Endpoints with a scheme that is not listed are attempted last, in the order they
+    /// were provided. If empty, endpoints are attempted in the order they were provided.
+    pub fn with_endpoint_preference(mut self, endpoint_preference: Vec<String>) -> Self {
+        self.endpoint_preference = endpoint_preference;
+        self
+    }
+
+    /// Set the clock to use with the resulting `PeerManager`.
+    ///
+    /// The clock drives the retry interval and measures the time between attempts to connect to
+    /// pending peers. Defaults to the `SystemClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Starts the `PeerManager`
     ///
     /// Starts up a thread that will handle incoming requests to add, remove and get peers. Also
@@ -163,6 +188,8 @@ impl PeerManagerBuilder {
             retry_frequency,
             max_retry_frequency,
             endpoint_retry_frequency,
+            std::mem::take(&mut self.endpoint_preference),
+            self.clock.take().unwrap_or_else(|| Arc::new(SystemClock)),
         )
     }
 }
@@ -0,0 +1,574 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Time-bounded capture of the messages exchanged with peers, for debugging.
+//!
+//! While a [`MessageCapture`] is running, the `PeerInterconnect` records the metadata of every
+//! message it receives from or sends to a peer: the peer, the connection, the network message
+//! type, the circuit message type for circuit messages, and the payload size. Optionally, a
+//! SHA-256 hash of each payload is recorded, so identical messages can be matched across nodes
+//! without capturing their contents. Peer connection events can be recorded as well.
+//!
+//! Each record is written as a line of JSON to a capture file in the capture directory. A capture
+//! stops on its own once its duration has elapsed.
+//!
+//! [`MessageCapture`]: struct.MessageCapture.html
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{InternalError, InvalidArgumentError, InvalidStateError};
+use crate::hex::to_hex;
+use crate::protos::circuit::CircuitMessageType;
+use crate::protos::envelope::EnvelopeRef;
+use crate::protos::network::NetworkMessageType;
+
+use super::notification::PeerManagerNotification;
+
+/// The longest a single capture may run.
+pub const MAX_CAPTURE_DURATION: Duration = Duration::from_secs(3600);
+
+const CAPTURE_FILE_PREFIX: &str = "capture-";
+const CAPTURE_FILE_EXTENSION: &str = ".jsonl";
+
+/// The direction of a captured message, relative to this node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureDirection {
+    Inbound,
+    Outbound,
+}
+
+impl fmt::Display for CaptureDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureDirection::Inbound => f.write_str("inbound"),
+            CaptureDirection::Outbound => f.write_str("outbound"),
+        }
+    }
+}
+
+/// A single line of a capture file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    /// `inbound` or `outbound` for messages, `peer` for peer connection events
+    pub direction: String,
+    pub peer_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+    /// The network message type, or the peer event for peer connection events
+    pub message_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_message_type: Option<String>,
+    /// The size of the network message in bytes
+    pub size: usize,
+    /// The hex-encoded SHA-256 hash of the network message, if payload hashes were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_hash: Option<String>,
+}
+
+/// The state of the current or most recent capture.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CaptureStatus {
+    pub active: bool,
+    /// The name of the capture file, which can be passed to `MessageCapture::read_capture_file`
+    pub file: String,
+    /// Seconds since the unix epoch
+    pub started_at: u64,
+    /// Seconds since the unix epoch
+    pub ends_at: u64,
+    pub include_payload_hashes: bool,
+    pub records: u64,
+}
+
+/// Errors that may occur while starting or reading a capture.
+#[derive(Debug)]
+pub enum MessageCaptureError {
+    Internal(InternalError),
+    InvalidArgument(InvalidArgumentError),
+    InvalidState(InvalidStateError),
+}
+
+impl Error for MessageCaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MessageCaptureError::Internal(err) => err.source(),
+            MessageCaptureError::InvalidArgument(err) => err.source(),
+            MessageCaptureError::InvalidState(err) => err.source(),
+        }
+    }
+}
+
+impl fmt::Display for MessageCaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageCaptureError::Internal(err) => f.write_str(&err.to_string()),
+            MessageCaptureError::InvalidArgument(err) => f.write_str(&err.to_string()),
+            MessageCaptureError::InvalidState(err) => f.write_str(&err.to_string()),
+        }
+    }
+}
+
+struct ActiveCapture {
+    writer: BufWriter<File>,
+    ends_at: SystemTime,
+    status: CaptureStatus,
+}
+
+#[derive(Default)]
+struct CaptureState {
+    active: Option<ActiveCapture>,
+    last: Option<CaptureStatus>,
+}
+
+/// Records the messages exchanged with peers into capture files while a capture is running.
+///
+/// The capture is cheap to clone; all clones share the same state. When no capture is running,
+/// recording a message only checks an atomic flag.
+#[derive(Clone)]
+pub struct MessageCapture {
+    capture_dir: PathBuf,
+    running: Arc<AtomicBool>,
+    state: Arc<Mutex<CaptureState>>,
+}
+
+impl MessageCapture {
+    /// Creates a `MessageCapture` that writes its capture files to the given directory, which is
+    /// created when the first capture is started.
+    pub fn new(capture_dir: PathBuf) -> Self {
+        MessageCapture {
+            capture_dir,
+            running: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(CaptureState::default())),
+        }
+    }
+
+    /// Starts a capture that runs for the given duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - how long to capture for, at most `MAX_CAPTURE_DURATION`
+    /// * `include_payload_hashes` - whether to record a hash of each message
+    ///
+    /// Returns an `InvalidState` error if a capture is already running.
+    pub fn start(
+        &self,
+        duration: Duration,
+        include_payload_hashes: bool,
+    ) -> Result<CaptureStatus, MessageCaptureError> {
+        if duration.as_secs() == 0 || duration > MAX_CAPTURE_DURATION {
+            return Err(MessageCaptureError::InvalidArgument(
+                InvalidArgumentError::new(
+                    "duration".into(),
+                    format!(
+                        "must be between 1 and {} seconds",
+                        MAX_CAPTURE_DURATION.as_secs()
+                    ),
+                ),
+            ));
+        }
+
+        let mut state = self.lock_state()?;
+        self.expire(&mut state);
+        if state.active.is_some() {
+            return Err(MessageCaptureError::InvalidState(
+                InvalidStateError::with_message("A capture is already running".into()),
+            ));
+        }
+
+        fs::create_dir_all(&self.capture_dir).map_err(|err| {
+            MessageCaptureError::Internal(InternalError::from_source_with_message(
+                Box::new(err),
+                format!(
+                    "Unable to create capture directory {}",
+                    self.capture_dir.display()
+                ),
+            ))
+        })?;
+
+        let now = SystemTime::now();
+        let started_at = secs_since_epoch(now);
+        let file = format!(
+            "{}{}{}",
+            CAPTURE_FILE_PREFIX, started_at, CAPTURE_FILE_EXTENSION
+        );
+        let writer = File::create(self.capture_dir.join(&file))
+            .map(BufWriter::new)
+            .map_err(|err| {
+                MessageCaptureError::Internal(InternalError::from_source_with_message(
+                    Box::new(err),
+                    format!("Unable to create capture file {}", file),
+                ))
+            })?;
+
+        let ends_at = now + duration;
+        let status = CaptureStatus {
+            active: true,
+            file,
+            started_at,
+            ends_at: secs_since_epoch(ends_at),
+            include_payload_hashes,
+            records: 0,
+        };
+        state.active = Some(ActiveCapture {
+            writer,
+            ends_at,
+            status: status.clone(),
+        });
+        self.running.store(true, Ordering::SeqCst);
+
+        info!(
+            "Started message capture {} for {} seconds",
+            status.file,
+            duration.as_secs()
+        );
+
+        Ok(status)
+    }
+
+    /// Stops the running capture before its duration has elapsed.
+    ///
+    /// Returns the status of the stopped capture, or `None` if no capture was running.
+    pub fn stop(&self) -> Result<Option<CaptureStatus>, MessageCaptureError> {
+        let mut state = self.lock_state()?;
+        self.expire(&mut state);
+        if state.active.is_none() {
+            return Ok(None);
+        }
+        self.finish(&mut state);
+        Ok(state.last.clone())
+    }
+
+    /// Returns the status of the running capture, or of the most recent capture if none is
+    /// running.
+    pub fn status(&self) -> Result<Option<CaptureStatus>, MessageCaptureError> {
+        let mut state = self.lock_state()?;
+        self.expire(&mut state);
+        Ok(match &state.active {
+            Some(active) => Some(active.status.clone()),
+            None => state.last.clone(),
+        })
+    }
+
+    /// Reads the capture file with the given name, or returns `None` if there is no such file.
+    ///
+    /// The capture file of a running capture may be read, but only contains the records written
+    /// so far.
+    pub fn read_capture_file(&self, file: &str) -> Result<Option<Vec<u8>>, MessageCaptureError> {
+        let is_capture_file = file.starts_with(CAPTURE_FILE_PREFIX)
+            && file.ends_with(CAPTURE_FILE_EXTENSION)
+            && file
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        if !is_capture_file {
+            return Err(MessageCaptureError::InvalidArgument(
+                InvalidArgumentError::new("file".into(), "not a capture file name".into()),
+            ));
+        }
+
+        {
+            let mut state = self.lock_state()?;
+            self.expire(&mut state);
+            if let Some(active) = state.active.as_mut() {
+                if active.status.file == file {
+                    let _ = active.writer.flush();
+                }
+            }
+        }
+
+        match fs::read(self.capture_dir.join(file)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(MessageCaptureError::Internal(
+                InternalError::from_source_with_message(
+                    Box::new(err),
+                    format!("Unable to read capture file {}", file),
+                ),
+            )),
+        }
+    }
+
+    /// Records a message received from or sent to a peer, if a capture is running.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - whether the message was received or sent
+    /// * `peer_id` - the peer the message was received from or sent to
+    /// * `connection_id` - the connection the message was received on or sent over, if known
+    /// * `message` - the bytes of the `NetworkMessage`
+    pub fn record_message(
+        &self,
+        direction: CaptureDirection,
+        peer_id: &str,
+        connection_id: Option<&str>,
+        message: &[u8],
+    ) {
+        if !self.running.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let (message_type, circuit_message_type) = match EnvelopeRef::parse(message) {
+            Ok(envelope) => {
+                let message_type = envelope.message_type::<NetworkMessageType>();
+                let circuit_message_type = if message_type == NetworkMessageType::CIRCUIT {
+                    EnvelopeRef::parse(envelope.payload()).ok().map(|circuit| {
+                        format!("{:?}", circuit.message_type::<CircuitMessageType>())
+                    })
+                } else {
+                    None
+                };
+                (format!("{:?}", message_type), circuit_message_type)
+            }
+            Err(_) => ("UNPARSEABLE".to_string(), None),
+        };
+
+        self.write_record(|include_payload_hashes| CaptureRecord {
+            timestamp: millis_since_epoch(SystemTime::now()),
+            direction: direction.to_string(),
+            peer_id: peer_id.to_string(),
+            connection_id: connection_id.map(String::from),
+            message_type,
+            circuit_message_type,
+            size: message.len(),
+            payload_hash: if include_payload_hashes {
+                Some(to_hex(&openssl::sha::sha256(message)))
+            } else {
+                None
+            },
+        });
+    }
+
+    /// Records a peer connection event, if a capture is running.
+    pub fn record_peer_notification(&self, notification: &PeerManagerNotification) {
+        if !self.running.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let (peer_id, event) = match notification {
+            PeerManagerNotification::Connected { peer } => (peer, "CONNECTED"),
+            PeerManagerNotification::Disconnected { peer } => (peer, "DISCONNECTED"),
+            PeerManagerNotification::EndpointsUpdated { peer, .. } => (peer, "ENDPOINTS_UPDATED"),
+            PeerManagerNotification::Removed { peer } => (peer, "REMOVED"),
+        };
+
+        self.write_record(|_| CaptureRecord {
+            timestamp: millis_since_epoch(SystemTime::now()),
+            direction: "peer".into(),
+            peer_id: peer_id.to_string(),
+            connection_id: None,
+            message_type: event.into(),
+            circuit_message_type: None,
+            size: 0,
+            payload_hash: None,
+        });
+    }
+
+    fn write_record<F>(&self, record: F)
+    where
+        F: FnOnce(bool) -> CaptureRecord,
+    {
+        let mut state = match self.lock_state() {
+            Ok(state) => state,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+        self.expire(&mut state);
+
+        let active = match state.active.as_mut() {
+            Some(active) => active,
+            None => return,
+        };
+
+        let record = record(active.status.include_payload_hashes);
+        let result = serde_json::to_writer(&mut active.writer, &record)
+            .map_err(|err| err.to_string())
+            .and_then(|_| writeln!(active.writer).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => active.status.records += 1,
+            Err(err) => {
+                error!(
+                    "Unable to write to capture file {}, stopping capture: {}",
+                    active.status.file, err
+                );
+                self.finish(&mut state);
+            }
+        }
+    }
+
+    // Finishes the running capture if its duration has elapsed
+    fn expire(&self, state: &mut CaptureState) {
+        let expired = state
+            .active
+            .as_ref()
+            .map(|active| SystemTime::now() >= active.ends_at)
+            .unwrap_or(false);
+        if expired {
+            self.finish(state);
+        }
+    }
+
+    fn finish(&self, state: &mut CaptureState) {
+        if let Some(mut active) = state.active.take() {
+            self.running.store(false, Ordering::SeqCst);
+            if let Err(err) = active.writer.flush() {
+                error!(
+                    "Unable to flush capture file {}: {}",
+                    active.status.file, err
+                );
+            }
+            active.status.active = false;
+            info!(
+                "Finished message capture {} with {} records",
+                active.status.file, active.status.records
+            );
+            state.last = Some(active.status);
+        }
+    }
+
+    fn lock_state(&self) -> Result<MutexGuard<CaptureState>, MessageCaptureError> {
+        self.state.lock().map_err(|_| {
+            MessageCaptureError::Internal(InternalError::with_message(
+                "Message capture lock was poisoned".into(),
+            ))
+        })
+    }
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crate::protos::envelope::{circuit_envelope, write_envelope};
+
+    /// Verify that a running capture records messages and peer events as JSON lines, including
+    /// the circuit message type of circuit messages and the payload hash when requested, and that
+    /// nothing is recorded once the capture is stopped.
+    #[test]
+    fn test_capture_records_messages() {
+        let temp_dir = TempDir::new("test_capture_records_messages").expect("Failed to create dir");
+        let capture = MessageCapture::new(temp_dir.path().join("captures"));
+
+        let message = write_envelope(
+            NetworkMessageType::CIRCUIT,
+            &circuit_envelope(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE, b"hello"),
+        );
+
+        let status = capture
+            .start(Duration::from_secs(60), true)
+            .expect("Unable to start capture");
+        assert!(status.active);
+
+        capture.record_message(
+            CaptureDirection::Inbound,
+            "peer-1",
+            Some("connection-1"),
+            &message,
+        );
+        capture.record_peer_notification(&PeerManagerNotification::Disconnected {
+            peer: "peer-1".into(),
+        });
+
+        let stopped = capture
+            .stop()
+            .expect("Unable to stop capture")
+            .expect("No capture was running");
+        assert!(!stopped.active);
+        assert_eq!(stopped.records, 2);
+
+        // nothing is recorded after the capture stops
+        capture.record_message(CaptureDirection::Outbound, "peer-1", None, &message);
+
+        let contents = capture
+            .read_capture_file(&status.file)
+            .expect("Unable to read capture file")
+            .expect("Capture file not found");
+        let records = String::from_utf8(contents)
+            .expect("Capture file is not UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str::<CaptureRecord>(line).expect("Invalid record"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].direction, "inbound");
+        assert_eq!(records[0].connection_id.as_deref(), Some("connection-1"));
+        assert_eq!(records[0].message_type, "CIRCUIT");
+        assert_eq!(
+            records[0].circuit_message_type.as_deref(),
+            Some("CIRCUIT_DIRECT_MESSAGE")
+        );
+        assert_eq!(records[0].size, message.len());
+        assert_eq!(
+            records[0].payload_hash,
+            Some(to_hex(&openssl::sha::sha256(&message)))
+        );
+        assert_eq!(records[1].direction, "peer");
+        assert_eq!(records[1].message_type, "DISCONNECTED");
+    }
+
+    /// Verify that only one capture may run at a time, that the duration is bounded, and that
+    /// only capture files can be read.
+    #[test]
+    fn test_capture_rejects_invalid_requests() {
+        let temp_dir =
+            TempDir::new("test_capture_rejects_invalid_requests").expect("Failed to create dir");
+        let capture = MessageCapture::new(temp_dir.path().to_path_buf());
+
+        assert!(matches!(
+            capture.start(MAX_CAPTURE_DURATION + Duration::from_secs(1), false),
+            Err(MessageCaptureError::InvalidArgument(_))
+        ));
+
+        capture
+            .start(Duration::from_secs(60), false)
+            .expect("Unable to start capture");
+        assert!(matches!(
+            capture.start(Duration::from_secs(60), false),
+            Err(MessageCaptureError::InvalidState(_))
+        ));
+
+        assert!(matches!(
+            capture.read_capture_file("../node_id"),
+            Err(MessageCaptureError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            capture
+                .read_capture_file("capture-1.jsonl")
+                .expect("Unable to read capture file"),
+            None
+        );
+    }
+}
@@ -270,6 +270,62 @@ impl PeerManagerConnector {
             PeerManagerError::SendMessageError("The peer manager is no longer running".into())
         })?
     }
+
+    /// Replaces the set of blocked peers.
+    ///
+    /// Blocked peers cannot be added, inbound connections from them are closed, and any existing
+    /// connections to them are closed when the blocklist is set. Peers removed from the blocklist
+    /// are reconnected if they are still referenced.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_ids` - The IDs of the peers to block
+    pub fn set_blocklist(&self, peer_ids: Vec<String>) -> Result<(), PeerManagerError> {
+        let (sender, recv) = channel();
+        self.sender
+            .send(PeerManagerMessage::Request(
+                PeerManagerRequest::SetBlocklist { peer_ids, sender },
+            ))
+            .map_err(|_| {
+                PeerManagerError::SendMessageError("The peer manager is no longer running".into())
+            })?;
+
+        recv.recv().map_err(|_| {
+            PeerManagerError::SendMessageError("The peer manager is no longer running".into())
+        })?
+    }
+
+    /// Replaces the endpoints of an existing peer.
+    ///
+    /// If the peer is connected on an endpoint that is no longer listed, the connection is closed
+    /// and the new endpoints are attempted. Updates for unknown peers are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The unique ID for the peer
+    /// * `endpoints` - The peer's new list of endpoints, in order of preference
+    pub fn update_peer_endpoints(
+        &self,
+        peer_id: &str,
+        endpoints: Vec<String>,
+    ) -> Result<(), PeerManagerError> {
+        let (sender, recv) = channel();
+        self.sender
+            .send(PeerManagerMessage::Request(
+                PeerManagerRequest::UpdatePeerEndpoints {
+                    peer_id: peer_id.to_string(),
+                    endpoints,
+                    sender,
+                },
+            ))
+            .map_err(|_| {
+                PeerManagerError::SendMessageError("The peer manager is no longer running".into())
+            })?;
+
+        recv.recv().map_err(|_| {
+            PeerManagerError::SendMessageError("The peer manager is no longer running".into())
+        })?
+    }
 }
 
 impl PeerLookup for PeerManagerConnector {
@@ -27,13 +27,18 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
 
 use crate::network::dispatch::DispatchMessageSender;
-use crate::protos::network::{NetworkMessage, NetworkMessageType};
+use crate::protos::envelope::EnvelopeRef;
+use crate::protos::network::NetworkMessageType;
 use crate::transport::matrix::{
     ConnectionMatrixReceiver, ConnectionMatrixRecvError, ConnectionMatrixSender,
 };
 
+#[cfg(feature = "message-capture")]
+use super::capture::{CaptureDirection, MessageCapture};
 use super::connector::{PeerLookup, PeerLookupProvider};
 use super::error::PeerInterconnectError;
+#[cfg(feature = "outbound-spool")]
+use super::spool::OutboundSpool;
 
 /// Message to send to the network message sender with the recipient and payload
 #[derive(Clone, Debug, PartialEq)]
@@ -157,6 +162,12 @@ where
     message_sender: Option<U>,
     // a Dispatcher with handlers for NetworkMessageTypes
     network_dispatcher_sender: Option<DispatchMessageSender<NetworkMessageType>>,
+    // spool for circuit messages that could not be sent
+    #[cfg(feature = "outbound-spool")]
+    outbound_spool: Option<OutboundSpool>,
+    // capture that messages to and from peers are recorded to while it is running
+    #[cfg(feature = "message-capture")]
+    message_capture: Option<MessageCapture>,
 }
 
 impl<T, U, P> PeerInterconnectBuilder<T, U, P>
@@ -172,6 +183,10 @@ where
             message_receiver: None,
             message_sender: None,
             network_dispatcher_sender: None,
+            #[cfg(feature = "outbound-spool")]
+            outbound_spool: None,
+            #[cfg(feature = "message-capture")]
+            message_capture: None,
         }
     }
 
@@ -221,6 +236,30 @@ where
         self
     }
 
+    /// Adds an `OutboundSpool` to `PeerInterconnectBuilder`
+    ///
+    /// # Arguments
+    ///
+    /// * `outbound_spool` - an `OutboundSpool` that circuit messages will be written to when they
+    ///   cannot be sent because the peer is unknown or its connection is unavailable
+    #[cfg(feature = "outbound-spool")]
+    pub fn with_outbound_spool(mut self, outbound_spool: OutboundSpool) -> Self {
+        self.outbound_spool = Some(outbound_spool);
+        self
+    }
+
+    /// Adds a `MessageCapture` to `PeerInterconnectBuilder`
+    ///
+    /// # Arguments
+    ///
+    /// * `message_capture` - a `MessageCapture` that messages received from and sent to peers
+    ///   will be recorded to while a capture is running
+    #[cfg(feature = "message-capture")]
+    pub fn with_message_capture(mut self, message_capture: MessageCapture) -> Self {
+        self.message_capture = Some(message_capture);
+        self
+    }
+
     /// Builds the `PeerInterconnect`. This function will start up threads to send and recv messages
     /// from the peers.
     ///
@@ -242,6 +281,8 @@ where
         })?;
 
         let recv_peer_lookup = peer_lookup_provider.peer_lookup();
+        #[cfg(feature = "message-capture")]
+        let recv_message_capture = self.message_capture.clone();
         debug!("Starting peer interconnect receiver");
         let recv_join_handle = thread::Builder::new()
             .name("PeerInterconnect Receiver".into())
@@ -250,6 +291,8 @@ where
                     &*recv_peer_lookup,
                     message_receiver,
                     network_dispatcher_sender,
+                    #[cfg(feature = "message-capture")]
+                    recv_message_capture,
                 ) {
                     error!("Shutting down peer interconnect recevier: {}", err);
                 }
@@ -266,13 +309,23 @@ where
             .message_sender
             .take()
             .ok_or_else(|| PeerInterconnectError::StartUpError("Already started".to_string()))?;
+        #[cfg(feature = "outbound-spool")]
+        let outbound_spool = self.outbound_spool.take();
+        #[cfg(feature = "message-capture")]
+        let send_message_capture = self.message_capture.take();
         debug!("Starting peer interconnect sender");
         let send_join_handle = thread::Builder::new()
             .name("PeerInterconnect Sender".into())
             .spawn(move || {
-                if let Err(err) =
-                    run_send_loop(&*send_peer_lookup, dispatched_receiver, message_sender)
-                {
+                if let Err(err) = run_send_loop(
+                    &*send_peer_lookup,
+                    dispatched_receiver,
+                    message_sender,
+                    #[cfg(feature = "outbound-spool")]
+                    outbound_spool,
+                    #[cfg(feature = "message-capture")]
+                    send_message_capture,
+                ) {
                     error!("Shutting down peer interconnect sender: {}", err);
                 }
             })
@@ -298,6 +351,7 @@ fn run_recv_loop<R>(
     peer_connector: &dyn PeerLookup,
     message_receiver: R,
     dispatch_msg_sender: DispatchMessageSender<NetworkMessageType>,
+    #[cfg(feature = "message-capture")] message_capture: Option<MessageCapture>,
 ) -> Result<(), String>
 where
     R: ConnectionMatrixReceiver + 'static,
@@ -334,23 +388,32 @@ where
 
         // If we have the peer, pass message to dispatcher, else print error
         if let Some(peer_id) = peer_id {
-            let mut network_msg: NetworkMessage =
-                match protobuf::parse_from_bytes(&envelope.payload()) {
-                    Ok(msg) => msg,
-                    Err(err) => {
-                        error!("Unable to dispatch message: {}", err);
-                        continue;
-                    }
-                };
-
-            trace!(
-                "Received message from {}: {:?}",
-                peer_id,
-                network_msg.get_message_type()
-            );
-            match dispatch_msg_sender.send(
-                network_msg.get_message_type(),
-                network_msg.take_payload(),
+            // The payload is dispatched in place, without copying it out of the envelope
+            let (message_type, payload_range) = match EnvelopeRef::parse(envelope.payload()) {
+                Ok(network_msg) => (
+                    network_msg.message_type::<NetworkMessageType>(),
+                    network_msg.payload_range(),
+                ),
+                Err(err) => {
+                    error!("Unable to dispatch message: {}", err);
+                    continue;
+                }
+            };
+
+            trace!("Received message from {}: {:?}", peer_id, message_type);
+            #[cfg(feature = "message-capture")]
+            if let Some(message_capture) = &message_capture {
+                message_capture.record_message(
+                    CaptureDirection::Inbound,
+                    &peer_id,
+                    Some(connection_id),
+                    envelope.payload(),
+                );
+            }
+            match dispatch_msg_sender.send_range(
+                message_type,
+                envelope.into_inner(),
+                payload_range,
                 peer_id.into(),
             ) {
                 Ok(()) => (),
@@ -371,6 +434,8 @@ fn run_send_loop<S>(
     peer_connector: &dyn PeerLookup,
     receiver: Receiver<SendRequest>,
     message_sender: S,
+    #[cfg(feature = "outbound-spool")] outbound_spool: Option<OutboundSpool>,
+    #[cfg(feature = "message-capture")] message_capture: Option<MessageCapture>,
 ) -> Result<(), String>
 where
     S: ConnectionMatrixSender + 'static,
@@ -401,6 +466,16 @@ where
             None
         };
 
+        #[cfg(feature = "message-capture")]
+        if let Some(message_capture) = &message_capture {
+            message_capture.record_message(
+                CaptureDirection::Outbound,
+                &recipient,
+                connection_id.as_deref(),
+                &payload,
+            );
+        }
+
         // if peer exists, send message over the network
         if let Some(connection_id) = connection_id {
             // If connection is missing, check with peer manager to see if connection id has
@@ -415,18 +490,36 @@ where
                     if new_connection_id != connection_id {
                         peer_id_to_connection_id
                             .insert(recipient.clone(), new_connection_id.clone());
-                        if let Err(err) = message_sender.send(new_connection_id, payload) {
+                        if let Err(err) = message_sender.send(new_connection_id, payload.clone()) {
                             error!("Unable to send message to {}: {}", recipient, err);
+                            #[cfg(feature = "outbound-spool")]
+                            spool_message(outbound_spool.as_ref(), &recipient, &payload);
                         }
                     }
                 } else {
                     error!("Unable to send message to {}: {}", recipient, err);
                     // remove cached connection id, peer has gone away
                     peer_id_to_connection_id.remove(&recipient);
+                    #[cfg(feature = "outbound-spool")]
+                    spool_message(outbound_spool.as_ref(), &recipient, &payload);
                 }
             }
         } else {
             error!("Cannot send message, unknown peer: {}", recipient);
+            #[cfg(feature = "outbound-spool")]
+            spool_message(outbound_spool.as_ref(), &recipient, &payload);
+        }
+    }
+}
+
+/// Writes a message that could not be sent to the outbound spool, if there is one
+#[cfg(feature = "outbound-spool")]
+fn spool_message(outbound_spool: Option<&OutboundSpool>, recipient: &str, payload: &[u8]) {
+    if let Some(outbound_spool) = outbound_spool {
+        match outbound_spool.spool(recipient, payload) {
+            Ok(true) => debug!("Spooled message for unreachable peer {}", recipient),
+            Ok(false) => (),
+            Err(err) => error!("Unable to spool message for {}: {}", recipient, err),
         }
     }
 }
@@ -490,6 +583,7 @@ pub mod tests {
     };
     use crate::peer::{PeerManager, PeerManagerNotification};
     use crate::protos::network::NetworkEcho;
+    use crate::protos::network::NetworkMessage;
     use crate::transport::{inproc::InprocTransport, Connection, Transport};
 
     // Verify that the PeerInterconnect properly receives messages from peers, passes them to
@@ -23,17 +23,22 @@
 //! [`PeerManagerNotification`]: notification/enum.PeerManagerNotification.html
 
 mod builder;
+#[cfg(feature = "message-capture")]
+pub mod capture;
 mod connector;
 mod error;
 pub mod interconnect;
 mod notification;
 mod peer_map;
 mod peer_ref;
+#[cfg(feature = "outbound-spool")]
+pub mod spool;
 
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::ErrorKind;
 use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
@@ -42,12 +47,15 @@ use uuid::Uuid;
 use crate::collections::{BiHashMap, RefMap};
 use crate::network::connection_manager::ConnectionManagerNotification;
 use crate::network::connection_manager::{ConnectionManagerError, Connector};
+use crate::threading::clock::Clock;
 use crate::threading::pacemaker;
 
 pub use self::builder::PeerManagerBuilder;
+#[cfg(feature = "circuit-relay")]
+pub use self::connector::PeerLookup;
 pub use self::connector::PeerManagerConnector;
 use self::connector::PeerRemover;
-use self::error::{
+pub use self::error::{
     PeerConnectionIdError, PeerListError, PeerLookupError, PeerManagerError, PeerRefAddError,
     PeerRefRemoveError, PeerUnknownAddError,
 };
@@ -121,6 +129,15 @@ pub(crate) enum PeerManagerRequest {
         subscriber_id: SubscriberId,
         sender: Sender<Result<(), PeerManagerError>>,
     },
+    SetBlocklist {
+        peer_ids: Vec<String>,
+        sender: Sender<Result<(), PeerManagerError>>,
+    },
+    UpdatePeerEndpoints {
+        peer_id: String,
+        endpoints: Vec<String>,
+        sender: Sender<Result<(), PeerManagerError>>,
+    },
 }
 
 /// The `PeerManager` is in charge of keeping track of peers and their reference counts, as well as
@@ -240,17 +257,20 @@ impl PeerManager {
         retry_frequency: u64,
         max_retry_frequency: u64,
         endpoint_retry_frequency: u64,
+        endpoint_preference: Vec<String>,
+        clock: Arc<dyn Clock>,
     ) -> Result<PeerManager, PeerManagerError> {
         debug!(
             "Starting peer manager with retry_interval={}s, max_retry_attempts={} \
-            strict_ref_counts={}, retry_frequency={}, max_retry_frequency={}, and \
-            endpoint_retry_frequency={}",
+            strict_ref_counts={}, retry_frequency={}, max_retry_frequency={}, \
+            endpoint_retry_frequency={}, and endpoint_preference={:?}",
             retry_interval,
             max_retry_attempts,
             strict_ref_counts,
             retry_frequency,
             max_retry_frequency,
             endpoint_retry_frequency,
+            endpoint_preference,
         );
 
         let (sender, recv) = channel();
@@ -275,6 +295,7 @@ impl PeerManager {
             .with_interval(retry_interval)
             .with_sender(sender.clone())
             .with_message_factory(|| PeerManagerMessage::RetryPending)
+            .with_clock(clock.clone())
             .start()
             .map_err(|err| PeerManagerError::StartUpError(err.to_string()))?;
 
@@ -283,12 +304,15 @@ impl PeerManager {
         let join_handle = thread::Builder::new()
             .name("Peer Manager".into())
             .spawn(move || {
-                let mut peers = PeerMap::new(retry_frequency);
+                let mut peers = PeerMap::new(retry_frequency, clock.clone());
                 // a map of identities to unreferenced peers.
                 // and a list of endpoints that should be turned into peers
-                let mut unreferenced_peers = UnreferencedPeerState::new(endpoint_retry_frequency);
+                let mut unreferenced_peers =
+                    UnreferencedPeerState::new(endpoint_retry_frequency, clock);
                 let mut ref_map = RefMap::new();
                 let mut subscribers = SubscriberMap::new();
+                // peer IDs that are not allowed to connect
+                let mut blocklist = HashSet::new();
                 loop {
                     match recv.recv() {
                         Ok(PeerManagerMessage::Shutdown) => break,
@@ -302,6 +326,8 @@ impl PeerManager {
                                 &mut ref_map,
                                 &mut subscribers,
                                 strict_ref_counts,
+                                &mut blocklist,
+                                &endpoint_preference,
                             );
                         }
                         Ok(PeerManagerMessage::Subscribe(sender)) => {
@@ -321,6 +347,7 @@ impl PeerManager {
                                 &identity,
                                 &mut ref_map,
                                 retry_frequency,
+                                &blocklist,
                             )
                         }
                         Ok(PeerManagerMessage::RetryPending) => retry_pending(
@@ -328,6 +355,7 @@ impl PeerManager {
                             connector.clone(),
                             &mut unreferenced_peers,
                             max_retry_frequency,
+                            &blocklist,
                         ),
                         Err(_) => {
                             warn!("All senders have disconnected");
@@ -423,6 +451,8 @@ fn handle_request(
     ref_map: &mut RefMap,
     subscribers: &mut SubscriberMap,
     strict_ref_counts: bool,
+    blocklist: &mut HashSet<String>,
+    endpoint_preference: &[String],
 ) {
     match request {
         PeerManagerRequest::AddPeer {
@@ -433,13 +463,14 @@ fn handle_request(
             if sender
                 .send(add_peer(
                     peer_id,
-                    endpoints,
+                    order_endpoints(endpoints, endpoint_preference),
                     connector,
                     unreferenced_peers,
                     peers,
                     peer_remover,
                     ref_map,
                     subscribers,
+                    blocklist,
                 ))
                 .is_err()
             {
@@ -469,6 +500,7 @@ fn handle_request(
                     unreferenced_peers,
                     peers,
                     ref_map,
+                    subscribers,
                     strict_ref_counts,
                 ))
                 .is_err()
@@ -483,6 +515,7 @@ fn handle_request(
                     connector,
                     peers,
                     ref_map,
+                    subscribers,
                     strict_ref_counts,
                 ))
                 .is_err()
@@ -560,7 +593,160 @@ fn handle_request(
                 warn!("connector dropped before receiving result of remove connection");
             }
         }
+        PeerManagerRequest::SetBlocklist { peer_ids, sender } => {
+            *blocklist = peer_ids.into_iter().collect();
+            disconnect_blocked_peers(blocklist, connector, unreferenced_peers, peers, subscribers);
+            if sender.send(Ok(())).is_err() {
+                warn!("connector dropped before receiving result of set blocklist");
+            }
+        }
+        PeerManagerRequest::UpdatePeerEndpoints {
+            peer_id,
+            endpoints,
+            sender,
+        } => {
+            update_peer_endpoints(
+                peer_id,
+                order_endpoints(endpoints, endpoint_preference),
+                connector,
+                peers,
+                subscribers,
+                blocklist,
+            );
+            if sender.send(Ok(())).is_err() {
+                warn!("connector dropped before receiving result of update peer endpoints");
+            }
+        }
+    };
+}
+
+/// Replaces the endpoints of an existing peer.
+///
+/// If the peer's outbound connection uses an endpoint that is no longer listed, that connection
+/// is closed and the new endpoints are attempted, in order. Subscribers are notified of the
+/// updated endpoints and of the peer disconnecting, and will be notified again when the peer
+/// reconnects. Requests for peers that are not known, or whose endpoints have not changed, are
+/// ignored.
+fn update_peer_endpoints(
+    peer_id: String,
+    endpoints: Vec<String>,
+    connector: Connector,
+    peers: &mut PeerMap,
+    subscribers: &mut SubscriberMap,
+    blocklist: &HashSet<String>,
+) {
+    let mut peer_metadata = match peers.get_by_peer_id(&peer_id) {
+        Some(peer_metadata) if peer_metadata.endpoints != endpoints => peer_metadata.clone(),
+        _ => return,
     };
+
+    if endpoints.is_empty() {
+        warn!(
+            "Ignoring endpoint update for peer {}: no endpoints provided",
+            peer_id
+        );
+        return;
+    }
+
+    info!(
+        "Updating peer {} endpoints from {:?} to {:?}",
+        peer_id, peer_metadata.endpoints, endpoints
+    );
+
+    // Only outbound connections use one of the peer's endpoints; an inbound connection is kept
+    let stale_connection = peer_metadata
+        .endpoints
+        .contains(&peer_metadata.active_endpoint)
+        && !endpoints.contains(&peer_metadata.active_endpoint);
+
+    peer_metadata.endpoints = endpoints.clone();
+    if let Some(connected_endpoint) = &peer_metadata.connected_endpoint {
+        if !endpoints.contains(connected_endpoint) {
+            peer_metadata.connected_endpoint = None;
+        }
+    }
+
+    subscribers.broadcast(PeerManagerNotification::EndpointsUpdated {
+        peer: peer_id.to_string(),
+        endpoints,
+    });
+
+    if stale_connection {
+        if peer_metadata.status != PeerStatus::Pending {
+            if let Err(err) = connector.remove_connection(&peer_metadata.active_endpoint) {
+                error!("Unable to clean up stale connection: {}", err);
+            }
+
+            if peer_metadata.status == PeerStatus::Connected {
+                subscribers.broadcast(PeerManagerNotification::Disconnected {
+                    peer: peer_id.to_string(),
+                });
+            }
+        }
+
+        peer_metadata.status = PeerStatus::Pending;
+        peer_metadata.last_connection_attempt = peers.clock().now();
+
+        if !blocklist.contains(&peer_id) {
+            info!(
+                "Attempting to peer with {} using updated endpoints",
+                peer_id
+            );
+            for endpoint in peer_metadata.endpoints_by_preference() {
+                match connector.request_connection(&endpoint, &peer_metadata.connection_id) {
+                    Ok(()) => {
+                        peer_metadata.active_endpoint = endpoint;
+                        break;
+                    }
+                    // If request_connection errored try the next endpoint, the peer will be
+                    // retried in the future if none of them succeed
+                    Err(err) => log_connect_request_err(err, &peer_id, &endpoint),
+                }
+            }
+        }
+    }
+
+    if let Err(err) = peers.update_peer_endpoints(peer_metadata) {
+        error!("Unable to update peer: {}", err);
+    }
+}
+
+/// Closes the connections of any connected peers that are in the blocklist. Referenced peers are
+/// kept in the peer map as pending, so they will be reconnected if they are later removed from
+/// the blocklist, but no connection attempts are made while they are blocked.
+fn disconnect_blocked_peers(
+    blocklist: &HashSet<String>,
+    connector: Connector,
+    unreferenced_peers: &mut UnreferencedPeerState,
+    peers: &mut PeerMap,
+    subscribers: &mut SubscriberMap,
+) {
+    for peer_id in blocklist {
+        if let Some(unref_peer) = unreferenced_peers.peers.remove(peer_id) {
+            info!("Disconnecting blocked peer {}", peer_id);
+            if let Err(err) = connector.remove_connection(&unref_peer.endpoint) {
+                error!("Unable to remove connection to blocked peer: {}", err);
+            }
+        }
+
+        if let Some(mut peer_metadata) = peers.get_by_peer_id(peer_id).cloned() {
+            if peer_metadata.status == PeerStatus::Connected {
+                info!("Disconnecting blocked peer {}", peer_id);
+                if let Err(err) = connector.remove_connection(&peer_metadata.active_endpoint) {
+                    error!("Unable to remove connection to blocked peer: {}", err);
+                }
+
+                peer_metadata.status = PeerStatus::Pending;
+                if let Err(err) = peers.update_peer(peer_metadata) {
+                    error!("Unable to update peer: {}", err);
+                }
+
+                subscribers.broadcast(PeerManagerNotification::Disconnected {
+                    peer: peer_id.to_string(),
+                });
+            }
+        }
+    }
 }
 
 /// An entry of unreferenced peers, that may have connected externally, but have not yet been
@@ -579,15 +765,18 @@ struct UnreferencedPeerState {
     last_connection_attempt: Instant,
     // How often to try to connect to requested endpoints
     retry_frequency: u64,
+    // The clock used to measure time between connection attempts
+    clock: Arc<dyn Clock>,
 }
 
 impl UnreferencedPeerState {
-    fn new(retry_frequency: u64) -> Self {
+    fn new(retry_frequency: u64, clock: Arc<dyn Clock>) -> Self {
         UnreferencedPeerState {
             peers: HashMap::default(),
             requested_endpoints: Vec::default(),
-            last_connection_attempt: Instant::now(),
+            last_connection_attempt: clock.now(),
             retry_frequency,
+            clock,
         }
     }
 }
@@ -604,7 +793,15 @@ fn add_peer(
     peer_remover: &PeerRemover,
     ref_map: &mut RefMap,
     subscribers: &mut SubscriberMap,
+    blocklist: &HashSet<String>,
 ) -> Result<PeerRef, PeerRefAddError> {
+    if blocklist.contains(&peer_id) {
+        return Err(PeerRefAddError::AddError(format!(
+            "Peer {} is blocked",
+            peer_id
+        )));
+    }
+
     let new_ref_count = ref_map.add_ref(peer_id.to_string());
 
     // if this is not a new peer, return success
@@ -768,6 +965,7 @@ fn remove_peer(
     unreferenced_peers: &mut UnreferencedPeerState,
     peers: &mut PeerMap,
     ref_map: &mut RefMap,
+    subscribers: &mut SubscriberMap,
     strict_ref_counts: bool,
 ) -> Result<(), PeerRefRemoveError> {
     debug!("Removing peer: {}", peer_id);
@@ -800,6 +998,9 @@ fn remove_peer(
                 peer_id
             ))
         })?;
+        subscribers.broadcast(PeerManagerNotification::Removed {
+            peer: peer_metadata.id.clone(),
+        });
 
         // If the peer is pending there is no connection to remove
         if peer_metadata.status == PeerStatus::Pending {
@@ -830,6 +1031,7 @@ fn remove_peer_by_endpoint(
     connector: Connector,
     peers: &mut PeerMap,
     ref_map: &mut RefMap,
+    subscribers: &mut SubscriberMap,
     strict_ref_counts: bool,
 ) -> Result<(), PeerRefRemoveError> {
     let peer_metadata = match peers.get_peer_from_endpoint(&endpoint) {
@@ -870,6 +1072,9 @@ fn remove_peer_by_endpoint(
                 endpoint
             ))
         })?;
+        subscribers.broadcast(PeerManagerNotification::Removed {
+            peer: peer_metadata.id.clone(),
+        });
 
         // If the peer is pending there is no connection to remove
         if peer_metadata.status == PeerStatus::Pending {
@@ -909,6 +1114,7 @@ fn handle_notifications(
     local_identity: &str,
     ref_map: &mut RefMap,
     retry_frequency: u64,
+    blocklist: &HashSet<String>,
 ) {
     match notification {
         // If a connection has disconnected, forward notification to subscribers
@@ -942,9 +1148,9 @@ fn handle_notifications(
                         return;
                     };
                     info!("Attempting to find available endpoint for {}", identity);
-                    for endpoint in peer_metadata.endpoints.iter() {
+                    for endpoint in peer_metadata.endpoints_by_preference() {
                         // do not retry the connection that is currently failing
-                        if endpoint == &peer_metadata.active_endpoint {
+                        if endpoint == peer_metadata.active_endpoint {
                             continue;
                         }
                         match connector.request_connection(&endpoint, &peer_metadata.connection_id)
@@ -980,6 +1186,7 @@ fn handle_notifications(
             subscribers,
             local_identity,
             retry_frequency,
+            blocklist,
         ),
         ConnectionManagerNotification::Connected {
             endpoint,
@@ -996,12 +1203,14 @@ fn handle_notifications(
             local_identity,
             ref_map,
             retry_frequency,
+            blocklist,
         ),
         ConnectionManagerNotification::FatalConnectionError { endpoint, error } => {
             handle_fatal_connection(
                 endpoint,
                 error.to_string(),
                 peers,
+                connector,
                 subscribers,
                 max_retry_attempts,
             )
@@ -1049,7 +1258,7 @@ fn handle_disconnection(
             }
 
             info!("Attempting to find available endpoint for {}", identity);
-            for endpoint in peer_metadata.endpoints.iter() {
+            for endpoint in peer_metadata.endpoints_by_preference() {
                 match connector.request_connection(&endpoint, &peer_metadata.connection_id) {
                     Ok(()) => break,
                     Err(err) => {
@@ -1087,12 +1296,24 @@ fn handle_inbound_connection(
     subscribers: &mut SubscriberMap,
     local_identity: &str,
     retry_frequency: u64,
+    blocklist: &HashSet<String>,
 ) {
     info!(
         "Received peer connection from {} (remote endpoint: {})",
         identity, endpoint
     );
 
+    if blocklist.contains(&identity) {
+        info!(
+            "Rejecting inbound connection from blocked peer {}",
+            identity
+        );
+        if let Err(err) = connector.remove_connection(&endpoint) {
+            error!("Unable to remove connection to blocked peer: {}", err);
+        }
+        return;
+    }
+
     // If we got an inbound counnection for an existing peer, replace old connection with
     // this new one unless we are already connected.
     if let Some(mut peer_metadata) = peers.get_by_peer_id(&identity).cloned() {
@@ -1133,7 +1354,7 @@ fn handle_inbound_connection(
         peer_metadata.connection_id = connection_id;
         // reset retry settings
         peer_metadata.retry_frequency = retry_frequency;
-        peer_metadata.last_connection_attempt = Instant::now();
+        peer_metadata.last_connection_attempt = peers.clock().now();
 
         let notification = PeerManagerNotification::Connected {
             peer: peer_metadata.id.to_string(),
@@ -1186,7 +1407,16 @@ fn handle_connected(
     local_identity: &str,
     ref_map: &mut RefMap,
     retry_frequency: u64,
+    blocklist: &HashSet<String>,
 ) {
+    if blocklist.contains(&identity) {
+        info!("Closing connection to blocked peer {}", identity);
+        if let Err(err) = connector.remove_connection(&endpoint) {
+            error!("Unable to remove connection to blocked peer: {}", err);
+        }
+        return;
+    }
+
     if let Some(mut peer_metadata) = peers.get_peer_from_endpoint(&endpoint).cloned() {
         match peer_metadata.status {
             PeerStatus::Pending => {
@@ -1266,11 +1496,12 @@ fn handle_connected(
         let starting_status = peer_metadata.status;
         let old_endpoint = peer_metadata.active_endpoint;
         peer_metadata.active_endpoint = endpoint.to_string();
+        peer_metadata.connected_endpoint = Some(endpoint.to_string());
         peer_metadata.status = PeerStatus::Connected;
         peer_metadata.connection_id = connection_id;
         // reset retry settings
         peer_metadata.retry_frequency = retry_frequency;
-        peer_metadata.last_connection_attempt = Instant::now();
+        peer_metadata.last_connection_attempt = peers.clock().now();
 
         if let Err(err) = peers.update_peer(peer_metadata) {
             error!("Unable to update peer: {}", err);
@@ -1327,6 +1558,7 @@ fn handle_fatal_connection(
     endpoint: String,
     error: String,
     peers: &mut PeerMap,
+    connector: Connector,
     subscribers: &mut SubscriberMap,
     max_retry_frequency: u64,
 ) {
@@ -1343,7 +1575,32 @@ fn handle_fatal_connection(
 
         // reset retry settings
         peer_metadata.retry_frequency = min(peer_metadata.retry_frequency * 2, max_retry_frequency);
-        peer_metadata.last_connection_attempt = Instant::now();
+        peer_metadata.last_connection_attempt = peers.clock().now();
+
+        // fail over to the endpoints after the one that failed; if none of them can be
+        // requested the peer's endpoints will be retried in the future
+        let next_endpoints: Vec<String> = peer_metadata
+            .endpoints
+            .iter()
+            .skip_while(|peer_endpoint| **peer_endpoint != endpoint)
+            .skip(1)
+            .cloned()
+            .collect();
+        for next_endpoint in next_endpoints {
+            match connector.request_connection(&next_endpoint, &peer_metadata.connection_id) {
+                Ok(()) => {
+                    info!(
+                        "Failing over peer {} from {} to {}",
+                        peer_metadata.id, endpoint, next_endpoint
+                    );
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("splinter.peer.endpoint_failovers", 1);
+                    peer_metadata.active_endpoint = next_endpoint;
+                    break;
+                }
+                Err(err) => log_connect_request_err(err, &peer_metadata.id, &next_endpoint),
+            }
+        }
 
         // set peer to pending so its endpoints will be retried in the future
         peer_metadata.status = PeerStatus::Pending;
@@ -1363,20 +1620,33 @@ fn retry_pending(
     connector: Connector,
     unreferenced_peers: &mut UnreferencedPeerState,
     max_retry_frequency: u64,
+    blocklist: &HashSet<String>,
 ) {
     let mut to_retry = Vec::new();
     for (_, peer) in peers.get_pending() {
-        if peer.last_connection_attempt.elapsed().as_secs() > peer.retry_frequency {
+        if blocklist.contains(&peer.id) {
+            continue;
+        }
+        if peers
+            .clock()
+            .elapsed(peer.last_connection_attempt)
+            .as_secs()
+            > peer.retry_frequency
+        {
             to_retry.push(peer.clone());
         }
     }
 
     for mut peer_metadata in to_retry {
         debug!("Attempting to peer with pending peer {}", peer_metadata.id);
-        for endpoint in peer_metadata.endpoints.iter() {
+        for endpoint in peer_metadata.endpoints_by_preference() {
             match connector.request_connection(&endpoint, &peer_metadata.connection_id) {
-                Ok(()) => peer_metadata.active_endpoint = endpoint.to_string(),
-                // If request_connection errored we will retry in the future
+                Ok(()) => {
+                    peer_metadata.active_endpoint = endpoint;
+                    break;
+                }
+                // If request_connection errored try the next endpoint, the peer will be retried
+                // in the future if none of them succeed
                 Err(err) => {
                     log_connect_request_err(err, &peer_metadata.id, &endpoint);
                 }
@@ -1384,15 +1654,15 @@ fn retry_pending(
         }
 
         peer_metadata.retry_frequency = min(peer_metadata.retry_frequency * 2, max_retry_frequency);
-        peer_metadata.last_connection_attempt = Instant::now();
+        peer_metadata.last_connection_attempt = peers.clock().now();
         if let Err(err) = peers.update_peer(peer_metadata) {
             error!("Unable to update peer: {}", err);
         }
     }
 
     if unreferenced_peers
-        .last_connection_attempt
-        .elapsed()
+        .clock
+        .elapsed(unreferenced_peers.last_connection_attempt)
         .as_secs()
         > unreferenced_peers.retry_frequency
     {
@@ -1438,7 +1708,30 @@ fn retry_pending(
             }
         }
 
-        unreferenced_peers.last_connection_attempt = Instant::now();
+        unreferenced_peers.last_connection_attempt = unreferenced_peers.clock.now();
+    }
+}
+
+/// Orders endpoints by the position of their transport scheme in the preference list. Endpoints
+/// with a scheme that is not listed keep their relative order after the preferred endpoints.
+fn order_endpoints(mut endpoints: Vec<String>, preference: &[String]) -> Vec<String> {
+    if !preference.is_empty() {
+        endpoints.sort_by_key(|endpoint| {
+            let scheme = endpoint_scheme(endpoint);
+            preference
+                .iter()
+                .position(|preferred| preferred == scheme)
+                .unwrap_or_else(|| preference.len())
+        });
+    }
+    endpoints
+}
+
+/// Returns the transport scheme of an endpoint; endpoints without a scheme are plain TCP.
+fn endpoint_scheme(endpoint: &str) -> &str {
+    match endpoint.find("://") {
+        Some(index) => &endpoint[..index],
+        None => "tcp",
     }
 }
 
@@ -1556,6 +1849,82 @@ pub mod tests {
         mesh.shutdown_signaler().shutdown();
     }
 
+    // Test that a blocked peer cannot be added, and that it can be added once it is removed from
+    // the blocklist.
+    //
+    // 1. block test_peer
+    // 2. verify that adding test_peer returns an error
+    // 3. clear the blocklist
+    // 4. verify that test_peer can be added and a Connected notification is received
+    #[test]
+    fn test_peer_manager_blocklist() {
+        let mut transport = Box::new(InprocTransport::default());
+        let mut listener = transport.listen("inproc://test").unwrap();
+
+        thread::spawn(move || {
+            listener.accept().unwrap();
+        });
+
+        let mesh = Mesh::new(512, 128);
+        let cm = ConnectionManager::builder()
+            .with_authorizer(Box::new(NoopAuthorizer::new("test_peer")))
+            .with_matrix_life_cycle(mesh.get_life_cycle())
+            .with_matrix_sender(mesh.get_sender())
+            .with_transport(transport.clone())
+            .start()
+            .expect("Unable to start Connection Manager");
+
+        let connector = cm.connector();
+        let peer_manager = PeerManager::builder()
+            .with_connector(connector)
+            .with_retry_interval(1)
+            .with_identity("my_id".to_string())
+            .with_strict_ref_counts(true)
+            .start()
+            .expect("Cannot start peer_manager");
+        let peer_connector = peer_manager.connector();
+        let (tx, notification_rx): (
+            Sender<PeerManagerNotification>,
+            mpsc::Receiver<PeerManagerNotification>,
+        ) = channel();
+        peer_connector
+            .subscribe_sender(tx)
+            .expect("Unable to get subscriber");
+
+        peer_connector
+            .set_blocklist(vec!["test_peer".to_string()])
+            .expect("Unable to set blocklist");
+        assert!(peer_connector
+            .add_peer_ref("test_peer".to_string(), vec!["inproc://test".to_string()])
+            .is_err());
+        assert!(peer_connector.list_peers().unwrap().is_empty());
+
+        peer_connector
+            .set_blocklist(vec![])
+            .expect("Unable to set blocklist");
+        let _peer_ref = peer_connector
+            .add_peer_ref("test_peer".to_string(), vec!["inproc://test".to_string()])
+            .expect("Unable to add peer");
+
+        // timeout after 60 seconds
+        let timeout = Duration::from_secs(60);
+        let notification = notification_rx
+            .recv_timeout(timeout)
+            .expect("Unable to get new notifications");
+        assert!(
+            notification
+                == PeerManagerNotification::Connected {
+                    peer: "test_peer".to_string(),
+                }
+        );
+
+        peer_manager.shutdown_signaler().shutdown();
+        cm.shutdown_signaler().shutdown();
+        peer_manager.await_shutdown();
+        cm.await_shutdown();
+        mesh.shutdown_signaler().shutdown();
+    }
+
     // Test that a call to add_peer_ref, where the authorizer returns an different id than
     // requested, the connector returns an error.
     //
@@ -1981,6 +2350,7 @@ pub mod tests {
     // 4. drop the PeerRef
     // 5. call list peers
     // 6. verify that the new peer list is empty
+    // 7. verify that subscribers are notified that test_peer was removed
     #[test]
     fn test_peer_manager_drop_peer_ref() {
         let mut transport = Box::new(InprocTransport::default());
@@ -2009,22 +2379,23 @@ pub mod tests {
             .expect("Cannot start peer_manager");
         let peer_connector = peer_manager.connector();
 
+        let (tx, notification_rx): (
+            Sender<PeerManagerNotification>,
+            mpsc::Receiver<PeerManagerNotification>,
+        ) = channel();
+        peer_connector
+            .subscribe_sender(tx)
+            .expect("Unable to get subscriber");
+        // timeout after 60 seconds
+        let timeout = Duration::from_secs(60);
+
         {
-            let (tx, notification_rx): (
-                Sender<PeerManagerNotification>,
-                mpsc::Receiver<PeerManagerNotification>,
-            ) = channel();
-            peer_connector
-                .subscribe_sender(tx)
-                .expect("Unable to get subscriber");
             let peer_ref = peer_connector
                 .add_peer_ref("test_peer".to_string(), vec!["inproc://test".to_string()])
                 .expect("Unable to add peer");
 
             assert_eq!(peer_ref.peer_id(), "test_peer");
 
-            // timeout after 60 seconds
-            let timeout = Duration::from_secs(60);
             let notification = notification_rx
                 .recv_timeout(timeout)
                 .expect("Unable to get new notifications");
@@ -2049,6 +2420,16 @@ pub mod tests {
 
         assert_eq!(peer_list, Vec::<String>::new());
 
+        let notification = notification_rx
+            .recv_timeout(timeout)
+            .expect("Unable to get new notifications");
+        assert_eq!(
+            notification,
+            PeerManagerNotification::Removed {
+                peer: "test_peer".to_string(),
+            }
+        );
+
         peer_manager.shutdown_signaler().shutdown();
         cm.shutdown_signaler().shutdown();
         peer_manager.await_shutdown();
@@ -2495,6 +2876,38 @@ pub mod tests {
         mesh.shutdown_signaler().shutdown();
     }
 
+    // Test that endpoints are ordered by transport preference
+    //
+    // 1. verify that endpoints keep their order if there is no preference
+    // 2. verify that endpoints are sorted by the position of their scheme in the preference, with
+    //    unlisted schemes last in their original order, and endpoints without a scheme as tcp
+    #[test]
+    fn test_order_endpoints() {
+        let endpoints = vec![
+            "tcp://10.0.0.1:8044".to_string(),
+            "inproc://local".to_string(),
+            "ws://10.0.0.1:8045".to_string(),
+            "10.0.0.2:8044".to_string(),
+            "tcps://10.0.0.1:8046".to_string(),
+        ];
+
+        assert_eq!(order_endpoints(endpoints.clone(), &[]), endpoints);
+
+        assert_eq!(
+            order_endpoints(
+                endpoints,
+                &["tcps".to_string(), "ws".to_string(), "tcp".to_string()]
+            ),
+            vec![
+                "tcps://10.0.0.1:8046".to_string(),
+                "ws://10.0.0.1:8045".to_string(),
+                "tcp://10.0.0.1:8044".to_string(),
+                "10.0.0.2:8044".to_string(),
+                "inproc://local".to_string(),
+            ]
+        );
+    }
+
     #[derive(PartialEq)]
     enum TestEnum {
         Notification(PeerManagerNotification),
@@ -29,6 +29,15 @@ pub enum PeerManagerNotification {
     /// Notifies subscribers that a peer is disconnected. Include the peer ID of the disconnected
     /// peer.
     Disconnected { peer: String },
+    /// Notifies subscribers that a peer's endpoints have changed. Includes the peer ID and the
+    /// peer's new endpoints.
+    EndpointsUpdated {
+        peer: String,
+        endpoints: Vec<String>,
+    },
+    /// Notifies subscribers that a peer has been removed because it is no longer referenced.
+    /// Includes the peer ID of the removed peer.
+    Removed { peer: String },
 }
 
 /// `PeerNotificationIter` is used to receive notfications from the `PeerManager`. The notifications
@@ -36,6 +45,8 @@ pub enum PeerManagerNotification {
 /// - `PeerManagerNotification::Disconnected`: peer disconnected and reconnection is being
 ///   attempted
 /// - `PeerManagerNotification::Connected`: connection to peer was successful
+/// - `PeerManagerNotification::EndpointsUpdated`: the peer's endpoints have changed
+/// - `PeerManagerNotification::Removed`: peer was removed and will not be reconnected
 pub struct PeerNotificationIter {
     pub(super) recv: Receiver<PeerManagerNotification>,
 }
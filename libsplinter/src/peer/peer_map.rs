@@ -15,9 +15,11 @@
 //! Data structure for keeping track of peer information
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 
 use crate::collections::BiHashMap;
+use crate::threading::clock::Clock;
 
 use super::error::PeerUpdateError;
 
@@ -43,6 +45,8 @@ pub struct PeerMetadata {
     pub endpoints: Vec<String>,
     /// The endpoint of the peer's current connection
     pub active_endpoint: String,
+    /// The endpoint of the peer's last successful connection, if it has ever connected
+    pub connected_endpoint: Option<String>,
     /// The peer's current status
     pub status: PeerStatus,
     /// The last time that a peer was attempted to be connected to
@@ -51,6 +55,27 @@ pub struct PeerMetadata {
     pub retry_frequency: u64,
 }
 
+impl PeerMetadata {
+    /// Returns the peer's endpoints in the order they should be attempted: the endpoint of the
+    /// last successful connection first, followed by the remaining endpoints in their preference
+    /// order.
+    pub fn endpoints_by_preference(&self) -> Vec<String> {
+        let mut endpoints = Vec::with_capacity(self.endpoints.len());
+        if let Some(connected_endpoint) = &self.connected_endpoint {
+            if self.endpoints.contains(connected_endpoint) {
+                endpoints.push(connected_endpoint.to_string());
+            }
+        }
+        endpoints.extend(
+            self.endpoints
+                .iter()
+                .filter(|endpoint| Some(*endpoint) != self.connected_endpoint.as_ref())
+                .cloned(),
+        );
+        endpoints
+    }
+}
+
 /// A map of peer IDs to peer metadata, which also maintains a redirect table for updated peer IDs.
 ///
 /// Peer metadata includes the peer ID, the list of endpoints, and the current active endpoint.
@@ -59,6 +84,7 @@ pub struct PeerMap {
     // Endpoint to peer id
     endpoints: HashMap<String, String>,
     initial_retry_frequency: u64,
+    clock: Arc<dyn Clock>,
 }
 
 impl PeerMap {
@@ -67,14 +93,21 @@ impl PeerMap {
     /// # Arguments
     ///
     /// * `initial_retry_frequency` - The value to set as the retry frequency for a new peer
-    pub fn new(initial_retry_frequency: u64) -> Self {
+    /// * `clock` - The clock used to record and measure connection attempts
+    pub fn new(initial_retry_frequency: u64, clock: Arc<dyn Clock>) -> Self {
         PeerMap {
             peers: HashMap::new(),
             endpoints: HashMap::new(),
             initial_retry_frequency,
+            clock,
         }
     }
 
+    /// Returns the clock used to record and measure connection attempts
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
     /// Returns the current list of peer IDs
     pub fn peer_ids(&self) -> Vec<String> {
         self.peers
@@ -110,13 +143,19 @@ impl PeerMap {
         active_endpoint: String,
         status: PeerStatus,
     ) {
+        let connected_endpoint = if status == PeerStatus::Connected {
+            Some(active_endpoint.to_string())
+        } else {
+            None
+        };
         let peer_metadata = PeerMetadata {
             id: peer_id.clone(),
             endpoints: endpoints.clone(),
             active_endpoint,
+            connected_endpoint,
             status,
             connection_id,
-            last_connection_attempt: Instant::now(),
+            last_connection_attempt: self.clock.now(),
             retry_frequency: self.initial_retry_frequency,
         };
 
@@ -171,6 +210,25 @@ impl PeerMap {
         }
     }
 
+    /// Updates an existing peer's metadata, including changes to its list of endpoints. Endpoints
+    /// that are no longer listed will no longer resolve to the peer.
+    pub fn update_peer_endpoints(
+        &mut self,
+        peer_metadata: PeerMetadata,
+    ) -> Result<(), PeerUpdateError> {
+        if let Some(existing) = self.peers.get(&peer_metadata.id) {
+            for endpoint in existing.endpoints.iter() {
+                if !peer_metadata.endpoints.contains(endpoint)
+                    && self.endpoints.get(endpoint) == Some(&peer_metadata.id)
+                {
+                    self.endpoints.remove(endpoint);
+                }
+            }
+        }
+
+        self.update_peer(peer_metadata)
+    }
+
     /// Returns the metadata for a peer from the provided endpoint
     pub fn get_peer_from_endpoint(&self, endpoint: &str) -> Option<&PeerMetadata> {
         if let Some(peer) = self.endpoints.get(endpoint) {
@@ -209,13 +267,15 @@ impl PeerMap {
 pub mod tests {
     use super::*;
 
+    use crate::threading::clock::SystemClock;
+
     // Test that peer_ids() are returned correctly
     //  1. Test that an empty peer_map returns an empty vec of peer IDs
     //  2. Add two peers and test that their id are returned from peer_ids()
     //  3. Update the first peer and test the updated peer id is returned in place of the old id.
     #[test]
     fn test_get_peer_ids() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
 
         let peers = peer_map.peer_ids();
         assert_eq!(peers, Vec::<String>::new());
@@ -249,7 +309,7 @@ pub mod tests {
     //  2. Add two peers and test that their ids are returned from connection_ids()
     #[test]
     fn test_get_connection_ids() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
 
         let peers = peer_map.peer_ids();
         assert_eq!(peers, Vec::<String>::new());
@@ -289,7 +349,7 @@ pub mod tests {
     //  4. Validate same metadata is returned from get_peer_from_endpoint("test_endpoint2")
     #[test]
     fn test_get_peer_by_endpoint() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
 
         let peer_metadata = peer_map.get_peer_from_endpoint("bad_endpoint");
         assert_eq!(peer_metadata, None);
@@ -326,7 +386,7 @@ pub mod tests {
     //  3. Check that the correct metadata is returned from self.peers.get()
     #[test]
     fn test_insert_peer() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
 
         peer_map.insert(
             "test_peer".to_string(),
@@ -356,7 +416,7 @@ pub mod tests {
     //  3. Verify that the correct peer_metadata is returned when removing test_peer
     #[test]
     fn test_remove_peer() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
 
         let peer_metdata = peer_map.remove("test_peer");
 
@@ -386,12 +446,13 @@ pub mod tests {
     //  4. Check that the peer's metadata now points to test_endpoint1 and the peer is disconnected
     #[test]
     fn test_get_update_active_endpoint() {
-        let mut peer_map = PeerMap::new(10);
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
         let no_peer_metadata = PeerMetadata {
             id: "test_peer".to_string(),
             connection_id: "connection_id".to_string(),
             endpoints: vec!["test_endpoint1".to_string(), "test_endpoint2".to_string()],
             active_endpoint: "test_endpoint1".to_string(),
+            connected_endpoint: Some("test_endpoint1".to_string()),
             status: PeerStatus::Connected,
             last_connection_attempt: Instant::now(),
             retry_frequency: 10,
@@ -443,4 +504,90 @@ pub mod tests {
             PeerStatus::Disconnected { retry_attempts: 5 }
         );
     }
+
+    // Test that replacing a peer's endpoints removes the stale endpoints from the endpoint index
+    //  1. Insert test_peer with endpoints test_endpoint1 and test_endpoint2
+    //  2. Replace the endpoints with test_endpoint2 and new_endpoint
+    //  3. Check that test_endpoint1 no longer resolves to the peer, and that the remaining
+    //     endpoints do
+    #[test]
+    fn test_update_peer_endpoints() {
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
+        peer_map.insert(
+            "test_peer".to_string(),
+            "connection_id".to_string(),
+            vec!["test_endpoint1".to_string(), "test_endpoint2".to_string()],
+            "test_endpoint1".to_string(),
+            PeerStatus::Connected,
+        );
+
+        let mut peer_metadata = peer_map
+            .get_by_peer_id("test_peer")
+            .cloned()
+            .expect("Missing peer_metadata");
+        peer_metadata.endpoints = vec!["test_endpoint2".to_string(), "new_endpoint".to_string()];
+        peer_metadata.active_endpoint = "test_endpoint2".to_string();
+
+        peer_map
+            .update_peer_endpoints(peer_metadata)
+            .expect("Unable to update endpoints");
+
+        assert!(peer_map.get_peer_from_endpoint("test_endpoint1").is_none());
+        assert_eq!(
+            peer_map
+                .get_peer_from_endpoint("test_endpoint2")
+                .map(|peer_metadata| peer_metadata.id.as_str()),
+            Some("test_peer")
+        );
+        assert_eq!(
+            peer_map
+                .get_peer_from_endpoint("new_endpoint")
+                .map(|peer_metadata| peer_metadata.active_endpoint.as_str()),
+            Some("test_endpoint2")
+        );
+    }
+
+    // Test that a peer's endpoints are ordered with the last connected endpoint first
+    //  1. Insert a pending peer and check that its endpoints are returned in their original order
+    //  2. Record a successful connection on the second endpoint and check that it is now first,
+    //     followed by the remaining endpoints in order
+    #[test]
+    fn test_endpoints_by_preference() {
+        let mut peer_map = PeerMap::new(10, Arc::new(SystemClock));
+        peer_map.insert(
+            "test_peer".to_string(),
+            "connection_id".to_string(),
+            vec![
+                "tcps://test_endpoint1".to_string(),
+                "ws://test_endpoint2".to_string(),
+                "tcp://test_endpoint3".to_string(),
+            ],
+            "tcps://test_endpoint1".to_string(),
+            PeerStatus::Pending,
+        );
+
+        let mut peer_metadata = peer_map
+            .get_by_peer_id("test_peer")
+            .cloned()
+            .expect("Missing peer_metadata");
+        assert_eq!(peer_metadata.connected_endpoint, None);
+        assert_eq!(
+            peer_metadata.endpoints_by_preference(),
+            vec![
+                "tcps://test_endpoint1".to_string(),
+                "ws://test_endpoint2".to_string(),
+                "tcp://test_endpoint3".to_string(),
+            ]
+        );
+
+        peer_metadata.connected_endpoint = Some("ws://test_endpoint2".to_string());
+        assert_eq!(
+            peer_metadata.endpoints_by_preference(),
+            vec![
+                "ws://test_endpoint2".to_string(),
+                "tcps://test_endpoint1".to_string(),
+                "tcp://test_endpoint3".to_string(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,566 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Store-and-forward for circuit messages sent to peers that are temporarily unreachable.
+//!
+//! When the `PeerInterconnect` cannot send a circuit direct message or an admin direct message
+//! because the peer is unknown or its connection has gone away, the message is written to an
+//! [`OutboundSpool`] instead of being dropped. The [`OutboundSpoolForwarder`] listens for
+//! `PeerManagerNotification::Connected` notifications and re-sends the spooled messages once the
+//! peer is reachable again.
+//!
+//! The spool is bounded per circuit, both by number of messages and by total payload size; when
+//! a bound is exceeded the oldest messages for that circuit are discarded. Messages that have been
+//! spooled for longer than the configured time-to-live are discarded instead of being delivered.
+//! If a spool file is provided, the spool is persisted so messages survive a restart.
+//!
+//! [`OutboundSpool`]: struct.OutboundSpool.html
+//! [`OutboundSpoolForwarder`]: struct.OutboundSpoolForwarder.html
+
+use std::collections::VecDeque;
+use std::fs::{rename, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::InternalError;
+use crate::hex::{as_hex, deserialize_hex};
+use crate::protos::circuit::{
+    AdminDirectMessage, CircuitDirectMessage, CircuitMessage, CircuitMessageType,
+};
+use crate::protos::network::{NetworkMessage, NetworkMessageType};
+
+use super::connector::PeerManagerConnector;
+use super::error::PeerManagerError;
+use super::interconnect::NetworkMessageSender;
+use super::notification::PeerManagerNotification;
+
+/// The default maximum number of messages spooled for a single circuit.
+pub const DEFAULT_MAX_MESSAGES_PER_CIRCUIT: usize = 1000;
+/// The default maximum number of payload bytes spooled for a single circuit.
+pub const DEFAULT_MAX_BYTES_PER_CIRCUIT: usize = 10 * 1024 * 1024;
+/// The default length of time a message is kept in the spool.
+pub const DEFAULT_SPOOL_TTL: Duration = Duration::from_secs(300);
+
+// How often the forwarder discards expired messages when no notifications are received
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SpooledMessage {
+    recipient: String,
+    circuit_id: String,
+    // seconds since the unix epoch
+    spooled_at: u64,
+    #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+    payload: Vec<u8>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SpoolFile {
+    messages: Vec<SpooledMessage>,
+}
+
+/// A bounded, optionally persistent spool of outbound circuit messages.
+///
+/// The spool is cheap to clone; all clones share the same messages.
+#[derive(Clone)]
+pub struct OutboundSpool {
+    messages: Arc<Mutex<VecDeque<SpooledMessage>>>,
+    max_messages_per_circuit: usize,
+    max_bytes_per_circuit: usize,
+    ttl: Duration,
+    spool_file: Option<PathBuf>,
+}
+
+impl OutboundSpool {
+    /// Adds a message for the given peer to the spool.
+    ///
+    /// Only circuit direct messages and admin direct messages are spooled. Returns `true` if the
+    /// message was spooled, or `false` if it is not a circuit message or is larger than the
+    /// per-circuit byte limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipient` - the peer ID the message is for
+    /// * `payload` - the bytes of the `NetworkMessage` that could not be sent
+    pub fn spool(&self, recipient: &str, payload: &[u8]) -> Result<bool, InternalError> {
+        let circuit_id = match circuit_id_of(payload) {
+            Some(circuit_id) => circuit_id,
+            None => return Ok(false),
+        };
+
+        if payload.len() > self.max_bytes_per_circuit {
+            return Ok(false);
+        }
+
+        let mut messages = self.lock_messages()?;
+        let now = now_secs();
+        self.remove_expired(&mut messages, now);
+
+        messages.push_back(SpooledMessage {
+            recipient: recipient.to_string(),
+            circuit_id: circuit_id.clone(),
+            spooled_at: now,
+            payload: payload.to_vec(),
+        });
+
+        // drop the circuit's oldest messages until it is back within its bounds
+        loop {
+            let (count, bytes) = messages
+                .iter()
+                .filter(|msg| msg.circuit_id == circuit_id)
+                .fold((0, 0), |(count, bytes), msg| {
+                    (count + 1, bytes + msg.payload.len())
+                });
+
+            if count <= self.max_messages_per_circuit && bytes <= self.max_bytes_per_circuit {
+                break;
+            }
+
+            if let Some(index) = messages.iter().position(|msg| msg.circuit_id == circuit_id) {
+                messages.remove(index);
+                warn!(
+                    "Outbound spool for circuit {} is full; dropping oldest message",
+                    circuit_id
+                );
+            }
+        }
+
+        self.write_spool(&messages)?;
+
+        Ok(true)
+    }
+
+    /// Removes and returns the unexpired messages spooled for the given peer, oldest first.
+    pub fn take_for(&self, recipient: &str) -> Result<Vec<Vec<u8>>, InternalError> {
+        let mut messages = self.lock_messages()?;
+        let removed = self.remove_expired(&mut messages, now_secs());
+
+        let (taken, remaining): (VecDeque<_>, VecDeque<_>) = messages
+            .drain(..)
+            .partition(|msg| msg.recipient == recipient);
+        *messages = remaining;
+
+        if removed > 0 || !taken.is_empty() {
+            self.write_spool(&messages)?;
+        }
+
+        Ok(taken.into_iter().map(|msg| msg.payload).collect())
+    }
+
+    /// Discards all messages that have outlived the spool's time-to-live.
+    ///
+    /// Returns the number of messages that were discarded.
+    pub fn purge_expired(&self) -> Result<usize, InternalError> {
+        let mut messages = self.lock_messages()?;
+        let removed = self.remove_expired(&mut messages, now_secs());
+        if removed > 0 {
+            self.write_spool(&messages)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Returns the number of messages currently in the spool.
+    pub fn len(&self) -> Result<usize, InternalError> {
+        Ok(self.lock_messages()?.len())
+    }
+
+    /// Returns `true` if the spool contains no messages.
+    pub fn is_empty(&self) -> Result<bool, InternalError> {
+        Ok(self.lock_messages()?.is_empty())
+    }
+
+    fn lock_messages(
+        &self,
+    ) -> Result<std::sync::MutexGuard<VecDeque<SpooledMessage>>, InternalError> {
+        self.messages
+            .lock()
+            .map_err(|_| InternalError::with_message("Outbound spool lock was poisoned".into()))
+    }
+
+    fn remove_expired(&self, messages: &mut VecDeque<SpooledMessage>, now: u64) -> usize {
+        let ttl = self.ttl.as_secs();
+        let before = messages.len();
+        messages.retain(|msg| now.saturating_sub(msg.spooled_at) < ttl);
+        let removed = before - messages.len();
+        if removed > 0 {
+            debug!(
+                "Discarded {} expired message(s) from outbound spool",
+                removed
+            );
+        }
+
+        removed
+    }
+
+    /// Write the spool to the spool file, if there is one. The spool is written to a temporary
+    /// file first to avoid corrupting the spool if an IO error occurs during the write.
+    fn write_spool(&self, messages: &VecDeque<SpooledMessage>) -> Result<(), InternalError> {
+        let spool_file = match &self.spool_file {
+            Some(spool_file) => spool_file,
+            None => return Ok(()),
+        };
+
+        let output = serde_yaml::to_vec(&SpoolFile {
+            messages: messages.iter().cloned().collect(),
+        })
+        .map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                "Failed to serialize outbound spool".into(),
+            )
+        })?;
+
+        let mut temp_file_path = spool_file.clone().into_os_string();
+        temp_file_path.push(".temp");
+        let temp_file_path = PathBuf::from(temp_file_path);
+
+        let mut file = File::create(&temp_file_path).map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!(
+                    "Failed to open outbound spool file '{}'",
+                    temp_file_path.display()
+                ),
+            )
+        })?;
+        file.write_all(&output).map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!(
+                    "Failed to write outbound spool file '{}'",
+                    temp_file_path.display()
+                ),
+            )
+        })?;
+
+        rename(&temp_file_path, spool_file).map_err(|err| {
+            InternalError::from_source_with_prefix(
+                Box::new(err),
+                format!(
+                    "Failed to replace outbound spool file '{}'",
+                    spool_file.display()
+                ),
+            )
+        })
+    }
+}
+
+/// A builder for creating an `OutboundSpool`
+#[derive(Default)]
+pub struct OutboundSpoolBuilder {
+    max_messages_per_circuit: Option<usize>,
+    max_bytes_per_circuit: Option<usize>,
+    ttl: Option<Duration>,
+    spool_file: Option<PathBuf>,
+}
+
+impl OutboundSpoolBuilder {
+    /// Creates an empty builder for an `OutboundSpool`
+    pub fn new() -> Self {
+        OutboundSpoolBuilder::default()
+    }
+
+    /// Sets the maximum number of messages spooled for a single circuit. Defaults to
+    /// `DEFAULT_MAX_MESSAGES_PER_CIRCUIT`.
+    pub fn with_max_messages_per_circuit(mut self, max_messages: usize) -> Self {
+        self.max_messages_per_circuit = Some(max_messages);
+        self
+    }
+
+    /// Sets the maximum number of payload bytes spooled for a single circuit. Defaults to
+    /// `DEFAULT_MAX_BYTES_PER_CIRCUIT`.
+    pub fn with_max_bytes_per_circuit(mut self, max_bytes: usize) -> Self {
+        self.max_bytes_per_circuit = Some(max_bytes);
+        self
+    }
+
+    /// Sets how long a message is kept in the spool before it is discarded. Defaults to
+    /// `DEFAULT_SPOOL_TTL`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the file the spool is persisted to. If the file exists, the messages it contains are
+    /// loaded when the spool is built. If no file is set, the spool is only kept in memory.
+    pub fn with_spool_file<P: Into<PathBuf>>(mut self, spool_file: P) -> Self {
+        self.spool_file = Some(spool_file.into());
+        self
+    }
+
+    /// Builds the `OutboundSpool`, loading any messages from the spool file.
+    pub fn build(self) -> Result<OutboundSpool, InternalError> {
+        let messages = match &self.spool_file {
+            Some(spool_file) if spool_file.exists() => {
+                let file = File::open(spool_file).map_err(|err| {
+                    InternalError::from_source_with_prefix(
+                        Box::new(err),
+                        format!(
+                            "Failed to open outbound spool file '{}'",
+                            spool_file.display()
+                        ),
+                    )
+                })?;
+                let spool: SpoolFile = serde_yaml::from_reader(file).map_err(|err| {
+                    InternalError::from_source_with_prefix(
+                        Box::new(err),
+                        format!(
+                            "Failed to read outbound spool file '{}'",
+                            spool_file.display()
+                        ),
+                    )
+                })?;
+                spool.messages.into_iter().collect()
+            }
+            _ => VecDeque::new(),
+        };
+
+        Ok(OutboundSpool {
+            messages: Arc::new(Mutex::new(messages)),
+            max_messages_per_circuit: self
+                .max_messages_per_circuit
+                .unwrap_or(DEFAULT_MAX_MESSAGES_PER_CIRCUIT),
+            max_bytes_per_circuit: self
+                .max_bytes_per_circuit
+                .unwrap_or(DEFAULT_MAX_BYTES_PER_CIRCUIT),
+            ttl: self.ttl.unwrap_or(DEFAULT_SPOOL_TTL),
+            spool_file: self.spool_file,
+        })
+    }
+}
+
+/// Delivers spooled messages to peers when they reconnect.
+///
+/// The forwarder runs in its own thread until the `PeerManager` it is subscribed to shuts down.
+pub struct OutboundSpoolForwarder {
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl OutboundSpoolForwarder {
+    /// Starts the forwarder.
+    ///
+    /// # Arguments
+    ///
+    /// * `spool` - the spool that failed messages are written to
+    /// * `peer_connector` - used to subscribe to peer connection notifications
+    /// * `network_sender` - used to re-send the spooled messages
+    pub fn start(
+        spool: OutboundSpool,
+        peer_connector: &PeerManagerConnector,
+        network_sender: NetworkMessageSender,
+    ) -> Result<Self, PeerManagerError> {
+        let (sender, receiver) = channel();
+        peer_connector.subscribe_sender::<PeerManagerNotification>(sender)?;
+
+        let join_handle = thread::Builder::new()
+            .name("OutboundSpoolForwarder".into())
+            .spawn(move || loop {
+                match receiver.recv_timeout(EXPIRY_CHECK_INTERVAL) {
+                    Ok(PeerManagerNotification::Connected { peer }) => {
+                        let messages = match spool.take_for(&peer) {
+                            Ok(messages) => messages,
+                            Err(err) => {
+                                error!("Unable to take spooled messages for {}: {}", peer, err);
+                                continue;
+                            }
+                        };
+                        if !messages.is_empty() {
+                            debug!(
+                                "Delivering {} spooled message(s) to {}",
+                                messages.len(),
+                                peer
+                            );
+                        }
+                        for payload in messages {
+                            if network_sender.send(peer.clone(), payload).is_err() {
+                                warn!("Peer interconnect is no longer running");
+                                return;
+                            }
+                        }
+                    }
+                    Ok(_) => (),
+                    Err(RecvTimeoutError::Timeout) => {
+                        if let Err(err) = spool.purge_expired() {
+                            error!("Unable to purge outbound spool: {}", err);
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        debug!("Peer manager has shutdown; stopping outbound spool forwarder");
+                        return;
+                    }
+                }
+            })
+            .map_err(|err| {
+                PeerManagerError::StartUpError(format!(
+                    "Unable to start outbound spool forwarder thread: {}",
+                    err
+                ))
+            })?;
+
+        Ok(OutboundSpoolForwarder { join_handle })
+    }
+
+    /// Waits for the forwarder thread to exit, which happens once the `PeerManager` has shut down
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Outbound spool forwarder thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Returns the circuit ID of the message, if the bytes are a `NetworkMessage` that wraps a
+/// circuit direct message or an admin direct message.
+fn circuit_id_of(payload: &[u8]) -> Option<String> {
+    let network_msg: NetworkMessage = protobuf::parse_from_bytes(payload).ok()?;
+    if network_msg.get_message_type() != NetworkMessageType::CIRCUIT {
+        return None;
+    }
+
+    let circuit_msg: CircuitMessage = protobuf::parse_from_bytes(network_msg.get_payload()).ok()?;
+    match circuit_msg.get_message_type() {
+        CircuitMessageType::CIRCUIT_DIRECT_MESSAGE => {
+            let msg: CircuitDirectMessage =
+                protobuf::parse_from_bytes(circuit_msg.get_payload()).ok()?;
+            Some(msg.get_circuit().to_string())
+        }
+        CircuitMessageType::ADMIN_DIRECT_MESSAGE => {
+            let msg: AdminDirectMessage =
+                protobuf::parse_from_bytes(circuit_msg.get_payload()).ok()?;
+            Some(msg.get_circuit().to_string())
+        }
+        _ => None,
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use protobuf::Message;
+    use tempdir::TempDir;
+
+    fn direct_message(circuit: &str, payload: &[u8]) -> Vec<u8> {
+        let mut direct_msg = CircuitDirectMessage::new();
+        direct_msg.set_circuit(circuit.into());
+        direct_msg.set_sender("abc".into());
+        direct_msg.set_recipient("def".into());
+        direct_msg.set_payload(payload.to_vec());
+
+        let mut circuit_msg = CircuitMessage::new();
+        circuit_msg.set_message_type(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE);
+        circuit_msg.set_payload(
+            direct_msg
+                .write_to_bytes()
+                .expect("unable to write message"),
+        );
+
+        let mut network_msg = NetworkMessage::new();
+        network_msg.set_message_type(NetworkMessageType::CIRCUIT);
+        network_msg.set_payload(
+            circuit_msg
+                .write_to_bytes()
+                .expect("unable to write message"),
+        );
+        network_msg
+            .write_to_bytes()
+            .expect("unable to write message")
+    }
+
+    // Verify that circuit messages are spooled and returned for their recipient in order, that
+    // other messages are not spooled, and that the per-circuit message limit drops the oldest
+    // message.
+    #[test]
+    fn test_spool_and_take() {
+        let spool = OutboundSpoolBuilder::new()
+            .with_max_messages_per_circuit(2)
+            .build()
+            .expect("Unable to build spool");
+
+        let mut echo = NetworkMessage::new();
+        echo.set_message_type(NetworkMessageType::NETWORK_ECHO);
+        let echo = echo.write_to_bytes().expect("unable to write message");
+        assert!(!spool.spool("node-b", &echo).expect("Unable to spool"));
+
+        let first = direct_message("alpha-bravo", b"1");
+        let second = direct_message("alpha-bravo", b"2");
+        let third = direct_message("alpha-bravo", b"3");
+        let other = direct_message("charlie-delta", b"4");
+
+        assert!(spool.spool("node-b", &first).expect("Unable to spool"));
+        assert!(spool.spool("node-b", &second).expect("Unable to spool"));
+        assert!(spool.spool("node-c", &other).expect("Unable to spool"));
+        assert!(spool.spool("node-b", &third).expect("Unable to spool"));
+        assert_eq!(3, spool.len().expect("Unable to get length"));
+
+        assert_eq!(
+            vec![second, third],
+            spool.take_for("node-b").expect("Unable to take messages")
+        );
+        assert_eq!(
+            vec![other],
+            spool.take_for("node-c").expect("Unable to take messages")
+        );
+        assert!(spool.is_empty().expect("Unable to check if empty"));
+    }
+
+    // Verify that a spool persisted to a file is reloaded, and that expired messages are not
+    // delivered.
+    #[test]
+    fn test_spool_file_and_expiry() {
+        let temp_dir = TempDir::new("test_spool_file_and_expiry").expect("Failed to create dir");
+        let spool_file = temp_dir.path().join("outbound_spool.yaml");
+
+        let message = direct_message("alpha-bravo", b"1");
+        let spool = OutboundSpoolBuilder::new()
+            .with_spool_file(spool_file.clone())
+            .build()
+            .expect("Unable to build spool");
+        assert!(spool.spool("node-b", &message).expect("Unable to spool"));
+
+        let reloaded = OutboundSpoolBuilder::new()
+            .with_spool_file(spool_file.clone())
+            .build()
+            .expect("Unable to build spool");
+        assert_eq!(
+            vec![message],
+            reloaded
+                .take_for("node-b")
+                .expect("Unable to take messages")
+        );
+
+        let expired = OutboundSpoolBuilder::new()
+            .with_ttl(Duration::from_secs(0))
+            .build()
+            .expect("Unable to build spool");
+        assert!(expired
+            .spool("node-b", &direct_message("alpha-bravo", b"2"))
+            .expect("Unable to spool"));
+        assert!(expired
+            .take_for("node-b")
+            .expect("Unable to take messages")
+            .is_empty());
+    }
+}
@@ -53,6 +53,19 @@ pub enum ConnectRequest {
 #[derive(Debug)]
 pub struct ConnectResponse {
     pub accepted_authorization_types: Vec<AuthorizationType>,
+    /// The capabilities of the sending node, if it advertised them
+    pub capabilities: Option<NodeCapabilities>,
+}
+
+/// The capabilities a node advertises to its peers during authorization.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeCapabilities {
+    /// The version of splinter the node is running
+    pub version: String,
+    /// The admin service protocol version the node supports
+    pub admin_service_protocol_version: u32,
+    /// The optional features enabled on the node
+    pub features: Vec<String>,
 }
 
 /// A trust request.
@@ -106,7 +119,9 @@ impl FromNative<ConnectRequest> for authorization::ConnectRequest {
 }
 
 impl FromProto<authorization::ConnectResponse> for ConnectResponse {
-    fn from_proto(source: authorization::ConnectResponse) -> Result<Self, ProtoConversionError> {
+    fn from_proto(
+        mut source: authorization::ConnectResponse,
+    ) -> Result<Self, ProtoConversionError> {
         use authorization::ConnectResponse_AuthorizationType::*;
         Ok(Self {
             accepted_authorization_types: source
@@ -119,6 +134,11 @@ impl FromProto<authorization::ConnectResponse> for ConnectResponse {
                     )),
                 })
                 .collect::<Result<Vec<AuthorizationType>, ProtoConversionError>>()?,
+            capabilities: if source.has_capabilities() {
+                Some(NodeCapabilities::from_proto(source.take_capabilities())?)
+            } else {
+                None
+            },
         })
     }
 }
@@ -139,10 +159,36 @@ impl FromNative<ConnectResponse> for authorization::ConnectResponse {
                 .collect(),
         );
 
+        if let Some(capabilities) = source.capabilities {
+            response.set_capabilities(authorization::NodeCapabilities::from_native(capabilities)?);
+        }
+
         Ok(response)
     }
 }
 
+impl FromProto<authorization::NodeCapabilities> for NodeCapabilities {
+    fn from_proto(
+        mut source: authorization::NodeCapabilities,
+    ) -> Result<Self, ProtoConversionError> {
+        Ok(Self {
+            version: source.take_version(),
+            admin_service_protocol_version: source.get_admin_service_protocol_version(),
+            features: source.take_features().into_vec(),
+        })
+    }
+}
+
+impl FromNative<NodeCapabilities> for authorization::NodeCapabilities {
+    fn from_native(source: NodeCapabilities) -> Result<Self, ProtoConversionError> {
+        let mut capabilities = authorization::NodeCapabilities::new();
+        capabilities.set_version(source.version);
+        capabilities.set_admin_service_protocol_version(source.admin_service_protocol_version);
+        capabilities.set_features(source.features.into());
+        Ok(capabilities)
+    }
+}
+
 impl FromProto<authorization::TrustRequest> for TrustRequest {
     fn from_proto(mut source: authorization::TrustRequest) -> Result<Self, ProtoConversionError> {
         Ok(Self {
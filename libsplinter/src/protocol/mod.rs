@@ -35,6 +35,18 @@ pub(crate) const ADMIN_LIST_PROPOSALS_PROTOCOL_MIN: u32 = 1;
 pub(crate) const ADMIN_LIST_CIRCUITS_MIN: u32 = 1;
 #[cfg(all(feature = "rest-api-actix", feature = "admin-service"))]
 pub(crate) const ADMIN_FETCH_CIRCUIT_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "admin-allowed-signers"))]
+pub(crate) const ADMIN_ALLOWED_SIGNERS_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "admin-service-debug"))]
+pub(crate) const ADMIN_DEBUG_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "admin-signed-submit"))]
+pub(crate) const ADMIN_SIGNED_SUBMIT_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "admin-vote-cosigning"))]
+pub(crate) const ADMIN_VOTE_COSIGNING_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "admin-vote-delegation"))]
+pub(crate) const ADMIN_VOTE_DELEGATION_MIN: u32 = 1;
+#[cfg(all(feature = "rest-api-actix", feature = "tenancy"))]
+pub(crate) const ADMIN_TENANTS_MIN: u32 = 1;
 
 // Admin Service protocol versions
 pub const ADMIN_SERVICE_PROTOCOL_VERSION: u32 = 2;
@@ -52,6 +64,12 @@ pub(crate) const OAUTH_LOGIN_MIN: u32 = 1;
 #[cfg(all(feature = "oauth", feature = "rest-api-actix"))]
 pub(crate) const OAUTH_LOGOUT_MIN: u32 = 1;
 
+#[cfg(feature = "protos-reflection")]
+pub const PROTOS_PROTOCOL_VERSION: u32 = 1;
+
+#[cfg(all(feature = "protos-reflection", feature = "rest-api-actix"))]
+pub(crate) const PROTOS_DESCRIPTORS_MIN: u32 = 1;
+
 #[cfg(feature = "registry")]
 pub const REGISTRY_PROTOCOL_VERSION: u32 = 1;
 
@@ -59,6 +77,20 @@ pub const REGISTRY_PROTOCOL_VERSION: u32 = 1;
 pub(crate) const REGISTRY_LIST_NODES_MIN: u32 = 1;
 #[cfg(all(feature = "registry", feature = "rest-api-actix"))]
 pub(crate) const REGISTRY_FETCH_NODE_MIN: u32 = 1;
+#[cfg(all(feature = "registry-source-status", feature = "rest-api-actix"))]
+pub(crate) const REGISTRY_LIST_SOURCES_MIN: u32 = 1;
+
+#[cfg(feature = "runtime-diagnostics")]
+pub const DIAGNOSTICS_PROTOCOL_VERSION: u32 = 1;
+
+#[cfg(all(feature = "runtime-diagnostics", feature = "rest-api-actix"))]
+pub(crate) const DIAGNOSTICS_RUNTIME_MIN: u32 = 1;
+
+#[cfg(feature = "scheduler")]
+pub const SCHEDULER_PROTOCOL_VERSION: u32 = 1;
+
+#[cfg(all(feature = "scheduler", feature = "rest-api-actix"))]
+pub(crate) const SCHEDULER_LIST_JOBS_MIN: u32 = 1;
 
 #[cfg(any(
     feature = "biome-credentials",
@@ -81,3 +113,134 @@ pub(crate) const BIOME_VERIFY_PROTOCOL_MIN: u32 = 1;
 
 #[cfg(all(feature = "biome-key-management", feature = "rest-api",))]
 pub(crate) const BIOME_KEYS_PROTOCOL_MIN: u32 = 1;
+
+#[cfg(all(feature = "biome-oauth-account-linking", feature = "rest-api"))]
+pub(crate) const BIOME_OAUTH_LINK_PROTOCOL_MIN: u32 = 1;
+
+#[cfg(all(feature = "biome-user-admin", feature = "rest-api"))]
+pub(crate) const BIOME_ADMIN_USERS_PROTOCOL_MIN: u32 = 1;
+
+/// The range of protocol versions accepted by one of the REST APIs provided by splinter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolVersionRange {
+    /// The name of the API
+    pub name: String,
+    /// The oldest protocol version still accepted by the API's endpoints
+    pub min: u32,
+    /// The current protocol version of the API
+    pub max: u32,
+}
+
+/// Returns the protocol version ranges of the REST APIs compiled into this library.
+#[cfg(feature = "rest-api-actix")]
+pub fn protocol_version_ranges() -> Vec<ProtocolVersionRange> {
+    #[allow(unused_mut)]
+    let mut ranges = vec![];
+
+    #[cfg(feature = "admin-service")]
+    {
+        ranges.push(version_range(
+            "admin",
+            &[
+                ADMIN_APPLICATION_REGISTRATION_PROTOCOL_MIN,
+                ADMIN_SUBMIT_PROTOCOL_MIN,
+                ADMIN_FETCH_PROPOSALS_PROTOCOL_MIN,
+                ADMIN_LIST_PROPOSALS_PROTOCOL_MIN,
+                ADMIN_LIST_CIRCUITS_MIN,
+                ADMIN_FETCH_CIRCUIT_MIN,
+            ],
+            ADMIN_PROTOCOL_VERSION,
+        ));
+        ranges.push(version_range(
+            "admin-service",
+            &[ADMIN_SERVICE_PROTOCOL_MIN],
+            ADMIN_SERVICE_PROTOCOL_VERSION,
+        ));
+    }
+
+    #[cfg(all(
+        any(feature = "biome-credentials", feature = "biome-key-management"),
+        feature = "rest-api"
+    ))]
+    {
+        #[allow(unused_mut)]
+        let mut mins = vec![];
+        #[cfg(feature = "biome-credentials")]
+        mins.extend_from_slice(&[
+            BIOME_REGISTER_PROTOCOL_MIN,
+            BIOME_LOGIN_PROTOCOL_MIN,
+            BIOME_USER_PROTOCOL_MIN,
+            BIOME_LIST_USERS_PROTOCOL_MIN,
+            BIOME_VERIFY_PROTOCOL_MIN,
+        ]);
+        #[cfg(feature = "biome-key-management")]
+        mins.push(BIOME_KEYS_PROTOCOL_MIN);
+        ranges.push(version_range("biome", &mins, BIOME_PROTOCOL_VERSION));
+    }
+
+    #[cfg(feature = "runtime-diagnostics")]
+    ranges.push(version_range(
+        "diagnostics",
+        &[DIAGNOSTICS_RUNTIME_MIN],
+        DIAGNOSTICS_PROTOCOL_VERSION,
+    ));
+
+    #[cfg(feature = "oauth")]
+    ranges.push(version_range(
+        "oauth",
+        &[OAUTH_CALLBACK_MIN, OAUTH_LOGIN_MIN, OAUTH_LOGOUT_MIN],
+        OAUTH_PROTOCOL_VERSION,
+    ));
+
+    #[cfg(feature = "protos-reflection")]
+    ranges.push(version_range(
+        "protos",
+        &[PROTOS_DESCRIPTORS_MIN],
+        PROTOS_PROTOCOL_VERSION,
+    ));
+
+    #[cfg(feature = "registry")]
+    ranges.push(version_range(
+        "registry",
+        &[REGISTRY_LIST_NODES_MIN, REGISTRY_FETCH_NODE_MIN],
+        REGISTRY_PROTOCOL_VERSION,
+    ));
+
+    #[cfg(feature = "scheduler")]
+    ranges.push(version_range(
+        "scheduler",
+        &[SCHEDULER_LIST_JOBS_MIN],
+        SCHEDULER_PROTOCOL_VERSION,
+    ));
+
+    ranges
+}
+
+#[cfg(feature = "rest-api-actix")]
+fn version_range(name: &str, mins: &[u32], max: u32) -> ProtocolVersionRange {
+    ProtocolVersionRange {
+        name: name.into(),
+        min: mins.iter().copied().min().unwrap_or(max),
+        max,
+    }
+}
+
+#[cfg(all(test, feature = "rest-api-actix", feature = "admin-service"))]
+mod tests {
+    use super::*;
+
+    /// Verify that the admin API range spans from the oldest accepted version to the current
+    /// protocol version.
+    #[test]
+    fn test_admin_protocol_version_range() {
+        let ranges = protocol_version_ranges();
+        let admin = ranges
+            .iter()
+            .find(|range| range.name == "admin")
+            .expect("admin range not reported");
+
+        assert_eq!(admin.min, 1);
+        assert_eq!(admin.max, ADMIN_PROTOCOL_VERSION);
+        assert!(ranges.iter().all(|range| range.min <= range.max));
+    }
+}
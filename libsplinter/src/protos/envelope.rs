@@ -0,0 +1,357 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zero-copy encoding and decoding of message envelopes.
+//!
+//! `NetworkMessage` and `CircuitMessage` are envelopes: each has a message type and a payload
+//! that is either a message or another envelope. Parsing an envelope with `protobuf` copies its
+//! payload, and wrapping a payload in nested envelopes copies it once per envelope when each
+//! envelope is serialized.
+//!
+//! [`EnvelopeRef`] reads an envelope's message type and locates its payload without copying, and
+//! [`write_envelope`] and [`circuit_envelope`] serialize a payload into its envelopes with a single
+//! allocation. The bytes produced are the same as the bytes produced by `protobuf`.
+//!
+//! [`EnvelopeRef`]: struct.EnvelopeRef.html
+//! [`write_envelope`]: fn.write_envelope.html
+//! [`circuit_envelope`]: fn.circuit_envelope.html
+
+use std::ops::Range;
+
+use protobuf::ProtobufEnum;
+
+use super::circuit::CircuitMessageType;
+use super::network::NetworkMessageType;
+use super::ProtoConversionError;
+
+// Field numbers shared by all envelope messages
+const MESSAGE_TYPE_FIELD: u32 = 1;
+const PAYLOAD_FIELD: u32 = 2;
+
+// Protobuf wire types
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_FIXED64: u32 = 1;
+const WIRE_TYPE_LENGTH_DELIMITED: u32 = 2;
+const WIRE_TYPE_FIXED32: u32 = 5;
+
+/// An envelope whose payload is borrowed from the bytes it was parsed from.
+#[derive(Debug, PartialEq)]
+pub struct EnvelopeRef<'a> {
+    message_type: i32,
+    payload: &'a [u8],
+    payload_range: Range<usize>,
+}
+
+impl<'a> EnvelopeRef<'a> {
+    /// Parses the envelope's message type and payload from the given bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ProtoConversionError::DeserializationError` if the bytes are not a valid
+    /// envelope.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ProtoConversionError> {
+        let mut message_type = 0;
+        let mut payload_range = 0..0;
+
+        let mut position = 0;
+        while position < bytes.len() {
+            let tag = read_varint(bytes, &mut position)?;
+            let field = (tag >> 3) as u32;
+            let wire_type = (tag & 0x7) as u32;
+
+            match (field, wire_type) {
+                (MESSAGE_TYPE_FIELD, WIRE_TYPE_VARINT) => {
+                    message_type = read_varint(bytes, &mut position)? as i32;
+                }
+                (PAYLOAD_FIELD, WIRE_TYPE_LENGTH_DELIMITED) => {
+                    payload_range = read_length_delimited(bytes, &mut position)?;
+                }
+                // Skip unknown fields
+                (_, wire_type) => skip_field(bytes, &mut position, wire_type)?,
+            }
+        }
+
+        Ok(EnvelopeRef {
+            message_type,
+            payload: &bytes[payload_range.clone()],
+            payload_range,
+        })
+    }
+
+    /// Returns the envelope's message type. Message types that are not known are returned as the
+    /// default message type.
+    pub fn message_type<E: ProtobufEnum + Default>(&self) -> E {
+        E::from_i32(self.message_type).unwrap_or_default()
+    }
+
+    /// Returns the envelope's payload.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns the position of the payload within the bytes the envelope was parsed from.
+    pub fn payload_range(&self) -> Range<usize> {
+        self.payload_range.clone()
+    }
+}
+
+/// Serializes an envelope with the given message type and payload.
+pub fn write_envelope<E: ProtobufEnum>(message_type: E, payload: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(envelope_len(message_type.value(), payload.len()));
+    write_envelope_header(message_type.value(), payload.len(), &mut buffer);
+    buffer.extend_from_slice(payload);
+    buffer
+}
+
+/// Serializes a `NetworkMessage` of type `CIRCUIT` that contains a `CircuitMessage` with the
+/// given message type and payload.
+pub fn circuit_envelope(circuit_message_type: CircuitMessageType, payload: &[u8]) -> Vec<u8> {
+    let circuit_len = envelope_len(circuit_message_type.value(), payload.len());
+    let network_type = NetworkMessageType::CIRCUIT.value();
+
+    let mut buffer = Vec::with_capacity(envelope_len(network_type, circuit_len));
+    write_envelope_header(network_type, circuit_len, &mut buffer);
+    write_envelope_header(circuit_message_type.value(), payload.len(), &mut buffer);
+    buffer.extend_from_slice(payload);
+    buffer
+}
+
+/// Reads a string field of a serialized message without parsing the rest of the message. This is
+/// used to read routing information, such as the circuit of a `CircuitDirectMessage`, from
+/// messages that are otherwise passed along as bytes.
+///
+/// Returns `None` if the field is not set.
+///
+/// # Errors
+///
+/// Returns a `ProtoConversionError::DeserializationError` if the bytes are not a valid message or
+/// the field is not valid UTF-8.
+pub fn read_string_field(
+    bytes: &[u8],
+    field_number: u32,
+) -> Result<Option<&str>, ProtoConversionError> {
+    let mut value = None;
+
+    let mut position = 0;
+    while position < bytes.len() {
+        let tag = read_varint(bytes, &mut position)?;
+        let field = (tag >> 3) as u32;
+        let wire_type = (tag & 0x7) as u32;
+
+        if field == field_number && wire_type == WIRE_TYPE_LENGTH_DELIMITED {
+            // The last value of a repeated scalar field wins, as it does when parsing
+            value = Some(read_length_delimited(bytes, &mut position)?);
+        } else {
+            skip_field(bytes, &mut position, wire_type)?;
+        }
+    }
+
+    value
+        .map(|range| {
+            std::str::from_utf8(&bytes[range]).map_err(|err| {
+                ProtoConversionError::DeserializationError(format!(
+                    "field {} is not valid UTF-8: {}",
+                    field_number, err
+                ))
+            })
+        })
+        .transpose()
+}
+
+// Fields that have their default value are not serialized, matching proto3 serialization
+fn envelope_len(message_type: i32, payload_len: usize) -> usize {
+    let mut len = 0;
+    if message_type != 0 {
+        len += 1 + varint_len(message_type as i64 as u64);
+    }
+    if payload_len != 0 {
+        len += 1 + varint_len(payload_len as u64) + payload_len;
+    }
+    len
+}
+
+fn write_envelope_header(message_type: i32, payload_len: usize, buffer: &mut Vec<u8>) {
+    if message_type != 0 {
+        write_varint(
+            u64::from(MESSAGE_TYPE_FIELD << 3 | WIRE_TYPE_VARINT),
+            buffer,
+        );
+        // Negative enum values are sign-extended to 64 bits
+        write_varint(message_type as i64 as u64, buffer);
+    }
+    if payload_len != 0 {
+        write_varint(
+            u64::from(PAYLOAD_FIELD << 3 | WIRE_TYPE_LENGTH_DELIMITED),
+            buffer,
+        );
+        write_varint(payload_len as u64, buffer);
+    }
+}
+
+fn varint_len(mut value: u64) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+fn write_varint(mut value: u64, buffer: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u64, ProtoConversionError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*position).ok_or_else(|| {
+            ProtoConversionError::DeserializationError("truncated varint in envelope".into())
+        })?;
+        *position += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(ProtoConversionError::DeserializationError(
+        "varint in envelope is too long".into(),
+    ))
+}
+
+fn read_length_delimited(
+    bytes: &[u8],
+    position: &mut usize,
+) -> Result<Range<usize>, ProtoConversionError> {
+    let len = read_varint(bytes, position)? as usize;
+    let start = *position;
+    skip(bytes, position, len)?;
+    Ok(start..*position)
+}
+
+fn skip_field(
+    bytes: &[u8],
+    position: &mut usize,
+    wire_type: u32,
+) -> Result<(), ProtoConversionError> {
+    match wire_type {
+        WIRE_TYPE_VARINT => read_varint(bytes, position).map(|_| ()),
+        WIRE_TYPE_FIXED64 => skip(bytes, position, 8),
+        WIRE_TYPE_LENGTH_DELIMITED => read_length_delimited(bytes, position).map(|_| ()),
+        WIRE_TYPE_FIXED32 => skip(bytes, position, 4),
+        _ => Err(ProtoConversionError::DeserializationError(format!(
+            "unsupported wire type {} in envelope",
+            wire_type
+        ))),
+    }
+}
+
+fn skip(bytes: &[u8], position: &mut usize, len: usize) -> Result<(), ProtoConversionError> {
+    match position.checked_add(len) {
+        Some(end) if end <= bytes.len() => {
+            *position = end;
+            Ok(())
+        }
+        _ => Err(ProtoConversionError::DeserializationError(
+            "truncated field in envelope".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use protobuf::Message;
+
+    use crate::protos::circuit::{CircuitDirectMessage, CircuitMessage};
+    use crate::protos::network::NetworkMessage;
+
+    /// Test that envelopes written with `write_envelope` and `circuit_envelope` are the same
+    /// bytes as those written by protobuf, including for empty payloads and default message types.
+    #[test]
+    fn test_write_matches_protobuf() {
+        let payloads: Vec<Vec<u8>> = vec![vec![], b"hello".to_vec(), vec![7; 300]];
+        for payload in payloads {
+            for message_type in &[
+                CircuitMessageType::UNSET_CIRCUIT_MESSAGE_TYPE,
+                CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            ] {
+                let mut circuit_msg = CircuitMessage::new();
+                circuit_msg.set_message_type(*message_type);
+                circuit_msg.set_payload(payload.clone());
+                let circuit_bytes = circuit_msg.write_to_bytes().expect("Unable to serialize");
+
+                assert_eq!(circuit_bytes, write_envelope(*message_type, &payload));
+
+                let mut network_msg = NetworkMessage::new();
+                network_msg.set_message_type(NetworkMessageType::CIRCUIT);
+                network_msg.set_payload(circuit_bytes);
+                let network_bytes = network_msg.write_to_bytes().expect("Unable to serialize");
+
+                assert_eq!(network_bytes, circuit_envelope(*message_type, &payload));
+            }
+        }
+    }
+
+    /// Test that an envelope serialized by protobuf is parsed without copying its payload, and
+    /// that unknown fields are skipped.
+    #[test]
+    fn test_parse() {
+        let mut network_msg = NetworkMessage::new();
+        network_msg.set_message_type(NetworkMessageType::NETWORK_ECHO);
+        network_msg.set_payload(b"hello".to_vec());
+        let mut bytes = network_msg.write_to_bytes().expect("Unable to serialize");
+        // Field 3, a fixed32
+        bytes.extend_from_slice(&[3 << 3 | 5, 1, 2, 3, 4]);
+
+        let envelope = EnvelopeRef::parse(&bytes).expect("Unable to parse envelope");
+        assert_eq!(
+            NetworkMessageType::NETWORK_ECHO,
+            envelope.message_type::<NetworkMessageType>()
+        );
+        assert_eq!(b"hello", envelope.payload());
+        assert_eq!(&bytes[envelope.payload_range()], b"hello");
+        assert_eq!(4..9, envelope.payload_range());
+
+        let envelope = EnvelopeRef::parse(&[]).expect("Unable to parse empty envelope");
+        assert_eq!(
+            NetworkMessageType::UNSET_NETWORK_MESSAGE_TYPE,
+            envelope.message_type::<NetworkMessageType>()
+        );
+        assert!(envelope.payload().is_empty());
+
+        assert!(EnvelopeRef::parse(&bytes[..bytes.len() - 6]).is_err());
+    }
+
+    /// Test that a string field is read from a serialized message, and that a field that is not
+    /// set is returned as `None`.
+    #[test]
+    fn test_read_string_field() {
+        let mut direct_msg = CircuitDirectMessage::new();
+        direct_msg.set_circuit("alpha".into());
+        direct_msg.set_sender("abc".into());
+        direct_msg.set_payload(vec![0xff; 20]);
+        let bytes = direct_msg.write_to_bytes().expect("Unable to serialize");
+
+        assert_eq!(Some("alpha"), read_string_field(&bytes, 1).unwrap());
+        assert_eq!(Some("abc"), read_string_field(&bytes, 2).unwrap());
+        assert_eq!(None, read_string_field(&bytes, 3).unwrap());
+        assert!(read_string_field(&bytes, 4).is_err());
+    }
+}
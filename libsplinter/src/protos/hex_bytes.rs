@@ -0,0 +1,60 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serde functions for the `bytes` fields of the generated messages, which are represented as hex
+//! strings in the canonical JSON form. The build script adds them to each `bytes` field.
+
+use serde::{Deserializer, Serializer};
+
+use crate::hex::{as_hex, deserialize_hex};
+
+pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    as_hex(bytes, serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_hex(deserializer)
+}
+
+/// Serde functions for `repeated bytes` fields, which are represented as arrays of hex strings.
+pub mod repeated {
+    use protobuf::RepeatedField;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::hex::{parse_hex, to_hex};
+
+    pub fn serialize<S>(values: &RepeatedField<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(|bytes| to_hex(bytes)))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<RepeatedField<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| parse_hex(hex).map_err(de::Error::custom))
+            .collect::<Result<Vec<_>, _>>()
+            .map(RepeatedField::from_vec)
+    }
+}
@@ -13,6 +13,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Protocol buffer messages and the traits for converting between them and native types.
+//!
+//! # Canonical JSON
+//!
+//! With the `protos-serde` feature, every message and enum implements serde's `Serialize` and
+//! `Deserialize`. The JSON produced with `serde_json` is the canonical JSON form of the messages,
+//! and should be used by new consumers that represent a message as JSON, instead of a custom
+//! mapping. The existing REST API resources and CLI output keep their own versioned models.
+//!
+//! * A message is an object with a member for every field, named as in the `.proto` file
+//!   (`snake_case`). Fields with default values are included.
+//! * An enum value is a string with the name of the value, as in the `.proto` file (for example,
+//!   `"CIRCUIT_DIRECT_MESSAGE"`).
+//! * A `bytes` field is a string of lowercase hex digits, two per byte, and a `repeated bytes`
+//!   field is an array of such strings.
+//! * A repeated field is an array, and an unset message field is `null`.
+//! * A `oneof` is a member named after the `oneof`, holding `null` or an object with a single
+//!   member named after the field that is set.
+//!
+//! Deserialization requires every member to be present, so the canonical form of a message can be
+//! read back into an equal message.
+
+pub mod envelope;
+#[cfg(feature = "protos-serde")]
+mod hex_bytes;
+#[cfg(feature = "protos-reflection")]
+mod rest_api;
+
+#[cfg(feature = "protos-reflection")]
+pub use self::rest_api::ProtoDescriptorResourceProvider;
+
+/// The version of the protocol buffer message definitions in this module. It is incremented
+/// whenever a message, field, or enum value is added, removed, or changed, so that tooling can
+/// tell which definitions a node was built with.
+pub const PROTOS_VERSION: u32 = 1;
+
 #[derive(Debug)]
 pub enum ProtoConversionError {
     DeserializationError(String),
@@ -124,3 +160,76 @@ pub mod prelude {
 }
 
 include!(concat!(env!("OUT_DIR"), "/protos/mod.rs"));
+
+/// Returns the compiled descriptors of every protocol buffer file in this module.
+///
+/// The descriptors can be used to decode captured messages or to generate clients for the
+/// message definitions this library was built with.
+pub fn file_descriptor_set() -> protobuf::descriptor::FileDescriptorSet {
+    let files = vec![
+        admin::file_descriptor_proto().clone(),
+        authorization::file_descriptor_proto().clone(),
+        circuit::file_descriptor_proto().clone(),
+        component::file_descriptor_proto().clone(),
+        consensus::file_descriptor_proto().clone(),
+        network::file_descriptor_proto().clone(),
+        service::file_descriptor_proto().clone(),
+        two_phase::file_descriptor_proto().clone(),
+    ];
+
+    let mut descriptor_set = protobuf::descriptor::FileDescriptorSet::new();
+    descriptor_set.set_file(files.into());
+    descriptor_set
+}
+
+#[cfg(all(test, feature = "protos-serde"))]
+mod tests {
+    use super::admin::{
+        Circuit, CircuitManagementPayload, Circuit_AuthorizationType, SplinterNode,
+    };
+    use super::network::{NetworkMessage, NetworkMessageType};
+    use super::two_phase::RequiredVerifiers;
+
+    /// Test that messages serialize to the canonical JSON form and back, including enum values,
+    /// bytes fields, nested messages, and repeated fields.
+    #[test]
+    fn canonical_json_round_trip() {
+        let mut network_msg = NetworkMessage::new();
+        network_msg.set_message_type(NetworkMessageType::NETWORK_ECHO);
+        network_msg.set_payload(vec![1, 2, 3]);
+
+        let json = serde_json::to_value(&network_msg).expect("Unable to serialize");
+        assert_eq!(
+            serde_json::json!({"message_type": "NETWORK_ECHO", "payload": "010203"}),
+            json
+        );
+        let deserialized: NetworkMessage =
+            serde_json::from_value(json).expect("Unable to deserialize");
+        assert_eq!(network_msg, deserialized);
+
+        let mut circuit = Circuit::new();
+        circuit.set_circuit_id("01234-abcde".into());
+        circuit.set_authorization_type(Circuit_AuthorizationType::TRUST_AUTHORIZATION);
+        let mut node = SplinterNode::new();
+        node.set_node_id("node-a".into());
+        circuit.set_members(vec![node].into());
+        let mut payload = CircuitManagementPayload::new();
+        payload.set_header(vec![1, 2, 3]);
+        payload.set_signature(vec![4, 5, 6]);
+        payload.mut_circuit_create_request().set_circuit(circuit);
+
+        let json = serde_json::to_string(&payload).expect("Unable to serialize");
+        let deserialized: CircuitManagementPayload =
+            serde_json::from_str(&json).expect("Unable to deserialize");
+        assert_eq!(payload, deserialized);
+
+        let mut verifiers = RequiredVerifiers::new();
+        verifiers.set_verifiers(vec![vec![1, 2], vec![255]].into());
+
+        let json = serde_json::to_value(&verifiers).expect("Unable to serialize");
+        assert_eq!(serde_json::json!({"verifiers": ["0102", "ff"]}), json);
+        let deserialized: RequiredVerifiers =
+            serde_json::from_value(json).expect("Unable to deserialize");
+        assert_eq!(verifiers, deserialized);
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::HttpResponse;
+use futures::future::IntoFuture;
+
+use crate::protocol;
+use crate::protos::file_descriptor_set;
+use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+
+use super::resources::ProtosResponse;
+
+pub fn make_protos_resource() -> Resource {
+    Resource::build("/protos")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::PROTOS_DESCRIPTORS_MIN,
+            protocol::PROTOS_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            let descriptor_set = file_descriptor_set();
+            match ProtosResponse::try_from_descriptor_set(&descriptor_set) {
+                Ok(response) => Box::new(HttpResponse::Ok().json(response).into_future()),
+                Err(err) => {
+                    error!("Unable to encode protocol buffer descriptors: {}", err);
+                    Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    )
+                }
+            }
+        })
+}
@@ -0,0 +1,105 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use protobuf::descriptor::FileDescriptorSet;
+use protobuf::{Message, ProtobufError};
+
+use crate::hex::to_hex;
+use crate::protos::PROTOS_VERSION;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtosResponse<'a> {
+    /// The version of the message definitions, incremented whenever they change
+    pub version: u32,
+    pub files: Vec<ProtoFileResponse<'a>>,
+    /// The hex-encoded `FileDescriptorSet` of all the files
+    pub descriptor_set: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtoFileResponse<'a> {
+    pub name: &'a str,
+    pub messages: Vec<&'a str>,
+    pub enums: Vec<&'a str>,
+}
+
+impl<'a> ProtosResponse<'a> {
+    pub fn try_from_descriptor_set(
+        descriptor_set: &'a FileDescriptorSet,
+    ) -> Result<Self, ProtobufError> {
+        Ok(Self {
+            version: PROTOS_VERSION,
+            files: descriptor_set
+                .get_file()
+                .iter()
+                .map(|file| ProtoFileResponse {
+                    name: file.get_name(),
+                    messages: file
+                        .get_message_type()
+                        .iter()
+                        .map(|message| message.get_name())
+                        .collect(),
+                    enums: file
+                        .get_enum_type()
+                        .iter()
+                        .map(|enum_type| enum_type.get_name())
+                        .collect(),
+                })
+                .collect(),
+            descriptor_set: to_hex(&descriptor_set.write_to_bytes()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::hex::parse_hex;
+    use crate::protos::file_descriptor_set;
+
+    /// Verify that the response lists the admin, circuit, and network message files, and that the
+    /// encoded descriptor set decodes back to the descriptors the node was built with.
+    #[test]
+    fn protos_response_round_trip() {
+        let descriptor_set = file_descriptor_set();
+        let response = ProtosResponse::try_from_descriptor_set(&descriptor_set)
+            .expect("Unable to build response");
+
+        assert_eq!(response.version, PROTOS_VERSION);
+
+        let admin = response
+            .files
+            .iter()
+            .find(|file| file.name == "admin.proto")
+            .expect("admin.proto is missing");
+        assert!(admin.messages.contains(&"CircuitManagementPayload"));
+        assert!(response
+            .files
+            .iter()
+            .any(|file| file.name == "circuit.proto"));
+        let network = response
+            .files
+            .iter()
+            .find(|file| file.name == "network.proto")
+            .expect("network.proto is missing");
+        assert!(network.enums.contains(&"NetworkMessageType"));
+
+        let decoded: FileDescriptorSet = protobuf::parse_from_bytes(
+            &parse_hex(&response.descriptor_set).expect("Descriptor set is not valid hex"),
+        )
+        .expect("Unable to decode descriptor set");
+        assert_eq!(decoded, descriptor_set);
+    }
+}
@@ -30,16 +30,24 @@ mod error;
 #[cfg(feature = "rest-api")]
 mod rest_api;
 mod unified;
+#[cfg(feature = "registry-watcher")]
+pub mod watcher;
 mod yaml;
 
 use std::collections::HashMap;
 use std::iter::ExactSizeIterator;
+#[cfg(feature = "registry-source-status")]
+use std::time::SystemTime;
 
 #[cfg(feature = "registry-database")]
 pub use self::diesel::DieselRegistry;
 pub use error::{InvalidNodeError, RegistryError};
+#[cfg(all(feature = "registry-source-status", feature = "rest-api"))]
+pub use rest_api::RegistrySourcesResourceProvider;
 pub use unified::UnifiedRegistry;
 pub use yaml::LocalYamlRegistry;
+#[cfg(feature = "registry-remote-auth")]
+pub use yaml::RemoteYamlRegistryAuth;
 #[cfg(feature = "registry-remote")]
 pub use yaml::{RemoteYamlRegistry, RemoteYamlShutdownHandle};
 
@@ -211,6 +219,26 @@ impl MetadataPredicate {
     }
 }
 
+/// The health of a single source backing a registry.
+///
+/// Registries that read from an external location (such as a local or remote YAML file) report
+/// when they last loaded their contents successfully and the most recent error encountered while
+/// doing so.
+#[cfg(feature = "registry-source-status")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistrySourceStatus {
+    /// The location of the source, such as a file path or URL.
+    pub location: String,
+    /// Whether or not the source is read-only.
+    pub read_only: bool,
+    /// The last time the source's contents were successfully loaded, if ever.
+    pub last_refresh: Option<SystemTime>,
+    /// The error from the most recent failed load, if the last attempt failed.
+    pub last_error: Option<String>,
+    /// The number of nodes currently provided by the source, if known.
+    pub node_count: Option<u32>,
+}
+
 /// Type returned by the `RegistryReader::list_nodes` method
 pub type NodeIter<'a> = Box<dyn ExactSizeIterator<Item = Node> + Send + 'a>;
 
@@ -252,6 +280,15 @@ pub trait RegistryReader: Send + Sync {
     fn has_node(&self, identity: &str) -> Result<bool, RegistryError> {
         self.fetch_node(identity).map(|opt| opt.is_some())
     }
+
+    /// Returns the status of the source backing this registry, if the registry tracks one.
+    ///
+    /// Implementations must not contact the underlying source to determine the status; only the
+    /// outcome of previous loads is reported.
+    #[cfg(feature = "registry-source-status")]
+    fn source_status(&self) -> Option<RegistrySourceStatus> {
+        None
+    }
 }
 
 /// Defines registry write capabilities.
@@ -320,6 +357,11 @@ where
     fn has_node(&self, identity: &str) -> Result<bool, RegistryError> {
         (**self).has_node(identity)
     }
+
+    #[cfg(feature = "registry-source-status")]
+    fn source_status(&self) -> Option<RegistrySourceStatus> {
+        (**self).source_status()
+    }
 }
 
 impl<NW> RegistryWriter for Box<NW>
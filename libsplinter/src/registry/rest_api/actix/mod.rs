@@ -14,3 +14,41 @@
 
 pub(super) mod nodes;
 pub(super) mod nodes_identity;
+#[cfg(feature = "registry-source-status")]
+pub(super) mod sources;
+
+use std::fmt;
+
+#[cfg(feature = "tenancy")]
+use crate::error::InternalError;
+use crate::registry::RegistryError;
+
+#[cfg(feature = "tenancy")]
+fn tenant_error(err: InternalError) -> RegistryError {
+    RegistryError::general_error_with_source("Unable to check the tenant of a node", Box::new(err))
+}
+
+/// Errors that prevent a node from being added, replaced or deleted
+#[derive(Debug)]
+enum NodeRequestError {
+    Registry(RegistryError),
+    /// The client's tenant may not manage the node
+    #[cfg(feature = "tenancy")]
+    Forbidden,
+}
+
+impl fmt::Display for NodeRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeRequestError::Registry(err) => err.fmt(f),
+            #[cfg(feature = "tenancy")]
+            NodeRequestError::Forbidden => f.write_str("not permitted to manage node"),
+        }
+    }
+}
+
+impl From<RegistryError> for NodeRequestError {
+    fn from(err: RegistryError) -> Self {
+        NodeRequestError::Registry(err)
+    }
+}
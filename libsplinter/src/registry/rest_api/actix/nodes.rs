@@ -16,6 +16,10 @@
 //!
 //! * `GET /registry/nodes` for listing nodes in the registry
 //! * `POST /registry/nodes` for adding a node to the registry
+//!
+//! When tenancy is enabled, clients only see the nodes of their own tenant and the nodes that are
+//! not assigned to a tenant, and the nodes added by a tenant's clients are assigned to the tenant.
+//! Clients that are neither operators nor assigned to a tenant may not add nodes.
 
 use std::collections::HashMap;
 
@@ -31,6 +35,12 @@ use crate::rest_api::{
     paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET},
     percent_encode_filter_query, ErrorResponse, Method, ProtocolVersionRangeGuard, Resource,
 };
+#[cfg(feature = "tenancy")]
+use crate::tenant::{TenantResourceType, TenantScope};
+
+#[cfg(feature = "tenancy")]
+use super::tenant_error;
+use super::NodeRequestError;
 
 type Filter = HashMap<String, (String, String)>;
 
@@ -44,8 +54,8 @@ pub fn make_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
         .add_method(Method::Get, move |r, _| {
             list_nodes(r, web::Data::new(registry.clone_box_as_reader()))
         })
-        .add_method(Method::Post, move |_, p| {
-            add_node(p, web::Data::new(registry1.clone()))
+        .add_method(Method::Post, move |r, p| {
+            add_node(r, p, web::Data::new(registry1.clone()))
         })
 }
 
@@ -134,12 +144,17 @@ fn list_nodes(
         }
     };
 
+    #[cfg(feature = "tenancy")]
+    let tenant_scope = req.extensions().get::<TenantScope>().cloned();
+
     Box::new(query_list_nodes(
         registry,
         link,
         predicates,
         Some(offset),
         Some(limit),
+        #[cfg(feature = "tenancy")]
+        tenant_scope,
     ))
 }
 
@@ -149,9 +164,30 @@ fn query_list_nodes(
     filters: Vec<MetadataPredicate>,
     offset: Option<usize>,
     limit: Option<usize>,
+    #[cfg(feature = "tenancy")] tenant_scope: Option<TenantScope>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     web::block(move || {
         let nodes = registry.list_nodes(&filters)?;
+
+        // Only list the nodes the client's tenant may see, so that the paging reflects the nodes
+        // the client may see
+        #[cfg(feature = "tenancy")]
+        let nodes = {
+            let mut permitted = vec![];
+            for node in nodes {
+                let is_permitted = match &tenant_scope {
+                    Some(scope) => scope
+                        .permits(TenantResourceType::Node, &node.identity)
+                        .map_err(tenant_error)?,
+                    None => true,
+                };
+                if is_permitted {
+                    permitted.push(node);
+                }
+            }
+            permitted.into_iter()
+        };
+
         let offset_value = offset.unwrap_or(0);
         let total = nodes.len();
         let limit_value = limit.unwrap_or_else(|| total as usize);
@@ -196,9 +232,12 @@ fn to_predicates(filters: Option<Filter>) -> Result<Vec<MetadataPredicate>, Stri
 }
 
 fn add_node(
+    #[allow(unused_variables)] req: HttpRequest,
     payload: web::Payload,
     registry: web::Data<Box<dyn RwRegistry>>,
 ) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    #[cfg(feature = "tenancy")]
+    let tenant_scope = req.extensions().get::<TenantScope>().cloned();
     Box::new(
         payload
             .from_err::<Error>()
@@ -211,20 +250,45 @@ fn add_node(
                 Ok(node) => Box::new(
                     web::block(move || {
                         if registry.has_node(&node.identity)? {
-                            Err(RegistryError::InvalidNode(
+                            return Err(NodeRequestError::from(RegistryError::InvalidNode(
                                 InvalidNodeError::DuplicateIdentity(node.identity),
-                            ))
-                        } else {
-                            registry.insert_node(node)
+                            )));
+                        }
+
+                        // New nodes are assigned to the tenant of the client that adds them
+                        #[cfg(feature = "tenancy")]
+                        {
+                            if let Some(scope) = tenant_scope {
+                                if !scope
+                                    .may_claim(TenantResourceType::Node, &node.identity)
+                                    .map_err(tenant_error)?
+                                {
+                                    return Err(NodeRequestError::Forbidden);
+                                }
+
+                                let identity = node.identity.clone();
+                                registry.insert_node(node)?;
+                                scope
+                                    .claim(TenantResourceType::Node, &identity)
+                                    .map_err(tenant_error)?;
+                                return Ok(());
+                            }
                         }
+
+                        Ok(registry.insert_node(node)?)
                     })
                     .then(|res| {
                         Ok(match res {
                             Ok(_) => HttpResponse::Ok().finish(),
-                            Err(BlockingError::Error(RegistryError::InvalidNode(err))) => {
-                                HttpResponse::BadRequest().json(ErrorResponse::bad_request(
-                                    &format!("Invalid node: {}", err),
-                                ))
+                            Err(BlockingError::Error(NodeRequestError::Registry(
+                                RegistryError::InvalidNode(err),
+                            ))) => HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                                &format!("Invalid node: {}", err),
+                            )),
+                            #[cfg(feature = "tenancy")]
+                            Err(BlockingError::Error(NodeRequestError::Forbidden)) => {
+                                HttpResponse::Forbidden()
+                                    .json(ErrorResponse::forbidden("Not permitted to add node"))
                             }
                             Err(err) => {
                                 error!("Unable to add node: {}", err);
@@ -17,6 +17,9 @@
 //! * `GET /registry/nodes/{identity}` for fetching a node in the registry
 //! * `PUT /registry/nodes/{identity}` for replacing a node in the registry
 //! * `DELETE /registry/nodes/{identity}` for deleting a node from the registry
+//!
+//! When tenancy is enabled, the nodes of other tenants are reported as not found, and clients may
+//! only replace or delete the nodes of their own tenant; operators may manage every node.
 
 use crate::actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
 use crate::futures::{future::IntoFuture, stream::Stream, Future};
@@ -26,6 +29,12 @@ use crate::registry::{
     RegistryReader, RegistryWriter, RwRegistry,
 };
 use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+#[cfg(feature = "tenancy")]
+use crate::tenant::{TenantResourceType, TenantScope};
+
+#[cfg(feature = "tenancy")]
+use super::tenant_error;
+use super::NodeRequestError;
 
 pub fn make_nodes_identity_resource(registry: Box<dyn RwRegistry>) -> Resource {
     let registry1 = registry.clone();
@@ -39,7 +48,7 @@ pub fn make_nodes_identity_resource(registry: Box<dyn RwRegistry>) -> Resource {
             fetch_node(r, web::Data::new(registry.clone_box_as_reader()))
         })
         .add_method(Method::Put, move |r, p| {
-            put_node(r, p, web::Data::new(registry1.clone_box_as_writer()))
+            put_node(r, p, web::Data::new(registry1.clone()))
         })
         .add_method(Method::Delete, move |r, _| {
             delete_node(r, web::Data::new(registry2.clone_box_as_writer()))
@@ -55,8 +64,25 @@ fn fetch_node(
         .get("identity")
         .unwrap_or("")
         .to_string();
+    #[cfg(feature = "tenancy")]
+    let tenant_scope = request.extensions().get::<TenantScope>().cloned();
     Box::new(
-        web::block(move || registry.fetch_node(&identity)).then(|res| {
+        web::block(move || {
+            #[cfg(feature = "tenancy")]
+            {
+                if let Some(scope) = tenant_scope {
+                    if !scope
+                        .permits(TenantResourceType::Node, &identity)
+                        .map_err(tenant_error)?
+                    {
+                        return Ok(None);
+                    }
+                }
+            }
+
+            registry.fetch_node(&identity)
+        })
+        .then(|res| {
             Ok(match res {
                 Ok(Some(node)) => HttpResponse::Ok().json(NodeResponse::from(&node)),
                 Ok(None) => {
@@ -74,13 +100,15 @@ fn fetch_node(
 fn put_node(
     request: HttpRequest,
     payload: web::Payload,
-    registry: web::Data<Box<dyn RegistryWriter>>,
+    registry: web::Data<Box<dyn RwRegistry>>,
 ) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
     let path_identity = request
         .match_info()
         .get("identity")
         .unwrap_or("")
         .to_string();
+    #[cfg(feature = "tenancy")]
+    let tenant_scope = request.extensions().get::<TenantScope>().cloned();
     Box::new(
         payload
             .from_err::<Error>()
@@ -93,23 +121,55 @@ fn put_node(
                 Ok(node) => Box::new(
                     web::block(move || {
                         if node.identity != path_identity {
-                            Err(RegistryError::InvalidNode(
+                            return Err(NodeRequestError::from(RegistryError::InvalidNode(
                                 InvalidNodeError::InvalidIdentity(
                                     node.identity,
                                     "Node identity cannot be changed".into(),
                                 ),
-                            ))
-                        } else {
-                            registry.insert_node(node)
+                            )));
                         }
+
+                        // Existing nodes may only be replaced by their tenant, and new nodes are
+                        // assigned to the tenant of the client that adds them
+                        #[cfg(feature = "tenancy")]
+                        {
+                            if let Some(scope) = tenant_scope {
+                                let permitted = if registry.has_node(&node.identity)? {
+                                    scope
+                                        .owns(TenantResourceType::Node, &node.identity)
+                                        .map_err(tenant_error)?
+                                } else {
+                                    scope
+                                        .may_claim(TenantResourceType::Node, &node.identity)
+                                        .map_err(tenant_error)?
+                                };
+                                if !permitted {
+                                    return Err(NodeRequestError::Forbidden);
+                                }
+
+                                let identity = node.identity.clone();
+                                registry.insert_node(node)?;
+                                scope
+                                    .claim(TenantResourceType::Node, &identity)
+                                    .map_err(tenant_error)?;
+                                return Ok(());
+                            }
+                        }
+
+                        Ok(registry.insert_node(node)?)
                     })
                     .then(|res| {
                         Ok(match res {
                             Ok(_) => HttpResponse::Ok().finish(),
-                            Err(BlockingError::Error(RegistryError::InvalidNode(err))) => {
-                                HttpResponse::BadRequest().json(ErrorResponse::bad_request(
-                                    &format!("Invalid node: {}", err),
-                                ))
+                            Err(BlockingError::Error(NodeRequestError::Registry(
+                                RegistryError::InvalidNode(err),
+                            ))) => HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                                &format!("Invalid node: {}", err),
+                            )),
+                            #[cfg(feature = "tenancy")]
+                            Err(BlockingError::Error(NodeRequestError::Forbidden)) => {
+                                HttpResponse::Forbidden()
+                                    .json(ErrorResponse::forbidden("Not permitted to replace node"))
                             }
                             Err(err) => {
                                 error!("Unable to put node: {}", err);
@@ -141,13 +201,39 @@ fn delete_node(
         .get("identity")
         .unwrap_or("")
         .to_string();
+    #[cfg(feature = "tenancy")]
+    let tenant_scope = request.extensions().get::<TenantScope>().cloned();
     Box::new(
-        web::block(move || registry.delete_node(&identity)).then(|res| {
+        web::block(move || {
+            #[cfg(feature = "tenancy")]
+            {
+                if let Some(scope) = tenant_scope {
+                    if !scope
+                        .owns(TenantResourceType::Node, &identity)
+                        .map_err(tenant_error)?
+                    {
+                        return Err(NodeRequestError::Forbidden);
+                    }
+
+                    let node = registry.delete_node(&identity)?;
+                    scope
+                        .release(TenantResourceType::Node, &identity)
+                        .map_err(tenant_error)?;
+                    return Ok(node);
+                }
+            }
+
+            Ok(registry.delete_node(&identity)?)
+        })
+        .then(|res| {
             Ok(match res {
                 Ok(Some(_)) => HttpResponse::Ok().finish(),
                 Ok(None) => {
                     HttpResponse::NotFound().json(ErrorResponse::not_found("Node not found"))
                 }
+                #[cfg(feature = "tenancy")]
+                Err(BlockingError::Error(NodeRequestError::Forbidden)) => HttpResponse::Forbidden()
+                    .json(ErrorResponse::forbidden("Not permitted to delete node")),
                 Err(err) => {
                     error!("Unable to delete node: {}", err);
                     HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
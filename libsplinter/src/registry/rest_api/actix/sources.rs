@@ -0,0 +1,53 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints:
+//!
+//! * `GET /registry/sources` for listing the status of each source of the registry
+
+use crate::actix_web::{web, Error, HttpResponse};
+use crate::futures::Future;
+use crate::protocol;
+use crate::registry::{
+    rest_api::resources::sources::{ListSourcesResponse, SourceResponse},
+    UnifiedRegistry,
+};
+use crate::rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard, Resource};
+
+pub fn make_sources_resource(registry: UnifiedRegistry) -> Resource {
+    Resource::build("/registry/sources")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::REGISTRY_LIST_SOURCES_MIN,
+            protocol::REGISTRY_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            list_sources(web::Data::new(registry.clone()))
+        })
+}
+
+fn list_sources(
+    registry: web::Data<UnifiedRegistry>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(web::block(move || registry.source_statuses()).then(|res| {
+        Ok(match res {
+            Ok(statuses) => HttpResponse::Ok().json(ListSourcesResponse {
+                data: statuses.iter().map(SourceResponse::from).collect(),
+            }),
+            Err(err) => {
+                error!("Unable to get registry source statuses: {}", err);
+                HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+            }
+        })
+    }))
+}
@@ -21,6 +21,8 @@ mod resources;
 use crate::rest_api::{Resource, RestResourceProvider};
 
 use super::RwRegistry;
+#[cfg(feature = "registry-source-status")]
+use super::UnifiedRegistry;
 
 /// The `RwRegistry` trait service provides the following endpoints as REST API resources:
 ///
@@ -51,3 +53,44 @@ impl RestResourceProvider for dyn RwRegistry {
         resources
     }
 }
+
+/// Provides the REST API [`Resource`](crate::rest_api::Resource) definitions for reporting the
+/// status of each source of a [`UnifiedRegistry`](crate::registry::UnifiedRegistry).
+///
+/// The following endpoints are provided:
+///
+/// * `GET /registry/sources` - List each source with its last successful refresh time, last
+///   error, and node count
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "registry-source-status")]
+#[derive(Clone)]
+pub struct RegistrySourcesResourceProvider {
+    registry: UnifiedRegistry,
+}
+
+#[cfg(feature = "registry-source-status")]
+impl RegistrySourcesResourceProvider {
+    pub fn new(registry: UnifiedRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+#[cfg(feature = "registry-source-status")]
+impl RestResourceProvider for RegistrySourcesResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.push(actix::sources::make_sources_resource(self.registry.clone()));
+        }
+
+        resources
+    }
+}
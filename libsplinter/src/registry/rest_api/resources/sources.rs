@@ -0,0 +1,47 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::UNIX_EPOCH;
+
+use crate::registry::RegistrySourceStatus;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListSourcesResponse<'a> {
+    pub data: Vec<SourceResponse<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceResponse<'a> {
+    pub location: &'a str,
+    pub read_only: bool,
+    /// Seconds since the Unix epoch
+    pub last_refresh: Option<u64>,
+    pub last_error: Option<&'a str>,
+    pub node_count: Option<u32>,
+}
+
+impl<'a> From<&'a RegistrySourceStatus> for SourceResponse<'a> {
+    fn from(status: &'a RegistrySourceStatus) -> Self {
+        Self {
+            location: &status.location,
+            read_only: status.read_only,
+            last_refresh: status
+                .last_refresh
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs()),
+            last_error: status.last_error.as_deref(),
+            node_count: status.node_count,
+        }
+    }
+}
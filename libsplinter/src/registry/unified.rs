@@ -21,8 +21,10 @@
 //! [`RwRegistry`]: ../trait.RwRegistry.html
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
+#[cfg(feature = "registry-source-status")]
+use super::RegistrySourceStatus;
 use super::{
     MetadataPredicate, Node, NodeIter, RegistryError, RegistryReader, RegistryWriter, RwRegistry,
 };
@@ -71,7 +73,7 @@ use super::{
 #[derive(Clone)]
 pub struct UnifiedRegistry {
     internal_source: Arc<dyn RwRegistry>,
-    external_sources: Vec<Arc<dyn RegistryReader>>,
+    external_sources: Arc<RwLock<Vec<Arc<dyn RegistryReader>>>>,
 }
 
 impl UnifiedRegistry {
@@ -83,15 +85,63 @@ impl UnifiedRegistry {
     ) -> Self {
         Self {
             internal_source: internal_source.into(),
-            external_sources: external_sources.into_iter().map(Arc::from).collect(),
+            external_sources: Arc::new(RwLock::new(
+                external_sources.into_iter().map(Arc::from).collect(),
+            )),
         }
     }
 
+    /// Replaces the read-only registries. Since clones of a `UnifiedRegistry` share their sources,
+    /// the change is visible through all clones.
+    pub fn set_read_only_registries(
+        &self,
+        external_sources: Vec<Box<dyn RegistryReader>>,
+    ) -> Result<(), RegistryError> {
+        *self
+            .external_sources
+            .write()
+            .map_err(|_| RegistryError::general_error("Unified registry lock poisoned"))? =
+            external_sources.into_iter().map(Arc::from).collect();
+        Ok(())
+    }
+
+    /// Reports the status of each source, starting with the internal registry and followed by the
+    /// read-only registries in descending order of precedence.
+    ///
+    /// Sources that don't track their own status are described by their current node count, or by
+    /// the error returned when attempting to count their nodes.
+    #[cfg(feature = "registry-source-status")]
+    pub fn source_statuses(&self) -> Result<Vec<RegistrySourceStatus>, RegistryError> {
+        let mut statuses = vec![source_status_of(
+            &*self.internal_source,
+            "internal".into(),
+            false,
+        )];
+        statuses.extend(
+            self.external_sources()?
+                .iter()
+                .enumerate()
+                .map(|(i, source)| {
+                    source_status_of(&**source, format!("read-only source {}", i), true)
+                }),
+        );
+        Ok(statuses)
+    }
+
+    /// Gets the current read-only sources, in descending order of precedence.
+    fn external_sources(&self) -> Result<Vec<Arc<dyn RegistryReader>>, RegistryError> {
+        self.external_sources
+            .read()
+            .map(|sources| sources.clone())
+            .map_err(|_| RegistryError::general_error("Unified registry lock poisoned"))
+    }
+
     /// Gets all nodes from all sources (in ascending order of precedence) without deduplication.
-    fn all_nodes<'a>(&'a self) -> Box<dyn Iterator<Item = Node> + 'a> {
-        Box::new(
+    fn all_nodes(&self) -> Result<Vec<Node>, RegistryError> {
+        let external_sources = self.external_sources()?;
+        Ok(
             // Get node iterators from all read-only sources
-            self.external_sources
+            external_sources
                 .iter()
                 .map(|registry| registry.list_nodes(&[]))
                 // Reverse the sources, so lowest precedence is first
@@ -104,12 +154,41 @@ impl UnifiedRegistry {
                     res.map_err(|err| debug!("Failed to list nodes in source registry: {}", err))
                         .ok()
                 })
-                // Flatten into a single iterator
-                .flatten(),
+                // Flatten into a single list
+                .flatten()
+                .collect(),
         )
     }
 }
 
+/// Gets the status reported by the given source, falling back to a status derived from the
+/// source's node count.
+#[cfg(feature = "registry-source-status")]
+fn source_status_of<R: RegistryReader + ?Sized>(
+    source: &R,
+    default_location: String,
+    read_only: bool,
+) -> RegistrySourceStatus {
+    if let Some(status) = source.source_status() {
+        return RegistrySourceStatus {
+            read_only,
+            ..status
+        };
+    }
+
+    let (node_count, last_error) = match source.count_nodes(&[]) {
+        Ok(count) => (Some(count), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+    RegistrySourceStatus {
+        location: default_location,
+        read_only,
+        last_refresh: None,
+        last_error,
+        node_count,
+    }
+}
+
 impl RegistryReader for UnifiedRegistry {
     fn list_nodes<'a, 'b: 'a>(
         &'b self,
@@ -117,7 +196,8 @@ impl RegistryReader for UnifiedRegistry {
     ) -> Result<NodeIter<'a>, RegistryError> {
         let mut id_map = self
             // Get all nodes from all sources
-            .all_nodes()
+            .all_nodes()?
+            .into_iter()
             // Deduplicate and merge metadata
             .fold(HashMap::<String, Node>::new(), |mut acc, mut node| {
                 // If the node is already present, merge metadata
@@ -144,7 +224,7 @@ impl RegistryReader for UnifiedRegistry {
     fn fetch_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
         // Get node from all read-only sources
         Ok(self
-            .external_sources
+            .external_sources()?
             .iter()
             .map(|registry| registry.fetch_node(identity))
             // Reverse the sources, so lowest precedence is first
@@ -187,7 +267,7 @@ impl RegistryReader for UnifiedRegistry {
                 );
                 false
             })
-            || self.external_sources.iter().any(|source| {
+            || self.external_sources()?.iter().any(|source| {
                 source.has_node(identity).unwrap_or_else(|err| {
                     debug!(
                         "Failed to check for existence of node in source registry: {}",
@@ -666,6 +746,91 @@ mod test {
             .expect("Unable to check writeable for node1"));
     }
 
+    /// Verify that replacing the read-only registries is visible through clones of the unified
+    /// registry.
+    #[test]
+    fn set_read_only_registries() {
+        let node1 = new_node("node1", "endpoint1", &[]);
+        let node2 = new_node("node2", "endpoint2", &[]);
+
+        let readable1 = MemRegistry::default();
+        readable1
+            .insert_node(node1.clone())
+            .expect("Unable to insert node1");
+        let readable2 = MemRegistry::default();
+        readable2
+            .insert_node(node2.clone())
+            .expect("Unable to insert node2");
+
+        let unified =
+            UnifiedRegistry::new(Box::new(MemRegistry::default()), vec![Box::new(readable1)]);
+        let clone = unified.clone_box_as_reader();
+
+        assert!(clone.has_node("node1").expect("Unable to check for node1"));
+        assert!(!clone.has_node("node2").expect("Unable to check for node2"));
+
+        unified
+            .set_read_only_registries(vec![Box::new(readable2)])
+            .expect("Unable to set read-only registries");
+
+        assert!(!clone.has_node("node1").expect("Unable to check for node1"));
+        assert!(clone.has_node("node2").expect("Unable to check for node2"));
+        assert_eq!(1, clone.count_nodes(&[]).expect("Unable to count nodes"));
+    }
+
+    /// Verify that the status of every source is reported, in order of precedence, and that
+    /// sources without their own status are described by their node count.
+    #[cfg(feature = "registry-source-status")]
+    #[test]
+    fn source_statuses() {
+        let internal = MemRegistry::default();
+        internal
+            .insert_node(new_node("node1", "endpoint1", &[]))
+            .expect("Unable to insert node1");
+        let readable = MemRegistry::default();
+        readable
+            .insert_node(new_node("node2", "endpoint2", &[]))
+            .expect("Unable to insert node2");
+        readable
+            .insert_node(new_node("node3", "endpoint3", &[]))
+            .expect("Unable to insert node3");
+
+        let unified = UnifiedRegistry::new(
+            Box::new(internal),
+            vec![Box::new(readable), Box::new(MemRegistry::default())],
+        );
+
+        let statuses = unified
+            .source_statuses()
+            .expect("Unable to get source statuses");
+        assert_eq!(
+            statuses,
+            vec![
+                RegistrySourceStatus {
+                    location: "internal".into(),
+                    read_only: false,
+                    last_refresh: None,
+                    last_error: None,
+                    node_count: Some(1),
+                },
+                RegistrySourceStatus {
+                    location: "read-only source 0".into(),
+                    read_only: true,
+                    last_refresh: None,
+                    last_error: None,
+                    node_count: Some(2),
+                },
+                RegistrySourceStatus {
+                    location: "read-only source 1".into(),
+                    read_only: true,
+                    last_refresh: None,
+                    last_error: None,
+                    node_count: Some(0),
+                },
+            ]
+        );
+    }
+
     #[derive(Clone, Default)]
     struct MemRegistry {
         nodes: Arc<Mutex<HashMap<String, Node>>>,
@@ -0,0 +1,217 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A watcher that reports changes to the nodes in a registry.
+//!
+//! Registries do not notify callers when their contents change, so the [`RegistryWatcher`] polls
+//! a [`RegistryReader`] on a fixed interval and reports each node that was added, updated, or
+//! removed since the previous poll.
+//!
+//! [`RegistryWatcher`]: struct.RegistryWatcher.html
+//! [`RegistryReader`]: ../trait.RegistryReader.html
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::InternalError;
+
+use super::{Node, RegistryError, RegistryReader};
+
+/// A change to a node in the registry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegistryChange {
+    /// A node was added to the registry.
+    NodeAdded(Node),
+    /// A node in the registry was modified.
+    NodeUpdated { previous: Node, current: Node },
+    /// A node was removed from the registry.
+    NodeRemoved(Node),
+}
+
+/// Polls a registry and passes every change to its nodes to a subscriber.
+pub struct RegistryWatcher {
+    join_handle: JoinHandle<()>,
+    shutdown_signaler: RegistryWatcherShutdownSignaler,
+}
+
+impl RegistryWatcher {
+    /// Starts watching the registry.
+    ///
+    /// The registry's current nodes are read before this function returns; only changes made
+    /// after that are reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The registry to watch
+    /// * `interval` - How often the registry is checked for changes
+    /// * `subscriber` - Called with each change, in the order the changes were found
+    pub fn start(
+        reader: Box<dyn RegistryReader>,
+        interval: Duration,
+        subscriber: Box<dyn Fn(RegistryChange) + Send>,
+    ) -> Result<Self, InternalError> {
+        let mut nodes = list_nodes(&*reader).map_err(|err| {
+            InternalError::with_message(format!("Unable to read initial registry nodes: {}", err))
+        })?;
+
+        let (sender, receiver) = channel();
+
+        let join_handle = thread::Builder::new()
+            .name("RegistryWatcher".into())
+            .spawn(move || loop {
+                match receiver.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => match list_nodes(&*reader) {
+                        Ok(current) => {
+                            for change in diff_nodes(&nodes, &current) {
+                                subscriber(change);
+                            }
+                            nodes = current;
+                        }
+                        Err(err) => error!("Unable to check registry for changes: {}", err),
+                    },
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                        debug!("Stopping registry watcher");
+                        return;
+                    }
+                }
+            })
+            .map_err(|err| {
+                InternalError::with_message(format!(
+                    "Unable to start registry watcher thread: {}",
+                    err
+                ))
+            })?;
+
+        Ok(RegistryWatcher {
+            join_handle,
+            shutdown_signaler: RegistryWatcherShutdownSignaler { sender },
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the watcher.
+    pub fn shutdown_signaler(&self) -> RegistryWatcherShutdownSignaler {
+        self.shutdown_signaler.clone()
+    }
+
+    /// Waits for the watcher thread to exit once shutdown has been signaled.
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Registry watcher thread panicked");
+        }
+    }
+}
+
+/// Signals a `RegistryWatcher` to stop.
+#[derive(Clone)]
+pub struct RegistryWatcherShutdownSignaler {
+    sender: Sender<()>,
+}
+
+impl RegistryWatcherShutdownSignaler {
+    /// Signals the watcher to stop; the watcher will not report any further changes.
+    pub fn shutdown(&self) {
+        if self.sender.send(()).is_err() {
+            warn!("Registry watcher has already stopped");
+        }
+    }
+}
+
+fn list_nodes(reader: &dyn RegistryReader) -> Result<BTreeMap<String, Node>, RegistryError> {
+    Ok(reader
+        .list_nodes(&[])?
+        .map(|node| (node.identity.clone(), node))
+        .collect())
+}
+
+/// Returns the changes between two snapshots of a registry's nodes, keyed by node identity.
+fn diff_nodes(
+    previous: &BTreeMap<String, Node>,
+    current: &BTreeMap<String, Node>,
+) -> Vec<RegistryChange> {
+    let mut changes = vec![];
+
+    for (identity, node) in current {
+        match previous.get(identity) {
+            None => changes.push(RegistryChange::NodeAdded(node.clone())),
+            Some(previous_node) if previous_node != node => {
+                changes.push(RegistryChange::NodeUpdated {
+                    previous: previous_node.clone(),
+                    current: node.clone(),
+                })
+            }
+            Some(_) => (),
+        }
+    }
+
+    changes.extend(
+        previous
+            .iter()
+            .filter(|(identity, _)| !current.contains_key(*identity))
+            .map(|(_, node)| RegistryChange::NodeRemoved(node.clone())),
+    );
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(identity: &str, endpoint: &str) -> Node {
+        Node::builder(identity)
+            .with_endpoint(endpoint)
+            .with_display_name(identity)
+            .with_key("0123")
+            .build()
+            .expect("Failed to build node")
+    }
+
+    fn snapshot(nodes: Vec<Node>) -> BTreeMap<String, Node> {
+        nodes
+            .into_iter()
+            .map(|node| (node.identity.clone(), node))
+            .collect()
+    }
+
+    /// Verifies that `diff_nodes` reports added, updated, and removed nodes, and does not report
+    /// nodes that are unchanged.
+    #[test]
+    fn test_diff_nodes() {
+        let previous = snapshot(vec![
+            node("node-a", "tcps://a:8044"),
+            node("node-b", "tcps://b:8044"),
+            node("node-c", "tcps://c:8044"),
+        ]);
+        let current = snapshot(vec![
+            node("node-a", "tcps://a:8044"),
+            node("node-b", "tcps://b2:8044"),
+            node("node-d", "tcps://d:8044"),
+        ]);
+
+        assert_eq!(
+            diff_nodes(&previous, &current),
+            vec![
+                RegistryChange::NodeUpdated {
+                    previous: node("node-b", "tcps://b:8044"),
+                    current: node("node-b", "tcps://b2:8044"),
+                },
+                RegistryChange::NodeAdded(node("node-d", "tcps://d:8044")),
+                RegistryChange::NodeRemoved(node("node-c", "tcps://c:8044")),
+            ]
+        );
+        assert!(diff_nodes(&current, &current).is_empty());
+    }
+}
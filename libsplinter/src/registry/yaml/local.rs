@@ -21,16 +21,23 @@
 //! [`RwRegistry`]: ../../trait.RwRegistry.html
 
 use std::fs::File;
-use std::io::Write;
+#[cfg(not(feature = "yaml-state-journal"))]
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
+#[cfg(feature = "yaml-state-journal")]
+use crate::journal::{self, STATE_BACKUPS};
+#[cfg(feature = "registry-source-status")]
+use crate::registry::RegistrySourceStatus;
 use crate::registry::{
     validate_nodes, MetadataPredicate, Node, NodeIter, RegistryError, RegistryReader,
     RegistryWriter, RwRegistry,
 };
 
+#[cfg(not(feature = "yaml-state-journal"))]
+use super::write_file_atomically;
+
 /// A local, read/write registry.
 ///
 /// The `LocalYamlRegistry` provides access to and modification of a local registry YAML file. The
@@ -46,6 +53,10 @@ use crate::registry::{
 /// file already exists, the registry will attempt to load, parse, and validate it. If the backing
 /// file does not already exist, the registry will attempt to create it.
 ///
+/// With the `yaml-state-journal` feature, the registry keeps backups of the previous versions of
+/// its backing file. If the backing file cannot be loaded on initialization, the newest valid
+/// backup is restored.
+///
 /// [`Node`]: struct.Node.html
 #[derive(Clone)]
 pub struct LocalYamlRegistry {
@@ -117,6 +128,20 @@ impl RegistryReader for LocalYamlRegistry {
             .iter()
             .any(|node| node.identity == identity))
     }
+
+    #[cfg(feature = "registry-source-status")]
+    fn source_status(&self) -> Option<RegistrySourceStatus> {
+        // If the lock is poisoned, the caller will get the error when falling back to reading
+        let mut internal = self.internal.lock().ok()?;
+        let node_count = internal.get_nodes().len() as u32;
+        Some(RegistrySourceStatus {
+            location: internal.file_path.clone(),
+            read_only: false,
+            last_refresh: Some(internal.last_read).filter(|time| *time != SystemTime::UNIX_EPOCH),
+            last_error: internal.last_error.clone(),
+            node_count: Some(node_count),
+        })
+    }
 }
 
 impl RegistryWriter for LocalYamlRegistry {
@@ -164,6 +189,9 @@ struct Internal {
     file_path: String,
     cached_nodes: Vec<Node>,
     last_read: SystemTime,
+    /// The error from the most recent failed attempt to read the backing file
+    #[cfg(feature = "registry-source-status")]
+    last_error: Option<String>,
 }
 
 impl Internal {
@@ -172,11 +200,21 @@ impl Internal {
             file_path: file_path.into(),
             cached_nodes: vec![],
             last_read: SystemTime::UNIX_EPOCH,
+            #[cfg(feature = "registry-source-status")]
+            last_error: None,
         };
 
+        #[cfg(not(feature = "yaml-state-journal"))]
+        let file_exists = PathBuf::from(file_path).is_file();
+        #[cfg(feature = "yaml-state-journal")]
+        let file_exists = journal::exists(file_path, STATE_BACKUPS);
+
         // If file already exists, read it; otherwise initialize it.
-        if PathBuf::from(file_path).is_file() {
+        if file_exists {
+            #[cfg(not(feature = "yaml-state-journal"))]
             internal.read_nodes()?;
+            #[cfg(feature = "yaml-state-journal")]
+            internal.recover_nodes()?;
         } else {
             internal.write_nodes(vec![])?;
         }
@@ -205,64 +243,56 @@ impl Internal {
 
         // Log any errors that occurred with checking or reading the backing file and use the
         // in-memory cache.
-        if let Err(err) = file_read_result {
+        if let Err(err) = &file_read_result {
             warn!(
                 "Using cached nodes; failed to read from YAML registry file: {}",
                 err
             );
         }
+        #[cfg(feature = "registry-source-status")]
+        {
+            self.last_error = file_read_result.err().map(|err| err.to_string());
+        }
 
         self.cached_nodes.clone()
     }
 
     /// Read the backing file, verify that it's valid, and cache its contents.
     fn read_nodes(&mut self) -> Result<(), RegistryError> {
-        let file = File::open(&self.file_path).map_err(|err| {
-            RegistryError::general_error_with_source(
-                "Failed to open YAML registry file",
-                Box::new(err),
-            )
-        })?;
-        let nodes: Vec<Node> = serde_yaml::from_reader(&file).map_err(|err| {
-            RegistryError::general_error_with_source(
-                "Failed to read YAML registry file",
-                Box::new(err),
-            )
-        })?;
+        self.cached_nodes = load_nodes(&self.file_path)?;
+        self.last_read = SystemTime::now();
 
-        validate_nodes(&nodes)?;
+        Ok(())
+    }
 
-        self.cached_nodes = nodes;
+    /// Read the backing file, or its newest valid backup if the backing file is missing or
+    /// invalid, and cache its contents.
+    #[cfg(feature = "yaml-state-journal")]
+    fn recover_nodes(&mut self) -> Result<(), RegistryError> {
+        self.cached_nodes = journal::read_verified(&self.file_path, STATE_BACKUPS, load_nodes)?;
         self.last_read = SystemTime::now();
 
         Ok(())
     }
 
     /// Verify that the given nodes represent a valid registry, write them to the backing file, and
-    /// update the in-memory cache.
+    /// update the in-memory cache. The backing file is replaced atomically, so a failed write
+    /// leaves the previous contents intact.
     fn write_nodes(&mut self, nodes: Vec<Node>) -> Result<(), RegistryError> {
         validate_nodes(&nodes)?;
 
-        let output = serde_yaml::to_vec(&nodes).map_err(|err| {
+        let mut output = serde_yaml::to_vec(&nodes).map_err(|err| {
             RegistryError::general_error_with_source("Failed to write nodes to YAML", Box::new(err))
         })?;
-
-        let mut file = File::create(&self.file_path).map_err(|err| {
-            RegistryError::general_error_with_source(
-                &format!("Failed to open YAML registry file '{}'", self.file_path),
-                Box::new(err),
-            )
-        })?;
-        file.write_all(&output).map_err(|err| {
-            RegistryError::general_error_with_source(
-                &format!("Failed to write to YAML registry file '{}'", self.file_path),
-                Box::new(err),
-            )
-        })?;
         // Append newline to file
-        writeln!(file).map_err(|err| {
+        output.push(b'\n');
+
+        #[cfg(not(feature = "yaml-state-journal"))]
+        write_file_atomically(&self.file_path, &output)?;
+        #[cfg(feature = "yaml-state-journal")]
+        journal::write_journaled(&self.file_path, &output, STATE_BACKUPS).map_err(|err| {
             RegistryError::general_error_with_source(
-                &format!("Failed to write to YAML registry file '{}'", self.file_path),
+                &format!("Failed to write file '{}'", self.file_path),
                 Box::new(err),
             )
         })?;
@@ -274,6 +304,20 @@ impl Internal {
     }
 }
 
+/// Read the registry file at the given path and verify that it's valid.
+fn load_nodes(file_path: &str) -> Result<Vec<Node>, RegistryError> {
+    let file = File::open(file_path).map_err(|err| {
+        RegistryError::general_error_with_source("Failed to open YAML registry file", Box::new(err))
+    })?;
+    let nodes: Vec<Node> = serde_yaml::from_reader(&file).map_err(|err| {
+        RegistryError::general_error_with_source("Failed to read YAML registry file", Box::new(err))
+    })?;
+
+    validate_nodes(&nodes)?;
+
+    Ok(nodes)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -19,5 +19,35 @@ mod local;
 mod remote;
 
 pub use local::LocalYamlRegistry;
+#[cfg(feature = "registry-remote-auth")]
+pub use remote::RemoteYamlRegistryAuth;
 #[cfg(feature = "registry-remote")]
 pub use remote::{RemoteYamlRegistry, ShutdownHandle as RemoteYamlShutdownHandle};
+
+#[cfg(any(not(feature = "yaml-state-journal"), feature = "registry-remote"))]
+use std::fs::{self, File};
+#[cfg(any(not(feature = "yaml-state-journal"), feature = "registry-remote"))]
+use std::io::Write;
+
+#[cfg(any(not(feature = "yaml-state-journal"), feature = "registry-remote"))]
+use super::RegistryError;
+
+/// Write `contents` to the file at `path` by writing a temporary file next to it and renaming the
+/// temporary file into place. Readers see either the previous contents or the new contents, never
+/// a partially written file.
+#[cfg(any(not(feature = "yaml-state-journal"), feature = "registry-remote"))]
+fn write_file_atomically(path: &str, contents: &[u8]) -> Result<(), RegistryError> {
+    let temp_path = format!("{}.tmp", path);
+    File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|err| {
+            RegistryError::general_error_with_source(
+                &format!("Failed to write file '{}'", path),
+                Box::new(err),
+            )
+        })
+}
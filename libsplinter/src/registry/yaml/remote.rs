@@ -20,22 +20,34 @@
 //! [`RemoteYamlRegistry`]: struct.RemoteYamlRegistry.html
 //! [`RegistryReader`]: ../../trait.RegistryReader.html
 
+use std::fs;
 use std::path::Path;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
 use std::thread;
+#[cfg(feature = "registry-source-status")]
+use std::time::SystemTime;
 use std::time::{Duration, Instant};
 
 use openssl::hash::{hash, MessageDigest};
+use reqwest::blocking::Client;
+use reqwest::header::{
+    HeaderName, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+#[cfg(feature = "registry-remote-auth")]
+use reqwest::Identity;
+use reqwest::StatusCode;
 
 use crate::hex::to_hex;
+#[cfg(feature = "registry-source-status")]
+use crate::registry::RegistrySourceStatus;
 use crate::registry::{
     validate_nodes, MetadataPredicate, Node, NodeIter, RegistryError, RegistryReader,
 };
 
-use super::LocalYamlRegistry;
+use super::{write_file_atomically, LocalYamlRegistry};
 
 /// A remote, read-only registry.
 ///
@@ -48,6 +60,11 @@ use super::LocalYamlRegistry;
 /// location of the local cache is determined by the `cache_dir` argument of the registry's
 /// [`constructor`].
 ///
+/// The cache is replaced atomically, and its checksum is recorded alongside the `ETag` and
+/// `Last-Modified` values the server returned for it. Refreshes are conditional requests, so an
+/// unchanged remote file is not downloaded again. If the cache does not match its recorded
+/// checksum on startup, it is discarded and the remote file is fetched in full.
+///
 /// On initialization, the `RemoteYamlRegistry` will attempt to immediately fetch and cache the
 /// remote file. If this fails, the registry will log an error message and attempt to fetch/cache
 /// the remote file every time a read query is made on the registry (through one of the
@@ -67,9 +84,13 @@ use super::LocalYamlRegistry;
 /// and the previously cached registry values will continue to be used. The next time the registry
 /// is read, it will try again to refresh the cache.
 ///
+/// If the remote file is served from an authenticated endpoint, the registry can be constructed
+/// with [`new_with_auth`] to provide the credentials used for every fetch.
+///
 /// [`Node`]: struct.Node.html
 /// [`RegistryReader`]: trait.RegistryReader.html
 /// [`constructor`]: struct.RemoteYamlRegistry.html#method.new
+/// [`new_with_auth`]: struct.RemoteYamlRegistry.html#method.new_with_auth
 pub struct RemoteYamlRegistry {
     internal: Arc<Mutex<Internal>>,
     shutdown_handle: ShutdownHandle,
@@ -93,11 +114,81 @@ impl RemoteYamlRegistry {
         cache_dir: &str,
         automatic_refresh_period: Option<Duration>,
         forced_refresh_period: Option<Duration>,
+    ) -> Result<Self, RegistryError> {
+        Self::build(
+            url,
+            cache_dir,
+            automatic_refresh_period,
+            forced_refresh_period,
+            Client::new(),
+            None,
+        )
+    }
+
+    /// Construct a new `RemoteYamlRegistry` that authenticates to the server hosting the remote
+    /// YAML file.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL of the registry's backing YAML file.
+    /// * `cache_dir` - Directory that the local registry cache will be stored in.
+    /// * `automatic_refresh_period` - See [`new`](#method.new).
+    /// * `forced_refresh_period` - See [`new`](#method.new).
+    /// * `auth` - Credentials presented on every fetch of the remote YAML file.
+    #[cfg(feature = "registry-remote-auth")]
+    pub fn new_with_auth(
+        url: &str,
+        cache_dir: &str,
+        automatic_refresh_period: Option<Duration>,
+        forced_refresh_period: Option<Duration>,
+        auth: RemoteYamlRegistryAuth,
+    ) -> Result<Self, RegistryError> {
+        let (client, bearer_token) = match auth {
+            RemoteYamlRegistryAuth::BearerToken(token) => (Client::new(), Some(token)),
+            RemoteYamlRegistryAuth::ClientCertificate { pkcs12, password } => {
+                let identity = Identity::from_pkcs12_der(&pkcs12, &password).map_err(|err| {
+                    RegistryError::general_error_with_source(
+                        "Failed to load client certificate for remote registry",
+                        Box::new(err),
+                    )
+                })?;
+                let client = Client::builder()
+                    .identity(identity)
+                    .build()
+                    .map_err(|err| {
+                        RegistryError::general_error_with_source(
+                            "Failed to build HTTP client for remote registry",
+                            Box::new(err),
+                        )
+                    })?;
+                (client, None)
+            }
+        };
+
+        Self::build(
+            url,
+            cache_dir,
+            automatic_refresh_period,
+            forced_refresh_period,
+            client,
+            bearer_token,
+        )
+    }
+
+    fn build(
+        url: &str,
+        cache_dir: &str,
+        automatic_refresh_period: Option<Duration>,
+        forced_refresh_period: Option<Duration>,
+        client: Client,
+        bearer_token: Option<String>,
     ) -> Result<Self, RegistryError> {
         let internal = Arc::new(Mutex::new(Internal::new(
             url,
             cache_dir,
             forced_refresh_period,
+            client,
+            bearer_token,
         )?));
 
         let running = automatic_refresh_period
@@ -175,13 +266,88 @@ impl RegistryReader for RemoteYamlRegistry {
             .filter(move |node| predicates.iter().all(|predicate| predicate.apply(node)))
             .count() as u32)
     }
+
+    #[cfg(feature = "registry-source-status")]
+    fn source_status(&self) -> Option<RegistrySourceStatus> {
+        // If the lock is poisoned, the caller will get the error when falling back to reading
+        let internal = self.internal.lock().ok()?;
+        // Report what is currently cached, without attempting to refresh from the remote
+        let node_count = internal
+            .cache
+            .get_nodes()
+            .ok()
+            .map(|nodes| nodes.len() as u32);
+        Some(RegistrySourceStatus {
+            location: internal.url.clone(),
+            read_only: true,
+            last_refresh: internal.last_refresh,
+            last_error: internal.last_error.clone(),
+            node_count,
+        })
+    }
+}
+
+/// Credentials used to authenticate to the server hosting a remote registry file.
+#[cfg(feature = "registry-remote-auth")]
+#[derive(Clone)]
+pub enum RemoteYamlRegistryAuth {
+    /// A token sent in the `Authorization: Bearer` header of each request
+    BearerToken(String),
+    /// A client certificate and its private key, as a DER-encoded PKCS #12 archive, presented
+    /// during the TLS handshake
+    ClientCertificate { pkcs12: Vec<u8>, password: String },
+}
+
+#[cfg(feature = "registry-remote-auth")]
+impl std::fmt::Debug for RemoteYamlRegistryAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Never expose the credentials themselves
+        match self {
+            RemoteYamlRegistryAuth::BearerToken(_) => f.write_str("BearerToken(<HIDDEN>)"),
+            RemoteYamlRegistryAuth::ClientCertificate { .. } => {
+                f.write_str("ClientCertificate(<HIDDEN>)")
+            }
+        }
+    }
+}
+
+/// Describes the cached copy of the remote file; saved next to the cache so that conditional
+/// requests can be made across restarts.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+struct CacheMetadata {
+    /// The `ETag` the server returned with the cached copy
+    etag: Option<String>,
+    /// The `Last-Modified` time the server returned with the cached copy
+    last_modified: Option<String>,
+    /// The hex-encoded SHA-256 checksum of the cache file
+    checksum: String,
+}
+
+/// A remote file that was downloaded because it changed since it was cached.
+struct FetchedRegistry {
+    nodes: Vec<Node>,
+    etag: Option<String>,
+    last_modified: Option<String>,
 }
 
 /// Holds the internal state of the remote registry.
 struct Internal {
     url: String,
+    client: Client,
+    bearer_token: Option<String>,
     cache: LocalYamlRegistry,
+    cache_path: String,
+    metadata_path: String,
+    /// The description of the cache, if the cache is known to hold a complete copy of the remote
+    /// file
+    cache_metadata: Option<CacheMetadata>,
     last_refresh_successful: bool,
+    /// The last time the cache was successfully refreshed from the remote file
+    #[cfg(feature = "registry-source-status")]
+    last_refresh: Option<SystemTime>,
+    /// The error from the most recent refresh, if it failed
+    #[cfg(feature = "registry-source-status")]
+    last_error: Option<String>,
     forced_refresh_period: Option<Duration>,
     next_forced_refresh: Option<Instant>,
 }
@@ -192,15 +358,39 @@ impl Internal {
         url: &str,
         cache_dir: &str,
         forced_refresh_period: Option<Duration>,
+        client: Client,
+        bearer_token: Option<String>,
     ) -> Result<Self, RegistryError> {
         let url = url.to_string();
 
-        let cache = LocalYamlRegistry::new(&compute_cache_filename(&url, cache_dir)?)?;
+        let cache_path = compute_cache_filename(&url, cache_dir)?;
+        let metadata_path = format!("{}.meta", cache_path);
+
+        let cache_metadata = match load_cache_metadata(&cache_path, &metadata_path) {
+            Ok(cache_metadata) => cache_metadata,
+            Err(err) => {
+                warn!("Discarding cache of remote registry '{}': {}", url, err);
+                remove_file_if_exists(&cache_path)?;
+                remove_file_if_exists(&metadata_path)?;
+                None
+            }
+        };
+
+        let cache = LocalYamlRegistry::new(&cache_path)?;
 
         let mut internal = Self {
             url,
+            client,
+            bearer_token,
             cache,
+            cache_path,
+            metadata_path,
+            cache_metadata,
             last_refresh_successful: false,
+            #[cfg(feature = "registry-source-status")]
+            last_refresh: None,
+            #[cfg(feature = "registry-source-status")]
+            last_error: None,
             forced_refresh_period,
             next_forced_refresh: None,
         };
@@ -219,29 +409,75 @@ impl Internal {
 
     /// Attempt to refresh the internal cache and update state accordingly.
     fn refresh_cache(&mut self) -> Result<(), RegistryError> {
-        fetch_nodes_from_remote(&self.url)
-            .and_then(|nodes| self.cache.write_nodes(nodes))
-            .map_err(|err| {
-                self.last_refresh_successful = false;
-                err
-            })
-            .and_then(|_| {
-                self.last_refresh_successful = true;
-                // If a forced refresh period was configured, set the next time a forced refresh
-                // will be required
-                self.next_forced_refresh = self
-                    .forced_refresh_period
-                    .map(|duration| {
-                        Instant::now().checked_add(duration).ok_or_else(|| {
-                            RegistryError::general_error(
-                                "Forced refresh time could not be determined; \
+        fetch_nodes_from_remote(
+            &self.client,
+            &self.url,
+            self.bearer_token.as_deref(),
+            self.cache_metadata.as_ref(),
+        )
+        .and_then(|fetched| match fetched {
+            Some(fetched) => self.write_cache(fetched),
+            None => {
+                debug!("Remote registry '{}' has not been modified", self.url);
+                Ok(())
+            }
+        })
+        .map_err(|err| {
+            self.last_refresh_successful = false;
+            #[cfg(feature = "registry-source-status")]
+            {
+                self.last_error = Some(err.to_string());
+            }
+            err
+        })
+        .and_then(|_| {
+            self.last_refresh_successful = true;
+            #[cfg(feature = "registry-source-status")]
+            {
+                self.last_refresh = Some(SystemTime::now());
+                self.last_error = None;
+            }
+            // If a forced refresh period was configured, set the next time a forced refresh
+            // will be required
+            self.next_forced_refresh = self
+                .forced_refresh_period
+                .map(|duration| {
+                    Instant::now().checked_add(duration).ok_or_else(|| {
+                        RegistryError::general_error(
+                            "Forced refresh time could not be determined; \
                                  forced_refresh_period may be too large",
-                            )
-                        })
+                        )
                     })
-                    .transpose()?;
-                Ok(())
-            })
+                })
+                .transpose()?;
+            Ok(())
+        })
+    }
+
+    /// Write the downloaded nodes to the cache, then record the cache's checksum and the
+    /// validators for future conditional requests.
+    fn write_cache(&mut self, fetched: FetchedRegistry) -> Result<(), RegistryError> {
+        // The previous validators no longer describe the cache once it's being replaced
+        self.cache_metadata = None;
+
+        self.cache.write_nodes(fetched.nodes)?;
+
+        let cache_metadata = CacheMetadata {
+            etag: fetched.etag,
+            last_modified: fetched.last_modified,
+            checksum: file_checksum(&self.cache_path)?,
+        };
+        let output = serde_yaml::to_vec(&cache_metadata).map_err(|err| {
+            RegistryError::general_error_with_source(
+                "Failed to serialize remote registry cache metadata",
+                Box::new(err),
+            )
+        })?;
+        write_file_atomically(&self.metadata_path, &output)?;
+
+        self.cache_metadata = Some(cache_metadata);
+
+        Ok(())
     }
 
     /// Attempt to refresh the internal cache if necessary and return the cache's contents.
@@ -297,23 +533,139 @@ fn compute_cache_filename(url: &str, cache_dir: &str) -> Result<String, Registry
         .to_string())
 }
 
-/// Fetch, parse, and validate the YAML registry file at the given URL.
-fn fetch_nodes_from_remote(url: &str) -> Result<Vec<Node>, RegistryError> {
-    let bytes = reqwest::blocking::get(url)
-        .and_then(|response| response.error_for_status())
+/// Load the description of the cache saved by a previous run. Returns `None` if there is no
+/// complete cache to describe, or an error if the cache does not match its recorded checksum.
+fn load_cache_metadata(
+    cache_path: &str,
+    metadata_path: &str,
+) -> Result<Option<CacheMetadata>, RegistryError> {
+    if !Path::new(cache_path).is_file() || !Path::new(metadata_path).is_file() {
+        return Ok(None);
+    }
+
+    let cache_metadata: CacheMetadata = fs::read(metadata_path)
+        .map_err(|err| {
+            RegistryError::general_error_with_source("Failed to read cache metadata", Box::new(err))
+        })
+        .and_then(|bytes| {
+            serde_yaml::from_slice(&bytes).map_err(|err| {
+                RegistryError::general_error_with_source(
+                    "Failed to deserialize cache metadata",
+                    Box::new(err),
+                )
+            })
+        })?;
+
+    if file_checksum(cache_path)? != cache_metadata.checksum {
+        return Err(RegistryError::general_error(
+            "Cache file does not match its recorded checksum",
+        ));
+    }
+
+    Ok(Some(cache_metadata))
+}
+
+/// Compute the hex-encoded SHA-256 checksum of the file at the given path.
+fn file_checksum(path: &str) -> Result<String, RegistryError> {
+    let bytes = fs::read(path).map_err(|err| {
+        RegistryError::general_error_with_source(
+            &format!("Failed to read file '{}'", path),
+            Box::new(err),
+        )
+    })?;
+    hash(MessageDigest::sha256(), &bytes)
+        .map(|digest| to_hex(&*digest))
         .map_err(|err| {
             RegistryError::general_error_with_source(
-                &format!("Failed to fetch remote registry file from {}", url),
+                &format!("Failed to compute checksum of file '{}'", path),
                 Box::new(err),
             )
-        })?
-        .bytes()
+        })
+}
+
+fn remove_file_if_exists(path: &str) -> Result<(), RegistryError> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(RegistryError::general_error_with_source(
+                &format!("Failed to remove file '{}'", path),
+                Box::new(err),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fetch, parse, and validate the YAML registry file at the given URL, sending the bearer token
+/// if one is provided. If the cache is described by `cache_metadata`, the request is conditional
+/// and `None` is returned when the remote file has not been modified.
+fn fetch_nodes_from_remote(
+    client: &Client,
+    url: &str,
+    bearer_token: Option<&str>,
+    cache_metadata: Option<&CacheMetadata>,
+) -> Result<Option<FetchedRegistry>, RegistryError> {
+    let mut request = client.get(url);
+    if let Some(token) = bearer_token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(cache_metadata) = cache_metadata {
+        if let Some(etag) = &cache_metadata.etag {
+            request = request.header(IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(last_modified) = &cache_metadata.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+    }
+
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
         .map_err(|err| {
             RegistryError::general_error_with_source(
-                "Failed to get bytes from remote registry file HTTP response",
+                &format!("Failed to fetch remote registry file from {}", url),
                 Box::new(err),
             )
         })?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if cache_metadata.is_some() {
+            return Ok(None);
+        }
+        return Err(RegistryError::general_error(&format!(
+            "Remote registry file at {} was reported as not modified, but is not cached",
+            url
+        )));
+    }
+
+    let header_value = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header_value(ETAG);
+    let last_modified = header_value(LAST_MODIFIED);
+    let content_length = header_value(CONTENT_LENGTH).and_then(|value| value.parse::<usize>().ok());
+
+    let bytes = response.bytes().map_err(|err| {
+        RegistryError::general_error_with_source(
+            "Failed to get bytes from remote registry file HTTP response",
+            Box::new(err),
+        )
+    })?;
+
+    // Never replace the cache with a truncated download
+    if let Some(content_length) = content_length {
+        if bytes.len() != content_length {
+            return Err(RegistryError::general_error(&format!(
+                "Remote registry file download was incomplete: received {} of {} bytes",
+                bytes.len(),
+                content_length
+            )));
+        }
+    }
+
     let nodes: Vec<Node> = serde_yaml::from_slice(&bytes).map_err(|_| {
         RegistryError::general_error(
             "Failed to deserialize remote registry file: Not a valid YAML sequence of nodes",
@@ -322,7 +674,11 @@ fn fetch_nodes_from_remote(url: &str) -> Result<Vec<Node>, RegistryError> {
 
     validate_nodes(&nodes)?;
 
-    Ok(nodes)
+    Ok(Some(FetchedRegistry {
+        nodes,
+        etag,
+        last_modified,
+    }))
 }
 
 /// Infinitely loop, attempting to refresh the `internal` cache every `refresh_period`, until no
@@ -395,6 +751,7 @@ mod tests {
     use super::*;
 
     use std::fs::File;
+    use std::sync::atomic::AtomicUsize;
 
     use actix_web::HttpResponse;
     use futures::future::IntoFuture;
@@ -795,6 +1152,106 @@ mod tests {
         test_config.shutdown();
     }
 
+    /// Verifies that the registry's status reports the failure to fetch the remote file, and that
+    /// the error is cleared and the refresh time recorded once the file has been fetched.
+    #[cfg(feature = "registry-source-status")]
+    #[test]
+    fn source_status() {
+        // Start without a remote file
+        let test_config = TestConfig::setup("source_status", None);
+
+        let remote_registry =
+            RemoteYamlRegistry::new(test_config.url(), test_config.path(), None, None)
+                .expect("Failed to create registry");
+
+        let status = remote_registry
+            .source_status()
+            .expect("Remote registry did not report a status");
+        assert_eq!(status.location, test_config.url());
+        assert!(status.read_only);
+        assert!(status.last_refresh.is_none());
+        assert!(status.last_error.is_some());
+        assert_eq!(status.node_count, Some(0));
+
+        // Make the remote file available and read the registry to trigger a refresh
+        test_config.update_registry(Some(mock_registry()));
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+
+        let status = remote_registry
+            .source_status()
+            .expect("Remote registry did not report a status");
+        assert!(status.last_refresh.is_some());
+        assert!(status.last_error.is_none());
+        assert_eq!(status.node_count, Some(mock_registry().len() as u32));
+
+        remote_registry.shutdown_handle().shutdown();
+        test_config.shutdown();
+    }
+
+    /// Verifies that a registry constructed with a bearer token sends the token when fetching the
+    /// remote file, and that a registry without the token can't fetch the file.
+    #[cfg(feature = "registry-remote-auth")]
+    #[test]
+    fn bearer_token_auth() {
+        let temp_dir = TempDir::new("bearer_token_auth").expect("Failed to create temp dir");
+        let temp_dir_path = temp_dir.path().to_str().expect("Failed to get path");
+
+        let registry = mock_registry();
+        let (shutdown_handle, join_handle, url) = run_rest_api_on_open_port(vec![Resource::build(
+            "/registry.yaml",
+        )
+        .add_method(Method::Get, move |req, _| {
+            let authorized = req
+                .headers()
+                .get("Authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value == "Bearer secret-token")
+                .unwrap_or(false);
+            Box::new(if authorized {
+                HttpResponse::Ok()
+                    .body(serde_yaml::to_vec(&registry).expect("Failed to serialize registry file"))
+                    .into_future()
+            } else {
+                HttpResponse::Unauthorized().finish().into_future()
+            })
+        })]);
+        let url = format!("http://{}/registry.yaml", url);
+
+        let anonymous_dir =
+            TempDir::new("bearer_token_auth_anonymous").expect("Failed to create temp dir");
+        let anonymous_registry = RemoteYamlRegistry::new(
+            &url,
+            anonymous_dir.path().to_str().expect("Failed to get path"),
+            None,
+            None,
+        )
+        .expect("Failed to create registry");
+        assert!(anonymous_registry
+            .get_nodes()
+            .expect("Failed to get nodes")
+            .is_empty());
+
+        let authenticated_registry = RemoteYamlRegistry::new_with_auth(
+            &url,
+            temp_dir_path,
+            None,
+            None,
+            RemoteYamlRegistryAuth::BearerToken("secret-token".into()),
+        )
+        .expect("Failed to create registry");
+        assert_eq!(
+            authenticated_registry
+                .get_nodes()
+                .expect("Failed to get nodes"),
+            mock_registry()
+        );
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
     /// Verifies that when auto refresh is turned off, the auto refresh thread is not running.
     #[test]
     fn auto_refresh_disabled() {
@@ -946,6 +1403,83 @@ mod tests {
         test_config.shutdown();
     }
 
+    /// Verifies that once the remote file has been cached, refreshes are conditional requests: an
+    /// unmodified remote file is not downloaded again, including after a restart.
+    #[test]
+    fn conditional_refresh() {
+        let temp_dir = TempDir::new("conditional_refresh").expect("Failed to create temp dir");
+        let temp_dir_path = temp_dir.path().to_str().expect("Failed to get path");
+
+        let downloads = Arc::new(AtomicUsize::new(0));
+        let (shutdown_handle, join_handle, url) = serve_registry_with_etag(downloads.clone());
+
+        let remote_registry = RemoteYamlRegistry::new(&url, temp_dir_path, None, None)
+            .expect("Failed to create registry");
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+
+        remote_registry
+            .internal
+            .lock()
+            .expect("Internal lock poisoned")
+            .refresh_cache()
+            .expect("Failed to refresh cache");
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            remote_registry.get_nodes().expect("Failed to get nodes"),
+            mock_registry()
+        );
+        remote_registry.shutdown_handle().shutdown();
+
+        // The cache is still valid after a restart, so the remote file is not downloaded again
+        let remote_registry = RemoteYamlRegistry::new(&url, temp_dir_path, None, None)
+            .expect("Failed to create registry");
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            remote_registry.get_nodes().expect("Failed to get nodes"),
+            mock_registry()
+        );
+        remote_registry.shutdown_handle().shutdown();
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
+    /// Verifies that a cache file that doesn't match its recorded checksum is discarded on startup
+    /// instead of preventing the registry from starting, and that the remote file is then fetched
+    /// in full.
+    #[test]
+    fn corrupted_cache_discarded() {
+        let test_config = TestConfig::setup("corrupted_cache_discarded", Some(mock_registry()));
+
+        let remote_registry =
+            RemoteYamlRegistry::new(test_config.url(), test_config.path(), None, None)
+                .expect("Failed to create registry");
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+        remote_registry.shutdown_handle().shutdown();
+
+        // Truncate the cache file
+        let filename = compute_cache_filename(test_config.url(), test_config.path())
+            .expect("Failed to compute cache filename");
+        let contents = fs::read(&filename).expect("Failed to read cache file");
+        fs::write(&filename, &contents[..contents.len() / 2]).expect("Failed to write cache file");
+
+        // Restart with the remote file unavailable; the corrupted cache is discarded
+        test_config.update_registry(None);
+        let remote_registry =
+            RemoteYamlRegistry::new(test_config.url(), test_config.path(), None, None)
+                .expect("Failed to create registry");
+        verify_internal_cache(&test_config, &remote_registry, vec![]);
+
+        // Once the remote file is available again, it's fetched and cached
+        test_config.update_registry(Some(mock_registry()));
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+
+        remote_registry.shutdown_handle().shutdown();
+        test_config.shutdown();
+    }
+
     // Restart, remote file not available
 
     /// Creates a mock registry.
@@ -1086,6 +1620,39 @@ mod tests {
         (shutdown, join, format!("http://{}/registry.yaml", url))
     }
 
+    /// Serves the mock registry with an `ETag`, responding to a matching `If-None-Match` with
+    /// `304 Not Modified`. Counts the number of times the registry file was downloaded in full.
+    fn serve_registry_with_etag(
+        downloads: Arc<AtomicUsize>,
+    ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
+        let (shutdown, join, url) = run_rest_api_on_open_port(vec![Resource::build(
+            "/registry.yaml",
+        )
+        .add_method(Method::Get, move |req, _| {
+            let etag = "\"registry-v1\"";
+            let not_modified = req
+                .headers()
+                .get("If-None-Match")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value == etag)
+                .unwrap_or(false);
+            Box::new(if not_modified {
+                HttpResponse::NotModified().finish().into_future()
+            } else {
+                downloads.fetch_add(1, Ordering::SeqCst);
+                HttpResponse::Ok()
+                    .header("ETag", etag)
+                    .body(
+                        serde_yaml::to_vec(&mock_registry())
+                            .expect("Failed to serialize registry file"),
+                    )
+                    .into_future()
+            })
+        })]);
+
+        (shutdown, join, format!("http://{}/registry.yaml", url))
+    }
+
     fn run_rest_api_on_open_port(
         resources: Vec<Resource>,
     ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
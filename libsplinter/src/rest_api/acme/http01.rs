@@ -0,0 +1,153 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A minimal HTTP responder for ACME HTTP-01 challenges.
+//!
+//! The responder only runs while a challenge is being validated, and answers requests for
+//! `/.well-known/acme-challenge/<token>` with the challenge's key authorization. All other
+//! requests receive a 404.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::InternalError;
+
+const CHALLENGE_PATH_PREFIX: &str = "/.well-known/acme-challenge/";
+
+pub struct Http01Responder {
+    running: Arc<AtomicBool>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl Http01Responder {
+    /// Starts serving the key authorization `proof` for `token` on the given bind address.
+    pub fn start(bind: &str, token: String, proof: String) -> Result<Self, InternalError> {
+        let listener = TcpListener::bind(bind).map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Unable to bind ACME HTTP-01 responder to {}", bind),
+            )
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let path = format!("{}{}", CHALLENGE_PATH_PREFIX, token);
+
+        let join_handle = thread::Builder::new()
+            .name("ACME HTTP-01 Responder".into())
+            .spawn(move || {
+                while thread_running.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            if let Err(err) = respond(stream, &path, &proof) {
+                                debug!("Unable to respond to ACME HTTP-01 request: {}", err);
+                            }
+                        }
+                        Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(err) => {
+                            debug!("ACME HTTP-01 responder accept failed: {}", err);
+                        }
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(Self {
+            running,
+            join_handle: Some(join_handle),
+        })
+    }
+
+    /// Stops the responder and waits for its thread to exit.
+    pub fn shutdown(mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(join_handle) = self.join_handle.take() {
+            if join_handle.join().is_err() {
+                error!("ACME HTTP-01 responder thread panicked");
+            }
+        }
+    }
+}
+
+fn respond(stream: TcpStream, path: &str, proof: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(request_path)) if request_path == path => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            proof.len(),
+            proof
+        ),
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".into(),
+    };
+
+    (&stream).write_all(response.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    /// Verify that the responder returns the proof for the challenge path and a 404 for any
+    /// other path.
+    #[test]
+    fn serve_challenge() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+        let bind = listener
+            .local_addr()
+            .expect("Failed to get address")
+            .to_string();
+        drop(listener);
+
+        let responder = Http01Responder::start(&bind, "token".into(), "token.proof".into())
+            .expect("Failed to start responder");
+
+        let response = get(&bind, "/.well-known/acme-challenge/token");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("token.proof"));
+
+        let response = get(&bind, "/.well-known/acme-challenge/other");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+
+        responder.shutdown();
+    }
+
+    fn get(bind: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(bind).expect("Failed to connect");
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path)
+            .expect("Failed to write request");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("Failed to read response");
+        response
+    }
+}
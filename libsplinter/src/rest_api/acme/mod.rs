@@ -0,0 +1,490 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Automatic certificate management for the REST API using the ACME protocol (RFC 8555), as
+//! implemented by certificate authorities such as Let's Encrypt.
+//!
+//! The `AcmeCertificateManager` obtains a certificate for the REST API's public host names and
+//! stores it, with its private key, in a storage directory. A background renewal thread
+//! periodically checks the certificate and, when it is close to expiring, obtains a new one and
+//! loads it into the running REST API using a [`TlsCertificateReloader`].
+//!
+//! Domain ownership can be proven using either the HTTP-01 challenge, for which the manager
+//! temporarily serves the challenge response over plain HTTP (normally on port 80), or the DNS-01
+//! challenge, for which an external hook command is run to publish a DNS TXT record.
+//!
+//! [`TlsCertificateReloader`]: ../struct.TlsCertificateReloader.html
+
+mod http01;
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use acme_lib::persist::FilePersist;
+use acme_lib::{create_p384_key, Directory, DirectoryUrl};
+use openssl::asn1::Asn1Time;
+use openssl::x509::X509;
+
+use crate::error::{InternalError, InvalidStateError};
+#[cfg(feature = "scheduler")]
+use crate::scheduler::{Job, Scheduler};
+
+use super::TlsCertificateReloader;
+
+use self::http01::Http01Responder;
+
+/// The Let's Encrypt production directory.
+pub const LETS_ENCRYPT_DIRECTORY_URL: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+const DEFAULT_HTTP_CHALLENGE_BIND: &str = "0.0.0.0:80";
+const DEFAULT_RENEW_BEFORE_DAYS: i32 = 30;
+const DEFAULT_DNS_PROPAGATION_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+// How long to wait for the ACME server to validate a challenge or issue a certificate, in
+// milliseconds
+const ACME_POLL_INTERVAL_MS: u64 = 5000;
+
+/// The method used to prove control of the certificate's domains.
+#[derive(Clone, Debug)]
+pub enum AcmeChallenge {
+    /// Serve the challenge response over plain HTTP at `bind`, which must be reachable as port 80
+    /// of each domain.
+    Http01 { bind: String },
+    /// Publish the challenge response as a DNS TXT record using an external hook command.
+    ///
+    /// The hook is run as `<hook> present <domain> <txt-value>` before validation and
+    /// `<hook> cleanup <domain> <txt-value>` afterwards. The record must be published at
+    /// `_acme-challenge.<domain>`. Validation is requested after `propagation_delay` has elapsed.
+    Dns01 {
+        hook: String,
+        propagation_delay: Duration,
+    },
+}
+
+impl Default for AcmeChallenge {
+    fn default() -> Self {
+        AcmeChallenge::Http01 {
+            bind: DEFAULT_HTTP_CHALLENGE_BIND.into(),
+        }
+    }
+}
+
+/// Builds an `AcmeCertificateManager`.
+#[derive(Default)]
+pub struct AcmeCertificateManagerBuilder {
+    domains: Vec<String>,
+    contact_email: Option<String>,
+    directory_url: Option<String>,
+    challenge: Option<AcmeChallenge>,
+    storage_dir: Option<PathBuf>,
+    renew_before_days: Option<i32>,
+}
+
+impl AcmeCertificateManagerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the domains the certificate is issued for. The first domain is used as the
+    /// certificate's common name.
+    pub fn with_domains(mut self, domains: Vec<String>) -> Self {
+        self.domains = domains;
+        self
+    }
+
+    /// Sets the contact email address for the ACME account.
+    pub fn with_contact_email(mut self, contact_email: String) -> Self {
+        self.contact_email = Some(contact_email);
+        self
+    }
+
+    /// Sets the ACME directory URL; defaults to the Let's Encrypt production directory.
+    pub fn with_directory_url(mut self, directory_url: String) -> Self {
+        self.directory_url = Some(directory_url);
+        self
+    }
+
+    /// Sets the challenge type; defaults to HTTP-01 on `0.0.0.0:80`.
+    pub fn with_challenge(mut self, challenge: AcmeChallenge) -> Self {
+        self.challenge = Some(challenge);
+        self
+    }
+
+    /// Sets the directory where the ACME account, certificate, and private key are stored.
+    pub fn with_storage_dir(mut self, storage_dir: PathBuf) -> Self {
+        self.storage_dir = Some(storage_dir);
+        self
+    }
+
+    /// Sets how many days before expiration the certificate is renewed; defaults to 30.
+    pub fn with_renew_before_days(mut self, renew_before_days: i32) -> Self {
+        self.renew_before_days = Some(renew_before_days);
+        self
+    }
+
+    pub fn build(self) -> Result<AcmeCertificateManager, InvalidStateError> {
+        if self.domains.is_empty() {
+            return Err(InvalidStateError::with_message(
+                "At least one domain is required to obtain an ACME certificate".into(),
+            ));
+        }
+
+        let contact_email = self.contact_email.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A contact email is required to obtain an ACME certificate".into(),
+            )
+        })?;
+
+        let storage_dir = self.storage_dir.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A storage directory is required to obtain an ACME certificate".into(),
+            )
+        })?;
+
+        Ok(AcmeCertificateManager {
+            domains: self.domains,
+            contact_email,
+            directory_url: self
+                .directory_url
+                .unwrap_or_else(|| LETS_ENCRYPT_DIRECTORY_URL.into()),
+            challenge: self.challenge.unwrap_or_default(),
+            storage_dir,
+            renew_before_days: self.renew_before_days.unwrap_or(DEFAULT_RENEW_BEFORE_DAYS),
+        })
+    }
+}
+
+/// Obtains and renews the REST API's certificate from an ACME certificate authority.
+#[derive(Clone)]
+pub struct AcmeCertificateManager {
+    domains: Vec<String>,
+    contact_email: String,
+    directory_url: String,
+    challenge: AcmeChallenge,
+    storage_dir: PathBuf,
+    renew_before_days: i32,
+}
+
+impl AcmeCertificateManager {
+    /// Returns the path of the certificate chain file.
+    pub fn cert_path(&self) -> PathBuf {
+        self.storage_dir.join(format!("{}.crt", self.domains[0]))
+    }
+
+    /// Returns the path of the private key file.
+    pub fn key_path(&self) -> PathBuf {
+        self.storage_dir.join(format!("{}.key", self.domains[0]))
+    }
+
+    /// Obtains a new certificate if there is no stored certificate, or if the stored certificate
+    /// expires within the renewal period.
+    ///
+    /// Returns `true` if a new certificate was obtained.
+    pub fn ensure_certificate(&self) -> Result<bool, InternalError> {
+        match self.days_remaining()? {
+            Some(days) if days > self.renew_before_days => {
+                debug!(
+                    "ACME certificate for {} expires in {} days; not renewing",
+                    self.domains[0], days
+                );
+                Ok(false)
+            }
+            Some(days) => {
+                info!(
+                    "ACME certificate for {} expires in {} days; renewing",
+                    self.domains[0], days
+                );
+                self.obtain_certificate()?;
+                Ok(true)
+            }
+            None => {
+                info!("Obtaining ACME certificate for {}", self.domains.join(", "));
+                self.obtain_certificate()?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Starts a background thread that periodically renews the certificate and loads renewed
+    /// certificates into the REST API using the given reloader.
+    pub fn start_renewal(
+        self,
+        reloader: TlsCertificateReloader,
+    ) -> Result<AcmeRenewalShutdownHandle, InternalError> {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+
+        thread::Builder::new()
+            .name("ACME Certificate Renewal".into())
+            .spawn(move || {
+                let mut last_check = Instant::now();
+                while thread_running.load(Ordering::SeqCst) {
+                    // Sleep in short intervals so shutdown is not delayed by the check interval
+                    thread::sleep(Duration::from_secs(1));
+                    if last_check.elapsed() < DEFAULT_RENEWAL_CHECK_INTERVAL {
+                        continue;
+                    }
+                    last_check = Instant::now();
+
+                    match self.ensure_certificate() {
+                        Ok(true) => {
+                            if let Err(err) = reloader.reload(
+                                &self.cert_path().to_string_lossy(),
+                                &self.key_path().to_string_lossy(),
+                            ) {
+                                error!("Unable to load renewed ACME certificate: {}", err);
+                            }
+                        }
+                        Ok(false) => (),
+                        Err(err) => error!(
+                            "Unable to renew ACME certificate; will retry later: {}",
+                            err
+                        ),
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(AcmeRenewalShutdownHandle { running })
+    }
+
+    /// Registers a job with the given scheduler that periodically renews the certificate and
+    /// loads renewed certificates into the REST API using the given reloader. This is used in
+    /// place of `start_renewal`.
+    #[cfg(feature = "scheduler")]
+    pub fn schedule_renewal(
+        self,
+        reloader: TlsCertificateReloader,
+        scheduler: &Scheduler,
+    ) -> Result<(), InternalError> {
+        let job = Job::new("acme-renewal", DEFAULT_RENEWAL_CHECK_INTERVAL, move || {
+            if self.ensure_certificate()? {
+                reloader.reload(
+                    &self.cert_path().to_string_lossy(),
+                    &self.key_path().to_string_lossy(),
+                )?;
+            }
+            Ok(())
+        });
+
+        scheduler
+            .register(job)
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+
+    fn days_remaining(&self) -> Result<Option<i32>, InternalError> {
+        let cert_path = self.cert_path();
+        if !cert_path.exists() || !self.key_path().exists() {
+            return Ok(None);
+        }
+
+        let cert = fs::read(&cert_path)
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+            .and_then(|pem| {
+                X509::from_pem(&pem).map_err(|err| InternalError::from_source(Box::new(err)))
+            })?;
+
+        Asn1Time::days_from_now(0)
+            .and_then(|now| now.diff(cert.not_after()))
+            .map(|diff| Some(diff.days))
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+
+    fn obtain_certificate(&self) -> Result<(), InternalError> {
+        fs::create_dir_all(&self.storage_dir)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let persist = FilePersist::new(&self.storage_dir.join("account"));
+        fs::create_dir_all(&self.storage_dir.join("account"))
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let directory = Directory::from_url(persist, DirectoryUrl::Other(&self.directory_url))
+            .map_err(acme_error)?;
+        let account = directory.account(&self.contact_email).map_err(acme_error)?;
+
+        let alt_names = self.domains[1..]
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let mut order = account
+            .new_order(&self.domains[0], &alt_names)
+            .map_err(acme_error)?;
+
+        let csr_order = loop {
+            if let Some(csr_order) = order.confirm_validations() {
+                break csr_order;
+            }
+
+            let authorizations = order.authorizations().map_err(acme_error)?;
+            for authorization in authorizations.iter().filter(|auth| auth.need_challenge()) {
+                let domain = authorization.domain_name();
+                match &self.challenge {
+                    AcmeChallenge::Http01 { bind } => {
+                        let challenge = authorization.http_challenge();
+                        let responder = Http01Responder::start(
+                            bind,
+                            challenge.http_token().to_string(),
+                            challenge.http_proof(),
+                        )?;
+                        let result = challenge.validate(ACME_POLL_INTERVAL_MS);
+                        responder.shutdown();
+                        result.map_err(acme_error)?;
+                    }
+                    AcmeChallenge::Dns01 {
+                        hook,
+                        propagation_delay,
+                    } => {
+                        let challenge = authorization.dns_challenge();
+                        let proof = challenge.dns_proof();
+                        run_dns_hook(hook, "present", domain, &proof)?;
+                        thread::sleep(*propagation_delay);
+                        let result = challenge.validate(ACME_POLL_INTERVAL_MS);
+                        if let Err(err) = run_dns_hook(hook, "cleanup", domain, &proof) {
+                            warn!("Unable to clean up ACME DNS record: {}", err);
+                        }
+                        result.map_err(acme_error)?;
+                    }
+                }
+            }
+
+            order.refresh().map_err(acme_error)?;
+        };
+
+        let cert_order = csr_order
+            .finalize_pkey(create_p384_key(), ACME_POLL_INTERVAL_MS)
+            .map_err(acme_error)?;
+        let cert = cert_order.download_and_save_cert().map_err(acme_error)?;
+
+        // Write the key first; the certificate's presence indicates a complete pair
+        write_pem(&self.key_path(), cert.private_key().as_bytes(), 0o600)?;
+        write_pem(&self.cert_path(), cert.certificate().as_bytes(), 0o644)?;
+
+        info!(
+            "Obtained ACME certificate for {}, valid for {} days",
+            self.domains.join(", "),
+            cert.valid_days_left()
+        );
+
+        Ok(())
+    }
+}
+
+/// Handle for stopping the ACME certificate renewal thread.
+#[derive(Clone)]
+pub struct AcmeRenewalShutdownHandle {
+    running: Arc<AtomicBool>,
+}
+
+impl AcmeRenewalShutdownHandle {
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst)
+    }
+}
+
+/// Returns the default DNS propagation delay used for DNS-01 challenges.
+pub fn default_dns_propagation_delay() -> Duration {
+    DEFAULT_DNS_PROPAGATION_DELAY
+}
+
+fn run_dns_hook(hook: &str, action: &str, domain: &str, proof: &str) -> Result<(), InternalError> {
+    let status = Command::new(hook)
+        .args(&[action, domain, proof])
+        .status()
+        .map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Unable to run ACME DNS hook {}", hook),
+            )
+        })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(InternalError::with_message(format!(
+            "ACME DNS hook {} {} for {} failed: {}",
+            hook, action, domain, status
+        )))
+    }
+}
+
+/// Writes the file to a temporary path and renames it, so the final file is never partially
+/// written.
+fn write_pem(path: &Path, bytes: &[u8], mode: u32) -> Result<(), InternalError> {
+    let temp_path = path.with_extension("new");
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(&temp_path)
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    file.write_all(bytes)
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    fs::rename(&temp_path, path).map_err(|err| InternalError::from_source(Box::new(err)))
+}
+
+fn acme_error(err: acme_lib::Error) -> InternalError {
+    InternalError::with_message(format!("ACME request failed: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the builder requires domains, a contact email, and a storage directory, and
+    /// that the certificate and key paths are named after the first domain.
+    #[test]
+    fn builder() {
+        assert!(AcmeCertificateManagerBuilder::new()
+            .with_contact_email("admin@example.com".into())
+            .with_storage_dir(PathBuf::from("/tmp"))
+            .build()
+            .is_err());
+        assert!(AcmeCertificateManagerBuilder::new()
+            .with_domains(vec!["node.example.com".into()])
+            .with_storage_dir(PathBuf::from("/tmp"))
+            .build()
+            .is_err());
+        assert!(AcmeCertificateManagerBuilder::new()
+            .with_domains(vec!["node.example.com".into()])
+            .with_contact_email("admin@example.com".into())
+            .build()
+            .is_err());
+
+        let manager = AcmeCertificateManagerBuilder::new()
+            .with_domains(vec!["node.example.com".into(), "api.example.com".into()])
+            .with_contact_email("admin@example.com".into())
+            .with_storage_dir(PathBuf::from("/var/lib/splinter/acme"))
+            .build()
+            .expect("Failed to build manager");
+
+        assert_eq!(
+            manager.cert_path(),
+            PathBuf::from("/var/lib/splinter/acme/node.example.com.crt")
+        );
+        assert_eq!(
+            manager.key_path(),
+            PathBuf::from("/var/lib/splinter/acme/node.example.com.key")
+        );
+        assert_eq!(manager.directory_url, LETS_ENCRYPT_DIRECTORY_URL);
+    }
+}
@@ -32,6 +32,8 @@ use futures::{
 use crate::error::InternalError;
 use crate::rest_api::ErrorResponse;
 
+#[cfg(feature = "rest-api-circuit-scopes")]
+use super::circuit_scope::CircuitScopeProvider;
 use super::{
     authorize, identity::IdentityProvider, AuthorizationHeader, AuthorizationMapping,
     AuthorizationResult,
@@ -42,6 +44,8 @@ use super::{
 pub struct Authorization {
     identity_providers: Vec<Box<dyn IdentityProvider>>,
     identity_extensions: Vec<Arc<IdentityExtension>>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_provider: Option<Box<dyn CircuitScopeProvider>>,
 }
 
 /// This is a wrapper to avoid multiple generic types.
@@ -86,6 +90,8 @@ impl Authorization {
         Self {
             identity_providers,
             identity_extensions: Vec::new(),
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_provider: None,
         }
     }
 
@@ -100,6 +106,17 @@ impl Authorization {
 
         self
     }
+
+    /// Add a circuit scope provider, which determines the `CircuitScope` that is added to the
+    /// requests of authorized clients.
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    pub fn with_circuit_scope_provider(
+        mut self,
+        circuit_scope_provider: Box<dyn CircuitScopeProvider>,
+    ) -> Self {
+        self.circuit_scope_provider = Some(circuit_scope_provider);
+        self
+    }
 }
 
 impl<S, B> Transform<S> for Authorization
@@ -119,6 +136,8 @@ where
         ok(AuthorizationMiddleware {
             identity_providers: self.identity_providers.clone(),
             identity_extensions: self.identity_extensions.clone(),
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_provider: self.circuit_scope_provider.clone(),
             service,
         })
     }
@@ -128,6 +147,8 @@ where
 pub struct AuthorizationMiddleware<S> {
     identity_providers: Vec<Box<dyn IdentityProvider>>,
     identity_extensions: Vec<Arc<IdentityExtension>>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_provider: Option<Box<dyn CircuitScopeProvider>>,
     service: S,
 }
 
@@ -200,6 +221,48 @@ where
                         );
                     }
                 }
+
+                #[cfg(feature = "rest-api-circuit-scopes")]
+                {
+                    if let Some(circuit_scope_provider) = &self.circuit_scope_provider {
+                        match circuit_scope_provider.get_circuit_scope(&identity) {
+                            Ok(scope) => {
+                                req.extensions_mut().insert(scope);
+                            }
+                            Err(err) => {
+                                error!("Unable to get circuit scope of {}: {}", identity, err);
+                                return Box::new(
+                                    req.into_response(
+                                        HttpResponse::InternalServerError()
+                                            .json(ErrorResponse::internal_error())
+                                            .into_body(),
+                                    )
+                                    .into_future(),
+                                );
+                            }
+                        }
+
+                        #[cfg(feature = "tenancy")]
+                        match circuit_scope_provider.get_tenant_scope(&identity) {
+                            Ok(Some(scope)) => {
+                                req.extensions_mut().insert(scope);
+                            }
+                            Ok(None) => (),
+                            Err(err) => {
+                                error!("Unable to get tenant scope of {}: {}", identity, err);
+                                return Box::new(
+                                    req.into_response(
+                                        HttpResponse::InternalServerError()
+                                            .json(ErrorResponse::internal_error())
+                                            .into_body(),
+                                    )
+                                    .into_future(),
+                                );
+                            }
+                        }
+                    }
+                }
+
                 debug!("Authenticated user {}", identity);
             }
             AuthorizationResult::NoAuthorizationNecessary => {}
@@ -304,6 +367,42 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    /// Verifies that the authorization middleware adds the circuit scope of the authorized
+    /// identity to the request.
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    #[test]
+    fn auth_middleware_circuit_scope() {
+        use crate::rest_api::auth::circuit_scope::{CircuitScope, RoleCircuitScopeProvider};
+
+        let auth_middleware = Authorization::new(vec![Box::new(AlwaysAcceptIdentityProvider)])
+            .with_circuit_scope_provider(Box::new(
+                RoleCircuitScopeProvider::new()
+                    .with_role("tenant", vec!["circuit".into()])
+                    .with_assignment("identity", "tenant"),
+            ));
+
+        let mut app = test::init_service(App::new().wrap(auth_middleware).route(
+            "/",
+            web::get().to(|req: HttpRequest| {
+                // Verify that the identity's scope was added to the request extensions
+                let expected_scope =
+                    CircuitScope::Circuits(vec!["circuit".to_string()].into_iter().collect());
+                if req.extensions().get() == Some(&expected_scope) {
+                    HttpResponse::Ok()
+                } else {
+                    HttpResponse::InternalServerError()
+                }
+            }),
+        ));
+
+        let req = test::TestRequest::with_uri("/")
+            .header("Authorization", "test")
+            .to_request();
+        let resp = test::block_on(app.call(req)).unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
     /// An identity provider that always returns `Ok(Some("identity"))`
     #[derive(Clone)]
     struct AlwaysAcceptIdentityProvider;
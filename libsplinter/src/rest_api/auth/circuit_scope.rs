@@ -0,0 +1,182 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Circuit-scoped authorization, which limits the circuits that an authenticated client may see
+//! and use through the REST API.
+//!
+//! A role defines an allowlist of circuits, and roles are assigned to identities. When a client is
+//! authorized, its [`CircuitScope`] is added to the request so that circuit-specific endpoints can
+//! filter or reject requests for circuits outside of the scope.
+//!
+//! [`CircuitScope`]: enum.CircuitScope.html
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::InternalError;
+#[cfg(feature = "tenancy")]
+use crate::tenant::TenantScope;
+
+/// The circuit ID that, when included in a role's allowlist, permits every circuit
+pub const ANY_CIRCUIT: &str = "*";
+
+/// The circuits that a client is permitted to access
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitScope {
+    /// The client may access any circuit
+    Unrestricted,
+    /// The client may only access the given circuits
+    Circuits(BTreeSet<String>),
+}
+
+impl CircuitScope {
+    /// Returns whether or not the circuit with the given ID is in the scope.
+    pub fn is_permitted(&self, circuit_id: &str) -> bool {
+        match self {
+            CircuitScope::Unrestricted => true,
+            CircuitScope::Circuits(circuits) => circuits.contains(circuit_id),
+        }
+    }
+}
+
+/// A service that determines the circuit scope of an identity
+pub trait CircuitScopeProvider: Send + Sync {
+    /// Returns the circuit scope of the given identity, as returned by an identity provider.
+    fn get_circuit_scope(&self, identity: &str) -> Result<CircuitScope, InternalError>;
+
+    /// Returns the tenant scope of the given identity, if the provider isolates tenants.
+    #[cfg(feature = "tenancy")]
+    fn get_tenant_scope(&self, _identity: &str) -> Result<Option<TenantScope>, InternalError> {
+        Ok(None)
+    }
+
+    /// Clone implementation for `CircuitScopeProvider`. The implementation of the `Clone` trait
+    /// for `Box<dyn CircuitScopeProvider>` calls this method.
+    fn clone_box(&self) -> Box<dyn CircuitScopeProvider>;
+}
+
+impl Clone for Box<dyn CircuitScopeProvider> {
+    fn clone(&self) -> Box<dyn CircuitScopeProvider> {
+        self.clone_box()
+    }
+}
+
+/// A `CircuitScopeProvider` backed by a fixed set of roles and role assignments.
+///
+/// An identity with no role assignments is not restricted; an identity with one or more roles may
+/// only access the circuits in the union of its roles' allowlists.
+#[derive(Clone, Default)]
+pub struct RoleCircuitScopeProvider {
+    roles: HashMap<String, BTreeSet<String>>,
+    assignments: HashMap<String, Vec<String>>,
+}
+
+impl RoleCircuitScopeProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a role with the given circuit allowlist; an allowlist containing `"*"` permits every
+    /// circuit.
+    pub fn with_role(mut self, role: &str, circuits: Vec<String>) -> Self {
+        self.roles
+            .entry(role.to_string())
+            .or_default()
+            .extend(circuits);
+        self
+    }
+
+    /// Assigns the role to the given identity.
+    pub fn with_assignment(mut self, identity: &str, role: &str) -> Self {
+        self.assignments
+            .entry(identity.to_string())
+            .or_default()
+            .push(role.to_string());
+        self
+    }
+}
+
+impl CircuitScopeProvider for RoleCircuitScopeProvider {
+    fn get_circuit_scope(&self, identity: &str) -> Result<CircuitScope, InternalError> {
+        let roles = match self.assignments.get(identity) {
+            Some(roles) => roles,
+            None => return Ok(CircuitScope::Unrestricted),
+        };
+
+        let mut circuits = BTreeSet::new();
+        for role in roles {
+            match self.roles.get(role) {
+                Some(allowlist) if allowlist.contains(ANY_CIRCUIT) => {
+                    return Ok(CircuitScope::Unrestricted)
+                }
+                Some(allowlist) => circuits.extend(allowlist.iter().cloned()),
+                None => warn!(
+                    "Identity {} is assigned to unknown role {}; ignoring",
+                    identity, role
+                ),
+            }
+        }
+
+        Ok(CircuitScope::Circuits(circuits))
+    }
+
+    fn clone_box(&self) -> Box<dyn CircuitScopeProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifies that an identity without any role assignments is not restricted.
+    #[test]
+    fn unassigned_identity_is_unrestricted() {
+        let provider =
+            RoleCircuitScopeProvider::new().with_role("tenant-a", vec!["circuit-a".into()]);
+
+        assert_eq!(
+            provider.get_circuit_scope("identity").unwrap(),
+            CircuitScope::Unrestricted
+        );
+    }
+
+    /// Verifies that an identity's scope is the union of its roles' allowlists, that unknown roles
+    /// are ignored, and that the `"*"` circuit lifts the restriction.
+    #[test]
+    fn role_allowlists() {
+        let provider = RoleCircuitScopeProvider::new()
+            .with_role("tenant-a", vec!["circuit-a".into()])
+            .with_role("tenant-b", vec!["circuit-b".into(), "circuit-c".into()])
+            .with_role("operator", vec![ANY_CIRCUIT.into()])
+            .with_assignment("alice", "tenant-a")
+            .with_assignment("alice", "tenant-b")
+            .with_assignment("bob", "unknown")
+            .with_assignment("carol", "tenant-a")
+            .with_assignment("carol", "operator");
+
+        let scope = provider.get_circuit_scope("alice").unwrap();
+        assert!(scope.is_permitted("circuit-a"));
+        assert!(scope.is_permitted("circuit-c"));
+        assert!(!scope.is_permitted("circuit-d"));
+
+        let scope = provider.get_circuit_scope("bob").unwrap();
+        assert_eq!(scope, CircuitScope::Circuits(BTreeSet::new()));
+        assert!(!scope.is_permitted("circuit-a"));
+
+        assert_eq!(
+            provider.get_circuit_scope("carol").unwrap(),
+            CircuitScope::Unrestricted
+        );
+    }
+}
@@ -15,14 +15,23 @@
 //! An identity provider that extracts the public key from a Cylinder JWT
 
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "biome-key-expiration")]
+use std::time::{Duration, SystemTime};
 
 use cylinder::{jwt::JsonWebTokenParser, Verifier};
 
+#[cfg(feature = "biome-key-expiration")]
+use crate::biome::{key_management::Key, KeyStore};
 use crate::error::InternalError;
 use crate::rest_api::auth::{AuthorizationHeader, BearerToken};
 
 use super::IdentityProvider;
 
+/// The minimum time between updates of a key's last-used time, which keeps every request from
+/// writing to the key store
+#[cfg(feature = "biome-key-expiration")]
+const LAST_USED_RESOLUTION: Duration = Duration::from_secs(60);
+
 /// Extracts the public key from a Cylinder JWT
 ///
 /// This provider only accepts `AuthorizationHeader::Bearer(BearerToken::Cylinder(token))`
@@ -31,12 +40,27 @@ use super::IdentityProvider;
 pub struct CylinderKeyIdentityProvider {
     /// The verifier is wrapped in an `Arc<Mutex<_>>` to ensure this struct is `Sync`
     verifier: Arc<Mutex<Box<dyn Verifier>>>,
+    #[cfg(feature = "biome-key-expiration")]
+    key_store: Option<Arc<dyn KeyStore>>,
 }
 
 impl CylinderKeyIdentityProvider {
     /// Creates a new Cylinder key identity provider
     pub fn new(verifier: Arc<Mutex<Box<dyn Verifier>>>) -> Self {
-        Self { verifier }
+        Self {
+            verifier,
+            #[cfg(feature = "biome-key-expiration")]
+            key_store: None,
+        }
+    }
+
+    /// Checks the Biome keys with the token's public key when authenticating. The token is
+    /// rejected if all of the Biome keys with its public key have expired; otherwise, the time the
+    /// keys were used is recorded. Public keys that are not stored in Biome are not affected.
+    #[cfg(feature = "biome-key-expiration")]
+    pub fn with_key_store(mut self, key_store: Box<dyn KeyStore>) -> Self {
+        self.key_store = Some(key_store.into());
+        self
     }
 }
 
@@ -50,19 +74,63 @@ impl IdentityProvider for CylinderKeyIdentityProvider {
             _ => return Ok(None),
         };
 
-        Ok(
-            JsonWebTokenParser::new(&**self.verifier.lock().map_err(|_| {
-                InternalError::with_message(
-                    "Cylinder key identity provider's verifier lock poisoned".into(),
-                )
-            })?)
-            .parse(token)
-            .ok()
-            .map(|parsed_token| parsed_token.issuer().as_hex()),
-        )
+        let public_key = match JsonWebTokenParser::new(&**self.verifier.lock().map_err(|_| {
+            InternalError::with_message(
+                "Cylinder key identity provider's verifier lock poisoned".into(),
+            )
+        })?)
+        .parse(token)
+        {
+            Ok(parsed_token) => parsed_token.issuer().as_hex(),
+            Err(_) => return Ok(None),
+        };
+
+        #[cfg(feature = "biome-key-expiration")]
+        {
+            if let Some(key_store) = &self.key_store {
+                if !check_biome_keys(&**key_store, &public_key)? {
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(public_key))
     }
 
     fn clone_box(&self) -> Box<dyn IdentityProvider> {
         Box::new(self.clone())
     }
 }
+
+/// Returns `false` if the public key belongs to Biome keys that have all expired. If any of the
+/// keys is valid, the time they were used is recorded.
+#[cfg(feature = "biome-key-expiration")]
+fn check_biome_keys(key_store: &dyn KeyStore, public_key: &str) -> Result<bool, InternalError> {
+    let keys = key_store
+        .list_keys_with_public_key(public_key)
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+    if keys.is_empty() {
+        return Ok(true);
+    }
+
+    if keys.iter().all(Key::is_expired) {
+        debug!("Rejecting expired Biome key {}", public_key);
+        return Ok(false);
+    }
+
+    let now = SystemTime::now();
+    let recently_used = keys.iter().all(|key| {
+        key.last_used
+            .and_then(|last_used| now.duration_since(last_used).ok())
+            .map(|elapsed| elapsed < LAST_USED_RESOLUTION)
+            .unwrap_or(false)
+    });
+    if !recently_used {
+        key_store
+            .update_key_last_used(public_key, now)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    }
+
+    Ok(true)
+}
@@ -16,6 +16,8 @@
 
 #[cfg(feature = "rest-api-actix")]
 pub(crate) mod actix;
+#[cfg(feature = "rest-api-circuit-scopes")]
+pub mod circuit_scope;
 pub mod identity;
 
 use std::str::FromStr;
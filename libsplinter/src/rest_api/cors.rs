@@ -13,32 +13,191 @@
 // limitations under the License.
 
 //! Provides CORS support for the REST API
+use std::sync::{Arc, RwLock};
+
 use actix_web::dev::*;
 use actix_web::{
-    http::header, http::header::HeaderValue, http::Method, Error as ActixError, HttpResponse,
+    http::header, http::header::HeaderMap, http::header::HeaderValue, http::Method,
+    Error as ActixError, HttpResponse,
 };
 use futures::{
     future::{ok, FutureResult},
     Future, IntoFuture, Poll,
 };
 
+/// The origins, methods, and headers allowed for cross-origin requests, and whether credentials
+/// may be included in them.
+///
+/// By default, any method and any requested header is allowed, and credentials are not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorsPolicy {
+    allowed_origins: Vec<String>,
+    allowed_methods: Option<Vec<String>>,
+    allowed_headers: Option<Vec<String>>,
+    allow_credentials: bool,
+}
+
+impl CorsPolicy {
+    /// Creates a policy that allows the given domains. Use "*" to allow any domain.
+    pub fn new(allowed_origins: Vec<String>) -> Self {
+        CorsPolicy {
+            allowed_origins,
+            allowed_methods: None,
+            allowed_headers: None,
+            allow_credentials: false,
+        }
+    }
+
+    /// Restricts the methods that are allowed, such as "GET" or "POST".
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn with_allowed_methods(mut self, methods: Vec<String>) -> Self {
+        self.allowed_methods = Some(methods);
+        self
+    }
+
+    /// Restricts the request headers that are allowed.
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn with_allowed_headers(mut self, headers: Vec<String>) -> Self {
+        self.allowed_headers = Some(headers);
+        self
+    }
+
+    /// Sets whether requests may include credentials, such as cookies. This should not be
+    /// combined with the "*" domain.
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn with_allow_credentials(mut self, allow_credentials: bool) -> Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|domain| domain == "*" || origin.contains(domain.as_str()))
+    }
+
+    /// Adds the CORS response headers for an allowed request to `headers`.
+    fn add_headers(
+        &self,
+        headers: &mut HeaderMap,
+        origin: HeaderValue,
+        request_method: Option<HeaderValue>,
+        request_headers: Option<HeaderValue>,
+    ) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+
+        // Browsers treat "*" literally when credentials are allowed, so the requested method
+        // is echoed instead
+        let allow_methods = match &self.allowed_methods {
+            Some(methods) => join_header_values(methods),
+            None if self.allow_credentials => request_method,
+            None => Some(HeaderValue::from_static("*")),
+        };
+        if let Some(allow_methods) = allow_methods {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, allow_methods);
+        }
+
+        let allow_headers = match &self.allowed_headers {
+            Some(allowed_headers) => join_header_values(allowed_headers),
+            None => request_headers.or_else(|| Some(HeaderValue::from_static("*"))),
+        };
+        if let Some(allow_headers) = allow_headers {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+fn join_header_values(values: &[String]) -> Option<HeaderValue> {
+    match HeaderValue::from_str(&values.join(", ")) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            error!("Invalid CORS header values: {:?}", values);
+            None
+        }
+    }
+}
+
+/// The default policy, and the policies of route groups, by path prefix.
+struct CorsPolicies {
+    default: CorsPolicy,
+    routes: Vec<(String, CorsPolicy)>,
+}
+
+impl CorsPolicies {
+    /// Returns the policy of the longest route prefix that matches the path, or the default
+    /// policy if none match.
+    fn policy_for(&self, path: &str) -> &CorsPolicy {
+        self.routes
+            .iter()
+            .filter(|(prefix, _)| path_has_prefix(path, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Checks whether the path is the prefix, or is below it; "/oauth" matches "/oauth/login" but not
+/// "/oauthx".
+fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.starts_with(prefix)
+        && (path.len() == prefix.len() || path[prefix.len()..].starts_with('/'))
+}
+
 /// Configuration for CORS support
+///
+/// Clones of a `Cors` share the same configuration, so a clone may be used to replace the
+/// whitelist or route policies of a running REST API.
 #[derive(Clone)]
 pub struct Cors {
-    whitelist: Vec<String>,
+    policies: Arc<RwLock<CorsPolicies>>,
 }
 
 impl Cors {
     /// Initialize the CORS preflight check with a set of allowed domains.
     pub fn new(whitelist: Vec<String>) -> Self {
         debug!("Creating CORS with whitelist: {:?}", whitelist);
-        Cors { whitelist }
+        Cors {
+            policies: Arc::new(RwLock::new(CorsPolicies {
+                default: CorsPolicy::new(whitelist),
+                routes: vec![],
+            })),
+        }
     }
 
     /// Initialize the CORS preflight check with "*" domains.
     pub fn new_allow_any() -> Self {
         Cors::new(vec!["*".into()])
     }
+
+    /// Replaces the set of allowed domains. Use "*" to allow any domain.
+    pub fn set_whitelist(&self, whitelist: Vec<String>) {
+        debug!("Updating CORS whitelist: {:?}", whitelist);
+        match self.policies.write() {
+            Ok(mut policies) => policies.default = CorsPolicy::new(whitelist),
+            Err(_) => error!("Unable to update CORS whitelist: lock poisoned"),
+        }
+    }
+
+    /// Replaces the policies of route groups, given as pairs of a path prefix, such as "/oauth",
+    /// and the policy of the routes under it. Requests to routes that are not in a group use the
+    /// whitelist.
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn set_route_policies(&self, route_policies: Vec<(String, CorsPolicy)>) {
+        debug!("Updating CORS route policies: {:?}", route_policies);
+        match self.policies.write() {
+            Ok(mut policies) => policies.routes = route_policies,
+            Err(_) => error!("Unable to update CORS route policies: lock poisoned"),
+        }
+    }
 }
 
 impl<S, B> Transform<S> for Cors
@@ -57,7 +216,7 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ok(CorsMiddleware {
             service,
-            whitelist: self.whitelist.clone(),
+            policies: self.policies.clone(),
         })
     }
 }
@@ -65,7 +224,7 @@ where
 #[doc(hidden)]
 pub struct CorsMiddleware<S> {
     service: S,
-    whitelist: Vec<String>,
+    policies: Arc<RwLock<CorsPolicies>>,
 }
 
 impl<S, B> Service for CorsMiddleware<S>
@@ -92,50 +251,53 @@ where
 
         match (origin, origin_header) {
             (Ok(Some(origin)), Some(origin_header)) => {
+                let request_method = req
+                    .headers()
+                    .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+                    .cloned();
                 let request_headers = req
                     .headers()
                     .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
                     .cloned();
-                let allowed_origin = self
-                    .whitelist
-                    .iter()
-                    .any(|domain| domain == "*" || origin.contains(domain));
-                // This verifies if a client is making a preflight check with the OPTIONS
-                // http request method and the origin is allowed, the preflight check responds
-                // with a 200 OK status.
-                if allowed_origin && req.method() == Method::OPTIONS {
-                    debug!("Preflight check passed");
-                    let mut res = req.into_response(HttpResponse::Ok().finish().into_body());
-                    let headers = res.headers_mut();
-                    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin_header);
-                    headers.insert(
-                        header::ACCESS_CONTROL_ALLOW_METHODS,
-                        HeaderValue::from_static("*"),
-                    );
-                    headers.insert(
-                        header::ACCESS_CONTROL_ALLOW_HEADERS,
-                        request_headers.unwrap_or_else(|| HeaderValue::from_static("*")),
-                    );
-                    Box::new(res.into_future())
-                } else if allowed_origin {
-                    Box::new(self.service.call(req).map(move |mut res| {
-                        let headers = res.headers_mut();
-                        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin_header);
-                        headers.insert(
-                            header::ACCESS_CONTROL_ALLOW_METHODS,
-                            HeaderValue::from_static("*"),
+                let policy = match self.policies.read() {
+                    Ok(policies) => Some(policies.policy_for(req.path()).clone()),
+                    Err(_) => {
+                        error!("Unable to read CORS policies: lock poisoned");
+                        None
+                    }
+                };
+                match policy {
+                    // This verifies if a client is making a preflight check with the OPTIONS
+                    // http request method and the origin is allowed, the preflight check
+                    // responds with a 200 OK status.
+                    Some(policy)
+                        if policy.allows_origin(&origin) && req.method() == Method::OPTIONS =>
+                    {
+                        debug!("Preflight check passed");
+                        let mut res = req.into_response(HttpResponse::Ok().finish().into_body());
+                        policy.add_headers(
+                            res.headers_mut(),
+                            origin_header,
+                            request_method,
+                            request_headers,
                         );
-                        headers.insert(
-                            header::ACCESS_CONTROL_ALLOW_HEADERS,
-                            request_headers.unwrap_or_else(|| HeaderValue::from_static("*")),
-                        );
-                        res
-                    }))
-                } else {
-                    Box::new(
+                        Box::new(res.into_future())
+                    }
+                    Some(policy) if policy.allows_origin(&origin) => {
+                        Box::new(self.service.call(req).map(move |mut res| {
+                            policy.add_headers(
+                                res.headers_mut(),
+                                origin_header,
+                                request_method,
+                                request_headers,
+                            );
+                            res
+                        }))
+                    }
+                    _ => Box::new(
                         req.into_response(HttpResponse::PreconditionFailed().finish().into_body())
                             .into_future(),
-                    )
+                    ),
                 }
             }
             (Ok(Some(_)), None) => unreachable!(),
@@ -147,3 +309,30 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that requests use the policy of the longest route prefix that matches their path,
+    /// and the default policy otherwise.
+    #[test]
+    fn policy_for_path() {
+        let default = CorsPolicy::new(vec!["*".into()]);
+        let oauth = CorsPolicy::new(vec!["app.example.com".into()]);
+        let callback = CorsPolicy::new(vec!["idp.example.com".into()]);
+        let policies = CorsPolicies {
+            default: default.clone(),
+            routes: vec![
+                ("/oauth".into(), oauth.clone()),
+                ("/oauth/callback/".into(), callback.clone()),
+            ],
+        };
+
+        assert_eq!(policies.policy_for("/oauth"), &oauth);
+        assert_eq!(policies.policy_for("/oauth/login"), &oauth);
+        assert_eq!(policies.policy_for("/oauth/callback"), &callback);
+        assert_eq!(policies.policy_for("/oauthx"), &default);
+        assert_eq!(policies.policy_for("/admin/circuits"), &default);
+    }
+}
@@ -56,6 +56,8 @@
 //!     .run();
 //! ```
 
+#[cfg(feature = "rest-api-acme")]
+pub mod acme;
 #[cfg(feature = "auth")]
 pub mod auth;
 #[cfg(feature = "rest-api-cors")]
@@ -63,9 +65,13 @@ pub mod cors;
 mod errors;
 mod events;
 pub mod paging;
+#[cfg(feature = "rest-api-read-only")]
+pub mod read_only;
 mod response_models;
 pub mod secrets;
 pub mod sessions;
+#[cfg(feature = "https-bind")]
+mod tls;
 
 use actix_web::{
     error::ErrorBadRequest, http::header, middleware, web, App, Error as ActixError, HttpRequest,
@@ -85,9 +91,15 @@ use std::thread;
 
 #[cfg(all(feature = "auth", feature = "biome-credentials"))]
 use crate::biome::rest_api::BiomeRestResourceManager;
+#[cfg(all(
+    feature = "auth",
+    feature = "biome-key-expiration",
+    feature = "cylinder-jwt"
+))]
+use crate::biome::KeyStore;
 #[cfg(feature = "oauth")]
 use crate::biome::{rest_api::auth::GetUserByOAuthAuthorization, OAuthUserSessionStore};
-#[cfg(feature = "auth")]
+#[cfg(any(feature = "auth", all(feature = "https-bind", feature = "tls-policy")))]
 use crate::error::InvalidStateError;
 #[cfg(feature = "oauth")]
 use crate::oauth::rest_api::OAuthResourceProvider;
@@ -97,6 +109,10 @@ use crate::oauth::store::InflightOAuthRequestStore;
 use crate::oauth::GithubOAuthClientBuilder;
 #[cfg(feature = "oauth-openid")]
 use crate::oauth::OpenIdOAuthClientBuilder;
+#[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+use crate::transport::tls::TlsPolicy;
+#[cfg(feature = "rest-api-circuit-scopes")]
+use auth::circuit_scope::CircuitScopeProvider;
 #[cfg(all(feature = "auth", feature = "cylinder-jwt"))]
 use auth::identity::cylinder::CylinderKeyIdentityProvider;
 #[cfg(feature = "oauth")]
@@ -109,6 +125,8 @@ pub use errors::{RequestError, ResponseError, RestApiServerError};
 pub use events::{new_websocket_event_sender, EventSender};
 
 pub use response_models::ErrorResponse;
+#[cfg(feature = "https-bind")]
+pub use tls::TlsCertificateReloader;
 
 const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS
     .add(b' ')
@@ -547,15 +565,39 @@ impl std::fmt::Display for RestApiBind {
 pub struct RestApi {
     resources: Vec<Resource>,
     bind: RestApiBind,
+    #[cfg(feature = "https-bind")]
+    tls_reloader: Option<TlsCertificateReloader>,
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    tls_policy: Option<TlsPolicy>,
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    tls_client_ca_file: Option<String>,
     #[cfg(feature = "rest-api-cors")]
-    whitelist: Option<Vec<String>>,
+    cors: cors::Cors,
+    #[cfg(feature = "rest-api-read-only")]
+    read_only: read_only::ReadOnly,
     #[cfg(feature = "auth")]
     identity_providers: Vec<Box<dyn IdentityProvider>>,
     #[cfg(feature = "auth")]
     authorization_mappings: Vec<ConfigureAuthorizationMapping>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_provider: Option<Box<dyn CircuitScopeProvider>>,
 }
 
 impl RestApi {
+    /// Returns a handle to the REST API's CORS configuration, which can be used to replace the
+    /// whitelist while the REST API is running.
+    #[cfg(feature = "rest-api-cors")]
+    pub fn cors(&self) -> cors::Cors {
+        self.cors.clone()
+    }
+
+    /// Returns a handle to the REST API's read-only mode, which can be used to enable or disable
+    /// it while the REST API is running.
+    #[cfg(feature = "rest-api-read-only")]
+    pub fn read_only(&self) -> read_only::ReadOnly {
+        self.read_only.clone()
+    }
+
     pub fn run(
         self,
     ) -> Result<(RestApiShutdownHandle, thread::JoinHandle<()>), RestApiServerError> {
@@ -564,7 +606,9 @@ impl RestApi {
         let bind_config_for_err = self.bind.clone();
         let resources = self.resources;
         #[cfg(feature = "rest-api-cors")]
-        let whitelist = self.whitelist;
+        let cors = self.cors;
+        #[cfg(feature = "rest-api-read-only")]
+        let read_only = self.read_only;
         #[cfg(feature = "auth")]
         let mut authorization = Authorization::new(self.identity_providers.to_owned());
 
@@ -576,11 +620,12 @@ impl RestApi {
             }
         }
 
-        #[cfg(feature = "rest-api-cors")]
-        let cors = match &whitelist {
-            Some(list) => cors::Cors::new(list.to_vec()),
-            None => cors::Cors::new_allow_any(),
-        };
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        {
+            if let Some(circuit_scope_provider) = self.circuit_scope_provider {
+                authorization = authorization.with_circuit_scope_provider(circuit_scope_provider);
+            }
+        }
 
         #[cfg(feature = "https-bind")]
         let bind_info = match self.bind {
@@ -591,10 +636,24 @@ impl RestApi {
             } => {
                 let mut acceptor =
                     openssl::ssl::SslAcceptor::mozilla_modern(openssl::ssl::SslMethod::tls())?;
-                acceptor.set_private_key_file(key_path, openssl::ssl::SslFiletype::PEM)?;
+                acceptor.set_private_key_file(&key_path, openssl::ssl::SslFiletype::PEM)?;
                 acceptor.set_certificate_chain_file(&cert_path)?;
                 acceptor.check_private_key()?;
 
+                #[cfg(feature = "tls-policy")]
+                {
+                    if let Some(ca_file) = &self.tls_client_ca_file {
+                        acceptor.set_ca_file(ca_file)?;
+                    }
+                    if let Some(policy) = &self.tls_policy {
+                        policy.configure_acceptor(&mut acceptor)?;
+                    }
+                }
+
+                if let Some(tls_reloader) = &self.tls_reloader {
+                    tls_reloader.configure(&mut acceptor, &cert_path, &key_path)?;
+                }
+
                 (bind, Some(acceptor))
             }
             RestApiBind::Insecure(bind) => (bind, None),
@@ -613,6 +672,11 @@ impl RestApi {
                     #[cfg(feature = "rest-api-cors")]
                     let app = app.wrap(cors.clone());
 
+                    // Wrapped inside of authorization, so that unauthorized clients are refused
+                    // before they can learn whether the node is in read-only mode
+                    #[cfg(feature = "rest-api-read-only")]
+                    let app = app.wrap(read_only.clone());
+
                     #[cfg(feature = "auth")]
                     let app = app.wrap(authorization.clone());
 
@@ -715,13 +779,9 @@ impl RestApi {
 
         let resources = self.resources.to_owned();
         #[cfg(feature = "rest-api-cors")]
-        let whitelist = self.whitelist.to_owned();
-
-        #[cfg(feature = "rest-api-cors")]
-        let cors = match &whitelist {
-            Some(list) => cors::Cors::new(list.to_vec()),
-            None => cors::Cors::new_allow_any(),
-        };
+        let cors = self.cors.clone();
+        #[cfg(feature = "rest-api-read-only")]
+        let read_only = self.read_only.clone();
 
         let join_handle = thread::Builder::new()
             .name("SplinterDRestApi".into())
@@ -733,6 +793,9 @@ impl RestApi {
                     #[cfg(feature = "rest-api-cors")]
                     let app = app.wrap(cors.clone());
 
+                    #[cfg(feature = "rest-api-read-only")]
+                    let app = app.wrap(read_only.clone());
+
                     let mut app = app.wrap(middleware::Logger::default());
 
                     for resource in resources.clone() {
@@ -803,12 +866,24 @@ impl RestApi {
 pub struct RestApiBuilder {
     resources: Vec<Resource>,
     bind: Option<RestApiBind>,
+    #[cfg(feature = "https-bind")]
+    tls_reloader: Option<TlsCertificateReloader>,
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    tls_policy: Option<TlsPolicy>,
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    tls_client_ca_file: Option<String>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_route_policies: Vec<(String, cors::CorsPolicy)>,
+    #[cfg(feature = "rest-api-read-only")]
+    read_only: Option<read_only::ReadOnly>,
     #[cfg(feature = "auth")]
     auth_configs: Vec<AuthConfig>,
     #[cfg(feature = "auth")]
     authorization_mappings: Vec<ConfigureAuthorizationMapping>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_provider: Option<Box<dyn CircuitScopeProvider>>,
 }
 
 impl Default for RestApiBuilder {
@@ -816,12 +891,24 @@ impl Default for RestApiBuilder {
         Self {
             resources: Vec::new(),
             bind: None,
+            #[cfg(feature = "https-bind")]
+            tls_reloader: None,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_policy: None,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_client_ca_file: None,
             #[cfg(feature = "rest-api-cors")]
             whitelist: None,
+            #[cfg(feature = "rest-api-cors-policies")]
+            cors_route_policies: vec![],
+            #[cfg(feature = "rest-api-read-only")]
+            read_only: None,
             #[cfg(feature = "auth")]
             auth_configs: Vec::new(),
             #[cfg(feature = "auth")]
             authorization_mappings: vec![],
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_provider: None,
         }
     }
 }
@@ -843,6 +930,29 @@ impl RestApiBuilder {
         self
     }
 
+    /// Allows the certificate of a secure bind to be replaced while the REST API is running.
+    #[cfg(feature = "https-bind")]
+    pub fn with_tls_reloader(mut self, value: TlsCertificateReloader) -> Self {
+        self.tls_reloader = Some(value);
+        self
+    }
+
+    /// Sets the minimum TLS version, cipher suites and client certificate requirements of a
+    /// secure bind.
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    pub fn with_tls_policy(mut self, value: TlsPolicy) -> Self {
+        self.tls_policy = Some(value);
+        self
+    }
+
+    /// Sets the CA certificate file used to verify client certificates when the TLS policy
+    /// requests them.
+    #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+    pub fn with_tls_client_ca_file(mut self, value: String) -> Self {
+        self.tls_client_ca_file = Some(value);
+        self
+    }
+
     pub fn add_resource(mut self, value: Resource) -> Self {
         self.resources.push(value);
         self
@@ -859,6 +969,22 @@ impl RestApiBuilder {
         self
     }
 
+    /// Adds a CORS policy for the routes under the given path prefix, such as "/oauth", in place
+    /// of the whitelist.
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn with_cors_route_policy(mut self, path_prefix: &str, policy: cors::CorsPolicy) -> Self {
+        self.cors_route_policies
+            .push((path_prefix.to_string(), policy));
+        self
+    }
+
+    /// Sets the read-only mode configuration; without one, read-only mode is disabled.
+    #[cfg(feature = "rest-api-read-only")]
+    pub fn with_read_only(mut self, read_only: read_only::ReadOnly) -> Self {
+        self.read_only = Some(read_only);
+        self
+    }
+
     #[cfg(feature = "auth")]
     pub fn with_auth_configs(mut self, auth_configs: Vec<AuthConfig>) -> Self {
         self.auth_configs = auth_configs;
@@ -877,6 +1003,17 @@ impl RestApiBuilder {
         self
     }
 
+    /// Limits the circuits that authorized clients may access to the scopes determined by the
+    /// given provider.
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    pub fn with_circuit_scope_provider(
+        mut self,
+        circuit_scope_provider: Box<dyn CircuitScopeProvider>,
+    ) -> Self {
+        self.circuit_scope_provider = Some(circuit_scope_provider);
+        self
+    }
+
     // Allowing unused_mut because self must be mutable if feature `auth` is enabled
     #[allow(unused_mut)]
     pub fn build(mut self) -> Result<RestApi, RestApiServerError> {
@@ -884,6 +1021,24 @@ impl RestApiBuilder {
             .bind
             .ok_or_else(|| RestApiServerError::MissingField("bind".to_string()))?;
 
+        #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+        {
+            let requests_client_certs = self
+                .tls_policy
+                .as_ref()
+                .map(|policy| policy.requests_client_certs())
+                .unwrap_or(false);
+            if requests_client_certs && self.tls_client_ca_file.is_none() {
+                return Err(RestApiServerError::InvalidStateError(
+                    InvalidStateError::with_message(
+                        "REST API TLS policy requests client certificates, but no client CA \
+                        file has been configured"
+                            .to_string(),
+                    ),
+                ));
+            }
+        }
+
         #[cfg(feature = "auth")]
         let identity_providers = {
             if self.auth_configs.is_empty() {
@@ -914,10 +1069,19 @@ impl RestApiBuilder {
                             .append(&mut biome_resource_manager.resources());
                     }
                     #[cfg(feature = "cylinder-jwt")]
-                    AuthConfig::Cylinder { verifier } => {
-                        identity_providers.push(Box::new(CylinderKeyIdentityProvider::new(
-                            Arc::new(Mutex::new(verifier)),
-                        )));
+                    AuthConfig::Cylinder {
+                        verifier,
+                        #[cfg(feature = "biome-key-expiration")]
+                        key_store,
+                    } => {
+                        let identity_provider =
+                            CylinderKeyIdentityProvider::new(Arc::new(Mutex::new(verifier)));
+                        #[cfg(feature = "biome-key-expiration")]
+                        let identity_provider = match key_store {
+                            Some(key_store) => identity_provider.with_key_store(key_store),
+                            None => identity_provider,
+                        };
+                        identity_providers.push(Box::new(identity_provider));
                     }
                     #[cfg(feature = "oauth")]
                     AuthConfig::OAuth {
@@ -940,13 +1104,15 @@ impl RestApiBuilder {
                                 redirect_url,
                                 oauth_openid_url,
                                 inflight_request_store,
-                            } => OpenIdOAuthClientBuilder::new_azure()
-                                .with_discovery_url(oauth_openid_url)
-                                .with_client_id(client_id)
-                                .with_client_secret(client_secret)
-                                .with_redirect_url(redirect_url)
-                                .with_inflight_request_store(inflight_request_store)
-                                .build()?,
+                            } => with_openid_client_secret(
+                                OpenIdOAuthClientBuilder::new_azure()
+                                    .with_discovery_url(oauth_openid_url)
+                                    .with_client_id(client_id),
+                                client_secret,
+                            )
+                            .with_redirect_url(redirect_url)
+                            .with_inflight_request_store(inflight_request_store)
+                            .build()?,
                             #[cfg(feature = "oauth-github")]
                             OAuthConfig::GitHub {
                                 client_id,
@@ -965,12 +1131,13 @@ impl RestApiBuilder {
                                 client_secret,
                                 redirect_url,
                                 inflight_request_store,
-                            } => OpenIdOAuthClientBuilder::new_google()
-                                .with_client_id(client_id)
-                                .with_client_secret(client_secret)
-                                .with_redirect_url(redirect_url)
-                                .with_inflight_request_store(inflight_request_store)
-                                .build()?,
+                            } => with_openid_client_secret(
+                                OpenIdOAuthClientBuilder::new_google().with_client_id(client_id),
+                                client_secret,
+                            )
+                            .with_redirect_url(redirect_url)
+                            .with_inflight_request_store(inflight_request_store)
+                            .build()?,
                             #[cfg(feature = "oauth-openid")]
                             OAuthConfig::OpenId {
                                 client_id,
@@ -978,13 +1145,15 @@ impl RestApiBuilder {
                                 redirect_url,
                                 oauth_openid_url,
                                 inflight_request_store,
-                            } => OpenIdOAuthClientBuilder::new()
-                                .with_discovery_url(oauth_openid_url)
-                                .with_client_id(client_id)
-                                .with_client_secret(client_secret)
-                                .with_redirect_url(redirect_url)
-                                .with_inflight_request_store(inflight_request_store)
-                                .build()?,
+                            } => with_openid_client_secret(
+                                OpenIdOAuthClientBuilder::new()
+                                    .with_discovery_url(oauth_openid_url)
+                                    .with_client_id(client_id),
+                                client_secret,
+                            )
+                            .with_redirect_url(redirect_url)
+                            .with_inflight_request_store(inflight_request_store)
+                            .build()?,
                         };
 
                         // Add the configuration mapping for the Biome User value.
@@ -1017,15 +1186,35 @@ impl RestApiBuilder {
             identity_providers
         };
 
+        #[cfg(feature = "rest-api-cors")]
+        let cors = match self.whitelist {
+            Some(list) => cors::Cors::new(list),
+            None => cors::Cors::new_allow_any(),
+        };
+        #[cfg(feature = "rest-api-cors-policies")]
+        cors.set_route_policies(self.cors_route_policies);
+
         Ok(RestApi {
             bind,
+            #[cfg(feature = "https-bind")]
+            tls_reloader: self.tls_reloader,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_policy: self.tls_policy,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_client_ca_file: self.tls_client_ca_file,
             resources: self.resources,
             #[cfg(feature = "rest-api-cors")]
-            whitelist: self.whitelist,
+            cors,
+            #[cfg(feature = "rest-api-read-only")]
+            read_only: self
+                .read_only
+                .unwrap_or_else(|| read_only::ReadOnly::new(false)),
             #[cfg(feature = "auth")]
             identity_providers,
             #[cfg(feature = "auth")]
             authorization_mappings: self.authorization_mappings,
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_provider: self.circuit_scope_provider,
         })
     }
 
@@ -1042,15 +1231,35 @@ impl RestApiBuilder {
             insecure @ RestApiBind::Insecure(_) => insecure,
         };
 
+        #[cfg(feature = "rest-api-cors")]
+        let cors = match self.whitelist {
+            Some(list) => cors::Cors::new(list),
+            None => cors::Cors::new_allow_any(),
+        };
+        #[cfg(feature = "rest-api-cors-policies")]
+        cors.set_route_policies(self.cors_route_policies);
+
         Ok(RestApi {
             bind,
+            #[cfg(feature = "https-bind")]
+            tls_reloader: self.tls_reloader,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_policy: self.tls_policy,
+            #[cfg(all(feature = "https-bind", feature = "tls-policy"))]
+            tls_client_ca_file: self.tls_client_ca_file,
             resources: self.resources,
             #[cfg(feature = "rest-api-cors")]
-            whitelist: self.whitelist,
+            cors,
+            #[cfg(feature = "rest-api-read-only")]
+            read_only: self
+                .read_only
+                .unwrap_or_else(|| read_only::ReadOnly::new(false)),
             #[cfg(feature = "auth")]
             identity_providers: vec![],
             #[cfg(feature = "auth")]
             authorization_mappings: self.authorization_mappings,
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_provider: self.circuit_scope_provider,
         })
     }
 }
@@ -1069,6 +1278,10 @@ pub enum AuthConfig {
     Cylinder {
         /// The signature verifier used to validate Cylinder JWTs
         verifier: Box<dyn Verifier>,
+        /// The Biome key store; if set, JWTs signed with expired Biome keys are rejected and the
+        /// use of Biome keys is recorded
+        #[cfg(feature = "biome-key-expiration")]
+        key_store: Option<Box<dyn KeyStore>>,
     },
     /// OAuth authentication
     #[cfg(feature = "oauth")]
@@ -1095,8 +1308,9 @@ pub enum OAuthConfig {
     Azure {
         /// The client ID of the Azure OAuth app
         client_id: String,
-        /// The client secret of the Azure OAuth app
-        client_secret: String,
+        /// The client secret of the Azure OAuth app, or `None` if the app is registered as a
+        /// public client, whose code exchange is protected by PKCE alone
+        client_secret: Option<String>,
         /// The redirect URL that is configured for the Azure OAuth app
         redirect_url: String,
         /// The URL of the OpenID discovery document for the Azure OAuth app
@@ -1120,8 +1334,9 @@ pub enum OAuthConfig {
     Google {
         /// The client ID of the Google OAuth app
         client_id: String,
-        /// The client secret of the Google OAuth app
-        client_secret: String,
+        /// The client secret of the Google OAuth app, or `None` if the app is registered as a
+        /// public client, whose code exchange is protected by PKCE alone
+        client_secret: Option<String>,
         /// The redirect URL that is configured for the Google OAuth app
         redirect_url: String,
         /// The store for in-flight requests
@@ -1131,8 +1346,9 @@ pub enum OAuthConfig {
     OpenId {
         /// The client ID of the OpenId OAuth app
         client_id: String,
-        /// The client secret of the OpenId OAuth app
-        client_secret: String,
+        /// The client secret of the OpenId OAuth app, or `None` if the app is registered as a
+        /// public client, whose code exchange is protected by PKCE alone
+        client_secret: Option<String>,
         /// The redirect URL that is configured for the OpenId OAuth app
         redirect_url: String,
         /// The URL of the OpenID discovery document for the OpenId OAuth app
@@ -1142,6 +1358,19 @@ pub enum OAuthConfig {
     },
 }
 
+/// Sets the client secret of a confidential client, or configures a public client if there is no
+/// secret.
+#[cfg(feature = "oauth-openid")]
+fn with_openid_client_secret(
+    builder: OpenIdOAuthClientBuilder,
+    client_secret: Option<String>,
+) -> OpenIdOAuthClientBuilder {
+    match client_secret {
+        Some(client_secret) => builder.with_client_secret(client_secret),
+        None => builder.with_public_client(),
+    }
+}
+
 pub fn into_protobuf<M: Message>(
     payload: web::Payload,
 ) -> impl Future<Item = M, Error = ActixError> {
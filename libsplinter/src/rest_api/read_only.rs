@@ -0,0 +1,182 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides a read-only mode for the REST API
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use actix_web::dev::*;
+use actix_web::{http::Method, Error as ActixError, HttpResponse};
+use futures::{
+    future::{ok, FutureResult},
+    Future, IntoFuture, Poll,
+};
+
+use super::ErrorResponse;
+
+/// Configuration for the read-only mode of the REST API
+///
+/// While read-only mode is enabled, requests that may change state are refused with
+/// `403 Forbidden`. Requests with the `GET`, `HEAD` and `OPTIONS` methods are always allowed, as
+/// are requests to exempt paths.
+///
+/// Clones of a `ReadOnly` share the same switch, so a clone may be used to enable or disable
+/// read-only mode while the REST API is running.
+#[derive(Clone)]
+pub struct ReadOnly {
+    enabled: Arc<AtomicBool>,
+    exempt_paths: Vec<String>,
+}
+
+impl ReadOnly {
+    /// Creates the read-only mode configuration, initially enabled or disabled.
+    pub fn new(enabled: bool) -> Self {
+        ReadOnly {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            exempt_paths: vec![],
+        }
+    }
+
+    /// Allows requests to the given path, such as "/node/challenge", while read-only mode is
+    /// enabled. The path must match exactly.
+    pub fn with_exempt_path(mut self, path: &str) -> Self {
+        self.exempt_paths.push(path.to_string());
+        self
+    }
+
+    /// Returns whether read-only mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Enables or disables read-only mode.
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.swap(enabled, Ordering::SeqCst) != enabled {
+            if enabled {
+                warn!("Read-only mode enabled; requests that change state will be refused");
+            } else {
+                info!("Read-only mode disabled");
+            }
+        }
+    }
+
+    fn allows(&self, method: &Method, path: &str) -> bool {
+        !self.is_enabled()
+            || method == Method::GET
+            || method == Method::HEAD
+            || method == Method::OPTIONS
+            || self
+                .exempt_paths
+                .iter()
+                .any(|exempt_path| exempt_path == path)
+    }
+}
+
+impl<S, B> Transform<S> for ReadOnly
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = FutureResult<Self::Transform, Self::InitError>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ReadOnlyMiddleware {
+            service,
+            read_only: self.clone(),
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct ReadOnlyMiddleware<S> {
+    service: S,
+    read_only: ReadOnly,
+}
+
+impl<S, B> Service for ReadOnlyMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Future = Box<dyn Future<Item = Self::Response, Error = Self::Error>>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.service.poll_ready()
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        if self.read_only.allows(req.method(), req.path()) {
+            return Box::new(self.service.call(req));
+        }
+
+        debug!("Refusing {} {} in read-only mode", req.method(), req.path());
+        Box::new(
+            req.into_response(
+                HttpResponse::Forbidden()
+                    .json(ErrorResponse::forbidden("The node is in read-only mode"))
+                    .into_body(),
+            )
+            .into_future(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use actix_web::{http::StatusCode, test, web, App};
+
+    /// Verifies that requests that may change state are refused while read-only mode is enabled,
+    /// unless their path is exempt, and that they are allowed again once it is disabled.
+    #[test]
+    fn read_only_middleware() {
+        let read_only = ReadOnly::new(true).with_exempt_path("/exempt");
+        let mut app = test::init_service(
+            App::new()
+                .wrap(read_only.clone())
+                .route("/", web::get().to(|| HttpResponse::Ok()))
+                .route("/", web::post().to(|| HttpResponse::Ok()))
+                .route("/exempt", web::post().to(|| HttpResponse::Ok())),
+        );
+
+        let req = test::TestRequest::with_uri("/").to_request();
+        let resp = test::block_on(app.call(req)).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::post().uri("/").to_request();
+        let resp = test::block_on(app.call(req)).unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let req = test::TestRequest::post().uri("/exempt").to_request();
+        let resp = test::block_on(app.call(req)).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        read_only.set_enabled(false);
+
+        let req = test::TestRequest::post().uri("/").to_request();
+        let resp = test::block_on(app.call(req)).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for replacing the REST API's TLS certificate while the server is running.
+
+use std::sync::{Arc, RwLock};
+
+use openssl::error::ErrorStack;
+use openssl::ssl::{
+    ClientHelloResponse, SslAcceptor, SslAcceptorBuilder, SslContext, SslFiletype, SslMethod,
+};
+
+use crate::error::InternalError;
+
+use super::RestApiServerError;
+
+/// A handle for replacing the certificate used by a secure REST API bind.
+///
+/// When a reloader is provided to the `RestApiBuilder`, each new TLS connection uses the most
+/// recently loaded certificate and key. Existing connections are not affected by a reload.
+///
+/// The certificate is selected when the client hello message is received, so it applies to every
+/// handshake whether or not the client sends a server name indication (SNI).
+#[derive(Clone, Default)]
+pub struct TlsCertificateReloader {
+    context: Arc<RwLock<Option<SslContext>>>,
+}
+
+impl TlsCertificateReloader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the given certificate chain and private key, which will be used for all new
+    /// connections.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_path` - Path to the PEM-encoded certificate chain
+    /// * `key_path` - Path to the PEM-encoded private key
+    pub fn reload(&self, cert_path: &str, key_path: &str) -> Result<(), InternalError> {
+        let context = build_context(cert_path, key_path).map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                format!(
+                    "Unable to load REST API certificate {} and key {}",
+                    cert_path, key_path
+                ),
+            )
+        })?;
+
+        *self.context.write().map_err(|_| {
+            InternalError::with_message("TLS certificate reloader lock poisoned".into())
+        })? = Some(context);
+
+        info!("Reloaded REST API certificate {}", cert_path);
+
+        Ok(())
+    }
+
+    /// Loads the initial certificate and configures the acceptor to use the reloader's current
+    /// certificate for each new connection.
+    pub(super) fn configure(
+        &self,
+        acceptor: &mut SslAcceptorBuilder,
+        cert_path: &str,
+        key_path: &str,
+    ) -> Result<(), RestApiServerError> {
+        self.reload(cert_path, key_path)
+            .map_err(RestApiServerError::InternalError)?;
+
+        let context = self.context.clone();
+        acceptor.set_client_hello_callback(move |ssl, _| {
+            let context = context.read().map_err(|_| {
+                error!("TLS certificate reloader lock poisoned");
+                ErrorStack::get()
+            })?;
+            if let Some(context) = &*context {
+                ssl.set_ssl_context(context)?;
+            }
+            Ok(ClientHelloResponse::SUCCESS)
+        });
+
+        Ok(())
+    }
+}
+
+fn build_context(cert_path: &str, key_path: &str) -> Result<SslContext, ErrorStack> {
+    let mut acceptor = SslAcceptor::mozilla_modern(SslMethod::tls())?;
+    acceptor.set_private_key_file(key_path, SslFiletype::PEM)?;
+    acceptor.set_certificate_chain_file(cert_path)?;
+    acceptor.check_private_key()?;
+    Ok(acceptor.build().into_context())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::path::Path;
+    use std::thread;
+
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::ssl::{SslConnector, SslVerifyMode};
+    use openssl::x509::{X509NameBuilder, X509};
+    use tempdir::TempDir;
+
+    /// Verify that a client that does not send a server name receives the certificate loaded by
+    /// the most recent reload.
+    #[test]
+    fn reload_without_sni() {
+        let temp_dir = TempDir::new("tls_reload").expect("Failed to create temp dir");
+        let (first_cert, first_key, first_der) = write_certificate(temp_dir.path(), "first");
+        let (second_cert, second_key, second_der) = write_certificate(temp_dir.path(), "second");
+
+        let reloader = TlsCertificateReloader::new();
+        let mut acceptor =
+            SslAcceptor::mozilla_modern(SslMethod::tls()).expect("Failed to create acceptor");
+        acceptor
+            .set_private_key_file(&first_key, SslFiletype::PEM)
+            .expect("Failed to set key");
+        acceptor
+            .set_certificate_chain_file(&first_cert)
+            .expect("Failed to set certificate");
+        reloader
+            .configure(&mut acceptor, &first_cert, &first_key)
+            .expect("Failed to configure reloader");
+        let acceptor = acceptor.build();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let addr = listener.local_addr().expect("Failed to get address");
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    let _ = acceptor.accept(stream);
+                }
+            }
+        });
+
+        assert_eq!(first_der, peer_certificate(addr));
+
+        reloader
+            .reload(&second_cert, &second_key)
+            .expect("Failed to reload certificate");
+        assert_eq!(second_der, peer_certificate(addr));
+    }
+
+    /// Connects without SNI and returns the DER encoding of the server's certificate
+    fn peer_certificate(addr: SocketAddr) -> Vec<u8> {
+        let mut connector =
+            SslConnector::builder(SslMethod::tls()).expect("Failed to create connector");
+        connector.set_verify(SslVerifyMode::NONE);
+        let stream = TcpStream::connect(addr).expect("Failed to connect");
+        let stream = connector
+            .build()
+            .configure()
+            .expect("Failed to configure connection")
+            .use_server_name_indication(false)
+            .verify_hostname(false)
+            .connect("localhost", stream)
+            .expect("TLS handshake failed");
+
+        stream
+            .ssl()
+            .peer_certificate()
+            .expect("No peer certificate")
+            .to_der()
+            .expect("Failed to encode certificate")
+    }
+
+    /// Writes a self-signed certificate and its key to the directory, returning the paths of the
+    /// certificate and key and the DER encoding of the certificate
+    fn write_certificate(dir: &Path, name: &str) -> (String, String, Vec<u8>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).expect("Failed to generate key"))
+            .expect("Failed to create key");
+
+        let mut subject = X509NameBuilder::new().expect("Failed to create name");
+        subject
+            .append_entry_by_text("CN", name)
+            .expect("Failed to set common name");
+        let subject = subject.build();
+
+        let mut builder = X509::builder().expect("Failed to create certificate");
+        builder.set_version(2).expect("Failed to set version");
+        let serial = BigNum::from_u32(1)
+            .and_then(|serial| serial.to_asn1_integer())
+            .expect("Failed to create serial number");
+        builder
+            .set_serial_number(&serial)
+            .expect("Failed to set serial number");
+        builder
+            .set_subject_name(&subject)
+            .expect("Failed to set subject");
+        builder
+            .set_issuer_name(&subject)
+            .expect("Failed to set issuer");
+        builder.set_pubkey(&key).expect("Failed to set public key");
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).expect("Failed to create time"))
+            .expect("Failed to set start date");
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).expect("Failed to create time"))
+            .expect("Failed to set end date");
+        builder
+            .sign(&key, MessageDigest::sha256())
+            .expect("Failed to sign certificate");
+        let cert = builder.build();
+
+        let cert_path = dir.join(format!("{}.crt", name));
+        let key_path = dir.join(format!("{}.key", name));
+        fs::write(
+            &cert_path,
+            cert.to_pem().expect("Failed to encode certificate"),
+        )
+        .expect("Failed to write certificate");
+        fs::write(
+            &key_path,
+            key.private_key_to_pem_pkcs8()
+                .expect("Failed to encode key"),
+        )
+        .expect("Failed to write key");
+
+        (
+            cert_path.to_str().expect("Invalid path").to_string(),
+            key_path.to_str().expect("Invalid path").to_string(),
+            cert.to_der().expect("Failed to encode certificate"),
+        )
+    }
+}
@@ -0,0 +1,409 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Observability of the long-running threads and channel queues that make up a node.
+//!
+//! A thread that should be visible in the runtime diagnostics owns a [`ThreadHeartbeat`], which
+//! it marks as idle each time it waits for work and as busy each time it starts handling a work
+//! item; when the thread exits, the heartbeat is dropped and the thread is reported as exited.
+//! Channels report their queue depth either from the channel itself or through a shared
+//! [`QueueCounter`].
+//!
+//! Both are collected by a [`RuntimeMonitor`], which provides a snapshot of every registered
+//! thread and queue. A thread that has been busy for a long time while its queue grows is likely
+//! stuck. The monitor also records how long each phase of the node's startup took.
+//!
+//! [`ThreadHeartbeat`]: struct.ThreadHeartbeat.html
+//! [`QueueCounter`]: struct.QueueCounter.html
+//! [`RuntimeMonitor`]: struct.RuntimeMonitor.html
+
+#[cfg(feature = "rest-api")]
+mod rest_api;
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "rest-api")]
+pub use self::rest_api::RuntimeDiagnosticsResourceProvider;
+
+/// The state of a monitored thread.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ThreadState {
+    /// The thread is waiting for work
+    Idle,
+    /// The thread has been handling a work item since the given time
+    Busy { since: SystemTime },
+    /// The thread has exited
+    Exited,
+}
+
+/// A snapshot of a monitored thread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadStatus {
+    pub name: String,
+    pub state: ThreadState,
+    /// The last time the thread reported that it was idle or busy
+    pub last_heartbeat: SystemTime,
+}
+
+/// The time taken by a phase of the node's startup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StartupPhase {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// A snapshot of a monitored queue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueueStatus {
+    pub name: String,
+    /// The number of items waiting to be received
+    pub depth: usize,
+}
+
+// Times are stored as milliseconds since the Unix epoch, so they can be updated without a lock
+#[derive(Default)]
+struct ThreadMonitorState {
+    last_heartbeat: AtomicU64,
+    // Zero while the thread is idle
+    busy_since: AtomicU64,
+    exited: AtomicBool,
+}
+
+/// Reports the liveness of the thread that owns it.
+///
+/// The heartbeat is not `Clone`; when the thread exits and the heartbeat is dropped, the thread is
+/// reported as exited.
+pub struct ThreadHeartbeat {
+    monitor: ThreadMonitor,
+}
+
+impl ThreadHeartbeat {
+    /// Creates a heartbeat for the thread with the given name. The thread starts as idle.
+    pub fn new(name: &str) -> Self {
+        let monitor = ThreadMonitor {
+            name: name.into(),
+            state: Arc::new(ThreadMonitorState::default()),
+        };
+        monitor
+            .state
+            .last_heartbeat
+            .store(now_millis(), Ordering::SeqCst);
+
+        Self { monitor }
+    }
+
+    /// Reports that the thread is waiting for work.
+    pub fn idle(&self) {
+        self.monitor
+            .state
+            .last_heartbeat
+            .store(now_millis(), Ordering::SeqCst);
+        self.monitor.state.busy_since.store(0, Ordering::SeqCst);
+    }
+
+    /// Reports that the thread has started handling a work item.
+    pub fn busy(&self) {
+        let now = now_millis();
+        self.monitor
+            .state
+            .last_heartbeat
+            .store(now, Ordering::SeqCst);
+        self.monitor.state.busy_since.store(now, Ordering::SeqCst);
+    }
+
+    /// Returns a monitor that reads this heartbeat.
+    pub fn monitor(&self) -> ThreadMonitor {
+        self.monitor.clone()
+    }
+}
+
+impl Drop for ThreadHeartbeat {
+    fn drop(&mut self) {
+        self.monitor.state.exited.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Reads the status of a thread from its `ThreadHeartbeat`.
+#[derive(Clone)]
+pub struct ThreadMonitor {
+    name: String,
+    state: Arc<ThreadMonitorState>,
+}
+
+impl ThreadMonitor {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn status(&self) -> ThreadStatus {
+        let state = if self.state.exited.load(Ordering::SeqCst) {
+            ThreadState::Exited
+        } else {
+            match self.state.busy_since.load(Ordering::SeqCst) {
+                0 => ThreadState::Idle,
+                since => ThreadState::Busy {
+                    since: from_millis(since),
+                },
+            }
+        };
+
+        ThreadStatus {
+            name: self.name.clone(),
+            state,
+            last_heartbeat: from_millis(self.state.last_heartbeat.load(Ordering::SeqCst)),
+        }
+    }
+}
+
+/// Counts the items waiting in a channel that cannot report its own length.
+///
+/// The sending side calls `increment` before sending an item, and `decrement` if the send fails;
+/// the receiving side calls `decrement` after receiving an item.
+#[derive(Clone, Default)]
+pub struct QueueCounter {
+    depth: Arc<AtomicUsize>,
+}
+
+impl QueueCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increment(&self) {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn decrement(&self) {
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+}
+
+type QueueDepthFn = Box<dyn Fn() -> usize + Send + Sync>;
+
+#[derive(Default)]
+struct RuntimeMonitorInner {
+    threads: Vec<ThreadMonitor>,
+    queues: Vec<(String, QueueDepthFn)>,
+    startup_phases: Vec<StartupPhase>,
+}
+
+/// Collects the threads and queues that are reported in the runtime diagnostics.
+///
+/// A `RuntimeMonitor` is a cheap handle to shared state; clones may be given to each component
+/// that registers its threads and queues.
+#[derive(Clone, Default)]
+pub struct RuntimeMonitor {
+    inner: Arc<Mutex<RuntimeMonitorInner>>,
+}
+
+impl RuntimeMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a thread to the diagnostics.
+    pub fn add_thread(&self, thread: ThreadMonitor) {
+        self.lock_inner().threads.push(thread);
+    }
+
+    /// Adds a queue to the diagnostics. The `depth` function is called each time the diagnostics
+    /// are read, and must not block.
+    pub fn add_queue<F>(&self, name: &str, depth: F)
+    where
+        F: Fn() -> usize + Send + Sync + 'static,
+    {
+        self.lock_inner()
+            .queues
+            .push((name.into(), Box::new(depth)));
+    }
+
+    /// Adds a queue whose depth is tracked by the given counter.
+    pub fn add_queue_counter(&self, name: &str, counter: QueueCounter) {
+        self.add_queue(name, move || counter.depth());
+    }
+
+    /// Records the time taken by a phase of the node's startup. A phase that is recorded again
+    /// replaces the earlier duration.
+    pub fn add_startup_phase(&self, name: &str, duration: Duration) {
+        let mut inner = self.lock_inner();
+        match inner
+            .startup_phases
+            .iter_mut()
+            .find(|phase| phase.name == name)
+        {
+            Some(phase) => phase.duration = duration,
+            None => inner.startup_phases.push(StartupPhase {
+                name: name.into(),
+                duration,
+            }),
+        }
+    }
+
+    /// Returns the recorded startup phases, in the order they were first recorded.
+    pub fn startup_phases(&self) -> Vec<StartupPhase> {
+        self.lock_inner().startup_phases.clone()
+    }
+
+    /// Returns the status of every registered thread, in the order they were registered.
+    pub fn threads(&self) -> Vec<ThreadStatus> {
+        self.lock_inner()
+            .threads
+            .iter()
+            .map(ThreadMonitor::status)
+            .collect()
+    }
+
+    /// Returns the depth of every registered queue, in the order they were registered.
+    pub fn queues(&self) -> Vec<QueueStatus> {
+        self.lock_inner()
+            .queues
+            .iter()
+            .map(|(name, depth)| QueueStatus {
+                name: name.clone(),
+                depth: depth(),
+            })
+            .collect()
+    }
+
+    // The monitors are only ever appended to, so a poisoned lock is still usable
+    fn lock_inner(&self) -> std::sync::MutexGuard<RuntimeMonitorInner> {
+        match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    /// Test that the runtime monitor reports the state of a thread through its lifecycle and the
+    /// depth of its queue.
+    ///
+    /// 1. Start a thread that handles items from a channel, registering its heartbeat and a
+    ///    queue counter with the monitor
+    /// 2. Verify that the thread is idle and the queue is empty
+    /// 3. Send an item that blocks the thread and a second item, and verify that the thread is
+    ///    busy and one item is queued
+    /// 4. Unblock the thread and close the channel, and verify that the thread has exited and the
+    ///    queue is empty
+    #[test]
+    fn test_runtime_monitor() {
+        let monitor = RuntimeMonitor::new();
+        let counter = QueueCounter::new();
+        monitor.add_queue_counter("work", counter.clone());
+
+        let heartbeat = ThreadHeartbeat::new("worker");
+        monitor.add_thread(heartbeat.monitor());
+
+        let (sender, receiver) = channel::<std::sync::mpsc::Receiver<()>>();
+        let thread_counter = counter.clone();
+        let join_handle = thread::spawn(move || loop {
+            heartbeat.idle();
+            match receiver.recv() {
+                Ok(block) => {
+                    thread_counter.decrement();
+                    heartbeat.busy();
+                    let _ = block.recv();
+                }
+                Err(_) => break,
+            }
+        });
+
+        let threads = monitor.threads();
+        assert_eq!(1, threads.len());
+        assert_eq!("worker", threads[0].name);
+        assert_eq!(ThreadState::Idle, threads[0].state);
+        assert_eq!(
+            vec![QueueStatus {
+                name: "work".into(),
+                depth: 0,
+            }],
+            monitor.queues()
+        );
+
+        let (unblock_sender, unblock_receiver) = channel();
+        counter.increment();
+        sender.send(unblock_receiver).expect("Unable to send item");
+        // the second item does not block, as its sender is dropped
+        let (_, other_receiver) = channel();
+        counter.increment();
+        sender.send(other_receiver).expect("Unable to send item");
+
+        wait_for(|| matches!(monitor.threads()[0].state, ThreadState::Busy { .. }));
+        assert_eq!(1, monitor.queues()[0].depth);
+
+        unblock_sender.send(()).expect("Unable to unblock thread");
+        drop(sender);
+        join_handle.join().expect("Worker thread panicked");
+
+        assert_eq!(ThreadState::Exited, monitor.threads()[0].state);
+        assert_eq!(0, monitor.queues()[0].depth);
+    }
+
+    /// Test that startup phases are reported in the order they were first recorded, and that
+    /// recording a phase again replaces its duration.
+    #[test]
+    fn test_startup_phases() {
+        let monitor = RuntimeMonitor::new();
+        monitor.add_startup_phase("admin-store-load", Duration::from_millis(20));
+        monitor.add_startup_phase("admin-circuit-init", Duration::from_millis(30));
+        monitor.add_startup_phase("admin-store-load", Duration::from_millis(10));
+
+        assert_eq!(
+            vec![
+                StartupPhase {
+                    name: "admin-store-load".into(),
+                    duration: Duration::from_millis(10),
+                },
+                StartupPhase {
+                    name: "admin-circuit-init".into(),
+                    duration: Duration::from_millis(30),
+                },
+            ],
+            monitor.startup_phases()
+        );
+    }
+
+    fn wait_for<F: Fn() -> bool>(predicate: F) {
+        for _ in 0..5000 {
+            if predicate() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Timed out waiting for condition");
+    }
+}
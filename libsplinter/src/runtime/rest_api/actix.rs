@@ -0,0 +1,47 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::HttpResponse;
+use futures::future::IntoFuture;
+
+use crate::protocol;
+use crate::rest_api::{Method, ProtocolVersionRangeGuard, Resource};
+use crate::runtime::RuntimeMonitor;
+
+use super::resources::{QueueResponse, RuntimeResponse, StartupPhaseResponse, ThreadResponse};
+
+pub fn make_runtime_resource(monitor: RuntimeMonitor) -> Resource {
+    Resource::build("/diagnostics/runtime")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::DIAGNOSTICS_RUNTIME_MIN,
+            protocol::DIAGNOSTICS_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            let threads = monitor.threads();
+            let queues = monitor.queues();
+            let startup_phases = monitor.startup_phases();
+            Box::new(
+                HttpResponse::Ok()
+                    .json(RuntimeResponse {
+                        threads: threads.iter().map(ThreadResponse::from).collect(),
+                        queues: queues.iter().map(QueueResponse::from).collect(),
+                        startup: startup_phases
+                            .iter()
+                            .map(StartupPhaseResponse::from)
+                            .collect(),
+                    })
+                    .into_future(),
+            )
+        })
+}
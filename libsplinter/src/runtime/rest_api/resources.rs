@@ -0,0 +1,96 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runtime::{QueueStatus, StartupPhase, ThreadState, ThreadStatus};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeResponse<'a> {
+    pub threads: Vec<ThreadResponse<'a>>,
+    pub queues: Vec<QueueResponse<'a>>,
+    pub startup: Vec<StartupPhaseResponse<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThreadResponse<'a> {
+    pub name: &'a str,
+    /// "Idle", "Busy" or "Exited"
+    pub state: &'static str,
+    /// Milliseconds the thread has been handling its current work item, if busy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub busy_for: Option<u64>,
+    /// Milliseconds since the Unix epoch
+    pub last_heartbeat: u64,
+}
+
+impl<'a> From<&'a ThreadStatus> for ThreadResponse<'a> {
+    fn from(status: &'a ThreadStatus) -> Self {
+        let (state, busy_for) = match status.state {
+            ThreadState::Idle => ("Idle", None),
+            ThreadState::Busy { since } => (
+                "Busy",
+                Some(
+                    SystemTime::now()
+                        .duration_since(since)
+                        .map(|duration| duration.as_millis() as u64)
+                        .unwrap_or(0),
+                ),
+            ),
+            ThreadState::Exited => ("Exited", None),
+        };
+
+        Self {
+            name: &status.name,
+            state,
+            busy_for,
+            last_heartbeat: status
+                .last_heartbeat
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueResponse<'a> {
+    pub name: &'a str,
+    pub depth: usize,
+}
+
+impl<'a> From<&'a QueueStatus> for QueueResponse<'a> {
+    fn from(status: &'a QueueStatus) -> Self {
+        Self {
+            name: &status.name,
+            depth: status.depth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StartupPhaseResponse<'a> {
+    pub name: &'a str,
+    /// Milliseconds the phase took
+    pub duration: u64,
+}
+
+impl<'a> From<&'a StartupPhase> for StartupPhaseResponse<'a> {
+    fn from(phase: &'a StartupPhase) -> Self {
+        Self {
+            name: &phase.name,
+            duration: phase.duration.as_millis() as u64,
+        }
+    }
+}
@@ -0,0 +1,460 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A shared scheduler for periodic background jobs.
+//!
+//! Subsystems that need to do work on a timer, such as refreshing caches or pushing metrics,
+//! register a named [`Job`] with a [`Scheduler`] instead of spawning their own timer threads. All
+//! jobs are run, one at a time, on the scheduler's thread; a job that takes a long time will
+//! delay the jobs that are due after it.
+//!
+//! The status of each job, including the result of its last run, is available from
+//! [`Scheduler::job_statuses`].
+//!
+//! [`Job`]: struct.Job.html
+//! [`Scheduler`]: struct.Scheduler.html
+//! [`Scheduler::job_statuses`]: struct.Scheduler.html#method.job_statuses
+
+#[cfg(feature = "rest-api")]
+mod rest_api;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use rand::Rng;
+
+use crate::error::{InternalError, InvalidArgumentError};
+
+#[cfg(feature = "rest-api")]
+pub use self::rest_api::SchedulerResourceProvider;
+
+type Task = Box<dyn FnMut() -> Result<(), InternalError> + Send>;
+
+/// A named task that is run periodically by a `Scheduler`.
+pub struct Job {
+    name: String,
+    interval: Duration,
+    jitter: Duration,
+    task: Task,
+}
+
+impl Job {
+    /// Creates a new job that runs `task` every `interval`. The first run happens one interval
+    /// after the job is registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique name for the job, used in its status and log messages
+    /// * `interval` - The time between the end of one run and the start of the next
+    /// * `task` - The work to be done; an error is logged and recorded in the job's status
+    pub fn new<F>(name: &str, interval: Duration, task: F) -> Self
+    where
+        F: FnMut() -> Result<(), InternalError> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            interval,
+            jitter: Duration::from_secs(0),
+            task: Box::new(task),
+        }
+    }
+
+    /// Adds a random delay of up to `jitter` to each interval, so that jobs on many nodes do not
+    /// run in lockstep.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+}
+
+/// The status of a job registered with a `Scheduler`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval: Duration,
+    pub jitter: Duration,
+    /// Whether the job is currently running
+    pub running: bool,
+    /// The number of times the job has been run
+    pub run_count: u64,
+    /// The time the last run started
+    pub last_run: Option<SystemTime>,
+    /// How long the last run took
+    pub last_duration: Option<Duration>,
+    /// The error returned by the last run, if it failed
+    pub last_error: Option<String>,
+    /// The time the next run is due; `None` while the job is running
+    pub next_run: Option<SystemTime>,
+}
+
+struct JobEntry {
+    interval: Duration,
+    jitter: Duration,
+    // The task is taken out of the entry while it is running, so that the state lock is not held
+    task: Option<Task>,
+    next_run: Instant,
+    run_count: u64,
+    last_run: Option<SystemTime>,
+    last_duration: Option<Duration>,
+    last_error: Option<String>,
+}
+
+impl JobEntry {
+    fn status(&self, name: &str) -> JobStatus {
+        let next_run = if self.task.is_some() {
+            let now = Instant::now();
+            Some(if self.next_run > now {
+                SystemTime::now() + (self.next_run - now)
+            } else {
+                SystemTime::now()
+            })
+        } else {
+            None
+        };
+
+        JobStatus {
+            name: name.into(),
+            interval: self.interval,
+            jitter: self.jitter,
+            running: self.task.is_none(),
+            run_count: self.run_count,
+            last_run: self.last_run,
+            last_duration: self.last_duration,
+            last_error: self.last_error.clone(),
+            next_run,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    jobs: BTreeMap<String, JobEntry>,
+    started: bool,
+    shutdown: bool,
+}
+
+/// Runs registered jobs periodically on a single background thread.
+///
+/// A `Scheduler` is a cheap handle to shared state; clones may be given to the subsystems that
+/// register jobs and to the REST API, which reports the job statuses.
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    shared: Arc<(Mutex<SchedulerState>, Condvar)>,
+}
+
+impl Scheduler {
+    /// Creates a new scheduler. Jobs may be registered before or after it is started.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a job.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgumentError` if a job with the same name is already registered or if
+    /// the job's interval is zero.
+    pub fn register(&self, job: Job) -> Result<(), InvalidArgumentError> {
+        if job.interval == Duration::from_secs(0) {
+            return Err(InvalidArgumentError::new(
+                "interval".into(),
+                format!(
+                    "the interval of job '{}' must be greater than zero",
+                    job.name
+                ),
+            ));
+        }
+
+        let mut state = self.lock_state();
+        if state.jobs.contains_key(&job.name) {
+            return Err(InvalidArgumentError::new(
+                "name".into(),
+                format!("a job named '{}' is already registered", job.name),
+            ));
+        }
+
+        debug!(
+            "Registering job '{}' to run every {:?}",
+            job.name, job.interval
+        );
+        state.jobs.insert(
+            job.name,
+            JobEntry {
+                interval: job.interval,
+                jitter: job.jitter,
+                task: Some(job.task),
+                next_run: Instant::now() + jittered(job.interval, job.jitter),
+                run_count: 0,
+                last_run: None,
+                last_duration: None,
+                last_error: None,
+            },
+        );
+        self.shared.1.notify_all();
+
+        Ok(())
+    }
+
+    /// Removes a job, returning whether it was registered. If the job is running, the current run
+    /// is completed but the job is not run again.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.lock_state().jobs.remove(name).is_some()
+    }
+
+    /// Returns the status of every registered job, ordered by name.
+    pub fn job_statuses(&self) -> Vec<JobStatus> {
+        self.lock_state()
+            .jobs
+            .iter()
+            .map(|(name, entry)| entry.status(name))
+            .collect()
+    }
+
+    /// Starts the scheduler's thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InternalError` if the scheduler has already been started or if the thread
+    /// cannot be spawned.
+    pub fn start(&self) -> Result<thread::JoinHandle<()>, InternalError> {
+        {
+            let mut state = self.lock_state();
+            if state.started {
+                return Err(InternalError::with_message(
+                    "Scheduler has already been started".into(),
+                ));
+            }
+            state.started = true;
+        }
+
+        let scheduler = self.clone();
+        thread::Builder::new()
+            .name("Scheduler".into())
+            .spawn(move || scheduler.run())
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+
+    /// Signals the scheduler's thread to stop. A job that is running is allowed to complete.
+    pub fn shutdown(&self) {
+        self.lock_state().shutdown = true;
+        self.shared.1.notify_all();
+    }
+
+    fn run(&self) {
+        let mut state = self.lock_state();
+        while !state.shutdown {
+            let now = Instant::now();
+            let due = state
+                .jobs
+                .iter()
+                .filter(|(_, entry)| entry.task.is_some())
+                .min_by_key(|(_, entry)| entry.next_run)
+                .map(|(name, entry)| (name.clone(), entry.next_run));
+
+            match due {
+                Some((name, next_run)) if next_run <= now => {
+                    let mut task = match state.jobs.get_mut(&name).and_then(|e| e.task.take()) {
+                        Some(task) => task,
+                        None => continue,
+                    };
+                    drop(state);
+
+                    trace!("Running job '{}'", name);
+                    let started_at = SystemTime::now();
+                    let start = Instant::now();
+                    let result = task();
+                    let duration = start.elapsed();
+
+                    state = self.lock_state();
+                    // The job may have been unregistered while it was running
+                    if let Some(entry) = state.jobs.get_mut(&name) {
+                        entry.run_count += 1;
+                        entry.last_run = Some(started_at);
+                        entry.last_duration = Some(duration);
+                        entry.last_error = match result {
+                            Ok(()) => None,
+                            Err(err) => {
+                                error!("Job '{}' failed: {}", name, err);
+                                Some(err.to_string())
+                            }
+                        };
+                        entry.next_run = Instant::now() + jittered(entry.interval, entry.jitter);
+                        entry.task = Some(task);
+                    }
+                }
+                Some((_, next_run)) => {
+                    state = self.wait(state, Some(next_run - now));
+                }
+                None => {
+                    state = self.wait(state, None);
+                }
+            }
+        }
+
+        debug!("Exiting scheduler thread");
+    }
+
+    fn wait<'a>(
+        &'a self,
+        state: MutexGuard<'a, SchedulerState>,
+        timeout: Option<Duration>,
+    ) -> MutexGuard<'a, SchedulerState> {
+        let condvar = &self.shared.1;
+        match timeout {
+            Some(timeout) => match condvar.wait_timeout(state, timeout) {
+                Ok((state, _)) => state,
+                Err(poisoned) => poisoned.into_inner().0,
+            },
+            None => match condvar.wait(state) {
+                Ok(state) => state,
+                Err(poisoned) => poisoned.into_inner(),
+            },
+        }
+    }
+
+    // The state is only modified while holding the lock, and never left inconsistent by a panic,
+    // so a poisoned lock is still usable
+    fn lock_state(&self) -> MutexGuard<SchedulerState> {
+        match self.shared.0.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Returns the interval with a random delay of up to `jitter` added.
+fn jittered(interval: Duration, jitter: Duration) -> Duration {
+    let jitter_millis = jitter.as_millis() as u64;
+    if jitter_millis == 0 {
+        interval
+    } else {
+        interval + Duration::from_millis(rand::thread_rng().gen_range(0, jitter_millis + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+
+    /// Test that registered jobs are run on their interval and that their statuses record the
+    /// results of the last run.
+    ///
+    /// 1. Register a job that succeeds and a job that fails, and start the scheduler
+    /// 2. Wait for each job to run twice
+    /// 3. Verify the statuses of both jobs
+    /// 4. Verify that registering a job with a duplicate name or a zero interval fails
+    /// 5. Shut down the scheduler and verify that the thread exits
+    #[test]
+    fn test_scheduler() {
+        let scheduler = Scheduler::new();
+
+        let (ok_sender, ok_receiver) = channel();
+        scheduler
+            .register(Job::new("ok", Duration::from_millis(10), move || {
+                ok_sender
+                    .send(())
+                    .map_err(|err| InternalError::from_source(Box::new(err)))
+            }))
+            .expect("Unable to register ok job");
+
+        let (err_sender, err_receiver) = channel();
+        scheduler
+            .register(
+                Job::new("err", Duration::from_millis(10), move || {
+                    err_sender
+                        .send(())
+                        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+                    Err(InternalError::with_message("job failed".into()))
+                })
+                .with_jitter(Duration::from_millis(5)),
+            )
+            .expect("Unable to register err job");
+
+        let join_handle = scheduler.start().expect("Unable to start scheduler");
+        assert!(scheduler.start().is_err());
+
+        for _ in 0..2 {
+            ok_receiver
+                .recv_timeout(Duration::from_secs(5))
+                .expect("ok job was not run");
+            err_receiver
+                .recv_timeout(Duration::from_secs(5))
+                .expect("err job was not run");
+        }
+
+        // each job's status is updated after its task returns
+        let statuses = wait_for_statuses(&scheduler, |statuses| {
+            statuses.iter().all(|status| status.run_count >= 2)
+        });
+        assert_eq!(2, statuses.len());
+
+        assert_eq!("err", statuses[0].name);
+        assert_eq!(Duration::from_millis(5), statuses[0].jitter);
+        assert!(statuses[0].run_count >= 2);
+        assert!(statuses[0].last_run.is_some());
+        assert_eq!(Some("job failed".to_string()), statuses[0].last_error);
+
+        assert_eq!("ok", statuses[1].name);
+        assert_eq!(Duration::from_millis(10), statuses[1].interval);
+        assert!(statuses[1].run_count >= 2);
+        assert!(statuses[1].last_duration.is_some());
+        assert_eq!(None, statuses[1].last_error);
+
+        assert!(scheduler
+            .register(Job::new("ok", Duration::from_secs(1), || Ok(())))
+            .is_err());
+        assert!(scheduler
+            .register(Job::new("zero", Duration::from_secs(0), || Ok(())))
+            .is_err());
+
+        assert!(scheduler.unregister("err"));
+        assert!(!scheduler.unregister("err"));
+
+        scheduler.shutdown();
+        join_handle.join().expect("Scheduler thread panicked");
+    }
+
+    fn wait_for_statuses<F>(scheduler: &Scheduler, predicate: F) -> Vec<JobStatus>
+    where
+        F: Fn(&[JobStatus]) -> bool,
+    {
+        let start = Instant::now();
+        loop {
+            let statuses = scheduler.job_statuses();
+            if predicate(&statuses) || start.elapsed() > Duration::from_secs(5) {
+                return statuses;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Test that jitter adds at most the given delay to the interval.
+    #[test]
+    fn test_jittered() {
+        assert_eq!(
+            Duration::from_secs(10),
+            jittered(Duration::from_secs(10), Duration::from_secs(0))
+        );
+
+        for _ in 0..100 {
+            let interval = jittered(Duration::from_secs(10), Duration::from_secs(2));
+            assert!(interval >= Duration::from_secs(10));
+            assert!(interval <= Duration::from_secs(12));
+        }
+    }
+}
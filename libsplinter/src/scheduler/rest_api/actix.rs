@@ -0,0 +1,40 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::HttpResponse;
+use futures::future::IntoFuture;
+
+use crate::protocol;
+use crate::rest_api::{Method, ProtocolVersionRangeGuard, Resource};
+use crate::scheduler::Scheduler;
+
+use super::resources::{JobResponse, ListJobsResponse};
+
+pub fn make_jobs_resource(scheduler: Scheduler) -> Resource {
+    Resource::build("/scheduler/jobs")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::SCHEDULER_LIST_JOBS_MIN,
+            protocol::SCHEDULER_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, move |_, _| {
+            let statuses = scheduler.job_statuses();
+            Box::new(
+                HttpResponse::Ok()
+                    .json(ListJobsResponse {
+                        data: statuses.iter().map(JobResponse::from).collect(),
+                    })
+                    .into_future(),
+            )
+        })
+}
@@ -0,0 +1,49 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "rest-api-actix")]
+mod actix;
+mod resources;
+
+use crate::rest_api::{Resource, RestResourceProvider};
+
+use super::Scheduler;
+
+/// Provides the `GET /scheduler/jobs` endpoint for listing the status of the scheduler's jobs.
+#[derive(Clone)]
+pub struct SchedulerResourceProvider {
+    scheduler: Scheduler,
+}
+
+impl SchedulerResourceProvider {
+    pub fn new(scheduler: Scheduler) -> Self {
+        Self { scheduler }
+    }
+}
+
+impl RestResourceProvider for SchedulerResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.push(actix::make_jobs_resource(self.scheduler.clone()));
+        }
+
+        resources
+    }
+}
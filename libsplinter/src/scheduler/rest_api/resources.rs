@@ -0,0 +1,73 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::UNIX_EPOCH;
+
+use crate::scheduler::JobStatus;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListJobsResponse<'a> {
+    pub data: Vec<JobResponse<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobResponse<'a> {
+    pub name: &'a str,
+    /// Milliseconds between runs
+    pub interval: u64,
+    /// Maximum random delay added to the interval, in milliseconds
+    pub jitter: u64,
+    pub running: bool,
+    pub run_count: u64,
+    /// Seconds since the Unix epoch
+    pub last_run: Option<u64>,
+    /// Milliseconds taken by the last run
+    pub last_duration: Option<u64>,
+    /// "Ok" or "Error"; omitted if the job has not run
+    pub last_result: Option<&'static str>,
+    pub last_error: Option<&'a str>,
+    /// Seconds since the Unix epoch
+    pub next_run: Option<u64>,
+}
+
+impl<'a> From<&'a JobStatus> for JobResponse<'a> {
+    fn from(status: &'a JobStatus) -> Self {
+        let epoch_secs = |time: std::time::SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_secs())
+        };
+
+        Self {
+            name: &status.name,
+            interval: status.interval.as_millis() as u64,
+            jitter: status.jitter.as_millis() as u64,
+            running: status.running,
+            run_count: status.run_count,
+            last_run: status.last_run.and_then(epoch_secs),
+            last_duration: status
+                .last_duration
+                .map(|duration| duration.as_millis() as u64),
+            last_result: status.last_run.map(|_| {
+                if status.last_error.is_some() {
+                    "Error"
+                } else {
+                    "Ok"
+                }
+            }),
+            last_error: status.last_error.as_deref(),
+            next_run: status.next_run.and_then(epoch_secs),
+        }
+    }
+}
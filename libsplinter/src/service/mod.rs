@@ -42,6 +42,8 @@ pub mod network;
 mod processor;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
+#[cfg(feature = "service-runner")]
+pub mod runner;
 #[cfg(feature = "service-arg-validation")]
 pub mod validation;
 
@@ -50,6 +52,7 @@ use std::any::Any;
 pub use factory::ServiceFactory;
 pub use processor::registry::StandardServiceNetworkRegistry;
 pub use processor::JoinHandles;
+pub use processor::SequenceNumbers;
 pub use processor::ServiceProcessor;
 pub use processor::ShutdownHandle;
 
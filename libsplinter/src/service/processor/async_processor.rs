@@ -0,0 +1,312 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the services of a `ServiceProcessor` as tasks on a tokio runtime.
+//!
+//! Each service is driven by a task that reads from a bounded channel, so the number of threads
+//! used by a processor no longer grows with the number of services it hosts. Receiving from and
+//! sending to the splinter node still happens on dedicated threads, since the mesh is blocking.
+//!
+//! Service implementations are synchronous; a service that blocks in `handle_message` (for
+//! example, while waiting on `send_and_await`) occupies one of the runtime's worker threads until
+//! it returns.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::future::{self, Either};
+use futures::sink::Wait;
+use futures::sync::{mpsc, oneshot};
+use futures::{Future, Sink, Stream};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::channel;
+use crate::circuit::routing::ServiceId;
+use crate::mutex_lock_unwrap;
+use crate::network::reply::InboundRouter;
+use crate::protos::circuit::CircuitMessageType;
+use crate::service::error::ServiceProcessorError;
+use crate::service::Service;
+
+use super::registry::StandardServiceNetworkRegistry;
+use super::sender::ProcessorMessage;
+use super::{
+    handle_service_message, parse_inbound_msg, spawn_incoming_thread, spawn_outgoing_thread,
+    InboundMessage, JoinHandles, ServiceProcessor, ShutdownHandle,
+};
+
+/// The number of runtime worker threads shared by all of a processor's services.
+const WORKER_THREADS: usize = 2;
+
+/// Creates the inbound router for a processor, along with the receiver for messages that did not
+/// match a pending reply.
+pub(super) fn inbound_channel(
+    capacity: usize,
+) -> (
+    InboundRouter<CircuitMessageType>,
+    mpsc::Receiver<InboundMessage>,
+) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    let inbound_sender = InboundSender {
+        sender: Arc::new(Mutex::new(sender.wait())),
+    };
+    (InboundRouter::new(Box::new(inbound_sender)), receiver)
+}
+
+/// Starts the processor's services on a new runtime, along with the threads that exchange
+/// messages with the splinter node.
+pub(super) fn start(
+    processor: ServiceProcessor,
+) -> Result<
+    (
+        ShutdownHandle,
+        JoinHandles<Result<(), ServiceProcessorError>>,
+    ),
+    ServiceProcessorError,
+> {
+    let runtime = Builder::new()
+        .core_threads(WORKER_THREADS)
+        .name_prefix("ServiceProcessor-")
+        .build()?;
+    let executor = runtime.executor();
+
+    let mut services = HashMap::new();
+    let mut tasks = vec![];
+    for (circuit, service) in processor.services.into_iter() {
+        let service_id = ServiceId::new(circuit.clone(), service.service_id().to_string());
+        let (sender, receiver) = mpsc::channel(processor.channel_capacity);
+        let (done_sender, done_receiver) = oneshot::channel();
+        let registry = StandardServiceNetworkRegistry::new(
+            circuit,
+            processor.network_sender.clone(),
+            processor.inbound_router.clone(),
+        )
+        .with_sequence_numbers(processor.sequence_numbers.clone());
+        #[cfg(feature = "service-endpoint-auth")]
+        let registry = match &processor.signer {
+            Some(signer) => registry.with_signer(signer.clone_box()),
+            None => registry,
+        };
+
+        let task_service_id = service_id.clone();
+        executor.spawn(run_service(service, registry, receiver).then(move |res| {
+            if let Err(err) = &res {
+                error!(
+                    "Terminating service {} due to error: {}",
+                    task_service_id, err
+                );
+            }
+            // The shutdown handle may have been dropped, in which case no one is waiting
+            let _ = done_sender.send(res);
+            Ok(())
+        }));
+
+        tasks.push(ServiceTask {
+            service_id: service_id.clone(),
+            sender: sender.clone(),
+            done: done_receiver,
+        });
+        services.insert(service_id, sender);
+    }
+
+    executor.spawn(route_inbound(processor.inbound_receiver, services));
+
+    let incoming_join_handle = spawn_incoming_thread(
+        processor.mesh.clone(),
+        processor.running.clone(),
+        processor.inbound_router,
+    )?;
+    let outgoing_join_handle = spawn_outgoing_thread(
+        processor.mesh,
+        processor.running,
+        processor.network_receiver,
+        processor.node_mesh_id,
+    )?;
+
+    let state = Mutex::new(Some(RuntimeState { runtime, tasks }));
+    // Creates the shutdown handle that will be called by the process starting up the
+    // Service processor
+    let do_shutdown = Box::new(move || {
+        debug!("Shutting down service processor");
+        let RuntimeState { runtime, tasks } = match mutex_lock_unwrap!(state).take() {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+
+        // Ask each service to stop, then wait for its task to finish
+        for task in tasks.iter() {
+            info!("Shutting down {}", task.service_id);
+            task.sender
+                .clone()
+                .send(ProcessorMessage::Shutdown)
+                .wait()
+                .map_err(|err| {
+                    ServiceProcessorError::ShutdownError(format!(
+                        "unable to send shutdown message: {:?}",
+                        err
+                    ))
+                })?;
+        }
+
+        for task in tasks.into_iter() {
+            task.done.wait().map_err(|_| {
+                ServiceProcessorError::ShutdownError(format!(
+                    "task for service {} ended without a result",
+                    task.service_id
+                ))
+            })??;
+        }
+
+        runtime.shutdown_now().wait().map_err(|_| {
+            ServiceProcessorError::ShutdownError("unable to shutdown service runtime".into())
+        })
+    });
+
+    Ok((
+        ShutdownHandle { do_shutdown },
+        JoinHandles::new(vec![incoming_join_handle, outgoing_join_handle]),
+    ))
+}
+
+/// Everything needed to shut down the services of a started processor.
+struct RuntimeState {
+    runtime: Runtime,
+    tasks: Vec<ServiceTask>,
+}
+
+struct ServiceTask {
+    service_id: ServiceId,
+    sender: mpsc::Sender<ProcessorMessage>,
+    done: oneshot::Receiver<Result<(), ServiceProcessorError>>,
+}
+
+/// Starts the service, passes it messages until it is told to shut down, then stops and destroys
+/// it.
+fn run_service(
+    mut service: Box<dyn Service>,
+    registry: StandardServiceNetworkRegistry,
+    receiver: mpsc::Receiver<ProcessorMessage>,
+) -> impl Future<Item = (), Error = ServiceProcessorError> {
+    future::lazy(move || -> Result<_, ServiceProcessorError> {
+        info!("Starting Service: {}", service.service_id());
+        service.start(&registry).map_err(|err| {
+            ServiceProcessorError::ProcessError(
+                format!("unable to start service {}", service.service_id()),
+                Box::new(err),
+            )
+        })?;
+        Ok((service, registry))
+    })
+    .and_then(move |(service, registry)| {
+        receiver
+            .take_while(|message| Ok(matches!(message, ProcessorMessage::ServiceMessage(_))))
+            .fold(service, |service, message| {
+                if let ProcessorMessage::ServiceMessage(service_message) = message {
+                    handle_service_message(&*service, service_message);
+                }
+                Ok::<_, ()>(service)
+            })
+            .map_err(|_| ServiceProcessorError::ShutdownError("service channel failed".into()))
+            .map(move |service| (service, registry))
+    })
+    .and_then(|(mut service, registry)| {
+        info!("Shutting down {}", service.service_id());
+        service.stop(&registry).map_err(|err| {
+            ServiceProcessorError::ProcessError("unable to stop service".into(), Box::new(err))
+        })?;
+        service.destroy().map_err(|err| {
+            ServiceProcessorError::ProcessError("unable to destroy service".into(), Box::new(err))
+        })
+    })
+}
+
+/// Routes messages that did not have a matching correlation id to the service they are addressed
+/// to, waiting for room in the service's channel before taking the next message.
+fn route_inbound(
+    inbound_receiver: mpsc::Receiver<InboundMessage>,
+    services: HashMap<ServiceId, mpsc::Sender<ProcessorMessage>>,
+) -> impl Future<Item = (), Error = ()> {
+    inbound_receiver
+        .fold(services, |mut services, inbound_message| {
+            let service_message = match inbound_message
+                .map_err(|err| {
+                    ServiceProcessorError::ProcessError(
+                        "received service message error".into(),
+                        Box::new(err),
+                    )
+                })
+                .and_then(parse_inbound_msg)
+            {
+                Ok(Some(service_message)) => service_message,
+                Ok(None) => return Either::A(future::ok(services)),
+                Err(err) => {
+                    error!("Unable to process inbound message: {}", err);
+                    return Either::A(future::ok(services));
+                }
+            };
+
+            let recipient = service_message.recipient_id();
+            // The sender is taken out of the map while sending, rather than cloned, so that the
+            // channel's capacity is respected
+            match services.remove(&recipient) {
+                Some(sender) => Either::B(
+                    sender
+                        .send(ProcessorMessage::ServiceMessage(service_message))
+                        .then(move |res| {
+                            match res {
+                                Ok(sender) => {
+                                    services.insert(recipient, sender);
+                                }
+                                Err(_) => {
+                                    warn!("Service {} has stopped, ignoring message", recipient)
+                                }
+                            }
+                            Ok(services)
+                        }),
+                ),
+                None => {
+                    warn!(
+                        "Service with id {} does not exist, ignoring message",
+                        recipient
+                    );
+                    Either::A(future::ok(services))
+                }
+            }
+        })
+        .map(|_| debug!("inbound sender dropped; ending inbound message task"))
+}
+
+/// The sender used by the inbound router, which routes messages from the incoming mesh thread.
+///
+/// A single sender is shared between clones of the router so that the channel's capacity applies
+/// to all of them.
+struct InboundSender {
+    sender: Arc<Mutex<Wait<mpsc::Sender<InboundMessage>>>>,
+}
+
+impl channel::Sender<InboundMessage> for InboundSender {
+    fn send(&self, message: InboundMessage) -> Result<(), channel::SendError> {
+        mutex_lock_unwrap!(self.sender)
+            .send(message)
+            .map_err(|err| channel::SendError {
+                error: err.to_string(),
+            })
+    }
+
+    fn box_clone(&self) -> Box<dyn channel::Sender<InboundMessage>> {
+        Box::new(InboundSender {
+            sender: Arc::clone(&self.sender),
+        })
+    }
+}
@@ -12,21 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "service-processor-async")]
+mod async_processor;
 pub(crate) mod registry;
 mod sender;
+mod sequence;
 
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::Receiver;
+#[cfg(not(feature = "service-processor-async"))]
+use crossbeam_channel::Sender;
+#[cfg(feature = "service-endpoint-auth")]
+use cylinder::Signer;
 use uuid::Uuid;
 
+#[cfg(not(feature = "service-processor-async"))]
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+#[cfg(not(feature = "service-processor-async"))]
+use std::sync::RwLock;
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 use crate::channel;
+#[cfg(not(feature = "service-processor-async"))]
+use crate::circuit::routing::ServiceId;
 use crate::mesh::{Envelope, Mesh, RecvTimeoutError as MeshRecvTimeoutError};
 use crate::network::reply::InboundRouter;
+#[cfg(feature = "service-endpoint-auth")]
+use crate::protos::circuit::ServiceAuthChallengeResponse;
 use crate::protos::circuit::{
     AdminDirectMessage, CircuitDirectMessage, CircuitError, CircuitMessage, CircuitMessageType,
     ServiceConnectResponse, ServiceDisconnectResponse,
@@ -35,18 +49,27 @@ use crate::protos::network::{NetworkMessage, NetworkMessageType};
 use crate::service::error::ServiceProcessorError;
 use crate::service::{Service, ServiceMessageContext};
 use crate::transport::Connection;
+#[cfg(not(feature = "service-processor-async"))]
 use crate::{rwlock_read_unwrap, rwlock_write_unwrap};
 
+#[cfg(not(feature = "service-processor-async"))]
 use self::registry::StandardServiceNetworkRegistry;
+#[cfg(not(feature = "service-processor-async"))]
+use self::sender::send_or_wait;
 use self::sender::{ProcessorMessage, ServiceMessage};
+pub use self::sequence::SequenceNumbers;
 
 // Recv timeout in secs
 const TIMEOUT_SEC: u64 = 2;
 
+/// Messages received from the splinter node that were not claimed by a pending reply.
+type InboundMessage = Result<(CircuitMessageType, Vec<u8>), channel::RecvError>;
+
 /// State that can be passed between threads.
 /// Includes the service senders and join_handles for the service threads.
+#[cfg(not(feature = "service-processor-async"))]
 struct SharedState {
-    pub services: HashMap<String, Sender<ProcessorMessage>>,
+    pub services: HashMap<ServiceId, Sender<ProcessorMessage>>,
     pub join_handles: Vec<JoinHandle<Result<(), ServiceProcessorError>>>,
 }
 
@@ -71,18 +94,32 @@ macro_rules! to_process_err {
 /// The ServiceProcessor handles the networking for services. This includes talking to the
 /// splinter node, connecting for authorization, registering the services, and routing
 /// direct messages to the correct service.
+///
+/// With the `service-processor-async` feature, services run as tasks on a small tokio runtime
+/// instead of each getting a dedicated thread.
+///
+/// With the `service-processor-multiplex` feature, services on other circuits than the
+/// processor's can be added with `add_circuit_service`, so that a single connection to the
+/// splinter node carries the messages of all of them.
 pub struct ServiceProcessor {
+    #[cfg(not(feature = "service-processor-async"))]
     shared_state: Arc<RwLock<SharedState>>,
-    services: Vec<Box<dyn Service>>,
+    services: Vec<(String, Box<dyn Service>)>,
     mesh: Mesh,
     circuit: String,
     node_mesh_id: String,
-    network_sender: Sender<Vec<u8>>,
+    network_sender: crossbeam_channel::Sender<Vec<u8>>,
     network_receiver: Receiver<Vec<u8>>,
     running: Arc<AtomicBool>,
     inbound_router: InboundRouter<CircuitMessageType>,
-    inbound_receiver: Receiver<Result<(CircuitMessageType, Vec<u8>), channel::RecvError>>,
+    #[cfg(not(feature = "service-processor-async"))]
+    inbound_receiver: Receiver<InboundMessage>,
+    #[cfg(feature = "service-processor-async")]
+    inbound_receiver: futures::sync::mpsc::Receiver<InboundMessage>,
     channel_capacity: usize,
+    sequence_numbers: SequenceNumbers,
+    #[cfg(feature = "service-endpoint-auth")]
+    signer: Option<Box<dyn Signer>>,
 }
 
 impl ServiceProcessor {
@@ -99,8 +136,18 @@ impl ServiceProcessor {
         mesh.add(connection, node_mesh_id.to_string())
             .map_err(|err| process_err!(err, "unable to add connection to mesh"))?;
         let (network_sender, network_receiver) = crossbeam_channel::bounded(channel_capacity);
-        let (inbound_sender, inbound_receiver) = crossbeam_channel::bounded(channel_capacity);
+        #[cfg(not(feature = "service-processor-async"))]
+        let (inbound_router, inbound_receiver) = {
+            let (inbound_sender, inbound_receiver) = crossbeam_channel::bounded(channel_capacity);
+            (
+                InboundRouter::new(Box::new(inbound_sender)),
+                inbound_receiver,
+            )
+        };
+        #[cfg(feature = "service-processor-async")]
+        let (inbound_router, inbound_receiver) = async_processor::inbound_channel(channel_capacity);
         Ok(ServiceProcessor {
+            #[cfg(not(feature = "service-processor-async"))]
             shared_state: Arc::new(RwLock::new(SharedState {
                 services: HashMap::new(),
                 join_handles: vec![],
@@ -112,27 +159,67 @@ impl ServiceProcessor {
             network_sender,
             network_receiver,
             running,
-            inbound_router: InboundRouter::new(Box::new(inbound_sender)),
+            inbound_router,
             inbound_receiver,
             channel_capacity,
+            sequence_numbers: SequenceNumbers::new(),
+            #[cfg(feature = "service-endpoint-auth")]
+            signer: None,
         })
     }
 
+    /// Numbers the direct messages sent by the processor's services with the given sequence
+    /// numbers, so they keep increasing across restarts when the numbers are backed by a file.
+    pub fn with_sequence_numbers(mut self, sequence_numbers: SequenceNumbers) -> Self {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
+
+    /// Authenticates the processor's services with the given signer when they connect, as
+    /// required for services that have a key registered in their circuit definition.
+    #[cfg(feature = "service-endpoint-auth")]
+    pub fn with_signer(mut self, signer: Box<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
     /// add_service takes a Service and sets up the thread that the service will run in.
     /// The service will be started, including registration and then messages are routed to the
     /// the services using a channel.
     pub fn add_service(&mut self, service: Box<dyn Service>) -> Result<(), ServiceProcessorError> {
+        let circuit = self.circuit.clone();
+        self.insert_service(circuit, service)
+    }
+
+    /// add_circuit_service adds a service that connects to the given circuit instead of the
+    /// processor's circuit. Its messages are carried over the processor's connection, along with
+    /// those of every other service the processor hosts.
+    #[cfg(feature = "service-processor-multiplex")]
+    pub fn add_circuit_service(
+        &mut self,
+        circuit: String,
+        service: Box<dyn Service>,
+    ) -> Result<(), ServiceProcessorError> {
+        self.insert_service(circuit, service)
+    }
+
+    fn insert_service(
+        &mut self,
+        circuit: String,
+        service: Box<dyn Service>,
+    ) -> Result<(), ServiceProcessorError> {
         if self
             .services
             .iter()
-            .any(|s| s.service_id() == service.service_id())
+            .any(|(c, s)| c == &circuit && s.service_id() == service.service_id())
         {
             Err(ServiceProcessorError::AddServiceError(format!(
-                "{} already exists",
+                "{}::{} already exists",
+                circuit,
                 service.service_id()
             )))
         } else {
-            self.services.push(service);
+            self.services.push((circuit, service));
 
             Ok(())
         }
@@ -142,6 +229,7 @@ impl ServiceProcessor {
     /// node and route it to a running service.
     ///
     /// Returns a ShutdownHandle and join_handles so the service can be properly shutdown.
+    #[cfg(not(feature = "service-processor-async"))]
     pub fn start(
         self,
     ) -> Result<
@@ -151,21 +239,30 @@ impl ServiceProcessor {
         ),
         ServiceProcessorError,
     > {
-        for service in self.services.into_iter() {
+        for (circuit, service) in self.services.into_iter() {
             let mut shared_state = rwlock_write_unwrap!(self.shared_state);
-            let service_id = service.service_id().to_string();
+            let service_id = ServiceId::new(circuit.clone(), service.service_id().to_string());
 
             let (send, recv) = crossbeam_channel::bounded(self.channel_capacity);
             let network_sender = self.network_sender.clone();
-            let circuit = self.circuit.clone();
             let inbound_router = self.inbound_router.clone();
+            let sequence_numbers = self.sequence_numbers.clone();
+            #[cfg(feature = "service-endpoint-auth")]
+            let signer = self.signer.as_ref().map(|signer| signer.clone_box());
             let join_handle = thread::Builder::new()
                 .name(format!("Service {}", service_id))
                 .spawn(move || {
                     let service_id = service.service_id().to_string();
-                    if let Err(err) =
-                        run_service_loop(circuit, service, network_sender, recv, inbound_router)
-                    {
+                    if let Err(err) = run_service_loop(
+                        circuit,
+                        service,
+                        network_sender,
+                        recv,
+                        inbound_router,
+                        sequence_numbers,
+                        #[cfg(feature = "service-endpoint-auth")]
+                        signer,
+                    ) {
                         error!("Terminating service {} due to error: {}", service_id, err);
                         Err(err)
                     } else {
@@ -173,47 +270,16 @@ impl ServiceProcessor {
                     }
                 })?;
             shared_state.join_handles.push(join_handle);
-            shared_state.services.insert(service_id.to_string(), send);
+            shared_state.services.insert(service_id, send);
         }
 
-        let incoming_mesh = self.mesh.clone();
-        let shared_state = self.shared_state.clone();
-        let incoming_running = self.running.clone();
-        let mut inbound_router = self.inbound_router.clone();
-        // Thread to handle incoming messages from a splinter node.
-        let incoming_join_handle: JoinHandle<Result<(), ServiceProcessorError>> =
-            thread::Builder::new()
-                .name("ServiceProcessor incoming".into())
-                .spawn(move || {
-                    while incoming_running.load(Ordering::SeqCst) {
-                        let timeout = Duration::from_secs(TIMEOUT_SEC);
-                        let message_bytes = match incoming_mesh.recv_timeout(timeout) {
-                            Ok(envelope) => Vec::from(envelope),
-                            Err(MeshRecvTimeoutError::Timeout) => continue,
-                            Err(MeshRecvTimeoutError::Disconnected) => {
-                                error!("Mesh Disconnected");
-                                break;
-                            }
-                            Err(MeshRecvTimeoutError::PoisonedLock) => {
-                                error!("Mesh lock was poisoned");
-                                break;
-                            }
-                            Err(MeshRecvTimeoutError::Shutdown) => {
-                                error!("Mesh has shutdown");
-                                break;
-                            }
-                        };
-
-                        if let Err(err) = process_incoming_msg(&message_bytes, &mut inbound_router)
-                        {
-                            error!("Unable to process message: {}", err);
-                            continue;
-                        }
-                    }
-
-                    Ok(())
-                })?;
+        let incoming_join_handle = spawn_incoming_thread(
+            self.mesh.clone(),
+            self.running.clone(),
+            self.inbound_router.clone(),
+        )?;
 
+        let shared_state = self.shared_state.clone();
         let inbound_receiver = self.inbound_receiver;
         let inbound_running = self.running.clone();
         // Thread that handles messages that do not have a matching correlation id
@@ -242,40 +308,12 @@ impl ServiceProcessor {
                     Ok(())
                 })?;
 
-        let outgoing_mesh = self.mesh;
-        let outgoing_running = self.running.clone();
-        let outgoing_receiver = self.network_receiver;
-        let node_mesh_id = self.node_mesh_id.to_string();
-
-        // Thread that handles outgoing messages that need to be sent to the splinter node
-        let outgoing_join_handle: JoinHandle<Result<(), ServiceProcessorError>> =
-            thread::Builder::new()
-                .name("ServiceProcessor outgoing".into())
-                .spawn(move || {
-                    while outgoing_running.load(Ordering::SeqCst) {
-                        let timeout = Duration::from_secs(TIMEOUT_SEC);
-                        let message_bytes = match outgoing_receiver.recv_timeout(timeout) {
-                            Ok(msg) => msg,
-                            Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
-                            Err(err) => {
-                                error!("channel dropped while handling outgoing messages: {}", err);
-                                break;
-                            }
-                        };
-
-                        // Send message to splinter node
-                        if let Err(err) = outgoing_mesh
-                            .send(Envelope::new(node_mesh_id.to_string(), message_bytes))
-                        {
-                            error!(
-                                "Unable to send message via mesh to {}: {}",
-                                node_mesh_id, err
-                            );
-                            continue;
-                        }
-                    }
-                    Ok(())
-                })?;
+        let outgoing_join_handle = spawn_outgoing_thread(
+            self.mesh,
+            self.running.clone(),
+            self.network_receiver,
+            self.node_mesh_id.to_string(),
+        )?;
 
         let shutdown_shared_state = self.shared_state;
         // Creates the shutdown handle that will be called by the process starting up the
@@ -316,6 +354,103 @@ impl ServiceProcessor {
             ]),
         ))
     }
+
+    /// Once the service processor is started it will handle incoming messages from the splinter
+    /// node and route it to a running service.
+    ///
+    /// Services are run as tasks on a tokio runtime owned by the processor; only the mesh
+    /// receive and send loops keep dedicated threads.
+    ///
+    /// Returns a ShutdownHandle and join_handles so the service can be properly shutdown.
+    #[cfg(feature = "service-processor-async")]
+    pub fn start(
+        self,
+    ) -> Result<
+        (
+            ShutdownHandle,
+            JoinHandles<Result<(), ServiceProcessorError>>,
+        ),
+        ServiceProcessorError,
+    > {
+        async_processor::start(self)
+    }
+}
+
+/// Spawns the thread that receives messages from the splinter node and hands them to the inbound
+/// router.
+fn spawn_incoming_thread(
+    incoming_mesh: Mesh,
+    incoming_running: Arc<AtomicBool>,
+    mut inbound_router: InboundRouter<CircuitMessageType>,
+) -> Result<JoinHandle<Result<(), ServiceProcessorError>>, ServiceProcessorError> {
+    thread::Builder::new()
+        .name("ServiceProcessor incoming".into())
+        .spawn(move || {
+            while incoming_running.load(Ordering::SeqCst) {
+                let timeout = Duration::from_secs(TIMEOUT_SEC);
+                let message_bytes = match incoming_mesh.recv_timeout(timeout) {
+                    Ok(envelope) => Vec::from(envelope),
+                    Err(MeshRecvTimeoutError::Timeout) => continue,
+                    Err(MeshRecvTimeoutError::Disconnected) => {
+                        error!("Mesh Disconnected");
+                        break;
+                    }
+                    Err(MeshRecvTimeoutError::PoisonedLock) => {
+                        error!("Mesh lock was poisoned");
+                        break;
+                    }
+                    Err(MeshRecvTimeoutError::Shutdown) => {
+                        error!("Mesh has shutdown");
+                        break;
+                    }
+                };
+
+                if let Err(err) = process_incoming_msg(&message_bytes, &mut inbound_router) {
+                    error!("Unable to process message: {}", err);
+                    continue;
+                }
+            }
+
+            Ok(())
+        })
+        .map_err(ServiceProcessorError::from)
+}
+
+/// Spawns the thread that handles outgoing messages that need to be sent to the splinter node.
+fn spawn_outgoing_thread(
+    outgoing_mesh: Mesh,
+    outgoing_running: Arc<AtomicBool>,
+    outgoing_receiver: Receiver<Vec<u8>>,
+    node_mesh_id: String,
+) -> Result<JoinHandle<Result<(), ServiceProcessorError>>, ServiceProcessorError> {
+    thread::Builder::new()
+        .name("ServiceProcessor outgoing".into())
+        .spawn(move || {
+            while outgoing_running.load(Ordering::SeqCst) {
+                let timeout = Duration::from_secs(TIMEOUT_SEC);
+                let message_bytes = match outgoing_receiver.recv_timeout(timeout) {
+                    Ok(msg) => msg,
+                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
+                    Err(err) => {
+                        error!("channel dropped while handling outgoing messages: {}", err);
+                        break;
+                    }
+                };
+
+                // Send message to splinter node
+                if let Err(err) =
+                    outgoing_mesh.send(Envelope::new(node_mesh_id.to_string(), message_bytes))
+                {
+                    error!(
+                        "Unable to send message via mesh to {}: {}",
+                        node_mesh_id, err
+                    );
+                    continue;
+                }
+            }
+            Ok(())
+        })
+        .map_err(ServiceProcessorError::from)
 }
 
 fn process_incoming_msg(
@@ -376,6 +511,22 @@ fn process_incoming_msg(
                         )
                         .map_err(to_process_err!("unable to route message"))?;
                 }
+                #[cfg(feature = "service-endpoint-auth")]
+                CircuitMessageType::SERVICE_AUTH_CHALLENGE_RESPONSE => {
+                    let response: ServiceAuthChallengeResponse =
+                        protobuf::parse_from_bytes(circuit_msg.get_payload()).map_err(|err| {
+                            process_err!(err, "unable to parse service auth challenge response")
+                        })?;
+                    inbound_router
+                        .route(
+                            response.get_correlation_id(),
+                            Ok((
+                                CircuitMessageType::SERVICE_AUTH_CHALLENGE_RESPONSE,
+                                circuit_msg.take_payload(),
+                            )),
+                        )
+                        .map_err(to_process_err!("unable to route message"))?;
+                }
                 CircuitMessageType::SERVICE_DISCONNECT_RESPONSE => {
                     let response: ServiceDisconnectResponse =
                         protobuf::parse_from_bytes(circuit_msg.get_payload()).map_err(|err| {
@@ -401,10 +552,11 @@ fn process_incoming_msg(
     Ok(())
 }
 
-fn process_inbound_msg_with_correlation_id(
+/// Parses a message that did not have a matching correlation id into the message that should be
+/// delivered to a service, if any.
+fn parse_inbound_msg(
     service_message: (CircuitMessageType, Vec<u8>),
-    shared_state: &Arc<RwLock<SharedState>>,
-) -> Result<(), ServiceProcessorError> {
+) -> Result<Option<ServiceMessage>, ServiceProcessorError> {
     match service_message {
         (CircuitMessageType::ADMIN_DIRECT_MESSAGE, msg) => {
             let admin_direct_message: AdminDirectMessage = protobuf::parse_from_bytes(&msg)
@@ -412,9 +564,9 @@ fn process_inbound_msg_with_correlation_id(
                     "unable to parse inbound admin direct message"
                 ))?;
 
-            handle_admin_direct_msg(admin_direct_message, &shared_state).map_err(
-                to_process_err!("unable to handle inbound admin direct message"),
-            )?;
+            Ok(Some(ServiceMessage::AdminDirectMessage(
+                admin_direct_message,
+            )))
         }
         (CircuitMessageType::CIRCUIT_DIRECT_MESSAGE, msg) => {
             let circuit_direct_message: CircuitDirectMessage = protobuf::parse_from_bytes(&msg)
@@ -422,19 +574,33 @@ fn process_inbound_msg_with_correlation_id(
                     "unable to parse inbound circuit direct message"
                 ))?;
 
-            handle_circuit_direct_msg(circuit_direct_message, &shared_state).map_err(
-                to_process_err!("unable to handle inbound circuit direct message"),
-            )?;
+            Ok(Some(ServiceMessage::CircuitDirectMessage(
+                circuit_direct_message,
+            )))
         }
         (CircuitMessageType::CIRCUIT_ERROR_MESSAGE, msg) => {
             let response: CircuitError = protobuf::parse_from_bytes(&msg)
                 .map_err(to_process_err!("unable to parse circuit error message"))?;
             warn!("Received circuit error message {:?}", response);
+            Ok(None)
         }
-        (msg_type, _) => warn!(
-            "Received message ({:?}) that does not have a correlation id",
-            msg_type
-        ),
+        (msg_type, _) => {
+            warn!(
+                "Received message ({:?}) that does not have a correlation id",
+                msg_type
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(not(feature = "service-processor-async"))]
+fn process_inbound_msg_with_correlation_id(
+    service_message: (CircuitMessageType, Vec<u8>),
+    shared_state: &Arc<RwLock<SharedState>>,
+) -> Result<(), ServiceProcessorError> {
+    if let Some(service_message) = parse_inbound_msg(service_message)? {
+        handle_service_msg(service_message, shared_state)?;
     }
     Ok(())
 }
@@ -469,15 +635,24 @@ impl ShutdownHandle {
     }
 }
 
+#[cfg(not(feature = "service-processor-async"))]
 fn run_service_loop(
     circuit: String,
     mut service: Box<dyn Service>,
     network_sender: Sender<Vec<u8>>,
     service_recv: Receiver<ProcessorMessage>,
     inbound_router: InboundRouter<CircuitMessageType>,
+    sequence_numbers: SequenceNumbers,
+    #[cfg(feature = "service-endpoint-auth")] signer: Option<Box<dyn Signer>>,
 ) -> Result<(), ServiceProcessorError> {
     info!("Starting Service: {}", service.service_id());
-    let registry = StandardServiceNetworkRegistry::new(circuit, network_sender, inbound_router);
+    let registry = StandardServiceNetworkRegistry::new(circuit, network_sender, inbound_router)
+        .with_sequence_numbers(sequence_numbers);
+    #[cfg(feature = "service-endpoint-auth")]
+    let registry = match signer {
+        Some(signer) => registry.with_signer(signer),
+        None => registry,
+    };
     service.start(&registry).map_err(to_process_err!(
         "unable to start service {}",
         service.service_id()
@@ -499,81 +674,60 @@ fn run_service_loop(
             Err(err) => Err(process_err!(err, "unable to receive service messages")),
         }?;
 
-        match service_message {
-            ServiceMessage::AdminDirectMessage(mut admin_direct_message) => {
-                let msg_context = ServiceMessageContext {
-                    sender: admin_direct_message.take_sender(),
-                    circuit: admin_direct_message.take_circuit(),
-                    correlation_id: admin_direct_message.take_correlation_id(),
-                };
-
-                if let Err(err) =
-                    service.handle_message(admin_direct_message.get_payload(), &msg_context)
-                {
-                    error!("unable to handle admin direct message: {}", err);
-                }
-            }
-            ServiceMessage::CircuitDirectMessage(mut direct_message) => {
-                let msg_context = ServiceMessageContext {
-                    sender: direct_message.take_sender(),
-                    circuit: direct_message.take_circuit(),
-                    correlation_id: direct_message.take_correlation_id(),
-                };
-
-                if let Err(err) = service.handle_message(direct_message.get_payload(), &msg_context)
-                {
-                    error!("unable to handle circuit direct message: {}", err);
-                }
-            }
-        }
+        handle_service_message(&*service, service_message);
     }
     Ok(())
 }
 
-fn handle_circuit_direct_msg(
-    direct_message: CircuitDirectMessage,
-    shared_state: &Arc<RwLock<SharedState>>,
-) -> Result<(), ServiceProcessorError> {
-    let shared_state = rwlock_read_unwrap!(shared_state);
-
-    if let Some(service_sender) = shared_state.services.get(direct_message.get_recipient()) {
-        service_sender
-            .send(ProcessorMessage::ServiceMessage(
-                ServiceMessage::CircuitDirectMessage(direct_message),
-            ))
-            .map_err(to_process_err!(
-                "unable to send service (circuit direct) message"
-            ))?;
-    } else {
-        warn!(
-            "Service with id {} does not exist, ignoring message",
-            direct_message.get_recipient()
-        );
+/// Passes a message routed to a service to its `handle_message` implementation.
+fn handle_service_message(service: &dyn Service, service_message: ServiceMessage) {
+    match service_message {
+        ServiceMessage::AdminDirectMessage(mut admin_direct_message) => {
+            let msg_context = ServiceMessageContext {
+                sender: admin_direct_message.take_sender(),
+                circuit: admin_direct_message.take_circuit(),
+                correlation_id: admin_direct_message.take_correlation_id(),
+            };
+
+            if let Err(err) =
+                service.handle_message(admin_direct_message.get_payload(), &msg_context)
+            {
+                error!("unable to handle admin direct message: {}", err);
+            }
+        }
+        ServiceMessage::CircuitDirectMessage(mut direct_message) => {
+            let msg_context = ServiceMessageContext {
+                sender: direct_message.take_sender(),
+                circuit: direct_message.take_circuit(),
+                correlation_id: direct_message.take_correlation_id(),
+            };
+
+            if let Err(err) = service.handle_message(direct_message.get_payload(), &msg_context) {
+                error!("unable to handle circuit direct message: {}", err);
+            }
+        }
     }
-    Ok(())
 }
 
-fn handle_admin_direct_msg(
-    admin_direct_message: AdminDirectMessage,
+#[cfg(not(feature = "service-processor-async"))]
+fn handle_service_msg(
+    service_message: ServiceMessage,
     shared_state: &Arc<RwLock<SharedState>>,
 ) -> Result<(), ServiceProcessorError> {
     let shared_state = rwlock_read_unwrap!(shared_state);
 
-    if let Some(service_sender) = shared_state
-        .services
-        .get(admin_direct_message.get_recipient())
-    {
-        service_sender
-            .send(ProcessorMessage::ServiceMessage(
-                ServiceMessage::AdminDirectMessage(admin_direct_message),
-            ))
-            .map_err(to_process_err!(
-                "unable to send service (admin direct) message"
-            ))?;
+    let recipient = service_message.recipient_id();
+    if let Some(service_sender) = shared_state.services.get(&recipient) {
+        send_or_wait(
+            service_sender,
+            ProcessorMessage::ServiceMessage(service_message),
+            "service_inbound",
+        )
+        .map_err(to_process_err!("unable to send service message"))?;
     } else {
         warn!(
             "Service with id {} does not exist, ignoring message",
-            admin_direct_message.get_recipient()
+            recipient
         );
     }
     Ok(())
@@ -696,6 +850,85 @@ pub mod tests {
         r.store(false, Ordering::SeqCst);
     }
 
+    #[cfg(feature = "service-processor-multiplex")]
+    #[test]
+    // Verifies that services with the same ID on different circuits can share the processor's
+    // connection, and that messages are routed to the service on the circuit they were sent over.
+    fn multiplexed_direct_message() {
+        let mut transport = InprocTransport::default();
+        let mut inproc_listener = transport.listen("internal").unwrap();
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+
+        let mesh = Mesh::new(512, 128);
+        let mesh_sender = mesh.get_sender();
+
+        thread::Builder::new()
+            .name("multiplexed_direct_message".to_string())
+            .spawn(move || {
+                let connection = transport.connect("internal").unwrap();
+                let mut processor =
+                    ServiceProcessor::new(connection, "alpha".to_string(), 3, 3, 3, running)
+                        .unwrap();
+
+                processor.add_service(Box::new(MockService::new())).unwrap();
+                processor
+                    .add_circuit_service("beta".to_string(), Box::new(MockService::new()))
+                    .unwrap();
+                assert!(processor
+                    .add_circuit_service("beta".to_string(), Box::new(MockService::new()))
+                    .is_err());
+                let _ = processor.start().unwrap();
+            })
+            .unwrap();
+
+        let connection = inproc_listener.accept().unwrap();
+        mesh.add(connection, "service_processor".to_string())
+            .unwrap();
+
+        // Both services connect over the same connection
+        let mut circuits = vec![];
+        for _ in 0..2 {
+            let mut service_request = get_service_connect(mesh.recv().unwrap().payload().to_vec());
+            assert_eq!(service_request.get_service_id(), "mock_service");
+            circuits.push(service_request.get_circuit().to_string());
+
+            let service_response = create_service_connect_response(
+                service_request.take_correlation_id(),
+                service_request.take_circuit(),
+            )
+            .unwrap();
+            mesh_sender
+                .send("service_processor".to_string(), service_response)
+                .unwrap();
+        }
+        circuits.sort();
+        assert_eq!(circuits, vec!["alpha".to_string(), "beta".to_string()]);
+
+        // Each service replies over the circuit it is connected to
+        for circuit in &["beta", "alpha"] {
+            let mut direct_message = CircuitDirectMessage::new();
+            direct_message.set_recipient("mock_service".to_string());
+            direct_message.set_sender("service_a".to_string());
+            direct_message.set_circuit(circuit.to_string());
+            direct_message.set_payload(b"send".to_vec());
+            let send_msg = create_message(
+                direct_message.write_to_bytes().unwrap(),
+                CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            )
+            .unwrap();
+            mesh_sender
+                .send("service_processor".to_string(), send_msg)
+                .unwrap();
+
+            let send_response = get_circuit_direct_msg(mesh.recv().unwrap().payload().to_vec());
+            assert_eq!(send_response.get_payload(), b"send_response");
+            assert_eq!(send_response.get_circuit(), *circuit);
+        }
+
+        r.store(false, Ordering::SeqCst);
+    }
+
     #[test]
     fn test_admin_direct_message() {
         let mut transport = InprocTransport::default();
@@ -12,27 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crossbeam_channel::Sender;
+#[cfg(feature = "service-endpoint-auth")]
+use cylinder::Signer;
 use protobuf::Message;
 use uuid::Uuid;
 
+#[cfg(feature = "service-endpoint-auth")]
+use crate::circuit::service_auth::challenge_message;
 use crate::network::reply::InboundRouter;
 use crate::protos::circuit::{
     CircuitMessageType, ServiceConnectRequest, ServiceConnectResponse,
     ServiceConnectResponse_Status, ServiceDisconnectRequest, ServiceDisconnectResponse,
     ServiceDisconnectResponse_Status,
 };
+#[cfg(feature = "service-endpoint-auth")]
+use crate::protos::circuit::{ServiceAuthChallengeRequest, ServiceAuthChallengeResponse};
 use crate::service::error::{ServiceConnectionError, ServiceDisconnectionError};
 use crate::service::{ServiceNetworkRegistry, ServiceNetworkSender};
 
 use super::sender::create_message;
 use super::sender::{AdminServiceNetworkSender, StandardServiceNetworkSender};
+use super::sequence::SequenceNumbers;
 
-const ADMIN_CIRCUIT_NAME: &str = "admin";
+pub(super) const ADMIN_CIRCUIT_NAME: &str = "admin";
 
 pub struct StandardServiceNetworkRegistry {
     circuit: String,
     outgoing_sender: Sender<Vec<u8>>,
     inbound_router: InboundRouter<CircuitMessageType>,
+    sequence_numbers: SequenceNumbers,
+    #[cfg(feature = "service-endpoint-auth")]
+    signer: Option<Box<dyn Signer>>,
 }
 
 /// This is an implementation of ServiceNetworkRegistry that can be used by a standard service
@@ -47,8 +57,59 @@ impl StandardServiceNetworkRegistry {
             circuit,
             outgoing_sender,
             inbound_router,
+            sequence_numbers: SequenceNumbers::new(),
+            #[cfg(feature = "service-endpoint-auth")]
+            signer: None,
         }
     }
+
+    /// Numbers the direct messages sent by the connected services with the given sequence
+    /// numbers, so they keep increasing across restarts when the numbers are backed by a file.
+    pub fn with_sequence_numbers(mut self, sequence_numbers: SequenceNumbers) -> Self {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
+
+    /// Signs the node's challenge with the given signer when connecting services, as required for
+    /// services that have a key registered in their circuit definition.
+    #[cfg(feature = "service-endpoint-auth")]
+    pub fn with_signer(mut self, signer: Box<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Sends a ServiceAuthChallengeRequest for the provided service_id and blocks until the
+    /// nonce to sign is returned from the splinter node
+    #[cfg(feature = "service-endpoint-auth")]
+    fn request_challenge(&self, service_id: &str) -> Result<Vec<u8>, ServiceConnectionError> {
+        let correlation_id = Uuid::new_v4().to_string();
+        let mut challenge_msg = ServiceAuthChallengeRequest::new();
+        challenge_msg.set_circuit(self.circuit.to_string());
+        challenge_msg.set_service_id(service_id.to_string());
+        challenge_msg.set_correlation_id(correlation_id.clone());
+
+        let challenge_msg_bytes = challenge_msg
+            .write_to_bytes()
+            .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
+
+        let msg_bytes = create_message(
+            challenge_msg_bytes,
+            CircuitMessageType::SERVICE_AUTH_CHALLENGE_REQUEST,
+        )
+        .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
+
+        let mut future = self.inbound_router.expect_reply(correlation_id);
+
+        self.outgoing_sender
+            .send(msg_bytes)
+            .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
+
+        let mut response: ServiceAuthChallengeResponse = future
+            .get()
+            .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
+
+        Ok(response.take_nonce())
+    }
 }
 
 impl ServiceNetworkRegistry for StandardServiceNetworkRegistry {
@@ -64,6 +125,17 @@ impl ServiceNetworkRegistry for StandardServiceNetworkRegistry {
         connect_msg.set_service_id(service_id.to_string());
         connect_msg.set_correlation_id(correlation_id.clone());
 
+        #[cfg(feature = "service-endpoint-auth")]
+        {
+            if let Some(signer) = &self.signer {
+                let nonce = self.request_challenge(service_id)?;
+                let signature = signer
+                    .sign(&challenge_message(&self.circuit, service_id, &nonce))
+                    .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
+                connect_msg.set_signature(signature.take_bytes());
+            }
+        }
+
         let connect_msg_bytes = connect_msg
             .write_to_bytes()
             .map_err(|err| ServiceConnectionError::ConnectionError(Box::new(err)))?;
@@ -103,7 +175,8 @@ impl ServiceNetworkRegistry for StandardServiceNetworkRegistry {
                 self.circuit.to_string(),
                 service_id.to_string(),
                 self.inbound_router.clone(),
-            );
+            )
+            .with_sequence_numbers(self.sequence_numbers.clone());
             Ok(Box::new(standard_network_sender))
         }
     }
@@ -254,6 +327,93 @@ pub mod tests {
         let _service_network_sender = registry.connect("service_a").unwrap();
     }
 
+    #[cfg(feature = "service-endpoint-auth")]
+    #[test]
+    // Test connecting a standard service with a signer. The service requests a challenge before
+    // connecting, and signs the nonce in its connect request.
+    fn test_signed_connect() {
+        use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
+
+        use crate::protos::circuit::{ServiceAuthChallengeRequest, ServiceAuthChallengeResponse};
+
+        let context = Secp256k1Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let public_key = signer.public_key().unwrap();
+
+        let (outgoing_sender, outgoing_receiver) = crossbeam_channel::bounded(3);
+        let (internal_sender, _) = crossbeam_channel::bounded(3);
+        let mut inbound_router: InboundRouter<CircuitMessageType> =
+            InboundRouter::new(Box::new(internal_sender));
+        let registry = StandardServiceNetworkRegistry::new(
+            "test".to_string(),
+            outgoing_sender,
+            inbound_router.clone(),
+        )
+        .with_signer(signer);
+
+        thread::Builder::new()
+            .name("test_signed_connect".to_string())
+            .spawn(move || {
+                let msg_bytes = outgoing_receiver.recv().unwrap();
+                let network_msg: NetworkMessage = protobuf::parse_from_bytes(&msg_bytes).unwrap();
+                let circuit_msg: CircuitMessage =
+                    protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+                let mut challenge_request: ServiceAuthChallengeRequest =
+                    protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap();
+
+                let nonce = vec![1u8; 32];
+                let mut challenge = ServiceAuthChallengeResponse::new();
+                challenge.set_circuit(challenge_request.take_circuit());
+                challenge.set_service_id(challenge_request.take_service_id());
+                challenge.set_correlation_id(challenge_request.take_correlation_id());
+                challenge.set_nonce(nonce.clone());
+                inbound_router
+                    .route(
+                        challenge.get_correlation_id(),
+                        Ok((
+                            CircuitMessageType::SERVICE_AUTH_CHALLENGE_RESPONSE,
+                            challenge.write_to_bytes().expect("Failed to write bytes"),
+                        )),
+                    )
+                    .unwrap();
+
+                let msg_bytes = outgoing_receiver.recv().unwrap();
+                let network_msg: NetworkMessage = protobuf::parse_from_bytes(&msg_bytes).unwrap();
+                let circuit_msg: CircuitMessage =
+                    protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+                let mut connect_request: ServiceConnectRequest =
+                    protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap();
+
+                assert!(Secp256k1Context::new()
+                    .new_verifier()
+                    .verify(
+                        &challenge_message("test", "service_a", &nonce),
+                        &Signature::new(connect_request.get_signature().to_vec()),
+                        &PublicKey::new(public_key.as_slice().to_vec()),
+                    )
+                    .unwrap());
+
+                let mut response = ServiceConnectResponse::new();
+                response.set_circuit(connect_request.take_circuit());
+                response.set_service_id(connect_request.take_service_id());
+                response.set_status(ServiceConnectResponse_Status::OK);
+                response.set_correlation_id(connect_request.take_correlation_id());
+
+                inbound_router
+                    .route(
+                        response.get_correlation_id(),
+                        Ok((
+                            CircuitMessageType::SERVICE_CONNECT_RESPONSE,
+                            response.write_to_bytes().expect("Failed to write bytes"),
+                        )),
+                    )
+                    .unwrap();
+            })
+            .unwrap();
+
+        let _service_network_sender = registry.connect("service_a").unwrap();
+    }
+
     #[test]
     // Test disconnecting a standard service from a splinter daemon. The connect function will
     // block until a response is returned.
@@ -11,10 +11,11 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use crossbeam_channel::Sender;
+use crossbeam_channel::{SendError, Sender, TrySendError};
 use protobuf::Message;
 use uuid::Uuid;
 
+use crate::circuit::routing::ServiceId;
 use crate::network::reply::InboundRouter;
 use crate::protos::circuit::{
     AdminDirectMessage, CircuitDirectMessage, CircuitMessage, CircuitMessageType,
@@ -23,12 +24,32 @@ use crate::protos::network::{NetworkMessage, NetworkMessageType};
 use crate::service::error::ServiceSendError;
 use crate::service::{ServiceMessageContext, ServiceNetworkSender};
 
+use super::registry::ADMIN_CIRCUIT_NAME;
+use super::sequence::SequenceNumbers;
+
 #[derive(Debug, Clone)]
 pub enum ServiceMessage {
     AdminDirectMessage(AdminDirectMessage),
     CircuitDirectMessage(CircuitDirectMessage),
 }
 
+impl ServiceMessage {
+    /// The circuit and ID of the service this message is addressed to.
+    ///
+    /// Admin direct messages can be sent over any circuit, but are always addressed to a service
+    /// on the admin circuit.
+    pub fn recipient_id(&self) -> ServiceId {
+        match self {
+            ServiceMessage::AdminDirectMessage(msg) => {
+                ServiceId::new(ADMIN_CIRCUIT_NAME.into(), msg.get_recipient().into())
+            }
+            ServiceMessage::CircuitDirectMessage(msg) => {
+                ServiceId::new(msg.get_circuit().into(), msg.get_recipient().into())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ProcessorMessage {
     ServiceMessage(ServiceMessage),
@@ -76,8 +97,7 @@ impl ServiceNetworkSender for AdminServiceNetworkSender {
         let msg = create_message(bytes, CircuitMessageType::ADMIN_DIRECT_MESSAGE)
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
-        self.outgoing_sender
-            .send(msg)
+        send_or_wait(&self.outgoing_sender, msg, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
         Ok(())
     }
@@ -103,8 +123,7 @@ impl ServiceNetworkSender for AdminServiceNetworkSender {
 
         let mut future = self.inbound_router.expect_reply(correlation_id);
 
-        self.outgoing_sender
-            .send(message)
+        send_or_wait(&self.outgoing_sender, message, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
         // block until the response is received
@@ -135,8 +154,7 @@ impl ServiceNetworkSender for AdminServiceNetworkSender {
         let message = create_message(bytes, CircuitMessageType::ADMIN_DIRECT_MESSAGE)
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
-        self.outgoing_sender
-            .send(message)
+        send_or_wait(&self.outgoing_sender, message, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
         Ok(())
     }
@@ -154,6 +172,7 @@ pub struct StandardServiceNetworkSender {
     circuit: String,
     message_sender: String,
     inbound_router: InboundRouter<CircuitMessageType>,
+    sequence_numbers: SequenceNumbers,
 }
 
 impl StandardServiceNetworkSender {
@@ -168,8 +187,16 @@ impl StandardServiceNetworkSender {
             circuit,
             message_sender,
             inbound_router,
+            sequence_numbers: SequenceNumbers::new(),
         }
     }
+
+    /// Numbers the sent messages with the given sequence numbers, which may be shared with the
+    /// senders of other services
+    pub fn with_sequence_numbers(mut self, sequence_numbers: SequenceNumbers) -> Self {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
 }
 
 impl ServiceNetworkSender for StandardServiceNetworkSender {
@@ -180,6 +207,12 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
         direct_message.set_sender(self.message_sender.to_string());
         direct_message.set_recipient(recipient.to_string());
         direct_message.set_payload(message.to_vec());
+        direct_message.set_message_id(Uuid::new_v4().to_string());
+        direct_message.set_sequence_number(
+            self.sequence_numbers
+                .next()
+                .map_err(|err| ServiceSendError(Box::new(err)))?,
+        );
 
         let bytes = direct_message
             .write_to_bytes()
@@ -188,8 +221,7 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
         let message = create_message(bytes, CircuitMessageType::CIRCUIT_DIRECT_MESSAGE)
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
-        self.outgoing_sender
-            .send(message)
+        send_or_wait(&self.outgoing_sender, message, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
         Ok(())
     }
@@ -203,6 +235,12 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
         direct_message.set_sender(self.message_sender.to_string());
         direct_message.set_recipient(recipient.to_string());
         direct_message.set_payload(message.to_vec());
+        direct_message.set_message_id(Uuid::new_v4().to_string());
+        direct_message.set_sequence_number(
+            self.sequence_numbers
+                .next()
+                .map_err(|err| ServiceSendError(Box::new(err)))?,
+        );
 
         let correlation_id = Uuid::new_v4().to_string();
         direct_message.set_correlation_id(correlation_id.to_string());
@@ -216,8 +254,7 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
 
         let mut future = self.inbound_router.expect_reply(correlation_id);
 
-        self.outgoing_sender
-            .send(message)
+        send_or_wait(&self.outgoing_sender, message, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
         // block until the response is received
@@ -239,6 +276,12 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
         direct_message.set_sender(self.message_sender.to_string());
         direct_message.set_recipient(message_origin.sender.to_string());
         direct_message.set_payload(message.to_vec());
+        direct_message.set_message_id(Uuid::new_v4().to_string());
+        direct_message.set_sequence_number(
+            self.sequence_numbers
+                .next()
+                .map_err(|err| ServiceSendError(Box::new(err)))?,
+        );
         direct_message.set_correlation_id(message_origin.correlation_id.to_string());
 
         let bytes = direct_message
@@ -248,8 +291,7 @@ impl ServiceNetworkSender for StandardServiceNetworkSender {
         let message = create_message(bytes, CircuitMessageType::CIRCUIT_DIRECT_MESSAGE)
             .map_err(|err| ServiceSendError(Box::new(err)))?;
 
-        self.outgoing_sender
-            .send(message)
+        send_or_wait(&self.outgoing_sender, message, "service_outgoing")
             .map_err(|err| ServiceSendError(Box::new(err)))?;
         Ok(())
     }
@@ -280,6 +322,28 @@ pub fn create_message(
     network_msg.write_to_bytes()
 }
 
+/// Send a message on a bounded channel, blocking if the channel is full.
+///
+/// A full channel means the consumer is not keeping up with the producer; each time this happens
+/// the `splinter.queue.full` counter is incremented for the given queue so that undersized
+/// capacities can be detected.
+pub(crate) fn send_or_wait<T>(
+    sender: &Sender<T>,
+    message: T,
+    queue: &'static str,
+) -> Result<(), SendError<T>> {
+    match sender.try_send(message) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(message)) => {
+            trace!("Queue {} is full, waiting for capacity", queue);
+            #[cfg(feature = "metrics")]
+            metrics::counter!("splinter.queue.full", 1, "queue" => queue);
+            sender.send(message)
+        }
+        Err(TrySendError::Disconnected(message)) => Err(SendError(message)),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -293,12 +357,14 @@ pub mod tests {
         let (internal_sender, _) = crossbeam_channel::bounded(3);
         let inbound_router: InboundRouter<CircuitMessageType> =
             InboundRouter::new(Box::new(internal_sender));
+        let sequence_numbers = SequenceNumbers::new();
         let network_sender = StandardServiceNetworkSender::new(
             outgoing_sender,
             "test_circuit".to_string(),
             "service_a".to_string(),
             inbound_router,
-        );
+        )
+        .with_sequence_numbers(sequence_numbers.clone());
 
         thread::Builder::new()
             .name("test_standard_send".to_string())
@@ -322,6 +388,14 @@ pub mod tests {
         assert_eq!(direct_message.get_sender(), "service_a");
         assert_eq!(direct_message.get_circuit(), "test_circuit");
         assert_eq!(direct_message.get_payload(), b"test_message");
+        assert!(!direct_message.get_message_id().is_empty());
+        assert!(direct_message.get_sequence_number() > 0);
+        assert!(
+            sequence_numbers
+                .next()
+                .expect("Unable to get sequence number")
+                > direct_message.get_sequence_number()
+        );
     }
 
     #[test]
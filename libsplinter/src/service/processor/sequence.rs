@@ -0,0 +1,200 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sequence numbers for the circuit direct messages sent by services.
+//!
+//! A peer drops a direct message whose sequence number is not greater than the ones it has already
+//! seen from the same sender, so the numbers a sender uses must keep increasing when its node
+//! restarts. The numbers are taken from a counter, not from the clock, so that a clock that is set
+//! back does not make new messages look like old ones. When the counter is backed by a file, the
+//! highest number that may have been used is written to the file before it is handed out, in
+//! blocks of `RESERVED_SEQUENCE_NUMBERS`, and a restarted node continues above it.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::InternalError;
+
+/// The number of sequence numbers reserved with each write of the state file
+const RESERVED_SEQUENCE_NUMBERS: u64 = 10_000;
+
+struct SequenceState {
+    next: u64,
+    // the highest number written to the state file; numbers up to it may be handed out
+    reserved: u64,
+    path: Option<PathBuf>,
+}
+
+/// A counter of the sequence numbers set on the direct messages sent by services.
+///
+/// The counter is cheap to clone; all clones hand out numbers from the same sequence.
+#[derive(Clone)]
+pub struct SequenceNumbers {
+    state: Arc<Mutex<SequenceState>>,
+}
+
+impl SequenceNumbers {
+    /// Creates a counter that is kept in memory only.
+    ///
+    /// The counter starts at the current time in microseconds, so it continues above the numbers
+    /// used before a restart as long as the clock has not been set back.
+    pub fn new() -> Self {
+        SequenceNumbers {
+            state: Arc::new(Mutex::new(SequenceState {
+                next: now_micros(),
+                reserved: u64::MAX,
+                path: None,
+            })),
+        }
+    }
+
+    /// Creates a counter that is backed by the file at the given path.
+    ///
+    /// The counter starts above the highest number recorded in the file, or at the current time in
+    /// microseconds if that is higher, and the first block of numbers is reserved immediately.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<Self, InternalError> {
+        let path = path.into();
+        let recorded = if path.is_file() {
+            fs::read_to_string(&path)
+                .map_err(|err| {
+                    InternalError::from_source_with_prefix(
+                        Box::new(err),
+                        format!("Unable to read sequence number file {}", path.display()),
+                    )
+                })?
+                .trim()
+                .parse::<u64>()
+                .map_err(|err| {
+                    InternalError::from_source_with_prefix(
+                        Box::new(err),
+                        format!("Invalid sequence number file {}", path.display()),
+                    )
+                })?
+        } else {
+            0
+        };
+
+        let next = std::cmp::max(recorded.saturating_add(1), now_micros());
+        let reserved = next.saturating_add(RESERVED_SEQUENCE_NUMBERS);
+        write_reserved(&path, reserved)?;
+
+        Ok(SequenceNumbers {
+            state: Arc::new(Mutex::new(SequenceState {
+                next,
+                reserved,
+                path: Some(path),
+            })),
+        })
+    }
+
+    /// Returns the next sequence number, reserving a new block in the state file if needed
+    pub fn next(&self) -> Result<u64, InternalError> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| InternalError::with_message("Sequence number lock was poisoned".into()))?;
+
+        let next = state.next;
+        if next > state.reserved {
+            if let Some(path) = &state.path {
+                let reserved = next.saturating_add(RESERVED_SEQUENCE_NUMBERS);
+                write_reserved(path, reserved)?;
+                state.reserved = reserved;
+            }
+        }
+        state.next = next.saturating_add(1);
+
+        Ok(next)
+    }
+}
+
+impl Default for SequenceNumbers {
+    fn default() -> Self {
+        SequenceNumbers::new()
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_micros() as u64)
+        .unwrap_or(0)
+        .max(1)
+}
+
+/// Writes the reserved sequence number to the file at `path` through a temporary file, so the
+/// file always holds a complete number, and syncs it to disk.
+fn write_reserved(path: &Path, reserved: u64) -> Result<(), InternalError> {
+    let write = || -> std::io::Result<()> {
+        let temp_path = path.with_extension("tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(reserved.to_string().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+            _ => Ok(()),
+        }
+    };
+
+    write().map_err(|err| {
+        InternalError::from_source_with_prefix(
+            Box::new(err),
+            format!("Unable to write sequence number file {}", path.display()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that a counter backed by a file continues above the numbers handed out before it was
+    /// recreated, even when the recorded numbers are ahead of the clock, as they are after the
+    /// clock has been set back.
+    #[test]
+    fn test_sequence_numbers_from_file() {
+        let temp_dir =
+            TempDir::new("test_sequence_numbers_from_file").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("sequence_numbers");
+
+        let ahead_of_clock = now_micros() * 2;
+        fs::write(&path, ahead_of_clock.to_string()).expect("Failed to write file");
+
+        let sequence_numbers = SequenceNumbers::from_file(&path).expect("Unable to load file");
+        let first = sequence_numbers.next().expect("Unable to get number");
+        assert!(first > ahead_of_clock);
+
+        let mut last = first;
+        for _ in 0..(RESERVED_SEQUENCE_NUMBERS + 10) {
+            let next = sequence_numbers.next().expect("Unable to get number");
+            assert!(next > last);
+            last = next;
+        }
+
+        let recorded: u64 = fs::read_to_string(&path)
+            .expect("Failed to read file")
+            .parse()
+            .expect("Invalid file contents");
+        assert!(recorded >= last);
+
+        let restarted = SequenceNumbers::from_file(&path).expect("Unable to load file");
+        assert!(restarted.next().expect("Unable to get number") > last);
+    }
+}
@@ -0,0 +1,746 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A runner for services that live outside of the splinter daemon.
+//!
+//! The [`ExternalServiceRunner`] hosts a set of message handlers, each registered for a service
+//! on a circuit, on a single connection to a splinter node's service endpoint. It takes care of
+//! connecting the services, reconnecting them when the connection to the node is lost, and
+//! disconnecting them when the runner is shut down, so that an external service only has to
+//! provide the handling of its messages.
+//!
+//! [`ExternalServiceRunner`]: struct.ExternalServiceRunner.html
+
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+#[cfg(feature = "service-endpoint-auth")]
+use cylinder::Signer;
+use mio::Evented;
+use protobuf::Message;
+
+use crate::error::{InternalError, InvalidStateError};
+use crate::transport::{
+    Connection, DisconnectError, RecvError, SendError as TransportSendError, Transport,
+};
+
+use super::error::{
+    ServiceDestroyError, ServiceError, ServiceProcessorError, ServiceSendError, ServiceStartError,
+    ServiceStopError,
+};
+use super::{
+    JoinHandles, Service, ServiceMessageContext, ServiceNetworkRegistry, ServiceNetworkSender,
+    ServiceProcessor, ShutdownHandle,
+};
+
+const DEFAULT_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_CHANNEL_CAPACITY: usize = 8;
+
+/// The interval at which a running processor's connection is checked.
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A message handler for a service hosted by an `ExternalServiceRunner`.
+///
+/// The handler is given the sender of the service, the bytes of the message, and its context.
+pub type MessageHandler =
+    dyn Fn(&ServiceSender, &[u8], &ServiceMessageContext) -> Result<(), ServiceError> + Send + Sync;
+
+/// Sends the messages of a service hosted by an `ExternalServiceRunner`.
+///
+/// In addition to sending raw bytes, protobuf messages may be sent and received directly.
+#[derive(Clone)]
+pub struct ServiceSender {
+    sender: Box<dyn ServiceNetworkSender>,
+}
+
+impl ServiceSender {
+    /// Send the message bytes to the given recipient (another service).
+    pub fn send(&self, recipient: &str, message: &[u8]) -> Result<(), ServiceSendError> {
+        self.sender.send(recipient, message)
+    }
+
+    /// Send the message bytes to the given recipient (another service) and await the reply.
+    /// This function blocks until the reply is returned.
+    pub fn send_and_await(
+        &self,
+        recipient: &str,
+        message: &[u8],
+    ) -> Result<Vec<u8>, ServiceSendError> {
+        self.sender.send_and_await(recipient, message)
+    }
+
+    /// Send the message bytes back to the origin specified in the given message context.
+    pub fn reply(
+        &self,
+        message_origin: &ServiceMessageContext,
+        message: &[u8],
+    ) -> Result<(), ServiceSendError> {
+        self.sender.reply(message_origin, message)
+    }
+
+    /// Send the protobuf message to the given recipient (another service).
+    pub fn send_message<M: Message>(
+        &self,
+        recipient: &str,
+        message: &M,
+    ) -> Result<(), ServiceSendError> {
+        self.send(recipient, &to_bytes(message)?)
+    }
+
+    /// Send the protobuf message to the given recipient (another service) and await the reply,
+    /// which is parsed as a protobuf message of type `R`. This function blocks until the reply is
+    /// returned.
+    pub fn send_and_await_message<M: Message, R: Message>(
+        &self,
+        recipient: &str,
+        message: &M,
+    ) -> Result<R, ServiceSendError> {
+        let reply = self.send_and_await(recipient, &to_bytes(message)?)?;
+        protobuf::parse_from_bytes(&reply).map_err(|err| ServiceSendError(Box::new(err)))
+    }
+
+    /// Send the protobuf message back to the origin specified in the given message context.
+    pub fn reply_message<M: Message>(
+        &self,
+        message_origin: &ServiceMessageContext,
+        message: &M,
+    ) -> Result<(), ServiceSendError> {
+        self.reply(message_origin, &to_bytes(message)?)
+    }
+}
+
+fn to_bytes<M: Message>(message: &M) -> Result<Vec<u8>, ServiceSendError> {
+    message
+        .write_to_bytes()
+        .map_err(|err| ServiceSendError(Box::new(err)))
+}
+
+/// A service registered with an `ExternalServiceRunnerBuilder`.
+struct ServiceDefinition {
+    circuit: String,
+    service_id: String,
+    service_type: String,
+    handler: Arc<MessageHandler>,
+}
+
+/// Builds an `ExternalServiceRunner`.
+#[derive(Default)]
+pub struct ExternalServiceRunnerBuilder {
+    endpoint: Option<String>,
+    transport: Option<Box<dyn Transport>>,
+    services: Vec<ServiceDefinition>,
+    reconnect_interval: Option<Duration>,
+    channel_capacity: Option<usize>,
+    #[cfg(feature = "service-endpoint-auth")]
+    signer: Option<Box<dyn Signer>>,
+}
+
+impl ExternalServiceRunnerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the service endpoint of the splinter node to connect to.
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Sets the transport used to connect to the service endpoint.
+    pub fn with_transport(mut self, transport: Box<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Hosts the service with the given ID and type on the circuit, handling its messages with
+    /// the given handler.
+    pub fn with_handler<F>(
+        mut self,
+        circuit: String,
+        service_id: String,
+        service_type: String,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(&ServiceSender, &[u8], &ServiceMessageContext) -> Result<(), ServiceError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.services.push(ServiceDefinition {
+            circuit,
+            service_id,
+            service_type,
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Sets how long to wait between attempts to connect to the service endpoint. Defaults to 5
+    /// seconds.
+    pub fn with_reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = Some(reconnect_interval);
+        self
+    }
+
+    /// Sets the capacity of the channels used by the underlying service processor. Defaults to
+    /// 8.
+    pub fn with_channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = Some(channel_capacity);
+        self
+    }
+
+    /// Authenticates the services with the given signer when they connect, as required for
+    /// services that have a key registered in their circuit definition.
+    #[cfg(feature = "service-endpoint-auth")]
+    pub fn with_signer(mut self, signer: Box<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    pub fn build(self) -> Result<ExternalServiceRunner, InvalidStateError> {
+        let endpoint = self.endpoint.ok_or_else(|| {
+            InvalidStateError::with_message("An endpoint is required".to_string())
+        })?;
+        let transport = self.transport.ok_or_else(|| {
+            InvalidStateError::with_message("A transport is required".to_string())
+        })?;
+
+        if self.services.is_empty() {
+            return Err(InvalidStateError::with_message(
+                "At least one handler is required".to_string(),
+            ));
+        }
+
+        for (i, service) in self.services.iter().enumerate() {
+            if self.services[..i].iter().any(|other| {
+                other.circuit == service.circuit && other.service_id == service.service_id
+            }) {
+                return Err(InvalidStateError::with_message(format!(
+                    "Multiple handlers were provided for service {}::{}",
+                    service.circuit, service.service_id
+                )));
+            }
+        }
+
+        Ok(ExternalServiceRunner {
+            endpoint,
+            transport,
+            services: self.services,
+            reconnect_interval: self
+                .reconnect_interval
+                .unwrap_or(DEFAULT_RECONNECT_INTERVAL),
+            channel_capacity: self.channel_capacity.unwrap_or(DEFAULT_CHANNEL_CAPACITY),
+            #[cfg(feature = "service-endpoint-auth")]
+            signer: self.signer,
+        })
+    }
+}
+
+/// Hosts services on a connection to a splinter node's service endpoint.
+///
+/// When the connection to the node is lost, the runner stops the services and reconnects them
+/// over a new connection, retrying until the node is reachable again or the runner is shut down.
+pub struct ExternalServiceRunner {
+    endpoint: String,
+    transport: Box<dyn Transport>,
+    services: Vec<ServiceDefinition>,
+    reconnect_interval: Duration,
+    channel_capacity: usize,
+    #[cfg(feature = "service-endpoint-auth")]
+    signer: Option<Box<dyn Signer>>,
+}
+
+impl ExternalServiceRunner {
+    /// Starts the runner in a background thread.
+    ///
+    /// Returns a handle that is used to shut down the runner and wait for it to complete.
+    pub fn start(self) -> Result<RunningExternalService, InternalError> {
+        let (shutdown_sender, shutdown_receiver) = crossbeam_channel::bounded(1);
+
+        let join_handle = thread::Builder::new()
+            .name("ExternalServiceRunner".into())
+            .spawn(move || self.run(shutdown_receiver))
+            .map_err(|err| {
+                InternalError::from_source_with_message(
+                    Box::new(err),
+                    "Unable to start external service runner thread".into(),
+                )
+            })?;
+
+        Ok(RunningExternalService {
+            join_handle,
+            shutdown_sender,
+        })
+    }
+
+    fn run(mut self, shutdown_receiver: Receiver<()>) {
+        let mut reconnecting = false;
+
+        loop {
+            let connection = match self.transport.connect(&self.endpoint) {
+                Ok(connection) => connection,
+                Err(err) => {
+                    warn!(
+                        "Unable to connect to service endpoint {}: {}; retrying in {:?}",
+                        self.endpoint, err, self.reconnect_interval
+                    );
+                    if await_shutdown_signal(&shutdown_receiver, self.reconnect_interval) {
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            let connection_lost = Arc::new(AtomicBool::new(false));
+            let running = Arc::new(AtomicBool::new(true));
+            let (shutdown_handle, join_handles) = match self.start_processor(
+                connection,
+                connection_lost.clone(),
+                running.clone(),
+                reconnecting,
+            ) {
+                Ok(handles) => handles,
+                Err(err) => {
+                    error!("Unable to start external services: {}", err);
+                    running.store(false, Ordering::SeqCst);
+                    if await_shutdown_signal(&shutdown_receiver, self.reconnect_interval) {
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            info!("Connected external services to {}", self.endpoint);
+
+            let shutdown = loop {
+                if await_shutdown_signal(&shutdown_receiver, CONNECTION_CHECK_INTERVAL) {
+                    break true;
+                }
+                if connection_lost.load(Ordering::SeqCst) {
+                    break false;
+                }
+            };
+
+            if shutdown {
+                // The services disconnect from the node as they stop, which requires the
+                // processor to keep running until they have done so.
+                if let Err(err) = shutdown_handle.shutdown() {
+                    error!("Unable to cleanly shut down external services: {}", err);
+                }
+                running.store(false, Ordering::SeqCst);
+                if join_handles.join_all().is_err() {
+                    error!("Unable to cleanly join the service processor threads");
+                }
+                return;
+            }
+
+            warn!(
+                "Lost connection to service endpoint {}; reconnecting",
+                self.endpoint
+            );
+            running.store(false, Ordering::SeqCst);
+            // A service may be blocked waiting on a reply that will never arrive over the lost
+            // connection, so its shutdown is not waited on.
+            if let Err(err) = thread::Builder::new()
+                .name("ExternalServiceRunner cleanup".into())
+                .spawn(move || {
+                    if let Err(err) = shutdown_handle.shutdown() {
+                        debug!("Unable to shut down disconnected services: {}", err);
+                    }
+                })
+            {
+                error!("Unable to start external service cleanup thread: {}", err);
+            }
+            if join_handles.join_all().is_err() {
+                error!("Unable to cleanly join the service processor threads");
+            }
+
+            reconnecting = true;
+        }
+    }
+
+    fn start_processor(
+        &self,
+        connection: Box<dyn Connection>,
+        connection_lost: Arc<AtomicBool>,
+        running: Arc<AtomicBool>,
+        reconnecting: bool,
+    ) -> Result<
+        (
+            ShutdownHandle,
+            JoinHandles<Result<(), ServiceProcessorError>>,
+        ),
+        ServiceProcessorError,
+    > {
+        let connection = MonitoredConnection {
+            inner: connection,
+            connection_lost: connection_lost.clone(),
+        };
+        let mut processor = ServiceProcessor::new(
+            Box::new(connection),
+            self.services[0].circuit.clone(),
+            self.channel_capacity,
+            self.channel_capacity,
+            self.channel_capacity,
+            running,
+        )?;
+        #[cfg(feature = "service-endpoint-auth")]
+        {
+            if let Some(signer) = &self.signer {
+                processor = processor.with_signer(signer.clone_box());
+            }
+        }
+
+        for definition in &self.services {
+            processor.add_circuit_service(
+                definition.circuit.clone(),
+                Box::new(HandlerService {
+                    service_id: definition.service_id.clone(),
+                    service_type: definition.service_type.clone(),
+                    handler: definition.handler.clone(),
+                    sender: None,
+                    connection_lost: connection_lost.clone(),
+                    reconnecting,
+                }),
+            )?;
+        }
+
+        processor.start()
+    }
+}
+
+/// Waits up to the given timeout for the runner to be signaled to shut down, returning whether
+/// it was.
+fn await_shutdown_signal(shutdown_receiver: &Receiver<()>, timeout: Duration) -> bool {
+    match shutdown_receiver.recv_timeout(timeout) {
+        Ok(()) | Err(RecvTimeoutError::Disconnected) => true,
+        Err(RecvTimeoutError::Timeout) => false,
+    }
+}
+
+/// A started `ExternalServiceRunner`.
+pub struct RunningExternalService {
+    join_handle: JoinHandle<()>,
+    shutdown_sender: Sender<()>,
+}
+
+impl RunningExternalService {
+    pub fn shutdown_signaler(&self) -> ShutdownSignaler {
+        ShutdownSignaler {
+            sender: self.shutdown_sender.clone(),
+        }
+    }
+
+    /// Blocks until the runner has disconnected its services and shut down.
+    pub fn await_shutdown(self) {
+        if let Err(err) = self.join_handle.join() {
+            error!(
+                "External service runner thread did not shutdown correctly: {:?}",
+                err
+            );
+        }
+    }
+}
+
+/// Signals a running `ExternalServiceRunner` to disconnect its services and shut down.
+#[derive(Clone)]
+pub struct ShutdownSignaler {
+    sender: Sender<()>,
+}
+
+impl ShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.try_send(()).is_err() {
+            // ignore a shutdown that has already been signaled
+        }
+    }
+}
+
+/// The service run by the processor for a handler.
+struct HandlerService {
+    service_id: String,
+    service_type: String,
+    handler: Arc<MessageHandler>,
+    sender: Option<ServiceSender>,
+    connection_lost: Arc<AtomicBool>,
+    reconnecting: bool,
+}
+
+impl Service for HandlerService {
+    fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    fn service_type(&self) -> &str {
+        &self.service_type
+    }
+
+    fn start(
+        &mut self,
+        service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStartError> {
+        if self.sender.is_some() {
+            return Err(ServiceStartError::AlreadyStarted);
+        }
+
+        // The node keeps the service registered to the lost connection until it is explicitly
+        // disconnected, which would cause it to reject the new connection.
+        if self.reconnecting {
+            if let Err(err) = service_registry.disconnect(&self.service_id) {
+                debug!(
+                    "Unable to clear previous registration of {}: {}",
+                    self.service_id, err
+                );
+            }
+        }
+
+        let sender = service_registry.connect(&self.service_id)?;
+        self.sender = Some(ServiceSender { sender });
+
+        Ok(())
+    }
+
+    fn stop(
+        &mut self,
+        service_registry: &dyn ServiceNetworkRegistry,
+    ) -> Result<(), ServiceStopError> {
+        if self.sender.take().is_none() {
+            return Err(ServiceStopError::NotStarted);
+        }
+
+        // A disconnect request cannot be answered over a lost connection
+        if !self.connection_lost.load(Ordering::SeqCst) {
+            service_registry.disconnect(&self.service_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn destroy(self: Box<Self>) -> Result<(), ServiceDestroyError> {
+        if self.sender.is_some() {
+            Err(ServiceDestroyError::NotStopped)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn handle_message(
+        &self,
+        message_bytes: &[u8],
+        message_context: &ServiceMessageContext,
+    ) -> Result<(), ServiceError> {
+        let sender = self.sender.as_ref().ok_or(ServiceError::NotStarted)?;
+
+        (*self.handler)(sender, message_bytes, message_context)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A connection that records when it has been disconnected from the remote endpoint.
+struct MonitoredConnection {
+    inner: Box<dyn Connection>,
+    connection_lost: Arc<AtomicBool>,
+}
+
+impl Connection for MonitoredConnection {
+    fn send(&mut self, message: &[u8]) -> Result<(), TransportSendError> {
+        let res = self.inner.send(message);
+        if let Err(TransportSendError::Disconnected) = res {
+            self.connection_lost.store(true, Ordering::SeqCst);
+        }
+        res
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
+        let res = self.inner.recv();
+        if let Err(RecvError::Disconnected) = res {
+            self.connection_lost.store(true, Ordering::SeqCst);
+        }
+        res
+    }
+
+    fn recv_into(&mut self, buffer: &mut Vec<u8>) -> Result<(), RecvError> {
+        let res = self.inner.recv_into(buffer);
+        if let Err(RecvError::Disconnected) = res {
+            self.connection_lost.store(true, Ordering::SeqCst);
+        }
+        res
+    }
+
+    fn remote_endpoint(&self) -> String {
+        self.inner.remote_endpoint()
+    }
+
+    fn local_endpoint(&self) -> String {
+        self.inner.local_endpoint()
+    }
+
+    fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.inner.disconnect()
+    }
+
+    fn evented(&self) -> &dyn Evented {
+        self.inner.evented()
+    }
+
+    #[cfg(feature = "spiffe")]
+    fn peer_certificate_uris(&self) -> Vec<String> {
+        self.inner.peer_certificate_uris()
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    fn peer_certificate_fingerprint(&self) -> Option<String> {
+        self.inner.peer_certificate_fingerprint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mesh::Mesh;
+    use crate::protos::circuit::{
+        CircuitDirectMessage, CircuitMessage, CircuitMessageType, ServiceConnectRequest,
+        ServiceConnectResponse, ServiceConnectResponse_Status, ServiceDisconnectRequest,
+        ServiceDisconnectResponse, ServiceDisconnectResponse_Status,
+    };
+    use crate::protos::network::{NetworkMessage, NetworkMessageType};
+    use crate::transport::inproc::InprocTransport;
+    use crate::transport::matrix::ConnectionMatrixSender;
+
+    /// Verifies that the runner connects its service, routes messages to the service's handler,
+    /// and disconnects the service when it is shut down.
+    #[test]
+    fn handler_round_trip() {
+        let mut transport = InprocTransport::default();
+        let mut listener = transport.listen("inproc://runner").unwrap();
+
+        let running_service = ExternalServiceRunnerBuilder::new()
+            .with_endpoint("inproc://runner".into())
+            .with_transport(Box::new(transport))
+            .with_handler(
+                "alpha".into(),
+                "echo".into(),
+                "echo".into(),
+                |sender, message, context| {
+                    sender.reply(context, message)?;
+                    Ok(())
+                },
+            )
+            .build()
+            .expect("Unable to build runner")
+            .start()
+            .expect("Unable to start runner");
+
+        // this part of the test mimics the splinter daemon
+        let mesh = Mesh::new(8, 8);
+        let mesh_sender = mesh.get_sender();
+        mesh.add(listener.accept().unwrap(), "runner".into())
+            .unwrap();
+
+        let connect: ServiceConnectRequest = parse_circuit_message(mesh.recv().unwrap().payload());
+        assert_eq!(connect.get_circuit(), "alpha");
+        assert_eq!(connect.get_service_id(), "echo");
+        let mut response = ServiceConnectResponse::new();
+        response.set_circuit("alpha".into());
+        response.set_service_id("echo".into());
+        response.set_correlation_id(connect.get_correlation_id().into());
+        response.set_status(ServiceConnectResponse_Status::OK);
+        mesh_sender
+            .send(
+                "runner".into(),
+                circuit_message(&response, CircuitMessageType::SERVICE_CONNECT_RESPONSE),
+            )
+            .unwrap();
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("other".into());
+        direct_message.set_recipient("echo".into());
+        direct_message.set_correlation_id("correlation".into());
+        direct_message.set_payload(b"ping".to_vec());
+        mesh_sender
+            .send(
+                "runner".into(),
+                circuit_message(&direct_message, CircuitMessageType::CIRCUIT_DIRECT_MESSAGE),
+            )
+            .unwrap();
+
+        let reply: CircuitDirectMessage = parse_circuit_message(mesh.recv().unwrap().payload());
+        assert_eq!(reply.get_circuit(), "alpha");
+        assert_eq!(reply.get_recipient(), "other");
+        assert_eq!(reply.get_correlation_id(), "correlation");
+        assert_eq!(reply.get_payload(), b"ping");
+
+        let shutdown_signaler = running_service.shutdown_signaler();
+        shutdown_signaler.shutdown();
+
+        let disconnect: ServiceDisconnectRequest =
+            parse_circuit_message(mesh.recv().unwrap().payload());
+        assert_eq!(disconnect.get_service_id(), "echo");
+        let mut response = ServiceDisconnectResponse::new();
+        response.set_circuit("alpha".into());
+        response.set_service_id("echo".into());
+        response.set_correlation_id(disconnect.get_correlation_id().into());
+        response.set_status(ServiceDisconnectResponse_Status::OK);
+        mesh_sender
+            .send(
+                "runner".into(),
+                circuit_message(&response, CircuitMessageType::SERVICE_DISCONNECT_RESPONSE),
+            )
+            .unwrap();
+
+        running_service.await_shutdown();
+    }
+
+    /// Verifies that a runner cannot be built with two handlers for the same service.
+    #[test]
+    fn duplicate_handlers() {
+        let result = ExternalServiceRunnerBuilder::new()
+            .with_endpoint("inproc://runner".into())
+            .with_transport(Box::new(InprocTransport::default()))
+            .with_handler("alpha".into(), "a".into(), "t".into(), |_, _, _| Ok(()))
+            .with_handler("beta".into(), "a".into(), "t".into(), |_, _, _| Ok(()))
+            .with_handler("alpha".into(), "a".into(), "t".into(), |_, _, _| Ok(()))
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    fn circuit_message<M: Message>(message: &M, message_type: CircuitMessageType) -> Vec<u8> {
+        let mut circuit_msg = CircuitMessage::new();
+        circuit_msg.set_message_type(message_type);
+        circuit_msg.set_payload(message.write_to_bytes().unwrap());
+
+        let mut network_msg = NetworkMessage::new();
+        network_msg.set_message_type(NetworkMessageType::CIRCUIT);
+        network_msg.set_payload(circuit_msg.write_to_bytes().unwrap());
+        network_msg.write_to_bytes().unwrap()
+    }
+
+    fn parse_circuit_message<M: Message>(bytes: &[u8]) -> M {
+        let network_msg: NetworkMessage = protobuf::parse_from_bytes(bytes).unwrap();
+        let circuit_msg: CircuitMessage =
+            protobuf::parse_from_bytes(network_msg.get_payload()).unwrap();
+        protobuf::parse_from_bytes(circuit_msg.get_payload()).unwrap()
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An Ed25519 implementation of cylinder's signing traits, backed by OpenSSL.
+//!
+//! Private keys are the 32-byte Ed25519 seed and public keys are the 32-byte encoded point, as
+//! defined by RFC 8032.
+
+use cylinder::{
+    Context, ContextError, PrivateKey, PublicKey, Signature, Signer, SigningError,
+    VerificationError, Verifier,
+};
+use openssl::pkey::{Id, PKey};
+
+const ALGORITHM_NAME: &str = "ed25519";
+
+/// A cylinder `Context` for Ed25519 keys.
+#[derive(Clone, Default)]
+pub struct Ed25519Context;
+
+impl Ed25519Context {
+    pub fn new() -> Self {
+        Ed25519Context
+    }
+}
+
+impl Context for Ed25519Context {
+    fn algorithm_name(&self) -> &str {
+        ALGORITHM_NAME
+    }
+
+    fn new_signer(&self, key: PrivateKey) -> Box<dyn Signer> {
+        Box::new(Ed25519Signer { key })
+    }
+
+    fn new_verifier(&self) -> Box<dyn Verifier> {
+        Box::new(Ed25519Verifier)
+    }
+
+    fn new_random_private_key(&self) -> PrivateKey {
+        let key = PKey::generate_ed25519().expect("unable to generate Ed25519 key");
+        PrivateKey::new(
+            key.raw_private_key()
+                .expect("unable to get raw Ed25519 private key"),
+        )
+    }
+
+    fn get_public_key(&self, private_key: &PrivateKey) -> Result<PublicKey, ContextError> {
+        public_key_from_private(private_key).map_err(ContextError::Internal)
+    }
+}
+
+/// A cylinder `Signer` for a single Ed25519 private key.
+#[derive(Clone)]
+pub struct Ed25519Signer {
+    key: PrivateKey,
+}
+
+impl Signer for Ed25519Signer {
+    fn algorithm_name(&self) -> &str {
+        ALGORITHM_NAME
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        let key = PKey::private_key_from_raw_bytes(self.key.as_slice(), Id::ED25519)
+            .map_err(|err| SigningError::Internal(err.to_string()))?;
+        let mut signer = openssl::sign::Signer::new_without_digest(&key)
+            .map_err(|err| SigningError::Internal(err.to_string()))?;
+        signer
+            .sign_oneshot_to_vec(message)
+            .map(Signature::new)
+            .map_err(|err| SigningError::Internal(err.to_string()))
+    }
+
+    fn public_key(&self) -> Result<PublicKey, SigningError> {
+        public_key_from_private(&self.key).map_err(SigningError::Internal)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signer> {
+        Box::new(self.clone())
+    }
+}
+
+/// A cylinder `Verifier` for Ed25519 signatures.
+pub struct Ed25519Verifier;
+
+impl Verifier for Ed25519Verifier {
+    fn algorithm_name(&self) -> &str {
+        ALGORITHM_NAME
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<bool, VerificationError> {
+        let key = PKey::public_key_from_raw_bytes(public_key.as_slice(), Id::ED25519)
+            .map_err(|err| VerificationError::Internal(err.to_string()))?;
+        let mut verifier = openssl::sign::Verifier::new_without_digest(&key)
+            .map_err(|err| VerificationError::Internal(err.to_string()))?;
+        verifier
+            .verify_oneshot(signature.as_slice(), message)
+            .map_err(|err| VerificationError::Internal(err.to_string()))
+    }
+}
+
+fn public_key_from_private(private_key: &PrivateKey) -> Result<PublicKey, String> {
+    PKey::private_key_from_raw_bytes(private_key.as_slice(), Id::ED25519)
+        .and_then(|key| key.raw_public_key())
+        .map(PublicKey::new)
+        .map_err(|err| format!("invalid Ed25519 private key: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a message signed with a random key verifies against the key's public key, and
+    /// that a modified message does not.
+    #[test]
+    fn sign_and_verify() {
+        let context = Ed25519Context::new();
+        let private_key = context.new_random_private_key();
+        assert_eq!(private_key.as_slice().len(), 32);
+
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("failed to get public key");
+        assert_eq!(public_key.as_slice().len(), 32);
+
+        let signer = context.new_signer(private_key);
+        assert_eq!(
+            signer.public_key().expect("failed to get public key"),
+            public_key
+        );
+
+        let signature = signer.sign(b"hello").expect("failed to sign");
+        let verifier = context.new_verifier();
+        assert!(verifier
+            .verify(b"hello", &signature, &public_key)
+            .expect("failed to verify"));
+        assert!(!verifier
+            .verify(b"goodbye", &signature, &public_key)
+            .expect("failed to verify"));
+    }
+
+    /// Verify that a public key of the wrong length results in an error rather than a failed
+    /// verification.
+    #[test]
+    fn verify_invalid_public_key() {
+        let context = Ed25519Context::new();
+        let signer = context.new_signer(context.new_random_private_key());
+        let signature = signer.sign(b"hello").expect("failed to sign");
+
+        assert!(context
+            .new_verifier()
+            .verify(b"hello", &signature, &PublicKey::new(vec![1, 2, 3]))
+            .is_err());
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signing algorithm selection for Splinter nodes.
+//!
+//! Cylinder provides secp256k1 signing; this module adds an Ed25519 implementation of cylinder's
+//! `Context`, `Signer`, and `Verifier` traits, along with a `MultiVerifier` that accepts
+//! signatures from any of a configured set of algorithms. This allows a node to verify both
+//! secp256k1 and Ed25519 signatures (for example, on admin payloads or Cylinder JWTs) without the
+//! callers needing to know which algorithm a given key uses.
+
+#[cfg(feature = "signing-ed25519")]
+pub mod ed25519;
+
+use std::fmt;
+use std::str::FromStr;
+
+use cylinder::{
+    secp256k1::Secp256k1Context, Context, PublicKey, Signature, VerificationError, Verifier,
+    VerifierFactory,
+};
+
+/// The signing algorithms supported by Splinter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SigningAlgorithm {
+    Secp256k1,
+    #[cfg(feature = "signing-ed25519")]
+    Ed25519,
+}
+
+impl SigningAlgorithm {
+    /// Returns the name of the algorithm, as used in configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Secp256k1 => "secp256k1",
+            #[cfg(feature = "signing-ed25519")]
+            SigningAlgorithm::Ed25519 => "ed25519",
+        }
+    }
+
+    /// Creates a new signing context for the algorithm.
+    pub fn new_context(&self) -> Box<dyn Context> {
+        match self {
+            SigningAlgorithm::Secp256k1 => Box::new(Secp256k1Context::new()),
+            #[cfg(feature = "signing-ed25519")]
+            SigningAlgorithm::Ed25519 => Box::new(ed25519::Ed25519Context::new()),
+        }
+    }
+}
+
+impl Default for SigningAlgorithm {
+    fn default() -> Self {
+        SigningAlgorithm::Secp256k1
+    }
+}
+
+impl fmt::Display for SigningAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SigningAlgorithm {
+    type Err = InvalidSigningAlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "secp256k1" => Ok(SigningAlgorithm::Secp256k1),
+            #[cfg(feature = "signing-ed25519")]
+            "ed25519" => Ok(SigningAlgorithm::Ed25519),
+            _ => Err(InvalidSigningAlgorithmError(s.to_string())),
+        }
+    }
+}
+
+/// Returned when a string does not name a supported signing algorithm.
+#[derive(Debug)]
+pub struct InvalidSigningAlgorithmError(pub String);
+
+impl std::error::Error for InvalidSigningAlgorithmError {}
+
+impl fmt::Display for InvalidSigningAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported signing algorithm: {}", self.0)
+    }
+}
+
+/// A verifier that accepts signatures made with any of its configured algorithms.
+///
+/// A signature is considered valid if any of the underlying verifiers reports it as valid. Errors
+/// from an individual verifier (for example, because the public key has the wrong length for that
+/// algorithm) are treated as a failed verification for that algorithm only.
+pub struct MultiVerifier {
+    algorithm_name: String,
+    verifiers: Vec<Box<dyn Verifier>>,
+}
+
+impl MultiVerifier {
+    /// Creates a verifier that accepts signatures from any of the given algorithms.
+    pub fn new(algorithms: &[SigningAlgorithm]) -> Self {
+        Self {
+            algorithm_name: algorithms
+                .iter()
+                .map(SigningAlgorithm::name)
+                .collect::<Vec<_>>()
+                .join("+"),
+            verifiers: algorithms
+                .iter()
+                .map(|algorithm| algorithm.new_context().new_verifier())
+                .collect(),
+        }
+    }
+}
+
+impl Verifier for MultiVerifier {
+    fn algorithm_name(&self) -> &str {
+        &self.algorithm_name
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<bool, VerificationError> {
+        for verifier in &self.verifiers {
+            match verifier.verify(message, signature, public_key) {
+                Ok(true) => return Ok(true),
+                Ok(false) => (),
+                Err(err) => trace!(
+                    "{} verifier unable to verify signature: {}",
+                    verifier.algorithm_name(),
+                    err
+                ),
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Creates `MultiVerifier`s for a fixed set of algorithms.
+#[derive(Clone, Debug)]
+pub struct MultiVerifierFactory {
+    algorithms: Vec<SigningAlgorithm>,
+}
+
+impl MultiVerifierFactory {
+    pub fn new(algorithms: Vec<SigningAlgorithm>) -> Self {
+        Self { algorithms }
+    }
+
+    /// Returns the algorithms accepted by the verifiers this factory creates.
+    pub fn algorithms(&self) -> &[SigningAlgorithm] {
+        &self.algorithms
+    }
+}
+
+impl VerifierFactory for MultiVerifierFactory {
+    fn new_verifier(&self) -> Box<dyn Verifier> {
+        Box::new(MultiVerifier::new(&self.algorithms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that algorithm names round-trip through `FromStr` and that unknown names are
+    /// rejected.
+    #[test]
+    fn parse_algorithm_names() {
+        assert_eq!(
+            "secp256k1"
+                .parse::<SigningAlgorithm>()
+                .expect("failed to parse"),
+            SigningAlgorithm::Secp256k1
+        );
+        assert_eq!(
+            "SECP256K1"
+                .parse::<SigningAlgorithm>()
+                .expect("failed to parse"),
+            SigningAlgorithm::Secp256k1
+        );
+        #[cfg(feature = "signing-ed25519")]
+        assert_eq!(
+            "ed25519"
+                .parse::<SigningAlgorithm>()
+                .expect("failed to parse"),
+            SigningAlgorithm::Ed25519
+        );
+        assert!("rsa".parse::<SigningAlgorithm>().is_err());
+    }
+
+    /// Verify that a `MultiVerifier` accepts valid signatures from each configured algorithm and
+    /// rejects signatures from algorithms it was not configured with.
+    #[test]
+    fn multi_verifier() {
+        let message = b"hello";
+
+        let secp_context = SigningAlgorithm::Secp256k1.new_context();
+        let secp_signer = secp_context.new_signer(secp_context.new_random_private_key());
+        let secp_signature = secp_signer.sign(message).expect("failed to sign");
+        let secp_public_key = secp_signer.public_key().expect("failed to get public key");
+
+        let verifier = MultiVerifier::new(&[SigningAlgorithm::Secp256k1]);
+        assert!(verifier
+            .verify(message, &secp_signature, &secp_public_key)
+            .expect("failed to verify"));
+        assert!(!verifier
+            .verify(b"goodbye", &secp_signature, &secp_public_key)
+            .expect("failed to verify"));
+
+        #[cfg(feature = "signing-ed25519")]
+        {
+            let ed_context = SigningAlgorithm::Ed25519.new_context();
+            let ed_signer = ed_context.new_signer(ed_context.new_random_private_key());
+            let ed_signature = ed_signer.sign(message).expect("failed to sign");
+            let ed_public_key = ed_signer.public_key().expect("failed to get public key");
+
+            assert!(!verifier
+                .verify(message, &ed_signature, &ed_public_key)
+                .expect("failed to verify"));
+
+            let verifier =
+                MultiVerifier::new(&[SigningAlgorithm::Secp256k1, SigningAlgorithm::Ed25519]);
+            assert!(verifier
+                .verify(message, &secp_signature, &secp_public_key)
+                .expect("failed to verify"));
+            assert!(verifier
+                .verify(message, &ed_signature, &ed_public_key)
+                .expect("failed to verify"));
+        }
+    }
+}
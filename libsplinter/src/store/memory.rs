@@ -20,8 +20,16 @@ use diesel::{
     sqlite::SqliteConnection,
 };
 
+#[cfg(feature = "admin-allowed-signers")]
+use crate::admin::allowed_signers::MemoryAllowedSignerStore;
+#[cfg(feature = "admin-vote-cosigning")]
+use crate::admin::cosign::MemoryVoteCosignStore;
+#[cfg(feature = "admin-vote-delegation")]
+use crate::admin::delegation::MemoryVoteDelegationStore;
 #[cfg(feature = "biome-oauth")]
 use crate::biome::MemoryOAuthUserSessionStore;
+#[cfg(feature = "biome-user-admin")]
+use crate::biome::MemoryUserAccountStore;
 #[cfg(feature = "biome-credentials")]
 use crate::biome::{
     CredentialsStore, MemoryCredentialsStore, MemoryRefreshTokenStore, RefreshTokenStore,
@@ -30,6 +38,8 @@ use crate::biome::{
 use crate::biome::{KeyStore, MemoryKeyStore};
 #[cfg(feature = "oauth")]
 use crate::oauth::store::MemoryInflightOAuthRequestStore;
+#[cfg(feature = "tenancy")]
+use crate::tenant::MemoryTenantStore;
 
 use super::StoreFactory;
 
@@ -44,8 +54,18 @@ pub struct MemoryStoreFactory {
     biome_refresh_token_store: MemoryRefreshTokenStore,
     #[cfg(feature = "biome-oauth")]
     biome_oauth_user_session_store: MemoryOAuthUserSessionStore,
+    #[cfg(feature = "biome-user-admin")]
+    biome_user_account_store: MemoryUserAccountStore,
+    #[cfg(feature = "admin-allowed-signers")]
+    admin_allowed_signer_store: MemoryAllowedSignerStore,
+    #[cfg(feature = "admin-vote-cosigning")]
+    admin_vote_cosign_store: MemoryVoteCosignStore,
+    #[cfg(feature = "admin-vote-delegation")]
+    admin_vote_delegation_store: MemoryVoteDelegationStore,
     #[cfg(feature = "oauth")]
     inflight_request_store: MemoryInflightOAuthRequestStore,
+    #[cfg(feature = "tenancy")]
+    tenant_store: MemoryTenantStore,
 }
 
 impl MemoryStoreFactory {
@@ -73,8 +93,18 @@ impl MemoryStoreFactory {
             biome_refresh_token_store: MemoryRefreshTokenStore::new(),
             #[cfg(feature = "biome-oauth")]
             biome_oauth_user_session_store,
+            #[cfg(feature = "biome-user-admin")]
+            biome_user_account_store: MemoryUserAccountStore::new(),
+            #[cfg(feature = "admin-allowed-signers")]
+            admin_allowed_signer_store: MemoryAllowedSignerStore::new(),
+            #[cfg(feature = "admin-vote-cosigning")]
+            admin_vote_cosign_store: MemoryVoteCosignStore::new(),
+            #[cfg(feature = "admin-vote-delegation")]
+            admin_vote_delegation_store: MemoryVoteDelegationStore::new(),
             #[cfg(feature = "oauth")]
             inflight_request_store,
+            #[cfg(feature = "tenancy")]
+            tenant_store: MemoryTenantStore::new(),
         }
     }
 }
@@ -100,6 +130,11 @@ impl StoreFactory for MemoryStoreFactory {
         Box::new(self.biome_oauth_user_session_store.clone())
     }
 
+    #[cfg(feature = "biome-user-admin")]
+    fn get_biome_user_account_store(&self) -> Box<dyn crate::biome::UserAccountStore> {
+        Box::new(self.biome_user_account_store.clone())
+    }
+
     #[cfg(all(feature = "admin-service", feature = "sqlite"))]
     fn get_admin_service_store(&self) -> Box<dyn crate::admin::store::AdminServiceStore> {
         let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
@@ -123,6 +158,25 @@ impl StoreFactory for MemoryStoreFactory {
         unimplemented!()
     }
 
+    #[cfg(feature = "admin-allowed-signers")]
+    fn get_admin_allowed_signer_store(
+        &self,
+    ) -> Box<dyn crate::admin::allowed_signers::AllowedSignerStore> {
+        Box::new(self.admin_allowed_signer_store.clone())
+    }
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn get_admin_vote_cosign_store(&self) -> Box<dyn crate::admin::cosign::VoteCosignStore> {
+        Box::new(self.admin_vote_cosign_store.clone())
+    }
+
+    #[cfg(feature = "admin-vote-delegation")]
+    fn get_admin_vote_delegation_store(
+        &self,
+    ) -> Box<dyn crate::admin::delegation::VoteDelegationStore> {
+        Box::new(self.admin_vote_delegation_store.clone())
+    }
+
     #[cfg(feature = "oauth")]
     fn get_oauth_inflight_request_store(
         &self,
@@ -150,4 +204,9 @@ impl StoreFactory for MemoryStoreFactory {
     fn get_registry_store(&self) -> Box<dyn crate::registry::RwRegistry> {
         unimplemented!()
     }
+
+    #[cfg(feature = "tenancy")]
+    fn get_tenant_store(&self) -> Box<dyn crate::tenant::TenantStore> {
+        Box::new(self.tenant_store.clone())
+    }
 }
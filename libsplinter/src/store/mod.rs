@@ -44,6 +44,10 @@ pub trait StoreFactory {
     #[cfg(feature = "biome-credentials")]
     fn get_biome_refresh_token_store(&self) -> Box<dyn crate::biome::RefreshTokenStore>;
 
+    /// Get a new `UserAccountStore`
+    #[cfg(feature = "biome-user-admin")]
+    fn get_biome_user_account_store(&self) -> Box<dyn crate::biome::UserAccountStore>;
+
     /// Get a new `OAuthUserSessionStore`
     #[cfg(feature = "biome-oauth")]
     fn get_biome_oauth_user_session_store(&self) -> Box<dyn crate::biome::OAuthUserSessionStore>;
@@ -51,6 +55,22 @@ pub trait StoreFactory {
     #[cfg(feature = "admin-service")]
     fn get_admin_service_store(&self) -> Box<dyn crate::admin::store::AdminServiceStore>;
 
+    /// Get a new `AllowedSignerStore`
+    #[cfg(feature = "admin-allowed-signers")]
+    fn get_admin_allowed_signer_store(
+        &self,
+    ) -> Box<dyn crate::admin::allowed_signers::AllowedSignerStore>;
+
+    /// Get a new `VoteCosignStore`
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn get_admin_vote_cosign_store(&self) -> Box<dyn crate::admin::cosign::VoteCosignStore>;
+
+    /// Get a new `VoteDelegationStore`
+    #[cfg(feature = "admin-vote-delegation")]
+    fn get_admin_vote_delegation_store(
+        &self,
+    ) -> Box<dyn crate::admin::delegation::VoteDelegationStore>;
+
     #[cfg(feature = "oauth")]
     fn get_oauth_inflight_request_store(
         &self,
@@ -58,6 +78,10 @@ pub trait StoreFactory {
 
     #[cfg(feature = "registry-database")]
     fn get_registry_store(&self) -> Box<dyn crate::registry::RwRegistry>;
+
+    /// Get a new `TenantStore`
+    #[cfg(feature = "tenancy")]
+    fn get_tenant_store(&self) -> Box<dyn crate::tenant::TenantStore>;
 }
 
 /// Creates a `StoreFactory` backed by the given connection
@@ -64,6 +64,11 @@ impl StoreFactory for PgStoreFactory {
         unreachable!()
     }
 
+    #[cfg(feature = "biome-user-admin")]
+    fn get_biome_user_account_store(&self) -> Box<dyn crate::biome::UserAccountStore> {
+        Box::new(crate::biome::DieselUserAccountStore::new(self.pool.clone()))
+    }
+
     #[cfg(feature = "admin-service")]
     fn get_admin_service_store(&self) -> Box<dyn crate::admin::store::AdminServiceStore> {
         Box::new(crate::admin::store::diesel::DieselAdminServiceStore::new(
@@ -71,6 +76,29 @@ impl StoreFactory for PgStoreFactory {
         ))
     }
 
+    #[cfg(feature = "admin-allowed-signers")]
+    fn get_admin_allowed_signer_store(
+        &self,
+    ) -> Box<dyn crate::admin::allowed_signers::AllowedSignerStore> {
+        Box::new(crate::admin::allowed_signers::DieselAllowedSignerStore::new(self.pool.clone()))
+    }
+
+    #[cfg(feature = "admin-vote-cosigning")]
+    fn get_admin_vote_cosign_store(&self) -> Box<dyn crate::admin::cosign::VoteCosignStore> {
+        Box::new(crate::admin::cosign::DieselVoteCosignStore::new(
+            self.pool.clone(),
+        ))
+    }
+
+    #[cfg(feature = "admin-vote-delegation")]
+    fn get_admin_vote_delegation_store(
+        &self,
+    ) -> Box<dyn crate::admin::delegation::VoteDelegationStore> {
+        Box::new(crate::admin::delegation::DieselVoteDelegationStore::new(
+            self.pool.clone(),
+        ))
+    }
+
     #[cfg(feature = "oauth-inflight-request-store-postgres")]
     fn get_oauth_inflight_request_store(
         &self,
@@ -94,4 +122,8 @@ impl StoreFactory for PgStoreFactory {
     fn get_registry_store(&self) -> Box<dyn crate::registry::RwRegistry> {
         Box::new(crate::registry::DieselRegistry::new(self.pool.clone()))
     }
+    #[cfg(feature = "tenancy")]
+    fn get_tenant_store(&self) -> Box<dyn crate::tenant::TenantStore> {
+        Box::new(crate::tenant::DieselTenantStore::new(self.pool.clone()))
+    }
 }
@@ -0,0 +1,201 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::error::InternalError;
+use crate::rest_api::auth::circuit_scope::{CircuitScope, CircuitScopeProvider};
+
+use super::{TenantResourceType, TenantScope, TenantStore};
+
+/// A `CircuitScopeProvider` that limits the identities of a tenant to the tenant's circuits.
+///
+/// An identity belongs to a tenant if it is assigned to the tenant either as a Biome user ID or
+/// as a public key. Operator identities are unrestricted. Identities that are neither operators
+/// nor assigned to a tenant are denied every circuit, unless the inner provider, if one is set,
+/// grants them an explicit allowlist of circuits.
+#[derive(Clone)]
+pub struct TenantCircuitScopeProvider {
+    store: Box<dyn TenantStore>,
+    inner: Option<Box<dyn CircuitScopeProvider>>,
+    operators: HashSet<String>,
+}
+
+impl TenantCircuitScopeProvider {
+    /// Creates a new provider backed by the given tenant store
+    pub fn new(store: Box<dyn TenantStore>) -> Self {
+        Self {
+            store,
+            inner: None,
+            operators: HashSet::new(),
+        }
+    }
+
+    /// Sets the provider that scopes the identities that do not belong to a tenant. Only the
+    /// circuit allowlists returned by the provider are used; identities that it leaves
+    /// unrestricted are still denied every circuit.
+    pub fn with_inner(mut self, inner: Box<dyn CircuitScopeProvider>) -> Self {
+        self.inner = Some(inner);
+        self
+    }
+
+    /// Sets the identities of the node operators, which may use every circuit and manage tenants.
+    /// Each identity is either a Biome user ID or a public key.
+    pub fn with_operators(mut self, operators: Vec<String>) -> Self {
+        self.operators.extend(
+            operators
+                .into_iter()
+                .map(|operator| operator.to_lowercase()),
+        );
+        self
+    }
+
+    fn is_operator(&self, identity: &str) -> bool {
+        self.operators.contains(&identity.to_lowercase())
+    }
+
+    fn get_tenant(&self, identity: &str) -> Result<Option<String>, InternalError> {
+        for resource_type in &[TenantResourceType::User, TenantResourceType::Key] {
+            let tenant = self
+                .store
+                .get_tenant(*resource_type, identity)
+                .map_err(|err| InternalError::from_source(Box::new(err)))?;
+            if tenant.is_some() {
+                return Ok(tenant);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl CircuitScopeProvider for TenantCircuitScopeProvider {
+    fn get_circuit_scope(&self, identity: &str) -> Result<CircuitScope, InternalError> {
+        if self.is_operator(identity) {
+            return Ok(CircuitScope::Unrestricted);
+        }
+
+        let tenant_id = match self.get_tenant(identity)? {
+            Some(tenant_id) => tenant_id,
+            None => {
+                let scope = match &self.inner {
+                    Some(inner) => inner.get_circuit_scope(identity)?,
+                    None => CircuitScope::Unrestricted,
+                };
+                return Ok(match scope {
+                    CircuitScope::Unrestricted => CircuitScope::Circuits(BTreeSet::new()),
+                    scope => scope,
+                });
+            }
+        };
+
+        let circuits = self
+            .store
+            .list_assignments(Some(&tenant_id))
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+            .into_iter()
+            .filter(|assignment| assignment.resource_type() == TenantResourceType::Circuit)
+            .map(|assignment| assignment.resource_id().to_string())
+            .collect();
+
+        Ok(CircuitScope::Circuits(circuits))
+    }
+
+    fn get_tenant_scope(&self, identity: &str) -> Result<Option<TenantScope>, InternalError> {
+        let store = self.store.clone();
+        if self.is_operator(identity) {
+            return Ok(Some(TenantScope::operator(store)));
+        }
+
+        Ok(Some(match self.get_tenant(identity)? {
+            Some(tenant_id) => TenantScope::tenant(&tenant_id, store),
+            None => TenantScope::unassigned(store),
+        }))
+    }
+
+    fn clone_box(&self) -> Box<dyn CircuitScopeProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rest_api::auth::circuit_scope::RoleCircuitScopeProvider;
+    use crate::tenant::{MemoryTenantStore, TenantAssignment};
+
+    /// Verify that users and keys of a tenant are limited to the tenant's circuits, that operators
+    /// are unrestricted, and that other identities only get the inner provider's allowlists.
+    #[test]
+    fn tenant_circuit_scope() {
+        let store = MemoryTenantStore::new();
+        for (tenant_id, resource_type, resource_id) in &[
+            ("tenant-a", TenantResourceType::User, "alice"),
+            ("tenant-a", TenantResourceType::Circuit, "circuit-a"),
+            ("tenant-a", TenantResourceType::Node, "node-a"),
+            ("tenant-b", TenantResourceType::Key, "02bb"),
+            ("tenant-b", TenantResourceType::Circuit, "circuit-b"),
+        ] {
+            store
+                .assign(TenantAssignment::new(
+                    tenant_id,
+                    *resource_type,
+                    resource_id,
+                ))
+                .expect("Failed to assign resource");
+        }
+
+        let provider = TenantCircuitScopeProvider::new(Box::new(store))
+            .with_inner(Box::new(
+                RoleCircuitScopeProvider::new()
+                    .with_role("auditor", vec!["circuit-c".into()])
+                    .with_assignment("carol", "auditor"),
+            ))
+            .with_operators(vec!["operator".into(), "03CC".into()]);
+
+        assert_eq!(
+            provider.get_circuit_scope("alice").unwrap(),
+            CircuitScope::Circuits(vec!["circuit-a".to_string()].into_iter().collect())
+        );
+        assert_eq!(
+            provider.get_circuit_scope("02BB").unwrap(),
+            CircuitScope::Circuits(vec!["circuit-b".to_string()].into_iter().collect())
+        );
+        assert_eq!(
+            provider.get_circuit_scope("carol").unwrap(),
+            CircuitScope::Circuits(vec!["circuit-c".to_string()].into_iter().collect())
+        );
+        assert_eq!(
+            provider.get_circuit_scope("operator").unwrap(),
+            CircuitScope::Unrestricted
+        );
+        assert_eq!(
+            provider.get_circuit_scope("03cc").unwrap(),
+            CircuitScope::Unrestricted
+        );
+        assert_eq!(
+            provider.get_circuit_scope("mallory").unwrap(),
+            CircuitScope::Circuits(BTreeSet::new())
+        );
+
+        let scope = provider.get_tenant_scope("operator").unwrap().unwrap();
+        assert!(scope.is_operator());
+        let scope = provider.get_tenant_scope("alice").unwrap().unwrap();
+        assert_eq!(scope.tenant_id(), Some("tenant-a"));
+        let scope = provider.get_tenant_scope("mallory").unwrap().unwrap();
+        assert!(!scope.is_operator());
+        assert_eq!(scope.tenant_id(), None);
+    }
+}
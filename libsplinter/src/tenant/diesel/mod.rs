@@ -0,0 +1,214 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(in crate::tenant) mod models;
+mod operations;
+pub(in crate::tenant) mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use super::{TenantAssignment, TenantResourceType, TenantStore, TenantStoreError};
+
+use operations::{
+    assign::TenantStoreAssign as _, get_tenant::TenantStoreGetTenant as _,
+    list_assignments::TenantStoreListAssignments as _, unassign::TenantStoreUnassign as _,
+    TenantStoreOperations,
+};
+
+pub struct DieselTenantStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselTenantStore<C> {
+    /// Creates a new `DieselTenantStore`
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TenantStore for DieselTenantStore<diesel::sqlite::SqliteConnection> {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?).assign(assignment)
+    }
+
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?)
+            .get_tenant(resource_type, resource_id)
+    }
+
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?).list_assignments(tenant_id)
+    }
+
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?)
+            .unassign(resource_type, resource_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn TenantStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl TenantStore for DieselTenantStore<diesel::pg::PgConnection> {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?).assign(assignment)
+    }
+
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?)
+            .get_tenant(resource_type, resource_id)
+    }
+
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?).list_assignments(tenant_id)
+    }
+
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError> {
+        TenantStoreOperations::new(&*self.connection_pool.get()?)
+            .unassign(resource_type, resource_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn TenantStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::sqlite::SqliteConnection;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+    /// Verify that a SQLite-backed `DieselTenantStore` correctly supports assigning,
+    /// reassigning, listing and unassigning resources.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Assign a key and a circuit to different tenants and verify that their tenants are
+    ///    returned and that they are listed, both in full and by tenant.
+    /// 3. Reassign the circuit and verify that the new tenant is returned.
+    /// 4. Unassign the key and verify that it no longer has a tenant, and that unassigning it
+    ///    again returns a `NotFound` error.
+    #[test]
+    fn sqlite_tenant_assignments() {
+        let store = DieselTenantStore::new(create_connection_pool_and_migrate());
+
+        let key = TenantAssignment::new("tenant-a", TenantResourceType::Key, KEY_A);
+        let circuit = TenantAssignment::new("tenant-b", TenantResourceType::Circuit, "abcde-01234");
+
+        store.assign(key.clone()).expect("Failed to assign key");
+        store
+            .assign(circuit.clone())
+            .expect("Failed to assign circuit");
+
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Key, &KEY_A.to_uppercase())
+                .expect("Failed to get tenant"),
+            Some("tenant-a".to_string())
+        );
+        assert_eq!(
+            store
+                .list_assignments(None)
+                .expect("Failed to list assignments"),
+            vec![circuit.clone(), key.clone()]
+        );
+        assert_eq!(
+            store
+                .list_assignments(Some("tenant-b"))
+                .expect("Failed to list assignments"),
+            vec![circuit]
+        );
+
+        store
+            .assign(TenantAssignment::new(
+                "tenant-a",
+                TenantResourceType::Circuit,
+                "abcde-01234",
+            ))
+            .expect("Failed to reassign circuit");
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Circuit, "abcde-01234")
+                .expect("Failed to get tenant"),
+            Some("tenant-a".to_string())
+        );
+
+        store
+            .unassign(TenantResourceType::Key, KEY_A)
+            .expect("Failed to unassign key");
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Key, KEY_A)
+                .expect("Failed to get tenant"),
+            None
+        );
+        assert!(matches!(
+            store.unassign(TenantResourceType::Key, KEY_A),
+            Err(TenantStoreError::NotFound(_))
+        ));
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
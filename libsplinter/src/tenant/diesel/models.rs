@@ -0,0 +1,54 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use crate::tenant::{TenantAssignment, TenantResourceType, TenantStoreError};
+
+use super::schema::tenant_assignments;
+
+#[derive(Debug, PartialEq, Insertable, Queryable)]
+#[table_name = "tenant_assignments"]
+pub struct TenantAssignmentModel {
+    pub resource_type: String,
+    pub resource_id: String,
+    pub tenant_id: String,
+}
+
+impl From<TenantAssignment> for TenantAssignmentModel {
+    fn from(assignment: TenantAssignment) -> Self {
+        TenantAssignmentModel {
+            resource_type: assignment.resource_type.as_str().to_string(),
+            resource_id: assignment.resource_id,
+            tenant_id: assignment.tenant_id,
+        }
+    }
+}
+
+impl TryFrom<TenantAssignmentModel> for TenantAssignment {
+    type Error = TenantStoreError;
+
+    fn try_from(model: TenantAssignmentModel) -> Result<Self, Self::Error> {
+        let resource_type = model
+            .resource_type
+            .parse::<TenantResourceType>()
+            .map_err(TenantStoreError::InvalidArgument)?;
+
+        Ok(TenantAssignment::new(
+            &model.tenant_id,
+            resource_type,
+            &model.resource_id,
+        ))
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::tenant::{
+    check_assignment,
+    diesel::{models::TenantAssignmentModel, schema::tenant_assignments},
+    TenantAssignment, TenantStoreError,
+};
+
+use super::TenantStoreOperations;
+
+pub trait TenantStoreAssign {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> TenantStoreAssign for TenantStoreOperations<'a, diesel::sqlite::SqliteConnection> {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        check_assignment(&assignment)?;
+        let model = TenantAssignmentModel::from(assignment);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing assignment of the resource
+            delete(tenant_assignments::table.find((&model.resource_type, &model.resource_id)))
+                .execute(self.conn)?;
+
+            insert_into(tenant_assignments::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> TenantStoreAssign for TenantStoreOperations<'a, diesel::pg::PgConnection> {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        check_assignment(&assignment)?;
+        let model = TenantAssignmentModel::from(assignment);
+
+        self.conn.transaction::<_, _, _>(|| {
+            // Replace any existing assignment of the resource
+            delete(tenant_assignments::table.find((&model.resource_type, &model.resource_id)))
+                .execute(self.conn)?;
+
+            insert_into(tenant_assignments::table)
+                .values(&model)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use diesel::prelude::*;
+
+use crate::tenant::{
+    diesel::{models::TenantAssignmentModel, schema::tenant_assignments},
+    TenantAssignment, TenantStoreError,
+};
+
+use super::TenantStoreOperations;
+
+pub trait TenantStoreListAssignments {
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError>;
+}
+
+impl<'a, C> TenantStoreListAssignments for TenantStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError> {
+        let order = (
+            tenant_assignments::resource_type,
+            tenant_assignments::resource_id,
+        );
+        let assignments = match tenant_id {
+            Some(tenant_id) => tenant_assignments::table
+                .filter(tenant_assignments::tenant_id.eq(tenant_id))
+                .order(order)
+                .load::<TenantAssignmentModel>(self.conn)?,
+            None => tenant_assignments::table
+                .order(order)
+                .load::<TenantAssignmentModel>(self.conn)?,
+        };
+
+        assignments
+            .into_iter()
+            .map(TenantAssignment::try_from)
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::tenant::{
+    diesel::schema::tenant_assignments, normalize_resource_id, TenantResourceType, TenantStoreError,
+};
+
+use super::TenantStoreOperations;
+
+pub trait TenantStoreUnassign {
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError>;
+}
+
+impl<'a, C> TenantStoreUnassign for TenantStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError> {
+        let deleted = delete(tenant_assignments::table.find((
+            resource_type.as_str(),
+            normalize_resource_id(resource_type, resource_id),
+        )))
+        .execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(TenantStoreError::NotFound(format!(
+                "{} {}",
+                resource_type, resource_id
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::InternalError;
+
+use super::{
+    check_assignment, normalize_resource_id, TenantAssignment, TenantResourceType, TenantStore,
+    TenantStoreError,
+};
+
+type Assignments = BTreeMap<(TenantResourceType, String), TenantAssignment>;
+
+#[derive(Default, Clone)]
+pub struct MemoryTenantStore {
+    /// Map of (resource type, resource ID) -> assignment
+    inner: Arc<Mutex<Assignments>>,
+}
+
+impl MemoryTenantStore {
+    /// Creates a new memory-backed tenant store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<Assignments>, TenantStoreError> {
+        self.inner.lock().map_err(|_| {
+            TenantStoreError::Internal(InternalError::with_message(
+                "Cannot access tenant store: mutex lock poisoned".to_string(),
+            ))
+        })
+    }
+}
+
+impl TenantStore for MemoryTenantStore {
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        check_assignment(&assignment)?;
+        self.lock()?.insert(
+            (assignment.resource_type, assignment.resource_id.clone()),
+            assignment,
+        );
+        Ok(())
+    }
+
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, TenantStoreError> {
+        Ok(self
+            .lock()?
+            .get(&(
+                resource_type,
+                normalize_resource_id(resource_type, resource_id),
+            ))
+            .map(|assignment| assignment.tenant_id.clone()))
+    }
+
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError> {
+        Ok(self
+            .lock()?
+            .values()
+            .filter(|assignment| {
+                tenant_id
+                    .map(|id| assignment.tenant_id == id)
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError> {
+        self.lock()?
+            .remove(&(
+                resource_type,
+                normalize_resource_id(resource_type, resource_id),
+            ))
+            .map(|_| ())
+            .ok_or_else(|| TenantStoreError::NotFound(format!("{} {}", resource_type, resource_id)))
+    }
+
+    fn clone_box(&self) -> Box<dyn TenantStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+    /// Verify that the memory store assigns, reassigns, lists and unassigns resources, and
+    /// rejects invalid assignments.
+    #[test]
+    fn assign_list_and_unassign() {
+        let store = MemoryTenantStore::new();
+
+        store
+            .assign(TenantAssignment::new(
+                "tenant-a",
+                TenantResourceType::Key,
+                &KEY_A.to_uppercase(),
+            ))
+            .expect("Failed to assign key");
+        store
+            .assign(TenantAssignment::new(
+                "tenant-b",
+                TenantResourceType::Circuit,
+                "abcde-01234",
+            ))
+            .expect("Failed to assign circuit");
+
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Key, KEY_A)
+                .expect("Failed to get tenant"),
+            Some("tenant-a".to_string())
+        );
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Node, "abcde-01234")
+                .expect("Failed to get tenant"),
+            None
+        );
+
+        store
+            .assign(TenantAssignment::new(
+                "tenant-a",
+                TenantResourceType::Circuit,
+                "abcde-01234",
+            ))
+            .expect("Failed to reassign circuit");
+        assert!(store
+            .list_assignments(Some("tenant-b"))
+            .expect("Failed to list assignments")
+            .is_empty());
+        assert_eq!(
+            store
+                .list_assignments(Some("tenant-a"))
+                .expect("Failed to list assignments"),
+            vec![
+                TenantAssignment::new("tenant-a", TenantResourceType::Circuit, "abcde-01234"),
+                TenantAssignment::new("tenant-a", TenantResourceType::Key, KEY_A),
+            ]
+        );
+
+        assert!(matches!(
+            store.assign(TenantAssignment::new("", TenantResourceType::User, "user")),
+            Err(TenantStoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            store.assign(TenantAssignment::new(
+                "tenant-a",
+                TenantResourceType::Key,
+                "xyz"
+            )),
+            Err(TenantStoreError::InvalidArgument(_))
+        ));
+
+        store
+            .unassign(TenantResourceType::Key, KEY_A)
+            .expect("Failed to unassign key");
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Key, KEY_A)
+                .expect("Failed to get tenant"),
+            None
+        );
+        assert!(matches!(
+            store.unassign(TenantResourceType::Key, KEY_A),
+            Err(TenantStoreError::NotFound(_))
+        ));
+    }
+}
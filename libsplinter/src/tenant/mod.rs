@@ -0,0 +1,287 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tenants, which isolate the resources of different customers that share a single node.
+//!
+//! A tenant is identified by an opaque tenant ID. Circuits, registry nodes, Biome users and
+//! public keys may each be assigned to at most one tenant; resources that are not assigned to a
+//! tenant belong to the node operator. The assignments are kept in a [`TenantStore`] and are used
+//! to:
+//!
+//! * limit the circuits that a tenant's users and keys may see and use through the REST API (see
+//!   [`TenantCircuitScopeProvider`]); identities that are neither operators nor assigned to a
+//!   tenant may not use any circuit
+//! * limit the registry nodes, Biome users and keys that REST API clients may see and manage, and
+//!   reserve tenant management for operators (see [`TenantScope`])
+//! * reject circuit proposals and votes submitted by a tenant's keys for circuits or nodes of
+//!   another tenant, and assign the circuits proposed by a tenant's keys to that tenant
+//!
+//! [`TenantStore`]: trait.TenantStore.html
+//! [`TenantCircuitScopeProvider`]: struct.TenantCircuitScopeProvider.html
+//! [`TenantScope`]: struct.TenantScope.html
+
+mod circuit_scope;
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod memory;
+mod scope;
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InvalidArgumentError;
+
+pub use self::circuit_scope::TenantCircuitScopeProvider;
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselTenantStore;
+pub use error::TenantStoreError;
+pub use memory::MemoryTenantStore;
+pub use scope::TenantScope;
+
+/// The types of resources that may be assigned to a tenant
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TenantResourceType {
+    /// A circuit, identified by its circuit ID
+    Circuit,
+    /// A public key, identified by its hex encoding
+    Key,
+    /// A registry node, identified by its node ID
+    Node,
+    /// A Biome user, identified by its user ID
+    User,
+}
+
+impl TenantResourceType {
+    /// Returns the string representation of the resource type, as stored and used in the REST API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TenantResourceType::Circuit => "circuit",
+            TenantResourceType::Key => "key",
+            TenantResourceType::Node => "node",
+            TenantResourceType::User => "user",
+        }
+    }
+}
+
+impl fmt::Display for TenantResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TenantResourceType {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "circuit" => Ok(TenantResourceType::Circuit),
+            "key" => Ok(TenantResourceType::Key),
+            "node" => Ok(TenantResourceType::Node),
+            "user" => Ok(TenantResourceType::User),
+            _ => Err(InvalidArgumentError::new(
+                "resource_type".to_string(),
+                format!(
+                    "unknown resource type '{}'; must be 'circuit', 'key', 'node' or 'user'",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// The assignment of a resource to a tenant
+#[derive(Clone, Debug, PartialEq)]
+pub struct TenantAssignment {
+    tenant_id: String,
+    resource_type: TenantResourceType,
+    resource_id: String,
+}
+
+impl TenantAssignment {
+    /// Creates a new assignment
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant_id`: The ID of the tenant the resource is assigned to
+    /// * `resource_type`: The type of the resource
+    /// * `resource_id`: The ID of the resource; public keys are normalized to lowercase hex
+    pub fn new(tenant_id: &str, resource_type: TenantResourceType, resource_id: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            resource_type,
+            resource_id: normalize_resource_id(resource_type, resource_id),
+        }
+    }
+
+    /// Returns the ID of the tenant the resource is assigned to
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Returns the type of the resource
+    pub fn resource_type(&self) -> TenantResourceType {
+        self.resource_type
+    }
+
+    /// Returns the ID of the resource
+    pub fn resource_id(&self) -> &str {
+        &self.resource_id
+    }
+}
+
+/// Defines methods for managing the assignment of resources to tenants
+pub trait TenantStore: Send + Sync {
+    /// Assigns the resource to the tenant, replacing any existing assignment of the resource
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidArgument` error if the tenant ID or resource ID is empty, or if a key
+    /// resource ID is not valid hex.
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError>;
+
+    /// Returns the ID of the tenant the resource is assigned to, if any
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, TenantStoreError>;
+
+    /// Lists the assignments, sorted by resource type and resource ID. If `tenant_id` is
+    /// provided, only the assignments of that tenant are returned.
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError>;
+
+    /// Removes the assignment of the resource
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NotFound` error if the resource is not assigned to a tenant.
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn TenantStore>;
+}
+
+impl Clone for Box<dyn TenantStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<TS> TenantStore for Box<TS>
+where
+    TS: TenantStore + ?Sized,
+{
+    fn assign(&self, assignment: TenantAssignment) -> Result<(), TenantStoreError> {
+        (**self).assign(assignment)
+    }
+
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, TenantStoreError> {
+        (**self).get_tenant(resource_type, resource_id)
+    }
+
+    fn list_assignments(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<TenantAssignment>, TenantStoreError> {
+        (**self).list_assignments(tenant_id)
+    }
+
+    fn unassign(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), TenantStoreError> {
+        (**self).unassign(resource_type, resource_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn TenantStore> {
+        (**self).clone_box()
+    }
+}
+
+/// Public keys are stored as lowercase hex so that lookups are not case-sensitive
+fn normalize_resource_id(resource_type: TenantResourceType, resource_id: &str) -> String {
+    match resource_type {
+        TenantResourceType::Key => resource_id.to_lowercase(),
+        _ => resource_id.to_string(),
+    }
+}
+
+/// Checks that the assignment has a tenant ID and a valid resource ID
+fn check_assignment(assignment: &TenantAssignment) -> Result<(), TenantStoreError> {
+    let invalid = |argument: &str, message: &str| {
+        Err(TenantStoreError::InvalidArgument(
+            InvalidArgumentError::new(argument.to_string(), message.to_string()),
+        ))
+    };
+
+    if assignment.tenant_id.trim().is_empty() {
+        return invalid("tenant_id", "tenant ID cannot be empty");
+    }
+
+    if assignment.resource_id.trim().is_empty() {
+        return invalid("resource_id", "resource ID cannot be empty");
+    }
+
+    if assignment.resource_type == TenantResourceType::Key
+        && (assignment.resource_id.len() % 2 != 0
+            || !assignment
+                .resource_id
+                .chars()
+                .all(|c| c.is_ascii_hexdigit()))
+    {
+        return invalid("resource_id", "public key must be a hex string");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that resource types round-trip through their string representation and that
+    /// unknown types are rejected.
+    #[test]
+    fn resource_type_from_str() {
+        for resource_type in &[
+            TenantResourceType::Circuit,
+            TenantResourceType::Key,
+            TenantResourceType::Node,
+            TenantResourceType::User,
+        ] {
+            assert_eq!(
+                resource_type
+                    .as_str()
+                    .parse::<TenantResourceType>()
+                    .expect("Failed to parse resource type"),
+                *resource_type
+            );
+        }
+
+        assert!("service".parse::<TenantResourceType>().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::InternalError;
+
+use super::{TenantAssignment, TenantResourceType, TenantStore, TenantStoreError};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Scope {
+    Operator,
+    Tenant(String),
+    Unassigned,
+}
+
+/// The tenant resources that an authenticated REST API client may see and manage.
+///
+/// When tenancy is enabled, the scope of the client is added to each authorized request so that
+/// the registry, Biome and tenant management endpoints can filter or reject requests for the
+/// resources of other tenants.
+#[derive(Clone)]
+pub struct TenantScope {
+    scope: Scope,
+    store: Box<dyn TenantStore>,
+}
+
+impl TenantScope {
+    /// Creates the scope of a node operator, which may see and manage every resource
+    pub fn operator(store: Box<dyn TenantStore>) -> Self {
+        Self {
+            scope: Scope::Operator,
+            store,
+        }
+    }
+
+    /// Creates the scope of a client that belongs to the given tenant
+    pub fn tenant(tenant_id: &str, store: Box<dyn TenantStore>) -> Self {
+        Self {
+            scope: Scope::Tenant(tenant_id.to_string()),
+            store,
+        }
+    }
+
+    /// Creates the scope of a client that is neither an operator nor assigned to a tenant
+    pub fn unassigned(store: Box<dyn TenantStore>) -> Self {
+        Self {
+            scope: Scope::Unassigned,
+            store,
+        }
+    }
+
+    /// Returns whether the client is a node operator
+    pub fn is_operator(&self) -> bool {
+        self.scope == Scope::Operator
+    }
+
+    /// Returns the ID of the client's tenant, if it belongs to one
+    pub fn tenant_id(&self) -> Option<&str> {
+        match &self.scope {
+            Scope::Tenant(tenant_id) => Some(tenant_id),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the client may see the resource. Operators may see every resource, and
+    /// tenants may see their own resources. Registry nodes that are not assigned to a tenant are
+    /// shared, so every client may see them.
+    pub fn permits(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<bool, InternalError> {
+        if self.is_operator() {
+            return Ok(true);
+        }
+
+        Ok(match self.get_tenant(resource_type, resource_id)? {
+            Some(tenant_id) => self.tenant_id() == Some(tenant_id.as_str()),
+            None => resource_type == TenantResourceType::Node,
+        })
+    }
+
+    /// Returns whether the client may modify or remove the resource. Operators may manage every
+    /// resource, and tenants may only manage their own resources.
+    pub fn owns(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<bool, InternalError> {
+        if self.is_operator() {
+            return Ok(true);
+        }
+
+        match self.tenant_id() {
+            Some(own_tenant_id) => Ok(self
+                .get_tenant(resource_type, resource_id)?
+                .map(|tenant_id| tenant_id == own_tenant_id)
+                .unwrap_or(false)),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns whether the client may create the resource. Operators may create any resource, and
+    /// tenants may create resources that are not assigned to another tenant.
+    pub fn may_claim(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<bool, InternalError> {
+        match &self.scope {
+            Scope::Operator => Ok(true),
+            Scope::Tenant(own_tenant_id) => Ok(self
+                .get_tenant(resource_type, resource_id)?
+                .map(|tenant_id| &tenant_id == own_tenant_id)
+                .unwrap_or(true)),
+            Scope::Unassigned => Ok(false),
+        }
+    }
+
+    /// Assigns a resource created by the client to the client's tenant. Resources created by
+    /// operators are not assigned to a tenant.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is neither an operator nor assigned to a tenant, since such
+    /// clients may not create resources.
+    pub fn claim(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), InternalError> {
+        match &self.scope {
+            Scope::Operator => Ok(()),
+            Scope::Tenant(tenant_id) => self
+                .store
+                .assign(TenantAssignment::new(tenant_id, resource_type, resource_id))
+                .map_err(|err| InternalError::from_source(Box::new(err))),
+            Scope::Unassigned => Err(InternalError::with_message(format!(
+                "Cannot assign {} {} to a tenant: client is not assigned to a tenant",
+                resource_type, resource_id
+            ))),
+        }
+    }
+
+    /// Removes the tenant assignment of a resource that the client has removed, if it has one
+    pub fn release(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<(), InternalError> {
+        match self.store.unassign(resource_type, resource_id) {
+            Ok(()) | Err(TenantStoreError::NotFound(_)) => Ok(()),
+            Err(err) => Err(InternalError::from_source(Box::new(err))),
+        }
+    }
+
+    fn get_tenant(
+        &self,
+        resource_type: TenantResourceType,
+        resource_id: &str,
+    ) -> Result<Option<String>, InternalError> {
+        self.store
+            .get_tenant(resource_type, resource_id)
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tenant::MemoryTenantStore;
+
+    /// Verify that operators may see and manage every resource, that tenants may only manage their
+    /// own resources, and that unassigned clients may only see the shared registry nodes.
+    #[test]
+    fn tenant_scope_permissions() {
+        let store = MemoryTenantStore::new();
+        for (tenant_id, resource_type, resource_id) in &[
+            ("tenant-a", TenantResourceType::Node, "node-a"),
+            ("tenant-a", TenantResourceType::User, "alice"),
+            ("tenant-b", TenantResourceType::Node, "node-b"),
+        ] {
+            store
+                .assign(TenantAssignment::new(
+                    tenant_id,
+                    *resource_type,
+                    resource_id,
+                ))
+                .expect("Failed to assign resource");
+        }
+
+        let operator = TenantScope::operator(Box::new(store.clone()));
+        assert!(operator
+            .permits(TenantResourceType::Node, "node-b")
+            .unwrap());
+        assert!(operator.owns(TenantResourceType::User, "alice").unwrap());
+        assert!(operator.owns(TenantResourceType::User, "bob").unwrap());
+
+        let tenant = TenantScope::tenant("tenant-a", Box::new(store.clone()));
+        assert!(tenant.permits(TenantResourceType::Node, "node-a").unwrap());
+        assert!(tenant.permits(TenantResourceType::Node, "node-c").unwrap());
+        assert!(!tenant.permits(TenantResourceType::Node, "node-b").unwrap());
+        assert!(tenant.permits(TenantResourceType::User, "alice").unwrap());
+        assert!(!tenant.permits(TenantResourceType::User, "bob").unwrap());
+        assert!(tenant.owns(TenantResourceType::Node, "node-a").unwrap());
+        assert!(!tenant.owns(TenantResourceType::Node, "node-c").unwrap());
+        assert!(tenant
+            .may_claim(TenantResourceType::Node, "node-c")
+            .unwrap());
+        assert!(!tenant
+            .may_claim(TenantResourceType::Node, "node-b")
+            .unwrap());
+
+        let unassigned = TenantScope::unassigned(Box::new(store.clone()));
+        assert!(unassigned
+            .permits(TenantResourceType::Node, "node-c")
+            .unwrap());
+        assert!(!unassigned
+            .permits(TenantResourceType::Node, "node-a")
+            .unwrap());
+        assert!(!unassigned.permits(TenantResourceType::User, "bob").unwrap());
+        assert!(!unassigned.owns(TenantResourceType::Node, "node-c").unwrap());
+        assert!(!unassigned
+            .may_claim(TenantResourceType::Node, "node-c")
+            .unwrap());
+        assert!(unassigned
+            .claim(TenantResourceType::Node, "node-c")
+            .is_err());
+
+        tenant
+            .claim(TenantResourceType::Node, "node-c")
+            .expect("Failed to claim node");
+        assert!(tenant.owns(TenantResourceType::Node, "node-c").unwrap());
+        assert!(!unassigned
+            .permits(TenantResourceType::Node, "node-c")
+            .unwrap());
+
+        tenant
+            .release(TenantResourceType::Node, "node-c")
+            .expect("Failed to release node");
+        tenant
+            .release(TenantResourceType::Node, "node-c")
+            .expect("Failed to release unassigned node");
+        assert_eq!(
+            store
+                .get_tenant(TenantResourceType::Node, "node-c")
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sources of time for components that act on an interval.
+//!
+//! The pacemaker, the connection manager and the peer manager all measure elapsed time to decide
+//! when to send heartbeats and when to retry lost connections. They read the time through the
+//! [`Clock`] trait, which defaults to the [`SystemClock`]. Tests may supply a [`SimulatedClock`]
+//! instead, whose time only moves when it is explicitly advanced, so that reconnection and
+//! backoff behavior can be verified without waiting on real time.
+//!
+//! [`Clock`]: trait.Clock.html
+//! [`SystemClock`]: struct.SystemClock.html
+//! [`SimulatedClock`]: struct.SimulatedClock.html
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long a thread sleeping on a simulated clock will wait, in real time, before returning to
+// its caller so that it may check for shutdown.
+const SIMULATED_SLEEP_POLL: Duration = Duration::from_millis(10);
+
+/// A source of monotonic time.
+pub trait Clock: Send + Sync {
+    /// Returns the current instant, according to this clock.
+    fn now(&self) -> Instant;
+
+    /// Returns the amount of time that has passed on this clock since the given instant.
+    fn elapsed(&self, since: Instant) -> Duration {
+        self.now()
+            .checked_duration_since(since)
+            .unwrap_or_else(|| Duration::from_secs(0))
+    }
+
+    /// Blocks the current thread for the given duration, according to this clock.
+    ///
+    /// Implementations may return early; callers that wait for a specific instant should check
+    /// `now` after sleeping.
+    fn sleep(&self, duration: Duration);
+}
+
+/// A clock backed by the operating system's monotonic clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// A clock whose time only moves when `advance` is called.
+///
+/// Clones of a `SimulatedClock` share the same time, so a test may hand a clone to a component
+/// and keep one to drive it. A thread sleeping on a simulated clock is woken as soon as the clock
+/// is advanced past its deadline; to allow the thread to observe shutdown, it also returns after
+/// a short real-time poll interval even if the simulated deadline has not been reached.
+#[derive(Clone)]
+pub struct SimulatedClock {
+    inner: Arc<(Mutex<Instant>, Condvar)>,
+}
+
+impl SimulatedClock {
+    /// Constructs a new simulated clock, starting at the current system instant.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new((Mutex::new(Instant::now()), Condvar::new())),
+        }
+    }
+
+    /// Moves the clock forward by the given duration and wakes any sleeping threads.
+    pub fn advance(&self, duration: Duration) {
+        let (lock, cvar) = &*self.inner;
+        let mut now = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *now += duration;
+        cvar.notify_all();
+    }
+}
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        let (lock, _) = &*self.inner;
+        *lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let (lock, cvar) = &*self.inner;
+        let now = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let deadline = *now + duration;
+        let _ = cvar
+            .wait_timeout_while(now, SIMULATED_SLEEP_POLL, |now| *now < deadline)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a simulated clock only moves when advanced, and that clones share its time.
+    #[test]
+    fn test_simulated_clock_advance() {
+        let clock = SimulatedClock::new();
+        let start = clock.now();
+        let clone = clock.clone();
+
+        assert_eq!(clock.elapsed(start), Duration::from_secs(0));
+
+        clone.advance(Duration::from_secs(30));
+        assert_eq!(clock.elapsed(start), Duration::from_secs(30));
+        assert_eq!(clone.now(), clock.now());
+    }
+
+    /// Test that a thread sleeping on a simulated clock is woken when the clock is advanced past
+    /// its deadline.
+    #[test]
+    fn test_simulated_clock_sleep() {
+        let clock = SimulatedClock::new();
+        let start = clock.now();
+
+        let sleeper = clock.clone();
+        let handle = thread::spawn(move || {
+            while sleeper.elapsed(start) < Duration::from_secs(60) {
+                sleeper.sleep(Duration::from_secs(60));
+            }
+            sleeper.elapsed(start)
+        });
+
+        clock.advance(Duration::from_secs(60));
+        let elapsed = handle.join().expect("Sleeping thread panicked");
+        assert_eq!(elapsed, Duration::from_secs(60));
+    }
+}
@@ -14,5 +14,6 @@
 
 //! This module will contain components that will be used to support different threading models
 
-pub mod error;
-pub mod pacemaker;
+pub mod clock;
+pub(crate) mod error;
+pub(crate) mod pacemaker;
@@ -18,8 +18,9 @@ use std::sync::{
     Arc,
 };
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use super::clock::{Clock, SystemClock};
 use super::error::PacemakerStartError;
 
 /// Build new Pacemakers
@@ -35,6 +36,7 @@ where
     interval: Option<u64>,
     sender: Option<Sender<M>>,
     message_factory: Option<F>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl<M, F> PacemakerBuilder<M, F>
@@ -48,6 +50,7 @@ where
             interval: None,
             sender: None,
             message_factory: None,
+            clock: None,
         }
     }
 
@@ -69,6 +72,14 @@ where
         self
     }
 
+    /// Set the clock used to measure the firing interval.
+    ///
+    /// Defaults to the `SystemClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Construct and start the Pacemaker.
     ///
     /// # Errors
@@ -91,17 +102,18 @@ where
             .message_factory
             .take()
             .ok_or_else(|| PacemakerStartError("No message factory function provided".into()))?;
+        let clock = self.clock.take().unwrap_or_else(|| Arc::new(SystemClock));
 
         let join_handle = thread::Builder::new()
             .name("Pacemaker".into())
             .spawn(move || {
-                let mut start = Instant::now();
+                let mut start = clock.now();
                 let loop_duration = Duration::from_secs(1);
                 let pace_duration = Duration::from_secs(interval);
 
                 while running_clone.load(Ordering::SeqCst) {
-                    if start.elapsed() >= pace_duration {
-                        start = Instant::now();
+                    if clock.elapsed(start) >= pace_duration {
+                        start = clock.now();
                         if let Err(err) = sender.send(new_message()) {
                             warn!(
                                 "Sender has disconnected before \
@@ -111,7 +123,7 @@ where
                             break;
                         }
                     }
-                    thread::sleep(loop_duration);
+                    clock.sleep(loop_duration);
                 }
             })
             .map_err(|err| PacemakerStartError(err.to_string()))?;
@@ -161,3 +173,48 @@ impl ShutdownSignaler {
         self.running.store(false, Ordering::SeqCst)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{channel, RecvTimeoutError};
+
+    use crate::threading::clock::SimulatedClock;
+
+    /// Test that a pacemaker using a simulated clock only fires once the clock has been advanced
+    /// past its interval, and fires again for each interval the clock is advanced.
+    #[test]
+    fn test_pacemaker_simulated_clock() {
+        let clock = SimulatedClock::new();
+        let (sender, recv) = channel();
+
+        let pacemaker = Pacemaker::builder()
+            .with_interval(10)
+            .with_sender(sender)
+            .with_message_factory(|| "beat")
+            .with_clock(Arc::new(clock.clone()))
+            .start()
+            .expect("Unable to start pacemaker");
+
+        assert_eq!(
+            recv.recv_timeout(Duration::from_millis(100)),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(recv.recv_timeout(Duration::from_secs(5)), Ok("beat"));
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(
+            recv.recv_timeout(Duration::from_millis(100)),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(recv.recv_timeout(Duration::from_secs(5)), Ok("beat"));
+
+        pacemaker.shutdown_signaler().shutdown();
+        pacemaker.await_shutdown();
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reusable byte buffers for message payloads.
+//!
+//! Receiving a message requires a buffer large enough to hold it. Allocating a new buffer for
+//! every message is costly on busy connections, so a [`BufferPool`] keeps buffers that are no
+//! longer needed, such as the payload of a message that has been sent, and hands them out for
+//! the next message to be received.
+//!
+//! [`BufferPool`]: struct.BufferPool.html
+
+use std::sync::{Arc, Mutex};
+
+/// The default number of buffers retained by a `BufferPool`
+pub const DEFAULT_MAX_BUFFERS: usize = 256;
+/// The default capacity, in bytes, of the largest buffer retained by a `BufferPool`
+pub const DEFAULT_MAX_BUFFER_CAPACITY: usize = 64 * 1024;
+
+/// A pool of reusable byte buffers.
+///
+/// Buffers taken from the pool are empty, but keep the capacity they had when they were released,
+/// so filling one with a message of a similar size does not allocate. Buffers with a capacity
+/// larger than the pool's maximum are not retained, so that a single large message does not hold
+/// on to memory indefinitely.
+///
+/// A `BufferPool` can be cloned cheaply; clones share the same buffers.
+#[derive(Clone, Debug)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_buffers: usize,
+    max_buffer_capacity: usize,
+}
+
+impl BufferPool {
+    /// Creates a new `BufferPool`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_buffers` - The number of released buffers to retain for reuse
+    /// * `max_buffer_capacity` - The capacity, in bytes, above which released buffers are dropped
+    pub fn new(max_buffers: usize, max_buffer_capacity: usize) -> Self {
+        BufferPool {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(max_buffers))),
+            max_buffers,
+            max_buffer_capacity,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, allocating a new one if none are available.
+    pub fn take(&self) -> Vec<u8> {
+        match self.buffers.lock() {
+            Ok(mut buffers) => buffers.pop().unwrap_or_default(),
+            Err(_) => {
+                warn!("BufferPool lock was poisoned; allocating a new buffer");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns a buffer to the pool so that it can be reused.
+    ///
+    /// The buffer is dropped instead if it is too large or the pool is full.
+    pub fn release(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() == 0 || buffer.capacity() > self.max_buffer_capacity {
+            return;
+        }
+
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < self.max_buffers {
+                buffer.clear();
+                buffers.push(buffer);
+            }
+        }
+    }
+
+    /// Returns the number of buffers available for reuse.
+    pub fn len(&self) -> usize {
+        self.buffers
+            .lock()
+            .map(|buffers| buffers.len())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if no buffers are available for reuse.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        BufferPool::new(DEFAULT_MAX_BUFFERS, DEFAULT_MAX_BUFFER_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that released buffers are reused:
+    /// 1. Release a buffer and check that the next buffer taken is empty but keeps its capacity
+    /// 2. Release a buffer larger than the maximum capacity and check that it is not retained
+    /// 3. Release more buffers than the pool holds and check that the extras are not retained
+    #[test]
+    fn test_take_and_release() {
+        let pool = BufferPool::new(2, 16);
+        assert!(pool.is_empty());
+
+        let mut buffer = pool.take();
+        buffer.extend_from_slice(b"hello");
+        let capacity = buffer.capacity();
+        pool.release(buffer);
+        assert_eq!(1, pool.len());
+
+        let buffer = pool.take();
+        assert!(buffer.is_empty());
+        assert_eq!(capacity, buffer.capacity());
+        assert!(pool.is_empty());
+
+        pool.release(vec![0; 17]);
+        assert!(pool.is_empty());
+
+        pool.release(vec![0; 1]);
+        pool.release(vec![0; 2]);
+        pool.release(vec![0; 3]);
+        assert_eq!(2, pool.len());
+    }
+}
@@ -85,7 +85,46 @@ impl ConnectionMatrixEnvelope {
 
 impl From<ConnectionMatrixEnvelope> for Vec<u8> {
     fn from(envelope: ConnectionMatrixEnvelope) -> Self {
-        envelope.payload.to_vec()
+        envelope.payload
+    }
+}
+
+/// Limits on the messages waiting to be sent on a connection. A limit that is not set is left to
+/// the connection matrix.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SendLimits {
+    outgoing_capacity: Option<usize>,
+    send_weight: Option<usize>,
+}
+
+impl SendLimits {
+    /// Creates a new `SendLimits` with no limits set
+    pub fn new() -> Self {
+        SendLimits::default()
+    }
+
+    /// Sets the number of messages that may be waiting to be sent on the connection; sends fail
+    /// once it is reached
+    pub fn with_outgoing_capacity(mut self, outgoing_capacity: usize) -> Self {
+        self.outgoing_capacity = Some(outgoing_capacity);
+        self
+    }
+
+    /// Sets the number of messages the connection may send in turn when other connections also
+    /// have messages waiting, relative to their weights
+    pub fn with_send_weight(mut self, send_weight: usize) -> Self {
+        self.send_weight = Some(send_weight);
+        self
+    }
+
+    /// Returns the number of messages that may be waiting to be sent on the connection, if set
+    pub fn outgoing_capacity(&self) -> Option<usize> {
+        self.outgoing_capacity
+    }
+
+    /// Returns the send weight of the connection, if set
+    pub fn send_weight(&self) -> Option<usize> {
+        self.send_weight
     }
 }
 
@@ -109,6 +148,27 @@ pub trait ConnectionMatrixLifeCycle: Clone + Send {
         id: String,
     ) -> Result<usize, ConnectionMatrixAddError>;
 
+    /// Adds a connection to the connection matrix with limits on the messages waiting to be sent
+    /// on it
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - Connection being added to the connection matrix
+    /// * `id` - Connection identifier; must be unique within the connection matrix
+    /// * `send_limits` - The limits on the connection's outgoing messages
+    ///
+    /// Connection matrices that don't support send limits add the connection without them.
+    ///
+    /// If the add failed, a `ConnectionMatrixAddError` will be returned.
+    fn add_with_send_limits(
+        &self,
+        connection: Box<dyn Connection>,
+        id: String,
+        _send_limits: SendLimits,
+    ) -> Result<usize, ConnectionMatrixAddError> {
+        self.add(connection, id)
+    }
+
     /// Removes a connection from the connection matrix
     ///
     /// # Arguments
@@ -35,12 +35,15 @@
 //! [`Listener::accept`]: trait.Listener.html#tymethod.accept
 //! [`Transport`]: trait.Transport.html
 
+pub mod buffer;
 mod error;
 pub mod inproc;
 pub(crate) mod matrix;
 pub mod multi;
 #[deprecated(since = "0.3.14", note = "please use splinter::transport::socket")]
 pub mod raw;
+#[cfg(feature = "tls-revocation")]
+pub mod revocation;
 pub mod socket;
 pub mod tls;
 #[cfg(feature = "ws-transport")]
@@ -60,6 +63,21 @@ pub trait Connection: Send {
     /// Attempt to receive a message consisting of bytes from the connection.
     fn recv(&mut self) -> Result<Vec<u8>, RecvError>;
 
+    /// Attempt to receive a message from the connection into the given buffer, replacing its
+    /// contents.
+    ///
+    /// Connections that read messages from a byte stream reuse the buffer's allocation when it is
+    /// large enough to hold the message, which avoids allocating a buffer per message when used
+    /// with a [`BufferPool`]. The default implementation replaces the buffer with the result of
+    /// [`Connection::recv`].
+    ///
+    /// [`BufferPool`]: buffer/struct.BufferPool.html
+    /// [`Connection::recv`]: trait.Connection.html#tymethod.recv
+    fn recv_into(&mut self, buffer: &mut Vec<u8>) -> Result<(), RecvError> {
+        *buffer = self.recv()?;
+        Ok(())
+    }
+
     /// Return the remote endpoint address for this connection.
     ///
     /// For TCP-based connection types, this will contain the remote peer
@@ -80,6 +98,23 @@ pub trait Connection: Send {
 
     /// Returns a `mio::event::Evented` for this connection which can be used for polling.
     fn evented(&self) -> &dyn Evented;
+
+    /// Return the URI subject alternative names of the certificate presented by the remote peer.
+    ///
+    /// Connections that do not authenticate the remote peer with a certificate return an empty
+    /// list, which is the default implementation.
+    #[cfg(feature = "spiffe")]
+    fn peer_certificate_uris(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Return the hex-encoded SHA-256 fingerprint of the certificate presented by the remote
+    /// peer, or `None` if the peer did not present a certificate, which is the default
+    /// implementation.
+    #[cfg(feature = "peer-identity-pinning")]
+    fn peer_certificate_fingerprint(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait Listener: Send {
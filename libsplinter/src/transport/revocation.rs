@@ -0,0 +1,282 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Certificate revocation checking for TLS peer connections.
+//!
+//! Two revocation sources are supported. A certificate revocation list (CRL) file, issued by the
+//! CA that signs the network's certificates, is checked for the certificates presented by both
+//! the servers this node connects to and the clients that connect to it. OCSP responses stapled
+//! by remote servers are verified against the CA certificates, and a node may staple its own
+//! response, obtained from its CA's OCSP responder out of band, from a file that is re-read on
+//! every handshake so it can be refreshed without a restart.
+
+use std::fs;
+
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::ocsp::{OcspCertId, OcspCertStatus, OcspFlag, OcspResponse, OcspResponseStatus};
+use openssl::ssl::{
+    SslAcceptorBuilder, SslConnectorBuilder, SslContextBuilder, SslFiletype, SslRef,
+};
+use openssl::x509::store::X509Lookup;
+use openssl::x509::verify::X509VerifyFlags;
+
+/// Raw OpenSSL verification results that indicate a failed CRL check.
+const X509_V_ERR_UNABLE_TO_GET_CRL: i32 = 3;
+const X509_V_ERR_CRL_SIGNATURE_FAILURE: i32 = 8;
+const X509_V_ERR_CRL_NOT_YET_VALID: i32 = 11;
+const X509_V_ERR_CRL_HAS_EXPIRED: i32 = 12;
+const X509_V_ERR_CERT_REVOKED: i32 = 23;
+
+/// The number of seconds of clock skew allowed when checking the validity period of an OCSP
+/// response.
+const OCSP_VALIDITY_LEEWAY_SECS: u32 = 300;
+
+/// The revocation sources used to verify the certificates of TLS peers.
+#[derive(Clone, Debug, Default)]
+pub struct RevocationConfig {
+    crl_file: Option<String>,
+    ocsp_response_file: Option<String>,
+    require_ocsp: bool,
+}
+
+impl RevocationConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the PEM-encoded CRL file that peer certificates are checked against.
+    pub fn with_crl_file(mut self, crl_file: String) -> Self {
+        self.crl_file = Some(crl_file);
+        self
+    }
+
+    /// Sets the DER-encoded OCSP response for this node's server certificate, which is stapled
+    /// to the handshake of each incoming connection.
+    pub fn with_ocsp_response_file(mut self, ocsp_response_file: String) -> Self {
+        self.ocsp_response_file = Some(ocsp_response_file);
+        self
+    }
+
+    /// Sets whether outgoing connections are refused when the remote server does not staple an
+    /// OCSP response.
+    pub fn with_require_ocsp(mut self, require_ocsp: bool) -> Self {
+        self.require_ocsp = require_ocsp;
+        self
+    }
+
+    pub fn crl_file(&self) -> Option<&str> {
+        self.crl_file.as_deref()
+    }
+
+    pub fn ocsp_response_file(&self) -> Option<&str> {
+        self.ocsp_response_file.as_deref()
+    }
+
+    pub fn require_ocsp(&self) -> bool {
+        self.require_ocsp
+    }
+
+    /// Returns whether any revocation source has been configured.
+    pub fn is_enabled(&self) -> bool {
+        self.crl_file.is_some() || self.ocsp_response_file.is_some() || self.require_ocsp
+    }
+
+    /// Configures a server's acceptor to check client certificates against the CRL and to staple
+    /// this node's OCSP response.
+    pub fn configure_acceptor(&self, builder: &mut SslAcceptorBuilder) -> Result<(), ErrorStack> {
+        self.load_crl(builder)?;
+
+        if let Some(ocsp_response_file) = self.ocsp_response_file.clone() {
+            builder.set_status_callback(move |ssl| match fs::read(&ocsp_response_file) {
+                Ok(response) => {
+                    ssl.set_ocsp_status(&response)?;
+                    Ok(true)
+                }
+                Err(err) => {
+                    warn!(
+                        "Unable to read OCSP response file {}; not stapling: {}",
+                        ocsp_response_file, err
+                    );
+                    Ok(false)
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Configures a client's connector to check server certificates against the CRL and to
+    /// verify the OCSP responses stapled by servers.
+    ///
+    /// A stapled response is only sent if the connection requests one, which connections made
+    /// with the connector must do by setting their status type to OCSP.
+    pub fn configure_connector(&self, builder: &mut SslConnectorBuilder) -> Result<(), ErrorStack> {
+        self.load_crl(builder)?;
+
+        let require_ocsp = self.require_ocsp;
+        builder.set_status_callback(move |ssl| {
+            let accepted = check_stapled_response(ssl, require_ocsp)?;
+            if !accepted {
+                record_failure("ocsp");
+            }
+            Ok(accepted)
+        })?;
+
+        Ok(())
+    }
+
+    fn load_crl(&self, builder: &mut SslContextBuilder) -> Result<(), ErrorStack> {
+        if let Some(crl_file) = &self.crl_file {
+            let store = builder.cert_store_mut();
+            store
+                .add_lookup(X509Lookup::file())?
+                .load_crl_file(crl_file, SslFiletype::PEM)?;
+            store.set_flags(X509VerifyFlags::CRL_CHECK)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifies the OCSP response stapled by a remote server, returning whether the connection may
+/// proceed.
+fn check_stapled_response(ssl: &mut SslRef, require_ocsp: bool) -> Result<bool, ErrorStack> {
+    let response = match ssl.ocsp_status() {
+        Some(response) => OcspResponse::from_der(response)?,
+        None if require_ocsp => {
+            warn!("Refusing TLS connection: server did not staple an OCSP response");
+            return Ok(false);
+        }
+        None => return Ok(true),
+    };
+
+    if response.status() != OcspResponseStatus::SUCCESSFUL {
+        warn!(
+            "Refusing TLS connection: stapled OCSP response has status {:?}",
+            response.status()
+        );
+        return Ok(false);
+    }
+
+    let chain = match ssl.verified_chain() {
+        Some(chain) if chain.len() > 1 => chain,
+        _ => {
+            warn!("Refusing TLS connection: unable to find the issuer of the server certificate");
+            return Ok(false);
+        }
+    };
+    let (subject, issuer) = match (chain.get(0), chain.get(1)) {
+        (Some(subject), Some(issuer)) => (subject, issuer),
+        _ => return Ok(false),
+    };
+
+    let basic = response.basic()?;
+    if let Err(err) = basic.verify(chain, ssl.ssl_context().cert_store(), OcspFlag::empty()) {
+        warn!(
+            "Refusing TLS connection: stapled OCSP response could not be verified: {}",
+            err
+        );
+        return Ok(false);
+    }
+
+    let cert_id = OcspCertId::from_cert(MessageDigest::sha1(), subject, issuer)?;
+    match basic.find_status(&cert_id) {
+        Some(status) => {
+            if let Err(err) = status.check_validity(OCSP_VALIDITY_LEEWAY_SECS, None) {
+                warn!(
+                    "Refusing TLS connection: stapled OCSP response is not current: {}",
+                    err
+                );
+                return Ok(false);
+            }
+            if status.status == OcspCertStatus::GOOD {
+                Ok(true)
+            } else {
+                warn!(
+                    "Refusing TLS connection: server certificate is {}",
+                    if status.status == OcspCertStatus::REVOKED {
+                        "revoked"
+                    } else {
+                        "unknown to its OCSP responder"
+                    }
+                );
+                Ok(false)
+            }
+        }
+        None => {
+            warn!("Refusing TLS connection: stapled OCSP response does not cover the certificate");
+            Ok(false)
+        }
+    }
+}
+
+/// Records a failed handshake if its certificate verification failed because of a CRL check.
+pub(crate) fn check_handshake_failure(ssl: &SslRef) {
+    let result = ssl.verify_result();
+    match result.as_raw() {
+        X509_V_ERR_CERT_REVOKED
+        | X509_V_ERR_UNABLE_TO_GET_CRL
+        | X509_V_ERR_CRL_SIGNATURE_FAILURE
+        | X509_V_ERR_CRL_NOT_YET_VALID
+        | X509_V_ERR_CRL_HAS_EXPIRED => {
+            warn!(
+                "Refused TLS connection, revocation check failed: {}",
+                result.error_string()
+            );
+            record_failure("crl");
+        }
+        _ => (),
+    }
+}
+
+#[allow(unused_variables)]
+fn record_failure(source: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("splinter.tls.revocation_check_failures", 1, "source" => source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use openssl::ssl::{SslAcceptor, SslConnector, SslMethod};
+
+    /// Verify that a revocation configuration is only enabled once a revocation source or the
+    /// OCSP requirement has been set.
+    #[test]
+    fn test_is_enabled() {
+        assert!(!RevocationConfig::new().is_enabled());
+        assert!(RevocationConfig::new()
+            .with_crl_file("crl.pem".into())
+            .is_enabled());
+        assert!(RevocationConfig::new()
+            .with_ocsp_response_file("server.ocsp".into())
+            .is_enabled());
+        assert!(RevocationConfig::new().with_require_ocsp(true).is_enabled());
+    }
+
+    /// Verify that a missing CRL file is reported when the acceptor and connector are configured,
+    /// rather than silently disabling the revocation check.
+    #[test]
+    fn test_missing_crl_file() {
+        let config = RevocationConfig::new().with_crl_file("/nonexistent/crl.pem".into());
+
+        let mut acceptor = SslAcceptor::mozilla_modern(SslMethod::tls()).unwrap();
+        assert!(config.configure_acceptor(&mut acceptor).is_err());
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        assert!(config.configure_connector(&mut connector).is_err());
+    }
+}
@@ -102,6 +102,24 @@ impl Frame {
     /// - the data length doesn't match the header length
     /// - an IO error occurs
     pub fn read<R: Read>(reader: &mut R) -> Result<Self, FrameError> {
+        let mut data = vec![];
+        Frame::read_into(reader, &mut data)?;
+        Ok(Self { data })
+    }
+
+    /// Read a frame's data from the given reader into the given buffer, replacing the buffer's
+    /// contents.
+    ///
+    /// The buffer's allocation is reused if it has the capacity to hold the frame's data.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if:
+    ///
+    /// - the header is malformed
+    /// - the data length doesn't match the header length
+    /// - an IO error occurs
+    pub fn read_into<R: Read>(reader: &mut R, buffer: &mut Vec<u8>) -> Result<(), FrameError> {
         let frame_header = loop {
             match FrameHeader::read(reader) {
                 Err(FrameError::IoError(ref e)) if e.kind() == io::ErrorKind::WouldBlock => {
@@ -115,7 +133,8 @@ impl Frame {
 
         match frame_header {
             FrameHeader::V1 { length } => {
-                let mut buffer = vec![0; length as usize];
+                buffer.clear();
+                buffer.resize(length as usize, 0);
                 let mut remaining = &mut buffer[..];
 
                 while !remaining.is_empty() {
@@ -138,7 +157,7 @@ impl Frame {
                         "Could not receive complete frame",
                     )))
                 } else {
-                    Ok(Self { data: buffer })
+                    Ok(())
                 }
             }
         }
@@ -659,6 +678,30 @@ mod tests {
         assert_eq!(input.to_vec(), frame.data);
     }
 
+    /// Read two frames from a stream into the same buffer, and verify that the buffer holds only
+    /// the second frame's data and that its allocation was reused.
+    #[test]
+    fn read_frame_into_buffer() {
+        let mut cursor = Cursor::new(vec![]);
+        FrameRef::new(FrameVersion::V1, b"hello world")
+            .write(&mut cursor)
+            .expect("Unable to write first frame");
+        FrameRef::new(FrameVersion::V1, b"hello")
+            .write(&mut cursor)
+            .expect("Unable to write second frame");
+
+        cursor.set_position(0);
+
+        let mut buffer = vec![];
+        Frame::read_into(&mut cursor, &mut buffer).expect("Unable to read first frame");
+        assert_eq!(b"hello world".to_vec(), buffer);
+
+        let capacity = buffer.capacity();
+        Frame::read_into(&mut cursor, &mut buffer).expect("Unable to read second frame");
+        assert_eq!(b"hello".to_vec(), buffer);
+        assert_eq!(capacity, buffer.capacity());
+    }
+
     /// Write a frame to a stream and verify that an equivalent frame is read back from the stream.
     #[test]
     fn frame_round_trip() {
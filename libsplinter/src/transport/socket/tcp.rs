@@ -142,6 +142,14 @@ impl Connection for TcpConnection {
         }
     }
 
+    fn recv_into(&mut self, buffer: &mut Vec<u8>) -> Result<(), RecvError> {
+        match Frame::read_into(&mut self.stream, buffer) {
+            Err(FrameError::IoError(e)) => Err(RecvError::from(e)),
+            Err(err) => Err(RecvError::ProtocolError(err.to_string())),
+            Ok(()) => Ok(()),
+        }
+    }
+
     fn remote_endpoint(&self) -> String {
         format!("tcp://{}", self.stream.peer_addr().unwrap())
     }
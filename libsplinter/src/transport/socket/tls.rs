@@ -14,6 +14,10 @@
 
 use mio::{unix::EventedFd, Evented, Poll, PollOpt, Ready, Token};
 use openssl::error::ErrorStack;
+#[cfg(feature = "peer-identity-pinning")]
+use openssl::hash::MessageDigest;
+#[cfg(feature = "tls-revocation")]
+use openssl::ssl::StatusType;
 use openssl::ssl::{
     Error as OpensslError, HandshakeError, SslAcceptor, SslConnector, SslFiletype, SslMethod,
     SslStream, SslVerifyMode,
@@ -22,11 +26,24 @@ use url::{ParseError, Url};
 
 use std::error::Error;
 use std::fmt;
+#[cfg(feature = "spiffe")]
+use std::fs;
 use std::io;
 use std::net::{Ipv4Addr, Ipv6Addr, TcpListener, TcpStream};
 use std::os::unix::io::{AsRawFd, RawFd};
 use std::path::Path;
-
+#[cfg(feature = "spiffe")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "spiffe")]
+use std::time::SystemTime;
+
+#[cfg(feature = "peer-identity-pinning")]
+use crate::hex::to_hex;
+#[cfg(feature = "tls-revocation")]
+use crate::transport::revocation::{check_handshake_failure, RevocationConfig};
+use crate::transport::tls::TlsConfig;
+#[cfg(feature = "tls-policy")]
+use crate::transport::tls::TlsPolicy;
 use crate::transport::{
     AcceptError, ConnectError, Connection, DisconnectError, ListenError, Listener, RecvError,
     SendError, Transport,
@@ -41,6 +58,10 @@ const PROTOCOL_PREFIX: &str = "tcps://";
 pub struct TlsTransport {
     connector: SslConnector,
     acceptor: SslAcceptor,
+    #[cfg(feature = "tls-revocation")]
+    request_ocsp_status: bool,
+    #[cfg(feature = "spiffe")]
+    reloader: Option<Arc<ContextReloader>>,
 }
 
 impl TlsTransport {
@@ -50,6 +71,61 @@ impl TlsTransport {
         client_cert: String,
         server_key: String,
         server_cert: String,
+    ) -> Result<Self, TlsInitError> {
+        Self::build(
+            ca_cert,
+            client_key,
+            client_cert,
+            server_key,
+            server_cert,
+            #[cfg(feature = "tls-policy")]
+            &TlsPolicy::default(),
+            #[cfg(feature = "tls-revocation")]
+            &RevocationConfig::default(),
+        )
+    }
+
+    /// Creates a transport from the given TLS configuration, including its TLS policy.
+    pub fn from_config(config: &TlsConfig) -> Result<Self, TlsInitError> {
+        #[allow(unused_mut)]
+        let mut transport = Self::build_from_config(config)?;
+
+        #[cfg(feature = "spiffe")]
+        {
+            if config.reload_certificates() {
+                transport.reloader = Some(Arc::new(ContextReloader::new(
+                    config.clone(),
+                    transport.connector.clone(),
+                    transport.acceptor.clone(),
+                )));
+            }
+        }
+
+        Ok(transport)
+    }
+
+    fn build_from_config(config: &TlsConfig) -> Result<Self, TlsInitError> {
+        Self::build(
+            config.ca_certs_file().clone(),
+            config.client_private_key_file().to_string(),
+            config.client_cert_file().to_string(),
+            config.server_private_key_file().to_string(),
+            config.server_cert_file().to_string(),
+            #[cfg(feature = "tls-policy")]
+            config.policy(),
+            #[cfg(feature = "tls-revocation")]
+            config.revocation(),
+        )
+    }
+
+    fn build(
+        ca_cert: Option<String>,
+        client_key: String,
+        client_cert: String,
+        server_key: String,
+        server_cert: String,
+        #[cfg(feature = "tls-policy")] policy: &TlsPolicy,
+        #[cfg(feature = "tls-revocation")] revocation: &RevocationConfig,
     ) -> Result<Self, TlsInitError> {
         let client_cert_path = Path::new(&client_cert);
         let client_key_path = Path::new(&client_key);
@@ -68,31 +144,171 @@ impl TlsTransport {
         acceptor.set_certificate_chain_file(&server_cert_path)?;
         acceptor.check_private_key()?;
 
+        #[cfg(feature = "tls-revocation")]
+        {
+            if ca_cert.is_none() && revocation.is_enabled() {
+                return Err(TlsInitError::ProtocolError(
+                    "certificate revocation cannot be checked without a CA certificate".into(),
+                ));
+            }
+        }
+
         // if ca_cert is provided set as accept cert, otherwise set verify to none
-        let (acceptor, connector) = {
-            if let Some(ca_cert) = ca_cert {
-                let ca_cert_path = Path::new(&ca_cert);
-                acceptor.set_ca_file(ca_cert_path)?;
-                connector.set_ca_file(ca_cert_path)?;
-                connector.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
-                acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
-                let connector = connector.build();
-                let acceptor = acceptor.build();
-                (acceptor, connector)
-            } else {
-                connector.set_verify(SslVerifyMode::NONE);
-                acceptor.set_verify(SslVerifyMode::NONE);
-                let connector = connector.build();
-                let acceptor = acceptor.build();
-                (acceptor, connector)
+        if let Some(ca_cert) = ca_cert {
+            let ca_cert_path = Path::new(&ca_cert);
+            acceptor.set_ca_file(ca_cert_path)?;
+            connector.set_ca_file(ca_cert_path)?;
+            connector.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+            acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        } else {
+            #[cfg(feature = "tls-policy")]
+            {
+                if policy.requests_client_certs() {
+                    return Err(TlsInitError::ProtocolError(
+                        "client certificates cannot be verified without a CA certificate".into(),
+                    ));
+                }
             }
-        };
+            connector.set_verify(SslVerifyMode::NONE);
+            acceptor.set_verify(SslVerifyMode::NONE);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            policy.configure_connector(&mut connector)?;
+            policy.configure_acceptor(&mut acceptor)?;
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            if revocation.is_enabled() {
+                revocation.configure_connector(&mut connector)?;
+                revocation.configure_acceptor(&mut acceptor)?;
+            }
+        }
+
+        let connector = connector.build();
+        let acceptor = acceptor.build();
 
         Ok(TlsTransport {
             connector,
             acceptor,
+            #[cfg(feature = "tls-revocation")]
+            request_ocsp_status: revocation.is_enabled(),
+            #[cfg(feature = "spiffe")]
+            reloader: None,
         })
     }
+
+    /// Returns the connector for new connections, reloading it first if the certificate files
+    /// have changed.
+    fn connector(&self) -> SslConnector {
+        #[cfg(feature = "spiffe")]
+        if let Some(reloader) = &self.reloader {
+            return reloader.contexts().0;
+        }
+        self.connector.clone()
+    }
+
+    /// Connects to a server, requesting a stapled OCSP response and recording handshakes that
+    /// fail because the server's certificate has been revoked.
+    #[cfg(feature = "tls-revocation")]
+    fn connect_checking_revocation(
+        &self,
+        dns_name: &str,
+        stream: TcpStream,
+    ) -> Result<SslStream<TcpStream>, ConnectError> {
+        let to_connect_error = |err: ErrorStack| {
+            ConnectError::ProtocolError(format!("Unable to configure TLS connection: {}", err))
+        };
+        let mut configuration = self.connector().configure().map_err(to_connect_error)?;
+        if self.request_ocsp_status {
+            configuration
+                .set_status_type(StatusType::OCSP)
+                .map_err(to_connect_error)?;
+        }
+
+        let result = configuration.connect(dns_name, stream);
+        if let Err(HandshakeError::Failure(mid_handshake)) = &result {
+            check_handshake_failure(mid_handshake.ssl());
+        }
+        Ok(result?)
+    }
+}
+
+/// Rebuilds a transport's TLS contexts when its certificate, key or CA files change on disk, so
+/// that short-lived certificates, such as SPIFFE SVIDs, can be rotated without a restart.
+#[cfg(feature = "spiffe")]
+struct ContextReloader {
+    config: TlsConfig,
+    contexts: Mutex<ReloadedContexts>,
+}
+
+#[cfg(feature = "spiffe")]
+struct ReloadedContexts {
+    modified: Option<SystemTime>,
+    connector: SslConnector,
+    acceptor: SslAcceptor,
+}
+
+#[cfg(feature = "spiffe")]
+impl ContextReloader {
+    fn new(config: TlsConfig, connector: SslConnector, acceptor: SslAcceptor) -> Self {
+        let modified = last_modified(&config);
+        ContextReloader {
+            config,
+            contexts: Mutex::new(ReloadedContexts {
+                modified,
+                connector,
+                acceptor,
+            }),
+        }
+    }
+
+    /// Returns the current connector and acceptor, rebuilding them if any of the files have
+    /// been modified since they were last loaded. If the files cannot be loaded, such as while
+    /// they are being rewritten, the previous contexts are kept and the reload is retried on the
+    /// next call.
+    fn contexts(&self) -> (SslConnector, SslAcceptor) {
+        let mut contexts = match self.contexts.lock() {
+            Ok(contexts) => contexts,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let modified = last_modified(&self.config);
+        if modified.is_some() && modified != contexts.modified {
+            match TlsTransport::build_from_config(&self.config) {
+                Ok(transport) => {
+                    debug!("Reloaded TLS certificates");
+                    contexts.modified = modified;
+                    contexts.connector = transport.connector;
+                    contexts.acceptor = transport.acceptor;
+                }
+                Err(err) => warn!("Unable to reload TLS certificates: {}", err),
+            }
+        }
+
+        (contexts.connector.clone(), contexts.acceptor.clone())
+    }
+}
+
+/// Returns the most recent modification time of the configured certificate, key and CA files.
+#[cfg(feature = "spiffe")]
+fn last_modified(config: &TlsConfig) -> Option<SystemTime> {
+    let mut files = vec![
+        config.client_cert_file(),
+        config.client_private_key_file(),
+        config.server_cert_file(),
+        config.server_private_key_file(),
+    ];
+    if let Some(ca_certs_file) = config.ca_certs_file() {
+        files.push(ca_certs_file);
+    }
+
+    files
+        .into_iter()
+        .filter_map(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .max()
 }
 
 fn endpoint_to_dns_name(endpoint: &str) -> Result<String, ParseError> {
@@ -134,7 +350,10 @@ impl Transport for TlsTransport {
         let dns_name = endpoint_to_dns_name(address)?;
 
         let stream = TcpStream::connect(address)?;
-        let mut tls_stream = self.connector.connect(&dns_name, stream)?;
+        #[cfg(not(feature = "tls-revocation"))]
+        let mut tls_stream = self.connector().connect(&dns_name, stream)?;
+        #[cfg(feature = "tls-revocation")]
+        let mut tls_stream = self.connect_checking_revocation(&dns_name, stream)?;
 
         let frame_version = FrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V1)
             .negotiate(&mut tls_stream)
@@ -175,6 +394,8 @@ impl Transport for TlsTransport {
                 ListenError::IoError(format!("Failed to bind to {}", address), err)
             })?,
             acceptor: self.acceptor.clone(),
+            #[cfg(feature = "spiffe")]
+            reloader: self.reloader.clone(),
         }))
     }
 }
@@ -182,12 +403,31 @@ impl Transport for TlsTransport {
 pub struct TlsListener {
     listener: TcpListener,
     acceptor: SslAcceptor,
+    #[cfg(feature = "spiffe")]
+    reloader: Option<Arc<ContextReloader>>,
+}
+
+impl TlsListener {
+    /// Returns the acceptor for new connections, reloading it first if the certificate files
+    /// have changed.
+    fn acceptor(&self) -> SslAcceptor {
+        #[cfg(feature = "spiffe")]
+        if let Some(reloader) = &self.reloader {
+            return reloader.contexts().1;
+        }
+        self.acceptor.clone()
+    }
 }
 
 impl Listener for TlsListener {
     fn accept(&mut self) -> Result<Box<dyn Connection>, AcceptError> {
         let (stream, _) = self.listener.accept()?;
-        let mut tls_stream = self.acceptor.accept(stream)?;
+        let result = self.acceptor().accept(stream);
+        #[cfg(feature = "tls-revocation")]
+        if let Err(HandshakeError::Failure(mid_handshake)) = &result {
+            check_handshake_failure(mid_handshake.ssl());
+        }
+        let mut tls_stream = result?;
 
         let frame_version = FrameNegotiation::inbound(FrameVersion::V1)
             .negotiate(&mut tls_stream)
@@ -236,6 +476,14 @@ impl Connection for TlsConnection {
         }
     }
 
+    fn recv_into(&mut self, buffer: &mut Vec<u8>) -> Result<(), RecvError> {
+        match Frame::read_into(&mut self.stream, buffer) {
+            Err(FrameError::IoError(e)) => Err(RecvError::from(e)),
+            Err(err) => Err(RecvError::ProtocolError(err.to_string())),
+            Ok(()) => Ok(()),
+        }
+    }
+
     fn remote_endpoint(&self) -> String {
         format!("tcps://{}", self.stream.get_ref().peer_addr().unwrap())
     }
@@ -253,6 +501,30 @@ impl Connection for TlsConnection {
     fn evented(&self) -> &dyn Evented {
         self
     }
+
+    #[cfg(feature = "spiffe")]
+    fn peer_certificate_uris(&self) -> Vec<String> {
+        self.stream
+            .ssl()
+            .peer_certificate()
+            .and_then(|cert| cert.subject_alt_names())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.uri().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    fn peer_certificate_fingerprint(&self) -> Option<String> {
+        self.stream
+            .ssl()
+            .peer_certificate()
+            .and_then(|cert| cert.digest(MessageDigest::sha256()).ok())
+            .map(|digest| to_hex(&digest))
+    }
 }
 
 impl TlsConnection {
@@ -368,6 +640,18 @@ pub(crate) mod tests {
     }
 
     pub fn create_test_tls_transport(insecure: bool) -> TlsTransport {
+        build_test_tls_transport(
+            insecure,
+            #[cfg(feature = "tls-policy")]
+            &TlsPolicy::default(),
+        )
+        .unwrap()
+    }
+
+    fn build_test_tls_transport(
+        insecure: bool,
+        #[cfg(feature = "tls-policy")] policy: &TlsPolicy,
+    ) -> Result<TlsTransport, TlsInitError> {
         // Genearte Certificat Authority keys and certificate
         let (ca_key, ca_cert) = make_ca_cert();
 
@@ -416,14 +700,17 @@ pub(crate) mod tests {
         );
 
         // Create TLsTransport
-        TlsTransport::new(
+        TlsTransport::build(
             ca_path_file,
             client_key_file,
             client_cert_file,
             server_key_file,
             server_cert_file,
+            #[cfg(feature = "tls-policy")]
+            policy,
+            #[cfg(feature = "tls-revocation")]
+            &RevocationConfig::default(),
         )
-        .unwrap()
     }
 
     #[test]
@@ -467,4 +754,39 @@ pub(crate) mod tests {
             Ready::readable() | Ready::writable(),
         );
     }
+
+    /// Test that a transport whose policy requires TLS 1.3 and client certificates can connect to
+    /// and accept connections from itself.
+    #[cfg(feature = "tls-policy")]
+    #[test]
+    fn test_transport_tls13_required_client_auth() {
+        use crate::transport::tls::{ClientAuth, TlsVersion};
+
+        let policy = TlsPolicy::new()
+            .with_min_version(TlsVersion::Tls13)
+            .with_cipher_suites(vec!["TLS_AES_256_GCM_SHA384".into()])
+            .with_client_auth(ClientAuth::Required);
+        let transport =
+            build_test_tls_transport(false, &policy).expect("Unable to create transport");
+        tests::test_transport(transport, "127.0.0.1:0");
+    }
+
+    /// Test that a policy that asks for client certificates is rejected when there is no CA to
+    /// verify them against.
+    #[cfg(feature = "tls-policy")]
+    #[test]
+    fn test_policy_client_auth_requires_ca() {
+        use crate::transport::tls::ClientAuth;
+
+        let policy = TlsPolicy::new().with_client_auth(ClientAuth::Optional);
+        assert!(build_test_tls_transport(true, &policy).is_err());
+    }
+
+    /// Test that a policy with an unknown cipher suite is rejected when the transport is created.
+    #[cfg(feature = "tls-policy")]
+    #[test]
+    fn test_policy_invalid_cipher_suite() {
+        let policy = TlsPolicy::new().with_cipher_suites(vec!["NOT-A-CIPHER".into()]);
+        assert!(build_test_tls_transport(false, &policy).is_err());
+    }
 }
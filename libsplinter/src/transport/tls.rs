@@ -12,20 +12,40 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "tls-policy")]
+use std::fmt;
 #[cfg(feature = "ws-transport")]
 use std::path::Path;
+#[cfg(feature = "tls-policy")]
+use std::str::FromStr;
 
-#[cfg(feature = "ws-transport")]
+#[cfg(any(feature = "ws-transport", feature = "tls-policy"))]
 use openssl::error::ErrorStack;
+#[cfg(any(feature = "ws-transport", feature = "tls-policy"))]
+use openssl::ssl::SslVerifyMode;
 #[cfg(feature = "ws-transport")]
-use openssl::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod, SslVerifyMode};
+use openssl::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod};
+#[cfg(feature = "tls-policy")]
+use openssl::ssl::{SslAcceptorBuilder, SslConnectorBuilder, SslContextBuilder, SslVersion};
+
+#[cfg(feature = "tls-policy")]
+use crate::error::InvalidArgumentError;
+#[cfg(feature = "tls-revocation")]
+use crate::transport::revocation::RevocationConfig;
 
+#[derive(Clone)]
 pub struct TlsConfig {
     ca_certs_file: Option<String>,
     server_cert_file: String,
     server_private_key_file: String,
     client_cert_file: String,
     client_private_key_file: String,
+    #[cfg(feature = "tls-policy")]
+    policy: TlsPolicy,
+    #[cfg(feature = "tls-revocation")]
+    revocation: RevocationConfig,
+    #[cfg(feature = "spiffe")]
+    reload_certificates: bool,
 }
 
 impl TlsConfig {
@@ -48,6 +68,22 @@ impl TlsConfig {
     pub fn client_private_key_file(&self) -> &str {
         &self.client_private_key_file
     }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn policy(&self) -> &TlsPolicy {
+        &self.policy
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    pub fn revocation(&self) -> &RevocationConfig {
+        &self.revocation
+    }
+
+    /// Returns whether the transport reloads the certificate, key and CA files when they change.
+    #[cfg(feature = "spiffe")]
+    pub fn reload_certificates(&self) -> bool {
+        self.reload_certificates
+    }
 }
 
 #[derive(Default)]
@@ -57,6 +93,12 @@ pub struct TlsConfigBuilder {
     server_private_key_file: Option<String>,
     client_cert_file: Option<String>,
     client_private_key_file: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    policy: TlsPolicy,
+    #[cfg(feature = "tls-revocation")]
+    revocation: RevocationConfig,
+    #[cfg(feature = "spiffe")]
+    reload_certificates: bool,
 }
 
 impl TlsConfigBuilder {
@@ -67,6 +109,12 @@ impl TlsConfigBuilder {
             server_private_key_file: None,
             client_cert_file: None,
             client_private_key_file: None,
+            #[cfg(feature = "tls-policy")]
+            policy: TlsPolicy::default(),
+            #[cfg(feature = "tls-revocation")]
+            revocation: RevocationConfig::default(),
+            #[cfg(feature = "spiffe")]
+            reload_certificates: false,
         }
     }
 
@@ -95,7 +143,46 @@ impl TlsConfigBuilder {
         self
     }
 
+    #[cfg(feature = "tls-policy")]
+    pub fn with_policy(mut self, policy: TlsPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    pub fn with_revocation(mut self, revocation: RevocationConfig) -> Self {
+        self.revocation = revocation;
+        self
+    }
+
+    /// Sets whether the transport reloads the certificate, key and CA files when they change on
+    /// disk, such as when short-lived SPIFFE SVIDs are rotated.
+    #[cfg(feature = "spiffe")]
+    pub fn with_reload_certificates(mut self, reload_certificates: bool) -> Self {
+        self.reload_certificates = reload_certificates;
+        self
+    }
+
     pub fn build(self) -> Result<TlsConfig, TlsConfigBuilderError> {
+        #[cfg(feature = "tls-policy")]
+        {
+            if self.ca_certs_file.is_none() && self.policy.requests_client_certs() {
+                return Err(TlsConfigBuilderError::InvalidPolicy(
+                    "client certificates cannot be verified without a CA certificate file"
+                        .to_string(),
+                ));
+            }
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            if self.ca_certs_file.is_none() && self.revocation.is_enabled() {
+                return Err(TlsConfigBuilderError::InvalidRevocation(
+                    "revocation cannot be checked without a CA certificate file".to_string(),
+                ));
+            }
+        }
+
         let ca_certs_file = self.ca_certs_file;
         let server_cert_file = self
             .server_cert_file
@@ -116,6 +203,12 @@ impl TlsConfigBuilder {
             server_private_key_file,
             client_cert_file,
             client_private_key_file,
+            #[cfg(feature = "tls-policy")]
+            policy: self.policy,
+            #[cfg(feature = "tls-revocation")]
+            revocation: self.revocation,
+            #[cfg(feature = "spiffe")]
+            reload_certificates: self.reload_certificates,
         })
     }
 }
@@ -123,6 +216,10 @@ impl TlsConfigBuilder {
 #[derive(Debug)]
 pub enum TlsConfigBuilderError {
     MissingField(String),
+    #[cfg(feature = "tls-policy")]
+    InvalidPolicy(String),
+    #[cfg(feature = "tls-revocation")]
+    InvalidRevocation(String),
 }
 
 impl std::error::Error for TlsConfigBuilderError {}
@@ -133,7 +230,202 @@ impl std::fmt::Display for TlsConfigBuilderError {
             TlsConfigBuilderError::MissingField(ref s) => {
                 write!(f, "Missing required field '{}' in TLS configuration", s)
             }
+            #[cfg(feature = "tls-policy")]
+            TlsConfigBuilderError::InvalidPolicy(ref s) => {
+                write!(f, "Invalid TLS policy: {}", s)
+            }
+            #[cfg(feature = "tls-revocation")]
+            TlsConfigBuilderError::InvalidRevocation(ref s) => {
+                write!(f, "Invalid TLS revocation configuration: {}", s)
+            }
+        }
+    }
+}
+
+/// A minimum TLS protocol version.
+#[cfg(feature = "tls-policy")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TlsVersion {
+    Tls12,
+    Tls13,
+}
+
+#[cfg(feature = "tls-policy")]
+impl TlsVersion {
+    fn ssl_version(self) -> SslVersion {
+        match self {
+            TlsVersion::Tls12 => SslVersion::TLS1_2,
+            TlsVersion::Tls13 => SslVersion::TLS1_3,
+        }
+    }
+}
+
+#[cfg(feature = "tls-policy")]
+impl FromStr for TlsVersion {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1.2" => Ok(TlsVersion::Tls12),
+            "1.3" => Ok(TlsVersion::Tls13),
+            _ => Err(InvalidArgumentError::new(
+                "min_version".into(),
+                format!("{} is not a supported TLS version; must be 1.2 or 1.3", s),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "tls-policy")]
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsVersion::Tls12 => f.write_str("1.2"),
+            TlsVersion::Tls13 => f.write_str("1.3"),
+        }
+    }
+}
+
+/// Whether a server requests and requires certificates from its clients.
+#[cfg(feature = "tls-policy")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClientAuth {
+    /// Clients must present a certificate signed by a trusted CA.
+    Required,
+    /// Clients are asked for a certificate, which is verified if presented.
+    Optional,
+    /// Clients are not asked for a certificate.
+    Off,
+}
+
+#[cfg(feature = "tls-policy")]
+impl FromStr for ClientAuth {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "required" => Ok(ClientAuth::Required),
+            "optional" => Ok(ClientAuth::Optional),
+            "off" => Ok(ClientAuth::Off),
+            _ => Err(InvalidArgumentError::new(
+                "client_auth".into(),
+                format!("{} is not valid; must be required, optional, or off", s),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "tls-policy")]
+impl fmt::Display for ClientAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientAuth::Required => f.write_str("required"),
+            ClientAuth::Optional => f.write_str("optional"),
+            ClientAuth::Off => f.write_str("off"),
+        }
+    }
+}
+
+/// Restrictions on the TLS connections a transport or server will make and accept.
+///
+/// Any setting that is not provided keeps the OpenSSL defaults used without a policy. Cipher
+/// suites whose names begin with `TLS_` are TLS 1.3 suites; all others are TLS 1.2 cipher names
+/// in OpenSSL's format.
+#[cfg(feature = "tls-policy")]
+#[derive(Clone, Debug, Default)]
+pub struct TlsPolicy {
+    min_version: Option<TlsVersion>,
+    cipher_suites: Vec<String>,
+    client_auth: Option<ClientAuth>,
+}
+
+#[cfg(feature = "tls-policy")]
+impl TlsPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minimum protocol version for both accepted and created connections.
+    pub fn with_min_version(mut self, min_version: TlsVersion) -> Self {
+        self.min_version = Some(min_version);
+        self
+    }
+
+    /// Set the cipher suites allowed for both accepted and created connections.
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<String>) -> Self {
+        self.cipher_suites = cipher_suites;
+        self
+    }
+
+    /// Set whether accepted connections must present a client certificate.
+    pub fn with_client_auth(mut self, client_auth: ClientAuth) -> Self {
+        self.client_auth = Some(client_auth);
+        self
+    }
+
+    pub fn min_version(&self) -> Option<TlsVersion> {
+        self.min_version
+    }
+
+    pub fn cipher_suites(&self) -> &[String] {
+        &self.cipher_suites
+    }
+
+    pub fn client_auth(&self) -> Option<ClientAuth> {
+        self.client_auth
+    }
+
+    /// Returns whether this policy asks clients for a certificate, which requires a CA to verify
+    /// it against.
+    pub fn requests_client_certs(&self) -> bool {
+        match self.client_auth {
+            Some(ClientAuth::Required) | Some(ClientAuth::Optional) => true,
+            Some(ClientAuth::Off) | None => false,
+        }
+    }
+
+    /// Applies the policy to a server's acceptor, including the client certificate requirement.
+    ///
+    /// If the policy does not set a client certificate requirement, the acceptor's verify mode is
+    /// not changed.
+    pub fn configure_acceptor(&self, builder: &mut SslAcceptorBuilder) -> Result<(), ErrorStack> {
+        self.configure_context(builder)?;
+
+        match self.client_auth {
+            Some(ClientAuth::Required) => {
+                builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT)
+            }
+            Some(ClientAuth::Optional) => builder.set_verify(SslVerifyMode::PEER),
+            Some(ClientAuth::Off) => builder.set_verify(SslVerifyMode::NONE),
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    /// Applies the policy's protocol version and cipher suites to a client's connector.
+    pub fn configure_connector(&self, builder: &mut SslConnectorBuilder) -> Result<(), ErrorStack> {
+        self.configure_context(builder)
+    }
+
+    fn configure_context(&self, builder: &mut SslContextBuilder) -> Result<(), ErrorStack> {
+        if let Some(min_version) = self.min_version {
+            builder.set_min_proto_version(Some(min_version.ssl_version()))?;
         }
+
+        let (tls13_suites, tls12_ciphers): (Vec<&str>, Vec<&str>) = self
+            .cipher_suites
+            .iter()
+            .map(String::as_str)
+            .partition(|suite| suite.starts_with("TLS_"));
+        if !tls13_suites.is_empty() {
+            builder.set_ciphersuites(&tls13_suites.join(":"))?;
+        }
+        if !tls12_ciphers.is_empty() {
+            builder.set_cipher_list(&tls12_ciphers.join(":"))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -154,6 +446,9 @@ pub(super) fn build_connector(config: &TlsConfig) -> Result<SslConnector, ErrorS
         builder.set_verify(SslVerifyMode::NONE);
     }
 
+    #[cfg(feature = "tls-policy")]
+    config.policy().configure_connector(&mut builder)?;
+
     Ok(builder.build())
 }
 
@@ -174,6 +469,9 @@ pub(super) fn build_acceptor(config: &TlsConfig) -> Result<SslAcceptor, ErrorSta
         builder.set_verify(SslVerifyMode::NONE);
     }
 
+    #[cfg(feature = "tls-policy")]
+    config.policy().configure_acceptor(&mut builder)?;
+
     Ok(builder.build())
 }
 
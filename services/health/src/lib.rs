@@ -14,28 +14,62 @@
 
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate serde_derive;
+
+mod prober;
+pub mod readiness;
+pub mod store;
 
 use splinter::{
     actix_web::HttpResponse,
     futures::IntoFuture,
-    rest_api::{Method, Resource, RestResourceProvider},
+    rest_api::{ErrorResponse, Method, Resource, RestResourceProvider},
     service::{
         error::{ServiceDestroyError, ServiceError, ServiceStartError, ServiceStopError},
         Service, ServiceMessageContext, ServiceNetworkRegistry,
     },
 };
 use std::any::Any;
+use std::sync::Arc;
+
+pub use prober::{
+    HealthProber, HealthProberShutdownSignaler, DEFAULT_PROBE_INTERVAL, DEFAULT_PROBE_TIMEOUT,
+};
+use readiness::{check_readiness, ReadinessCheck};
+use store::{HealthStore, MemoryHealthStore, NodeHealth};
 
 pub struct HealthService {
     service_id: String,
+    store: Box<dyn HealthStore>,
+    readiness_checks: Vec<Arc<dyn ReadinessCheck>>,
 }
 
 impl HealthService {
     pub fn new(node_id: &str) -> Self {
         Self {
             service_id: format!("health::{}", node_id),
+            store: Box::new(MemoryHealthStore::default()),
+            readiness_checks: vec![],
         }
     }
+
+    /// Replaces the in-memory store that probe results are read from
+    pub fn with_store(mut self, store: Box<dyn HealthStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Adds a check that must pass for `/health/ready` to report the node as ready
+    pub fn with_readiness_check(mut self, check: Box<dyn ReadinessCheck>) -> Self {
+        self.readiness_checks.push(Arc::from(check));
+        self
+    }
+
+    /// Returns the store that probe results are read from, to be shared with a `HealthProber`
+    pub fn store(&self) -> Box<dyn HealthStore> {
+        self.store.clone()
+    }
 }
 
 impl Service for HealthService {
@@ -84,7 +118,11 @@ impl Service for HealthService {
 
 impl RestResourceProvider for HealthService {
     fn resources(&self) -> Vec<Resource> {
-        vec![make_status_resource()]
+        vec![
+            make_status_resource(),
+            make_ready_resource(self.readiness_checks.clone()),
+            make_nodes_resource(self.store.clone()),
+        ]
     }
 }
 
@@ -93,3 +131,99 @@ fn make_status_resource() -> Resource {
         Box::new(HttpResponse::Ok().finish().into_future())
     })
 }
+
+fn make_ready_resource(checks: Vec<Arc<dyn ReadinessCheck>>) -> Resource {
+    Resource::build("/health/ready").add_method(Method::Get, move |_, _| {
+        let response = check_readiness(&checks);
+        Box::new(
+            if response.ready {
+                HttpResponse::Ok().json(response)
+            } else {
+                HttpResponse::ServiceUnavailable().json(response)
+            }
+            .into_future(),
+        )
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct NodesResponse {
+    nodes: Vec<NodeHealthResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProbeResponse {
+    timestamp: u64,
+    round_trip_ms: Option<f64>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeHealthResponse {
+    node_id: String,
+    reachable: bool,
+    last_probe: Option<u64>,
+    average_round_trip_ms: Option<f64>,
+    failures: usize,
+    history: Vec<ProbeResponse>,
+}
+
+impl From<NodeHealth> for NodeHealthResponse {
+    fn from(node: NodeHealth) -> Self {
+        let round_trips = node
+            .history
+            .iter()
+            .filter_map(|record| record.round_trip)
+            .map(|round_trip| round_trip.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        let average_round_trip_ms = if round_trips.is_empty() {
+            None
+        } else {
+            Some(round_trips.iter().sum::<f64>() / round_trips.len() as f64)
+        };
+
+        NodeHealthResponse {
+            reachable: node
+                .history
+                .last()
+                .map(|record| record.is_success())
+                .unwrap_or(false),
+            last_probe: node.history.last().map(|record| record.timestamp),
+            average_round_trip_ms,
+            failures: node
+                .history
+                .iter()
+                .filter(|record| !record.is_success())
+                .count(),
+            history: node
+                .history
+                .into_iter()
+                .map(|record| ProbeResponse {
+                    timestamp: record.timestamp,
+                    round_trip_ms: record
+                        .round_trip
+                        .map(|round_trip| round_trip.as_secs_f64() * 1000.0),
+                    error: record.error,
+                })
+                .collect(),
+            node_id: node.node_id,
+        }
+    }
+}
+
+fn make_nodes_resource(store: Box<dyn HealthStore>) -> Resource {
+    Resource::build("/health/nodes").add_method(Method::Get, move |_, _| {
+        Box::new(
+            match store.list_nodes() {
+                Ok(nodes) => HttpResponse::Ok().json(NodesResponse {
+                    nodes: nodes.into_iter().map(NodeHealthResponse::from).collect(),
+                }),
+                Err(err) => {
+                    error!("Unable to list node health: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            }
+            .into_future(),
+        )
+    })
+}
@@ -0,0 +1,273 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic liveness probes of the other members of this node's circuits.
+//!
+//! Each round, the prober picks one service on every other circuit member and sends it a circuit
+//! ping. A node is considered alive if the ping response shows the request reached it, even if
+//! the service itself is not currently connected.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use splinter::circuit::ping::{CircuitPingError, CircuitPinger};
+use splinter::circuit::routing::{Circuit, RoutingTableReader};
+use splinter::error::InternalError;
+
+use crate::store::{HealthStore, ProbeRecord};
+
+/// How often the members are probed by default
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+/// How long to wait for a probe response by default
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends liveness probes to the other members of this node's circuits and records the results in
+/// a `HealthStore`.
+pub struct HealthProber {
+    join_handle: thread::JoinHandle<()>,
+    shutdown_signaler: HealthProberShutdownSignaler,
+}
+
+impl HealthProber {
+    /// Starts the prober.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the local node
+    /// * `routing_reader` - Used to find the circuit members to probe
+    /// * `pinger` - Used to send the probes
+    /// * `store` - Where the results of the probes are recorded
+    /// * `interval` - How long to wait between rounds of probes
+    /// * `timeout` - How long to wait for each probe's response
+    pub fn start(
+        node_id: String,
+        routing_reader: Box<dyn RoutingTableReader>,
+        pinger: CircuitPinger,
+        store: Box<dyn HealthStore>,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Self, InternalError> {
+        let (sender, receiver) = channel();
+
+        let join_handle = thread::Builder::new()
+            .name("HealthProber".into())
+            .spawn(move || loop {
+                probe_members(&node_id, &*routing_reader, &pinger, &*store, timeout);
+
+                match receiver.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                        debug!("Stopping health prober");
+                        return;
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(HealthProber {
+            join_handle,
+            shutdown_signaler: HealthProberShutdownSignaler { sender },
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the prober
+    pub fn shutdown_signaler(&self) -> HealthProberShutdownSignaler {
+        self.shutdown_signaler.clone()
+    }
+
+    /// Waits for the prober thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Health prober thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals a `HealthProber` to stop
+#[derive(Clone)]
+pub struct HealthProberShutdownSignaler {
+    sender: Sender<()>,
+}
+
+impl HealthProberShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.send(()).is_err() {
+            debug!("Health prober has already stopped");
+        }
+    }
+}
+
+/// Sends one round of probes and records the results
+fn probe_members(
+    node_id: &str,
+    routing_reader: &dyn RoutingTableReader,
+    pinger: &CircuitPinger,
+    store: &dyn HealthStore,
+    timeout: Duration,
+) {
+    let circuits = match routing_reader.list_circuits() {
+        Ok(circuits) => circuits.map(|(_, circuit)| circuit).collect::<Vec<_>>(),
+        Err(err) => {
+            error!("Unable to list circuits to probe: {}", err);
+            return;
+        }
+    };
+
+    let targets = probe_targets(node_id, &circuits);
+
+    // Forget nodes that are no longer members of any of this node's circuits
+    let members: HashSet<&str> = circuits
+        .iter()
+        .flat_map(|circuit| circuit.members().iter().map(String::as_str))
+        .collect();
+    match store.list_nodes() {
+        Ok(nodes) => {
+            for node in nodes {
+                if !members.contains(node.node_id.as_str()) {
+                    if let Err(err) = store.remove_node(&node.node_id) {
+                        error!("Unable to remove health of {}: {}", node.node_id, err);
+                    }
+                }
+            }
+        }
+        Err(err) => error!("Unable to list node health: {}", err),
+    }
+
+    for (target_node, (circuit_id, service_id)) in targets {
+        let record = probe(&target_node, &circuit_id, &service_id, pinger, timeout);
+        if let Some(err) = &record.error {
+            debug!("Probe of node {} failed: {}", target_node, err);
+        }
+        if let Err(err) = store.add_probe(&target_node, record) {
+            error!("Unable to record probe of {}: {}", target_node, err);
+        }
+    }
+}
+
+/// Returns a circuit and a service to ping for every other member of the given circuits, keyed by
+/// node ID. Members that have no services on any circuit cannot be probed.
+fn probe_targets(node_id: &str, circuits: &[Circuit]) -> BTreeMap<String, (String, String)> {
+    let mut targets = BTreeMap::new();
+    for circuit in circuits {
+        for member in circuit.members() {
+            if member == node_id || targets.contains_key(member) {
+                continue;
+            }
+
+            if let Some(service) = circuit
+                .roster()
+                .iter()
+                .find(|service| service.node_id() == member)
+            {
+                targets.insert(
+                    member.to_string(),
+                    (
+                        circuit.circuit_id().to_string(),
+                        service.service_id().to_string(),
+                    ),
+                );
+            }
+        }
+    }
+
+    targets
+}
+
+fn probe(
+    node_id: &str,
+    circuit_id: &str,
+    service_id: &str,
+    pinger: &CircuitPinger,
+    timeout: Duration,
+) -> ProbeRecord {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let (round_trip, error) = match pinger.ping(circuit_id, service_id, timeout) {
+        Ok(result) if result.path.iter().any(|hop| hop == node_id) => {
+            (Some(result.round_trip), None)
+        }
+        Ok(result) => (
+            None,
+            Some(
+                result
+                    .error_message
+                    .unwrap_or_else(|| result.status.to_string()),
+            ),
+        ),
+        Err(CircuitPingError::Timeout(_)) => (
+            None,
+            Some(format!("No response received after {:?}", timeout)),
+        ),
+        Err(err) => {
+            error!("Unable to probe node {}: {}", node_id, err);
+            (None, Some(err.to_string()))
+        }
+    };
+
+    ProbeRecord {
+        timestamp,
+        round_trip,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use splinter::circuit::routing::Service;
+
+    fn service(service_id: &str, node_id: &str) -> Service {
+        Service::new(
+            service_id.to_string(),
+            "test".to_string(),
+            node_id.to_string(),
+            vec![],
+        )
+    }
+
+    /// Verify that one service is chosen for every other circuit member, that the local node is
+    /// skipped, and that members without services are not probed.
+    #[test]
+    fn test_probe_targets() {
+        let circuits = vec![
+            Circuit::new(
+                "abcde-01234".to_string(),
+                vec![service("a000", "node-a"), service("b000", "node-b")],
+                vec!["node-a".to_string(), "node-b".to_string()],
+            ),
+            Circuit::new(
+                "fghij-56789".to_string(),
+                vec![service("a001", "node-a"), service("b001", "node-b")],
+                vec![
+                    "node-a".to_string(),
+                    "node-b".to_string(),
+                    "node-c".to_string(),
+                ],
+            ),
+        ];
+
+        let targets = probe_targets("node-a", &circuits);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(
+            targets.get("node-b"),
+            Some(&("abcde-01234".to_string(), "b000".to_string()))
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that determine whether the node is ready to serve requests.
+
+use std::sync::Arc;
+
+/// A condition that must hold for the node to be considered ready
+pub trait ReadinessCheck: Send + Sync {
+    /// Returns the name the check is reported under
+    fn name(&self) -> &str;
+
+    /// Returns a description of each problem currently preventing the node from being ready; an
+    /// empty list means the check passes
+    fn problems(&self) -> Vec<String>;
+}
+
+/// The outcome of a single readiness check
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct CheckResponse {
+    name: String,
+    ready: bool,
+    problems: Vec<String>,
+}
+
+/// The outcome of all readiness checks
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct ReadyResponse {
+    pub ready: bool,
+    checks: Vec<CheckResponse>,
+}
+
+/// Runs each of the given checks; the node is ready only if every check passes
+pub(crate) fn check_readiness(checks: &[Arc<dyn ReadinessCheck>]) -> ReadyResponse {
+    let checks = checks
+        .iter()
+        .map(|check| {
+            let problems = check.problems();
+            CheckResponse {
+                name: check.name().to_string(),
+                ready: problems.is_empty(),
+                problems,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ReadyResponse {
+        ready: checks.iter().all(|check| check.ready),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticCheck(&'static str, Vec<String>);
+
+    impl ReadinessCheck for StaticCheck {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn problems(&self) -> Vec<String> {
+            self.1.clone()
+        }
+    }
+
+    /// Verify that the node is ready when there are no checks or all checks pass, and not ready
+    /// when any check reports a problem.
+    #[test]
+    fn readiness() {
+        assert!(check_readiness(&[]).ready);
+
+        let passing: Arc<dyn ReadinessCheck> = Arc::new(StaticCheck("passing", vec![]));
+        let failing: Arc<dyn ReadinessCheck> =
+            Arc::new(StaticCheck("failing", vec!["source unavailable".into()]));
+
+        assert!(check_readiness(&[passing.clone()]).ready);
+
+        let response = check_readiness(&[passing, failing]);
+        assert!(!response.ready);
+        assert_eq!(
+            response.checks,
+            vec![
+                CheckResponse {
+                    name: "passing".into(),
+                    ready: true,
+                    problems: vec![],
+                },
+                CheckResponse {
+                    name: "failing".into(),
+                    ready: false,
+                    problems: vec!["source unavailable".into()],
+                },
+            ]
+        );
+    }
+}
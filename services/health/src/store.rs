@@ -0,0 +1,191 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage for the results of liveness probes sent to other nodes.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use splinter::error::InternalError;
+
+/// The number of probe results kept for each node by default
+pub const DEFAULT_HISTORY_SIZE: usize = 20;
+
+/// The result of a single liveness probe
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeRecord {
+    /// When the probe was sent, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// The round-trip time of the probe, if the node responded
+    pub round_trip: Option<Duration>,
+    /// Why the probe failed, if the node did not respond
+    pub error: Option<String>,
+}
+
+impl ProbeRecord {
+    /// Returns true if the node responded to the probe
+    pub fn is_success(&self) -> bool {
+        self.round_trip.is_some()
+    }
+}
+
+/// The recent probe history of a node, oldest first
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeHealth {
+    pub node_id: String,
+    pub history: Vec<ProbeRecord>,
+}
+
+/// Stores the results of liveness probes
+pub trait HealthStore: Send + Sync {
+    /// Adds the result of a probe sent to the given node
+    fn add_probe(&self, node_id: &str, record: ProbeRecord) -> Result<(), InternalError>;
+
+    /// Returns the probe history of every node that has been probed, ordered by node ID
+    fn list_nodes(&self) -> Result<Vec<NodeHealth>, InternalError>;
+
+    /// Removes the history of a node that is no longer probed
+    fn remove_node(&self, node_id: &str) -> Result<(), InternalError>;
+
+    fn clone_box(&self) -> Box<dyn HealthStore>;
+}
+
+impl Clone for Box<dyn HealthStore> {
+    fn clone(&self) -> Box<dyn HealthStore> {
+        self.clone_box()
+    }
+}
+
+/// A `HealthStore` that keeps a bounded history for each node in memory
+#[derive(Clone)]
+pub struct MemoryHealthStore {
+    history_size: usize,
+    nodes: Arc<Mutex<BTreeMap<String, VecDeque<ProbeRecord>>>>,
+}
+
+impl MemoryHealthStore {
+    /// Creates a new `MemoryHealthStore` that keeps up to `history_size` results for each node
+    pub fn new(history_size: usize) -> Self {
+        MemoryHealthStore {
+            history_size,
+            nodes: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl Default for MemoryHealthStore {
+    fn default() -> Self {
+        MemoryHealthStore::new(DEFAULT_HISTORY_SIZE)
+    }
+}
+
+impl HealthStore for MemoryHealthStore {
+    fn add_probe(&self, node_id: &str, record: ProbeRecord) -> Result<(), InternalError> {
+        let mut nodes = self.nodes.lock().map_err(|_| {
+            InternalError::with_message("Health store lock was poisoned".to_string())
+        })?;
+
+        let history = nodes
+            .entry(node_id.to_string())
+            .or_insert_with(VecDeque::new);
+        history.push_back(record);
+        while history.len() > self.history_size {
+            history.pop_front();
+        }
+
+        Ok(())
+    }
+
+    fn list_nodes(&self) -> Result<Vec<NodeHealth>, InternalError> {
+        let nodes = self.nodes.lock().map_err(|_| {
+            InternalError::with_message("Health store lock was poisoned".to_string())
+        })?;
+
+        Ok(nodes
+            .iter()
+            .map(|(node_id, history)| NodeHealth {
+                node_id: node_id.to_string(),
+                history: history.iter().cloned().collect(),
+            })
+            .collect())
+    }
+
+    fn remove_node(&self, node_id: &str) -> Result<(), InternalError> {
+        self.nodes
+            .lock()
+            .map_err(|_| InternalError::with_message("Health store lock was poisoned".to_string()))?
+            .remove(node_id);
+
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn HealthStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn success(timestamp: u64, millis: u64) -> ProbeRecord {
+        ProbeRecord {
+            timestamp,
+            round_trip: Some(Duration::from_millis(millis)),
+            error: None,
+        }
+    }
+
+    /// Verify that the memory store keeps only the most recent results for each node, oldest
+    /// first, and that removed nodes are no longer listed.
+    #[test]
+    fn test_memory_store_history() {
+        let store = MemoryHealthStore::new(2);
+
+        store
+            .add_probe("node-b", success(1, 10))
+            .expect("Unable to add probe");
+        store
+            .add_probe("node-a", success(1, 5))
+            .expect("Unable to add probe");
+        store
+            .add_probe("node-b", success(2, 20))
+            .expect("Unable to add probe");
+        store
+            .add_probe(
+                "node-b",
+                ProbeRecord {
+                    timestamp: 3,
+                    round_trip: None,
+                    error: Some("timed out".into()),
+                },
+            )
+            .expect("Unable to add probe");
+
+        let nodes = store.list_nodes().expect("Unable to list nodes");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].node_id, "node-a");
+        assert_eq!(nodes[0].history, vec![success(1, 5)]);
+        assert_eq!(nodes[1].node_id, "node-b");
+        assert_eq!(nodes[1].history.len(), 2);
+        assert_eq!(nodes[1].history[0], success(2, 20));
+        assert!(!nodes[1].history[1].is_success());
+
+        store.remove_node("node-a").expect("Unable to remove node");
+        let nodes = store.list_nodes().expect("Unable to list nodes");
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].node_id, "node-b");
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for committing batches on an in-process, two-node scabbard harness.
+//!
+//! Each node has its own `ScabbardState`, as it would in a two-node circuit. A batch is prepared
+//! on the coordinator, prepared on the participant, and committed on both once their resulting
+//! state roots agree, following the two-phase commit that scabbard's consensus performs.
+
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, Bencher, BenchmarkId, Criterion};
+use cylinder::{secp256k1::Secp256k1Context, Context, Signer};
+use scabbard::service::ScabbardState;
+use tempdir::TempDir;
+use transact::{
+    families::command::make_command_transaction,
+    protocol::{
+        batch::{BatchBuilder, BatchPair},
+        command::{BytesEntry, Command, SetState},
+    },
+};
+
+const TEMP_DB_SIZE: usize = 1 << 30; // 1024 ** 3
+
+// A node in the harness, which keeps its state's temporary directory alive.
+struct BenchNode {
+    _temp_dir: TempDir,
+    state: ScabbardState,
+}
+
+impl BenchNode {
+    fn new(name: &str) -> Self {
+        let temp_dir = TempDir::new(name).expect("Failed to create temp dir");
+        let state_db_path: PathBuf = temp_dir.path().join("state.lmdb");
+        let receipt_db_path: PathBuf = temp_dir.path().join("receipts.lmdb");
+        let state = ScabbardState::new(
+            &state_db_path,
+            TEMP_DB_SIZE,
+            &receipt_db_path,
+            TEMP_DB_SIZE,
+            vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("Failed to initialize state");
+
+        Self {
+            _temp_dir: temp_dir,
+            state,
+        }
+    }
+}
+
+// Builds a batch of `txn_count` transactions, each setting a unique address.
+fn make_batch(signer: &dyn Signer, batch_num: usize, txn_count: usize) -> BatchPair {
+    let transactions = (0..txn_count)
+        .map(|txn_num| {
+            make_command_transaction(
+                &[Command::SetState(SetState::new(vec![BytesEntry::new(
+                    format!("{:064x}", batch_num * txn_count + txn_num),
+                    vec![0u8; 256],
+                )]))],
+                signer,
+            )
+            .take()
+            .0
+        })
+        .collect();
+
+    BatchBuilder::new()
+        .with_transactions(transactions)
+        .build_pair(signer)
+        .expect("Failed to build batch")
+}
+
+// Prepares and commits batches of `txn_count` transactions on both nodes of the harness.
+fn two_node_commit(b: &mut Bencher, txn_count: usize) {
+    let mut coordinator = BenchNode::new("bench_coordinator");
+    let mut participant = BenchNode::new("bench_participant");
+
+    let signing_context = Secp256k1Context::new();
+    let signer = signing_context.new_signer(signing_context.new_random_private_key());
+
+    let mut batch_num = 0;
+    b.iter(|| {
+        let batch = make_batch(&*signer, batch_num, txn_count);
+        batch_num += 1;
+
+        let expected_hash = coordinator
+            .state
+            .prepare_change(batch.clone())
+            .expect("Failed to prepare change on coordinator");
+        let hash = participant
+            .state
+            .prepare_change(batch)
+            .expect("Failed to prepare change on participant");
+        assert_eq!(expected_hash, hash);
+
+        coordinator
+            .state
+            .commit()
+            .expect("Failed to commit on coordinator");
+        participant
+            .state
+            .commit()
+            .expect("Failed to commit on participant");
+    });
+}
+
+// Benchmark committing batches with one and with ten transactions.
+fn bench_two_node_commit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("two_node_commit");
+    for txn_count in &[1, 10] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(txn_count),
+            txn_count,
+            |b, txn_count| two_node_commit(b, *txn_count),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_two_node_commit);
+criterion_main!(benches);
@@ -55,6 +55,38 @@ impl ScabbardClient {
         service_id: &ServiceId,
         batches: Vec<Batch>,
         wait: Option<Duration>,
+    ) -> Result<(), ScabbardClientError> {
+        self.submit_batches(service_id, batches, None, wait)
+    }
+
+    /// Submit the given `batches` to the scabbard service with the given `service_id`, identified
+    /// by the given `idempotency_key`. If batches were already submitted with the same key, the
+    /// service does not add the new batches; if a `wait` time is specified, the client waits for
+    /// the original batches instead.
+    ///
+    /// Using the same key when retrying a submission ensures that the batches are only executed
+    /// once, even if the original submission was received by the service.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same cases as `submit`.
+    #[cfg(feature = "batch-idempotency")]
+    pub fn submit_with_idempotency_key(
+        &self,
+        service_id: &ServiceId,
+        batches: Vec<Batch>,
+        idempotency_key: &str,
+        wait: Option<Duration>,
+    ) -> Result<(), ScabbardClientError> {
+        self.submit_batches(service_id, batches, Some(idempotency_key), wait)
+    }
+
+    fn submit_batches(
+        &self,
+        service_id: &ServiceId,
+        batches: Vec<Batch>,
+        idempotency_key: Option<&str>,
+        wait: Option<Duration>,
     ) -> Result<(), ScabbardClientError> {
         let url = parse_http_url(&format!(
             "{}/scabbard/{}/{}/batches",
@@ -66,15 +98,15 @@ impl ScabbardClient {
         let body = batches.into_bytes()?;
 
         debug!("Submitting batches via {}", url);
-        // Allowing unused_mut because request must be mutable if experimental feature
-        // client-auth is enabled, if feature is removed unused_mut notation can be removed
-        #[allow(unused_mut)]
         let mut request = Client::new().post(url).body(body);
 
         #[cfg(feature = "client-auth")]
         {
             request = request.header("Authorization", &self.auth);
         }
+        if let Some(idempotency_key) = idempotency_key {
+            request = request.header("Idempotency-Key", idempotency_key);
+        }
         let response = perform_request(request)?;
 
         let batch_link: Link = response.json().map_err(|err| {
@@ -165,6 +197,9 @@ impl ScabbardClient {
     /// Get all entries under the given address `prefix` in state for the scabbard instance with
     /// the given `service_id`.
     ///
+    /// Entries are fetched a page at a time; all pages are read from the same state root, so the
+    /// result is consistent even if batches are committed while the entries are being fetched.
+    ///
     /// # Errors
     ///
     /// Returns an error in any of the following cases:
@@ -177,13 +212,6 @@ impl ScabbardClient {
         service_id: &ServiceId,
         prefix: Option<&str>,
     ) -> Result<Vec<StateEntry>, ScabbardClientError> {
-        let mut url = Url::parse(&format!(
-            "{}/scabbard/{}/{}/state",
-            &self.url,
-            service_id.circuit(),
-            service_id.service_id()
-        ))
-        .map_err(|err| ScabbardClientError::new_with_source("invalid URL", err.into()))?;
         if let Some(prefix) = prefix {
             parse_hex(prefix).map_err(|err| {
                 ScabbardClientError::new_with_source("invalid prefix", err.into())
@@ -193,43 +221,79 @@ impl ScabbardClient {
                     "prefix must be less than 70 characters",
                 ));
             }
-            url.set_query(Some(&format!("prefix={}", prefix)))
         }
-        // Allowing unused_mut because request must be mutable if experimental feature
-        // client-auth is enabled, if feature is removed unused_mut notation can be removed
-        #[allow(unused_mut)]
-        let mut request = Client::new().get(url);
 
-        #[cfg(feature = "client-auth")]
-        {
-            request = request.header("Authorization", &self.auth);
-        }
+        let mut entries = vec![];
+        let mut state_root: Option<String> = None;
+        let mut offset = 0;
 
-        let response = request
-            .header("SplinterProtocolVersion", SCABBARD_PROTOCOL_VERSION)
-            .send()
-            .map_err(|err| ScabbardClientError::new_with_source("request failed", err.into()))?;
+        loop {
+            let mut url = Url::parse(&format!(
+                "{}/scabbard/{}/{}/state",
+                &self.url,
+                service_id.circuit(),
+                service_id.service_id()
+            ))
+            .map_err(|err| ScabbardClientError::new_with_source("invalid URL", err.into()))?;
+            {
+                let mut query = url.query_pairs_mut();
+                if let Some(prefix) = prefix {
+                    query.append_pair("prefix", prefix);
+                }
+                if let Some(state_root) = &state_root {
+                    query.append_pair("state_root", state_root);
+                }
+                query.append_pair("offset", &offset.to_string());
+            }
+            // Allowing unused_mut because request must be mutable if experimental feature
+            // client-auth is enabled, if feature is removed unused_mut notation can be removed
+            #[allow(unused_mut)]
+            let mut request = Client::new().get(url);
+
+            #[cfg(feature = "client-auth")]
+            {
+                request = request.header("Authorization", &self.auth);
+            }
 
-        if response.status().is_success() {
-            response.json().map_err(|err| {
+            let response = request
+                .header("SplinterProtocolVersion", SCABBARD_PROTOCOL_VERSION)
+                .send()
+                .map_err(|err| {
+                    ScabbardClientError::new_with_source("request failed", err.into())
+                })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let msg: ErrorResponse = response.json().map_err(|err| {
+                    ScabbardClientError::new_with_source(
+                        "failed to deserialize error response body",
+                        err.into(),
+                    )
+                })?;
+                return Err(ScabbardClientError::new(&format!(
+                    "failed to get state with prefix: {}: {}",
+                    status, msg
+                )));
+            }
+
+            let page: StatePage = response.json().map_err(|err| {
                 ScabbardClientError::new_with_source(
                     "failed to deserialize response body",
                     err.into(),
                 )
-            })
-        } else {
-            let status = response.status();
-            let msg: ErrorResponse = response.json().map_err(|err| {
-                ScabbardClientError::new_with_source(
-                    "failed to deserialize error response body",
-                    err.into(),
-                )
             })?;
-            Err(ScabbardClientError::new(&format!(
-                "failed to get state with prefix: {}: {}",
-                status, msg
-            )))
+
+            let page_len = page.data.len();
+            entries.extend(page.data);
+            offset = page.paging.offset + page_len;
+            if page_len == 0 || offset >= page.paging.total {
+                break;
+            }
+
+            state_root.get_or_insert(page.state_root);
         }
+
+        Ok(entries)
     }
 
     /// Get the current state root hash of the scabbard instance with the given `service_id`.
@@ -517,6 +581,21 @@ struct InvalidTransaction {
     pub error_data: Vec<u8>,
 }
 
+/// Used for deserializing a page of state entries from the Scabbard REST API.
+#[derive(Debug, Deserialize)]
+struct StatePage {
+    data: Vec<StateEntry>,
+    paging: StatePaging,
+    state_root: String,
+}
+
+/// The paging information of a `StatePage`.
+#[derive(Debug, Deserialize)]
+struct StatePaging {
+    offset: usize,
+    total: usize,
+}
+
 /// Used for deserializing error responses from the Scabbard REST API.
 #[derive(Debug, Serialize, Deserialize)]
 struct ErrorResponse {
@@ -542,6 +621,7 @@ mod tests {
     use actix_web::web;
     use actix_web::HttpResponse;
     use futures::future::IntoFuture;
+    use serde_json::json;
     use splinter::rest_api::{
         Method, ProtocolVersionRangeGuard, Resource, RestApiBuilder, RestApiServerError,
         RestApiShutdownHandle,
@@ -959,7 +1039,24 @@ mod tests {
                         } else {
                             vec![]
                         };
-                        Box::new(HttpResponse::Ok().json(entries).into_future())
+                        Box::new(
+                            HttpResponse::Ok()
+                                .json(json!({
+                                    "data": entries,
+                                    "paging": {
+                                        "current": "",
+                                        "offset": 0,
+                                        "limit": 100,
+                                        "total": entries.len(),
+                                        "first": "",
+                                        "prev": "",
+                                        "next": "",
+                                        "last": "",
+                                    },
+                                    "state_root": MOCK_STATE_ROOT_HASH,
+                                }))
+                                .into_future(),
+                        )
                     }
                 });
             resources.push(state);
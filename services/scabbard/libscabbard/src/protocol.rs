@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub const SCABBARD_PROTOCOL_VERSION: u32 = 1;
+pub const SCABBARD_PROTOCOL_VERSION: u32 = 2;
 
 #[cfg(all(feature = "rest-api", feature = "rest-api-actix"))]
 pub(crate) const SCABBARD_SUBSCRIBE_PROTOCOL_MIN: u32 = 1;
@@ -20,9 +20,27 @@ pub(crate) const SCABBARD_SUBSCRIBE_PROTOCOL_MIN: u32 = 1;
 pub(crate) const SCABBARD_ADD_BATCHES_PROTOCOL_MIN: u32 = 1;
 #[cfg(all(feature = "rest-api", feature = "rest-api-actix"))]
 pub(crate) const SCABBARD_BATCH_STATUSES_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(
+    feature = "batch-history",
+    feature = "rest-api",
+    feature = "rest-api-actix"
+))]
+pub(crate) const SCABBARD_QUERY_BATCH_STATUSES_PROTOCOL_MIN: u32 = 2;
 #[cfg(all(feature = "rest-api", feature = "rest-api-actix"))]
 pub(crate) const SCABBARD_GET_STATE_PROTOCOL_MIN: u32 = 1;
 #[cfg(all(feature = "rest-api", feature = "rest-api-actix"))]
 pub(crate) const SCABBARD_LIST_STATE_PROTOCOL_MIN: u32 = 1;
 #[cfg(all(feature = "rest-api", feature = "rest-api-actix"))]
 pub(crate) const SCABBARD_STATE_ROOT_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(
+    feature = "db-placement",
+    feature = "rest-api",
+    feature = "rest-api-actix"
+))]
+pub(crate) const SCABBARD_STORAGE_PROTOCOL_MIN: u32 = 2;
+#[cfg(all(
+    feature = "state-root-comparison",
+    feature = "rest-api",
+    feature = "rest-api-actix"
+))]
+pub(crate) const SCABBARD_COMPARE_STATE_ROOTS_PROTOCOL_MIN: u32 = 2;
@@ -0,0 +1,52 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pauses the commits of the scabbard services running on a node.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A switch that stops scabbard services from committing batches, such as while the disk their
+/// databases are on is running out of space.
+///
+/// A pause is shared between the services created by a `ScabbardFactory`. While it is paused,
+/// the services keep their queued batches instead of proposing them and vote against the
+/// proposals of other services, so no new batches are written to their databases. Proposals that
+/// have already been accepted by every service are still committed.
+#[derive(Clone, Default)]
+pub struct CommitPause {
+    paused: Arc<AtomicBool>,
+}
+
+impl CommitPause {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether commits are paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Pauses or resumes commits
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) != paused {
+            if paused {
+                warn!("Scabbard commits paused");
+            } else {
+                info!("Scabbard commits resumed");
+            }
+        }
+    }
+}
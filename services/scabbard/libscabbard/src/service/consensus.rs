@@ -25,8 +25,12 @@ use splinter::consensus::{
     ConsensusEngine, ConsensusMessage, ConsensusNetworkSender, PeerId, Proposal, ProposalId,
     ProposalManager, ProposalUpdate, StartupState,
 };
+#[cfg(feature = "observers")]
+use transact::protocol::batch::BatchPair;
 use transact::protos::IntoBytes;
 
+#[cfg(feature = "observers")]
+use crate::protos::scabbard::CommittedBatch;
 use crate::protos::scabbard::{ProposedBatch, ScabbardMessage, ScabbardMessage_Type};
 
 use super::error::{ScabbardConsensusManagerError, ScabbardError};
@@ -166,6 +170,17 @@ impl ProposalManager for ScabbardProposalManager {
             .lock()
             .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?;
 
+        // Leave the queued batches for when commits are resumed
+        #[cfg(feature = "commit-pause")]
+        {
+            if shared.commits_paused() {
+                debug!("Commits are paused; not proposing a batch");
+                self.proposal_update_sender
+                    .send(ProposalUpdate::ProposalCreated(None))?;
+                return Ok(());
+            }
+        }
+
         if let Some(batch) = shared.pop_batch_from_queue() {
             let expected_hash = self
                 .state
@@ -226,13 +241,27 @@ impl ProposalManager for ScabbardProposalManager {
     }
 
     fn check_proposal(&self, id: &ProposalId) -> Result<(), ProposalManagerError> {
-        let batch = self
+        let shared = self
             .shared
             .lock()
-            .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?
+            .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?;
+
+        // Vote against the proposal, so that no service commits a batch this service can't write
+        #[cfg(feature = "commit-pause")]
+        {
+            if shared.commits_paused() {
+                warn!("Commits are paused; rejecting proposal {}", id);
+                self.proposal_update_sender
+                    .send(ProposalUpdate::ProposalInvalid(id.clone()))?;
+                return Ok(());
+            }
+        }
+
+        let batch = shared
             .get_proposed_batch(id)
             .ok_or_else(|| ProposalManagerError::UnknownProposal(id.clone()))?
             .clone();
+        drop(shared);
 
         let hash = self
             .state
@@ -266,7 +295,10 @@ impl ProposalManager for ScabbardProposalManager {
             .lock()
             .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?;
 
-        shared
+        // Allowing unused_variables because the batch is only sent on to observers if the
+        // observers feature is enabled
+        #[allow(unused_variables)]
+        let batch = shared
             .remove_proposed_batch(id)
             .ok_or_else(|| ProposalManagerError::UnknownProposal(id.clone()))?;
 
@@ -276,6 +308,9 @@ impl ProposalManager for ScabbardProposalManager {
             .commit()
             .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?;
 
+        #[cfg(feature = "observers")]
+        self.send_committed_batch(&shared, batch, id);
+
         self.proposal_update_sender
             .send(ProposalUpdate::ProposalAccepted(id.clone()))?;
 
@@ -306,6 +341,71 @@ impl ProposalManager for ScabbardProposalManager {
     }
 }
 
+#[cfg(feature = "observers")]
+impl ScabbardProposalManager {
+    /// Send a committed batch to each of the observer services, along with the state root it
+    /// results in. Failing to reach an observer does not affect the commit, so errors are only
+    /// logged.
+    fn send_committed_batch(&self, shared: &ScabbardShared, batch: BatchPair, id: &ProposalId) {
+        if shared.observer_services().is_empty() {
+            return;
+        }
+
+        let msg_bytes = match Self::committed_batch_message(&self.service_id, batch, id) {
+            Ok(msg_bytes) => msg_bytes,
+            Err(err) => {
+                error!(
+                    "Failed to create committed batch message for observers: {}",
+                    err
+                );
+                return;
+            }
+        };
+
+        let sender = match shared.network_sender() {
+            Some(sender) => sender,
+            None => {
+                warn!("Unable to send committed batch to observers: service not connected");
+                return;
+            }
+        };
+
+        for service in shared.observer_services() {
+            if let Err(err) = sender.send(service, msg_bytes.as_slice()) {
+                warn!(
+                    "Failed to send committed batch to observer {}: {}",
+                    service, err
+                );
+            }
+        }
+    }
+
+    fn committed_batch_message(
+        service_id: &str,
+        batch: BatchPair,
+        id: &ProposalId,
+    ) -> Result<Vec<u8>, ProposalManagerError> {
+        let mut committed_batch = CommittedBatch::new();
+        committed_batch.set_batch(
+            batch
+                .into_bytes()
+                .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
+        );
+        committed_batch.set_state_root(
+            String::from_utf8(id.clone().into())
+                .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
+        );
+        committed_batch.set_service_id(service_id.into());
+
+        let mut msg = ScabbardMessage::new();
+        msg.set_message_type(ScabbardMessage_Type::COMMITTED_BATCH);
+        msg.set_committed_batch(committed_batch);
+
+        msg.write_to_bytes()
+            .map_err(|err| ProposalManagerError::Internal(Box::new(err)))
+    }
+}
+
 pub struct ScabbardConsensusNetworkSender {
     service_id: String,
     shared: Arc<Mutex<ScabbardShared>>,
@@ -475,6 +575,180 @@ mod tests {
         assert_eq!(consensus_message.origin_id, "0".as_bytes().into());
     }
 
+    /// Tests that observers are removed from the peers that take part in consensus, and that an
+    /// observer has no consensus peers but accepts committed batches from the other services.
+    #[cfg(feature = "observers")]
+    #[test]
+    fn observers_excluded_from_consensus() {
+        let service_sender = MockServiceNetworkSender::new();
+        let peer_services: HashSet<String> =
+            vec!["1".to_string(), "2".to_string()].into_iter().collect();
+        let observers: HashSet<String> = vec!["2".to_string()].into_iter().collect();
+
+        let shared = Arc::new(Mutex::new(ScabbardShared::new(
+            VecDeque::new(),
+            Some(Box::new(service_sender.clone())),
+            peer_services,
+            Secp256k1Context::new().new_verifier(),
+        )));
+        shared
+            .lock()
+            .expect("shared lock poisoned")
+            .set_observers("0", &observers);
+        let consensus_sender = ScabbardConsensusNetworkSender::new("0".into(), shared.clone());
+
+        consensus_sender.broadcast(vec![1]).expect("failed to send");
+        let sent = service_sender.sent.lock().expect("sent lock poisoned");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "1");
+
+        assert!(consensus_sender
+            .send_to(&"2".as_bytes().into(), vec![0])
+            .is_err());
+
+        {
+            let shared = shared.lock().expect("shared lock poisoned");
+            assert!(!shared.is_observer());
+            assert_eq!(shared.observer_services(), &observers);
+        }
+
+        // The observer itself has no consensus peers
+        let mut observer_shared = ScabbardShared::new(
+            VecDeque::new(),
+            None,
+            vec!["0".to_string(), "1".to_string()].into_iter().collect(),
+            Secp256k1Context::new().new_verifier(),
+        );
+        observer_shared.set_observers("2", &observers);
+        assert!(observer_shared.is_observer());
+        assert!(observer_shared.peer_services().is_empty());
+        assert!(observer_shared.is_observing("0"));
+        assert!(observer_shared.is_observing("1"));
+        assert!(!observer_shared.is_observing("3"));
+    }
+
+    /// Tests that a service whose commits are paused keeps its queued batches instead of
+    /// proposing them and votes against the proposals of other services, and that it proposes
+    /// the queued batches again once commits are resumed.
+    #[cfg(feature = "commit-pause")]
+    #[test]
+    fn commits_paused() {
+        use tempdir::TempDir;
+
+        use super::super::CommitPause;
+
+        let temp_dir = TempDir::new("commits_paused").expect("Failed to create temp dir");
+        let state = Arc::new(Mutex::new(
+            ScabbardState::new(
+                &temp_dir.path().join("state.lmdb"),
+                1 << 30,
+                &temp_dir.path().join("receipts.lmdb"),
+                1 << 30,
+                vec![],
+                #[cfg(feature = "db-auto-grow")]
+                None,
+            )
+            .expect("Failed to initialize state"),
+        ));
+
+        let batch = mock_batch();
+
+        let service_sender = MockServiceNetworkSender::new();
+        let shared = Arc::new(Mutex::new(ScabbardShared::new(
+            vec![batch.clone()].into_iter().collect(),
+            Some(Box::new(service_sender.clone())),
+            vec!["1".to_string()].into_iter().collect(),
+            Secp256k1Context::new().new_verifier(),
+        )));
+        let pause = CommitPause::new();
+        shared
+            .lock()
+            .expect("shared lock poisoned")
+            .set_commit_pause(pause.clone());
+
+        let (update_sender, update_receiver) = channel();
+        let proposal_manager =
+            ScabbardProposalManager::new("0".into(), update_sender, shared.clone(), state);
+
+        pause.set_paused(true);
+
+        proposal_manager
+            .create_proposal(None, vec![])
+            .expect("failed to create proposal");
+        match update_receiver.try_recv() {
+            Ok(ProposalUpdate::ProposalCreated(None)) => (),
+            res => panic!("Expected no proposal, got {:?}", res),
+        }
+        assert!(service_sender
+            .sent
+            .lock()
+            .expect("sent lock poisoned")
+            .is_empty());
+
+        let id: ProposalId = b"proposal".to_vec().into();
+        shared
+            .lock()
+            .expect("shared lock poisoned")
+            .add_proposed_batch(id.clone(), batch);
+        proposal_manager
+            .check_proposal(&id)
+            .expect("failed to check proposal");
+        match update_receiver.try_recv() {
+            Ok(ProposalUpdate::ProposalInvalid(invalid_id)) => assert_eq!(invalid_id, id),
+            res => panic!("Expected invalid proposal, got {:?}", res),
+        }
+        proposal_manager
+            .reject_proposal(&id)
+            .expect("failed to reject proposal");
+
+        pause.set_paused(false);
+
+        proposal_manager
+            .create_proposal(None, vec![])
+            .expect("failed to create proposal");
+        match update_receiver.try_recv() {
+            Ok(ProposalUpdate::ProposalCreated(Some(_))) => (),
+            res => panic!("Expected a proposal, got {:?}", res),
+        }
+        assert_eq!(
+            service_sender
+                .sent
+                .lock()
+                .expect("sent lock poisoned")
+                .len(),
+            1
+        );
+    }
+
+    #[cfg(feature = "commit-pause")]
+    fn mock_batch() -> transact::protocol::batch::BatchPair {
+        use cylinder::Context;
+        use transact::{
+            families::command::make_command_transaction,
+            protocol::{
+                batch::BatchBuilder,
+                command::{BytesEntry, Command, SetState},
+            },
+        };
+
+        let signing_context = Secp256k1Context::new();
+        let signer = signing_context.new_signer(signing_context.new_random_private_key());
+        BatchBuilder::new()
+            .with_transactions(vec![
+                make_command_transaction(
+                    &[Command::SetState(SetState::new(vec![BytesEntry::new(
+                        "abcdef".into(),
+                        b"value".to_vec(),
+                    )]))],
+                    &*signer,
+                )
+                .take()
+                .0,
+            ])
+            .build_pair(&*signer)
+            .expect("Failed to build batch")
+    }
+
     #[derive(Clone, Debug)]
     pub struct MockServiceNetworkSender {
         pub sent: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
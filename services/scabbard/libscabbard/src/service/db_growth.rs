@@ -0,0 +1,364 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sizing and monitoring of the LMDB databases of scabbard services.
+//!
+//! An LMDB database can never hold more data than the size of its memory map, and every write
+//! fails once the map is full. Scabbard grows the map of a database when the database is opened
+//! and is close to full, and warns when a database approaches the maximum size it may grow to.
+//!
+//! The usage of a database is read from the meta pages at the start of its data file, since the
+//! LMDB environments are owned by the transact and sawtooth stores.
+
+use std::cmp::max;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::error::ScabbardStateError;
+
+const DEFAULT_WARNING_THRESHOLDS: [u8; 2] = [80, 90];
+/// A database is grown when it is opened with at least this much of its map in use, in percent
+const GROWTH_THRESHOLD: u64 = 80;
+
+const LMDB_MAGIC: u32 = 0xBEEF_C0DE;
+// Offsets of the meta page fields used here, for 64-bit builds of LMDB
+const META_MAGIC_OFFSET: usize = 16;
+const META_MAP_SIZE_OFFSET: usize = 32;
+const META_PAGE_SIZE_OFFSET: usize = 40;
+const META_LAST_PAGE_OFFSET: usize = 136;
+const META_TXN_ID_OFFSET: usize = 144;
+const META_LEN: usize = 152;
+
+/// Controls how the LMDB databases of scabbard services grow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbGrowthPolicy {
+    increment: usize,
+    max_size: usize,
+    warning_thresholds: Vec<u8>,
+}
+
+impl DbGrowthPolicy {
+    /// Grow a database's map by `increment` bytes at a time, up to `max_size` bytes. With an
+    /// `increment` of zero the databases are only monitored.
+    pub fn new(increment: usize, max_size: usize) -> Self {
+        Self {
+            increment,
+            max_size,
+            warning_thresholds: DEFAULT_WARNING_THRESHOLDS.to_vec(),
+        }
+    }
+
+    /// Warn when a database's usage reaches each of the given percentages of its maximum size.
+    pub fn with_warning_thresholds(mut self, warning_thresholds: Vec<u8>) -> Self {
+        self.warning_thresholds = warning_thresholds;
+        self
+    }
+
+    /// Returns the map size a database should be opened with, given the bytes it uses and the
+    /// map size it would be opened with otherwise.
+    fn grown_size(&self, used: u64, mut map_size: usize) -> usize {
+        if self.increment == 0 {
+            return map_size;
+        }
+
+        while used * 100 >= map_size as u64 * GROWTH_THRESHOLD && map_size < self.max_size {
+            map_size = (map_size + self.increment).min(self.max_size);
+        }
+        map_size
+    }
+}
+
+/// The size and usage of an LMDB database, in bytes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DbUsage {
+    /// The size of the database's memory map
+    pub map_size: u64,
+    /// The largest size the database's memory map may grow to
+    pub max_size: u64,
+    /// The bytes used by the database as of its last committed write
+    pub used: u64,
+}
+
+/// Watches the usage of one of a scabbard service's LMDB databases.
+pub(super) struct DbMonitor {
+    name: &'static str,
+    path: PathBuf,
+    map_size: usize,
+    max_size: usize,
+    warning_thresholds: Vec<u8>,
+    warned_threshold: Option<u8>,
+    growth_pending: bool,
+}
+
+impl DbMonitor {
+    /// Determine the map size for the database at `path`, which is opened with `size` bytes
+    /// unless its map was larger when it was last written or it needs to grow.
+    pub(super) fn open(
+        name: &'static str,
+        path: &Path,
+        size: usize,
+        policy: Option<&DbGrowthPolicy>,
+    ) -> Self {
+        let meta = match read_meta(path) {
+            Ok(meta) => Some(meta),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                warn!(
+                    "Unable to read usage of {} database {}: {}",
+                    name,
+                    path.display(),
+                    err
+                );
+                None
+            }
+        };
+
+        let mut map_size = meta
+            .map(|meta| max(size, meta.map_size as usize))
+            .unwrap_or(size);
+        if let (Some(policy), Some(meta)) = (policy, meta) {
+            let grown_size = policy.grown_size(meta.used, map_size);
+            if grown_size > map_size {
+                info!(
+                    "Growing {} database {} from {} to {} bytes",
+                    name,
+                    path.display(),
+                    map_size,
+                    grown_size
+                );
+                map_size = grown_size;
+            }
+        }
+
+        Self {
+            name,
+            path: path.to_path_buf(),
+            map_size,
+            max_size: policy
+                .map(|policy| max(policy.max_size, map_size))
+                .unwrap_or(map_size),
+            warning_thresholds: policy
+                .map(|policy| policy.warning_thresholds.clone())
+                .unwrap_or_else(|| DEFAULT_WARNING_THRESHOLDS.to_vec()),
+            warned_threshold: None,
+            growth_pending: false,
+        }
+    }
+
+    /// The map size the database is opened with
+    pub(super) fn map_size(&self) -> usize {
+        self.map_size
+    }
+
+    pub(super) fn usage(&self) -> Result<DbUsage, ScabbardStateError> {
+        let meta = read_meta(&self.path).map_err(|err| {
+            ScabbardStateError(format!(
+                "unable to read usage of {} database {}: {}",
+                self.name,
+                self.path.display(),
+                err
+            ))
+        })?;
+        Ok(DbUsage {
+            map_size: self.map_size as u64,
+            max_size: self.max_size as u64,
+            used: meta.used,
+        })
+    }
+
+    /// Check the database's usage after a write, warning when it crosses one of the warning
+    /// thresholds or needs to grow.
+    pub(super) fn check(&mut self) {
+        let usage = match self.usage() {
+            Ok(usage) => usage,
+            Err(err) => {
+                warn!("{}", err);
+                return;
+            }
+        };
+
+        let capacity = usage.used * 100 / usage.max_size.max(1);
+        let crossed = self
+            .warning_thresholds
+            .iter()
+            .filter(|threshold| capacity >= u64::from(**threshold))
+            .max()
+            .copied();
+        if crossed > self.warned_threshold {
+            warn!(
+                "The {} database {} is using {}% of its maximum size ({} of {} bytes)",
+                self.name,
+                self.path.display(),
+                capacity,
+                usage.used,
+                usage.max_size
+            );
+        }
+        self.warned_threshold = crossed;
+
+        if !self.growth_pending
+            && usage.map_size < usage.max_size
+            && usage.used * 100 >= usage.map_size * GROWTH_THRESHOLD
+        {
+            warn!(
+                "The {} database {} is using {} of {} bytes; it will be grown when the service \
+                 is restarted",
+                self.name,
+                self.path.display(),
+                usage.used,
+                usage.map_size
+            );
+            self.growth_pending = true;
+        }
+    }
+
+    /// Adds the database's usage to an error from writing to it, since a full map only surfaces
+    /// as a generic LMDB error.
+    pub(super) fn write_error(&self, err: &dyn std::fmt::Display) -> ScabbardStateError {
+        match self.usage() {
+            Ok(usage) => ScabbardStateError(format!(
+                "{}; the {} database {} is using {} of {} bytes",
+                err,
+                self.name,
+                self.path.display(),
+                usage.used,
+                usage.map_size
+            )),
+            Err(_) => ScabbardStateError(err.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LmdbMeta {
+    map_size: u64,
+    used: u64,
+    txn_id: u64,
+}
+
+/// Read the current meta page of the LMDB data file at `path`; LMDB alternates between the two
+/// meta pages at the start of the file, so the one with the latest transaction is current.
+fn read_meta(path: &Path) -> io::Result<LmdbMeta> {
+    let mut file = File::open(path)?;
+    let mut page = [0u8; META_LEN];
+
+    file.read_exact(&mut page)?;
+    let page_size = u64::from(read_u32(&page, META_PAGE_SIZE_OFFSET));
+    let first = parse_meta(&page, page_size)?;
+
+    file.seek(SeekFrom::Start(page_size))?;
+    file.read_exact(&mut page)?;
+    let second = parse_meta(&page, page_size)?;
+
+    Ok(if second.txn_id > first.txn_id {
+        second
+    } else {
+        first
+    })
+}
+
+fn parse_meta(page: &[u8], page_size: u64) -> io::Result<LmdbMeta> {
+    if read_u32(page, META_MAGIC_OFFSET) != LMDB_MAGIC || page_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an LMDB data file",
+        ));
+    }
+
+    Ok(LmdbMeta {
+        map_size: read_u64(page, META_MAP_SIZE_OFFSET),
+        used: (read_u64(page, META_LAST_PAGE_OFFSET) + 1) * page_size,
+        txn_id: read_u64(page, META_TXN_ID_OFFSET),
+    })
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_ne_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_ne_bytes(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    const PAGE_SIZE: usize = 4096;
+
+    /// Verify that a database is grown by increments until it is below the growth threshold,
+    /// without exceeding the maximum size.
+    #[test]
+    fn grown_size() {
+        let policy = DbGrowthPolicy::new(100, 450);
+
+        assert_eq!(policy.grown_size(50, 100), 100);
+        assert_eq!(policy.grown_size(80, 100), 200);
+        assert_eq!(policy.grown_size(250, 100), 400);
+        assert_eq!(policy.grown_size(1000, 100), 450);
+        assert_eq!(DbGrowthPolicy::new(0, 450).grown_size(80, 100), 100);
+    }
+
+    /// Verify that the map size and usage are read from the current meta page, and that a
+    /// database is opened with its recorded map size when that is larger than the given size.
+    #[test]
+    fn monitor_usage() {
+        let temp_dir = TempDir::new("monitor_usage").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("state.lmdb");
+
+        let mut file = vec![0u8; PAGE_SIZE * 2];
+        write_meta(&mut file[..PAGE_SIZE], 8 * PAGE_SIZE as u64, 1, 4);
+        write_meta(&mut file[PAGE_SIZE..], 10 * PAGE_SIZE as u64, 7, 5);
+        fs::write(&path, &file).expect("Failed to write data file");
+
+        let monitor = DbMonitor::open("state", &path, 4 * PAGE_SIZE, None);
+        assert_eq!(monitor.map_size(), 10 * PAGE_SIZE);
+        assert_eq!(
+            monitor.usage().expect("Failed to get usage"),
+            DbUsage {
+                map_size: 10 * PAGE_SIZE as u64,
+                max_size: 10 * PAGE_SIZE as u64,
+                used: 8 * PAGE_SIZE as u64,
+            }
+        );
+
+        let policy = DbGrowthPolicy::new(10 * PAGE_SIZE, 100 * PAGE_SIZE);
+        let monitor = DbMonitor::open("state", &path, 4 * PAGE_SIZE, Some(&policy));
+        assert_eq!(monitor.map_size(), 20 * PAGE_SIZE);
+
+        let missing = DbMonitor::open("state", &temp_dir.path().join("none.lmdb"), 42, None);
+        assert_eq!(missing.map_size(), 42);
+        assert!(missing.usage().is_err());
+    }
+
+    fn write_meta(page: &mut [u8], map_size: u64, last_page: u64, txn_id: u64) {
+        page[META_MAGIC_OFFSET..META_MAGIC_OFFSET + 4].copy_from_slice(&LMDB_MAGIC.to_ne_bytes());
+        page[META_MAP_SIZE_OFFSET..META_MAP_SIZE_OFFSET + 8]
+            .copy_from_slice(&map_size.to_ne_bytes());
+        page[META_PAGE_SIZE_OFFSET..META_PAGE_SIZE_OFFSET + 4]
+            .copy_from_slice(&(PAGE_SIZE as u32).to_ne_bytes());
+        page[META_LAST_PAGE_OFFSET..META_LAST_PAGE_OFFSET + 8]
+            .copy_from_slice(&last_page.to_ne_bytes());
+        page[META_TXN_ID_OFFSET..META_TXN_ID_OFFSET + 8].copy_from_slice(&txn_id.to_ne_bytes());
+    }
+}
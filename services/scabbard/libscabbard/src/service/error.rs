@@ -30,7 +30,11 @@ pub enum ScabbardError {
     LockPoisoned,
     MessageTypeUnset,
     NotConnected,
+    #[cfg(feature = "observers")]
+    ReadOnly,
     StateInteractionFailed(ScabbardStateError),
+    #[cfg(feature = "state-root-comparison")]
+    StateRootComparisonFailed(Box<dyn Error + Send>),
 }
 
 impl Error for ScabbardError {
@@ -42,7 +46,11 @@ impl Error for ScabbardError {
             ScabbardError::LockPoisoned => None,
             ScabbardError::MessageTypeUnset => None,
             ScabbardError::NotConnected => None,
+            #[cfg(feature = "observers")]
+            ScabbardError::ReadOnly => None,
             ScabbardError::StateInteractionFailed(err) => Some(err),
+            #[cfg(feature = "state-root-comparison")]
+            ScabbardError::StateRootComparisonFailed(err) => Some(&**err),
         }
     }
 }
@@ -62,9 +70,17 @@ impl std::fmt::Display for ScabbardError {
             ScabbardError::NotConnected => {
                 write!(f, "attempted to send message, but service isn't connected")
             }
+            #[cfg(feature = "observers")]
+            ScabbardError::ReadOnly => {
+                write!(f, "service is an observer and does not accept batches")
+            }
             ScabbardError::StateInteractionFailed(err) => {
                 write!(f, "interaction with scabbard state failed: {}", err)
             }
+            #[cfg(feature = "state-root-comparison")]
+            ScabbardError::StateRootComparisonFailed(err) => {
+                write!(f, "failed to compare state roots: {}", err)
+            }
         }
     }
 }
@@ -13,8 +13,13 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+#[cfg(feature = "db-placement")]
+use std::fs;
 use std::iter::FromIterator;
+#[cfg(not(feature = "db-placement"))]
 use std::path::Path;
+#[cfg(feature = "db-placement")]
+use std::path::PathBuf;
 use std::time::Duration;
 
 use cylinder::VerifierFactory;
@@ -25,6 +30,22 @@ use splinter::service::{FactoryCreateError, Service, ServiceFactory};
 #[cfg(feature = "service-arg-validation")]
 use crate::hex::parse_hex;
 
+#[cfg(feature = "db-placement")]
+use super::compute_db_paths;
+#[cfg(feature = "batch-history")]
+use super::BatchHistoryRetention;
+#[cfg(feature = "batch-queue-monitor")]
+use super::BatchQueueMonitor;
+#[cfg(feature = "commit-pause")]
+use super::CommitPause;
+#[cfg(feature = "db-auto-grow")]
+use super::DbGrowthPolicy;
+#[cfg(feature = "service-lookup")]
+use super::ScabbardServiceLookup;
+#[cfg(feature = "state-root-comparison")]
+use super::StateRootMonitor;
+#[cfg(feature = "state-subscriber-factory")]
+use super::StateSubscriber;
 use super::{Scabbard, SERVICE_TYPE};
 
 const DEFAULT_STATE_DB_DIR: &str = "/var/lib/splinter";
@@ -38,7 +59,33 @@ pub struct ScabbardFactory {
     state_db_size: usize,
     receipt_db_dir: String,
     receipt_db_size: usize,
+    #[cfg(feature = "db-placement")]
+    circuit_db_dirs: bool,
+    /// Database directories for circuits, keyed by circuit ID, and for services, keyed by circuit
+    /// and service ID
+    #[cfg(feature = "db-placement")]
+    db_dir_overrides: HashMap<(String, Option<String>), String>,
+    #[cfg(feature = "db-auto-grow")]
+    db_growth_policy: Option<DbGrowthPolicy>,
     signature_verifier_factory: Box<dyn VerifierFactory>,
+    #[cfg(feature = "batch-history")]
+    batch_history_retention: Option<BatchHistoryRetention>,
+    #[cfg(feature = "batch-queue-monitor")]
+    batch_queue_monitor: Option<BatchQueueMonitor>,
+    #[cfg(feature = "commit-pause")]
+    commit_pause: Option<CommitPause>,
+    #[cfg(feature = "state-root-comparison")]
+    state_root_monitor: Option<StateRootMonitor>,
+    #[cfg(feature = "state-subscriber-factory")]
+    state_subscriber_factories: Vec<Box<dyn StateSubscriberFactory>>,
+    #[cfg(feature = "service-lookup")]
+    service_lookup: Option<ScabbardServiceLookup>,
+}
+
+/// Creates a state subscriber for each scabbard service as the service is created
+#[cfg(feature = "state-subscriber-factory")]
+pub trait StateSubscriberFactory: Send {
+    fn create_subscriber(&self, circuit_id: &str, service_id: &str) -> Box<dyn StateSubscriber>;
 }
 
 impl ScabbardFactory {
@@ -55,9 +102,175 @@ impl ScabbardFactory {
             state_db_size: state_db_size.unwrap_or(DEFAULT_STATE_DB_SIZE),
             receipt_db_dir: receipt_db_dir.unwrap_or_else(|| DEFAULT_RECEIPT_DB_DIR.into()),
             receipt_db_size: receipt_db_size.unwrap_or(DEFAULT_RECEIPT_DB_SIZE),
+            #[cfg(feature = "db-placement")]
+            circuit_db_dirs: false,
+            #[cfg(feature = "db-placement")]
+            db_dir_overrides: HashMap::new(),
+            #[cfg(feature = "db-auto-grow")]
+            db_growth_policy: None,
             signature_verifier_factory,
+            #[cfg(feature = "batch-history")]
+            batch_history_retention: None,
+            #[cfg(feature = "batch-queue-monitor")]
+            batch_queue_monitor: None,
+            #[cfg(feature = "commit-pause")]
+            commit_pause: None,
+            #[cfg(feature = "state-root-comparison")]
+            state_root_monitor: None,
+            #[cfg(feature = "state-subscriber-factory")]
+            state_subscriber_factories: vec![],
+            #[cfg(feature = "service-lookup")]
+            service_lookup: None,
         }
     }
+
+    /// Returns the directory where the state databases of the services are stored
+    pub fn state_db_dir(&self) -> &str {
+        &self.state_db_dir
+    }
+
+    /// Returns the directory where the receipt databases of the services are stored
+    pub fn receipt_db_dir(&self) -> &str {
+        &self.receipt_db_dir
+    }
+
+    /// Store the databases of each circuit's services in a subdirectory, named after the circuit,
+    /// of the state and receipt database directories
+    #[cfg(feature = "db-placement")]
+    pub fn with_circuit_db_dirs(mut self, circuit_db_dirs: bool) -> Self {
+        self.circuit_db_dirs = circuit_db_dirs;
+        self
+    }
+
+    /// Store both databases of the given circuit's services in the given directory, such as a
+    /// separate volume for a busy circuit
+    #[cfg(feature = "db-placement")]
+    pub fn with_circuit_db_dir(mut self, circuit_id: String, db_dir: String) -> Self {
+        self.db_dir_overrides.insert((circuit_id, None), db_dir);
+        self
+    }
+
+    /// Store both databases of the given service in the given directory; this takes precedence
+    /// over a directory set for the service's circuit
+    #[cfg(feature = "db-placement")]
+    pub fn with_service_db_dir(
+        mut self,
+        circuit_id: String,
+        service_id: String,
+        db_dir: String,
+    ) -> Self {
+        self.db_dir_overrides
+            .insert((circuit_id, Some(service_id)), db_dir);
+        self
+    }
+
+    /// Returns the directories for the state and receipt databases of the given service, creating
+    /// them if they do not exist.
+    ///
+    /// Databases that already exist in the state and receipt database directories are kept there,
+    /// so that services created before their directories were configured keep their state.
+    #[cfg(feature = "db-placement")]
+    fn db_dirs(
+        &self,
+        service_id: &str,
+        circuit_id: &str,
+    ) -> Result<(PathBuf, PathBuf), FactoryCreateError> {
+        let state_db_dir = PathBuf::from(&self.state_db_dir);
+        let receipt_db_dir = PathBuf::from(&self.receipt_db_dir);
+
+        let db_dirs = match self
+            .db_dir_overrides
+            .get(&(circuit_id.to_string(), Some(service_id.to_string())))
+            .or_else(|| self.db_dir_overrides.get(&(circuit_id.to_string(), None)))
+        {
+            Some(db_dir) => (PathBuf::from(db_dir), PathBuf::from(db_dir)),
+            None if self.circuit_db_dirs => {
+                if circuit_id.is_empty()
+                    || circuit_id == "."
+                    || circuit_id == ".."
+                    || circuit_id.chars().any(std::path::is_separator)
+                {
+                    return Err(FactoryCreateError::InvalidArguments(format!(
+                        "circuit ID {} cannot be used as a database directory",
+                        circuit_id
+                    )));
+                }
+                (
+                    state_db_dir.join(circuit_id),
+                    receipt_db_dir.join(circuit_id),
+                )
+            }
+            None => return Ok((state_db_dir, receipt_db_dir)),
+        };
+
+        let (existing_state_db, _) =
+            compute_db_paths(service_id, circuit_id, &state_db_dir, &receipt_db_dir)
+                .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+        if existing_state_db.exists() {
+            return Ok((state_db_dir, receipt_db_dir));
+        }
+
+        for dir in &[&db_dirs.0, &db_dirs.1] {
+            fs::create_dir_all(dir)
+                .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+        }
+
+        Ok(db_dirs)
+    }
+
+    /// Grow the databases of the services created by this factory according to the given policy
+    #[cfg(feature = "db-auto-grow")]
+    pub fn with_db_growth_policy(mut self, policy: DbGrowthPolicy) -> Self {
+        self.db_growth_policy = Some(policy);
+        self
+    }
+
+    /// Keep the batches selected by the given retention in the batch history of the services
+    /// created by this factory
+    #[cfg(feature = "batch-history")]
+    pub fn with_batch_history_retention(mut self, retention: BatchHistoryRetention) -> Self {
+        self.batch_history_retention = Some(retention);
+        self
+    }
+
+    /// Report the batch queue depth of the services created by this factory to the given monitor
+    #[cfg(feature = "batch-queue-monitor")]
+    pub fn with_batch_queue_monitor(mut self, monitor: BatchQueueMonitor) -> Self {
+        self.batch_queue_monitor = Some(monitor);
+        self
+    }
+
+    /// Pause the commits of the services created by this factory while the given pause is paused
+    #[cfg(feature = "commit-pause")]
+    pub fn with_commit_pause(mut self, pause: CommitPause) -> Self {
+        self.commit_pause = Some(pause);
+        self
+    }
+
+    /// Record the state root comparisons of the services created by this factory in the given
+    /// monitor
+    #[cfg(feature = "state-root-comparison")]
+    pub fn with_state_root_monitor(mut self, monitor: StateRootMonitor) -> Self {
+        self.state_root_monitor = Some(monitor);
+        self
+    }
+
+    /// Add a subscriber, created by the given factory, to each service created by this factory
+    #[cfg(feature = "state-subscriber-factory")]
+    pub fn with_state_subscriber_factory(
+        mut self,
+        subscriber_factory: Box<dyn StateSubscriberFactory>,
+    ) -> Self {
+        self.state_subscriber_factories.push(subscriber_factory);
+        self
+    }
+
+    /// Add the services created by this factory to the given lookup
+    #[cfg(feature = "service-lookup")]
+    pub fn with_service_lookup(mut self, lookup: ScabbardServiceLookup) -> Self {
+        self.service_lookup = Some(lookup);
+        self
+    }
 }
 
 #[cfg(feature = "service-arg-validation")]
@@ -74,6 +287,15 @@ impl ServiceArgValidator for ScabbardArgValidator {
             ServiceArgValidationError(format!("failed to parse peer_services list: {}", err,))
         })?;
 
+        #[cfg(feature = "observers")]
+        {
+            if let Some(observers_str) = args.get("observers") {
+                serde_json::from_str::<Vec<String>>(observers_str).map_err(|err| {
+                    ServiceArgValidationError(format!("failed to parse observers list: {}", err,))
+                })?;
+            }
+        }
+
         let admin_keys_str = args
             .get("admin_keys")
             .ok_or_else(|| ServiceArgValidationError("admin_keys argument not provided".into()))?;
@@ -116,6 +338,9 @@ impl ServiceFactory for ScabbardFactory {
     /// - `coordinator_timeout`: the length of time (in milliseconds) that the network has to
     ///   commit a proposal before the coordinator rejects it (if not provided, default is 30
     ///   seconds)
+    /// - `observers`: list of scabbard services on the same circuit, possibly including this
+    ///   service, that only receive committed batches and do not take part in consensus,
+    ///   formatted as a serialized JSON array of strings (requires the `observers` feature)
     fn create(
         &self,
         service_id: String,
@@ -136,8 +361,13 @@ impl ServiceFactory for ScabbardFactory {
                 })?
                 .into_iter(),
         );
-        let state_db_dir = Path::new(&self.state_db_dir);
-        let receipt_db_dir = Path::new(&self.receipt_db_dir);
+        #[cfg(feature = "db-placement")]
+        let (state_db_dir, receipt_db_dir) = self.db_dirs(&service_id, circuit_id)?;
+        #[cfg(not(feature = "db-placement"))]
+        let (state_db_dir, receipt_db_dir) = (
+            Path::new(&self.state_db_dir),
+            Path::new(&self.receipt_db_dir),
+        );
         let admin_keys_str = args.get("admin_keys").ok_or_else(|| {
             FactoryCreateError::InvalidArguments("admin_keys argument not provided".into())
         })?;
@@ -159,6 +389,28 @@ impl ServiceFactory for ScabbardFactory {
             })
             .transpose()?;
 
+        #[cfg(feature = "observers")]
+        let observers = args
+            .get("observers")
+            .map(|observers_str| {
+                serde_json::from_str::<Vec<String>>(observers_str)
+                    .map(HashSet::from_iter)
+                    .map_err(|err| {
+                        FactoryCreateError::InvalidArguments(format!(
+                            "failed to parse observers list: {}",
+                            err,
+                        ))
+                    })
+            })
+            .transpose()?;
+
+        #[cfg(feature = "state-subscriber-factory")]
+        let subscribers = self
+            .state_subscriber_factories
+            .iter()
+            .map(|factory| factory.create_subscriber(circuit_id, &service_id))
+            .collect::<Vec<_>>();
+
         let service = Scabbard::new(
             service_id,
             circuit_id,
@@ -170,9 +422,72 @@ impl ServiceFactory for ScabbardFactory {
             self.signature_verifier_factory.new_verifier(),
             admin_keys,
             coordinator_timeout,
+            #[cfg(feature = "db-auto-grow")]
+            self.db_growth_policy.as_ref(),
         )
         .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
 
+        #[cfg(feature = "observers")]
+        {
+            if let Some(observers) = &observers {
+                service
+                    .set_observers(observers)
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "batch-history")]
+        {
+            if let Some(retention) = &self.batch_history_retention {
+                service
+                    .set_batch_history_retention(retention.clone())
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "batch-queue-monitor")]
+        {
+            if let Some(monitor) = &self.batch_queue_monitor {
+                service
+                    .set_batch_queue_monitor(monitor.clone())
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "commit-pause")]
+        {
+            if let Some(pause) = &self.commit_pause {
+                service
+                    .set_commit_pause(pause.clone())
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "state-root-comparison")]
+        {
+            if let Some(monitor) = &self.state_root_monitor {
+                service
+                    .set_state_root_monitor(monitor.clone())
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "state-subscriber-factory")]
+        for subscriber in subscribers {
+            service
+                .add_state_subscriber(subscriber)
+                .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+        }
+
+        #[cfg(feature = "service-lookup")]
+        {
+            if let Some(lookup) = &self.service_lookup {
+                service
+                    .add_to_service_lookup(lookup.clone())
+                    .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+            }
+        }
+
         Ok(Box::new(service))
     }
 
@@ -182,10 +497,17 @@ impl ServiceFactory for ScabbardFactory {
     ///
     /// * `POST /batches` - Add one or more batches to scabbard's queue
     /// * `GET /batch_statuses` - Get the status of one or more batches
+    /// * `POST /batch_statuses/query` - Query the batch history by batch ID, status, and time
+    ///   (requires the `batch-history` feature)
     /// * `GET /ws/subscribe` - Subscribe to scabbard state-delta events
     /// * `GET /state/{address}` - Get a value from scabbard's state
     /// * `GET /state` - Get multiple scabbard state entries
     /// * `GET /state_root` - Get the current state root hash of scabbard's state
+    /// * `POST /state_root/compare` - Compare the current state root with the other scabbard
+    ///   services on the circuit (requires the `state-root-comparison` feature)
+    /// * `GET /storage` - Get the paths and sizes of scabbard's databases (requires the
+    ///   `db-placement` feature), including their map sizes and usage (requires the
+    ///   `db-auto-grow` feature)
     ///
     /// These endpoints are only available if the following REST API backend feature is enabled:
     ///
@@ -208,7 +530,14 @@ impl ServiceFactory for ScabbardFactory {
                 actix::state_address::make_get_state_at_address_endpoint(),
                 actix::state::make_get_state_with_prefix_endpoint(),
                 actix::state_root::make_get_state_root_endpoint(),
-            ])
+            ]);
+
+            #[cfg(feature = "batch-history")]
+            endpoints.push(actix::batch_statuses::make_query_batch_statuses_endpoint());
+            #[cfg(feature = "db-placement")]
+            endpoints.push(actix::storage::make_get_storage_endpoint());
+            #[cfg(feature = "state-root-comparison")]
+            endpoints.push(actix::state_root::make_compare_state_roots_endpoint());
         }
 
         endpoints
@@ -284,6 +613,74 @@ mod tests {
         );
     }
 
+    /// Verify that `Scabbard` creation fails when the `observers` argument is not a valid JSON
+    /// list.
+    #[cfg(feature = "observers")]
+    #[test]
+    fn create_with_invalid_observers() {
+        let factory = get_factory();
+        let mut args = get_mock_args();
+        args.insert("observers".into(), "not a list".into());
+
+        assert!(
+            factory.create("".into(), "", "", args).is_err(),
+            "Creating factory with invalid observers did not fail"
+        );
+    }
+
+    /// Verify that the databases of services are placed in the directory configured for the
+    /// service, then for its circuit, then in a subdirectory for the circuit, and that databases
+    /// that already exist in the base directory are kept there.
+    #[cfg(feature = "db-placement")]
+    #[test]
+    fn db_placement() {
+        let temp_dir = tempdir::TempDir::new("db_placement").expect("failed to create temp dir");
+        let base = temp_dir.path().join("base");
+        let hot = temp_dir.path().join("hot");
+        let pinned = temp_dir.path().join("pinned");
+
+        let factory = ScabbardFactory::new(
+            Some(base.to_string_lossy().into()),
+            Some(1024 * 1024),
+            Some(base.to_string_lossy().into()),
+            Some(1024 * 1024),
+            Box::new(Secp256k1Context::new()),
+        )
+        .with_circuit_db_dirs(true)
+        .with_circuit_db_dir("hot-circuit".into(), hot.to_string_lossy().into())
+        .with_service_db_dir(
+            "hot-circuit".into(),
+            "pinned".into(),
+            pinned.to_string_lossy().into(),
+        );
+
+        let db_dir = |service_id: &str, circuit_id: &str| {
+            let service = factory
+                .create(service_id.into(), "", circuit_id, get_mock_args())
+                .expect("failed to create service");
+            let scabbard = service
+                .as_any()
+                .downcast_ref::<Scabbard>()
+                .expect("failed to downcast Service to Scabbard");
+            let (state_db_path, receipt_db_path) = scabbard.db_paths();
+            assert_eq!(state_db_path.parent(), receipt_db_path.parent());
+            state_db_path.parent().unwrap().to_path_buf()
+        };
+
+        assert_eq!(db_dir("pinned", "hot-circuit"), pinned);
+        assert_eq!(db_dir("a", "hot-circuit"), hot);
+        assert_eq!(db_dir("a", "cold-circuit"), base.join("cold-circuit"));
+
+        let (existing_state_db, _) = compute_db_paths("b", "cold-circuit", &base, &base)
+            .expect("failed to compute db paths");
+        fs::write(&existing_state_db, b"").expect("failed to create existing db");
+        assert_eq!(db_dir("b", "cold-circuit"), base);
+
+        assert!(factory
+            .create("a".into(), "", "..", get_mock_args())
+            .is_err());
+    }
+
     fn get_factory() -> ScabbardFactory {
         ScabbardFactory::new(
             Some("/tmp".into()),
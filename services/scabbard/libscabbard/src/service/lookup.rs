@@ -0,0 +1,66 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finds the scabbard services running on a node.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use super::Scabbard;
+
+/// Tracks every scabbard service created by the factories it is given to.
+///
+/// A lookup is shared between the services created by a `ScabbardFactory`; each service is added
+/// when it is created and removed when it is destroyed.
+#[derive(Clone, Default)]
+pub struct ScabbardServiceLookup {
+    services: Arc<Mutex<BTreeMap<(String, String), Scabbard>>>,
+}
+
+impl ScabbardServiceLookup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the service with the given ID on the given circuit, if it is running on this node
+    pub fn get(&self, circuit_id: &str, service_id: &str) -> Option<Scabbard> {
+        match self.services.lock() {
+            Ok(services) => services
+                .get(&(circuit_id.to_string(), service_id.to_string()))
+                .cloned(),
+            Err(_) => {
+                error!("Unable to look up scabbard service: lock poisoned");
+                None
+            }
+        }
+    }
+
+    pub(super) fn add(&self, circuit_id: &str, service_id: &str, service: Scabbard) {
+        match self.services.lock() {
+            Ok(mut services) => {
+                services.insert((circuit_id.to_string(), service_id.to_string()), service);
+            }
+            Err(_) => error!("Unable to add scabbard service to lookup: lock poisoned"),
+        }
+    }
+
+    pub(super) fn remove(&self, circuit_id: &str, service_id: &str) {
+        match self.services.lock() {
+            Ok(mut services) => {
+                services.remove(&(circuit_id.to_string(), service_id.to_string()));
+            }
+            Err(_) => error!("Unable to remove scabbard service from lookup: lock poisoned"),
+        }
+    }
+}
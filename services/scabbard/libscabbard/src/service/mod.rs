@@ -16,23 +16,45 @@
 //! `transact` library for state. Scabbard uses two-phase consensus to reach agreement on
 //! transactions.
 
+#[cfg(feature = "commit-pause")]
+mod commit_pause;
 mod consensus;
+#[cfg(feature = "db-auto-grow")]
+mod db_growth;
 mod error;
 mod factory;
+#[cfg(feature = "service-lookup")]
+mod lookup;
+#[cfg(feature = "batch-queue-monitor")]
+mod queue_monitor;
 #[cfg(feature = "rest-api")]
 mod rest_api;
 mod shared;
 mod state;
+#[cfg(feature = "state-root-comparison")]
+mod state_root_comparison;
+#[cfg(feature = "state-verify")]
+mod verify;
 
 use std::any::Any;
+#[cfg(feature = "state-root-comparison")]
+use std::collections::HashMap;
 use std::collections::{HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "state-root-comparison")]
+use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
+#[cfg(feature = "state-root-comparison")]
+use std::time::Instant;
 
 use cylinder::Verifier as SignatureVerifier;
 use openssl::hash::{hash, MessageDigest};
+#[cfg(feature = "state-root-comparison")]
+use openssl::rand::rand_bytes;
+#[cfg(feature = "state-root-comparison")]
+use protobuf::Message;
 use splinter::{
     consensus::{Proposal, ProposalUpdate},
     service::{
@@ -43,21 +65,61 @@ use splinter::{
 use transact::{protocol::batch::BatchPair, protos::FromBytes};
 
 use super::hex::to_hex;
+#[cfg(feature = "observers")]
+use super::protos::scabbard::CommittedBatch;
+#[cfg(feature = "state-root-comparison")]
+use super::protos::scabbard::StateRootExchange;
 use super::protos::scabbard::{ScabbardMessage, ScabbardMessage_Type};
 
+#[cfg(feature = "commit-pause")]
+pub use commit_pause::CommitPause;
 use consensus::ScabbardConsensusManager;
+#[cfg(feature = "db-auto-grow")]
+pub use db_growth::{DbGrowthPolicy, DbUsage};
 use error::ScabbardError;
+#[cfg(feature = "state-subscriber-factory")]
+pub use error::StateSubscriberError;
 #[cfg(feature = "service-arg-validation")]
 pub use factory::ScabbardArgValidator;
 pub use factory::ScabbardFactory;
+#[cfg(feature = "state-subscriber-factory")]
+pub use factory::StateSubscriberFactory;
+#[cfg(feature = "service-lookup")]
+pub use lookup::ScabbardServiceLookup;
+#[cfg(feature = "batch-queue-monitor")]
+pub use queue_monitor::{BatchQueueDepth, BatchQueueMonitor};
 use shared::ScabbardShared;
+#[cfg(feature = "benchmark")]
+pub use state::ScabbardState;
+#[cfg(not(feature = "benchmark"))]
+use state::ScabbardState;
+#[cfg(feature = "state-subscriber-factory")]
+pub use state::StateSubscriber;
+#[cfg(not(feature = "state-subscriber-factory"))]
+use state::StateSubscriber;
+#[cfg(feature = "batch-history")]
+pub use state::{BatchHistoryRetention, BatchInfoQuery};
 pub use state::{
     BatchInfo, BatchInfoIter, BatchStatus, Events, StateChange, StateChangeEvent, StateIter,
 };
-use state::{ScabbardState, StateSubscriber};
+#[cfg(feature = "state-root-comparison")]
+pub use state_root_comparison::{MemberStateRoot, StateRootComparison, StateRootMonitor};
+#[cfg(feature = "state-verify")]
+pub use verify::{verify_state, StateVerification};
 
 const SERVICE_TYPE: &str = "scabbard";
 
+/// The result of submitting batches with an idempotency key.
+#[cfg(feature = "batch-idempotency")]
+#[derive(Debug, PartialEq)]
+pub enum BatchSubmission {
+    /// The batches were added to the service's queue; the link is for their statuses
+    Accepted { link: String },
+    /// Batches were already submitted with the same key; the link is for the statuses of the
+    /// original batches
+    Duplicate { link: String },
+}
+
 const DEFAULT_COORDINATOR_TIMEOUT: u64 = 30; // 30 seconds
 
 /// A service for running Sawtooth Sabre smart contracts with two-phase commit consensus.
@@ -65,6 +127,10 @@ const DEFAULT_COORDINATOR_TIMEOUT: u64 = 30; // 30 seconds
 pub struct Scabbard {
     circuit_id: String,
     service_id: String,
+    #[cfg(feature = "db-placement")]
+    state_db_path: PathBuf,
+    #[cfg(feature = "db-placement")]
+    receipt_db_path: PathBuf,
     shared: Arc<Mutex<ScabbardShared>>,
     state: Arc<Mutex<ScabbardState>>,
     /// The coordinator timeout for the two-phase commit consensus engine
@@ -94,6 +160,8 @@ impl Scabbard {
         // The coordinator timeout for the two-phase commit consensus engine; if `None`, the
         // default value will be used (30 seconds).
         coordinator_timeout: Option<Duration>,
+        // How the LMDB databases grow; if `None`, they are opened with the given sizes
+        #[cfg(feature = "db-auto-grow")] db_growth_policy: Option<&DbGrowthPolicy>,
     ) -> Result<Self, ScabbardError> {
         let shared = ScabbardShared::new(VecDeque::new(), None, peer_services, signature_verifier);
 
@@ -105,6 +173,8 @@ impl Scabbard {
             receipt_db_path.as_path(),
             receipt_db_size,
             admin_keys,
+            #[cfg(feature = "db-auto-grow")]
+            db_growth_policy,
         )
         .map_err(|err| ScabbardError::InitializationFailed(Box::new(err)))?;
 
@@ -114,6 +184,10 @@ impl Scabbard {
         Ok(Scabbard {
             circuit_id: circuit_id.to_string(),
             service_id,
+            #[cfg(feature = "db-placement")]
+            state_db_path,
+            #[cfg(feature = "db-placement")]
+            receipt_db_path,
             shared: Arc::new(Mutex::new(shared)),
             state: Arc::new(Mutex::new(state)),
             coordinator_timeout,
@@ -121,6 +195,92 @@ impl Scabbard {
         })
     }
 
+    /// Returns the paths of this service's state and receipt databases.
+    #[cfg(feature = "db-placement")]
+    pub fn db_paths(&self) -> (&Path, &Path) {
+        (&self.state_db_path, &self.receipt_db_path)
+    }
+
+    /// Returns the size and usage of this service's state and receipt databases.
+    #[cfg(feature = "db-auto-grow")]
+    pub fn db_usage(&self) -> Result<(DbUsage, DbUsage), ScabbardError> {
+        Ok(self
+            .state
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .db_usage()?)
+    }
+
+    /// Report the depth of this service's batch queue to the given monitor.
+    #[cfg(feature = "batch-queue-monitor")]
+    pub fn set_batch_queue_monitor(&self, monitor: BatchQueueMonitor) -> Result<(), ScabbardError> {
+        self.shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .set_batch_queue_monitor(monitor, &self.circuit_id, &self.service_id);
+        Ok(())
+    }
+
+    /// Pause this service's commits while the given pause is paused.
+    #[cfg(feature = "commit-pause")]
+    pub fn set_commit_pause(&self, pause: CommitPause) -> Result<(), ScabbardError> {
+        self.shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .set_commit_pause(pause);
+        Ok(())
+    }
+
+    /// Record the result of this service's state root comparisons in the given monitor.
+    #[cfg(feature = "state-root-comparison")]
+    pub fn set_state_root_monitor(&self, monitor: StateRootMonitor) -> Result<(), ScabbardError> {
+        self.shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .set_state_root_monitor(monitor);
+        Ok(())
+    }
+
+    /// Set which batches are kept in this service's batch history.
+    #[cfg(feature = "batch-history")]
+    pub fn set_batch_history_retention(
+        &self,
+        retention: BatchHistoryRetention,
+    ) -> Result<(), ScabbardError> {
+        self.state
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .batch_history()
+            .set_retention(retention);
+        Ok(())
+    }
+
+    /// Set which of the circuit's scabbard services are observers. Observers receive the batches
+    /// committed by the other services but do not take part in consensus or accept batches. This
+    /// must be called before the service is started.
+    #[cfg(feature = "observers")]
+    pub fn set_observers(&self, observers: &HashSet<String>) -> Result<(), ScabbardError> {
+        self.shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .set_observers(&self.service_id, observers);
+        Ok(())
+    }
+
+    /// Add this service to the given lookup; it is removed again when the service is destroyed.
+    #[cfg(feature = "service-lookup")]
+    pub fn add_to_service_lookup(
+        &self,
+        lookup: ScabbardServiceLookup,
+    ) -> Result<(), ScabbardError> {
+        lookup.add(&self.circuit_id, &self.service_id, self.clone());
+        self.shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .set_service_lookup(lookup);
+        Ok(())
+    }
+
     /// Fetch the value at the given `address` in the scabbard service's state. Returns `None` if
     /// the `address` is not set.
     pub fn get_state_at_address(&self, address: &str) -> Result<Option<Vec<u8>>, ScabbardError> {
@@ -142,6 +302,20 @@ impl Scabbard {
             .get_state_with_prefix(prefix)?)
     }
 
+    /// Fetch a list of entries in the scabbard service's state as of the given `state_root`.
+    /// Returns `None` if the state root is unknown.
+    pub fn get_state_with_prefix_at_root(
+        &self,
+        prefix: Option<&str>,
+        state_root: &str,
+    ) -> Result<Option<StateIter>, ScabbardError> {
+        Ok(self
+            .state
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .get_state_with_prefix_at_root(prefix, state_root)?)
+    }
+
     /// Get the current state root hash of the scabbard service's state.
     pub fn get_current_state_root(&self) -> Result<String, ScabbardError> {
         Ok(self
@@ -152,12 +326,116 @@ impl Scabbard {
             .to_string())
     }
 
+    /// Compare this service's current state root with the other scabbard services on the circuit.
+    ///
+    /// The other services are asked for their current state roots; this blocks until all of them
+    /// have responded or the `timeout` expires, in which case the remaining services are reported
+    /// without a state root. Divergence is logged and recorded in the service's
+    /// `StateRootMonitor`, if it has one.
+    #[cfg(feature = "state-root-comparison")]
+    pub fn compare_state_roots(
+        &self,
+        timeout: Duration,
+    ) -> Result<StateRootComparison, ScabbardError> {
+        let mut request_id = [0; 16];
+        rand_bytes(&mut request_id)
+            .map_err(|err| ScabbardError::StateRootComparisonFailed(Box::new(err)))?;
+        let request_id = to_hex(&request_id);
+
+        let mut exchange = StateRootExchange::new();
+        exchange.set_request_id(request_id.clone());
+        exchange.set_service_id(self.service_id.clone());
+        let mut msg = ScabbardMessage::new();
+        msg.set_message_type(ScabbardMessage_Type::STATE_ROOT_REQUEST);
+        msg.set_state_root_exchange(exchange);
+        let msg_bytes = msg
+            .write_to_bytes()
+            .map_err(|err| ScabbardError::StateRootComparisonFailed(Box::new(err)))?;
+
+        let (sender, receiver) = channel();
+        let members = {
+            let mut shared = self
+                .shared
+                .lock()
+                .map_err(|_| ScabbardError::LockPoisoned)?;
+            let members = shared.circuit_members();
+            let network_sender = shared.network_sender().ok_or(ScabbardError::NotConnected)?;
+            for member in &members {
+                if let Err(err) = network_sender.send(member, &msg_bytes) {
+                    warn!("Failed to request state root from {}: {}", member, err);
+                }
+            }
+            // Responses are handled with the shared lock held, so none can arrive before the
+            // request is added
+            shared.add_state_root_request(request_id.clone(), sender);
+            members
+        };
+
+        let state_root = self.get_current_state_root();
+
+        let deadline = Instant::now() + timeout;
+        let mut responses = HashMap::new();
+        while responses.len() < members.len() {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok((service_id, state_root)) => {
+                    if members.contains(&service_id) {
+                        responses.insert(service_id, state_root);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let mut shared = self
+            .shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?;
+        shared.remove_state_root_request(&request_id);
+
+        let comparison = StateRootComparison::new(
+            &self.circuit_id,
+            &self.service_id,
+            state_root?,
+            members,
+            responses,
+        );
+
+        for member in comparison.divergent_members() {
+            error!(
+                "State root of {}::{} ({}) differs from {} ({})",
+                self.circuit_id,
+                self.service_id,
+                comparison.state_root,
+                member.service_id,
+                member.state_root.as_deref().unwrap_or_default()
+            );
+        }
+        for member in comparison.unresponsive_members() {
+            warn!(
+                "{} did not report its state root to {}::{} before the comparison timed out",
+                member.service_id, self.circuit_id, self.service_id
+            );
+        }
+        if let Some(monitor) = shared.state_root_monitor() {
+            monitor.record(&comparison);
+        }
+
+        Ok(comparison)
+    }
+
     pub fn add_batches(&self, batches: Vec<BatchPair>) -> Result<Option<String>, ScabbardError> {
         let mut shared = self
             .shared
             .lock()
             .map_err(|_| ScabbardError::LockPoisoned)?;
 
+        #[cfg(feature = "observers")]
+        {
+            if shared.is_observer() {
+                return Err(ScabbardError::ReadOnly);
+            }
+        }
+
         if shared.verify_batches(&batches)? {
             let mut link = format!(
                 "/scabbard/{}/{}/batch_statuses?ids=",
@@ -185,6 +463,76 @@ impl Scabbard {
         }
     }
 
+    /// Add the given batches to the service's queue, unless batches were already submitted with
+    /// the same `idempotency_key`; in that case, the new batches are dropped and the original
+    /// batches are returned as a `BatchSubmission::Duplicate`. Returns `None` if the batches are
+    /// not valid.
+    ///
+    /// Idempotency keys are kept for as long as the batches they were submitted with remain in
+    /// the service's batch history.
+    #[cfg(feature = "batch-idempotency")]
+    pub fn add_batches_with_idempotency_key(
+        &self,
+        batches: Vec<BatchPair>,
+        idempotency_key: &str,
+    ) -> Result<Option<BatchSubmission>, ScabbardError> {
+        let mut shared = self
+            .shared
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?;
+        let mut state = self.state.lock().map_err(|_| ScabbardError::LockPoisoned)?;
+
+        #[cfg(feature = "observers")]
+        {
+            if shared.is_observer() {
+                return Err(ScabbardError::ReadOnly);
+            }
+        }
+
+        if let Some(ids) = state
+            .batch_history()
+            .batches_with_idempotency_key(idempotency_key)
+        {
+            debug!(
+                "Batches already submitted with idempotency key {}: {:?}",
+                idempotency_key, ids
+            );
+            return Ok(Some(BatchSubmission::Duplicate {
+                link: self.batch_statuses_link(&ids),
+            }));
+        }
+
+        if !shared.verify_batches(&batches)? {
+            return Ok(None);
+        }
+
+        let ids = batches
+            .iter()
+            .map(|batch| batch.batch().header_signature().to_string())
+            .collect::<Vec<_>>();
+
+        for batch in batches {
+            state
+                .batch_history()
+                .add_batch_with_idempotency_key(&batch.batch().header_signature(), idempotency_key);
+            shared.add_batch_to_queue(batch);
+        }
+
+        let link = self.batch_statuses_link(&ids);
+        debug!("Batch Status Link Created: {}", link);
+        Ok(Some(BatchSubmission::Accepted { link }))
+    }
+
+    #[cfg(feature = "batch-idempotency")]
+    fn batch_statuses_link(&self, ids: &[String]) -> String {
+        format!(
+            "/scabbard/{}/{}/batch_statuses?ids={}",
+            self.circuit_id,
+            self.service_id,
+            ids.join(",")
+        )
+    }
+
     /// Get the `BatchInfo` for each specified batch.
     ///
     /// # Arguments
@@ -204,6 +552,18 @@ impl Scabbard {
         Ok(state.batch_history().get_batch_info(ids, wait)?)
     }
 
+    /// Get the `BatchInfo` of each batch in the service's batch history that is selected by the
+    /// given query, ordered by the time the batches were submitted.
+    #[cfg(feature = "batch-history")]
+    pub fn query_batch_info(&self, query: &BatchInfoQuery) -> Result<BatchInfoIter, ScabbardError> {
+        Ok(self
+            .state
+            .lock()
+            .map_err(|_| ScabbardError::LockPoisoned)?
+            .batch_history()
+            .query_batch_info(query))
+    }
+
     pub fn get_events_since(&self, event_id: Option<String>) -> Result<Events, ScabbardError> {
         Ok(self
             .state
@@ -223,6 +583,120 @@ impl Scabbard {
 
         Ok(())
     }
+
+    /// Apply a batch that was committed by one of the services this observer receives batches
+    /// from. Batches from any other service are ignored.
+    #[cfg(feature = "observers")]
+    fn handle_committed_batch(
+        &self,
+        committed_batch: &CommittedBatch,
+        sender: &str,
+    ) -> Result<(), ServiceError> {
+        if !self
+            .shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("shared lock poisoned".into()))?
+            .is_observing(sender)
+        {
+            warn!(
+                "Ignoring committed batch from {}, which this service does not observe",
+                sender
+            );
+            return Ok(());
+        }
+
+        let batch = BatchPair::from_bytes(committed_batch.get_batch())
+            .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?;
+        let batch_id = batch.batch().header_signature().to_string();
+
+        if self
+            .state
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("state lock poisoned".into()))?
+            .apply_committed_batch(batch, committed_batch.get_state_root())
+            .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))?
+        {
+            info!("Applied batch {} committed by {}", batch_id, sender);
+        }
+
+        Ok(())
+    }
+
+    /// Respond to a state root request with this service's current state root.
+    #[cfg(feature = "state-root-comparison")]
+    fn handle_state_root_request(
+        &self,
+        request: &StateRootExchange,
+        sender: &str,
+    ) -> Result<(), ServiceError> {
+        let shared = self
+            .shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("shared lock poisoned".into()))?;
+
+        if !shared.circuit_members().contains(sender) {
+            warn!(
+                "Ignoring state root request from {}, which is not a member of the circuit",
+                sender
+            );
+            return Ok(());
+        }
+
+        let mut exchange = StateRootExchange::new();
+        exchange.set_request_id(request.get_request_id().into());
+        exchange.set_service_id(self.service_id.clone());
+        exchange.set_state_root(
+            self.state
+                .lock()
+                .map_err(|_| ServiceError::PoisonedLock("state lock poisoned".into()))?
+                .current_state_root()
+                .into(),
+        );
+        let mut msg = ScabbardMessage::new();
+        msg.set_message_type(ScabbardMessage_Type::STATE_ROOT_RESPONSE);
+        msg.set_state_root_exchange(exchange);
+
+        shared
+            .network_sender()
+            .ok_or(ServiceError::NotStarted)?
+            .send(sender, &msg.write_to_bytes()?)
+            .map_err(|err| ServiceError::UnableToSendMessage(Box::new(err)))
+    }
+
+    /// Pass a state root response to the comparison that requested it.
+    #[cfg(feature = "state-root-comparison")]
+    fn handle_state_root_response(
+        &self,
+        response: &StateRootExchange,
+        sender: &str,
+    ) -> Result<(), ServiceError> {
+        match self
+            .shared
+            .lock()
+            .map_err(|_| ServiceError::PoisonedLock("shared lock poisoned".into()))?
+            .state_root_request(response.get_request_id())
+        {
+            Some(comparison) => {
+                if comparison
+                    .send((sender.to_string(), response.get_state_root().to_string()))
+                    .is_err()
+                {
+                    debug!(
+                        "State root comparison {} ended before the response from {} arrived",
+                        response.get_request_id(),
+                        sender
+                    );
+                }
+            }
+            None => debug!(
+                "Ignoring state root response from {} for unknown comparison {}",
+                sender,
+                response.get_request_id()
+            ),
+        }
+
+        Ok(())
+    }
 }
 
 impl Service for Scabbard {
@@ -306,10 +780,46 @@ impl Service for Scabbard {
             .map_err(|_| ServiceDestroyError::PoisonedLock("consensus lock poisoned".into()))?
             .is_some()
         {
-            Err(ServiceDestroyError::NotStopped)
-        } else {
-            Ok(())
+            return Err(ServiceDestroyError::NotStopped);
+        }
+
+        #[cfg(feature = "batch-queue-monitor")]
+        {
+            if let Some((monitor, circuit_id, service_id)) = self
+                .shared
+                .lock()
+                .map_err(|_| ServiceDestroyError::PoisonedLock("shared lock poisoned".into()))?
+                .take_batch_queue_monitor()
+            {
+                monitor.remove(&circuit_id, &service_id);
+            }
+        }
+
+        #[cfg(feature = "state-root-comparison")]
+        {
+            if let Some(monitor) = self
+                .shared
+                .lock()
+                .map_err(|_| ServiceDestroyError::PoisonedLock("shared lock poisoned".into()))?
+                .take_state_root_monitor()
+            {
+                monitor.remove(&self.circuit_id, &self.service_id);
+            }
+        }
+
+        #[cfg(feature = "service-lookup")]
+        {
+            if let Some(lookup) = self
+                .shared
+                .lock()
+                .map_err(|_| ServiceDestroyError::PoisonedLock("shared lock poisoned".into()))?
+                .take_service_lookup()
+            {
+                lookup.remove(&self.circuit_id, &self.service_id);
+            }
         }
+
+        Ok(())
     }
 
     fn handle_message(
@@ -351,6 +861,35 @@ impl Service for Scabbard {
                     ))
                     .map_err(|err| ServiceError::UnableToHandleMessage(Box::new(err)))
             }
+            #[cfg(feature = "observers")]
+            ScabbardMessage_Type::COMMITTED_BATCH => {
+                self.handle_committed_batch(message.get_committed_batch(), &_message_context.sender)
+            }
+            #[cfg(not(feature = "observers"))]
+            ScabbardMessage_Type::COMMITTED_BATCH => {
+                warn!("Ignoring committed batch; observers are not supported");
+                Ok(())
+            }
+            #[cfg(feature = "state-root-comparison")]
+            ScabbardMessage_Type::STATE_ROOT_REQUEST => self.handle_state_root_request(
+                message.get_state_root_exchange(),
+                &_message_context.sender,
+            ),
+            #[cfg(not(feature = "state-root-comparison"))]
+            ScabbardMessage_Type::STATE_ROOT_REQUEST => {
+                warn!("Ignoring state root request; state root comparison is not supported");
+                Ok(())
+            }
+            #[cfg(feature = "state-root-comparison")]
+            ScabbardMessage_Type::STATE_ROOT_RESPONSE => self.handle_state_root_response(
+                message.get_state_root_exchange(),
+                &_message_context.sender,
+            ),
+            #[cfg(not(feature = "state-root-comparison"))]
+            ScabbardMessage_Type::STATE_ROOT_RESPONSE => {
+                warn!("Ignoring state root response; state root comparison is not supported");
+                Ok(())
+            }
             ScabbardMessage_Type::UNSET => Err(ServiceError::InvalidMessageFormat(Box::new(
                 ScabbardError::MessageTypeUnset,
             ))),
@@ -405,6 +944,8 @@ pub mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("failed to create service");
         assert_eq!(service.service_id(), "new_scabbard");
@@ -426,6 +967,8 @@ pub mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("failed to create service");
         let registry = MockServiceNetworkRegistry::new();
@@ -447,11 +990,152 @@ pub mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("failed to create service");
         test_connect_and_disconnect(&mut service);
     }
 
+    /// Tests that a state root comparison collects the state roots of the other services on the
+    /// circuit, and reports the services that do not respond before the timeout.
+    ///
+    /// 1. Start two services, `a` and `b`, that are peers of each other.
+    /// 2. Compare state roots from `a`, and pass the request it sends to `b`
+    /// 3. Pass the response `b` sends back to `a`, and check that the comparison finds the two
+    ///    services in agreement
+    /// 4. Compare state roots from `a` again without passing on the request, and check that `b` is
+    ///    reported as unresponsive
+    #[cfg(feature = "state-root-comparison")]
+    #[test]
+    fn compare_state_roots() {
+        let new_service = |service_id: &str, peer_id: &str| {
+            Scabbard::new(
+                service_id.into(),
+                "compare_state_roots",
+                vec![peer_id.to_string()].into_iter().collect(),
+                Path::new("/tmp"),
+                1024 * 1024,
+                Path::new("/tmp"),
+                1024 * 1024,
+                Secp256k1Context::new().new_verifier(),
+                vec![],
+                None,
+                #[cfg(feature = "db-auto-grow")]
+                None,
+            )
+            .expect("failed to create service")
+        };
+        let mut service_a = new_service("compare_state_roots_a", "compare_state_roots_b");
+        let mut service_b = new_service("compare_state_roots_b", "compare_state_roots_a");
+        let registry_a = MockServiceNetworkRegistry::new();
+        let registry_b = MockServiceNetworkRegistry::new();
+        service_a.start(&registry_a).expect("failed to start a");
+        service_b.start(&registry_b).expect("failed to start b");
+
+        let comparing_service = service_a.clone();
+        let join_handle = std::thread::spawn(move || {
+            comparing_service.compare_state_roots(Duration::from_secs(10))
+        });
+
+        let request = loop {
+            if let Some((recipient, request)) = registry_a
+                .network_sender()
+                .sent
+                .lock()
+                .expect("sent lock poisoned")
+                .pop()
+            {
+                assert_eq!(recipient, "compare_state_roots_b");
+                break request;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        service_b
+            .handle_message(&request, &message_context("compare_state_roots_a"))
+            .expect("failed to handle request");
+
+        let (recipient, response) = registry_b
+            .network_sender()
+            .sent
+            .lock()
+            .expect("sent lock poisoned")
+            .pop()
+            .expect("no response sent");
+        assert_eq!(recipient, "compare_state_roots_a");
+        service_a
+            .handle_message(&response, &message_context("compare_state_roots_b"))
+            .expect("failed to handle response");
+
+        let comparison = join_handle
+            .join()
+            .expect("comparison thread panicked")
+            .expect("failed to compare state roots");
+        assert!(comparison.is_agreed());
+        assert_eq!(
+            comparison.members,
+            vec![MemberStateRoot {
+                service_id: "compare_state_roots_b".into(),
+                state_root: Some(service_b.get_current_state_root().expect("no state root")),
+            }]
+        );
+
+        let comparison = service_a
+            .compare_state_roots(Duration::from_millis(100))
+            .expect("failed to compare state roots");
+        assert!(comparison.is_agreed());
+        assert_eq!(comparison.unresponsive_members().len(), 1);
+
+        service_a.stop(&registry_a).expect("failed to stop a");
+        service_b.stop(&registry_b).expect("failed to stop b");
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    fn message_context(sender: &str) -> ServiceMessageContext {
+        ServiceMessageContext {
+            sender: sender.into(),
+            circuit: "compare_state_roots".into(),
+            correlation_id: "".into(),
+        }
+    }
+
+    /// Tests that an observer does not accept batches.
+    #[cfg(feature = "observers")]
+    #[test]
+    fn observer_rejects_batches() {
+        let service = Scabbard::new(
+            "observer_rejects_batches".into(),
+            "test_circuit",
+            vec!["peer".to_string()].into_iter().collect(),
+            Path::new("/tmp"),
+            1024 * 1024,
+            Path::new("/tmp"),
+            1024 * 1024,
+            Secp256k1Context::new().new_verifier(),
+            vec![],
+            None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("failed to create service");
+        assert!(service
+            .add_batches(vec![])
+            .expect("failed to add batches")
+            .is_some());
+
+        service
+            .set_observers(
+                &vec!["observer_rejects_batches".to_string()]
+                    .into_iter()
+                    .collect(),
+            )
+            .expect("failed to set observers");
+        match service.add_batches(vec![]) {
+            Err(ScabbardError::ReadOnly) => (),
+            res => panic!("expected ReadOnly error, got {:?}", res),
+        }
+    }
+
     #[derive(Debug)]
     pub struct MockServiceNetworkRegistryError(pub String);
 
@@ -0,0 +1,78 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports the depth of the batch queues of the scabbard services running on a node.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// The number of batches waiting to be proposed by a scabbard service
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchQueueDepth {
+    pub circuit_id: String,
+    pub service_id: String,
+    pub depth: usize,
+}
+
+/// Tracks the batch queue depth of every scabbard service it is given to.
+///
+/// A monitor is shared between the services created by a `ScabbardFactory`; each service updates
+/// its entry whenever a batch is added to or taken from its queue, and removes it when the
+/// service is destroyed.
+#[derive(Clone, Default)]
+pub struct BatchQueueMonitor {
+    depths: Arc<Mutex<BTreeMap<(String, String), usize>>>,
+}
+
+impl BatchQueueMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current queue depth of each service, ordered by circuit and service ID
+    pub fn depths(&self) -> Vec<BatchQueueDepth> {
+        match self.depths.lock() {
+            Ok(depths) => depths
+                .iter()
+                .map(|((circuit_id, service_id), depth)| BatchQueueDepth {
+                    circuit_id: circuit_id.to_string(),
+                    service_id: service_id.to_string(),
+                    depth: *depth,
+                })
+                .collect(),
+            Err(_) => {
+                error!("Unable to read batch queue depths: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    pub(super) fn set_depth(&self, circuit_id: &str, service_id: &str, depth: usize) {
+        match self.depths.lock() {
+            Ok(mut depths) => {
+                depths.insert((circuit_id.to_string(), service_id.to_string()), depth);
+            }
+            Err(_) => error!("Unable to record batch queue depth: lock poisoned"),
+        }
+    }
+
+    pub(super) fn remove(&self, circuit_id: &str, service_id: &str) {
+        match self.depths.lock() {
+            Ok(mut depths) => {
+                depths.remove(&(circuit_id.to_string(), service_id.to_string()));
+            }
+            Err(_) => error!("Unable to remove batch queue depth: lock poisoned"),
+        }
+    }
+}
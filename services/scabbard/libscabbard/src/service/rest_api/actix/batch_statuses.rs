@@ -13,22 +13,34 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+#[cfg(feature = "batch-history")]
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
+#[cfg(feature = "batch-history")]
+use std::time::UNIX_EPOCH;
 
+#[cfg(feature = "batch-history")]
+use actix_web::Error as ActixError;
 use actix_web::{web, HttpResponse};
 use futures::IntoFuture;
+#[cfg(feature = "batch-history")]
+use futures::{stream::Stream, Future};
 use splinter::{
     rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard},
     service::rest_api::ServiceEndpoint,
 };
 
 use crate::protocol;
+#[cfg(feature = "batch-history")]
+use crate::service::{rest_api::resources::batch_statuses::BatchInfoQueryRequest, BatchInfoQuery};
 use crate::service::{
     rest_api::resources::batch_statuses::BatchInfoResponse, Scabbard, SERVICE_TYPE,
 };
 
 const DEFAULT_BATCH_STATUS_WAIT_SECS: u64 = 300;
+#[cfg(feature = "batch-history")]
+const STATUS_TYPES: &[&str] = &["Unknown", "Pending", "Invalid", "Valid", "Committed"];
 
 pub fn make_get_batch_status_endpoint() -> ServiceEndpoint {
     ServiceEndpoint {
@@ -122,3 +134,170 @@ pub fn make_get_batch_status_endpoint() -> ServiceEndpoint {
         ))],
     }
 }
+
+#[cfg(feature = "batch-history")]
+pub fn make_query_batch_statuses_endpoint() -> ServiceEndpoint {
+    ServiceEndpoint {
+        service_type: SERVICE_TYPE.into(),
+        route: "/batch_statuses/query".into(),
+        method: Method::Post,
+        handler: Arc::new(move |_, payload, service| {
+            let scabbard = match service.as_any().downcast_ref::<Scabbard>() {
+                Some(s) => s,
+                None => {
+                    error!("Failed to downcast to scabbard service");
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
+                }
+            }
+            .clone();
+
+            Box::new(
+                payload
+                    .from_err::<ActixError>()
+                    .fold(web::BytesMut::new(), move |mut body, chunk| {
+                        body.extend_from_slice(&chunk);
+                        Ok::<_, ActixError>(body)
+                    })
+                    .into_future()
+                    .and_then(move |body| {
+                        let request: BatchInfoQueryRequest = match serde_json::from_slice(&body) {
+                            Ok(request) => request,
+                            Err(err) => {
+                                return HttpResponse::BadRequest()
+                                    .json(ErrorResponse::bad_request(&format!(
+                                        "Invalid body: {}",
+                                        err
+                                    )))
+                                    .into_future()
+                            }
+                        };
+
+                        let query = match to_batch_info_query(request) {
+                            Ok(query) => query,
+                            Err(msg) => {
+                                return HttpResponse::BadRequest()
+                                    .json(ErrorResponse::bad_request(&msg))
+                                    .into_future()
+                            }
+                        };
+
+                        let batch_info_iter = match scabbard.query_batch_info(&query) {
+                            Ok(iter) => iter,
+                            Err(err) => {
+                                error!("Failed to query batch history: {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        };
+
+                        match batch_info_iter.collect::<Result<Vec<_>, _>>() {
+                            Ok(batch_infos) => HttpResponse::Ok()
+                                .json(
+                                    batch_infos
+                                        .iter()
+                                        .map(BatchInfoResponse::from)
+                                        .collect::<Vec<_>>(),
+                                )
+                                .into_future(),
+                            Err(err) => {
+                                error!("Failed to query batch history: {}", err);
+                                HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future()
+                            }
+                        }
+                    }),
+            )
+        }),
+        request_guards: vec![Box::new(ProtocolVersionRangeGuard::new(
+            protocol::SCABBARD_QUERY_BATCH_STATUSES_PROTOCOL_MIN,
+            protocol::SCABBARD_PROTOCOL_VERSION,
+        ))],
+    }
+}
+
+#[cfg(feature = "batch-history")]
+fn to_batch_info_query(request: BatchInfoQueryRequest) -> Result<BatchInfoQuery, String> {
+    if request.ids.is_none()
+        && request.status_types.is_none()
+        && request.since.is_none()
+        && request.until.is_none()
+    {
+        return Err("At least one of ids, status_types, since, or until must be specified".into());
+    }
+
+    if let Some(status_types) = &request.status_types {
+        if let Some(invalid) = status_types
+            .iter()
+            .find(|status_type| !STATUS_TYPES.contains(&status_type.as_str()))
+        {
+            return Err(format!(
+                "Invalid status type: {}; must be one of {}",
+                invalid,
+                STATUS_TYPES.join(", ")
+            ));
+        }
+    }
+
+    Ok(BatchInfoQuery {
+        ids: request
+            .ids
+            .map(|ids| ids.into_iter().collect::<HashSet<_>>()),
+        status_types: request
+            .status_types
+            .map(|status_types| status_types.into_iter().collect::<HashSet<_>>()),
+        since: request
+            .since
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        until: request
+            .until
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+    })
+}
+
+#[cfg(all(test, feature = "batch-history"))]
+mod tests {
+    use super::*;
+
+    /// Verify that a `BatchInfoQueryRequest` is correctly converted to a `BatchInfoQuery`, and
+    /// that invalid requests are rejected.
+    #[test]
+    fn batch_info_query_from_request() {
+        let query = to_batch_info_query(BatchInfoQueryRequest {
+            ids: Some(vec!["batch1".into(), "batch2".into()]),
+            status_types: Some(vec!["Committed".into()]),
+            since: Some(10),
+            until: Some(20),
+        })
+        .expect("Failed to convert valid request");
+        assert_eq!(
+            query.ids,
+            Some(
+                vec!["batch1".to_string(), "batch2".to_string()]
+                    .into_iter()
+                    .collect()
+            )
+        );
+        assert_eq!(
+            query.status_types,
+            Some(vec!["Committed".to_string()].into_iter().collect())
+        );
+        assert_eq!(query.since, Some(UNIX_EPOCH + Duration::from_secs(10)));
+        assert_eq!(query.until, Some(UNIX_EPOCH + Duration::from_secs(20)));
+
+        // An empty request would select the entire history
+        assert!(to_batch_info_query(BatchInfoQueryRequest::default()).is_err());
+
+        // Status types must match the `statusType` of a batch status
+        assert!(to_batch_info_query(BatchInfoQueryRequest {
+            status_types: Some(vec!["committed".into()]),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
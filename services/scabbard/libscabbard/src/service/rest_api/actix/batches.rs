@@ -25,14 +25,23 @@ use splinter::{
 };
 
 use crate::protocol;
+#[cfg(feature = "observers")]
+use crate::service::error::ScabbardError;
+#[cfg(feature = "batch-idempotency")]
+use crate::service::BatchSubmission;
 use crate::service::{rest_api::resources::batches::BatchLinkResponse, Scabbard, SERVICE_TYPE};
 
+/// The header a client may use to identify a batch submission; if batches were already submitted
+/// with the same key, the original batches' status link is returned instead.
+#[cfg(feature = "batch-idempotency")]
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
 pub fn make_add_batches_to_queue_endpoint() -> ServiceEndpoint {
     ServiceEndpoint {
         service_type: SERVICE_TYPE.into(),
         route: "/batches".into(),
         method: Method::Post,
-        handler: Arc::new(move |_, payload, service| {
+        handler: Arc::new(move |_request, payload, service| {
             let scabbard = match service.as_any().downcast_ref::<Scabbard>() {
                 Some(s) => s,
                 None => {
@@ -46,6 +55,24 @@ pub fn make_add_batches_to_queue_endpoint() -> ServiceEndpoint {
             }
             .clone();
 
+            #[cfg(feature = "batch-idempotency")]
+            let idempotency_key = match _request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+                Some(header_value) => match header_value.to_str() {
+                    Ok(key) if !key.is_empty() => Some(key.to_string()),
+                    _ => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(&format!(
+                                    "Invalid {} header",
+                                    IDEMPOTENCY_KEY_HEADER
+                                )))
+                                .into_future(),
+                        )
+                    }
+                },
+                None => None,
+            };
+
             Box::new(
                 payload
                     .from_err::<ActixError>()
@@ -66,6 +93,45 @@ pub fn make_add_batches_to_queue_endpoint() -> ServiceEndpoint {
                             }
                         };
 
+                        #[cfg(feature = "batch-idempotency")]
+                        {
+                            if let Some(key) = idempotency_key {
+                                return match scabbard
+                                    .add_batches_with_idempotency_key(batches, &key)
+                                {
+                                    Ok(Some(BatchSubmission::Accepted { link })) => {
+                                        HttpResponse::Accepted()
+                                            .json(BatchLinkResponse::from(link.as_str()))
+                                            .into_future()
+                                    }
+                                    // Batches were already submitted with this key, so respond
+                                    // with their status link and 200 instead of 202
+                                    Ok(Some(BatchSubmission::Duplicate { link })) => {
+                                        HttpResponse::Ok()
+                                            .json(BatchLinkResponse::from(link.as_str()))
+                                            .into_future()
+                                    }
+                                    Ok(None) => HttpResponse::BadRequest()
+                                        .json(ErrorResponse::bad_request(
+                                            "No valid batches provided",
+                                        ))
+                                        .into_future(),
+                                    #[cfg(feature = "observers")]
+                                    Err(ScabbardError::ReadOnly) => HttpResponse::Forbidden()
+                                        .json(ErrorResponse::forbidden(
+                                            "Service is an observer and does not accept batches",
+                                        ))
+                                        .into_future(),
+                                    Err(err) => {
+                                        error!("Failed to add batches: {}", err);
+                                        HttpResponse::InternalServerError()
+                                            .json(ErrorResponse::internal_error())
+                                            .into_future()
+                                    }
+                                };
+                            }
+                        }
+
                         match scabbard.add_batches(batches) {
                             Ok(Some(link)) => HttpResponse::Accepted()
                                 .json(BatchLinkResponse::from(link.as_str()))
@@ -73,6 +139,12 @@ pub fn make_add_batches_to_queue_endpoint() -> ServiceEndpoint {
                             Ok(None) => HttpResponse::BadRequest()
                                 .json(ErrorResponse::bad_request("No valid batches provided"))
                                 .into_future(),
+                            #[cfg(feature = "observers")]
+                            Err(ScabbardError::ReadOnly) => HttpResponse::Forbidden()
+                                .json(ErrorResponse::forbidden(
+                                    "Service is an observer and does not accept batches",
+                                ))
+                                .into_future(),
                             Err(err) => {
                                 error!("Failed to add batches: {}", err);
                                 HttpResponse::InternalServerError()
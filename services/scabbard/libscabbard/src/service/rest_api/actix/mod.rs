@@ -17,4 +17,6 @@ pub mod batches;
 pub mod state;
 pub mod state_address;
 pub mod state_root;
+#[cfg(feature = "db-placement")]
+pub mod storage;
 pub mod ws_subscribe;
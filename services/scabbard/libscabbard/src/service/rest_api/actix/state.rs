@@ -18,12 +18,18 @@ use std::sync::Arc;
 use actix_web::{web, HttpResponse};
 use futures::IntoFuture;
 use splinter::{
-    rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard},
+    rest_api::{
+        paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET},
+        ErrorResponse, Method, ProtocolVersionRangeGuard,
+    },
     service::rest_api::ServiceEndpoint,
 };
 
 use crate::protocol;
-use crate::service::{rest_api::resources::state::StateEntryResponse, Scabbard, SERVICE_TYPE};
+use crate::service::{
+    rest_api::resources::state::{ListStateResponse, StateEntryResponse},
+    Scabbard, SERVICE_TYPE,
+};
 
 pub fn make_get_state_with_prefix_endpoint() -> ServiceEndpoint {
     ServiceEndpoint {
@@ -54,35 +60,155 @@ pub fn make_get_state_with_prefix_endpoint() -> ServiceEndpoint {
                     );
                 };
 
+            let offset = match query.get("offset") {
+                Some(value) => match value.parse::<usize>() {
+                    Ok(val) => val,
+                    Err(err) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(&format!(
+                                    "Invalid offset value passed: {}. Error: {}",
+                                    value, err
+                                )))
+                                .into_future(),
+                        )
+                    }
+                },
+                None => DEFAULT_OFFSET,
+            };
+
+            let limit = match query.get("limit") {
+                Some(value) => match value.parse::<usize>() {
+                    Ok(val) if val > 0 => val,
+                    Ok(_) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(
+                                    "Invalid limit value passed: must be greater than 0",
+                                ))
+                                .into_future(),
+                        )
+                    }
+                    Err(err) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(&format!(
+                                    "Invalid limit value passed: {}. Error: {}",
+                                    value, err
+                                )))
+                                .into_future(),
+                        )
+                    }
+                },
+                None => DEFAULT_LIMIT,
+            };
+
+            let protocol_version = match request.headers().get("SplinterProtocolVersion") {
+                Some(header_value) => match header_value.to_str() {
+                    Ok(protocol_version) => protocol_version.to_string(),
+                    Err(_) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(
+                                    "Unable to get SplinterProtocolVersion",
+                                ))
+                                .into_future(),
+                        )
+                    }
+                },
+                None => format!("{}", protocol::SCABBARD_PROTOCOL_VERSION),
+            };
+
             let prefix = query.get("prefix").map(String::as_str);
 
-            Box::new(match scabbard.get_state_with_prefix(prefix) {
-                Ok(state_iter) => {
-                    let res = state_iter.collect::<Result<Vec<_>, _>>();
-                    match res {
-                        Ok(entries) => HttpResponse::Ok()
-                            .json(
-                                entries
-                                    .iter()
-                                    .map(StateEntryResponse::from)
-                                    .collect::<Vec<_>>(),
-                            )
-                            .into_future(),
-                        Err(err) => {
-                            error!("Failed to consume state iterator: {}", err);
+            // Pin the state root for the whole listing so that pages are consistent with each
+            // other, even if new batches are committed while a client is paging through state
+            let state_root = match query.get("state_root") {
+                Some(state_root) => state_root.to_string(),
+                None => match scabbard.get_current_state_root() {
+                    Ok(state_root) => state_root,
+                    Err(err) => {
+                        error!("Failed to get current state root hash: {}", err);
+                        return Box::new(
                             HttpResponse::InternalServerError()
                                 .json(ErrorResponse::internal_error())
-                                .into_future()
-                        }
+                                .into_future(),
+                        );
                     }
+                },
+            };
+
+            let state_iter = match scabbard.get_state_with_prefix_at_root(prefix, &state_root) {
+                Ok(Some(state_iter)) => state_iter,
+                Ok(None) => {
+                    return Box::new(
+                        HttpResponse::NotFound()
+                            .json(ErrorResponse::not_found(&format!(
+                                "State root not found: {}",
+                                state_root
+                            )))
+                            .into_future(),
+                    )
                 }
                 Err(err) => {
                     error!("Failed to get state with prefix: {}", err);
-                    HttpResponse::InternalServerError()
-                        .json(ErrorResponse::internal_error())
-                        .into_future()
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
                 }
-            })
+            };
+
+            let entries = match state_iter.collect::<Result<Vec<_>, _>>() {
+                Ok(entries) => entries,
+                Err(err) => {
+                    error!("Failed to consume state iterator: {}", err);
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
+                }
+            };
+
+            Box::new(
+                match protocol_version.as_str() {
+                    // Version 1 returns all entries as a plain list
+                    "1" => HttpResponse::Ok().json(
+                        entries
+                            .iter()
+                            .map(StateEntryResponse::from)
+                            .collect::<Vec<_>>(),
+                    ),
+
+                    // Handles 2 (and catch all)
+                    _ => {
+                        let mut link =
+                            format!("{}?state_root={}&", request.uri().path(), state_root);
+                        if let Some(prefix) = prefix {
+                            link.push_str(&format!("prefix={}&", prefix));
+                        }
+
+                        HttpResponse::Ok().json(ListStateResponse {
+                            data: entries
+                                .iter()
+                                .skip(offset)
+                                .take(limit)
+                                .map(StateEntryResponse::from)
+                                .collect(),
+                            paging: get_response_paging_info(
+                                Some(limit),
+                                Some(offset),
+                                &link,
+                                entries.len(),
+                            ),
+                            state_root: &state_root,
+                        })
+                    }
+                }
+                .into_future(),
+            )
         }),
         request_guards: vec![Box::new(ProtocolVersionRangeGuard::new(
             protocol::SCABBARD_LIST_STATE_PROTOCOL_MIN,
@@ -121,7 +247,7 @@ mod tests {
     const MOCK_SERVICE_ID: &str = "ABCD";
     const TEMP_DB_SIZE: usize = 1 << 30; // 1024 ** 3
 
-    /// Verify that the `GET /state` endpoint works properly.
+    /// Verify that the `GET /state` endpoint works properly for protocol version 1 clients.
     ///
     /// 1. Initialize a temporary instance of `ScabbardState` and set some values in state; 2 with
     ///    a shared prefix, and 1 without.
@@ -154,6 +280,8 @@ mod tests {
                 &paths.receipt_db_path,
                 TEMP_DB_SIZE,
                 vec![],
+                #[cfg(feature = "db-auto-grow")]
+                None,
             )
             .expect("Failed to initialize state");
 
@@ -192,6 +320,8 @@ mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("Failed to create scabbard");
 
@@ -208,10 +338,7 @@ mod tests {
         let url = Url::parse(&base_url).expect("Failed to parse URL");
         let resp = Client::new()
             .get(url)
-            .header(
-                "SplinterProtocolVersion",
-                protocol::SCABBARD_PROTOCOL_VERSION,
-            )
+            .header("SplinterProtocolVersion", "1")
             .send()
             .expect("Failed to perform request");
         assert_eq!(resp.status(), StatusCode::OK);
@@ -251,10 +378,7 @@ mod tests {
             Url::parse(&format!("{}?prefix={}", base_url, prefix)).expect("Failed to parse URL");
         let resp = Client::new()
             .get(url)
-            .header(
-                "SplinterProtocolVersion",
-                protocol::SCABBARD_PROTOCOL_VERSION,
-            )
+            .header("SplinterProtocolVersion", "1")
             .send()
             .expect("Failed to perform request");
         assert_eq!(resp.status(), StatusCode::OK);
@@ -287,10 +411,7 @@ mod tests {
             .expect("Failed to parse URL");
         let resp = Client::new()
             .get(url)
-            .header(
-                "SplinterProtocolVersion",
-                protocol::SCABBARD_PROTOCOL_VERSION,
-            )
+            .header("SplinterProtocolVersion", "1")
             .send()
             .expect("Failed to perform request");
         assert_eq!(resp.status(), StatusCode::OK);
@@ -309,6 +430,174 @@ mod tests {
         join_handle.join().expect("Unable to join rest api thread");
     }
 
+    /// Verify that the `GET /state` endpoint pages its results and supports reading state at a
+    /// specific state root.
+    ///
+    /// 1. Initialize a temporary instance of `ScabbardState`, set 3 values under a shared prefix,
+    ///    and record the resulting state root. Then set a 4th value under the same prefix.
+    /// 2. Initialize an instance of the `Scabbard` service that's backed by the same underlying
+    ///    state and setup the REST API with the `GET /state` endpoint exposed.
+    /// 3. Make a request for the prefix with a limit of 2 and verify that the first 2 of the 4
+    ///    entries are returned, along with the paging info and the current state root.
+    /// 4. Request the next page using the returned state root and verify that the remaining 2
+    ///    entries are returned.
+    /// 5. Make a request for the prefix at the recorded state root and verify that only the 3
+    ///    entries set before that root are returned.
+    /// 6. Make a request with an unknown state root and verify that the response code is 404.
+    /// 7. Make a request with an invalid limit and verify that the response code is 400.
+    #[test]
+    fn state_with_paging_and_state_root() {
+        let paths = StatePaths::new("state_with_paging_and_state_root");
+
+        let prefix = "abcdef".to_string();
+        let entries = (1..=4)
+            .map(|i| {
+                (
+                    format!("{}{:02}", prefix, i),
+                    format!("value{}", i).into_bytes(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let old_state_root = {
+            let mut state = ScabbardState::new(
+                &paths.state_db_path,
+                TEMP_DB_SIZE,
+                &paths.receipt_db_path,
+                TEMP_DB_SIZE,
+                vec![],
+                #[cfg(feature = "db-auto-grow")]
+                None,
+            )
+            .expect("Failed to initialize state");
+
+            let signing_context = Secp256k1Context::new();
+            let signer = signing_context.new_signer(signing_context.new_random_private_key());
+            let mut set_state = |entries: &[(String, Vec<u8>)]| {
+                let batch = BatchBuilder::new()
+                    .with_transactions(vec![
+                        make_command_transaction(
+                            &[Command::SetState(SetState::new(
+                                entries
+                                    .iter()
+                                    .map(|(address, value)| {
+                                        BytesEntry::new(address.clone(), value.clone())
+                                    })
+                                    .collect(),
+                            ))],
+                            &*signer,
+                        )
+                        .take()
+                        .0,
+                    ])
+                    .build_pair(&*signer)
+                    .expect("Failed to build batch");
+                state
+                    .prepare_change(batch)
+                    .expect("Failed to prepare change");
+                state.commit().expect("Failed to commit change");
+                state.current_state_root().to_string()
+            };
+
+            let old_state_root = set_state(&entries[..3]);
+            set_state(&entries[3..]);
+            old_state_root
+        };
+
+        let scabbard = Scabbard::new(
+            MOCK_SERVICE_ID.into(),
+            MOCK_CIRCUIT_ID,
+            Default::default(),
+            paths.temp_dir.path(),
+            TEMP_DB_SIZE,
+            paths.temp_dir.path(),
+            TEMP_DB_SIZE,
+            Secp256k1Context::new().new_verifier(),
+            vec![],
+            None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("Failed to create scabbard");
+        let current_state_root = scabbard
+            .get_current_state_root()
+            .expect("Failed to get current state root");
+
+        let (shutdown_handle, join_handle, bind_url) =
+            run_rest_api_on_open_port(vec![resource_from_service_endpoint(
+                make_get_state_with_prefix_endpoint(),
+                Arc::new(Mutex::new(scabbard.clone())),
+            )]);
+
+        let base_url = format!("http://{}/state", bind_url);
+        let get = |query: &str| {
+            let url = Url::parse(&format!("{}?{}", base_url, query)).expect("Failed to parse URL");
+            Client::new()
+                .get(url)
+                .header(
+                    "SplinterProtocolVersion",
+                    protocol::SCABBARD_PROTOCOL_VERSION,
+                )
+                .send()
+                .expect("Failed to perform request")
+        };
+        let expected = |entries: &[(String, Vec<u8>)]| {
+            to_value(
+                entries
+                    .iter()
+                    .map(StateEntryResponse::from)
+                    .collect::<Vec<_>>(),
+            )
+            .expect("Failed to convert entries to JsonValue")
+        };
+
+        // Verify that the first page is returned along with the current state root
+        let resp = get(&format!("prefix={}&limit=2", prefix));
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp
+            .json::<JsonValue>()
+            .expect("Failed to deserialize body");
+        assert_eq!(body["data"], expected(&entries[..2]));
+        assert_eq!(body["paging"]["offset"], 0);
+        assert_eq!(body["paging"]["limit"], 2);
+        assert_eq!(body["paging"]["total"], 4);
+        assert_eq!(body["state_root"], current_state_root.as_str());
+
+        // Verify that the next page at the returned state root contains the remaining entries
+        let resp = get(&format!(
+            "prefix={}&limit=2&offset=2&state_root={}",
+            prefix, current_state_root
+        ));
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp
+            .json::<JsonValue>()
+            .expect("Failed to deserialize body");
+        assert_eq!(body["data"], expected(&entries[2..]));
+        assert_eq!(body["paging"]["offset"], 2);
+
+        // Verify that state can be read as of an older state root
+        let resp = get(&format!("prefix={}&state_root={}", prefix, old_state_root));
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp
+            .json::<JsonValue>()
+            .expect("Failed to deserialize body");
+        assert_eq!(body["data"], expected(&entries[..3]));
+        assert_eq!(body["paging"]["total"], 3);
+        assert_eq!(body["state_root"], old_state_root.as_str());
+
+        // Verify that an unknown state root results in a 404
+        let resp = get(&format!("state_root={}", "0".repeat(64)));
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // Verify that an invalid limit results in a 400
+        let resp = get("limit=0");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
     struct StatePaths {
         pub temp_dir: TempDir,
         pub state_db_path: PathBuf,
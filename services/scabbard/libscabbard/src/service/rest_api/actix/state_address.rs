@@ -120,6 +120,8 @@ mod tests {
                 &paths.receipt_db_path,
                 TEMP_DB_SIZE,
                 vec![],
+                #[cfg(feature = "db-auto-grow")]
+                None,
             )
             .expect("Failed to initialize state");
 
@@ -157,6 +159,8 @@ mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("Failed to create scabbard");
 
@@ -12,8 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "state-root-comparison")]
+use std::collections::HashMap;
 use std::sync::Arc;
+#[cfg(feature = "state-root-comparison")]
+use std::time::Duration;
 
+#[cfg(feature = "state-root-comparison")]
+use actix_web::web;
 use actix_web::HttpResponse;
 use futures::IntoFuture;
 use splinter::{
@@ -22,8 +28,13 @@ use splinter::{
 };
 
 use crate::protocol;
+#[cfg(feature = "state-root-comparison")]
+use crate::service::rest_api::resources::state_root::StateRootComparisonResponse;
 use crate::service::{Scabbard, SERVICE_TYPE};
 
+#[cfg(feature = "state-root-comparison")]
+const DEFAULT_STATE_ROOT_COMPARISON_TIMEOUT_SECS: u64 = 10;
+
 pub fn make_get_state_root_endpoint() -> ServiceEndpoint {
     ServiceEndpoint {
         service_type: SERVICE_TYPE.into(),
@@ -59,6 +70,73 @@ pub fn make_get_state_root_endpoint() -> ServiceEndpoint {
     }
 }
 
+#[cfg(feature = "state-root-comparison")]
+pub fn make_compare_state_roots_endpoint() -> ServiceEndpoint {
+    ServiceEndpoint {
+        service_type: SERVICE_TYPE.into(),
+        route: "/state_root/compare".into(),
+        method: Method::Post,
+        handler: Arc::new(move |req, _, service| {
+            let scabbard = match service.as_any().downcast_ref::<Scabbard>() {
+                Some(s) => s,
+                None => {
+                    error!("Failed to downcast to scabbard service");
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
+                }
+            };
+            let query: web::Query<HashMap<String, String>> =
+                if let Ok(q) = web::Query::from_query(req.query_string()) {
+                    q
+                } else {
+                    return Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("Invalid query"))
+                            .into_future(),
+                    );
+                };
+
+            let timeout = match query.get("timeout") {
+                Some(timeout) => match timeout.parse() {
+                    Ok(timeout) => timeout,
+                    Err(_) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(&format!(
+                                    "Invalid timeout: {}",
+                                    timeout
+                                )))
+                                .into_future(),
+                        )
+                    }
+                },
+                None => DEFAULT_STATE_ROOT_COMPARISON_TIMEOUT_SECS,
+            };
+
+            Box::new(
+                match scabbard.compare_state_roots(Duration::from_secs(timeout)) {
+                    Ok(comparison) => HttpResponse::Ok()
+                        .json(StateRootComparisonResponse::from(&comparison))
+                        .into_future(),
+                    Err(err) => {
+                        error!("Failed to compare state roots: {}", err);
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future()
+                    }
+                },
+            )
+        }),
+        request_guards: vec![Box::new(ProtocolVersionRangeGuard::new(
+            protocol::SCABBARD_COMPARE_STATE_ROOTS_PROTOCOL_MIN,
+            protocol::SCABBARD_PROTOCOL_VERSION,
+        ))],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +188,8 @@ mod tests {
                 &paths.receipt_db_path,
                 TEMP_DB_SIZE,
                 vec![],
+                #[cfg(feature = "db-auto-grow")]
+                None,
             )
             .expect("Failed to initialize state");
 
@@ -148,6 +228,8 @@ mod tests {
             Secp256k1Context::new().new_verifier(),
             vec![],
             None,
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("Failed to create scabbard");
 
@@ -0,0 +1,113 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use actix_web::HttpResponse;
+use futures::IntoFuture;
+use splinter::{
+    rest_api::{ErrorResponse, Method, ProtocolVersionRangeGuard},
+    service::rest_api::ServiceEndpoint,
+};
+
+use crate::protocol;
+use crate::service::rest_api::resources::storage::{DatabaseResponse, StorageResponse};
+#[cfg(feature = "db-auto-grow")]
+use crate::service::DbUsage;
+use crate::service::{Scabbard, SERVICE_TYPE};
+
+pub fn make_get_storage_endpoint() -> ServiceEndpoint {
+    ServiceEndpoint {
+        service_type: SERVICE_TYPE.into(),
+        route: "/storage".into(),
+        method: Method::Get,
+        handler: Arc::new(move |_, _, service| {
+            let scabbard = match service.as_any().downcast_ref::<Scabbard>() {
+                Some(s) => s,
+                None => {
+                    error!("Failed to downcast to scabbard service");
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
+                }
+            };
+
+            let (state_db_path, receipt_db_path) = scabbard.db_paths();
+            #[cfg(feature = "db-auto-grow")]
+            let (state_db_usage, receipt_db_usage) = match scabbard.db_usage() {
+                Ok(usage) => usage,
+                Err(err) => {
+                    error!("Failed to get usage of scabbard databases: {}", err);
+                    return Box::new(
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    );
+                }
+            };
+
+            Box::new(
+                match (
+                    database_response(
+                        state_db_path,
+                        #[cfg(feature = "db-auto-grow")]
+                        state_db_usage,
+                    ),
+                    database_response(
+                        receipt_db_path,
+                        #[cfg(feature = "db-auto-grow")]
+                        receipt_db_usage,
+                    ),
+                ) {
+                    (Ok(state_db), Ok(receipt_db)) => HttpResponse::Ok()
+                        .json(StorageResponse {
+                            state_db,
+                            receipt_db,
+                        })
+                        .into_future(),
+                    (Err(err), _) | (_, Err(err)) => {
+                        error!("Failed to get size of scabbard database: {}", err);
+                        HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future()
+                    }
+                },
+            )
+        }),
+        request_guards: vec![Box::new(ProtocolVersionRangeGuard::new(
+            protocol::SCABBARD_STORAGE_PROTOCOL_MIN,
+            protocol::SCABBARD_PROTOCOL_VERSION,
+        ))],
+    }
+}
+
+fn database_response(
+    path: &Path,
+    #[cfg(feature = "db-auto-grow")] usage: DbUsage,
+) -> Result<DatabaseResponse, std::io::Error> {
+    Ok(DatabaseResponse {
+        path: path.to_string_lossy().into(),
+        size: fs::metadata(path)?.len(),
+        #[cfg(feature = "db-auto-grow")]
+        map_size: usage.map_size,
+        #[cfg(feature = "db-auto-grow")]
+        max_map_size: usage.max_size,
+        #[cfg(feature = "db-auto-grow")]
+        used: usage.used,
+    })
+}
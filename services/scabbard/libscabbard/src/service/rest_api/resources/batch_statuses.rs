@@ -16,6 +16,17 @@ use std::time::SystemTime;
 
 use crate::service::state::{BatchInfo, BatchStatus, InvalidTransaction, ValidTransaction};
 
+/// The body of a `POST /batch_statuses/query` request; times are in seconds since the Unix epoch.
+#[cfg(feature = "batch-history")]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BatchInfoQueryRequest {
+    pub ids: Option<Vec<String>>,
+    pub status_types: Option<Vec<String>>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BatchInfoResponse<'a> {
     pub id: &'a str,
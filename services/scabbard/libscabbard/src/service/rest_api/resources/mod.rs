@@ -15,3 +15,7 @@
 pub mod batch_statuses;
 pub mod batches;
 pub mod state;
+#[cfg(feature = "state-root-comparison")]
+pub mod state_root;
+#[cfg(feature = "db-placement")]
+pub mod storage;
@@ -12,6 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use splinter::rest_api::paging::Paging;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListStateResponse<'a> {
+    pub data: Vec<StateEntryResponse<'a>>,
+    pub paging: Paging,
+    pub state_root: &'a str,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct StateEntryResponse<'a> {
     pub address: &'a str,
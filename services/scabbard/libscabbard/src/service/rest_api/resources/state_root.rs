@@ -0,0 +1,59 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::service::{MemberStateRoot, StateRootComparison};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateRootComparisonResponse<'a> {
+    pub state_root: &'a str,
+    /// Whether every member that responded has the same state root as this service
+    pub agreed: bool,
+    pub members: Vec<MemberStateRootResponse<'a>>,
+}
+
+impl<'a> From<&'a StateRootComparison> for StateRootComparisonResponse<'a> {
+    fn from(comparison: &'a StateRootComparison) -> Self {
+        Self {
+            state_root: &comparison.state_root,
+            agreed: comparison.is_agreed(),
+            members: comparison
+                .members
+                .iter()
+                .map(|member| MemberStateRootResponse::from_member(member, &comparison.state_root))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemberStateRootResponse<'a> {
+    pub service_id: &'a str,
+    /// `null` if the member did not respond before the comparison timed out
+    pub state_root: Option<&'a str>,
+    /// `null` if the member did not respond before the comparison timed out
+    pub agreed: Option<bool>,
+}
+
+impl<'a> MemberStateRootResponse<'a> {
+    fn from_member(member: &'a MemberStateRoot, state_root: &str) -> Self {
+        Self {
+            service_id: &member.service_id,
+            state_root: member.state_root.as_deref(),
+            agreed: member
+                .state_root
+                .as_ref()
+                .map(|member_root| member_root == state_root),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageResponse {
+    pub state_db: DatabaseResponse,
+    pub receipt_db: DatabaseResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DatabaseResponse {
+    pub path: String,
+    /// The size of the database file, in bytes
+    pub size: u64,
+    /// The size of the database's memory map, in bytes
+    #[cfg(feature = "db-auto-grow")]
+    pub map_size: u64,
+    /// The largest size the database's memory map may grow to, in bytes
+    #[cfg(feature = "db-auto-grow")]
+    pub max_map_size: u64,
+    /// The bytes used by the database's data
+    #[cfg(feature = "db-auto-grow")]
+    pub used: u64,
+}
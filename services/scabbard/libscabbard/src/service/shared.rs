@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "state-root-comparison")]
+use std::sync::mpsc::Sender;
 
 use cylinder::{PublicKey, Signature, Verifier as SignatureVerifier};
 use openssl::hash::{hash, MessageDigest};
@@ -24,7 +26,15 @@ use splinter::{consensus::ProposalId, service::ServiceNetworkSender};
 
 use crate::hex::parse_hex;
 
+#[cfg(feature = "commit-pause")]
+use super::commit_pause::CommitPause;
 use super::error::ScabbardError;
+#[cfg(feature = "service-lookup")]
+use super::lookup::ScabbardServiceLookup;
+#[cfg(feature = "batch-queue-monitor")]
+use super::queue_monitor::BatchQueueMonitor;
+#[cfg(feature = "state-root-comparison")]
+use super::state_root_comparison::StateRootMonitor;
 
 /// Data structure used to store information that's shared between components in this service
 pub struct ScabbardShared {
@@ -36,9 +46,31 @@ pub struct ScabbardShared {
     network_sender: Option<Box<dyn ServiceNetworkSender>>,
     /// List of service IDs that this service is configured to communicate and share state with.
     peer_services: HashSet<String>,
+    /// Peer services that are observers; committed batches are sent to these services.
+    #[cfg(feature = "observers")]
+    observer_services: HashSet<String>,
+    /// If this service is an observer, the services that send it committed batches.
+    #[cfg(feature = "observers")]
+    observed_services: Option<HashSet<String>>,
     /// Tracks which batches are currently being evaluated, indexed by corresponding proposal IDs.
     proposed_batches: HashMap<ProposalId, BatchPair>,
     signature_verifier: Box<dyn SignatureVerifier>,
+    /// Reports the depth of the batch queue, keyed by the service's circuit and service ID
+    #[cfg(feature = "batch-queue-monitor")]
+    batch_queue_monitor: Option<(BatchQueueMonitor, String, String)>,
+    /// Stops this service from committing batches while it is paused
+    #[cfg(feature = "commit-pause")]
+    commit_pause: Option<CommitPause>,
+    /// The lookup this service has been added to, if any
+    #[cfg(feature = "service-lookup")]
+    service_lookup: Option<ScabbardServiceLookup>,
+    /// The state root comparisons that are waiting for responses, keyed by request ID; responses
+    /// are sent as `(service_id, state_root)`
+    #[cfg(feature = "state-root-comparison")]
+    state_root_requests: HashMap<String, Sender<(String, String)>>,
+    /// Records the result of the state root comparisons started by this service
+    #[cfg(feature = "state-root-comparison")]
+    state_root_monitor: Option<StateRootMonitor>,
 }
 
 impl ScabbardShared {
@@ -52,17 +84,129 @@ impl ScabbardShared {
             batch_queue,
             network_sender,
             peer_services,
+            #[cfg(feature = "observers")]
+            observer_services: HashSet::new(),
+            #[cfg(feature = "observers")]
+            observed_services: None,
             proposed_batches: HashMap::new(),
             signature_verifier,
+            #[cfg(feature = "batch-queue-monitor")]
+            batch_queue_monitor: None,
+            #[cfg(feature = "commit-pause")]
+            commit_pause: None,
+            #[cfg(feature = "service-lookup")]
+            service_lookup: None,
+            #[cfg(feature = "state-root-comparison")]
+            state_root_requests: HashMap::new(),
+            #[cfg(feature = "state-root-comparison")]
+            state_root_monitor: None,
         }
     }
 
     pub fn add_batch_to_queue(&mut self, batch: BatchPair) {
-        self.batch_queue.push_back(batch)
+        self.batch_queue.push_back(batch);
+        #[cfg(feature = "batch-queue-monitor")]
+        self.report_batch_queue_depth();
     }
 
     pub fn pop_batch_from_queue(&mut self) -> Option<BatchPair> {
-        self.batch_queue.pop_front()
+        let batch = self.batch_queue.pop_front();
+        #[cfg(feature = "batch-queue-monitor")]
+        self.report_batch_queue_depth();
+        batch
+    }
+
+    #[cfg(feature = "batch-queue-monitor")]
+    pub fn set_batch_queue_monitor(
+        &mut self,
+        monitor: BatchQueueMonitor,
+        circuit_id: &str,
+        service_id: &str,
+    ) {
+        self.batch_queue_monitor = Some((monitor, circuit_id.into(), service_id.into()));
+        self.report_batch_queue_depth();
+    }
+
+    #[cfg(feature = "batch-queue-monitor")]
+    pub fn take_batch_queue_monitor(&mut self) -> Option<(BatchQueueMonitor, String, String)> {
+        self.batch_queue_monitor.take()
+    }
+
+    #[cfg(feature = "commit-pause")]
+    pub fn set_commit_pause(&mut self, pause: CommitPause) {
+        self.commit_pause = Some(pause);
+    }
+
+    /// Returns whether this service's commits are paused
+    #[cfg(feature = "commit-pause")]
+    pub fn commits_paused(&self) -> bool {
+        self.commit_pause
+            .as_ref()
+            .map(|pause| pause.is_paused())
+            .unwrap_or(false)
+    }
+
+    #[cfg(feature = "service-lookup")]
+    pub fn set_service_lookup(&mut self, lookup: ScabbardServiceLookup) {
+        self.service_lookup = Some(lookup);
+    }
+
+    #[cfg(feature = "service-lookup")]
+    pub fn take_service_lookup(&mut self) -> Option<ScabbardServiceLookup> {
+        self.service_lookup.take()
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn set_state_root_monitor(&mut self, monitor: StateRootMonitor) {
+        self.state_root_monitor = Some(monitor);
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn state_root_monitor(&self) -> Option<&StateRootMonitor> {
+        self.state_root_monitor.as_ref()
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn take_state_root_monitor(&mut self) -> Option<StateRootMonitor> {
+        self.state_root_monitor.take()
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn add_state_root_request(&mut self, request_id: String, sender: Sender<(String, String)>) {
+        self.state_root_requests.insert(request_id, sender);
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn state_root_request(&self, request_id: &str) -> Option<&Sender<(String, String)>> {
+        self.state_root_requests.get(request_id)
+    }
+
+    #[cfg(feature = "state-root-comparison")]
+    pub fn remove_state_root_request(&mut self, request_id: &str) {
+        self.state_root_requests.remove(request_id);
+    }
+
+    /// Returns every other scabbard service on the circuit, including observers and the services
+    /// this service observes.
+    #[cfg(feature = "state-root-comparison")]
+    pub fn circuit_members(&self) -> HashSet<String> {
+        #[allow(unused_mut)]
+        let mut members = self.peer_services.clone();
+        #[cfg(feature = "observers")]
+        {
+            members.extend(self.observer_services.iter().cloned());
+            if let Some(observed_services) = &self.observed_services {
+                members.extend(observed_services.iter().cloned());
+            }
+        }
+        members
+    }
+
+    #[cfg(feature = "batch-queue-monitor")]
+    fn report_batch_queue_depth(&self) {
+        if let Some((monitor, circuit_id, service_id)) = &self.batch_queue_monitor {
+            monitor.set_depth(circuit_id, service_id, self.batch_queue.len());
+        }
     }
 
     pub fn network_sender(&self) -> Option<&dyn ServiceNetworkSender> {
@@ -81,6 +225,53 @@ impl ScabbardShared {
         &self.peer_services
     }
 
+    /// Set which of the circuit's services are observers. Observers are removed from the peer
+    /// services, so they do not take part in consensus. If this service is itself an observer, it
+    /// has no peers for consensus; its former peers are the services that send it committed
+    /// batches instead.
+    #[cfg(feature = "observers")]
+    pub fn set_observers(&mut self, service_id: &str, observers: &HashSet<String>) {
+        if observers.contains(service_id) {
+            self.observed_services = Some(
+                self.peer_services
+                    .drain()
+                    .filter(|service| !observers.contains(service))
+                    .collect(),
+            );
+            self.observer_services.clear();
+        } else {
+            self.observer_services = self
+                .peer_services
+                .intersection(observers)
+                .cloned()
+                .collect();
+            self.peer_services
+                .retain(|service| !observers.contains(service));
+        }
+    }
+
+    /// Returns whether this service is an observer.
+    #[cfg(feature = "observers")]
+    pub fn is_observer(&self) -> bool {
+        self.observed_services.is_some()
+    }
+
+    /// Returns the peer services that are observers.
+    #[cfg(feature = "observers")]
+    pub fn observer_services(&self) -> &HashSet<String> {
+        &self.observer_services
+    }
+
+    /// Returns whether this service is an observer that receives committed batches from the given
+    /// service.
+    #[cfg(feature = "observers")]
+    pub fn is_observing(&self, service_id: &str) -> bool {
+        self.observed_services
+            .as_ref()
+            .map(|services| services.contains(service_id))
+            .unwrap_or(false)
+    }
+
     pub fn add_proposed_batch(
         &mut self,
         proposal_id: ProposalId,
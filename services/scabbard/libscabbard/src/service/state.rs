@@ -51,12 +51,16 @@ use transact::{
 };
 
 use crate::hex;
-use crate::protos::scabbard::{Setting, Setting_Entry};
+use crate::protos::scabbard::{self as scabbard_protos, Setting, Setting_Entry};
 
+#[cfg(feature = "db-auto-grow")]
+use super::db_growth::{DbGrowthPolicy, DbMonitor, DbUsage};
 use super::error::{ScabbardStateError, StateSubscriberError};
 
 const EXECUTION_TIMEOUT: u64 = 300; // five minutes
 const CURRENT_STATE_ROOT_INDEX: &str = "current_state_root";
+#[cfg(feature = "batch-history")]
+const BATCH_HISTORY_INDEX: &str = "batch_history";
 const ITER_CACHE_SIZE: usize = 64;
 const COMPLETED_BATCH_INFO_ITER_RETRY_MILLIS: u64 = 100;
 const DEFAULT_BATCH_HISTORY_SIZE: usize = 100;
@@ -73,6 +77,10 @@ pub struct ScabbardState {
     pending_changes: Option<(String, Vec<TransactionReceipt>)>,
     event_subscribers: Vec<Box<dyn StateSubscriber>>,
     batch_history: BatchHistory,
+    #[cfg(feature = "db-auto-grow")]
+    state_db_monitor: DbMonitor,
+    #[cfg(feature = "db-auto-grow")]
+    receipt_db_monitor: DbMonitor,
 }
 
 impl ScabbardState {
@@ -82,14 +90,26 @@ impl ScabbardState {
         receipt_db_path: &Path,
         receipt_db_size: usize,
         admin_keys: Vec<String>,
+        #[cfg(feature = "db-auto-grow")] db_growth_policy: Option<&DbGrowthPolicy>,
     ) -> Result<Self, ScabbardStateError> {
+        // Size the databases' maps according to their usage
+        #[cfg(feature = "db-auto-grow")]
+        let state_db_monitor =
+            DbMonitor::open("state", state_db_path, state_db_size, db_growth_policy);
+        #[cfg(feature = "db-auto-grow")]
+        let state_db_size = state_db_monitor.map_size();
+        #[cfg(feature = "db-auto-grow")]
+        let receipt_db_monitor = DbMonitor::open(
+            "receipt",
+            receipt_db_path,
+            receipt_db_size,
+            db_growth_policy,
+        );
+        #[cfg(feature = "db-auto-grow")]
+        let receipt_db_size = receipt_db_monitor.map_size();
+
         // Initialize the database
-        let mut indexes = INDEXES.to_vec();
-        indexes.push(CURRENT_STATE_ROOT_INDEX);
-        let db = Box::new(LmdbDatabase::new(
-            LmdbContext::new(state_db_path, indexes.len(), Some(state_db_size))?,
-            &indexes,
-        )?);
+        let db = Self::open_state_db(state_db_path, state_db_size)?;
 
         let current_state_root = if let Some(current_state_root) =
             Self::read_current_state_root(&*db)?
@@ -137,6 +157,11 @@ impl ScabbardState {
             .start()
             .map_err(|err| ScabbardStateError(format!("failed to start executor: {}", err)))?;
 
+        #[cfg(feature = "batch-history")]
+        let batch_history = BatchHistory::load(db.clone())?;
+        #[cfg(not(feature = "batch-history"))]
+        let batch_history = BatchHistory::new();
+
         Ok(ScabbardState {
             db,
             context_manager,
@@ -150,11 +175,31 @@ impl ScabbardState {
             ))),
             pending_changes: None,
             event_subscribers: vec![],
-            batch_history: BatchHistory::new(),
+            batch_history,
+            #[cfg(feature = "db-auto-grow")]
+            state_db_monitor,
+            #[cfg(feature = "db-auto-grow")]
+            receipt_db_monitor,
         })
     }
 
-    fn read_current_state_root(db: &dyn Database) -> Result<Option<String>, ScabbardStateError> {
+    pub(super) fn open_state_db(
+        state_db_path: &Path,
+        state_db_size: usize,
+    ) -> Result<Box<LmdbDatabase>, ScabbardStateError> {
+        let mut indexes = INDEXES.to_vec();
+        indexes.push(CURRENT_STATE_ROOT_INDEX);
+        #[cfg(feature = "batch-history")]
+        indexes.push(BATCH_HISTORY_INDEX);
+        Ok(Box::new(LmdbDatabase::new(
+            LmdbContext::new(state_db_path, indexes.len(), Some(state_db_size))?,
+            &indexes,
+        )?))
+    }
+
+    pub(super) fn read_current_state_root(
+        db: &dyn Database,
+    ) -> Result<Option<String>, ScabbardStateError> {
         db.get_reader()
             .and_then(|reader| reader.index_get(CURRENT_STATE_ROOT_INDEX, b"HEAD"))
             .map(|head| head.map(|bytes| hex::to_hex(&bytes)))
@@ -204,9 +249,40 @@ impl ScabbardState {
     pub fn get_state_with_prefix(
         &self,
         prefix: Option<&str>,
+    ) -> Result<StateIter, ScabbardStateError> {
+        self.leaves(prefix, &self.current_state_root)
+    }
+
+    /// Fetch a list of entries in state as of the given `state_root`, which may be any state root
+    /// that was committed by this service. Returns `None` if the state root is unknown.
+    pub fn get_state_with_prefix_at_root(
+        &self,
+        prefix: Option<&str>,
+        state_root: &str,
+    ) -> Result<Option<StateIter>, ScabbardStateError> {
+        let root_exists = self
+            .db
+            .get_reader()
+            .and_then(|reader| reader.get(state_root.as_bytes()))
+            .map_err(|err| {
+                ScabbardStateError(format!("Unable to read state root {}: {}", state_root, err))
+            })?
+            .is_some();
+
+        if root_exists {
+            self.leaves(prefix, state_root).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn leaves(
+        &self,
+        prefix: Option<&str>,
+        state_root: &str,
     ) -> Result<StateIter, ScabbardStateError> {
         Ok(Box::new(
-            MerkleRadixTree::new(self.db.clone(), Some(&self.current_state_root))?
+            MerkleRadixTree::new(self.db.clone(), Some(state_root))?
                 .leaves(prefix)
                 .or_else(|err| match err {
                     StateDatabaseError::NotFound(_) => Ok(Box::new(std::iter::empty())),
@@ -276,8 +352,11 @@ impl ScabbardState {
         match self.pending_changes.take() {
             Some((signature, txn_receipts)) => {
                 let state_changes = receipts_into_transact_state_changes(&txn_receipts)?;
-                self.current_state_root = MerkleState::new(self.db.clone())
-                    .commit(&self.current_state_root, &state_changes)?;
+                let result = MerkleState::new(self.db.clone())
+                    .commit(&self.current_state_root, &state_changes);
+                #[cfg(feature = "db-auto-grow")]
+                let result = result.map_err(|err| self.state_db_monitor.write_error(&err));
+                self.current_state_root = result?;
 
                 self.write_current_state_root()?;
 
@@ -293,7 +372,8 @@ impl ScabbardState {
                     .map(StateChangeEvent::try_from)
                     .collect::<Result<Vec<_>, _>>()?;
 
-                self.transaction_receipt_store
+                let result = self
+                    .transaction_receipt_store
                     .write()
                     .map_err(|err| {
                         ScabbardStateError(format!(
@@ -307,7 +387,16 @@ impl ScabbardState {
                             "failed to add transaction receipts to store: {}",
                             err
                         ))
-                    })?;
+                    });
+                #[cfg(feature = "db-auto-grow")]
+                let result = result.map_err(|err| self.receipt_db_monitor.write_error(&err));
+                result?;
+
+                #[cfg(feature = "db-auto-grow")]
+                {
+                    self.state_db_monitor.check();
+                    self.receipt_db_monitor.check();
+                }
 
                 for event in events {
                     self.event_subscribers.retain(|subscriber| {
@@ -342,10 +431,47 @@ impl ScabbardState {
         Ok(())
     }
 
+    /// Apply a batch that was committed by the services taking part in consensus. The batch is
+    /// only committed if it results in the given state root.
+    ///
+    /// Returns `false` without changing state if the current state root already is the given
+    /// state root, since the batch was applied before.
+    #[cfg(feature = "observers")]
+    pub fn apply_committed_batch(
+        &mut self,
+        batch: BatchPair,
+        state_root: &str,
+    ) -> Result<bool, ScabbardStateError> {
+        if self.current_state_root == state_root {
+            return Ok(false);
+        }
+
+        let resulting_state_root = self.prepare_change(batch)?;
+        if resulting_state_root != state_root {
+            self.rollback()?;
+            return Err(ScabbardStateError(format!(
+                "committed batch results in state root {} instead of {}; state is out of sync",
+                resulting_state_root, state_root
+            )));
+        }
+
+        self.commit()?;
+        Ok(true)
+    }
+
     pub fn batch_history(&mut self) -> &mut BatchHistory {
         &mut self.batch_history
     }
 
+    /// Get the size and usage of the state and receipt databases.
+    #[cfg(feature = "db-auto-grow")]
+    pub fn db_usage(&self) -> Result<(DbUsage, DbUsage), ScabbardStateError> {
+        Ok((
+            self.state_db_monitor.usage()?,
+            self.receipt_db_monitor.usage()?,
+        ))
+    }
+
     pub fn get_events_since(&self, event_id: Option<String>) -> Result<Events, ScabbardStateError> {
         Events::new(self.transaction_receipt_store.clone(), event_id)
     }
@@ -379,6 +505,20 @@ pub struct StateChangeEvent {
     pub state_changes: Vec<StateChange>,
 }
 
+impl StateChangeEvent {
+    pub fn into_proto(self) -> scabbard_protos::StateChangeEvent {
+        let mut proto = scabbard_protos::StateChangeEvent::new();
+        proto.set_id(self.id);
+        proto.set_state_changes(
+            self.state_changes
+                .into_iter()
+                .map(StateChange::into_proto)
+                .collect(),
+        );
+        proto
+    }
+}
+
 #[cfg(feature = "events")]
 impl ParseBytes<StateChangeEvent> for StateChangeEvent {
     fn from_bytes(bytes: &[u8]) -> Result<StateChangeEvent, ParseError> {
@@ -417,6 +557,24 @@ pub enum StateChange {
     Delete { key: String },
 }
 
+impl StateChange {
+    pub fn into_proto(self) -> scabbard_protos::StateChange {
+        let mut proto = scabbard_protos::StateChange::new();
+        match self {
+            StateChange::Set { key, value } => {
+                proto.set_change_type(scabbard_protos::StateChange_Type::SET);
+                proto.set_key(key);
+                proto.set_value(value);
+            }
+            StateChange::Delete { key } => {
+                proto.set_change_type(scabbard_protos::StateChange_Type::DELETE);
+                proto.set_key(key);
+            }
+        }
+        proto
+    }
+}
+
 impl fmt::Display for StateChange {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -612,11 +770,107 @@ impl BatchInfo {
     }
 }
 
+impl BatchStatus {
+    /// The name of the status, as used in the `statusType` field of its serialized form.
+    pub fn status_type(&self) -> &'static str {
+        match self {
+            BatchStatus::Unknown => "Unknown",
+            BatchStatus::Pending => "Pending",
+            BatchStatus::Invalid(_) => "Invalid",
+            BatchStatus::Valid(_) => "Valid",
+            BatchStatus::Committed(_) => "Committed",
+        }
+    }
+}
+
+/// Determines which batches are kept in a scabbard service's batch history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchHistoryRetention {
+    max_entries: usize,
+    max_age: Option<Duration>,
+}
+
+impl BatchHistoryRetention {
+    /// Keep at most `max_entries` batches; once the limit is reached, the oldest batch is
+    /// removed for each new batch.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            max_age: None,
+        }
+    }
+
+    /// Also remove batches that were submitted more than `max_age` ago.
+    #[cfg(feature = "batch-history")]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+impl Default for BatchHistoryRetention {
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_HISTORY_SIZE)
+    }
+}
+
+/// Selects batches from a scabbard service's batch history. Each criteria that is set must be
+/// matched for a batch to be selected.
+#[cfg(feature = "batch-history")]
+#[derive(Clone, Debug, Default)]
+pub struct BatchInfoQuery {
+    /// Only select the batches with these IDs; IDs that are not in the history are returned with
+    /// the `Unknown` status
+    pub ids: Option<HashSet<String>>,
+    /// Only select batches with one of these status types (see `BatchStatus::status_type`)
+    pub status_types: Option<HashSet<String>>,
+    /// Only select batches submitted at or after this time
+    pub since: Option<SystemTime>,
+    /// Only select batches submitted before this time
+    pub until: Option<SystemTime>,
+}
+
+#[cfg(feature = "batch-history")]
+impl BatchInfoQuery {
+    fn matches(&self, info: &BatchInfo) -> bool {
+        self.status_types
+            .as_ref()
+            .map(|types| types.contains(info.status.status_type()))
+            .unwrap_or(true)
+            && self
+                .since
+                .map(|since| info.timestamp >= since)
+                .unwrap_or(true)
+            && self
+                .until
+                .map(|until| info.timestamp < until)
+                .unwrap_or(true)
+    }
+}
+
+/// The form in which a `BatchInfo` is persisted; unlike the `BatchInfo` itself, this includes
+/// the timestamp.
+#[cfg(feature = "batch-history")]
+#[derive(Serialize, Deserialize)]
+struct StoredBatchInfo {
+    id: String,
+    status: BatchStatus,
+    timestamp: SystemTime,
+    #[cfg(feature = "batch-idempotency")]
+    #[serde(default)]
+    idempotency_key: Option<String>,
+}
+
 /// BatchHistory keeps track of batches submitted to scabbard
 pub struct BatchHistory {
     history: HashMap<String, BatchInfo>,
-    limit: usize,
+    retention: BatchHistoryRetention,
     batch_subscribers: Vec<(HashSet<String>, Sender<BatchInfo>)>,
+    #[cfg(feature = "batch-history")]
+    db: Option<Box<dyn Database>>,
+    /// The idempotency key each batch was submitted with, by batch ID
+    #[cfg(feature = "batch-idempotency")]
+    idempotency_keys: HashMap<String, String>,
 }
 
 impl BatchHistory {
@@ -624,10 +878,117 @@ impl BatchHistory {
         Self::default()
     }
 
+    /// Load the batch history that was persisted in the given database; all changes to the
+    /// history will be persisted to the database as well.
+    ///
+    /// Batches that had not completed when the history was persisted are loaded with the
+    /// `Unknown` status, since they were lost from scabbard's queue when it stopped.
+    #[cfg(feature = "batch-history")]
+    fn load(db: Box<dyn Database>) -> Result<Self, ScabbardStateError> {
+        #[cfg(feature = "batch-idempotency")]
+        let mut idempotency_keys = HashMap::new();
+
+        let reader = db.get_reader()?;
+        let history = reader
+            .index_cursor(BATCH_HISTORY_INDEX)?
+            .map(|(_, bytes)| {
+                let stored: StoredBatchInfo = serde_json::from_slice(&bytes).map_err(|err| {
+                    ScabbardStateError(format!("failed to deserialize batch info: {}", err))
+                })?;
+                let status = match stored.status {
+                    BatchStatus::Pending | BatchStatus::Valid(_) => BatchStatus::Unknown,
+                    status => status,
+                };
+                #[cfg(feature = "batch-idempotency")]
+                {
+                    if let Some(key) = stored.idempotency_key {
+                        idempotency_keys.insert(stored.id.clone(), key);
+                    }
+                }
+                Ok((
+                    stored.id.clone(),
+                    BatchInfo {
+                        id: stored.id,
+                        status,
+                        timestamp: stored.timestamp,
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, ScabbardStateError>>()?;
+        drop(reader);
+
+        debug!("Loaded {} batch(es) from batch history", history.len());
+
+        let mut batch_history = Self {
+            history,
+            db: Some(db),
+            #[cfg(feature = "batch-idempotency")]
+            idempotency_keys,
+            ..Self::default()
+        };
+        batch_history.prune();
+
+        Ok(batch_history)
+    }
+
+    /// Set which batches are kept in the history; batches that fall outside of the given
+    /// retention are removed immediately.
+    #[cfg(feature = "batch-history")]
+    pub fn set_retention(&mut self, retention: BatchHistoryRetention) {
+        self.retention = retention;
+        self.prune();
+    }
+
+    /// Get the `BatchInfo` of each batch that is selected by the given query, ordered by the time
+    /// the batches were submitted.
+    #[cfg(feature = "batch-history")]
+    pub fn query_batch_info(&mut self, query: &BatchInfoQuery) -> BatchInfoIter {
+        self.prune();
+
+        let mut infos = match &query.ids {
+            Some(ids) => self
+                .no_wait_batch_info_iter(ids)
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>(),
+            None => self.history.values().cloned().collect(),
+        };
+        infos.retain(|info| query.matches(info));
+        infos.sort_by_key(|info| info.timestamp);
+
+        Box::new(infos.into_iter().map(Ok))
+    }
+
     pub fn add_batch(&mut self, signature: &str) {
         self.upsert_batch(signature.into(), BatchStatus::Pending);
     }
 
+    /// Add a batch that was submitted with the given idempotency key.
+    #[cfg(feature = "batch-idempotency")]
+    pub fn add_batch_with_idempotency_key(&mut self, signature: &str, idempotency_key: &str) {
+        self.idempotency_keys
+            .insert(signature.into(), idempotency_key.into());
+        self.add_batch(signature);
+    }
+
+    /// Get the IDs of the batches that were submitted with the given idempotency key, if any are
+    /// still in the history.
+    #[cfg(feature = "batch-idempotency")]
+    pub fn batches_with_idempotency_key(&self, idempotency_key: &str) -> Option<Vec<String>> {
+        let mut ids = self
+            .idempotency_keys
+            .iter()
+            .filter(|(_, key)| key.as_str() == idempotency_key)
+            .filter_map(|(id, _)| self.history.get(id))
+            .collect::<Vec<_>>();
+
+        if ids.is_empty() {
+            None
+        } else {
+            ids.sort_by_key(|info| info.timestamp);
+            Some(ids.into_iter().map(|info| info.id.clone()).collect())
+        }
+    }
+
     fn update_batch_status(&mut self, signature: &str, status: BatchStatus) {
         let batch_info = self.upsert_batch(signature.into(), status);
 
@@ -644,6 +1005,11 @@ impl BatchHistory {
             Some(info) => match info.status.clone() {
                 BatchStatus::Valid(txns) => {
                     info.set_status(BatchStatus::Committed(txns));
+                    #[cfg(feature = "batch-history")]
+                    {
+                        let info = info.clone();
+                        self.persist(&info);
+                    }
                 }
                 _ => {
                     error!(
@@ -662,7 +1028,7 @@ impl BatchHistory {
     }
 
     fn upsert_batch(&mut self, signature: String, status: BatchStatus) -> BatchInfo {
-        match self.history.get_mut(&signature) {
+        let batch_info = match self.history.get_mut(&signature) {
             Some(info) => {
                 info.set_status(status);
                 info.clone()
@@ -675,17 +1041,99 @@ impl BatchHistory {
                 };
 
                 self.history.insert(signature, batch_info.clone());
-
-                if self.history.len() > self.limit {
-                    self.history
-                        .clone()
-                        .into_iter()
-                        .min_by_key(|(_, v)| v.timestamp)
-                        .and_then(|(k, _)| self.history.remove(&k));
-                }
+                self.prune();
 
                 batch_info
             }
+        };
+
+        #[cfg(feature = "batch-history")]
+        self.persist(&batch_info);
+
+        batch_info
+    }
+
+    /// Remove the batches that fall outside of the history's retention.
+    fn prune(&mut self) {
+        let mut expired = vec![];
+
+        if let Some(oldest_allowed) = self
+            .retention
+            .max_age
+            .and_then(|max_age| SystemTime::now().checked_sub(max_age))
+        {
+            expired.extend(
+                self.history
+                    .values()
+                    .filter(|info| info.timestamp < oldest_allowed)
+                    .map(|info| info.id.clone()),
+            );
+        }
+
+        let remaining = self.history.len() - expired.len();
+        if remaining > self.retention.max_entries {
+            let mut infos = self
+                .history
+                .values()
+                .filter(|info| !expired.contains(&info.id))
+                .collect::<Vec<_>>();
+            infos.sort_by_key(|info| info.timestamp);
+            expired.extend(
+                infos
+                    .into_iter()
+                    .take(remaining - self.retention.max_entries)
+                    .map(|info| info.id.clone()),
+            );
+        }
+
+        for id in expired {
+            self.history.remove(&id);
+            #[cfg(feature = "batch-idempotency")]
+            self.idempotency_keys.remove(&id);
+            #[cfg(feature = "batch-history")]
+            self.unpersist(&id);
+        }
+    }
+
+    /// Write the given batch info to the history's database, if it has one. Failures are logged,
+    /// since the in-memory history is still correct.
+    #[cfg(feature = "batch-history")]
+    fn persist(&self, info: &BatchInfo) {
+        if let Some(db) = &self.db {
+            let result = serde_json::to_vec(&StoredBatchInfo {
+                id: info.id.clone(),
+                status: info.status.clone(),
+                timestamp: info.timestamp,
+                #[cfg(feature = "batch-idempotency")]
+                idempotency_key: self.idempotency_keys.get(&info.id).cloned(),
+            })
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                let mut writer = db.get_writer().map_err(|err| err.to_string())?;
+                writer
+                    .index_put(BATCH_HISTORY_INDEX, info.id.as_bytes(), &bytes)
+                    .map_err(|err| err.to_string())?;
+                writer.commit().map_err(|err| err.to_string())
+            });
+
+            if let Err(err) = result {
+                error!("Unable to persist batch info for {}: {}", info.id, err);
+            }
+        }
+    }
+
+    /// Remove the batch with the given ID from the history's database, if it has one.
+    #[cfg(feature = "batch-history")]
+    fn unpersist(&self, id: &str) {
+        if let Some(db) = &self.db {
+            let result = db.get_writer().and_then(|mut writer| {
+                writer.index_delete(BATCH_HISTORY_INDEX, id.as_bytes())?;
+                writer.commit()
+            });
+
+            if let Err(err) = result {
+                error!("Unable to remove batch info for {}: {}", id, err);
+            }
         }
     }
 
@@ -773,8 +1221,12 @@ impl Default for BatchHistory {
     fn default() -> Self {
         Self {
             history: HashMap::new(),
-            limit: DEFAULT_BATCH_HISTORY_SIZE,
+            retention: BatchHistoryRetention::default(),
             batch_subscribers: vec![],
+            #[cfg(feature = "batch-history")]
+            db: None,
+            #[cfg(feature = "batch-idempotency")]
+            idempotency_keys: HashMap::new(),
         }
     }
 }
@@ -937,6 +1389,8 @@ mod tests {
             &paths.receipt_db_path,
             TEMP_DB_SIZE,
             vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("Failed to initialize state");
 
@@ -1003,6 +1457,8 @@ mod tests {
             &paths.receipt_db_path,
             TEMP_DB_SIZE,
             vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
         )
         .expect("Failed to initialize state");
 
@@ -1068,6 +1524,304 @@ mod tests {
         assert!(no_entries.is_empty());
     }
 
+    /// Verify that the `ScabbardState::get_state_with_prefix_at_root` method works properly.
+    ///
+    /// 1. Initialize a new, empty `ScabbardState`.
+    /// 2. Set a value in state and record the resulting state root, then set a second value.
+    /// 3. Call `get_state_with_prefix_at_root` with the recorded state root and verify that only
+    ///    the first value is returned.
+    /// 4. Call `get_state_with_prefix_at_root` with the current state root and verify that both
+    ///    values are returned.
+    /// 5. Call `get_state_with_prefix_at_root` with an unknown state root and verify that `None`
+    ///    is returned.
+    #[test]
+    fn get_state_with_prefix_at_root() {
+        let paths = StatePaths::new("get_state_with_prefix_at_root");
+        let mut state = ScabbardState::new(
+            &paths.state_db_path,
+            TEMP_DB_SIZE,
+            &paths.receipt_db_path,
+            TEMP_DB_SIZE,
+            vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("Failed to initialize state");
+
+        let prefix = "abcdef";
+        let entry1 = (format!("{}01", prefix), b"value1".to_vec());
+        let entry2 = (format!("{}02", prefix), b"value2".to_vec());
+
+        let signing_context = Secp256k1Context::new();
+        let signer = signing_context.new_signer(signing_context.new_random_private_key());
+        let set_state = |state: &mut ScabbardState, entry: &(String, Vec<u8>)| {
+            let batch = BatchBuilder::new()
+                .with_transactions(vec![
+                    make_command_transaction(
+                        &[Command::SetState(SetState::new(vec![BytesEntry::new(
+                            entry.0.clone(),
+                            entry.1.clone(),
+                        )]))],
+                        &*signer,
+                    )
+                    .take()
+                    .0,
+                ])
+                .build_pair(&*signer)
+                .expect("Failed to build batch");
+            state
+                .prepare_change(batch)
+                .expect("Failed to prepare change");
+            state.commit().expect("Failed to commit change");
+        };
+
+        set_state(&mut state, &entry1);
+        let old_state_root = state.current_state_root().to_string();
+        set_state(&mut state, &entry2);
+        let current_state_root = state.current_state_root().to_string();
+
+        let old_entries = state
+            .get_state_with_prefix_at_root(Some(prefix), &old_state_root)
+            .expect("Failed to get entries at old root")
+            .expect("Old root not found")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to collect entries at old root");
+        assert_eq!(old_entries, vec![entry1.clone()]);
+
+        let current_entries = state
+            .get_state_with_prefix_at_root(Some(prefix), &current_state_root)
+            .expect("Failed to get entries at current root")
+            .expect("Current root not found")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to collect entries at current root");
+        assert_eq!(current_entries, vec![entry1, entry2]);
+
+        assert!(state
+            .get_state_with_prefix_at_root(Some(prefix), &"0".repeat(64))
+            .expect("Failed to check unknown root")
+            .is_none());
+    }
+
+    /// Verify that an observer's state applies a batch committed by another service only if it
+    /// results in the expected state root, and that a batch that was already applied is ignored.
+    #[cfg(feature = "observers")]
+    #[test]
+    fn apply_committed_batch() {
+        let participant_paths = StatePaths::new("apply_committed_batch_participant");
+        let mut participant = ScabbardState::new(
+            &participant_paths.state_db_path,
+            TEMP_DB_SIZE,
+            &participant_paths.receipt_db_path,
+            TEMP_DB_SIZE,
+            vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("Failed to initialize participant state");
+        let observer_paths = StatePaths::new("apply_committed_batch_observer");
+        let mut observer = ScabbardState::new(
+            &observer_paths.state_db_path,
+            TEMP_DB_SIZE,
+            &observer_paths.receipt_db_path,
+            TEMP_DB_SIZE,
+            vec![],
+            #[cfg(feature = "db-auto-grow")]
+            None,
+        )
+        .expect("Failed to initialize observer state");
+
+        let signing_context = Secp256k1Context::new();
+        let signer = signing_context.new_signer(signing_context.new_random_private_key());
+        let make_batch = |address: &str| {
+            BatchBuilder::new()
+                .with_transactions(vec![
+                    make_command_transaction(
+                        &[Command::SetState(SetState::new(vec![BytesEntry::new(
+                            address.into(),
+                            b"value".to_vec(),
+                        )]))],
+                        &*signer,
+                    )
+                    .take()
+                    .0,
+                ])
+                .build_pair(&*signer)
+                .expect("Failed to build batch")
+        };
+
+        let batch = make_batch("abcdef01");
+        let state_root = participant
+            .prepare_change(batch.clone())
+            .expect("Failed to prepare change");
+        participant.commit().expect("Failed to commit change");
+
+        assert!(observer
+            .apply_committed_batch(batch.clone(), &state_root)
+            .expect("Failed to apply committed batch"));
+        assert_eq!(observer.current_state_root(), state_root);
+        assert_eq!(
+            observer
+                .get_state_at_address("abcdef01")
+                .expect("Failed to get state"),
+            Some(b"value".to_vec())
+        );
+
+        // The same batch, received from another service, is ignored
+        assert!(!observer
+            .apply_committed_batch(batch, &state_root)
+            .expect("Failed to apply committed batch again"));
+
+        // A batch that does not result in the expected state root is not applied
+        assert!(observer
+            .apply_committed_batch(make_batch("abcdef02"), &"0".repeat(64))
+            .is_err());
+        assert_eq!(observer.current_state_root(), state_root);
+        assert_eq!(
+            observer
+                .get_state_at_address("abcdef02")
+                .expect("Failed to get state"),
+            None
+        );
+    }
+
+    /// Verify that the batch history removes the oldest batches when it exceeds its maximum
+    /// number of entries, and batches that are older than its maximum age.
+    #[cfg(feature = "batch-history")]
+    #[test]
+    fn batch_history_retention() {
+        let mut history = BatchHistory::new();
+        history.set_retention(BatchHistoryRetention::new(2));
+
+        history.add_batch("batch1");
+        std::thread::sleep(Duration::from_millis(1));
+        history.add_batch("batch2");
+        std::thread::sleep(Duration::from_millis(1));
+        history.add_batch("batch3");
+
+        assert_eq!(history.history.len(), 2);
+        assert!(!history.history.contains_key("batch1"));
+
+        // Make batch2 older than the max age that will be set
+        history
+            .history
+            .get_mut("batch2")
+            .expect("batch2 not in history")
+            .timestamp = SystemTime::now() - Duration::from_secs(120);
+        history.set_retention(BatchHistoryRetention::new(2).with_max_age(Duration::from_secs(60)));
+
+        assert_eq!(history.history.len(), 1);
+        assert!(history.history.contains_key("batch3"));
+    }
+
+    /// Verify that the batch history is persisted to and correctly loaded from its database.
+    ///
+    /// 1. Load a batch history from an empty database.
+    /// 2. Add 2 batches, mark the first as valid and commit it.
+    /// 3. Load a new batch history from the same database and verify that the committed batch is
+    ///    still committed and the incomplete batch is loaded as unknown.
+    /// 4. Query the loaded history by status type and by ID and verify the results.
+    #[cfg(feature = "batch-history")]
+    #[test]
+    fn batch_history_persistence() {
+        let paths = StatePaths::new("batch_history_persistence");
+        let db: Box<dyn Database> = Box::new(
+            LmdbDatabase::new(
+                LmdbContext::new(&paths.state_db_path, 1, Some(TEMP_DB_SIZE))
+                    .expect("Failed to create LMDB context"),
+                &[BATCH_HISTORY_INDEX],
+            )
+            .expect("Failed to create LMDB database"),
+        );
+
+        let mut history = BatchHistory::load(db.clone()).expect("Failed to load empty history");
+        assert!(history.history.is_empty());
+
+        let txns = vec![ValidTransaction::new("txn1".into())];
+        history.add_batch("batch1");
+        history.add_batch("batch2");
+        history.update_batch_status("batch1", BatchStatus::Valid(txns.clone()));
+        history.commit("batch1");
+
+        let mut history = BatchHistory::load(db).expect("Failed to load history");
+        assert_eq!(history.history.len(), 2);
+        assert_eq!(
+            history.history["batch1"].status,
+            BatchStatus::Committed(txns.clone())
+        );
+        assert_eq!(history.history["batch2"].status, BatchStatus::Unknown);
+
+        let committed = history
+            .query_batch_info(&BatchInfoQuery {
+                status_types: Some(vec!["Committed".to_string()].into_iter().collect()),
+                ..Default::default()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to query committed batches");
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].id, "batch1");
+
+        let by_id = history
+            .query_batch_info(&BatchInfoQuery {
+                ids: Some(
+                    vec!["batch2".to_string(), "batch3".to_string()]
+                        .into_iter()
+                        .collect(),
+                ),
+                ..Default::default()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to query batches by ID");
+        assert_eq!(by_id.len(), 2);
+        assert!(by_id.iter().all(|info| info.status == BatchStatus::Unknown));
+
+        let none = history
+            .query_batch_info(&BatchInfoQuery {
+                since: Some(SystemTime::now() + Duration::from_secs(60)),
+                ..Default::default()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to query batches by time");
+        assert!(none.is_empty());
+    }
+
+    /// Verify that batches can be looked up by the idempotency key they were submitted with, that
+    /// the keys are persisted, and that the keys are removed along with their batches.
+    #[cfg(feature = "batch-idempotency")]
+    #[test]
+    fn batch_idempotency_keys() {
+        let paths = StatePaths::new("batch_idempotency_keys");
+        let db: Box<dyn Database> = Box::new(
+            LmdbDatabase::new(
+                LmdbContext::new(&paths.state_db_path, 1, Some(TEMP_DB_SIZE))
+                    .expect("Failed to create LMDB context"),
+                &[BATCH_HISTORY_INDEX],
+            )
+            .expect("Failed to create LMDB database"),
+        );
+
+        let mut history = BatchHistory::load(db.clone()).expect("Failed to load empty history");
+        history.add_batch_with_idempotency_key("batch1", "key1");
+        std::thread::sleep(Duration::from_millis(1));
+        history.add_batch("batch2");
+
+        assert_eq!(
+            history.batches_with_idempotency_key("key1"),
+            Some(vec!["batch1".to_string()])
+        );
+        assert_eq!(history.batches_with_idempotency_key("key2"), None);
+
+        let mut history = BatchHistory::load(db).expect("Failed to load history");
+        assert_eq!(
+            history.batches_with_idempotency_key("key1"),
+            Some(vec!["batch1".to_string()])
+        );
+
+        history.set_retention(BatchHistoryRetention::new(1));
+        assert!(!history.history.contains_key("batch1"));
+        assert_eq!(history.batches_with_idempotency_key("key1"), None);
+        assert!(history.idempotency_keys.is_empty());
+    }
+
     struct StatePaths {
         _temp_dir_handle: TempDir,
         pub state_db_path: PathBuf,
@@ -0,0 +1,204 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares the state roots of the scabbard services on a circuit.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// The state root reported by one of the other scabbard services on the circuit
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberStateRoot {
+    pub service_id: String,
+    /// `None` if the service did not respond before the comparison timed out
+    pub state_root: Option<String>,
+}
+
+/// The result of comparing a scabbard service's state root with the other services on its circuit
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateRootComparison {
+    pub circuit_id: String,
+    pub service_id: String,
+    /// The state root of the service that started the comparison
+    pub state_root: String,
+    /// The state roots of the other services, ordered by service ID
+    pub members: Vec<MemberStateRoot>,
+}
+
+impl StateRootComparison {
+    /// Builds the comparison from the state roots received from the given members; members that
+    /// did not respond are reported without a state root.
+    pub(super) fn new(
+        circuit_id: &str,
+        service_id: &str,
+        state_root: String,
+        member_ids: impl IntoIterator<Item = String>,
+        mut responses: HashMap<String, String>,
+    ) -> Self {
+        let mut members = member_ids
+            .into_iter()
+            .map(|member_id| MemberStateRoot {
+                state_root: responses.remove(&member_id),
+                service_id: member_id,
+            })
+            .collect::<Vec<_>>();
+        members.sort_by(|a, b| a.service_id.cmp(&b.service_id));
+
+        Self {
+            circuit_id: circuit_id.into(),
+            service_id: service_id.into(),
+            state_root,
+            members,
+        }
+    }
+
+    /// Returns the members whose state root differs from this service's
+    pub fn divergent_members(&self) -> Vec<&MemberStateRoot> {
+        self.members
+            .iter()
+            .filter(|member| match &member.state_root {
+                Some(state_root) => state_root != &self.state_root,
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Returns the members that did not respond before the comparison timed out
+    pub fn unresponsive_members(&self) -> Vec<&MemberStateRoot> {
+        self.members
+            .iter()
+            .filter(|member| member.state_root.is_none())
+            .collect()
+    }
+
+    /// Returns whether every member that responded has the same state root as this service
+    pub fn is_agreed(&self) -> bool {
+        self.divergent_members().is_empty()
+    }
+}
+
+/// Keeps the latest divergent comparison of every scabbard service it is given to.
+///
+/// A monitor is shared between the services created by a `ScabbardFactory`; each service records
+/// the result of every comparison it runs, so a divergence is kept until a later comparison finds
+/// the members in agreement again or the service is destroyed.
+#[derive(Clone, Default)]
+pub struct StateRootMonitor {
+    divergences: Arc<Mutex<BTreeMap<(String, String), StateRootComparison>>>,
+}
+
+impl StateRootMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the divergent comparisons, ordered by circuit and service ID
+    pub fn divergences(&self) -> Vec<StateRootComparison> {
+        match self.divergences.lock() {
+            Ok(divergences) => divergences.values().cloned().collect(),
+            Err(_) => {
+                error!("Unable to read state root divergences: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    pub(super) fn record(&self, comparison: &StateRootComparison) {
+        let key = (
+            comparison.circuit_id.to_string(),
+            comparison.service_id.to_string(),
+        );
+        match self.divergences.lock() {
+            Ok(mut divergences) => {
+                if comparison.is_agreed() {
+                    divergences.remove(&key);
+                } else {
+                    divergences.insert(key, comparison.clone());
+                }
+            }
+            Err(_) => error!("Unable to record state root comparison: lock poisoned"),
+        }
+    }
+
+    pub(super) fn remove(&self, circuit_id: &str, service_id: &str) {
+        match self.divergences.lock() {
+            Ok(mut divergences) => {
+                divergences.remove(&(circuit_id.to_string(), service_id.to_string()));
+            }
+            Err(_) => error!("Unable to remove state root divergence: lock poisoned"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a comparison reports the members that diverge and the members that did not
+    /// respond, and that the monitor only keeps divergent comparisons.
+    #[test]
+    fn compare_and_record() {
+        let mut responses = HashMap::new();
+        responses.insert("b".to_string(), "root".to_string());
+        responses.insert("c".to_string(), "other".to_string());
+
+        let comparison = StateRootComparison::new(
+            "circuit",
+            "a",
+            "root".into(),
+            vec!["d".to_string(), "c".to_string(), "b".to_string()],
+            responses,
+        );
+        assert_eq!(
+            comparison
+                .members
+                .iter()
+                .map(|member| member.service_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c", "d"]
+        );
+        assert!(!comparison.is_agreed());
+        assert_eq!(
+            comparison.divergent_members(),
+            vec![&MemberStateRoot {
+                service_id: "c".into(),
+                state_root: Some("other".into()),
+            }]
+        );
+        assert_eq!(
+            comparison.unresponsive_members(),
+            vec![&MemberStateRoot {
+                service_id: "d".into(),
+                state_root: None,
+            }]
+        );
+
+        let monitor = StateRootMonitor::new();
+        monitor.record(&comparison);
+        assert_eq!(monitor.divergences(), vec![comparison]);
+
+        let mut responses = HashMap::new();
+        responses.insert("b".to_string(), "root".to_string());
+        let agreed = StateRootComparison::new(
+            "circuit",
+            "a",
+            "root".into(),
+            vec!["b".to_string(), "d".to_string()],
+            responses,
+        );
+        assert!(agreed.is_agreed());
+        monitor.record(&agreed);
+        assert!(monitor.divergences().is_empty());
+    }
+}
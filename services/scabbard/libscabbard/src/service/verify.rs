@@ -0,0 +1,194 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of a scabbard service's state against its transaction receipts.
+//!
+//! Every transaction a scabbard service commits is recorded in its receipt store, along with the
+//! state changes it made. Replaying those state changes against a fresh state tree must result
+//! in the state root the service's state database is on; a different state root means that the
+//! state or receipt database was corrupted, or that the service's state diverged from what was
+//! committed.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+use sawtooth::store::{lmdb::LmdbOrderedStore, receipt_store::TransactionReceiptStore};
+use sawtooth_sabre::ADMINISTRATORS_SETTING_ADDRESS;
+use transact::{
+    database::{btree::BTreeDatabase, Database},
+    state::{
+        merkle::{MerkleRadixTree, MerkleState, INDEXES},
+        StateChange, Write,
+    },
+};
+
+use super::compute_db_paths;
+use super::error::{ScabbardError, ScabbardStateError};
+use super::state::ScabbardState;
+
+/// The outcome of replaying a scabbard service's transaction receipts
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateVerification {
+    /// The state root the service's state database is on
+    pub current_state_root: String,
+    /// The state root that results from replaying the service's transaction receipts
+    pub replayed_state_root: String,
+    /// The number of transaction receipts that were replayed
+    pub receipt_count: usize,
+}
+
+impl StateVerification {
+    /// Returns whether replaying the receipts results in the service's current state root.
+    pub fn is_verified(&self) -> bool {
+        self.current_state_root == self.replayed_state_root
+    }
+}
+
+/// Replay the transactions committed by a scabbard service, as recorded in its receipt store,
+/// against a fresh state tree and compare the resulting state root with the service's current
+/// state root.
+///
+/// The databases are found in the given directories the same way the service finds them. The
+/// service's initial state, which only holds the administrators setting, is taken from its
+/// current state. Since the databases are read one after the other, the service should not be
+/// committing batches while it is verified.
+pub fn verify_state(
+    circuit_id: &str,
+    service_id: &str,
+    state_db_dir: &Path,
+    state_db_size: usize,
+    receipt_db_dir: &Path,
+    receipt_db_size: usize,
+) -> Result<StateVerification, ScabbardError> {
+    let (state_db_path, receipt_db_path) =
+        compute_db_paths(service_id, circuit_id, state_db_dir, receipt_db_dir)?;
+    // Opening a database that does not exist would create it
+    for path in &[&state_db_path, &receipt_db_path] {
+        if !path.exists() {
+            return Err(ScabbardError::InitializationFailed(Box::new(
+                ScabbardStateError(format!("database {} does not exist", path.display())),
+            )));
+        }
+    }
+
+    let db = ScabbardState::open_state_db(&state_db_path, state_db_size)?;
+    let current_state_root = ScabbardState::read_current_state_root(&*db)?.ok_or_else(|| {
+        ScabbardStateError(format!(
+            "state database {} has no current state root",
+            state_db_path.display()
+        ))
+    })?;
+    let admin_setting = MerkleRadixTree::new(db, Some(&current_state_root))?
+        .get_value(ADMINISTRATORS_SETTING_ADDRESS)?;
+
+    let receipt_store = TransactionReceiptStore::new(Box::new(
+        LmdbOrderedStore::new(&receipt_db_path, Some(receipt_db_size))
+            .map_err(|err| ScabbardStateError(err.to_string()))?,
+    ));
+
+    // Apply the state changes of each receipt in the order they were committed; only the last
+    // value of each address affects the resulting state root
+    let mut entries = BTreeMap::new();
+    if let Some(admin_setting) = admin_setting {
+        entries.insert(
+            ADMINISTRATORS_SETTING_ADDRESS.to_string(),
+            Some(admin_setting),
+        );
+    }
+    let mut receipt_count = 0;
+    for receipt in receipt_store.iter().map_err(|err| {
+        ScabbardStateError(format!(
+            "failed to get transaction receipts from store: {}",
+            err
+        ))
+    })? {
+        let state_changes = Vec::<StateChange>::try_from(receipt)
+            .map_err(|err| ScabbardStateError(err.to_string()))?;
+        for state_change in state_changes {
+            match state_change {
+                StateChange::Set { key, value } => entries.insert(key, Some(value)),
+                StateChange::Delete { key } => entries.insert(key, None),
+            };
+        }
+        receipt_count += 1;
+    }
+
+    Ok(StateVerification {
+        current_state_root,
+        replayed_state_root: replay(entries)?,
+        receipt_count,
+    })
+}
+
+/// Write the given entries to a fresh, in-memory state tree and return its state root. Deleted
+/// entries are skipped, since the fresh tree does not contain them.
+fn replay(entries: BTreeMap<String, Option<Vec<u8>>>) -> Result<String, ScabbardStateError> {
+    let db: Box<dyn Database> = Box::new(BTreeDatabase::new(&INDEXES));
+    let initial_state_root = MerkleRadixTree::new(db.clone_box(), None)?.get_merkle_root();
+    let state_changes = entries
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| StateChange::Set { key, value }))
+        .collect::<Vec<_>>();
+
+    Ok(MerkleState::new(db).commit(&initial_state_root, &state_changes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that replaying entries results in the same state root as committing the state
+    /// changes they came from one after the other, including deleted entries.
+    #[test]
+    fn replay_entries() {
+        let db: Box<dyn Database> = Box::new(BTreeDatabase::new(&INDEXES));
+        let mut state_root = MerkleRadixTree::new(db.clone_box(), None)
+            .expect("Failed to create tree")
+            .get_merkle_root();
+        let commits = vec![
+            vec![set("abcdef00", b"1"), set("abcdef01", b"2")],
+            vec![set("abcdef00", b"3"), delete("abcdef01")],
+            vec![set("abcdef02", b"4")],
+        ];
+
+        let mut entries = BTreeMap::new();
+        for state_changes in commits {
+            state_root = MerkleState::new(db.clone())
+                .commit(&state_root, &state_changes)
+                .expect("Failed to commit");
+            for state_change in state_changes {
+                match state_change {
+                    StateChange::Set { key, value } => entries.insert(key, Some(value)),
+                    StateChange::Delete { key } => entries.insert(key, None),
+                };
+            }
+        }
+
+        assert_eq!(replay(entries).expect("Failed to replay"), state_root);
+    }
+
+    fn set(key: &str, value: &[u8]) -> StateChange {
+        StateChange::Set {
+            key: key.to_string(),
+            value: value.to_vec(),
+        }
+    }
+
+    fn delete(key: &str) -> StateChange {
+        StateChange::Delete {
+            key: key.to_string(),
+        }
+    }
+}
@@ -0,0 +1,636 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alerting on node events.
+//!
+//! Operators define alert rules in the config file. The [`AlertEngine`] periodically evaluates
+//! the rules against the state of the node; when a rule trips for a subject (a peer, a circuit
+//! proposal, a scabbard service, or a resource) an alert starts firing and a notification is sent
+//! to every configured sink. Another notification is sent when the condition clears.
+//!
+//! [`AlertEngine`]: struct.AlertEngine.html
+
+mod sink;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use scabbard::service::BatchQueueMonitor;
+#[cfg(feature = "scabbard-state-root-comparison")]
+use scabbard::service::{StateRootComparison, StateRootMonitor};
+use splinter::admin::store::AdminServiceStore;
+#[cfg(feature = "peer-identity-pinning")]
+use splinter::network::auth::pinning::IdentityPinVerifier;
+use splinter::peer::{PeerManagerConnector, PeerManagerNotification};
+
+#[cfg(feature = "resource-watchdog")]
+use crate::resource_watchdog::ResourceStatus;
+
+pub use sink::{AlertError, AlertSink, AlertSinkConfig, EmailSink, LogSink, WebhookSink};
+
+/// How often the alert rules are evaluated
+pub const DEFAULT_EVALUATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The condition that trips an alert rule
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// A peer has been disconnected for more than the given number of minutes
+    PeerDisconnected { minutes: u64 },
+    /// A circuit proposal has been pending for more than the given number of hours
+    ProposalPending { hours: u64 },
+    /// A scabbard service has more than the given number of batches waiting to be proposed
+    ScabbardQueueDepth { depth: usize },
+    /// A connection claimed a peer's identity with a certificate other than the one the identity
+    /// is pinned to within the given number of minutes
+    #[cfg(feature = "peer-identity-pinning")]
+    PeerIdentityMismatch { minutes: u64 },
+    /// The resource watchdog found a resource, such as the free space of a state directory, that
+    /// has run low
+    #[cfg(feature = "resource-watchdog")]
+    ResourcePressure,
+    /// A state root comparison found a scabbard service whose state root differs from another
+    /// service on its circuit
+    #[cfg(feature = "scabbard-state-root-comparison")]
+    ScabbardStateDivergence,
+}
+
+/// An alert rule, as defined in the `[[alert_rules]]` tables of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+}
+
+/// An alert that is currently firing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Alert {
+    /// The name of the rule that tripped
+    pub rule: String,
+    /// The peer, circuit, service or resource the rule tripped for
+    pub subject: String,
+    pub message: String,
+    /// When the alert started firing, in seconds since the Unix epoch
+    pub since: u64,
+}
+
+/// Whether a notification reports a new alert or one that has cleared
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+/// The state of the node that the alert rules are evaluated against
+#[derive(Default)]
+struct Observations {
+    /// Disconnected peers, with the time each one disconnected
+    disconnected_peers: Vec<(String, Instant)>,
+    /// Pending circuit proposals, with the time each one was first seen
+    pending_proposals: Vec<(String, Instant)>,
+    /// The batch queue depth of each scabbard service, keyed by `circuit_id::service_id`
+    queue_depths: Vec<(String, usize)>,
+    /// Peer identities claimed with an unpinned certificate, with the time of the latest attempt
+    #[cfg(feature = "peer-identity-pinning")]
+    identity_mismatches: Vec<(String, Instant)>,
+    /// The resources the resource watchdog found problems with, with the problem's message
+    #[cfg(feature = "resource-watchdog")]
+    resource_problems: Vec<(String, String)>,
+    /// The scabbard services whose latest state root comparison found a divergence, keyed by
+    /// `circuit_id::service_id`, with a description of the divergence
+    #[cfg(feature = "scabbard-state-root-comparison")]
+    state_divergences: Vec<(String, String)>,
+}
+
+/// Returns the `(subject, message)` of every alert that the given rule trips
+fn evaluate(rule: &AlertRule, observations: &Observations, now: Instant) -> Vec<(String, String)> {
+    match rule.condition {
+        AlertCondition::PeerDisconnected { minutes } => observations
+            .disconnected_peers
+            .iter()
+            .filter(|(_, since)| now.duration_since(*since) > Duration::from_secs(minutes * 60))
+            .map(|(peer_id, _)| {
+                (
+                    peer_id.to_string(),
+                    format!(
+                        "Peer {} has been disconnected for more than {} minute(s)",
+                        peer_id, minutes
+                    ),
+                )
+            })
+            .collect(),
+        AlertCondition::ProposalPending { hours } => observations
+            .pending_proposals
+            .iter()
+            .filter(|(_, since)| now.duration_since(*since) > Duration::from_secs(hours * 3600))
+            .map(|(circuit_id, _)| {
+                (
+                    circuit_id.to_string(),
+                    format!(
+                        "Proposal for circuit {} has been pending for more than {} hour(s)",
+                        circuit_id, hours
+                    ),
+                )
+            })
+            .collect(),
+        AlertCondition::ScabbardQueueDepth { depth } => observations
+            .queue_depths
+            .iter()
+            .filter(|(_, queued)| *queued > depth)
+            .map(|(service, queued)| {
+                (
+                    service.to_string(),
+                    format!(
+                        "Scabbard service {} has {} queued batches (more than {})",
+                        service, queued, depth
+                    ),
+                )
+            })
+            .collect(),
+        #[cfg(feature = "peer-identity-pinning")]
+        AlertCondition::PeerIdentityMismatch { minutes } => observations
+            .identity_mismatches
+            .iter()
+            .filter(|(_, at)| now.duration_since(*at) <= Duration::from_secs(minutes * 60))
+            .map(|(peer_id, _)| {
+                (
+                    peer_id.to_string(),
+                    format!(
+                        "A connection claimed peer {} with a certificate other than its pinned \
+                         certificate in the last {} minute(s)",
+                        peer_id, minutes
+                    ),
+                )
+            })
+            .collect(),
+        #[cfg(feature = "resource-watchdog")]
+        AlertCondition::ResourcePressure => observations.resource_problems.clone(),
+        #[cfg(feature = "scabbard-state-root-comparison")]
+        AlertCondition::ScabbardStateDivergence => observations.state_divergences.clone(),
+    }
+}
+
+/// The alerts that are currently firing, shared between the `AlertEngine` and the REST API
+#[derive(Clone, Default)]
+pub struct AlertState {
+    alerts: Arc<Mutex<BTreeMap<(String, String), Alert>>>,
+}
+
+impl AlertState {
+    /// Returns the alerts that are currently firing, ordered by rule and subject
+    pub fn list_alerts(&self) -> Vec<Alert> {
+        match self.alerts.lock() {
+            Ok(alerts) => alerts.values().cloned().collect(),
+            Err(_) => {
+                error!("Unable to list alerts: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    /// Replaces the firing alerts of the given rule, returning the alerts that started firing and
+    /// the alerts that cleared.
+    fn update(&self, rule: &str, tripped: Vec<(String, String)>) -> (Vec<Alert>, Vec<Alert>) {
+        let mut alerts = match self.alerts.lock() {
+            Ok(alerts) => alerts,
+            Err(_) => {
+                error!("Unable to update alerts: lock poisoned");
+                return (vec![], vec![]);
+            }
+        };
+
+        let tripped: HashMap<String, String> = tripped.into_iter().collect();
+
+        let cleared_keys = alerts
+            .keys()
+            .filter(|(alert_rule, subject)| alert_rule == rule && !tripped.contains_key(subject))
+            .cloned()
+            .collect::<Vec<_>>();
+        let cleared = cleared_keys
+            .iter()
+            .filter_map(|key| alerts.remove(key))
+            .collect();
+
+        let mut fired = vec![];
+        for (subject, message) in tripped {
+            let key = (rule.to_string(), subject.clone());
+            if !alerts.contains_key(&key) {
+                let alert = Alert {
+                    rule: rule.to_string(),
+                    subject,
+                    message,
+                    since: now_secs(),
+                };
+                alerts.insert(key, alert.clone());
+                fired.push(alert);
+            }
+        }
+
+        (fired, cleared)
+    }
+}
+
+enum EngineMessage {
+    Peer(PeerManagerNotification),
+    Shutdown,
+}
+
+impl From<PeerManagerNotification> for EngineMessage {
+    fn from(notification: PeerManagerNotification) -> Self {
+        EngineMessage::Peer(notification)
+    }
+}
+
+/// Evaluates the alert rules and notifies the sinks when alerts fire or clear.
+pub struct AlertEngine {
+    join_handle: thread::JoinHandle<()>,
+    shutdown_signaler: AlertEngineShutdownSignaler,
+}
+
+impl AlertEngine {
+    /// Starts the engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules to evaluate
+    /// * `sinks` - Where notifications are sent
+    /// * `state` - Where the firing alerts are kept
+    /// * `peer_connector` - Used to track when peers disconnect
+    /// * `admin_store` - Used to find pending circuit proposals
+    /// * `batch_queue_monitor` - Used to read the scabbard batch queue depths
+    /// * `identity_pin_verifier` - Used to read the peer identity mismatches, if pinning is enabled
+    /// * `resource_status` - Used to read the problems found by the resource watchdog
+    /// * `state_root_monitor` - Used to read the divergences found by scabbard state root
+    ///   comparisons
+    /// * `interval` - How often the rules are evaluated
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        rules: Vec<AlertRule>,
+        sinks: Vec<Box<dyn AlertSink>>,
+        state: AlertState,
+        peer_connector: &PeerManagerConnector,
+        admin_store: Box<dyn AdminServiceStore>,
+        batch_queue_monitor: BatchQueueMonitor,
+        #[cfg(feature = "peer-identity-pinning")] identity_pin_verifier: Option<
+            IdentityPinVerifier,
+        >,
+        #[cfg(feature = "resource-watchdog")] resource_status: ResourceStatus,
+        #[cfg(feature = "scabbard-state-root-comparison")] state_root_monitor: StateRootMonitor,
+        interval: Duration,
+    ) -> Result<Self, AlertError> {
+        let (sender, receiver) = channel();
+        peer_connector
+            .subscribe_sender::<EngineMessage>(sender.clone())
+            .map_err(|err| {
+                AlertError(format!(
+                    "unable to subscribe to peer notifications: {}",
+                    err
+                ))
+            })?;
+
+        let join_handle = thread::Builder::new()
+            .name("AlertEngine".into())
+            .spawn(move || {
+                let mut disconnected_peers: HashMap<String, Instant> = HashMap::new();
+                let mut pending_proposals: HashMap<String, Instant> = HashMap::new();
+                let mut next_evaluation = Instant::now() + interval;
+
+                loop {
+                    let timeout = next_evaluation.saturating_duration_since(Instant::now());
+                    match receiver.recv_timeout(timeout) {
+                        Ok(EngineMessage::Peer(PeerManagerNotification::Disconnected { peer })) => {
+                            disconnected_peers.entry(peer).or_insert_with(Instant::now);
+                        }
+                        Ok(EngineMessage::Peer(PeerManagerNotification::Connected { peer }))
+                        | Ok(EngineMessage::Peer(PeerManagerNotification::Removed { peer })) => {
+                            disconnected_peers.remove(&peer);
+                        }
+                        Ok(EngineMessage::Peer(PeerManagerNotification::EndpointsUpdated {
+                            ..
+                        })) => (),
+                        Err(RecvTimeoutError::Timeout) => {
+                            next_evaluation = Instant::now() + interval;
+
+                            match admin_store.list_proposals(&[]) {
+                                Ok(proposals) => {
+                                    let circuit_ids = proposals
+                                        .map(|proposal| proposal.circuit_id().to_string())
+                                        .collect::<Vec<_>>();
+                                    pending_proposals
+                                        .retain(|circuit_id, _| circuit_ids.contains(circuit_id));
+                                    for circuit_id in circuit_ids {
+                                        pending_proposals
+                                            .entry(circuit_id)
+                                            .or_insert_with(Instant::now);
+                                    }
+                                }
+                                Err(err) => error!("Unable to list proposals for alerts: {}", err),
+                            }
+
+                            let observations = Observations {
+                                disconnected_peers: disconnected_peers
+                                    .iter()
+                                    .map(|(peer, since)| (peer.to_string(), *since))
+                                    .collect(),
+                                pending_proposals: pending_proposals
+                                    .iter()
+                                    .map(|(circuit_id, since)| (circuit_id.to_string(), *since))
+                                    .collect(),
+                                queue_depths: batch_queue_monitor
+                                    .depths()
+                                    .into_iter()
+                                    .map(|queue| {
+                                        (
+                                            format!("{}::{}", queue.circuit_id, queue.service_id),
+                                            queue.depth,
+                                        )
+                                    })
+                                    .collect(),
+                                #[cfg(feature = "peer-identity-pinning")]
+                                identity_mismatches: identity_pin_verifier
+                                    .as_ref()
+                                    .map(latest_identity_mismatches)
+                                    .unwrap_or_default(),
+                                #[cfg(feature = "resource-watchdog")]
+                                resource_problems: resource_status
+                                    .problems()
+                                    .into_iter()
+                                    .map(|problem| (problem.resource, problem.message))
+                                    .collect(),
+                                #[cfg(feature = "scabbard-state-root-comparison")]
+                                state_divergences: state_root_monitor
+                                    .divergences()
+                                    .iter()
+                                    .map(describe_divergence)
+                                    .collect(),
+                            };
+
+                            let now = Instant::now();
+                            for rule in &rules {
+                                let tripped = evaluate(rule, &observations, now);
+                                let (fired, cleared) = state.update(&rule.name, tripped);
+                                for alert in fired {
+                                    notify(&sinks, &alert, AlertStatus::Firing);
+                                }
+                                for alert in cleared {
+                                    notify(&sinks, &alert, AlertStatus::Resolved);
+                                }
+                            }
+                        }
+                        Ok(EngineMessage::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                            debug!("Stopping alert engine");
+                            return;
+                        }
+                    }
+                }
+            })
+            .map_err(|err| AlertError(format!("unable to start alert engine thread: {}", err)))?;
+
+        Ok(AlertEngine {
+            join_handle,
+            shutdown_signaler: AlertEngineShutdownSignaler { sender },
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the engine
+    pub fn shutdown_signaler(&self) -> AlertEngineShutdownSignaler {
+        self.shutdown_signaler.clone()
+    }
+
+    /// Waits for the engine thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Alert engine thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals an `AlertEngine` to stop
+#[derive(Clone)]
+pub struct AlertEngineShutdownSignaler {
+    sender: Sender<EngineMessage>,
+}
+
+impl AlertEngineShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.send(EngineMessage::Shutdown).is_err() {
+            debug!("Alert engine has already stopped");
+        }
+    }
+}
+
+fn notify(sinks: &[Box<dyn AlertSink>], alert: &Alert, status: AlertStatus) {
+    for sink in sinks {
+        if let Err(err) = sink.notify(alert, status) {
+            error!("Unable to send alert {} notification: {}", alert.rule, err);
+        }
+    }
+}
+
+/// Returns each peer identity that has been claimed with an unpinned certificate, with the time of
+/// the latest attempt
+#[cfg(feature = "peer-identity-pinning")]
+fn latest_identity_mismatches(verifier: &IdentityPinVerifier) -> Vec<(String, Instant)> {
+    let now = Instant::now();
+    let mut latest: HashMap<String, Instant> = HashMap::new();
+    for mismatch in verifier.mismatches() {
+        let elapsed = SystemTime::now()
+            .duration_since(mismatch.timestamp)
+            .unwrap_or_default();
+        let at = now.checked_sub(elapsed).unwrap_or(now);
+        let entry = latest.entry(mismatch.identity).or_insert(at);
+        if at > *entry {
+            *entry = at;
+        }
+    }
+    latest.into_iter().collect()
+}
+
+/// Returns the `(subject, message)` of a divergent state root comparison
+#[cfg(feature = "scabbard-state-root-comparison")]
+fn describe_divergence(comparison: &StateRootComparison) -> (String, String) {
+    let service = format!("{}::{}", comparison.circuit_id, comparison.service_id);
+    let members = comparison
+        .divergent_members()
+        .iter()
+        .map(|member| {
+            format!(
+                "{} ({})",
+                member.service_id,
+                member.state_root.as_deref().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!(
+        "Scabbard service {} has state root {}, which differs from {}",
+        service, comparison.state_root, members
+    );
+    (service, message)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that rules trip only for subjects past their thresholds, and that the alert state
+    /// reports alerts once when they start firing and once when they clear.
+    #[test]
+    fn test_evaluate_rules() {
+        let now = Instant::now();
+        let observations = Observations {
+            disconnected_peers: vec![
+                ("node-a".into(), now - Duration::from_secs(600)),
+                ("node-b".into(), now - Duration::from_secs(60)),
+            ],
+            pending_proposals: vec![("abcde-01234".into(), now - Duration::from_secs(60))],
+            queue_depths: vec![("abcde-01234::a000".into(), 50)],
+            #[cfg(feature = "peer-identity-pinning")]
+            identity_mismatches: vec![
+                ("node-c".into(), now - Duration::from_secs(60)),
+                ("node-d".into(), now - Duration::from_secs(600)),
+            ],
+            #[cfg(feature = "resource-watchdog")]
+            resource_problems: vec![(
+                "/var/lib/splinter".into(),
+                "/var/lib/splinter has 100MB of free space, less than the minimum of 512MB".into(),
+            )],
+            #[cfg(feature = "scabbard-state-root-comparison")]
+            state_divergences: vec![(
+                "abcde-01234::a000".into(),
+                "Scabbard service abcde-01234::a000 has state root 0a, which differs from b000 \
+                 (0b)"
+                    .into(),
+            )],
+        };
+
+        let peer_rule = AlertRule {
+            name: "peer-down".into(),
+            condition: AlertCondition::PeerDisconnected { minutes: 5 },
+        };
+        let tripped = evaluate(&peer_rule, &observations, now);
+        assert_eq!(tripped.len(), 1);
+        assert_eq!(tripped[0].0, "node-a");
+
+        let proposal_rule = AlertRule {
+            name: "stale-proposal".into(),
+            condition: AlertCondition::ProposalPending { hours: 1 },
+        };
+        assert!(evaluate(&proposal_rule, &observations, now).is_empty());
+
+        let queue_rule = AlertRule {
+            name: "queue".into(),
+            condition: AlertCondition::ScabbardQueueDepth { depth: 10 },
+        };
+        assert_eq!(evaluate(&queue_rule, &observations, now).len(), 1);
+
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            let mismatch_rule = AlertRule {
+                name: "identity-mismatch".into(),
+                condition: AlertCondition::PeerIdentityMismatch { minutes: 5 },
+            };
+            let tripped = evaluate(&mismatch_rule, &observations, now);
+            assert_eq!(tripped.len(), 1);
+            assert_eq!(tripped[0].0, "node-c");
+        }
+
+        #[cfg(feature = "resource-watchdog")]
+        {
+            let resource_rule = AlertRule {
+                name: "resources".into(),
+                condition: AlertCondition::ResourcePressure,
+            };
+            let tripped = evaluate(&resource_rule, &observations, now);
+            assert_eq!(tripped.len(), 1);
+            assert_eq!(tripped[0].0, "/var/lib/splinter");
+        }
+
+        #[cfg(feature = "scabbard-state-root-comparison")]
+        {
+            let divergence_rule = AlertRule {
+                name: "divergence".into(),
+                condition: AlertCondition::ScabbardStateDivergence,
+            };
+            let tripped = evaluate(&divergence_rule, &observations, now);
+            assert_eq!(tripped.len(), 1);
+            assert_eq!(tripped[0].0, "abcde-01234::a000");
+        }
+
+        let state = AlertState::default();
+        let (fired, cleared) = state.update("peer-down", tripped.clone());
+        assert_eq!(fired.len(), 1);
+        assert!(cleared.is_empty());
+
+        let (fired, cleared) = state.update("peer-down", tripped);
+        assert!(fired.is_empty());
+        assert!(cleared.is_empty());
+        assert_eq!(state.list_alerts().len(), 1);
+
+        let (fired, cleared) = state.update("peer-down", vec![]);
+        assert!(fired.is_empty());
+        assert_eq!(cleared.len(), 1);
+        assert!(state.list_alerts().is_empty());
+    }
+
+    /// Verify that rules are read from the `[[alert_rules]]` tables of a config file.
+    #[test]
+    fn test_deserialize_rules() {
+        #[derive(Deserialize)]
+        struct Rules {
+            alert_rules: Vec<AlertRule>,
+        }
+
+        let rules: Rules = toml::from_str(
+            r#"
+            [[alert_rules]]
+            name = "peer-down"
+            condition = "peer_disconnected"
+            minutes = 10
+
+            [[alert_rules]]
+            name = "queue"
+            condition = "scabbard_queue_depth"
+            depth = 100
+            "#,
+        )
+        .expect("Unable to parse rules");
+
+        assert_eq!(
+            rules.alert_rules,
+            vec![
+                AlertRule {
+                    name: "peer-down".into(),
+                    condition: AlertCondition::PeerDisconnected { minutes: 10 },
+                },
+                AlertRule {
+                    name: "queue".into(),
+                    condition: AlertCondition::ScabbardQueueDepth { depth: 100 },
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The destinations that alert notifications are sent to.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use super::{Alert, AlertStatus};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An error that occurred while starting the alert engine or sending a notification
+#[derive(Debug)]
+pub struct AlertError(pub String);
+
+impl Error for AlertError {}
+
+impl fmt::Display for AlertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A sink, as defined in the `[[alert_sinks]]` tables of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertSinkConfig {
+    /// Writes notifications to the daemon's log
+    Log,
+    /// POSTs each notification as JSON to the given URL
+    Webhook { url: String },
+    /// Emails each notification through an SMTP relay that accepts unauthenticated, plain-text
+    /// connections, given as `host:port`
+    Email {
+        smtp_server: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+impl AlertSinkConfig {
+    /// Creates the sink described by this config
+    pub fn into_sink(self) -> Box<dyn AlertSink> {
+        match self {
+            AlertSinkConfig::Log => Box::new(LogSink),
+            AlertSinkConfig::Webhook { url } => Box::new(WebhookSink::new(url)),
+            AlertSinkConfig::Email {
+                smtp_server,
+                from,
+                to,
+            } => Box::new(EmailSink::new(smtp_server, from, to)),
+        }
+    }
+}
+
+/// Sends notifications when alerts fire or clear
+pub trait AlertSink: Send {
+    fn notify(&self, alert: &Alert, status: AlertStatus) -> Result<(), AlertError>;
+}
+
+/// Writes notifications to the daemon's log
+pub struct LogSink;
+
+impl AlertSink for LogSink {
+    fn notify(&self, alert: &Alert, status: AlertStatus) -> Result<(), AlertError> {
+        match status {
+            AlertStatus::Firing => warn!("Alert {} firing: {}", alert.rule, alert.message),
+            AlertStatus::Resolved => info!("Alert {} resolved: {}", alert.rule, alert.message),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    status: AlertStatus,
+    #[serde(flatten)]
+    alert: &'a Alert,
+}
+
+/// POSTs each notification as JSON to a URL
+pub struct WebhookSink {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        WebhookSink {
+            client: reqwest::blocking::Client::new(),
+            url,
+        }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn notify(&self, alert: &Alert, status: AlertStatus) -> Result<(), AlertError> {
+        let body = serde_json::to_vec(&WebhookPayload { status, alert })
+            .map_err(|err| AlertError(format!("unable to serialize notification: {}", err)))?;
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .map_err(|err| AlertError(format!("unable to call webhook {}: {}", self.url, err)))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(AlertError(format!(
+                "webhook {} responded with {}",
+                self.url,
+                response.status()
+            )))
+        }
+    }
+}
+
+/// Emails each notification through an SMTP relay.
+///
+/// The relay must accept unauthenticated, plain-text connections, such as a local mail transfer
+/// agent.
+pub struct EmailSink {
+    smtp_server: String,
+    from: String,
+    to: Vec<String>,
+}
+
+impl EmailSink {
+    pub fn new(smtp_server: String, from: String, to: Vec<String>) -> Self {
+        EmailSink {
+            smtp_server,
+            from,
+            to,
+        }
+    }
+
+    fn send(&self, subject: &str, body: &str) -> Result<(), AlertError> {
+        let stream = TcpStream::connect(&self.smtp_server).map_err(|err| {
+            AlertError(format!(
+                "unable to connect to {}: {}",
+                self.smtp_server, err
+            ))
+        })?;
+        stream
+            .set_read_timeout(Some(SMTP_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(SMTP_TIMEOUT)))
+            .map_err(|err| AlertError(format!("unable to configure SMTP connection: {}", err)))?;
+        let mut writer = stream
+            .try_clone()
+            .map_err(|err| AlertError(format!("unable to configure SMTP connection: {}", err)))?;
+        let mut reader = BufReader::new(stream);
+
+        expect_reply(&mut reader, &[220])?;
+        command(&mut writer, &mut reader, "HELO splinterd", &[250])?;
+        command(
+            &mut writer,
+            &mut reader,
+            &format!("MAIL FROM:<{}>", self.from),
+            &[250],
+        )?;
+        for recipient in &self.to {
+            command(
+                &mut writer,
+                &mut reader,
+                &format!("RCPT TO:<{}>", recipient),
+                &[250, 251],
+            )?;
+        }
+        command(&mut writer, &mut reader, "DATA", &[354])?;
+
+        let mut message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n",
+            self.from,
+            self.to.join(", "),
+            subject
+        );
+        for line in body.lines() {
+            // Lines starting with a period are escaped, so they are not read as the end of data
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message.push('.');
+        command(&mut writer, &mut reader, &message, &[250])?;
+        command(&mut writer, &mut reader, "QUIT", &[221])
+    }
+}
+
+impl AlertSink for EmailSink {
+    fn notify(&self, alert: &Alert, status: AlertStatus) -> Result<(), AlertError> {
+        let subject = match status {
+            AlertStatus::Firing => format!("[FIRING] {}: {}", alert.rule, alert.subject),
+            AlertStatus::Resolved => format!("[RESOLVED] {}: {}", alert.rule, alert.subject),
+        };
+        self.send(&subject, &alert.message)
+    }
+}
+
+fn command(
+    writer: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    line: &str,
+    expected: &[u16],
+) -> Result<(), AlertError> {
+    writer
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .map_err(|err| AlertError(format!("unable to write to SMTP server: {}", err)))?;
+    expect_reply(reader, expected)
+}
+
+/// Reads an SMTP reply, which may span multiple lines, and checks its code
+fn expect_reply(reader: &mut BufReader<TcpStream>, expected: &[u16]) -> Result<(), AlertError> {
+    loop {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|err| AlertError(format!("unable to read from SMTP server: {}", err)))?;
+
+        let code = line
+            .get(0..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| AlertError(format!("invalid SMTP reply: {}", line.trim_end())))?;
+        if !expected.contains(&code) {
+            return Err(AlertError(format!(
+                "unexpected SMTP reply: {}",
+                line.trim_end()
+            )));
+        }
+        // A hyphen after the code marks a reply that continues on the next line
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that sinks are read from the `[[alert_sinks]]` tables of a config file.
+    #[test]
+    fn test_deserialize_sinks() {
+        #[derive(Deserialize)]
+        struct Sinks {
+            alert_sinks: Vec<AlertSinkConfig>,
+        }
+
+        let sinks: Sinks = toml::from_str(
+            r#"
+            [[alert_sinks]]
+            type = "log"
+
+            [[alert_sinks]]
+            type = "webhook"
+            url = "https://alerts.example.com/splinter"
+
+            [[alert_sinks]]
+            type = "email"
+            smtp_server = "localhost:25"
+            from = "splinterd@example.com"
+            to = ["ops@example.com"]
+            "#,
+        )
+        .expect("Unable to parse sinks");
+
+        assert_eq!(
+            sinks.alert_sinks,
+            vec![
+                AlertSinkConfig::Log,
+                AlertSinkConfig::Webhook {
+                    url: "https://alerts.example.com/splinter".into()
+                },
+                AlertSinkConfig::Email {
+                    smtp_server: "localhost:25".into(),
+                    from: "splinterd@example.com".into(),
+                    to: vec!["ops@example.com".into()],
+                },
+            ]
+        );
+    }
+}
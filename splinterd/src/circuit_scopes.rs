@@ -0,0 +1,43 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Roles that limit the circuits their identities may access through the REST API, so that the
+//! tenants of a shared node only see their own circuits.
+
+use splinter::rest_api::auth::circuit_scope::RoleCircuitScopeProvider;
+
+/// A role, as defined in a `[[circuit_scope_roles]]` table of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CircuitScopeRoleConfig {
+    /// The name of the role
+    pub name: String,
+    /// The IDs of the circuits the role may access; "*" allows any circuit
+    #[serde(default)]
+    pub circuits: Vec<String>,
+    /// The identities assigned to the role, such as Biome user IDs or public keys
+    #[serde(default)]
+    pub identities: Vec<String>,
+}
+
+/// Returns a circuit scope provider with the given roles and their assignments.
+pub fn circuit_scope_provider(configs: &[CircuitScopeRoleConfig]) -> RoleCircuitScopeProvider {
+    configs
+        .iter()
+        .fold(RoleCircuitScopeProvider::new(), |provider, config| {
+            config.identities.iter().fold(
+                provider.with_role(&config.name, config.circuits.clone()),
+                |provider, identity| provider.with_assignment(identity, &config.name),
+            )
+        })
+}
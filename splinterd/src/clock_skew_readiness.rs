@@ -0,0 +1,82 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports the node as not ready while any peer's clock is skewed beyond the configured
+//! threshold.
+
+use health::readiness::ReadinessCheck;
+use splinter::network::clock_skew::ClockSkewMonitor;
+
+/// Fails while the clock of any peer is further from the local clock than the monitor's
+/// threshold, since timestamps exchanged with that peer, such as those of proposals, are
+/// unreliable.
+pub struct ClockSkewReadinessCheck {
+    monitor: ClockSkewMonitor,
+}
+
+impl ClockSkewReadinessCheck {
+    pub fn new(monitor: ClockSkewMonitor) -> Self {
+        Self { monitor }
+    }
+}
+
+impl ReadinessCheck for ClockSkewReadinessCheck {
+    fn name(&self) -> &str {
+        "clock-skew"
+    }
+
+    fn problems(&self) -> Vec<String> {
+        match self.monitor.skewed_peers() {
+            Ok(peers) => peers
+                .into_iter()
+                .map(|(peer_id, offset_millis)| {
+                    format!(
+                        "clock of peer {} is off by {}ms, exceeding the threshold of {}ms",
+                        peer_id,
+                        offset_millis,
+                        self.monitor.threshold().as_millis()
+                    )
+                })
+                .collect(),
+            Err(err) => vec![format!("Unable to get peer clock skew: {}", err)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    /// Verifies that the check passes while peer clocks are within the threshold, and reports
+    /// the peer whose clock is skewed.
+    #[test]
+    fn test_clock_skew_readiness() {
+        let monitor = ClockSkewMonitor::new(Duration::from_secs(1));
+        let check = ClockSkewReadinessCheck::new(monitor.clone());
+
+        monitor
+            .record("node-a", 10_000, 10_100)
+            .expect("Failed to record heartbeat");
+        assert!(check.problems().is_empty());
+
+        monitor
+            .record("node-b", 10_000, 15_000)
+            .expect("Failed to record heartbeat");
+        let problems = check.problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("clock of peer node-b is off by -5000ms"));
+    }
+}
@@ -252,6 +252,16 @@ impl ConfigBuilder {
                     None => None,
                 })
                 .ok_or_else(|| ConfigError::MissingValue("peers".to_string()))?,
+            peer_transport_preference: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.peer_transport_preference() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue("peer transport preference".to_string())
+                })?,
             display_name: self
                 .partial_configs
                 .iter()
@@ -319,6 +329,72 @@ impl ConfigBuilder {
                 .ok_or_else(|| {
                     ConfigError::MissingValue("admin service coordinator timeout".to_string())
                 })?,
+            orchestrator_incoming_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.orchestrator_incoming_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue("orchestrator incoming capacity".to_string())
+                })?,
+            orchestrator_outgoing_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.orchestrator_outgoing_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue("orchestrator outgoing capacity".to_string())
+                })?,
+            orchestrator_channel_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.orchestrator_channel_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue("orchestrator channel capacity".to_string())
+                })?,
+            admin_processor_incoming_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.admin_processor_incoming_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue(
+                        "admin service processor incoming capacity".to_string(),
+                    )
+                })?,
+            admin_processor_outgoing_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.admin_processor_outgoing_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue(
+                        "admin service processor outgoing capacity".to_string(),
+                    )
+                })?,
+            admin_processor_channel_capacity: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.admin_processor_channel_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                })
+                .ok_or_else(|| {
+                    ConfigError::MissingValue(
+                        "admin service processor channel capacity".to_string(),
+                    )
+                })?,
             state_dir,
             tls_insecure: self
                 .partial_configs
@@ -353,6 +429,29 @@ impl ConfigBuilder {
                     Some(v) => Some((v, p.source())),
                     None => None,
                 }),
+            #[cfg(feature = "rest-api-cors-policies")]
+            cors_policies: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.cors_policies() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_roles: self.partial_configs.iter().find_map(|p| {
+                match p.circuit_scope_roles() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "registry-remote-auth")]
+            registry_auth: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.registry_auth() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
             #[cfg(feature = "auth")]
             oauth_provider: self
                 .partial_configs
@@ -377,6 +476,13 @@ impl ConfigBuilder {
                 }
             }),
             #[cfg(feature = "auth")]
+            oauth_public_client: self.partial_configs.iter().find_map(|p| {
+                match p.oauth_public_client() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "auth")]
             oauth_redirect_url: self.partial_configs.iter().find_map(|p| {
                 match p.oauth_redirect_url() {
                     Some(v) => Some((v, p.source())),
@@ -390,6 +496,409 @@ impl ConfigBuilder {
                     None => None,
                 }
             }),
+            #[cfg(feature = "signing-ed25519")]
+            signing_algorithms: self.partial_configs.iter().find_map(|p| {
+                match p.signing_algorithms() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_domains: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.acme_domains() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_email: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.acme_email() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_directory_url: self.partial_configs.iter().find_map(|p| {
+                match p.acme_directory_url() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_challenge: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.acme_challenge() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_http_bind: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.acme_http_bind() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "rest-api-acme")]
+            acme_dns_hook: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.acme_dns_hook() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "config-reload")]
+            log_level: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.log_level() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "config-reload")]
+            peer_blocklist: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.peer_blocklist() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "metrics-push")]
+            metrics_url: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.metrics_url() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "metrics-push")]
+            metrics_username: self.partial_configs.iter().find_map(|p| {
+                match p.metrics_username() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "metrics-push")]
+            metrics_password: self.partial_configs.iter().find_map(|p| {
+                match p.metrics_password() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "metrics-push")]
+            metrics_interval: self.partial_configs.iter().find_map(|p| {
+                match p.metrics_interval() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "alerting")]
+            alert_rules: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.alert_rules() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "alerting")]
+            alert_sinks: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.alert_sinks() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "event-publisher")]
+            event_publisher: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.event_publisher() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "mqtt-bridge")]
+            mqtt_bridge: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.mqtt_bridge() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "node-challenge")]
+            node_key_file: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.node_key_file() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "tls-policy")]
+            tls_min_version: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.tls_min_version() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "tls-policy")]
+            tls_cipher_suites: self.partial_configs.iter().find_map(|p| {
+                match p.tls_cipher_suites() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tls-policy")]
+            tls_client_auth: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.tls_client_auth() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_min_version: self.partial_configs.iter().find_map(|p| {
+                match p.tls_rest_api_min_version() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_cipher_suites: self.partial_configs.iter().find_map(|p| {
+                match p.tls_rest_api_cipher_suites() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_client_auth: self.partial_configs.iter().find_map(|p| {
+                match p.tls_rest_api_client_auth() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tls-revocation")]
+            tls_crl_file: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.tls_crl_file() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "tls-revocation")]
+            tls_ocsp_response_file: self.partial_configs.iter().find_map(|p| {
+                match p.tls_ocsp_response_file() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tls-revocation")]
+            tls_require_ocsp: self.partial_configs.iter().find_map(|p| {
+                match p.tls_require_ocsp() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "spiffe")]
+            spiffe_svid_dir: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.spiffe_svid_dir() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "spiffe")]
+            spiffe_trust_domain: self.partial_configs.iter().find_map(|p| {
+                match p.spiffe_trust_domain() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "spiffe")]
+            spiffe_require_id: self.partial_configs.iter().find_map(|p| {
+                match p.spiffe_require_id() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "peer-identity-pinning")]
+            peer_identity_pinning: self.partial_configs.iter().find_map(|p| {
+                match p.peer_identity_pinning() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-key-rotation")]
+            admin_key_rotation_overlap: self.partial_configs.iter().find_map(|p| {
+                match p.admin_key_rotation_overlap() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "circuit-id-policy")]
+            accepted_circuit_id_formats: self.partial_configs.iter().find_map(|p| {
+                match p.accepted_circuit_id_formats() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_key_file: self.partial_configs.iter().find_map(|p| {
+                match p.auto_vote_key_file() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_members: self.partial_configs.iter().find_map(|p| {
+                match p.auto_vote_members() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_service_types: self.partial_configs.iter().find_map(|p| {
+                match p.auto_vote_service_types() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_metadata_pattern: self.partial_configs.iter().find_map(|p| {
+                match p.auto_vote_metadata_pattern() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_reject: self.partial_configs.iter().find_map(|p| {
+                match p.auto_vote_reject() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-routing-snapshot")]
+            persist_routing_table: self.partial_configs.iter().find_map(|p| {
+                match p.persist_routing_table() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "admin-signature-cache")]
+            admin_signature_cache_size: self.partial_configs.iter().find_map(|p| {
+                match p.admin_signature_cache_size() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "read-only-mode")]
+            read_only: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.read_only() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "resource-watchdog")]
+            resource_min_free_space: self.partial_configs.iter().find_map(|p| {
+                match p.resource_min_free_space() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_circuit_db_dirs: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_circuit_db_dirs() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_db_dirs: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_db_dirs() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_growth_increment: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_db_growth_increment() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_max_size: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_db_max_size() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_warning_thresholds: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_db_warning_thresholds() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "external-services")]
+            external_service_types: self.partial_configs.iter().find_map(|p| {
+                match p.external_service_types() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "tenancy")]
+            tenant_operators: self.partial_configs.iter().find_map(|p| {
+                match p.tenant_operators() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "biome-user-admin")]
+            biome_admin_keys: self.partial_configs.iter().find_map(|p| {
+                match p.biome_admin_keys() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "network-clock-skew")]
+            clock_skew_threshold: self.partial_configs.iter().find_map(|p| {
+                match p.clock_skew_threshold() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_queue_capacity: self.partial_configs.iter().find_map(|p| {
+                match p.peer_send_queue_capacity() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_weights: self.partial_configs.iter().find_map(|p| {
+                match p.peer_send_weights() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-batch-history")]
+            scabbard_batch_history_size: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_batch_history_size() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "scabbard-batch-history")]
+            scabbard_batch_history_max_age: self.partial_configs.iter().find_map(|p| {
+                match p.scabbard_batch_history_max_age() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
             strict_ref_counts: self
                 .partial_configs
                 .iter()
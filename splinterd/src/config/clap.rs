@@ -142,6 +142,11 @@ impl<'a> PartialConfigBuilder for ClapPartialConfigBuilder<'_> {
                         .value_of("oauth_client_secret")
                         .map(String::from),
                 )
+                .with_oauth_public_client(if self.matches.is_present("oauth_public_client") {
+                    Some(true)
+                } else {
+                    None
+                })
                 .with_oauth_redirect_url(
                     self.matches
                         .value_of("oauth_redirect_url")
@@ -150,6 +155,371 @@ impl<'a> PartialConfigBuilder for ClapPartialConfigBuilder<'_> {
                 .with_oauth_openid_url(self.matches.value_of("oauth_openid_url").map(String::from))
         }
 
+        #[cfg(feature = "signing-ed25519")]
+        {
+            partial_config = partial_config.with_signing_algorithms(
+                self.matches
+                    .values_of("signing_algorithms")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            )
+        }
+
+        #[cfg(feature = "rest-api-acme")]
+        {
+            partial_config = partial_config
+                .with_acme_domains(
+                    self.matches
+                        .values_of("acme_domains")
+                        .map(|values| values.map(String::from).collect::<Vec<String>>()),
+                )
+                .with_acme_email(self.matches.value_of("acme_email").map(String::from))
+                .with_acme_directory_url(
+                    self.matches
+                        .value_of("acme_directory_url")
+                        .map(String::from),
+                )
+                .with_acme_challenge(self.matches.value_of("acme_challenge").map(String::from))
+                .with_acme_http_bind(self.matches.value_of("acme_http_bind").map(String::from))
+                .with_acme_dns_hook(self.matches.value_of("acme_dns_hook").map(String::from));
+        }
+
+        #[cfg(feature = "config-reload")]
+        {
+            partial_config = partial_config.with_peer_blocklist(
+                self.matches
+                    .values_of("peer_blocklist")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "metrics-push")]
+        {
+            partial_config = partial_config
+                .with_metrics_url(self.matches.value_of("metrics_url").map(String::from))
+                .with_metrics_username(self.matches.value_of("metrics_username").map(String::from))
+                .with_metrics_password(self.matches.value_of("metrics_password").map(String::from))
+                .with_metrics_interval(parse_value(&self.matches, "metrics_interval")?);
+        }
+
+        #[cfg(feature = "node-challenge")]
+        {
+            partial_config = partial_config
+                .with_node_key_file(self.matches.value_of("node_key_file").map(String::from));
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config
+                .with_tls_min_version(self.matches.value_of("tls_min_version").map(String::from));
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_cipher_suites(
+                self.matches
+                    .values_of("tls_cipher_suites")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config
+                .with_tls_client_auth(self.matches.value_of("tls_client_auth").map(String::from));
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_rest_api_min_version(
+                self.matches
+                    .value_of("tls_rest_api_min_version")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_rest_api_cipher_suites(
+                self.matches
+                    .values_of("tls_rest_api_cipher_suites")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_rest_api_client_auth(
+                self.matches
+                    .value_of("tls_rest_api_client_auth")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config = partial_config
+                .with_tls_crl_file(self.matches.value_of("tls_crl_file").map(String::from));
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config = partial_config.with_tls_ocsp_response_file(
+                self.matches
+                    .value_of("tls_ocsp_response_file")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config = partial_config.with_tls_require_ocsp(
+                if self.matches.is_present("tls_require_ocsp") {
+                    Some(true)
+                } else {
+                    None
+                },
+            );
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config = partial_config
+                .with_spiffe_svid_dir(self.matches.value_of("spiffe_svid_dir").map(String::from));
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config = partial_config.with_spiffe_trust_domain(
+                self.matches
+                    .value_of("spiffe_trust_domain")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config = partial_config.with_spiffe_require_id(
+                if self.matches.is_present("spiffe_require_id") {
+                    Some(true)
+                } else {
+                    None
+                },
+            );
+        }
+
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            partial_config = partial_config.with_peer_identity_pinning(
+                self.matches
+                    .value_of("peer_identity_pinning")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "admin-key-rotation")]
+        {
+            partial_config = partial_config.with_admin_key_rotation_overlap(parse_value(
+                &self.matches,
+                "admin_key_rotation_overlap",
+            )?);
+        }
+
+        #[cfg(feature = "circuit-id-policy")]
+        {
+            partial_config = partial_config.with_accepted_circuit_id_formats(
+                self.matches
+                    .values_of("accepted_circuit_id_formats")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config.with_auto_vote_key_file(
+                self.matches
+                    .value_of("auto_vote_key_file")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config.with_auto_vote_members(
+                self.matches
+                    .values_of("auto_vote_members")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config.with_auto_vote_service_types(
+                self.matches
+                    .values_of("auto_vote_service_types")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config.with_auto_vote_metadata_pattern(
+                self.matches
+                    .value_of("auto_vote_metadata_pattern")
+                    .map(String::from),
+            );
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config.with_auto_vote_reject(
+                if self.matches.is_present("auto_vote_reject") {
+                    Some(true)
+                } else {
+                    None
+                },
+            );
+        }
+
+        #[cfg(feature = "admin-routing-snapshot")]
+        {
+            partial_config = partial_config.with_persist_routing_table(
+                if self.matches.is_present("persist_routing_table") {
+                    Some(true)
+                } else {
+                    None
+                },
+            );
+        }
+
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            partial_config = partial_config.with_admin_signature_cache_size(parse_value(
+                &self.matches,
+                "admin_signature_cache_size",
+            )?);
+        }
+
+        #[cfg(feature = "read-only-mode")]
+        {
+            partial_config =
+                partial_config.with_read_only(if self.matches.is_present("read_only") {
+                    Some(true)
+                } else {
+                    None
+                });
+        }
+
+        #[cfg(feature = "resource-watchdog")]
+        {
+            partial_config = partial_config.with_resource_min_free_space(parse_value(
+                &self.matches,
+                "resource_min_free_space",
+            )?);
+        }
+
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            partial_config = partial_config.with_scabbard_circuit_db_dirs(
+                if self.matches.is_present("scabbard_circuit_db_dirs") {
+                    Some(true)
+                } else {
+                    None
+                },
+            );
+        }
+
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            partial_config = partial_config.with_scabbard_db_dirs(
+                self.matches
+                    .values_of("scabbard_db_dirs")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config = partial_config.with_scabbard_db_growth_increment(parse_value(
+                &self.matches,
+                "scabbard_db_growth_increment",
+            )?);
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config = partial_config
+                .with_scabbard_db_max_size(parse_value(&self.matches, "scabbard_db_max_size")?);
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config = partial_config.with_scabbard_db_warning_thresholds(
+                self.matches
+                    .values_of("scabbard_db_warning_thresholds")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "external-services")]
+        {
+            partial_config = partial_config.with_external_service_types(
+                self.matches
+                    .values_of("external_service_types")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "tenancy")]
+        {
+            partial_config = partial_config.with_tenant_operators(
+                self.matches
+                    .values_of("tenant_operators")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "biome-user-admin")]
+        {
+            partial_config = partial_config.with_biome_admin_keys(
+                self.matches
+                    .values_of("biome_admin_keys")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            );
+        }
+
+        #[cfg(feature = "network-clock-skew")]
+        {
+            partial_config = partial_config
+                .with_clock_skew_threshold(parse_value(&self.matches, "clock_skew_threshold")?);
+        }
+
+        #[cfg(feature = "peer-send-limits")]
+        {
+            partial_config = partial_config
+                .with_peer_send_queue_capacity(parse_value(
+                    &self.matches,
+                    "peer_send_queue_capacity",
+                )?)
+                .with_peer_send_weights(
+                    self.matches
+                        .values_of("peer_send_weights")
+                        .map(|values| values.map(String::from).collect::<Vec<String>>()),
+                );
+        }
+
+        #[cfg(feature = "scabbard-batch-history")]
+        {
+            partial_config = partial_config
+                .with_scabbard_batch_history_size(parse_value(
+                    &self.matches,
+                    "scabbard_batch_history_size",
+                )?)
+                .with_scabbard_batch_history_max_age(parse_value(
+                    &self.matches,
+                    "scabbard_batch_history_max_age",
+                )?);
+        }
+
         Ok(partial_config)
     }
 }
@@ -41,6 +41,7 @@ const REGISTRY_AUTO_REFRESH: u64 = 600; // 600 seconds = 10 minutes
 const REGISTRY_FORCED_REFRESH: u64 = 10; // 10 seconds
 const HEARTBEAT: u64 = 30; // 30 seconds
 const ADMIN_TIMEOUT: u64 = 30; // 30 seconds
+const QUEUE_CAPACITY: usize = 8;
 
 pub struct DefaultPartialConfigBuilder;
 
@@ -65,12 +66,19 @@ impl PartialConfigBuilder for DefaultPartialConfigBuilder {
             .with_tls_server_key(Some(String::from(TLS_SERVER_KEY)))
             .with_network_endpoints(Some(vec![String::from(NETWORK_ENDPOINT)]))
             .with_peers(Some(vec![]))
+            .with_peer_transport_preference(Some(vec![]))
             .with_rest_api_endpoint(Some(String::from(REST_API_ENDPOINT)))
             .with_registries(Some(vec![]))
             .with_registry_auto_refresh(Some(REGISTRY_AUTO_REFRESH))
             .with_registry_forced_refresh(Some(REGISTRY_FORCED_REFRESH))
             .with_heartbeat(Some(HEARTBEAT))
             .with_admin_timeout(Some(ADMIN_TIMEOUT))
+            .with_orchestrator_incoming_capacity(Some(QUEUE_CAPACITY))
+            .with_orchestrator_outgoing_capacity(Some(QUEUE_CAPACITY))
+            .with_orchestrator_channel_capacity(Some(QUEUE_CAPACITY))
+            .with_admin_processor_incoming_capacity(Some(QUEUE_CAPACITY))
+            .with_admin_processor_outgoing_capacity(Some(QUEUE_CAPACITY))
+            .with_admin_processor_channel_capacity(Some(QUEUE_CAPACITY))
             .with_state_dir(Some(String::from(STATE_DIR)))
             .with_tls_insecure(Some(false))
             .with_no_tls(Some(false))
@@ -144,6 +152,7 @@ mod tests {
             Some(vec![String::from(NETWORK_ENDPOINT)])
         );
         assert_eq!(config.peers(), Some(vec![]));
+        assert_eq!(config.peer_transport_preference(), Some(vec![]));
         assert_eq!(config.node_id(), None);
         assert_eq!(config.display_name(), None);
         assert_eq!(
@@ -163,6 +172,27 @@ mod tests {
             config.admin_timeout(),
             Some(Duration::from_secs(ADMIN_TIMEOUT))
         );
+        assert_eq!(
+            config.orchestrator_incoming_capacity(),
+            Some(QUEUE_CAPACITY)
+        );
+        assert_eq!(
+            config.orchestrator_outgoing_capacity(),
+            Some(QUEUE_CAPACITY)
+        );
+        assert_eq!(config.orchestrator_channel_capacity(), Some(QUEUE_CAPACITY));
+        assert_eq!(
+            config.admin_processor_incoming_capacity(),
+            Some(QUEUE_CAPACITY)
+        );
+        assert_eq!(
+            config.admin_processor_outgoing_capacity(),
+            Some(QUEUE_CAPACITY)
+        );
+        assert_eq!(
+            config.admin_processor_channel_capacity(),
+            Some(QUEUE_CAPACITY)
+        );
         assert_eq!(config.state_dir(), Some(String::from(STATE_DIR)));
         assert_eq!(config.tls_insecure(), Some(false));
         assert_eq!(config.no_tls(), Some(false));
@@ -13,10 +13,25 @@
 // limitations under the License.
 
 //! `PartialConfig` builder using values from environment variables.
+//!
+//! Every setting that has a single value or a list of values can be set with an environment
+//! variable, so container deployments can be configured without a config file. Settings that are
+//! structured tables in the config file, such as `cors_policies` or `alert_rules`, can only be
+//! set in the config file.
+//!
+//! Lists are comma-separated, for example `SPLINTER_PEERS=tcps://a:8044,tcps://b:8044`. Boolean
+//! values accept `true`/`false`, `1`/`0` and `yes`/`no`. An empty value is treated as unset.
+//!
+//! Any variable may instead be given as the path of a file that holds its value, by appending
+//! `_FILE` to its name, e.g. `SPLINTER_DB_URL_FILE=/run/secrets/db_url`. This keeps secrets out of
+//! the process environment when they are mounted as files, as with Docker and Kubernetes secrets.
+//! Trailing newlines are removed from the file's contents. Setting both a variable and its
+//! `_FILE` variant is an error.
 
 use std::env;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
 use crate::config::{ConfigError, ConfigSource, PartialConfig, PartialConfigBuilder};
 
@@ -25,6 +40,43 @@ const STATE_DIR_ENV: &str = "SPLINTER_STATE_DIR";
 const CERT_DIR_ENV: &str = "SPLINTER_CERT_DIR";
 const SPLINTER_HOME_ENV: &str = "SPLINTER_HOME";
 const SPLINTER_STRICT_REF_COUNT_ENV: &str = "SPLINTER_STRICT_REF_COUNT";
+const STORAGE_ENV: &str = "SPLINTER_STORAGE";
+const TLS_CA_FILE_ENV: &str = "SPLINTER_TLS_CA_FILE";
+const TLS_CLIENT_CERT_ENV: &str = "SPLINTER_TLS_CLIENT_CERT";
+const TLS_CLIENT_KEY_ENV: &str = "SPLINTER_TLS_CLIENT_KEY";
+const TLS_SERVER_CERT_ENV: &str = "SPLINTER_TLS_SERVER_CERT";
+const TLS_SERVER_KEY_ENV: &str = "SPLINTER_TLS_SERVER_KEY";
+#[cfg(feature = "https-bind")]
+const TLS_REST_API_CERT_ENV: &str = "SPLINTER_TLS_REST_API_CERT";
+#[cfg(feature = "https-bind")]
+const TLS_REST_API_KEY_ENV: &str = "SPLINTER_TLS_REST_API_KEY";
+const TLS_INSECURE_ENV: &str = "SPLINTER_TLS_INSECURE";
+const NO_TLS_ENV: &str = "SPLINTER_NO_TLS";
+#[cfg(feature = "service-endpoint")]
+const SERVICE_ENDPOINT_ENV: &str = "SPLINTER_SERVICE_ENDPOINT";
+const NETWORK_ENDPOINTS_ENV: &str = "SPLINTER_NETWORK_ENDPOINTS";
+const ADVERTISED_ENDPOINTS_ENV: &str = "SPLINTER_ADVERTISED_ENDPOINTS";
+const PEERS_ENV: &str = "SPLINTER_PEERS";
+const PEER_TRANSPORT_PREFERENCE_ENV: &str = "SPLINTER_PEER_TRANSPORT_PREFERENCE";
+const NODE_ID_ENV: &str = "SPLINTER_NODE_ID";
+const DISPLAY_NAME_ENV: &str = "SPLINTER_DISPLAY_NAME";
+const REST_API_ENDPOINT_ENV: &str = "SPLINTER_REST_API_ENDPOINT";
+#[cfg(feature = "database")]
+const DB_URL_ENV: &str = "SPLINTER_DB_URL";
+const REGISTRIES_ENV: &str = "SPLINTER_REGISTRIES";
+const REGISTRY_AUTO_REFRESH_ENV: &str = "SPLINTER_REGISTRY_AUTO_REFRESH";
+const REGISTRY_FORCED_REFRESH_ENV: &str = "SPLINTER_REGISTRY_FORCED_REFRESH";
+const HEARTBEAT_ENV: &str = "SPLINTER_HEARTBEAT";
+const ADMIN_TIMEOUT_ENV: &str = "SPLINTER_ADMIN_TIMEOUT";
+const ORCHESTRATOR_INCOMING_CAPACITY_ENV: &str = "SPLINTER_ORCHESTRATOR_INCOMING_CAPACITY";
+const ORCHESTRATOR_OUTGOING_CAPACITY_ENV: &str = "SPLINTER_ORCHESTRATOR_OUTGOING_CAPACITY";
+const ORCHESTRATOR_CHANNEL_CAPACITY_ENV: &str = "SPLINTER_ORCHESTRATOR_CHANNEL_CAPACITY";
+const ADMIN_PROCESSOR_INCOMING_CAPACITY_ENV: &str = "SPLINTER_ADMIN_PROCESSOR_INCOMING_CAPACITY";
+const ADMIN_PROCESSOR_OUTGOING_CAPACITY_ENV: &str = "SPLINTER_ADMIN_PROCESSOR_OUTGOING_CAPACITY";
+const ADMIN_PROCESSOR_CHANNEL_CAPACITY_ENV: &str = "SPLINTER_ADMIN_PROCESSOR_CHANNEL_CAPACITY";
+const ENABLE_BIOME_ENV: &str = "SPLINTER_ENABLE_BIOME";
+#[cfg(feature = "rest-api-cors")]
+const WHITELIST_ENV: &str = "SPLINTER_WHITELIST";
 #[cfg(feature = "auth")]
 const OAUTH_PROVIDER_ENV: &str = "OAUTH_PROVIDER";
 #[cfg(feature = "auth")]
@@ -32,9 +84,116 @@ const OAUTH_CLIENT_ID_ENV: &str = "OAUTH_CLIENT_ID";
 #[cfg(feature = "auth")]
 const OAUTH_CLIENT_SECRET_ENV: &str = "OAUTH_CLIENT_SECRET";
 #[cfg(feature = "auth")]
+const OAUTH_PUBLIC_CLIENT_ENV: &str = "OAUTH_PUBLIC_CLIENT";
+#[cfg(feature = "auth")]
 const OAUTH_REDIRECT_URL_ENV: &str = "OAUTH_REDIRECT_URL";
 #[cfg(feature = "auth")]
 const OAUTH_OPENID_URL_ENV: &str = "OAUTH_OPENID_URL";
+#[cfg(feature = "signing-ed25519")]
+const SIGNING_ALGORITHMS_ENV: &str = "SPLINTER_SIGNING_ALGORITHMS";
+#[cfg(feature = "rest-api-acme")]
+const ACME_DOMAINS_ENV: &str = "SPLINTER_ACME_DOMAINS";
+#[cfg(feature = "rest-api-acme")]
+const ACME_EMAIL_ENV: &str = "SPLINTER_ACME_EMAIL";
+#[cfg(feature = "rest-api-acme")]
+const ACME_DIRECTORY_URL_ENV: &str = "SPLINTER_ACME_DIRECTORY_URL";
+#[cfg(feature = "rest-api-acme")]
+const ACME_CHALLENGE_ENV: &str = "SPLINTER_ACME_CHALLENGE";
+#[cfg(feature = "rest-api-acme")]
+const ACME_HTTP_BIND_ENV: &str = "SPLINTER_ACME_HTTP_BIND";
+#[cfg(feature = "rest-api-acme")]
+const ACME_DNS_HOOK_ENV: &str = "SPLINTER_ACME_DNS_HOOK";
+#[cfg(feature = "config-reload")]
+const LOG_LEVEL_ENV: &str = "SPLINTER_LOG_LEVEL";
+#[cfg(feature = "config-reload")]
+const PEER_BLOCKLIST_ENV: &str = "SPLINTER_PEER_BLOCKLIST";
+#[cfg(feature = "metrics-push")]
+const METRICS_URL_ENV: &str = "SPLINTER_METRICS_URL";
+#[cfg(feature = "metrics-push")]
+const METRICS_USERNAME_ENV: &str = "SPLINTER_METRICS_USERNAME";
+#[cfg(feature = "metrics-push")]
+const METRICS_PASSWORD_ENV: &str = "SPLINTER_METRICS_PASSWORD";
+#[cfg(feature = "metrics-push")]
+const METRICS_INTERVAL_ENV: &str = "SPLINTER_METRICS_INTERVAL";
+#[cfg(feature = "node-challenge")]
+const NODE_KEY_FILE_ENV: &str = "SPLINTER_NODE_KEY_FILE";
+#[cfg(feature = "tls-policy")]
+const TLS_MIN_VERSION_ENV: &str = "SPLINTER_TLS_MIN_VERSION";
+#[cfg(feature = "tls-policy")]
+const TLS_CIPHER_SUITES_ENV: &str = "SPLINTER_TLS_CIPHER_SUITES";
+#[cfg(feature = "tls-policy")]
+const TLS_CLIENT_AUTH_ENV: &str = "SPLINTER_TLS_CLIENT_AUTH";
+#[cfg(feature = "tls-policy")]
+const TLS_REST_API_MIN_VERSION_ENV: &str = "SPLINTER_TLS_REST_API_MIN_VERSION";
+#[cfg(feature = "tls-policy")]
+const TLS_REST_API_CIPHER_SUITES_ENV: &str = "SPLINTER_TLS_REST_API_CIPHER_SUITES";
+#[cfg(feature = "tls-policy")]
+const TLS_REST_API_CLIENT_AUTH_ENV: &str = "SPLINTER_TLS_REST_API_CLIENT_AUTH";
+#[cfg(feature = "tls-revocation")]
+const TLS_CRL_FILE_ENV: &str = "SPLINTER_TLS_CRL_FILE";
+#[cfg(feature = "tls-revocation")]
+const TLS_OCSP_RESPONSE_FILE_ENV: &str = "SPLINTER_TLS_OCSP_RESPONSE_FILE";
+#[cfg(feature = "tls-revocation")]
+const TLS_REQUIRE_OCSP_ENV: &str = "SPLINTER_TLS_REQUIRE_OCSP";
+#[cfg(feature = "spiffe")]
+const SPIFFE_SVID_DIR_ENV: &str = "SPLINTER_SPIFFE_SVID_DIR";
+#[cfg(feature = "spiffe")]
+const SPIFFE_TRUST_DOMAIN_ENV: &str = "SPLINTER_SPIFFE_TRUST_DOMAIN";
+#[cfg(feature = "spiffe")]
+const SPIFFE_REQUIRE_ID_ENV: &str = "SPLINTER_SPIFFE_REQUIRE_ID";
+#[cfg(feature = "peer-identity-pinning")]
+const PEER_IDENTITY_PINNING_ENV: &str = "SPLINTER_PEER_IDENTITY_PINNING";
+#[cfg(feature = "admin-key-rotation")]
+const ADMIN_KEY_ROTATION_OVERLAP_ENV: &str = "SPLINTER_ADMIN_KEY_ROTATION_OVERLAP";
+#[cfg(feature = "circuit-id-policy")]
+const ACCEPTED_CIRCUIT_ID_FORMATS_ENV: &str = "SPLINTER_ACCEPTED_CIRCUIT_ID_FORMATS";
+#[cfg(feature = "admin-auto-vote")]
+const AUTO_VOTE_KEY_FILE_ENV: &str = "SPLINTER_AUTO_VOTE_KEY_FILE";
+#[cfg(feature = "admin-auto-vote")]
+const AUTO_VOTE_MEMBERS_ENV: &str = "SPLINTER_AUTO_VOTE_MEMBERS";
+#[cfg(feature = "admin-auto-vote")]
+const AUTO_VOTE_SERVICE_TYPES_ENV: &str = "SPLINTER_AUTO_VOTE_SERVICE_TYPES";
+#[cfg(feature = "admin-auto-vote")]
+const AUTO_VOTE_METADATA_PATTERN_ENV: &str = "SPLINTER_AUTO_VOTE_METADATA_PATTERN";
+#[cfg(feature = "admin-auto-vote")]
+const AUTO_VOTE_REJECT_ENV: &str = "SPLINTER_AUTO_VOTE_REJECT";
+#[cfg(feature = "admin-routing-snapshot")]
+const PERSIST_ROUTING_TABLE_ENV: &str = "SPLINTER_PERSIST_ROUTING_TABLE";
+#[cfg(feature = "admin-signature-cache")]
+const ADMIN_SIGNATURE_CACHE_SIZE_ENV: &str = "SPLINTER_ADMIN_SIGNATURE_CACHE_SIZE";
+#[cfg(feature = "read-only-mode")]
+const READ_ONLY_ENV: &str = "SPLINTER_READ_ONLY";
+#[cfg(feature = "resource-watchdog")]
+const RESOURCE_MIN_FREE_SPACE_ENV: &str = "SPLINTER_RESOURCE_MIN_FREE_SPACE";
+#[cfg(feature = "scabbard-db-placement")]
+const SCABBARD_CIRCUIT_DB_DIRS_ENV: &str = "SPLINTER_SCABBARD_CIRCUIT_DB_DIRS";
+#[cfg(feature = "scabbard-db-placement")]
+const SCABBARD_DB_DIRS_ENV: &str = "SPLINTER_SCABBARD_DB_DIRS";
+#[cfg(feature = "scabbard-db-auto-grow")]
+const SCABBARD_DB_GROWTH_INCREMENT_ENV: &str = "SPLINTER_SCABBARD_DB_GROWTH_INCREMENT";
+#[cfg(feature = "scabbard-db-auto-grow")]
+const SCABBARD_DB_MAX_SIZE_ENV: &str = "SPLINTER_SCABBARD_DB_MAX_SIZE";
+#[cfg(feature = "scabbard-db-auto-grow")]
+const SCABBARD_DB_WARNING_THRESHOLDS_ENV: &str = "SPLINTER_SCABBARD_DB_WARNING_THRESHOLDS";
+#[cfg(feature = "external-services")]
+const EXTERNAL_SERVICE_TYPES_ENV: &str = "SPLINTER_EXTERNAL_SERVICE_TYPES";
+#[cfg(feature = "tenancy")]
+const TENANT_OPERATORS_ENV: &str = "SPLINTER_TENANT_OPERATORS";
+#[cfg(feature = "biome-user-admin")]
+const BIOME_ADMIN_KEYS_ENV: &str = "SPLINTER_BIOME_ADMIN_KEYS";
+#[cfg(feature = "network-clock-skew")]
+const CLOCK_SKEW_THRESHOLD_ENV: &str = "SPLINTER_CLOCK_SKEW_THRESHOLD";
+#[cfg(feature = "peer-send-limits")]
+const PEER_SEND_QUEUE_CAPACITY_ENV: &str = "SPLINTER_PEER_SEND_QUEUE_CAPACITY";
+#[cfg(feature = "peer-send-limits")]
+const PEER_SEND_WEIGHTS_ENV: &str = "SPLINTER_PEER_SEND_WEIGHTS";
+#[cfg(feature = "scabbard-batch-history")]
+const SCABBARD_BATCH_HISTORY_SIZE_ENV: &str = "SPLINTER_SCABBARD_BATCH_HISTORY_SIZE";
+#[cfg(feature = "scabbard-batch-history")]
+const SCABBARD_BATCH_HISTORY_MAX_AGE_ENV: &str = "SPLINTER_SCABBARD_BATCH_HISTORY_MAX_AGE";
+
+/// The suffix of a variable whose value is the path of a file that holds the setting's value.
+const FILE_SUFFIX: &str = "_FILE";
 
 pub struct EnvPartialConfigBuilder;
 
@@ -48,76 +207,409 @@ impl EnvPartialConfigBuilder {
 /// environment variable config options.
 impl PartialConfigBuilder for EnvPartialConfigBuilder {
     fn build(self) -> Result<PartialConfig, ConfigError> {
-        let mut config = PartialConfig::new(ConfigSource::Environment);
-
-        let config_dir_env = match (
-            env::var(CONFIG_DIR_ENV).ok(),
-            env::var(SPLINTER_HOME_ENV).ok(),
-        ) {
-            (Some(config_dir), _) => Some(config_dir),
-            (None, Some(splinter_home)) => {
-                let opt_path = Path::new(&splinter_home).join("etc");
-                if !opt_path.is_dir() {
-                    fs::create_dir_all(&opt_path).map_err(ConfigError::StdError)?;
-                }
-                opt_path.to_str().map(ToOwned::to_owned)
-            }
-            _ => None,
-        };
-        let tls_cert_dir_env = match (
-            env::var(CERT_DIR_ENV).ok(),
-            env::var(SPLINTER_HOME_ENV).ok(),
-        ) {
-            (Some(tls_cert_dir), _) => Some(tls_cert_dir),
-            (None, Some(splinter_home)) => {
-                let opt_path = Path::new(&splinter_home).join("certs");
-                if !opt_path.is_dir() {
-                    fs::create_dir_all(&opt_path).map_err(ConfigError::StdError)?;
-                }
-                opt_path.to_str().map(ToOwned::to_owned)
-            }
-            _ => None,
-        };
-        let state_dir_env = match (
-            env::var(STATE_DIR_ENV).ok(),
-            env::var(SPLINTER_HOME_ENV).ok(),
-        ) {
-            (Some(state_dir), _) => Some(state_dir),
-            (None, Some(splinter_home)) => {
-                let opt_path = Path::new(&splinter_home).join("data");
-                if !opt_path.is_dir() {
-                    fs::create_dir_all(&opt_path).map_err(ConfigError::StdError)?;
-                }
-                opt_path.to_str().map(ToOwned::to_owned)
-            }
-            _ => None,
-        };
+        build_partial_config(&EnvVars::new(&|name| env::var(name).ok()))
+    }
+}
 
-        let strict_ref_counts = match env::var(SPLINTER_STRICT_REF_COUNT_ENV).ok() {
-            Some(value) => {
-                let t: bool = value.parse().unwrap_or(false);
-                Some(t)
-            }
-            None => Some(false),
-        };
+fn build_partial_config(vars: &EnvVars) -> Result<PartialConfig, ConfigError> {
+    let mut config = PartialConfig::new(ConfigSource::Environment);
+
+    let splinter_home = vars.string(SPLINTER_HOME_ENV)?;
+    let config_dir_env = match (vars.string(CONFIG_DIR_ENV)?, &splinter_home) {
+        (Some(config_dir), _) => Some(config_dir),
+        (None, Some(splinter_home)) => home_subdir(splinter_home, "etc")?,
+        _ => None,
+    };
+    let tls_cert_dir_env = match (vars.string(CERT_DIR_ENV)?, &splinter_home) {
+        (Some(tls_cert_dir), _) => Some(tls_cert_dir),
+        (None, Some(splinter_home)) => home_subdir(splinter_home, "certs")?,
+        _ => None,
+    };
+    let state_dir_env = match (vars.string(STATE_DIR_ENV)?, &splinter_home) {
+        (Some(state_dir), _) => Some(state_dir),
+        (None, Some(splinter_home)) => home_subdir(splinter_home, "data")?,
+        _ => None,
+    };
+
+    let strict_ref_counts = match vars.string(SPLINTER_STRICT_REF_COUNT_ENV)? {
+        Some(value) => {
+            let t: bool = value.parse().unwrap_or(false);
+            Some(t)
+        }
+        None => Some(false),
+    };
+
+    config = config
+        .with_config_dir(config_dir_env)
+        .with_tls_cert_dir(tls_cert_dir_env)
+        .with_state_dir(state_dir_env)
+        .with_strict_ref_counts(strict_ref_counts)
+        .with_storage(vars.string(STORAGE_ENV)?)
+        .with_tls_ca_file(vars.string(TLS_CA_FILE_ENV)?)
+        .with_tls_client_cert(vars.string(TLS_CLIENT_CERT_ENV)?)
+        .with_tls_client_key(vars.string(TLS_CLIENT_KEY_ENV)?)
+        .with_tls_server_cert(vars.string(TLS_SERVER_CERT_ENV)?)
+        .with_tls_server_key(vars.string(TLS_SERVER_KEY_ENV)?)
+        .with_tls_insecure(vars.boolean(TLS_INSECURE_ENV)?)
+        .with_no_tls(vars.boolean(NO_TLS_ENV)?)
+        .with_network_endpoints(vars.list(NETWORK_ENDPOINTS_ENV)?)
+        .with_advertised_endpoints(vars.list(ADVERTISED_ENDPOINTS_ENV)?)
+        .with_peers(vars.list(PEERS_ENV)?)
+        .with_peer_transport_preference(vars.list(PEER_TRANSPORT_PREFERENCE_ENV)?)
+        .with_node_id(vars.string(NODE_ID_ENV)?)
+        .with_display_name(vars.string(DISPLAY_NAME_ENV)?)
+        .with_rest_api_endpoint(vars.string(REST_API_ENDPOINT_ENV)?)
+        .with_registries(vars.list(REGISTRIES_ENV)?)
+        .with_registry_auto_refresh(vars.number(REGISTRY_AUTO_REFRESH_ENV)?)
+        .with_registry_forced_refresh(vars.number(REGISTRY_FORCED_REFRESH_ENV)?)
+        .with_heartbeat(vars.number(HEARTBEAT_ENV)?)
+        .with_admin_timeout(vars.number(ADMIN_TIMEOUT_ENV)?)
+        .with_orchestrator_incoming_capacity(vars.number(ORCHESTRATOR_INCOMING_CAPACITY_ENV)?)
+        .with_orchestrator_outgoing_capacity(vars.number(ORCHESTRATOR_OUTGOING_CAPACITY_ENV)?)
+        .with_orchestrator_channel_capacity(vars.number(ORCHESTRATOR_CHANNEL_CAPACITY_ENV)?)
+        .with_admin_processor_incoming_capacity(vars.number(ADMIN_PROCESSOR_INCOMING_CAPACITY_ENV)?)
+        .with_admin_processor_outgoing_capacity(vars.number(ADMIN_PROCESSOR_OUTGOING_CAPACITY_ENV)?)
+        .with_admin_processor_channel_capacity(vars.number(ADMIN_PROCESSOR_CHANNEL_CAPACITY_ENV)?)
+        .with_enable_biome(vars.boolean(ENABLE_BIOME_ENV)?);
+
+    #[cfg(feature = "https-bind")]
+    {
+        config = config
+            .with_tls_rest_api_cert(vars.string(TLS_REST_API_CERT_ENV)?)
+            .with_tls_rest_api_key(vars.string(TLS_REST_API_KEY_ENV)?);
+    }
+
+    #[cfg(feature = "service-endpoint")]
+    {
+        config = config.with_service_endpoint(vars.string(SERVICE_ENDPOINT_ENV)?);
+    }
+
+    #[cfg(feature = "database")]
+    {
+        config = config.with_database(vars.string(DB_URL_ENV)?);
+    }
+
+    #[cfg(feature = "rest-api-cors")]
+    {
+        config = config.with_whitelist(vars.list(WHITELIST_ENV)?);
+    }
+
+    #[cfg(feature = "auth")]
+    {
+        config = config
+            .with_oauth_provider(vars.string(OAUTH_PROVIDER_ENV)?)
+            .with_oauth_client_id(vars.string(OAUTH_CLIENT_ID_ENV)?)
+            .with_oauth_client_secret(vars.string(OAUTH_CLIENT_SECRET_ENV)?)
+            .with_oauth_public_client(
+                vars.string(OAUTH_PUBLIC_CLIENT_ENV)?
+                    .map(|value| value.parse().unwrap_or(false)),
+            )
+            .with_oauth_redirect_url(vars.string(OAUTH_REDIRECT_URL_ENV)?)
+            .with_oauth_openid_url(vars.string(OAUTH_OPENID_URL_ENV)?);
+    }
+
+    #[cfg(feature = "signing-ed25519")]
+    {
+        config = config.with_signing_algorithms(vars.list(SIGNING_ALGORITHMS_ENV)?);
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    {
+        config = config
+            .with_acme_domains(vars.list(ACME_DOMAINS_ENV)?)
+            .with_acme_email(vars.string(ACME_EMAIL_ENV)?)
+            .with_acme_directory_url(vars.string(ACME_DIRECTORY_URL_ENV)?)
+            .with_acme_challenge(vars.string(ACME_CHALLENGE_ENV)?)
+            .with_acme_http_bind(vars.string(ACME_HTTP_BIND_ENV)?)
+            .with_acme_dns_hook(vars.string(ACME_DNS_HOOK_ENV)?);
+    }
+
+    #[cfg(feature = "config-reload")]
+    {
+        config = config
+            .with_log_level(vars.string(LOG_LEVEL_ENV)?)
+            .with_peer_blocklist(vars.list(PEER_BLOCKLIST_ENV)?);
+    }
+
+    #[cfg(feature = "metrics-push")]
+    {
+        config = config
+            .with_metrics_url(vars.string(METRICS_URL_ENV)?)
+            .with_metrics_username(vars.string(METRICS_USERNAME_ENV)?)
+            .with_metrics_password(vars.string(METRICS_PASSWORD_ENV)?)
+            .with_metrics_interval(vars.number(METRICS_INTERVAL_ENV)?);
+    }
+
+    #[cfg(feature = "node-challenge")]
+    {
+        config = config.with_node_key_file(vars.string(NODE_KEY_FILE_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_min_version(vars.string(TLS_MIN_VERSION_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_cipher_suites(vars.list(TLS_CIPHER_SUITES_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_client_auth(vars.string(TLS_CLIENT_AUTH_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_rest_api_min_version(vars.string(TLS_REST_API_MIN_VERSION_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_rest_api_cipher_suites(vars.list(TLS_REST_API_CIPHER_SUITES_ENV)?);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        config = config.with_tls_rest_api_client_auth(vars.string(TLS_REST_API_CLIENT_AUTH_ENV)?);
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    {
+        config = config.with_tls_crl_file(vars.string(TLS_CRL_FILE_ENV)?);
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    {
+        config = config.with_tls_ocsp_response_file(vars.string(TLS_OCSP_RESPONSE_FILE_ENV)?);
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    {
+        config = config.with_tls_require_ocsp(vars.boolean(TLS_REQUIRE_OCSP_ENV)?);
+    }
+
+    #[cfg(feature = "spiffe")]
+    {
+        config = config.with_spiffe_svid_dir(vars.string(SPIFFE_SVID_DIR_ENV)?);
+    }
+
+    #[cfg(feature = "spiffe")]
+    {
+        config = config.with_spiffe_trust_domain(vars.string(SPIFFE_TRUST_DOMAIN_ENV)?);
+    }
+
+    #[cfg(feature = "spiffe")]
+    {
+        config = config.with_spiffe_require_id(vars.boolean(SPIFFE_REQUIRE_ID_ENV)?);
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    {
+        config = config.with_peer_identity_pinning(vars.string(PEER_IDENTITY_PINNING_ENV)?);
+    }
+
+    #[cfg(feature = "admin-key-rotation")]
+    {
+        config =
+            config.with_admin_key_rotation_overlap(vars.number(ADMIN_KEY_ROTATION_OVERLAP_ENV)?);
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    {
+        config =
+            config.with_accepted_circuit_id_formats(vars.list(ACCEPTED_CIRCUIT_ID_FORMATS_ENV)?);
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        config = config.with_auto_vote_key_file(vars.string(AUTO_VOTE_KEY_FILE_ENV)?);
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        config = config.with_auto_vote_members(vars.list(AUTO_VOTE_MEMBERS_ENV)?);
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        config = config.with_auto_vote_service_types(vars.list(AUTO_VOTE_SERVICE_TYPES_ENV)?);
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        config =
+            config.with_auto_vote_metadata_pattern(vars.string(AUTO_VOTE_METADATA_PATTERN_ENV)?);
+    }
 
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        config = config.with_auto_vote_reject(vars.boolean(AUTO_VOTE_REJECT_ENV)?);
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    {
+        config = config.with_persist_routing_table(vars.boolean(PERSIST_ROUTING_TABLE_ENV)?);
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    {
+        config =
+            config.with_admin_signature_cache_size(vars.number(ADMIN_SIGNATURE_CACHE_SIZE_ENV)?);
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    {
+        config = config.with_read_only(vars.boolean(READ_ONLY_ENV)?);
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    {
+        config = config.with_resource_min_free_space(vars.number(RESOURCE_MIN_FREE_SPACE_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    {
+        config = config.with_scabbard_circuit_db_dirs(vars.boolean(SCABBARD_CIRCUIT_DB_DIRS_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    {
+        config = config.with_scabbard_db_dirs(vars.list(SCABBARD_DB_DIRS_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    {
+        config = config
+            .with_scabbard_db_growth_increment(vars.number(SCABBARD_DB_GROWTH_INCREMENT_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    {
+        config = config.with_scabbard_db_max_size(vars.number(SCABBARD_DB_MAX_SIZE_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    {
         config = config
-            .with_config_dir(config_dir_env)
-            .with_tls_cert_dir(tls_cert_dir_env)
-            .with_state_dir(state_dir_env)
-            .with_strict_ref_counts(strict_ref_counts);
-
-        #[cfg(feature = "auth")]
-        {
-            config = config
-                .with_oauth_provider(env::var(OAUTH_PROVIDER_ENV).ok())
-                .with_oauth_client_id(env::var(OAUTH_CLIENT_ID_ENV).ok())
-                .with_oauth_client_secret(env::var(OAUTH_CLIENT_SECRET_ENV).ok())
-                .with_oauth_redirect_url(env::var(OAUTH_REDIRECT_URL_ENV).ok())
-                .with_oauth_openid_url(env::var(OAUTH_OPENID_URL_ENV).ok());
+            .with_scabbard_db_warning_thresholds(vars.list(SCABBARD_DB_WARNING_THRESHOLDS_ENV)?);
+    }
+
+    #[cfg(feature = "external-services")]
+    {
+        config = config.with_external_service_types(vars.list(EXTERNAL_SERVICE_TYPES_ENV)?);
+    }
+
+    #[cfg(feature = "tenancy")]
+    {
+        config = config.with_tenant_operators(vars.list(TENANT_OPERATORS_ENV)?);
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    {
+        config = config.with_biome_admin_keys(vars.list(BIOME_ADMIN_KEYS_ENV)?);
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    {
+        config = config.with_clock_skew_threshold(vars.number(CLOCK_SKEW_THRESHOLD_ENV)?);
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    {
+        config = config
+            .with_peer_send_queue_capacity(vars.number(PEER_SEND_QUEUE_CAPACITY_ENV)?)
+            .with_peer_send_weights(vars.list(PEER_SEND_WEIGHTS_ENV)?);
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    {
+        config = config
+            .with_scabbard_batch_history_size(vars.number(SCABBARD_BATCH_HISTORY_SIZE_ENV)?)
+            .with_scabbard_batch_history_max_age(vars.number(SCABBARD_BATCH_HISTORY_MAX_AGE_ENV)?);
+    }
+
+    Ok(config)
+}
+
+/// Returns the given subdirectory of `SPLINTER_HOME`, creating it if it does not exist.
+fn home_subdir(splinter_home: &str, subdir: &str) -> Result<Option<String>, ConfigError> {
+    let opt_path = Path::new(splinter_home).join(subdir);
+    if !opt_path.is_dir() {
+        fs::create_dir_all(&opt_path).map_err(ConfigError::StdError)?;
+    }
+    Ok(opt_path.to_str().map(ToOwned::to_owned))
+}
+
+/// Reads and parses environment variables, resolving `_FILE` variants.
+struct EnvVars<'a> {
+    lookup: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl<'a> EnvVars<'a> {
+    fn new(lookup: &'a dyn Fn(&str) -> Option<String>) -> Self {
+        Self { lookup }
+    }
+
+    fn string(&self, name: &str) -> Result<Option<String>, ConfigError> {
+        let file_name = format!("{}{}", name, FILE_SUFFIX);
+        let value = (self.lookup)(name).filter(|value| !value.is_empty());
+        let file = (self.lookup)(&file_name).filter(|value| !value.is_empty());
+
+        match (value, file) {
+            (Some(_), Some(_)) => Err(ConfigError::InvalidEnvVar(format!(
+                "only one of {} and {} may be set",
+                name, file_name
+            ))),
+            (Some(value), None) => Ok(Some(value)),
+            (None, Some(path)) => {
+                let mut value =
+                    fs::read_to_string(&path).map_err(|err| ConfigError::ReadError {
+                        file: path.clone(),
+                        err,
+                    })?;
+                while value.ends_with('\n') || value.ends_with('\r') {
+                    value.pop();
+                }
+                Ok(Some(value))
+            }
+            (None, None) => Ok(None),
         }
+    }
+
+    fn list(&self, name: &str) -> Result<Option<Vec<String>>, ConfigError> {
+        Ok(self.string(name)?.map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        }))
+    }
+
+    fn number<T: FromStr>(&self, name: &str) -> Result<Option<T>, ConfigError> {
+        self.string(name)?
+            .map(|value| {
+                value.trim().parse().map_err(|_| {
+                    ConfigError::InvalidEnvVar(format!("{} must be a number, got {}", name, value))
+                })
+            })
+            .transpose()
+    }
 
-        Ok(config)
+    fn boolean(&self, name: &str) -> Result<Option<bool>, ConfigError> {
+        self.string(name)?
+            .map(|value| match value.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(true),
+                "false" | "0" | "no" => Ok(false),
+                _ => Err(ConfigError::InvalidEnvVar(format!(
+                    "{} must be true or false, got {}",
+                    name, value
+                ))),
+            })
+            .transpose()
     }
 }
 
@@ -125,6 +617,100 @@ impl PartialConfigBuilder for EnvPartialConfigBuilder {
 mod tests {
     use super::*;
 
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    /// Verify that settings are read from variables and `_FILE` variants, that lists, numbers and
+    /// booleans are parsed, and that unset or empty variables leave the setting unset.
+    #[test]
+    fn test_env_vars() {
+        let temp_dir = TempDir::new("test_env_vars").expect("Failed to create temp dir");
+        let secret_path = temp_dir.path().join("db_url");
+        fs::write(&secret_path, "postgres://admin:secret@db:5432/splinter\n")
+            .expect("Failed to write secret file");
+
+        let mut vars = HashMap::new();
+        vars.insert(NODE_ID_ENV, "node-000".to_string());
+        vars.insert(DISPLAY_NAME_ENV, "".to_string());
+        vars.insert(
+            NETWORK_ENDPOINTS_ENV,
+            "tcps://0.0.0.0:8044, tcp://0.0.0.0:8045,".to_string(),
+        );
+        vars.insert(HEARTBEAT_ENV, "15".to_string());
+        vars.insert(NO_TLS_ENV, "yes".to_string());
+        vars.insert("SPLINTER_DB_URL_FILE", secret_path.display().to_string());
+        let lookup = |name: &str| vars.get(name).cloned();
+        let env_vars = EnvVars::new(&lookup);
+
+        assert_eq!(
+            env_vars
+                .string(NODE_ID_ENV)
+                .expect("Failed to read node ID"),
+            Some("node-000".to_string())
+        );
+        assert_eq!(
+            env_vars
+                .string(DISPLAY_NAME_ENV)
+                .expect("Failed to read display name"),
+            None
+        );
+        assert_eq!(
+            env_vars
+                .list(NETWORK_ENDPOINTS_ENV)
+                .expect("Failed to read endpoints"),
+            Some(vec![
+                "tcps://0.0.0.0:8044".to_string(),
+                "tcp://0.0.0.0:8045".to_string()
+            ])
+        );
+        assert_eq!(
+            env_vars
+                .number::<u64>(HEARTBEAT_ENV)
+                .expect("Failed to read heartbeat"),
+            Some(15)
+        );
+        assert_eq!(
+            env_vars.boolean(NO_TLS_ENV).expect("Failed to read no TLS"),
+            Some(true)
+        );
+        assert_eq!(
+            env_vars
+                .string("SPLINTER_DB_URL")
+                .expect("Failed to read database URL"),
+            Some("postgres://admin:secret@db:5432/splinter".to_string())
+        );
+        assert_eq!(
+            env_vars.string(PEERS_ENV).expect("Failed to read peers"),
+            None
+        );
+    }
+
+    /// Verify that invalid values, and a variable set alongside its `_FILE` variant, are rejected.
+    #[test]
+    fn test_invalid_env_vars() {
+        let mut vars = HashMap::new();
+        vars.insert(HEARTBEAT_ENV, "soon".to_string());
+        vars.insert(NO_TLS_ENV, "maybe".to_string());
+        vars.insert(NODE_ID_ENV, "node-000".to_string());
+        vars.insert("SPLINTER_NODE_ID_FILE", "/run/secrets/node_id".to_string());
+        let lookup = |name: &str| vars.get(name).cloned();
+        let env_vars = EnvVars::new(&lookup);
+
+        assert!(matches!(
+            env_vars.number::<u64>(HEARTBEAT_ENV),
+            Err(ConfigError::InvalidEnvVar(_))
+        ));
+        assert!(matches!(
+            env_vars.boolean(NO_TLS_ENV),
+            Err(ConfigError::InvalidEnvVar(_))
+        ));
+        assert!(matches!(
+            env_vars.string(NODE_ID_ENV),
+            Err(ConfigError::InvalidEnvVar(_))
+        ));
+    }
+
     #[test]
     // This test intermittently fails due to interaction with other tests that set the environment
     // variables used within. It also fails to reset the environment variables to their original
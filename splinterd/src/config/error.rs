@@ -26,6 +26,7 @@ pub enum ConfigError {
     InvalidArgument(clap::Error),
     MissingValue(String),
     InvalidVersion(String),
+    InvalidEnvVar(String),
     StdError(io::Error),
 }
 
@@ -49,6 +50,7 @@ impl Error for ConfigError {
             ConfigError::InvalidArgument(source) => Some(source),
             ConfigError::MissingValue(_) => None,
             ConfigError::InvalidVersion(_) => None,
+            ConfigError::InvalidEnvVar(_) => None,
             ConfigError::StdError(source) => Some(source),
         }
     }
@@ -64,6 +66,9 @@ impl fmt::Display for ConfigError {
             }
             ConfigError::MissingValue(msg) => write!(f, "Configuration value must be set: {}", msg),
             ConfigError::InvalidVersion(msg) => write!(f, "{}", msg),
+            ConfigError::InvalidEnvVar(msg) => {
+                write!(f, "Invalid environment variable: {}", msg)
+            }
             ConfigError::StdError(source) => write!(f, "{}", source),
         }
     }
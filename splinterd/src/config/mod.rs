@@ -19,6 +19,7 @@
 //! sources to be combined into a final `Config` object.
 
 mod builder;
+mod check;
 mod clap;
 mod default;
 mod env;
@@ -28,11 +29,25 @@ mod toml;
 
 use std::time::Duration;
 
+#[cfg(feature = "alerting")]
+use splinter_daemon::alerts::{AlertRule, AlertSinkConfig};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use splinter_daemon::circuit_scopes::CircuitScopeRoleConfig;
+#[cfg(feature = "rest-api-cors-policies")]
+use splinter_daemon::cors::CorsPolicyConfig;
+#[cfg(feature = "event-publisher")]
+use splinter_daemon::event_publisher::EventPublisherConfig;
+#[cfg(feature = "mqtt-bridge")]
+use splinter_daemon::mqtt_bridge::MqttBridgeConfig;
+#[cfg(feature = "registry-remote-auth")]
+use splinter_daemon::registry_auth::RegistryAuthConfig;
+
 pub use crate::config::clap::ClapPartialConfigBuilder;
 pub use crate::config::default::DefaultPartialConfigBuilder;
 pub use crate::config::env::EnvPartialConfigBuilder;
 pub use crate::config::toml::TomlPartialConfigBuilder;
 pub use builder::{ConfigBuilder, PartialConfigBuilder};
+pub use check::{effective_config_toml, validate_config};
 pub use error::ConfigError;
 pub use partial::{ConfigSource, PartialConfig};
 
@@ -57,6 +72,7 @@ pub struct Config {
     network_endpoints: (Vec<String>, ConfigSource),
     advertised_endpoints: (Vec<String>, ConfigSource),
     peers: (Vec<String>, ConfigSource),
+    peer_transport_preference: (Vec<String>, ConfigSource),
     node_id: Option<(String, ConfigSource)>,
     display_name: Option<(String, ConfigSource)>,
     rest_api_endpoint: (String, ConfigSource),
@@ -67,6 +83,12 @@ pub struct Config {
     registry_forced_refresh: (u64, ConfigSource),
     heartbeat: (u64, ConfigSource),
     admin_timeout: (Duration, ConfigSource),
+    orchestrator_incoming_capacity: (usize, ConfigSource),
+    orchestrator_outgoing_capacity: (usize, ConfigSource),
+    orchestrator_channel_capacity: (usize, ConfigSource),
+    admin_processor_incoming_capacity: (usize, ConfigSource),
+    admin_processor_outgoing_capacity: (usize, ConfigSource),
+    admin_processor_channel_capacity: (usize, ConfigSource),
     state_dir: (String, ConfigSource),
     tls_insecure: (bool, ConfigSource),
     no_tls: (bool, ConfigSource),
@@ -74,6 +96,12 @@ pub struct Config {
     enable_biome: (bool, ConfigSource),
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_policies: Option<(Vec<CorsPolicyConfig>, ConfigSource)>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_roles: Option<(Vec<CircuitScopeRoleConfig>, ConfigSource)>,
+    #[cfg(feature = "registry-remote-auth")]
+    registry_auth: Option<(Vec<RegistryAuthConfig>, ConfigSource)>,
     #[cfg(feature = "auth")]
     oauth_provider: Option<(String, ConfigSource)>,
     #[cfg(feature = "auth")]
@@ -81,9 +109,121 @@ pub struct Config {
     #[cfg(feature = "auth")]
     oauth_client_secret: Option<(String, ConfigSource)>,
     #[cfg(feature = "auth")]
+    oauth_public_client: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "auth")]
     oauth_redirect_url: Option<(String, ConfigSource)>,
     #[cfg(feature = "auth")]
     oauth_openid_url: Option<(String, ConfigSource)>,
+    #[cfg(feature = "signing-ed25519")]
+    signing_algorithms: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_domains: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_email: Option<(String, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_directory_url: Option<(String, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_challenge: Option<(String, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_http_bind: Option<(String, ConfigSource)>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_dns_hook: Option<(String, ConfigSource)>,
+    #[cfg(feature = "config-reload")]
+    log_level: Option<(String, ConfigSource)>,
+    #[cfg(feature = "config-reload")]
+    peer_blocklist: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "metrics-push")]
+    metrics_url: Option<(String, ConfigSource)>,
+    #[cfg(feature = "metrics-push")]
+    metrics_username: Option<(String, ConfigSource)>,
+    #[cfg(feature = "metrics-push")]
+    metrics_password: Option<(String, ConfigSource)>,
+    #[cfg(feature = "metrics-push")]
+    metrics_interval: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "alerting")]
+    alert_rules: Option<(Vec<AlertRule>, ConfigSource)>,
+    #[cfg(feature = "alerting")]
+    alert_sinks: Option<(Vec<AlertSinkConfig>, ConfigSource)>,
+    #[cfg(feature = "event-publisher")]
+    event_publisher: Option<(EventPublisherConfig, ConfigSource)>,
+    #[cfg(feature = "mqtt-bridge")]
+    mqtt_bridge: Option<(MqttBridgeConfig, ConfigSource)>,
+    #[cfg(feature = "node-challenge")]
+    node_key_file: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_min_version: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_cipher_suites: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_client_auth: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_min_version: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_cipher_suites: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_client_auth: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-revocation")]
+    tls_crl_file: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-revocation")]
+    tls_ocsp_response_file: Option<(String, ConfigSource)>,
+    #[cfg(feature = "tls-revocation")]
+    tls_require_ocsp: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "spiffe")]
+    spiffe_svid_dir: Option<(String, ConfigSource)>,
+    #[cfg(feature = "spiffe")]
+    spiffe_trust_domain: Option<(String, ConfigSource)>,
+    #[cfg(feature = "spiffe")]
+    spiffe_require_id: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "peer-identity-pinning")]
+    peer_identity_pinning: Option<(String, ConfigSource)>,
+    #[cfg(feature = "admin-key-rotation")]
+    admin_key_rotation_overlap: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "circuit-id-policy")]
+    accepted_circuit_id_formats: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_key_file: Option<(String, ConfigSource)>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_members: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_service_types: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_metadata_pattern: Option<(String, ConfigSource)>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_reject: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "admin-routing-snapshot")]
+    persist_routing_table: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "admin-signature-cache")]
+    admin_signature_cache_size: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "read-only-mode")]
+    read_only: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "resource-watchdog")]
+    resource_min_free_space: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_circuit_db_dirs: Option<(bool, ConfigSource)>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_db_dirs: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_growth_increment: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_max_size: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_warning_thresholds: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "external-services")]
+    external_service_types: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "tenancy")]
+    tenant_operators: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "biome-user-admin")]
+    biome_admin_keys: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_threshold: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_queue_capacity: Option<(usize, ConfigSource)>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_weights: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_size: Option<(usize, ConfigSource)>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_max_age: Option<(u64, ConfigSource)>,
     strict_ref_counts: (bool, ConfigSource),
 }
 
@@ -151,6 +291,10 @@ impl Config {
         &self.peers.0
     }
 
+    pub fn peer_transport_preference(&self) -> &[String] {
+        &self.peer_transport_preference.0
+    }
+
     pub fn node_id(&self) -> Option<&str> {
         if let Some((id, _)) = &self.node_id {
             Some(id)
@@ -196,6 +340,30 @@ impl Config {
         self.admin_timeout.0
     }
 
+    pub fn orchestrator_incoming_capacity(&self) -> usize {
+        self.orchestrator_incoming_capacity.0
+    }
+
+    pub fn orchestrator_outgoing_capacity(&self) -> usize {
+        self.orchestrator_outgoing_capacity.0
+    }
+
+    pub fn orchestrator_channel_capacity(&self) -> usize {
+        self.orchestrator_channel_capacity.0
+    }
+
+    pub fn admin_processor_incoming_capacity(&self) -> usize {
+        self.admin_processor_incoming_capacity.0
+    }
+
+    pub fn admin_processor_outgoing_capacity(&self) -> usize {
+        self.admin_processor_outgoing_capacity.0
+    }
+
+    pub fn admin_processor_channel_capacity(&self) -> usize {
+        self.admin_processor_channel_capacity.0
+    }
+
     pub fn state_dir(&self) -> &str {
         &self.state_dir.0
     }
@@ -222,6 +390,33 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn cors_policies(&self) -> &[CorsPolicyConfig] {
+        if let Some((value, _)) = &self.cors_policies {
+            value
+        } else {
+            &[]
+        }
+    }
+
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    pub fn circuit_scope_roles(&self) -> &[CircuitScopeRoleConfig] {
+        if let Some((value, _)) = &self.circuit_scope_roles {
+            value
+        } else {
+            &[]
+        }
+    }
+
+    #[cfg(feature = "registry-remote-auth")]
+    pub fn registry_auth(&self) -> &[RegistryAuthConfig] {
+        if let Some((value, _)) = &self.registry_auth {
+            value
+        } else {
+            &[]
+        }
+    }
+
     #[cfg(feature = "auth")]
     pub fn oauth_provider(&self) -> Option<&str> {
         if let Some((provider, _)) = &self.oauth_provider {
@@ -249,6 +444,14 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "auth")]
+    pub fn oauth_public_client(&self) -> bool {
+        self.oauth_public_client
+            .as_ref()
+            .map(|(value, _)| *value)
+            .unwrap_or(false)
+    }
+
     #[cfg(feature = "auth")]
     pub fn oauth_redirect_url(&self) -> Option<&str> {
         if let Some((redirect_url, _)) = &self.oauth_redirect_url {
@@ -267,251 +470,1283 @@ impl Config {
         }
     }
 
-    pub fn strict_ref_counts(&self) -> bool {
-        self.strict_ref_counts.0
+    #[cfg(feature = "signing-ed25519")]
+    pub fn signing_algorithms(&self) -> Option<&[String]> {
+        if let Some((algorithms, _)) = &self.signing_algorithms {
+            Some(algorithms)
+        } else {
+            None
+        }
     }
 
-    pub fn config_dir_source(&self) -> &ConfigSource {
-        &self.config_dir.1
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_domains(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.acme_domains {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn storage_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.storage {
-            Some(source)
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_email(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.acme_email {
+            Some(value)
         } else {
             None
         }
     }
 
-    fn tls_cert_dir_source(&self) -> &ConfigSource {
-        &self.tls_cert_dir.1
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_directory_url(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.acme_directory_url {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn tls_ca_file_source(&self) -> &ConfigSource {
-        &self.tls_ca_file.1
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_challenge(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.acme_challenge {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn tls_client_cert_source(&self) -> &ConfigSource {
-        &self.tls_client_cert.1
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_http_bind(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.acme_http_bind {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn tls_client_key_source(&self) -> &ConfigSource {
-        &self.tls_client_key.1
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_dns_hook(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.acme_dns_hook {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn tls_server_cert_source(&self) -> &ConfigSource {
-        &self.tls_server_cert.1
+    #[cfg(feature = "config-reload")]
+    pub fn log_level(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.log_level {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn tls_server_key_source(&self) -> &ConfigSource {
-        &self.tls_server_key.1
+    #[cfg(feature = "config-reload")]
+    pub fn peer_blocklist(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.peer_blocklist {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[cfg(feature = "https-bind")]
-    fn tls_rest_api_cert_source(&self) -> &ConfigSource {
-        &self.tls_rest_api_cert.1
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_url(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.metrics_url {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[cfg(feature = "https-bind")]
-    fn tls_rest_api_key_source(&self) -> &ConfigSource {
-        &self.tls_rest_api_key.1
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_username(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.metrics_username {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[cfg(feature = "service-endpoint")]
-    fn service_endpoint_source(&self) -> &ConfigSource {
-        &self.service_endpoint.1
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_password(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.metrics_password {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn network_endpoints_source(&self) -> &ConfigSource {
-        &self.network_endpoints.1
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_interval(&self) -> Option<u64> {
+        self.metrics_interval.as_ref().map(|(value, _)| *value)
     }
 
-    fn advertised_endpoints_source(&self) -> &ConfigSource {
-        &self.advertised_endpoints.1
+    #[cfg(feature = "alerting")]
+    pub fn alert_rules(&self) -> &[AlertRule] {
+        if let Some((value, _)) = &self.alert_rules {
+            value
+        } else {
+            &[]
+        }
     }
 
-    fn peers_source(&self) -> &ConfigSource {
-        &self.peers.1
+    #[cfg(feature = "alerting")]
+    pub fn alert_sinks(&self) -> &[AlertSinkConfig] {
+        if let Some((value, _)) = &self.alert_sinks {
+            value
+        } else {
+            &[]
+        }
     }
 
-    fn node_id_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.node_id {
-            Some(source)
+    #[cfg(feature = "event-publisher")]
+    pub fn event_publisher(&self) -> Option<&EventPublisherConfig> {
+        self.event_publisher.as_ref().map(|(value, _)| value)
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    pub fn mqtt_bridge(&self) -> Option<&MqttBridgeConfig> {
+        self.mqtt_bridge.as_ref().map(|(value, _)| value)
+    }
+
+    #[cfg(feature = "node-challenge")]
+    pub fn node_key_file(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.node_key_file {
+            Some(value)
         } else {
             None
         }
     }
 
-    fn display_name_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.display_name {
-            Some(source)
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_min_version(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_min_version {
+            Some(value)
         } else {
             None
         }
     }
 
-    fn rest_api_endpoint_source(&self) -> &ConfigSource {
-        &self.rest_api_endpoint.1
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_cipher_suites(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.tls_cipher_suites {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[cfg(feature = "database")]
-    fn database_source(&self) -> &ConfigSource {
-        &self.database.1
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_client_auth(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_client_auth {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn registries_source(&self) -> &ConfigSource {
-        &self.registries.1
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_min_version(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_rest_api_min_version {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn registry_auto_refresh_source(&self) -> &ConfigSource {
-        &self.registry_auto_refresh.1
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_cipher_suites(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.tls_rest_api_cipher_suites {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn registry_forced_refresh_source(&self) -> &ConfigSource {
-        &self.registry_forced_refresh.1
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_client_auth(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_rest_api_client_auth {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn heartbeat_source(&self) -> &ConfigSource {
-        &self.heartbeat.1
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_crl_file(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_crl_file {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn admin_timeout_source(&self) -> &ConfigSource {
-        &self.admin_timeout.1
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_ocsp_response_file(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.tls_ocsp_response_file {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn state_dir_source(&self) -> &ConfigSource {
-        &self.state_dir.1
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_require_ocsp(&self) -> bool {
+        if let Some((value, _)) = &self.tls_require_ocsp {
+            *value
+        } else {
+            false
+        }
     }
 
-    fn tls_insecure_source(&self) -> &ConfigSource {
-        &self.tls_insecure.1
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_svid_dir(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.spiffe_svid_dir {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    fn no_tls_source(&self) -> &ConfigSource {
-        &self.no_tls.1
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_trust_domain(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.spiffe_trust_domain {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
-    fn enable_biome_source(&self) -> &ConfigSource {
-        &self.enable_biome.1
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_require_id(&self) -> bool {
+        if let Some((value, _)) = &self.spiffe_require_id {
+            *value
+        } else {
+            false
+        }
     }
 
-    #[cfg(feature = "rest-api-cors")]
-    pub fn whitelist_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.whitelist {
-            Some(source)
+    #[cfg(feature = "peer-identity-pinning")]
+    pub fn peer_identity_pinning(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.peer_identity_pinning {
+            Some(value)
         } else {
             None
         }
     }
 
-    #[cfg(feature = "auth")]
-    pub fn oauth_provider_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.oauth_provider {
-            Some(source)
+    #[cfg(feature = "admin-key-rotation")]
+    pub fn admin_key_rotation_overlap(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.admin_key_rotation_overlap {
+            Some(*value)
         } else {
             None
         }
     }
 
-    #[cfg(feature = "auth")]
-    pub fn oauth_client_id_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.oauth_client_id {
-            Some(source)
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn accepted_circuit_id_formats(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.accepted_circuit_id_formats {
+            Some(value)
         } else {
             None
         }
     }
 
-    #[cfg(feature = "auth")]
-    pub fn oauth_client_secret_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.oauth_client_secret {
-            Some(source)
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_key_file(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.auto_vote_key_file {
+            Some(value)
         } else {
             None
         }
     }
 
-    #[cfg(feature = "auth")]
-    pub fn oauth_redirect_url_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.oauth_redirect_url {
-            Some(source)
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_members(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.auto_vote_members {
+            Some(value)
         } else {
             None
         }
     }
 
-    #[cfg(feature = "auth")]
-    pub fn oauth_openid_url_source(&self) -> Option<&ConfigSource> {
-        if let Some((_, source)) = &self.oauth_openid_url {
-            Some(source)
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_service_types(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.auto_vote_service_types {
+            Some(value)
         } else {
             None
         }
     }
 
-    fn strict_ref_counts_source(&self) -> &ConfigSource {
-        &self.strict_ref_counts.1
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_metadata_pattern(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.auto_vote_metadata_pattern {
+            Some(value)
+        } else {
+            None
+        }
     }
 
-    #[allow(clippy::cognitive_complexity)]
-    /// Displays the configuration value along with where the value was sourced from.
-    pub fn log_as_debug(&self) {
-        debug!(
-            "Config: config_dir: {} (source: {:?})",
-            self.config_dir(),
-            self.config_dir_source()
-        );
-        if let (Some(id), Some(source)) = (self.storage(), self.storage_source()) {
-            debug!("Config: storage: {} (source: {:?})", id, source,);
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_reject(&self) -> bool {
+        if let Some((value, _)) = &self.auto_vote_reject {
+            *value
+        } else {
+            false
         }
-        debug!(
-            "Config: tls_ca_file: {} (source: {:?})",
-            self.tls_ca_file(),
-            self.tls_ca_file_source()
-        );
-        debug!(
-            "Config: tls_cert_dir: {} (source: {:?})",
-            self.tls_cert_dir(),
-            self.tls_cert_dir_source()
-        );
-        debug!(
-            "Config: tls_client_cert: {} (source: {:?})",
-            self.tls_client_cert(),
-            self.tls_client_cert_source()
-        );
-        debug!(
-            "Config: tls_client_key: {} (source: {:?})",
-            self.tls_client_key(),
-            self.tls_client_key_source()
-        );
-        debug!(
-            "Config: tls_server_cert: {} (source: {:?})",
-            self.tls_server_cert(),
-            self.tls_server_cert_source()
-        );
-        debug!(
-            "Config: tls_server_key: {} (source: {:?})",
-            self.tls_server_key(),
-            self.tls_server_key_source()
-        );
-        #[cfg(feature = "https-bind")]
-        {
-            debug!(
-                "Config: tls_rest_api_cert: {} (source: {:?})",
-                self.tls_rest_api_cert(),
-                self.tls_rest_api_cert_source()
-            );
-            debug!(
-                "Config: tls_rest_api_key: {} (source: {:?})",
-                self.tls_rest_api_key(),
-                self.tls_rest_api_key_source()
-            );
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn persist_routing_table(&self) -> bool {
+        if let Some((value, _)) = &self.persist_routing_table {
+            *value
+        } else {
+            false
         }
-        #[cfg(feature = "service-endpoint")]
-        debug!(
-            "Config: service_endpoint: {} (source: {:?})",
-            self.service_endpoint(),
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    pub fn admin_signature_cache_size(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.admin_signature_cache_size {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    pub fn read_only(&self) -> bool {
+        if let Some((value, _)) = &self.read_only {
+            *value
+        } else {
+            false
+        }
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    pub fn resource_min_free_space(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.resource_min_free_space {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn scabbard_circuit_db_dirs(&self) -> bool {
+        if let Some((value, _)) = &self.scabbard_circuit_db_dirs {
+            *value
+        } else {
+            false
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn scabbard_db_dirs(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.scabbard_db_dirs {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_growth_increment(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.scabbard_db_growth_increment {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_max_size(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.scabbard_db_max_size {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_warning_thresholds(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.scabbard_db_warning_thresholds {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "external-services")]
+    pub fn external_service_types(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.external_service_types {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tenancy")]
+    pub fn tenant_operators(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.tenant_operators {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    pub fn biome_admin_keys(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.biome_admin_keys {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    pub fn clock_skew_threshold(&self) -> Option<u64> {
+        self.clock_skew_threshold.as_ref().map(|(value, _)| *value)
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    pub fn peer_send_queue_capacity(&self) -> Option<usize> {
+        self.peer_send_queue_capacity
+            .as_ref()
+            .map(|(value, _)| *value)
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    pub fn peer_send_weights(&self) -> Option<&[String]> {
+        if let Some((value, _)) = &self.peer_send_weights {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn scabbard_batch_history_size(&self) -> Option<usize> {
+        self.scabbard_batch_history_size
+            .as_ref()
+            .map(|(value, _)| *value)
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn scabbard_batch_history_max_age(&self) -> Option<u64> {
+        self.scabbard_batch_history_max_age
+            .as_ref()
+            .map(|(value, _)| *value)
+    }
+
+    pub fn strict_ref_counts(&self) -> bool {
+        self.strict_ref_counts.0
+    }
+
+    pub fn config_dir_source(&self) -> &ConfigSource {
+        &self.config_dir.1
+    }
+
+    fn storage_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.storage {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    fn tls_cert_dir_source(&self) -> &ConfigSource {
+        &self.tls_cert_dir.1
+    }
+
+    fn tls_ca_file_source(&self) -> &ConfigSource {
+        &self.tls_ca_file.1
+    }
+
+    fn tls_client_cert_source(&self) -> &ConfigSource {
+        &self.tls_client_cert.1
+    }
+
+    fn tls_client_key_source(&self) -> &ConfigSource {
+        &self.tls_client_key.1
+    }
+
+    fn tls_server_cert_source(&self) -> &ConfigSource {
+        &self.tls_server_cert.1
+    }
+
+    fn tls_server_key_source(&self) -> &ConfigSource {
+        &self.tls_server_key.1
+    }
+
+    #[cfg(feature = "https-bind")]
+    fn tls_rest_api_cert_source(&self) -> &ConfigSource {
+        &self.tls_rest_api_cert.1
+    }
+
+    #[cfg(feature = "https-bind")]
+    fn tls_rest_api_key_source(&self) -> &ConfigSource {
+        &self.tls_rest_api_key.1
+    }
+
+    #[cfg(feature = "service-endpoint")]
+    fn service_endpoint_source(&self) -> &ConfigSource {
+        &self.service_endpoint.1
+    }
+
+    fn network_endpoints_source(&self) -> &ConfigSource {
+        &self.network_endpoints.1
+    }
+
+    fn advertised_endpoints_source(&self) -> &ConfigSource {
+        &self.advertised_endpoints.1
+    }
+
+    fn peers_source(&self) -> &ConfigSource {
+        &self.peers.1
+    }
+
+    fn peer_transport_preference_source(&self) -> &ConfigSource {
+        &self.peer_transport_preference.1
+    }
+
+    fn node_id_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.node_id {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    fn display_name_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.display_name {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    fn rest_api_endpoint_source(&self) -> &ConfigSource {
+        &self.rest_api_endpoint.1
+    }
+
+    #[cfg(feature = "database")]
+    fn database_source(&self) -> &ConfigSource {
+        &self.database.1
+    }
+
+    fn registries_source(&self) -> &ConfigSource {
+        &self.registries.1
+    }
+
+    fn registry_auto_refresh_source(&self) -> &ConfigSource {
+        &self.registry_auto_refresh.1
+    }
+
+    fn registry_forced_refresh_source(&self) -> &ConfigSource {
+        &self.registry_forced_refresh.1
+    }
+
+    fn heartbeat_source(&self) -> &ConfigSource {
+        &self.heartbeat.1
+    }
+
+    fn admin_timeout_source(&self) -> &ConfigSource {
+        &self.admin_timeout.1
+    }
+
+    fn orchestrator_incoming_capacity_source(&self) -> &ConfigSource {
+        &self.orchestrator_incoming_capacity.1
+    }
+
+    fn orchestrator_outgoing_capacity_source(&self) -> &ConfigSource {
+        &self.orchestrator_outgoing_capacity.1
+    }
+
+    fn orchestrator_channel_capacity_source(&self) -> &ConfigSource {
+        &self.orchestrator_channel_capacity.1
+    }
+
+    fn admin_processor_incoming_capacity_source(&self) -> &ConfigSource {
+        &self.admin_processor_incoming_capacity.1
+    }
+
+    fn admin_processor_outgoing_capacity_source(&self) -> &ConfigSource {
+        &self.admin_processor_outgoing_capacity.1
+    }
+
+    fn admin_processor_channel_capacity_source(&self) -> &ConfigSource {
+        &self.admin_processor_channel_capacity.1
+    }
+
+    fn state_dir_source(&self) -> &ConfigSource {
+        &self.state_dir.1
+    }
+
+    fn tls_insecure_source(&self) -> &ConfigSource {
+        &self.tls_insecure.1
+    }
+
+    fn no_tls_source(&self) -> &ConfigSource {
+        &self.no_tls.1
+    }
+
+    #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
+    fn enable_biome_source(&self) -> &ConfigSource {
+        &self.enable_biome.1
+    }
+
+    #[cfg(feature = "rest-api-cors")]
+    pub fn whitelist_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.whitelist {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-cors-policies")]
+    fn cors_policies_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.cors_policies {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    fn circuit_scope_roles_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.circuit_scope_roles {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "registry-remote-auth")]
+    fn registry_auth_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.registry_auth {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_provider_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_provider {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_client_id_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_client_id {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_client_secret_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_client_secret {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_redirect_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_redirect_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_public_client_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_public_client {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    pub fn oauth_openid_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.oauth_openid_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "signing-ed25519")]
+    pub fn signing_algorithms_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.signing_algorithms {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_domains_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_domains {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_email_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_email {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_directory_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_directory_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_challenge_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_challenge {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_http_bind_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_http_bind {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    fn acme_dns_hook_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.acme_dns_hook {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "config-reload")]
+    fn log_level_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.log_level {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "config-reload")]
+    fn peer_blocklist_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_blocklist {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    fn metrics_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.metrics_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    fn metrics_username_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.metrics_username {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    fn metrics_password_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.metrics_password {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    fn metrics_interval_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.metrics_interval {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "alerting")]
+    fn alert_rules_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.alert_rules {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "alerting")]
+    fn alert_sinks_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.alert_sinks {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "event-publisher")]
+    fn event_publisher_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.event_publisher {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    fn mqtt_bridge_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.mqtt_bridge {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "node-challenge")]
+    fn node_key_file_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.node_key_file {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_min_version_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_min_version {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_cipher_suites_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_cipher_suites {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_client_auth_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_client_auth {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_rest_api_min_version_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_rest_api_min_version {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_rest_api_cipher_suites_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_rest_api_cipher_suites {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-policy")]
+    fn tls_rest_api_client_auth_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_rest_api_client_auth {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    fn tls_crl_file_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_crl_file {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    fn tls_ocsp_response_file_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_ocsp_response_file {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    fn tls_require_ocsp_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tls_require_ocsp {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "spiffe")]
+    fn spiffe_svid_dir_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.spiffe_svid_dir {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "spiffe")]
+    fn spiffe_trust_domain_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.spiffe_trust_domain {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "spiffe")]
+    fn spiffe_require_id_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.spiffe_require_id {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    fn peer_identity_pinning_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_identity_pinning {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-key-rotation")]
+    fn admin_key_rotation_overlap_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.admin_key_rotation_overlap {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    fn accepted_circuit_id_formats_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.accepted_circuit_id_formats {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote_key_file_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.auto_vote_key_file {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote_members_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.auto_vote_members {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote_service_types_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.auto_vote_service_types {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote_metadata_pattern_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.auto_vote_metadata_pattern {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    fn auto_vote_reject_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.auto_vote_reject {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    fn persist_routing_table_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.persist_routing_table {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    fn admin_signature_cache_size_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.admin_signature_cache_size {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    fn read_only_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.read_only {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    fn resource_min_free_space_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.resource_min_free_space {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    fn scabbard_circuit_db_dirs_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_circuit_db_dirs {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    fn scabbard_db_dirs_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_db_dirs {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    fn scabbard_db_growth_increment_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_db_growth_increment {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    fn scabbard_db_max_size_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_db_max_size {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    fn scabbard_db_warning_thresholds_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_db_warning_thresholds {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "external-services")]
+    fn external_service_types_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.external_service_types {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "tenancy")]
+    fn tenant_operators_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.tenant_operators {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    fn biome_admin_keys_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.biome_admin_keys {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    fn clock_skew_threshold_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.clock_skew_threshold {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    fn peer_send_queue_capacity_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_send_queue_capacity {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    fn peer_send_weights_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_send_weights {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    fn scabbard_batch_history_size_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_batch_history_size {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    fn scabbard_batch_history_max_age_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.scabbard_batch_history_max_age {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    fn strict_ref_counts_source(&self) -> &ConfigSource {
+        &self.strict_ref_counts.1
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    /// Displays the configuration value along with where the value was sourced from.
+    pub fn log_as_debug(&self) {
+        debug!(
+            "Config: config_dir: {} (source: {:?})",
+            self.config_dir(),
+            self.config_dir_source()
+        );
+        if let (Some(id), Some(source)) = (self.storage(), self.storage_source()) {
+            debug!("Config: storage: {} (source: {:?})", id, source,);
+        }
+        debug!(
+            "Config: tls_ca_file: {} (source: {:?})",
+            self.tls_ca_file(),
+            self.tls_ca_file_source()
+        );
+        debug!(
+            "Config: tls_cert_dir: {} (source: {:?})",
+            self.tls_cert_dir(),
+            self.tls_cert_dir_source()
+        );
+        debug!(
+            "Config: tls_client_cert: {} (source: {:?})",
+            self.tls_client_cert(),
+            self.tls_client_cert_source()
+        );
+        debug!(
+            "Config: tls_client_key: {} (source: {:?})",
+            self.tls_client_key(),
+            self.tls_client_key_source()
+        );
+        debug!(
+            "Config: tls_server_cert: {} (source: {:?})",
+            self.tls_server_cert(),
+            self.tls_server_cert_source()
+        );
+        debug!(
+            "Config: tls_server_key: {} (source: {:?})",
+            self.tls_server_key(),
+            self.tls_server_key_source()
+        );
+        #[cfg(feature = "https-bind")]
+        {
+            debug!(
+                "Config: tls_rest_api_cert: {} (source: {:?})",
+                self.tls_rest_api_cert(),
+                self.tls_rest_api_cert_source()
+            );
+            debug!(
+                "Config: tls_rest_api_key: {} (source: {:?})",
+                self.tls_rest_api_key(),
+                self.tls_rest_api_key_source()
+            );
+        }
+        #[cfg(feature = "service-endpoint")]
+        debug!(
+            "Config: service_endpoint: {} (source: {:?})",
+            self.service_endpoint(),
             self.service_endpoint_source()
         );
         debug!(
@@ -529,112 +1764,683 @@ impl Config {
             self.peers(),
             self.peers_source()
         );
+        debug!(
+            "Config: peer_transport_preference: {:?} (source: {:?})",
+            self.peer_transport_preference(),
+            self.peer_transport_preference_source()
+        );
         if let (Some(id), Some(source)) = (self.node_id(), self.node_id_source()) {
             debug!("Config: node_id: {} (source: {:?})", id, source,);
         }
-        if let (Some(name), Some(source)) = (self.display_name(), self.display_name_source()) {
-            debug!("Config: display_name: {} (source: {:?})", name, source,);
+        if let (Some(name), Some(source)) = (self.display_name(), self.display_name_source()) {
+            debug!("Config: display_name: {} (source: {:?})", name, source,);
+        }
+        debug!(
+            "Config: rest_api_endpoint: {} (source: {:?})",
+            self.rest_api_endpoint(),
+            self.rest_api_endpoint_source()
+        );
+        debug!(
+            "Config: registries: {:?} (source: {:?})",
+            self.registries(),
+            self.registries_source()
+        );
+        debug!(
+            "Config: registry_auto_refresh: {} (source: {:?})",
+            self.registry_auto_refresh(),
+            self.registry_auto_refresh_source()
+        );
+        debug!(
+            "Config: registry_forced_refresh: {} (source: {:?})",
+            self.registry_forced_refresh(),
+            self.registry_forced_refresh_source()
+        );
+        debug!(
+            "Config: state_dir: {} (source: {:?})",
+            self.state_dir(),
+            self.state_dir_source()
+        );
+        debug!(
+            "Config: heartbeat: {} (source: {:?})",
+            self.heartbeat(),
+            self.heartbeat_source()
+        );
+        debug!(
+            "Config: admin_timeout: {:?} (source: {:?})",
+            self.admin_timeout(),
+            self.admin_timeout_source()
+        );
+        debug!(
+            "Config: orchestrator_incoming_capacity: {} (source: {:?})",
+            self.orchestrator_incoming_capacity(),
+            self.orchestrator_incoming_capacity_source()
+        );
+        debug!(
+            "Config: orchestrator_outgoing_capacity: {} (source: {:?})",
+            self.orchestrator_outgoing_capacity(),
+            self.orchestrator_outgoing_capacity_source()
+        );
+        debug!(
+            "Config: orchestrator_channel_capacity: {} (source: {:?})",
+            self.orchestrator_channel_capacity(),
+            self.orchestrator_channel_capacity_source()
+        );
+        debug!(
+            "Config: admin_processor_incoming_capacity: {} (source: {:?})",
+            self.admin_processor_incoming_capacity(),
+            self.admin_processor_incoming_capacity_source()
+        );
+        debug!(
+            "Config: admin_processor_outgoing_capacity: {} (source: {:?})",
+            self.admin_processor_outgoing_capacity(),
+            self.admin_processor_outgoing_capacity_source()
+        );
+        debug!(
+            "Config: admin_processor_channel_capacity: {} (source: {:?})",
+            self.admin_processor_channel_capacity(),
+            self.admin_processor_channel_capacity_source()
+        );
+        #[cfg(feature = "database")]
+        debug!(
+            "database: {} (source: {:?})",
+            self.database(),
+            self.database_source(),
+        );
+        debug!(
+            "Config: tls_insecure: {:?} (source: {:?})",
+            self.tls_insecure(),
+            self.tls_insecure_source()
+        );
+        debug!(
+            "Config: no_tls: {:?} (source: {:?})",
+            self.no_tls(),
+            self.no_tls_source()
+        );
+        #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
+        debug!(
+            "Config: enable_biome: {:?} (source: {:?})",
+            self.enable_biome(),
+            self.enable_biome_source()
+        );
+        #[cfg(feature = "rest-api-cors")]
+        self.log_whitelist();
+        #[cfg(feature = "auth")]
+        {
+            if let (Some(provider), Some(source)) =
+                (self.oauth_provider(), self.oauth_provider_source())
+            {
+                debug!(
+                    "Config: oauth_provider: {} (source: {:?})",
+                    provider, source,
+                );
+            }
+            if let (Some(client_id), Some(source)) =
+                (self.oauth_client_id(), self.oauth_client_id_source())
+            {
+                debug!(
+                    "Config: oauth_client_id: {} (source: {:?})",
+                    client_id, source,
+                );
+            }
+            if let (Some(_), Some(source)) = (
+                self.oauth_client_secret(),
+                self.oauth_client_secret_source(),
+            ) {
+                debug!(
+                    "Config: oauth_client_secret: <HIDDEN> (source: {:?})",
+                    source,
+                );
+            }
+            if let (Some(redirect_url), Some(source)) =
+                (self.oauth_redirect_url(), self.oauth_redirect_url_source())
+            {
+                debug!(
+                    "Config: oauth_redirect_url: {} (source: {:?})",
+                    redirect_url, source,
+                );
+            }
+            if let Some(source) = self.oauth_public_client_source() {
+                debug!(
+                    "Config: oauth_public_client: {} (source: {:?})",
+                    self.oauth_public_client(),
+                    source,
+                );
+            }
+            if let (Some(openid_url), Some(source)) =
+                (self.oauth_openid_url(), self.oauth_openid_url_source())
+            {
+                debug!(
+                    "Config: oauth_openid_url: {} (source: {:?})",
+                    openid_url, source,
+                );
+            }
+        }
+        #[cfg(feature = "signing-ed25519")]
+        {
+            if let (Some(algorithms), Some(source)) =
+                (self.signing_algorithms(), self.signing_algorithms_source())
+            {
+                debug!(
+                    "Config: signing_algorithms: {:?} (source: {:?})",
+                    algorithms, source,
+                );
+            }
+        }
+        #[cfg(feature = "rest-api-acme")]
+        {
+            if let (Some(value), Some(source)) = (self.acme_domains(), self.acme_domains_source()) {
+                debug!("Config: acme_domains: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) = (self.acme_email(), self.acme_email_source()) {
+                debug!("Config: acme_email: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.acme_directory_url(), self.acme_directory_url_source())
+            {
+                debug!(
+                    "Config: acme_directory_url: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+            if let (Some(value), Some(source)) =
+                (self.acme_challenge(), self.acme_challenge_source())
+            {
+                debug!("Config: acme_challenge: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.acme_http_bind(), self.acme_http_bind_source())
+            {
+                debug!("Config: acme_http_bind: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) = (self.acme_dns_hook(), self.acme_dns_hook_source())
+            {
+                debug!("Config: acme_dns_hook: {:?} (source: {:?})", value, source);
+            }
+        }
+        #[cfg(feature = "config-reload")]
+        {
+            if let (Some(value), Some(source)) = (self.log_level(), self.log_level_source()) {
+                debug!("Config: log_level: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.peer_blocklist(), self.peer_blocklist_source())
+            {
+                debug!("Config: peer_blocklist: {:?} (source: {:?})", value, source);
+            }
+        }
+        #[cfg(feature = "metrics-push")]
+        {
+            if let (Some(value), Some(source)) = (self.metrics_url(), self.metrics_url_source()) {
+                debug!("Config: metrics_url: {:?} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.metrics_username(), self.metrics_username_source())
+            {
+                debug!(
+                    "Config: metrics_username: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+            if let Some(source) = self.metrics_password_source() {
+                debug!("Config: metrics_password: <HIDDEN> (source: {:?})", source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.metrics_interval(), self.metrics_interval_source())
+            {
+                debug!(
+                    "Config: metrics_interval: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "alerting")]
+        {
+            if let Some(source) = self.alert_rules_source() {
+                debug!(
+                    "Config: alert_rules: {:?} (source: {:?})",
+                    self.alert_rules(),
+                    source
+                );
+            }
+            if let Some(source) = self.alert_sinks_source() {
+                debug!(
+                    "Config: alert_sinks: {:?} (source: {:?})",
+                    self.alert_sinks(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "event-publisher")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.event_publisher(), self.event_publisher_source())
+            {
+                debug!(
+                    "Config: event_publisher: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "mqtt-bridge")]
+        {
+            if let (Some(value), Some(source)) = (self.mqtt_bridge(), self.mqtt_bridge_source()) {
+                debug!("Config: mqtt_bridge: {:?} (source: {:?})", value, source);
+            }
+        }
+        #[cfg(feature = "node-challenge")]
+        {
+            if let (Some(value), Some(source)) = (self.node_key_file(), self.node_key_file_source())
+            {
+                debug!("Config: node_key_file: {:?} (source: {:?})", value, source);
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.tls_min_version(), self.tls_min_version_source())
+            {
+                debug!(
+                    "Config: tls_min_version: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.tls_cipher_suites(), self.tls_cipher_suites_source())
+            {
+                debug!(
+                    "Config: tls_cipher_suites: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.tls_client_auth(), self.tls_client_auth_source())
+            {
+                debug!(
+                    "Config: tls_client_auth: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.tls_rest_api_min_version(),
+                self.tls_rest_api_min_version_source(),
+            ) {
+                debug!(
+                    "Config: tls_rest_api_min_version: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.tls_rest_api_cipher_suites(),
+                self.tls_rest_api_cipher_suites_source(),
+            ) {
+                debug!(
+                    "Config: tls_rest_api_cipher_suites: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-policy")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.tls_rest_api_client_auth(),
+                self.tls_rest_api_client_auth_source(),
+            ) {
+                debug!(
+                    "Config: tls_rest_api_client_auth: {:?} (source: {:?})",
+                    value, source
+                );
+            }
         }
-        debug!(
-            "Config: rest_api_endpoint: {} (source: {:?})",
-            self.rest_api_endpoint(),
-            self.rest_api_endpoint_source()
-        );
-        debug!(
-            "Config: registries: {:?} (source: {:?})",
-            self.registries(),
-            self.registries_source()
-        );
-        debug!(
-            "Config: registry_auto_refresh: {} (source: {:?})",
-            self.registry_auto_refresh(),
-            self.registry_auto_refresh_source()
-        );
-        debug!(
-            "Config: registry_forced_refresh: {} (source: {:?})",
-            self.registry_forced_refresh(),
-            self.registry_forced_refresh_source()
-        );
-        debug!(
-            "Config: state_dir: {} (source: {:?})",
-            self.state_dir(),
-            self.state_dir_source()
-        );
-        debug!(
-            "Config: heartbeat: {} (source: {:?})",
-            self.heartbeat(),
-            self.heartbeat_source()
-        );
-        debug!(
-            "Config: admin_timeout: {:?} (source: {:?})",
-            self.admin_timeout(),
-            self.admin_timeout_source()
-        );
-        #[cfg(feature = "database")]
-        debug!(
-            "database: {} (source: {:?})",
-            self.database(),
-            self.database_source(),
-        );
-        debug!(
-            "Config: tls_insecure: {:?} (source: {:?})",
-            self.tls_insecure(),
-            self.tls_insecure_source()
-        );
-        debug!(
-            "Config: no_tls: {:?} (source: {:?})",
-            self.no_tls(),
-            self.no_tls_source()
-        );
-        #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
-        debug!(
-            "Config: enable_biome: {:?} (source: {:?})",
-            self.enable_biome(),
-            self.enable_biome_source()
-        );
-        #[cfg(feature = "rest-api-cors")]
-        self.log_whitelist();
-        #[cfg(feature = "auth")]
+        #[cfg(feature = "tls-revocation")]
         {
-            if let (Some(provider), Some(source)) =
-                (self.oauth_provider(), self.oauth_provider_source())
+            if let (Some(value), Some(source)) = (self.tls_crl_file(), self.tls_crl_file_source()) {
+                debug!("Config: tls_crl_file: {:?} (source: {:?})", value, source);
+            }
+        }
+        #[cfg(feature = "tls-revocation")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.tls_ocsp_response_file(),
+                self.tls_ocsp_response_file_source(),
+            ) {
+                debug!(
+                    "Config: tls_ocsp_response_file: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tls-revocation")]
+        {
+            if let Some(source) = self.tls_require_ocsp_source() {
+                debug!(
+                    "Config: tls_require_ocsp: {:?} (source: {:?})",
+                    self.tls_require_ocsp(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "spiffe")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.spiffe_svid_dir(), self.spiffe_svid_dir_source())
             {
                 debug!(
-                    "Config: oauth_provider: {} (source: {:?})",
-                    provider, source,
+                    "Config: spiffe_svid_dir: {:?} (source: {:?})",
+                    value, source
                 );
             }
-            if let (Some(client_id), Some(source)) =
-                (self.oauth_client_id(), self.oauth_client_id_source())
+        }
+        #[cfg(feature = "spiffe")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.spiffe_trust_domain(),
+                self.spiffe_trust_domain_source(),
+            ) {
+                debug!(
+                    "Config: spiffe_trust_domain: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "spiffe")]
+        {
+            if let Some(source) = self.spiffe_require_id_source() {
+                debug!(
+                    "Config: spiffe_require_id: {:?} (source: {:?})",
+                    self.spiffe_require_id(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.peer_identity_pinning(),
+                self.peer_identity_pinning_source(),
+            ) {
+                debug!(
+                    "Config: peer_identity_pinning: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "admin-key-rotation")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.admin_key_rotation_overlap(),
+                self.admin_key_rotation_overlap_source(),
+            ) {
+                debug!(
+                    "Config: admin_key_rotation_overlap: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "circuit-id-policy")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.accepted_circuit_id_formats(),
+                self.accepted_circuit_id_formats_source(),
+            ) {
+                debug!(
+                    "Config: accepted_circuit_id_formats: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.auto_vote_key_file(), self.auto_vote_key_file_source())
             {
                 debug!(
-                    "Config: oauth_client_id: {} (source: {:?})",
-                    client_id, source,
+                    "Config: auto_vote_key_file: {:?} (source: {:?})",
+                    value, source
                 );
             }
-            if let (Some(_), Some(source)) = (
-                self.oauth_client_secret(),
-                self.oauth_client_secret_source(),
+        }
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.auto_vote_members(), self.auto_vote_members_source())
+            {
+                debug!(
+                    "Config: auto_vote_members: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.auto_vote_service_types(),
+                self.auto_vote_service_types_source(),
             ) {
                 debug!(
-                    "Config: oauth_client_secret: <HIDDEN> (source: {:?})",
-                    source,
+                    "Config: auto_vote_service_types: {:?} (source: {:?})",
+                    value, source
                 );
             }
-            if let (Some(redirect_url), Some(source)) =
-                (self.oauth_redirect_url(), self.oauth_redirect_url_source())
+        }
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.auto_vote_metadata_pattern(),
+                self.auto_vote_metadata_pattern_source(),
+            ) {
+                debug!(
+                    "Config: auto_vote_metadata_pattern: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let Some(source) = self.auto_vote_reject_source() {
+                debug!(
+                    "Config: auto_vote_reject: {:?} (source: {:?})",
+                    self.auto_vote_reject(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "admin-routing-snapshot")]
+        {
+            if let Some(source) = self.persist_routing_table_source() {
+                debug!(
+                    "Config: persist_routing_table: {:?} (source: {:?})",
+                    self.persist_routing_table(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.admin_signature_cache_size(),
+                self.admin_signature_cache_size_source(),
+            ) {
+                debug!(
+                    "Config: admin_signature_cache_size: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "read-only-mode")]
+        {
+            if let Some(source) = self.read_only_source() {
+                debug!(
+                    "Config: read_only: {:?} (source: {:?})",
+                    self.read_only(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "resource-watchdog")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.resource_min_free_space(),
+                self.resource_min_free_space_source(),
+            ) {
+                debug!(
+                    "Config: resource_min_free_space: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            if let Some(source) = self.scabbard_circuit_db_dirs_source() {
+                debug!(
+                    "Config: scabbard_circuit_db_dirs: {:?} (source: {:?})",
+                    self.scabbard_circuit_db_dirs(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.scabbard_db_dirs(), self.scabbard_db_dirs_source())
             {
                 debug!(
-                    "Config: oauth_redirect_url: {} (source: {:?})",
-                    redirect_url, source,
+                    "Config: scabbard_db_dirs: {:?} (source: {:?})",
+                    value, source
                 );
             }
-            if let (Some(openid_url), Some(source)) =
-                (self.oauth_openid_url(), self.oauth_openid_url_source())
+        }
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.scabbard_db_growth_increment(),
+                self.scabbard_db_growth_increment_source(),
+            ) {
+                debug!(
+                    "Config: scabbard_db_growth_increment: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.scabbard_db_max_size(),
+                self.scabbard_db_max_size_source(),
+            ) {
+                debug!(
+                    "Config: scabbard_db_max_size: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.scabbard_db_warning_thresholds(),
+                self.scabbard_db_warning_thresholds_source(),
+            ) {
+                debug!(
+                    "Config: scabbard_db_warning_thresholds: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "external-services")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.external_service_types(),
+                self.external_service_types_source(),
+            ) {
+                debug!(
+                    "Config: external_service_types: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "tenancy")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.tenant_operators(), self.tenant_operators_source())
             {
                 debug!(
-                    "Config: oauth_openid_url: {} (source: {:?})",
-                    openid_url, source,
+                    "Config: tenant_operators: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "biome-user-admin")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.biome_admin_keys(), self.biome_admin_keys_source())
+            {
+                debug!(
+                    "Config: biome_admin_keys: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "network-clock-skew")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.clock_skew_threshold(),
+                self.clock_skew_threshold_source(),
+            ) {
+                debug!(
+                    "Config: clock_skew_threshold: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "peer-send-limits")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.peer_send_queue_capacity(),
+                self.peer_send_queue_capacity_source(),
+            ) {
+                debug!(
+                    "Config: peer_send_queue_capacity: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+            if let (Some(value), Some(source)) =
+                (self.peer_send_weights(), self.peer_send_weights_source())
+            {
+                debug!(
+                    "Config: peer_send_weights: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "scabbard-batch-history")]
+        {
+            if let (Some(value), Some(source)) = (
+                self.scabbard_batch_history_size(),
+                self.scabbard_batch_history_size_source(),
+            ) {
+                debug!(
+                    "Config: scabbard_batch_history_size: {:?} (source: {:?})",
+                    value, source
+                );
+            }
+            if let (Some(value), Some(source)) = (
+                self.scabbard_batch_history_max_age(),
+                self.scabbard_batch_history_max_age_source(),
+            ) {
+                debug!(
+                    "Config: scabbard_batch_history_max_age: {:?} (source: {:?})",
+                    value, source
                 );
             }
         }
@@ -650,6 +2456,37 @@ impl Config {
         if let (Some(list), Some(source)) = (self.whitelist(), self.whitelist_source()) {
             debug!("Config: whitelist: {:?} (source: {:?})", list, source,);
         }
+        #[cfg(feature = "rest-api-cors-policies")]
+        {
+            if let Some(source) = self.cors_policies_source() {
+                debug!(
+                    "Config: cors_policies: {:?} (source: {:?})",
+                    self.cors_policies(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        {
+            if let Some(source) = self.circuit_scope_roles_source() {
+                debug!(
+                    "Config: circuit_scope_roles: {:?} (source: {:?})",
+                    self.circuit_scope_roles(),
+                    source
+                );
+            }
+        }
+        #[cfg(feature = "registry-remote-auth")]
+        {
+            if let Some(source) = self.registry_auth_source() {
+                // Only the locations of the secrets files are logged, never the secrets
+                debug!(
+                    "Config: registry_auth: {:?} (source: {:?})",
+                    self.registry_auth(),
+                    source
+                );
+            }
+        }
     }
 }
 
@@ -17,6 +17,19 @@
 
 use std::time::Duration;
 
+#[cfg(feature = "alerting")]
+use splinter_daemon::alerts::{AlertRule, AlertSinkConfig};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use splinter_daemon::circuit_scopes::CircuitScopeRoleConfig;
+#[cfg(feature = "rest-api-cors-policies")]
+use splinter_daemon::cors::CorsPolicyConfig;
+#[cfg(feature = "event-publisher")]
+use splinter_daemon::event_publisher::EventPublisherConfig;
+#[cfg(feature = "mqtt-bridge")]
+use splinter_daemon::mqtt_bridge::MqttBridgeConfig;
+#[cfg(feature = "registry-remote-auth")]
+use splinter_daemon::registry_auth::RegistryAuthConfig;
+
 /// `ConfigSource` displays the source of configuration values, used to identify which of the various
 /// config modules were used to create a particular `PartialConfig` object.
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -50,6 +63,7 @@ pub struct PartialConfig {
     network_endpoints: Option<Vec<String>>,
     advertised_endpoints: Option<Vec<String>>,
     peers: Option<Vec<String>>,
+    peer_transport_preference: Option<Vec<String>>,
     node_id: Option<String>,
     display_name: Option<String>,
     rest_api_endpoint: Option<String>,
@@ -60,6 +74,12 @@ pub struct PartialConfig {
     registry_forced_refresh: Option<u64>,
     heartbeat: Option<u64>,
     admin_timeout: Option<Duration>,
+    orchestrator_incoming_capacity: Option<usize>,
+    orchestrator_outgoing_capacity: Option<usize>,
+    orchestrator_channel_capacity: Option<usize>,
+    admin_processor_incoming_capacity: Option<usize>,
+    admin_processor_outgoing_capacity: Option<usize>,
+    admin_processor_channel_capacity: Option<usize>,
     state_dir: Option<String>,
     tls_insecure: Option<bool>,
     no_tls: Option<bool>,
@@ -67,6 +87,12 @@ pub struct PartialConfig {
     enable_biome: Option<bool>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_policies: Option<Vec<CorsPolicyConfig>>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_roles: Option<Vec<CircuitScopeRoleConfig>>,
+    #[cfg(feature = "registry-remote-auth")]
+    registry_auth: Option<Vec<RegistryAuthConfig>>,
     #[cfg(feature = "auth")]
     oauth_provider: Option<String>,
     #[cfg(feature = "auth")]
@@ -74,9 +100,121 @@ pub struct PartialConfig {
     #[cfg(feature = "auth")]
     oauth_client_secret: Option<String>,
     #[cfg(feature = "auth")]
+    oauth_public_client: Option<bool>,
+    #[cfg(feature = "auth")]
     oauth_redirect_url: Option<String>,
     #[cfg(feature = "auth")]
     oauth_openid_url: Option<String>,
+    #[cfg(feature = "signing-ed25519")]
+    signing_algorithms: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_domains: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_email: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_directory_url: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_challenge: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_http_bind: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_dns_hook: Option<String>,
+    #[cfg(feature = "config-reload")]
+    log_level: Option<String>,
+    #[cfg(feature = "config-reload")]
+    peer_blocklist: Option<Vec<String>>,
+    #[cfg(feature = "metrics-push")]
+    metrics_url: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_username: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_password: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_interval: Option<u64>,
+    #[cfg(feature = "alerting")]
+    alert_rules: Option<Vec<AlertRule>>,
+    #[cfg(feature = "alerting")]
+    alert_sinks: Option<Vec<AlertSinkConfig>>,
+    #[cfg(feature = "event-publisher")]
+    event_publisher: Option<EventPublisherConfig>,
+    #[cfg(feature = "mqtt-bridge")]
+    mqtt_bridge: Option<MqttBridgeConfig>,
+    #[cfg(feature = "node-challenge")]
+    node_key_file: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_min_version: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_cipher_suites: Option<Vec<String>>,
+    #[cfg(feature = "tls-policy")]
+    tls_client_auth: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_min_version: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_cipher_suites: Option<Vec<String>>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_client_auth: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_crl_file: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_ocsp_response_file: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_require_ocsp: Option<bool>,
+    #[cfg(feature = "spiffe")]
+    spiffe_svid_dir: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_trust_domain: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_require_id: Option<bool>,
+    #[cfg(feature = "peer-identity-pinning")]
+    peer_identity_pinning: Option<String>,
+    #[cfg(feature = "admin-key-rotation")]
+    admin_key_rotation_overlap: Option<u64>,
+    #[cfg(feature = "circuit-id-policy")]
+    accepted_circuit_id_formats: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_key_file: Option<String>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_members: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_service_types: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_metadata_pattern: Option<String>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_reject: Option<bool>,
+    #[cfg(feature = "admin-routing-snapshot")]
+    persist_routing_table: Option<bool>,
+    #[cfg(feature = "admin-signature-cache")]
+    admin_signature_cache_size: Option<u64>,
+    #[cfg(feature = "read-only-mode")]
+    read_only: Option<bool>,
+    #[cfg(feature = "resource-watchdog")]
+    resource_min_free_space: Option<u64>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_circuit_db_dirs: Option<bool>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_db_dirs: Option<Vec<String>>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_growth_increment: Option<u64>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_max_size: Option<u64>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_warning_thresholds: Option<Vec<String>>,
+    #[cfg(feature = "external-services")]
+    external_service_types: Option<Vec<String>>,
+    #[cfg(feature = "tenancy")]
+    tenant_operators: Option<Vec<String>>,
+    #[cfg(feature = "biome-user-admin")]
+    biome_admin_keys: Option<Vec<String>>,
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_threshold: Option<u64>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_queue_capacity: Option<usize>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_weights: Option<Vec<String>>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_size: Option<usize>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_max_age: Option<u64>,
     strict_ref_counts: Option<bool>,
 }
 
@@ -102,6 +240,7 @@ impl PartialConfig {
             network_endpoints: None,
             advertised_endpoints: None,
             peers: None,
+            peer_transport_preference: None,
             node_id: None,
             display_name: None,
             rest_api_endpoint: None,
@@ -112,6 +251,12 @@ impl PartialConfig {
             registry_forced_refresh: None,
             heartbeat: None,
             admin_timeout: None,
+            orchestrator_incoming_capacity: None,
+            orchestrator_outgoing_capacity: None,
+            orchestrator_channel_capacity: None,
+            admin_processor_incoming_capacity: None,
+            admin_processor_outgoing_capacity: None,
+            admin_processor_channel_capacity: None,
             state_dir: None,
             tls_insecure: None,
             no_tls: None,
@@ -119,6 +264,12 @@ impl PartialConfig {
             enable_biome: None,
             #[cfg(feature = "rest-api-cors")]
             whitelist: None,
+            #[cfg(feature = "rest-api-cors-policies")]
+            cors_policies: None,
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_roles: None,
+            #[cfg(feature = "registry-remote-auth")]
+            registry_auth: None,
             #[cfg(feature = "auth")]
             oauth_provider: None,
             #[cfg(feature = "auth")]
@@ -126,9 +277,121 @@ impl PartialConfig {
             #[cfg(feature = "auth")]
             oauth_client_secret: None,
             #[cfg(feature = "auth")]
+            oauth_public_client: None,
+            #[cfg(feature = "auth")]
             oauth_redirect_url: None,
             #[cfg(feature = "auth")]
             oauth_openid_url: None,
+            #[cfg(feature = "signing-ed25519")]
+            signing_algorithms: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_domains: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_email: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_directory_url: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_challenge: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_http_bind: None,
+            #[cfg(feature = "rest-api-acme")]
+            acme_dns_hook: None,
+            #[cfg(feature = "config-reload")]
+            log_level: None,
+            #[cfg(feature = "config-reload")]
+            peer_blocklist: None,
+            #[cfg(feature = "metrics-push")]
+            metrics_url: None,
+            #[cfg(feature = "metrics-push")]
+            metrics_username: None,
+            #[cfg(feature = "metrics-push")]
+            metrics_password: None,
+            #[cfg(feature = "metrics-push")]
+            metrics_interval: None,
+            #[cfg(feature = "alerting")]
+            alert_rules: None,
+            #[cfg(feature = "alerting")]
+            alert_sinks: None,
+            #[cfg(feature = "event-publisher")]
+            event_publisher: None,
+            #[cfg(feature = "mqtt-bridge")]
+            mqtt_bridge: None,
+            #[cfg(feature = "node-challenge")]
+            node_key_file: None,
+            #[cfg(feature = "tls-policy")]
+            tls_min_version: None,
+            #[cfg(feature = "tls-policy")]
+            tls_cipher_suites: None,
+            #[cfg(feature = "tls-policy")]
+            tls_client_auth: None,
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_min_version: None,
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_cipher_suites: None,
+            #[cfg(feature = "tls-policy")]
+            tls_rest_api_client_auth: None,
+            #[cfg(feature = "tls-revocation")]
+            tls_crl_file: None,
+            #[cfg(feature = "tls-revocation")]
+            tls_ocsp_response_file: None,
+            #[cfg(feature = "tls-revocation")]
+            tls_require_ocsp: None,
+            #[cfg(feature = "spiffe")]
+            spiffe_svid_dir: None,
+            #[cfg(feature = "spiffe")]
+            spiffe_trust_domain: None,
+            #[cfg(feature = "spiffe")]
+            spiffe_require_id: None,
+            #[cfg(feature = "peer-identity-pinning")]
+            peer_identity_pinning: None,
+            #[cfg(feature = "admin-key-rotation")]
+            admin_key_rotation_overlap: None,
+            #[cfg(feature = "circuit-id-policy")]
+            accepted_circuit_id_formats: None,
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_key_file: None,
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_members: None,
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_service_types: None,
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_metadata_pattern: None,
+            #[cfg(feature = "admin-auto-vote")]
+            auto_vote_reject: None,
+            #[cfg(feature = "admin-routing-snapshot")]
+            persist_routing_table: None,
+            #[cfg(feature = "admin-signature-cache")]
+            admin_signature_cache_size: None,
+            #[cfg(feature = "read-only-mode")]
+            read_only: None,
+            #[cfg(feature = "resource-watchdog")]
+            resource_min_free_space: None,
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_circuit_db_dirs: None,
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_db_dirs: None,
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_growth_increment: None,
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_max_size: None,
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_warning_thresholds: None,
+            #[cfg(feature = "external-services")]
+            external_service_types: None,
+            #[cfg(feature = "tenancy")]
+            tenant_operators: None,
+            #[cfg(feature = "biome-user-admin")]
+            biome_admin_keys: None,
+            #[cfg(feature = "network-clock-skew")]
+            clock_skew_threshold: None,
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_queue_capacity: None,
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_weights: None,
+            #[cfg(feature = "scabbard-batch-history")]
+            scabbard_batch_history_size: None,
+            #[cfg(feature = "scabbard-batch-history")]
+            scabbard_batch_history_max_age: None,
             strict_ref_counts: None,
         }
     }
@@ -196,6 +459,10 @@ impl PartialConfig {
         self.peers.clone()
     }
 
+    pub fn peer_transport_preference(&self) -> Option<Vec<String>> {
+        self.peer_transport_preference.clone()
+    }
+
     pub fn node_id(&self) -> Option<String> {
         self.node_id.clone()
     }
@@ -233,6 +500,30 @@ impl PartialConfig {
         self.admin_timeout
     }
 
+    pub fn orchestrator_incoming_capacity(&self) -> Option<usize> {
+        self.orchestrator_incoming_capacity
+    }
+
+    pub fn orchestrator_outgoing_capacity(&self) -> Option<usize> {
+        self.orchestrator_outgoing_capacity
+    }
+
+    pub fn orchestrator_channel_capacity(&self) -> Option<usize> {
+        self.orchestrator_channel_capacity
+    }
+
+    pub fn admin_processor_incoming_capacity(&self) -> Option<usize> {
+        self.admin_processor_incoming_capacity
+    }
+
+    pub fn admin_processor_outgoing_capacity(&self) -> Option<usize> {
+        self.admin_processor_outgoing_capacity
+    }
+
+    pub fn admin_processor_channel_capacity(&self) -> Option<usize> {
+        self.admin_processor_channel_capacity
+    }
+
     pub fn state_dir(&self) -> Option<String> {
         self.state_dir.clone()
     }
@@ -255,6 +546,21 @@ impl PartialConfig {
         self.whitelist.clone()
     }
 
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn cors_policies(&self) -> Option<Vec<CorsPolicyConfig>> {
+        self.cors_policies.clone()
+    }
+
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    pub fn circuit_scope_roles(&self) -> Option<Vec<CircuitScopeRoleConfig>> {
+        self.circuit_scope_roles.clone()
+    }
+
+    #[cfg(feature = "registry-remote-auth")]
+    pub fn registry_auth(&self) -> Option<Vec<RegistryAuthConfig>> {
+        self.registry_auth.clone()
+    }
+
     #[cfg(feature = "auth")]
     pub fn oauth_provider(&self) -> Option<String> {
         self.oauth_provider.clone()
@@ -270,6 +576,11 @@ impl PartialConfig {
         self.oauth_client_secret.clone()
     }
 
+    #[cfg(feature = "auth")]
+    pub fn oauth_public_client(&self) -> Option<bool> {
+        self.oauth_public_client
+    }
+
     #[cfg(feature = "auth")]
     pub fn oauth_redirect_url(&self) -> Option<String> {
         self.oauth_redirect_url.clone()
@@ -280,6 +591,281 @@ impl PartialConfig {
         self.oauth_openid_url.clone()
     }
 
+    #[cfg(feature = "signing-ed25519")]
+    pub fn signing_algorithms(&self) -> Option<Vec<String>> {
+        self.signing_algorithms.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_domains(&self) -> Option<Vec<String>> {
+        self.acme_domains.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_email(&self) -> Option<String> {
+        self.acme_email.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_directory_url(&self) -> Option<String> {
+        self.acme_directory_url.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_challenge(&self) -> Option<String> {
+        self.acme_challenge.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_http_bind(&self) -> Option<String> {
+        self.acme_http_bind.clone()
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn acme_dns_hook(&self) -> Option<String> {
+        self.acme_dns_hook.clone()
+    }
+
+    #[cfg(feature = "config-reload")]
+    pub fn log_level(&self) -> Option<String> {
+        self.log_level.clone()
+    }
+
+    #[cfg(feature = "config-reload")]
+    pub fn peer_blocklist(&self) -> Option<Vec<String>> {
+        self.peer_blocklist.clone()
+    }
+
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_url(&self) -> Option<String> {
+        self.metrics_url.clone()
+    }
+
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_username(&self) -> Option<String> {
+        self.metrics_username.clone()
+    }
+
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_password(&self) -> Option<String> {
+        self.metrics_password.clone()
+    }
+
+    #[cfg(feature = "metrics-push")]
+    pub fn metrics_interval(&self) -> Option<u64> {
+        self.metrics_interval
+    }
+
+    #[cfg(feature = "alerting")]
+    pub fn alert_rules(&self) -> Option<Vec<AlertRule>> {
+        self.alert_rules.clone()
+    }
+
+    #[cfg(feature = "alerting")]
+    pub fn alert_sinks(&self) -> Option<Vec<AlertSinkConfig>> {
+        self.alert_sinks.clone()
+    }
+
+    #[cfg(feature = "event-publisher")]
+    pub fn event_publisher(&self) -> Option<EventPublisherConfig> {
+        self.event_publisher.clone()
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    pub fn mqtt_bridge(&self) -> Option<MqttBridgeConfig> {
+        self.mqtt_bridge.clone()
+    }
+
+    #[cfg(feature = "node-challenge")]
+    pub fn node_key_file(&self) -> Option<String> {
+        self.node_key_file.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_min_version(&self) -> Option<String> {
+        self.tls_min_version.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_cipher_suites(&self) -> Option<Vec<String>> {
+        self.tls_cipher_suites.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_client_auth(&self) -> Option<String> {
+        self.tls_client_auth.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_min_version(&self) -> Option<String> {
+        self.tls_rest_api_min_version.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_cipher_suites(&self) -> Option<Vec<String>> {
+        self.tls_rest_api_cipher_suites.clone()
+    }
+
+    #[cfg(feature = "tls-policy")]
+    pub fn tls_rest_api_client_auth(&self) -> Option<String> {
+        self.tls_rest_api_client_auth.clone()
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_crl_file(&self) -> Option<String> {
+        self.tls_crl_file.clone()
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_ocsp_response_file(&self) -> Option<String> {
+        self.tls_ocsp_response_file.clone()
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    pub fn tls_require_ocsp(&self) -> Option<bool> {
+        self.tls_require_ocsp
+    }
+
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_svid_dir(&self) -> Option<String> {
+        self.spiffe_svid_dir.clone()
+    }
+
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_trust_domain(&self) -> Option<String> {
+        self.spiffe_trust_domain.clone()
+    }
+
+    #[cfg(feature = "spiffe")]
+    pub fn spiffe_require_id(&self) -> Option<bool> {
+        self.spiffe_require_id
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    pub fn peer_identity_pinning(&self) -> Option<String> {
+        self.peer_identity_pinning.clone()
+    }
+
+    #[cfg(feature = "admin-key-rotation")]
+    pub fn admin_key_rotation_overlap(&self) -> Option<u64> {
+        self.admin_key_rotation_overlap
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn accepted_circuit_id_formats(&self) -> Option<Vec<String>> {
+        self.accepted_circuit_id_formats.clone()
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_key_file(&self) -> Option<String> {
+        self.auto_vote_key_file.clone()
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_members(&self) -> Option<Vec<String>> {
+        self.auto_vote_members.clone()
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_service_types(&self) -> Option<Vec<String>> {
+        self.auto_vote_service_types.clone()
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_metadata_pattern(&self) -> Option<String> {
+        self.auto_vote_metadata_pattern.clone()
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn auto_vote_reject(&self) -> Option<bool> {
+        self.auto_vote_reject
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn persist_routing_table(&self) -> Option<bool> {
+        self.persist_routing_table
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    pub fn admin_signature_cache_size(&self) -> Option<u64> {
+        self.admin_signature_cache_size
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    pub fn read_only(&self) -> Option<bool> {
+        self.read_only
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    pub fn resource_min_free_space(&self) -> Option<u64> {
+        self.resource_min_free_space
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn scabbard_circuit_db_dirs(&self) -> Option<bool> {
+        self.scabbard_circuit_db_dirs
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn scabbard_db_dirs(&self) -> Option<Vec<String>> {
+        self.scabbard_db_dirs.clone()
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_growth_increment(&self) -> Option<u64> {
+        self.scabbard_db_growth_increment
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_max_size(&self) -> Option<u64> {
+        self.scabbard_db_max_size
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn scabbard_db_warning_thresholds(&self) -> Option<Vec<String>> {
+        self.scabbard_db_warning_thresholds.clone()
+    }
+
+    #[cfg(feature = "external-services")]
+    pub fn external_service_types(&self) -> Option<Vec<String>> {
+        self.external_service_types.clone()
+    }
+
+    #[cfg(feature = "tenancy")]
+    pub fn tenant_operators(&self) -> Option<Vec<String>> {
+        self.tenant_operators.clone()
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    pub fn biome_admin_keys(&self) -> Option<Vec<String>> {
+        self.biome_admin_keys.clone()
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    pub fn clock_skew_threshold(&self) -> Option<u64> {
+        self.clock_skew_threshold
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    pub fn peer_send_queue_capacity(&self) -> Option<usize> {
+        self.peer_send_queue_capacity
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    pub fn peer_send_weights(&self) -> Option<Vec<String>> {
+        self.peer_send_weights.clone()
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn scabbard_batch_history_size(&self) -> Option<usize> {
+        self.scabbard_batch_history_size
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn scabbard_batch_history_max_age(&self) -> Option<u64> {
+        self.scabbard_batch_history_max_age
+    }
+
     pub fn strict_ref_counts(&self) -> Option<bool> {
         self.strict_ref_counts
     }
@@ -444,6 +1030,21 @@ impl PartialConfig {
         self
     }
 
+    /// Adds a `peer_transport_preference` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_transport_preference` - Transport schemes, in the order a peer's endpoints should
+    ///   be attempted.
+    ///
+    pub fn with_peer_transport_preference(
+        mut self,
+        peer_transport_preference: Option<Vec<String>>,
+    ) -> Self {
+        self.peer_transport_preference = peer_transport_preference;
+        self
+    }
+
     /// Adds a `node_id` value to the `PartialConfig` object.
     ///
     /// # Arguments
@@ -551,6 +1152,78 @@ impl PartialConfig {
         self
     }
 
+    /// Adds an `orchestrator_incoming_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages from the splinter node the orchestrator can
+    /// buffer.
+    ///
+    pub fn with_orchestrator_incoming_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.orchestrator_incoming_capacity = capacity;
+        self
+    }
+
+    /// Adds an `orchestrator_outgoing_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages to the splinter node the orchestrator can
+    /// buffer.
+    ///
+    pub fn with_orchestrator_outgoing_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.orchestrator_outgoing_capacity = capacity;
+        self
+    }
+
+    /// Adds an `orchestrator_channel_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages the orchestrator's internal channels can
+    /// buffer.
+    ///
+    pub fn with_orchestrator_channel_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.orchestrator_channel_capacity = capacity;
+        self
+    }
+
+    /// Adds an `admin_processor_incoming_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages from the splinter node the admin service
+    /// processor can buffer.
+    ///
+    pub fn with_admin_processor_incoming_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.admin_processor_incoming_capacity = capacity;
+        self
+    }
+
+    /// Adds an `admin_processor_outgoing_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages to the splinter node the admin service
+    /// processor can buffer.
+    ///
+    pub fn with_admin_processor_outgoing_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.admin_processor_outgoing_capacity = capacity;
+        self
+    }
+
+    /// Adds an `admin_processor_channel_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of messages the admin service processor's internal
+    /// channels can buffer.
+    ///
+    pub fn with_admin_processor_channel_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.admin_processor_channel_capacity = capacity;
+        self
+    }
+
     /// Adds a `state_dir` value to the `PartialConfig` object.
     ///
     /// # Arguments
@@ -608,6 +1281,46 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "rest-api-cors-policies")]
+    /// Adds a `cors_policies` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `cors_policies` - CORS policies of REST API route groups, used in place of the whitelist
+    ///
+    pub fn with_cors_policies(mut self, cors_policies: Option<Vec<CorsPolicyConfig>>) -> Self {
+        self.cors_policies = cors_policies;
+        self
+    }
+
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    /// Adds a `circuit_scope_roles` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_scope_roles` - Roles that limit the circuits their identities may access
+    ///
+    pub fn with_circuit_scope_roles(
+        mut self,
+        circuit_scope_roles: Option<Vec<CircuitScopeRoleConfig>>,
+    ) -> Self {
+        self.circuit_scope_roles = circuit_scope_roles;
+        self
+    }
+
+    #[cfg(feature = "registry-remote-auth")]
+    /// Adds a `registry_auth` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry_auth` - Credentials for fetching remote registries from authenticated
+    ///   endpoints, referencing the secrets files that hold them
+    ///
+    pub fn with_registry_auth(mut self, registry_auth: Option<Vec<RegistryAuthConfig>>) -> Self {
+        self.registry_auth = registry_auth;
+        self
+    }
+
     #[cfg(feature = "auth")]
     /// Adds an `oauth_provider` value to the `PartialConfig` object.
     ///
@@ -656,6 +1369,19 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "auth")]
+    /// Adds an `oauth_public_client` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `oauth_public_client` - Whether the OAuth app is registered as a public client, which
+    ///   has no client secret
+    ///
+    pub fn with_oauth_public_client(mut self, oauth_public_client: Option<bool>) -> Self {
+        self.oauth_public_client = oauth_public_client;
+        self
+    }
+
     #[cfg(feature = "auth")]
     /// Adds an `with_oauth_openid_url` value to the `PartialConfig` object.
     ///
@@ -668,6 +1394,725 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "signing-ed25519")]
+    /// Adds a `signing_algorithms` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `signing_algorithms` - Signature algorithms accepted for admin payloads and Cylinder JWTs
+    ///
+    pub fn with_signing_algorithms(mut self, signing_algorithms: Option<Vec<String>>) -> Self {
+        self.signing_algorithms = signing_algorithms;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_domains` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_domains` - Domains to obtain an ACME certificate for
+    ///
+    pub fn with_acme_domains(mut self, acme_domains: Option<Vec<String>>) -> Self {
+        self.acme_domains = acme_domains;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_email` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_email` - Contact email for the ACME account
+    ///
+    pub fn with_acme_email(mut self, acme_email: Option<String>) -> Self {
+        self.acme_email = acme_email;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_directory_url` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_directory_url` - URL of the ACME directory
+    ///
+    pub fn with_acme_directory_url(mut self, acme_directory_url: Option<String>) -> Self {
+        self.acme_directory_url = acme_directory_url;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_challenge` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_challenge` - ACME challenge type, either http-01 or dns-01
+    ///
+    pub fn with_acme_challenge(mut self, acme_challenge: Option<String>) -> Self {
+        self.acme_challenge = acme_challenge;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_http_bind` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_http_bind` - Bind address for the ACME HTTP-01 challenge responder
+    ///
+    pub fn with_acme_http_bind(mut self, acme_http_bind: Option<String>) -> Self {
+        self.acme_http_bind = acme_http_bind;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    /// Adds a `acme_dns_hook` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `acme_dns_hook` - Command run to publish ACME DNS-01 challenge records
+    ///
+    pub fn with_acme_dns_hook(mut self, acme_dns_hook: Option<String>) -> Self {
+        self.acme_dns_hook = acme_dns_hook;
+        self
+    }
+
+    #[cfg(feature = "config-reload")]
+    /// Adds a `log_level` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_level` - Default log level, used unless verbosity is set on the command line
+    ///
+    pub fn with_log_level(mut self, log_level: Option<String>) -> Self {
+        self.log_level = log_level;
+        self
+    }
+
+    #[cfg(feature = "config-reload")]
+    /// Adds a `peer_blocklist` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_blocklist` - IDs of peers that are not allowed to connect
+    ///
+    pub fn with_peer_blocklist(mut self, peer_blocklist: Option<Vec<String>>) -> Self {
+        self.peer_blocklist = peer_blocklist;
+        self
+    }
+
+    #[cfg(feature = "metrics-push")]
+    /// Adds a `metrics_url` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics_url` - URL of the InfluxDB database or statsd server that metrics are pushed to
+    ///
+    pub fn with_metrics_url(mut self, metrics_url: Option<String>) -> Self {
+        self.metrics_url = metrics_url;
+        self
+    }
+
+    #[cfg(feature = "metrics-push")]
+    /// Adds a `metrics_username` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics_username` - Username used to authenticate with InfluxDB
+    ///
+    pub fn with_metrics_username(mut self, metrics_username: Option<String>) -> Self {
+        self.metrics_username = metrics_username;
+        self
+    }
+
+    #[cfg(feature = "metrics-push")]
+    /// Adds a `metrics_password` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics_password` - Password used to authenticate with InfluxDB
+    ///
+    pub fn with_metrics_password(mut self, metrics_password: Option<String>) -> Self {
+        self.metrics_password = metrics_password;
+        self
+    }
+
+    #[cfg(feature = "metrics-push")]
+    /// Adds a `metrics_interval` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics_interval` - Number of seconds between metrics pushes
+    ///
+    pub fn with_metrics_interval(mut self, metrics_interval: Option<u64>) -> Self {
+        self.metrics_interval = metrics_interval;
+        self
+    }
+
+    #[cfg(feature = "alerting")]
+    /// Adds an `alert_rules` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `alert_rules` - Rules that fire alerts when they trip
+    ///
+    pub fn with_alert_rules(mut self, alert_rules: Option<Vec<AlertRule>>) -> Self {
+        self.alert_rules = alert_rules;
+        self
+    }
+
+    #[cfg(feature = "alerting")]
+    /// Adds an `alert_sinks` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `alert_sinks` - Destinations that alert notifications are sent to
+    ///
+    pub fn with_alert_sinks(mut self, alert_sinks: Option<Vec<AlertSinkConfig>>) -> Self {
+        self.alert_sinks = alert_sinks;
+        self
+    }
+
+    #[cfg(feature = "event-publisher")]
+    /// Adds an `event_publisher` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_publisher` - The message broker that admin and scabbard events are published to
+    ///
+    pub fn with_event_publisher(mut self, event_publisher: Option<EventPublisherConfig>) -> Self {
+        self.event_publisher = event_publisher;
+        self
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    /// Adds an `mqtt_bridge` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `mqtt_bridge` - The MQTT broker that scabbard services are bridged to
+    ///
+    pub fn with_mqtt_bridge(mut self, mqtt_bridge: Option<MqttBridgeConfig>) -> Self {
+        self.mqtt_bridge = mqtt_bridge;
+        self
+    }
+
+    #[cfg(feature = "node-challenge")]
+    /// Adds a `node_key_file` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_key_file` - Path of the secp256k1 private key the node signs identity challenges
+    ///   with
+    ///
+    pub fn with_node_key_file(mut self, node_key_file: Option<String>) -> Self {
+        self.node_key_file = node_key_file;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_min_version` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_min_version` - Minimum TLS protocol version accepted on peer connections, either
+    ///   "1.2" or "1.3"
+    ///
+    pub fn with_tls_min_version(mut self, tls_min_version: Option<String>) -> Self {
+        self.tls_min_version = tls_min_version;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_cipher_suites` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_cipher_suites` - Cipher suites allowed on peer connections
+    ///
+    pub fn with_tls_cipher_suites(mut self, tls_cipher_suites: Option<Vec<String>>) -> Self {
+        self.tls_cipher_suites = tls_cipher_suites;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_client_auth` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_client_auth` - Whether peer connections require, optionally accept, or ignore client
+    ///   certificates
+    ///
+    pub fn with_tls_client_auth(mut self, tls_client_auth: Option<String>) -> Self {
+        self.tls_client_auth = tls_client_auth;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_rest_api_min_version` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_rest_api_min_version` - Minimum TLS protocol version accepted by the REST API,
+    ///   either "1.2" or "1.3"
+    ///
+    pub fn with_tls_rest_api_min_version(
+        mut self,
+        tls_rest_api_min_version: Option<String>,
+    ) -> Self {
+        self.tls_rest_api_min_version = tls_rest_api_min_version;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_rest_api_cipher_suites` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_rest_api_cipher_suites` - Cipher suites allowed by the REST API
+    ///
+    pub fn with_tls_rest_api_cipher_suites(
+        mut self,
+        tls_rest_api_cipher_suites: Option<Vec<String>>,
+    ) -> Self {
+        self.tls_rest_api_cipher_suites = tls_rest_api_cipher_suites;
+        self
+    }
+
+    #[cfg(feature = "tls-policy")]
+    /// Adds a `tls_rest_api_client_auth` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_rest_api_client_auth` - Whether the REST API requires, optionally accepts, or
+    ///   ignores client certificates
+    ///
+    pub fn with_tls_rest_api_client_auth(
+        mut self,
+        tls_rest_api_client_auth: Option<String>,
+    ) -> Self {
+        self.tls_rest_api_client_auth = tls_rest_api_client_auth;
+        self
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    /// Adds a `tls_crl_file` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_crl_file` - Path of the PEM-encoded certificate revocation list that peer
+    ///   certificates are checked against
+    ///
+    pub fn with_tls_crl_file(mut self, tls_crl_file: Option<String>) -> Self {
+        self.tls_crl_file = tls_crl_file;
+        self
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    /// Adds a `tls_ocsp_response_file` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_ocsp_response_file` - Path of the DER-encoded OCSP response stapled to incoming peer
+    ///   connections
+    ///
+    pub fn with_tls_ocsp_response_file(mut self, tls_ocsp_response_file: Option<String>) -> Self {
+        self.tls_ocsp_response_file = tls_ocsp_response_file;
+        self
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    /// Adds a `tls_require_ocsp` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls_require_ocsp` - Whether outgoing peer connections are refused when the remote node
+    ///   does not staple an OCSP response
+    ///
+    pub fn with_tls_require_ocsp(mut self, tls_require_ocsp: Option<bool>) -> Self {
+        self.tls_require_ocsp = tls_require_ocsp;
+        self
+    }
+
+    #[cfg(feature = "spiffe")]
+    /// Adds a `spiffe_svid_dir` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `spiffe_svid_dir` - Directory the node's SPIFFE X.509 SVID, key and trust bundle are
+    ///   written to, replacing the configured TLS certificates
+    ///
+    pub fn with_spiffe_svid_dir(mut self, spiffe_svid_dir: Option<String>) -> Self {
+        self.spiffe_svid_dir = spiffe_svid_dir;
+        self
+    }
+
+    #[cfg(feature = "spiffe")]
+    /// Adds a `spiffe_trust_domain` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `spiffe_trust_domain` - SPIFFE trust domain that peer SPIFFE IDs must belong to
+    ///
+    pub fn with_spiffe_trust_domain(mut self, spiffe_trust_domain: Option<String>) -> Self {
+        self.spiffe_trust_domain = spiffe_trust_domain;
+        self
+    }
+
+    #[cfg(feature = "spiffe")]
+    /// Adds a `spiffe_require_id` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `spiffe_require_id` - Whether peers whose certificates do not contain a SPIFFE ID are
+    ///   refused
+    ///
+    pub fn with_spiffe_require_id(mut self, spiffe_require_id: Option<bool>) -> Self {
+        self.spiffe_require_id = spiffe_require_id;
+        self
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    /// Adds a `peer_identity_pinning` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_identity_pinning` - How peer certificate fingerprints are pinned to node identities;
+    ///   either tofu or registry
+    ///
+    pub fn with_peer_identity_pinning(mut self, peer_identity_pinning: Option<String>) -> Self {
+        self.peer_identity_pinning = peer_identity_pinning;
+        self
+    }
+
+    #[cfg(feature = "admin-key-rotation")]
+    /// Adds a `admin_key_rotation_overlap` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_key_rotation_overlap` - Number of seconds a node's retired admin key remains
+    ///   permitted after a key rotation
+    ///
+    pub fn with_admin_key_rotation_overlap(
+        mut self,
+        admin_key_rotation_overlap: Option<u64>,
+    ) -> Self {
+        self.admin_key_rotation_overlap = admin_key_rotation_overlap;
+        self
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    /// Adds an `accepted_circuit_id_formats` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `accepted_circuit_id_formats` - Circuit ID formats accepted for new circuit proposals
+    ///
+    pub fn with_accepted_circuit_id_formats(
+        mut self,
+        accepted_circuit_id_formats: Option<Vec<String>>,
+    ) -> Self {
+        self.accepted_circuit_id_formats = accepted_circuit_id_formats;
+        self
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    /// Adds a `auto_vote_key_file` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_vote_key_file` - Path of the private key that automatic votes are signed with
+    ///
+    pub fn with_auto_vote_key_file(mut self, auto_vote_key_file: Option<String>) -> Self {
+        self.auto_vote_key_file = auto_vote_key_file;
+        self
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    /// Adds a `auto_vote_members` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_vote_members` - Nodes that circuits accepted automatically may have as members
+    ///
+    pub fn with_auto_vote_members(mut self, auto_vote_members: Option<Vec<String>>) -> Self {
+        self.auto_vote_members = auto_vote_members;
+        self
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    /// Adds a `auto_vote_service_types` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_vote_service_types` - Service types that circuits accepted automatically may have
+    ///
+    pub fn with_auto_vote_service_types(
+        mut self,
+        auto_vote_service_types: Option<Vec<String>>,
+    ) -> Self {
+        self.auto_vote_service_types = auto_vote_service_types;
+        self
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    /// Adds a `auto_vote_metadata_pattern` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_vote_metadata_pattern` - Pattern automatically accepted circuits must match
+    ///
+    pub fn with_auto_vote_metadata_pattern(
+        mut self,
+        auto_vote_metadata_pattern: Option<String>,
+    ) -> Self {
+        self.auto_vote_metadata_pattern = auto_vote_metadata_pattern;
+        self
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    /// Adds a `auto_vote_reject` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_vote_reject` - Whether proposals that break an automatic voting rule are rejected
+    ///
+    pub fn with_auto_vote_reject(mut self, auto_vote_reject: Option<bool>) -> Self {
+        self.auto_vote_reject = auto_vote_reject;
+        self
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    /// Adds a `persist_routing_table` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `persist_routing_table` - Whether the routing table is saved to speed up the next start
+    ///
+    pub fn with_persist_routing_table(mut self, persist_routing_table: Option<bool>) -> Self {
+        self.persist_routing_table = persist_routing_table;
+        self
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    /// Adds a `admin_signature_cache_size` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_signature_cache_size` - Number of admin signature verification results to cache
+    ///
+    pub fn with_admin_signature_cache_size(
+        mut self,
+        admin_signature_cache_size: Option<u64>,
+    ) -> Self {
+        self.admin_signature_cache_size = admin_signature_cache_size;
+        self
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    /// Adds a `read_only` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `read_only` - Start the node in read-only mode.
+    ///
+    pub fn with_read_only(mut self, read_only: Option<bool>) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    /// Adds a `resource_min_free_space` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_min_free_space` - Free disk space, in megabytes, below which the node is
+    ///   degraded to read-only mode
+    ///
+    pub fn with_resource_min_free_space(mut self, resource_min_free_space: Option<u64>) -> Self {
+        self.resource_min_free_space = resource_min_free_space;
+        self
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    /// Adds a `scabbard_circuit_db_dirs` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_circuit_db_dirs` - Whether scabbard databases are stored per circuit
+    ///
+    pub fn with_scabbard_circuit_db_dirs(mut self, scabbard_circuit_db_dirs: Option<bool>) -> Self {
+        self.scabbard_circuit_db_dirs = scabbard_circuit_db_dirs;
+        self
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    /// Adds a `scabbard_db_dirs` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_db_dirs` - Scabbard database directories, as CIRCUIT=DIR or CIRCUIT::SERVICE=DIR
+    ///
+    pub fn with_scabbard_db_dirs(mut self, scabbard_db_dirs: Option<Vec<String>>) -> Self {
+        self.scabbard_db_dirs = scabbard_db_dirs;
+        self
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    /// Adds a `scabbard_db_growth_increment` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_db_growth_increment` - Megabytes by which a scabbard database's map grows
+    ///
+    pub fn with_scabbard_db_growth_increment(
+        mut self,
+        scabbard_db_growth_increment: Option<u64>,
+    ) -> Self {
+        self.scabbard_db_growth_increment = scabbard_db_growth_increment;
+        self
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    /// Adds a `scabbard_db_max_size` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_db_max_size` - Largest size, in megabytes, a scabbard database may grow to
+    ///
+    pub fn with_scabbard_db_max_size(mut self, scabbard_db_max_size: Option<u64>) -> Self {
+        self.scabbard_db_max_size = scabbard_db_max_size;
+        self
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    /// Adds a `scabbard_db_warning_thresholds` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_db_warning_thresholds` - Percentages of the maximum size at which to warn
+    ///
+    pub fn with_scabbard_db_warning_thresholds(
+        mut self,
+        scabbard_db_warning_thresholds: Option<Vec<String>>,
+    ) -> Self {
+        self.scabbard_db_warning_thresholds = scabbard_db_warning_thresholds;
+        self
+    }
+
+    #[cfg(feature = "external-services")]
+    /// Adds a `external_service_types` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `external_service_types` - Service types executed by external processes, as TYPE=PATH
+    ///
+    pub fn with_external_service_types(
+        mut self,
+        external_service_types: Option<Vec<String>>,
+    ) -> Self {
+        self.external_service_types = external_service_types;
+        self
+    }
+
+    #[cfg(feature = "tenancy")]
+    /// Adds a `tenant_operators` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant_operators` - The identities of the node operators, which may manage tenants
+    ///
+    pub fn with_tenant_operators(mut self, tenant_operators: Option<Vec<String>>) -> Self {
+        self.tenant_operators = tenant_operators;
+        self
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    /// Adds a `biome_admin_keys` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `biome_admin_keys` - Public keys that are permitted to administer Biome users
+    ///
+    pub fn with_biome_admin_keys(mut self, biome_admin_keys: Option<Vec<String>>) -> Self {
+        self.biome_admin_keys = biome_admin_keys;
+        self
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    /// Adds a `clock_skew_threshold` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock_skew_threshold` - Number of seconds a peer's clock may be off before it is
+    ///   reported
+    ///
+    pub fn with_clock_skew_threshold(mut self, clock_skew_threshold: Option<u64>) -> Self {
+        self.clock_skew_threshold = clock_skew_threshold;
+        self
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    /// Adds a `peer_send_queue_capacity` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_send_queue_capacity` - Number of outgoing messages that may be queued for a peer
+    ///   connection
+    ///
+    pub fn with_peer_send_queue_capacity(
+        mut self,
+        peer_send_queue_capacity: Option<usize>,
+    ) -> Self {
+        self.peer_send_queue_capacity = peer_send_queue_capacity;
+        self
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    /// Adds a `peer_send_weights` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_send_weights` - Send weights of peer connections, as NODE_ID=WEIGHT
+    ///
+    pub fn with_peer_send_weights(mut self, peer_send_weights: Option<Vec<String>>) -> Self {
+        self.peer_send_weights = peer_send_weights;
+        self
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    /// Adds a `scabbard_batch_history_size` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_batch_history_size` - Number of batches kept in each scabbard service's batch
+    ///   history
+    ///
+    pub fn with_scabbard_batch_history_size(
+        mut self,
+        scabbard_batch_history_size: Option<usize>,
+    ) -> Self {
+        self.scabbard_batch_history_size = scabbard_batch_history_size;
+        self
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    /// Adds a `scabbard_batch_history_max_age` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `scabbard_batch_history_max_age` - Number of seconds batches are kept in each scabbard
+    ///   service's batch history
+    ///
+    pub fn with_scabbard_batch_history_max_age(
+        mut self,
+        scabbard_batch_history_max_age: Option<u64>,
+    ) -> Self {
+        self.scabbard_batch_history_max_age = scabbard_batch_history_max_age;
+        self
+    }
+
     /// Adds a `strict_ref_counts` value to the `PartialConfig` object.
     ///
     /// # Arguments
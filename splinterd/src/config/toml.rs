@@ -18,6 +18,18 @@ use crate::config::PartialConfigBuilder;
 use crate::config::{ConfigError, ConfigSource, PartialConfig};
 
 use serde_derive::Deserialize;
+#[cfg(feature = "alerting")]
+use splinter_daemon::alerts::{AlertRule, AlertSinkConfig};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use splinter_daemon::circuit_scopes::CircuitScopeRoleConfig;
+#[cfg(feature = "rest-api-cors-policies")]
+use splinter_daemon::cors::CorsPolicyConfig;
+#[cfg(feature = "event-publisher")]
+use splinter_daemon::event_publisher::EventPublisherConfig;
+#[cfg(feature = "mqtt-bridge")]
+use splinter_daemon::mqtt_bridge::MqttBridgeConfig;
+#[cfg(feature = "registry-remote-auth")]
+use splinter_daemon::registry_auth::RegistryAuthConfig;
 
 /// `TOML_VERSION` represents the version of the toml config file.
 /// The version determines the most current valid toml config entries.
@@ -44,6 +56,7 @@ struct TomlConfig {
     network_endpoints: Option<Vec<String>>,
     advertised_endpoints: Option<Vec<String>>,
     peers: Option<Vec<String>>,
+    peer_transport_preference: Option<Vec<String>>,
     node_id: Option<String>,
     display_name: Option<String>,
     rest_api_endpoint: Option<String>,
@@ -54,9 +67,21 @@ struct TomlConfig {
     registry_forced_refresh: Option<u64>,
     heartbeat: Option<u64>,
     admin_timeout: Option<u64>,
+    orchestrator_incoming_capacity: Option<usize>,
+    orchestrator_outgoing_capacity: Option<usize>,
+    orchestrator_channel_capacity: Option<usize>,
+    admin_processor_incoming_capacity: Option<usize>,
+    admin_processor_outgoing_capacity: Option<usize>,
+    admin_processor_channel_capacity: Option<usize>,
     version: Option<String>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_policies: Option<Vec<CorsPolicyConfig>>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_roles: Option<Vec<CircuitScopeRoleConfig>>,
+    #[cfg(feature = "registry-remote-auth")]
+    registry_auth: Option<Vec<RegistryAuthConfig>>,
     #[cfg(feature = "auth")]
     oauth_provider: Option<String>,
     #[cfg(feature = "auth")]
@@ -64,9 +89,121 @@ struct TomlConfig {
     #[cfg(feature = "auth")]
     oauth_client_secret: Option<String>,
     #[cfg(feature = "auth")]
+    oauth_public_client: Option<bool>,
+    #[cfg(feature = "auth")]
     oauth_redirect_url: Option<String>,
     #[cfg(feature = "auth")]
     oauth_openid_url: Option<String>,
+    #[cfg(feature = "signing-ed25519")]
+    signing_algorithms: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_domains: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_email: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_directory_url: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_challenge: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_http_bind: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_dns_hook: Option<String>,
+    #[cfg(feature = "config-reload")]
+    log_level: Option<String>,
+    #[cfg(feature = "config-reload")]
+    peer_blocklist: Option<Vec<String>>,
+    #[cfg(feature = "metrics-push")]
+    metrics_url: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_username: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_password: Option<String>,
+    #[cfg(feature = "metrics-push")]
+    metrics_interval: Option<u64>,
+    #[cfg(feature = "alerting")]
+    alert_rules: Option<Vec<AlertRule>>,
+    #[cfg(feature = "alerting")]
+    alert_sinks: Option<Vec<AlertSinkConfig>>,
+    #[cfg(feature = "event-publisher")]
+    event_publisher: Option<EventPublisherConfig>,
+    #[cfg(feature = "mqtt-bridge")]
+    mqtt_bridge: Option<MqttBridgeConfig>,
+    #[cfg(feature = "node-challenge")]
+    node_key_file: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_min_version: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_cipher_suites: Option<Vec<String>>,
+    #[cfg(feature = "tls-policy")]
+    tls_client_auth: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_min_version: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_cipher_suites: Option<Vec<String>>,
+    #[cfg(feature = "tls-policy")]
+    tls_rest_api_client_auth: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_crl_file: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_ocsp_response_file: Option<String>,
+    #[cfg(feature = "tls-revocation")]
+    tls_require_ocsp: Option<bool>,
+    #[cfg(feature = "spiffe")]
+    spiffe_svid_dir: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_trust_domain: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_require_id: Option<bool>,
+    #[cfg(feature = "peer-identity-pinning")]
+    peer_identity_pinning: Option<String>,
+    #[cfg(feature = "admin-key-rotation")]
+    admin_key_rotation_overlap: Option<u64>,
+    #[cfg(feature = "circuit-id-policy")]
+    accepted_circuit_id_formats: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_key_file: Option<String>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_members: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_service_types: Option<Vec<String>>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_metadata_pattern: Option<String>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_vote_reject: Option<bool>,
+    #[cfg(feature = "admin-routing-snapshot")]
+    persist_routing_table: Option<bool>,
+    #[cfg(feature = "admin-signature-cache")]
+    admin_signature_cache_size: Option<u64>,
+    #[cfg(feature = "read-only-mode")]
+    read_only: Option<bool>,
+    #[cfg(feature = "resource-watchdog")]
+    resource_min_free_space: Option<u64>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_circuit_db_dirs: Option<bool>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_db_dirs: Option<Vec<String>>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_growth_increment: Option<u64>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_max_size: Option<u64>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_warning_thresholds: Option<Vec<String>>,
+    #[cfg(feature = "external-services")]
+    external_service_types: Option<Vec<String>>,
+    #[cfg(feature = "tenancy")]
+    tenant_operators: Option<Vec<String>>,
+    #[cfg(feature = "biome-user-admin")]
+    biome_admin_keys: Option<Vec<String>>,
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_threshold: Option<u64>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_queue_capacity: Option<usize>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_weights: Option<Vec<String>>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_size: Option<usize>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_max_age: Option<u64>,
 
     // Deprecated values
     cert_dir: Option<String>,
@@ -139,6 +276,7 @@ impl PartialConfigBuilder for TomlPartialConfigBuilder {
             .with_network_endpoints(self.toml_config.network_endpoints)
             .with_advertised_endpoints(self.toml_config.advertised_endpoints)
             .with_peers(self.toml_config.peers)
+            .with_peer_transport_preference(self.toml_config.peer_transport_preference)
             .with_node_id(self.toml_config.node_id)
             .with_display_name(self.toml_config.display_name)
             .with_rest_api_endpoint(self.toml_config.rest_api_endpoint)
@@ -146,7 +284,19 @@ impl PartialConfigBuilder for TomlPartialConfigBuilder {
             .with_registry_auto_refresh(self.toml_config.registry_auto_refresh)
             .with_registry_forced_refresh(self.toml_config.registry_forced_refresh)
             .with_heartbeat(self.toml_config.heartbeat)
-            .with_admin_timeout(self.toml_config.admin_timeout);
+            .with_admin_timeout(self.toml_config.admin_timeout)
+            .with_orchestrator_incoming_capacity(self.toml_config.orchestrator_incoming_capacity)
+            .with_orchestrator_outgoing_capacity(self.toml_config.orchestrator_outgoing_capacity)
+            .with_orchestrator_channel_capacity(self.toml_config.orchestrator_channel_capacity)
+            .with_admin_processor_incoming_capacity(
+                self.toml_config.admin_processor_incoming_capacity,
+            )
+            .with_admin_processor_outgoing_capacity(
+                self.toml_config.admin_processor_outgoing_capacity,
+            )
+            .with_admin_processor_channel_capacity(
+                self.toml_config.admin_processor_channel_capacity,
+            );
 
         #[cfg(feature = "https-bind")]
         {
@@ -170,16 +320,298 @@ impl PartialConfigBuilder for TomlPartialConfigBuilder {
             partial_config = partial_config.with_whitelist(self.toml_config.whitelist);
         }
 
+        #[cfg(feature = "rest-api-cors-policies")]
+        {
+            partial_config = partial_config.with_cors_policies(self.toml_config.cors_policies);
+        }
+
+        #[cfg(feature = "rest-api-circuit-scopes")]
+        {
+            partial_config =
+                partial_config.with_circuit_scope_roles(self.toml_config.circuit_scope_roles);
+        }
+
+        #[cfg(feature = "registry-remote-auth")]
+        {
+            partial_config = partial_config.with_registry_auth(self.toml_config.registry_auth);
+        }
+
         #[cfg(feature = "auth")]
         {
             partial_config = partial_config
                 .with_oauth_provider(self.toml_config.oauth_provider)
                 .with_oauth_client_id(self.toml_config.oauth_client_id)
                 .with_oauth_client_secret(self.toml_config.oauth_client_secret)
+                .with_oauth_public_client(self.toml_config.oauth_public_client)
                 .with_oauth_redirect_url(self.toml_config.oauth_redirect_url)
                 .with_oauth_openid_url(self.toml_config.oauth_openid_url);
         }
 
+        #[cfg(feature = "signing-ed25519")]
+        {
+            partial_config =
+                partial_config.with_signing_algorithms(self.toml_config.signing_algorithms);
+        }
+
+        #[cfg(feature = "rest-api-acme")]
+        {
+            partial_config = partial_config
+                .with_acme_domains(self.toml_config.acme_domains)
+                .with_acme_email(self.toml_config.acme_email)
+                .with_acme_directory_url(self.toml_config.acme_directory_url)
+                .with_acme_challenge(self.toml_config.acme_challenge)
+                .with_acme_http_bind(self.toml_config.acme_http_bind)
+                .with_acme_dns_hook(self.toml_config.acme_dns_hook);
+        }
+
+        #[cfg(feature = "config-reload")]
+        {
+            partial_config = partial_config
+                .with_log_level(self.toml_config.log_level)
+                .with_peer_blocklist(self.toml_config.peer_blocklist);
+        }
+
+        #[cfg(feature = "metrics-push")]
+        {
+            partial_config = partial_config
+                .with_metrics_url(self.toml_config.metrics_url)
+                .with_metrics_username(self.toml_config.metrics_username)
+                .with_metrics_password(self.toml_config.metrics_password)
+                .with_metrics_interval(self.toml_config.metrics_interval);
+        }
+
+        #[cfg(feature = "alerting")]
+        {
+            partial_config = partial_config
+                .with_alert_rules(self.toml_config.alert_rules)
+                .with_alert_sinks(self.toml_config.alert_sinks);
+        }
+
+        #[cfg(feature = "event-publisher")]
+        {
+            partial_config = partial_config.with_event_publisher(self.toml_config.event_publisher);
+        }
+
+        #[cfg(feature = "mqtt-bridge")]
+        {
+            partial_config = partial_config.with_mqtt_bridge(self.toml_config.mqtt_bridge);
+        }
+
+        #[cfg(feature = "node-challenge")]
+        {
+            partial_config = partial_config.with_node_key_file(self.toml_config.node_key_file);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_min_version(self.toml_config.tls_min_version);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config =
+                partial_config.with_tls_cipher_suites(self.toml_config.tls_cipher_suites);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config.with_tls_client_auth(self.toml_config.tls_client_auth);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config
+                .with_tls_rest_api_min_version(self.toml_config.tls_rest_api_min_version);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config
+                .with_tls_rest_api_cipher_suites(self.toml_config.tls_rest_api_cipher_suites);
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            partial_config = partial_config
+                .with_tls_rest_api_client_auth(self.toml_config.tls_rest_api_client_auth);
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config = partial_config.with_tls_crl_file(self.toml_config.tls_crl_file);
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config =
+                partial_config.with_tls_ocsp_response_file(self.toml_config.tls_ocsp_response_file);
+        }
+
+        #[cfg(feature = "tls-revocation")]
+        {
+            partial_config =
+                partial_config.with_tls_require_ocsp(self.toml_config.tls_require_ocsp);
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config = partial_config.with_spiffe_svid_dir(self.toml_config.spiffe_svid_dir);
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config =
+                partial_config.with_spiffe_trust_domain(self.toml_config.spiffe_trust_domain);
+        }
+
+        #[cfg(feature = "spiffe")]
+        {
+            partial_config =
+                partial_config.with_spiffe_require_id(self.toml_config.spiffe_require_id);
+        }
+
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            partial_config =
+                partial_config.with_peer_identity_pinning(self.toml_config.peer_identity_pinning);
+        }
+
+        #[cfg(feature = "admin-key-rotation")]
+        {
+            partial_config = partial_config
+                .with_admin_key_rotation_overlap(self.toml_config.admin_key_rotation_overlap);
+        }
+
+        #[cfg(feature = "circuit-id-policy")]
+        {
+            partial_config = partial_config
+                .with_accepted_circuit_id_formats(self.toml_config.accepted_circuit_id_formats);
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config =
+                partial_config.with_auto_vote_key_file(self.toml_config.auto_vote_key_file);
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config =
+                partial_config.with_auto_vote_members(self.toml_config.auto_vote_members);
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config
+                .with_auto_vote_service_types(self.toml_config.auto_vote_service_types);
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config = partial_config
+                .with_auto_vote_metadata_pattern(self.toml_config.auto_vote_metadata_pattern);
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            partial_config =
+                partial_config.with_auto_vote_reject(self.toml_config.auto_vote_reject);
+        }
+
+        #[cfg(feature = "admin-routing-snapshot")]
+        {
+            partial_config =
+                partial_config.with_persist_routing_table(self.toml_config.persist_routing_table);
+        }
+
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            partial_config = partial_config
+                .with_admin_signature_cache_size(self.toml_config.admin_signature_cache_size);
+        }
+
+        #[cfg(feature = "read-only-mode")]
+        {
+            partial_config = partial_config.with_read_only(self.toml_config.read_only);
+        }
+
+        #[cfg(feature = "resource-watchdog")]
+        {
+            partial_config = partial_config
+                .with_resource_min_free_space(self.toml_config.resource_min_free_space);
+        }
+
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            partial_config = partial_config
+                .with_scabbard_circuit_db_dirs(self.toml_config.scabbard_circuit_db_dirs);
+        }
+
+        #[cfg(feature = "scabbard-db-placement")]
+        {
+            partial_config =
+                partial_config.with_scabbard_db_dirs(self.toml_config.scabbard_db_dirs);
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config = partial_config
+                .with_scabbard_db_growth_increment(self.toml_config.scabbard_db_growth_increment);
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config =
+                partial_config.with_scabbard_db_max_size(self.toml_config.scabbard_db_max_size);
+        }
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        {
+            partial_config = partial_config.with_scabbard_db_warning_thresholds(
+                self.toml_config.scabbard_db_warning_thresholds,
+            );
+        }
+
+        #[cfg(feature = "external-services")]
+        {
+            partial_config =
+                partial_config.with_external_service_types(self.toml_config.external_service_types);
+        }
+
+        #[cfg(feature = "tenancy")]
+        {
+            partial_config =
+                partial_config.with_tenant_operators(self.toml_config.tenant_operators);
+        }
+
+        #[cfg(feature = "biome-user-admin")]
+        {
+            partial_config =
+                partial_config.with_biome_admin_keys(self.toml_config.biome_admin_keys);
+        }
+
+        #[cfg(feature = "network-clock-skew")]
+        {
+            partial_config =
+                partial_config.with_clock_skew_threshold(self.toml_config.clock_skew_threshold);
+        }
+
+        #[cfg(feature = "peer-send-limits")]
+        {
+            partial_config = partial_config
+                .with_peer_send_queue_capacity(self.toml_config.peer_send_queue_capacity)
+                .with_peer_send_weights(self.toml_config.peer_send_weights);
+        }
+
+        #[cfg(feature = "scabbard-batch-history")]
+        {
+            partial_config = partial_config
+                .with_scabbard_batch_history_size(self.toml_config.scabbard_batch_history_size)
+                .with_scabbard_batch_history_max_age(
+                    self.toml_config.scabbard_batch_history_max_age,
+                );
+        }
+
         // deprecated values, only set if the current value was not set
         if partial_config.tls_cert_dir().is_none() {
             partial_config = partial_config.with_tls_cert_dir(self.toml_config.cert_dir)
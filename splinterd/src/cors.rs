@@ -0,0 +1,64 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! CORS policies for groups of REST API routes, such as allowing credentials on the OAuth routes
+//! of a browser application without allowing them on the admin routes.
+
+use splinter::rest_api::cors::CorsPolicy;
+
+/// A route group's policy, as defined in a `[[cors_policies]]` table of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CorsPolicyConfig {
+    /// The path prefixes of the routes in the group, such as "/oauth" or "/biome"
+    pub routes: Vec<String>,
+    /// The allowed domains; "*" allows any domain, and an empty list blocks cross-origin requests
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// The allowed methods; any method is allowed if empty
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    /// The allowed request headers; any header is allowed if empty
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+}
+
+impl CorsPolicyConfig {
+    fn to_policy(&self) -> CorsPolicy {
+        let mut policy = CorsPolicy::new(self.allowed_origins.clone())
+            .with_allow_credentials(self.allow_credentials);
+        if !self.allowed_methods.is_empty() {
+            policy = policy.with_allowed_methods(self.allowed_methods.clone());
+        }
+        if !self.allowed_headers.is_empty() {
+            policy = policy.with_allowed_headers(self.allowed_headers.clone());
+        }
+        policy
+    }
+}
+
+/// Returns the policies of the given route groups as pairs of path prefix and policy.
+pub fn route_policies(configs: &[CorsPolicyConfig]) -> Vec<(String, CorsPolicy)> {
+    configs
+        .iter()
+        .flat_map(|config| {
+            let policy = config.to_policy();
+            config
+                .routes
+                .iter()
+                .map(move |route| (route.clone(), policy.clone()))
+        })
+        .collect()
+}
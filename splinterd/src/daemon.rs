@@ -12,35 +12,101 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(feature = "service-arg-validation")]
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::path::Path;
+#[cfg(any(feature = "external-services", feature = "resource-watchdog"))]
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use cylinder::{secp256k1::Secp256k1Context, VerifierFactory};
+#[cfg(feature = "node-challenge")]
+use cylinder::{Context, PrivateKey};
 #[cfg(feature = "health")]
-use health::HealthService;
+use health::{HealthProber, HealthService, DEFAULT_PROBE_INTERVAL, DEFAULT_PROBE_TIMEOUT};
+#[cfg(feature = "scabbard-batch-history")]
+use scabbard::service::BatchHistoryRetention;
+#[cfg(feature = "alerting")]
+use scabbard::service::BatchQueueMonitor;
+#[cfg(feature = "resource-watchdog")]
+use scabbard::service::CommitPause;
+#[cfg(feature = "scabbard-db-auto-grow")]
+use scabbard::service::DbGrowthPolicy;
 #[cfg(feature = "service-arg-validation")]
 use scabbard::service::ScabbardArgValidator;
 use scabbard::service::ScabbardFactory;
+#[cfg(feature = "mqtt-bridge")]
+use scabbard::service::ScabbardServiceLookup;
+#[cfg(all(feature = "alerting", feature = "scabbard-state-root-comparison"))]
+use scabbard::service::StateRootMonitor;
+#[cfg(feature = "config-reload")]
+use signal_hook::{iterator::Signals, SIGHUP};
+#[cfg(feature = "admin-allowed-signers")]
+use splinter::admin::allowed_signers::AllowedSignerKeyVerifier;
+#[cfg(feature = "admin-auto-vote")]
+use splinter::admin::auto_vote::AutoVoter;
+#[cfg(feature = "circuit-id-policy")]
+use splinter::admin::circuit_id::CircuitIdPolicy;
+#[cfg(feature = "admin-key-rotation")]
+use splinter::admin::key_rotation::{KeyRotationVerifier, DEFAULT_KEY_ROTATION_OVERLAP};
+#[cfg(feature = "admin-allowed-signers")]
+use splinter::admin::rest_api::AllowedSignerResourceProvider;
 use splinter::admin::rest_api::CircuitResourceProvider;
-use splinter::admin::service::{admin_service_id, AdminService};
+#[cfg(feature = "admin-signed-submit")]
+use splinter::admin::rest_api::SignedPayloadResourceProvider;
+#[cfg(feature = "tenancy")]
+use splinter::admin::rest_api::TenantResourceProvider;
+#[cfg(feature = "admin-vote-cosigning")]
+use splinter::admin::rest_api::VoteCosignResourceProvider;
+#[cfg(feature = "admin-vote-delegation")]
+use splinter::admin::rest_api::VoteDelegationResourceProvider;
+#[cfg(feature = "event-publisher")]
+use splinter::admin::service::AdminCommands;
+use splinter::admin::service::{admin_service_id, AdminKeyVerifier, AdminService};
+#[cfg(feature = "admin-signature-cache")]
+use splinter::admin::signature_cache::{CachingVerifier, DEFAULT_SIGNATURE_CACHE_SIZE};
 use splinter::admin::store::yaml::YamlAdminServiceStore;
 #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
 use splinter::biome::rest_api::{BiomeRestResourceManager, BiomeRestResourceManagerBuilder};
+#[cfg(feature = "circuit-dead-letter")]
+use splinter::circuit::dead_letter::{DeadLetterQueue, DEFAULT_DEAD_LETTER_CAPACITY};
+#[cfg(feature = "circuit-relay")]
+use splinter::circuit::handlers::AdminRelayRoutes;
 use splinter::circuit::handlers::{
     AdminDirectMessageHandler, CircuitDirectMessageHandler, CircuitErrorHandler,
     CircuitMessageHandler, ServiceConnectRequestHandler, ServiceDisconnectRequestHandler,
 };
+#[cfg(any(feature = "circuit-ping", feature = "health"))]
+use splinter::circuit::handlers::{CircuitPingRequestHandler, CircuitPingResponseHandler};
+#[cfg(feature = "resource-watchdog")]
+use splinter::circuit::intake::IntakePause;
+#[cfg(any(feature = "circuit-ping", feature = "health"))]
+use splinter::circuit::ping::{CircuitPinger, PendingPings};
+#[cfg(feature = "circuit-qos")]
+use splinter::circuit::qos::QosEnforcer;
+#[cfg(feature = "circuit-replay-protection")]
+use splinter::circuit::replay::{ReplayWindow, DEFAULT_REPLAY_WINDOW_SIZE};
 use splinter::circuit::routing::{memory::RoutingTable, RoutingTableReader, RoutingTableWriter};
+#[cfg(feature = "circuit-payload-schema")]
+use splinter::circuit::schema::PayloadValidator;
+#[cfg(feature = "service-endpoint-auth")]
+use splinter::circuit::{
+    handlers::ServiceAuthChallengeRequestHandler, service_auth::ServiceChallenges,
+};
 use splinter::keys::insecure::AllowAllKeyPermissionManager;
 use splinter::mesh::Mesh;
+#[cfg(feature = "peer-identity-pinning")]
+use splinter::network::auth::pinning::{IdentityPinVerifier, PinningMode, YamlIdentityPinStore};
+#[cfg(feature = "spiffe")]
+use splinter::network::auth::spiffe::SpiffeIdVerifier;
 use splinter::network::auth::AuthorizationManager;
+#[cfg(feature = "network-clock-skew")]
+use splinter::network::clock_skew::{ClockSkewMonitor, DEFAULT_CLOCK_SKEW_THRESHOLD};
 use splinter::network::connection_manager::{
     authorizers::Authorizers, authorizers::InprocAuthorizer, ConnectionManager, Connector,
 };
@@ -48,38 +114,108 @@ use splinter::network::dispatch::{
     dispatch_channel, DispatchLoopBuilder, DispatchMessageSender, Dispatcher,
 };
 use splinter::network::handlers::{NetworkEchoHandler, NetworkHeartbeatHandler};
+#[cfg(feature = "external-services")]
+use splinter::orchestrator::ExternalServiceFactory;
 use splinter::orchestrator::{NewOrchestratorError, ServiceOrchestrator};
+#[cfg(feature = "message-capture")]
+use splinter::peer::capture::MessageCapture;
 use splinter::peer::interconnect::NetworkMessageSender;
 use splinter::peer::interconnect::PeerInterconnectBuilder;
-use splinter::peer::PeerManager;
+#[cfg(feature = "outbound-spool")]
+use splinter::peer::spool::{OutboundSpoolBuilder, OutboundSpoolForwarder};
+#[cfg(any(feature = "message-capture", feature = "circuit-replay-protection"))]
+use splinter::peer::PeerManagerNotification;
+use splinter::peer::{PeerManager, PeerManagerConnector};
 use splinter::protos::circuit::CircuitMessageType;
 use splinter::protos::network::NetworkMessageType;
+#[cfg(feature = "protos-reflection")]
+use splinter::protos::ProtoDescriptorResourceProvider;
+#[cfg(feature = "registry-watcher")]
+use splinter::registry::watcher::{RegistryChange, RegistryWatcher};
+#[cfg(feature = "registry-remote-auth")]
+use splinter::registry::RegistryError;
+#[cfg(feature = "registry-source-status")]
+use splinter::registry::RegistrySourcesResourceProvider;
 use splinter::registry::{
     LocalYamlRegistry, RegistryReader, RemoteYamlRegistry, RemoteYamlShutdownHandle, RwRegistry,
     UnifiedRegistry,
 };
+#[cfg(feature = "rest-api-acme")]
+use splinter::rest_api::acme::AcmeCertificateManager;
+#[cfg(all(feature = "config-reload", feature = "rest-api-cors"))]
+use splinter::rest_api::cors::Cors;
+#[cfg(feature = "read-only-mode")]
+use splinter::rest_api::read_only::ReadOnly;
+#[cfg(feature = "rest-api-acme")]
+use splinter::rest_api::TlsCertificateReloader;
 #[cfg(feature = "auth")]
 use splinter::rest_api::{AuthConfig, OAuthConfig};
 use splinter::rest_api::{
     Method, Resource, RestApiBuilder, RestApiServerError, RestResourceProvider,
 };
+#[cfg(feature = "runtime-diagnostics")]
+use splinter::runtime::{RuntimeDiagnosticsResourceProvider, RuntimeMonitor, ThreadHeartbeat};
+#[cfg(feature = "scheduler")]
+use splinter::scheduler::{Scheduler, SchedulerResourceProvider};
 #[cfg(feature = "service-arg-validation")]
 use splinter::service::validation::ServiceArgValidator;
-use splinter::service::{self, ServiceProcessor, ShutdownHandle};
+#[cfg(feature = "service-factories")]
+use splinter::service::ServiceFactory;
+use splinter::service::{self, SequenceNumbers, ServiceProcessor, ShutdownHandle};
+use splinter::signing::{MultiVerifierFactory, SigningAlgorithm};
+#[cfg(feature = "tenancy")]
+use splinter::tenant::TenantCircuitScopeProvider;
+#[cfg(feature = "peer-send-limits")]
+use splinter::transport::matrix::SendLimits;
+#[cfg(feature = "tls-policy")]
+use splinter::transport::tls::TlsPolicy;
 use splinter::transport::{
     inproc::InprocTransport, multi::MultiTransport, AcceptError, ConnectError, Connection,
     Incoming, ListenError, Listener, Transport,
 };
 
+#[cfg(feature = "alerting")]
+use crate::alerts::{
+    AlertEngine, AlertRule, AlertSinkConfig, AlertState, DEFAULT_EVALUATION_INTERVAL,
+};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use crate::circuit_scopes::{circuit_scope_provider, CircuitScopeRoleConfig};
+#[cfg(all(feature = "health", feature = "network-clock-skew"))]
+use crate::clock_skew_readiness::ClockSkewReadinessCheck;
+#[cfg(feature = "rest-api-cors-policies")]
+use crate::cors::{route_policies, CorsPolicyConfig};
+#[cfg(feature = "event-publisher")]
+use crate::event_publisher::{EventPublisher, EventPublisherConfig};
+#[cfg(feature = "log-retrieval")]
+use crate::log_buffer::LogBuffer;
+#[cfg(feature = "metrics-push")]
+use crate::metrics_reporter::MetricsReporter;
+#[cfg(feature = "mqtt-bridge")]
+use crate::mqtt_bridge::{MqttBridge, MqttBridgeConfig};
+#[cfg(feature = "node-stats")]
+use crate::node_stats::{
+    StatsCollector, StatsStore, DEFAULT_RETENTION_HOURS, DEFAULT_SAMPLE_INTERVAL, NODE_STATS_FILE,
+};
+#[cfg(feature = "registry-remote-auth")]
+use crate::registry_auth::{find_registry_auth, RegistryAuthConfig};
+#[cfg(all(feature = "health", feature = "registry-source-status"))]
+use crate::registry_readiness::RegistryReadinessCheck;
+#[cfg(all(feature = "health", feature = "resource-watchdog"))]
+use crate::resource_watchdog::ResourceReadinessCheck;
+#[cfg(feature = "resource-watchdog")]
+use crate::resource_watchdog::{
+    ResourceStatus, ResourceWatchdog, DEFAULT_CHECK_INTERVAL, DEFAULT_MIN_FREE_SPACE_MB,
+};
 use crate::routes;
+use crate::shutdown::{ShutdownCoordinator, ShutdownError, Subsystem, DEFAULT_SHUTDOWN_TIMEOUT};
 
-const ORCHESTRATOR_INCOMING_CAPACITY: usize = 8;
-const ORCHESTRATOR_OUTGOING_CAPACITY: usize = 8;
-const ORCHESTRATOR_CHANNEL_CAPACITY: usize = 8;
+const DEFAULT_ORCHESTRATOR_INCOMING_CAPACITY: usize = 8;
+const DEFAULT_ORCHESTRATOR_OUTGOING_CAPACITY: usize = 8;
+const DEFAULT_ORCHESTRATOR_CHANNEL_CAPACITY: usize = 8;
 
-const ADMIN_SERVICE_PROCESSOR_INCOMING_CAPACITY: usize = 8;
-const ADMIN_SERVICE_PROCESSOR_OUTGOING_CAPACITY: usize = 8;
-const ADMIN_SERVICE_PROCESSOR_CHANNEL_CAPACITY: usize = 8;
+const DEFAULT_ADMIN_SERVICE_PROCESSOR_INCOMING_CAPACITY: usize = 8;
+const DEFAULT_ADMIN_SERVICE_PROCESSOR_OUTGOING_CAPACITY: usize = 8;
+const DEFAULT_ADMIN_SERVICE_PROCESSOR_CHANNEL_CAPACITY: usize = 8;
 
 #[cfg(feature = "health")]
 const HEALTH_SERVICE_PROCESSOR_INCOMING_CAPACITY: usize = 8;
@@ -88,8 +224,43 @@ const HEALTH_SERVICE_PROCESSOR_OUTGOING_CAPACITY: usize = 8;
 #[cfg(feature = "health")]
 const HEALTH_SERVICE_PROCESSOR_CHANNEL_CAPACITY: usize = 8;
 
+/// The file in the state directory that peer identities are pinned in on first use
+#[cfg(feature = "peer-identity-pinning")]
+const IDENTITY_PINS_FILE: &str = "identity_pins.yaml";
+
+/// The file in the state directory that the sequence numbers of services' direct messages are
+/// reserved in, so they keep increasing when the node restarts
+const SEQUENCE_NUMBERS_FILE: &str = "sequence_numbers";
+
+/// The startup phase that loading the admin service store is reported as in the runtime diagnostics
+#[cfg(feature = "runtime-diagnostics")]
+const ADMIN_STORE_LOAD_PHASE: &str = "admin-store-load";
+
+/// The file in the state directory that the routing table is saved to on shutdown
+#[cfg(feature = "admin-routing-snapshot")]
+const ROUTING_SNAPSHOT_FILE: &str = "routing_table.json";
+
 type ServiceJoinHandle = service::JoinHandles<Result<(), service::error::ServiceProcessorError>>;
 
+/// The daemon settings that are reloaded when the daemon receives SIGHUP.
+#[cfg(feature = "config-reload")]
+pub struct ReloadableConfig {
+    pub registries: Vec<String>,
+    pub registry_auto_refresh: u64,
+    pub registry_forced_refresh: u64,
+    #[cfg(feature = "registry-remote-auth")]
+    pub registry_auth: Vec<RegistryAuthConfig>,
+    #[cfg(feature = "rest-api-cors")]
+    pub whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub cors_policies: Vec<CorsPolicyConfig>,
+    pub peer_blocklist: Vec<String>,
+}
+
+/// Re-reads the daemon's configuration, returning the settings to apply.
+#[cfg(feature = "config-reload")]
+pub type ConfigReloader = Box<dyn Fn() -> Result<ReloadableConfig, String> + Send>;
+
 pub struct SplinterDaemon {
     state_dir: String,
     #[cfg(feature = "service-endpoint")]
@@ -97,12 +268,41 @@ pub struct SplinterDaemon {
     network_endpoints: Vec<String>,
     advertised_endpoints: Vec<String>,
     initial_peers: Vec<String>,
+    peer_transport_preference: Vec<String>,
     mesh: Mesh,
     node_id: String,
     display_name: String,
     rest_api_endpoint: String,
     #[cfg(feature = "https-bind")]
     rest_api_ssl_settings: Option<(String, String)>,
+    #[cfg(feature = "tls-policy")]
+    rest_api_tls_policy: TlsPolicy,
+    #[cfg(feature = "tls-policy")]
+    rest_api_client_ca_file: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_certificate_manager: Option<AcmeCertificateManager>,
+    #[cfg(feature = "spiffe")]
+    spiffe_trust_domain: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_require_id: bool,
+    #[cfg(feature = "peer-identity-pinning")]
+    peer_identity_pinning: Option<PinningMode>,
+    #[cfg(feature = "admin-key-rotation")]
+    admin_key_rotation_overlap: Duration,
+    #[cfg(feature = "circuit-id-policy")]
+    circuit_id_policy: CircuitIdPolicy,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_voter: Option<AutoVoter>,
+    #[cfg(feature = "admin-routing-snapshot")]
+    persist_routing_table: bool,
+    #[cfg(feature = "admin-signature-cache")]
+    admin_signature_cache_size: usize,
+    #[cfg(feature = "read-only-mode")]
+    read_only: bool,
+    #[cfg(feature = "resource-watchdog")]
+    resource_min_free_space: u64,
+    #[cfg(feature = "external-services")]
+    external_service_types: Vec<(String, PathBuf)>,
     #[cfg(feature = "database")]
     db_url: Option<String>,
     #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
@@ -112,8 +312,22 @@ pub struct SplinterDaemon {
     registry_forced_refresh: u64,
     storage_type: Option<String>,
     admin_timeout: Duration,
+    orchestrator_incoming_capacity: usize,
+    orchestrator_outgoing_capacity: usize,
+    orchestrator_channel_capacity: usize,
+    admin_processor_incoming_capacity: usize,
+    admin_processor_outgoing_capacity: usize,
+    admin_processor_channel_capacity: usize,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_policies: Vec<CorsPolicyConfig>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_roles: Vec<CircuitScopeRoleConfig>,
+    #[cfg(feature = "tenancy")]
+    tenant_operators: Vec<String>,
+    #[cfg(feature = "registry-remote-auth")]
+    registry_auth: Vec<RegistryAuthConfig>,
     #[cfg(feature = "auth")]
     oauth_provider: Option<String>,
     #[cfg(feature = "auth")]
@@ -121,16 +335,139 @@ pub struct SplinterDaemon {
     #[cfg(feature = "auth")]
     oauth_client_secret: Option<String>,
     #[cfg(feature = "auth")]
+    oauth_public_client: bool,
+    #[cfg(feature = "auth")]
     oauth_redirect_url: Option<String>,
     #[cfg(feature = "auth")]
     oauth_openid_url: Option<String>,
+    signing_algorithms: Vec<SigningAlgorithm>,
     heartbeat: u64,
     strict_ref_counts: bool,
+    #[cfg(feature = "config-reload")]
+    peer_blocklist: Vec<String>,
+    #[cfg(feature = "config-reload")]
+    config_reloader: Option<ConfigReloader>,
+    #[cfg(feature = "metrics-push")]
+    metrics_reporter: Option<MetricsReporter>,
+    #[cfg(feature = "alerting")]
+    alert_rules: Vec<AlertRule>,
+    #[cfg(feature = "alerting")]
+    alert_sinks: Vec<AlertSinkConfig>,
+    #[cfg(feature = "event-publisher")]
+    event_publisher: Option<EventPublisherConfig>,
+    #[cfg(feature = "mqtt-bridge")]
+    mqtt_bridge: Option<MqttBridgeConfig>,
+    #[cfg(feature = "log-retrieval")]
+    log_buffer: Option<LogBuffer>,
+    #[cfg(feature = "service-factories")]
+    service_factories: Vec<Box<dyn ServiceFactory>>,
+    #[cfg(feature = "node-challenge")]
+    node_key: Option<PrivateKey>,
+    #[cfg(feature = "biome-user-admin")]
+    biome_admin_keys: Vec<String>,
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_threshold: Duration,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_limits: SendLimits,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_weights: Vec<(String, usize)>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_retention: Option<BatchHistoryRetention>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_circuit_db_dirs: bool,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_db_dirs: Vec<(String, Option<String>, String)>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_growth_policy: Option<DbGrowthPolicy>,
+}
+
+/// A daemon whose subsystems were started by `SplinterDaemon::run`.
+pub struct RunningDaemon {
+    peer_connector: PeerManagerConnector,
+    connection_connector: Connector,
+    shutdown_coordinator: ShutdownCoordinator,
+}
+
+impl RunningDaemon {
+    /// Returns the connector for the daemon's peer manager.
+    pub fn peer_connector(&self) -> &PeerManagerConnector {
+        &self.peer_connector
+    }
+
+    /// Returns the connector for the daemon's connection manager.
+    pub fn connection_connector(&self) -> &Connector {
+        &self.connection_connector
+    }
+
+    /// Shuts down the daemon's subsystems, returning the names of any that did not stop in time.
+    pub fn shutdown(self) -> Vec<String> {
+        self.shutdown_coordinator.shutdown()
+    }
 }
 
 impl SplinterDaemon {
-    pub fn start(&mut self, mut transport: MultiTransport) -> Result<(), StartError> {
-        // Setup up ctrlc handling
+    /// Starts the daemon and blocks until it receives a shutdown signal (Ctrl-C).
+    pub fn start(&mut self, transport: MultiTransport) -> Result<(), StartError> {
+        let running_daemon = self.run(transport)?;
+
+        let (shutdown_sender, shutdown_receiver) = channel();
+        ctrlc::set_handler(move || {
+            info!("Received Shutdown");
+            // The receiver is gone if shutdown is already in progress
+            let _ = shutdown_sender.send(());
+        })
+        .expect("Error setting Ctrl-C handler");
+
+        let _ = shutdown_receiver.recv();
+        running_daemon.shutdown();
+        Ok(())
+    }
+
+    /// Starts the daemon's subsystems without blocking.
+    ///
+    /// The daemon runs until `RunningDaemon::shutdown` is called on the returned value. Unlike
+    /// `start`, this does not install a Ctrl-C handler, so several daemons may be run in the same
+    /// process.
+    pub fn run(&mut self, mut transport: MultiTransport) -> Result<RunningDaemon, StartError> {
+        // Subsystems are registered as they are started, and are shut down in reverse order
+        let mut shutdown_coordinator = ShutdownCoordinator::new(DEFAULT_SHUTDOWN_TIMEOUT);
+
+        // Periodic jobs are registered with the scheduler as their subsystems are started; it is
+        // registered for shutdown once all of them have been started, so that it stops first
+        #[cfg(feature = "scheduler")]
+        let scheduler = Scheduler::new();
+        #[cfg(feature = "scheduler")]
+        let scheduler_join_handle = scheduler.start().map_err(|err| {
+            StartError::SchedulerError(format!("unable to start scheduler: {}", err))
+        })?;
+
+        #[cfg(all(feature = "metrics-push", feature = "scheduler"))]
+        {
+            if let Some(metrics_reporter) = self.metrics_reporter.take() {
+                metrics_reporter
+                    .schedule(&scheduler)
+                    .map_err(|err| StartError::MetricsError(err.to_string()))?;
+            }
+        }
+
+        #[cfg(all(feature = "metrics-push", not(feature = "scheduler")))]
+        {
+            if let Some(metrics_reporter) = self.metrics_reporter.take() {
+                let metrics_reporter_shutdown = metrics_reporter
+                    .start()
+                    .map_err(|err| StartError::MetricsError(err.to_string()))?;
+                shutdown_coordinator.register(
+                    Subsystem::new("metrics-reporter")
+                        .with_signal(move || metrics_reporter_shutdown.shutdown()),
+                )?;
+            }
+        }
+
+        let mesh_shutdown = self.mesh.shutdown_signaler();
+        shutdown_coordinator
+            .register(Subsystem::new("mesh").with_signal(move || mesh_shutdown.shutdown()))?;
+
+        // Signals the service processors to stop
         let running = Arc::new(AtomicBool::new(true));
 
         let mut service_transport = InprocTransport::default();
@@ -163,6 +500,7 @@ impl SplinterDaemon {
         ))]
         let store_factory = create_store_factory(&db_url)?;
 
+        let admin_store_load_start = Instant::now();
         let admin_service_store = {
             if let Some(storage) = &self.storage_type {
                 // Get state from the configured storage type and state directory, then
@@ -225,6 +563,12 @@ impl SplinterDaemon {
             }
         };
 
+        let admin_store_load_time = admin_store_load_start.elapsed();
+        info!(
+            "Loaded admin service store in {} ms",
+            admin_store_load_time.as_millis()
+        );
+
         let table = RoutingTable::default();
         let routing_reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
         let routing_writer: Box<dyn RoutingTableWriter> = Box::new(table);
@@ -255,17 +599,71 @@ impl SplinterDaemon {
             service_listener.endpoint()
         );
 
+        // The internal services listen on the daemon's own inproc transport, so their endpoints
+        // do not collide with those of other daemons in the same process
         let mut internal_service_listeners = vec![];
-        internal_service_listeners.push(transport.listen("inproc://admin-service")?);
-        internal_service_listeners.push(transport.listen("inproc://orchestator")?);
+        internal_service_listeners.push(service_transport.listen("inproc://admin-service")?);
+        internal_service_listeners.push(service_transport.listen("inproc://orchestator")?);
         #[cfg(feature = "health")]
-        internal_service_listeners.push(transport.listen("inproc://health_service")?);
+        internal_service_listeners.push(service_transport.listen("inproc://health_service")?);
+
+        let (unified_registry, registry_shutdown) = create_registry(
+            &self.state_dir,
+            &self.registries,
+            self.registry_auto_refresh,
+            self.registry_forced_refresh,
+            #[cfg(feature = "registry-remote-auth")]
+            &self.registry_auth,
+            #[cfg(feature = "registry-database")]
+            &*store_factory,
+        )?;
+        // Allowing possibly redundant clone of `unified_registry` since it will be needed again if
+        // the `config-reload` feature is enabled
+        #[allow(clippy::redundant_clone)]
+        let registry: Box<dyn RwRegistry> = Box::new(unified_registry.clone());
+        let registry_shutdown = Arc::new(Mutex::new(registry_shutdown));
 
         info!("Starting SpinterNode with ID {}", self.node_id);
-        let authorization_manager =
-            AuthorizationManager::new(self.node_id.clone()).map_err(|err| {
+        #[allow(unused_mut)]
+        let mut authorization_manager = AuthorizationManager::new(self.node_id.clone())
+            .map_err(|err| {
                 StartError::NetworkError(format!("Unable to create authorization manager: {}", err))
-            })?;
+            })?
+            .with_advertised_features(advertised_features());
+
+        // Peers presenting SPIFFE SVIDs must claim the identity the registry maps their SPIFFE ID
+        // to
+        #[cfg(feature = "spiffe")]
+        {
+            if let Some(trust_domain) = &self.spiffe_trust_domain {
+                authorization_manager = authorization_manager.with_spiffe_id_verifier(
+                    SpiffeIdVerifier::new(trust_domain, registry.clone_box_as_reader())
+                        .with_require_id(self.spiffe_require_id),
+                );
+            }
+        }
+
+        // Peers must present the certificate their identity was pinned to, either in the registry
+        // or the first time they were authorized
+        #[cfg(feature = "peer-identity-pinning")]
+        let identity_pin_verifier = self.peer_identity_pinning.map(|mode| {
+            IdentityPinVerifier::new(
+                mode,
+                Box::new(YamlIdentityPinStore::new(
+                    Path::new(&self.state_dir).join(IDENTITY_PINS_FILE),
+                )),
+            )
+            .with_registry(registry.clone_box_as_reader())
+        });
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            if let Some(verifier) = &identity_pin_verifier {
+                authorization_manager =
+                    authorization_manager.with_identity_pin_verifier(verifier.clone());
+            }
+        }
+        #[cfg(feature = "peer-management")]
+        let peer_capabilities = authorization_manager.peer_capabilities();
 
         // Allowing unused_mut because inproc_ids must be mutable if feature health is enabled
         #[allow(unused_mut)]
@@ -286,29 +684,53 @@ impl SplinterDaemon {
             format!("health::{}", &self.node_id),
         ));
 
-        let inproc_authorizer = InprocAuthorizer::new(inproc_ids);
-
+        // Only the internal service endpoints are authorized by identity; any other inproc
+        // connection, such as a peer in the same process, is authorized like a network peer
         let mut authorizers = Authorizers::new();
-        authorizers.add_authorizer("inproc", inproc_authorizer);
+        for (endpoint, identity) in inproc_ids {
+            authorizers.add_authorizer(
+                &endpoint,
+                InprocAuthorizer::new(vec![(endpoint.clone(), identity)]),
+            );
+        }
         authorizers.add_authorizer("", authorization_manager.authorization_connector());
 
-        let connection_manager = ConnectionManager::builder()
+        let connection_manager_builder = ConnectionManager::builder()
             .with_authorizer(Box::new(authorizers))
             .with_matrix_life_cycle(self.mesh.get_life_cycle())
             .with_matrix_sender(self.mesh.get_sender())
             .with_transport(Box::new(transport))
-            .with_heartbeat_interval(self.heartbeat)
-            .start()
-            .map_err(|err| {
-                StartError::NetworkError(format!("Unable to start connection manager: {}", err))
-            })?;
+            .with_heartbeat_interval(self.heartbeat);
+        #[cfg(feature = "peer-send-limits")]
+        let connection_manager_builder = {
+            let send_limits = self.peer_send_limits;
+            self.peer_send_weights.iter().fold(
+                connection_manager_builder.with_send_limits(send_limits),
+                |builder, (node_id, weight)| {
+                    builder.with_peer_send_limits(
+                        node_id.to_string(),
+                        send_limits.with_send_weight(*weight),
+                    )
+                },
+            )
+        };
+        let connection_manager = connection_manager_builder.start().map_err(|err| {
+            StartError::NetworkError(format!("Unable to start connection manager: {}", err))
+        })?;
         let connection_connector = connection_manager.connector();
         let connection_manager_shutdown = connection_manager.shutdown_signaler();
+        shutdown_coordinator.register(
+            Subsystem::new("connection-manager")
+                .with_dependency("mesh")
+                .with_signal(move || connection_manager_shutdown.shutdown())
+                .with_wait(move || connection_manager.await_shutdown()),
+        )?;
 
         let peer_manager = PeerManager::builder()
             .with_connector(connection_connector.clone())
             .with_identity(self.node_id.to_string())
             .with_strict_ref_counts(self.strict_ref_counts)
+            .with_endpoint_preference(self.peer_transport_preference.clone())
             .start()
             .map_err(|err| {
                 StartError::NetworkError(format!("Unable to start peer manager: {}", err))
@@ -317,6 +739,20 @@ impl SplinterDaemon {
         let peer_connector = peer_manager.connector();
         let peer_manager_shutdown = peer_manager.shutdown_signaler();
 
+        #[cfg(feature = "config-reload")]
+        {
+            if !self.peer_blocklist.is_empty() {
+                peer_connector
+                    .set_blocklist(self.peer_blocklist.clone())
+                    .map_err(|err| {
+                        StartError::NetworkError(format!("Unable to set peer blocklist: {}", err))
+                    })?;
+            }
+        }
+        #[cfg(feature = "config-reload")]
+        let reload_peer_connector = peer_connector.clone();
+        let running_peer_connector = peer_connector.clone();
+
         // Listen for services
         Self::listen_for_services(
             connection_connector.clone(),
@@ -356,24 +792,149 @@ impl SplinterDaemon {
             })?;
 
         let (network_dispatcher_sender, network_dispatch_receiver) = dispatch_channel();
-        let interconnect = PeerInterconnectBuilder::new()
+        let mut interconnect_builder = PeerInterconnectBuilder::new()
             .with_peer_connector(peer_connector.clone())
             .with_message_receiver(self.mesh.get_receiver())
             .with_message_sender(self.mesh.get_sender())
-            .with_network_dispatcher_sender(network_dispatcher_sender.clone())
+            .with_network_dispatcher_sender(network_dispatcher_sender.clone());
+
+        // Circuit messages for unreachable peers are spooled to the state directory and
+        // delivered when the peer reconnects
+        #[cfg(feature = "outbound-spool")]
+        let outbound_spool = OutboundSpoolBuilder::new()
+            .with_spool_file(Path::new(&self.state_dir).join("outbound_spool.yaml"))
             .build()
             .map_err(|err| {
-                StartError::NetworkError(format!("Unable to create peer interconnect: {}", err))
+                StartError::StorageError(format!("Unable to load outbound spool: {}", err))
             })?;
+        #[cfg(feature = "outbound-spool")]
+        {
+            interconnect_builder = interconnect_builder.with_outbound_spool(outbound_spool.clone());
+        }
+
+        // Captures of the messages exchanged with peers are written to the state directory
+        #[cfg(feature = "message-capture")]
+        let message_capture = MessageCapture::new(Path::new(&self.state_dir).join("captures"));
+        #[cfg(feature = "message-capture")]
+        {
+            interconnect_builder =
+                interconnect_builder.with_message_capture(message_capture.clone());
+
+            let (notification_sender, notifications) = channel();
+            peer_connector
+                .subscribe_sender::<PeerManagerNotification>(notification_sender)
+                .map_err(|err| {
+                    StartError::NetworkError(format!(
+                        "Unable to subscribe to peer notifications for message capture: {}",
+                        err
+                    ))
+                })?;
+            let capture = message_capture.clone();
+            // this thread exits when the peer manager shuts down
+            thread::Builder::new()
+                .name("MessageCapturePeerEvents".into())
+                .spawn(move || {
+                    for notification in notifications.iter() {
+                        capture.record_peer_notification(&notification);
+                    }
+                })
+                .map_err(|err| {
+                    StartError::NetworkError(format!(
+                        "Unable to start message capture peer events thread: {}",
+                        err
+                    ))
+                })?;
+        }
+
+        let interconnect = interconnect_builder.build().map_err(|err| {
+            StartError::NetworkError(format!("Unable to create peer interconnect: {}", err))
+        })?;
 
         let network_sender = interconnect.new_network_sender();
 
+        #[cfg(feature = "outbound-spool")]
+        let outbound_spool_forwarder =
+            OutboundSpoolForwarder::start(outbound_spool, &peer_connector, network_sender.clone())
+                .map_err(|err| {
+                    StartError::NetworkError(format!(
+                        "Unable to start outbound spool forwarder: {}",
+                        err
+                    ))
+                })?;
+
+        #[cfg(any(feature = "circuit-ping", feature = "health"))]
+        let pending_pings = PendingPings::new();
+        #[cfg(feature = "circuit-dead-letter")]
+        let dead_letters = DeadLetterQueue::new(DEFAULT_DEAD_LETTER_CAPACITY);
+        // The windows of removed peers are forgotten, so they do not build up over time
+        #[cfg(feature = "circuit-replay-protection")]
+        let replay_window = {
+            let replay_window = ReplayWindow::new(DEFAULT_REPLAY_WINDOW_SIZE);
+            let (notification_sender, notifications) = channel();
+            peer_connector
+                .subscribe_sender::<PeerManagerNotification>(notification_sender)
+                .map_err(|err| {
+                    StartError::NetworkError(format!(
+                        "Unable to subscribe to peer notifications for replay protection: {}",
+                        err
+                    ))
+                })?;
+            let peer_window = replay_window.clone();
+            // this thread exits when the peer manager shuts down
+            thread::Builder::new()
+                .name("ReplayWindowPeerEvents".into())
+                .spawn(move || {
+                    for notification in notifications.iter() {
+                        if let Err(err) = peer_window.handle_peer_notification(&notification) {
+                            error!("Unable to update replay window: {}", err);
+                        }
+                    }
+                })
+                .map_err(|err| {
+                    StartError::NetworkError(format!(
+                        "Unable to start replay window peer events thread: {}",
+                        err
+                    ))
+                })?;
+            replay_window
+        };
+        #[cfg(feature = "circuit-qos")]
+        let qos_enforcer = QosEnforcer::new(routing_reader.clone());
+        // Paused by the resource watchdog while the node is degraded
+        #[cfg(feature = "resource-watchdog")]
+        let intake_pause = IntakePause::new();
+        #[cfg(feature = "network-clock-skew")]
+        let clock_skew_monitor = ClockSkewMonitor::new(self.clock_skew_threshold);
+
+        // Long-running threads and channel queues are registered as they are created, and
+        // reported by the runtime diagnostics endpoint
+        #[cfg(feature = "runtime-diagnostics")]
+        let runtime_monitor = RuntimeMonitor::new();
+        #[cfg(feature = "runtime-diagnostics")]
+        runtime_monitor.add_startup_phase(ADMIN_STORE_LOAD_PHASE, admin_store_load_time);
+
+        // Routes to the admin services of unpeered proposal members, through relay members
+        #[cfg(feature = "circuit-relay")]
+        let admin_relay_routes = AdminRelayRoutes::new();
+
         // Set up the Circuit dispatcher
         let circuit_dispatcher = set_up_circuit_dispatcher(
             network_sender.clone(),
             &self.node_id,
             routing_reader.clone(),
             routing_writer.clone(),
+            #[cfg(any(feature = "circuit-ping", feature = "health"))]
+            pending_pings.clone(),
+            #[cfg(feature = "circuit-dead-letter")]
+            dead_letters.clone(),
+            #[cfg(feature = "circuit-replay-protection")]
+            replay_window,
+            #[cfg(feature = "circuit-relay")]
+            peer_connector.clone(),
+            #[cfg(feature = "circuit-relay")]
+            admin_relay_routes.clone(),
+            #[cfg(feature = "circuit-qos")]
+            qos_enforcer.clone(),
         );
         let circuit_dispatch_loop = DispatchLoopBuilder::new()
             .with_dispatcher(circuit_dispatcher)
@@ -384,11 +945,33 @@ impl SplinterDaemon {
             })?;
         let circuit_dispatch_sender = circuit_dispatch_loop.new_dispatcher_sender();
 
+        #[cfg(feature = "circuit-dead-letter")]
+        let dead_letter_resources =
+            routes::make_dead_letter_resources(dead_letters, circuit_dispatch_sender.clone());
+
+        #[cfg(any(feature = "circuit-ping", feature = "health"))]
+        let circuit_pinger = CircuitPinger::new(
+            self.node_id.to_string(),
+            circuit_dispatch_sender.clone(),
+            pending_pings,
+        );
+        #[cfg(feature = "health")]
+        let health_pinger = circuit_pinger.clone();
+
         let circuit_dispatcher_shutdown = circuit_dispatch_loop.shutdown_signaler();
 
         // Set up the Network dispatcher
-        let network_dispatcher =
-            set_up_network_dispatcher(network_sender, &self.node_id, circuit_dispatch_sender);
+        let network_dispatcher = set_up_network_dispatcher(
+            network_sender,
+            &self.node_id,
+            circuit_dispatch_sender,
+            #[cfg(feature = "circuit-qos")]
+            qos_enforcer,
+            #[cfg(feature = "resource-watchdog")]
+            intake_pause.clone(),
+            #[cfg(feature = "network-clock-skew")]
+            clock_skew_monitor.clone(),
+        );
 
         let network_dispatch_loop = DispatchLoopBuilder::new()
             .with_dispatcher(network_dispatcher)
@@ -400,14 +983,26 @@ impl SplinterDaemon {
             })?;
         let network_dispatcher_shutdown = network_dispatch_loop.shutdown_signaler();
 
+        #[cfg(feature = "runtime-diagnostics")]
+        {
+            circuit_dispatch_loop.register_runtime_diagnostics(&runtime_monitor);
+            network_dispatch_loop.register_runtime_diagnostics(&runtime_monitor);
+        }
+
         let interconnect_shutdown = interconnect.shutdown_signaler();
 
         // setup threads to listen on the network ports and add incoming connections to the network
-        // these threads will just be dropped on shutdown
+        // these threads block on accepting connections, so they are not shut down; once the
+        // connection manager has stopped, each thread exits on its next connection
         let _ = network_listeners
             .into_iter()
             .map(|mut network_listener| {
                 let connection_connector_clone = connection_connector.clone();
+                #[cfg(feature = "runtime-diagnostics")]
+                let heartbeat =
+                    ThreadHeartbeat::new(&format!("Listener({})", network_listener.endpoint()));
+                #[cfg(feature = "runtime-diagnostics")]
+                runtime_monitor.add_thread(heartbeat.monitor());
                 thread::spawn(move || {
                     let endpoint = network_listener.endpoint();
                     for connection_result in network_listener.incoming() {
@@ -423,6 +1018,8 @@ impl SplinterDaemon {
                             }
                         };
                         debug!("Received connection from {}", connection.remote_endpoint());
+                        #[cfg(feature = "runtime-diagnostics")]
+                        heartbeat.busy();
                         if let Err(err) =
                             connection_connector_clone.add_inbound_connection(connection)
                         {
@@ -433,46 +1030,243 @@ impl SplinterDaemon {
                             error!("Exiting listener thread for {}", endpoint);
                             break;
                         }
+                        #[cfg(feature = "runtime-diagnostics")]
+                        heartbeat.idle();
                     }
                 })
             })
             .collect::<Vec<_>>();
 
-        // hold on to peer refs for the peers provided to ensure the connections are kept around
-        let mut peer_refs = vec![];
+        // hold on to peer refs for the peers provided to ensure the connections are kept around;
+        // the refs are keyed by endpoint so the peers can be removed through the REST API
+        let mut initial_peer_refs = HashMap::new();
         for endpoint in self.initial_peers.iter() {
             match peer_connector.add_unidentified_peer(endpoint.into()) {
-                Ok(peer_ref) => peer_refs.push(peer_ref),
+                Ok(peer_ref) => {
+                    initial_peer_refs.insert(endpoint.to_string(), peer_ref);
+                }
                 Err(err) => error!("Connect Error: {}", err),
             }
         }
+        let peer_refs = Arc::new(Mutex::new(initial_peer_refs));
+        #[cfg(feature = "peer-management")]
+        let rest_api_peer_refs = Arc::clone(&peer_refs);
 
+        // Scabbard batches are always secp256k1-signed; the admin service and Cylinder JWT auth
+        // accept any of the node's configured signing algorithms
         let signing_context = Secp256k1Context::new();
-        let admin_service_verifier = signing_context.new_verifier();
+        let verifier_factory = MultiVerifierFactory::new(self.signing_algorithms.clone());
+        let admin_service_verifier = verifier_factory.new_verifier();
+
+        let scabbard_factory =
+            ScabbardFactory::new(None, None, None, None, Box::new(signing_context));
+
+        #[cfg(feature = "scabbard-batch-history")]
+        let scabbard_factory = match &self.scabbard_batch_history_retention {
+            Some(retention) => scabbard_factory.with_batch_history_retention(retention.clone()),
+            None => scabbard_factory,
+        };
+
+        #[cfg(feature = "scabbard-db-placement")]
+        let scabbard_factory = self.scabbard_db_dirs.iter().fold(
+            scabbard_factory.with_circuit_db_dirs(self.scabbard_circuit_db_dirs),
+            |factory, (circuit_id, service_id, db_dir)| match service_id {
+                Some(service_id) => factory.with_service_db_dir(
+                    circuit_id.clone(),
+                    service_id.clone(),
+                    db_dir.clone(),
+                ),
+                None => factory.with_circuit_db_dir(circuit_id.clone(), db_dir.clone()),
+            },
+        );
+
+        #[cfg(feature = "scabbard-db-auto-grow")]
+        let scabbard_factory = match &self.scabbard_db_growth_policy {
+            Some(policy) => scabbard_factory.with_db_growth_policy(policy.clone()),
+            None => scabbard_factory,
+        };
+
+        // The watchdog is started with the REST API, since it degrades the node through the REST
+        // API's read-only mode, but its status is needed by the alert engine and health checks
+        #[cfg(feature = "resource-watchdog")]
+        let resource_status = ResourceStatus::default();
+        #[cfg(feature = "resource-watchdog")]
+        #[allow(unused_mut)]
+        let mut watchdog_directories = vec![
+            PathBuf::from(&self.state_dir),
+            PathBuf::from(scabbard_factory.state_db_dir()),
+            PathBuf::from(scabbard_factory.receipt_db_dir()),
+        ];
+        // Circuits may keep their databases on other volumes
+        #[cfg(all(feature = "resource-watchdog", feature = "scabbard-db-placement"))]
+        watchdog_directories.extend(
+            self.scabbard_db_dirs
+                .iter()
+                .map(|(_, _, db_dir)| PathBuf::from(db_dir)),
+        );
+        #[cfg(feature = "resource-watchdog")]
+        let commit_pause = CommitPause::new();
+        #[cfg(feature = "resource-watchdog")]
+        let scabbard_factory = scabbard_factory.with_commit_pause(commit_pause.clone());
+        #[cfg(feature = "resource-watchdog")]
+        let watchdog_store = admin_service_store.clone();
+
+        #[cfg(feature = "alerting")]
+        let alert_state = AlertState::default();
+        #[cfg(feature = "alerting")]
+        let (scabbard_factory, alert_engine) = {
+            let batch_queue_monitor = BatchQueueMonitor::new();
+            #[cfg(feature = "scabbard-state-root-comparison")]
+            let state_root_monitor = StateRootMonitor::new();
+            let alert_engine = AlertEngine::start(
+                self.alert_rules.clone(),
+                self.alert_sinks
+                    .iter()
+                    .cloned()
+                    .map(AlertSinkConfig::into_sink)
+                    .collect(),
+                alert_state.clone(),
+                &peer_connector,
+                admin_service_store.clone(),
+                batch_queue_monitor.clone(),
+                #[cfg(feature = "peer-identity-pinning")]
+                identity_pin_verifier.clone(),
+                #[cfg(feature = "resource-watchdog")]
+                resource_status.clone(),
+                #[cfg(feature = "scabbard-state-root-comparison")]
+                state_root_monitor.clone(),
+                DEFAULT_EVALUATION_INTERVAL,
+            )
+            .map_err(|err| StartError::AlertingError(err.to_string()))?;
+            let scabbard_factory = scabbard_factory.with_batch_queue_monitor(batch_queue_monitor);
+            #[cfg(feature = "scabbard-state-root-comparison")]
+            let scabbard_factory = scabbard_factory.with_state_root_monitor(state_root_monitor);
+            (scabbard_factory, alert_engine)
+        };
+
+        #[cfg(feature = "node-stats")]
+        let (stats_store, stats_collector) = {
+            let stats_store = StatsStore::open(
+                &Path::new(&self.state_dir).join(NODE_STATS_FILE),
+                DEFAULT_RETENTION_HOURS,
+            )
+            .map_err(|err| StartError::NodeStatsError(err.to_string()))?;
+            let stats_collector = StatsCollector::start(
+                stats_store.clone(),
+                admin_service_store.clone(),
+                peer_connector.clone(),
+                network_dispatch_loop.message_count(),
+                circuit_dispatch_loop.message_count(),
+                vec![
+                    Path::new(&self.state_dir).to_path_buf(),
+                    Path::new(scabbard_factory.state_db_dir()).to_path_buf(),
+                    Path::new(scabbard_factory.receipt_db_dir()).to_path_buf(),
+                ],
+                DEFAULT_SAMPLE_INTERVAL,
+            )
+            .map_err(|err| StartError::NodeStatsError(err.to_string()))?;
+            (stats_store, stats_collector)
+        };
+
+        #[cfg(feature = "event-publisher")]
+        let event_publisher = match &self.event_publisher {
+            Some(config) => Some(
+                EventPublisher::start(config.clone())
+                    .map_err(|err| StartError::EventPublisherError(err.to_string()))?,
+            ),
+            None => None,
+        };
+        #[cfg(feature = "event-publisher")]
+        let scabbard_factory = match &event_publisher {
+            Some(publisher) => {
+                scabbard_factory.with_state_subscriber_factory(publisher.state_subscriber_factory())
+            }
+            None => scabbard_factory,
+        };
+
+        #[cfg(feature = "mqtt-bridge")]
+        let (scabbard_factory, mqtt_bridge) = match &self.mqtt_bridge {
+            Some(config) => {
+                let services = ScabbardServiceLookup::new();
+                let bridge = MqttBridge::start(config.clone(), services.clone())
+                    .map_err(|err| StartError::MqttBridgeError(err.to_string()))?;
+                (
+                    scabbard_factory
+                        .with_service_lookup(services)
+                        .with_state_subscriber_factory(bridge.state_subscriber_factory()),
+                    Some(bridge),
+                )
+            }
+            None => (scabbard_factory, None),
+        };
+
+        #[allow(unused_mut)]
+        let mut service_factories: Vec<Box<dyn service::ServiceFactory>> =
+            vec![Box::new(scabbard_factory)];
+        #[cfg(feature = "service-factories")]
+        service_factories.append(&mut self.service_factories);
+        #[cfg(feature = "external-services")]
+        {
+            if !self.external_service_types.is_empty() {
+                let factory = self.external_service_types.iter().fold(
+                    ExternalServiceFactory::new(self.service_endpoint.clone()),
+                    |factory, (service_type, executable)| {
+                        factory.with_service_type(service_type.clone(), executable.clone())
+                    },
+                );
+                service_factories.push(Box::new(factory));
+            }
+        }
 
+        let service_types = service_factories
+            .iter()
+            .flat_map(|factory| factory.available_service_types().to_vec())
+            .collect();
+        let capabilities = routes::Capabilities::new(
+            self.enabled_features(),
+            service_types,
+            splinter::protocol::protocol_version_ranges(),
+        );
+
+        let sequence_numbers =
+            SequenceNumbers::from_file(Path::new(&self.state_dir).join(SEQUENCE_NUMBERS_FILE))
+                .map_err(|err| StartError::OrchestratorError(err.to_string()))?;
         let (orchestrator, orchestator_join_handles) = ServiceOrchestrator::new(
-            vec![Box::new(ScabbardFactory::new(
-                None,
-                None,
-                None,
-                None,
-                Box::new(signing_context),
-            ))],
+            service_factories,
             orchestrator_connection,
-            ORCHESTRATOR_INCOMING_CAPACITY,
-            ORCHESTRATOR_OUTGOING_CAPACITY,
-            ORCHESTRATOR_CHANNEL_CAPACITY,
+            self.orchestrator_incoming_capacity,
+            self.orchestrator_outgoing_capacity,
+            self.orchestrator_channel_capacity,
         )?;
+        let orchestrator = orchestrator.with_sequence_numbers(sequence_numbers);
         let orchestrator_resources = orchestrator.resources();
+        #[cfg(feature = "runtime-diagnostics")]
+        orchestrator.register_runtime_diagnostics(&runtime_monitor);
+
+        // A node's retired keys remain permitted for the overlap period after a key rotation, so
+        // payloads signed before the rotation reached every member are still accepted
+        #[cfg(feature = "admin-key-rotation")]
+        let registry_key_verifier: Box<dyn AdminKeyVerifier> = Box::new(KeyRotationVerifier::new(
+            registry.clone_box_as_reader(),
+            self.admin_key_rotation_overlap,
+        ));
+        #[cfg(not(feature = "admin-key-rotation"))]
+        let registry_key_verifier: Box<dyn AdminKeyVerifier> =
+            Box::new(registry.clone_box_as_reader());
+
+        // Only the allowed signers of a node may propose or vote on its behalf, if the node has
+        // any allowed signers
+        #[cfg(feature = "admin-allowed-signers")]
+        let admin_key_verifier = Box::new(AllowedSignerKeyVerifier::new(
+            registry_key_verifier,
+            store_factory.get_admin_allowed_signer_store(),
+        ));
+        #[cfg(not(feature = "admin-allowed-signers"))]
+        let admin_key_verifier = registry_key_verifier;
 
-        let (registry, registry_shutdown) = create_registry(
-            &self.state_dir,
-            &self.registries,
-            self.registry_auto_refresh,
-            self.registry_forced_refresh,
-            #[cfg(feature = "registry-database")]
-            &*store_factory,
-        )?;
+        // Existing peers must be told when their endpoints change in the registry
+        #[cfg(feature = "registry-watcher")]
+        let registry_watcher_peer_connector = peer_connector.clone();
 
         let (admin_service, admin_notification_join) = AdminService::new(
             &self.node_id,
@@ -487,7 +1281,7 @@ impl SplinterDaemon {
             peer_connector,
             admin_service_store.clone(),
             admin_service_verifier,
-            Box::new(registry.clone_box_as_reader()),
+            admin_key_verifier,
             Box::new(AllowAllKeyPermissionManager),
             Some(self.admin_timeout),
             routing_writer.clone(),
@@ -496,6 +1290,280 @@ impl SplinterDaemon {
             StartError::AdminServiceError(format!("unable to create admin service: {}", err))
         })?;
 
+        #[cfg(feature = "admin-signature-cache")]
+        {
+            if self.admin_signature_cache_size > 0 {
+                admin_service
+                    .set_signature_cache(CachingVerifier::new(
+                        verifier_factory.new_verifier(),
+                        self.admin_signature_cache_size,
+                    ))
+                    .map_err(|err| {
+                        StartError::AdminServiceError(format!(
+                            "unable to set admin signature cache: {}",
+                            err
+                        ))
+                    })?;
+            }
+        }
+
+        #[cfg(feature = "read-only-mode")]
+        let read_only = {
+            // The node challenge only signs a nonce, so it remains available to clients checking
+            // the node's identity
+            #[allow(unused_mut)]
+            let mut read_only =
+                ReadOnly::new(self.read_only).with_exempt_path(routes::READ_ONLY_ROUTE);
+            #[cfg(feature = "node-challenge")]
+            {
+                read_only = read_only.with_exempt_path(routes::NODE_CHALLENGE_ROUTE);
+            }
+            if self.read_only {
+                warn!("Starting in read-only mode; requests that change state will be refused");
+            }
+            read_only
+        };
+
+        #[cfg(feature = "read-only-mode")]
+        admin_service
+            .set_read_only(read_only.clone())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!("unable to set read-only mode: {}", err))
+            })?;
+
+        #[cfg(feature = "circuit-relay")]
+        admin_service
+            .set_relay_routes(admin_relay_routes)
+            .map_err(|err| {
+                StartError::AdminServiceError(format!("unable to set admin relay routes: {}", err))
+            })?;
+
+        #[cfg(feature = "admin-vote-cosigning")]
+        admin_service
+            .set_vote_cosign_store(store_factory.get_admin_vote_cosign_store())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!(
+                    "unable to set admin vote co-signing store: {}",
+                    err
+                ))
+            })?;
+
+        #[cfg(feature = "admin-vote-delegation")]
+        admin_service
+            .set_vote_delegation_store(store_factory.get_admin_vote_delegation_store())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!(
+                    "unable to set admin vote delegation store: {}",
+                    err
+                ))
+            })?;
+
+        #[cfg(feature = "tenancy")]
+        admin_service
+            .set_tenant_store(store_factory.get_tenant_store())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!("unable to set admin tenant store: {}", err))
+            })?;
+
+        #[cfg(feature = "circuit-id-policy")]
+        admin_service
+            .set_circuit_id_policy(self.circuit_id_policy.clone())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!("unable to set circuit ID policy: {}", err))
+            })?;
+
+        #[cfg(feature = "admin-routing-reconciliation")]
+        admin_service
+            .set_routing_table_reader(routing_reader.clone())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!(
+                    "unable to set admin routing table reader: {}",
+                    err
+                ))
+            })?;
+
+        #[cfg(feature = "runtime-diagnostics")]
+        admin_service
+            .set_runtime_monitor(runtime_monitor.clone())
+            .map_err(|err| {
+                StartError::AdminServiceError(format!(
+                    "unable to set admin runtime monitor: {}",
+                    err
+                ))
+            })?;
+
+        #[cfg(feature = "admin-routing-snapshot")]
+        {
+            if self.persist_routing_table {
+                admin_service
+                    .set_routing_snapshot_path(
+                        Path::new(&self.state_dir).join(ROUTING_SNAPSHOT_FILE),
+                    )
+                    .map_err(|err| {
+                        StartError::AdminServiceError(format!(
+                            "unable to set routing snapshot path: {}",
+                            err
+                        ))
+                    })?;
+            }
+        }
+
+        #[cfg(feature = "admin-auto-vote")]
+        {
+            if let Some(auto_voter) = self.auto_voter.take() {
+                admin_service.set_auto_voter(auto_voter).map_err(|err| {
+                    StartError::AdminServiceError(format!(
+                        "unable to set admin auto-voter: {}",
+                        err
+                    ))
+                })?;
+            }
+        }
+
+        #[cfg(feature = "event-publisher")]
+        {
+            if let (Some(publisher), Some(config)) = (&event_publisher, &self.event_publisher) {
+                let admin_commands = admin_service.commands();
+                for circuit_management_type in &config.circuit_management_types {
+                    admin_commands
+                        .add_event_subscriber(circuit_management_type, publisher.admin_subscriber())
+                        .map_err(|err| StartError::EventPublisherError(err.to_string()))?;
+                }
+            }
+        }
+
+        // The admin service's notification receiver exits once the peer manager has stopped
+        shutdown_coordinator.register(
+            Subsystem::new("peer-manager")
+                .with_dependency("connection-manager")
+                .with_signal(move || {
+                    // Release the peers added by endpoint before the peer manager stops
+                    match peer_refs.lock() {
+                        Ok(mut peer_refs) => peer_refs.clear(),
+                        Err(_) => error!("Unable to release peers: lock poisoned"),
+                    }
+                    peer_manager_shutdown.shutdown()
+                })
+                .with_wait(move || {
+                    peer_manager.await_shutdown();
+                    let _ = admin_notification_join.join();
+                }),
+        )?;
+        shutdown_coordinator.register(
+            Subsystem::new("circuit-dispatcher")
+                .with_signal(move || circuit_dispatcher_shutdown.shutdown())
+                .with_wait(move || circuit_dispatch_loop.wait_for_shutdown()),
+        )?;
+        shutdown_coordinator.register(
+            Subsystem::new("network-dispatcher")
+                .with_dependency("circuit-dispatcher")
+                .with_signal(move || network_dispatcher_shutdown.shutdown())
+                .with_wait(move || network_dispatch_loop.wait_for_shutdown()),
+        )?;
+        shutdown_coordinator.register(
+            Subsystem::new("interconnect")
+                .with_dependency("network-dispatcher")
+                .with_dependency("peer-manager")
+                .with_signal(move || interconnect_shutdown.shutdown())
+                .with_wait(move || interconnect.await_shutdown()),
+        )?;
+        // The forwarder exits once the peer manager has stopped
+        #[cfg(feature = "outbound-spool")]
+        shutdown_coordinator.register(
+            Subsystem::new("outbound-spool-forwarder")
+                .with_dependency("peer-manager")
+                .with_wait(move || outbound_spool_forwarder.await_shutdown()),
+        )?;
+        #[cfg(feature = "alerting")]
+        {
+            let alert_engine_shutdown = alert_engine.shutdown_signaler();
+            shutdown_coordinator.register(
+                Subsystem::new("alert-engine")
+                    .with_dependency("peer-manager")
+                    .with_signal(move || alert_engine_shutdown.shutdown())
+                    .with_wait(move || alert_engine.await_shutdown()),
+            )?;
+        }
+        #[cfg(feature = "node-stats")]
+        {
+            let stats_collector_shutdown = stats_collector.shutdown_signaler();
+            shutdown_coordinator.register(
+                Subsystem::new("stats-collector")
+                    .with_dependency("peer-manager")
+                    .with_signal(move || stats_collector_shutdown.shutdown())
+                    .with_wait(move || stats_collector.await_shutdown()),
+            )?;
+        }
+        #[cfg(feature = "event-publisher")]
+        {
+            if let Some(event_publisher) = event_publisher {
+                let event_publisher_shutdown = event_publisher.shutdown_signaler();
+                shutdown_coordinator.register(
+                    Subsystem::new("event-publisher")
+                        .with_signal(move || event_publisher_shutdown.shutdown())
+                        .with_wait(move || event_publisher.await_shutdown()),
+                )?;
+            }
+        }
+        #[cfg(feature = "mqtt-bridge")]
+        {
+            if let Some(mqtt_bridge) = mqtt_bridge {
+                let mqtt_bridge_shutdown = mqtt_bridge.shutdown_signaler();
+                shutdown_coordinator.register(
+                    Subsystem::new("mqtt-bridge")
+                        .with_signal(move || mqtt_bridge_shutdown.shutdown())
+                        .with_wait(move || mqtt_bridge.await_shutdown()),
+                )?;
+            }
+        }
+        shutdown_coordinator.register(
+            Subsystem::new("orchestrator")
+                .with_dependency("connection-manager")
+                .with_wait(move || {
+                    let _ = orchestator_join_handles.join_all();
+                }),
+        )?;
+        let registry_subsystem_shutdown = Arc::clone(&registry_shutdown);
+        shutdown_coordinator.register(Subsystem::new("registry").with_signal(move || {
+            match registry_subsystem_shutdown.lock() {
+                Ok(registry_shutdown) => registry_shutdown.shutdown(),
+                Err(_) => error!("Unable to shut down registries: lock poisoned"),
+            }
+        }))?;
+        // Registries are only re-read on the automatic refresh interval, so there is nothing to
+        // watch for if automatic refresh is disabled
+        #[cfg(feature = "registry-watcher")]
+        {
+            if self.registry_auto_refresh > 0 {
+                let registry_watcher = RegistryWatcher::start(
+                    registry.clone_box_as_reader(),
+                    Duration::from_secs(self.registry_auto_refresh),
+                    Box::new(move |change| {
+                        if let RegistryChange::NodeUpdated { previous, current } = change {
+                            if previous.endpoints != current.endpoints {
+                                if let Err(err) = registry_watcher_peer_connector
+                                    .update_peer_endpoints(&current.identity, current.endpoints)
+                                {
+                                    error!(
+                                        "Unable to update endpoints of peer {}: {}",
+                                        current.identity, err
+                                    );
+                                }
+                            }
+                        }
+                    }),
+                )
+                .map_err(|err| StartError::RegistryWatcherError(err.to_string()))?;
+                let registry_watcher_shutdown = registry_watcher.shutdown_signaler();
+                shutdown_coordinator.register(
+                    Subsystem::new("registry-watcher")
+                        .with_dependency("peer-manager")
+                        .with_signal(move || registry_watcher_shutdown.shutdown())
+                        .with_wait(move || registry_watcher.await_shutdown()),
+                )?;
+            }
+        }
+
         let node_id = self.node_id.clone();
         let display_name = self.display_name.clone();
         #[cfg(feature = "service-endpoint")]
@@ -503,12 +1571,34 @@ impl SplinterDaemon {
         let network_endpoints = self.network_endpoints.clone();
         let advertised_endpoints = self.advertised_endpoints.clone();
 
+        #[cfg(feature = "routing-table-rest-api")]
+        let routing_resources = routes::make_routing_resources(
+            self.node_id.to_string(),
+            routing_reader.clone(),
+            routing_writer.clone(),
+            admin_service_store.clone(),
+        );
+
         let circuit_resource_provider =
             CircuitResourceProvider::new(self.node_id.to_string(), admin_service_store);
 
         #[cfg(not(feature = "https-bind"))]
         let bind = &self.rest_api_endpoint;
 
+        #[cfg(feature = "rest-api-acme")]
+        {
+            if let Some(acme_certificate_manager) = &self.acme_certificate_manager {
+                acme_certificate_manager
+                    .ensure_certificate()
+                    .map_err(|err| {
+                        StartError::RestApiError(format!(
+                            "Unable to obtain ACME certificate: {}",
+                            err
+                        ))
+                    })?;
+            }
+        }
+
         #[cfg(feature = "https-bind")]
         let bind = self.build_rest_api_bind()?;
 
@@ -528,6 +1618,7 @@ impl SplinterDaemon {
                         service_endpoint.clone(),
                         network_endpoints.clone(),
                         advertised_endpoints.clone(),
+                        capabilities.clone(),
                     )
                 }),
             )
@@ -536,6 +1627,154 @@ impl SplinterDaemon {
             .add_resources(orchestrator_resources)
             .add_resources(circuit_resource_provider.resources());
 
+        #[cfg(feature = "registry-source-status")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                RegistrySourcesResourceProvider::new(unified_registry.clone()).resources(),
+            );
+        }
+
+        #[cfg(feature = "scheduler")]
+        {
+            rest_api_builder = rest_api_builder
+                .add_resources(SchedulerResourceProvider::new(scheduler.clone()).resources());
+        }
+
+        #[cfg(feature = "runtime-diagnostics")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                RuntimeDiagnosticsResourceProvider::new(runtime_monitor).resources(),
+            );
+        }
+
+        #[cfg(feature = "protos-reflection")]
+        {
+            rest_api_builder =
+                rest_api_builder.add_resources(ProtoDescriptorResourceProvider::new().resources());
+        }
+
+        #[cfg(feature = "admin-allowed-signers")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                AllowedSignerResourceProvider::new(store_factory.get_admin_allowed_signer_store())
+                    .resources(),
+            );
+        }
+
+        #[cfg(feature = "admin-vote-cosigning")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                VoteCosignResourceProvider::new(
+                    store_factory.get_admin_vote_cosign_store(),
+                    Box::new(admin_service.commands()),
+                )
+                .resources(),
+            );
+        }
+
+        #[cfg(feature = "admin-vote-delegation")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                VoteDelegationResourceProvider::new(
+                    store_factory.get_admin_vote_delegation_store(),
+                    verifier_factory.clone(),
+                )
+                .resources(),
+            );
+        }
+
+        #[cfg(feature = "admin-signed-submit")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                SignedPayloadResourceProvider::new(
+                    Box::new(admin_service.commands()),
+                    verifier_factory.clone(),
+                )
+                .resources(),
+            );
+        }
+
+        #[cfg(feature = "tenancy")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                TenantResourceProvider::new(store_factory.get_tenant_store()).resources(),
+            );
+        }
+
+        #[cfg(feature = "peer-management")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(routes::make_peers_resources(
+                running_peer_connector.clone(),
+                rest_api_peer_refs,
+                peer_capabilities,
+                #[cfg(feature = "network-clock-skew")]
+                clock_skew_monitor.clone(),
+            ));
+        }
+
+        #[cfg(feature = "routing-table-rest-api")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(routing_resources);
+        }
+
+        #[cfg(feature = "circuit-dead-letter")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(dead_letter_resources);
+        }
+
+        #[cfg(feature = "alerting")]
+        {
+            rest_api_builder = rest_api_builder.add_resource(routes::make_alerts_resource(
+                self.alert_rules.clone(),
+                alert_state,
+            ));
+        }
+
+        #[cfg(feature = "circuit-ping")]
+        {
+            rest_api_builder =
+                rest_api_builder.add_resources(routes::make_circuit_ping_resources(circuit_pinger));
+        }
+
+        #[cfg(feature = "node-stats")]
+        {
+            rest_api_builder =
+                rest_api_builder.add_resource(routes::make_stats_resource(stats_store));
+        }
+
+        #[cfg(feature = "peer-identity-pinning")]
+        {
+            if let Some(verifier) = identity_pin_verifier {
+                rest_api_builder =
+                    rest_api_builder.add_resources(routes::make_identity_pin_resources(verifier));
+            }
+        }
+
+        #[cfg(feature = "message-capture")]
+        {
+            rest_api_builder =
+                rest_api_builder.add_resources(routes::make_capture_resources(message_capture));
+        }
+
+        #[cfg(feature = "log-retrieval")]
+        {
+            if let Some(log_buffer) = &self.log_buffer {
+                rest_api_builder =
+                    rest_api_builder.add_resource(routes::make_logs_resource(log_buffer.clone()));
+            }
+        }
+
+        #[cfg(feature = "node-challenge")]
+        {
+            if let Some(node_key) = &self.node_key {
+                rest_api_builder =
+                    rest_api_builder.add_resource(routes::make_node_challenge_resource(
+                        self.node_id.clone(),
+                        Secp256k1Context::new().new_signer(node_key.clone()),
+                    ));
+            }
+        }
+
         #[cfg(feature = "rest-api-cors")]
         {
             if let Some(list) = &self.whitelist {
@@ -544,13 +1783,26 @@ impl SplinterDaemon {
             }
         }
 
+        #[cfg(feature = "rest-api-cors-policies")]
+        {
+            for (route, policy) in route_policies(&self.cors_policies) {
+                rest_api_builder = rest_api_builder.with_cors_route_policy(&route, policy);
+            }
+        }
+
         #[cfg(feature = "auth")]
         {
             let mut auth_configs = vec![];
 
             // Add Cylinder JWT as an auth provider
             auth_configs.push(AuthConfig::Cylinder {
-                verifier: Secp256k1Context::new().new_verifier(),
+                verifier: verifier_factory.new_verifier(),
+                #[cfg(feature = "biome-key-expiration")]
+                key_store: if self.enable_biome {
+                    Some(store_factory.get_biome_key_store())
+                } else {
+                    None
+                },
             });
 
             // Handle OAuth config. If no OAuth config values are provided, just skip this;
@@ -558,6 +1810,7 @@ impl SplinterDaemon {
             let any_oauth_args_provided = self.oauth_provider.is_some()
                 || self.oauth_client_id.is_some()
                 || self.oauth_client_secret.is_some()
+                || self.oauth_public_client
                 || self.oauth_redirect_url.is_some();
             if any_oauth_args_provided {
                 let oauth_provider = self.oauth_provider.as_deref().ok_or_else(|| {
@@ -566,9 +1819,19 @@ impl SplinterDaemon {
                 let client_id = self.oauth_client_id.clone().ok_or_else(|| {
                     StartError::RestApiError("missing OAuth client ID configuration".into())
                 })?;
-                let client_secret = self.oauth_client_secret.clone().ok_or_else(|| {
-                    StartError::RestApiError("missing OAuth client secret configuration".into())
-                })?;
+                // A public client has no secret; PKCE alone protects its code exchange
+                let client_secret = match (self.oauth_public_client, &self.oauth_client_secret) {
+                    (true, Some(_)) => {
+                        return Err(StartError::RestApiError(
+                            "an OAuth client secret cannot be configured for a public client"
+                                .into(),
+                        ))
+                    }
+                    (true, None) => None,
+                    (false, client_secret) => Some(client_secret.clone().ok_or_else(|| {
+                        StartError::RestApiError("missing OAuth client secret configuration".into())
+                    })?),
+                };
                 let redirect_url = self.oauth_redirect_url.clone().ok_or_else(|| {
                     StartError::RestApiError("missing OAuth redirect URL configuration".into())
                 })?;
@@ -586,7 +1849,11 @@ impl SplinterDaemon {
                     },
                     "github" => OAuthConfig::GitHub {
                         client_id,
-                        client_secret,
+                        client_secret: client_secret.ok_or_else(|| {
+                            StartError::RestApiError(
+                                "the github OAuth provider does not support public clients".into(),
+                            )
+                        })?,
                         redirect_url,
                         inflight_request_store: store_factory.get_oauth_inflight_request_store(),
                     },
@@ -625,7 +1892,11 @@ impl SplinterDaemon {
             // is configured. This informs the REST API that Biome is providing auth.
             #[cfg(feature = "biome-credentials")]
             if self.enable_biome {
-                let biome_resource_manager = build_biome_routes(&*store_factory)?;
+                let biome_resource_manager = build_biome_routes(
+                    &*store_factory,
+                    #[cfg(feature = "biome-user-admin")]
+                    &self.biome_admin_keys,
+                )?;
                 auth_configs.push(AuthConfig::Biome {
                     biome_resource_manager,
                 });
@@ -634,88 +1905,356 @@ impl SplinterDaemon {
             rest_api_builder = rest_api_builder.with_auth_configs(auth_configs);
         }
 
+        #[cfg(all(feature = "rest-api-circuit-scopes", not(feature = "tenancy")))]
+        {
+            if !self.circuit_scope_roles.is_empty() {
+                debug!("Limiting REST API clients to the circuits of their roles");
+                rest_api_builder = rest_api_builder.with_circuit_scope_provider(Box::new(
+                    circuit_scope_provider(&self.circuit_scope_roles),
+                ));
+            }
+        }
+
+        // The users and keys of a tenant are limited to the tenant's circuits, and operators are
+        // unrestricted; other clients are limited to the circuits of their roles, if any
+        #[cfg(feature = "tenancy")]
+        {
+            debug!("Limiting REST API clients of tenants to the circuits of their tenant");
+            if self.tenant_operators.is_empty() {
+                warn!(
+                    "No tenant operators are configured; tenant assignments cannot be managed \
+                    through the REST API"
+                );
+            }
+            let mut provider = TenantCircuitScopeProvider::new(store_factory.get_tenant_store())
+                .with_operators(self.tenant_operators.clone());
+            if !self.circuit_scope_roles.is_empty() {
+                provider = provider
+                    .with_inner(Box::new(circuit_scope_provider(&self.circuit_scope_roles)));
+            }
+            rest_api_builder = rest_api_builder.with_circuit_scope_provider(Box::new(provider));
+        }
+
         // If Biome is enabled but wasn't already added as an auth provider, add it now
         #[cfg(all(
             any(feature = "biome-credentials", feature = "biome-key-management"),
             not(all(feature = "auth", feature = "biome-credentials"))
         ))]
         if self.enable_biome {
-            let biome_resources = build_biome_routes(&*store_factory)?;
+            let biome_resources = build_biome_routes(
+                &*store_factory,
+                #[cfg(feature = "biome-user-admin")]
+                &self.biome_admin_keys,
+            )?;
             rest_api_builder = rest_api_builder.add_resources(biome_resources.resources());
         }
 
-        let mut health_service_processor_join_handle: Option<_> = None;
         #[cfg(feature = "health")]
         {
             let health_service = HealthService::new(&self.node_id);
+            #[cfg(feature = "registry-source-status")]
+            let health_service = health_service.with_readiness_check(Box::new(
+                RegistryReadinessCheck::new(unified_registry.clone()),
+            ));
+            #[cfg(feature = "network-clock-skew")]
+            let health_service = health_service.with_readiness_check(Box::new(
+                ClockSkewReadinessCheck::new(clock_skew_monitor.clone()),
+            ));
+            #[cfg(feature = "resource-watchdog")]
+            let health_service = health_service.with_readiness_check(Box::new(
+                ResourceReadinessCheck::new(resource_status.clone()),
+            ));
             rest_api_builder = rest_api_builder.add_resources(health_service.resources());
 
-            health_service_processor_join_handle.replace(start_health_service(
-                health_connection,
-                health_service,
-                Arc::clone(&running),
-            )?);
+            let health_prober = HealthProber::start(
+                self.node_id.to_string(),
+                routing_reader.clone(),
+                health_pinger,
+                health_service.store(),
+                DEFAULT_PROBE_INTERVAL,
+                DEFAULT_PROBE_TIMEOUT,
+            )
+            .map_err(|err| {
+                StartError::HealthServiceError(format!("unable to start health prober: {}", err))
+            })?;
+            let health_prober_shutdown = health_prober.shutdown_signaler();
+            shutdown_coordinator.register(
+                Subsystem::new("health-prober")
+                    .with_dependency("circuit-dispatcher")
+                    .with_signal(move || health_prober_shutdown.shutdown())
+                    .with_wait(move || health_prober.await_shutdown()),
+            )?;
+
+            let health_service_processor_join_handle =
+                start_health_service(health_connection, health_service, Arc::clone(&running))?;
+            let health_running = Arc::clone(&running);
+            shutdown_coordinator.register(
+                Subsystem::new("health-service")
+                    .with_signal(move || health_running.store(false, Ordering::SeqCst))
+                    .with_wait(move || {
+                        let _ = health_service_processor_join_handle.join_all();
+                    }),
+            )?;
+        }
+
+        #[cfg(feature = "rest-api-acme")]
+        let tls_reloader = TlsCertificateReloader::new();
+        #[cfg(feature = "rest-api-acme")]
+        {
+            if self.acme_certificate_manager.is_some() {
+                rest_api_builder = rest_api_builder.with_tls_reloader(tls_reloader.clone());
+            }
+        }
+
+        #[cfg(feature = "tls-policy")]
+        {
+            rest_api_builder = rest_api_builder.with_tls_policy(self.rest_api_tls_policy.clone());
+            if let Some(ca_file) = &self.rest_api_client_ca_file {
+                rest_api_builder = rest_api_builder.with_tls_client_ca_file(ca_file.clone());
+            }
         }
 
-        #[cfg(not(feature = "health"))]
+        #[cfg(feature = "read-only-mode")]
         {
-            health_service_processor_join_handle.replace(());
+            #[cfg(feature = "resource-watchdog")]
+            {
+                let resource_watchdog = ResourceWatchdog::start(
+                    watchdog_directories,
+                    self.resource_min_free_space,
+                    watchdog_store,
+                    read_only.clone(),
+                    intake_pause,
+                    commit_pause,
+                    resource_status,
+                    DEFAULT_CHECK_INTERVAL,
+                )
+                .map_err(|err| StartError::ResourceWatchdogError(err.to_string()))?;
+                let resource_watchdog_shutdown = resource_watchdog.shutdown_signaler();
+                shutdown_coordinator.register(
+                    Subsystem::new("resource-watchdog")
+                        .with_signal(move || resource_watchdog_shutdown.shutdown())
+                        .with_wait(move || resource_watchdog.await_shutdown()),
+                )?;
+            }
+            rest_api_builder = rest_api_builder
+                .add_resource(routes::make_read_only_resource(read_only.clone()))
+                .with_read_only(read_only);
         }
 
-        let (rest_api_shutdown_handle, rest_api_join_handle) = rest_api_builder.build()?.run()?;
+        let rest_api = rest_api_builder.build()?;
+        #[cfg(all(feature = "config-reload", feature = "rest-api-cors"))]
+        let cors = rest_api.cors();
+        let (rest_api_shutdown_handle, rest_api_join_handle) = rest_api.run()?;
+        let rest_api_subsystem = Subsystem::new("rest-api")
+            .with_dependency("registry")
+            .with_dependency("orchestrator")
+            .with_signal(move || {
+                if let Err(err) = rest_api_shutdown_handle.shutdown() {
+                    error!("Unable to cleanly shut down REST API server: {}", err);
+                }
+            })
+            .with_wait(move || {
+                let _ = rest_api_join_handle.join();
+            });
+        #[cfg(feature = "health")]
+        let rest_api_subsystem = rest_api_subsystem.with_dependency("health-service");
+        shutdown_coordinator.register(rest_api_subsystem)?;
 
-        let (admin_shutdown_handle, service_processor_join_handle) =
-            Self::start_admin_service(admin_connection, admin_service, Arc::clone(&running))?;
+        #[cfg(feature = "config-reload")]
+        {
+            if let Some(config_reloader) = self.config_reloader.take() {
+                let reload_signals = start_config_reload(
+                    config_reloader,
+                    self.state_dir.clone(),
+                    unified_registry,
+                    Arc::clone(&registry_shutdown),
+                    reload_peer_connector,
+                    #[cfg(feature = "rest-api-cors")]
+                    cors,
+                )?;
+                shutdown_coordinator.register(
+                    Subsystem::new("config-reload")
+                        .with_dependency("registry")
+                        .with_dependency("peer-manager")
+                        .with_dependency("rest-api")
+                        .with_signal(move || reload_signals.close()),
+                )?;
+            }
+        }
 
-        // Allowing possibly redundant clone of `running` since it will be needed again if the
-        // `health` feature is enabled
-        #[allow(clippy::redundant_clone)]
-        let r = running.clone();
-        ctrlc::set_handler(move || {
-            info!("Received Shutdown");
-            r.store(false, Ordering::SeqCst);
+        #[cfg(all(feature = "rest-api-acme", feature = "scheduler"))]
+        {
+            if let Some(acme_certificate_manager) = self.acme_certificate_manager.take() {
+                acme_certificate_manager
+                    .schedule_renewal(tls_reloader, &scheduler)
+                    .map_err(|err| {
+                        StartError::RestApiError(format!(
+                            "Unable to schedule ACME certificate renewal: {}",
+                            err
+                        ))
+                    })?;
+            }
+        }
+
+        #[cfg(all(feature = "rest-api-acme", not(feature = "scheduler")))]
+        {
+            if let Some(acme_certificate_manager) = self.acme_certificate_manager.take() {
+                let acme_renewal_shutdown = acme_certificate_manager
+                    .start_renewal(tls_reloader)
+                    .map_err(|err| {
+                        StartError::RestApiError(format!(
+                            "Unable to start ACME certificate renewal: {}",
+                            err
+                        ))
+                    })?;
+                shutdown_coordinator.register(
+                    Subsystem::new("acme-renewal")
+                        .with_dependency("rest-api")
+                        .with_signal(move || acme_renewal_shutdown.shutdown()),
+                )?;
+            }
+        }
 
-            if let Err(err) = admin_shutdown_handle.shutdown() {
-                error!("Unable to cleanly shut down Admin service: {}", err);
-            }
+        #[cfg(feature = "scheduler")]
+        shutdown_coordinator.register(
+            Subsystem::new("scheduler")
+                .with_dependency("rest-api")
+                .with_signal(move || scheduler.shutdown())
+                .with_wait(move || {
+                    let _ = scheduler_join_handle.join();
+                }),
+        )?;
 
-            if let Err(err) = rest_api_shutdown_handle.shutdown() {
-                error!("Unable to cleanly shut down REST API server: {}", err);
-            }
-            circuit_dispatcher_shutdown.shutdown();
-            network_dispatcher_shutdown.shutdown();
-            registry_shutdown.shutdown();
-            interconnect_shutdown.shutdown();
+        let (admin_shutdown_handle, service_processor_join_handle) = Self::start_admin_service(
+            admin_connection,
+            admin_service,
+            self.admin_processor_incoming_capacity,
+            self.admin_processor_outgoing_capacity,
+            self.admin_processor_channel_capacity,
+            Arc::clone(&running),
+        )?;
+        shutdown_coordinator.register(
+            Subsystem::new("admin-service")
+                .with_dependency("orchestrator")
+                .with_dependency("peer-manager")
+                .with_dependency("registry")
+                .with_signal(move || {
+                    running.store(false, Ordering::SeqCst);
+                    if let Err(err) = admin_shutdown_handle.shutdown() {
+                        error!("Unable to cleanly shut down Admin service: {}", err);
+                    }
+                })
+                .with_wait(move || {
+                    let _ = service_processor_join_handle.join_all();
+                }),
+        )?;
+        debug!(
+            "Shutdown order: {}",
+            shutdown_coordinator.shutdown_order().join(", ")
+        );
+
+        Ok(RunningDaemon {
+            peer_connector: running_peer_connector,
+            connection_connector,
+            shutdown_coordinator,
         })
-        .expect("Error setting Ctrl-C handler");
+    }
 
+    /// Returns the features enabled on this node, as reported in the `/status` capabilities.
+    fn enabled_features(&self) -> Vec<String> {
+        let mut features: Vec<&str> = vec![];
+        #[cfg(feature = "admin-allowed-signers")]
+        features.push("admin-allowed-signers");
+        #[cfg(feature = "admin-auto-vote")]
+        features.push("admin-auto-vote");
+        #[cfg(feature = "admin-key-rotation")]
+        features.push("admin-key-rotation");
+        #[cfg(feature = "admin-parallel-startup")]
+        features.push("admin-parallel-startup");
+        #[cfg(feature = "admin-permission-prefetch")]
+        features.push("admin-permission-prefetch");
+        #[cfg(feature = "admin-routing-reconciliation")]
+        features.push("admin-routing-reconciliation");
+        #[cfg(feature = "admin-routing-snapshot")]
+        features.push("admin-routing-snapshot");
+        #[cfg(feature = "admin-service-debug")]
+        features.push("admin-service-debug");
+        #[cfg(feature = "admin-signature-cache")]
+        features.push("admin-signature-cache");
+        #[cfg(feature = "admin-signed-submit")]
+        features.push("admin-signed-submit");
+        #[cfg(feature = "admin-vote-cosigning")]
+        features.push("admin-vote-cosigning");
+        #[cfg(feature = "admin-vote-delegation")]
+        features.push("admin-vote-delegation");
+        #[cfg(feature = "alerting")]
+        features.push("alerting");
+        #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
+        if self.enable_biome {
+            features.push("biome");
+        }
+        #[cfg(feature = "circuit-dead-letter")]
+        features.push("circuit-dead-letter");
+        #[cfg(feature = "circuit-id-policy")]
+        features.push("circuit-id-policy");
+        #[cfg(feature = "circuit-ping")]
+        features.push("circuit-ping");
+        #[cfg(feature = "external-services")]
+        features.push("external-services");
         #[cfg(feature = "health")]
-        {
-            let _ = health_service_processor_join_handle
-                .expect(
-                    "The join handle was not configured correctly, which indicates a feature \
-                    compile error",
-                )
-                .join_all();
+        features.push("health");
+        #[cfg(feature = "https-bind")]
+        if !self.rest_api_endpoint.starts_with("http://") {
+            features.push("https-bind");
         }
-        #[cfg(not(feature = "health"))]
-        {
-            let _ = health_service_processor_join_handle.take();
+        #[cfg(feature = "log-retrieval")]
+        if self.log_buffer.is_some() {
+            features.push("log-retrieval");
         }
-
-        // Join threads and shutdown network components
-        let _ = rest_api_join_handle.join();
-        let _ = service_processor_join_handle.join_all();
-        let _ = orchestator_join_handles.join_all();
-        peer_manager_shutdown.shutdown();
-        peer_manager.await_shutdown();
-        debug!("Shutting down admin service's peer manager notification receiver...");
-        let _ = admin_notification_join.join();
-        debug!("Shutting down admin service's peer manager notification receiver (complete)");
-        connection_manager_shutdown.shutdown();
-        connection_manager.await_shutdown();
-        self.mesh.shutdown_signaler().shutdown();
-        Ok(())
+        #[cfg(feature = "message-capture")]
+        features.push("message-capture");
+        #[cfg(feature = "node-challenge")]
+        features.push("node-challenge");
+        #[cfg(feature = "node-stats")]
+        features.push("node-stats");
+        #[cfg(feature = "auth")]
+        if self.oauth_provider.is_some() {
+            features.push("oauth");
+        }
+        #[cfg(feature = "peer-identity-pinning")]
+        features.push("peer-identity-pinning");
+        #[cfg(feature = "peer-management")]
+        features.push("peer-management");
+        #[cfg(feature = "protos-reflection")]
+        features.push("protos-reflection");
+        #[cfg(feature = "read-only-mode")]
+        features.push("read-only-mode");
+        #[cfg(feature = "registry-source-status")]
+        features.push("registry-source-status");
+        #[cfg(feature = "resource-watchdog")]
+        features.push("resource-watchdog");
+        #[cfg(feature = "routing-table-rest-api")]
+        features.push("routing-table-rest-api");
+        #[cfg(feature = "runtime-diagnostics")]
+        features.push("runtime-diagnostics");
+        features.push("scabbard");
+        #[cfg(feature = "scheduler")]
+        features.push("scheduler");
+        #[cfg(feature = "service-endpoint")]
+        features.push("service-endpoint");
+        #[cfg(feature = "service-endpoint-auth")]
+        features.push("service-endpoint-auth");
+        #[cfg(feature = "spiffe")]
+        features.push("spiffe");
+        #[cfg(feature = "tenancy")]
+        features.push("tenancy");
+        #[cfg(feature = "tls-policy")]
+        features.push("tls-policy");
+        #[cfg(feature = "tls-revocation")]
+        features.push("tls-revocation");
+        #[cfg(feature = "yaml-state-journal")]
+        features.push("yaml-state-journal");
+        features.into_iter().map(String::from).collect()
     }
 
     #[cfg(feature = "https-bind")]
@@ -804,6 +2343,9 @@ impl SplinterDaemon {
     fn start_admin_service(
         connection: Box<dyn Connection>,
         admin_service: AdminService,
+        incoming_capacity: usize,
+        outgoing_capacity: usize,
+        channel_capacity: usize,
         running: Arc<AtomicBool>,
     ) -> Result<(ShutdownHandle, ServiceJoinHandle), StartError> {
         let start_admin: std::thread::JoinHandle<
@@ -812,9 +2354,9 @@ impl SplinterDaemon {
             let mut admin_service_processor = ServiceProcessor::new(
                 connection,
                 "admin".into(),
-                ADMIN_SERVICE_PROCESSOR_INCOMING_CAPACITY,
-                ADMIN_SERVICE_PROCESSOR_OUTGOING_CAPACITY,
-                ADMIN_SERVICE_PROCESSOR_CHANNEL_CAPACITY,
+                incoming_capacity,
+                outgoing_capacity,
+                channel_capacity,
                 running,
             )
             .map_err(|err| {
@@ -911,6 +2453,7 @@ fn create_store_factory(
 #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
 fn build_biome_routes(
     store_factory: &dyn splinter::store::StoreFactory,
+    #[cfg(feature = "biome-user-admin")] admin_keys: &[String],
 ) -> Result<BiomeRestResourceManager, StartError> {
     info!("Adding biome routes");
     #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
@@ -927,6 +2470,17 @@ fn build_biome_routes(
         biome_rest_provider_builder =
             biome_rest_provider_builder.with_key_store(store_factory.get_biome_key_store())
     }
+    #[cfg(feature = "biome-oauth-account-linking")]
+    {
+        biome_rest_provider_builder = biome_rest_provider_builder
+            .with_oauth_user_session_store(store_factory.get_biome_oauth_user_session_store());
+    }
+    #[cfg(feature = "biome-user-admin")]
+    {
+        biome_rest_provider_builder = biome_rest_provider_builder
+            .with_user_account_store(store_factory.get_biome_user_account_store())
+            .with_admin_keys(admin_keys.to_vec());
+    }
     let biome_rest_provider = biome_rest_provider_builder.build().map_err(|err| {
         StartError::RestApiError(format!("Unable to build Biome REST routes: {}", err))
     })?;
@@ -942,6 +2496,7 @@ pub struct SplinterDaemonBuilder {
     network_endpoints: Option<Vec<String>>,
     advertised_endpoints: Option<Vec<String>>,
     initial_peers: Option<Vec<String>>,
+    peer_transport_preference: Vec<String>,
     node_id: Option<String>,
     display_name: Option<String>,
     rest_api_endpoint: Option<String>,
@@ -949,6 +2504,76 @@ pub struct SplinterDaemonBuilder {
     rest_api_server_cert: Option<String>,
     #[cfg(feature = "https-bind")]
     rest_api_server_key: Option<String>,
+    #[cfg(feature = "tls-policy")]
+    rest_api_tls_policy: Option<TlsPolicy>,
+    #[cfg(feature = "tls-policy")]
+    rest_api_client_ca_file: Option<String>,
+    #[cfg(feature = "rest-api-acme")]
+    acme_certificate_manager: Option<AcmeCertificateManager>,
+    #[cfg(feature = "spiffe")]
+    spiffe_trust_domain: Option<String>,
+    #[cfg(feature = "spiffe")]
+    spiffe_require_id: bool,
+    #[cfg(feature = "peer-identity-pinning")]
+    peer_identity_pinning: Option<PinningMode>,
+    #[cfg(feature = "admin-key-rotation")]
+    admin_key_rotation_overlap: Option<Duration>,
+    #[cfg(feature = "circuit-id-policy")]
+    circuit_id_policy: Option<CircuitIdPolicy>,
+    #[cfg(feature = "admin-auto-vote")]
+    auto_voter: Option<AutoVoter>,
+    #[cfg(feature = "admin-routing-snapshot")]
+    persist_routing_table: bool,
+    #[cfg(feature = "admin-signature-cache")]
+    admin_signature_cache_size: Option<usize>,
+    #[cfg(feature = "read-only-mode")]
+    read_only: bool,
+    #[cfg(feature = "resource-watchdog")]
+    resource_min_free_space: Option<u64>,
+    #[cfg(feature = "external-services")]
+    external_service_types: Vec<(String, PathBuf)>,
+    #[cfg(feature = "config-reload")]
+    peer_blocklist: Option<Vec<String>>,
+    #[cfg(feature = "config-reload")]
+    config_reloader: Option<ConfigReloader>,
+    #[cfg(feature = "metrics-push")]
+    metrics_reporter: Option<MetricsReporter>,
+    #[cfg(feature = "alerting")]
+    alert_rules: Vec<AlertRule>,
+    #[cfg(feature = "alerting")]
+    alert_sinks: Vec<AlertSinkConfig>,
+    #[cfg(feature = "event-publisher")]
+    event_publisher: Option<EventPublisherConfig>,
+    #[cfg(feature = "mqtt-bridge")]
+    mqtt_bridge: Option<MqttBridgeConfig>,
+    #[cfg(feature = "log-retrieval")]
+    log_buffer: Option<LogBuffer>,
+    #[cfg(feature = "service-factories")]
+    service_factories: Vec<Box<dyn ServiceFactory>>,
+    #[cfg(feature = "node-challenge")]
+    node_key: Option<PrivateKey>,
+    #[cfg(feature = "biome-user-admin")]
+    biome_admin_keys: Option<Vec<String>>,
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_threshold: Option<Duration>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_queue_capacity: Option<usize>,
+    #[cfg(feature = "peer-send-limits")]
+    peer_send_weights: Vec<(String, usize)>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_size: Option<usize>,
+    #[cfg(feature = "scabbard-batch-history")]
+    scabbard_batch_history_max_age: Option<Duration>,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_circuit_db_dirs: bool,
+    #[cfg(feature = "scabbard-db-placement")]
+    scabbard_db_dirs: Vec<(String, Option<String>, String)>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_growth_increment: Option<usize>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_max_size: Option<usize>,
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    scabbard_db_warning_thresholds: Option<Vec<u8>>,
     #[cfg(feature = "database")]
     db_url: Option<String>,
     #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
@@ -959,8 +2584,22 @@ pub struct SplinterDaemonBuilder {
     storage_type: Option<String>,
     heartbeat: Option<u64>,
     admin_timeout: Duration,
+    orchestrator_incoming_capacity: Option<usize>,
+    orchestrator_outgoing_capacity: Option<usize>,
+    orchestrator_channel_capacity: Option<usize>,
+    admin_processor_incoming_capacity: Option<usize>,
+    admin_processor_outgoing_capacity: Option<usize>,
+    admin_processor_channel_capacity: Option<usize>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "rest-api-cors-policies")]
+    cors_policies: Vec<CorsPolicyConfig>,
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    circuit_scope_roles: Vec<CircuitScopeRoleConfig>,
+    #[cfg(feature = "tenancy")]
+    tenant_operators: Vec<String>,
+    #[cfg(feature = "registry-remote-auth")]
+    registry_auth: Vec<RegistryAuthConfig>,
     #[cfg(feature = "auth")]
     oauth_provider: Option<String>,
     #[cfg(feature = "auth")]
@@ -968,9 +2607,12 @@ pub struct SplinterDaemonBuilder {
     #[cfg(feature = "auth")]
     oauth_client_secret: Option<String>,
     #[cfg(feature = "auth")]
+    oauth_public_client: bool,
+    #[cfg(feature = "auth")]
     oauth_redirect_url: Option<String>,
     #[cfg(feature = "auth")]
     oauth_openid_url: Option<String>,
+    signing_algorithms: Vec<SigningAlgorithm>,
     strict_ref_counts: Option<bool>,
 }
 
@@ -1005,6 +2647,11 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    pub fn with_peer_transport_preference(mut self, value: Vec<String>) -> Self {
+        self.peer_transport_preference = value;
+        self
+    }
+
     pub fn with_node_id(mut self, value: String) -> Self {
         self.node_id = Some(value);
         self
@@ -1032,6 +2679,104 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    /// Sets the minimum TLS version, cipher suites and client certificate requirement of the
+    /// REST API's HTTPS bind.
+    #[cfg(feature = "tls-policy")]
+    pub fn with_rest_api_tls_policy(mut self, value: TlsPolicy) -> Self {
+        self.rest_api_tls_policy = Some(value);
+        self
+    }
+
+    /// Sets the CA certificate file the REST API verifies client certificates against.
+    #[cfg(feature = "tls-policy")]
+    pub fn with_rest_api_client_ca_file(mut self, value: String) -> Self {
+        self.rest_api_client_ca_file = Some(value);
+        self
+    }
+
+    /// Sets the SPIFFE trust domain of the peers whose SPIFFE IDs are mapped to registry nodes
+    /// during authorization.
+    #[cfg(feature = "spiffe")]
+    pub fn with_spiffe_trust_domain(mut self, value: String) -> Self {
+        self.spiffe_trust_domain = Some(value);
+        self
+    }
+
+    /// Sets whether peers must present a certificate containing a SPIFFE ID.
+    #[cfg(feature = "spiffe")]
+    pub fn with_spiffe_require_id(mut self, value: bool) -> Self {
+        self.spiffe_require_id = value;
+        self
+    }
+
+    /// Sets how the certificates of peers are pinned to their node identities.
+    #[cfg(feature = "peer-identity-pinning")]
+    pub fn with_peer_identity_pinning(mut self, value: PinningMode) -> Self {
+        self.peer_identity_pinning = Some(value);
+        self
+    }
+
+    /// Sets how long a node's retired admin key remains permitted after a key rotation.
+    #[cfg(feature = "admin-key-rotation")]
+    pub fn with_admin_key_rotation_overlap(mut self, value: Duration) -> Self {
+        self.admin_key_rotation_overlap = Some(value);
+        self
+    }
+
+    /// Sets the circuit ID formats accepted for new circuit proposals.
+    #[cfg(feature = "circuit-id-policy")]
+    pub fn with_circuit_id_policy(mut self, value: CircuitIdPolicy) -> Self {
+        self.circuit_id_policy = Some(value);
+        self
+    }
+
+    /// Sets the auto-voter that votes on circuit proposals on behalf of this node.
+    #[cfg(feature = "admin-auto-vote")]
+    pub fn with_auto_voter(mut self, value: AutoVoter) -> Self {
+        self.auto_voter = Some(value);
+        self
+    }
+
+    /// Sets whether the routing table is saved to the state directory on shutdown, so that
+    /// unchanged circuits can be restored from it on the next start.
+    #[cfg(feature = "admin-routing-snapshot")]
+    pub fn with_persist_routing_table(mut self, value: bool) -> Self {
+        self.persist_routing_table = value;
+        self
+    }
+
+    /// Sets the number of signature verification results the admin service caches; a size of
+    /// zero disables the cache.
+    #[cfg(feature = "admin-signature-cache")]
+    pub fn with_admin_signature_cache_size(mut self, value: usize) -> Self {
+        self.admin_signature_cache_size = Some(value);
+        self
+    }
+
+    /// Sets whether the node starts in read-only mode, where REST API requests that may change
+    /// state are refused.
+    #[cfg(feature = "read-only-mode")]
+    pub fn with_read_only(mut self, value: bool) -> Self {
+        self.read_only = value;
+        self
+    }
+
+    /// Sets the free disk space, in megabytes, below which the resource watchdog switches the
+    /// node to read-only mode.
+    #[cfg(feature = "resource-watchdog")]
+    pub fn with_resource_min_free_space(mut self, value: u64) -> Self {
+        self.resource_min_free_space = Some(value);
+        self
+    }
+
+    /// Sets the service types that are executed by launching an external executable, as pairs of
+    /// service type and the path to its executable.
+    #[cfg(feature = "external-services")]
+    pub fn with_external_service_types(mut self, value: Vec<(String, PathBuf)>) -> Self {
+        self.external_service_types = value;
+        self
+    }
+
     #[cfg(feature = "database")]
     pub fn with_db_url(mut self, value: Option<String>) -> Self {
         self.db_url = value;
@@ -1074,12 +2819,226 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    pub fn with_orchestrator_incoming_capacity(mut self, value: usize) -> Self {
+        self.orchestrator_incoming_capacity = Some(value);
+        self
+    }
+
+    pub fn with_orchestrator_outgoing_capacity(mut self, value: usize) -> Self {
+        self.orchestrator_outgoing_capacity = Some(value);
+        self
+    }
+
+    pub fn with_orchestrator_channel_capacity(mut self, value: usize) -> Self {
+        self.orchestrator_channel_capacity = Some(value);
+        self
+    }
+
+    pub fn with_admin_processor_incoming_capacity(mut self, value: usize) -> Self {
+        self.admin_processor_incoming_capacity = Some(value);
+        self
+    }
+
+    pub fn with_admin_processor_outgoing_capacity(mut self, value: usize) -> Self {
+        self.admin_processor_outgoing_capacity = Some(value);
+        self
+    }
+
+    pub fn with_admin_processor_channel_capacity(mut self, value: usize) -> Self {
+        self.admin_processor_channel_capacity = Some(value);
+        self
+    }
+
+    pub fn with_signing_algorithms(mut self, value: Vec<SigningAlgorithm>) -> Self {
+        self.signing_algorithms = value;
+        self
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    pub fn with_acme_certificate_manager(mut self, value: AcmeCertificateManager) -> Self {
+        self.acme_certificate_manager = Some(value);
+        self
+    }
+
+    #[cfg(feature = "config-reload")]
+    pub fn with_peer_blocklist(mut self, value: Vec<String>) -> Self {
+        self.peer_blocklist = Some(value);
+        self
+    }
+
+    #[cfg(feature = "config-reload")]
+    pub fn with_config_reloader(mut self, value: ConfigReloader) -> Self {
+        self.config_reloader = Some(value);
+        self
+    }
+
+    #[cfg(feature = "metrics-push")]
+    pub fn with_metrics_reporter(mut self, value: MetricsReporter) -> Self {
+        self.metrics_reporter = Some(value);
+        self
+    }
+
+    #[cfg(feature = "alerting")]
+    pub fn with_alert_rules(mut self, value: Vec<AlertRule>) -> Self {
+        self.alert_rules = value;
+        self
+    }
+
+    #[cfg(feature = "alerting")]
+    pub fn with_alert_sinks(mut self, value: Vec<AlertSinkConfig>) -> Self {
+        self.alert_sinks = value;
+        self
+    }
+
+    #[cfg(feature = "event-publisher")]
+    pub fn with_event_publisher(mut self, value: Option<EventPublisherConfig>) -> Self {
+        self.event_publisher = value;
+        self
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    pub fn with_mqtt_bridge(mut self, value: Option<MqttBridgeConfig>) -> Self {
+        self.mqtt_bridge = value;
+        self
+    }
+
+    /// Sets the buffer of recent log entries served by the `/admin/logs` endpoint; the endpoint
+    /// is only provided if a buffer is set.
+    #[cfg(feature = "log-retrieval")]
+    pub fn with_log_buffer(mut self, value: LogBuffer) -> Self {
+        self.log_buffer = Some(value);
+        self
+    }
+
+    /// Adds a factory for service types other than scabbard. The orchestrator will create
+    /// services of the factory's types when they are part of a circuit.
+    #[cfg(feature = "service-factories")]
+    pub fn with_service_factory(mut self, value: Box<dyn ServiceFactory>) -> Self {
+        self.service_factories.push(value);
+        self
+    }
+
+    /// Sets the secp256k1 key the node signs identity challenges with.
+    #[cfg(feature = "node-challenge")]
+    pub fn with_node_key(mut self, value: Option<PrivateKey>) -> Self {
+        self.node_key = value;
+        self
+    }
+
+    /// Sets the public keys that are permitted to administer Biome users.
+    #[cfg(feature = "biome-user-admin")]
+    pub fn with_biome_admin_keys(mut self, value: Vec<String>) -> Self {
+        self.biome_admin_keys = Some(value);
+        self
+    }
+
+    /// Sets how far a peer's clock may be from the local clock before it is reported as skewed.
+    #[cfg(feature = "network-clock-skew")]
+    pub fn with_clock_skew_threshold(mut self, value: Duration) -> Self {
+        self.clock_skew_threshold = Some(value);
+        self
+    }
+
+    /// Sets how many outgoing messages may be queued for each peer connection.
+    #[cfg(feature = "peer-send-limits")]
+    pub fn with_peer_send_queue_capacity(mut self, value: usize) -> Self {
+        self.peer_send_queue_capacity = Some(value);
+        self
+    }
+
+    /// Sets the send weights of the connections to the given peers, by node ID.
+    #[cfg(feature = "peer-send-limits")]
+    pub fn with_peer_send_weights(mut self, value: Vec<(String, usize)>) -> Self {
+        self.peer_send_weights = value;
+        self
+    }
+
+    /// Sets how many batches are kept in the batch history of each scabbard service.
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn with_scabbard_batch_history_size(mut self, value: usize) -> Self {
+        self.scabbard_batch_history_size = Some(value);
+        self
+    }
+
+    /// Sets how long batches are kept in the batch history of each scabbard service.
+    #[cfg(feature = "scabbard-batch-history")]
+    pub fn with_scabbard_batch_history_max_age(mut self, value: Duration) -> Self {
+        self.scabbard_batch_history_max_age = Some(value);
+        self
+    }
+
+    /// Sets whether the databases of each circuit's scabbard services are stored in a directory
+    /// per circuit.
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn with_scabbard_circuit_db_dirs(mut self, value: bool) -> Self {
+        self.scabbard_circuit_db_dirs = value;
+        self
+    }
+
+    /// Sets the directories where the databases of scabbard services are stored, as triples of
+    /// circuit ID, an optional service ID, and the directory.
+    #[cfg(feature = "scabbard-db-placement")]
+    pub fn with_scabbard_db_dirs(mut self, value: Vec<(String, Option<String>, String)>) -> Self {
+        self.scabbard_db_dirs = value;
+        self
+    }
+
+    /// Sets the number of bytes by which a nearly full scabbard database grows when it is opened.
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn with_scabbard_db_growth_increment(mut self, value: usize) -> Self {
+        self.scabbard_db_growth_increment = Some(value);
+        self
+    }
+
+    /// Sets the largest size, in bytes, that a scabbard database may grow to.
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn with_scabbard_db_max_size(mut self, value: usize) -> Self {
+        self.scabbard_db_max_size = Some(value);
+        self
+    }
+
+    /// Sets the percentages of the maximum size at which scabbard warns about a database's usage.
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    pub fn with_scabbard_db_warning_thresholds(mut self, value: Vec<u8>) -> Self {
+        self.scabbard_db_warning_thresholds = Some(value);
+        self
+    }
+
     #[cfg(feature = "rest-api-cors")]
     pub fn with_whitelist(mut self, value: Option<Vec<String>>) -> Self {
         self.whitelist = value;
         self
     }
 
+    /// Sets the CORS policies of REST API route groups; routes outside of the groups use the
+    /// whitelist.
+    #[cfg(feature = "rest-api-cors-policies")]
+    pub fn with_cors_policies(mut self, value: Vec<CorsPolicyConfig>) -> Self {
+        self.cors_policies = value;
+        self
+    }
+
+    /// Sets the roles that limit the circuits their identities may access through the REST API.
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    pub fn with_circuit_scope_roles(mut self, value: Vec<CircuitScopeRoleConfig>) -> Self {
+        self.circuit_scope_roles = value;
+        self
+    }
+
+    /// Sets the identities of the node operators, which may use every circuit and manage tenants.
+    #[cfg(feature = "tenancy")]
+    pub fn with_tenant_operators(mut self, value: Vec<String>) -> Self {
+        self.tenant_operators = value;
+        self
+    }
+
+    /// Sets the credentials presented when fetching remote registries.
+    #[cfg(feature = "registry-remote-auth")]
+    pub fn with_registry_auth(mut self, value: Vec<RegistryAuthConfig>) -> Self {
+        self.registry_auth = value;
+        self
+    }
+
     #[cfg(feature = "auth")]
     pub fn with_oauth_provider(mut self, value: Option<String>) -> Self {
         self.oauth_provider = value;
@@ -1104,6 +3063,13 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    /// Sets whether the OAuth app is registered as a public client, which has no client secret.
+    #[cfg(feature = "auth")]
+    pub fn with_oauth_public_client(mut self, value: bool) -> Self {
+        self.oauth_public_client = value;
+        self
+    }
+
     #[cfg(feature = "auth")]
     pub fn with_oauth_openid_url(mut self, value: Option<String>) -> Self {
         self.oauth_openid_url = value;
@@ -1166,6 +3132,22 @@ impl SplinterDaemonBuilder {
             (None, None) => None,
         };
 
+        // A certificate managed by ACME replaces any configured REST API certificate and key
+        #[cfg(feature = "rest-api-acme")]
+        let rest_api_ssl_settings = match &self.acme_certificate_manager {
+            Some(acme_certificate_manager) => Some((
+                acme_certificate_manager
+                    .cert_path()
+                    .to_string_lossy()
+                    .into_owned(),
+                acme_certificate_manager
+                    .key_path()
+                    .to_string_lossy()
+                    .into_owned(),
+            )),
+            None => rest_api_ssl_settings,
+        };
+
         #[cfg(feature = "database")]
         let db_url = self.db_url;
 
@@ -1192,6 +3174,12 @@ impl SplinterDaemonBuilder {
             CreateError::MissingRequiredField("Missing field: strict_ref_counts".to_string())
         })?;
 
+        let signing_algorithms = if self.signing_algorithms.is_empty() {
+            vec![SigningAlgorithm::default()]
+        } else {
+            self.signing_algorithms
+        };
+
         Ok(SplinterDaemon {
             state_dir,
             #[cfg(feature = "service-endpoint")]
@@ -1199,12 +3187,47 @@ impl SplinterDaemonBuilder {
             network_endpoints,
             advertised_endpoints,
             initial_peers,
+            peer_transport_preference: self.peer_transport_preference,
             mesh,
             node_id,
             display_name,
             rest_api_endpoint,
             #[cfg(feature = "https-bind")]
             rest_api_ssl_settings,
+            #[cfg(feature = "tls-policy")]
+            rest_api_tls_policy: self.rest_api_tls_policy.unwrap_or_default(),
+            #[cfg(feature = "tls-policy")]
+            rest_api_client_ca_file: self.rest_api_client_ca_file,
+            #[cfg(feature = "rest-api-acme")]
+            acme_certificate_manager: self.acme_certificate_manager,
+            #[cfg(feature = "spiffe")]
+            spiffe_trust_domain: self.spiffe_trust_domain,
+            #[cfg(feature = "spiffe")]
+            spiffe_require_id: self.spiffe_require_id,
+            #[cfg(feature = "peer-identity-pinning")]
+            peer_identity_pinning: self.peer_identity_pinning,
+            #[cfg(feature = "admin-key-rotation")]
+            admin_key_rotation_overlap: self
+                .admin_key_rotation_overlap
+                .unwrap_or(DEFAULT_KEY_ROTATION_OVERLAP),
+            #[cfg(feature = "circuit-id-policy")]
+            circuit_id_policy: self.circuit_id_policy.unwrap_or_default(),
+            #[cfg(feature = "admin-auto-vote")]
+            auto_voter: self.auto_voter,
+            #[cfg(feature = "admin-routing-snapshot")]
+            persist_routing_table: self.persist_routing_table,
+            #[cfg(feature = "admin-signature-cache")]
+            admin_signature_cache_size: self
+                .admin_signature_cache_size
+                .unwrap_or(DEFAULT_SIGNATURE_CACHE_SIZE),
+            #[cfg(feature = "read-only-mode")]
+            read_only: self.read_only,
+            #[cfg(feature = "resource-watchdog")]
+            resource_min_free_space: self
+                .resource_min_free_space
+                .unwrap_or(DEFAULT_MIN_FREE_SPACE_MB),
+            #[cfg(feature = "external-services")]
+            external_service_types: self.external_service_types,
             #[cfg(feature = "database")]
             db_url,
             #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
@@ -1214,8 +3237,34 @@ impl SplinterDaemonBuilder {
             registry_forced_refresh,
             storage_type,
             admin_timeout: self.admin_timeout,
+            orchestrator_incoming_capacity: self
+                .orchestrator_incoming_capacity
+                .unwrap_or(DEFAULT_ORCHESTRATOR_INCOMING_CAPACITY),
+            orchestrator_outgoing_capacity: self
+                .orchestrator_outgoing_capacity
+                .unwrap_or(DEFAULT_ORCHESTRATOR_OUTGOING_CAPACITY),
+            orchestrator_channel_capacity: self
+                .orchestrator_channel_capacity
+                .unwrap_or(DEFAULT_ORCHESTRATOR_CHANNEL_CAPACITY),
+            admin_processor_incoming_capacity: self
+                .admin_processor_incoming_capacity
+                .unwrap_or(DEFAULT_ADMIN_SERVICE_PROCESSOR_INCOMING_CAPACITY),
+            admin_processor_outgoing_capacity: self
+                .admin_processor_outgoing_capacity
+                .unwrap_or(DEFAULT_ADMIN_SERVICE_PROCESSOR_OUTGOING_CAPACITY),
+            admin_processor_channel_capacity: self
+                .admin_processor_channel_capacity
+                .unwrap_or(DEFAULT_ADMIN_SERVICE_PROCESSOR_CHANNEL_CAPACITY),
             #[cfg(feature = "rest-api-cors")]
             whitelist: self.whitelist,
+            #[cfg(feature = "rest-api-cors-policies")]
+            cors_policies: self.cors_policies,
+            #[cfg(feature = "rest-api-circuit-scopes")]
+            circuit_scope_roles: self.circuit_scope_roles,
+            #[cfg(feature = "tenancy")]
+            tenant_operators: self.tenant_operators,
+            #[cfg(feature = "registry-remote-auth")]
+            registry_auth: self.registry_auth,
             #[cfg(feature = "auth")]
             oauth_provider: self.oauth_provider,
             #[cfg(feature = "auth")]
@@ -1223,19 +3272,118 @@ impl SplinterDaemonBuilder {
             #[cfg(feature = "auth")]
             oauth_client_secret: self.oauth_client_secret,
             #[cfg(feature = "auth")]
+            oauth_public_client: self.oauth_public_client,
+            #[cfg(feature = "auth")]
             oauth_redirect_url: self.oauth_redirect_url,
             #[cfg(feature = "auth")]
             oauth_openid_url: self.oauth_openid_url,
+            signing_algorithms,
             heartbeat,
             strict_ref_counts,
+            #[cfg(feature = "config-reload")]
+            peer_blocklist: self.peer_blocklist.unwrap_or_default(),
+            #[cfg(feature = "config-reload")]
+            config_reloader: self.config_reloader,
+            #[cfg(feature = "metrics-push")]
+            metrics_reporter: self.metrics_reporter,
+            #[cfg(feature = "alerting")]
+            alert_rules: self.alert_rules,
+            #[cfg(feature = "alerting")]
+            alert_sinks: self.alert_sinks,
+            #[cfg(feature = "event-publisher")]
+            event_publisher: self.event_publisher,
+            #[cfg(feature = "mqtt-bridge")]
+            mqtt_bridge: self.mqtt_bridge,
+            #[cfg(feature = "log-retrieval")]
+            log_buffer: self.log_buffer,
+            #[cfg(feature = "service-factories")]
+            service_factories: self.service_factories,
+            #[cfg(feature = "node-challenge")]
+            node_key: self.node_key,
+            #[cfg(feature = "biome-user-admin")]
+            biome_admin_keys: self.biome_admin_keys.unwrap_or_default(),
+            #[cfg(feature = "network-clock-skew")]
+            clock_skew_threshold: self
+                .clock_skew_threshold
+                .unwrap_or(DEFAULT_CLOCK_SKEW_THRESHOLD),
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_limits: match self.peer_send_queue_capacity {
+                Some(capacity) => SendLimits::new().with_outgoing_capacity(capacity),
+                None => SendLimits::new(),
+            },
+            #[cfg(feature = "peer-send-limits")]
+            peer_send_weights: self.peer_send_weights,
+            #[cfg(feature = "scabbard-batch-history")]
+            scabbard_batch_history_retention: match (
+                self.scabbard_batch_history_size,
+                self.scabbard_batch_history_max_age,
+            ) {
+                (None, None) => None,
+                (size, max_age) => {
+                    let retention = size.map(BatchHistoryRetention::new).unwrap_or_default();
+                    Some(match max_age {
+                        Some(max_age) => retention.with_max_age(max_age),
+                        None => retention,
+                    })
+                }
+            },
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_circuit_db_dirs: self.scabbard_circuit_db_dirs,
+            #[cfg(feature = "scabbard-db-placement")]
+            scabbard_db_dirs: self.scabbard_db_dirs,
+            #[cfg(feature = "scabbard-db-auto-grow")]
+            scabbard_db_growth_policy: match (
+                self.scabbard_db_growth_increment,
+                self.scabbard_db_max_size,
+                self.scabbard_db_warning_thresholds,
+            ) {
+                (None, None, None) => None,
+                (increment, max_size, warning_thresholds) => {
+                    let policy = DbGrowthPolicy::new(increment.unwrap_or(0), max_size.unwrap_or(0));
+                    Some(match warning_thresholds {
+                        Some(thresholds) => policy.with_warning_thresholds(thresholds),
+                        None => policy,
+                    })
+                }
+            },
         })
     }
 }
 
+/// Returns the optional features that change how this node interacts with its peers. They are
+/// advertised to each peer during authorization, so operators can check that the nodes of a
+/// network are compatible before upgrading them.
+fn advertised_features() -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut features: Vec<&str> = vec![];
+    #[cfg(feature = "admin-vote-cosigning")]
+    features.push("admin-vote-cosigning");
+    #[cfg(feature = "admin-vote-delegation")]
+    features.push("admin-vote-delegation");
+    #[cfg(feature = "circuit-payload-schema")]
+    features.push("circuit-payload-schema");
+    #[cfg(feature = "circuit-ping")]
+    features.push("circuit-ping");
+    #[cfg(feature = "circuit-qos")]
+    features.push("circuit-qos");
+    #[cfg(feature = "circuit-relay")]
+    features.push("circuit-relay");
+    #[cfg(feature = "circuit-replay-protection")]
+    features.push("circuit-replay-protection");
+    #[cfg(feature = "network-clock-skew")]
+    features.push("network-clock-skew");
+    #[cfg(feature = "node-challenge")]
+    features.push("node-challenge");
+    features.into_iter().map(String::from).collect()
+}
+
 fn set_up_network_dispatcher(
     network_sender: NetworkMessageSender,
     node_id: &str,
     circuit_sender: DispatchMessageSender<CircuitMessageType>,
+    #[cfg(feature = "circuit-qos")] qos_enforcer: QosEnforcer,
+    #[cfg(feature = "resource-watchdog")] intake_pause: IntakePause,
+    #[cfg(feature = "network-clock-skew")] clock_skew_monitor: ClockSkewMonitor,
 ) -> Dispatcher<NetworkMessageType> {
     let mut dispatcher = Dispatcher::<NetworkMessageType>::new(Box::new(network_sender));
 
@@ -1243,10 +3391,17 @@ fn set_up_network_dispatcher(
     dispatcher.set_handler(Box::new(network_echo_handler));
 
     let network_heartbeat_handler = NetworkHeartbeatHandler::new();
+    #[cfg(feature = "network-clock-skew")]
+    let network_heartbeat_handler =
+        network_heartbeat_handler.with_clock_skew_monitor(clock_skew_monitor);
     // do not add auth guard
     dispatcher.set_handler(Box::new(network_heartbeat_handler));
 
     let circuit_message_handler = CircuitMessageHandler::new(circuit_sender);
+    #[cfg(feature = "circuit-qos")]
+    let circuit_message_handler = circuit_message_handler.with_qos_enforcer(qos_enforcer);
+    #[cfg(feature = "resource-watchdog")]
+    let circuit_message_handler = circuit_message_handler.with_intake_pause(intake_pause);
     dispatcher.set_handler(Box::new(circuit_message_handler));
 
     dispatcher
@@ -1257,6 +3412,12 @@ fn set_up_circuit_dispatcher(
     node_id: &str,
     routing_reader: Box<dyn RoutingTableReader>,
     routing_writer: Box<dyn RoutingTableWriter>,
+    #[cfg(any(feature = "circuit-ping", feature = "health"))] pending_pings: PendingPings,
+    #[cfg(feature = "circuit-dead-letter")] dead_letters: DeadLetterQueue,
+    #[cfg(feature = "circuit-replay-protection")] replay_window: ReplayWindow,
+    #[cfg(feature = "circuit-relay")] peer_connector: PeerManagerConnector,
+    #[cfg(feature = "circuit-relay")] admin_relay_routes: AdminRelayRoutes,
+    #[cfg(feature = "circuit-qos")] qos_enforcer: QosEnforcer,
 ) -> Dispatcher<CircuitMessageType> {
     let mut dispatcher = Dispatcher::<CircuitMessageType>::new(Box::new(network_sender));
 
@@ -1265,6 +3426,15 @@ fn set_up_circuit_dispatcher(
         routing_reader.clone(),
         routing_writer.clone(),
     );
+    // Services with a key in their circuit definition must sign a challenge to connect
+    #[cfg(feature = "service-endpoint-auth")]
+    let service_connect_request_handler = {
+        let challenges = ServiceChallenges::new();
+        dispatcher.set_handler(Box::new(ServiceAuthChallengeRequestHandler::new(
+            challenges.clone(),
+        )));
+        service_connect_request_handler.with_service_auth(challenges)
+    };
     dispatcher.set_handler(Box::new(service_connect_request_handler));
 
     let service_disconnect_request_handler =
@@ -1273,15 +3443,44 @@ fn set_up_circuit_dispatcher(
 
     let direct_message_handler =
         CircuitDirectMessageHandler::new(node_id.to_string(), routing_reader.clone());
+    #[cfg(feature = "circuit-dead-letter")]
+    let direct_message_handler = direct_message_handler.with_dead_letter_queue(dead_letters);
+    #[cfg(feature = "circuit-replay-protection")]
+    let direct_message_handler = direct_message_handler.with_replay_window(replay_window);
+    #[cfg(feature = "circuit-relay")]
+    let direct_message_handler =
+        direct_message_handler.with_relay_routing(Box::new(peer_connector.clone()));
+    #[cfg(feature = "circuit-payload-schema")]
+    let direct_message_handler =
+        direct_message_handler.with_payload_validator(PayloadValidator::new());
+    #[cfg(feature = "circuit-qos")]
+    let direct_message_handler = direct_message_handler.with_qos_enforcer(qos_enforcer);
     dispatcher.set_handler(Box::new(direct_message_handler));
 
     let circuit_error_handler =
         CircuitErrorHandler::new(node_id.to_string(), routing_reader.clone());
     dispatcher.set_handler(Box::new(circuit_error_handler));
 
+    #[cfg(any(feature = "circuit-ping", feature = "health"))]
+    {
+        let ping_request_handler = CircuitPingRequestHandler::new(
+            node_id.to_string(),
+            routing_reader.clone(),
+            pending_pings.clone(),
+        );
+        dispatcher.set_handler(Box::new(ping_request_handler));
+
+        let ping_response_handler =
+            CircuitPingResponseHandler::new(node_id.to_string(), pending_pings);
+        dispatcher.set_handler(Box::new(ping_response_handler));
+    }
+
     // Circuit Admin handlers
     let admin_direct_message_handler =
         AdminDirectMessageHandler::new(node_id.to_string(), routing_reader);
+    #[cfg(feature = "circuit-relay")]
+    let admin_direct_message_handler = admin_direct_message_handler
+        .with_relay_routing(admin_relay_routes, Box::new(peer_connector));
     dispatcher.set_handler(Box::new(admin_direct_message_handler));
 
     dispatcher
@@ -1315,16 +3514,38 @@ fn create_registry(
     registries: &[String],
     auto_refresh_interval: u64,
     forced_refresh_interval: u64,
+    #[cfg(feature = "registry-remote-auth")] registry_auth: &[RegistryAuthConfig],
     #[cfg(feature = "registry-database")] store_factory: &dyn splinter::store::StoreFactory,
-) -> Result<(Box<dyn RwRegistry>, RegistryShutdownHandle), StartError> {
-    let mut registry_shutdown_handle = RegistryShutdownHandle::new();
-
+) -> Result<(UnifiedRegistry, RegistryShutdownHandle), StartError> {
     #[cfg(not(feature = "registry-database"))]
     let local_registry = create_local_registry(state_dir)?;
 
     #[cfg(feature = "registry-database")]
     let local_registry = store_factory.get_registry_store();
 
+    let (read_only_registries, registry_shutdown_handle) = create_read_only_registries(
+        state_dir,
+        registries,
+        auto_refresh_interval,
+        forced_refresh_interval,
+        #[cfg(feature = "registry-remote-auth")]
+        registry_auth,
+    );
+
+    let unified_registry = UnifiedRegistry::new(local_registry, read_only_registries);
+
+    Ok((unified_registry, registry_shutdown_handle))
+}
+
+fn create_read_only_registries(
+    state_dir: &str,
+    registries: &[String],
+    auto_refresh_interval: u64,
+    forced_refresh_interval: u64,
+    #[cfg(feature = "registry-remote-auth")] registry_auth: &[RegistryAuthConfig],
+) -> (Vec<Box<dyn RegistryReader>>, RegistryShutdownHandle) {
+    let mut registry_shutdown_handle = RegistryShutdownHandle::new();
+
     let read_only_registries = registries
         .iter()
         .filter_map(|registry| {
@@ -1362,12 +3583,46 @@ fn create_registry(
                 } else {
                     None
                 };
-                match RemoteYamlRegistry::new(
+                #[cfg(feature = "registry-remote-auth")]
+                let remote_registry = match find_registry_auth(registry_auth, registry) {
+                    Some(auth) => {
+                        debug!(
+                            "Using credentials for {} to fetch remote registry {}",
+                            auth.url, registry
+                        );
+                        auth.load()
+                            .map_err(|err| {
+                                RegistryError::general_error(&format!(
+                                    "Unable to load credentials: {}",
+                                    err
+                                ))
+                            })
+                            .and_then(|auth| {
+                                RemoteYamlRegistry::new_with_auth(
+                                    registry,
+                                    state_dir,
+                                    auto_refresh_interval,
+                                    forced_refresh_interval,
+                                    auth,
+                                )
+                            })
+                    }
+                    None => RemoteYamlRegistry::new(
+                        registry,
+                        state_dir,
+                        auto_refresh_interval,
+                        forced_refresh_interval,
+                    ),
+                };
+                #[cfg(not(feature = "registry-remote-auth"))]
+                let remote_registry = RemoteYamlRegistry::new(
                     registry,
                     state_dir,
                     auto_refresh_interval,
                     forced_refresh_interval,
-                ) {
+                );
+
+                match remote_registry {
                     Ok(registry) => {
                         registry_shutdown_handle
                             .add_remote_yaml_shutdown_handle(registry.shutdown_handle());
@@ -1391,9 +3646,78 @@ fn create_registry(
         })
         .collect();
 
-    let unified_registry = Box::new(UnifiedRegistry::new(local_registry, read_only_registries));
+    (read_only_registries, registry_shutdown_handle)
+}
 
-    Ok((unified_registry, registry_shutdown_handle))
+/// Starts a thread that reloads the daemon's configuration each time SIGHUP is received.
+///
+/// The returned `Signals` must be closed to stop the thread.
+#[cfg(feature = "config-reload")]
+fn start_config_reload(
+    config_reloader: ConfigReloader,
+    state_dir: String,
+    unified_registry: UnifiedRegistry,
+    registry_shutdown: Arc<Mutex<RegistryShutdownHandle>>,
+    peer_connector: PeerManagerConnector,
+    #[cfg(feature = "rest-api-cors")] cors: Cors,
+) -> Result<Arc<Signals>, StartError> {
+    let signals = Arc::new(Signals::new(&[SIGHUP]).map_err(|err| {
+        StartError::ConfigReloadError(format!("Unable to register SIGHUP handler: {}", err))
+    })?);
+
+    let thread_signals = Arc::clone(&signals);
+    thread::Builder::new()
+        .name("ConfigReload".into())
+        .spawn(move || {
+            for _ in thread_signals.forever() {
+                info!("Received SIGHUP, reloading configuration");
+                let config = match config_reloader() {
+                    Ok(config) => config,
+                    Err(err) => {
+                        error!("Unable to reload configuration: {}", err);
+                        continue;
+                    }
+                };
+
+                let (read_only_registries, new_registry_shutdown) = create_read_only_registries(
+                    &state_dir,
+                    &config.registries,
+                    config.registry_auto_refresh,
+                    config.registry_forced_refresh,
+                    #[cfg(feature = "registry-remote-auth")]
+                    &config.registry_auth,
+                );
+                match unified_registry.set_read_only_registries(read_only_registries) {
+                    Ok(()) => match registry_shutdown.lock() {
+                        Ok(mut registry_shutdown) => {
+                            std::mem::replace(&mut *registry_shutdown, new_registry_shutdown)
+                                .shutdown()
+                        }
+                        Err(_) => error!("Unable to replace registries: lock poisoned"),
+                    },
+                    Err(err) => {
+                        error!("Unable to replace read-only registries: {}", err);
+                        new_registry_shutdown.shutdown();
+                    }
+                }
+
+                #[cfg(feature = "rest-api-cors")]
+                cors.set_whitelist(config.whitelist.unwrap_or_else(|| vec!["*".into()]));
+                #[cfg(feature = "rest-api-cors-policies")]
+                cors.set_route_policies(route_policies(&config.cors_policies));
+
+                if let Err(err) = peer_connector.set_blocklist(config.peer_blocklist) {
+                    error!("Unable to update peer blocklist: {}", err);
+                }
+
+                info!("Configuration reloaded");
+            }
+        })
+        .map_err(|err| {
+            StartError::ConfigReloadError(format!("Unable to start reload thread: {}", err))
+        })?;
+
+    Ok(signals)
 }
 
 fn parse_registry_arg(registry: &str) -> Result<(&str, &str), &str> {
@@ -1454,6 +3778,25 @@ pub enum StartError {
     #[cfg(feature = "health")]
     HealthServiceError(String),
     OrchestratorError(String),
+    #[cfg(feature = "config-reload")]
+    ConfigReloadError(String),
+    #[cfg(feature = "metrics-push")]
+    MetricsError(String),
+    #[cfg(feature = "alerting")]
+    AlertingError(String),
+    #[cfg(feature = "event-publisher")]
+    EventPublisherError(String),
+    #[cfg(feature = "mqtt-bridge")]
+    MqttBridgeError(String),
+    #[cfg(feature = "node-stats")]
+    NodeStatsError(String),
+    #[cfg(feature = "scheduler")]
+    SchedulerError(String),
+    #[cfg(feature = "registry-watcher")]
+    RegistryWatcherError(String),
+    #[cfg(feature = "resource-watchdog")]
+    ResourceWatchdogError(String),
+    ShutdownRegistrationError(String),
 }
 
 impl Error for StartError {}
@@ -1478,10 +3821,55 @@ impl fmt::Display for StartError {
             StartError::OrchestratorError(msg) => {
                 write!(f, "the orchestrator encountered an error: {}", msg)
             }
+            #[cfg(feature = "config-reload")]
+            StartError::ConfigReloadError(msg) => {
+                write!(f, "unable to set up configuration reload: {}", msg)
+            }
+            #[cfg(feature = "metrics-push")]
+            StartError::MetricsError(msg) => {
+                write!(f, "unable to start metrics reporter: {}", msg)
+            }
+            #[cfg(feature = "alerting")]
+            StartError::AlertingError(msg) => {
+                write!(f, "unable to start alert engine: {}", msg)
+            }
+            #[cfg(feature = "event-publisher")]
+            StartError::EventPublisherError(msg) => {
+                write!(f, "unable to start event publisher: {}", msg)
+            }
+            #[cfg(feature = "mqtt-bridge")]
+            StartError::MqttBridgeError(msg) => {
+                write!(f, "unable to start MQTT bridge: {}", msg)
+            }
+            #[cfg(feature = "node-stats")]
+            StartError::NodeStatsError(msg) => {
+                write!(f, "unable to start node stats collection: {}", msg)
+            }
+            #[cfg(feature = "scheduler")]
+            StartError::SchedulerError(msg) => {
+                write!(f, "the scheduler encountered an error: {}", msg)
+            }
+            #[cfg(feature = "registry-watcher")]
+            StartError::RegistryWatcherError(msg) => {
+                write!(f, "unable to start registry watcher: {}", msg)
+            }
+            #[cfg(feature = "resource-watchdog")]
+            StartError::ResourceWatchdogError(msg) => {
+                write!(f, "unable to start resource watchdog: {}", msg)
+            }
+            StartError::ShutdownRegistrationError(msg) => {
+                write!(f, "unable to register subsystem for shutdown: {}", msg)
+            }
         }
     }
 }
 
+impl From<ShutdownError> for StartError {
+    fn from(shutdown_error: ShutdownError) -> Self {
+        StartError::ShutdownRegistrationError(shutdown_error.to_string())
+    }
+}
+
 impl From<RestApiServerError> for StartError {
     fn from(rest_api_error: RestApiServerError) -> Self {
         StartError::RestApiError(rest_api_error.to_string())
@@ -17,9 +17,15 @@ use std::fmt;
 use std::io;
 
 use splinter::transport::socket::TlsInitError;
+use splinter_daemon::daemon::StartError;
+#[cfg(feature = "secrets")]
+use splinter_daemon::secrets::SecretError;
 
 use crate::config::ConfigError;
-use crate::daemon::StartError;
+#[cfg(feature = "provision")]
+use crate::provision::ProvisionError;
+#[cfg(feature = "state-dir-lock")]
+use crate::state_dir::StateDirError;
 
 #[derive(Debug)]
 pub enum UserError {
@@ -117,6 +123,27 @@ impl From<StartError> for UserError {
     }
 }
 
+#[cfg(feature = "state-dir-lock")]
+impl From<StateDirError> for UserError {
+    fn from(error: StateDirError) -> Self {
+        UserError::daemon_err_with_source("unable to prepare the state directory", Box::new(error))
+    }
+}
+
+#[cfg(feature = "provision")]
+impl From<ProvisionError> for UserError {
+    fn from(error: ProvisionError) -> Self {
+        UserError::daemon_err_with_source("unable to provision the node", Box::new(error))
+    }
+}
+
+#[cfg(feature = "secrets")]
+impl From<SecretError> for UserError {
+    fn from(error: SecretError) -> Self {
+        UserError::daemon_err_with_source("unable to resolve a secret", Box::new(error))
+    }
+}
+
 impl From<GetTransportError> for UserError {
     fn from(error: GetTransportError) -> Self {
         UserError::TransportError(error)
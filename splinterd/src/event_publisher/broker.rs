@@ -0,0 +1,89 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The message brokers that events are published to.
+
+use std::time::Duration;
+
+use kafka::producer::{Producer, Record, RequiredAcks};
+
+use super::{EventPublisherError, Publication};
+
+const KAFKA_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends publications to a message broker
+pub trait Broker: Send {
+    fn publish(&mut self, publication: &Publication) -> Result<(), EventPublisherError>;
+}
+
+/// Publishes to the topics of a Kafka cluster
+pub struct KafkaBroker {
+    producer: Producer,
+}
+
+impl KafkaBroker {
+    /// Connects to the Kafka cluster through the given `host:port` bootstrap servers
+    pub fn connect(servers: &[String]) -> Result<Self, EventPublisherError> {
+        let producer = Producer::from_hosts(servers.to_vec())
+            .with_ack_timeout(KAFKA_ACK_TIMEOUT)
+            .with_required_acks(RequiredAcks::One)
+            .create()
+            .map_err(|err| {
+                EventPublisherError(format!("unable to connect to Kafka cluster: {}", err))
+            })?;
+
+        Ok(KafkaBroker { producer })
+    }
+}
+
+impl Broker for KafkaBroker {
+    fn publish(&mut self, publication: &Publication) -> Result<(), EventPublisherError> {
+        self.producer
+            .send(&Record::from_key_value(
+                &publication.topic,
+                publication.key.as_bytes(),
+                publication.payload.as_slice(),
+            ))
+            .map_err(|err| EventPublisherError(err.to_string()))
+    }
+}
+
+/// Publishes to the subjects of a NATS server.
+///
+/// The subject of each publication is its topic followed by its key.
+pub struct NatsBroker {
+    connection: nats::Connection,
+}
+
+impl NatsBroker {
+    /// Connects to the first reachable server of the given NATS server URLs
+    pub fn connect(servers: &[String]) -> Result<Self, EventPublisherError> {
+        let connection = nats::connect(&servers.join(",")).map_err(|err| {
+            EventPublisherError(format!("unable to connect to NATS server: {}", err))
+        })?;
+
+        Ok(NatsBroker { connection })
+    }
+}
+
+impl Broker for NatsBroker {
+    fn publish(&mut self, publication: &Publication) -> Result<(), EventPublisherError> {
+        self.connection
+            .publish(
+                &format!("{}.{}", publication.topic, publication.key),
+                &publication.payload,
+            )
+            .map_err(|err| EventPublisherError(err.to_string()))
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Publishing of admin service and scabbard events to a message broker.
+//!
+//! The [`EventPublisher`] forwards admin service events to the admin topic and scabbard state
+//! changes to the scabbard topic of a Kafka cluster or NATS server, so integrations can consume
+//! the events of every node from the broker rather than opening a websocket to each node.
+//!
+//! Each event is published with a key made of the circuit ID (and the service ID for scabbard
+//! events), joined by a `.`. Kafka records carry the key as the record key; on NATS the key is
+//! appended to the topic to form the subject, so consumers can subscribe with wildcards.
+//!
+//! [`EventPublisher`]: struct.EventPublisher.html
+
+mod broker;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+use protobuf::Message;
+use scabbard::service::{
+    StateChangeEvent, StateSubscriber, StateSubscriberError, StateSubscriberFactory,
+};
+use splinter::admin::messages::AdminServiceEvent;
+use splinter::admin::service::{AdminServiceEventSubscriber, AdminSubscriberError};
+
+pub use broker::{Broker, KafkaBroker, NatsBroker};
+
+/// The topic admin service events are published to if none is configured
+pub const DEFAULT_ADMIN_TOPIC: &str = "splinter.admin";
+/// The topic scabbard state changes are published to if none is configured
+pub const DEFAULT_SCABBARD_TOPIC: &str = "splinter.scabbard";
+
+/// The kind of message broker events are published to
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokerType {
+    Kafka,
+    Nats,
+}
+
+/// How events are serialized before they are published
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventFormat {
+    Json,
+    Protobuf,
+}
+
+impl Default for EventFormat {
+    fn default() -> Self {
+        EventFormat::Json
+    }
+}
+
+/// The publisher, as defined in the `[event_publisher]` table of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventPublisherConfig {
+    pub broker: BrokerType,
+    /// The brokers to connect to; `host:port` for Kafka, or NATS server URLs
+    pub servers: Vec<String>,
+    #[serde(default)]
+    pub format: EventFormat,
+    #[serde(default = "default_admin_topic")]
+    pub admin_topic: String,
+    #[serde(default = "default_scabbard_topic")]
+    pub scabbard_topic: String,
+    /// The circuit management types whose admin service events are published
+    #[serde(default)]
+    pub circuit_management_types: Vec<String>,
+}
+
+fn default_admin_topic() -> String {
+    DEFAULT_ADMIN_TOPIC.into()
+}
+
+fn default_scabbard_topic() -> String {
+    DEFAULT_SCABBARD_TOPIC.into()
+}
+
+/// An error that occurred while connecting to or publishing to a message broker
+#[derive(Debug)]
+pub struct EventPublisherError(pub String);
+
+impl Error for EventPublisherError {}
+
+impl fmt::Display for EventPublisherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An event that is ready to be sent to the broker
+#[derive(Clone, Debug, PartialEq)]
+pub struct Publication {
+    pub topic: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+}
+
+enum PublisherMessage {
+    Publish(Publication),
+    Shutdown,
+}
+
+/// Forwards events to a message broker from a background thread, so the admin service and
+/// scabbard services are not blocked on the broker.
+pub struct EventPublisher {
+    join_handle: thread::JoinHandle<()>,
+    sender: Sender<PublisherMessage>,
+    config: EventPublisherConfig,
+}
+
+impl EventPublisher {
+    /// Connects to the configured broker and starts the publisher thread.
+    pub fn start(config: EventPublisherConfig) -> Result<Self, EventPublisherError> {
+        let broker: Box<dyn Broker> = match config.broker {
+            BrokerType::Kafka => Box::new(KafkaBroker::connect(&config.servers)?),
+            BrokerType::Nats => Box::new(NatsBroker::connect(&config.servers)?),
+        };
+
+        Self::start_with_broker(config, broker)
+    }
+
+    /// Starts the publisher thread, publishing to the given broker.
+    pub fn start_with_broker(
+        config: EventPublisherConfig,
+        mut broker: Box<dyn Broker>,
+    ) -> Result<Self, EventPublisherError> {
+        let (sender, receiver) = channel();
+
+        let join_handle = thread::Builder::new()
+            .name("EventPublisher".into())
+            .spawn(move || loop {
+                match receiver.recv() {
+                    Ok(PublisherMessage::Publish(publication)) => {
+                        if let Err(err) = broker.publish(&publication) {
+                            error!("Unable to publish event to {}: {}", publication.topic, err);
+                        }
+                    }
+                    Ok(PublisherMessage::Shutdown) | Err(_) => {
+                        debug!("Stopping event publisher");
+                        return;
+                    }
+                }
+            })
+            .map_err(|err| {
+                EventPublisherError(format!("unable to start event publisher thread: {}", err))
+            })?;
+
+        Ok(EventPublisher {
+            join_handle,
+            sender,
+            config,
+        })
+    }
+
+    /// Returns a subscriber that publishes admin service events to the admin topic
+    pub fn admin_subscriber(&self) -> Box<dyn AdminServiceEventSubscriber> {
+        Box::new(AdminEventPublisher {
+            sender: self.sender.clone(),
+            topic: self.config.admin_topic.clone(),
+            format: self.config.format,
+        })
+    }
+
+    /// Returns a factory for subscribers that publish scabbard state changes to the scabbard
+    /// topic
+    pub fn state_subscriber_factory(&self) -> Box<dyn StateSubscriberFactory> {
+        Box::new(ScabbardEventPublisherFactory {
+            sender: self.sender.clone(),
+            topic: self.config.scabbard_topic.clone(),
+            format: self.config.format,
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the publisher
+    pub fn shutdown_signaler(&self) -> EventPublisherShutdownSignaler {
+        EventPublisherShutdownSignaler {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Waits for the publisher thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Event publisher thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals an `EventPublisher` to stop
+#[derive(Clone)]
+pub struct EventPublisherShutdownSignaler {
+    sender: Sender<PublisherMessage>,
+}
+
+impl EventPublisherShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.send(PublisherMessage::Shutdown).is_err() {
+            debug!("Event publisher has already stopped");
+        }
+    }
+}
+
+struct AdminEventPublisher {
+    sender: Sender<PublisherMessage>,
+    topic: String,
+    format: EventFormat,
+}
+
+impl AdminEventPublisher {
+    fn publish(&self, event: &AdminServiceEvent) -> Result<(), AdminSubscriberError> {
+        let payload = encode_admin_event(event, self.format)
+            .map_err(|err| AdminSubscriberError::UnableToHandleEvent(err.to_string()))?;
+
+        self.sender
+            .send(PublisherMessage::Publish(Publication {
+                topic: self.topic.clone(),
+                key: event.proposal().circuit_id.clone(),
+                payload,
+            }))
+            .map_err(|_| {
+                debug!("Unsubscribing from admin service events; event publisher has stopped");
+                AdminSubscriberError::Unsubscribe
+            })
+    }
+}
+
+impl AdminServiceEventSubscriber for AdminEventPublisher {
+    fn handle_event(
+        &self,
+        event: &AdminServiceEvent,
+        _event_id: &i64,
+    ) -> Result<(), AdminSubscriberError> {
+        self.publish(event)
+    }
+}
+
+struct ScabbardEventPublisherFactory {
+    sender: Sender<PublisherMessage>,
+    topic: String,
+    format: EventFormat,
+}
+
+impl StateSubscriberFactory for ScabbardEventPublisherFactory {
+    fn create_subscriber(&self, circuit_id: &str, service_id: &str) -> Box<dyn StateSubscriber> {
+        Box::new(ScabbardEventPublisher {
+            sender: self.sender.clone(),
+            topic: self.topic.clone(),
+            key: format!("{}.{}", circuit_id, service_id),
+            format: self.format,
+        })
+    }
+}
+
+struct ScabbardEventPublisher {
+    sender: Sender<PublisherMessage>,
+    topic: String,
+    key: String,
+    format: EventFormat,
+}
+
+impl StateSubscriber for ScabbardEventPublisher {
+    fn handle_event(&self, event: StateChangeEvent) -> Result<(), StateSubscriberError> {
+        let payload = encode_state_change_event(event, self.format)
+            .map_err(|err| StateSubscriberError::UnableToHandleEvent(err.to_string()))?;
+
+        self.sender
+            .send(PublisherMessage::Publish(Publication {
+                topic: self.topic.clone(),
+                key: self.key.clone(),
+                payload,
+            }))
+            .map_err(|_| {
+                debug!(
+                    "Unsubscribing from {} state changes; event publisher has stopped",
+                    self.key
+                );
+                StateSubscriberError::Unsubscribe
+            })
+    }
+}
+
+fn encode_admin_event(
+    event: &AdminServiceEvent,
+    format: EventFormat,
+) -> Result<Vec<u8>, EventPublisherError> {
+    match format {
+        EventFormat::Json => serde_json::to_vec(event)
+            .map_err(|err| EventPublisherError(format!("unable to serialize event: {}", err))),
+        EventFormat::Protobuf => event
+            .clone()
+            .into_proto()
+            .map_err(|err| EventPublisherError(format!("unable to convert event: {}", err)))?
+            .write_to_bytes()
+            .map_err(|err| EventPublisherError(format!("unable to serialize event: {}", err))),
+    }
+}
+
+fn encode_state_change_event(
+    event: StateChangeEvent,
+    format: EventFormat,
+) -> Result<Vec<u8>, EventPublisherError> {
+    match format {
+        EventFormat::Json => serde_json::to_vec(&event)
+            .map_err(|err| EventPublisherError(format!("unable to serialize event: {}", err))),
+        EventFormat::Protobuf => event
+            .into_proto()
+            .write_to_bytes()
+            .map_err(|err| EventPublisherError(format!("unable to serialize event: {}", err))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use scabbard::protos::scabbard as scabbard_protos;
+    use scabbard::service::StateChange;
+
+    #[derive(Clone, Default)]
+    struct RecordingBroker {
+        publications: Arc<Mutex<Vec<Publication>>>,
+    }
+
+    impl Broker for RecordingBroker {
+        fn publish(&mut self, publication: &Publication) -> Result<(), EventPublisherError> {
+            self.publications
+                .lock()
+                .expect("publications lock poisoned")
+                .push(publication.clone());
+            Ok(())
+        }
+    }
+
+    /// Verify that scabbard state changes are published to the scabbard topic, keyed by circuit
+    /// and service, in the configured format; and that subscribers unsubscribe once the publisher
+    /// has stopped.
+    #[test]
+    fn test_publish_state_changes() {
+        let broker = RecordingBroker::default();
+        let config: EventPublisherConfig = toml::from_str(
+            r#"
+            broker = "nats"
+            servers = ["nats://localhost:4222"]
+            format = "protobuf"
+            "#,
+        )
+        .expect("Unable to parse config");
+        assert_eq!(config.scabbard_topic, DEFAULT_SCABBARD_TOPIC);
+
+        let publisher = EventPublisher::start_with_broker(config, Box::new(broker.clone()))
+            .expect("Unable to start publisher");
+        let subscriber = publisher
+            .state_subscriber_factory()
+            .create_subscriber("abcde-01234", "a000");
+
+        subscriber
+            .handle_event(StateChangeEvent {
+                id: "txn".into(),
+                state_changes: vec![StateChange::Set {
+                    key: "key".into(),
+                    value: b"value".to_vec(),
+                }],
+            })
+            .expect("Unable to handle event");
+
+        publisher.shutdown_signaler().shutdown();
+        publisher.await_shutdown();
+
+        let publications = broker.publications.lock().expect("lock poisoned");
+        assert_eq!(publications.len(), 1);
+        assert_eq!(publications[0].topic, DEFAULT_SCABBARD_TOPIC);
+        assert_eq!(publications[0].key, "abcde-01234.a000");
+
+        let event: scabbard_protos::StateChangeEvent =
+            protobuf::parse_from_bytes(&publications[0].payload).expect("Unable to parse event");
+        assert_eq!(event.get_id(), "txn");
+        assert_eq!(event.get_state_changes()[0].get_key(), "key");
+        assert_eq!(event.get_state_changes()[0].get_value(), b"value");
+
+        match subscriber.handle_event(StateChangeEvent {
+            id: "txn2".into(),
+            state_changes: vec![],
+        }) {
+            Err(StateSubscriberError::Unsubscribe) => (),
+            res => panic!("Expected unsubscribe, got {:?}", res),
+        }
+    }
+}
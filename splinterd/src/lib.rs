@@ -0,0 +1,55 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Splinter daemon.
+//!
+//! The `splinterd` binary is built on this library, which can also be used to run Splinter nodes
+//! in-process, such as in integration tests.
+
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate serde_derive;
+
+#[cfg(feature = "alerting")]
+pub mod alerts;
+#[cfg(feature = "rest-api-circuit-scopes")]
+pub mod circuit_scopes;
+#[cfg(all(feature = "health", feature = "network-clock-skew"))]
+pub mod clock_skew_readiness;
+#[cfg(feature = "rest-api-cors-policies")]
+pub mod cors;
+pub mod daemon;
+#[cfg(feature = "event-publisher")]
+pub mod event_publisher;
+#[cfg(feature = "log-retrieval")]
+pub mod log_buffer;
+#[cfg(feature = "metrics-push")]
+pub mod metrics_reporter;
+#[cfg(feature = "mqtt-bridge")]
+pub mod mqtt_bridge;
+#[cfg(feature = "node-harness")]
+pub mod node;
+#[cfg(feature = "node-stats")]
+pub mod node_stats;
+#[cfg(feature = "registry-remote-auth")]
+pub mod registry_auth;
+#[cfg(all(feature = "health", feature = "registry-source-status"))]
+pub mod registry_readiness;
+#[cfg(feature = "resource-watchdog")]
+pub mod resource_watchdog;
+mod routes;
+#[cfg(feature = "secrets")]
+pub mod secrets;
+pub mod shutdown;
@@ -0,0 +1,259 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An in-memory ring buffer of the daemon's most recent log entries, which the REST API exposes
+//! so logs can be retrieved without shell access to the host.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FormatFunction};
+use log::{Level, LevelFilter, Record};
+
+/// The number of log entries kept by default
+pub const DEFAULT_LOG_BUFFER_CAPACITY: usize = 1000;
+
+/// A single log entry held in the buffer.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LogEntry {
+    /// Increases by one for each entry logged, so clients can request only newer entries
+    pub seq: u64,
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    pub level: String,
+    pub module: String,
+    pub message: String,
+}
+
+/// Selects the entries returned by `LogBuffer::entries`.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+    /// Only return entries with a sequence number greater than this one
+    pub since: Option<u64>,
+    /// Only return entries at this level or more severe
+    pub level: Option<LevelFilter>,
+    /// Only return entries from modules whose path starts with this prefix
+    pub module: Option<String>,
+    /// Return at most this many entries, keeping the most recent ones
+    pub limit: Option<usize>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: VecDeque<LogEntry>,
+    next_seq: u64,
+}
+
+/// A fixed-capacity buffer of the most recent log entries; once full, the oldest entry is
+/// dropped for each new one.
+#[derive(Clone)]
+pub struct LogBuffer {
+    capacity: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        LogBuffer {
+            capacity,
+            inner: Arc::new(Mutex::new(Inner {
+                entries: VecDeque::with_capacity(capacity),
+                next_seq: 1,
+            })),
+        }
+    }
+
+    /// Adds an entry to the buffer, evicting the oldest entry if the buffer is full.
+    pub fn push(&self, level: Level, module: &str, message: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        // A poisoned lock only means another thread panicked while logging; the entries are
+        // still usable.
+        let mut inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if inner.entries.len() == self.capacity {
+            inner.entries.pop_front();
+        }
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        inner.entries.push_back(LogEntry {
+            seq,
+            timestamp,
+            level: level.to_string(),
+            module: module.to_string(),
+            message,
+        });
+    }
+
+    /// Returns the buffered entries that match the filter, oldest first.
+    pub fn entries(&self, filter: &LogFilter) -> Vec<LogEntry> {
+        let inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut entries = inner
+            .entries
+            .iter()
+            .filter(|entry| filter.since.map(|since| entry.seq > since).unwrap_or(true))
+            .filter(|entry| match filter.level {
+                Some(max_level) => entry
+                    .level
+                    .parse::<Level>()
+                    .map(|level| level <= max_level)
+                    .unwrap_or(false),
+                None => true,
+            })
+            .filter(|entry| match &filter.module {
+                Some(module) => entry.module.starts_with(module.as_str()),
+                None => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if let Some(limit) = filter.limit {
+            if entries.len() > limit {
+                entries.drain(..entries.len() - limit);
+            }
+        }
+
+        entries
+    }
+
+    /// Returns the sequence number of the most recent entry, or 0 if nothing has been logged.
+    pub fn last_seq(&self) -> u64 {
+        let inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.next_seq - 1
+    }
+}
+
+/// A log writer that writes each record to stdout, as the daemon's default log target does, and
+/// also records it in a `LogBuffer`.
+pub struct BufferedLogWriter {
+    buffer: LogBuffer,
+    format: FormatFunction,
+}
+
+impl BufferedLogWriter {
+    pub fn new(buffer: LogBuffer, format: FormatFunction) -> Self {
+        BufferedLogWriter { buffer, format }
+    }
+}
+
+impl LogWriter for BufferedLogWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            (self.format)(&mut out, now, record)?;
+            writeln!(out)?;
+        }
+
+        self.buffer.push(
+            record.level(),
+            record.module_path().unwrap_or("<unnamed>"),
+            record.args().to_string(),
+        );
+
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer_with_entries() -> LogBuffer {
+        let buffer = LogBuffer::new(3);
+        buffer.push(Level::Info, "splinterd::daemon", "starting".into());
+        buffer.push(Level::Debug, "splinter::network", "peer added".into());
+        buffer.push(Level::Warn, "splinterd::daemon", "slow start".into());
+        buffer.push(Level::Error, "splinter::network", "peer lost".into());
+        buffer
+    }
+
+    /// Verify that the buffer keeps only the most recent entries once it is full, and that
+    /// sequence numbers keep increasing across evictions.
+    #[test]
+    fn test_buffer_evicts_oldest() {
+        let buffer = buffer_with_entries();
+
+        let entries = buffer.entries(&LogFilter::default());
+        assert_eq!(
+            entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(buffer.last_seq(), 4);
+    }
+
+    /// Verify that the since, level, module and limit filters each narrow the returned entries.
+    #[test]
+    fn test_buffer_filters() {
+        let buffer = buffer_with_entries();
+
+        let newer = buffer.entries(&LogFilter {
+            since: Some(3),
+            ..Default::default()
+        });
+        assert_eq!(newer.len(), 1);
+        assert_eq!(newer[0].message, "peer lost");
+
+        let warnings = buffer.entries(&LogFilter {
+            level: Some(LevelFilter::Warn),
+            ..Default::default()
+        });
+        assert_eq!(
+            warnings.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+
+        let network = buffer.entries(&LogFilter {
+            module: Some("splinter::network".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            network.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+
+        let latest = buffer.entries(&LogFilter {
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].seq, 4);
+    }
+}
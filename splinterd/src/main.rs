@@ -20,30 +20,71 @@ extern crate serde_derive;
 extern crate clap;
 
 mod config;
-mod daemon;
 mod error;
-mod routes;
+#[cfg(feature = "provision")]
+mod provision;
+#[cfg(feature = "state-dir-lock")]
+mod state_dir;
 mod transport;
 
-use flexi_logger::{style, DeferredNow, LogSpecBuilder, Logger};
+#[cfg(feature = "config-reload")]
+use flexi_logger::ReconfigurationHandle;
+use flexi_logger::{style, DeferredNow, LogSpecBuilder, LogSpecification, Logger};
 use log::Record;
 use rand::{thread_rng, Rng};
 
 use crate::config::{
-    ClapPartialConfigBuilder, Config, ConfigBuilder, ConfigError, DefaultPartialConfigBuilder,
-    EnvPartialConfigBuilder, PartialConfigBuilder, TomlPartialConfigBuilder,
+    effective_config_toml, validate_config, ClapPartialConfigBuilder, Config, ConfigBuilder,
+    ConfigError, DefaultPartialConfigBuilder, EnvPartialConfigBuilder, PartialConfigBuilder,
+    TomlPartialConfigBuilder,
 };
-use crate::daemon::SplinterDaemonBuilder;
 use clap::{clap_app, crate_version};
 use clap::{Arg, ArgMatches};
+#[cfg(any(feature = "admin-auto-vote", feature = "node-challenge"))]
+use cylinder::PrivateKey;
+#[cfg(feature = "admin-auto-vote")]
+use cylinder::{secp256k1::Secp256k1Context, Context};
+#[cfg(feature = "admin-auto-vote")]
+use splinter::admin::auto_vote::{AutoVoter, RuleVotePolicy};
+#[cfg(feature = "circuit-id-policy")]
+use splinter::admin::circuit_id::{CircuitIdFormat, CircuitIdPolicy};
+#[cfg(feature = "peer-identity-pinning")]
+use splinter::network::auth::pinning::PinningMode;
+#[cfg(feature = "rest-api-acme")]
+use splinter::rest_api::acme::{
+    default_dns_propagation_delay, AcmeCertificateManagerBuilder, AcmeChallenge,
+};
+#[cfg(feature = "signing-ed25519")]
+use splinter::signing::SigningAlgorithm;
+#[cfg(feature = "config-reload")]
+use splinter_daemon::daemon::ReloadableConfig;
+use splinter_daemon::daemon::SplinterDaemonBuilder;
+#[cfg(feature = "log-retrieval")]
+use splinter_daemon::log_buffer::{BufferedLogWriter, LogBuffer, DEFAULT_LOG_BUFFER_CAPACITY};
+#[cfg(feature = "metrics-push")]
+use splinter_daemon::metrics_reporter::{MetricsReporter, MetricsSink, DEFAULT_PUSH_INTERVAL};
+#[cfg(feature = "secrets")]
+use splinter_daemon::secrets::{SecretResolver, SECRETS_DIR};
 
 use std::env;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
+#[cfg(feature = "config-reload")]
+use std::sync::Mutex;
 use std::thread;
+#[cfg(any(
+    feature = "admin-key-rotation",
+    feature = "metrics-push",
+    feature = "network-clock-skew"
+))]
+use std::time::Duration;
 
 use error::UserError;
+#[cfg(feature = "state-dir-lock")]
+use state_dir::StateDirLock;
+#[cfg(feature = "tls-policy")]
+use transport::build_tls_policy;
 use transport::build_transport;
 
 fn create_config(_toml_path: Option<&str>, _matches: ArgMatches) -> Result<Config, UserError> {
@@ -142,6 +183,16 @@ fn find_node_id(config: &Config) -> Result<String, UserError> {
     }
 }
 
+fn log_spec(log_level: log::LevelFilter) -> LogSpecification {
+    let mut log_spec_builder = LogSpecBuilder::new();
+    log_spec_builder.default(log_level);
+    log_spec_builder.module("hyper", log::LevelFilter::Warn);
+    log_spec_builder.module("tokio", log::LevelFilter::Warn);
+    #[cfg(feature = "https-bind")]
+    log_spec_builder.module("h2", log::LevelFilter::Warn);
+    log_spec_builder.build()
+}
+
 // format for logs
 pub fn log_format(
     w: &mut dyn std::io::Write,
@@ -183,6 +234,14 @@ fn main() {
           "Increase output verbosity"));
 
     let app = app
+        .arg(
+            Arg::with_name("check_config")
+                .long("check-config")
+                .long_help(
+                    "Validate the configuration and print the effective configuration, \
+                     without starting the daemon",
+                ),
+        )
         .arg(
             Arg::with_name("advertised_endpoints")
                 .long("advertised-endpoints")
@@ -342,6 +401,270 @@ fn main() {
             .alias("rest-api-key"),
     );
 
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_min_version")
+            .long("tls-min-version")
+            .help("Minimum TLS protocol version for peer connections: 1.2 or 1.3")
+            .takes_value(true)
+            .possible_values(&["1.2", "1.3"]),
+    );
+
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_cipher_suites")
+            .long("tls-cipher-suite")
+            .help("Cipher suite allowed on peer connections; may be specified multiple times")
+            .takes_value(true)
+            .multiple(true),
+    );
+
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_client_auth")
+            .long("tls-client-auth")
+            .help("Client certificate requirement for peer connections: required, optional or off")
+            .takes_value(true)
+            .possible_values(&["required", "optional", "off"]),
+    );
+
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_rest_api_min_version")
+            .long("tls-rest-api-min-version")
+            .help("Minimum TLS protocol version for the REST API: 1.2 or 1.3")
+            .takes_value(true)
+            .possible_values(&["1.2", "1.3"]),
+    );
+
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_rest_api_cipher_suites")
+            .long("tls-rest-api-cipher-suite")
+            .help("Cipher suite allowed by the REST API; may be specified multiple times")
+            .takes_value(true)
+            .multiple(true),
+    );
+
+    #[cfg(feature = "tls-policy")]
+    let app = app.arg(
+        Arg::with_name("tls_rest_api_client_auth")
+            .long("tls-rest-api-client-auth")
+            .help("Client certificate requirement for the REST API: required, optional or off")
+            .takes_value(true)
+            .possible_values(&["required", "optional", "off"]),
+    );
+
+    #[cfg(feature = "tls-revocation")]
+    let app = app.arg(
+        Arg::with_name("tls_crl_file")
+            .long("tls-crl-file")
+            .help("File path to the CRL that peer certificates are checked against")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "tls-revocation")]
+    let app = app.arg(
+        Arg::with_name("tls_ocsp_response_file")
+            .long("tls-ocsp-response-file")
+            .help("File path to the OCSP response stapled to incoming peer connections")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "tls-revocation")]
+    let app = app.arg(
+        Arg::with_name("tls_require_ocsp")
+            .long("tls-require-ocsp")
+            .help("Refuse peer connections to nodes that do not staple an OCSP response"),
+    );
+
+    #[cfg(feature = "spiffe")]
+    let app = app.arg(
+        Arg::with_name("spiffe_svid_dir")
+            .long("spiffe-svid-dir")
+            .help("Directory containing the SPIFFE SVID, key and bundle used as the TLS identity")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "spiffe")]
+    let app = app.arg(
+        Arg::with_name("spiffe_trust_domain")
+            .long("spiffe-trust-domain")
+            .help("SPIFFE trust domain of the peer SPIFFE IDs mapped to registry nodes")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "spiffe")]
+    let app = app.arg(
+        Arg::with_name("spiffe_require_id")
+            .long("spiffe-require-id")
+            .help("Refuse peers whose certificates do not contain a SPIFFE ID"),
+    );
+
+    #[cfg(feature = "peer-identity-pinning")]
+    let app = app.arg(
+        Arg::with_name("peer_identity_pinning")
+            .long("peer-identity-pinning")
+            .help("Pin peer certificates to node identities on first use or from the registry")
+            .takes_value(true)
+            .possible_values(&["tofu", "registry"]),
+    );
+
+    #[cfg(feature = "admin-key-rotation")]
+    let app = app.arg(
+        Arg::with_name("admin_key_rotation_overlap")
+            .long("admin-key-rotation-overlap")
+            .help("Seconds a node's retired admin key remains permitted after a key rotation")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "circuit-id-policy")]
+    let app = app.arg(
+        Arg::with_name("accepted_circuit_id_formats")
+            .long("accepted-circuit-id-format")
+            .help("Circuit ID format accepted for new proposals; may be specified multiple times")
+            .takes_value(true)
+            .multiple(true)
+            .possible_values(&["random", "uuidv7"]),
+    );
+
+    #[cfg(feature = "admin-auto-vote")]
+    let app = app.arg(
+        Arg::with_name("auto_vote_key_file")
+            .long("auto-vote-key-file")
+            .help("Path of the private key that automatic votes are signed with")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "admin-auto-vote")]
+    let app = app.arg(
+        Arg::with_name("auto_vote_members")
+            .long("auto-vote-member")
+            .help("Node ID auto-accepted circuits may include; may be specified multiple times")
+            .takes_value(true)
+            .multiple(true),
+    );
+
+    #[cfg(feature = "admin-auto-vote")]
+    let app = app.arg(
+        Arg::with_name("auto_vote_service_types")
+            .long("auto-vote-service-type")
+            .help("Service type auto-accepted circuits may have; may be specified multiple times")
+            .takes_value(true)
+            .multiple(true),
+    );
+
+    #[cfg(feature = "admin-auto-vote")]
+    let app = app.arg(
+        Arg::with_name("auto_vote_metadata_pattern")
+            .long("auto-vote-metadata-pattern")
+            .help("Pattern the application metadata of auto-accepted circuits must match")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "admin-auto-vote")]
+    let app = app.arg(
+        Arg::with_name("auto_vote_reject")
+            .long("auto-vote-reject")
+            .help("Reject proposals that break an automatic voting rule"),
+    );
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    let app = app.arg(
+        Arg::with_name("persist_routing_table")
+            .long("persist-routing-table")
+            .help("Save the routing table on shutdown to speed up the next start"),
+    );
+
+    #[cfg(feature = "admin-signature-cache")]
+    let app = app.arg(
+        Arg::with_name("admin_signature_cache_size")
+            .long("admin-signature-cache-size")
+            .help("Number of admin signature verification results to cache")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "read-only-mode")]
+    let app = app.arg(
+        Arg::with_name("read_only")
+            .long("read-only")
+            .help("Start in read-only mode, refusing REST API requests that change state"),
+    );
+
+    #[cfg(feature = "resource-watchdog")]
+    let app = app.arg(
+        Arg::with_name("resource_min_free_space")
+            .long("resource-min-free-space")
+            .help("Free disk space, in megabytes, below which the node switches to read-only mode")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "scabbard-db-placement")]
+    let app = app.arg(
+        Arg::with_name("scabbard_circuit_db_dirs")
+            .long("scabbard-circuit-db-dirs")
+            .help("Store the databases of each circuit's scabbard services in their own directory"),
+    );
+
+    #[cfg(feature = "scabbard-db-placement")]
+    let app = app.arg(
+        Arg::with_name("scabbard_db_dirs")
+            .long("scabbard-db-dir")
+            .long_help(
+                "Store the scabbard databases of a circuit or service in a directory, as \
+                 CIRCUIT=DIR or CIRCUIT::SERVICE=DIR",
+            )
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    );
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    let app = app.arg(
+        Arg::with_name("scabbard_db_growth_increment")
+            .long("scabbard-db-growth-increment")
+            .help("Megabytes by which a nearly full scabbard database grows when opened")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    let app = app.arg(
+        Arg::with_name("scabbard_db_max_size")
+            .long("scabbard-db-max-size")
+            .help("Largest size, in megabytes, a scabbard database may grow to")
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    let app = app.arg(
+        Arg::with_name("scabbard_db_warning_thresholds")
+            .long("scabbard-db-warning-threshold")
+            .help("Percentage of the maximum database size at which scabbard warns")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    );
+
+    #[cfg(feature = "external-services")]
+    let app = app.arg(
+        Arg::with_name("external_service_types")
+            .long("external-service-type")
+            .help("Execute services of a type with an external executable, as TYPE=PATH")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    );
+
+    #[cfg(feature = "tenancy")]
+    let app = app.arg(
+        Arg::with_name("tenant_operators")
+            .long("tenant-operator")
+            .help("Biome user ID or public key of a node operator, which may manage tenants")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    );
+
     #[cfg(feature = "rest-api-cors")]
     let app = app.arg(
         Arg::with_name("whitelist")
@@ -352,6 +675,199 @@ fn main() {
             .help("Whitelisted domains"),
     );
 
+    #[cfg(feature = "rest-api-acme")]
+    let app = app
+        .arg(
+            Arg::with_name("acme_domains")
+                .long("acme-domain")
+                .long_help(
+                    "Domain to obtain the REST API certificate for using ACME; may be repeated. \
+                     Enables ACME certificate management.",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("acme_email")
+                .long("acme-email")
+                .long_help("Contact email address for the ACME account")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("acme_directory_url")
+                .long("acme-directory-url")
+                .long_help("URL of the ACME directory (default: Let's Encrypt)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("acme_challenge")
+                .long("acme-challenge")
+                .long_help("Challenge used to prove control of the ACME domains (default: http-01)")
+                .takes_value(true)
+                .possible_values(&["http-01", "dns-01"]),
+        )
+        .arg(
+            Arg::with_name("acme_http_bind")
+                .long("acme-http-bind")
+                .long_help("Bind address for the HTTP-01 challenge responder (default: 0.0.0.0:80)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("acme_dns_hook")
+                .long("acme-dns-hook")
+                .long_help("Command run to publish and remove DNS-01 challenge TXT records")
+                .takes_value(true),
+        );
+
+    #[cfg(feature = "config-reload")]
+    let app = app.arg(
+        Arg::with_name("peer_blocklist")
+            .long("peer-blocklist")
+            .long_help(
+                "ID of a peer that is not allowed to connect; may be repeated. The blocklist is \
+                 re-read from the config file on SIGHUP.",
+            )
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    );
+
+    #[cfg(feature = "metrics-push")]
+    let app = app
+        .arg(
+            Arg::with_name("metrics_url")
+                .long("metrics-url")
+                .long_help(
+                    "URL that metrics are pushed to: http(s)://host:port/database for InfluxDB, \
+                     or udp://host:port for statsd",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics_username")
+                .long("metrics-username")
+                .long_help("Username used to authenticate with InfluxDB")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics_password")
+                .long("metrics-password")
+                .long_help("Password used to authenticate with InfluxDB")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics_interval")
+                .long("metrics-interval")
+                .long_help("Number of seconds between metrics pushes (default: 10)")
+                .takes_value(true),
+        );
+
+    #[cfg(feature = "provision")]
+    let app = app.arg(
+        Arg::with_name("provision")
+            .long("provision")
+            .long_help(
+                "Generate the node's key and TLS certificates, write a starter config file and \
+                 registry entry, and run database migrations, then exit; existing files are \
+                 left untouched",
+            )
+            .conflicts_with("check_config"),
+    );
+
+    #[cfg(feature = "node-challenge")]
+    let app = app.arg(
+        Arg::with_name("node_key_file")
+            .long("node-key-file")
+            .long_help(
+                "Path of the secp256k1 private key the node signs identity challenges with; \
+                 its public key should be listed in the node's registry entry",
+            )
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "biome-user-admin")]
+    let app = app.arg(
+        Arg::with_name("biome_admin_keys")
+            .long("biome-admin-key")
+            .long_help(
+                "Public key that is permitted to administer Biome users with a Cylinder JWT; \
+                 may be specified multiple times",
+            )
+            .takes_value(true)
+            .multiple(true),
+    );
+
+    #[cfg(feature = "network-clock-skew")]
+    let app = app.arg(
+        Arg::with_name("clock_skew_threshold")
+            .long("clock-skew-threshold")
+            .long_help(
+                "Number of seconds a peer's clock may be off from the node's clock before a \
+                 warning is logged and the node is reported as not ready (default: 5)",
+            )
+            .takes_value(true),
+    );
+
+    #[cfg(feature = "peer-send-limits")]
+    let app = app
+        .arg(
+            Arg::with_name("peer_send_queue_capacity")
+                .long("peer-send-queue-capacity")
+                .long_help(
+                    "Number of outgoing messages that may be queued for a peer connection before \
+                     sends to the peer fail",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("peer_send_weights")
+                .long("peer-send-weight")
+                .long_help(
+                    "Number of messages sent to a peer in turn while other peers also have \
+                     messages queued, as NODE_ID=WEIGHT (default: 1)",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        );
+
+    #[cfg(feature = "scabbard-batch-history")]
+    let app = app
+        .arg(
+            Arg::with_name("scabbard_batch_history_size")
+                .long("scabbard-batch-history-size")
+                .long_help(
+                    "Number of batches kept in the batch history of each scabbard service \
+                     (default: 100)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("scabbard_batch_history_max_age")
+                .long("scabbard-batch-history-max-age")
+                .long_help(
+                    "Number of seconds batches are kept in the batch history of each scabbard \
+                     service; if not set, batches are only removed to stay within the history \
+                     size",
+                )
+                .takes_value(true),
+        );
+
+    #[cfg(feature = "signing-ed25519")]
+    let app = app.arg(
+        Arg::with_name("signing_algorithms")
+            .long("signing-algorithms")
+            .long_help(
+                "Signature algorithms accepted for admin service payloads and Cylinder JWTs \
+                 (default: secp256k1)",
+            )
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .possible_values(&["secp256k1", "ed25519"]),
+    );
+
     #[cfg(feature = "auth")]
     let app = app
         .arg(
@@ -373,6 +889,14 @@ fn main() {
                 .long_help("Client secret for the OAuth provider used by the REST API")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("oauth_public_client")
+                .long("oauth-public-client")
+                .long_help(
+                    "Use an OAuth app registered as a public client, which has no client secret; \
+                     the code exchange is protected by PKCE alone",
+                ),
+        )
         .arg(
             Arg::with_name("oauth_redirect_url")
                 .long("oauth-redirect-url")
@@ -395,30 +919,57 @@ fn main() {
         _ => log::LevelFilter::Trace,
     };
 
-    let mut log_spec_builder = LogSpecBuilder::new();
-    log_spec_builder.default(log_level);
-    log_spec_builder.module("hyper", log::LevelFilter::Warn);
-    log_spec_builder.module("tokio", log::LevelFilter::Warn);
-    #[cfg(feature = "https-bind")]
-    log_spec_builder.module("h2", log::LevelFilter::Warn);
+    let logger = Logger::with(log_spec(log_level)).format(log_format);
+    #[cfg(not(feature = "log-retrieval"))]
+    let logger = logger.log_target(flexi_logger::LogTarget::StdOut);
+    // Log entries are still written to stdout, and are also kept for the REST API
+    #[cfg(feature = "log-retrieval")]
+    let log_buffer = LogBuffer::new(DEFAULT_LOG_BUFFER_CAPACITY);
+    #[cfg(feature = "log-retrieval")]
+    let logger = logger.log_target(flexi_logger::LogTarget::Writer(Box::new(
+        BufferedLogWriter::new(log_buffer.clone(), log_format),
+    )));
+    #[cfg(not(feature = "config-reload"))]
+    logger.start().expect("Failed to create logger");
+    #[cfg(feature = "config-reload")]
+    let log_handle = logger.start().expect("Failed to create logger");
+
+    if matches.is_present("check_config") {
+        if let Err(err) = check_config(&matches) {
+            error!("Invalid configuration, {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    Logger::with(log_spec_builder.build())
-        .format(log_format)
-        .log_target(flexi_logger::LogTarget::StdOut)
-        .start()
-        .expect("Failed to create logger");
+    #[cfg(feature = "provision")]
+    {
+        if matches.is_present("provision") {
+            if let Err(err) = provision_node(&matches) {
+                error!("Failed to provision node, {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
 
-    if let Err(err) = start_daemon(matches) {
+    if let Err(err) = start_daemon(
+        matches,
+        #[cfg(feature = "config-reload")]
+        log_handle,
+        #[cfg(feature = "log-retrieval")]
+        log_buffer,
+    ) {
         error!("Failed to start daemon, {}", err);
         std::process::exit(1);
     }
 }
 
-fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
+const DEFAULT_CONFIG_FILE: &str = "/etc/splinter/splinterd.toml";
+
+fn load_config(matches: &ArgMatches) -> Result<Config, UserError> {
     // get provided config file or search default location
-    let config_file = matches
-        .value_of("config")
-        .unwrap_or("/etc/splinter/splinterd.toml");
+    let config_file = matches.value_of("config").unwrap_or(DEFAULT_CONFIG_FILE);
 
     let config_file_path = if Path::new(&config_file).is_file() {
         Some(config_file)
@@ -426,7 +977,103 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         None
     };
 
-    let config = create_config(config_file_path, matches.clone())?;
+    create_config(config_file_path, matches.clone())
+}
+
+// Prints the effective configuration and reports any problems with it, without starting the
+// daemon.
+fn check_config(matches: &ArgMatches) -> Result<(), UserError> {
+    let config = load_config(matches)?;
+
+    print!("{}", effective_config_toml(&config));
+
+    let problems = validate_config(&config);
+    if problems.is_empty() {
+        println!("# configuration is valid");
+        Ok(())
+    } else {
+        for problem in &problems {
+            eprintln!("error: {}", problem);
+        }
+        Err(UserError::InvalidArgument(format!(
+            "found {} configuration problem(s)",
+            problems.len()
+        )))
+    }
+}
+
+// Prepares a new node, or completes a partially-prepared one, and reports each step taken,
+// without starting the daemon.
+#[cfg(feature = "provision")]
+fn provision_node(matches: &ArgMatches) -> Result<(), UserError> {
+    let config = load_config(matches)?;
+
+    provision::create_directories(&config)?;
+
+    #[cfg(feature = "state-dir-lock")]
+    let _state_dir_lock = {
+        let state_dir = Path::new(config.state_dir());
+        let lock = StateDirLock::acquire(state_dir)?;
+        state_dir::ensure_layout(state_dir)?;
+        lock
+    };
+
+    let node_id = find_node_id(&config)?;
+    let display_name = config
+        .display_name()
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| format!("Node {}", &node_id));
+
+    let config_file = matches.value_of("config").unwrap_or(DEFAULT_CONFIG_FILE);
+    let report = provision::provision(&config, Path::new(config_file), &node_id, &display_name)?;
+    for step in report {
+        println!("{}", step);
+    }
+    println!("# node {} is provisioned", node_id);
+
+    Ok(())
+}
+
+/// Applies the configured log level, unless the verbosity was set on the command line.
+#[cfg(feature = "config-reload")]
+fn apply_log_level(
+    config: &Config,
+    matches: &ArgMatches,
+    log_handle: &mut ReconfigurationHandle,
+) -> Result<(), UserError> {
+    if matches.occurrences_of("verbose") > 0 {
+        return Ok(());
+    }
+
+    let log_level = match config.log_level() {
+        Some(log_level) => log_level.parse::<log::LevelFilter>().map_err(|_| {
+            UserError::InvalidArgument(format!("invalid log_level value: {}", log_level))
+        })?,
+        None => log::LevelFilter::Warn,
+    };
+    log_handle.set_new_spec(log_spec(log_level));
+
+    Ok(())
+}
+
+fn start_daemon(
+    matches: ArgMatches<'static>,
+    #[cfg(feature = "config-reload")] mut log_handle: ReconfigurationHandle,
+    #[cfg(feature = "log-retrieval")] log_buffer: LogBuffer,
+) -> Result<(), UserError> {
+    let config = load_config(&matches)?;
+
+    #[cfg(feature = "config-reload")]
+    apply_log_level(&config, &matches, &mut log_handle)?;
+
+    // The lock is held until the daemon exits
+    #[cfg(feature = "state-dir-lock")]
+    let _state_dir_lock = {
+        let state_dir = Path::new(config.state_dir());
+        let lock = StateDirLock::acquire(state_dir)?;
+        state_dir::ensure_layout(state_dir)?;
+        lock
+    };
 
     if config.no_tls() {
         for network_endpoint in config.network_endpoints() {
@@ -439,12 +1086,22 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         }
     }
 
-    let transport = build_transport(&config)?;
+    // Secret references in the config are resolved once, at startup
+    #[cfg(feature = "secrets")]
+    let secrets = SecretResolver::from_env()?;
+
+    let transport = build_transport(
+        &config,
+        #[cfg(feature = "secrets")]
+        &secrets,
+    )?;
 
     let rest_api_endpoint = config.rest_api_endpoint();
 
-    #[cfg(feature = "database")]
-    let db_url = config.database();
+    #[cfg(all(feature = "database", not(feature = "secrets")))]
+    let db_url = config.database().to_string();
+    #[cfg(all(feature = "database", feature = "secrets"))]
+    let db_url = secrets.resolve(config.database())?;
 
     let admin_timeout = config.admin_timeout();
 
@@ -456,6 +1113,36 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         .map(ToOwned::to_owned)
         .unwrap_or_else(|| format!("Node {}", &node_id));
 
+    #[cfg(feature = "metrics-push")]
+    let metrics_reporter = match config.metrics_url() {
+        Some(metrics_url) => {
+            let credentials = match (config.metrics_username(), config.metrics_password()) {
+                (Some(username), Some(password)) => {
+                    #[cfg(feature = "secrets")]
+                    let password = secrets.resolve(password)?;
+                    Some((username.into(), password.into()))
+                }
+                (None, None) => None,
+                _ => {
+                    return Err(UserError::MissingArgument(
+                        "metrics_username and metrics_password must be set together".into(),
+                    ))
+                }
+            };
+            let sink = MetricsSink::from_url(metrics_url, credentials)
+                .map_err(|err| UserError::InvalidArgument(err.to_string()))?;
+            Some(MetricsReporter::new(
+                sink,
+                Duration::from_secs(config.metrics_interval().unwrap_or(DEFAULT_PUSH_INTERVAL)),
+                vec![
+                    ("node_id".into(), node_id.clone()),
+                    ("display_name".into(), display_name.clone()),
+                ],
+            ))
+        }
+        None => None,
+    };
+
     let mut daemon_builder = SplinterDaemonBuilder::new();
 
     daemon_builder = daemon_builder
@@ -463,6 +1150,7 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         .with_network_endpoints(config.network_endpoints().to_vec())
         .with_advertised_endpoints(config.advertised_endpoints().to_vec())
         .with_initial_peers(config.peers().to_vec())
+        .with_peer_transport_preference(config.peer_transport_preference().to_vec())
         .with_node_id(node_id)
         .with_display_name(display_name)
         .with_rest_api_endpoint(String::from(rest_api_endpoint))
@@ -472,13 +1160,167 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         .with_registry_forced_refresh(config.registry_forced_refresh())
         .with_heartbeat(config.heartbeat())
         .with_admin_timeout(admin_timeout)
+        .with_orchestrator_incoming_capacity(config.orchestrator_incoming_capacity())
+        .with_orchestrator_outgoing_capacity(config.orchestrator_outgoing_capacity())
+        .with_orchestrator_channel_capacity(config.orchestrator_channel_capacity())
+        .with_admin_processor_incoming_capacity(config.admin_processor_incoming_capacity())
+        .with_admin_processor_outgoing_capacity(config.admin_processor_outgoing_capacity())
+        .with_admin_processor_channel_capacity(config.admin_processor_channel_capacity())
         .with_strict_ref_counts(config.strict_ref_counts());
 
     #[cfg(feature = "https-bind")]
     {
+        let rest_api_key = config.tls_rest_api_key().to_string();
+        #[cfg(feature = "secrets")]
+        let rest_api_key = secrets.resolve_to_file(
+            &rest_api_key,
+            &Path::new(config.state_dir())
+                .join(SECRETS_DIR)
+                .join("rest_api.key"),
+        )?;
         daemon_builder = daemon_builder
             .with_rest_api_server_cert(config.tls_rest_api_cert().to_string())
-            .with_rest_api_server_key(config.tls_rest_api_key().to_string());
+            .with_rest_api_server_key(rest_api_key);
+    }
+
+    #[cfg(feature = "tls-policy")]
+    {
+        daemon_builder = daemon_builder.with_rest_api_tls_policy(build_tls_policy(
+            config.tls_rest_api_min_version(),
+            config.tls_rest_api_cipher_suites(),
+            config.tls_rest_api_client_auth(),
+        )?);
+        if Path::new(config.tls_ca_file()).is_file() {
+            daemon_builder =
+                daemon_builder.with_rest_api_client_ca_file(config.tls_ca_file().to_string());
+        }
+    }
+
+    #[cfg(feature = "spiffe")]
+    {
+        if let Some(trust_domain) = config.spiffe_trust_domain() {
+            daemon_builder = daemon_builder.with_spiffe_trust_domain(trust_domain.to_string());
+        }
+        daemon_builder = daemon_builder.with_spiffe_require_id(config.spiffe_require_id());
+    }
+
+    #[cfg(feature = "peer-identity-pinning")]
+    {
+        if let Some(mode) = config.peer_identity_pinning() {
+            daemon_builder = daemon_builder.with_peer_identity_pinning(
+                mode.parse::<PinningMode>().map_err(|err| {
+                    UserError::InvalidArgument(format!(
+                        "invalid peer_identity_pinning value: {}",
+                        err
+                    ))
+                })?,
+            );
+        }
+    }
+
+    #[cfg(feature = "admin-key-rotation")]
+    {
+        if let Some(overlap) = config.admin_key_rotation_overlap() {
+            daemon_builder =
+                daemon_builder.with_admin_key_rotation_overlap(Duration::from_secs(overlap));
+        }
+    }
+
+    #[cfg(feature = "circuit-id-policy")]
+    {
+        if let Some(formats) = config.accepted_circuit_id_formats() {
+            let policy = formats
+                .iter()
+                .map(|format| format.parse::<CircuitIdFormat>())
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(CircuitIdPolicy::new)
+                .map_err(|err| {
+                    UserError::InvalidArgument(format!(
+                        "invalid accepted_circuit_id_formats: {}",
+                        err
+                    ))
+                })?;
+            daemon_builder = daemon_builder.with_circuit_id_policy(policy);
+        }
+    }
+
+    #[cfg(feature = "admin-auto-vote")]
+    {
+        if let Some(key_file) = config.auto_vote_key_file() {
+            let key = fs::read_to_string(key_file).map_err(|err| {
+                UserError::io_err_with_source("Unable to read auto-vote key file", Box::new(err))
+            })?;
+            let key = PrivateKey::new_from_hex(key.trim()).map_err(|err| {
+                UserError::InvalidArgument(format!(
+                    "auto_vote_key_file is not a valid secp256k1 private key: {}",
+                    err
+                ))
+            })?;
+
+            let mut policy =
+                RuleVotePolicy::new().with_reject_mismatches(config.auto_vote_reject());
+            if let Some(members) = config.auto_vote_members() {
+                policy = policy.with_allowed_members(members.to_vec());
+            }
+            if let Some(service_types) = config.auto_vote_service_types() {
+                policy = policy.with_allowed_service_types(service_types.to_vec());
+            }
+            if let Some(pattern) = config.auto_vote_metadata_pattern() {
+                policy = policy.with_metadata_pattern(pattern);
+            }
+
+            let signer = Secp256k1Context::new().new_signer(key);
+            daemon_builder =
+                daemon_builder.with_auto_voter(AutoVoter::new(Box::new(policy), signer));
+        }
+    }
+
+    #[cfg(feature = "admin-routing-snapshot")]
+    {
+        daemon_builder = daemon_builder.with_persist_routing_table(config.persist_routing_table());
+    }
+
+    #[cfg(feature = "admin-signature-cache")]
+    {
+        if let Some(size) = config.admin_signature_cache_size() {
+            daemon_builder = daemon_builder.with_admin_signature_cache_size(size as usize);
+        }
+    }
+
+    #[cfg(feature = "read-only-mode")]
+    {
+        daemon_builder = daemon_builder.with_read_only(config.read_only());
+    }
+
+    #[cfg(feature = "resource-watchdog")]
+    {
+        if let Some(min_free_space) = config.resource_min_free_space() {
+            daemon_builder = daemon_builder.with_resource_min_free_space(min_free_space);
+        }
+    }
+
+    #[cfg(feature = "external-services")]
+    {
+        if let Some(service_types) = config.external_service_types() {
+            let mut external_service_types = Vec::with_capacity(service_types.len());
+            for service_type in service_types {
+                let mut parts = service_type.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(service_type), Some(path))
+                        if !service_type.is_empty() && !path.is_empty() =>
+                    {
+                        external_service_types.push((service_type.to_string(), path.into()))
+                    }
+                    _ => {
+                        return Err(UserError::InvalidArgument(format!(
+                            "invalid external_service_types value, expected TYPE=PATH: {}",
+                            service_type
+                        )))
+                    }
+                }
+            }
+            daemon_builder = daemon_builder.with_external_service_types(external_service_types);
+        }
     }
 
     #[cfg(feature = "service-endpoint")]
@@ -498,7 +1340,7 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
 
     #[cfg(feature = "database")]
     {
-        daemon_builder = daemon_builder.with_db_url(Some(String::from(db_url)));
+        daemon_builder = daemon_builder.with_db_url(Some(db_url));
     }
 
     #[cfg(any(feature = "biome-credentials", feature = "biome-key-management"))]
@@ -511,16 +1353,317 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         daemon_builder = daemon_builder.with_whitelist(config.whitelist().map(ToOwned::to_owned));
     }
 
+    #[cfg(feature = "rest-api-cors-policies")]
+    {
+        daemon_builder = daemon_builder.with_cors_policies(config.cors_policies().to_vec());
+    }
+
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    {
+        daemon_builder =
+            daemon_builder.with_circuit_scope_roles(config.circuit_scope_roles().to_vec());
+    }
+
+    #[cfg(feature = "tenancy")]
+    {
+        if let Some(tenant_operators) = config.tenant_operators() {
+            daemon_builder = daemon_builder.with_tenant_operators(tenant_operators.to_vec());
+        }
+    }
+
+    #[cfg(feature = "registry-remote-auth")]
+    {
+        daemon_builder = daemon_builder.with_registry_auth(config.registry_auth().to_vec());
+    }
+
     #[cfg(feature = "auth")]
     {
+        let oauth_client_secret = config.oauth_client_secret().map(ToOwned::to_owned);
+        #[cfg(feature = "secrets")]
+        let oauth_client_secret = oauth_client_secret
+            .map(|secret| secrets.resolve(&secret))
+            .transpose()?;
         daemon_builder = daemon_builder
             .with_oauth_provider(config.oauth_provider().map(ToOwned::to_owned))
             .with_oauth_client_id(config.oauth_client_id().map(ToOwned::to_owned))
-            .with_oauth_client_secret(config.oauth_client_secret().map(ToOwned::to_owned))
+            .with_oauth_client_secret(oauth_client_secret)
+            .with_oauth_public_client(config.oauth_public_client())
             .with_oauth_redirect_url(config.oauth_redirect_url().map(ToOwned::to_owned))
             .with_oauth_openid_url(config.oauth_openid_url().map(ToOwned::to_owned));
     }
 
+    #[cfg(feature = "signing-ed25519")]
+    {
+        if let Some(algorithms) = config.signing_algorithms() {
+            daemon_builder = daemon_builder.with_signing_algorithms(
+                algorithms
+                    .iter()
+                    .map(|algorithm| {
+                        algorithm.parse::<SigningAlgorithm>().map_err(|err| {
+                            UserError::InvalidArgument(format!(
+                                "invalid signing_algorithms value: {}",
+                                err
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+    }
+
+    #[cfg(feature = "rest-api-acme")]
+    {
+        if let Some(domains) = config.acme_domains() {
+            let challenge = match config.acme_challenge() {
+                None | Some("http-01") => match config.acme_http_bind() {
+                    Some(bind) => AcmeChallenge::Http01 { bind: bind.into() },
+                    None => AcmeChallenge::default(),
+                },
+                Some("dns-01") => AcmeChallenge::Dns01 {
+                    hook: config
+                        .acme_dns_hook()
+                        .ok_or_else(|| {
+                            UserError::MissingArgument(
+                                "acme_dns_hook is required for the dns-01 challenge".into(),
+                            )
+                        })?
+                        .into(),
+                    propagation_delay: default_dns_propagation_delay(),
+                },
+                Some(challenge) => {
+                    return Err(UserError::InvalidArgument(format!(
+                        "invalid acme_challenge value: {}",
+                        challenge
+                    )))
+                }
+            };
+
+            let mut acme_builder = AcmeCertificateManagerBuilder::new()
+                .with_domains(domains.to_vec())
+                .with_challenge(challenge)
+                .with_storage_dir(Path::new(config.state_dir()).join("acme"));
+            if let Some(email) = config.acme_email() {
+                acme_builder = acme_builder.with_contact_email(email.into());
+            }
+            if let Some(directory_url) = config.acme_directory_url() {
+                acme_builder = acme_builder.with_directory_url(directory_url.into());
+            }
+
+            daemon_builder = daemon_builder.with_acme_certificate_manager(
+                acme_builder.build().map_err(|err| {
+                    UserError::InvalidArgument(format!("invalid ACME configuration: {}", err))
+                })?,
+            );
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    {
+        if let Some(metrics_reporter) = metrics_reporter {
+            daemon_builder = daemon_builder.with_metrics_reporter(metrics_reporter);
+        }
+    }
+
+    #[cfg(feature = "alerting")]
+    {
+        daemon_builder = daemon_builder
+            .with_alert_rules(config.alert_rules().to_vec())
+            .with_alert_sinks(config.alert_sinks().to_vec());
+    }
+
+    #[cfg(feature = "event-publisher")]
+    {
+        daemon_builder = daemon_builder.with_event_publisher(config.event_publisher().cloned());
+    }
+
+    #[cfg(feature = "mqtt-bridge")]
+    {
+        daemon_builder = daemon_builder.with_mqtt_bridge(config.mqtt_bridge().cloned());
+    }
+
+    #[cfg(feature = "log-retrieval")]
+    {
+        daemon_builder = daemon_builder.with_log_buffer(log_buffer);
+    }
+
+    #[cfg(feature = "node-challenge")]
+    {
+        if let Some(node_key_file) = config.node_key_file() {
+            let node_key = fs::read_to_string(node_key_file).map_err(|err| {
+                UserError::io_err_with_source("Unable to read node key file", Box::new(err))
+            })?;
+            let node_key = PrivateKey::new_from_hex(node_key.trim()).map_err(|err| {
+                UserError::InvalidArgument(format!(
+                    "node_key_file is not a valid secp256k1 private key: {}",
+                    err
+                ))
+            })?;
+            daemon_builder = daemon_builder.with_node_key(Some(node_key));
+        }
+    }
+
+    #[cfg(feature = "biome-user-admin")]
+    {
+        daemon_builder = daemon_builder.with_biome_admin_keys(
+            config
+                .biome_admin_keys()
+                .map(ToOwned::to_owned)
+                .unwrap_or_default(),
+        );
+    }
+
+    #[cfg(feature = "network-clock-skew")]
+    {
+        if let Some(clock_skew_threshold) = config.clock_skew_threshold() {
+            daemon_builder =
+                daemon_builder.with_clock_skew_threshold(Duration::from_secs(clock_skew_threshold));
+        }
+    }
+
+    #[cfg(feature = "peer-send-limits")]
+    {
+        if let Some(capacity) = config.peer_send_queue_capacity() {
+            daemon_builder = daemon_builder.with_peer_send_queue_capacity(capacity);
+        }
+        if let Some(send_weights) = config.peer_send_weights() {
+            let mut peer_send_weights = Vec::with_capacity(send_weights.len());
+            for send_weight in send_weights {
+                let mut parts = send_weight.splitn(2, '=');
+                let (node_id, weight) = match (
+                    parts.next(),
+                    parts.next().and_then(|weight| weight.parse::<usize>().ok()),
+                ) {
+                    (Some(node_id), Some(weight)) if !node_id.is_empty() && weight > 0 => {
+                        (node_id, weight)
+                    }
+                    _ => {
+                        return Err(UserError::InvalidArgument(format!(
+                            "invalid peer_send_weights value, expected NODE_ID=WEIGHT: {}",
+                            send_weight
+                        )))
+                    }
+                };
+                peer_send_weights.push((node_id.to_string(), weight));
+            }
+            daemon_builder = daemon_builder.with_peer_send_weights(peer_send_weights);
+        }
+    }
+
+    #[cfg(feature = "scabbard-batch-history")]
+    {
+        if let Some(size) = config.scabbard_batch_history_size() {
+            daemon_builder = daemon_builder.with_scabbard_batch_history_size(size);
+        }
+        if let Some(max_age) = config.scabbard_batch_history_max_age() {
+            daemon_builder =
+                daemon_builder.with_scabbard_batch_history_max_age(Duration::from_secs(max_age));
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-placement")]
+    {
+        daemon_builder =
+            daemon_builder.with_scabbard_circuit_db_dirs(config.scabbard_circuit_db_dirs());
+        if let Some(db_dirs) = config.scabbard_db_dirs() {
+            let mut scabbard_db_dirs = Vec::with_capacity(db_dirs.len());
+            for db_dir in db_dirs {
+                let mut parts = db_dir.splitn(2, '=');
+                let (target, dir) = match (parts.next(), parts.next()) {
+                    (Some(target), Some(dir)) if !target.is_empty() && !dir.is_empty() => {
+                        (target, dir)
+                    }
+                    _ => {
+                        return Err(UserError::InvalidArgument(format!(
+                            "invalid scabbard_db_dirs value, expected CIRCUIT[::SERVICE]=DIR: {}",
+                            db_dir
+                        )))
+                    }
+                };
+                let mut target = target.splitn(2, "::");
+                let circuit_id = target.next().unwrap_or_default().to_string();
+                let service_id = target.next().map(String::from);
+                scabbard_db_dirs.push((circuit_id, service_id, dir.to_string()));
+            }
+            daemon_builder = daemon_builder.with_scabbard_db_dirs(scabbard_db_dirs);
+        }
+    }
+
+    #[cfg(feature = "scabbard-db-auto-grow")]
+    {
+        match (
+            config.scabbard_db_growth_increment(),
+            config.scabbard_db_max_size(),
+        ) {
+            (Some(_), None) => {
+                return Err(UserError::InvalidArgument(
+                    "scabbard_db_growth_increment requires scabbard_db_max_size".into(),
+                ))
+            }
+            (increment, max_size) => {
+                if let Some(increment) = increment {
+                    daemon_builder = daemon_builder
+                        .with_scabbard_db_growth_increment(increment as usize * 1024 * 1024);
+                }
+                if let Some(max_size) = max_size {
+                    daemon_builder =
+                        daemon_builder.with_scabbard_db_max_size(max_size as usize * 1024 * 1024);
+                }
+            }
+        }
+        if let Some(thresholds) = config.scabbard_db_warning_thresholds() {
+            let mut warning_thresholds = Vec::with_capacity(thresholds.len());
+            for threshold in thresholds {
+                match threshold.parse::<u8>() {
+                    Ok(threshold) if threshold <= 100 => warning_thresholds.push(threshold),
+                    _ => {
+                        return Err(UserError::InvalidArgument(format!(
+                            "invalid scabbard_db_warning_thresholds value, expected a percentage: \
+                             {}",
+                            threshold
+                        )))
+                    }
+                }
+            }
+            daemon_builder = daemon_builder.with_scabbard_db_warning_thresholds(warning_thresholds);
+        }
+    }
+
+    #[cfg(feature = "config-reload")]
+    {
+        daemon_builder = daemon_builder.with_peer_blocklist(
+            config
+                .peer_blocklist()
+                .map(ToOwned::to_owned)
+                .unwrap_or_default(),
+        );
+
+        let matches = matches.clone();
+        let log_handle = Mutex::new(log_handle);
+        daemon_builder = daemon_builder.with_config_reloader(Box::new(move || {
+            let config = load_config(&matches).map_err(|err| err.to_string())?;
+            let mut log_handle = log_handle
+                .lock()
+                .map_err(|_| "logger lock poisoned".to_string())?;
+            apply_log_level(&config, &matches, &mut log_handle).map_err(|err| err.to_string())?;
+
+            Ok(ReloadableConfig {
+                registries: config.registries().to_vec(),
+                registry_auto_refresh: config.registry_auto_refresh(),
+                registry_forced_refresh: config.registry_forced_refresh(),
+                #[cfg(feature = "registry-remote-auth")]
+                registry_auth: config.registry_auth().to_vec(),
+                #[cfg(feature = "rest-api-cors")]
+                whitelist: config.whitelist().map(ToOwned::to_owned),
+                #[cfg(feature = "rest-api-cors-policies")]
+                cors_policies: config.cors_policies().to_vec(),
+                peer_blocklist: config
+                    .peer_blocklist()
+                    .map(ToOwned::to_owned)
+                    .unwrap_or_default(),
+            })
+        }));
+    }
+
     let mut node = daemon_builder.build().map_err(|err| {
         UserError::daemon_err_with_source("unable to build the Splinter daemon", Box::new(err))
     })?;
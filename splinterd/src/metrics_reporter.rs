@@ -0,0 +1,494 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodically pushes the counters and gauges recorded through the `metrics` facade to InfluxDB
+//! or statsd.
+//!
+//! Histograms are not reported.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use metrics::{Key, Recorder};
+#[cfg(feature = "scheduler")]
+use splinter::error::InternalError;
+#[cfg(feature = "scheduler")]
+use splinter::scheduler::{Job, Scheduler};
+
+/// The default number of seconds between metrics pushes.
+pub const DEFAULT_PUSH_INTERVAL: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MetricValue {
+    Counter(u64),
+    Gauge(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Metric {
+    name: String,
+    labels: Vec<(String, String)>,
+    value: MetricValue,
+}
+
+/// A `metrics` recorder that keeps the current value of each counter and gauge.
+#[derive(Default)]
+struct MetricsRecorder {
+    metrics: Mutex<BTreeMap<String, Metric>>,
+}
+
+impl MetricsRecorder {
+    fn update<F>(&self, key: Key, update: F)
+    where
+        F: FnOnce(Option<MetricValue>) -> MetricValue,
+    {
+        let name = key.name().to_string();
+        let labels = key
+            .labels()
+            .map(|label| (label.key().to_string(), label.value().to_string()))
+            .collect::<Vec<_>>();
+        let id = format!("{}{:?}", name, labels);
+
+        match self.metrics.lock() {
+            Ok(mut metrics) => {
+                let value = update(metrics.get(&id).map(|metric| metric.value));
+                metrics.insert(
+                    id,
+                    Metric {
+                        name,
+                        labels,
+                        value,
+                    },
+                );
+            }
+            Err(_) => error!("Unable to record metric {}: lock poisoned", name),
+        }
+    }
+
+    fn snapshot(&self) -> Vec<Metric> {
+        match self.metrics.lock() {
+            Ok(metrics) => metrics.values().cloned().collect(),
+            Err(_) => {
+                error!("Unable to read metrics: lock poisoned");
+                vec![]
+            }
+        }
+    }
+}
+
+impl Recorder for MetricsRecorder {
+    fn register_counter(&self, _key: Key, _description: Option<&'static str>) {}
+
+    fn register_gauge(&self, _key: Key, _description: Option<&'static str>) {}
+
+    fn register_histogram(&self, _key: Key, _description: Option<&'static str>) {}
+
+    fn increment_counter(&self, key: Key, value: u64) {
+        self.update(key, |current| match current {
+            Some(MetricValue::Counter(count)) => MetricValue::Counter(count.saturating_add(value)),
+            _ => MetricValue::Counter(value),
+        })
+    }
+
+    fn update_gauge(&self, key: Key, value: f64) {
+        self.update(key, |_| MetricValue::Gauge(value))
+    }
+
+    fn record_histogram(&self, _key: Key, _value: u64) {}
+}
+
+/// The destination that metrics are pushed to.
+pub enum MetricsSink {
+    /// An InfluxDB (1.x) database, written to using the line protocol over HTTP.
+    InfluxDb {
+        write_url: String,
+        credentials: Option<(String, String)>,
+    },
+    /// A statsd server, written to over UDP using the DogStatsD tag extension.
+    Statsd { address: String },
+}
+
+impl MetricsSink {
+    /// Creates the sink for the given URL.
+    ///
+    /// An `http://` or `https://` URL refers to an InfluxDB server, with the database name as the
+    /// URL's path (for example, `http://influxdb:8086/splinter`). A `udp://` URL refers to a
+    /// statsd server (for example, `udp://statsd:8125`).
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the InfluxDB database or statsd server
+    /// * `credentials` - The username and password used to authenticate with InfluxDB
+    pub fn from_url(
+        url: &str,
+        credentials: Option<(String, String)>,
+    ) -> Result<Self, MetricsError> {
+        if let Some(address) = url.strip_prefix("udp://") {
+            if credentials.is_some() {
+                warn!("Metrics credentials are ignored for statsd");
+            }
+            return Ok(MetricsSink::Statsd {
+                address: address.into(),
+            });
+        }
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(MetricsError(format!(
+                "unsupported metrics URL {}; expected http://, https://, or udp://",
+                url
+            )));
+        }
+
+        let (scheme, rest) = url.split_at(url.find("://").unwrap_or(0) + 3);
+        let (host, database) = match rest.find('/') {
+            Some(index) => (&rest[..index], rest[index + 1..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        if host.is_empty() || database.is_empty() || database.contains('/') {
+            return Err(MetricsError(format!(
+                "metrics URL {} must include the InfluxDB database name as its path",
+                url
+            )));
+        }
+
+        Ok(MetricsSink::InfluxDb {
+            write_url: format!("{}{}/write?db={}", scheme, host, database),
+            credentials,
+        })
+    }
+}
+
+/// Pushes recorded metrics to a `MetricsSink` at a fixed interval.
+pub struct MetricsReporter {
+    sink: MetricsSink,
+    interval: Duration,
+    tags: Vec<(String, String)>,
+}
+
+impl MetricsReporter {
+    /// Creates a new reporter.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The destination for the metrics
+    /// * `interval` - The time between pushes
+    /// * `tags` - Tags added to every metric, such as the node ID
+    pub fn new(sink: MetricsSink, interval: Duration, tags: Vec<(String, String)>) -> Self {
+        Self {
+            sink,
+            interval,
+            tags,
+        }
+    }
+
+    /// Installs the global metrics recorder and starts pushing metrics in a background thread.
+    pub fn start(self) -> Result<MetricsReporterShutdownHandle, MetricsError> {
+        let recorder = install_recorder()?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+
+        let join_handle = thread::Builder::new()
+            .name("MetricsReporter".into())
+            .spawn(move || {
+                let mut pusher = match Pusher::new(self.sink) {
+                    Ok(pusher) => pusher,
+                    Err(err) => {
+                        error!("Unable to start metrics reporter: {}", err);
+                        return;
+                    }
+                };
+
+                let mut next_push = Instant::now() + self.interval;
+                while thread_running.load(Ordering::SeqCst) {
+                    let now = Instant::now();
+                    if now < next_push {
+                        thread::park_timeout(next_push - now);
+                        continue;
+                    }
+
+                    if let Err(err) = pusher.push(&recorder.snapshot(), &self.tags) {
+                        warn!("Unable to push metrics: {}", err);
+                    }
+                    next_push += self.interval;
+                }
+            })
+            .map_err(|err| MetricsError(format!("unable to start reporter thread: {}", err)))?;
+
+        Ok(MetricsReporterShutdownHandle {
+            running,
+            thread: join_handle.thread().clone(),
+        })
+    }
+
+    /// Installs the global metrics recorder and registers a job with the given scheduler that
+    /// pushes metrics. This is used in place of `start`.
+    #[cfg(feature = "scheduler")]
+    pub fn schedule(self, scheduler: &Scheduler) -> Result<(), MetricsError> {
+        let recorder = install_recorder()?;
+        let mut pusher = Pusher::new(self.sink)?;
+        let tags = self.tags;
+
+        scheduler
+            .register(Job::new("metrics-push", self.interval, move || {
+                pusher
+                    .push(&recorder.snapshot(), &tags)
+                    .map_err(|err| InternalError::from_source(Box::new(err)))
+            }))
+            .map_err(|err| MetricsError(format!("unable to schedule metrics push: {}", err)))
+    }
+}
+
+fn install_recorder() -> Result<&'static MetricsRecorder, MetricsError> {
+    let recorder: &'static MetricsRecorder = Box::leak(Box::new(MetricsRecorder::default()));
+    metrics::set_recorder(recorder)
+        .map_err(|err| MetricsError(format!("unable to install metrics recorder: {}", err)))?;
+    Ok(recorder)
+}
+
+/// Stops a running `MetricsReporter`.
+pub struct MetricsReporterShutdownHandle {
+    running: Arc<AtomicBool>,
+    thread: Thread,
+}
+
+impl MetricsReporterShutdownHandle {
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
+
+enum Pusher {
+    InfluxDb {
+        client: reqwest::blocking::Client,
+        write_url: String,
+        credentials: Option<(String, String)>,
+    },
+    Statsd {
+        socket: UdpSocket,
+        address: String,
+        // statsd counters are deltas, so the last value pushed for each counter is kept
+        last_counts: HashMap<String, u64>,
+    },
+}
+
+impl Pusher {
+    fn new(sink: MetricsSink) -> Result<Self, MetricsError> {
+        match sink {
+            MetricsSink::InfluxDb {
+                write_url,
+                credentials,
+            } => Ok(Pusher::InfluxDb {
+                client: reqwest::blocking::Client::new(),
+                write_url,
+                credentials,
+            }),
+            MetricsSink::Statsd { address } => Ok(Pusher::Statsd {
+                socket: UdpSocket::bind("0.0.0.0:0")
+                    .map_err(|err| MetricsError(format!("unable to bind UDP socket: {}", err)))?,
+                address,
+                last_counts: HashMap::new(),
+            }),
+        }
+    }
+
+    fn push(&mut self, metrics: &[Metric], tags: &[(String, String)]) -> Result<(), MetricsError> {
+        if metrics.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            Pusher::InfluxDb {
+                client,
+                write_url,
+                credentials,
+            } => {
+                let body = metrics
+                    .iter()
+                    .map(|metric| influxdb_line(metric, tags))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                let mut request = client.post(write_url.as_str()).body(body);
+                if let Some((username, password)) = credentials {
+                    request = request.basic_auth(username, Some(password));
+                }
+                let response = request
+                    .send()
+                    .map_err(|err| MetricsError(format!("unable to reach InfluxDB: {}", err)))?;
+                if !response.status().is_success() {
+                    return Err(MetricsError(format!(
+                        "InfluxDB returned {}",
+                        response.status()
+                    )));
+                }
+                Ok(())
+            }
+            Pusher::Statsd {
+                socket,
+                address,
+                last_counts,
+            } => {
+                for metric in metrics {
+                    let value = match metric.value {
+                        MetricValue::Counter(count) => {
+                            let id = format!("{}{:?}", metric.name, metric.labels);
+                            let last = last_counts.insert(id, count).unwrap_or(0);
+                            if count == last {
+                                continue;
+                            }
+                            MetricValue::Counter(count.saturating_sub(last))
+                        }
+                        gauge => gauge,
+                    };
+                    socket
+                        .send_to(
+                            statsd_line(metric, value, tags).as_bytes(),
+                            address.as_str(),
+                        )
+                        .map_err(|err| {
+                            MetricsError(format!("unable to send to statsd {}: {}", address, err))
+                        })?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Formats a metric using the InfluxDB line protocol, without a timestamp.
+fn influxdb_line(metric: &Metric, tags: &[(String, String)]) -> String {
+    let mut line = escape_influxdb(&metric.name, false);
+    for (key, value) in tags.iter().chain(metric.labels.iter()) {
+        line.push(',');
+        line.push_str(&escape_influxdb(key, true));
+        line.push('=');
+        line.push_str(&escape_influxdb(value, true));
+    }
+    match metric.value {
+        MetricValue::Counter(count) => line.push_str(&format!(" value={}i", count)),
+        MetricValue::Gauge(gauge) => line.push_str(&format!(" value={}", gauge)),
+    }
+    line
+}
+
+fn escape_influxdb(value: &str, escape_equals: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == ',' || c == ' ' || (escape_equals && c == '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Formats a metric for statsd, with tags in the DogStatsD format.
+fn statsd_line(metric: &Metric, value: MetricValue, tags: &[(String, String)]) -> String {
+    let mut line = match value {
+        MetricValue::Counter(count) => format!("{}:{}|c", metric.name, count),
+        MetricValue::Gauge(gauge) => format!("{}:{}|g", metric.name, gauge),
+    };
+    let tags = tags
+        .iter()
+        .chain(metric.labels.iter())
+        .map(|(key, value)| format!("{}:{}", key, value.replace(|c| c == ',' || c == '|', "_")))
+        .collect::<Vec<_>>();
+    if !tags.is_empty() {
+        line.push_str("|#");
+        line.push_str(&tags.join(","));
+    }
+    line
+}
+
+#[derive(Debug)]
+pub struct MetricsError(String);
+
+impl Error for MetricsError {}
+
+impl fmt::Display for MetricsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that metrics URLs are parsed into the correct sinks.
+    #[test]
+    fn test_sink_from_url() {
+        match MetricsSink::from_url("http://influxdb:8086/splinter", None) {
+            Ok(MetricsSink::InfluxDb { write_url, .. }) => {
+                assert_eq!(write_url, "http://influxdb:8086/write?db=splinter")
+            }
+            _ => panic!("Expected an InfluxDB sink"),
+        }
+
+        match MetricsSink::from_url("udp://statsd:8125", None) {
+            Ok(MetricsSink::Statsd { address }) => assert_eq!(address, "statsd:8125"),
+            _ => panic!("Expected a statsd sink"),
+        }
+
+        assert!(MetricsSink::from_url("http://influxdb:8086", None).is_err());
+        assert!(MetricsSink::from_url("tcp://statsd:8125", None).is_err());
+    }
+
+    /// Verify that counters and gauges are formatted correctly for InfluxDB and statsd, including
+    /// the node's tags and the metric's labels.
+    #[test]
+    fn test_format_metrics() {
+        let tags = vec![
+            ("node_id".to_string(), "alpha".to_string()),
+            ("display_name".to_string(), "Node Alpha".to_string()),
+        ];
+        let counter = Metric {
+            name: "splinter.peers.connected".into(),
+            labels: vec![("transport".into(), "tcps".into())],
+            value: MetricValue::Counter(3),
+        };
+        let gauge = Metric {
+            name: "splinter.circuits".into(),
+            labels: vec![],
+            value: MetricValue::Gauge(1.5),
+        };
+
+        assert_eq!(
+            influxdb_line(&counter, &tags),
+            "splinter.peers.connected,node_id=alpha,display_name=Node\\ Alpha,transport=tcps \
+             value=3i"
+        );
+        assert_eq!(
+            influxdb_line(&gauge, &tags),
+            "splinter.circuits,node_id=alpha,display_name=Node\\ Alpha value=1.5"
+        );
+
+        assert_eq!(
+            statsd_line(&counter, MetricValue::Counter(2), &tags),
+            "splinter.peers.connected:2|c|#node_id:alpha,display_name:Node Alpha,transport:tcps"
+        );
+        assert_eq!(
+            statsd_line(&gauge, gauge.value, &[]),
+            "splinter.circuits:1.5|g"
+        );
+    }
+}
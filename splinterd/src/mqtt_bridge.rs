@@ -0,0 +1,384 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bridge between the scabbard services of a node and an MQTT broker.
+//!
+//! The state changes of the selected scabbard services are published as JSON to
+//! `<topic_prefix>/<circuit_id>/<service_id>/state`. Messages received on the subscribed topics
+//! are read as serialized batch lists and submitted to the scabbard service the topic is mapped
+//! to, which lets devices that already speak MQTT take part in a circuit.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rumqttc::{Client, Event, MqttOptions, Outgoing, Packet, QoS};
+use scabbard::service::{
+    ScabbardServiceLookup, StateChangeEvent, StateSubscriber, StateSubscriberError,
+    StateSubscriberFactory,
+};
+use transact::protocol::batch::BatchPair;
+use transact::protos::FromBytes;
+
+/// The client ID used to connect to the broker if none is configured
+pub const DEFAULT_CLIENT_ID: &str = "splinterd";
+/// The prefix of the topics state changes are published to if none is configured
+pub const DEFAULT_TOPIC_PREFIX: &str = "splinter";
+
+const KEEP_ALIVE_SECS: u16 = 30;
+const REQUEST_CAPACITY: usize = 64;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The bridge, as defined in the `[mqtt_bridge]` table of the config file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MqttBridgeConfig {
+    /// The `host:port` of the MQTT broker
+    pub broker: String,
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+    /// The scabbard services whose state changes are published, as `<circuit_id>::<service_id>`;
+    /// `<circuit_id>::*` selects every scabbard service of a circuit
+    #[serde(default)]
+    pub publish: Vec<String>,
+    #[serde(default)]
+    pub subscribe: Vec<MqttSubscription>,
+}
+
+/// Maps an MQTT topic filter to the scabbard service that messages on the topic are submitted to
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MqttSubscription {
+    pub topic: String,
+    /// The scabbard service, as `<circuit_id>::<service_id>`
+    pub service: String,
+}
+
+fn default_client_id() -> String {
+    DEFAULT_CLIENT_ID.into()
+}
+
+fn default_topic_prefix() -> String {
+    DEFAULT_TOPIC_PREFIX.into()
+}
+
+/// An error that occurred while starting the MQTT bridge
+#[derive(Debug)]
+pub struct MqttBridgeError(pub String);
+
+impl Error for MqttBridgeError {}
+
+impl fmt::Display for MqttBridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Connects the scabbard services of the node to an MQTT broker.
+pub struct MqttBridge {
+    join_handle: thread::JoinHandle<()>,
+    client: Client,
+    shutdown: Arc<AtomicBool>,
+    config: MqttBridgeConfig,
+}
+
+impl MqttBridge {
+    /// Connects to the broker and starts the thread that receives messages on the subscribed
+    /// topics.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The broker, and which topics and services to bridge
+    /// * `services` - Used to find the scabbard services that received batches are submitted to
+    pub fn start(
+        config: MqttBridgeConfig,
+        services: ScabbardServiceLookup,
+    ) -> Result<Self, MqttBridgeError> {
+        let (host, port) = parse_broker(&config.broker)?;
+        let routes = config
+            .subscribe
+            .iter()
+            .map(|subscription| {
+                split_service_id(&subscription.service)
+                    .map(|(circuit_id, service_id)| {
+                        (subscription.topic.clone(), circuit_id, service_id)
+                    })
+                    .ok_or_else(|| {
+                        MqttBridgeError(format!(
+                            "invalid service {}; expected <circuit_id>::<service_id>",
+                            subscription.service
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut options = MqttOptions::new(config.client_id.clone(), host, port);
+        options.set_keep_alive(KEEP_ALIVE_SECS);
+        let (client, mut connection) = Client::new(options, REQUEST_CAPACITY);
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let mut thread_client = client.clone();
+        let broker = config.broker.clone();
+        let join_handle = thread::Builder::new()
+            .name("MqttBridge".into())
+            .spawn(move || {
+                for notification in connection.iter() {
+                    match notification {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            info!("Connected to MQTT broker {}", broker);
+                            // Subscriptions do not survive a reconnect with a clean session
+                            for (topic, _, _) in &routes {
+                                if let Err(err) =
+                                    thread_client.try_subscribe(topic, QoS::AtLeastOnce)
+                                {
+                                    error!("Unable to subscribe to MQTT topic {}: {}", topic, err);
+                                }
+                            }
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            submit(&services, &routes, &publish.topic, &publish.payload)
+                        }
+                        Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(_) => (),
+                        Err(_) if thread_shutdown.load(Ordering::SeqCst) => break,
+                        Err(err) => {
+                            warn!("MQTT connection to {} failed: {}", broker, err);
+                            thread::sleep(RECONNECT_DELAY);
+                        }
+                    }
+                }
+                debug!("Stopping MQTT bridge");
+            })
+            .map_err(|err| {
+                MqttBridgeError(format!("unable to start MQTT bridge thread: {}", err))
+            })?;
+
+        Ok(MqttBridge {
+            join_handle,
+            client,
+            shutdown,
+            config,
+        })
+    }
+
+    /// Returns a factory for the subscribers that publish the state changes of the selected
+    /// scabbard services
+    pub fn state_subscriber_factory(&self) -> Box<dyn StateSubscriberFactory> {
+        Box::new(MqttStateSubscriberFactory {
+            client: self.client.clone(),
+            topic_prefix: self.config.topic_prefix.clone(),
+            publish: self.config.publish.clone(),
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the bridge
+    pub fn shutdown_signaler(&self) -> MqttBridgeShutdownSignaler {
+        MqttBridgeShutdownSignaler {
+            client: self.client.clone(),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
+    /// Waits for the bridge thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("MQTT bridge thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals an `MqttBridge` to disconnect from the broker and stop
+#[derive(Clone)]
+pub struct MqttBridgeShutdownSignaler {
+    client: Client,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MqttBridgeShutdownSignaler {
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if self.client.clone().try_disconnect().is_err() {
+            debug!("MQTT bridge has already stopped");
+        }
+    }
+}
+
+struct MqttStateSubscriberFactory {
+    client: Client,
+    topic_prefix: String,
+    publish: Vec<String>,
+}
+
+impl StateSubscriberFactory for MqttStateSubscriberFactory {
+    fn create_subscriber(&self, circuit_id: &str, service_id: &str) -> Box<dyn StateSubscriber> {
+        Box::new(MqttStateSubscriber {
+            client: Mutex::new(self.client.clone()),
+            topic: format!("{}/{}/{}/state", self.topic_prefix, circuit_id, service_id),
+            selected: is_selected(&self.publish, circuit_id, service_id),
+        })
+    }
+}
+
+struct MqttStateSubscriber {
+    client: Mutex<Client>,
+    topic: String,
+    selected: bool,
+}
+
+impl StateSubscriber for MqttStateSubscriber {
+    fn handle_event(&self, event: StateChangeEvent) -> Result<(), StateSubscriberError> {
+        if !self.selected {
+            return Err(StateSubscriberError::Unsubscribe);
+        }
+
+        let payload = serde_json::to_vec(&event)
+            .map_err(|err| StateSubscriberError::UnableToHandleEvent(err.to_string()))?;
+        self.client
+            .lock()
+            .map_err(|_| StateSubscriberError::UnableToHandleEvent("lock poisoned".into()))?
+            .try_publish(self.topic.as_str(), QoS::AtLeastOnce, false, payload)
+            .map_err(|err| {
+                StateSubscriberError::UnableToHandleEvent(format!(
+                    "unable to publish to {}: {}",
+                    self.topic, err
+                ))
+            })
+    }
+}
+
+fn submit(
+    services: &ScabbardServiceLookup,
+    routes: &[(String, String, String)],
+    topic: &str,
+    payload: &[u8],
+) {
+    let batches = match Vec::<BatchPair>::from_bytes(payload) {
+        Ok(batches) => batches,
+        Err(err) => {
+            warn!(
+                "Ignoring MQTT message on {}: not a list of batches: {}",
+                topic, err
+            );
+            return;
+        }
+    };
+
+    for (_, circuit_id, service_id) in routes
+        .iter()
+        .filter(|(filter, _, _)| topic_matches(filter, topic))
+    {
+        let scabbard = match services.get(circuit_id, service_id) {
+            Some(scabbard) => scabbard,
+            None => {
+                warn!(
+                    "Ignoring MQTT message on {}: scabbard service {}::{} is not running",
+                    topic, circuit_id, service_id
+                );
+                continue;
+            }
+        };
+
+        match scabbard.add_batches(batches.clone()) {
+            Ok(Some(link)) => debug!("Submitted batches from MQTT topic {}: {}", topic, link),
+            Ok(None) => warn!("MQTT message on {} contained no valid batches", topic),
+            Err(err) => error!(
+                "Unable to submit batches from MQTT topic {}: {}",
+                topic, err
+            ),
+        }
+    }
+}
+
+/// Returns whether the topic matches the filter, which may contain the `+` and `#` wildcards
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for filter_level in filter.split('/') {
+        match (filter_level, topic_levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => (),
+            (filter_level, Some(topic_level)) if filter_level == topic_level => (),
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+fn is_selected(publish: &[String], circuit_id: &str, service_id: &str) -> bool {
+    publish
+        .iter()
+        .filter_map(|selection| split_service_id(selection))
+        .any(|(circuit, service)| {
+            circuit == circuit_id && (service == "*" || service == service_id)
+        })
+}
+
+fn split_service_id(service: &str) -> Option<(String, String)> {
+    let mut parts = service.splitn(2, "::");
+    match (parts.next(), parts.next()) {
+        (Some(circuit_id), Some(service_id))
+            if !circuit_id.is_empty() && !service_id.is_empty() =>
+        {
+            Some((circuit_id.to_string(), service_id.to_string()))
+        }
+        _ => None,
+    }
+}
+
+fn parse_broker(broker: &str) -> Result<(String, u16), MqttBridgeError> {
+    let mut parts = broker.rsplitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some(port), Some(host)) if !host.is_empty() => port
+            .parse()
+            .map(|port| (host.to_string(), port))
+            .map_err(|_| MqttBridgeError(format!("invalid port in MQTT broker {}", broker))),
+        _ => Err(MqttBridgeError(format!(
+            "invalid MQTT broker {}; expected host:port",
+            broker
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that topics are matched against filters level by level, with `+` matching a single
+    /// level and `#` matching all remaining levels.
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("gateway/batches", "gateway/batches"));
+        assert!(topic_matches("gateway/+/batches", "gateway/dev1/batches"));
+        assert!(topic_matches("gateway/#", "gateway/dev1/batches"));
+        assert!(!topic_matches("gateway/batches", "gateway/batches/extra"));
+        assert!(!topic_matches("gateway/+/batches", "gateway/batches"));
+        assert!(!topic_matches("gateway/batches", "gateway/other"));
+    }
+
+    /// Verify that services are selected for publishing by ID or by circuit wildcard.
+    #[test]
+    fn test_is_selected() {
+        let publish = vec![
+            "abcde-01234::a000".to_string(),
+            "fghij-56789::*".to_string(),
+        ];
+        assert!(is_selected(&publish, "abcde-01234", "a000"));
+        assert!(!is_selected(&publish, "abcde-01234", "b000"));
+        assert!(is_selected(&publish, "fghij-56789", "b000"));
+        assert!(!is_selected(&publish, "klmno-01234", "a000"));
+    }
+}
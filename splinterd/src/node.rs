@@ -0,0 +1,336 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-process Splinter nodes for integration tests.
+//!
+//! A `SplinterNode` runs a complete daemon, including the admin service, scabbard orchestrator,
+//! and REST API, inside the current process. Nodes that share an `InprocTransport` connect to
+//! each other over inproc endpoints, so a multi-node network can be tested with `cargo test`
+//! instead of docker-compose.
+//!
+//! ```ignore
+//! let nodes = start_network(3)?;
+//! let url = nodes[0].rest_api_url();
+//! // ... propose circuits, submit batches, etc.
+//! for node in nodes {
+//!     node.shutdown();
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use splinter::network::connection_manager::Connector;
+use splinter::peer::PeerManagerConnector;
+use splinter::transport::{inproc::InprocTransport, multi::MultiTransport};
+use tempdir::TempDir;
+
+use crate::daemon::{CreateError, RunningDaemon, SplinterDaemonBuilder, StartError};
+
+const DEFAULT_HEARTBEAT: u64 = 30;
+const DEFAULT_ADMIN_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_REGISTRY_AUTO_REFRESH: u64 = 600;
+const DEFAULT_REGISTRY_FORCED_REFRESH: u64 = 10;
+
+/// How long `start_network` waits for the nodes to connect to each other.
+pub const DEFAULT_PEERING_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A Splinter node running in the current process.
+pub struct SplinterNode {
+    node_id: String,
+    network_endpoint: String,
+    rest_api_port: u16,
+    running_daemon: RunningDaemon,
+    // The state directory is removed when the node is dropped
+    _state_dir: TempDir,
+}
+
+impl SplinterNode {
+    pub fn builder() -> SplinterNodeBuilder {
+        SplinterNodeBuilder::new()
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Returns the inproc endpoint other nodes use to connect to this node.
+    pub fn network_endpoint(&self) -> &str {
+        &self.network_endpoint
+    }
+
+    pub fn rest_api_port(&self) -> u16 {
+        self.rest_api_port
+    }
+
+    /// Returns the base URL of the node's REST API.
+    pub fn rest_api_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.rest_api_port)
+    }
+
+    pub fn peer_connector(&self) -> &PeerManagerConnector {
+        self.running_daemon.peer_connector()
+    }
+
+    pub fn connection_connector(&self) -> &Connector {
+        self.running_daemon.connection_connector()
+    }
+
+    /// Blocks until the node is connected to all of the given peers.
+    ///
+    /// Returns `SplinterNodeError::Timeout` if the peers are not all connected within `timeout`.
+    pub fn wait_for_peers(
+        &self,
+        peer_ids: &[&str],
+        timeout: Duration,
+    ) -> Result<(), SplinterNodeError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let peers = self
+                .peer_connector()
+                .list_peers()
+                .map_err(|err| SplinterNodeError::PeerError(err.to_string()))?;
+            if peer_ids
+                .iter()
+                .all(|peer_id| peers.iter().any(|peer| peer.as_str() == *peer_id))
+            {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(SplinterNodeError::Timeout(format!(
+                    "{} connected to {:?}, expected {:?}",
+                    self.node_id, peers, peer_ids
+                )));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Shuts down the node, returning the names of any subsystems that did not stop in time.
+    pub fn shutdown(self) -> Vec<String> {
+        self.running_daemon.shutdown()
+    }
+}
+
+/// Builds and starts a `SplinterNode`.
+#[derive(Default)]
+pub struct SplinterNodeBuilder {
+    node_id: Option<String>,
+    initial_peers: Vec<String>,
+    transport: Option<InprocTransport>,
+}
+
+impl SplinterNodeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_node_id(mut self, node_id: String) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    /// Sets the network endpoints of the nodes this node connects to on startup.
+    pub fn with_initial_peers(mut self, initial_peers: Vec<String>) -> Self {
+        self.initial_peers = initial_peers;
+        self
+    }
+
+    /// Sets the inproc transport the node listens on; nodes can only connect to each other if
+    /// they share the same transport.
+    pub fn with_transport(mut self, transport: InprocTransport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    pub fn start(self) -> Result<SplinterNode, SplinterNodeError> {
+        let node_id = self.node_id.ok_or_else(|| {
+            SplinterNodeError::CreateError(CreateError::MissingRequiredField(
+                "Missing field: node_id".to_string(),
+            ))
+        })?;
+        let transport = self.transport.unwrap_or_default();
+
+        let state_dir = TempDir::new(&format!("splinter-node-{}", node_id))
+            .map_err(|err| SplinterNodeError::io("unable to create state directory", err))?;
+        let state_dir_path = state_dir.path().to_str().ok_or_else(|| {
+            SplinterNodeError::StartError(StartError::StorageError(
+                "state directory is not a valid UTF-8 string".into(),
+            ))
+        })?;
+
+        let network_endpoint = format!("inproc://{}", node_id);
+        let rest_api_port = find_free_port()?;
+        #[cfg(not(feature = "https-bind"))]
+        let rest_api_endpoint = format!("127.0.0.1:{}", rest_api_port);
+        #[cfg(feature = "https-bind")]
+        let rest_api_endpoint = format!("http://127.0.0.1:{}", rest_api_port);
+
+        let daemon_builder = SplinterDaemonBuilder::new()
+            .with_state_dir(state_dir_path.to_string())
+            .with_network_endpoints(vec![network_endpoint.clone()])
+            .with_advertised_endpoints(vec![network_endpoint.clone()])
+            .with_initial_peers(self.initial_peers)
+            .with_node_id(node_id.clone())
+            .with_display_name(node_id.clone())
+            .with_rest_api_endpoint(rest_api_endpoint)
+            .with_storage_type(Some("memory".into()))
+            .with_registry_auto_refresh(DEFAULT_REGISTRY_AUTO_REFRESH)
+            .with_registry_forced_refresh(DEFAULT_REGISTRY_FORCED_REFRESH)
+            .with_heartbeat(DEFAULT_HEARTBEAT)
+            .with_admin_timeout(DEFAULT_ADMIN_TIMEOUT)
+            .with_strict_ref_counts(false);
+
+        #[cfg(feature = "service-endpoint")]
+        let daemon_builder =
+            daemon_builder.with_service_endpoint(format!("inproc://{}-services", node_id));
+
+        #[cfg(feature = "database")]
+        let daemon_builder = daemon_builder.with_db_url(Some("memory".into()));
+
+        let mut daemon = daemon_builder
+            .build()
+            .map_err(SplinterNodeError::CreateError)?;
+        let running_daemon = daemon
+            .run(MultiTransport::new(vec![Box::new(transport)]))
+            .map_err(SplinterNodeError::StartError)?;
+
+        Ok(SplinterNode {
+            node_id,
+            network_endpoint,
+            rest_api_port,
+            running_daemon,
+            _state_dir: state_dir,
+        })
+    }
+}
+
+/// Starts a network of `size` nodes, named `node-0` through `node-<size - 1>`, that are all
+/// connected to each other.
+///
+/// Returns once every node is connected to every other node.
+pub fn start_network(size: usize) -> Result<Vec<SplinterNode>, SplinterNodeError> {
+    let transport = InprocTransport::default();
+    let mut nodes: Vec<SplinterNode> = Vec::with_capacity(size);
+
+    // Each node connects to the nodes started before it, which results in a full mesh
+    for index in 0..size {
+        let node = SplinterNode::builder()
+            .with_node_id(format!("node-{}", index))
+            .with_initial_peers(
+                nodes
+                    .iter()
+                    .map(|node| node.network_endpoint().to_string())
+                    .collect(),
+            )
+            .with_transport(transport.clone())
+            .start()?;
+        nodes.push(node);
+    }
+
+    for node in &nodes {
+        let peer_ids = nodes
+            .iter()
+            .map(SplinterNode::node_id)
+            .filter(|peer_id| *peer_id != node.node_id())
+            .collect::<Vec<_>>();
+        node.wait_for_peers(&peer_ids, DEFAULT_PEERING_TIMEOUT)?;
+    }
+
+    Ok(nodes)
+}
+
+/// Finds a port on the loopback interface that is not currently in use.
+fn find_free_port() -> Result<u16, SplinterNodeError> {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|err| SplinterNodeError::io("unable to find a free port", err))
+}
+
+#[derive(Debug)]
+pub enum SplinterNodeError {
+    CreateError(CreateError),
+    StartError(StartError),
+    PeerError(String),
+    Timeout(String),
+    IoError { context: String, source: io::Error },
+}
+
+impl SplinterNodeError {
+    fn io(context: &str, source: io::Error) -> Self {
+        SplinterNodeError::IoError {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+impl Error for SplinterNodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SplinterNodeError::CreateError(err) => Some(err),
+            SplinterNodeError::StartError(err) => Some(err),
+            SplinterNodeError::IoError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SplinterNodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplinterNodeError::CreateError(err) => write!(f, "unable to create node: {}", err),
+            SplinterNodeError::StartError(err) => write!(f, "unable to start node: {}", err),
+            SplinterNodeError::PeerError(msg) => write!(f, "unable to list peers: {}", msg),
+            SplinterNodeError::Timeout(msg) => {
+                write!(f, "timed out waiting for peers: {}", msg)
+            }
+            SplinterNodeError::IoError { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a network of in-process nodes starts with every node connected to every other
+    /// node, that each node has its own REST API port, and that the nodes shut down cleanly.
+    #[test]
+    fn test_start_network() {
+        let nodes = start_network(3).expect("Failed to start network");
+
+        assert_eq!(
+            nodes.iter().map(SplinterNode::node_id).collect::<Vec<_>>(),
+            vec!["node-0", "node-1", "node-2"]
+        );
+        let mut ports = nodes
+            .iter()
+            .map(SplinterNode::rest_api_port)
+            .collect::<Vec<_>>();
+        ports.sort();
+        ports.dedup();
+        assert_eq!(ports.len(), 3);
+
+        for node in nodes {
+            assert!(node.shutdown().is_empty());
+        }
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hourly rollups of node statistics, kept so operators can see how the node grows over time
+//! without running a metrics stack.
+//!
+//! The [`StatsCollector`] samples the node every few minutes and folds each sample into the
+//! rollup for the current hour, which is saved in a [`StatsStore`]: circuit, proposal and peer
+//! counts and the size of the state files are the latest values sampled in the hour, while message
+//! counts are the number of messages dispatched during the hour. Rollups older than the retention
+//! period are dropped.
+//!
+//! [`StatsCollector`]: struct.StatsCollector.html
+//! [`StatsStore`]: struct.StatsStore.html
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use splinter::admin::store::AdminServiceStore;
+use splinter::network::dispatch::DispatchMessageCount;
+use splinter::peer::PeerManagerConnector;
+
+/// The name of the file, in the state directory, the rollups are saved to
+pub const NODE_STATS_FILE: &str = "node_stats.json";
+
+/// How often the node is sampled
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long rollups are kept, in hours
+pub const DEFAULT_RETENTION_HOURS: u64 = 90 * 24;
+
+const SECS_PER_HOUR: u64 = 3600;
+
+/// The statistics of the node for one hour
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct StatsRollup {
+    /// The start of the hour, in seconds since the Unix epoch
+    pub hour: u64,
+    pub circuits: u64,
+    pub proposals: u64,
+    pub peers: u64,
+    /// The number of network messages dispatched during the hour
+    pub network_messages: u64,
+    /// The number of circuit messages dispatched during the hour
+    pub circuit_messages: u64,
+    /// The total size, in bytes, of the files in the node's state directories
+    pub state_bytes: u64,
+}
+
+/// Error returned when the rollups can't be loaded or saved
+#[derive(Debug)]
+pub struct StatsError(pub String);
+
+impl Error for StatsError {}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The saved rollups, shared between the `StatsCollector` and the REST API
+#[derive(Clone)]
+pub struct StatsStore {
+    path: PathBuf,
+    retention_hours: u64,
+    rollups: Arc<Mutex<Vec<StatsRollup>>>,
+}
+
+impl StatsStore {
+    /// Loads the rollups saved at the given path, if any.
+    pub fn open(path: &Path, retention_hours: u64) -> Result<Self, StatsError> {
+        let rollups = if path.exists() {
+            let contents = fs::read_to_string(path)
+                .map_err(|err| StatsError(format!("unable to read {}: {}", path.display(), err)))?;
+            serde_json::from_str(&contents)
+                .map_err(|err| StatsError(format!("unable to parse {}: {}", path.display(), err)))?
+        } else {
+            vec![]
+        };
+
+        Ok(StatsStore {
+            path: path.to_path_buf(),
+            retention_hours,
+            rollups: Arc::new(Mutex::new(rollups)),
+        })
+    }
+
+    /// Returns the rollups of the hours starting at or after `since`, in seconds since the Unix
+    /// epoch, oldest first
+    pub fn list(&self, since: Option<u64>) -> Vec<StatsRollup> {
+        match self.rollups.lock() {
+            Ok(rollups) => rollups
+                .iter()
+                .filter(|rollup| since.map(|since| rollup.hour >= since).unwrap_or(true))
+                .cloned()
+                .collect(),
+            Err(_) => {
+                error!("Unable to list node stats: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    /// Folds a sample into the rollup of the hour it was taken in, drops the rollups past the
+    /// retention period, and saves the rollups.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - When the sample was taken, in seconds since the Unix epoch
+    /// * `sample` - The sampled counts; its message counts are the messages dispatched since the
+    ///   previous sample
+    fn record(&self, timestamp: u64, sample: StatsRollup) -> Result<(), StatsError> {
+        let mut rollups = self
+            .rollups
+            .lock()
+            .map_err(|_| StatsError("lock poisoned".into()))?;
+
+        let hour = timestamp - timestamp % SECS_PER_HOUR;
+        match rollups.last_mut() {
+            Some(rollup) if rollup.hour == hour => {
+                rollup.circuits = sample.circuits;
+                rollup.proposals = sample.proposals;
+                rollup.peers = sample.peers;
+                rollup.network_messages += sample.network_messages;
+                rollup.circuit_messages += sample.circuit_messages;
+                rollup.state_bytes = sample.state_bytes;
+            }
+            _ => rollups.push(StatsRollup { hour, ..sample }),
+        }
+
+        let oldest = hour.saturating_sub(self.retention_hours * SECS_PER_HOUR);
+        rollups.retain(|rollup| rollup.hour >= oldest);
+
+        let contents = serde_json::to_string(&*rollups)
+            .map_err(|err| StatsError(format!("unable to serialize node stats: {}", err)))?;
+
+        // Write to a temporary file first, so an interrupted write cannot lose the saved rollups
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, contents)
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .map_err(|err| StatsError(format!("unable to write {}: {}", self.path.display(), err)))
+    }
+}
+
+/// Samples the node and records the samples in a `StatsStore`.
+pub struct StatsCollector {
+    join_handle: thread::JoinHandle<()>,
+    shutdown_signaler: StatsCollectorShutdownSignaler,
+}
+
+impl StatsCollector {
+    /// Starts the collector.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - Where the rollups are saved
+    /// * `admin_store` - Used to count circuits and proposals
+    /// * `peer_connector` - Used to count peers
+    /// * `network_messages` - The message count of the network dispatch loop
+    /// * `circuit_messages` - The message count of the circuit dispatch loop
+    /// * `directories` - The directories whose files make up the node's state
+    /// * `interval` - How often the node is sampled
+    pub fn start(
+        store: StatsStore,
+        admin_store: Box<dyn AdminServiceStore>,
+        peer_connector: PeerManagerConnector,
+        network_messages: DispatchMessageCount,
+        circuit_messages: DispatchMessageCount,
+        mut directories: Vec<PathBuf>,
+        interval: Duration,
+    ) -> Result<Self, StatsError> {
+        directories.sort();
+        directories.dedup();
+
+        let (sender, receiver) = channel();
+        let join_handle = thread::Builder::new()
+            .name("StatsCollector".into())
+            .spawn(move || {
+                let mut last_network_messages = network_messages.get();
+                let mut last_circuit_messages = circuit_messages.get();
+
+                loop {
+                    // A sample is taken on shutdown as well, so the messages dispatched since the
+                    // last sample are not lost
+                    let shutdown = match receiver.recv_timeout(interval) {
+                        Err(RecvTimeoutError::Timeout) => false,
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => true,
+                    };
+
+                    let network_count = network_messages.get();
+                    let circuit_count = circuit_messages.get();
+                    let sample = StatsRollup {
+                        hour: 0,
+                        circuits: admin_store
+                            .list_circuits(&[])
+                            .map(|circuits| circuits.count() as u64)
+                            .unwrap_or_else(|err| {
+                                error!("Unable to count circuits for node stats: {}", err);
+                                0
+                            }),
+                        proposals: admin_store
+                            .list_proposals(&[])
+                            .map(|proposals| proposals.count() as u64)
+                            .unwrap_or_else(|err| {
+                                error!("Unable to count proposals for node stats: {}", err);
+                                0
+                            }),
+                        peers: peer_connector
+                            .list_peers()
+                            .map(|peers| peers.len() as u64)
+                            .unwrap_or_else(|err| {
+                                error!("Unable to count peers for node stats: {}", err);
+                                0
+                            }),
+                        network_messages: network_count.saturating_sub(last_network_messages),
+                        circuit_messages: circuit_count.saturating_sub(last_circuit_messages),
+                        state_bytes: directories.iter().map(|dir| dir_size(dir)).sum(),
+                    };
+                    last_network_messages = network_count;
+                    last_circuit_messages = circuit_count;
+
+                    if let Err(err) = store.record(now_secs(), sample) {
+                        error!("Unable to record node stats: {}", err);
+                    }
+
+                    if shutdown {
+                        debug!("Stopping stats collector");
+                        return;
+                    }
+                }
+            })
+            .map_err(|err| {
+                StatsError(format!("unable to start stats collector thread: {}", err))
+            })?;
+
+        Ok(StatsCollector {
+            join_handle,
+            shutdown_signaler: StatsCollectorShutdownSignaler { sender },
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the collector
+    pub fn shutdown_signaler(&self) -> StatsCollectorShutdownSignaler {
+        self.shutdown_signaler.clone()
+    }
+
+    /// Waits for the collector thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Stats collector thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals a `StatsCollector` to stop
+#[derive(Clone)]
+pub struct StatsCollectorShutdownSignaler {
+    sender: Sender<()>,
+}
+
+impl StatsCollectorShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.send(()).is_err() {
+            debug!("Stats collector has already stopped");
+        }
+    }
+}
+
+/// Returns the total size of the files directly in the given directory, or 0 if it can't be read
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verifies that samples taken in the same hour are folded into one rollup, that rollups past
+    /// the retention period are dropped, and that the rollups are reloaded from the file.
+    #[test]
+    fn test_stats_store() {
+        let temp_dir = TempDir::new("test_stats_store").expect("Failed to create temp dir");
+        let path = temp_dir.path().join(NODE_STATS_FILE);
+        let store = StatsStore::open(&path, 2).expect("Failed to open store");
+
+        let sample = |circuits, network_messages| StatsRollup {
+            circuits,
+            network_messages,
+            ..Default::default()
+        };
+
+        store
+            .record(3600, sample(1, 10))
+            .expect("Failed to record sample");
+        store
+            .record(3900, sample(2, 5))
+            .expect("Failed to record sample");
+        let rollups = store.list(None);
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(rollups[0].hour, 3600);
+        assert_eq!(rollups[0].circuits, 2);
+        assert_eq!(rollups[0].network_messages, 15);
+
+        store
+            .record(7200, sample(3, 1))
+            .expect("Failed to record sample");
+        assert_eq!(store.list(Some(7200)).len(), 1);
+
+        store
+            .record(4 * 3600, sample(3, 1))
+            .expect("Failed to record sample");
+        let hours = store
+            .list(None)
+            .into_iter()
+            .map(|rollup| rollup.hour)
+            .collect::<Vec<_>>();
+        assert_eq!(hours, vec![7200, 4 * 3600]);
+
+        let reopened = StatsStore::open(&path, 2).expect("Failed to reopen store");
+        assert_eq!(reopened.list(None), store.list(None));
+    }
+}
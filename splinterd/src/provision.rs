@@ -0,0 +1,610 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! First-run provisioning for a splinterd node.
+//!
+//! Provisioning creates everything a new node needs before it can start: the node's signing key,
+//! a CA and the TLS certificates signed by it, a starter config file, this node's entry in the
+//! local registry, and an up-to-date database. Each step is skipped if its output already exists,
+//! so provisioning can be run again safely; existing keys, certificates and config files are
+//! never overwritten.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey};
+use diesel::{pg::PgConnection, sqlite::SqliteConnection, Connection};
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, PKeyRef, Private};
+use openssl::rsa::Rsa;
+use openssl::x509::extension::{BasicConstraints, ExtendedKeyUsage, KeyUsage};
+use openssl::x509::{X509NameBuilder, X509Ref, X509};
+use splinter::migrations::{run_postgres_migrations, run_sqlite_migrations};
+#[cfg(not(feature = "registry-database"))]
+use splinter::registry::LocalYamlRegistry;
+use splinter::registry::{Node, RegistryReader, RegistryWriter, RwRegistry};
+use splinter::store::ConnectionUri;
+
+use crate::config::Config;
+
+/// The file name, relative to the config directory, of the node key created when no
+/// `node_key_file` is configured.
+const NODE_KEY_FILE: &str = "keys/splinterd.priv";
+/// The file name, relative to the TLS certificate directory, of the generated CA's private key.
+const CA_KEY_FILE: &str = "private/ca.key";
+/// The number of days the generated certificates are valid for.
+const CERT_VALIDITY_DAYS: u32 = 365;
+
+/// Provisions the node described by the given config.
+///
+/// The state directory must already exist and hold the node's ID. Returns a description of each
+/// step that was performed or skipped.
+pub fn provision(
+    config: &Config,
+    config_file: &Path,
+    node_id: &str,
+    display_name: &str,
+) -> Result<Vec<String>, ProvisionError> {
+    let mut report = vec![];
+
+    let private_key_path = node_key_path(config);
+    let public_key_path = private_key_path.with_extension("pub");
+    let (public_key, created) = ensure_node_key(&private_key_path, &public_key_path)?;
+    report.push(describe(created, "node key", &private_key_path));
+
+    if config.no_tls() {
+        report.push("TLS is disabled, skipped certificates".into());
+    } else {
+        let paths = CertPaths {
+            ca_cert: PathBuf::from(config.tls_ca_file()),
+            ca_key: Path::new(config.tls_cert_dir()).join(CA_KEY_FILE),
+            client_cert: PathBuf::from(config.tls_client_cert()),
+            client_key: PathBuf::from(config.tls_client_key()),
+            server_cert: PathBuf::from(config.tls_server_cert()),
+            server_key: PathBuf::from(config.tls_server_key()),
+        };
+        let created = ensure_certs(&paths, node_id)?;
+        report.push(describe(created, "TLS certificates", &paths.server_cert));
+    }
+
+    let created = ensure_starter_config(config, config_file, node_id, display_name)?;
+    report.push(describe(created, "config file", config_file));
+
+    report.push(run_migrations(config.database())?);
+
+    let endpoints = if config.advertised_endpoints().is_empty() {
+        config.network_endpoints()
+    } else {
+        config.advertised_endpoints()
+    };
+    let node = Node::builder(node_id)
+        .with_endpoints(endpoints.to_vec())
+        .with_display_name(display_name)
+        .with_key(public_key)
+        .build()
+        .map_err(|err| ProvisionError::RegistryError(err.to_string()))?;
+    let registry = open_local_registry(config)?;
+    if ensure_registry_entry(&*registry, node)? {
+        report.push(format!("added node {} to the local registry", node_id));
+    } else {
+        report.push(format!(
+            "node {} is already in the local registry, skipped",
+            node_id
+        ));
+    }
+
+    Ok(report)
+}
+
+fn describe(created: bool, what: &str, path: &Path) -> String {
+    if created {
+        format!("created {} {}", what, path.display())
+    } else {
+        format!("{} {} already exists, skipped", what, path.display())
+    }
+}
+
+/// Creates the config, state and TLS certificate directories, if they do not exist.
+pub fn create_directories(config: &Config) -> Result<(), ProvisionError> {
+    let mut dirs = vec![
+        PathBuf::from(config.config_dir()),
+        PathBuf::from(config.state_dir()),
+    ];
+    if !config.no_tls() {
+        dirs.push(Path::new(config.tls_cert_dir()).join("private"));
+    }
+
+    for dir in dirs {
+        fs::create_dir_all(&dir).map_err(|err| {
+            ProvisionError::io(format!("unable to create directory {}", dir.display()), err)
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "node-challenge")]
+fn node_key_path(config: &Config) -> PathBuf {
+    config
+        .node_key_file()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(config.config_dir()).join(NODE_KEY_FILE))
+}
+
+#[cfg(not(feature = "node-challenge"))]
+fn node_key_path(config: &Config) -> PathBuf {
+    Path::new(config.config_dir()).join(NODE_KEY_FILE)
+}
+
+/// Generates a secp256k1 key pair for the node, unless the private key file already exists.
+///
+/// Returns the node's public key in hex, and whether the key pair was created.
+fn ensure_node_key(
+    private_key_path: &Path,
+    public_key_path: &Path,
+) -> Result<(String, bool), ProvisionError> {
+    let context = Secp256k1Context::new();
+
+    if private_key_path.exists() {
+        let private_key = fs::read_to_string(private_key_path)
+            .map_err(|err| {
+                ProvisionError::io(
+                    format!("unable to read {}", private_key_path.display()),
+                    err,
+                )
+            })
+            .and_then(|hex| {
+                PrivateKey::new_from_hex(hex.trim())
+                    .map_err(|err| ProvisionError::KeyError(err.to_string()))
+            })?;
+        let public_key = context
+            .get_public_key(&private_key)
+            .map_err(|err| ProvisionError::KeyError(err.to_string()))?;
+        return Ok((public_key.as_hex(), false));
+    }
+
+    let private_key = context.new_random_private_key();
+    let public_key = context
+        .get_public_key(&private_key)
+        .map_err(|err| ProvisionError::KeyError(err.to_string()))?;
+
+    if let Some(key_dir) = private_key_path.parent() {
+        fs::create_dir_all(key_dir).map_err(|err| {
+            ProvisionError::io(
+                format!("unable to create directory {}", key_dir.display()),
+                err,
+            )
+        })?;
+    }
+    write_file(
+        public_key_path,
+        format!("{}\n", public_key.as_hex()).as_bytes(),
+        0o644,
+    )?;
+    write_file(
+        private_key_path,
+        format!("{}\n", private_key.as_hex()).as_bytes(),
+        0o600,
+    )?;
+
+    Ok((public_key.as_hex(), true))
+}
+
+struct CertPaths {
+    ca_cert: PathBuf,
+    ca_key: PathBuf,
+    client_cert: PathBuf,
+    client_key: PathBuf,
+    server_cert: PathBuf,
+    server_key: PathBuf,
+}
+
+/// Generates a CA and the client and server certificates signed by it, unless the server
+/// certificate already exists.
+///
+/// An existing CA is reused if the server or client certificate is missing, so certificates
+/// issued to other nodes stay valid. Returns whether any certificates were created.
+fn ensure_certs(paths: &CertPaths, node_id: &str) -> Result<bool, ProvisionError> {
+    if paths.server_cert.exists() && paths.client_cert.exists() {
+        return Ok(false);
+    }
+
+    let (ca_key, ca_cert) = if paths.ca_cert.exists() && paths.ca_key.exists() {
+        let ca_key = read_file(&paths.ca_key)?;
+        let ca_cert = read_file(&paths.ca_cert)?;
+        (
+            PKey::private_key_from_pem(&ca_key)?,
+            X509::from_pem(&ca_cert)?,
+        )
+    } else {
+        let (ca_key, ca_cert) = make_ca_cert()?;
+        write_file(&paths.ca_key, &ca_key.private_key_to_pem_pkcs8()?, 0o600)?;
+        write_file(&paths.ca_cert, &ca_cert.to_pem()?, 0o644)?;
+        (ca_key, ca_cert)
+    };
+
+    for (cert_path, key_path, common_name) in &[
+        (
+            &paths.server_cert,
+            &paths.server_key,
+            format!("{}-server", node_id),
+        ),
+        (
+            &paths.client_cert,
+            &paths.client_key,
+            format!("{}-client", node_id),
+        ),
+    ] {
+        if cert_path.exists() {
+            continue;
+        }
+        let (key, cert) = make_ca_signed_cert(&ca_cert, &ca_key, common_name)?;
+        write_file(key_path, &key.private_key_to_pem_pkcs8()?, 0o600)?;
+        write_file(cert_path, &cert.to_pem()?, 0o644)?;
+    }
+
+    Ok(true)
+}
+
+fn make_ca_cert() -> Result<(PKey<Private>, X509), ErrorStack> {
+    let privkey = PKey::from_rsa(Rsa::generate(2048)?)?;
+
+    let mut x509_name = X509NameBuilder::new()?;
+    x509_name.append_entry_by_text("CN", "generated_ca")?;
+    let x509_name = x509_name.build();
+
+    let mut cert_builder = X509::builder()?;
+    cert_builder.set_version(2)?;
+    cert_builder.set_subject_name(&x509_name)?;
+    cert_builder.set_issuer_name(&x509_name)?;
+    cert_builder.set_pubkey(&privkey)?;
+    cert_builder.set_not_before(&*Asn1Time::days_from_now(0)?)?;
+    cert_builder.set_not_after(&*Asn1Time::days_from_now(CERT_VALIDITY_DAYS)?)?;
+    cert_builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+    cert_builder.append_extension(KeyUsage::new().key_cert_sign().build()?)?;
+    cert_builder.sign(&privkey, MessageDigest::sha256())?;
+
+    Ok((privkey, cert_builder.build()))
+}
+
+// The certificate may be used for both server and client authentication
+fn make_ca_signed_cert(
+    ca_cert: &X509Ref,
+    ca_privkey: &PKeyRef<Private>,
+    common_name: &str,
+) -> Result<(PKey<Private>, X509), ErrorStack> {
+    let privkey = PKey::from_rsa(Rsa::generate(2048)?)?;
+
+    let mut x509_name = X509NameBuilder::new()?;
+    x509_name.append_entry_by_text("CN", common_name)?;
+    let x509_name = x509_name.build();
+
+    let serial_number = {
+        let mut serial = BigNum::new()?;
+        serial.rand(159, MsbOption::MAYBE_ZERO, false)?;
+        serial.to_asn1_integer()?
+    };
+
+    let mut cert_builder = X509::builder()?;
+    cert_builder.set_version(2)?;
+    cert_builder.set_serial_number(&serial_number)?;
+    cert_builder.set_subject_name(&x509_name)?;
+    cert_builder.set_issuer_name(ca_cert.subject_name())?;
+    cert_builder.set_pubkey(&privkey)?;
+    cert_builder.set_not_before(&*Asn1Time::days_from_now(0)?)?;
+    cert_builder.set_not_after(&*Asn1Time::days_from_now(CERT_VALIDITY_DAYS)?)?;
+    cert_builder.append_extension(
+        ExtendedKeyUsage::new()
+            .server_auth()
+            .client_auth()
+            .build()?,
+    )?;
+    cert_builder.sign(ca_privkey, MessageDigest::sha256())?;
+
+    Ok((privkey, cert_builder.build()))
+}
+
+/// The settings written to a new node's config file.
+#[derive(Serialize)]
+struct StarterConfig<'a> {
+    version: &'a str,
+    node_id: &'a str,
+    display_name: &'a str,
+    network_endpoints: &'a [String],
+    rest_api_endpoint: &'a str,
+    database: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tls_cert_dir: Option<&'a str>,
+    registries: &'a [String],
+    #[cfg(feature = "node-challenge")]
+    node_key_file: String,
+}
+
+/// Writes a config file for the node, unless the file already exists.
+///
+/// Returns whether the file was created.
+fn ensure_starter_config(
+    config: &Config,
+    config_file: &Path,
+    node_id: &str,
+    display_name: &str,
+) -> Result<bool, ProvisionError> {
+    if config_file.exists() {
+        return Ok(false);
+    }
+
+    let starter = StarterConfig {
+        version: "1",
+        node_id,
+        display_name,
+        network_endpoints: config.network_endpoints(),
+        rest_api_endpoint: config.rest_api_endpoint(),
+        database: config.database(),
+        tls_cert_dir: if config.no_tls() {
+            None
+        } else {
+            Some(config.tls_cert_dir())
+        },
+        registries: config.registries(),
+        #[cfg(feature = "node-challenge")]
+        node_key_file: node_key_path(config).display().to_string(),
+    };
+    let contents =
+        toml::to_string(&starter).map_err(|err| ProvisionError::ConfigError(err.to_string()))?;
+
+    write_file(
+        config_file,
+        format!("# Generated by splinterd --provision\n{}", contents).as_bytes(),
+        0o644,
+    )?;
+
+    Ok(true)
+}
+
+/// Runs the database migrations for the configured database; this is a no-op for migrations that
+/// have already been applied.
+fn run_migrations(database: &str) -> Result<String, ProvisionError> {
+    let connection_uri = database
+        .parse()
+        .map_err(|err| ProvisionError::MigrationError(format!("invalid database URL: {}", err)))?;
+
+    match connection_uri {
+        ConnectionUri::Memory => Ok("in-memory database, skipped migrations".into()),
+        ConnectionUri::Postgres(url) => {
+            let connection = PgConnection::establish(&url).map_err(|err| {
+                ProvisionError::MigrationError(format!(
+                    "unable to connect to database {}: {}",
+                    url, err
+                ))
+            })?;
+            run_postgres_migrations(&connection)
+                .map_err(|err| ProvisionError::MigrationError(err.to_string()))?;
+            Ok(format!("migrated database {}", url))
+        }
+        ConnectionUri::Sqlite(path) => {
+            let connection = SqliteConnection::establish(&path).map_err(|err| {
+                ProvisionError::MigrationError(format!("unable to open database {}: {}", path, err))
+            })?;
+            run_sqlite_migrations(&connection)
+                .map_err(|err| ProvisionError::MigrationError(err.to_string()))?;
+            Ok(format!("migrated database {}", path))
+        }
+    }
+}
+
+#[cfg(not(feature = "registry-database"))]
+fn open_local_registry(config: &Config) -> Result<Box<dyn RwRegistry>, ProvisionError> {
+    let path = Path::new(config.state_dir()).join("local_registry.yaml");
+    let registry = LocalYamlRegistry::new(&path.display().to_string())
+        .map_err(|err| ProvisionError::RegistryError(err.to_string()))?;
+    Ok(Box::new(registry))
+}
+
+#[cfg(feature = "registry-database")]
+fn open_local_registry(config: &Config) -> Result<Box<dyn RwRegistry>, ProvisionError> {
+    let connection_uri = config
+        .database()
+        .parse()
+        .map_err(|err| ProvisionError::RegistryError(format!("invalid database URL: {}", err)))?;
+    let store_factory = splinter::store::create_store_factory(connection_uri)
+        .map_err(|err| ProvisionError::RegistryError(err.to_string()))?;
+    Ok(store_factory.get_registry_store())
+}
+
+/// Adds the node to the registry, unless an identical entry is already present.
+///
+/// Returns whether the registry was changed.
+fn ensure_registry_entry(registry: &dyn RwRegistry, node: Node) -> Result<bool, ProvisionError> {
+    let existing = registry
+        .fetch_node(&node.identity)
+        .map_err(|err| ProvisionError::RegistryError(err.to_string()))?;
+    if existing.as_ref() == Some(&node) {
+        return Ok(false);
+    }
+
+    registry
+        .insert_node(node)
+        .map_err(|err| ProvisionError::RegistryError(err.to_string()))?;
+    Ok(true)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ProvisionError> {
+    fs::read(path)
+        .map_err(|err| ProvisionError::io(format!("unable to read {}", path.display()), err))
+}
+
+/// Writes the file via a temporary file, so an interrupted write cannot leave a partial file
+/// behind to be skipped on the next run.
+fn write_file(path: &Path, contents: &[u8], mode: u32) -> Result<(), ProvisionError> {
+    let temp_path = path.with_extension("provision.tmp");
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(&temp_path)
+        .and_then(|mut file| file.write_all(contents))
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|err| ProvisionError::io(format!("unable to write {}", path.display()), err))
+}
+
+#[derive(Debug)]
+pub enum ProvisionError {
+    CertError(ErrorStack),
+    ConfigError(String),
+    KeyError(String),
+    MigrationError(String),
+    RegistryError(String),
+    IoError { context: String, source: io::Error },
+}
+
+impl ProvisionError {
+    fn io(context: String, source: io::Error) -> Self {
+        ProvisionError::IoError { context, source }
+    }
+}
+
+impl Error for ProvisionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProvisionError::CertError(source) => Some(source),
+            ProvisionError::IoError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProvisionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProvisionError::CertError(err) => {
+                write!(f, "unable to generate TLS certificates: {}", err)
+            }
+            ProvisionError::ConfigError(msg) => write!(f, "unable to write config file: {}", msg),
+            ProvisionError::KeyError(msg) => write!(f, "invalid node key: {}", msg),
+            ProvisionError::MigrationError(msg) => {
+                write!(f, "unable to run database migrations: {}", msg)
+            }
+            ProvisionError::RegistryError(msg) => {
+                write!(f, "unable to update the local registry: {}", msg)
+            }
+            ProvisionError::IoError { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl From<ErrorStack> for ProvisionError {
+    fn from(err: ErrorStack) -> Self {
+        ProvisionError::CertError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that the node key is created on the first run, and that later runs keep the
+    /// existing key and report the same public key.
+    #[test]
+    fn test_ensure_node_key() {
+        let temp_dir = TempDir::new("test_ensure_node_key").expect("Failed to create temp dir");
+        let private_key_path = temp_dir.path().join("keys/splinterd.priv");
+        let public_key_path = temp_dir.path().join("keys/splinterd.pub");
+
+        let (public_key, created) =
+            ensure_node_key(&private_key_path, &public_key_path).expect("Failed to create key");
+        assert!(created);
+        assert_eq!(
+            fs::read_to_string(&public_key_path)
+                .expect("Failed to read public key")
+                .trim(),
+            public_key
+        );
+
+        let (existing_public_key, created) =
+            ensure_node_key(&private_key_path, &public_key_path).expect("Failed to check key");
+        assert!(!created);
+        assert_eq!(existing_public_key, public_key);
+    }
+
+    /// Verify that the CA and certificates are created on the first run, that later runs leave
+    /// them untouched, and that a missing client certificate is reissued by the existing CA.
+    #[test]
+    fn test_ensure_certs() {
+        let temp_dir = TempDir::new("test_ensure_certs").expect("Failed to create temp dir");
+        fs::create_dir_all(temp_dir.path().join("private")).expect("Failed to create private dir");
+        let paths = CertPaths {
+            ca_cert: temp_dir.path().join("ca.pem"),
+            ca_key: temp_dir.path().join(CA_KEY_FILE),
+            client_cert: temp_dir.path().join("client.crt"),
+            client_key: temp_dir.path().join("private/client.key"),
+            server_cert: temp_dir.path().join("server.crt"),
+            server_key: temp_dir.path().join("private/server.key"),
+        };
+
+        assert!(ensure_certs(&paths, "test-node").expect("Failed to create certs"));
+        let ca_cert = fs::read(&paths.ca_cert).expect("Failed to read CA cert");
+        let server_cert = fs::read(&paths.server_cert).expect("Failed to read server cert");
+
+        assert!(!ensure_certs(&paths, "test-node").expect("Failed to check certs"));
+
+        fs::remove_file(&paths.client_cert).expect("Failed to remove client cert");
+        assert!(ensure_certs(&paths, "test-node").expect("Failed to reissue client cert"));
+        assert!(paths.client_cert.exists());
+        assert_eq!(
+            fs::read(&paths.ca_cert).expect("Failed to read CA cert"),
+            ca_cert
+        );
+        assert_eq!(
+            fs::read(&paths.server_cert).expect("Failed to read server cert"),
+            server_cert
+        );
+    }
+
+    /// Verify that the node is added to an empty registry, and that provisioning the same node
+    /// again leaves the registry unchanged.
+    #[cfg(not(feature = "registry-database"))]
+    #[test]
+    fn test_ensure_registry_entry() {
+        let temp_dir =
+            TempDir::new("test_ensure_registry_entry").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("local_registry.yaml");
+        let registry =
+            LocalYamlRegistry::new(&path.display().to_string()).expect("Failed to create registry");
+        let node = Node::builder("test-node")
+            .with_endpoint("tcps://127.0.0.1:8044")
+            .with_key("0123")
+            .build()
+            .expect("Failed to build node");
+
+        assert!(ensure_registry_entry(&registry, node.clone()).expect("Failed to add node"));
+        assert!(!ensure_registry_entry(&registry, node.clone()).expect("Failed to check node"));
+        assert_eq!(
+            registry
+                .fetch_node("test-node")
+                .expect("Failed to fetch node"),
+            Some(node)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Credentials for fetching remote registries from authenticated endpoints.
+//!
+//! The credentials themselves are kept out of the config file: each `[[registry_auth]]` table
+//! references the secrets files that hold them, which are read whenever the remote registries are
+//! (re)created.
+
+use std::fs;
+
+use splinter::registry::RemoteYamlRegistryAuth;
+
+#[cfg(feature = "secrets")]
+use crate::secrets::SecretResolver;
+
+/// The credentials for remote registries, as defined in a `[[registry_auth]]` table of the config
+/// file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RegistryAuthConfig {
+    /// The credentials are presented when fetching any registry whose URL starts with this prefix
+    pub url: String,
+    /// Path of a secrets file holding a bearer token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer_token_file: Option<String>,
+    /// Path of a PKCS #12 archive holding the client certificate and its private key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate_file: Option<String>,
+    /// Path of a secrets file holding the password of the client certificate archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate_password_file: Option<String>,
+}
+
+impl RegistryAuthConfig {
+    /// Reads the referenced secrets files and returns the credentials to present.
+    pub fn load(&self) -> Result<RemoteYamlRegistryAuth, String> {
+        match (&self.bearer_token_file, &self.client_certificate_file) {
+            (Some(token_file), None) => Ok(RemoteYamlRegistryAuth::BearerToken(read_secret(
+                token_file,
+            )?)),
+            (None, Some(certificate_file)) => {
+                let pkcs12 = fs::read(certificate_file).map_err(|err| {
+                    format!(
+                        "unable to read client certificate {}: {}",
+                        certificate_file, err
+                    )
+                })?;
+                let password = self
+                    .client_certificate_password_file
+                    .as_deref()
+                    .map(read_secret)
+                    .transpose()?
+                    .unwrap_or_default();
+                Ok(RemoteYamlRegistryAuth::ClientCertificate { pkcs12, password })
+            }
+            _ => Err(format!(
+                "registry_auth for {} must have exactly one of bearer_token_file or \
+                 client_certificate_file",
+                self.url
+            )),
+        }
+    }
+}
+
+/// Returns the credentials to present when fetching the registry at `url`. If more than one
+/// `[[registry_auth]]` table matches, the one with the longest URL prefix is used.
+pub fn find_registry_auth<'a>(
+    configs: &'a [RegistryAuthConfig],
+    url: &str,
+) -> Option<&'a RegistryAuthConfig> {
+    configs
+        .iter()
+        .filter(|config| url.starts_with(&config.url))
+        .max_by_key(|config| config.url.len())
+}
+
+/// Reads a secret, ignoring surrounding whitespace such as a trailing newline.
+///
+/// The path may also be a secret reference, such as a `vault://` URI, which is resolved each time
+/// the credentials are loaded so rotated secrets are picked up when the registries are reloaded.
+fn read_secret(path: &str) -> Result<String, String> {
+    #[cfg(feature = "secrets")]
+    {
+        if SecretResolver::is_reference(path) {
+            return SecretResolver::from_env()
+                .and_then(|resolver| resolver.resolve(path))
+                .map_err(|err| err.to_string());
+        }
+    }
+
+    let secret = fs::read_to_string(path)
+        .map_err(|err| format!("unable to read secrets file {}: {}", path, err))?;
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return Err(format!("secrets file {} is empty", path));
+    }
+
+    Ok(secret.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    fn config(url: &str, bearer_token_file: Option<String>) -> RegistryAuthConfig {
+        RegistryAuthConfig {
+            url: url.into(),
+            bearer_token_file,
+            client_certificate_file: None,
+            client_certificate_password_file: None,
+        }
+    }
+
+    /// Verifies that the credentials with the longest matching URL prefix are selected, and that
+    /// no credentials are selected for a URL that doesn't match any prefix.
+    #[test]
+    fn test_find_registry_auth() {
+        let configs = vec![
+            config("https://artifacts.example.com/", None),
+            config("https://artifacts.example.com/splinter/", None),
+        ];
+
+        assert_eq!(
+            find_registry_auth(
+                &configs,
+                "https://artifacts.example.com/splinter/registry.yaml"
+            ),
+            Some(&configs[1])
+        );
+        assert_eq!(
+            find_registry_auth(&configs, "https://artifacts.example.com/other.yaml"),
+            Some(&configs[0])
+        );
+        assert_eq!(
+            find_registry_auth(&configs, "https://example.com/registry.yaml"),
+            None
+        );
+    }
+
+    /// Verifies that a bearer token is read from its secrets file without surrounding whitespace,
+    /// and that a config without exactly one kind of credential is rejected.
+    #[test]
+    fn test_load_bearer_token() {
+        let temp_dir = TempDir::new("test_load_bearer_token").expect("Failed to create temp dir");
+        let token_file = temp_dir.path().join("token");
+        fs::write(&token_file, "secret-token\n").expect("Failed to write token");
+        let token_file = token_file.to_str().expect("Invalid path").to_string();
+
+        match config("https://example.com/", Some(token_file.clone())).load() {
+            Ok(RemoteYamlRegistryAuth::BearerToken(token)) => assert_eq!(token, "secret-token"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        assert!(config("https://example.com/", None).load().is_err());
+
+        let mut both = config("https://example.com/", Some(token_file));
+        both.client_certificate_file = Some("client.p12".into());
+        assert!(both.load().is_err());
+    }
+}
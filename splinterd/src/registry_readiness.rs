@@ -0,0 +1,92 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports the node as not ready while any of its read-only registries can't be loaded.
+
+use health::readiness::ReadinessCheck;
+use splinter::registry::UnifiedRegistry;
+
+/// Fails while any read-only registry source's most recent refresh failed, since the node may be
+/// missing peers that are only defined in that registry.
+pub struct RegistryReadinessCheck {
+    registry: UnifiedRegistry,
+}
+
+impl RegistryReadinessCheck {
+    pub fn new(registry: UnifiedRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl ReadinessCheck for RegistryReadinessCheck {
+    fn name(&self) -> &str {
+        "registry"
+    }
+
+    fn problems(&self) -> Vec<String> {
+        match self.registry.source_statuses() {
+            Ok(statuses) => statuses
+                .into_iter()
+                .filter(|status| status.read_only)
+                .filter_map(|status| {
+                    status
+                        .last_error
+                        .map(|err| format!("{} failed to refresh: {}", status.location, err))
+                })
+                .collect(),
+            Err(err) => vec![format!("Unable to get registry source statuses: {}", err)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use splinter::registry::{LocalYamlRegistry, RegistryReader};
+    use tempdir::TempDir;
+
+    /// Verifies that the check passes while every read-only registry can be read, and reports the
+    /// registry whose backing file has become unavailable.
+    #[test]
+    fn test_registry_readiness() {
+        let temp_dir = TempDir::new("test_registry_readiness").expect("Failed to create temp dir");
+        let internal_path = temp_dir.path().join("internal.yaml");
+        let read_only_path = temp_dir.path().join("read_only.yaml");
+
+        let internal = LocalYamlRegistry::new(internal_path.to_str().expect("Invalid path"))
+            .expect("Failed to create internal registry");
+        let read_only = LocalYamlRegistry::new(read_only_path.to_str().expect("Invalid path"))
+            .expect("Failed to create read-only registry");
+
+        let check = RegistryReadinessCheck::new(UnifiedRegistry::new(
+            Box::new(internal),
+            vec![Box::new(read_only)],
+        ));
+        assert!(check.problems().is_empty());
+
+        fs::remove_file(&read_only_path).expect("Failed to remove read-only registry");
+        // Reading the registry records the failure to read the backing file
+        check
+            .registry
+            .list_nodes(&[])
+            .expect("Failed to list nodes");
+
+        let problems = check.problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with(read_only_path.to_str().expect("Invalid path")));
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watches the resources the node depends on, degrading the node to read-only mode before they
+//! run out.
+//!
+//! The [`ResourceWatchdog`] periodically checks the free space of the directories the node stores
+//! its state in, that the admin service store can be reached, and how many file descriptors the
+//! process has open. While any check fails, the node is degraded: it is kept in read-only mode, so
+//! that requests that change state are refused, the intake of circuit direct messages is paused,
+//! and scabbard services stop committing batches, so that writes don't start failing part way
+//! through. The problems found are kept in a [`ResourceStatus`], which the readiness check and the
+//! alert engine report from.
+//!
+//! [`ResourceWatchdog`]: struct.ResourceWatchdog.html
+//! [`ResourceStatus`]: struct.ResourceStatus.html
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(feature = "health")]
+use health::readiness::ReadinessCheck;
+use scabbard::service::CommitPause;
+use splinter::admin::store::AdminServiceStore;
+use splinter::circuit::intake::IntakePause;
+use splinter::rest_api::read_only::ReadOnly;
+
+/// How often the node's resources are checked
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The free disk space, in megabytes, below which the node is degraded to read-only mode
+pub const DEFAULT_MIN_FREE_SPACE_MB: u64 = 512;
+
+/// The percentage of the open file limit above which the node is degraded to read-only mode
+const MAX_FD_USAGE_PERCENT: u64 = 90;
+
+const MB: u64 = 1024 * 1024;
+
+/// A resource that has run low or can't be reached
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceProblem {
+    /// The directory, store or limit the problem was found with
+    pub resource: String,
+    pub message: String,
+}
+
+/// The problems found by the latest check, shared with the readiness check and the alert engine
+#[derive(Clone, Default)]
+pub struct ResourceStatus {
+    problems: Arc<Mutex<Vec<ResourceProblem>>>,
+}
+
+impl ResourceStatus {
+    /// Returns the problems found by the latest check
+    pub fn problems(&self) -> Vec<ResourceProblem> {
+        match self.problems.lock() {
+            Ok(problems) => problems.clone(),
+            Err(_) => {
+                error!("Unable to get resource problems: lock poisoned");
+                vec![]
+            }
+        }
+    }
+
+    /// Replaces the problems, logging the ones that weren't found by the previous check
+    fn update(&self, problems: Vec<ResourceProblem>) {
+        let mut current = match self.problems.lock() {
+            Ok(current) => current,
+            Err(_) => {
+                error!("Unable to update resource problems: lock poisoned");
+                return;
+            }
+        };
+
+        for problem in problems.iter().filter(|problem| !current.contains(problem)) {
+            warn!("{}", problem.message);
+        }
+
+        *current = problems;
+    }
+}
+
+/// Periodically checks the node's resources, keeping the node degraded while any of them has run
+/// low.
+pub struct ResourceWatchdog {
+    join_handle: thread::JoinHandle<()>,
+    shutdown_signaler: ResourceWatchdogShutdownSignaler,
+}
+
+impl ResourceWatchdog {
+    /// Starts the watchdog.
+    ///
+    /// # Arguments
+    ///
+    /// * `directories` - The directories whose free space is checked; a directory that doesn't
+    ///   exist yet, such as the scabbard state directory before the first service is created, is
+    ///   checked through its nearest existing parent
+    /// * `min_free_space` - The free space, in megabytes, each directory must have
+    /// * `admin_store` - The store that must be reachable
+    /// * `read_only` - The node's read-only mode, enabled while a check fails
+    /// * `intake_pause` - Pauses the intake of circuit direct messages while a check fails
+    /// * `commit_pause` - Pauses the commits of scabbard services while a check fails
+    /// * `status` - Where the problems found are kept
+    /// * `interval` - How often the resources are checked
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        mut directories: Vec<PathBuf>,
+        min_free_space: u64,
+        admin_store: Box<dyn AdminServiceStore>,
+        read_only: ReadOnly,
+        intake_pause: IntakePause,
+        commit_pause: CommitPause,
+        status: ResourceStatus,
+        interval: Duration,
+    ) -> Result<Self, std::io::Error> {
+        directories.sort();
+        directories.dedup();
+        let min_free_space = min_free_space.saturating_mul(MB);
+
+        let (sender, receiver) = channel();
+        let join_handle = thread::Builder::new()
+            .name("ResourceWatchdog".into())
+            .spawn(move || {
+                let mut degraded = false;
+                // Only lift read-only mode if the watchdog enabled it; an operator may have
+                // enabled it for other reasons
+                let mut enabled_read_only = false;
+
+                loop {
+                    let mut problems = check_free_space(&directories, min_free_space);
+                    problems.extend(check_store(&*admin_store));
+                    problems
+                        .extend(fd_usage().and_then(|(open, limit)| check_fd_usage(open, limit)));
+
+                    if problems.is_empty() {
+                        if degraded {
+                            info!("Node resources have recovered");
+                            if enabled_read_only {
+                                read_only.set_enabled(false);
+                                enabled_read_only = false;
+                            }
+                            intake_pause.set_paused(false);
+                            commit_pause.set_paused(false);
+                            degraded = false;
+                        }
+                    } else if !degraded {
+                        warn!("Node resources are running low; degrading the node");
+                        if !read_only.is_enabled() {
+                            read_only.set_enabled(true);
+                            enabled_read_only = true;
+                        }
+                        intake_pause.set_paused(true);
+                        commit_pause.set_paused(true);
+                        degraded = true;
+                    }
+                    status.update(problems);
+
+                    match receiver.recv_timeout(interval) {
+                        Err(RecvTimeoutError::Timeout) => (),
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                            debug!("Stopping resource watchdog");
+                            return;
+                        }
+                    }
+                }
+            })?;
+
+        Ok(ResourceWatchdog {
+            join_handle,
+            shutdown_signaler: ResourceWatchdogShutdownSignaler { sender },
+        })
+    }
+
+    /// Returns a signaler that can be used to stop the watchdog
+    pub fn shutdown_signaler(&self) -> ResourceWatchdogShutdownSignaler {
+        self.shutdown_signaler.clone()
+    }
+
+    /// Waits for the watchdog thread to exit once shutdown has been signaled
+    pub fn await_shutdown(self) {
+        if self.join_handle.join().is_err() {
+            error!("Resource watchdog thread did not shutdown correctly");
+        }
+    }
+}
+
+/// Signals a `ResourceWatchdog` to stop
+#[derive(Clone)]
+pub struct ResourceWatchdogShutdownSignaler {
+    sender: Sender<()>,
+}
+
+impl ResourceWatchdogShutdownSignaler {
+    pub fn shutdown(&self) {
+        if self.sender.send(()).is_err() {
+            debug!("Resource watchdog has already stopped");
+        }
+    }
+}
+
+/// Reports the node as not ready while any of its resources has run low.
+#[cfg(feature = "health")]
+pub struct ResourceReadinessCheck {
+    status: ResourceStatus,
+}
+
+#[cfg(feature = "health")]
+impl ResourceReadinessCheck {
+    pub fn new(status: ResourceStatus) -> Self {
+        Self { status }
+    }
+}
+
+#[cfg(feature = "health")]
+impl ReadinessCheck for ResourceReadinessCheck {
+    fn name(&self) -> &str {
+        "resources"
+    }
+
+    fn problems(&self) -> Vec<String> {
+        self.status
+            .problems()
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect()
+    }
+}
+
+/// Returns a problem for each directory with less than `min_free_space` bytes available; a
+/// directory that doesn't exist is checked through its nearest existing parent, since that is the
+/// file system it will be created on
+fn check_free_space(directories: &[PathBuf], min_free_space: u64) -> Vec<ResourceProblem> {
+    directories
+        .iter()
+        .filter_map(|dir| {
+            dir.ancestors()
+                .find(|ancestor| ancestor.exists())
+                .map(|existing| (dir, existing))
+        })
+        .filter_map(|(dir, existing)| match fs2::available_space(existing) {
+            Ok(available) if available < min_free_space => Some(ResourceProblem {
+                resource: dir.display().to_string(),
+                message: format!(
+                    "{} has {}MB of free space, less than the minimum of {}MB",
+                    dir.display(),
+                    available / MB,
+                    min_free_space / MB
+                ),
+            }),
+            Ok(_) => None,
+            Err(err) => Some(ResourceProblem {
+                resource: dir.display().to_string(),
+                message: format!("Unable to get the free space of {}: {}", dir.display(), err),
+            }),
+        })
+        .collect()
+}
+
+/// Returns a problem if the admin service store can't be read
+fn check_store(admin_store: &dyn AdminServiceStore) -> Option<ResourceProblem> {
+    admin_store
+        .get_circuit("")
+        .err()
+        .map(|err| ResourceProblem {
+            resource: "admin-store".into(),
+            message: format!("Unable to reach the admin service store: {}", err),
+        })
+}
+
+/// Returns a problem if more than `MAX_FD_USAGE_PERCENT` of the open file limit is in use
+fn check_fd_usage(open: u64, limit: u64) -> Option<ResourceProblem> {
+    if limit == 0 || open * 100 <= limit * MAX_FD_USAGE_PERCENT {
+        return None;
+    }
+
+    Some(ResourceProblem {
+        resource: "file-descriptors".into(),
+        message: format!(
+            "{} of the {} file descriptors the process may open are in use",
+            open, limit
+        ),
+    })
+}
+
+/// Returns the number of file descriptors the process has open and its limit on open files, if
+/// they can be read from `/proc`
+fn fd_usage() -> Option<(u64, u64)> {
+    let open = fs::read_dir("/proc/self/fd").ok()?.count() as u64;
+    let limits = fs::read_to_string("/proc/self/limits").ok()?;
+    Some((open, parse_open_file_limit(&limits)?))
+}
+
+/// Parses the soft limit of the "Max open files" line of `/proc/self/limits`; returns `None` if
+/// the line is missing or the limit is unlimited
+fn parse_open_file_limit(limits: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find(|line| line.starts_with("Max open files"))?
+        .trim_start_matches("Max open files")
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifies that the open file limit is parsed from the process limits, and that the file
+    /// descriptor check only fails past the threshold.
+    #[test]
+    fn test_fd_usage() {
+        let limits = "\
+Limit                     Soft Limit           Hard Limit           Units
+Max cpu time              unlimited            unlimited            seconds
+Max open files            1024                 524288               files
+Max locked memory         8388608              8388608              bytes
+";
+        assert_eq!(parse_open_file_limit(limits), Some(1024));
+        assert_eq!(
+            parse_open_file_limit("Max open files            unlimited            unlimited"),
+            None
+        );
+
+        assert!(check_fd_usage(900, 1024).is_none());
+        let problem = check_fd_usage(1000, 1024).expect("Expected a problem");
+        assert_eq!(problem.resource, "file-descriptors");
+        assert!(check_fd_usage(10, 0).is_none());
+    }
+
+    /// Verifies that a directory is reported when its free space is below the minimum, and that
+    /// a directory that doesn't exist yet is checked through its parent instead of being skipped.
+    #[test]
+    fn test_check_free_space() {
+        let dir = std::env::temp_dir();
+        let missing = dir.join("resource-watchdog-missing-dir").join("scabbard");
+
+        assert!(check_free_space(&[dir.clone(), missing.clone()], 0).is_empty());
+
+        let problems = check_free_space(&[dir.clone(), missing.clone()], u64::MAX);
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].resource, dir.display().to_string());
+        assert_eq!(problems[1].resource, missing.display().to_string());
+    }
+
+    /// Verifies that the status holds the problems of the latest check, and is cleared when no
+    /// problems are found.
+    #[test]
+    fn test_resource_status() {
+        let status = ResourceStatus::default();
+        let problem = ResourceProblem {
+            resource: "admin-store".into(),
+            message: "Unable to reach the admin service store".into(),
+        };
+
+        status.update(vec![problem.clone()]);
+        assert_eq!(status.problems(), vec![problem]);
+
+        status.update(vec![]);
+        assert!(status.problems().is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the `GET /alerts` endpoint, which lists the configured alert rules and
+//! the alerts that are currently firing.
+
+use splinter::actix_web::HttpResponse;
+use splinter::futures::future::IntoFuture;
+use splinter::rest_api::{Method, Resource};
+
+use crate::alerts::{Alert, AlertRule, AlertState};
+
+#[derive(Debug, Serialize)]
+struct AlertsResponse<'a> {
+    rules: &'a [AlertRule],
+    alerts: Vec<Alert>,
+}
+
+pub fn make_alerts_resource(rules: Vec<AlertRule>, state: AlertState) -> Resource {
+    Resource::build("/alerts").add_method(Method::Get, move |_, _| {
+        Box::new(
+            HttpResponse::Ok()
+                .json(AlertsResponse {
+                    rules: &rules,
+                    alerts: state.list_alerts(),
+                })
+                .into_future(),
+        )
+    })
+}
@@ -0,0 +1,217 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for capturing the messages exchanged with peers:
+//!
+//! * `GET /admin/capture` for fetching the status of the running or most recent capture
+//! * `POST /admin/capture` for starting a capture that runs for a number of seconds
+//! * `DELETE /admin/capture` for stopping the running capture early
+//! * `GET /admin/capture/{file}` for downloading a capture file
+
+use std::time::Duration;
+
+use splinter::actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use splinter::futures::{future::IntoFuture, stream::Stream, Future};
+use splinter::peer::capture::{CaptureStatus, MessageCapture, MessageCaptureError};
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+#[derive(Debug, Deserialize)]
+struct StartCaptureRequest {
+    /// How long to capture for, in seconds
+    duration: u64,
+    #[serde(default)]
+    payload_hashes: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CaptureStatusResponse {
+    data: Option<CaptureStatus>,
+}
+
+/// `MessageCaptureError` is not `Send`, so it is reduced to this before leaving the blocking
+/// thread.
+#[derive(Debug)]
+enum CaptureRequestError {
+    BadRequest(String),
+    Conflict(String),
+    Internal(String),
+}
+
+impl From<MessageCaptureError> for CaptureRequestError {
+    fn from(err: MessageCaptureError) -> Self {
+        match err {
+            MessageCaptureError::InvalidArgument(err) => {
+                CaptureRequestError::BadRequest(err.to_string())
+            }
+            MessageCaptureError::InvalidState(err) => {
+                CaptureRequestError::Conflict(err.to_string())
+            }
+            MessageCaptureError::Internal(err) => {
+                CaptureRequestError::Internal(err.reduce_to_string())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CaptureRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CaptureRequestError::BadRequest(msg)
+            | CaptureRequestError::Conflict(msg)
+            | CaptureRequestError::Internal(msg) => f.write_str(msg),
+        }
+    }
+}
+
+pub fn make_capture_resources(message_capture: MessageCapture) -> Vec<Resource> {
+    let status_capture = message_capture.clone();
+    let start_capture = message_capture.clone();
+    let stop_capture = message_capture.clone();
+    vec![
+        Resource::build("/admin/capture")
+            .add_method(Method::Get, move |_, _| {
+                capture_status(status_capture.clone())
+            })
+            .add_method(Method::Post, move |_, payload| {
+                start(payload, start_capture.clone())
+            })
+            .add_method(Method::Delete, move |_, _| stop(stop_capture.clone())),
+        Resource::build("/admin/capture/{file}").add_method(Method::Get, move |request, _| {
+            download(request, message_capture.clone())
+        }),
+    ]
+}
+
+fn capture_status(
+    message_capture: MessageCapture,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || message_capture.status().map_err(CaptureRequestError::from)).then(
+            |res| {
+                Ok(match res {
+                    Ok(data) => HttpResponse::Ok().json(CaptureStatusResponse { data }),
+                    Err(err) => {
+                        error!("Unable to fetch capture status: {}", err);
+                        HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                    }
+                })
+            },
+        ),
+    )
+}
+
+fn start(
+    payload: web::Payload,
+    message_capture: MessageCapture,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        payload
+            .from_err::<Error>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, Error>(body)
+            })
+            .into_future()
+            .and_then(
+                move |body| match serde_json::from_slice::<StartCaptureRequest>(&body) {
+                    Ok(request) => Box::new(
+                        web::block(move || {
+                            message_capture
+                                .start(
+                                    Duration::from_secs(request.duration),
+                                    request.payload_hashes,
+                                )
+                                .map_err(CaptureRequestError::from)
+                        })
+                        .then(|res| {
+                            Ok(match res {
+                                Ok(status) => HttpResponse::Ok()
+                                    .json(CaptureStatusResponse { data: Some(status) }),
+                                Err(BlockingError::Error(CaptureRequestError::Conflict(msg))) => {
+                                    HttpResponse::Conflict().json(ErrorResponse::conflict(&msg))
+                                }
+                                Err(BlockingError::Error(CaptureRequestError::BadRequest(msg))) => {
+                                    HttpResponse::BadRequest()
+                                        .json(ErrorResponse::bad_request(&msg))
+                                }
+                                Err(err) => {
+                                    error!("Unable to start capture: {}", err);
+                                    HttpResponse::InternalServerError()
+                                        .json(ErrorResponse::internal_error())
+                                }
+                            })
+                        }),
+                    )
+                        as Box<dyn Future<Item = HttpResponse, Error = Error>>,
+                    Err(err) => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Invalid capture request: {}",
+                                err
+                            )))
+                            .into_future(),
+                    ),
+                },
+            ),
+    )
+}
+
+fn stop(message_capture: MessageCapture) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || message_capture.stop().map_err(CaptureRequestError::from)).then(|res| {
+            Ok(match res {
+                Ok(Some(status)) => {
+                    HttpResponse::Ok().json(CaptureStatusResponse { data: Some(status) })
+                }
+                Ok(None) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("No capture is running"))
+                }
+                Err(err) => {
+                    error!("Unable to stop capture: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn download(
+    request: HttpRequest,
+    message_capture: MessageCapture,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let file = request.match_info().get("file").unwrap_or("").to_string();
+    Box::new(
+        web::block(move || {
+            message_capture
+                .read_capture_file(&file)
+                .map_err(CaptureRequestError::from)
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(Some(contents)) => HttpResponse::Ok()
+                    .content_type("application/x-ndjson")
+                    .body(contents),
+                Ok(None) => HttpResponse::NotFound()
+                    .json(ErrorResponse::not_found("Capture file not found")),
+                Err(BlockingError::Error(CaptureRequestError::BadRequest(msg))) => {
+                    HttpResponse::BadRequest().json(ErrorResponse::bad_request(&msg))
+                }
+                Err(err) => {
+                    error!("Unable to read capture file: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
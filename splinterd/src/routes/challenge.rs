@@ -0,0 +1,121 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the `POST /node/challenge` endpoint, which signs a caller-provided nonce
+//! with the node's key so the caller can check the node's identity against its registry entry.
+
+use std::sync::{Arc, Mutex};
+
+use cylinder::Signer;
+use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
+use splinter::futures::{future::IntoFuture, stream::Stream, Future};
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+/// The route of the node challenge endpoint
+pub const NODE_CHALLENGE_ROUTE: &str = "/node/challenge";
+
+const MAX_NONCE_LENGTH: usize = 256;
+
+#[derive(Debug, Deserialize)]
+struct ChallengeRequest {
+    nonce: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ChallengeResponse {
+    node_id: String,
+    public_key: String,
+    signature: String,
+}
+
+/// Returns the bytes signed in response to a challenge.
+///
+/// The prefix keeps the endpoint from being used to sign arbitrary messages, such as admin
+/// payload headers, with the node's key.
+pub fn challenge_message(node_id: &str, nonce: &str) -> Vec<u8> {
+    format!("splinter-node-challenge:{}:{}", node_id, nonce).into_bytes()
+}
+
+pub fn make_node_challenge_resource(node_id: String, signer: Box<dyn Signer>) -> Resource {
+    let signer = Arc::new(Mutex::new(signer));
+    Resource::build(NODE_CHALLENGE_ROUTE).add_method(Method::Post, move |r, p| {
+        sign_challenge(r, p, node_id.clone(), signer.clone())
+    })
+}
+
+fn sign_challenge(
+    _: HttpRequest,
+    payload: web::Payload,
+    node_id: String,
+    signer: Arc<Mutex<Box<dyn Signer>>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        payload
+            .from_err::<Error>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, Error>(body)
+            })
+            .into_future()
+            .and_then(
+                move |body| match serde_json::from_slice::<ChallengeRequest>(&body) {
+                    Ok(ChallengeRequest { nonce })
+                        if nonce.is_empty() || nonce.len() > MAX_NONCE_LENGTH =>
+                    {
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Nonce must be between 1 and {} characters",
+                                MAX_NONCE_LENGTH
+                            )))
+                            .into_future()
+                    }
+                    Ok(ChallengeRequest { nonce }) => match sign(&node_id, &nonce, &signer) {
+                        Ok(response) => HttpResponse::Ok().json(response).into_future(),
+                        Err(err) => {
+                            error!("Unable to sign node challenge: {}", err);
+                            HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future()
+                        }
+                    },
+                    Err(err) => HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Invalid challenge request: {}",
+                            err
+                        )))
+                        .into_future(),
+                },
+            ),
+    )
+}
+
+fn sign(
+    node_id: &str,
+    nonce: &str,
+    signer: &Mutex<Box<dyn Signer>>,
+) -> Result<ChallengeResponse, String> {
+    let signer = signer
+        .lock()
+        .map_err(|_| "node signer lock was poisoned".to_string())?;
+    let public_key = signer.public_key().map_err(|err| err.to_string())?;
+    let signature = signer
+        .sign(&challenge_message(node_id, nonce))
+        .map_err(|err| err.to_string())?;
+
+    Ok(ChallengeResponse {
+        node_id: node_id.to_string(),
+        public_key: public_key.as_hex(),
+        signature: signature.as_hex(),
+    })
+}
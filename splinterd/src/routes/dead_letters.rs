@@ -0,0 +1,330 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for circuit messages that could not be delivered:
+//!
+//! * `GET /admin/dead_letters` for listing the undeliverable messages and the counts by reason
+//! * `DELETE /admin/dead_letters` for purging all undeliverable messages
+//! * `POST /admin/dead_letters/replay` for replaying all undeliverable messages
+//! * `GET /admin/dead_letters/{id}` for fetching an undeliverable message, including its payload
+//! * `DELETE /admin/dead_letters/{id}` for purging an undeliverable message
+//! * `POST /admin/dead_letters/{id}/replay` for replaying an undeliverable message
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
+use splinter::circuit::dead_letter::{DeadLetter, DeadLetterQueue};
+use splinter::futures::{future::IntoFuture, Future};
+use splinter::network::dispatch::DispatchMessageSender;
+use splinter::protos::circuit::CircuitMessageType;
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+#[derive(Debug, Serialize)]
+struct DeadLetterResponse {
+    id: u64,
+    circuit_id: String,
+    sender: String,
+    recipient: String,
+    correlation_id: String,
+    reason: String,
+    source_peer_id: String,
+    /// Seconds since the unix epoch
+    received_at: u64,
+    payload_size: usize,
+    /// The hex-encoded payload, only included when fetching a single message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<String>,
+}
+
+impl DeadLetterResponse {
+    fn from_letter(letter: &DeadLetter, include_payload: bool) -> Self {
+        DeadLetterResponse {
+            id: letter.id(),
+            circuit_id: letter.circuit_id().to_string(),
+            sender: letter.sender().to_string(),
+            recipient: letter.recipient().to_string(),
+            correlation_id: letter.correlation_id().to_string(),
+            reason: letter.reason().to_string(),
+            source_peer_id: letter.source_peer_id().to_string(),
+            received_at: letter
+                .received_at()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            payload_size: letter.payload().len(),
+            payload: if include_payload {
+                Some(
+                    letter
+                        .payload()
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect(),
+                )
+            } else {
+                None
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DeadLetterFetchResponse {
+    data: DeadLetterResponse,
+}
+
+#[derive(Debug, Serialize)]
+struct DeadLetterListResponse {
+    data: Vec<DeadLetterResponse>,
+    /// The number of undeliverable messages by reason, including those no longer in the queue
+    counts: BTreeMap<String, u64>,
+    evicted: u64,
+    capacity: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct DeadLetterCountResponse {
+    count: usize,
+}
+
+/// `DispatchMessageSender` is not `Sync`, so the handlers share it behind a mutex.
+type SharedDispatchSender = Arc<Mutex<DispatchMessageSender<CircuitMessageType>>>;
+
+pub fn make_dead_letter_resources(
+    dead_letters: DeadLetterQueue,
+    dispatch_sender: DispatchMessageSender<CircuitMessageType>,
+) -> Vec<Resource> {
+    let dispatch_sender: SharedDispatchSender = Arc::new(Mutex::new(dispatch_sender));
+    let list_letters = dead_letters.clone();
+    let purge_letters = dead_letters.clone();
+    let replay_all_letters = dead_letters.clone();
+    let replay_all_sender = dispatch_sender.clone();
+    let fetch_letters = dead_letters.clone();
+    let remove_letters = dead_letters.clone();
+    vec![
+        Resource::build("/admin/dead_letters")
+            .add_method(Method::Get, move |_, _| {
+                list_dead_letters(list_letters.clone())
+            })
+            .add_method(Method::Delete, move |_, _| {
+                purge_dead_letters(purge_letters.clone())
+            }),
+        // registered before /admin/dead_letters/{id} so that "replay" is not matched as an ID
+        Resource::build("/admin/dead_letters/replay").add_method(Method::Post, move |_, _| {
+            replay_all_dead_letters(replay_all_letters.clone(), replay_all_sender.clone())
+        }),
+        Resource::build("/admin/dead_letters/{id}")
+            .add_method(Method::Get, move |r, _| {
+                fetch_dead_letter(r, fetch_letters.clone())
+            })
+            .add_method(Method::Delete, move |r, _| {
+                remove_dead_letter(r, remove_letters.clone())
+            }),
+        Resource::build("/admin/dead_letters/{id}/replay").add_method(Method::Post, move |r, _| {
+            replay_dead_letter(r, dead_letters.clone(), dispatch_sender.clone())
+        }),
+    ]
+}
+
+fn parse_id(request: &HttpRequest) -> Result<u64, HttpResponse> {
+    request
+        .match_info()
+        .get("id")
+        .unwrap_or("")
+        .parse::<u64>()
+        .map_err(|_| {
+            HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                "Dead letter ID must be a non-negative integer",
+            ))
+        })
+}
+
+fn list_dead_letters(
+    dead_letters: DeadLetterQueue,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let data = dead_letters
+                .list()
+                .map_err(|err| err.reduce_to_string())?
+                .iter()
+                .map(|letter| DeadLetterResponse::from_letter(letter, false))
+                .collect();
+            let counts = dead_letters
+                .counts()
+                .map_err(|err| err.reduce_to_string())?;
+
+            Ok::<_, String>(DeadLetterListResponse {
+                data,
+                counts: counts
+                    .by_reason
+                    .iter()
+                    .map(|(reason, count)| (reason.to_string(), *count))
+                    .collect(),
+                evicted: counts.evicted,
+                capacity: dead_letters.capacity(),
+            })
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(letters) => HttpResponse::Ok().json(letters),
+                Err(err) => {
+                    error!("Unable to list dead letters: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn purge_dead_letters(
+    dead_letters: DeadLetterQueue,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || dead_letters.purge().map_err(|err| err.reduce_to_string())).then(
+            |res| {
+                Ok(match res {
+                    Ok(count) => {
+                        info!("Purged {} dead letters", count);
+                        HttpResponse::Ok().json(DeadLetterCountResponse { count })
+                    }
+                    Err(err) => {
+                        error!("Unable to purge dead letters: {}", err);
+                        HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                    }
+                })
+            },
+        ),
+    )
+}
+
+fn replay_all_dead_letters(
+    dead_letters: DeadLetterQueue,
+    dispatch_sender: SharedDispatchSender,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let dispatch_sender = dispatch_sender
+                .lock()
+                .map_err(|_| "dispatch sender lock was poisoned".to_string())?;
+            dead_letters
+                .replay_all(&dispatch_sender)
+                .map_err(|err| err.reduce_to_string())
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(count) => {
+                    info!("Replayed {} dead letters", count);
+                    HttpResponse::Accepted().json(DeadLetterCountResponse { count })
+                }
+                Err(err) => {
+                    error!("Unable to replay dead letters: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn fetch_dead_letter(
+    request: HttpRequest,
+    dead_letters: DeadLetterQueue,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let id = match parse_id(&request) {
+        Ok(id) => id,
+        Err(response) => return Box::new(response.into_future()),
+    };
+    Box::new(
+        web::block(move || dead_letters.get(id).map_err(|err| err.reduce_to_string())).then(
+            |res| {
+                Ok(match res {
+                    Ok(Some(letter)) => HttpResponse::Ok().json(DeadLetterFetchResponse {
+                        data: DeadLetterResponse::from_letter(&letter, true),
+                    }),
+                    Ok(None) => HttpResponse::NotFound()
+                        .json(ErrorResponse::not_found("Dead letter not found")),
+                    Err(err) => {
+                        error!("Unable to fetch dead letter: {}", err);
+                        HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                    }
+                })
+            },
+        ),
+    )
+}
+
+fn remove_dead_letter(
+    request: HttpRequest,
+    dead_letters: DeadLetterQueue,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let id = match parse_id(&request) {
+        Ok(id) => id,
+        Err(response) => return Box::new(response.into_future()),
+    };
+    Box::new(
+        web::block(move || {
+            dead_letters
+                .remove(id)
+                .map_err(|err| err.reduce_to_string())
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(Some(_)) => HttpResponse::Ok().finish(),
+                Ok(None) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("Dead letter not found"))
+                }
+                Err(err) => {
+                    error!("Unable to remove dead letter: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn replay_dead_letter(
+    request: HttpRequest,
+    dead_letters: DeadLetterQueue,
+    dispatch_sender: SharedDispatchSender,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let id = match parse_id(&request) {
+        Ok(id) => id,
+        Err(response) => return Box::new(response.into_future()),
+    };
+    Box::new(
+        web::block(move || {
+            let dispatch_sender = dispatch_sender
+                .lock()
+                .map_err(|_| "dispatch sender lock was poisoned".to_string())?;
+            dead_letters
+                .replay(id, &dispatch_sender)
+                .map_err(|err| err.reduce_to_string())
+        })
+        .then(|res| {
+            Ok(match res {
+                // The message is dispatched in the background; if it still cannot be delivered
+                // it is added back to the queue with a new ID
+                Ok(true) => HttpResponse::Accepted().finish(),
+                Ok(false) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("Dead letter not found"))
+                }
+                Err(err) => {
+                    error!("Unable to replay dead letter: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
@@ -0,0 +1,154 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints:
+//!
+//! * `GET /identity_pins` for listing the certificate fingerprints peer identities are pinned
+//!   to on first use, along with recent identity mismatches
+//! * `DELETE /identity_pins/{node_id}` for removing a node's pin after its certificate has been
+//!   legitimately replaced
+
+use std::time::UNIX_EPOCH;
+
+use splinter::actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use splinter::futures::{future::IntoFuture, Future};
+use splinter::network::auth::pinning::{IdentityMismatch, IdentityPinVerifier};
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+#[derive(Debug, Serialize)]
+struct IdentityPinListResponse {
+    mode: String,
+    pins: Vec<IdentityPinResponse>,
+    /// The most recent connections refused because of a certificate mismatch, oldest first
+    mismatches: Vec<IdentityMismatchResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct IdentityPinResponse {
+    node_id: String,
+    fingerprint: String,
+}
+
+#[derive(Debug, Serialize)]
+struct IdentityMismatchResponse {
+    node_id: String,
+    remote_endpoint: String,
+    pinned_fingerprint: String,
+    presented_fingerprint: String,
+    /// When the connection was refused, in seconds since the Unix epoch
+    timestamp: u64,
+}
+
+impl From<IdentityMismatch> for IdentityMismatchResponse {
+    fn from(mismatch: IdentityMismatch) -> Self {
+        IdentityMismatchResponse {
+            node_id: mismatch.identity,
+            remote_endpoint: mismatch.remote_endpoint,
+            pinned_fingerprint: mismatch.pinned_fingerprint,
+            presented_fingerprint: mismatch.presented_fingerprint,
+            timestamp: mismatch
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+pub fn make_identity_pin_resources(verifier: IdentityPinVerifier) -> Vec<Resource> {
+    let list_verifier = verifier.clone();
+    vec![
+        Resource::build("/identity_pins").add_method(Method::Get, move |_, _| {
+            list_identity_pins(list_verifier.clone())
+        }),
+        Resource::build("/identity_pins/{node_id}").add_method(Method::Delete, move |r, _| {
+            remove_identity_pin(r, verifier.clone())
+        }),
+    ]
+}
+
+fn list_identity_pins(
+    verifier: IdentityPinVerifier,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let pins = verifier
+                .list_pins()
+                .map_err(|err| err.to_string())?
+                .into_iter()
+                .map(|(node_id, fingerprint)| IdentityPinResponse {
+                    node_id,
+                    fingerprint,
+                })
+                .collect();
+            Ok::<_, String>(IdentityPinListResponse {
+                mode: verifier.mode().to_string(),
+                pins,
+                mismatches: verifier
+                    .mismatches()
+                    .into_iter()
+                    .map(IdentityMismatchResponse::from)
+                    .collect(),
+            })
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(response) => HttpResponse::Ok().json(response),
+                Err(err) => {
+                    error!("Unable to list identity pins: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn remove_identity_pin(
+    request: HttpRequest,
+    verifier: IdentityPinVerifier,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let node_id = request
+        .match_info()
+        .get("node_id")
+        .unwrap_or("")
+        .to_string();
+
+    Box::new(
+        web::block(move || {
+            verifier
+                .remove_pin(&node_id)
+                .map(|removed| (node_id, removed))
+                .map_err(|err| err.to_string())
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok((node_id, true)) => {
+                    info!("Removed identity pin of {} through the REST API", node_id);
+                    HttpResponse::Ok().finish()
+                }
+                Ok((_, false)) => HttpResponse::NotFound().json(ErrorResponse::not_found(
+                    "The node's identity is not pinned",
+                )),
+                Err(BlockingError::Error(err)) => {
+                    error!("Unable to remove identity pin: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+                Err(BlockingError::Canceled) => {
+                    error!("Unable to remove identity pin: request canceled");
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
@@ -0,0 +1,88 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the `GET /admin/logs` endpoint, which returns the daemon's most recent
+//! log entries. The entries can be filtered with the following query parameters:
+//!
+//! * `since=<seq>` only returns entries logged after the entry with the given sequence number
+//! * `level=<level>` only returns entries at the given level or more severe
+//! * `module=<prefix>` only returns entries from modules whose path starts with the prefix
+//! * `limit=<count>` returns at most the given number of entries, keeping the most recent
+
+use std::collections::HashMap;
+
+use splinter::actix_web::{web, HttpResponse};
+use splinter::futures::future::IntoFuture;
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+use crate::log_buffer::{LogBuffer, LogEntry, LogFilter};
+
+#[derive(Debug, Serialize)]
+struct LogsResponse {
+    data: Vec<LogEntry>,
+    /// The sequence number of the most recent entry logged, which may be filtered out of `data`;
+    /// clients following the log pass it back as `since`
+    last_seq: u64,
+}
+
+pub fn make_logs_resource(log_buffer: LogBuffer) -> Resource {
+    Resource::build("/admin/logs").add_method(Method::Get, move |request, _| {
+        let response =
+            match web::Query::<HashMap<String, String>>::from_query(request.query_string())
+                .map_err(|_| "Invalid query".to_string())
+                .and_then(|query| parse_filter(&query))
+            {
+                Ok(filter) => HttpResponse::Ok().json(LogsResponse {
+                    last_seq: log_buffer.last_seq(),
+                    data: log_buffer.entries(&filter),
+                }),
+                Err(msg) => HttpResponse::BadRequest().json(ErrorResponse::bad_request(&msg)),
+            };
+        Box::new(response.into_future())
+    })
+}
+
+fn parse_filter(query: &HashMap<String, String>) -> Result<LogFilter, String> {
+    let since = query
+        .get("since")
+        .map(|since| {
+            since
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid since value: {}", since))
+        })
+        .transpose()?;
+    let level = query
+        .get("level")
+        .map(|level| {
+            level
+                .parse::<log::LevelFilter>()
+                .map_err(|_| format!("Invalid level value: {}", level))
+        })
+        .transpose()?;
+    let limit = query
+        .get("limit")
+        .map(|limit| {
+            limit
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid limit value: {}", limit))
+        })
+        .transpose()?;
+
+    Ok(LogFilter {
+        since,
+        level,
+        module: query.get("module").cloned(),
+        limit,
+    })
+}
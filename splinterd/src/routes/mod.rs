@@ -13,6 +13,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "alerting")]
+mod alerts;
+#[cfg(feature = "message-capture")]
+mod capture;
+#[cfg(feature = "node-challenge")]
+mod challenge;
+#[cfg(feature = "circuit-dead-letter")]
+mod dead_letters;
+#[cfg(feature = "peer-identity-pinning")]
+mod identity_pins;
+#[cfg(feature = "log-retrieval")]
+mod logs;
+#[cfg(feature = "peer-management")]
+mod peers;
+#[cfg(feature = "circuit-ping")]
+mod ping;
+#[cfg(feature = "read-only-mode")]
+mod read_only;
+#[cfg(feature = "routing-table-rest-api")]
+mod routing;
+#[cfg(feature = "node-stats")]
+mod stats;
 mod status;
 
+#[cfg(feature = "alerting")]
+pub use alerts::*;
+#[cfg(feature = "message-capture")]
+pub use capture::*;
+#[cfg(feature = "node-challenge")]
+pub use challenge::*;
+#[cfg(feature = "circuit-dead-letter")]
+pub use dead_letters::*;
+#[cfg(feature = "peer-identity-pinning")]
+pub use identity_pins::*;
+#[cfg(feature = "log-retrieval")]
+pub use logs::*;
+#[cfg(feature = "peer-management")]
+pub use peers::*;
+#[cfg(feature = "circuit-ping")]
+pub use ping::*;
+#[cfg(feature = "read-only-mode")]
+pub use read_only::*;
+#[cfg(feature = "routing-table-rest-api")]
+pub use routing::*;
+#[cfg(feature = "node-stats")]
+pub use stats::*;
 pub use status::*;
@@ -0,0 +1,392 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints:
+//!
+//! * `GET /peers` for listing the node's connected peers
+//! * `POST /peers` for connecting to a peer by its endpoint
+//! * `DELETE /peers?endpoint=<endpoint>` for removing a peer that was added by endpoint
+//! * `GET /peers/{peer_id}` for fetching a connected peer
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use splinter::actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use splinter::futures::{future::IntoFuture, stream::Stream, Future};
+use splinter::network::auth::PeerCapabilities;
+#[cfg(feature = "network-clock-skew")]
+use splinter::network::clock_skew::ClockSkewMonitor;
+use splinter::peer::{EndpointPeerRef, PeerManagerConnector, PeerUnknownAddError};
+use splinter::protocol::authorization::NodeCapabilities;
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+/// The peers added by endpoint, either from the daemon's configuration or through the REST API,
+/// keyed by endpoint. A peer is removed when its ref is dropped.
+pub type EndpointPeerRefs = Arc<Mutex<HashMap<String, EndpointPeerRef>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PeerResponse {
+    peer_id: String,
+    connection_id: Option<String>,
+    /// The estimated offset of the peer's clock from the node's clock, in milliseconds
+    #[cfg(feature = "network-clock-skew")]
+    clock_skew_millis: Option<i64>,
+    /// The capabilities the peer advertised when it was authorized
+    capabilities: Option<CapabilitiesResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CapabilitiesResponse {
+    version: String,
+    admin_service_protocol_version: u32,
+    features: Vec<String>,
+}
+
+impl From<NodeCapabilities> for CapabilitiesResponse {
+    fn from(capabilities: NodeCapabilities) -> Self {
+        CapabilitiesResponse {
+            version: capabilities.version,
+            admin_service_protocol_version: capabilities.admin_service_protocol_version,
+            features: capabilities.features,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PeerFetchResponse {
+    data: PeerResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PeerListResponse {
+    data: Vec<PeerResponse>,
+    /// The endpoints of the peers that may be removed by endpoint
+    endpoints: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AddPeerRequest {
+    endpoint: String,
+}
+
+/// `PeerManagerConnector` is not `Sync`, so the handlers share it behind a mutex and clone it for
+/// each request.
+type SharedConnector = Arc<Mutex<PeerManagerConnector>>;
+
+pub fn make_peers_resources(
+    peer_connector: PeerManagerConnector,
+    peer_refs: EndpointPeerRefs,
+    peer_capabilities: PeerCapabilities,
+    #[cfg(feature = "network-clock-skew")] clock_skew_monitor: ClockSkewMonitor,
+) -> Vec<Resource> {
+    let peer_connector: SharedConnector = Arc::new(Mutex::new(peer_connector));
+    let list_connector = peer_connector.clone();
+    let add_connector = peer_connector.clone();
+    let list_refs = peer_refs.clone();
+    let add_refs = peer_refs.clone();
+    let list_capabilities = peer_capabilities.clone();
+    #[cfg(feature = "network-clock-skew")]
+    let list_monitor = clock_skew_monitor.clone();
+    vec![
+        Resource::build("/peers")
+            .add_method(Method::Get, move |_, _| {
+                with_connector(&list_connector, |connector| {
+                    list_peers(
+                        connector,
+                        list_refs.clone(),
+                        list_capabilities.clone(),
+                        #[cfg(feature = "network-clock-skew")]
+                        list_monitor.clone(),
+                    )
+                })
+            })
+            .add_method(Method::Post, move |_, p| {
+                with_connector(&add_connector, |connector| {
+                    add_peer(p, connector, add_refs.clone())
+                })
+            })
+            .add_method(Method::Delete, move |r, _| {
+                remove_peer(r, peer_refs.clone())
+            }),
+        Resource::build("/peers/{peer_id}").add_method(Method::Get, move |r, _| {
+            with_connector(&peer_connector, |connector| {
+                fetch_peer(
+                    r,
+                    connector,
+                    peer_capabilities.clone(),
+                    #[cfg(feature = "network-clock-skew")]
+                    clock_skew_monitor.clone(),
+                )
+            })
+        }),
+    ]
+}
+
+fn with_connector<F>(
+    peer_connector: &SharedConnector,
+    handler: F,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>>
+where
+    F: FnOnce(PeerManagerConnector) -> Box<dyn Future<Item = HttpResponse, Error = Error>>,
+{
+    match peer_connector.lock() {
+        Ok(peer_connector) => handler(peer_connector.clone()),
+        Err(_) => {
+            error!("Unable to handle peer request: peer connector lock was poisoned");
+            Box::new(
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future(),
+            )
+        }
+    }
+}
+
+fn list_peers(
+    peer_connector: PeerManagerConnector,
+    peer_refs: EndpointPeerRefs,
+    peer_capabilities: PeerCapabilities,
+    #[cfg(feature = "network-clock-skew")] clock_skew_monitor: ClockSkewMonitor,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let peer_ids = peer_connector.list_peers().map_err(|err| err.to_string())?;
+            let connection_ids = peer_connector
+                .connection_ids()
+                .map_err(|err| err.to_string())?;
+            let mut capabilities = peer_capabilities.list().map_err(|err| err.to_string())?;
+            let data = peer_ids
+                .into_iter()
+                .map(|peer_id| {
+                    Ok(PeerResponse {
+                        capabilities: capabilities
+                            .remove(&peer_id)
+                            .map(CapabilitiesResponse::from),
+                        connection_id: connection_ids.get_by_key(&peer_id).cloned(),
+                        #[cfg(feature = "network-clock-skew")]
+                        clock_skew_millis: clock_skew_monitor
+                            .offset_millis(&peer_id)
+                            .map_err(|err| err.to_string())?,
+                        peer_id,
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+
+            let mut endpoints = peer_refs
+                .lock()
+                .map_err(|_| "peer refs lock was poisoned".to_string())?
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            endpoints.sort();
+
+            Ok::<_, String>(PeerListResponse { data, endpoints })
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(peers) => HttpResponse::Ok().json(peers),
+                Err(err) => {
+                    error!("Unable to list peers: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn fetch_peer(
+    request: HttpRequest,
+    peer_connector: PeerManagerConnector,
+    peer_capabilities: PeerCapabilities,
+    #[cfg(feature = "network-clock-skew")] clock_skew_monitor: ClockSkewMonitor,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let peer_id = request
+        .match_info()
+        .get("peer_id")
+        .unwrap_or("")
+        .to_string();
+    Box::new(
+        web::block(move || {
+            if !peer_connector
+                .list_peers()
+                .map_err(|err| err.to_string())?
+                .contains(&peer_id)
+            {
+                return Ok(None);
+            }
+
+            let connection_id = peer_connector
+                .connection_ids()
+                .map_err(|err| err.to_string())?
+                .get_by_key(&peer_id)
+                .cloned();
+
+            #[cfg(feature = "network-clock-skew")]
+            let clock_skew_millis = clock_skew_monitor
+                .offset_millis(&peer_id)
+                .map_err(|err| err.to_string())?;
+
+            let capabilities = peer_capabilities
+                .get(&peer_id)
+                .map_err(|err| err.to_string())?
+                .map(CapabilitiesResponse::from);
+
+            Ok::<_, String>(Some(PeerResponse {
+                peer_id,
+                connection_id,
+                capabilities,
+                #[cfg(feature = "network-clock-skew")]
+                clock_skew_millis,
+            }))
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(Some(peer)) => HttpResponse::Ok().json(PeerFetchResponse { data: peer }),
+                Ok(None) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("Peer not found"))
+                }
+                Err(err) => {
+                    error!("Unable to fetch peer: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn add_peer(
+    payload: web::Payload,
+    peer_connector: PeerManagerConnector,
+    peer_refs: EndpointPeerRefs,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        payload
+            .from_err::<Error>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, Error>(body)
+            })
+            .into_future()
+            .and_then(
+                move |body| match serde_json::from_slice::<AddPeerRequest>(&body) {
+                    Ok(AddPeerRequest { endpoint }) if endpoint.is_empty() => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("Peer endpoint cannot be empty"))
+                            .into_future(),
+                    ),
+                    Ok(AddPeerRequest { endpoint }) => Box::new(
+                        web::block(move || {
+                            let mut peer_refs = peer_refs.lock().map_err(|_| {
+                                PeerUnknownAddError::InternalError(
+                                    "peer refs lock was poisoned".into(),
+                                )
+                            })?;
+                            if peer_refs.contains_key(&endpoint) {
+                                return Ok(false);
+                            }
+
+                            let peer_ref =
+                                peer_connector.add_unidentified_peer(endpoint.clone())?;
+                            info!("Added peer {} through the REST API", endpoint);
+                            peer_refs.insert(endpoint, peer_ref);
+                            Ok(true)
+                        })
+                        .then(|res| {
+                            Ok(match res {
+                                // The connection to the peer is established in the background
+                                Ok(true) => HttpResponse::Accepted().finish(),
+                                Ok(false) => HttpResponse::Ok().finish(),
+                                Err(BlockingError::Error(PeerUnknownAddError::AddError(err))) => {
+                                    HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                                        &format!("Unable to add peer: {}", err),
+                                    ))
+                                }
+                                Err(err) => {
+                                    error!("Unable to add peer: {}", err);
+                                    HttpResponse::InternalServerError()
+                                        .json(ErrorResponse::internal_error())
+                                }
+                            })
+                        }),
+                    )
+                        as Box<dyn Future<Item = HttpResponse, Error = Error>>,
+                    Err(err) => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Invalid peer: {}",
+                                err
+                            )))
+                            .into_future(),
+                    ),
+                },
+            ),
+    )
+}
+
+fn remove_peer(
+    request: HttpRequest,
+    peer_refs: EndpointPeerRefs,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let endpoint = match web::Query::<HashMap<String, String>>::from_query(request.query_string()) {
+        Ok(query) => match query.get("endpoint") {
+            Some(endpoint) => endpoint.to_string(),
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "The endpoint query parameter is required",
+                        ))
+                        .into_future(),
+                )
+            }
+        },
+        Err(_) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request("Invalid query"))
+                    .into_future(),
+            )
+        }
+    };
+
+    Box::new(
+        web::block(move || {
+            let removed = peer_refs
+                .lock()
+                .map_err(|_| "peer refs lock was poisoned".to_string())?
+                .remove(&endpoint);
+            // Dropping the ref removes the peer
+            match removed {
+                Some(peer_ref) => {
+                    drop(peer_ref);
+                    info!("Removed peer {} through the REST API", endpoint);
+                    Ok(true)
+                }
+                None => Ok::<_, String>(false),
+            }
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(true) => HttpResponse::Ok().finish(),
+                Ok(false) => HttpResponse::NotFound().json(ErrorResponse::not_found(
+                    "No peer was added with the given endpoint",
+                )),
+                Err(err) => {
+                    error!("Unable to remove peer: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
@@ -0,0 +1,166 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the `POST /admin/circuits/{circuit_id}/ping` endpoint, which sends a
+//! diagnostic ping to a service on a circuit and reports the round-trip time and the nodes the
+//! ping passed through.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
+use splinter::circuit::ping::{CircuitPingError, CircuitPinger};
+use splinter::futures::{future::IntoFuture, stream::Stream, Future};
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+const DEFAULT_PING_TIMEOUT_SECS: u64 = 5;
+const MAX_PING_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Deserialize)]
+struct PingRequest {
+    service_id: String,
+    /// The number of seconds to wait for a response
+    timeout: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct PingResponse {
+    circuit_id: String,
+    service_id: String,
+    status: String,
+    message: Option<String>,
+    path: Vec<String>,
+    peer_id: Option<String>,
+    round_trip_ms: Option<f64>,
+}
+
+/// `CircuitPinger` is not `Sync`, so the handler shares it behind a mutex and clones it for each
+/// request.
+type SharedPinger = Arc<Mutex<CircuitPinger>>;
+
+pub fn make_circuit_ping_resources(circuit_pinger: CircuitPinger) -> Vec<Resource> {
+    let circuit_pinger: SharedPinger = Arc::new(Mutex::new(circuit_pinger));
+    vec![Resource::build("/admin/circuits/{circuit_id}/ping")
+        .add_method(Method::Post, move |r, p| {
+            ping_service(r, p, circuit_pinger.clone())
+        })]
+}
+
+fn ping_service(
+    request: HttpRequest,
+    payload: web::Payload,
+    circuit_pinger: SharedPinger,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let circuit_id = request
+        .match_info()
+        .get("circuit_id")
+        .unwrap_or("")
+        .to_string();
+    let circuit_pinger = match circuit_pinger.lock() {
+        Ok(circuit_pinger) => circuit_pinger.clone(),
+        Err(_) => {
+            error!("Unable to ping service: circuit pinger lock was poisoned");
+            return Box::new(
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future(),
+            );
+        }
+    };
+
+    Box::new(
+        payload
+            .from_err::<Error>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, Error>(body)
+            })
+            .into_future()
+            .and_then(
+                move |body| match serde_json::from_slice::<PingRequest>(&body) {
+                    Ok(PingRequest { service_id, .. }) if service_id.is_empty() => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("Service ID cannot be empty"))
+                            .into_future(),
+                    ),
+                    Ok(PingRequest {
+                        timeout: Some(timeout),
+                        ..
+                    }) if timeout == 0 || timeout > MAX_PING_TIMEOUT_SECS => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Timeout must be between 1 and {} seconds",
+                                MAX_PING_TIMEOUT_SECS
+                            )))
+                            .into_future(),
+                    ),
+                    Ok(PingRequest {
+                        service_id,
+                        timeout,
+                    }) => Box::new(
+                        web::block(move || {
+                            let timeout =
+                                Duration::from_secs(timeout.unwrap_or(DEFAULT_PING_TIMEOUT_SECS));
+                            match circuit_pinger.ping(&circuit_id, &service_id, timeout) {
+                                Ok(result) => Ok(PingResponse {
+                                    circuit_id: result.circuit_id,
+                                    service_id: result.service_id,
+                                    status: result.status.to_string(),
+                                    message: result.error_message,
+                                    path: result.path,
+                                    peer_id: result.peer_id,
+                                    round_trip_ms: Some(
+                                        result.round_trip.as_micros() as f64 / 1000.0,
+                                    ),
+                                }),
+                                // A timeout is a result of the diagnostic, not a failure of it
+                                Err(CircuitPingError::Timeout(timeout)) => Ok(PingResponse {
+                                    circuit_id,
+                                    service_id,
+                                    status: "TIMEOUT".into(),
+                                    message: Some(format!(
+                                        "No response received after {} seconds",
+                                        timeout.as_secs()
+                                    )),
+                                    path: vec![],
+                                    peer_id: None,
+                                    round_trip_ms: None,
+                                }),
+                                Err(err) => Err(err.to_string()),
+                            }
+                        })
+                        .then(|res| {
+                            Ok(match res {
+                                Ok(response) => HttpResponse::Ok().json(response),
+                                Err(err) => {
+                                    error!("Unable to ping service: {}", err);
+                                    HttpResponse::InternalServerError()
+                                        .json(ErrorResponse::internal_error())
+                                }
+                            })
+                        }),
+                    )
+                        as Box<dyn Future<Item = HttpResponse, Error = Error>>,
+                    Err(err) => Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&format!(
+                                "Invalid ping request: {}",
+                                err
+                            )))
+                            .into_future(),
+                    ),
+                },
+            ),
+    )
+}
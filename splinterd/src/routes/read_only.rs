@@ -0,0 +1,102 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for the node's read-only mode:
+//!
+//! * `GET /admin/read_only` for checking whether the node is in read-only mode
+//! * `PUT /admin/read_only` for enabling or disabling read-only mode
+//!
+//! While in read-only mode, the REST API refuses requests that may change state, but circuit
+//! traffic continues to be relayed. Like the other admin endpoints, changing the mode requires an
+//! authorized client, and the mode affects every circuit, so it may only be changed by clients
+//! whose access to circuits is not restricted, such as the node operator.
+
+use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
+use splinter::futures::{future::IntoFuture, stream::Stream, Future};
+#[cfg(feature = "rest-api-circuit-scopes")]
+use splinter::rest_api::auth::circuit_scope::CircuitScope;
+use splinter::rest_api::read_only::ReadOnly;
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+/// The route of the read-only mode endpoints, which is exempt from read-only mode
+pub const READ_ONLY_ROUTE: &str = "/admin/read_only";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ReadOnlyState {
+    read_only: bool,
+}
+
+pub fn make_read_only_resource(read_only: ReadOnly) -> Resource {
+    let put_read_only = read_only.clone();
+    Resource::build(READ_ONLY_ROUTE)
+        .add_method(Method::Get, move |_, _| {
+            Box::new(
+                HttpResponse::Ok()
+                    .json(ReadOnlyState {
+                        read_only: read_only.is_enabled(),
+                    })
+                    .into_future(),
+            )
+        })
+        .add_method(Method::Put, move |r, p| {
+            set_read_only(r, p, put_read_only.clone())
+        })
+}
+
+fn set_read_only(
+    #[allow(unused_variables)] request: HttpRequest,
+    payload: web::Payload,
+    read_only: ReadOnly,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    #[cfg(feature = "rest-api-circuit-scopes")]
+    {
+        if let Some(CircuitScope::Circuits(_)) = request.extensions().get::<CircuitScope>() {
+            return Box::new(
+                HttpResponse::Forbidden()
+                    .json(ErrorResponse::forbidden(
+                        "Not permitted to change the node's read-only mode",
+                    ))
+                    .into_future(),
+            );
+        }
+    }
+
+    Box::new(
+        payload
+            .from_err::<Error>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, Error>(body)
+            })
+            .into_future()
+            .and_then(
+                move |body| match serde_json::from_slice::<ReadOnlyState>(&body) {
+                    Ok(state) => {
+                        read_only.set_enabled(state.read_only);
+                        HttpResponse::Ok()
+                            .json(ReadOnlyState {
+                                read_only: read_only.is_enabled(),
+                            })
+                            .into_future()
+                    }
+                    Err(err) => HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Invalid read-only mode request: {}",
+                            err
+                        )))
+                        .into_future(),
+                },
+            ),
+    )
+}
@@ -0,0 +1,344 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints for inspecting and repairing the routing table:
+//!
+//! * `GET /routing/circuits` for listing the circuits in the routing table
+//! * `GET /routing/circuits/{circuit_id}` for fetching a circuit in the routing table
+//! * `GET /routing/nodes` for listing the nodes in the routing table
+//! * `POST /routing/rebuild` for rebuilding the routing table from the admin service store and
+//!   reporting where the two differed
+//!
+//! Each service is returned with the route messages for it will take: the peer ID of the
+//! connection for a local service, or the node ID for a service on another node. A service
+//! without a route is one that messages cannot currently be delivered to.
+//!
+//! Rebuilding the routing table is an administrative operation and is only accepted from the
+//! loopback interface.
+
+use std::sync::{Arc, Mutex};
+
+use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
+use splinter::admin::routing::{rebuild_routing_table, RoutingTableRebuild};
+use splinter::admin::store::AdminServiceStore;
+use splinter::circuit::routing::{Circuit, RoutingTableReader, RoutingTableWriter, ServiceId};
+use splinter::futures::{future::IntoFuture, Future};
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+#[derive(Debug, Serialize)]
+struct ServiceResponse {
+    service_id: String,
+    service_type: String,
+    node_id: String,
+    peer_id: Option<String>,
+    /// The peer ID or node ID that messages for the service are sent to, if any
+    route: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CircuitResponse {
+    circuit_id: String,
+    members: Vec<String>,
+    relays: Vec<String>,
+    observers: Vec<String>,
+    services: Vec<ServiceResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct CircuitFetchResponse {
+    data: CircuitResponse,
+}
+
+#[derive(Debug, Serialize)]
+struct CircuitListResponse {
+    data: Vec<CircuitResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeResponse {
+    node_id: String,
+    endpoints: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeListResponse {
+    data: Vec<NodeResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct RebuildResponse {
+    added_circuits: Vec<String>,
+    removed_circuits: Vec<String>,
+    changed_circuits: Vec<String>,
+    missing_nodes: Vec<String>,
+    circuit_count: usize,
+}
+
+impl From<RoutingTableRebuild> for RebuildResponse {
+    fn from(rebuild: RoutingTableRebuild) -> Self {
+        RebuildResponse {
+            added_circuits: rebuild.added_circuits,
+            removed_circuits: rebuild.removed_circuits,
+            changed_circuits: rebuild.changed_circuits,
+            missing_nodes: rebuild.missing_nodes,
+            circuit_count: rebuild.circuit_count,
+        }
+    }
+}
+
+/// The routing table reader and writer are not `Sync`, so the handlers share them behind mutexes
+/// and clone them for each request.
+struct RoutingTableResources {
+    node_id: String,
+    reader: Mutex<Box<dyn RoutingTableReader>>,
+    writer: Mutex<Box<dyn RoutingTableWriter>>,
+    store: Box<dyn AdminServiceStore>,
+}
+
+type SharedResources = Arc<RoutingTableResources>;
+
+pub fn make_routing_resources(
+    node_id: String,
+    routing_reader: Box<dyn RoutingTableReader>,
+    routing_writer: Box<dyn RoutingTableWriter>,
+    admin_store: Box<dyn AdminServiceStore>,
+) -> Vec<Resource> {
+    let resources: SharedResources = Arc::new(RoutingTableResources {
+        node_id,
+        reader: Mutex::new(routing_reader),
+        writer: Mutex::new(routing_writer),
+        store: admin_store,
+    });
+    let list_resources = resources.clone();
+    let fetch_resources = resources.clone();
+    let nodes_resources = resources.clone();
+    vec![
+        Resource::build("/routing/circuits").add_method(Method::Get, move |_, _| {
+            list_circuits(list_resources.clone())
+        }),
+        Resource::build("/routing/circuits/{circuit_id}").add_method(Method::Get, move |r, _| {
+            fetch_circuit(r, fetch_resources.clone())
+        }),
+        Resource::build("/routing/nodes")
+            .add_method(Method::Get, move |_, _| list_nodes(nodes_resources.clone())),
+        Resource::build("/routing/rebuild")
+            .add_method(Method::Post, move |r, _| rebuild(r, resources.clone())),
+    ]
+}
+
+fn clone_reader(resources: &RoutingTableResources) -> Result<Box<dyn RoutingTableReader>, String> {
+    resources
+        .reader
+        .lock()
+        .map(|reader| reader.clone())
+        .map_err(|_| "routing table reader lock was poisoned".to_string())
+}
+
+fn to_circuit_response(
+    circuit: Circuit,
+    node_id: &str,
+    reader: &dyn RoutingTableReader,
+) -> Result<CircuitResponse, String> {
+    let mut services = vec![];
+    for service in circuit.roster() {
+        let service_id = ServiceId::new(
+            circuit.circuit_id().to_string(),
+            service.service_id().to_string(),
+        );
+        // the service directory holds the current peer ID, not the circuit's roster
+        let peer_id = reader
+            .get_service(&service_id)
+            .map_err(|err| err.reduce_to_string())?
+            .and_then(|service| service.peer_id().clone());
+
+        let route = if service.node_id() == node_id {
+            peer_id.clone()
+        } else if reader
+            .get_node(service.node_id())
+            .map_err(|err| err.reduce_to_string())?
+            .is_some()
+        {
+            Some(service.node_id().to_string())
+        } else {
+            None
+        };
+
+        services.push(ServiceResponse {
+            service_id: service.service_id().to_string(),
+            service_type: service.service_type().to_string(),
+            node_id: service.node_id().to_string(),
+            peer_id,
+            route,
+        });
+    }
+
+    Ok(CircuitResponse {
+        circuit_id: circuit.circuit_id().to_string(),
+        members: circuit.members().to_vec(),
+        relays: circuit.relays().to_vec(),
+        observers: circuit.observers().to_vec(),
+        services,
+    })
+}
+
+fn list_circuits(
+    resources: SharedResources,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let reader = clone_reader(&resources)?;
+            let mut data = reader
+                .list_circuits()
+                .map_err(|err| err.reduce_to_string())?
+                .map(|(_, circuit)| to_circuit_response(circuit, &resources.node_id, &*reader))
+                .collect::<Result<Vec<_>, _>>()?;
+            data.sort_by(|a, b| a.circuit_id.cmp(&b.circuit_id));
+
+            Ok::<_, String>(CircuitListResponse { data })
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(circuits) => HttpResponse::Ok().json(circuits),
+                Err(err) => {
+                    error!("Unable to list routing table circuits: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn fetch_circuit(
+    request: HttpRequest,
+    resources: SharedResources,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let circuit_id = request
+        .match_info()
+        .get("circuit_id")
+        .unwrap_or("")
+        .to_string();
+    Box::new(
+        web::block(move || {
+            let reader = clone_reader(&resources)?;
+            match reader
+                .get_circuit(&circuit_id)
+                .map_err(|err| err.reduce_to_string())?
+            {
+                Some(circuit) => {
+                    to_circuit_response(circuit, &resources.node_id, &*reader).map(Some)
+                }
+                None => Ok(None),
+            }
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(Some(circuit)) => {
+                    HttpResponse::Ok().json(CircuitFetchResponse { data: circuit })
+                }
+                Ok(None) => HttpResponse::NotFound().json(ErrorResponse::not_found(
+                    "Circuit not found in routing table",
+                )),
+                Err(err) => {
+                    error!("Unable to fetch routing table circuit: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn list_nodes(resources: SharedResources) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || {
+            let reader = clone_reader(&resources)?;
+            let mut data = reader
+                .list_nodes()
+                .map_err(|err| err.reduce_to_string())?
+                .map(|(node_id, node)| NodeResponse {
+                    node_id,
+                    endpoints: node.endpoints().to_vec(),
+                })
+                .collect::<Vec<_>>();
+            data.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+
+            Ok::<_, String>(NodeListResponse { data })
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(nodes) => HttpResponse::Ok().json(nodes),
+                Err(err) => {
+                    error!("Unable to list routing table nodes: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn rebuild(
+    request: HttpRequest,
+    resources: SharedResources,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let is_local = request
+        .peer_addr()
+        .map(|addr| addr.ip().is_loopback())
+        .unwrap_or(false);
+    if !is_local {
+        return Box::new(
+            HttpResponse::Forbidden()
+                .json(ErrorResponse::forbidden(
+                    "The routing table may only be rebuilt from the local node",
+                ))
+                .into_future(),
+        );
+    }
+
+    Box::new(
+        web::block(move || {
+            let reader = clone_reader(&resources)?;
+            let mut writer = resources
+                .writer
+                .lock()
+                .map_err(|_| "routing table writer lock was poisoned".to_string())?;
+
+            rebuild_routing_table(
+                &resources.node_id,
+                &*resources.store,
+                &*reader,
+                &mut **writer,
+            )
+            .map_err(|err| err.reduce_to_string())
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(rebuild) => {
+                    info!(
+                        "Rebuilt routing table: {} circuits, added {:?}, removed {:?}, changed \
+                         {:?}, missing node information for {:?}",
+                        rebuild.circuit_count,
+                        rebuild.added_circuits,
+                        rebuild.removed_circuits,
+                        rebuild.changed_circuits,
+                        rebuild.missing_nodes
+                    );
+                    HttpResponse::Ok().json(RebuildResponse::from(rebuild))
+                }
+                Err(err) => {
+                    error!("Unable to rebuild routing table: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
@@ -0,0 +1,57 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the `GET /diagnostics/stats` endpoint, which returns the hourly rollups of
+//! the node's statistics, oldest first. The `since=<timestamp>` query parameter only returns the
+//! rollups of the hours starting at or after the given time, in seconds since the Unix epoch.
+
+use std::collections::HashMap;
+
+use splinter::actix_web::{web, HttpResponse};
+use splinter::futures::future::IntoFuture;
+use splinter::rest_api::{ErrorResponse, Method, Resource};
+
+use crate::node_stats::{StatsRollup, StatsStore};
+
+#[derive(Debug, Serialize)]
+struct StatsResponse {
+    data: Vec<StatsRollup>,
+}
+
+pub fn make_stats_resource(store: StatsStore) -> Resource {
+    Resource::build("/diagnostics/stats").add_method(Method::Get, move |request, _| {
+        let response =
+            match web::Query::<HashMap<String, String>>::from_query(request.query_string())
+                .map_err(|_| "Invalid query".to_string())
+                .and_then(|query| parse_since(&query))
+            {
+                Ok(since) => HttpResponse::Ok().json(StatsResponse {
+                    data: store.list(since),
+                }),
+                Err(msg) => HttpResponse::BadRequest().json(ErrorResponse::bad_request(&msg)),
+            };
+        Box::new(response.into_future())
+    })
+}
+
+fn parse_since(query: &HashMap<String, String>) -> Result<Option<u64>, String> {
+    query
+        .get("since")
+        .map(|since| {
+            since
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid since value: {}", since))
+        })
+        .transpose()
+}
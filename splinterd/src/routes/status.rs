@@ -14,6 +14,7 @@
 
 use splinter::actix_web::{web, Error, HttpRequest, HttpResponse};
 use splinter::futures::{Future, IntoFuture};
+use splinter::protocol::ProtocolVersionRange;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Status {
@@ -24,6 +25,47 @@ struct Status {
     network_endpoints: Vec<String>,
     advertised_endpoints: Vec<String>,
     version: String,
+    capabilities: Capabilities,
+}
+
+/// The features, service types and protocol versions supported by the node, reported so client
+/// tooling can adapt to the node without probing its endpoints.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Capabilities {
+    features: Vec<String>,
+    service_types: Vec<String>,
+    protocols: Vec<ProtocolRange>,
+}
+
+impl Capabilities {
+    pub fn new(
+        features: Vec<String>,
+        mut service_types: Vec<String>,
+        protocols: Vec<ProtocolVersionRange>,
+    ) -> Self {
+        service_types.sort();
+        service_types.dedup();
+
+        Capabilities {
+            features,
+            service_types,
+            protocols: protocols
+                .into_iter()
+                .map(|range| ProtocolRange {
+                    name: range.name,
+                    min: range.min,
+                    max: range.max,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProtocolRange {
+    name: String,
+    min: u32,
+    max: u32,
 }
 
 pub fn get_status(
@@ -32,6 +74,7 @@ pub fn get_status(
     #[cfg(feature = "service-endpoint")] service_endpoint: String,
     network_endpoints: Vec<String>,
     advertised_endpoints: Vec<String>,
+    capabilities: Capabilities,
 ) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
     let status = Status {
         node_id,
@@ -41,6 +84,7 @@ pub fn get_status(
         network_endpoints,
         advertised_endpoints,
         version: get_version(),
+        capabilities,
     };
 
     Box::new(HttpResponse::Ok().json(status).into_future())
@@ -0,0 +1,359 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of secret references in splinterd's configuration.
+//!
+//! Sensitive settings, such as the database URL, the OAuth client secret and TLS private keys,
+//! may be given as a reference to a secret instead of a plaintext value:
+//!
+//! * `file:///run/secrets/db_url` reads the secret from a file, such as a Docker or Kubernetes
+//!   secret mount; surrounding whitespace is removed.
+//! * `vault://secret/data/splinter#db_url` reads the `db_url` field of a secret from HashiCorp
+//!   Vault. The Vault server is taken from `VAULT_ADDR`, and the token from `VAULT_TOKEN` or the
+//!   file named by `VAULT_TOKEN_FILE`. Both KV version 1 and version 2 secrets engines are
+//!   supported.
+//!
+//! Any other value is used as-is.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The directory, relative to the state directory, that holds secrets resolved for consumers that
+/// can only read them from a file.
+pub const SECRETS_DIR: &str = "secrets";
+
+const FILE_SCHEME: &str = "file";
+const VAULT_SCHEME: &str = "vault";
+const VAULT_ADDR_ENV: &str = "VAULT_ADDR";
+const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+const VAULT_TOKEN_FILE_ENV: &str = "VAULT_TOKEN_FILE";
+const VAULT_NAMESPACE_ENV: &str = "VAULT_NAMESPACE";
+
+/// Resolves the references of a single scheme to secret values.
+pub trait SecretProvider: Send + Sync {
+    /// Returns the secret for the given reference, with the `scheme://` prefix removed.
+    fn resolve(&self, reference: &str) -> Result<String, SecretError>;
+}
+
+/// Reads secrets from files.
+pub struct FileSecretProvider;
+
+impl SecretProvider for FileSecretProvider {
+    fn resolve(&self, path: &str) -> Result<String, SecretError> {
+        let secret = fs::read_to_string(path)
+            .map_err(|err| SecretError(format!("unable to read secrets file {}: {}", path, err)))?;
+        let secret = secret.trim();
+        if secret.is_empty() {
+            return Err(SecretError(format!("secrets file {} is empty", path)));
+        }
+
+        Ok(secret.to_string())
+    }
+}
+
+/// Reads secrets from a HashiCorp Vault server.
+///
+/// References have the form `path#field`, where `path` is the API path of the secret, relative to
+/// `/v1/`.
+pub struct VaultSecretProvider {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl VaultSecretProvider {
+    pub fn new(address: &str, token: &str) -> Self {
+        VaultSecretProvider {
+            address: address.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            namespace: None,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Sets the Vault Enterprise namespace the secrets are read from.
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+}
+
+impl SecretProvider for VaultSecretProvider {
+    fn resolve(&self, reference: &str) -> Result<String, SecretError> {
+        let (path, field) = match reference.splitn(2, '#').collect::<Vec<_>>().as_slice() {
+            [path, field] if !path.is_empty() && !field.is_empty() => (*path, *field),
+            _ => {
+                return Err(SecretError(format!(
+                    "vault://{} must be of the form vault://path#field",
+                    reference
+                )))
+            }
+        };
+
+        let mut request = self
+            .client
+            .get(&format!("{}/v1/{}", self.address, path))
+            .header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request
+            .send()
+            .map_err(|err| SecretError(format!("unable to reach Vault: {}", err)))?;
+        if !response.status().is_success() {
+            return Err(SecretError(format!(
+                "Vault returned {} for {}",
+                response.status(),
+                path
+            )));
+        }
+        let body = response
+            .text()
+            .map_err(|err| SecretError(format!("unable to read Vault response: {}", err)))?;
+
+        vault_field(&body, field)
+            .map_err(|msg| SecretError(format!("secret {}: {}", reference, msg)))
+    }
+}
+
+/// Extracts a field from a Vault read response. KV version 2 nests the secret's fields in a
+/// second `data` object.
+fn vault_field(body: &str, field: &str) -> Result<String, String> {
+    let response: serde_json::Value =
+        serde_json::from_str(body).map_err(|err| format!("invalid Vault response: {}", err))?;
+    let data = &response["data"];
+    let fields = if data["data"].is_object() && data["metadata"].is_object() {
+        &data["data"]
+    } else {
+        data
+    };
+
+    match &fields[field] {
+        serde_json::Value::String(value) => Ok(value.clone()),
+        serde_json::Value::Null => Err(format!("field {} not found", field)),
+        _ => Err(format!("field {} is not a string", field)),
+    }
+}
+
+/// Resolves secret references using the provider registered for each reference's scheme.
+#[derive(Clone, Default)]
+pub struct SecretResolver {
+    providers: HashMap<String, Arc<dyn SecretProvider>>,
+}
+
+impl SecretResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a resolver for `file://` references, and for `vault://` references if `VAULT_ADDR`
+    /// is set.
+    pub fn from_env() -> Result<Self, SecretError> {
+        let mut resolver = Self::new().with_provider(FILE_SCHEME, FileSecretProvider);
+
+        if let Ok(address) = env::var(VAULT_ADDR_ENV) {
+            let token = match (env::var(VAULT_TOKEN_ENV), env::var(VAULT_TOKEN_FILE_ENV)) {
+                (Ok(token), _) => token,
+                (Err(_), Ok(token_file)) => FileSecretProvider.resolve(&token_file)?,
+                _ => {
+                    return Err(SecretError(format!(
+                        "{} is set, but neither {} nor {} is",
+                        VAULT_ADDR_ENV, VAULT_TOKEN_ENV, VAULT_TOKEN_FILE_ENV
+                    )))
+                }
+            };
+            resolver = resolver.with_provider(
+                VAULT_SCHEME,
+                VaultSecretProvider::new(&address, &token)
+                    .with_namespace(env::var(VAULT_NAMESPACE_ENV).ok()),
+            );
+        }
+
+        Ok(resolver)
+    }
+
+    /// Registers the provider for references of the given scheme.
+    pub fn with_provider<P: SecretProvider + 'static>(mut self, scheme: &str, provider: P) -> Self {
+        self.providers
+            .insert(scheme.to_string(), Arc::new(provider));
+        self
+    }
+
+    /// Returns whether the value is a secret reference rather than a plaintext value.
+    pub fn is_reference(value: &str) -> bool {
+        value.starts_with("file://") || value.starts_with("vault://")
+    }
+
+    /// Returns the secret the value refers to, or the value itself if it is not a reference.
+    pub fn resolve(&self, value: &str) -> Result<String, SecretError> {
+        if !Self::is_reference(value) {
+            return Ok(value.to_string());
+        }
+
+        let mut parts = value.splitn(2, "://");
+        let scheme = parts.next().unwrap_or_default();
+        let reference = parts.next().unwrap_or_default();
+        match self.providers.get(scheme) {
+            Some(provider) => provider.resolve(reference),
+            None if scheme == VAULT_SCHEME => Err(SecretError(format!(
+                "unable to resolve {}: {} is not set",
+                value, VAULT_ADDR_ENV
+            ))),
+            None => Err(SecretError(format!(
+                "unable to resolve {}: no provider for {}://",
+                value, scheme
+            ))),
+        }
+    }
+
+    /// Returns the path of a file holding the secret the value refers to, such as a TLS private
+    /// key, which must be read from a file by its consumer.
+    ///
+    /// A `file://` reference resolves to its own path. Other references are resolved and written
+    /// to `path`, readable only by the current user. A value that is not a reference is already a
+    /// path, and is returned unchanged.
+    pub fn resolve_to_file(&self, value: &str, path: &Path) -> Result<String, SecretError> {
+        if let Some(file_path) = value.strip_prefix("file://") {
+            return Ok(file_path.to_string());
+        }
+        if !Self::is_reference(value) {
+            return Ok(value.to_string());
+        }
+
+        let secret = self.resolve(value)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| {
+                SecretError(format!("unable to create {}: {}", dir.display(), err))
+            })?;
+        }
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", secret))
+            .map_err(|err| SecretError(format!("unable to write {}: {}", path.display(), err)))?;
+
+        Ok(path.display().to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct SecretError(String);
+
+impl Error for SecretError {}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that plaintext values are returned unchanged, that `file://` references are read
+    /// and trimmed, and that `vault://` references fail when Vault is not configured.
+    #[test]
+    fn test_resolve() {
+        let temp_dir = TempDir::new("test_resolve").expect("Failed to create temp dir");
+        let secret_path = temp_dir.path().join("db_url");
+        fs::write(&secret_path, "postgres://admin:secret@db/splinter\n")
+            .expect("Failed to write secret");
+
+        let resolver = SecretResolver::new().with_provider(FILE_SCHEME, FileSecretProvider);
+
+        assert_eq!(
+            resolver
+                .resolve("postgres://admin:admin@db/splinter")
+                .expect("Failed to resolve plaintext value"),
+            "postgres://admin:admin@db/splinter"
+        );
+        assert_eq!(
+            resolver
+                .resolve(&format!("file://{}", secret_path.display()))
+                .expect("Failed to resolve file reference"),
+            "postgres://admin:secret@db/splinter"
+        );
+        assert!(resolver.resolve("vault://secret/data/splinter#db").is_err());
+    }
+
+    /// Verify that fields are read from both KV version 1 and version 2 responses.
+    #[test]
+    fn test_vault_field() {
+        let kv1 = r#"{"data": {"password": "hunter2"}, "lease_duration": 2764800}"#;
+        assert_eq!(vault_field(kv1, "password"), Ok("hunter2".to_string()));
+
+        let kv2 = r#"{"data": {"data": {"password": "hunter2"}, "metadata": {"version": 3}}}"#;
+        assert_eq!(vault_field(kv2, "password"), Ok("hunter2".to_string()));
+
+        assert!(vault_field(kv2, "username").is_err());
+    }
+
+    /// Verify that a resolved secret is written to the given file, and that `file://` references
+    /// and plain paths are returned without writing anything.
+    #[test]
+    fn test_resolve_to_file() {
+        let temp_dir = TempDir::new("test_resolve_to_file").expect("Failed to create temp dir");
+        let key_path = temp_dir.path().join("server.key");
+        let resolver = SecretResolver::new();
+
+        assert_eq!(
+            resolver
+                .resolve_to_file("file:///etc/splinter/certs/private/server.key", &key_path)
+                .expect("Failed to resolve file reference"),
+            "/etc/splinter/certs/private/server.key"
+        );
+        assert_eq!(
+            resolver
+                .resolve_to_file("/etc/splinter/certs/private/server.key", &key_path)
+                .expect("Failed to resolve path"),
+            "/etc/splinter/certs/private/server.key"
+        );
+        assert!(!key_path.exists());
+
+        let resolver = SecretResolver::new().with_provider(VAULT_SCHEME, StaticProvider);
+        assert_eq!(
+            resolver
+                .resolve_to_file("vault://secret/data/splinter#server_key", &key_path)
+                .expect("Failed to resolve vault reference"),
+            key_path.display().to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(&key_path).expect("Failed to read key"),
+            "secret/data/splinter#server_key\n"
+        );
+    }
+
+    /// Returns the reference as the secret.
+    struct StaticProvider;
+
+    impl SecretProvider for StaticProvider {
+        fn resolve(&self, reference: &str) -> Result<String, SecretError> {
+            Ok(reference.to_string())
+        }
+    }
+}
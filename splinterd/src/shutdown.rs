@@ -0,0 +1,315 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ordered shutdown of the daemon's subsystems.
+//!
+//! Subsystems are registered with the `ShutdownCoordinator` as they are started. A subsystem may
+//! only depend on subsystems that were registered before it, so shutting down in the reverse of
+//! the registration order guarantees that every subsystem is stopped before the subsystems it
+//! depends on.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// The default time a subsystem is given to shut down before it is reported as a straggler.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ShutdownFn = Box<dyn FnOnce() + Send>;
+
+/// A component of the daemon that is shut down by the `ShutdownCoordinator`.
+pub struct Subsystem {
+    name: String,
+    dependencies: Vec<String>,
+    signal: Option<ShutdownFn>,
+    wait: Option<ShutdownFn>,
+    timeout: Option<Duration>,
+}
+
+impl Subsystem {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            dependencies: vec![],
+            signal: None,
+            wait: None,
+            timeout: None,
+        }
+    }
+
+    /// Sets the function that tells the subsystem to shut down.
+    pub fn with_signal<F: FnOnce() + Send + 'static>(mut self, signal: F) -> Self {
+        self.signal = Some(Box::new(signal));
+        self
+    }
+
+    /// Sets the function that blocks until the subsystem has shut down, such as joining its
+    /// threads.
+    pub fn with_wait<F: FnOnce() + Send + 'static>(mut self, wait: F) -> Self {
+        self.wait = Some(Box::new(wait));
+        self
+    }
+
+    /// Adds a subsystem that must keep running until this subsystem has shut down.
+    pub fn with_dependency(mut self, name: &str) -> Self {
+        self.dependencies.push(name.into());
+        self
+    }
+
+    /// Overrides the coordinator's default shutdown timeout for this subsystem.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Shuts down registered subsystems in dependency order.
+pub struct ShutdownCoordinator {
+    subsystems: Vec<Subsystem>,
+    default_timeout: Duration,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(default_timeout: Duration) -> Self {
+        Self {
+            subsystems: vec![],
+            default_timeout,
+        }
+    }
+
+    /// Registers a subsystem.
+    ///
+    /// Returns an error if the subsystem's name is already registered, or if any of its
+    /// dependencies have not been registered.
+    pub fn register(&mut self, subsystem: Subsystem) -> Result<(), ShutdownError> {
+        if self.is_registered(&subsystem.name) {
+            return Err(ShutdownError::DuplicateSubsystem(subsystem.name));
+        }
+
+        if let Some(dependency) = subsystem
+            .dependencies
+            .iter()
+            .find(|dependency| !self.is_registered(dependency))
+        {
+            return Err(ShutdownError::UnknownDependency {
+                subsystem: subsystem.name.clone(),
+                dependency: dependency.clone(),
+            });
+        }
+
+        self.subsystems.push(subsystem);
+        Ok(())
+    }
+
+    /// Returns the names of the registered subsystems, in the order they will be shut down.
+    pub fn shutdown_order(&self) -> Vec<&str> {
+        self.subsystems
+            .iter()
+            .rev()
+            .map(|subsystem| subsystem.name.as_str())
+            .collect()
+    }
+
+    /// Shuts down each subsystem in order, waiting up to the subsystem's timeout for it to stop.
+    ///
+    /// A subsystem that does not stop in time is logged and left running in the background, and
+    /// the shutdown continues with the next subsystem. Returns the names of these stragglers.
+    pub fn shutdown(self) -> Vec<String> {
+        let mut stragglers = vec![];
+
+        for subsystem in self.subsystems.into_iter().rev() {
+            let Subsystem {
+                name,
+                signal,
+                wait,
+                timeout,
+                ..
+            } = subsystem;
+            let timeout = timeout.unwrap_or(self.default_timeout);
+
+            debug!("Shutting down {}", name);
+            let (sender, receiver) = channel();
+            let spawn_result = thread::Builder::new()
+                .name(format!("Shutdown-{}", name))
+                .spawn(move || {
+                    if let Some(signal) = signal {
+                        signal();
+                    }
+                    if let Some(wait) = wait {
+                        wait();
+                    }
+                    // The coordinator may have stopped waiting
+                    let _ = sender.send(());
+                });
+            if let Err(err) = spawn_result {
+                error!("Unable to shut down {}: {}", name, err);
+                stragglers.push(name);
+                continue;
+            }
+
+            match receiver.recv_timeout(timeout) {
+                Ok(()) => debug!("Shut down {}", name),
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!("{} did not shut down within {:?}", name, timeout);
+                    stragglers.push(name);
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    error!("{} panicked while shutting down", name);
+                    stragglers.push(name);
+                }
+            }
+        }
+
+        if !stragglers.is_empty() {
+            warn!(
+                "Shutdown completed, but the following did not stop cleanly: {}",
+                stragglers.join(", ")
+            );
+        }
+
+        stragglers
+    }
+
+    fn is_registered(&self, name: &str) -> bool {
+        self.subsystems
+            .iter()
+            .any(|subsystem| subsystem.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub enum ShutdownError {
+    DuplicateSubsystem(String),
+    UnknownDependency {
+        subsystem: String,
+        dependency: String,
+    },
+}
+
+impl Error for ShutdownError {}
+
+impl fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShutdownError::DuplicateSubsystem(name) => {
+                write!(f, "subsystem {} is already registered", name)
+            }
+            ShutdownError::UnknownDependency {
+                subsystem,
+                dependency,
+            } => write!(
+                f,
+                "subsystem {} depends on {}, which has not been registered",
+                subsystem, dependency
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    /// Verify that subsystems are signaled and waited on in the reverse of their registration
+    /// order.
+    #[test]
+    fn test_shutdown_order() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut coordinator = ShutdownCoordinator::new(DEFAULT_SHUTDOWN_TIMEOUT);
+
+        for (name, dependency) in vec![("mesh", None), ("peers", Some("mesh")), ("rest", None)] {
+            let signal_events = events.clone();
+            let wait_events = events.clone();
+            let mut subsystem = Subsystem::new(name)
+                .with_signal(move || {
+                    signal_events
+                        .lock()
+                        .unwrap()
+                        .push(format!("signal {}", name))
+                })
+                .with_wait(move || wait_events.lock().unwrap().push(format!("wait {}", name)));
+            if let Some(dependency) = dependency {
+                subsystem = subsystem.with_dependency(dependency);
+            }
+            coordinator
+                .register(subsystem)
+                .expect("Failed to register subsystem");
+        }
+
+        assert_eq!(coordinator.shutdown_order(), vec!["rest", "peers", "mesh"]);
+        assert!(coordinator.shutdown().is_empty());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "signal rest",
+                "wait rest",
+                "signal peers",
+                "wait peers",
+                "signal mesh",
+                "wait mesh"
+            ]
+        );
+    }
+
+    /// Verify that a subsystem which does not stop within its timeout is reported as a straggler,
+    /// and that the remaining subsystems are still shut down.
+    #[test]
+    fn test_shutdown_straggler() {
+        let stopped = Arc::new(Mutex::new(false));
+        let mut coordinator = ShutdownCoordinator::new(DEFAULT_SHUTDOWN_TIMEOUT);
+
+        let thread_stopped = stopped.clone();
+        coordinator
+            .register(Subsystem::new("first").with_signal(move || {
+                *thread_stopped.lock().unwrap() = true;
+            }))
+            .expect("Failed to register subsystem");
+        coordinator
+            .register(
+                Subsystem::new("stuck")
+                    .with_wait(|| thread::sleep(Duration::from_secs(5)))
+                    .with_timeout(Duration::from_millis(10)),
+            )
+            .expect("Failed to register subsystem");
+
+        assert_eq!(coordinator.shutdown(), vec!["stuck".to_string()]);
+        assert!(*stopped.lock().unwrap());
+    }
+
+    /// Verify that a subsystem cannot depend on an unregistered subsystem, and that names must be
+    /// unique.
+    #[test]
+    fn test_register_errors() {
+        let mut coordinator = ShutdownCoordinator::new(DEFAULT_SHUTDOWN_TIMEOUT);
+
+        match coordinator.register(Subsystem::new("peers").with_dependency("mesh")) {
+            Err(ShutdownError::UnknownDependency { dependency, .. }) => {
+                assert_eq!(dependency, "mesh")
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        coordinator
+            .register(Subsystem::new("mesh"))
+            .expect("Failed to register subsystem");
+        match coordinator.register(Subsystem::new("mesh")) {
+            Err(ShutdownError::DuplicateSubsystem(name)) => assert_eq!(name, "mesh"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locking and layout versioning for the splinterd state directory.
+//!
+//! Only one splinterd process may use a state directory at a time; the process holds an
+//! exclusive lock on `splinterd.lock` for as long as it runs. The layout of the directory is
+//! recorded in `layout.toml`, and older layouts are migrated to the current version at startup.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use fs2::FileExt;
+
+const LOCK_FILE: &str = "splinterd.lock";
+const LAYOUT_FILE: &str = "layout.toml";
+
+/// Migrations between layout versions; the migration at index `n` upgrades a state directory
+/// from version `n` to version `n + 1`.
+const MIGRATIONS: &[fn(&Path) -> Result<(), StateDirError>] = &[migrate_v0_to_v1];
+
+/// The layout version written by this version of splinterd.
+pub const LAYOUT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+#[derive(Deserialize, Serialize)]
+struct LayoutManifest {
+    version: u32,
+}
+
+/// An exclusive lock on a state directory, which is released when dropped.
+pub struct StateDirLock {
+    _file: File,
+}
+
+impl StateDirLock {
+    /// Acquires the lock for the given state directory.
+    ///
+    /// Returns `StateDirError::Locked` if another process already holds the lock.
+    pub fn acquire(state_dir: &Path) -> Result<Self, StateDirError> {
+        let lock_path = state_dir.join(LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&lock_path)
+            .map_err(|err| {
+                StateDirError::io(format!("unable to open {}", lock_path.display()), err)
+            })?;
+
+        if file.try_lock_exclusive().is_err() {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            return Err(StateDirError::Locked {
+                state_dir: state_dir.display().to_string(),
+                pid: pid.trim().to_string(),
+            });
+        }
+
+        // Record the process ID to help identify the process holding the lock
+        file.set_len(0)
+            .and_then(|_| file.seek(SeekFrom::Start(0)))
+            .and_then(|_| writeln!(file, "{}", std::process::id()))
+            .map_err(|err| {
+                StateDirError::io(format!("unable to write {}", lock_path.display()), err)
+            })?;
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Migrates the state directory's layout to `LAYOUT_VERSION`, if required.
+///
+/// A state directory without a layout manifest was written by a version of splinterd that
+/// predates layout versioning, and is treated as version 0.
+pub fn ensure_layout(state_dir: &Path) -> Result<(), StateDirError> {
+    let mut version = read_layout_version(state_dir)?;
+
+    if version > LAYOUT_VERSION {
+        return Err(StateDirError::UnsupportedVersion {
+            found: version,
+            supported: LAYOUT_VERSION,
+        });
+    }
+
+    while version < LAYOUT_VERSION {
+        info!(
+            "Migrating state directory {} from layout version {} to {}",
+            state_dir.display(),
+            version,
+            version + 1
+        );
+        MIGRATIONS[version as usize](state_dir)?;
+        version += 1;
+        write_layout_version(state_dir, version)?;
+    }
+
+    Ok(())
+}
+
+fn read_layout_version(state_dir: &Path) -> Result<u32, StateDirError> {
+    let layout_path = state_dir.join(LAYOUT_FILE);
+    if !layout_path.exists() {
+        return Ok(0);
+    }
+
+    let contents = fs::read_to_string(&layout_path).map_err(|err| {
+        StateDirError::io(format!("unable to read {}", layout_path.display()), err)
+    })?;
+    let manifest: LayoutManifest = toml::from_str(&contents).map_err(|err| {
+        StateDirError::InvalidManifest(format!("{}: {}", layout_path.display(), err))
+    })?;
+
+    Ok(manifest.version)
+}
+
+fn write_layout_version(state_dir: &Path, version: u32) -> Result<(), StateDirError> {
+    let layout_path = state_dir.join(LAYOUT_FILE);
+    let temp_path = state_dir.join(format!("{}.tmp", LAYOUT_FILE));
+
+    let contents = toml::to_string(&LayoutManifest { version })
+        .map_err(|err| StateDirError::InvalidManifest(err.to_string()))?;
+
+    // Write to a temporary file first, so an interrupted write cannot leave a partial manifest
+    fs::write(&temp_path, contents)
+        .and_then(|_| fs::rename(&temp_path, &layout_path))
+        .map_err(|err| StateDirError::io(format!("unable to write {}", layout_path.display()), err))
+}
+
+/// Version 0 is the layout used before layout versioning was introduced; version 1 only adds the
+/// layout manifest, so no files need to change.
+fn migrate_v0_to_v1(_state_dir: &Path) -> Result<(), StateDirError> {
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum StateDirError {
+    Locked { state_dir: String, pid: String },
+    UnsupportedVersion { found: u32, supported: u32 },
+    InvalidManifest(String),
+    IoError { context: String, source: io::Error },
+}
+
+impl StateDirError {
+    fn io(context: String, source: io::Error) -> Self {
+        StateDirError::IoError { context, source }
+    }
+}
+
+impl Error for StateDirError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StateDirError::IoError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StateDirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateDirError::Locked { state_dir, pid } if pid.is_empty() => write!(
+                f,
+                "state directory {} is in use by another splinterd process",
+                state_dir
+            ),
+            StateDirError::Locked { state_dir, pid } => write!(
+                f,
+                "state directory {} is in use by another splinterd process (pid {})",
+                state_dir, pid
+            ),
+            StateDirError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "state directory layout version {} is newer than the latest version supported by \
+                 this splinterd ({}); upgrade splinterd to use this state directory",
+                found, supported
+            ),
+            StateDirError::InvalidManifest(msg) => {
+                write!(f, "invalid state directory layout manifest: {}", msg)
+            }
+            StateDirError::IoError { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that a second lock on the same state directory fails while the first is held, and
+    /// succeeds once the first lock is dropped.
+    #[test]
+    fn test_state_dir_lock() {
+        let temp_dir = TempDir::new("test_state_dir_lock").expect("Failed to create temp dir");
+
+        let lock = StateDirLock::acquire(temp_dir.path()).expect("Failed to acquire lock");
+
+        match StateDirLock::acquire(temp_dir.path()) {
+            Err(StateDirError::Locked { pid, .. }) => {
+                assert_eq!(pid, std::process::id().to_string())
+            }
+            Err(err) => panic!("Unexpected error: {}", err),
+            Ok(_) => panic!("Lock was acquired twice"),
+        }
+
+        drop(lock);
+
+        StateDirLock::acquire(temp_dir.path()).expect("Failed to re-acquire lock");
+    }
+
+    /// Verify that an unversioned state directory is migrated to the current layout version, and
+    /// that a layout newer than the current version is rejected.
+    #[test]
+    fn test_ensure_layout() {
+        let temp_dir = TempDir::new("test_ensure_layout").expect("Failed to create temp dir");
+
+        ensure_layout(temp_dir.path()).expect("Failed to migrate layout");
+        assert_eq!(
+            read_layout_version(temp_dir.path()).expect("Failed to read layout version"),
+            LAYOUT_VERSION
+        );
+
+        // Running again is a no-op
+        ensure_layout(temp_dir.path()).expect("Failed to check layout");
+
+        write_layout_version(temp_dir.path(), LAYOUT_VERSION + 1)
+            .expect("Failed to write layout version");
+        match ensure_layout(temp_dir.path()) {
+            Err(StateDirError::UnsupportedVersion { found, supported }) => {
+                assert_eq!(found, LAYOUT_VERSION + 1);
+                assert_eq!(supported, LAYOUT_VERSION);
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+}
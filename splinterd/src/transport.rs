@@ -16,19 +16,37 @@ use std::fs;
 use std::path::Path;
 
 use splinter::transport::multi::MultiTransport;
+#[cfg(feature = "tls-revocation")]
+use splinter::transport::revocation::RevocationConfig;
 use splinter::transport::socket::TcpTransport;
 use splinter::transport::socket::TlsTransport;
+#[cfg(feature = "tls-policy")]
+use splinter::transport::tls::{ClientAuth, TlsPolicy, TlsVersion};
 use splinter::transport::tls::{TlsConfig, TlsConfigBuilder};
 #[cfg(feature = "ws-transport")]
 use splinter::transport::ws::WsTransport;
 use splinter::transport::Transport;
+#[cfg(feature = "secrets")]
+use splinter_daemon::secrets::{SecretResolver, SECRETS_DIR};
 
 use crate::config::Config;
 use crate::error::GetTransportError;
 
 type SendableTransport = Box<dyn Transport + Send>;
 
-pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportError> {
+/// The names of the files spiffe-helper writes the node's X.509 SVID, its private key and the
+/// trust bundle to.
+#[cfg(feature = "spiffe")]
+const SVID_FILE: &str = "svid.pem";
+#[cfg(feature = "spiffe")]
+const SVID_KEY_FILE: &str = "svid_key.pem";
+#[cfg(feature = "spiffe")]
+const SVID_BUNDLE_FILE: &str = "svid_bundle.pem";
+
+pub fn build_transport(
+    config: &Config,
+    #[cfg(feature = "secrets")] secrets: &SecretResolver,
+) -> Result<MultiTransport, GetTransportError> {
     let mut transports: Vec<SendableTransport> = vec![];
 
     // add tcp transport
@@ -39,17 +57,15 @@ pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportEr
 
     // add tls transport
     if !config.no_tls() {
-        let tls_config = build_tls_config(&config)?;
+        let tls_config = build_tls_config(
+            &config,
+            #[cfg(feature = "secrets")]
+            secrets,
+        )?;
         validate_tls_config(&tls_config)?;
         print_tls_config(&tls_config)?;
 
-        transports.push(Box::new(TlsTransport::new(
-            tls_config.ca_certs_file().to_owned(),
-            tls_config.client_private_key_file().to_string(),
-            tls_config.client_cert_file().to_string(),
-            tls_config.server_private_key_file().to_string(),
-            tls_config.server_cert_file().to_string(),
-        )?));
+        transports.push(Box::new(TlsTransport::from_config(&tls_config)?));
 
         #[cfg(feature = "ws-transport")]
         transports.push(Box::new(WsTransport::new(Some(&tls_config)).map_err(
@@ -65,12 +81,34 @@ pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportEr
     Ok(MultiTransport::new(transports))
 }
 
-fn build_tls_config(config: &Config) -> Result<TlsConfig, GetTransportError> {
+fn build_tls_config(
+    config: &Config,
+    #[cfg(feature = "secrets")] secrets: &SecretResolver,
+) -> Result<TlsConfig, GetTransportError> {
+    let client_key = config.tls_client_key().to_string();
+    let server_key = config.tls_server_key().to_string();
+
+    // Keys stored as secrets are written to the state directory, as the transport reads them
+    // from files
+    #[cfg(feature = "secrets")]
+    let (client_key, server_key) = {
+        let secrets_dir = Path::new(config.state_dir()).join(SECRETS_DIR);
+        let resolve = |key: &str, file_name: &str| {
+            secrets
+                .resolve_to_file(key, &secrets_dir.join(file_name))
+                .map_err(|err| GetTransportError::CertError(err.to_string()))
+        };
+        (
+            resolve(&client_key, "client.key")?,
+            resolve(&server_key, "server.key")?,
+        )
+    };
+
     let mut builder = TlsConfigBuilder::new()
         .with_client_cert_file(config.tls_client_cert().to_string())
-        .with_client_private_key_file(config.tls_client_key().to_string())
+        .with_client_private_key_file(client_key)
         .with_server_cert_file(config.tls_server_cert().to_string())
-        .with_server_private_key_file(config.tls_server_key().to_string());
+        .with_server_private_key_file(server_key);
 
     if config.tls_insecure() {
         warn!("Starting TlsTransport in insecure mode");
@@ -78,11 +116,77 @@ fn build_tls_config(config: &Config) -> Result<TlsConfig, GetTransportError> {
         builder = builder.with_ca_certs_file(config.tls_ca_file().to_string());
     }
 
+    #[cfg(feature = "tls-policy")]
+    {
+        builder = builder.with_policy(build_tls_policy(
+            config.tls_min_version(),
+            config.tls_cipher_suites(),
+            config.tls_client_auth(),
+        )?);
+    }
+
+    #[cfg(feature = "tls-revocation")]
+    {
+        let mut revocation = RevocationConfig::new().with_require_ocsp(config.tls_require_ocsp());
+        if let Some(crl_file) = config.tls_crl_file() {
+            revocation = revocation.with_crl_file(crl_file.to_string());
+        }
+        if let Some(ocsp_response_file) = config.tls_ocsp_response_file() {
+            revocation = revocation.with_ocsp_response_file(ocsp_response_file.to_string());
+        }
+        builder = builder.with_revocation(revocation);
+    }
+
+    // The SVID is used as both the client and server certificate, and is reloaded whenever the
+    // SPIFFE Workload API rotates it
+    #[cfg(feature = "spiffe")]
+    {
+        if let Some(svid_dir) = config.spiffe_svid_dir() {
+            let svid_file = |name: &str| Path::new(svid_dir).join(name).display().to_string();
+            builder = builder
+                .with_client_cert_file(svid_file(SVID_FILE))
+                .with_client_private_key_file(svid_file(SVID_KEY_FILE))
+                .with_server_cert_file(svid_file(SVID_FILE))
+                .with_server_private_key_file(svid_file(SVID_KEY_FILE))
+                .with_ca_certs_file(svid_file(SVID_BUNDLE_FILE))
+                .with_reload_certificates(true);
+        }
+    }
+
     builder
         .build()
         .map_err(|e| GetTransportError::CertError(format!("TLS config error: {}", e)))
 }
 
+/// Builds a TLS policy from the configured minimum protocol version, cipher suites and client
+/// certificate requirement; settings that are not configured keep the OpenSSL defaults.
+#[cfg(feature = "tls-policy")]
+pub fn build_tls_policy(
+    min_version: Option<&str>,
+    cipher_suites: Option<&[String]>,
+    client_auth: Option<&str>,
+) -> Result<TlsPolicy, GetTransportError> {
+    let mut policy = TlsPolicy::new();
+
+    if let Some(min_version) = min_version {
+        policy = policy.with_min_version(min_version.parse::<TlsVersion>().map_err(|err| {
+            GetTransportError::CertError(format!("Invalid TLS minimum version: {}", err))
+        })?);
+    }
+
+    if let Some(cipher_suites) = cipher_suites {
+        policy = policy.with_cipher_suites(cipher_suites.to_vec());
+    }
+
+    if let Some(client_auth) = client_auth {
+        policy = policy.with_client_auth(client_auth.parse::<ClientAuth>().map_err(|err| {
+            GetTransportError::CertError(format!("Invalid TLS client auth: {}", err))
+        })?);
+    }
+
+    Ok(policy)
+}
+
 fn validate_tls_config(tls_config: &TlsConfig) -> Result<(), GetTransportError> {
     let client_cert = tls_config.client_cert_file();
     if !Path::new(&client_cert).is_file() {
@@ -148,6 +252,32 @@ fn print_tls_config(tls_config: &TlsConfig) -> Result<(), GetTransportError> {
     if let Some(ca_path) = tls_config.ca_certs_file() {
         debug!("Using ca certs file: {:?}", ca_path);
     }
+    #[cfg(feature = "tls-policy")]
+    {
+        let policy = tls_config.policy();
+        if let Some(min_version) = policy.min_version() {
+            debug!("Using TLS minimum version: {}", min_version);
+        }
+        if !policy.cipher_suites().is_empty() {
+            debug!("Using TLS cipher suites: {:?}", policy.cipher_suites());
+        }
+        if let Some(client_auth) = policy.client_auth() {
+            debug!("Using TLS client auth: {}", client_auth);
+        }
+    }
+    #[cfg(feature = "tls-revocation")]
+    {
+        let revocation = tls_config.revocation();
+        if let Some(crl_file) = revocation.crl_file() {
+            debug!("Using certificate revocation list: {:?}", crl_file);
+        }
+        if let Some(ocsp_response_file) = revocation.ocsp_response_file() {
+            debug!("Stapling OCSP response file: {:?}", ocsp_response_file);
+        }
+        if revocation.require_ocsp() {
+            debug!("Requiring stapled OCSP responses from peers");
+        }
+    }
 
     Ok(())
 }